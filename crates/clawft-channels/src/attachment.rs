@@ -0,0 +1,351 @@
+//! Outbound attachment helpers shared by channel implementations.
+//!
+//! Channels that can upload files override
+//! [`Channel::send_attachment`](crate::traits::Channel::send_attachment)
+//! and use [`load_within_limit`] to read the contents while enforcing
+//! their platform's size limit. [`send_with_attachments`] is the single
+//! outbound path used by the host: it sends the message text, then each
+//! attachment in order.
+
+use clawft_types::error::ChannelError;
+use clawft_types::event::{Attachment, AttachmentData, OutboundMessage};
+
+use crate::traits::{Channel, MessageId};
+
+/// Size of an attachment in bytes without reading file contents.
+pub async fn attachment_size(attachment: &Attachment) -> Result<u64, ChannelError> {
+    match attachment.data {
+        AttachmentData::Bytes { ref data } => Ok(data.len() as u64),
+        AttachmentData::Path { ref path } => tokio::fs::metadata(path)
+            .await
+            .map(|m| m.len())
+            .map_err(|e| {
+                ChannelError::SendFailed(format!(
+                    "cannot read attachment '{}': {e}",
+                    path.display()
+                ))
+            }),
+    }
+}
+
+/// Return [`ChannelError::AttachmentTooLarge`] when `size` exceeds `limit`.
+pub fn check_size(attachment: &Attachment, size: u64, limit: u64) -> Result<(), ChannelError> {
+    if size > limit {
+        return Err(ChannelError::AttachmentTooLarge {
+            filename: attachment.filename.clone(),
+            size,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+/// Load attachment contents after checking them against `limit`.
+///
+/// File-backed attachments are size-checked from metadata before being
+/// read, so oversized files are rejected without loading them.
+pub async fn load_within_limit(
+    attachment: &Attachment,
+    limit: u64,
+) -> Result<Vec<u8>, ChannelError> {
+    check_size(attachment, attachment_size(attachment).await?, limit)?;
+    match attachment.data {
+        AttachmentData::Bytes { ref data } => Ok(data.clone()),
+        AttachmentData::Path { ref path } => tokio::fs::read(path).await.map_err(|e| {
+            ChannelError::SendFailed(format!("cannot read attachment '{}': {e}", path.display()))
+        }),
+    }
+}
+
+/// Text sent in place of an attachment by channels that cannot upload
+/// files.
+pub fn omitted_note(attachment: &Attachment) -> String {
+    let mut note = format!(
+        "[attachment omitted: {} ({})]",
+        attachment.filename, attachment.mime_type
+    );
+    if let Some(ref caption) = attachment.caption {
+        note.push(' ');
+        note.push_str(caption);
+    }
+    note
+}
+
+/// Build the text-only message that carries an [`omitted_note`].
+pub fn omitted_note_message(msg: &OutboundMessage, attachment: &Attachment) -> OutboundMessage {
+    OutboundMessage {
+        channel: msg.channel.clone(),
+        chat_id: msg.chat_id.clone(),
+        content: omitted_note(attachment),
+        reply_to: msg.reply_to.clone(),
+        media: vec![],
+        attachments: vec![],
+        metadata: msg.metadata.clone(),
+    }
+}
+
+/// Send `msg` through `channel`, uploading any attachments after the text.
+///
+/// The text part is skipped when it is empty and attachments are
+/// present. Returns the ID of the last message sent. The first failure
+/// (including [`ChannelError::AttachmentTooLarge`]) stops delivery of the
+/// remaining attachments.
+pub async fn send_with_attachments(
+    channel: &dyn Channel,
+    msg: &OutboundMessage,
+) -> Result<MessageId, ChannelError> {
+    let mut last = None;
+    if !msg.content.trim().is_empty() || msg.attachments.is_empty() {
+        last = Some(channel.send(msg).await?);
+    }
+    for attachment in &msg.attachments {
+        last = Some(channel.send_attachment(msg, attachment).await?);
+    }
+    // At least one send happened: either text, or one or more attachments.
+    last.ok_or_else(|| ChannelError::SendFailed("nothing to send".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+    use tokio_util::sync::CancellationToken;
+
+    use super::*;
+    use crate::traits::{ChannelHost, ChannelMetadata, ChannelStatus};
+
+    /// Records everything sent through it. `upload_limit` of `None`
+    /// means the channel keeps the default (note-only) attachment path.
+    struct RecordingChannel {
+        upload_limit: Option<u64>,
+        texts: Mutex<Vec<String>>,
+        uploads: Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    impl RecordingChannel {
+        fn new(upload_limit: Option<u64>) -> Self {
+            Self {
+                upload_limit,
+                texts: Mutex::new(Vec::new()),
+                uploads: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn metadata(&self) -> ChannelMetadata {
+            ChannelMetadata {
+                name: "recording".into(),
+                display_name: "Recording".into(),
+                supports_threads: false,
+                supports_media: self.upload_limit.is_some(),
+            }
+        }
+
+        fn status(&self) -> ChannelStatus {
+            ChannelStatus::Running
+        }
+
+        fn is_allowed(&self, _sender_id: &str) -> bool {
+            true
+        }
+
+        async fn start(
+            &self,
+            _host: Arc<dyn ChannelHost>,
+            _cancel: CancellationToken,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
+            let mut texts = self.texts.lock().await;
+            texts.push(msg.content.clone());
+            Ok(MessageId(format!("text-{}", texts.len())))
+        }
+
+        async fn send_attachment(
+            &self,
+            msg: &OutboundMessage,
+            attachment: &Attachment,
+        ) -> Result<MessageId, ChannelError> {
+            let Some(limit) = self.upload_limit else {
+                return self.send(&omitted_note_message(msg, attachment)).await;
+            };
+            let bytes = load_within_limit(attachment, limit).await?;
+            let mut uploads = self.uploads.lock().await;
+            uploads.push((attachment.filename.clone(), bytes));
+            Ok(MessageId(format!("file-{}", uploads.len())))
+        }
+    }
+
+    /// Uses the trait's default `send_attachment`.
+    struct TextOnlyChannel {
+        texts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Channel for TextOnlyChannel {
+        fn name(&self) -> &str {
+            "text-only"
+        }
+
+        fn metadata(&self) -> ChannelMetadata {
+            ChannelMetadata {
+                name: "text-only".into(),
+                display_name: "Text Only".into(),
+                supports_threads: false,
+                supports_media: false,
+            }
+        }
+
+        fn status(&self) -> ChannelStatus {
+            ChannelStatus::Running
+        }
+
+        fn is_allowed(&self, _sender_id: &str) -> bool {
+            true
+        }
+
+        async fn start(
+            &self,
+            _host: Arc<dyn ChannelHost>,
+            _cancel: CancellationToken,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
+            self.texts.lock().await.push(msg.content.clone());
+            Ok(MessageId("t".into()))
+        }
+    }
+
+    fn message(content: &str, attachments: Vec<Attachment>) -> OutboundMessage {
+        OutboundMessage {
+            channel: "recording".into(),
+            chat_id: "c1".into(),
+            content: content.into(),
+            reply_to: None,
+            media: vec![],
+            attachments,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn text_then_attachments_in_order() {
+        let ch = RecordingChannel::new(Some(1024));
+        let msg = message(
+            "see attached",
+            vec![
+                Attachment::from_bytes("a.txt", "text/plain", b"aaa".to_vec()),
+                Attachment::from_bytes("b.txt", "text/plain", b"bb".to_vec()),
+            ],
+        );
+        let id = send_with_attachments(&ch, &msg).await.unwrap();
+        assert_eq!(id, MessageId("file-2".into()));
+        assert_eq!(*ch.texts.lock().await, vec!["see attached"]);
+        let uploads = ch.uploads.lock().await;
+        assert_eq!(uploads[0], ("a.txt".to_owned(), b"aaa".to_vec()));
+        assert_eq!(uploads[1].0, "b.txt");
+    }
+
+    #[tokio::test]
+    async fn empty_text_is_skipped_when_attachments_present() {
+        let ch = RecordingChannel::new(Some(1024));
+        let msg = message("", vec![Attachment::from_bytes("a", "text/plain", vec![1])]);
+        send_with_attachments(&ch, &msg).await.unwrap();
+        assert!(ch.texts.lock().await.is_empty());
+        assert_eq!(ch.uploads.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn plain_message_still_sends_text() {
+        let ch = RecordingChannel::new(None);
+        let id = send_with_attachments(&ch, &message("hi", vec![]))
+            .await
+            .unwrap();
+        assert_eq!(id, MessageId("text-1".into()));
+    }
+
+    #[tokio::test]
+    async fn oversized_attachment_reports_uniform_error() {
+        let ch = RecordingChannel::new(Some(4));
+        let msg = message(
+            "",
+            vec![Attachment::from_bytes(
+                "big.bin",
+                "application/octet-stream",
+                vec![0; 5],
+            )],
+        );
+        match send_with_attachments(&ch, &msg).await {
+            Err(ChannelError::AttachmentTooLarge {
+                filename,
+                size,
+                limit,
+            }) => {
+                assert_eq!(filename, "big.bin");
+                assert_eq!(size, 5);
+                assert_eq!(limit, 4);
+            }
+            other => panic!("expected AttachmentTooLarge, got {other:?}"),
+        }
+        assert!(ch.uploads.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn oversized_file_is_rejected_before_reading() {
+        let dir = std::env::temp_dir().join(format!("clawft-att-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("report.csv");
+        tokio::fs::write(&path, vec![b'x'; 64]).await.unwrap();
+
+        let att = Attachment::from_path(&path, "text/csv");
+        assert_eq!(attachment_size(&att).await.unwrap(), 64);
+        assert!(matches!(
+            load_within_limit(&att, 10).await,
+            Err(ChannelError::AttachmentTooLarge { .. })
+        ));
+        assert_eq!(load_within_limit(&att, 64).await.unwrap().len(), 64);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_send_failure() {
+        let att = Attachment::from_path("/nonexistent/clawft/file.bin", "application/pdf");
+        assert!(matches!(
+            load_within_limit(&att, 10).await,
+            Err(ChannelError::SendFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn channel_without_upload_support_sends_note() {
+        let ch = TextOnlyChannel {
+            texts: Mutex::new(Vec::new()),
+        };
+        let msg = message(
+            "done",
+            vec![Attachment::from_path("/tmp/plot.png", "image/png").with_caption("loss curve")],
+        );
+        send_with_attachments(&ch, &msg).await.unwrap();
+        assert_eq!(
+            *ch.texts.lock().await,
+            vec![
+                "done".to_owned(),
+                "[attachment omitted: plot.png (image/png)] loss curve".to_owned()
+            ]
+        );
+    }
+}
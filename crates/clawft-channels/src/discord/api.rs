@@ -2,13 +2,14 @@
 //!
 //! [`DiscordApiClient`] provides typed methods for the subset of the
 //! Discord REST API used by the channel plugin: sending and editing
-//! messages.
+//! messages, and uploading file attachments.
 
 use reqwest::Client;
 use tracing::{debug, warn};
 
 use clawft_types::error::ChannelError;
 
+use super::channel::DISCORD_MAX_UPLOAD_BYTES;
 use super::events::RateLimitInfo;
 
 /// Base URL for the Discord REST API v10.
@@ -118,6 +119,68 @@ impl DiscordApiClient {
         Ok(msg.id)
     }
 
+    /// Send a message carrying a single file attachment.
+    ///
+    /// Uses a multipart body with `payload_json` (caption and attachment
+    /// descriptor) and the file in `files[0]`. Returns the message ID, or
+    /// [`ChannelError::AttachmentTooLarge`] when Discord answers `413`.
+    pub async fn create_message_with_file(
+        &self,
+        channel_id: &str,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+        caption: Option<&str>,
+    ) -> Result<String, ChannelError> {
+        let url = format!("{}/channels/{channel_id}/messages", self.base_url);
+
+        let payload = serde_json::json!({
+            "content": caption.unwrap_or_default(),
+            "attachments": [{ "id": 0, "filename": filename }],
+        });
+        let size = data.len() as u64;
+        let file = reqwest::multipart::Part::bytes(data)
+            .file_name(filename.to_owned())
+            .mime_str(mime_type)
+            .map_err(|e| ChannelError::SendFailed(format!("invalid mime type: {e}")))?;
+        let form = reqwest::multipart::Form::new()
+            .text("payload_json", payload.to_string())
+            .part("files[0]", file);
+
+        debug!(channel_id = %channel_id, filename = %filename, "uploading attachment");
+
+        let resp = self
+            .http
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.token))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let status = resp.status();
+        if status == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+            return Err(ChannelError::AttachmentTooLarge {
+                filename: filename.to_owned(),
+                size,
+                limit: DISCORD_MAX_UPLOAD_BYTES,
+            });
+        }
+        if !status.is_success() {
+            let err_body = resp.text().await.unwrap_or_else(|_| "unknown error".into());
+            return Err(ChannelError::SendFailed(format!(
+                "Discord API returned {status}: {err_body}"
+            )));
+        }
+
+        let msg: DiscordMessage = resp
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        Ok(msg.id)
+    }
+
     /// Edit an existing message.
     pub async fn edit_message(
         &self,
@@ -17,8 +17,9 @@ use tracing::{debug, error, info, warn};
 
 use clawft_types::config::DiscordConfig;
 use clawft_types::error::ChannelError;
-use clawft_types::event::{InboundMessage, OutboundMessage};
+use clawft_types::event::{Attachment, InboundMessage, OutboundMessage};
 
+use crate::attachment::load_within_limit;
 use crate::traits::{Channel, ChannelHost, ChannelMetadata, ChannelStatus, MessageId};
 
 use super::api::DiscordApiClient;
@@ -36,6 +37,9 @@ const RECONNECT_DELAY_SECS: u64 = 5;
 /// We use 2000 to be universally safe.
 const DISCORD_MAX_MESSAGE_LEN: usize = 2000;
 
+/// Default upload limit for bots in non-boosted servers (10 MiB).
+pub(crate) const DISCORD_MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
 /// Split a message into chunks that fit within the Discord character limit.
 ///
 /// Tries to split at line boundaries first, then word boundaries. If a
@@ -202,6 +206,7 @@ impl DiscordChannel {
             content: msg.content.clone(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata,
         };
 
@@ -567,6 +572,7 @@ impl Channel for DiscordChannel {
                     info!("reconnecting Discord Gateway...");
                 }
             }
+            host.report_reconnect(self.name()).await;
         }
 
         self.set_status(ChannelStatus::Stopped).await;
@@ -584,6 +590,47 @@ impl Channel for DiscordChannel {
 
         Ok(MessageId(last_id))
     }
+
+    fn supports_edit(&self) -> bool {
+        true
+    }
+
+    /// Edit `id` to hold the first chunk of `msg`; any further chunks are
+    /// sent as new messages.
+    async fn edit_message(
+        &self,
+        id: &MessageId,
+        msg: &OutboundMessage,
+    ) -> Result<(), ChannelError> {
+        let chunks = chunk_message(&msg.content, DISCORD_MAX_MESSAGE_LEN);
+        let mut chunks = chunks.iter();
+        if let Some(first) = chunks.next() {
+            self.api.edit_message(&msg.chat_id, &id.0, first).await?;
+        }
+        for chunk in chunks {
+            self.api.create_message(&msg.chat_id, chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_attachment(
+        &self,
+        msg: &OutboundMessage,
+        attachment: &Attachment,
+    ) -> Result<MessageId, ChannelError> {
+        let data = load_within_limit(attachment, DISCORD_MAX_UPLOAD_BYTES).await?;
+        let id = self
+            .api
+            .create_message_with_file(
+                &msg.chat_id,
+                &attachment.filename,
+                &attachment.mime_type,
+                data,
+                attachment.caption.as_deref(),
+            )
+            .await?;
+        Ok(MessageId(id))
+    }
 }
 
 #[cfg(test)]
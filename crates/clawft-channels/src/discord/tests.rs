@@ -11,7 +11,8 @@ use clawft_types::event::InboundMessage;
 
 use crate::traits::{Channel, ChannelHost, ChannelMetadata, ChannelStatus, Command};
 
-use super::channel::DiscordChannel;
+use super::api::DiscordApiClient;
+use super::channel::{DISCORD_MAX_UPLOAD_BYTES, DiscordChannel};
 use super::events::{MessageCreate, MessageReference, User};
 
 // ── Mock host ────────────────────────────────────────────────────────────
@@ -273,3 +274,35 @@ async fn set_status_transitions() {
     ch.set_status(ChannelStatus::Stopped).await;
     assert_eq!(ch.status(), ChannelStatus::Stopped);
 }
+
+#[tokio::test]
+async fn upload_rejected_with_413_is_attachment_too_large() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/channels/123/messages"))
+        .respond_with(ResponseTemplate::new(413))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = DiscordApiClient::with_base_url("test-token".into(), server.uri());
+    let err = client
+        .create_message_with_file("123", "report.pdf", "application/pdf", vec![0; 5], None)
+        .await
+        .unwrap_err();
+    match err {
+        ChannelError::AttachmentTooLarge {
+            filename,
+            size,
+            limit,
+        } => {
+            assert_eq!(filename, "report.pdf");
+            assert_eq!(size, 5);
+            assert_eq!(limit, DISCORD_MAX_UPLOAD_BYTES);
+        }
+        other => panic!("expected AttachmentTooLarge, got {other:?}"),
+    }
+}
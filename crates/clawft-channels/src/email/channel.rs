@@ -4,7 +4,8 @@
 //! email communication. Polls an IMAP mailbox for new messages and
 //! delivers them to the agent pipeline via [`ChannelAdapterHost`].
 //!
-//! Outbound messages are sent as email replies via SMTP.
+//! Outbound messages are sent as email replies via SMTP. Attachments are
+//! encoded into a `multipart/mixed` message by [`super::mime`].
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -16,8 +17,12 @@ use tracing::{debug, info, warn};
 use clawft_plugin::error::PluginError;
 use clawft_plugin::message::MessagePayload;
 use clawft_plugin::traits::{ChannelAdapter, ChannelAdapterHost};
+use clawft_types::error::ChannelError;
+use clawft_types::event::Attachment;
 
+use super::mime::{MimeAttachment, build_multipart};
 use super::types::{EmailAdapterConfig, ParsedEmail};
+use crate::attachment::load_within_limit;
 
 /// Email channel adapter.
 ///
@@ -78,6 +83,74 @@ impl EmailChannelAdapter {
         metadata
     }
 
+    /// Send an email with file attachments.
+    ///
+    /// Each attachment is checked against `max_attachment_bytes`;
+    /// oversized files fail with [`ChannelError::AttachmentTooLarge`]
+    /// before anything is sent. Returns the generated message ID.
+    ///
+    /// Like [`send`](ChannelAdapter::send), delivery is still a stub, so
+    /// the adapter reports no media support until an SMTP transport lands.
+    pub async fn send_with_attachments(
+        &self,
+        target: &str,
+        subject: &str,
+        body: &str,
+        attachments: &[Attachment],
+    ) -> Result<String, ChannelError> {
+        if self.config.smtp_host.is_empty() {
+            return Err(ChannelError::SendFailed(
+                "email adapter: smtp_host is not configured".into(),
+            ));
+        }
+
+        let mut parts = Vec::with_capacity(attachments.len());
+        for attachment in attachments {
+            parts.push(MimeAttachment {
+                filename: attachment.filename.clone(),
+                mime_type: attachment.mime_type.clone(),
+                data: load_within_limit(attachment, self.config.max_attachment_bytes).await?,
+            });
+        }
+
+        let msg_id = self.generate_message_id(target);
+        let boundary = format!("clawft-{}", uuid::Uuid::new_v4().simple());
+        let message = build_multipart(
+            &self.config.email_address,
+            target,
+            subject,
+            body,
+            &parts,
+            &boundary,
+        );
+
+        // In production, this would hand `message` to `lettre` for SMTP
+        // delivery. The stub only logs the encoded size.
+        info!(
+            to = %target,
+            smtp_host = %self.config.smtp_host,
+            attachments = parts.len(),
+            "sending email with attachments (stub)"
+        );
+        debug!(message_len = message.len(), "email mime message");
+
+        Ok(msg_id)
+    }
+
+    /// Generate an RFC 5322 message ID for an outbound email.
+    fn generate_message_id(&self, target: &str) -> String {
+        format!(
+            "<{}-{}@{}>",
+            chrono::Utc::now().timestamp_millis(),
+            target.replace('@', "-at-"),
+            self.config
+                .email_address
+                .split('@')
+                .nth(1)
+                .unwrap_or("localhost")
+        )
+    }
+
     /// Deliver a parsed email to the host pipeline.
     pub async fn deliver_email(
         &self,
@@ -123,7 +196,10 @@ impl ChannelAdapter for EmailChannelAdapter {
     }
 
     fn supports_media(&self) -> bool {
-        false // Attachment support is a future enhancement
+        // `send_with_attachments` builds the MIME message, but there is no
+        // SMTP transport yet; report no media so callers fall back to the
+        // omitted-attachment note instead of dropping files silently.
+        false
     }
 
     async fn start(
@@ -205,18 +281,7 @@ impl ChannelAdapter for EmailChannelAdapter {
         );
         debug!(content_len = content.len(), "email content");
 
-        let msg_id = format!(
-            "<{}-{}@{}>",
-            chrono::Utc::now().timestamp_millis(),
-            target.replace('@', "-at-"),
-            self.config
-                .email_address
-                .split('@')
-                .nth(1)
-                .unwrap_or("localhost")
-        );
-
-        Ok(msg_id)
+        Ok(self.generate_message_id(target))
     }
 }
 
@@ -461,6 +526,37 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn send_with_attachments_within_limit() {
+        let adapter = EmailChannelAdapter::new(make_config());
+        let att = Attachment::from_bytes("notes.txt", "text/plain", b"hello".to_vec());
+        let msg_id = adapter
+            .send_with_attachments("user@example.com", "Notes", "attached", &[att])
+            .await
+            .unwrap();
+        assert!(msg_id.contains("test.com"));
+    }
+
+    #[tokio::test]
+    async fn send_with_oversized_attachment_fails() {
+        let mut config = make_config();
+        config.max_attachment_bytes = 4;
+        let adapter = EmailChannelAdapter::new(config);
+        let att = Attachment::from_bytes("big.bin", "application/octet-stream", vec![0; 5]);
+        let err = adapter
+            .send_with_attachments("user@example.com", "Big", "", &[att])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ChannelError::AttachmentTooLarge {
+                size: 5,
+                limit: 4,
+                ..
+            }
+        ));
+    }
+
     // -- Start validation --
 
     #[tokio::test]
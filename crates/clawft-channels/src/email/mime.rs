@@ -0,0 +1,120 @@
+//! Minimal MIME message builder for outbound email.
+//!
+//! Produces an RFC 2045/2046 `multipart/mixed` message with a plain-text
+//! body part followed by base64-encoded attachment parts. Kept free of
+//! I/O so the output can be handed to any SMTP transport.
+
+use base64::Engine;
+
+/// Line length for base64 bodies (RFC 2045 limits lines to 76 chars).
+const BASE64_LINE_LEN: usize = 76;
+
+/// A file part of an outbound email.
+#[derive(Debug, Clone)]
+pub struct MimeAttachment {
+    /// Filename advertised in `Content-Disposition`.
+    pub filename: String,
+    /// MIME type of the contents.
+    pub mime_type: String,
+    /// Raw file contents.
+    pub data: Vec<u8>,
+}
+
+/// Build a `multipart/mixed` message.
+///
+/// `boundary` must not occur in the body text; callers normally pass a
+/// random value.
+pub fn build_multipart(
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+    attachments: &[MimeAttachment],
+    boundary: &str,
+) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("From: {from}\r\n"));
+    out.push_str(&format!("To: {to}\r\n"));
+    out.push_str(&format!("Subject: {}\r\n", sanitize_header(subject)));
+    out.push_str("MIME-Version: 1.0\r\n");
+    out.push_str(&format!(
+        "Content-Type: multipart/mixed; boundary=\"{boundary}\"\r\n\r\n"
+    ));
+
+    out.push_str(&format!("--{boundary}\r\n"));
+    out.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    out.push_str("Content-Transfer-Encoding: 8bit\r\n\r\n");
+    out.push_str(body);
+    out.push_str("\r\n");
+
+    for att in attachments {
+        let name = sanitize_header(&att.filename).replace('"', "'");
+        out.push_str(&format!("--{boundary}\r\n"));
+        out.push_str(&format!(
+            "Content-Type: {}; name=\"{name}\"\r\n",
+            sanitize_header(&att.mime_type)
+        ));
+        out.push_str("Content-Transfer-Encoding: base64\r\n");
+        out.push_str(&format!(
+            "Content-Disposition: attachment; filename=\"{name}\"\r\n\r\n"
+        ));
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&att.data);
+        for line in encoded.as_bytes().chunks(BASE64_LINE_LEN) {
+            // base64 output is ASCII, so every chunk is valid UTF-8.
+            out.push_str(std::str::from_utf8(line).unwrap_or_default());
+            out.push_str("\r\n");
+        }
+    }
+
+    out.push_str(&format!("--{boundary}--\r\n"));
+    out
+}
+
+/// Strip CR/LF so values cannot inject extra headers.
+fn sanitize_header(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(data: Vec<u8>) -> MimeAttachment {
+        MimeAttachment {
+            filename: "report.csv".into(),
+            mime_type: "text/csv".into(),
+            data,
+        }
+    }
+
+    #[test]
+    fn builds_text_and_attachment_parts() {
+        let msg = build_multipart(
+            "bot@test.com",
+            "user@example.com",
+            "Your report",
+            "See attached.",
+            &[attachment(b"a,b\n1,2\n".to_vec())],
+            "BOUNDARY",
+        );
+        assert!(msg.contains("Content-Type: multipart/mixed; boundary=\"BOUNDARY\""));
+        assert!(msg.contains("See attached.\r\n"));
+        assert!(msg.contains("Content-Disposition: attachment; filename=\"report.csv\""));
+        assert!(msg.contains("YSxiCjEsMgo=\r\n"));
+        assert!(msg.ends_with("--BOUNDARY--\r\n"));
+    }
+
+    #[test]
+    fn base64_lines_are_wrapped() {
+        let msg = build_multipart("a@b", "c@d", "s", "", &[attachment(vec![7; 300])], "B");
+        let body = msg.split("filename=\"report.csv\"\r\n\r\n").nth(1).unwrap();
+        assert!(body.lines().all(|l| l.len() <= BASE64_LINE_LEN));
+    }
+
+    #[test]
+    fn header_injection_is_neutralized() {
+        let msg = build_multipart("a@b", "c@d", "hi\r\nBcc: x@evil", "", &[], "B");
+        assert!(msg.contains("Subject: hi  Bcc: x@evil\r\n"));
+        assert!(!msg.contains("\r\nBcc:"));
+    }
+}
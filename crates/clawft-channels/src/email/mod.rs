@@ -8,7 +8,7 @@
 //!
 //! - Polls an IMAP mailbox for new messages at a configurable interval
 //! - Delivers parsed emails to the agent pipeline via `ChannelAdapterHost`
-//! - Sends outbound replies via SMTP
+//! - Sends outbound replies via SMTP, with optional file attachments
 //! - Supports password and OAuth2 (Gmail) authentication
 //! - All credentials use [`SecretString`](clawft_types::secret::SecretString)
 //!
@@ -17,9 +17,11 @@
 //! - [`types`] -- Configuration and message types
 //! - [`channel`] -- `ChannelAdapter` trait implementation
 //! - [`factory`] -- Factory for creating adapters from JSON config
+//! - [`mime`] -- Multipart message builder for attachments
 
 pub mod channel;
 pub mod factory;
+pub mod mime;
 pub mod types;
 
 pub use channel::EmailChannelAdapter;
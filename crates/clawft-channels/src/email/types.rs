@@ -101,6 +101,10 @@ pub struct EmailAdapterConfig {
     /// Maximum body characters to process.
     #[serde(default = "default_max_body_chars", alias = "maxBodyChars")]
     pub max_body_chars: usize,
+
+    /// Maximum size of a single outbound attachment in bytes.
+    #[serde(default = "default_max_attachment_bytes", alias = "maxAttachmentBytes")]
+    pub max_attachment_bytes: u64,
 }
 
 fn default_imap_port() -> u16 {
@@ -121,6 +125,9 @@ fn default_true() -> bool {
 fn default_max_body_chars() -> usize {
     12000
 }
+fn default_max_attachment_bytes() -> u64 {
+    25 * 1024 * 1024
+}
 
 impl Default for EmailAdapterConfig {
     fn default() -> Self {
@@ -137,6 +144,7 @@ impl Default for EmailAdapterConfig {
             imap_use_tls: true,
             smtp_use_tls: true,
             max_body_chars: default_max_body_chars(),
+            max_attachment_bytes: default_max_attachment_bytes(),
         }
     }
 }
@@ -173,6 +181,7 @@ mod tests {
         assert!(cfg.imap_use_tls);
         assert!(cfg.smtp_use_tls);
         assert_eq!(cfg.max_body_chars, 12000);
+        assert_eq!(cfg.max_attachment_bytes, 25 * 1024 * 1024);
     }
 
     #[test]
@@ -193,6 +202,7 @@ mod tests {
             imap_use_tls: true,
             smtp_use_tls: true,
             max_body_chars: 8000,
+            max_attachment_bytes: 1024,
         };
         let json = serde_json::to_string(&cfg).unwrap();
         let restored: EmailAdapterConfig = serde_json::from_str(&json).unwrap();
@@ -6,19 +6,30 @@
 //! - Creating channel instances from configuration
 //! - Starting and stopping channels (each in its own tokio task)
 //! - Routing outbound messages to the correct channel
+//! - Counting per-channel traffic in a [`MetricsRegistry`]
+//! - Reconciling running channels against a new configuration
+//!   ([`PluginHost::reload`])
+//! - Updating a streaming reply in place on channels that support editing
+//!   ([`PluginHost::open_progressive`])
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
+use crate::metrics::{ChannelMetricsSnapshot, MeteredHost, MetricsRegistry};
 use crate::traits::*;
 use clawft_types::error::ChannelError;
 use clawft_types::event::OutboundMessage;
 
+/// Default minimum time between edits of a progressively updated reply,
+/// to stay within platform rate limits.
+pub const DEFAULT_EDIT_INTERVAL: Duration = Duration::from_millis(1500);
+
 /// Manages channel plugins: registration, lifecycle, and message routing.
 ///
 /// Channels are created from [`ChannelFactory`] instances, started in
@@ -29,26 +40,98 @@ pub struct PluginHost {
     factories: RwLock<HashMap<String, Arc<dyn ChannelFactory>>>,
     /// Active channel instances, keyed by channel name.
     channels: RwLock<HashMap<String, Arc<dyn Channel>>>,
+    /// Config each active channel was built from, for reload diffing.
+    configs: RwLock<HashMap<String, serde_json::Value>>,
     /// Cancellation tokens for running channel tasks.
     cancel_tokens: RwLock<HashMap<String, CancellationToken>>,
     /// Join handles for running channel tasks.
     task_handles: RwLock<HashMap<String, JoinHandle<()>>>,
     /// The host interface provided to channel plugins.
     host_impl: Arc<dyn ChannelHost>,
+    /// Per-channel traffic counters.
+    metrics: Arc<MetricsRegistry>,
+    /// Serializes concurrent [`reload`](PluginHost::reload) calls.
+    reload_lock: Mutex<()>,
+    /// Open progressive replies, keyed by `(channel, chat_id)`.
+    progressive: std::sync::Mutex<HashMap<(String, String), SharedProgressive>>,
+    /// Minimum time between edits of one progressive reply.
+    edit_interval: Duration,
+}
+
+/// A reply being shown progressively while the agent streams it.
+///
+/// Opened with [`PluginHost::open_progressive`] and fed the text so far
+/// with [`PluginHost::update_progressive`]. The final
+/// [streamed](OutboundMessage::is_streamed) outbound message for the same
+/// chat then replaces the partial text instead of being sent anew.
+#[derive(Clone)]
+pub struct ProgressiveReply {
+    channel: String,
+    chat_id: String,
+    state: SharedProgressive,
+}
+
+/// State shared by a [`ProgressiveReply`] and the host's open-reply map.
+type SharedProgressive = Arc<Mutex<ProgressiveState>>;
+
+/// Delivery state of a [`ProgressiveReply`].
+#[derive(Default)]
+struct ProgressiveState {
+    /// The message holding the partial text, once sent.
+    message_id: Option<MessageId>,
+    /// When the partial text was last sent or edited.
+    last_update: Option<Instant>,
+    /// Set once the final text was delivered, or editing is unavailable.
+    closed: bool,
+}
+
+/// Outcome of a [`PluginHost::reload`].
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// Channels that were newly initialized and started.
+    pub started: Vec<String>,
+    /// Channels that were stopped and removed.
+    pub stopped: Vec<String>,
+    /// Channels that were rebuilt because their config changed.
+    pub restarted: Vec<String>,
+    /// Channels left running untouched.
+    pub unchanged: Vec<String>,
+    /// Channels that could not be brought to the desired state.
+    pub failed: Vec<(String, ChannelError)>,
 }
 
 impl PluginHost {
     /// Create a new `PluginHost` with the given [`ChannelHost`] implementation.
     pub fn new(host: Arc<dyn ChannelHost>) -> Self {
+        Self::with_metrics(host, Arc::new(MetricsRegistry::new()))
+    }
+
+    /// Create a `PluginHost` that records into an existing [`MetricsRegistry`].
+    ///
+    /// Useful when the registry must be shared with components created
+    /// before the host (e.g. the gateway HTTP API).
+    pub fn with_metrics(host: Arc<dyn ChannelHost>, metrics: Arc<MetricsRegistry>) -> Self {
         Self {
             factories: RwLock::new(HashMap::new()),
             channels: RwLock::new(HashMap::new()),
+            configs: RwLock::new(HashMap::new()),
             cancel_tokens: RwLock::new(HashMap::new()),
             task_handles: RwLock::new(HashMap::new()),
             host_impl: host,
+            metrics,
+            reload_lock: Mutex::new(()),
+            progressive: std::sync::Mutex::new(HashMap::new()),
+            edit_interval: DEFAULT_EDIT_INTERVAL,
         }
     }
 
+    /// Set the minimum time between edits of a progressive reply
+    /// (default [`DEFAULT_EDIT_INTERVAL`]).
+    pub fn with_edit_interval(mut self, interval: Duration) -> Self {
+        self.edit_interval = interval;
+        self
+    }
+
     /// Register a channel factory.
     ///
     /// If a factory with the same channel name is already registered,
@@ -70,15 +153,38 @@ impl PluginHost {
         name: &str,
         config: &serde_json::Value,
     ) -> Result<(), ChannelError> {
+        let channel = self.build_channel(name, config).await?;
+        self.install_channel(name, channel, config).await;
+        Ok(())
+    }
+
+    /// Build a channel instance from its factory without registering it.
+    async fn build_channel(
+        &self,
+        name: &str,
+        config: &serde_json::Value,
+    ) -> Result<Arc<dyn Channel>, ChannelError> {
         let factories = self.factories.read().await;
         let factory = factories
             .get(name)
             .ok_or_else(|| ChannelError::NotFound(name.to_owned()))?;
+        factory.build(config)
+    }
 
-        let channel = factory.build(config)?;
+    /// Register a built channel instance and the config it came from.
+    async fn install_channel(
+        &self,
+        name: &str,
+        channel: Arc<dyn Channel>,
+        config: &serde_json::Value,
+    ) {
         info!(channel = %name, "channel initialized");
+        self.metrics.counters(name);
         self.channels.write().await.insert(name.to_owned(), channel);
-        Ok(())
+        self.configs
+            .write()
+            .await
+            .insert(name.to_owned(), config.clone());
     }
 
     /// Start all initialized channels concurrently.
@@ -127,7 +233,7 @@ impl PluginHost {
     /// Start a specific channel by name.
     ///
     /// Spawns a tokio task that calls [`Channel::start`] with the host
-    /// interface and a cancellation token.
+    /// interface (wrapped in a [`MeteredHost`]) and a cancellation token.
     pub async fn start_channel(&self, name: &str) -> Result<(), ChannelError> {
         let channels = self.channels.read().await;
         let channel = channels
@@ -137,7 +243,10 @@ impl PluginHost {
         drop(channels);
 
         let cancel = CancellationToken::new();
-        let host = self.host_impl.clone();
+        let host: Arc<dyn ChannelHost> = Arc::new(MeteredHost::new(
+            self.host_impl.clone(),
+            self.metrics.clone(),
+        ));
         let channel_name = name.to_owned();
         let cancel_clone = cancel.clone();
 
@@ -185,7 +294,128 @@ impl PluginHost {
         Ok(())
     }
 
+    /// Reconcile running channels with `desired` (channel name -> config).
+    ///
+    /// Channels absent from `desired` are stopped (cancelled and awaited)
+    /// and removed; new entries are initialized and started; channels
+    /// whose factory reports a material config change via
+    /// [`ChannelFactory::requires_restart`] are rebuilt and restarted. The
+    /// replacement is built before the running instance is stopped, so a
+    /// config that fails to build leaves the old channel serving.
+    /// Everything else keeps running untouched. A failure on one channel
+    /// is recorded in the report and does not stop the rest.
+    pub async fn reload(&self, desired: &HashMap<String, serde_json::Value>) -> ReloadReport {
+        let _guard = self.reload_lock.lock().await;
+        let mut report = ReloadReport::default();
+
+        let current = self.configs.read().await.clone();
+
+        let mut removed: Vec<&String> = current
+            .keys()
+            .filter(|n| !desired.contains_key(*n))
+            .collect();
+        removed.sort();
+        for name in removed {
+            self.remove_channel(name).await;
+            info!(channel = %name, "channel removed by reload");
+            report.stopped.push(name.clone());
+        }
+
+        let mut names: Vec<&String> = desired.keys().collect();
+        names.sort();
+        for name in names {
+            let new_config = &desired[name];
+            let Some(old_config) = current.get(name) else {
+                let result = match self.init_channel(name, new_config).await {
+                    Ok(()) => self.start_channel(name).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(()) => {
+                        info!(channel = %name, "channel started by reload");
+                        report.started.push(name.clone());
+                    }
+                    Err(e) => {
+                        error!(channel = %name, error = %e, "channel reload failed");
+                        report.failed.push((name.clone(), e));
+                    }
+                }
+                continue;
+            };
+
+            let factory = self.factories.read().await.get(name).cloned();
+            let changed = factory
+                .map(|f| f.requires_restart(old_config, new_config))
+                .unwrap_or(old_config != new_config);
+            if !changed {
+                report.unchanged.push(name.clone());
+                continue;
+            }
+            match self.replace_channel(name, old_config, new_config).await {
+                Ok(()) => {
+                    info!(channel = %name, "channel restarted by reload");
+                    report.restarted.push(name.clone());
+                }
+                Err(e) => {
+                    error!(
+                        channel = %name,
+                        error = %e,
+                        "channel reload failed, keeping previous instance"
+                    );
+                    report.failed.push((name.clone(), e));
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Swap a running channel for one built from `new_config`.
+    ///
+    /// The new instance is built first; on failure the old one is left
+    /// untouched. If the new instance cannot be started, the old one is
+    /// reinstalled and restarted.
+    async fn replace_channel(
+        &self,
+        name: &str,
+        old_config: &serde_json::Value,
+        new_config: &serde_json::Value,
+    ) -> Result<(), ChannelError> {
+        let channel = self.build_channel(name, new_config).await?;
+        let previous = self.channels.read().await.get(name).cloned();
+
+        if self.cancel_tokens.read().await.contains_key(name) {
+            let _ = self.stop_channel(name).await;
+        }
+        self.install_channel(name, channel, new_config).await;
+        let Err(e) = self.start_channel(name).await else {
+            return Ok(());
+        };
+
+        if let Some(previous) = previous {
+            self.install_channel(name, previous, old_config).await;
+            if let Err(restore) = self.start_channel(name).await {
+                error!(channel = %name, error = %restore, "failed to restore previous channel");
+            }
+        }
+        Err(e)
+    }
+
+    /// Stop a channel if running and forget its instance and config.
+    async fn remove_channel(&self, name: &str) {
+        if self.cancel_tokens.read().await.contains_key(name) {
+            let _ = self.stop_channel(name).await;
+        }
+        self.channels.write().await.remove(name);
+        self.configs.write().await.remove(name);
+    }
+
     /// Route an outbound message to the appropriate channel.
+    ///
+    /// Text is sent first, followed by each attachment (see
+    /// [`send_with_attachments`](crate::attachment::send_with_attachments)).
+    /// A [streamed](OutboundMessage::is_streamed) message whose chat has a
+    /// [`ProgressiveReply`] on screen is delivered by editing that message.
     pub async fn send_to_channel(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
         let channels = self.channels.read().await;
         let channel = channels
@@ -194,7 +424,96 @@ impl PluginHost {
             .clone();
         drop(channels);
 
-        channel.send(msg).await
+        let progressive = if msg.is_streamed() {
+            self.take_progressive(&msg.channel, &msg.chat_id)
+        } else {
+            None
+        };
+
+        let counters = self.metrics.counters(&msg.channel);
+        let result = match progressive {
+            Some(state) => finish_progressive(channel.as_ref(), &state, msg).await,
+            None => crate::attachment::send_with_attachments(channel.as_ref(), msg).await,
+        };
+        match result {
+            Ok(_) => counters.record_sent(),
+            Err(_) => counters.record_send_failure(),
+        }
+        result
+    }
+
+    /// Start a progressive reply in `chat_id` on `channel`.
+    ///
+    /// Replaces any reply still open for the same chat.
+    pub fn open_progressive(&self, channel: &str, chat_id: &str) -> ProgressiveReply {
+        let state = Arc::new(Mutex::new(ProgressiveState::default()));
+        if let Ok(mut open) = self.progressive.lock() {
+            open.insert((channel.to_owned(), chat_id.to_owned()), state.clone());
+        }
+        ProgressiveReply {
+            channel: channel.to_owned(),
+            chat_id: chat_id.to_owned(),
+            state,
+        }
+    }
+
+    /// Show `text`, the reply so far, in place of the previous partial text.
+    ///
+    /// The first update sends a message; later ones edit it, at most once
+    /// per edit interval -- updates arriving sooner are skipped, since the
+    /// final text replaces them anyway. Does nothing once the reply is
+    /// finished, or when the channel cannot edit messages.
+    pub async fn update_progressive(&self, reply: &ProgressiveReply, text: &str) {
+        let mut state = reply.state.lock().await;
+        if state.closed || text.trim().is_empty() {
+            return;
+        }
+        if state
+            .last_update
+            .is_some_and(|last| last.elapsed() < self.edit_interval)
+        {
+            return;
+        }
+        let channel = self.channels.read().await.get(&reply.channel).cloned();
+        let Some(channel) = channel.filter(|c| c.supports_edit()) else {
+            state.closed = true;
+            return;
+        };
+
+        let msg = OutboundMessage {
+            channel: reply.channel.clone(),
+            chat_id: reply.chat_id.clone(),
+            content: text.to_owned(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        let result = match state.message_id {
+            Some(ref id) => channel.edit_message(id, &msg).await,
+            None => channel.send(&msg).await.map(|id| {
+                state.message_id = Some(id);
+            }),
+        };
+        if let Err(e) = result {
+            warn!(
+                channel = %reply.channel,
+                chat_id = %reply.chat_id,
+                error = %e,
+                "progressive reply update failed"
+            );
+            // Without a first message there is nothing to edit later.
+            state.closed = state.message_id.is_none();
+        }
+        state.last_update = Some(Instant::now());
+    }
+
+    /// Remove and return the open progressive reply for a chat.
+    fn take_progressive(&self, channel: &str, chat_id: &str) -> Option<SharedProgressive> {
+        self.progressive
+            .lock()
+            .ok()?
+            .remove(&(channel.to_owned(), chat_id.to_owned()))
     }
 
     /// Get status of all initialized channels.
@@ -206,6 +525,24 @@ impl PluginHost {
             .collect()
     }
 
+    /// Per-channel metrics for every initialized channel, with status.
+    ///
+    /// Channels that have been removed keep their last counters and are
+    /// reported without a status.
+    pub async fn metrics(&self) -> Vec<ChannelMetricsSnapshot> {
+        let statuses = self.get_status().await;
+        let mut snapshots = self.metrics.snapshot();
+        for snap in &mut snapshots {
+            snap.status = statuses.get(&snap.channel).map(status_label);
+        }
+        snapshots
+    }
+
+    /// The shared metrics registry.
+    pub fn metrics_registry(&self) -> Arc<MetricsRegistry> {
+        self.metrics.clone()
+    }
+
     /// Get the list of registered factory names.
     pub async fn registered_channels(&self) -> Vec<String> {
         self.factories.read().await.keys().cloned().collect()
@@ -217,6 +554,47 @@ impl PluginHost {
     }
 }
 
+/// Deliver the final text of a progressive reply.
+///
+/// Edits the partial message to hold `msg`, then sends the attachments.
+/// Falls back to a normal send when no partial message was shown or the
+/// edit fails.
+async fn finish_progressive(
+    channel: &dyn Channel,
+    state: &Mutex<ProgressiveState>,
+    msg: &OutboundMessage,
+) -> Result<MessageId, ChannelError> {
+    let mut state = state.lock().await;
+    state.closed = true;
+    let Some(id) = state.message_id.clone() else {
+        return crate::attachment::send_with_attachments(channel, msg).await;
+    };
+    if let Err(e) = channel.edit_message(&id, msg).await {
+        warn!(
+            channel = %msg.channel,
+            error = %e,
+            "final edit of streamed reply failed, sending it instead"
+        );
+        return crate::attachment::send_with_attachments(channel, msg).await;
+    }
+    let mut last = id;
+    for attachment in &msg.attachments {
+        last = channel.send_attachment(msg, attachment).await?;
+    }
+    Ok(last)
+}
+
+/// Short lowercase label for a [`ChannelStatus`].
+fn status_label(status: &ChannelStatus) -> String {
+    match status {
+        ChannelStatus::Stopped => "stopped".into(),
+        ChannelStatus::Starting => "starting".into(),
+        ChannelStatus::Running => "running".into(),
+        ChannelStatus::Error(e) => format!("error: {e}"),
+        ChannelStatus::Stopping => "stopping".into(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +691,165 @@ mod tests {
         }
     }
 
+    /// Wraps [`MockChannelFactory`] and counts how often it builds.
+    struct CountingFactory {
+        inner: MockChannelFactory,
+        builds: std::sync::atomic::AtomicUsize,
+    }
+
+    impl CountingFactory {
+        fn new(name: &str) -> Arc<Self> {
+            Arc::new(Self {
+                inner: MockChannelFactory::new(name),
+                builds: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
+
+        fn builds(&self) -> usize {
+            self.builds.load(Ordering::SeqCst)
+        }
+    }
+
+    impl ChannelFactory for CountingFactory {
+        fn channel_name(&self) -> &str {
+            self.inner.channel_name()
+        }
+
+        fn build(&self, config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError> {
+            self.builds.fetch_add(1, Ordering::SeqCst);
+            self.inner.build(config)
+        }
+    }
+
+    /// A channel that publishes one inbound message and reports one
+    /// reconnect as soon as it starts.
+    struct ChattyChannel;
+
+    #[async_trait]
+    impl Channel for ChattyChannel {
+        fn name(&self) -> &str {
+            "chatty"
+        }
+
+        fn metadata(&self) -> ChannelMetadata {
+            ChannelMetadata {
+                name: "chatty".into(),
+                display_name: "Chatty".into(),
+                supports_threads: false,
+                supports_media: false,
+            }
+        }
+
+        fn status(&self) -> ChannelStatus {
+            ChannelStatus::Running
+        }
+
+        fn is_allowed(&self, _sender_id: &str) -> bool {
+            true
+        }
+
+        async fn start(
+            &self,
+            host: Arc<dyn ChannelHost>,
+            cancel: CancellationToken,
+        ) -> Result<(), ChannelError> {
+            host.report_reconnect("chatty").await;
+            host.publish_inbound("chatty", "u1", "c1", "hi", vec![], HashMap::new())
+                .await?;
+            cancel.cancelled().await;
+            Ok(())
+        }
+
+        async fn send(&self, _msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
+            Ok(MessageId("chatty-1".into()))
+        }
+    }
+
+    struct ChattyFactory;
+
+    impl ChannelFactory for ChattyFactory {
+        fn channel_name(&self) -> &str {
+            "chatty"
+        }
+
+        fn build(&self, _config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError> {
+            Ok(Arc::new(ChattyChannel))
+        }
+    }
+
+    /// A channel that supports editing and logs every send and edit.
+    #[derive(Default)]
+    struct EditingChannel {
+        log: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Channel for EditingChannel {
+        fn name(&self) -> &str {
+            "editing"
+        }
+
+        fn metadata(&self) -> ChannelMetadata {
+            ChannelMetadata {
+                name: "editing".into(),
+                display_name: "Editing".into(),
+                supports_threads: false,
+                supports_media: false,
+            }
+        }
+
+        fn status(&self) -> ChannelStatus {
+            ChannelStatus::Running
+        }
+
+        fn is_allowed(&self, _sender_id: &str) -> bool {
+            true
+        }
+
+        async fn start(
+            &self,
+            _host: Arc<dyn ChannelHost>,
+            cancel: CancellationToken,
+        ) -> Result<(), ChannelError> {
+            cancel.cancelled().await;
+            Ok(())
+        }
+
+        async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
+            let mut log = self.log.lock().unwrap();
+            log.push(format!("send:{}", msg.content));
+            Ok(MessageId(format!("editing-{}", log.len())))
+        }
+
+        fn supports_edit(&self) -> bool {
+            true
+        }
+
+        async fn edit_message(
+            &self,
+            id: &MessageId,
+            msg: &OutboundMessage,
+        ) -> Result<(), ChannelError> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("edit:{}:{}", id.0, msg.content));
+            Ok(())
+        }
+    }
+
+    struct EditingFactory(Arc<EditingChannel>);
+
+    impl ChannelFactory for EditingFactory {
+        fn channel_name(&self) -> &str {
+            "editing"
+        }
+
+        fn build(&self, _config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError> {
+            Ok(self.0.clone())
+        }
+    }
+
     /// A mock host that collects delivered inbound messages.
     struct MockChannelHost {
         messages: tokio::sync::Mutex<Vec<InboundMessage>>,
@@ -354,6 +891,7 @@ mod tests {
                 timestamp: chrono::Utc::now(),
                 media,
                 metadata,
+                attachments: vec![],
             };
             self.deliver_inbound(msg).await
         }
@@ -492,6 +1030,7 @@ mod tests {
             content: "hello".into(),
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
 
@@ -522,6 +1061,7 @@ mod tests {
             content: "hello".into(),
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
 
@@ -532,6 +1072,280 @@ mod tests {
         plugin_host.stop_all().await;
     }
 
+    fn outbound(channel: &str) -> OutboundMessage {
+        OutboundMessage {
+            channel: channel.into(),
+            chat_id: "c1".into(),
+            content: "hello".into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn metrics_count_inbound_and_reconnects() {
+        let host = Arc::new(MockChannelHost::new());
+        let plugin_host = PluginHost::new(host.clone());
+        plugin_host.register_factory(Arc::new(ChattyFactory)).await;
+        plugin_host
+            .init_channel("chatty", &serde_json::json!({}))
+            .await
+            .unwrap();
+
+        let before = plugin_host.metrics().await;
+        assert_eq!(before[0].inbound_received, 0);
+
+        plugin_host.start_channel("chatty").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let after = plugin_host.metrics().await;
+        assert_eq!(after[0].channel, "chatty");
+        assert_eq!(after[0].status.as_deref(), Some("running"));
+        assert_eq!(after[0].inbound_received, 1);
+        assert_eq!(after[0].reconnects, 1);
+        assert!(after[0].last_inbound.is_some());
+        assert_eq!(host.messages.lock().await.len(), 1);
+
+        plugin_host.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn metrics_count_sends_and_failures() {
+        let host = Arc::new(MockChannelHost::new());
+        let plugin_host = PluginHost::new(host);
+        plugin_host
+            .register_factory(Arc::new(MockChannelFactory::new("mock")))
+            .await;
+        plugin_host
+            .init_channel("mock", &serde_json::json!({}))
+            .await
+            .unwrap();
+
+        // Not started yet: the mock rejects sends.
+        assert!(
+            plugin_host
+                .send_to_channel(&outbound("mock"))
+                .await
+                .is_err()
+        );
+
+        plugin_host.start_channel("mock").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        plugin_host
+            .send_to_channel(&outbound("mock"))
+            .await
+            .unwrap();
+        plugin_host
+            .send_to_channel(&outbound("mock"))
+            .await
+            .unwrap();
+
+        let m = &plugin_host.metrics().await[0];
+        assert_eq!(m.outbound_sent, 2);
+        assert_eq!(m.send_failures, 1);
+        assert!(m.last_outbound.is_some());
+
+        plugin_host.stop_all().await;
+    }
+
+    fn streamed(channel: &str, content: &str) -> OutboundMessage {
+        let mut msg = outbound(channel);
+        msg.content = content.into();
+        msg.metadata
+            .insert(OutboundMessage::STREAMED_KEY.into(), true.into());
+        msg
+    }
+
+    #[tokio::test]
+    async fn progressive_reply_is_edited_in_place_and_throttled() {
+        let channel = Arc::new(EditingChannel::default());
+        let plugin_host = PluginHost::new(Arc::new(MockChannelHost::new()))
+            .with_edit_interval(Duration::from_secs(3600));
+        plugin_host
+            .register_factory(Arc::new(EditingFactory(channel.clone())))
+            .await;
+        plugin_host
+            .init_channel("editing", &serde_json::json!({}))
+            .await
+            .unwrap();
+
+        let reply = plugin_host.open_progressive("editing", "c1");
+        plugin_host.update_progressive(&reply, "Hel").await;
+        // Within the edit interval: skipped.
+        plugin_host.update_progressive(&reply, "Hello").await;
+
+        let id = plugin_host
+            .send_to_channel(&streamed("editing", "Hello!"))
+            .await
+            .unwrap();
+        assert_eq!(id, MessageId("editing-1".into()));
+
+        // The reply is finished; late updates and the next plain message
+        // leave it alone.
+        plugin_host.update_progressive(&reply, "Hello! again").await;
+        plugin_host
+            .send_to_channel(&outbound("editing"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *channel.log.lock().unwrap(),
+            vec!["send:Hel", "edit:editing-1:Hello!", "send:hello"]
+        );
+        assert_eq!(plugin_host.metrics().await[0].outbound_sent, 2);
+    }
+
+    #[tokio::test]
+    async fn progressive_reply_without_edit_support_sends_normally() {
+        let plugin_host =
+            PluginHost::new(Arc::new(MockChannelHost::new())).with_edit_interval(Duration::ZERO);
+        plugin_host
+            .register_factory(Arc::new(MockChannelFactory::new("mock")))
+            .await;
+        plugin_host
+            .init_channel("mock", &serde_json::json!({}))
+            .await
+            .unwrap();
+        plugin_host.start_channel("mock").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let reply = plugin_host.open_progressive("mock", "c1");
+        plugin_host.update_progressive(&reply, "partial").await;
+        assert_eq!(plugin_host.metrics().await[0].outbound_sent, 0);
+
+        let id = plugin_host
+            .send_to_channel(&streamed("mock", "final"))
+            .await
+            .unwrap();
+        assert_eq!(id, MessageId("mock-msg-001".into()));
+
+        plugin_host.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn reload_cycles_only_changed_channels() {
+        let host = Arc::new(MockChannelHost::new());
+        let plugin_host = PluginHost::new(host);
+
+        let factories: HashMap<&str, Arc<CountingFactory>> = ["keep", "change", "drop", "add"]
+            .into_iter()
+            .map(|n| (n, CountingFactory::new(n)))
+            .collect();
+        for f in factories.values() {
+            plugin_host.register_factory(f.clone()).await;
+        }
+        for (name, cfg) in [
+            ("keep", serde_json::json!({"token": "k"})),
+            ("change", serde_json::json!({"token": "old"})),
+            ("drop", serde_json::json!({})),
+        ] {
+            plugin_host.init_channel(name, &cfg).await.unwrap();
+        }
+        plugin_host.start_all().await;
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let keep_before = plugin_host.channels.read().await["keep"].clone();
+
+        let desired: HashMap<String, serde_json::Value> = [
+            ("keep".to_owned(), serde_json::json!({"token": "k"})),
+            ("change".to_owned(), serde_json::json!({"token": "new"})),
+            ("add".to_owned(), serde_json::json!({})),
+        ]
+        .into_iter()
+        .collect();
+        let report = plugin_host.reload(&desired).await;
+
+        assert_eq!(report.started, vec!["add"]);
+        assert_eq!(report.stopped, vec!["drop"]);
+        assert_eq!(report.restarted, vec!["change"]);
+        assert_eq!(report.unchanged, vec!["keep"]);
+        assert!(report.failed.is_empty());
+
+        assert_eq!(factories["keep"].builds(), 1);
+        assert_eq!(factories["change"].builds(), 2);
+        assert_eq!(factories["drop"].builds(), 1);
+        assert_eq!(factories["add"].builds(), 1);
+
+        let keep_after = plugin_host.channels.read().await["keep"].clone();
+        assert!(Arc::ptr_eq(&keep_before, &keep_after));
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let statuses = plugin_host.get_status().await;
+        assert!(!statuses.contains_key("drop"));
+        for name in ["keep", "change", "add"] {
+            assert_eq!(statuses.get(name), Some(&ChannelStatus::Running), "{name}");
+        }
+
+        plugin_host.stop_all().await;
+    }
+
+    /// Builds [`MockChannel`]s, rejecting configs with `"invalid": true`.
+    struct PickyFactory;
+
+    impl ChannelFactory for PickyFactory {
+        fn channel_name(&self) -> &str {
+            "picky"
+        }
+
+        fn build(&self, config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError> {
+            if config["invalid"] == serde_json::json!(true) {
+                return Err(ChannelError::Other("invalid picky config".into()));
+            }
+            Ok(Arc::new(MockChannel::new("picky")))
+        }
+    }
+
+    #[tokio::test]
+    async fn reload_with_invalid_config_keeps_running_channel() {
+        let host = Arc::new(MockChannelHost::new());
+        let plugin_host = PluginHost::new(host);
+        plugin_host.register_factory(Arc::new(PickyFactory)).await;
+
+        let good = serde_json::json!({"token": "a"});
+        plugin_host.init_channel("picky", &good).await.unwrap();
+        plugin_host.start_channel("picky").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let before = plugin_host.channels.read().await["picky"].clone();
+
+        let desired: HashMap<String, serde_json::Value> = [(
+            "picky".to_owned(),
+            serde_json::json!({"token": "b", "invalid": true}),
+        )]
+        .into_iter()
+        .collect();
+        let report = plugin_host.reload(&desired).await;
+
+        assert_eq!(report.failed.len(), 1);
+        assert!(matches!(report.failed[0].1, ChannelError::Other(_)));
+        assert!(report.restarted.is_empty());
+
+        let after = plugin_host.channels.read().await["picky"].clone();
+        assert!(Arc::ptr_eq(&before, &after));
+        assert_eq!(plugin_host.configs.read().await["picky"], good);
+        assert_eq!(
+            plugin_host.get_status().await.get("picky"),
+            Some(&ChannelStatus::Running)
+        );
+
+        plugin_host.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn reload_reports_unknown_factory() {
+        let host = Arc::new(MockChannelHost::new());
+        let plugin_host = PluginHost::new(host);
+
+        let desired: HashMap<String, serde_json::Value> =
+            [("ghost".to_owned(), serde_json::json!({}))]
+                .into_iter()
+                .collect();
+        let report = plugin_host.reload(&desired).await;
+        assert_eq!(report.failed.len(), 1);
+        assert!(matches!(report.failed[0].1, ChannelError::NotFound(_)));
+        assert!(plugin_host.active_channels().await.is_empty());
+    }
+
     #[tokio::test]
     async fn get_status_empty_host() {
         let host = Arc::new(MockChannelHost::new());
@@ -1,159 +1,440 @@
-//! IRC channel adapter implementation.
+//! [`IrcChannel`] -- `Channel` trait implementation for IRC.
 //!
-//! Implements [`ChannelAdapter`] for IRC messaging.
-//! This is a properly-typed skeleton -- the actual IRC protocol client
-//! integration will be added when the `irc` crate is brought in as a
-//! dependency.
-
+//! Maintains a single (optionally TLS) connection to the configured
+//! server, drives the [`Session`] state machine for registration and
+//! authentication, and delivers direct messages and nick-prefixed
+//! channel mentions to the pipeline through
+//! [`ChannelHost::deliver_inbound`](crate::traits::ChannelHost::deliver_inbound).
+//! Disconnects are retried with exponential backoff.
+
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, RwLock, mpsc};
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, info};
+use tracing::{debug, error, info, warn};
+
+use clawft_types::error::ChannelError;
+use clawft_types::event::{InboundMessage, OutboundMessage};
+
+use crate::traits::{Channel, ChannelHost, ChannelMetadata, ChannelStatus, MessageId};
 
-use clawft_plugin::error::PluginError;
-use clawft_plugin::message::MessagePayload;
-use clawft_plugin::traits::{ChannelAdapter, ChannelAdapterHost};
+use super::protocol::{IrcMessage, split_privmsg};
+use super::session::{IrcInbound, Session, SessionEvent, resolve_chat_id};
+use super::types::IrcAdapterConfig;
 
-use super::types::{validate_config, IrcAdapterConfig};
+/// Delay between consecutive outbound lines to stay under server
+/// flood limits.
+const SEND_INTERVAL_MS: u64 = 300;
 
-/// IRC channel adapter.
+/// Object-safe alias for a plaintext or TLS stream.
+trait IrcStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> IrcStream for T {}
+
+/// Compute the reconnect delay for the given consecutive failure count.
 ///
-/// Connects to an IRC server and bridges messages between IRC channels
-/// and the clawft agent pipeline.
+/// Doubles `base_secs` per attempt and caps at `max_secs`.
+pub(crate) fn backoff_delay(base_secs: u64, max_secs: u64, attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt.min(31)).unwrap_or(u64::MAX);
+    Duration::from_secs(
+        base_secs
+            .saturating_mul(factor)
+            .min(max_secs.max(base_secs)),
+    )
+}
+
+/// IRC channel implementation.
+///
+/// Created via [`IrcChannelFactory`](super::factory::IrcChannelFactory)
+/// from an [`IrcAdapterConfig`].
 ///
-/// **Status**: Skeleton implementation. The IRC protocol client will be
-/// integrated when the `irc` crate is added as a dependency.
-pub struct IrcChannelAdapter {
+/// Session keys are per-user for direct messages (`irc:<nick>`) and
+/// per-channel-and-user for mentions (`irc:<#channel>/<nick>`); replies
+/// to channel conversations are prefixed with the user's nick.
+pub struct IrcChannel {
     config: IrcAdapterConfig,
+    /// Current lifecycle status.
+    status: Arc<RwLock<ChannelStatus>>,
+    /// Queue of raw lines for the live connection, `None` when offline.
+    outgoing: Mutex<Option<mpsc::UnboundedSender<String>>>,
 }
 
-impl IrcChannelAdapter {
-    /// Create a new IRC channel adapter with the given configuration.
+impl IrcChannel {
+    /// Create a new IRC channel with the given configuration.
     pub fn new(config: IrcAdapterConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            status: Arc::new(RwLock::new(ChannelStatus::Stopped)),
+            outgoing: Mutex::new(None),
+        }
+    }
+
+    /// Access the channel configuration.
+    pub fn config(&self) -> &IrcAdapterConfig {
+        &self.config
+    }
+
+    async fn set_status(&self, status: ChannelStatus) {
+        *self.status.write().await = status;
+    }
+
+    /// Resolve the password from `password_env` when authentication is
+    /// configured.
+    fn resolve_password(&self) -> Result<Option<String>, ChannelError> {
+        if self.config.auth_method == "none" {
+            return Ok(None);
+        }
+        let Some(ref env_var) = self.config.password_env else {
+            return Err(ChannelError::AuthFailed(
+                "irc password_env is not configured".into(),
+            ));
+        };
+        match std::env::var(env_var) {
+            Ok(val) if !val.is_empty() => Ok(Some(val)),
+            _ => Err(ChannelError::AuthFailed(format!(
+                "irc password_env '{env_var}' is not set or empty"
+            ))),
+        }
     }
 
-    /// Check if a sender nickname is in the allow list.
+    /// Open the TCP connection, wrapping it in TLS when configured.
+    async fn connect(&self) -> Result<Box<dyn IrcStream>, ChannelError> {
+        let addr = (self.config.server.as_str(), self.config.port);
+        let tcp = TcpStream::connect(addr).await.map_err(|e| {
+            ChannelError::ConnectionFailed(format!(
+                "{}:{}: {e}",
+                self.config.server, self.config.port
+            ))
+        })?;
+
+        if !self.config.use_tls {
+            return Ok(Box::new(tcp));
+        }
+
+        use tokio_rustls::rustls;
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| ChannelError::ConnectionFailed(format!("tls config: {e}")))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+        let server_name = rustls::pki_types::ServerName::try_from(self.config.server.clone())
+            .map_err(|e| ChannelError::ConnectionFailed(format!("invalid tls server name: {e}")))?;
+        let stream = tokio_rustls::TlsConnector::from(Arc::new(tls_config))
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| ChannelError::ConnectionFailed(format!("tls handshake: {e}")))?;
+        Ok(Box::new(stream))
+    }
+
+    /// Run one connection until it drops, the server rejects our
+    /// credentials, or `cancel` fires.
     ///
-    /// If `allowed_senders` is empty, all senders are allowed.
-    /// Otherwise, the sender must match one of the entries exactly.
-    pub fn is_sender_allowed(&self, sender: &str) -> bool {
-        if self.config.allowed_senders.is_empty() {
-            return true;
+    /// Sets `registered` once the server welcomes us so the caller can
+    /// reset its backoff.
+    async fn run_connection(
+        &self,
+        stream: Box<dyn IrcStream>,
+        password: Option<String>,
+        host: &Arc<dyn ChannelHost>,
+        cancel: &CancellationToken,
+        registered: &mut bool,
+    ) -> Result<(), ChannelError> {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let mut session = Session::new(&self.config, password);
+
+        for line in session.handshake() {
+            write_line(&mut writer, &line).await?;
+        }
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    let _ = write_line(&mut writer, "QUIT :shutting down").await;
+                    return Ok(());
+                }
+                Some(line) = rx.recv() => {
+                    write_line(&mut writer, &line).await?;
+                    tokio::time::sleep(Duration::from_millis(SEND_INTERVAL_MS)).await;
+                }
+                next = lines.next_line() => {
+                    let line = match next {
+                        Ok(Some(line)) => line,
+                        Ok(None) => {
+                            return Err(ChannelError::ConnectionFailed(
+                                "server closed the connection".into(),
+                            ));
+                        }
+                        Err(e) => return Err(ChannelError::ReceiveFailed(e.to_string())),
+                    };
+                    let Some(msg) = IrcMessage::parse(&line) else {
+                        continue;
+                    };
+                    if msg.command == "ERROR" {
+                        warn!(reason = ?msg.param(0), "IRC server sent ERROR");
+                    }
+
+                    for event in session.handle(&msg) {
+                        match event {
+                            SessionEvent::Send(line) => write_line(&mut writer, &line).await?,
+                            SessionEvent::Registered => {
+                                *registered = true;
+                                *self.outgoing.lock().await = Some(tx.clone());
+                                self.set_status(ChannelStatus::Running).await;
+                                info!(
+                                    server = %self.config.server,
+                                    nick = %session.nick(),
+                                    "IRC registration complete"
+                                );
+                            }
+                            SessionEvent::Inbound(inbound) => {
+                                if let Err(e) = self.deliver(inbound, host).await {
+                                    error!(error = %e, "failed to deliver IRC message");
+                                }
+                            }
+                            SessionEvent::AuthFailed(reason) => {
+                                return Err(ChannelError::AuthFailed(reason));
+                            }
+                        }
+                    }
+                }
+            }
         }
-        self.config.allowed_senders.iter().any(|s| s == sender)
     }
 
-    /// Validate the adapter configuration, returning a `PluginError`
-    /// on failure.
-    fn validate(&self) -> Result<(), PluginError> {
-        validate_config(&self.config).map_err(PluginError::LoadFailed)
+    /// Forward an addressed message to the pipeline after the allow-list
+    /// check.
+    async fn deliver(
+        &self,
+        inbound: IrcInbound,
+        host: &Arc<dyn ChannelHost>,
+    ) -> Result<(), ChannelError> {
+        if !self.is_allowed(&inbound.sender) {
+            warn!(sender = %inbound.sender, "IRC message from disallowed sender, ignoring");
+            return Ok(());
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("nick".into(), inbound.sender.clone().into());
+        metadata.insert("server".into(), self.config.server.clone().into());
+        metadata.insert("is_direct".into(), inbound.channel.is_none().into());
+        if let Some(ref channel) = inbound.channel {
+            metadata.insert("irc_channel".into(), channel.clone().into());
+        }
+
+        host.deliver_inbound(InboundMessage {
+            channel: "irc".into(),
+            sender_id: inbound.sender,
+            chat_id: inbound.chat_id,
+            content: inbound.content,
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata,
+        })
+        .await
+    }
+}
+
+/// Write a single line followed by CRLF.
+async fn write_line<W: AsyncWrite + Unpin>(writer: &mut W, line: &str) -> Result<(), ChannelError> {
+    debug!(line = %redact(line), "irc >>");
+    writer
+        .write_all(format!("{line}\r\n").as_bytes())
+        .await
+        .map_err(|e| ChannelError::SendFailed(e.to_string()))
+}
+
+/// Hide credentials from debug logging.
+fn redact(line: &str) -> &str {
+    if line.starts_with("AUTHENTICATE ") && line != "AUTHENTICATE PLAIN" {
+        "AUTHENTICATE <redacted>"
+    } else if line.starts_with("PRIVMSG NickServ :IDENTIFY") {
+        "PRIVMSG NickServ :IDENTIFY <redacted>"
+    } else {
+        line
     }
 }
 
 #[async_trait]
-impl ChannelAdapter for IrcChannelAdapter {
+impl Channel for IrcChannel {
     fn name(&self) -> &str {
         "irc"
     }
 
-    fn display_name(&self) -> &str {
-        "IRC"
+    fn metadata(&self) -> ChannelMetadata {
+        ChannelMetadata {
+            name: "irc".into(),
+            display_name: "IRC".into(),
+            supports_threads: false,
+            supports_media: false,
+        }
     }
 
-    fn supports_threads(&self) -> bool {
-        false
+    fn status(&self) -> ChannelStatus {
+        self.status
+            .try_read()
+            .map(|s| s.clone())
+            .unwrap_or(ChannelStatus::Stopped)
     }
 
-    fn supports_media(&self) -> bool {
-        false
+    fn is_allowed(&self, sender_id: &str) -> bool {
+        self.config.allowed_senders.is_empty()
+            || self
+                .config
+                .allowed_senders
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(sender_id))
     }
 
     async fn start(
         &self,
-        _host: Arc<dyn ChannelAdapterHost>,
+        host: Arc<dyn ChannelHost>,
         cancel: CancellationToken,
-    ) -> Result<(), PluginError> {
-        info!("IRC channel adapter starting");
-
-        self.validate()?;
-
-        // TODO: Connect to the IRC server using an IRC client library.
-        //
-        // In production, this would:
-        // 1. Establish a TCP (or TLS) connection to `self.config.server:self.config.port`
-        // 2. Authenticate using the configured `auth_method`
-        // 3. Join all channels listed in `self.config.channels`
-        // 4. Listen for PRIVMSG events and forward them to host.deliver_inbound()
-        // 5. Handle reconnection with `self.config.reconnect_delay_secs`
-        //
-        // The actual IRC protocol client will be integrated when the `irc`
-        // crate is added as a dependency.
-        debug!(
-            server = %self.config.server,
-            port = self.config.port,
-            use_tls = self.config.use_tls,
-            nickname = %self.config.nickname,
-            channels = ?self.config.channels,
-            auth_method = %self.config.auth_method,
-            "IRC connection would be established here (stub)"
-        );
+    ) -> Result<(), ChannelError> {
+        super::types::validate_config(&self.config).map_err(ChannelError::Other)?;
+        let password = self.resolve_password()?;
+
+        let mut attempt: u32 = 0;
+        loop {
+            self.set_status(ChannelStatus::Starting).await;
+            let mut registered = false;
+
+            let result = tokio::select! {
+                _ = cancel.cancelled() => Ok(()),
+                stream = self.connect() => match stream {
+                    Ok(stream) => {
+                        info!(
+                            server = %self.config.server,
+                            port = self.config.port,
+                            tls = self.config.use_tls,
+                            "connected to IRC server"
+                        );
+                        self.run_connection(stream, password.clone(), &host, &cancel, &mut registered)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                },
+            };
+            *self.outgoing.lock().await = None;
+
+            if cancel.is_cancelled() {
+                break;
+            }
 
-        cancel.cancelled().await;
-        info!("IRC channel adapter shutting down");
-        Ok(())
-    }
+            match result {
+                Err(ChannelError::AuthFailed(reason)) => {
+                    error!(reason = %reason, "IRC authentication failed, not reconnecting");
+                    self.set_status(ChannelStatus::Error(reason.clone())).await;
+                    return Err(ChannelError::AuthFailed(reason));
+                }
+                Err(e) => {
+                    warn!(error = %e, "IRC connection lost");
+                    self.set_status(ChannelStatus::Error(e.to_string())).await;
+                }
+                Ok(()) => {}
+            }
 
-    async fn send(
-        &self,
-        target: &str,
-        payload: &MessagePayload,
-    ) -> Result<String, PluginError> {
-        // IRC only supports text payloads.
-        let content = match payload {
-            MessagePayload::Text { content } => content,
-            MessagePayload::Binary { .. } => {
-                return Err(PluginError::ExecutionFailed(
-                    "irc: binary payloads are not supported (IRC is text-only)".into(),
-                ));
+            if registered {
+                attempt = 0;
             }
-            MessagePayload::Structured { .. } => {
-                return Err(PluginError::ExecutionFailed(
-                    "irc: structured payloads are not supported (IRC is text-only)".into(),
-                ));
+            let delay = backoff_delay(
+                self.config.reconnect_delay_secs,
+                self.config.max_reconnect_delay_secs,
+                attempt,
+            );
+            attempt = attempt.saturating_add(1);
+            info!(delay_secs = delay.as_secs(), "reconnecting to IRC server");
+
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(delay) => {}
             }
+            host.report_reconnect(self.name()).await;
+        }
+
+        self.set_status(ChannelStatus::Stopped).await;
+        info!("IRC channel stopped");
+        Ok(())
+    }
+
+    async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
+        let (target, reply_nick) = resolve_chat_id(&msg.chat_id);
+        if target.is_empty() || target.contains([' ', ',', '\r', '\n', '\0']) {
+            return Err(ChannelError::SendFailed(format!(
+                "invalid irc target '{}'",
+                msg.chat_id
+            )));
+        }
+
+        let content = msg.content.replace(['\r', '\0'], "");
+        let content = match reply_nick {
+            Some(nick) => format!("{nick}: {content}"),
+            None => content,
         };
+        let lines = split_privmsg(target, &content);
 
-        // TODO: Send the message using the connected IRC client.
-        //
-        // In production, this would issue a PRIVMSG command:
-        //   PRIVMSG <target> :<content>
-        //
-        // For now, we return an error indicating the client is not
-        // connected since no real IRC client is instantiated.
-        debug!(
-            to = %target,
-            content_len = content.len(),
-            "would send IRC PRIVMSG (stub -- client not connected)"
-        );
+        let guard = self.outgoing.lock().await;
+        let Some(ref tx) = *guard else {
+            return Err(ChannelError::NotConnected);
+        };
+        for line in lines {
+            tx.send(line).map_err(|_| ChannelError::NotConnected)?;
+        }
 
-        // Stub: return a synthetic message ID.
-        // In a real implementation, this would only succeed when the
-        // client is connected and the PRIVMSG has been sent.
-        let msg_id = format!(
+        Ok(MessageId(format!(
             "irc-{}-{}",
             target,
             chrono::Utc::now().timestamp_millis()
-        );
-        Ok(msg_id)
+        )))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use crate::traits::Command;
+    use tokio::net::TcpListener;
+
+    struct MockHost {
+        messages: Mutex<Vec<InboundMessage>>,
+    }
+
+    #[async_trait]
+    impl ChannelHost for MockHost {
+        async fn deliver_inbound(&self, msg: InboundMessage) -> Result<(), ChannelError> {
+            self.messages.lock().await.push(msg);
+            Ok(())
+        }
+
+        async fn register_command(&self, _cmd: Command) -> Result<(), ChannelError> {
+            Ok(())
+        }
+
+        async fn publish_inbound(
+            &self,
+            _channel: &str,
+            _sender_id: &str,
+            _chat_id: &str,
+            _content: &str,
+            _media: Vec<String>,
+            _metadata: HashMap<String, serde_json::Value>,
+        ) -> Result<(), ChannelError> {
+            Ok(())
+        }
+    }
 
     fn make_config() -> IrcAdapterConfig {
         IrcAdapterConfig {
@@ -164,184 +445,191 @@ mod tests {
         }
     }
 
-    // E5-T6: Factory/constructor builds channel from valid config.
-    #[test]
-    fn construct_from_valid_config() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert_eq!(adapter.name(), "irc");
-        assert_eq!(adapter.display_name(), "IRC");
-    }
-
-    #[test]
-    fn name_is_irc() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert_eq!(adapter.name(), "irc");
-    }
-
-    #[test]
-    fn display_name_is_irc() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert_eq!(adapter.display_name(), "IRC");
-    }
-
-    #[test]
-    fn no_threads_or_media() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert!(!adapter.supports_threads());
-        assert!(!adapter.supports_media());
+    fn outbound(chat_id: &str, content: &str) -> OutboundMessage {
+        OutboundMessage {
+            channel: "irc".into(),
+            chat_id: chat_id.into(),
+            content: content.into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
     }
 
-    // E5-T8: is_sender_allowed respects allowed_senders (empty = allow all).
     #[test]
-    fn sender_allowed_empty_list_allows_all() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert!(adapter.is_sender_allowed("anyone"));
-        assert!(adapter.is_sender_allowed("stranger"));
+    fn metadata_and_name() {
+        let ch = IrcChannel::new(make_config());
+        assert_eq!(ch.name(), "irc");
+        let meta = ch.metadata();
+        assert_eq!(meta.display_name, "IRC");
+        assert!(!meta.supports_threads);
+        assert!(!meta.supports_media);
+        assert_eq!(ch.status(), ChannelStatus::Stopped);
     }
 
     #[test]
-    fn sender_allowed_with_filter() {
+    fn allow_list_is_case_insensitive() {
         let mut config = make_config();
-        config.allowed_senders = vec!["admin".into(), "moderator".into()];
-        let adapter = IrcChannelAdapter::new(config);
+        assert!(IrcChannel::new(config.clone()).is_allowed("anyone"));
 
-        assert!(adapter.is_sender_allowed("admin"));
-        assert!(adapter.is_sender_allowed("moderator"));
-        assert!(!adapter.is_sender_allowed("random-user"));
-        assert!(!adapter.is_sender_allowed(""));
+        config.allowed_senders = vec!["Admin".into()];
+        let ch = IrcChannel::new(config);
+        assert!(ch.is_allowed("admin"));
+        assert!(!ch.is_allowed("random-user"));
     }
 
     #[test]
-    fn validate_config_success() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        assert!(adapter.validate().is_ok());
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff_delay(5, 300, 0), Duration::from_secs(5));
+        assert_eq!(backoff_delay(5, 300, 1), Duration::from_secs(10));
+        assert_eq!(backoff_delay(5, 300, 3), Duration::from_secs(40));
+        assert_eq!(backoff_delay(5, 300, 10), Duration::from_secs(300));
+        assert_eq!(backoff_delay(5, 300, 200), Duration::from_secs(300));
     }
 
-    // E5-T7: Validate rejects nickserv without password_env.
     #[test]
-    fn validate_rejects_nickserv_without_password() {
-        let config = IrcAdapterConfig {
-            server: "irc.example.com".into(),
-            nickname: "bot".into(),
-            auth_method: "nickserv".into(),
-            password_env: None,
-            ..Default::default()
-        };
-        let adapter = IrcChannelAdapter::new(config);
-        let err = adapter.validate().unwrap_err();
-        assert!(err.to_string().contains("password_env"));
-    }
-
-    #[test]
-    fn validate_rejects_empty_server() {
-        let config = IrcAdapterConfig {
-            nickname: "bot".into(),
-            ..Default::default()
-        };
-        let adapter = IrcChannelAdapter::new(config);
-        let err = adapter.validate().unwrap_err();
-        assert!(err.to_string().contains("server"));
+    fn redact_hides_credentials() {
+        assert_eq!(redact("AUTHENTICATE abc="), "AUTHENTICATE <redacted>");
+        assert_eq!(redact("AUTHENTICATE PLAIN"), "AUTHENTICATE PLAIN");
+        assert!(!redact("PRIVMSG NickServ :IDENTIFY bot pw").contains("pw"));
+        assert_eq!(redact("JOIN #dev"), "JOIN #dev");
     }
 
-    #[test]
-    fn validate_rejects_empty_nickname() {
-        let config = IrcAdapterConfig {
-            server: "irc.example.com".into(),
-            ..Default::default()
-        };
-        let adapter = IrcChannelAdapter::new(config);
-        let err = adapter.validate().unwrap_err();
-        assert!(err.to_string().contains("nickname"));
+    #[tokio::test]
+    async fn send_when_disconnected_fails() {
+        let ch = IrcChannel::new(make_config());
+        let err = ch.send(&outbound("#general", "hi")).await.unwrap_err();
+        assert!(matches!(err, ChannelError::NotConnected));
     }
 
-    #[test]
-    fn validate_rejects_invalid_auth_method() {
-        let config = IrcAdapterConfig {
-            server: "irc.example.com".into(),
-            nickname: "bot".into(),
-            auth_method: "invalid".into(),
-            ..Default::default()
-        };
-        let adapter = IrcChannelAdapter::new(config);
-        let err = adapter.validate().unwrap_err();
-        assert!(err.to_string().contains("auth_method"));
+    #[tokio::test]
+    async fn send_rejects_injection_in_target() {
+        let ch = IrcChannel::new(make_config());
+        let err = ch
+            .send(&outbound("#general\r\nQUIT", "hi"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChannelError::SendFailed(_)));
     }
 
-    // E5-T10: send returns error for non-text (Binary) payload.
     #[tokio::test]
-    async fn send_binary_payload_fails() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        let payload = MessagePayload::binary("audio/wav", vec![0u8; 16]);
-        let result = adapter.send("#general", &payload).await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("binary"));
+    async fn send_addresses_channel_replies_and_splits() {
+        let ch = IrcChannel::new(make_config());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        *ch.outgoing.lock().await = Some(tx);
+
+        let id = ch
+            .send(&outbound("#general/alice", "first\nsecond"))
+            .await
+            .unwrap();
+        assert!(id.0.starts_with("irc-#general-"));
+        assert_eq!(rx.recv().await.unwrap(), "PRIVMSG #general :alice: first");
+        assert_eq!(rx.recv().await.unwrap(), "PRIVMSG #general :second");
+
+        ch.send(&outbound("alice", "dm reply")).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "PRIVMSG alice :dm reply");
     }
 
     #[tokio::test]
-    async fn send_structured_payload_fails() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        let payload = MessagePayload::structured(serde_json::json!({"key": "value"}));
-        let result = adapter.send("#general", &payload).await;
+    async fn start_rejects_invalid_config() {
+        let ch = IrcChannel::new(IrcAdapterConfig::default());
+        let host: Arc<dyn ChannelHost> = Arc::new(MockHost {
+            messages: Mutex::new(Vec::new()),
+        });
+        let result = ch.start(host, CancellationToken::new()).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("structured"));
     }
 
-    // E5-T9: send returns a synthetic message ID for text payloads
-    // (stub always succeeds since we generate synthetic IDs).
     #[tokio::test]
-    async fn send_text_message_returns_id() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        let payload = MessagePayload::text("Hello from bot");
-        let result = adapter.send("#general", &payload).await;
-        assert!(result.is_ok());
-        let msg_id = result.unwrap();
-        assert!(msg_id.starts_with("irc-#general-"));
+    async fn start_requires_password_for_sasl() {
+        let mut config = make_config();
+        config.auth_method = "sasl".into();
+        config.password_env = Some("CLAWFT_TEST_IRC_PASSWORD_UNSET".into());
+        let ch = IrcChannel::new(config);
+        let host: Arc<dyn ChannelHost> = Arc::new(MockHost {
+            messages: Mutex::new(Vec::new()),
+        });
+        let err = ch.start(host, CancellationToken::new()).await.unwrap_err();
+        assert!(matches!(err, ChannelError::AuthFailed(_)));
     }
 
+    /// End-to-end exchange against an in-process fake server over plain TCP.
     #[tokio::test]
-    async fn start_validates_config() {
+    async fn fake_server_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
         let config = IrcAdapterConfig {
-            // Missing server and nickname -- should fail validation.
+            server: "127.0.0.1".into(),
+            port,
+            use_tls: false,
+            nickname: "clawft".into(),
+            channels: vec!["#dev".into()],
             ..Default::default()
         };
-        let adapter = IrcChannelAdapter::new(config);
-
-        let host = Arc::new(MockHost);
-        let cancel = CancellationToken::new();
-        let result = adapter.start(host, cancel).await;
-        assert!(result.is_err());
-    }
-
-    #[tokio::test]
-    async fn start_shuts_down_on_cancel() {
-        let adapter = IrcChannelAdapter::new(make_config());
-        let cancel = CancellationToken::new();
-        let cancel_clone = cancel.clone();
-
-        let host = Arc::new(MockHost);
-        let handle = tokio::spawn(async move {
-            adapter.start(host, cancel_clone).await
+        let channel = Arc::new(IrcChannel::new(config));
+        let host = Arc::new(MockHost {
+            messages: Mutex::new(Vec::new()),
         });
+        let cancel = CancellationToken::new();
 
-        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-        cancel.cancel();
-        let result = handle.await.unwrap();
-        assert!(result.is_ok());
-    }
+        let run = {
+            let channel = channel.clone();
+            let host: Arc<dyn ChannelHost> = host.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move { channel.start(host, cancel).await })
+        };
 
-    struct MockHost;
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "NICK clawft");
+        assert!(
+            lines
+                .next_line()
+                .await
+                .unwrap()
+                .unwrap()
+                .starts_with("USER clawft")
+        );
 
-    #[async_trait]
-    impl ChannelAdapterHost for MockHost {
-        async fn deliver_inbound(
-            &self,
-            _channel: &str,
-            _sender_id: &str,
-            _chat_id: &str,
-            _payload: MessagePayload,
-            _metadata: HashMap<String, serde_json::Value>,
-        ) -> Result<(), PluginError> {
-            Ok(())
+        writer
+            .write_all(b":srv 001 clawft :Welcome\r\n")
+            .await
+            .unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "JOIN #dev");
+
+        writer
+            .write_all(b"PING :tok\r\n:bob!b@h PRIVMSG #dev :clawft, ping?\r\n")
+            .await
+            .unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "PONG tok");
+
+        // Wait for delivery.
+        for _ in 0..50 {
+            if !host.messages.lock().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        let inbound = host.messages.lock().await[0].clone();
+        assert_eq!(inbound.session_key(), "irc:#dev/bob");
+        assert_eq!(inbound.content, "ping?");
+        assert_eq!(channel.status(), ChannelStatus::Running);
+
+        channel
+            .send(&outbound(&inbound.chat_id, "pong!"))
+            .await
+            .unwrap();
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            "PRIVMSG #dev :bob: pong!"
+        );
+
+        cancel.cancel();
+        assert!(run.await.unwrap().is_ok());
+        assert_eq!(channel.status(), ChannelStatus::Stopped);
     }
 }
@@ -0,0 +1,74 @@
+//! [`IrcChannelFactory`] -- creates IRC channels from JSON config.
+
+use std::sync::Arc;
+
+use clawft_types::error::ChannelError;
+
+use crate::traits::{Channel, ChannelFactory};
+
+use super::channel::IrcChannel;
+use super::types::{IrcAdapterConfig, validate_config};
+
+/// Factory for creating [`IrcChannel`] instances from JSON configuration.
+///
+/// Expected config shape matches [`IrcAdapterConfig`]:
+///
+/// ```json
+/// {
+///   "server": "irc.libera.chat",
+///   "port": 6697,
+///   "use_tls": true,
+///   "nickname": "clawft-bot",
+///   "channels": ["#general"],
+///   "auth_method": "sasl",
+///   "password_env": "IRC_PASSWORD"
+/// }
+/// ```
+pub struct IrcChannelFactory;
+
+impl ChannelFactory for IrcChannelFactory {
+    fn channel_name(&self) -> &str {
+        "irc"
+    }
+
+    fn build(&self, config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError> {
+        let irc_config: IrcAdapterConfig = serde_json::from_value(config.clone())
+            .map_err(|e| ChannelError::Other(format!("invalid irc config: {e}")))?;
+        validate_config(&irc_config).map_err(ChannelError::Other)?;
+        Ok(Arc::new(IrcChannel::new(irc_config)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factory_channel_name() {
+        assert_eq!(IrcChannelFactory.channel_name(), "irc");
+    }
+
+    #[test]
+    fn factory_build_success() {
+        let config = serde_json::json!({
+            "server": "irc.libera.chat",
+            "nickname": "clawft-bot",
+            "channels": ["#general"],
+            "allowedSenders": ["admin"]
+        });
+        let channel = IrcChannelFactory.build(&config).unwrap();
+        assert_eq!(channel.name(), "irc");
+        assert!(channel.is_allowed("admin"));
+        assert!(!channel.is_allowed("stranger"));
+    }
+
+    #[test]
+    fn factory_build_rejects_invalid_config() {
+        let config = serde_json::json!({ "server": "irc.libera.chat" });
+        match IrcChannelFactory.build(&config) {
+            Err(ChannelError::Other(msg)) => assert!(msg.contains("nickname")),
+            Err(other) => panic!("expected ChannelError::Other, got: {other:?}"),
+            Ok(_) => panic!("expected error, got Ok"),
+        }
+    }
+}
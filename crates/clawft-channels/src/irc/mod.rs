@@ -1,9 +1,25 @@
-//! IRC channel adapter.
+//! IRC channel plugin.
 //!
-//! Feature-gated behind the `irc` feature flag.
-//! Connects to an IRC server for text-based message send/receive.
+//! Feature-gated behind the `irc` feature flag. Provides a
+//! [`Channel`](crate::traits::Channel) implementation that connects to an
+//! IRC server over TCP or TLS, authenticates with SASL `PLAIN` or
+//! NickServ, joins the configured channels, and answers direct messages
+//! and nick-prefixed mentions. The plugin is registered with the host
+//! through [`IrcChannelFactory`].
+//!
+//! # Modules
+//!
+//! - [`types`] -- Configuration and validation
+//! - [`protocol`] -- Line framing, parsing, and reply splitting
+//! - [`session`] -- Registration / authentication state machine
+//! - [`channel`] -- `Channel` trait implementation
+//! - [`factory`] -- `ChannelFactory` implementation
 
 pub mod channel;
+pub mod factory;
+pub mod protocol;
+pub mod session;
 pub mod types;
 
-pub use channel::IrcChannelAdapter;
+pub use channel::IrcChannel;
+pub use factory::IrcChannelFactory;
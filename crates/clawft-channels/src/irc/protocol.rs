@@ -0,0 +1,393 @@
+//! IRC wire protocol helpers (RFC 1459 / RFC 2812 framing).
+//!
+//! Pure functions with no I/O: line parsing and serialization,
+//! reply splitting under the 512-byte line limit, nick-prefixed
+//! mention detection, and the SASL `PLAIN` payload encoding.
+
+use base64::Engine as _;
+
+/// Maximum length of an IRC line in bytes, including the trailing CRLF.
+pub const MAX_LINE_BYTES: usize = 512;
+
+/// Bytes reserved for the `:nick!user@host ` prefix the server prepends
+/// when relaying our `PRIVMSG` to other clients.
+///
+/// The relayed line must still fit in [`MAX_LINE_BYTES`], so replies are
+/// split with this much headroom.
+pub const RELAY_PREFIX_RESERVE: usize = 100;
+
+/// A single parsed IRC protocol message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrcMessage {
+    /// Optional source prefix (`nick!user@host` or a server name).
+    pub prefix: Option<String>,
+    /// Command verb or three-digit numeric, upper-cased.
+    pub command: String,
+    /// Parameters; the trailing parameter (after `:`) is the last entry.
+    pub params: Vec<String>,
+}
+
+impl IrcMessage {
+    /// Build a message without a prefix.
+    pub fn new(command: &str, params: &[&str]) -> Self {
+        Self {
+            prefix: None,
+            command: command.to_owned(),
+            params: params.iter().map(|p| (*p).to_owned()).collect(),
+        }
+    }
+
+    /// Parse a single line (with or without the trailing CRLF).
+    ///
+    /// IRCv3 message tags (`@key=value ...`) are skipped. Returns `None`
+    /// for empty lines or lines without a command.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+
+        if let Some(tagged) = rest.strip_prefix('@') {
+            rest = tagged.split_once(' ').map(|(_, r)| r)?;
+        }
+        rest = rest.trim_start_matches(' ');
+
+        let prefix = if let Some(prefixed) = rest.strip_prefix(':') {
+            let (prefix, r) = prefixed.split_once(' ')?;
+            rest = r.trim_start_matches(' ');
+            Some(prefix.to_owned())
+        } else {
+            None
+        };
+
+        let (command, mut rest) = match rest.split_once(' ') {
+            Some((c, r)) => (c, r),
+            None => (rest, ""),
+        };
+        if command.is_empty() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        loop {
+            rest = rest.trim_start_matches(' ');
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(trailing) = rest.strip_prefix(':') {
+                params.push(trailing.to_owned());
+                break;
+            }
+            match rest.split_once(' ') {
+                Some((p, r)) => {
+                    params.push(p.to_owned());
+                    rest = r;
+                }
+                None => {
+                    params.push(rest.to_owned());
+                    break;
+                }
+            }
+        }
+
+        Some(Self {
+            prefix,
+            command: command.to_ascii_uppercase(),
+            params,
+        })
+    }
+
+    /// Serialize to a wire line, without the trailing CRLF.
+    ///
+    /// The last parameter is always sent in trailing form when it is
+    /// empty, contains a space, or starts with `:`.
+    pub fn to_line(&self) -> String {
+        let mut line = String::new();
+        if let Some(ref prefix) = self.prefix {
+            line.push(':');
+            line.push_str(prefix);
+            line.push(' ');
+        }
+        line.push_str(&self.command);
+        let last = self.params.len().saturating_sub(1);
+        for (i, param) in self.params.iter().enumerate() {
+            line.push(' ');
+            if i == last && (param.is_empty() || param.contains(' ') || param.starts_with(':')) {
+                line.push(':');
+            }
+            line.push_str(param);
+        }
+        line
+    }
+
+    /// Nickname portion of the prefix (`nick` in `nick!user@host`).
+    pub fn source_nick(&self) -> Option<&str> {
+        self.prefix
+            .as_deref()
+            .map(|p| p.split(['!', '@']).next().unwrap_or(p))
+    }
+
+    /// Parameter at `index`, if present.
+    pub fn param(&self, index: usize) -> Option<&str> {
+        self.params.get(index).map(String::as_str)
+    }
+}
+
+/// Whether `target` names a channel rather than a user.
+pub fn is_channel_target(target: &str) -> bool {
+    target.starts_with(['#', '&', '+', '!'])
+}
+
+/// Strip a leading nick mention (`nick: text`, `nick, text`, `@nick text`)
+/// from a channel message.
+///
+/// Nick comparison is case-insensitive. Returns the remaining text, or
+/// `None` when the message is not addressed to `nick`.
+pub fn strip_mention<'a>(text: &'a str, nick: &str) -> Option<&'a str> {
+    let body = text.trim_start();
+    let body = body.strip_prefix('@').unwrap_or(body);
+    let head = body.get(..nick.len())?;
+    if !head.eq_ignore_ascii_case(nick) {
+        return None;
+    }
+    let rest = &body[nick.len()..];
+    let rest = match rest.chars().next() {
+        Some(':' | ',') => &rest[1..],
+        Some(c) if c.is_whitespace() => rest,
+        None => rest,
+        // Longer nick that merely starts with ours (e.g. `bot2`).
+        Some(_) => return None,
+    };
+    Some(rest.trim())
+}
+
+/// Split `text` into `PRIVMSG` lines for `target` that fit the IRC limit.
+///
+/// Each returned line is a complete command without CRLF. Newlines in
+/// `text` always start a new message; otherwise lines break at the last
+/// whitespace that fits. A single word longer than the available space
+/// is hard-split on a UTF-8 character boundary.
+pub fn split_privmsg(target: &str, text: &str) -> Vec<String> {
+    let overhead = "PRIVMSG ".len() + target.len() + " :".len() + "\r\n".len();
+    let budget = MAX_LINE_BYTES
+        .saturating_sub(overhead + RELAY_PREFIX_RESERVE)
+        .max(1);
+
+    split_text(text, budget)
+        .into_iter()
+        .map(|chunk| format!("PRIVMSG {target} :{chunk}"))
+        .collect()
+}
+
+/// Split `text` into chunks of at most `max_bytes` bytes without breaking
+/// words where possible. Blank lines are dropped.
+pub fn split_text(text: &str, max_bytes: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+
+    for line in text.lines() {
+        let mut current = String::new();
+        for word in line.split_whitespace() {
+            let needed = if current.is_empty() {
+                word.len()
+            } else {
+                current.len() + 1 + word.len()
+            };
+            if needed <= max_bytes {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(word);
+                continue;
+            }
+
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+
+            if word.len() <= max_bytes {
+                current.push_str(word);
+            } else {
+                let mut rest = word;
+                while rest.len() > max_bytes {
+                    let mut cut = max_bytes;
+                    while !rest.is_char_boundary(cut) {
+                        cut -= 1;
+                    }
+                    if cut == 0 {
+                        // A single char wider than the budget: emit it whole.
+                        cut = rest.chars().next().map(char::len_utf8).unwrap_or(1);
+                    }
+                    chunks.push(rest[..cut].to_owned());
+                    rest = &rest[cut..];
+                }
+                current.push_str(rest);
+            }
+        }
+        if !current.is_empty() {
+            chunks.push(current);
+        }
+    }
+
+    chunks
+}
+
+/// Encode the SASL `PLAIN` credentials (`authzid \0 authcid \0 passwd`).
+pub fn sasl_plain_payload(account: &str, password: &str) -> String {
+    let raw = format!("{account}\0{account}\0{password}");
+    base64::engine::general_purpose::STANDARD.encode(raw.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_privmsg_with_prefix() {
+        let msg =
+            IrcMessage::parse(":alice!al@host.example PRIVMSG #dev :hello there\r\n").unwrap();
+        assert_eq!(msg.prefix.as_deref(), Some("alice!al@host.example"));
+        assert_eq!(msg.command, "PRIVMSG");
+        assert_eq!(msg.params, vec!["#dev", "hello there"]);
+        assert_eq!(msg.source_nick(), Some("alice"));
+    }
+
+    #[test]
+    fn parse_ping_without_prefix() {
+        let msg = IrcMessage::parse("PING :irc.example.net").unwrap();
+        assert!(msg.prefix.is_none());
+        assert_eq!(msg.command, "PING");
+        assert_eq!(msg.param(0), Some("irc.example.net"));
+    }
+
+    #[test]
+    fn parse_numeric_with_middle_params() {
+        let msg = IrcMessage::parse(":srv 001 clawft :Welcome to the network").unwrap();
+        assert_eq!(msg.command, "001");
+        assert_eq!(msg.params, vec!["clawft", "Welcome to the network"]);
+        assert_eq!(msg.source_nick(), Some("srv"));
+    }
+
+    #[test]
+    fn parse_skips_message_tags() {
+        let msg =
+            IrcMessage::parse("@time=2024-01-01T00:00:00Z :bob!b@h PRIVMSG clawft :hi").unwrap();
+        assert_eq!(msg.command, "PRIVMSG");
+        assert_eq!(msg.source_nick(), Some("bob"));
+        assert_eq!(msg.params, vec!["clawft", "hi"]);
+    }
+
+    #[test]
+    fn parse_lowercase_command_and_no_params() {
+        let msg = IrcMessage::parse("quit").unwrap();
+        assert_eq!(msg.command, "QUIT");
+        assert!(msg.params.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_empty() {
+        assert!(IrcMessage::parse("").is_none());
+        assert!(IrcMessage::parse("\r\n").is_none());
+        assert!(IrcMessage::parse(":prefix-only").is_none());
+    }
+
+    #[test]
+    fn parse_cap_ack() {
+        let msg = IrcMessage::parse(":srv CAP * ACK :sasl").unwrap();
+        assert_eq!(msg.params, vec!["*", "ACK", "sasl"]);
+    }
+
+    #[test]
+    fn to_line_uses_trailing_when_needed() {
+        let msg = IrcMessage::new("PRIVMSG", &["#dev", "hello world"]);
+        assert_eq!(msg.to_line(), "PRIVMSG #dev :hello world");
+
+        let msg = IrcMessage::new("JOIN", &["#dev"]);
+        assert_eq!(msg.to_line(), "JOIN #dev");
+
+        let msg = IrcMessage::new("USER", &["bot", "0", "*", ""]);
+        assert_eq!(msg.to_line(), "USER bot 0 * :");
+    }
+
+    #[test]
+    fn to_line_roundtrips_through_parse() {
+        let line = ":nick!u@h PRIVMSG #chan ::leading colon";
+        let msg = IrcMessage::parse(line).unwrap();
+        assert_eq!(msg.params[1], ":leading colon");
+        assert_eq!(msg.to_line(), line);
+    }
+
+    #[test]
+    fn channel_target_detection() {
+        assert!(is_channel_target("#general"));
+        assert!(is_channel_target("&local"));
+        assert!(!is_channel_target("alice"));
+    }
+
+    #[test]
+    fn mention_variants() {
+        assert_eq!(
+            strip_mention("clawft: what time is it", "clawft"),
+            Some("what time is it")
+        );
+        assert_eq!(strip_mention("Clawft, hi", "clawft"), Some("hi"));
+        assert_eq!(strip_mention("@clawft status", "clawft"), Some("status"));
+        assert_eq!(strip_mention("clawft", "clawft"), Some(""));
+    }
+
+    #[test]
+    fn mention_rejects_other_text() {
+        assert_eq!(strip_mention("hello clawft", "clawft"), None);
+        assert_eq!(strip_mention("clawft2: hi", "clawft"), None);
+        assert_eq!(strip_mention("cl", "clawft"), None);
+    }
+
+    #[test]
+    fn split_short_message_is_single_line() {
+        let lines = split_privmsg("#dev", "hello");
+        assert_eq!(lines, vec!["PRIVMSG #dev :hello"]);
+    }
+
+    #[test]
+    fn split_long_message_respects_limit_and_words() {
+        let word = "lorem";
+        let text = vec![word; 300].join(" ");
+        let lines = split_privmsg("#dev", &text);
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(line.len() + 2 + RELAY_PREFIX_RESERVE <= MAX_LINE_BYTES);
+            let body = line.strip_prefix("PRIVMSG #dev :").unwrap();
+            assert!(
+                body.split(' ').all(|w| w == word),
+                "word was broken: {body}"
+            );
+        }
+        let rejoined: Vec<&str> = lines
+            .iter()
+            .map(|l| l.strip_prefix("PRIVMSG #dev :").unwrap())
+            .collect();
+        assert_eq!(rejoined.join(" "), text);
+    }
+
+    #[test]
+    fn split_preserves_newlines_as_separate_messages() {
+        let lines = split_text("first line\n\nsecond line", 100);
+        assert_eq!(lines, vec!["first line", "second line"]);
+    }
+
+    #[test]
+    fn split_hard_splits_oversized_word_on_char_boundary() {
+        let word = "é".repeat(10); // 20 bytes
+        let chunks = split_text(&word, 5);
+        assert!(chunks.iter().all(|c| c.len() <= 5));
+        assert_eq!(chunks.concat(), word);
+    }
+
+    #[test]
+    fn split_text_packs_words_greedily() {
+        let chunks = split_text("aa bb cc dd", 5);
+        assert_eq!(chunks, vec!["aa bb", "cc dd"]);
+    }
+
+    #[test]
+    fn sasl_plain_encoding() {
+        // base64("bot\0bot\0secret")
+        assert_eq!(sasl_plain_payload("bot", "secret"), "Ym90AGJvdABzZWNyZXQ=");
+    }
+}
@@ -0,0 +1,455 @@
+//! Connection-level IRC session state machine.
+//!
+//! [`Session`] consumes parsed server messages and produces the lines to
+//! write back plus higher-level events (registration, inbound messages,
+//! authentication failures). It performs no I/O, so the registration
+//! handshake, SASL exchange, and message addressing rules can be tested
+//! without a live server.
+
+use super::protocol::{IrcMessage, is_channel_target, sasl_plain_payload, strip_mention};
+use super::types::IrcAdapterConfig;
+
+/// Maximum size of a single `AUTHENTICATE` payload chunk (IRCv3 SASL).
+const SASL_CHUNK_BYTES: usize = 400;
+
+/// Real name sent in the `USER` registration command.
+const REAL_NAME: &str = "clawft agent";
+
+/// A message addressed to the bot, extracted from a `PRIVMSG`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrcInbound {
+    /// Nickname of the sender.
+    pub sender: String,
+    /// Conversation identifier used for the session key.
+    ///
+    /// `"<nick>"` for direct messages and `"<#channel>/<nick>"` for
+    /// nick-prefixed mentions in a channel, so each user in a channel
+    /// gets their own session.
+    pub chat_id: String,
+    /// Channel name for mentions, `None` for direct messages.
+    pub channel: Option<String>,
+    /// Message text with any leading nick mention removed.
+    pub content: String,
+}
+
+/// Outcome of feeding one server message into a [`Session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// Raw line (without CRLF) to write to the server.
+    Send(String),
+    /// Registration completed (`RPL_WELCOME` received).
+    Registered,
+    /// A message addressed to the bot.
+    Inbound(IrcInbound),
+    /// The server rejected our credentials.
+    AuthFailed(String),
+}
+
+/// Per-connection protocol state.
+pub struct Session<'a> {
+    config: &'a IrcAdapterConfig,
+    password: Option<String>,
+    nick: String,
+    registered: bool,
+}
+
+impl<'a> Session<'a> {
+    /// Create a session for a fresh connection.
+    ///
+    /// `password` is the resolved secret from `password_env`, if any.
+    pub fn new(config: &'a IrcAdapterConfig, password: Option<String>) -> Self {
+        Self {
+            config,
+            password,
+            nick: config.nickname.clone(),
+            registered: false,
+        }
+    }
+
+    /// Current nickname (may differ from the configured one after a
+    /// nick collision).
+    pub fn nick(&self) -> &str {
+        &self.nick
+    }
+
+    /// Whether the server has accepted our registration.
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+
+    fn uses_sasl(&self) -> bool {
+        self.config.auth_method == "sasl" && self.password.is_some()
+    }
+
+    /// Lines to send immediately after the socket connects.
+    pub fn handshake(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.uses_sasl() {
+            lines.push("CAP REQ :sasl".to_owned());
+        }
+        lines.push(IrcMessage::new("NICK", &[&self.nick]).to_line());
+        lines.push(IrcMessage::new("USER", &[&self.nick, "0", "*", REAL_NAME]).to_line());
+        lines
+    }
+
+    /// Process one server message.
+    pub fn handle(&mut self, msg: &IrcMessage) -> Vec<SessionEvent> {
+        match msg.command.as_str() {
+            "PING" => {
+                let token = msg.param(0).unwrap_or_default();
+                vec![SessionEvent::Send(
+                    IrcMessage::new("PONG", &[token]).to_line(),
+                )]
+            }
+            "CAP" => self.on_cap(msg),
+            "AUTHENTICATE" if msg.param(0) == Some("+") => self.sasl_response(),
+            // RPL_SASLSUCCESS
+            "903" => vec![SessionEvent::Send("CAP END".to_owned())],
+            // ERR_NICKLOCKED, ERR_SASLFAIL, ERR_SASLTOOLONG
+            "902" | "904" | "905" => vec![SessionEvent::AuthFailed(format!(
+                "SASL authentication rejected: {}",
+                msg.params
+                    .last()
+                    .map(String::as_str)
+                    .unwrap_or("unknown reason")
+            ))],
+            // ERR_NICKNAMEINUSE before registration: try an alternate nick.
+            "433" if !self.registered => {
+                self.nick.push('_');
+                vec![SessionEvent::Send(
+                    IrcMessage::new("NICK", &[&self.nick]).to_line(),
+                )]
+            }
+            // RPL_WELCOME
+            "001" => self.on_welcome(msg),
+            "NICK" => {
+                if msg
+                    .source_nick()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(&self.nick))
+                    && let Some(new_nick) = msg.param(0)
+                {
+                    self.nick = new_nick.to_owned();
+                }
+                Vec::new()
+            }
+            "PRIVMSG" => self
+                .on_privmsg(msg)
+                .map(SessionEvent::Inbound)
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn on_cap(&self, msg: &IrcMessage) -> Vec<SessionEvent> {
+        let sub = msg.param(1).unwrap_or_default();
+        let caps = msg.params.last().map(String::as_str).unwrap_or_default();
+        let mentions_sasl = caps.split_whitespace().any(|c| c == "sasl");
+        match sub {
+            "ACK" if mentions_sasl => {
+                vec![SessionEvent::Send("AUTHENTICATE PLAIN".to_owned())]
+            }
+            "NAK" if mentions_sasl => vec![SessionEvent::AuthFailed(
+                "server does not support SASL".into(),
+            )],
+            _ => Vec::new(),
+        }
+    }
+
+    fn sasl_response(&self) -> Vec<SessionEvent> {
+        let Some(ref password) = self.password else {
+            return vec![SessionEvent::Send("AUTHENTICATE *".to_owned())];
+        };
+        let payload = sasl_plain_payload(self.config.account_name(), password);
+
+        let mut events: Vec<SessionEvent> = payload
+            .as_bytes()
+            .chunks(SASL_CHUNK_BYTES)
+            .map(|chunk| {
+                // Base64 output is ASCII, so any byte split is a char boundary.
+                let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+                SessionEvent::Send(format!("AUTHENTICATE {chunk}"))
+            })
+            .collect();
+        if payload.len().is_multiple_of(SASL_CHUNK_BYTES) {
+            events.push(SessionEvent::Send("AUTHENTICATE +".to_owned()));
+        }
+        events
+    }
+
+    fn on_welcome(&mut self, msg: &IrcMessage) -> Vec<SessionEvent> {
+        self.registered = true;
+        if let Some(nick) = msg.param(0) {
+            self.nick = nick.to_owned();
+        }
+
+        let mut events = Vec::new();
+        if self.config.auth_method == "nickserv"
+            && let Some(ref password) = self.password
+        {
+            let identify = format!("IDENTIFY {} {}", self.config.account_name(), password);
+            events.push(SessionEvent::Send(
+                IrcMessage::new("PRIVMSG", &["NickServ", &identify]).to_line(),
+            ));
+        }
+        for channel in &self.config.channels {
+            events.push(SessionEvent::Send(
+                IrcMessage::new("JOIN", &[channel]).to_line(),
+            ));
+        }
+        events.push(SessionEvent::Registered);
+        events
+    }
+
+    fn on_privmsg(&self, msg: &IrcMessage) -> Option<IrcInbound> {
+        let sender = msg.source_nick()?;
+        let target = msg.param(0)?;
+        let text = msg.param(1)?;
+
+        if sender.eq_ignore_ascii_case(&self.nick) {
+            return None;
+        }
+        // CTCP (ACTION, VERSION, ...) is not conversational input.
+        if text.starts_with('\u{1}') {
+            return None;
+        }
+
+        if is_channel_target(target) {
+            let content = strip_mention(text, &self.nick)?;
+            if content.is_empty() {
+                return None;
+            }
+            Some(IrcInbound {
+                sender: sender.to_owned(),
+                chat_id: format!("{target}/{sender}"),
+                channel: Some(target.to_owned()),
+                content: content.to_owned(),
+            })
+        } else if target.eq_ignore_ascii_case(&self.nick) {
+            Some(IrcInbound {
+                sender: sender.to_owned(),
+                chat_id: sender.to_owned(),
+                channel: None,
+                content: text.trim().to_owned(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Resolve an outbound `chat_id` into the IRC target and an optional
+/// nick to address the reply to.
+///
+/// Channel conversations use `"<#channel>/<nick>"` (see
+/// [`IrcInbound::chat_id`]); nicknames cannot contain `/`, so the last
+/// `/` separates the two parts.
+pub fn resolve_chat_id(chat_id: &str) -> (&str, Option<&str>) {
+    if is_channel_target(chat_id)
+        && let Some((channel, nick)) = chat_id.rsplit_once('/')
+        && !nick.is_empty()
+    {
+        return (channel, Some(nick));
+    }
+    (chat_id, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(auth_method: &str) -> IrcAdapterConfig {
+        IrcAdapterConfig {
+            server: "irc.example.net".into(),
+            nickname: "clawft".into(),
+            channels: vec!["#dev".into(), "#ops".into()],
+            auth_method: auth_method.into(),
+            password_env: Some("IRC_PASSWORD".into()),
+            ..Default::default()
+        }
+    }
+
+    fn parse(line: &str) -> IrcMessage {
+        IrcMessage::parse(line).unwrap()
+    }
+
+    fn sent(events: &[SessionEvent]) -> Vec<&str> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                SessionEvent::Send(line) => Some(line.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn handshake_without_auth() {
+        let cfg = config("none");
+        let session = Session::new(&cfg, None);
+        assert_eq!(
+            session.handshake(),
+            vec!["NICK clawft", "USER clawft 0 * :clawft agent"]
+        );
+    }
+
+    #[test]
+    fn handshake_with_sasl_requests_capability() {
+        let cfg = config("sasl");
+        let session = Session::new(&cfg, Some("pw".into()));
+        assert_eq!(session.handshake()[0], "CAP REQ :sasl");
+    }
+
+    #[test]
+    fn sasl_exchange() {
+        let cfg = config("sasl");
+        let mut session = Session::new(&cfg, Some("secret".into()));
+
+        let ev = session.handle(&parse(":srv CAP * ACK :sasl"));
+        assert_eq!(sent(&ev), vec!["AUTHENTICATE PLAIN"]);
+
+        let ev = session.handle(&parse("AUTHENTICATE +"));
+        assert_eq!(
+            sent(&ev),
+            vec![format!("AUTHENTICATE {}", sasl_plain_payload("clawft", "secret")).as_str()]
+        );
+
+        let ev = session.handle(&parse(":srv 903 clawft :SASL authentication successful"));
+        assert_eq!(sent(&ev), vec!["CAP END"]);
+    }
+
+    #[test]
+    fn sasl_failure_is_reported() {
+        let cfg = config("sasl");
+        let mut session = Session::new(&cfg, Some("wrong".into()));
+        let ev = session.handle(&parse(":srv 904 clawft :SASL authentication failed"));
+        assert!(
+            matches!(&ev[0], SessionEvent::AuthFailed(m) if m.contains("authentication failed"))
+        );
+    }
+
+    #[test]
+    fn sasl_nak_is_reported() {
+        let cfg = config("sasl");
+        let mut session = Session::new(&cfg, Some("pw".into()));
+        let ev = session.handle(&parse(":srv CAP * NAK :sasl"));
+        assert!(matches!(&ev[0], SessionEvent::AuthFailed(_)));
+    }
+
+    #[test]
+    fn welcome_joins_channels() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        let ev = session.handle(&parse(":srv 001 clawft :Welcome"));
+        assert_eq!(sent(&ev), vec!["JOIN #dev", "JOIN #ops"]);
+        assert_eq!(ev.last(), Some(&SessionEvent::Registered));
+        assert!(session.is_registered());
+    }
+
+    #[test]
+    fn welcome_identifies_with_nickserv() {
+        let cfg = config("nickserv");
+        let mut session = Session::new(&cfg, Some("hunter2".into()));
+        let ev = session.handle(&parse(":srv 001 clawft :Welcome"));
+        assert_eq!(sent(&ev)[0], "PRIVMSG NickServ :IDENTIFY clawft hunter2");
+    }
+
+    #[test]
+    fn ping_gets_pong() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        let ev = session.handle(&parse("PING :abc123"));
+        assert_eq!(sent(&ev), vec!["PONG abc123"]);
+    }
+
+    #[test]
+    fn nick_collision_before_registration() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        let ev = session.handle(&parse(":srv 433 * clawft :Nickname is already in use"));
+        assert_eq!(sent(&ev), vec!["NICK clawft_"]);
+        assert_eq!(session.nick(), "clawft_");
+    }
+
+    #[test]
+    fn direct_message_maps_to_sender_session() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        let ev = session.handle(&parse(":alice!a@h PRIVMSG clawft :hello bot"));
+        assert_eq!(
+            ev,
+            vec![SessionEvent::Inbound(IrcInbound {
+                sender: "alice".into(),
+                chat_id: "alice".into(),
+                channel: None,
+                content: "hello bot".into(),
+            })]
+        );
+    }
+
+    #[test]
+    fn channel_mention_maps_to_channel_and_user() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        let ev = session.handle(&parse(":bob!b@h PRIVMSG #dev :clawft: deploy status?"));
+        assert_eq!(
+            ev,
+            vec![SessionEvent::Inbound(IrcInbound {
+                sender: "bob".into(),
+                chat_id: "#dev/bob".into(),
+                channel: Some("#dev".into()),
+                content: "deploy status?".into(),
+            })]
+        );
+    }
+
+    #[test]
+    fn unaddressed_channel_chatter_is_ignored() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        assert!(
+            session
+                .handle(&parse(":bob!b@h PRIVMSG #dev :lunch?"))
+                .is_empty()
+        );
+        assert!(
+            session
+                .handle(&parse(":bob!b@h PRIVMSG #dev :clawft:"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn own_messages_and_ctcp_are_ignored() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        assert!(
+            session
+                .handle(&parse(":clawft!c@h PRIVMSG #dev :clawft: echo"))
+                .is_empty()
+        );
+        assert!(
+            session
+                .handle(&parse(":bob!b@h PRIVMSG clawft :\u{1}VERSION\u{1}"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn own_nick_change_is_tracked() {
+        let cfg = config("none");
+        let mut session = Session::new(&cfg, None);
+        session.handle(&parse(":clawft!c@h NICK :clawft2"));
+        assert_eq!(session.nick(), "clawft2");
+        let ev = session.handle(&parse(":bob!b@h PRIVMSG #dev :clawft2: hi"));
+        assert_eq!(ev.len(), 1);
+    }
+
+    #[test]
+    fn resolve_chat_id_variants() {
+        assert_eq!(resolve_chat_id("alice"), ("alice", None));
+        assert_eq!(resolve_chat_id("#dev/bob"), ("#dev", Some("bob")));
+        assert_eq!(resolve_chat_id("#dev"), ("#dev", None));
+        assert_eq!(resolve_chat_id("#a/b/carol"), ("#a/b", Some("carol")));
+    }
+}
@@ -44,8 +44,24 @@ pub struct IrcAdapterConfig {
     pub allowed_senders: Vec<String>,
 
     /// Delay in seconds before reconnecting after a disconnect (default 5).
+    ///
+    /// Consecutive failures double the delay up to
+    /// `max_reconnect_delay_secs`.
     #[serde(default = "default_reconnect_delay_secs", alias = "reconnectDelaySecs")]
     pub reconnect_delay_secs: u64,
+
+    /// Upper bound for the reconnect backoff in seconds (default 300).
+    #[serde(
+        default = "default_max_reconnect_delay_secs",
+        alias = "maxReconnectDelaySecs"
+    )]
+    pub max_reconnect_delay_secs: u64,
+
+    /// Account name used for SASL / NickServ authentication.
+    ///
+    /// Defaults to `nickname` when unset.
+    #[serde(default)]
+    pub account: Option<String>,
 }
 
 fn default_port() -> u16 {
@@ -64,6 +80,10 @@ fn default_reconnect_delay_secs() -> u64 {
     5
 }
 
+fn default_max_reconnect_delay_secs() -> u64 {
+    300
+}
+
 impl Default for IrcAdapterConfig {
     fn default() -> Self {
         Self {
@@ -76,10 +96,19 @@ impl Default for IrcAdapterConfig {
             password_env: None,
             allowed_senders: Vec::new(),
             reconnect_delay_secs: default_reconnect_delay_secs(),
+            max_reconnect_delay_secs: default_max_reconnect_delay_secs(),
+            account: None,
         }
     }
 }
 
+impl IrcAdapterConfig {
+    /// Account name for authentication (falls back to the nickname).
+    pub fn account_name(&self) -> &str {
+        self.account.as_deref().unwrap_or(&self.nickname)
+    }
+}
+
 /// Validate the IRC adapter configuration.
 ///
 /// Checks:
@@ -88,6 +117,7 @@ impl Default for IrcAdapterConfig {
 /// - `auth_method` is one of `"none"`, `"nickserv"`, `"sasl"`
 /// - `password_env` is set when `auth_method` requires authentication
 /// - Channel names start with `#` or `&`
+/// - `account`, when set, is free of protocol injection characters
 pub fn validate_config(config: &IrcAdapterConfig) -> Result<(), String> {
     // Server is required.
     if config.server.is_empty() {
@@ -133,6 +163,10 @@ pub fn validate_config(config: &IrcAdapterConfig) -> Result<(), String> {
             .map_err(|e| format!("irc adapter: invalid channel name: {e}"))?;
     }
 
+    if let Some(ref account) = config.account {
+        sanitize_irc_argument(account).map_err(|e| format!("irc adapter: invalid account: {e}"))?;
+    }
+
     Ok(())
 }
 
@@ -207,6 +241,8 @@ mod tests {
         assert!(cfg.use_tls);
         assert_eq!(cfg.auth_method, "none");
         assert_eq!(cfg.reconnect_delay_secs, 5);
+        assert_eq!(cfg.max_reconnect_delay_secs, 300);
+        assert!(cfg.account.is_none());
         assert!(cfg.server.is_empty());
         assert!(cfg.nickname.is_empty());
         assert!(cfg.channels.is_empty());
@@ -414,6 +450,8 @@ mod tests {
             password_env: Some("IRC_PASS".into()),
             allowed_senders: vec!["admin".into()],
             reconnect_delay_secs: 10,
+            max_reconnect_delay_secs: 120,
+            account: Some("clawft".into()),
         };
         let json = serde_json::to_string(&cfg).unwrap();
         let restored: IrcAdapterConfig = serde_json::from_str(&json).unwrap();
@@ -426,5 +464,16 @@ mod tests {
         assert_eq!(restored.password_env, Some("IRC_PASS".into()));
         assert_eq!(restored.allowed_senders, vec!["admin"]);
         assert_eq!(restored.reconnect_delay_secs, 10);
+        assert_eq!(restored.max_reconnect_delay_secs, 120);
+        assert_eq!(restored.account_name(), "clawft");
+    }
+
+    #[test]
+    fn account_name_defaults_to_nickname() {
+        let cfg = IrcAdapterConfig {
+            nickname: "clawft-bot".into(),
+            ..Default::default()
+        };
+        assert_eq!(cfg.account_name(), "clawft-bot");
     }
 }
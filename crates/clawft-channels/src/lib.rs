@@ -25,6 +25,7 @@
 //! Channel operations return [`ChannelError`](clawft_types::error::ChannelError)
 //! from the `clawft-types` crate. This crate re-exports it for convenience.
 
+pub mod attachment;
 pub mod discord;
 #[cfg(feature = "email")]
 pub mod email;
@@ -35,6 +36,7 @@ pub mod host;
 pub mod irc;
 #[cfg(feature = "matrix")]
 pub mod matrix;
+pub mod metrics;
 pub mod plugin_host;
 #[cfg(feature = "signal")]
 pub mod signal;
@@ -47,7 +49,8 @@ pub mod web;
 #[cfg(feature = "whatsapp")]
 pub mod whatsapp;
 
-pub use host::PluginHost;
+pub use host::{PluginHost, ProgressiveReply, ReloadReport};
+pub use metrics::{ChannelMetricsSnapshot, MetricsRegistry};
 pub use traits::*;
 
 // Re-export the canonical error type so callers do not need to depend
@@ -0,0 +1,324 @@
+//! Per-channel metrics for the plugin host.
+//!
+//! [`MetricsRegistry`] keeps lock-free counters for each channel:
+//! inbound messages received, outbound messages sent, send failures,
+//! reconnects, and last-activity timestamps. Channels never touch the
+//! registry directly; [`PluginHost`](crate::PluginHost) counts outbound
+//! sends itself and hands each channel a [`MeteredHost`] that counts
+//! inbound publishes on the way to the real [`ChannelHost`].
+//!
+//! Snapshots are serializable ([`ChannelMetricsSnapshot`]) and can be
+//! rendered in the Prometheus text exposition format with
+//! [`render_prometheus`].
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use clawft_types::error::ChannelError;
+use clawft_types::event::InboundMessage;
+
+use crate::traits::{ChannelHost, Command};
+
+/// Live counters for a single channel.
+#[derive(Debug, Default)]
+pub struct ChannelCounters {
+    inbound_received: AtomicU64,
+    outbound_sent: AtomicU64,
+    send_failures: AtomicU64,
+    reconnects: AtomicU64,
+    /// Unix milliseconds of the last inbound message (0 = never).
+    last_inbound_ms: AtomicI64,
+    /// Unix milliseconds of the last successful send (0 = never).
+    last_outbound_ms: AtomicI64,
+}
+
+impl ChannelCounters {
+    /// Count an inbound message delivered to the pipeline.
+    pub fn record_inbound(&self) {
+        self.inbound_received.fetch_add(1, Ordering::Relaxed);
+        self.last_inbound_ms
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Count a successful outbound send.
+    pub fn record_sent(&self) {
+        self.outbound_sent.fetch_add(1, Ordering::Relaxed);
+        self.last_outbound_ms
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    /// Count a failed outbound send.
+    pub fn record_send_failure(&self) {
+        self.send_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a reconnect to the upstream service.
+    pub fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a consistent-enough snapshot of the counters.
+    pub fn snapshot(&self, channel: &str) -> ChannelMetricsSnapshot {
+        ChannelMetricsSnapshot {
+            channel: channel.to_owned(),
+            status: None,
+            inbound_received: self.inbound_received.load(Ordering::Relaxed),
+            outbound_sent: self.outbound_sent.load(Ordering::Relaxed),
+            send_failures: self.send_failures.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            last_inbound: millis_to_time(self.last_inbound_ms.load(Ordering::Relaxed)),
+            last_outbound: millis_to_time(self.last_outbound_ms.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+fn millis_to_time(ms: i64) -> Option<DateTime<Utc>> {
+    if ms == 0 {
+        return None;
+    }
+    Utc.timestamp_millis_opt(ms).single()
+}
+
+/// Point-in-time metrics for one channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMetricsSnapshot {
+    /// Channel name (e.g. `"telegram"`).
+    pub channel: String,
+    /// Channel status as reported by the host, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Inbound messages delivered to the pipeline.
+    pub inbound_received: u64,
+    /// Outbound messages sent successfully.
+    pub outbound_sent: u64,
+    /// Outbound sends that returned an error.
+    pub send_failures: u64,
+    /// Reconnects reported by the channel.
+    pub reconnects: u64,
+    /// Time of the last inbound message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_inbound: Option<DateTime<Utc>>,
+    /// Time of the last successful send.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_outbound: Option<DateTime<Utc>>,
+}
+
+/// Registry of per-channel counters, shared between the host and the
+/// [`MeteredHost`] wrappers it hands to channels.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    channels: RwLock<HashMap<String, Arc<ChannelCounters>>>,
+}
+
+impl MetricsRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counters for `channel`, created on first use.
+    pub fn counters(&self, channel: &str) -> Arc<ChannelCounters> {
+        if let Some(c) = self
+            .channels
+            .read()
+            .ok()
+            .and_then(|m| m.get(channel).cloned())
+        {
+            return c;
+        }
+        let mut map = self.channels.write().unwrap_or_else(|e| e.into_inner());
+        map.entry(channel.to_owned()).or_default().clone()
+    }
+
+    /// Snapshot of every channel seen so far, sorted by name.
+    pub fn snapshot(&self) -> Vec<ChannelMetricsSnapshot> {
+        let map = self.channels.read().unwrap_or_else(|e| e.into_inner());
+        let mut out: Vec<_> = map.iter().map(|(n, c)| c.snapshot(n)).collect();
+        out.sort_by(|a, b| a.channel.cmp(&b.channel));
+        out
+    }
+}
+
+/// Render snapshots in the Prometheus text exposition format.
+pub fn render_prometheus(snapshots: &[ChannelMetricsSnapshot]) -> String {
+    type Getter = fn(&ChannelMetricsSnapshot) -> Option<i64>;
+    let families: [(&str, &str, &str, Getter); 6] = [
+        (
+            "clawft_channel_inbound_messages_total",
+            "counter",
+            "Inbound messages delivered to the agent pipeline.",
+            |s| Some(s.inbound_received as i64),
+        ),
+        (
+            "clawft_channel_outbound_messages_total",
+            "counter",
+            "Outbound messages sent successfully.",
+            |s| Some(s.outbound_sent as i64),
+        ),
+        (
+            "clawft_channel_send_failures_total",
+            "counter",
+            "Outbound sends that failed.",
+            |s| Some(s.send_failures as i64),
+        ),
+        (
+            "clawft_channel_reconnects_total",
+            "counter",
+            "Reconnects to the upstream service.",
+            |s| Some(s.reconnects as i64),
+        ),
+        (
+            "clawft_channel_last_inbound_timestamp_seconds",
+            "gauge",
+            "Unix time of the last inbound message.",
+            |s| s.last_inbound.map(|t| t.timestamp()),
+        ),
+        (
+            "clawft_channel_last_outbound_timestamp_seconds",
+            "gauge",
+            "Unix time of the last successful send.",
+            |s| s.last_outbound.map(|t| t.timestamp()),
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, kind, help, get) in families {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for s in snapshots {
+            if let Some(v) = get(s) {
+                let label = s.channel.replace('\\', "\\\\").replace('"', "\\\"");
+                let _ = writeln!(out, "{name}{{channel=\"{label}\"}} {v}");
+            }
+        }
+    }
+    out
+}
+
+/// [`ChannelHost`] wrapper that counts inbound traffic and reconnects.
+///
+/// Delegates every call to the wrapped host; counting happens only when
+/// the inner host accepts the message.
+pub struct MeteredHost {
+    inner: Arc<dyn ChannelHost>,
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl MeteredHost {
+    /// Wrap `inner`, recording into `metrics`.
+    pub fn new(inner: Arc<dyn ChannelHost>, metrics: Arc<MetricsRegistry>) -> Self {
+        Self { inner, metrics }
+    }
+}
+
+#[async_trait]
+impl ChannelHost for MeteredHost {
+    async fn deliver_inbound(&self, msg: InboundMessage) -> Result<(), ChannelError> {
+        let counters = self.metrics.counters(&msg.channel);
+        self.inner.deliver_inbound(msg).await?;
+        counters.record_inbound();
+        Ok(())
+    }
+
+    async fn register_command(&self, cmd: Command) -> Result<(), ChannelError> {
+        self.inner.register_command(cmd).await
+    }
+
+    async fn publish_inbound(
+        &self,
+        channel: &str,
+        sender_id: &str,
+        chat_id: &str,
+        content: &str,
+        media: Vec<String>,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<(), ChannelError> {
+        self.inner
+            .publish_inbound(channel, sender_id, chat_id, content, media, metadata)
+            .await?;
+        self.metrics.counters(channel).record_inbound();
+        Ok(())
+    }
+
+    async fn report_reconnect(&self, channel: &str) {
+        self.metrics.counters(channel).record_reconnect();
+        self.inner.report_reconnect(channel).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_snapshot() {
+        let registry = MetricsRegistry::new();
+        let c = registry.counters("slack");
+        c.record_inbound();
+        c.record_sent();
+        c.record_sent();
+        c.record_send_failure();
+        c.record_reconnect();
+
+        let snap = registry.snapshot();
+        assert_eq!(snap.len(), 1);
+        let s = &snap[0];
+        assert_eq!(s.channel, "slack");
+        assert_eq!(
+            (
+                s.inbound_received,
+                s.outbound_sent,
+                s.send_failures,
+                s.reconnects
+            ),
+            (1, 2, 1, 1)
+        );
+        assert!(s.last_inbound.is_some());
+        assert!(s.last_outbound.is_some());
+    }
+
+    #[test]
+    fn counters_are_shared_per_channel() {
+        let registry = MetricsRegistry::new();
+        registry.counters("a").record_inbound();
+        registry.counters("a").record_inbound();
+        registry.counters("b");
+        let snap = registry.snapshot();
+        assert_eq!(snap[0].inbound_received, 2);
+        assert_eq!(snap[1].channel, "b");
+        assert!(snap[1].last_inbound.is_none());
+    }
+
+    #[test]
+    fn snapshot_serializes_without_empty_fields() {
+        let registry = MetricsRegistry::new();
+        registry.counters("irc");
+        let json = serde_json::to_value(&registry.snapshot()[0]).unwrap();
+        assert_eq!(json["inbound_received"], 0);
+        assert!(json.get("last_inbound").is_none());
+        assert!(json.get("status").is_none());
+    }
+
+    #[test]
+    fn prometheus_text_format() {
+        let registry = MetricsRegistry::new();
+        registry.counters("telegram").record_sent();
+        registry.counters("web").record_inbound();
+        let text = render_prometheus(&registry.snapshot());
+
+        assert!(text.contains("# TYPE clawft_channel_outbound_messages_total counter\n"));
+        assert!(text.contains("clawft_channel_outbound_messages_total{channel=\"telegram\"} 1\n"));
+        assert!(text.contains("clawft_channel_inbound_messages_total{channel=\"web\"} 1\n"));
+        assert!(text.contains("clawft_channel_last_inbound_timestamp_seconds{channel=\"web\"}"));
+        // Never-seen timestamps are omitted rather than reported as zero.
+        assert!(
+            !text.contains("clawft_channel_last_inbound_timestamp_seconds{channel=\"telegram\"}")
+        );
+    }
+}
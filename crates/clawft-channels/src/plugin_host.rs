@@ -130,6 +130,7 @@ impl ChannelAdapter for ChannelAdapterShim {
             content,
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
 
@@ -2,14 +2,18 @@
 //!
 //! [`SlackApiClient`] provides typed methods for the subset of the
 //! Slack Web API used by the channel plugin: `apps.connections.open`,
-//! `chat.postMessage`, and `chat.update`.
+//! `chat.postMessage`, `chat.update`, and the external file upload flow
+//! (`files.getUploadURLExternal` + `files.completeUploadExternal`).
 
 use reqwest::Client;
 use tracing::debug;
 
 use clawft_types::error::ChannelError;
 
-use super::events::{ChatPostMessageResponse, ChatUpdateResponse, ConnectionsOpenResponse};
+use super::events::{
+    ChatPostMessageResponse, ChatUpdateResponse, CompleteUploadResponse, ConnectionsOpenResponse,
+    GetUploadUrlResponse,
+};
 
 /// Base URL for the Slack Web API.
 const SLACK_API_BASE: &str = "https://slack.com/api";
@@ -177,6 +181,104 @@ impl SlackApiClient {
 
         Ok(())
     }
+
+    /// Upload a file using the v2 external upload flow.
+    ///
+    /// 1. `files.getUploadURLExternal` reserves a file ID and upload URL.
+    /// 2. The contents are POSTed to the upload URL.
+    /// 3. `files.completeUploadExternal` shares the file into `channel`
+    ///    (optionally in `thread_ts`) with `initial_comment` as caption.
+    ///
+    /// Returns the Slack file ID on success.
+    pub async fn upload_file(
+        &self,
+        channel: &str,
+        filename: &str,
+        data: Vec<u8>,
+        initial_comment: Option<&str>,
+        thread_ts: Option<&str>,
+    ) -> Result<String, ChannelError> {
+        let url = format!("{}/files.getUploadURLExternal", self.base_url);
+        let length = data.len().to_string();
+
+        debug!(channel = %channel, filename = %filename, "reserving Slack upload URL");
+
+        let resp = self
+            .http
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.bot_token))
+            .form(&[("filename", filename), ("length", length.as_str())])
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let reserved: GetUploadUrlResponse = resp
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        if !reserved.ok {
+            let err_msg = reserved.error.unwrap_or_else(|| "unknown error".into());
+            return Err(ChannelError::SendFailed(format!(
+                "files.getUploadURLExternal failed: {err_msg}"
+            )));
+        }
+        let (Some(upload_url), Some(file_id)) = (reserved.upload_url, reserved.file_id) else {
+            return Err(ChannelError::SendFailed(
+                "files.getUploadURLExternal returned ok but no upload_url/file_id".into(),
+            ));
+        };
+
+        let resp = self
+            .http
+            .post(&upload_url)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(ChannelError::SendFailed(format!(
+                "Slack file upload returned {}",
+                resp.status()
+            )));
+        }
+
+        let url = format!("{}/files.completeUploadExternal", self.base_url);
+        let mut body = serde_json::json!({
+            "files": [{ "id": file_id, "title": filename }],
+            "channel_id": channel,
+        });
+        if let Some(comment) = initial_comment {
+            body["initial_comment"] = serde_json::Value::String(comment.to_owned());
+        }
+        if let Some(ts) = thread_ts {
+            body["thread_ts"] = serde_json::Value::String(ts.to_owned());
+        }
+
+        let resp = self
+            .http
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.bot_token))
+            .header("Content-Type", "application/json; charset=utf-8")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let result: CompleteUploadResponse = resp
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        if !result.ok {
+            let err_msg = result.error.unwrap_or_else(|| "unknown error".into());
+            return Err(ChannelError::SendFailed(format!(
+                "files.completeUploadExternal failed: {err_msg}"
+            )));
+        }
+
+        Ok(file_id)
+    }
 }
 
 #[cfg(test)]
@@ -16,8 +16,9 @@ use tracing::{debug, error, info, warn};
 
 use clawft_types::config::SlackConfig;
 use clawft_types::error::ChannelError;
-use clawft_types::event::{InboundMessage, OutboundMessage};
+use clawft_types::event::{Attachment, InboundMessage, OutboundMessage};
 
+use crate::attachment::load_within_limit;
 use crate::traits::{Channel, ChannelHost, ChannelMetadata, ChannelStatus, MessageId};
 
 use super::api::SlackApiClient;
@@ -26,6 +27,9 @@ use super::events::{SlackAcknowledge, SlackEnvelope};
 /// Delay before retrying after a WebSocket connection failure.
 const RECONNECT_DELAY_SECS: u64 = 5;
 
+/// Slack's per-file upload limit (1 GB).
+pub(crate) const SLACK_MAX_UPLOAD_BYTES: u64 = 1024 * 1024 * 1024;
+
 /// Slack channel implementation using Socket Mode.
 ///
 /// # Configuration
@@ -190,6 +194,7 @@ impl SlackChannel {
             content: text.clone(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata,
         };
 
@@ -354,6 +359,7 @@ impl Channel for SlackChannel {
                     info!("reconnecting Slack WebSocket...");
                 }
             }
+            host.report_reconnect(self.name()).await;
         }
 
         self.set_status(ChannelStatus::Stopped).await;
@@ -371,4 +377,40 @@ impl Channel for SlackChannel {
 
         Ok(MessageId(ts))
     }
+
+    fn supports_edit(&self) -> bool {
+        true
+    }
+
+    async fn edit_message(
+        &self,
+        id: &MessageId,
+        msg: &OutboundMessage,
+    ) -> Result<(), ChannelError> {
+        self.api
+            .chat_update(&msg.chat_id, &id.0, &msg.content)
+            .await
+    }
+
+    async fn send_attachment(
+        &self,
+        msg: &OutboundMessage,
+        attachment: &Attachment,
+    ) -> Result<MessageId, ChannelError> {
+        let thread_ts = msg.metadata.get("thread_ts").and_then(|v| v.as_str());
+        let data = load_within_limit(attachment, SLACK_MAX_UPLOAD_BYTES).await?;
+
+        let file_id = self
+            .api
+            .upload_file(
+                &msg.chat_id,
+                &attachment.filename,
+                data,
+                attachment.caption.as_deref(),
+                thread_ts,
+            )
+            .await?;
+
+        Ok(MessageId(file_id))
+    }
 }
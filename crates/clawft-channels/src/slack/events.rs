@@ -113,6 +113,32 @@ pub struct ConnectionsOpenResponse {
     pub error: Option<String>,
 }
 
+/// Response from `files.getUploadURLExternal`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetUploadUrlResponse {
+    /// Whether the API call succeeded.
+    pub ok: bool,
+
+    /// Pre-signed URL to POST the file contents to.
+    pub upload_url: Option<String>,
+
+    /// ID of the pending file, passed to `files.completeUploadExternal`.
+    pub file_id: Option<String>,
+
+    /// Error message if `ok` is `false`.
+    pub error: Option<String>,
+}
+
+/// Response from `files.completeUploadExternal`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompleteUploadResponse {
+    /// Whether the API call succeeded.
+    pub ok: bool,
+
+    /// Error message if `ok` is `false`.
+    pub error: Option<String>,
+}
+
 /// Response from `chat.postMessage`.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ChatPostMessageResponse {
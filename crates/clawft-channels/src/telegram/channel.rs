@@ -14,8 +14,9 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 use clawft_types::error::ChannelError;
-use clawft_types::event::{InboundMessage, OutboundMessage};
+use clawft_types::event::{Attachment, InboundMessage, OutboundMessage};
 
+use crate::attachment::load_within_limit;
 use crate::traits::{
     Channel, ChannelFactory, ChannelHost, ChannelMetadata, ChannelStatus, MessageId,
 };
@@ -31,6 +32,32 @@ const DEFAULT_POLL_INTERVAL_SECS: u64 = 1;
 /// Delay before retrying after an error, in seconds.
 const ERROR_RETRY_DELAY_SECS: u64 = 5;
 
+/// Bot API upload limit for `sendDocument` (50 MB).
+pub(crate) const TELEGRAM_MAX_UPLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Bot API upload limit for `sendPhoto` (10 MB); larger images are sent
+/// as documents.
+const TELEGRAM_MAX_PHOTO_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Parse the numeric chat and reply IDs of an outbound message.
+fn parse_ids(msg: &OutboundMessage) -> Result<(i64, Option<i64>), ChannelError> {
+    let chat_id: i64 = msg.chat_id.parse().map_err(|_| {
+        ChannelError::SendFailed(format!("invalid chat_id '{}': expected i64", msg.chat_id))
+    })?;
+
+    let reply_to: Option<i64> = msg
+        .reply_to
+        .as_ref()
+        .map(|id| {
+            id.parse::<i64>().map_err(|_| {
+                ChannelError::SendFailed(format!("invalid reply_to '{}': expected i64", id))
+            })
+        })
+        .transpose()?;
+
+    Ok((chat_id, reply_to))
+}
+
 /// Telegram Bot channel implementation.
 ///
 /// Connects to the Telegram Bot API using long polling. Inbound text
@@ -147,6 +174,7 @@ impl TelegramChannel {
             content: text.clone(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata,
         };
 
@@ -271,19 +299,7 @@ impl Channel for TelegramChannel {
     }
 
     async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError> {
-        let chat_id: i64 = msg.chat_id.parse().map_err(|_| {
-            ChannelError::SendFailed(format!("invalid chat_id '{}': expected i64", msg.chat_id))
-        })?;
-
-        let reply_to: Option<i64> = msg
-            .reply_to
-            .as_ref()
-            .map(|id| {
-                id.parse::<i64>().map_err(|_| {
-                    ChannelError::SendFailed(format!("invalid reply_to '{}': expected i64", id))
-                })
-            })
-            .transpose()?;
+        let (chat_id, reply_to) = parse_ids(msg)?;
 
         let sent = self
             .client
@@ -292,6 +308,31 @@ impl Channel for TelegramChannel {
 
         Ok(MessageId(sent.message_id.to_string()))
     }
+
+    async fn send_attachment(
+        &self,
+        msg: &OutboundMessage,
+        attachment: &Attachment,
+    ) -> Result<MessageId, ChannelError> {
+        let (chat_id, reply_to) = parse_ids(msg)?;
+        let data = load_within_limit(attachment, TELEGRAM_MAX_UPLOAD_BYTES).await?;
+        let as_photo = attachment.is_image() && data.len() as u64 <= TELEGRAM_MAX_PHOTO_BYTES;
+
+        let sent = self
+            .client
+            .send_file(
+                chat_id,
+                &attachment.filename,
+                &attachment.mime_type,
+                data,
+                attachment.caption.as_deref(),
+                reply_to,
+                as_photo,
+            )
+            .await?;
+
+        Ok(MessageId(sent.message_id.to_string()))
+    }
 }
 
 /// Factory for creating [`TelegramChannel`] instances from JSON config.
@@ -2,7 +2,7 @@
 //!
 //! [`TelegramClient`] provides typed methods for the subset of the
 //! Telegram Bot API used by the channel plugin: `getMe`, `getUpdates`,
-//! and `sendMessage`.
+//! `sendMessage`, and the `sendPhoto` / `sendDocument` uploads.
 
 use reqwest::Client;
 use tracing::{debug, trace};
@@ -131,6 +131,66 @@ impl TelegramClient {
             .ok_or_else(|| ChannelError::SendFailed("missing result in response".into()))
     }
 
+    /// Upload a file via multipart `sendPhoto` (when `as_photo`) or
+    /// `sendDocument`.
+    ///
+    /// Returns the sent [`Message`] on success.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_file(
+        &self,
+        chat_id: i64,
+        filename: &str,
+        mime_type: &str,
+        data: Vec<u8>,
+        caption: Option<&str>,
+        reply_to: Option<i64>,
+        as_photo: bool,
+    ) -> Result<Message, ChannelError> {
+        let (method, field) = if as_photo {
+            ("sendPhoto", "photo")
+        } else {
+            ("sendDocument", "document")
+        };
+        let url = format!("{}/{method}", self.base_url);
+
+        let part = reqwest::multipart::Part::bytes(data)
+            .file_name(filename.to_owned())
+            .mime_str(mime_type)
+            .map_err(|e| ChannelError::SendFailed(format!("invalid mime type: {e}")))?;
+        let mut form = reqwest::multipart::Form::new()
+            .text("chat_id", chat_id.to_string())
+            .part(field, part);
+        if let Some(caption) = caption {
+            form = form.text("caption", caption.to_owned());
+        }
+        if let Some(reply_to) = reply_to {
+            form = form.text("reply_to_message_id", reply_to.to_string());
+        }
+
+        debug!(chat_id, method, filename, "uploading file");
+
+        let resp = self
+            .http
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let body: TelegramResponse<Message> = resp
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        if !body.ok {
+            let desc = body.description.unwrap_or_else(|| "unknown error".into());
+            return Err(ChannelError::SendFailed(desc));
+        }
+
+        body.result
+            .ok_or_else(|| ChannelError::SendFailed("missing result in response".into()))
+    }
+
     /// Verify the bot token by calling the `getMe` endpoint.
     ///
     /// Returns the bot's [`User`] info on success.
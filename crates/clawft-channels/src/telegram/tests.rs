@@ -6,11 +6,11 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use clawft_types::error::ChannelError;
-use clawft_types::event::{InboundMessage, OutboundMessage};
+use clawft_types::event::{Attachment, InboundMessage, OutboundMessage};
 
 use crate::traits::{Channel, ChannelFactory, ChannelHost, ChannelStatus, Command};
 
-use super::channel::{TelegramChannel, TelegramChannelFactory};
+use super::channel::{TELEGRAM_MAX_UPLOAD_BYTES, TelegramChannel, TelegramChannelFactory};
 use super::types;
 
 // ── Mock host ────────────────────────────────────────────────────────────
@@ -110,6 +110,7 @@ async fn send_rejects_non_numeric_chat_id() {
         content: "hello".into(),
         reply_to: None,
         media: vec![],
+        attachments: vec![],
         metadata: HashMap::new(),
     };
     let result = ch.send(&msg).await;
@@ -130,6 +131,7 @@ async fn send_rejects_non_numeric_reply_to() {
         content: "hello".into(),
         reply_to: Some("abc".into()),
         media: vec![],
+        attachments: vec![],
         metadata: HashMap::new(),
     };
     let result = ch.send(&msg).await;
@@ -141,6 +143,43 @@ async fn send_rejects_non_numeric_reply_to() {
     );
 }
 
+// ── send_attachment ──────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn send_attachment_over_limit_is_rejected_before_upload() {
+    let dir = std::env::temp_dir().join(format!("clawft-tg-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("video.mp4");
+    // Sparse file: reports the size without allocating it.
+    std::fs::File::create(&path)
+        .unwrap()
+        .set_len(TELEGRAM_MAX_UPLOAD_BYTES + 1)
+        .unwrap();
+
+    let ch = TelegramChannel::new("tok".into(), vec![]);
+    let msg = OutboundMessage {
+        channel: "telegram".into(),
+        chat_id: "42".into(),
+        content: String::new(),
+        reply_to: None,
+        media: vec![],
+        attachments: vec![],
+        metadata: HashMap::new(),
+    };
+    let att = Attachment::from_path(&path, "video/mp4");
+    let err = ch.send_attachment(&msg, &att).await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ChannelError::AttachmentTooLarge { ref filename, limit, .. }
+                if filename == "video.mp4" && limit == TELEGRAM_MAX_UPLOAD_BYTES
+        ),
+        "expected AttachmentTooLarge, got: {err:?}"
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 // ── factory ──────────────────────────────────────────────────────────────
 
 #[test]
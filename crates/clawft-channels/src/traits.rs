@@ -15,7 +15,7 @@ use async_trait::async_trait;
 use tokio_util::sync::CancellationToken;
 
 use clawft_types::error::ChannelError;
-use clawft_types::event::{InboundMessage, OutboundMessage};
+use clawft_types::event::{Attachment, InboundMessage, OutboundMessage};
 
 /// Metadata describing a channel plugin's capabilities.
 #[derive(Debug, Clone)]
@@ -80,7 +80,11 @@ pub struct MessageId(pub String);
 /// 2. The host calls [`start`](Channel::start) with an `Arc<dyn ChannelHost>`
 ///    and a [`CancellationToken`].
 /// 3. `start` is long-lived -- it runs until the token is cancelled.
-/// 4. The host calls [`send`](Channel::send) to push outbound messages.
+/// 4. The host calls [`send`](Channel::send) to push outbound messages,
+///    then [`send_attachment`](Channel::send_attachment) once per
+///    attachment. Channels that [support editing](Channel::supports_edit)
+///    may have a streaming reply updated in place via
+///    [`edit_message`](Channel::edit_message).
 #[async_trait]
 pub trait Channel: Send + Sync {
     /// Unique channel identifier (e.g., `"telegram"`, `"slack"`).
@@ -110,7 +114,48 @@ pub trait Channel: Send + Sync {
     ) -> Result<(), ChannelError>;
 
     /// Send an outbound message through this channel.
+    ///
+    /// Implementations send the text content only; attachments are
+    /// delivered separately through [`send_attachment`](Channel::send_attachment).
     async fn send(&self, msg: &OutboundMessage) -> Result<MessageId, ChannelError>;
+
+    /// Upload one attachment belonging to `msg` to `msg.chat_id`.
+    ///
+    /// Channels with file support override this and return
+    /// [`ChannelError::AttachmentTooLarge`] when the file exceeds the
+    /// platform limit. The default sends a text note that the attachment
+    /// was omitted.
+    async fn send_attachment(
+        &self,
+        msg: &OutboundMessage,
+        attachment: &Attachment,
+    ) -> Result<MessageId, ChannelError> {
+        self.send(&crate::attachment::omitted_note_message(msg, attachment))
+            .await
+    }
+
+    /// Whether [`edit_message`](Channel::edit_message) is supported.
+    ///
+    /// The host only updates a streaming reply in place on channels that
+    /// return `true`.
+    fn supports_edit(&self) -> bool {
+        false
+    }
+
+    /// Replace the text of message `id`, previously returned by
+    /// [`send`](Channel::send), with `msg.content`.
+    ///
+    /// The default returns an error.
+    async fn edit_message(
+        &self,
+        _id: &MessageId,
+        _msg: &OutboundMessage,
+    ) -> Result<(), ChannelError> {
+        Err(ChannelError::Other(format!(
+            "{} does not support editing messages",
+            self.name()
+        )))
+    }
 }
 
 /// Services the host exposes to channel plugins.
@@ -139,6 +184,11 @@ pub trait ChannelHost: Send + Sync {
         media: Vec<String>,
         metadata: HashMap<String, serde_json::Value>,
     ) -> Result<(), ChannelError>;
+
+    /// Report that `channel` reconnected to its upstream service.
+    ///
+    /// Used for metrics only; the default implementation ignores it.
+    async fn report_reconnect(&self, _channel: &str) {}
 }
 
 /// Factory for creating [`Channel`] instances from JSON configuration.
@@ -151,6 +201,15 @@ pub trait ChannelFactory: Send + Sync {
 
     /// Create a channel instance from its JSON config section.
     fn build(&self, config: &serde_json::Value) -> Result<Arc<dyn Channel>, ChannelError>;
+
+    /// Whether a running channel must be rebuilt when its config changes
+    /// from `old` to `new` during [`PluginHost::reload`](crate::PluginHost::reload).
+    ///
+    /// The default treats any difference as material. Factories can
+    /// override this to ignore settings the channel picks up live.
+    fn requires_restart(&self, old: &serde_json::Value, new: &serde_json::Value) -> bool {
+        old != new
+    }
 }
 
 #[cfg(test)]
@@ -205,6 +205,7 @@ mod tests {
             content: "Hello!".into(),
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
 
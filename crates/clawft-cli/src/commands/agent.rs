@@ -2,7 +2,11 @@
 //!
 //! In single-message mode (`--message "..."`), sends one prompt to the agent
 //! and prints the response. In interactive mode (no `--message`), reads from
-//! stdin in a REPL loop.
+//! stdin in a REPL loop with line editing and Tab completion. Slash commands
+//! (`/help`, `/model`, `/clear`, `/save`, `/load`, `/usage`, `/cancel`, ...)
+//! are handled by the REPL; `/fork` and `/plan` go to the agent loop, and
+//! other unknown commands only when `agents.interactive.forward_unknown_commands`
+//! is set.
 //!
 //! Messages are processed through the full 6-stage pipeline via [`AgentLoop`]:
 //! Classifier -> Router -> Assembler -> Transport -> Scorer -> Learner.
@@ -18,7 +22,8 @@
 //! weft agent
 //! > What is Rust?
 //! [agent response]
-//! > /exit
+//! > /model openai/gpt-4o-mini
+//! > /quit
 //!
 //! # Override model
 //! weft agent --model openai/gpt-4o -m "hello"
@@ -27,22 +32,33 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use chrono::Utc;
 use clap::Args;
-use tokio::io::AsyncBufReadExt;
+use tokio::sync::RwLock;
 use tracing::info;
 
-use clawft_core::agent::loop_core::AutoDelegation;
+use clawft_core::agent::loop_core::{AutoDelegation, FORKED_SESSION_KEY};
+use clawft_core::agent::sink::{ResponseSink, ResponseSinkFactory};
 use clawft_core::agent::skills_v2::SkillRegistry;
 use clawft_core::bootstrap::AppContext;
 use clawft_core::bus::MessageBus;
+use clawft_core::session::SessionManager;
 use clawft_platform::NativePlatform;
+use clawft_types::config::Config;
 use clawft_types::event::InboundMessage;
 
 use super::load_config;
-use crate::interactive::builtins::{register_builtins, register_skill_commands, QUIT_SENTINEL};
-use crate::interactive::registry::{InteractiveContext, SlashCommandRegistry};
+use crate::interactive::builtins::{
+    AGENT_COMMANDS, QUIT_SENTINEL, register_builtins, register_skill_commands,
+};
+use crate::interactive::completion::Completions;
+use crate::interactive::reader::{LineReader, ReadEvent};
+use crate::interactive::registry::{
+    InteractiveContext, SessionAction, SlashCommandRegistry, parse_slash,
+};
+use crate::interactive::session::apply_action;
 
 /// Arguments for the `weft agent` subcommand.
 #[derive(Args)]
@@ -70,6 +86,21 @@ pub struct AgentArgs {
     /// measure (SEC-SKILL-05).
     #[arg(long)]
     pub trust_project_skills: bool,
+
+    /// Continue an existing session (e.g. one created by `/fork` or
+    /// `weft sessions fork`) instead of the default CLI session.
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Lift the `agents.budget` limits (tokens, time, tool calls, daily
+    /// spend) for this session's turns.
+    #[arg(long)]
+    pub no_budget: bool,
+
+    /// Record a trace of every turn (as `agents.sessions.trace` does), for
+    /// `weft sessions inspect --trace`.
+    #[arg(long)]
+    pub trace: bool,
 }
 
 /// Run the agent command.
@@ -79,13 +110,16 @@ pub struct AgentArgs {
 /// loop processes messages through the full pipeline, including
 /// tool execution.
 pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
-    let platform = Arc::new(NativePlatform::new());
-    let mut config = load_config(&*platform, args.config.as_deref()).await?;
+    let mut config = load_config(&NativePlatform::new(), args.config.as_deref()).await?;
+    let platform = Arc::new(super::platform_for_config(&config)?);
 
     // Apply model override if provided.
     if let Some(ref model) = args.model {
         config.agents.defaults.model = model.clone();
     }
+    if args.trace {
+        config.agents.sessions.trace = true;
+    }
 
     let effective_model = &config.agents.defaults.model;
     info!(model = %effective_model, "initializing agent");
@@ -96,7 +130,18 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
         .map_err(|e| anyhow::anyhow!("bootstrap failed: {e}"))?;
 
     // Register core tools (built-in + MCP proxied + delegation).
-    super::register_core_tools(ctx.tools_mut(), &config, platform.clone()).await;
+    let memory = ctx.memory_backend().clone();
+    let questions = ctx.questions().clone();
+    let usage = ctx.usage().clone();
+    super::register_core_tools(
+        ctx.tools_mut(),
+        &config,
+        platform.clone(),
+        memory,
+        Some(questions),
+        Some(usage.clone()),
+    )
+    .await;
 
     // Register message tool (needs bus reference, cannot go in register_all).
     let bus_ref = ctx.bus().clone();
@@ -164,17 +209,45 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
         }
     };
 
-    // Clone the bus before consuming the context.
+    // Clone the bus and sessions before consuming the context.
     let bus = ctx.bus().clone();
+    let sessions = ctx.sessions().clone();
 
     // Convert context into the agent loop (consumes ctx).
-    let agent = ctx.into_agent_loop();
+    let mut agent = ctx.into_agent_loop();
+    if config.agents.skills.auto_activate {
+        agent = agent.with_skill_registry(Arc::new(RwLock::new(skill_registry.clone())));
+    }
 
     if let Some(ref message) = args.message {
-        return run_single_message(message, &bus, agent, effective_model).await;
+        return run_single_message(
+            message,
+            &bus,
+            agent,
+            effective_model,
+            args.session,
+            args.no_budget,
+        )
+        .await;
     }
 
-    run_interactive(&bus, agent, &tool_names, effective_model, &skill_registry).await
+    let mut interactive = InteractiveContext::new(effective_model.clone());
+    interactive.model_ids = model_ids(&config);
+    interactive.session = args.session;
+    interactive.usage = Some(usage);
+    interactive.tool_names = tool_names;
+
+    let agent = agent.with_response_sinks(Arc::new(StdoutSinks));
+    run_interactive(
+        &bus,
+        agent,
+        &sessions,
+        interactive,
+        &skill_registry,
+        args.no_budget,
+        config.agents.interactive.forward_unknown_commands,
+    )
+    .await
 }
 
 /// Process a single message through the agent loop and exit.
@@ -186,6 +259,8 @@ async fn run_single_message(
     bus: &Arc<MessageBus>,
     agent: clawft_core::agent::loop_core::AgentLoop<NativePlatform>,
     model: &str,
+    session: Option<String>,
+    no_budget: bool,
 ) -> anyhow::Result<()> {
     info!(model = %model, "single-message mode");
 
@@ -197,7 +272,8 @@ async fn run_single_message(
         content: message.to_owned(),
         timestamp: Utc::now(),
         media: vec![],
-        metadata: HashMap::new(),
+        attachments: vec![],
+        metadata: message_metadata(session.as_deref(), no_budget),
     };
     bus.publish_inbound(inbound)
         .map_err(|e| anyhow::anyhow!("failed to publish message: {e}"))?;
@@ -233,21 +309,28 @@ async fn run_single_message(
 /// Run an interactive REPL loop reading from stdin.
 ///
 /// Spawns the agent loop in the background, then reads user input
-/// line-by-line. Slash commands (including v2 skill activations) are
-/// dispatched locally via [`SlashCommandRegistry`]. All other input is
-/// published to the bus for the agent loop to process.
+/// line-by-line. Replies are printed as they stream (see [`StdoutSink`]).
+/// Slash commands (including v2 skill activations) are dispatched locally
+/// via [`SlashCommandRegistry`]; unknown ones are sent to the agent only
+/// when [`forward_to_agent`] allows it. All other input is published to
+/// the bus for the agent loop to process.
 ///
 /// When a skill is active, its `instructions` and `allowed_tools` are
-/// injected into the message metadata so the agent loop can use them.
+/// injected into the message metadata so the agent loop can use them, and
+/// a model picked with `/model` is pinned the same way. While a turn runs,
+/// `/cancel` or Ctrl-C cancels it. After `/fork` or `/load`, the REPL
+/// continues in the other session.
 async fn run_interactive(
     bus: &Arc<MessageBus>,
     agent: clawft_core::agent::loop_core::AgentLoop<NativePlatform>,
-    tool_names: &[String],
-    model: &str,
+    sessions: &SessionManager<NativePlatform>,
+    mut ctx: InteractiveContext,
     skill_registry: &SkillRegistry,
+    no_budget: bool,
+    forward_unknown: bool,
 ) -> anyhow::Result<()> {
     println!("weft agent -- interactive mode (type /help for commands)");
-    println!("Model: {model}");
+    println!("Model: {}", ctx.model);
 
     // Set up slash command registry with builtins.
     let mut cmd_registry = SlashCommandRegistry::new();
@@ -266,15 +349,27 @@ async fn run_interactive(
     }
     println!();
 
-    // Initialize interactive context.
-    let mut ctx = InteractiveContext::new(model.to_string());
-    ctx.tool_names = tool_names.to_vec();
     ctx.skill_names = skill_registry
         .names()
         .iter()
         .map(|s| s.to_string())
         .collect();
 
+    let mut input = LineReader::spawn(Completions {
+        commands: cmd_registry
+            .names()
+            .into_iter()
+            .chain(AGENT_COMMANDS.iter().copied())
+            .map(String::from)
+            .collect(),
+        models: ctx.model_ids.clone(),
+        skills: ctx.skill_names.clone(),
+        agents: ctx.agent_names.clone(),
+    })?;
+
+    // Ctrl-C and /cancel cancel the running turn instead of exiting.
+    let turns = agent.active_turns();
+
     // Spawn the agent loop in the background.
     let agent_handle = tokio::spawn(async move {
         if let Err(e) = agent.run().await {
@@ -282,48 +377,63 @@ async fn run_interactive(
         }
     });
 
-    let stdin = tokio::io::stdin();
-    let mut reader = tokio::io::BufReader::new(stdin).lines();
-
     loop {
-        eprint!("> ");
-        // Flush stderr so the prompt appears before blocking on read.
-        use std::io::Write;
-        std::io::stderr().flush().ok();
-
-        let line = match reader.next_line().await? {
-            Some(l) => l,
-            None => break, // EOF
+        // The editor reports Ctrl-C at a terminal prompt itself; the signal
+        // covers piped input, since registering a Ctrl-C handler replaces
+        // the default one.
+        let line = tokio::select! {
+            event = input.read_line("> ") => match event {
+                ReadEvent::Line(l) => l,
+                ReadEvent::Interrupted | ReadEvent::Eof => break,
+            },
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!();
+                break;
+            }
         };
-        let input = line.trim();
+        let input_line = line.trim();
 
-        if input.is_empty() {
+        if input_line.is_empty() {
             continue;
         }
 
-        // Dispatch through slash command registry (handles /help, /skills,
-        // /use, /tools, /status, /quit, and skill-contributed commands).
-        if input.starts_with('/')
-            && let Some(result) = cmd_registry.dispatch(input, &mut ctx)
-        {
-            match result {
-                Ok(output) => {
-                    if output == QUIT_SENTINEL {
-                        break;
+        // Dispatch through slash command registry (handles /help, /model,
+        // /clear, /save, /load, /usage, /quit, skill-contributed commands,
+        // ...). Session changes are carried out here once the command
+        // returns.
+        if let Some((name, _)) = parse_slash(input_line) {
+            if let Some(result) = cmd_registry.dispatch(input_line, &mut ctx) {
+                let result = match (result, ctx.action.take()) {
+                    (Ok(output), Some(action)) => apply_action(action, &mut ctx, sessions, &turns)
+                        .await
+                        .map(|()| output),
+                    (result, _) => result,
+                };
+                match result {
+                    Ok(output) => {
+                        if output == QUIT_SENTINEL {
+                            break;
+                        }
+                        println!("{output}");
+                        println!();
+                    }
+                    Err(e) => {
+                        eprintln!("error: {e}");
                     }
-                    println!("{output}");
-                    println!();
-                }
-                Err(e) => {
-                    eprintln!("error: {e}");
                 }
+                continue;
+            }
+            if !forward_to_agent(name, forward_unknown) {
+                eprintln!("error: unknown command /{name} (type /help for commands)");
+                continue;
             }
-            continue;
-            // Unknown slash command -- fall through to send to agent.
         }
 
         // Build metadata with active skill info for the agent loop.
-        let mut metadata = HashMap::new();
+        let mut metadata = message_metadata(ctx.session.as_deref(), no_budget);
+        if let Some(model) = ctx.pinned_model() {
+            metadata.insert(InboundMessage::MODEL_KEY.into(), serde_json::json!(model));
+        }
         if !ctx.active_skill.is_empty()
             && let Some(skill) = skill_registry.get(&ctx.active_skill)
         {
@@ -346,9 +456,10 @@ async fn run_interactive(
             channel: "cli".into(),
             sender_id: "local".into(),
             chat_id: "cli-session".into(),
-            content: input.to_owned(),
+            content: input_line.to_owned(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata,
         };
 
@@ -357,11 +468,53 @@ async fn run_interactive(
             break;
         }
 
-        // Wait for the outbound response.
-        match bus.consume_outbound().await {
+        // Wait for the outbound response, cancelling the turn on Ctrl-C or
+        // `/cancel`. Other lines typed meanwhile wait for the next prompt.
+        // Streamed replies are already on screen; only the line needs
+        // ending.
+        input.watch();
+        let outbound = loop {
+            let typed = tokio::select! {
+                msg = bus.consume_outbound() => break msg,
+                line = input.next_watched() => line,
+                _ = tokio::signal::ctrl_c() => {
+                    if turns.cancel_all() > 0 {
+                        eprintln!("\n[cancelling...]");
+                    }
+                    continue;
+                }
+            };
+            if matches!(parse_slash(&typed), Some(("cancel", _))) {
+                match apply_action(SessionAction::Cancel, &mut ctx, sessions, &turns).await {
+                    Ok(()) => eprintln!("[cancelling...]"),
+                    Err(e) => eprintln!("error: {e}"),
+                }
+            } else if !typed.trim().is_empty() {
+                input.requeue(typed);
+            }
+        };
+        input.end_watch().await;
+        match outbound {
+            Some(msg) if msg.is_cancelled() => {
+                println!();
+                println!("[turn cancelled]");
+                println!();
+            }
+            Some(msg) if msg.is_streamed() => {
+                println!();
+                println!();
+            }
             Some(msg) => {
                 println!("{}", msg.content);
                 println!();
+                if let Some(fork) = msg
+                    .metadata
+                    .get(FORKED_SESSION_KEY)
+                    .and_then(|v| v.as_str())
+                {
+                    eprintln!("[session] continuing in {fork}");
+                    ctx.session = Some(fork.to_string());
+                }
             }
             None => {
                 eprintln!("error: agent loop closed unexpectedly");
@@ -378,25 +531,56 @@ async fn run_interactive(
     Ok(())
 }
 
+/// Whether the unknown slash command `/name` is sent to the agent as a
+/// message: always for the commands the agent loop handles itself, and for
+/// any other when `forward_unknown` is set.
+fn forward_to_agent(name: &str, forward_unknown: bool) -> bool {
+    forward_unknown || AGENT_COMMANDS.contains(&name)
+}
+
+/// Models offered by `/model` and its completion: the default, then the
+/// routing tiers' models and the fallback, without repeats.
+fn model_ids(config: &Config) -> Vec<String> {
+    let mut ids = vec![config.agents.defaults.model.clone()];
+    let tiers = config.routing.tiers.iter().flat_map(|t| t.models.iter());
+    for id in tiers.chain(config.routing.fallback_model.iter()) {
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+    ids
+}
+
 /// Build a [`WebSearchConfig`] from the tools configuration.
 ///
-/// Maps the `ToolsConfig.web.search` fields (api_key, max_results) into the
-/// web search tool's configuration struct. Resolves the API key from the
-/// environment variable `BRAVE_SEARCH_API_KEY` if the config value is empty.
+/// Maps the `ToolsConfig.web.search` fields (provider, fallback chain,
+/// per-provider keys and base URLs, max_results) into the web search
+/// tool's configuration struct. Resolves the Brave API key from the
+/// environment variable `BRAVE_SEARCH_API_KEY` and the Google key from
+/// `GOOGLE_SEARCH_API_KEY` if the config values are empty.
 pub(crate) fn build_web_search_config(
     config: &clawft_types::config::ToolsConfig,
 ) -> clawft_tools::web_search::WebSearchConfig {
     let search = &config.web.search;
-    let api_key = if search.api_key.is_empty() {
-        std::env::var("BRAVE_SEARCH_API_KEY").ok()
-    } else {
-        Some(search.api_key.expose().to_owned())
+    let secret = |value: &clawft_types::secret::SecretString, env: &str| {
+        if value.is_empty() {
+            std::env::var(env).ok()
+        } else {
+            Some(value.expose().to_owned())
+        }
     };
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_owned());
 
     clawft_tools::web_search::WebSearchConfig {
-        api_key,
+        api_key: secret(&search.api_key, "BRAVE_SEARCH_API_KEY"),
         endpoint: None, // Custom endpoint support can be added to config later.
         max_results: search.max_results,
+        provider: search.provider,
+        fallback_chain: search.fallback_chain.clone(),
+        google_api_key: secret(&search.google.api_key, "GOOGLE_SEARCH_API_KEY"),
+        google_cx: non_empty(&search.google.cx),
+        searxng_url: non_empty(&search.searxng.base_url),
+        duckduckgo_url: non_empty(&search.duckduckgo.base_url),
     }
 }
 
@@ -435,11 +619,27 @@ pub(crate) fn build_url_policy(
     )
 }
 
+/// Inbound metadata routing the message to `session`, when one is set,
+/// and lifting the turn budget when `no_budget` is set.
+fn message_metadata(session: Option<&str>, no_budget: bool) -> HashMap<String, serde_json::Value> {
+    let mut metadata = HashMap::new();
+    if let Some(key) = session {
+        metadata.insert(InboundMessage::SESSION_KEY.into(), serde_json::json!(key));
+    }
+    if no_budget {
+        metadata.insert(
+            InboundMessage::NO_BUDGET_KEY.into(),
+            serde_json::json!(true),
+        );
+    }
+    metadata
+}
+
 /// Discover workspace and user skill directories for v2 skill loading.
 ///
 /// Walks upward from `cwd` to find `.clawft/skills/` (workspace) and
 /// checks `~/.clawft/skills/` (user). Returns `(workspace_dir, user_dir)`.
-fn discover_skill_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
+pub(crate) fn discover_skill_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
     let user_dir = dirs::home_dir().map(|h| h.join(".clawft").join("skills"));
 
     // Walk upward from cwd to find .clawft/skills/
@@ -495,6 +695,42 @@ impl AutoDelegation for AutoDelegationRouter {
     }
 }
 
+/// Opens a [`StdoutSink`] for messages from the interactive REPL.
+struct StdoutSinks;
+
+impl ResponseSinkFactory for StdoutSinks {
+    fn open(&self, msg: &InboundMessage) -> Option<Arc<dyn ResponseSink>> {
+        (msg.channel == "cli").then(|| Arc::new(StdoutSink::default()) as Arc<dyn ResponseSink>)
+    }
+}
+
+/// Prints a reply to stdout as it streams, and tool calls to stderr.
+#[derive(Default)]
+struct StdoutSink {
+    /// Whether text was printed since the last newline.
+    mid_line: AtomicBool,
+}
+
+impl ResponseSink for StdoutSink {
+    fn on_text_delta(&self, delta: &str) {
+        use std::io::Write;
+        print!("{delta}");
+        std::io::stdout().flush().ok();
+        if let Some(last) = delta.chars().last() {
+            self.mid_line.store(last != '\n', Ordering::Relaxed);
+        }
+    }
+
+    fn on_tool_call_started(&self, _id: &str, name: &str) {
+        if self.mid_line.swap(false, Ordering::Relaxed) {
+            println!();
+        }
+        eprintln!("[tool] {name}");
+    }
+
+    fn on_complete(&self, _text: &str) {}
+}
+
 /// Build an [`AutoDelegation`] router from delegation config.
 #[cfg(feature = "delegate")]
 fn build_auto_delegation(
@@ -517,6 +753,9 @@ mod tests {
             config: None,
             intelligent_routing: false,
             trust_project_skills: false,
+            session: None,
+            no_budget: false,
+            trace: false,
         };
         assert!(args.message.is_none());
         assert!(args.model.is_none());
@@ -531,6 +770,9 @@ mod tests {
             config: None,
             intelligent_routing: false,
             trust_project_skills: false,
+            session: None,
+            no_budget: false,
+            trace: false,
         };
         assert_eq!(args.message.as_deref(), Some("test message"));
     }
@@ -543,6 +785,9 @@ mod tests {
             config: None,
             intelligent_routing: false,
             trust_project_skills: false,
+            session: None,
+            no_budget: false,
+            trace: false,
         };
         assert_eq!(args.model.as_deref(), Some("openai/gpt-4"));
     }
@@ -555,10 +800,47 @@ mod tests {
             config: Some("/tmp/test-config.json".into()),
             intelligent_routing: false,
             trust_project_skills: false,
+            session: None,
+            no_budget: false,
+            trace: false,
         };
         assert_eq!(args.config.as_deref(), Some("/tmp/test-config.json"));
     }
 
+    #[test]
+    fn message_metadata_sets_overrides() {
+        assert!(message_metadata(None, false).is_empty());
+        let metadata = message_metadata(Some("cli:cli-session:fork-1a2b3c4d"), true);
+        assert_eq!(metadata[InboundMessage::NO_BUDGET_KEY], true);
+        assert_eq!(
+            metadata[InboundMessage::SESSION_KEY],
+            "cli:cli-session:fork-1a2b3c4d"
+        );
+    }
+
+    #[test]
+    fn only_agent_commands_are_forwarded_by_default() {
+        assert!(forward_to_agent("fork", false));
+        assert!(forward_to_agent("plan", false));
+        assert!(!forward_to_agent("deploy", false));
+        assert!(forward_to_agent("deploy", true));
+    }
+
+    #[test]
+    fn model_ids_lists_default_tiers_and_fallback_once() {
+        let mut config = Config::default();
+        config.agents.defaults.model = "openai/gpt-4o".into();
+        config.routing.tiers = vec![clawft_types::routing::ModelTierConfig {
+            name: "fast".into(),
+            models: vec!["groq/llama-3.1-8b".into(), "openai/gpt-4o".into()],
+            complexity_range: [0.0, 1.0],
+            cost_per_1k_tokens: 0.0,
+            max_context_tokens: 8192,
+        }];
+        config.routing.fallback_model = Some("groq/llama-3.1-8b".into());
+        assert_eq!(model_ids(&config), ["openai/gpt-4o", "groq/llama-3.1-8b"]);
+    }
+
     #[test]
     fn discover_skill_dirs_returns_pair() {
         // Smoke test: discovery should not panic, and returns a tuple.
@@ -421,6 +421,7 @@ mod tests {
             allowed_tools: vec![],
             max_turns: None,
             variables: HashMap::new(),
+            templates: HashMap::new(),
             source_path: None,
         }
     }
@@ -443,6 +444,7 @@ mod tests {
         let agent = AgentDefinition {
             name: "test".into(),
             description: "test".into(),
+            templates: HashMap::new(),
             source_path: Some(PathBuf::from("/project/.clawft/agents/test")),
             model: None,
             system_prompt: None,
@@ -460,6 +462,7 @@ mod tests {
         let agent = AgentDefinition {
             name: "test".into(),
             description: "test".into(),
+            templates: HashMap::new(),
             source_path: Some(PathBuf::from("/home/user/.clawft/agents/test")),
             model: None,
             system_prompt: None,
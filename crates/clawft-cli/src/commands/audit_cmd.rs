@@ -0,0 +1,205 @@
+//! `weft audit` -- view the tool execution audit log.
+//!
+//! Reads the records the agent loop writes for every tool call (see
+//! `tools.audit` in the config): `~/.clawft/state/audit.jsonl`, or the
+//! `tool_audit` table in `sessions.db` with the SQLite session backend.
+//!
+//! # Examples
+//!
+//! ```text
+//! weft audit tail
+//! weft audit tail -n 50 --tool exec
+//! weft audit tail --session telegram:12345 --follow
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use comfy_table::{Table, presets::UTF8_FULL};
+
+use clawft_core::session::SessionManager;
+use clawft_core::tools::audit::{AuditFilter, AuditRecord, AuditStore, open_store};
+use clawft_platform::NativePlatform;
+use clawft_types::config::{Config, SessionBackend};
+
+/// How often `--follow` polls for new records.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Open the audit store the agent loop writes to.
+///
+/// Opened even when `tools.audit.enabled` is off, so older records stay
+/// readable.
+pub async fn open_audit_store(config: &Config) -> anyhow::Result<Arc<dyn AuditStore>> {
+    let platform = Arc::new(NativePlatform::new());
+    let sessions = SessionManager::from_config(platform, &config.agents.sessions).await?;
+    let home =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+
+    let mut audit = config.tools.audit.clone();
+    audit.enabled = true;
+    let sqlite = config.agents.sessions.backend == SessionBackend::Sqlite;
+    open_store(&audit, sqlite, sessions.sessions_dir(), &home)?
+        .ok_or_else(|| anyhow::anyhow!("tool audit log unavailable"))
+}
+
+/// Print the last `limit` audit records passing `filter`, then keep
+/// printing new ones when `follow` is set.
+pub async fn audit_tail(
+    filter: AuditFilter,
+    limit: usize,
+    follow: bool,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let store = open_audit_store(config).await?;
+    let records = store.recent(&filter, limit).await?;
+    if records.is_empty() && !follow {
+        println!("No tool executions recorded.");
+        return Ok(());
+    }
+
+    let mut last = records.last().map(|r| r.timestamp);
+    for record in &records {
+        println!("{}", format_line(record));
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        let records = store.recent(&filter, limit.max(100)).await?;
+        let since = last;
+        for record in records
+            .iter()
+            .filter(|r| since.is_none_or(|t| r.timestamp > t))
+        {
+            println!("{}", format_line(record));
+            last = Some(record.timestamp);
+        }
+    }
+}
+
+/// One record as a single line, in `weft audit tail` format.
+fn format_line(record: &AuditRecord) -> String {
+    format!(
+        "{}  {}  {}  {}  {}ms  {}  {}  {}",
+        record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        record.agent_id,
+        record.session_key,
+        record.tool,
+        record.duration_ms,
+        status(record),
+        format_bytes(record.result_bytes),
+        record.args,
+    )
+}
+
+/// A table of audit records for `weft sessions inspect --tools`.
+pub fn audit_table(records: &[AuditRecord]) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header([
+        "TIME", "AGENT", "TOOL", "DURATION", "STATUS", "RESULT", "ARGS",
+    ]);
+    for record in records {
+        table.add_row([
+            record.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            record.agent_id.clone(),
+            record.tool.clone(),
+            format!("{}ms", record.duration_ms),
+            status(record),
+            format_bytes(record.result_bytes),
+            args_preview(&record.args, 60),
+        ]);
+    }
+    table
+}
+
+/// `ok`, or `error: <message>`.
+fn status(record: &AuditRecord) -> String {
+    match &record.error {
+        Some(error) if !record.success => format!("error: {error}"),
+        _ if !record.success => "error".into(),
+        _ => "ok".into(),
+    }
+}
+
+/// Human-readable byte count.
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes}B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1}KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1}MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// The arguments as compact JSON, cut to `max_len` characters.
+pub fn args_preview(args: &serde_json::Value, max_len: usize) -> String {
+    let text = args.to_string();
+    if text.chars().count() <= max_len {
+        text
+    } else {
+        let cut: String = text.chars().take(max_len).collect();
+        format!("{cut}...")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record(success: bool, error: Option<&str>) -> AuditRecord {
+        AuditRecord {
+            timestamp: chrono::Utc
+                .with_ymd_and_hms(2025, 6, 15, 14, 30, 0)
+                .unwrap(),
+            agent_id: "default".into(),
+            session_key: "telegram:1".into(),
+            tool: "web_fetch".into(),
+            args: serde_json::json!({"url": "https://example.com", "token": "[REDACTED]"}),
+            duration_ms: 42,
+            success,
+            error: error.map(String::from),
+            result_bytes: 2048,
+        }
+    }
+
+    #[test]
+    fn line_format_includes_every_field() {
+        let line = format_line(&record(true, None));
+        assert!(
+            line.starts_with(
+                "2025-06-15 14:30:00  default  telegram:1  web_fetch  42ms  ok  2.0KB"
+            )
+        );
+        assert!(line.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn status_shows_error_message() {
+        assert_eq!(status(&record(true, None)), "ok");
+        assert_eq!(
+            status(&record(false, Some("timeout after 30s"))),
+            "error: timeout after 30s"
+        );
+        assert_eq!(status(&record(false, None)), "error");
+    }
+
+    #[test]
+    fn format_bytes_units() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(1536), "1.5KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0MB");
+    }
+
+    #[test]
+    fn args_preview_truncates() {
+        let args = serde_json::json!({"content": "x".repeat(100)});
+        let preview = args_preview(&args, 20);
+        assert_eq!(preview.chars().count(), 23);
+        assert!(preview.ends_with("..."));
+    }
+}
@@ -1,18 +1,47 @@
 //! `weft channels` -- inspect channel configuration status.
 //!
 //! Reads the configuration and displays a table summarizing which
-//! channels are enabled, and whether credentials are present.
+//! channels are enabled, and whether credentials are present. When a
+//! gateway with the API enabled is running, live per-channel traffic
+//! metrics are fetched from it and shown in a second table.
+//!
+//! `weft channels reload` signals a running gateway (SIGHUP) to re-read
+//! its config and restart only the channels whose settings changed.
 //!
 //! # Example
 //!
 //! ```text
 //! weft channels status
+//! weft channels reload
 //! ```
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use comfy_table::{Table, presets::UTF8_FULL};
+use serde::Deserialize;
 
+use clawft_platform::Platform;
 use clawft_types::config::Config;
 
+/// How long to wait for a running gateway to answer.
+const LIVE_METRICS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Per-channel counters as served by the gateway's
+/// `/api/channels/metrics` endpoint.
+#[derive(Debug, Deserialize)]
+struct LiveChannelMetrics {
+    channel: String,
+    #[serde(default)]
+    status: Option<String>,
+    inbound_received: u64,
+    outbound_sent: u64,
+    send_failures: u64,
+    reconnects: u64,
+    #[serde(default)]
+    last_inbound: Option<String>,
+}
+
 /// Display a table of channel status from the given configuration.
 pub fn channels_status(config: &Config) {
     let mut table = Table::new();
@@ -83,6 +112,116 @@ pub fn channels_status(config: &Config) {
     println!("{table}");
 }
 
+/// Fetch and display live channel metrics from a running gateway.
+///
+/// Prints a short note instead when the API is disabled or the gateway
+/// cannot be reached.
+pub async fn channels_live_metrics<P: Platform>(platform: &P, config: &Config) {
+    if !config.gateway.api_enabled {
+        println!("Live metrics unavailable: gateway.api_enabled is false.");
+        return;
+    }
+    let url = metrics_url(config);
+    let headers = HashMap::new();
+    let fetch = platform.http().get(&url, &headers);
+    let metrics = match tokio::time::timeout(LIVE_METRICS_TIMEOUT, fetch).await {
+        Ok(Ok(resp)) if resp.is_success() => resp.json::<Vec<LiveChannelMetrics>>().ok(),
+        _ => None,
+    };
+    match metrics {
+        Some(metrics) => println!("{}", live_metrics_table(&metrics)),
+        None => println!("Live metrics unavailable: no gateway answering at {url}."),
+    }
+}
+
+/// Signal the running gateway to reload its channels.
+///
+/// The gateway's pid is read from the file it writes at startup
+/// ([`pid_file_path`](super::gateway::pid_file_path)).
+pub fn channels_reload() -> anyhow::Result<()> {
+    let path = super::gateway::pid_file_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        anyhow::anyhow!(
+            "no running gateway found ({}: {e}); start one with `weft gateway`",
+            path.display()
+        )
+    })?;
+    if contents.trim().is_empty() {
+        anyhow::bail!("no running gateway found; start one with `weft gateway`");
+    }
+    let pid = parse_pid(&contents)
+        .ok_or_else(|| anyhow::anyhow!("invalid pid file {}", path.display()))?;
+    send_hangup(pid)?;
+    println!("Sent reload signal to gateway (pid {pid}).");
+    Ok(())
+}
+
+/// Parse the contents of a gateway pid file.
+fn parse_pid(contents: &str) -> Option<u32> {
+    contents.trim().parse().ok().filter(|&pid| pid > 0)
+}
+
+#[cfg(unix)]
+fn send_hangup(pid: u32) -> anyhow::Result<()> {
+    let status = std::process::Command::new("kill")
+        .args(["-HUP", &pid.to_string()])
+        .status()
+        .map_err(|e| anyhow::anyhow!("failed to run kill: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("gateway (pid {pid}) is not running; remove the stale pid file");
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_hangup(_pid: u32) -> anyhow::Result<()> {
+    anyhow::bail!("channel reload via signal is only supported on Unix")
+}
+
+/// URL of the metrics endpoint for the configured gateway.
+fn metrics_url(config: &Config) -> String {
+    api_url(config, "/api/channels/metrics")
+}
+
+/// URL of `path` on the configured gateway's API, reached over loopback
+/// when the gateway listens on every interface.
+pub(super) fn api_url(config: &Config, path: &str) -> String {
+    let host = match config.gateway.host.as_str() {
+        "0.0.0.0" | "" => "127.0.0.1",
+        "::" => "[::1]",
+        h => h,
+    };
+    format!("http://{host}:{}{path}", config.gateway.api_port)
+}
+
+/// Render live metrics as a table.
+fn live_metrics_table(metrics: &[LiveChannelMetrics]) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header([
+        "CHANNEL",
+        "STATUS",
+        "INBOUND",
+        "OUTBOUND",
+        "SEND FAILURES",
+        "RECONNECTS",
+        "LAST INBOUND",
+    ]);
+    for m in metrics {
+        table.add_row([
+            m.channel.clone(),
+            m.status.clone().unwrap_or_else(|| "-".into()),
+            m.inbound_received.to_string(),
+            m.outbound_sent.to_string(),
+            m.send_failures.to_string(),
+            m.reconnects.to_string(),
+            m.last_inbound.clone().unwrap_or_else(|| "never".into()),
+        ]);
+    }
+    table
+}
+
 /// Add a row for a known channel.
 fn add_row(table: &mut Table, name: &str, enabled: bool, has_credentials: bool) {
     let enabled_str = if enabled { "yes" } else { "no" };
@@ -172,6 +311,45 @@ mod tests {
         assert!(rendered.contains("no"));
     }
 
+    #[test]
+    fn parse_pid_accepts_trailing_newline() {
+        assert_eq!(parse_pid("4242\n"), Some(4242));
+        assert_eq!(parse_pid("0"), None);
+        assert_eq!(parse_pid("not-a-pid"), None);
+    }
+
+    #[test]
+    fn metrics_url_uses_loopback_for_wildcard_host() {
+        let mut cfg = default_config();
+        cfg.gateway.api_port = 9000;
+        assert_eq!(
+            metrics_url(&cfg),
+            "http://127.0.0.1:9000/api/channels/metrics"
+        );
+        cfg.gateway.host = "10.0.0.5".into();
+        assert_eq!(
+            metrics_url(&cfg),
+            "http://10.0.0.5:9000/api/channels/metrics"
+        );
+    }
+
+    #[test]
+    fn live_metrics_table_renders_counters() {
+        let metrics: Vec<LiveChannelMetrics> = serde_json::from_value(serde_json::json!([
+            {"channel": "telegram", "status": "running", "inbound_received": 12,
+             "outbound_sent": 10, "send_failures": 2, "reconnects": 1,
+             "last_inbound": "2026-01-01T00:00:00Z"},
+            {"channel": "irc", "inbound_received": 0, "outbound_sent": 0,
+             "send_failures": 0, "reconnects": 0}
+        ]))
+        .unwrap();
+        let rendered = live_metrics_table(&metrics).to_string();
+        assert!(rendered.contains("telegram"));
+        assert!(rendered.contains("running"));
+        assert!(rendered.contains("12"));
+        assert!(rendered.contains("never"));
+    }
+
     #[test]
     fn channels_status_with_slack_credentials() {
         let mut cfg = default_config();
@@ -0,0 +1,246 @@
+//! `weft __complete <kind>` -- candidate values for shell completion.
+//!
+//! Hidden from help; the scripts printed by `weft completions` call it to
+//! complete session ids, agent names, cron job ids, skill names and
+//! channel names. Candidates are printed one per line, read straight from
+//! the stores rather than through a running gateway so completion stays
+//! fast. When no config file is found, or a store cannot be read, nothing
+//! is printed: a broken setup must never garble the command line.
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use clap::ValueEnum;
+
+use clawft_core::agent::skills_v2::SkillRegistry;
+use clawft_core::session::SessionManager;
+use clawft_platform::{NativePlatform, Platform};
+use clawft_types::config::Config;
+
+/// What to complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompleteKind {
+    /// Session ids from the session store.
+    Sessions,
+    /// Names of the defined agents.
+    Agents,
+    /// Ids of the stored cron jobs.
+    CronJobs,
+    /// Names of the installed skills.
+    Skills,
+    /// Names of the enabled channels.
+    Channels,
+}
+
+/// Print the candidates for `kind`, one per line.
+pub async fn run(kind: CompleteKind) -> anyhow::Result<()> {
+    let platform = NativePlatform::new();
+    if super::discover_config_path(&platform).is_none() {
+        return Ok(());
+    }
+    let Ok(config) = super::load_config(&platform, None).await else {
+        return Ok(());
+    };
+
+    let candidates = match kind {
+        CompleteKind::Sessions => {
+            match SessionManager::from_config(Arc::new(platform), &config.agents.sessions).await {
+                Ok(sessions) => session_ids(&sessions).await,
+                Err(_) => Vec::new(),
+            }
+        }
+        CompleteKind::Agents => agent_names(&config),
+        #[cfg(feature = "services")]
+        CompleteKind::CronJobs => cron_job_ids(&super::cron::cron_store_path()),
+        #[cfg(not(feature = "services"))]
+        CompleteKind::CronJobs => Vec::new(),
+        CompleteKind::Skills => {
+            let (ws_dir, user_dir) = super::skills_cmd::discover_skill_dirs();
+            skill_names(ws_dir.as_deref(), user_dir.as_deref()).await
+        }
+        CompleteKind::Channels => channel_names(&config),
+    };
+
+    // Write errors (the shell closing the pipe early) are not worth a panic.
+    let mut out = std::io::stdout().lock();
+    for candidate in candidates {
+        if writeln!(out, "{candidate}").is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Keys of the live sessions in `sessions`.
+async fn session_ids<P: Platform>(sessions: &SessionManager<P>) -> Vec<String> {
+    sessions.list_sessions().await.unwrap_or_default()
+}
+
+/// Names of the agents defined for `config`.
+fn agent_names(config: &Config) -> Vec<String> {
+    clawft_core::bootstrap::discover_agents(config)
+        .map(|registry| registry.list().iter().map(|a| a.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Ids of the jobs in the cron store at `path`, sorted.
+#[cfg(feature = "services")]
+fn cron_job_ids(path: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = clawft_services::cron_service::storage::load_jobs_sync(path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|job| job.id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Names of the skills in the workspace and user skill directories.
+async fn skill_names(ws_dir: Option<&Path>, user_dir: Option<&Path>) -> Vec<String> {
+    match SkillRegistry::discover(ws_dir, user_dir, Vec::new()).await {
+        Ok(registry) => {
+            let mut names: Vec<String> = registry.names().into_iter().map(String::from).collect();
+            names.sort();
+            names
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Names of the channels enabled in `config`, built-in or plugin.
+fn channel_names(config: &Config) -> Vec<String> {
+    let Ok(serde_json::Value::Object(channels)) = serde_json::to_value(&config.channels) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = channels
+        .into_iter()
+        .filter(|(_, channel)| channel.get("enabled") == Some(&serde_json::Value::Bool(true)))
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use clawft_types::session::Session;
+
+    /// A fresh directory for one test's fixture state.
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clawft_complete_{name}_{}_{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn sessions_come_from_the_store() {
+        let dir = fixture_dir("sessions");
+        let sessions = SessionManager::with_dir(Arc::new(NativePlatform::new()), dir.clone());
+        for key in ["telegram:2", "slack:C1", "telegram:1"] {
+            sessions.save_session(&Session::new(key)).await.unwrap();
+        }
+        // A corrupt file is still offered: completion does not read sessions.
+        std::fs::write(dir.join("cli%3Abroken.jsonl"), "not json").unwrap();
+
+        assert_eq!(
+            session_ids(&sessions).await,
+            ["cli:broken", "slack:C1", "telegram:1", "telegram:2"]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn missing_session_store_offers_nothing() {
+        let dir = fixture_dir("no_sessions").join("missing");
+        let sessions = SessionManager::with_dir(Arc::new(NativePlatform::new()), dir);
+        assert!(session_ids(&sessions).await.is_empty());
+    }
+
+    #[test]
+    fn agents_come_from_the_workspace() {
+        let dir = fixture_dir("agents");
+        let agents = dir.join("agents");
+        std::fs::create_dir_all(&agents).unwrap();
+        std::fs::write(
+            agents.join("reviewer.yaml"),
+            "name: reviewer\ndescription: Reviews changes\n",
+        )
+        .unwrap();
+        let mut config = Config::default();
+        config.agents.defaults.workspace = dir.display().to_string();
+
+        assert!(agent_names(&config).contains(&"reviewer".to_string()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "services")]
+    #[test]
+    fn cron_jobs_come_from_the_store() {
+        let dir = fixture_dir("cron");
+        let path = dir.join("cron.jsonl");
+        let event = |id: &str| {
+            serde_json::json!({
+                "type": "create",
+                "job": {
+                    "id": id,
+                    "name": id,
+                    "enabled": true,
+                    "schedule": {"kind": "every", "every_ms": 60000},
+                    "payload": {"message": "hi"},
+                    "state": {},
+                    "created_at": "2026-01-01T00:00:00Z",
+                    "updated_at": "2026-01-01T00:00:00Z",
+                    "delete_after_run": false
+                }
+            })
+            .to_string()
+        };
+        std::fs::write(&path, format!("{}\n{}\n", event("job-b"), event("job-a"))).unwrap();
+
+        assert_eq!(cron_job_ids(&path), ["job-a", "job-b"]);
+        assert!(cron_job_ids(&dir.join("missing.jsonl")).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn skills_come_from_both_directories() {
+        let dir = fixture_dir("skills");
+        for (root, name) in [("ws", "release"), ("user", "triage")] {
+            let skill = dir.join(root).join(name);
+            std::fs::create_dir_all(&skill).unwrap();
+            std::fs::write(
+                skill.join("SKILL.md"),
+                format!("---\nname: {name}\ndescription: {name} skill\n---\n\nDo {name}."),
+            )
+            .unwrap();
+        }
+
+        let names = skill_names(Some(&dir.join("ws")), Some(&dir.join("user"))).await;
+        assert_eq!(names, ["release", "triage"]);
+        assert!(skill_names(None, None).await.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_enabled_channels_are_offered() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "channels": {
+                "telegram": {"enabled": true},
+                "slack": {"enabled": false},
+                "discord": {"enabled": true},
+                "matrix": {"enabled": true}
+            }
+        }))
+        .unwrap();
+        assert_eq!(channel_names(&config), ["discord", "matrix", "telegram"]);
+        assert!(channel_names(&Config::default()).is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 //! `weft config` -- display resolved configuration.
 //!
 //! Shows the full resolved configuration as formatted JSON, or a specific
-//! section by name.
+//! section by name. `weft config reload` asks a running gateway to re-read
+//! its config over the control socket (see [`super::control`]), and
+//! `weft config validate` checks the file (see [`super::config_validate`]).
 //!
 //! # Examples
 //!
@@ -9,10 +11,14 @@
 //! weft config show
 //! weft config section agents
 //! weft config section gateway
+//! weft config reload
+//! weft config validate --strict
 //! ```
 
 use clawft_types::config::Config;
 
+use super::control::{self, ControlRequest, ControlResponse};
+
 /// Display the resolved configuration as formatted JSON.
 pub fn config_show(config: &Config) {
     match serde_json::to_string_pretty(config) {
@@ -40,6 +46,32 @@ pub fn config_section(config: &Config, section: &str) {
     }
 }
 
+/// Ask the running gateway to reload its config, and print what changed.
+pub async fn config_reload() -> anyhow::Result<()> {
+    let path =
+        control::socket_path().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+    let report = match control::send(&path, &ControlRequest::ReloadConfig).await? {
+        ControlResponse::Reloaded { report } => report,
+        ControlResponse::Error { message } => anyhow::bail!("reload failed: {message}"),
+        other => anyhow::bail!("unexpected answer from the gateway: {other:?}"),
+    };
+
+    if report.is_empty() {
+        println!("Config unchanged.");
+        return Ok(());
+    }
+    for section in &report.applied {
+        println!("  applied   {section}");
+    }
+    for section in &report.channels {
+        println!("  channels  {section}");
+    }
+    for section in &report.restart_required {
+        println!("  restart   {section} (needs a gateway restart)");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
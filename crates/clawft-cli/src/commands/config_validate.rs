@@ -0,0 +1,801 @@
+//! `weft config validate` -- check a config file for mistakes.
+//!
+//! Fields clawft does not know are ignored when the config loads, so a
+//! misspelled key silently leaves its setting at the default. Validation
+//! parses the file again, reporting every ignored field (with the nearest
+//! known name), then checks what parsing cannot: enabled channels without
+//! credentials, routing rules naming undefined agents, MCP servers with
+//! both or neither transport, malformed model strings, quiet hours, and
+//! the routing tiers (see [`clawft_core::routing_validation`]). With the
+//! `services` feature, the schedules of the stored cron jobs are checked
+//! too.
+//!
+//! Each finding names its place in the file as a JSON pointer
+//! (RFC 6901) using the keys as written, e.g. `/channels/slack/botToken`.
+//!
+//! # Examples
+//!
+//! ```text
+//! weft config validate
+//! weft config validate --config ./config.json --strict
+//! weft config validate --json
+//! ```
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use clawft_core::routing_validation::{
+    ValidationError, ValidationSeverity, validate_routing_config, validate_routing_rules,
+};
+use clawft_core::security::validate_model_string;
+use clawft_platform::config_loader::{camel_to_snake, normalize_keys};
+use clawft_platform::{NativePlatform, Platform};
+use clawft_types::config::Config;
+use clawft_types::error::ClawftError;
+use clawft_types::secret::SecretString;
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The config will not work as written.
+    Error,
+    /// Probably not what was meant.
+    Warning,
+}
+
+/// One finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// How serious it is.
+    pub severity: Severity,
+    /// JSON pointer to the offending value; empty for the whole file.
+    pub path: String,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+/// Validate the config file at `config_path` (or the discovered one) and
+/// print the findings. Exits with status 1 on errors, or on warnings when
+/// `strict` is set.
+pub async fn config_validate(
+    config_path: Option<&str>,
+    strict: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let platform = NativePlatform::new();
+    let path = match config_path {
+        Some(path) => PathBuf::from(path),
+        None => super::discover_config_path(&platform)
+            .ok_or_else(|| anyhow::anyhow!("no config file found; pass --config"))?,
+    };
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("cannot read {}: {e}", path.display()))?;
+
+    let env = |name: &str| platform.env().get_var(name);
+    #[allow(unused_mut)]
+    let mut diagnostics = validate(&contents, &env, agent_names);
+    #[cfg(feature = "services")]
+    if let Some(home) = platform.fs().home_dir() {
+        let store = home.join(".clawft").join("cron.jsonl");
+        if let Ok(contents) = std::fs::read_to_string(&store) {
+            diagnostics.extend(check_cron_store(&contents, "cron.jsonl"));
+        }
+    }
+
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{}", format_diagnostic(diagnostic));
+        }
+        if diagnostics.is_empty() {
+            println!("{} is valid.", path.display());
+        } else {
+            println!();
+            println!(
+                "{}: {errors} error(s), {warnings} warning(s)",
+                path.display()
+            );
+        }
+    }
+    if errors > 0 || (strict && warnings > 0) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Names of the agents defined for `config`, for checking routing rules.
+fn agent_names(config: &Config) -> Vec<String> {
+    clawft_core::bootstrap::discover_agents(config)
+        .map(|registry| registry.list().iter().map(|a| a.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// `error    /path: message`.
+fn format_diagnostic(diagnostic: &Diagnostic) -> String {
+    let label = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    if diagnostic.path.is_empty() {
+        format!("{label:<8} {}", diagnostic.message)
+    } else {
+        format!("{label:<8} {}: {}", diagnostic.path, diagnostic.message)
+    }
+}
+
+/// Validate config file `contents`.
+///
+/// `env` reads environment variables named by `*Env` fields; `agents`
+/// lists the agents defined for the parsed config.
+pub fn validate(
+    contents: &str,
+    env: &dyn Fn(&str) -> Option<String>,
+    agents: impl FnOnce(&Config) -> Vec<String>,
+) -> Vec<Diagnostic> {
+    let raw: Value = match serde_json::from_str(contents) {
+        Ok(raw) => raw,
+        Err(e) => {
+            return vec![Diagnostic {
+                severity: Severity::Error,
+                path: String::new(),
+                message: format!("not valid JSON: {e}"),
+            }];
+        }
+    };
+    let mut report = Report {
+        raw: &raw,
+        diagnostics: Vec::new(),
+    };
+
+    let normalized = normalize_keys(raw.clone());
+    let config: Config = match serde_path_to_error::deserialize(normalized.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            let path: Vec<String> = e
+                .path()
+                .iter()
+                .filter_map(|segment| match segment {
+                    serde_path_to_error::Segment::Seq { index } => Some(index.to_string()),
+                    serde_path_to_error::Segment::Map { key } => Some(key.clone()),
+                    serde_path_to_error::Segment::Enum { variant } => Some(variant.clone()),
+                    serde_path_to_error::Segment::Unknown => None,
+                })
+                .collect();
+            report.error(&path, e.into_inner().to_string());
+            return report.diagnostics;
+        }
+    };
+
+    let mut ignored = Vec::new();
+    let _: Result<Config, _> = serde_ignored::deserialize(normalized, |path| {
+        ignored.push(ignored_path(&path));
+    });
+    let known = serde_json::to_value(&config).unwrap_or_default();
+    for path in ignored {
+        report.unknown_field(&known, &path);
+    }
+
+    let agents = agents(&config);
+    let agents: Vec<&str> = agents.iter().map(String::as_str).collect();
+    check_channels(&mut report, &config, env);
+    check_routing(&mut report, &config, &agents);
+    check_models(&mut report, &config);
+    check_mcp_servers(&mut report, &config);
+    check_gateway(&mut report, &config);
+    report.diagnostics
+}
+
+/// Findings for one file, with pointers resolved against its raw JSON.
+struct Report<'a> {
+    raw: &'a Value,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Report<'_> {
+    fn push<S: AsRef<str>>(&mut self, severity: Severity, path: &[S], message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            path: pointer(self.raw, path),
+            message: message.into(),
+        });
+    }
+
+    fn error<S: AsRef<str>>(&mut self, path: &[S], message: impl Into<String>) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn warning<S: AsRef<str>>(&mut self, path: &[S], message: impl Into<String>) {
+        self.push(Severity::Warning, path, message);
+    }
+
+    /// Warn about the ignored field at `path`, suggesting the known field
+    /// of the same object with the closest name.
+    fn unknown_field(&mut self, known: &Value, path: &[String]) {
+        let Some((key, parent)) = path.split_last() else {
+            return;
+        };
+        let siblings = parent
+            .iter()
+            .try_fold(known, |node, segment| match node {
+                Value::Object(map) => map.get(segment),
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => None,
+            })
+            .and_then(Value::as_object);
+        let suggestion = siblings.and_then(|map| closest(key, map.keys().map(String::as_str)));
+
+        let written = pointer(self.raw, path);
+        let written = written.rsplit('/').next().unwrap_or_default();
+        let message = match suggestion {
+            // Offer the name in the style the file uses.
+            Some(name) if written.contains(char::is_uppercase) => {
+                format!(
+                    "unknown field, ignored; did you mean `{}`?",
+                    snake_to_camel(name)
+                )
+            }
+            Some(name) => format!("unknown field, ignored; did you mean `{name}`?"),
+            None => "unknown field, ignored".into(),
+        };
+        self.warning(path, message);
+    }
+
+    /// Report a diagnostic from [`clawft_core::routing_validation`].
+    fn routing(&mut self, diagnostic: ValidationError) {
+        let severity = match diagnostic.severity {
+            ValidationSeverity::Error => Severity::Error,
+            _ => Severity::Warning,
+        };
+        self.push(
+            severity,
+            &dotted_path(&diagnostic.field),
+            diagnostic.message,
+        );
+    }
+}
+
+/// The JSON pointer to `path` (in normalized, snake_case keys) within
+/// `raw`, using each key as it is written there.
+fn pointer<S: AsRef<str>>(raw: &Value, path: &[S]) -> String {
+    let mut out = String::new();
+    let mut node = Some(raw);
+    for segment in path {
+        let segment = segment.as_ref();
+        let (key, next) = match node {
+            Some(Value::Object(map)) => {
+                let found = map
+                    .iter()
+                    .find(|(k, _)| *k == segment)
+                    .or_else(|| map.iter().find(|(k, _)| camel_to_snake(k) == segment));
+                match found {
+                    Some((k, v)) => (k.as_str(), Some(v)),
+                    None => (segment, None),
+                }
+            }
+            Some(Value::Array(items)) => (
+                segment,
+                segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            ),
+            _ => (segment, None),
+        };
+        out.push('/');
+        out.push_str(&key.replace('~', "~0").replace('/', "~1"));
+        node = next;
+    }
+    out
+}
+
+/// The segments of a serde_ignored path.
+fn ignored_path(path: &serde_ignored::Path<'_>) -> Vec<String> {
+    match path {
+        serde_ignored::Path::Root => Vec::new(),
+        serde_ignored::Path::Seq { parent, index } => {
+            let mut out = ignored_path(parent);
+            out.push(index.to_string());
+            out
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            let mut out = ignored_path(parent);
+            out.push(key.clone());
+            out
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_path(parent),
+    }
+}
+
+/// The segments of a dotted field path such as `routing.tiers[1].models`.
+fn dotted_path(field: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for part in field.split('.') {
+        let mut pieces = part.split('[');
+        if let Some(name) = pieces.next().filter(|n| !n.is_empty()) {
+            out.push(name.to_string());
+        }
+        out.extend(pieces.map(|index| index.trim_end_matches(']').to_string()));
+    }
+    out
+}
+
+/// The candidate most like `key`: within two edits, or containing it.
+fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .filter_map(|candidate| {
+            let distance = edit_distance(key, candidate);
+            let close = distance <= 2 && distance < key.len();
+            let contains = key.len() >= 4 && candidate.contains(key);
+            (close || contains).then_some((distance, candidate))
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// `bot_token` -> `botToken`.
+fn snake_to_camel(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for ch in name.chars() {
+        if ch == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(ch.to_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+// ── Checks ──────────────────────────────────────────────────────
+
+/// Enabled channels need their credentials.
+fn check_channels(report: &mut Report<'_>, config: &Config, env: &dyn Fn(&str) -> Option<String>) {
+    let channels = &config.channels;
+    let mut secret = |channel: &str, what: &str, value: &SecretString, env_var: Option<&str>| {
+        let path = ["channels", channel];
+        match env_var {
+            _ if !value.is_empty() => {}
+            Some(var) if env(var).is_some_and(|v| !v.is_empty()) => {}
+            Some(var) => report.warning(
+                &path,
+                format!("{channel} {what} comes from ${var}, which is not set here"),
+            ),
+            None => report.error(&path, format!("{channel} is enabled but has no {what}")),
+        }
+    };
+
+    if channels.telegram.enabled {
+        let env_var = channels.telegram.token_env.as_deref();
+        secret("telegram", "token", &channels.telegram.token, env_var);
+    }
+    if channels.slack.enabled {
+        let slack = &channels.slack;
+        secret(
+            "slack",
+            "bot token",
+            &slack.bot_token,
+            slack.bot_token_env.as_deref(),
+        );
+        if slack.mode == "socket" {
+            secret(
+                "slack",
+                "app token",
+                &slack.app_token,
+                slack.app_token_env.as_deref(),
+            );
+        }
+    }
+    if channels.discord.enabled {
+        let env_var = channels.discord.token_env.as_deref();
+        secret("discord", "token", &channels.discord.token, env_var);
+    }
+    if channels.feishu.enabled {
+        secret("feishu", "app secret", &channels.feishu.app_secret, None);
+    }
+    if channels.dingtalk.enabled {
+        secret(
+            "dingtalk",
+            "client secret",
+            &channels.dingtalk.client_secret,
+            None,
+        );
+    }
+    if channels.mochat.enabled {
+        secret("mochat", "claw token", &channels.mochat.claw_token, None);
+    }
+    if channels.qq.enabled {
+        secret("qq", "secret", &channels.qq.secret, None);
+    }
+
+    let ids = [
+        (
+            "feishu",
+            "app id",
+            channels.feishu.enabled,
+            &channels.feishu.app_id,
+        ),
+        (
+            "dingtalk",
+            "client id",
+            channels.dingtalk.enabled,
+            &channels.dingtalk.client_id,
+        ),
+        ("qq", "app id", channels.qq.enabled, &channels.qq.app_id),
+    ];
+    for (channel, what, enabled, id) in ids {
+        if enabled && id.is_empty() {
+            report.error(
+                &["channels", channel],
+                format!("{channel} is enabled but has no {what}"),
+            );
+        }
+    }
+}
+
+/// Routing rules must name defined agents; tiers must be consistent.
+fn check_routing(report: &mut Report<'_>, config: &Config, agents: &[&str]) {
+    for diagnostic in validate_routing_rules(&config.routing.rules, agents) {
+        report.routing(diagnostic);
+    }
+    for diagnostic in validate_routing_config(&config.routing) {
+        report.routing(diagnostic);
+    }
+}
+
+/// Model identifiers must be plain `provider/model` strings.
+fn check_models(report: &mut Report<'_>, config: &Config) {
+    let mut models = vec![(
+        vec!["agents".to_string(), "defaults".into(), "model".into()],
+        &config.agents.defaults.model,
+    )];
+    if let Some(fallback) = &config.routing.fallback_model {
+        models.push((vec!["routing".into(), "fallback_model".into()], fallback));
+    }
+    for (i, tier) in config.routing.tiers.iter().enumerate() {
+        for (j, model) in tier.models.iter().enumerate() {
+            let path = ["routing", "tiers", &i.to_string(), "models", &j.to_string()];
+            models.push((path.map(String::from).to_vec(), model));
+        }
+    }
+    for (path, model) in models {
+        if let Err(e) = validate_model_string(model) {
+            let reason = match e {
+                ClawftError::SecurityViolation { reason } => reason,
+                other => other.to_string(),
+            };
+            report.error(&path, format!("invalid model '{model}': {reason}"));
+        }
+    }
+}
+
+/// Every MCP server uses exactly one transport.
+fn check_mcp_servers(report: &mut Report<'_>, config: &Config) {
+    let mut servers: Vec<_> = config.tools.mcp_servers.iter().collect();
+    servers.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, server) in servers {
+        let path = ["tools", "mcp_servers", name.as_str()];
+        match (server.command.is_empty(), server.url.is_empty()) {
+            (false, false) => report.error(
+                &path,
+                "sets both `command` (stdio) and `url` (HTTP); keep one",
+            ),
+            (true, true) => report.error(&path, "needs a `command` (stdio) or a `url` (HTTP)"),
+            _ => {}
+        }
+    }
+}
+
+/// Gateway settings parsing accepts but the gateway rejects.
+fn check_gateway(report: &mut Report<'_>, config: &Config) {
+    if let Some(quiet) = &config.gateway.quiet_hours
+        && let Err(e) = quiet.validate()
+    {
+        report.error(&["gateway", "quiet_hours"], e);
+    }
+    let level = &config.gateway.logs.level;
+    if level.parse::<tracing::Level>().is_err() {
+        report.error(
+            &["gateway", "logs", "level"],
+            format!("unknown level '{level}' (expected error, warn, info, debug or trace)"),
+        );
+    }
+}
+
+/// Check the schedules of the enabled jobs in the cron store `contents`.
+/// Paths are pointers into `store` by job id.
+#[cfg(feature = "services")]
+fn check_cron_store(contents: &str, store: &str) -> Vec<Diagnostic> {
+    use clawft_services::cron_service::scheduler::next_fire;
+    use clawft_services::cron_service::storage::replay_events;
+
+    let now = chrono::Utc::now();
+    let mut jobs = replay_events(contents);
+    jobs.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut diagnostics = Vec::new();
+    for job in jobs.into_iter().filter(|j| j.enabled) {
+        let path = format!("{store}#/{}/schedule", job.id);
+        match next_fire(&job.schedule, &now) {
+            Ok(Some(_)) => {}
+            Ok(None) => diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                path,
+                message: format!("job '{}' is enabled but never fires again", job.name),
+            }),
+            Err(e) => diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                path,
+                message: format!("job '{}': {e}", job.name),
+            }),
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    fn run(json: &str) -> Vec<Diagnostic> {
+        validate(json, &no_env, |_| vec!["coder".into()])
+    }
+
+    /// `(severity, path, message fragment)`.
+    type Expected = (Severity, &'static str, &'static str);
+
+    const E: Severity = Severity::Error;
+    const W: Severity = Severity::Warning;
+
+    #[test]
+    fn bad_configs() {
+        let cases: &[(&str, &str, &[Expected])] = &[
+            ("empty object", "{}", &[]),
+            ("not json", r#"{"agents": "#, &[(E, "", "not valid JSON")]),
+            (
+                "wrong type",
+                r#"{"gateway": {"port": "eighty"}}"#,
+                &[(E, "/gateway/port", "invalid type")],
+            ),
+            (
+                "wrong type in camelCase section",
+                r#"{"tools": {"mcpServers": {"gh": {"command": "gh", "maxTools": -1}}}}"#,
+                &[(E, "/tools/mcpServers/gh/maxTools", "invalid value")],
+            ),
+            (
+                "typo with suggestion",
+                r#"{"agents": {"defaults": {"modle": "openai/gpt-4o"}}}"#,
+                &[(W, "/agents/defaults/modle", "did you mean `model`?")],
+            ),
+            (
+                "camelCase typo keeps camelCase",
+                r#"{"channels": {"slack": {"botTokn": "xoxb-1", "appToken": "xapp-1"}}}"#,
+                &[(W, "/channels/slack/botTokn", "did you mean `botToken`?")],
+            ),
+            (
+                "shortened name",
+                r#"{"tools": {"audit": {"files": 2}}}"#,
+                &[(W, "/tools/audit/files", "did you mean `max_files`?")],
+            ),
+            (
+                "unknown section",
+                r#"{"gatewya": {}, "zzz": 1}"#,
+                &[
+                    (W, "/gatewya", "did you mean `gateway`?"),
+                    (W, "/zzz", "unknown field, ignored"),
+                ],
+            ),
+            (
+                "enabled telegram without token",
+                r#"{"channels": {"telegram": {"enabled": true}}}"#,
+                &[(E, "/channels/telegram", "has no token")],
+            ),
+            (
+                "disabled telegram without token",
+                r#"{"channels": {"telegram": {"enabled": false}}}"#,
+                &[],
+            ),
+            (
+                "token env not set",
+                r#"{"channels": {"discord": {"enabled": true, "tokenEnv": "DISCORD_TOKEN"}}}"#,
+                &[(W, "/channels/discord", "$DISCORD_TOKEN, which is not set")],
+            ),
+            (
+                "slack socket mode needs both tokens",
+                r#"{"channels": {"slack": {"enabled": true, "botToken": "xoxb-1"}}}"#,
+                &[(E, "/channels/slack", "has no app token")],
+            ),
+            (
+                "feishu without id or secret",
+                r#"{"channels": {"feishu": {"enabled": true}}}"#,
+                &[
+                    (E, "/channels/feishu", "has no app secret"),
+                    (E, "/channels/feishu", "has no app id"),
+                ],
+            ),
+            (
+                "routing rule names unknown agent",
+                r#"{"routing": {"rules": [{"match": {"channel": "slack"}, "action": {"route": "reviewer"}}]}}"#,
+                &[(
+                    E,
+                    "/routing/rules/0/action",
+                    "agent 'reviewer' is not defined",
+                )],
+            ),
+            (
+                "routing rule names known agent",
+                r#"{"routing": {"rules": [{"match": {"channel": "slack"}, "action": {"route": "coder"}}]}}"#,
+                &[],
+            ),
+            (
+                "routing rule with bad regex",
+                r#"{"routing": {"rules": [{"match": {"content": "("}, "action": {"route": "coder"}}]}}"#,
+                &[(E, "/routing/rules/0/match/content", "invalid regex")],
+            ),
+            (
+                "mcp server with both transports",
+                r#"{"tools": {"mcpServers": {"gh": {"command": "gh-mcp", "url": "http://x"}}}}"#,
+                &[(E, "/tools/mcpServers/gh", "sets both `command`")],
+            ),
+            (
+                "mcp server with no transport",
+                r#"{"tools": {"mcp_servers": {"empty": {}}}}"#,
+                &[(E, "/tools/mcp_servers/empty", "needs a `command`")],
+            ),
+            (
+                "model with shell metacharacters",
+                r#"{"agents": {"defaults": {"model": "openai/gpt-4o; rm -rf /"}}}"#,
+                &[(E, "/agents/defaults/model", "invalid model")],
+            ),
+            (
+                "tier model",
+                r#"{"routing": {"tiers": [{"name": "free", "models": ["ok/model", "bad`model"]}]}}"#,
+                &[(E, "/routing/tiers/0/models/1", "invalid model")],
+            ),
+            (
+                "tiered mode without tiers",
+                r#"{"routing": {"mode": "tiered"}}"#,
+                &[
+                    (E, "/routing/tiers", "requires at least one tier"),
+                    (
+                        W,
+                        "/routing/escalation",
+                        "exceeds the number of defined tiers",
+                    ),
+                ],
+            ),
+            (
+                "bad quiet hours",
+                r#"{"gateway": {"quietHours": {"start": "25:00", "end": "07:00"}}}"#,
+                &[(E, "/gateway/quietHours", "")],
+            ),
+            (
+                "bad log level",
+                r#"{"gateway": {"logs": {"enabled": true, "level": "loud"}}}"#,
+                &[(E, "/gateway/logs/level", "unknown level 'loud'")],
+            ),
+        ];
+
+        for (name, json, expected) in cases {
+            let diagnostics = run(json);
+            assert_eq!(
+                diagnostics.len(),
+                expected.len(),
+                "{name}: {diagnostics:#?}"
+            );
+            for (diagnostic, (severity, path, fragment)) in diagnostics.iter().zip(*expected) {
+                assert_eq!(diagnostic.severity, *severity, "{name}: {diagnostic:?}");
+                assert!(
+                    diagnostic.path.starts_with(path),
+                    "{name}: path {} does not start with {path}",
+                    diagnostic.path
+                );
+                assert!(
+                    diagnostic.message.contains(fragment),
+                    "{name}: {:?} does not contain {fragment:?}",
+                    diagnostic.message
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn token_env_set_passes() {
+        let json = r#"{"channels": {"telegram": {"enabled": true, "tokenEnv": "TG"}}}"#;
+        let env = |name: &str| (name == "TG").then(|| "123:abc".to_string());
+        assert!(validate(json, &env, |_| Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn pointers_escape_and_keep_written_keys() {
+        let raw: Value =
+            serde_json::from_str(r#"{"tools": {"mcpServers": {"a/b~c": {}}}}"#).unwrap();
+        assert_eq!(
+            pointer(&raw, &["tools", "mcp_servers", "a/b~c", "url"]),
+            "/tools/mcpServers/a~1b~0c/url"
+        );
+        assert_eq!(pointer(&raw, &[] as &[&str]), "");
+    }
+
+    #[test]
+    fn dotted_paths_split_indices() {
+        assert_eq!(
+            dotted_path("routing.tiers[1].complexity_range"),
+            ["routing", "tiers", "1", "complexity_range"]
+        );
+        assert_eq!(dotted_path("routing.rules[0]"), ["routing", "rules", "0"]);
+    }
+
+    #[test]
+    fn closest_prefers_fewest_edits() {
+        let names = ["model", "models", "max_tokens"];
+        assert_eq!(closest("modle", names.into_iter()), Some("model"));
+        assert_eq!(closest("tokens", names.into_iter()), Some("max_tokens"));
+        assert_eq!(closest("xyz", names.into_iter()), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[cfg(feature = "services")]
+    #[test]
+    fn cron_store_schedules_are_checked() {
+        use clawft_types::cron::{CronJob, CronJobState, CronSchedule, ScheduleKind};
+
+        let now = chrono::Utc::now();
+        let job = |id: &str, expr: &str| CronJob {
+            id: id.into(),
+            name: id.into(),
+            enabled: true,
+            schedule: CronSchedule {
+                kind: ScheduleKind::Cron,
+                expr: Some(expr.into()),
+                ..Default::default()
+            },
+            payload: Default::default(),
+            state: CronJobState::default(),
+            created_at: now,
+            updated_at: now,
+            delete_after_run: false,
+            created_by: None,
+        };
+        let contents = [job("good", "0 0 9 * * *"), job("bad", "not a schedule")]
+            .iter()
+            .map(|j| serde_json::json!({"type": "create", "job": j}).to_string() + "\n")
+            .collect::<String>();
+
+        let diagnostics = check_cron_store(&contents, "cron.jsonl");
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].path, "cron.jsonl#/bad/schedule");
+    }
+}
@@ -0,0 +1,246 @@
+//! Local control socket of a running gateway.
+//!
+//! The gateway listens on a Unix socket next to its pid file
+//! (`~/.clawft/gateway.sock`). A client writes one [`ControlRequest`] as a
+//! JSON line and reads one [`ControlResponse`] line back. Only the user
+//! running the gateway can connect: the socket is created with mode 0600.
+//!
+//! `weft config reload` uses it to have the gateway re-read its config,
+//! and `weft gateway status` to ask how long it has been running.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use clawft_core::config_reload::ReloadReport;
+
+/// How long a client waits for the gateway to answer.
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Path of the control socket of a running gateway.
+pub fn socket_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".clawft").join("gateway.sock"))
+}
+
+/// A request sent to the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Re-read the config file and apply what can change without a
+    /// restart.
+    ReloadConfig,
+    /// Report the gateway's process and start time.
+    Status,
+}
+
+/// The gateway's answer to a [`ControlRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ControlResponse {
+    /// The config was reloaded.
+    Reloaded {
+        /// What happened to each changed section.
+        report: ReloadReport,
+    },
+    /// Answer to [`ControlRequest::Status`].
+    Running {
+        /// Process id of the gateway.
+        pid: u32,
+        /// When the gateway started.
+        started_at: DateTime<Utc>,
+        /// Version of the gateway binary.
+        version: String,
+    },
+    /// The request failed.
+    Error {
+        /// Why.
+        message: String,
+    },
+}
+
+/// Serve requests on `path` until `cancel` fires, answering each with
+/// `handle`. A stale socket left by an earlier gateway is replaced; the
+/// socket is removed on shutdown.
+#[cfg(unix)]
+pub fn spawn_control_server<F, Fut>(
+    path: PathBuf,
+    handle: F,
+    cancel: tokio_util::sync::CancellationToken,
+) -> anyhow::Result<()>
+where
+    F: Fn(ControlRequest) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = ControlResponse> + Send,
+{
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Arc;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)
+        .map_err(|e| anyhow::anyhow!("failed to bind {}: {e}", path.display()))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    tracing::info!(path = %path.display(), "control socket listening");
+
+    let handle = Arc::new(handle);
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                _ = cancel.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "control socket accept failed");
+                        continue;
+                    }
+                },
+            };
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(stream, &*handle).await {
+                    tracing::debug!(error = %e, "control connection failed");
+                }
+            });
+        }
+        let _ = std::fs::remove_file(&path);
+    });
+    Ok(())
+}
+
+/// Answer the one request of a control connection.
+#[cfg(unix)]
+async fn serve_connection<F, Fut>(stream: tokio::net::UnixStream, handle: &F) -> anyhow::Result<()>
+where
+    F: Fn(ControlRequest) -> Fut,
+    Fut: std::future::Future<Output = ControlResponse>,
+{
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => handle(request).await,
+        Err(e) => ControlResponse::Error {
+            message: format!("invalid request: {e}"),
+        },
+    };
+    let mut out = serde_json::to_vec(&response)?;
+    out.push(b'\n');
+    write.write_all(&out).await?;
+    Ok(())
+}
+
+/// Send `request` to the gateway listening on `path` and wait for its
+/// answer.
+#[cfg(unix)]
+pub async fn send(path: &Path, request: &ControlRequest) -> anyhow::Result<ControlResponse> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let exchange = async {
+        let stream = tokio::net::UnixStream::connect(path).await.map_err(|e| {
+            anyhow::anyhow!(
+                "no running gateway found ({}: {e}); start one with `weft gateway`",
+                path.display()
+            )
+        })?;
+        let (read, mut write) = stream.into_split();
+        let mut out = serde_json::to_vec(request)?;
+        out.push(b'\n');
+        write.write_all(&out).await?;
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await?;
+        Ok(serde_json::from_str(&line)?)
+    };
+    tokio::time::timeout(CONTROL_TIMEOUT, exchange)
+        .await
+        .map_err(|_| anyhow::anyhow!("the gateway did not answer within {CONTROL_TIMEOUT:?}"))?
+}
+
+/// Send `request` to the gateway (unavailable on this platform).
+#[cfg(not(unix))]
+pub async fn send(_path: &Path, _request: &ControlRequest) -> anyhow::Result<ControlResponse> {
+    anyhow::bail!("the gateway control socket is only supported on Unix")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_round_trips_through_the_socket() {
+        let dir = std::env::temp_dir().join(format!("clawft-control-{}", std::process::id()));
+        let path = dir.join("gateway.sock");
+        let cancel = tokio_util::sync::CancellationToken::new();
+        spawn_control_server(
+            path.clone(),
+            |request| async move {
+                assert_eq!(request, ControlRequest::ReloadConfig);
+                ControlResponse::Reloaded {
+                    report: ReloadReport {
+                        applied: vec!["agents.defaults.model".into()],
+                        ..Default::default()
+                    },
+                }
+            },
+            cancel.clone(),
+        )
+        .unwrap();
+
+        let response = send(&path, &ControlRequest::ReloadConfig).await.unwrap();
+        let ControlResponse::Reloaded { report } = response else {
+            panic!("unexpected response: {response:?}");
+        };
+        assert_eq!(report.applied, ["agents.defaults.model"]);
+
+        cancel.cancel();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn status_reports_pid_and_start_time() {
+        let dir =
+            std::env::temp_dir().join(format!("clawft-control-status-{}", std::process::id()));
+        let path = dir.join("gateway.sock");
+        let started_at = Utc::now();
+        let cancel = tokio_util::sync::CancellationToken::new();
+        spawn_control_server(
+            path.clone(),
+            move |request| async move {
+                assert_eq!(request, ControlRequest::Status);
+                ControlResponse::Running {
+                    pid: 42,
+                    started_at,
+                    version: "1.2.3".into(),
+                }
+            },
+            cancel.clone(),
+        )
+        .unwrap();
+
+        let response = send(&path, &ControlRequest::Status).await.unwrap();
+        assert_eq!(
+            response,
+            ControlResponse::Running {
+                pid: 42,
+                started_at,
+                version: "1.2.3".into(),
+            }
+        );
+
+        cancel.cancel();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn missing_gateway_is_reported() {
+        let path = std::env::temp_dir().join("clawft-control-missing.sock");
+        let err = send(&path, &ControlRequest::ReloadConfig)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no running gateway"), "{err}");
+    }
+}
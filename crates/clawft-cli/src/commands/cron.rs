@@ -12,10 +12,17 @@
 //! ```text
 //! weft cron list
 //! weft cron add --name "daily report" --schedule "0 9 * * *" --prompt "Generate report"
+//! weft cron add --name "digest" --schedule "0 8 * * *" --prompt "Summarise my inbox" \
+//!     --channel telegram --to 12345 --quiet-hours 22:00-07:00 --timezone +02:00
+//! weft cron add --name "standup" --schedule "0 9 * * Mon-Fri" --prompt "Post standup" \
+//!     --tz America/New_York --jitter 60 --catch-up run_once
+//! weft cron add --name "stretch" --at 2026-03-01T09:45:00Z --prompt "Remind me to stretch"
+//! weft cron add --name "poll" --every 10m --until 2026-03-01T18:00:00Z --prompt "Check the build"
 //! weft cron remove job-abc123
 //! weft cron enable job-abc123
 //! weft cron disable job-abc123
 //! weft cron run job-abc123
+//! weft cron history job-abc123 --limit 10
 //! ```
 
 use std::path::{Path, PathBuf};
@@ -25,10 +32,13 @@ use chrono::{TimeZone, Utc};
 use comfy_table::{Table, presets::UTF8_FULL};
 
 use clawft_rpc::{DaemonClient, Request};
+use clawft_services::cron_service::{history, scheduler};
 use clawft_types::config::Config;
 use clawft_types::cron::{
-    CronJob, CronJobState, CronPayload, CronSchedule, ScheduleKind,
+    CatchUp, CronJob, CronJobState, CronPayload, CronRun, CronSchedule, FailureAlert, JobStatus,
+    ScheduleKind, parse_interval,
 };
+use clawft_types::delivery::QuietHours;
 
 /// Default cron store filename (JSONL, shared with CronService).
 const CRON_STORE_FILENAME: &str = "cron.jsonl";
@@ -40,7 +50,7 @@ const LEGACY_STORE_FILENAME: &str = "cron.json";
 ///
 /// Tries `~/.clawft/cron.jsonl`, then `~/.nanobot/cron.jsonl`.
 /// Returns the first path whose parent directory exists.
-fn cron_store_path() -> PathBuf {
+pub(crate) fn cron_store_path() -> PathBuf {
     if let Some(home) = dirs::home_dir() {
         let clawft_path = home.join(".clawft").join(CRON_STORE_FILENAME);
         if clawft_path.parent().is_some_and(|p| p.exists()) {
@@ -110,6 +120,23 @@ fn format_ts(dt: Option<chrono::DateTime<Utc>>) -> String {
     }
 }
 
+/// Format a job's next run time in the job's own timezone, or "-" if it
+/// has none. Falls back to the stored time when the schedule is invalid.
+fn format_next_run(job: &CronJob, now: chrono::DateTime<Utc>) -> String {
+    if !job.enabled {
+        return "-".into();
+    }
+    let tz = scheduler::parse_timezone(job.schedule.tz.as_deref());
+    match (scheduler::next_run(job, &now), tz) {
+        (Ok(Some(next)), Ok(tz)) => next
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string(),
+        (Ok(None), Ok(_)) => "-".into(),
+        _ => format_ts(job.state.next_run_at),
+    }
+}
+
 /// List all cron jobs in a table.
 ///
 /// Tries daemon RPC first; falls back to direct file I/O.
@@ -151,16 +178,21 @@ fn cron_list_local() -> anyhow::Result<()> {
     table.load_preset(UTF8_FULL);
     table.set_header(["ID", "NAME", "SCHEDULE", "ENABLED", "LAST RUN", "NEXT RUN"]);
 
+    let now = Utc::now();
     for job in &jobs {
         let schedule_str = match job.schedule.kind {
             ScheduleKind::Cron => job.schedule.expr.as_deref().unwrap_or("-").to_owned(),
-            ScheduleKind::Every => {
-                if let Some(ms) = job.schedule.every_ms {
-                    format!("every {ms}ms")
-                } else {
-                    "every ?".into()
+            ScheduleKind::Every => match (job.schedule.every_ms, job.schedule.until) {
+                (Some(ms), Some(until)) => {
+                    format!(
+                        "every {} until {}",
+                        format_interval(ms),
+                        format_ts(Some(until))
+                    )
                 }
-            }
+                (Some(ms), None) => format!("every {}", format_interval(ms)),
+                (None, _) => "every ?".into(),
+            },
             ScheduleKind::At => {
                 match job.schedule.at_ms {
                     Some(ms) => Utc.timestamp_millis_opt(ms)
@@ -175,7 +207,7 @@ fn cron_list_local() -> anyhow::Result<()> {
 
         let enabled_str = if job.enabled { "yes" } else { "no" };
         let last_run = format_ts(job.state.last_run_at);
-        let next_run = format_ts(job.state.next_run_at);
+        let next_run = format_next_run(job, now);
 
         table.add_row([
             &job.id,
@@ -207,19 +239,212 @@ fn normalize_cron_expr(expr: &str) -> String {
     }
 }
 
+/// An interval in milliseconds as e.g. `"1h30m"` (or `"1500ms"` when it is
+/// not a whole number of seconds).
+fn format_interval(ms: i64) -> String {
+    if ms <= 0 || ms % 1000 != 0 {
+        return format!("{ms}ms");
+    }
+    let mut secs = ms / 1000;
+    let mut out = String::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if secs >= size {
+            out.push_str(&format!("{}{unit}", secs / size));
+            secs %= size;
+        }
+    }
+    out
+}
+
+/// When a job runs (`weft cron add --schedule`, `--at` or `--every`).
+#[derive(Debug)]
+pub enum CronWhen {
+    /// On a cron expression.
+    Cron(String),
+    /// Once, at an RFC 3339 time.
+    Once(String),
+    /// Every interval such as `"10m"`, and not after `until` (RFC 3339).
+    Every {
+        /// The interval.
+        every: String,
+        /// Last time the job may run.
+        until: Option<String>,
+    },
+}
+
+impl CronWhen {
+    /// Pick the schedule from the `weft cron add` flags; exactly one of
+    /// `schedule`, `at` and `every` must be given.
+    pub fn from_flags(
+        schedule: Option<String>,
+        at: Option<String>,
+        every: Option<String>,
+        until: Option<String>,
+    ) -> anyhow::Result<Self> {
+        match (schedule, at, every) {
+            (Some(expr), None, None) => Ok(Self::Cron(expr)),
+            (None, Some(at), None) => Ok(Self::Once(at)),
+            (None, None, Some(every)) => Ok(Self::Every { every, until }),
+            _ => anyhow::bail!("give exactly one of --schedule, --at and --every"),
+        }
+    }
+
+    /// Validate the flags and build the schedule.
+    fn to_schedule(&self) -> anyhow::Result<CronSchedule> {
+        let parse_time = |flag: &str, value: &str| {
+            chrono::DateTime::parse_from_rfc3339(value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| anyhow::anyhow!("invalid {flag} {value:?}: {e}"))
+        };
+        match self {
+            Self::Cron(expr) => {
+                let normalized = normalize_cron_expr(expr);
+                cron::Schedule::from_str(&normalized)
+                    .map_err(|e| anyhow::anyhow!("Invalid cron expression: {e}"))?;
+                Ok(CronSchedule {
+                    kind: ScheduleKind::Cron,
+                    expr: Some(normalized),
+                    tz: Some("UTC".into()),
+                    ..Default::default()
+                })
+            }
+            Self::Once(at) => Ok(CronSchedule::once(parse_time("--at", at)?)),
+            Self::Every { every, until } => {
+                let interval = parse_interval(every).ok_or_else(|| {
+                    anyhow::anyhow!("invalid --every {every:?}: expected e.g. 10m, 2h or 1h30m")
+                })?;
+                let until = until
+                    .as_deref()
+                    .map(|until| parse_time("--until", until))
+                    .transpose()?;
+                Ok(CronSchedule::interval(interval, until))
+            }
+        }
+    }
+}
+
+/// Where a job's reply goes and when it may be sent (`weft cron add`
+/// delivery flags).
+#[derive(Debug, Default)]
+pub struct CronDelivery {
+    /// Channel to send the reply on.
+    pub channel: Option<String>,
+    /// Conversation (chat ID) on `channel`.
+    pub to: Option<String>,
+    /// Quiet-hours window, e.g. `"22:00-07:00"`.
+    pub quiet_hours: Option<String>,
+    /// Timezone of the quiet-hours window.
+    pub timezone: String,
+    /// Channel to alert on when the job keeps failing.
+    pub alert_channel: Option<String>,
+    /// Conversation (chat ID) on `alert_channel`.
+    pub alert_to: Option<String>,
+    /// Consecutive failures before the first alert.
+    pub alert_after: u32,
+}
+
+impl CronDelivery {
+    /// Whether any delivery flag was given.
+    pub fn is_set(&self) -> bool {
+        self.channel.is_some()
+            || self.to.is_some()
+            || self.quiet_hours.is_some()
+            || self.alert_channel.is_some()
+    }
+
+    /// Validate the flags and copy them onto `payload`.
+    fn apply(&self, payload: &mut CronPayload) -> anyhow::Result<()> {
+        match (&self.channel, &self.to) {
+            (Some(_), Some(_)) => payload.deliver = true,
+            (None, None) => {}
+            _ => anyhow::bail!("--channel and --to must be given together"),
+        }
+        payload.channel = self.channel.clone();
+        payload.to = self.to.clone();
+        payload.quiet_hours = self
+            .quiet_hours
+            .as_deref()
+            .map(|window| QuietHours::parse(window, &self.timezone))
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("invalid --quiet-hours: {e}"))?;
+        payload.on_failure = match (&self.alert_channel, &self.alert_to) {
+            (Some(channel), Some(to)) => Some(FailureAlert {
+                channel: channel.clone(),
+                to: to.clone(),
+                after: self.alert_after,
+            }),
+            (None, None) => None,
+            _ => anyhow::bail!("--alert-channel and --alert-to must be given together"),
+        };
+        Ok(())
+    }
+}
+
+/// When a job fires (`weft cron add` timing flags).
+#[derive(Debug, Default)]
+pub struct CronTiming {
+    /// IANA timezone the schedule is evaluated in; UTC when unset.
+    pub tz: Option<String>,
+    /// Upper bound of the random delay added to each run, in seconds.
+    pub jitter_secs: u64,
+    /// Catch-up policy for runs missed while the gateway was down:
+    /// `"skip"`, `"run_once"` or `"run_all"`.
+    pub catch_up: Option<String>,
+}
+
+impl CronTiming {
+    /// Whether any timing flag was given.
+    pub fn is_set(&self) -> bool {
+        self.tz.is_some() || self.jitter_secs > 0 || self.catch_up.is_some()
+    }
+
+    /// Validate the flags and copy them onto `schedule`.
+    fn apply(&self, schedule: &mut CronSchedule) -> anyhow::Result<()> {
+        if let Some(tz) = &self.tz {
+            scheduler::parse_timezone(Some(tz))
+                .map_err(|e| anyhow::anyhow!("invalid --tz: {e}"))?;
+            schedule.tz = Some(tz.clone());
+        }
+        schedule.jitter_secs = self.jitter_secs;
+        if let Some(policy) = &self.catch_up {
+            schedule.catch_up = serde_json::from_value::<CatchUp>(serde_json::json!(policy))
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "invalid --catch-up {policy:?}: expected skip, run_once or run_all"
+                    )
+                })?;
+        }
+        Ok(())
+    }
+}
+
 /// Add a new cron job.
 ///
 /// Tries daemon RPC first; falls back to direct file I/O.
 pub async fn cron_add(
     name: String,
-    schedule: String,
+    when: CronWhen,
     prompt: String,
+    delivery: CronDelivery,
+    timing: CronTiming,
     _config: &Config,
 ) -> anyhow::Result<()> {
     // Validate locally regardless of daemon path.
-    let normalized = normalize_cron_expr(&schedule);
-    cron::Schedule::from_str(&normalized)
-        .map_err(|e| anyhow::anyhow!("Invalid cron expression: {e}"))?;
+    let mut cron_schedule = when.to_schedule()?;
+    let mut payload = CronPayload {
+        message: prompt.clone(),
+        ..Default::default()
+    };
+    delivery.apply(&mut payload)?;
+    timing.apply(&mut cron_schedule)?;
+
+    // One-shot and interval jobs, delivery rules and timing options are
+    // only honoured by the gateway scheduler, which reads the local store;
+    // the daemon's cron only knows cron expressions.
+    let normalized = match &cron_schedule.expr {
+        Some(expr) if !delivery.is_set() && !timing.is_set() => expr.clone(),
+        _ => return cron_add_local(name, cron_schedule, payload),
+    };
 
     if let Ok(mut client) = DaemonClient::connect().await.ok_or(()) {
         let params = serde_json::json!({
@@ -248,37 +473,37 @@ pub async fn cron_add(
     }
 
     // ── Direct file fallback (deprecated) ──
-    cron_add_local(name, normalized, prompt)
+    cron_add_local(name, cron_schedule, payload)
 }
 
 /// Direct-file implementation of cron add.
-fn cron_add_local(name: String, normalized: String, prompt: String) -> anyhow::Result<()> {
+fn cron_add_local(
+    name: String,
+    schedule: CronSchedule,
+    payload: CronPayload,
+) -> anyhow::Result<()> {
     let path = cron_store_path();
     migrate_legacy_store(&path);
 
     let job_id = generate_job_id();
     let now = Utc::now();
 
-    let job = CronJob {
+    let mut job = CronJob {
         id: job_id.clone(),
         name: name.clone(),
         enabled: true,
-        schedule: CronSchedule {
-            kind: ScheduleKind::Cron,
-            at_ms: None,
-            every_ms: None,
-            expr: Some(normalized),
-            tz: Some("UTC".into()),
-        },
-        payload: CronPayload {
-            message: prompt,
-            ..Default::default()
-        },
+        schedule,
+        payload,
         state: CronJobState::default(),
         created_at: now,
         updated_at: now,
         delete_after_run: false,
+        created_by: None,
     };
+    job.state.next_run_at = scheduler::next_run(&job, &now)?;
+    if job.state.next_run_at.is_none() {
+        anyhow::bail!("the schedule has no runs after {}", now.to_rfc3339());
+    }
 
     clawft_services::cron_service::storage::append_create_sync(&path, &job)
         .map_err(|e| anyhow::anyhow!("failed to write cron store: {e}"))?;
@@ -436,6 +661,60 @@ fn cron_run_local(job_id: String) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Show the most recent `limit` runs of a job, oldest first.
+///
+/// Reads the history the gateway keeps in `~/.clawft/state/cron-history/`.
+pub fn cron_history(job_id: &str, limit: usize) -> anyhow::Result<()> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("no home directory"))?;
+    let dir = history::history_dir(&home);
+    let runs = history::load_runs_sync(&dir, job_id)
+        .map_err(|e| anyhow::anyhow!("failed to read cron history in {}: {e}", dir.display()))?;
+
+    if runs.is_empty() {
+        println!("No recorded runs for cron job '{job_id}'.");
+        return Ok(());
+    }
+    let skip = runs.len().saturating_sub(limit);
+    println!("{}", history_table(&runs[skip..]));
+    Ok(())
+}
+
+/// Render runs as a table: start, duration, status, tokens, and the first
+/// line of the error or output.
+fn history_table(runs: &[CronRun]) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(["STARTED", "DURATION", "STATUS", "TOKENS", "OUTPUT"]);
+    for run in runs {
+        let secs = (run.finished_at - run.started_at).num_seconds();
+        let status = match run.status {
+            JobStatus::Ok => "ok",
+            JobStatus::Error => "error",
+            JobStatus::Skipped => "cancelled",
+            _ => "?",
+        };
+        let detail = run.error.as_deref().unwrap_or(&run.output);
+        let mut summary: String = detail
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(60)
+            .collect();
+        if summary.len() < detail.len() {
+            summary.push_str("...");
+        }
+        table.add_row([
+            format_ts(Some(run.started_at)),
+            format!("{secs}s"),
+            status.to_string(),
+            format!("{}/{}", run.input_tokens, run.output_tokens),
+            summary,
+        ]);
+    }
+    table
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -524,6 +803,192 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn cron_delivery_fills_payload() {
+        let delivery = CronDelivery {
+            channel: Some("telegram".into()),
+            to: Some("42".into()),
+            quiet_hours: Some("22:00-07:00".into()),
+            timezone: "+02:00".into(),
+            ..Default::default()
+        };
+        assert!(delivery.is_set());
+        let mut payload = CronPayload::default();
+        delivery.apply(&mut payload).unwrap();
+        assert!(payload.deliver);
+        assert_eq!(payload.channel.as_deref(), Some("telegram"));
+        assert_eq!(payload.to.as_deref(), Some("42"));
+        let quiet = payload.quiet_hours.unwrap();
+        assert_eq!(
+            (quiet.start.as_str(), quiet.end.as_str()),
+            ("22:00", "07:00")
+        );
+        assert_eq!(quiet.timezone, "+02:00");
+    }
+
+    #[test]
+    fn cron_delivery_rejects_bad_flags() {
+        let mut payload = CronPayload::default();
+        let half = CronDelivery {
+            channel: Some("telegram".into()),
+            ..Default::default()
+        };
+        assert!(half.apply(&mut payload).is_err());
+
+        let bad_window = CronDelivery {
+            quiet_hours: Some("late".into()),
+            timezone: "UTC".into(),
+            ..Default::default()
+        };
+        assert!(bad_window.apply(&mut payload).is_err());
+
+        assert!(!CronDelivery::default().is_set());
+    }
+
+    #[test]
+    fn cron_timing_fills_schedule() {
+        let mut schedule = CronSchedule::default();
+        let timing = CronTiming {
+            tz: Some("America/New_York".into()),
+            jitter_secs: 90,
+            catch_up: Some("run_all".into()),
+        };
+        assert!(timing.is_set());
+        timing.apply(&mut schedule).unwrap();
+        assert_eq!(schedule.tz.as_deref(), Some("America/New_York"));
+        assert_eq!(schedule.jitter_secs, 90);
+        assert_eq!(schedule.catch_up, CatchUp::RunAll);
+
+        let bad_tz = CronTiming {
+            tz: Some("Mars/Olympus_Mons".into()),
+            ..Default::default()
+        };
+        assert!(bad_tz.apply(&mut schedule).is_err());
+        let bad_policy = CronTiming {
+            catch_up: Some("sometimes".into()),
+            ..Default::default()
+        };
+        assert!(bad_policy.apply(&mut schedule).is_err());
+        assert!(!CronTiming::default().is_set());
+    }
+
+    #[test]
+    fn next_run_is_shown_in_job_timezone() {
+        let now = Utc.with_ymd_and_hms(2026, 7, 1, 12, 0, 0).unwrap();
+        let mut job = CronJob {
+            id: "j1".into(),
+            name: "nine".into(),
+            enabled: true,
+            schedule: CronSchedule {
+                kind: ScheduleKind::Cron,
+                expr: Some(normalize_cron_expr("0 9 * * *")),
+                tz: Some("America/New_York".into()),
+                ..Default::default()
+            },
+            payload: CronPayload::default(),
+            state: CronJobState::default(),
+            created_at: now,
+            updated_at: now,
+            delete_after_run: false,
+            created_by: None,
+        };
+        assert_eq!(format_next_run(&job, now), "2026-07-01 09:00:00 EDT");
+
+        job.enabled = false;
+        assert_eq!(format_next_run(&job, now), "-");
+    }
+
+    #[test]
+    fn cron_when_builds_each_schedule_kind() {
+        let cron = CronWhen::from_flags(Some("0 9 * * *".into()), None, None, None).unwrap();
+        let schedule = cron.to_schedule().unwrap();
+        assert_eq!(schedule.kind, ScheduleKind::Cron);
+        assert_eq!(schedule.expr.as_deref(), Some("0 0 9 * * * *"));
+
+        let once = CronWhen::from_flags(None, Some("2026-03-01T10:45:00+01:00".into()), None, None);
+        let schedule = once.unwrap().to_schedule().unwrap();
+        assert_eq!(schedule.kind, ScheduleKind::At);
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 9, 45, 0).unwrap();
+        assert_eq!(schedule.at_ms, Some(at.timestamp_millis()));
+        assert_eq!(schedule.catch_up, CatchUp::RunOnce);
+
+        let every = CronWhen::Every {
+            every: "1h30m".into(),
+            until: Some("2026-03-01T18:00:00Z".into()),
+        };
+        let schedule = every.to_schedule().unwrap();
+        assert_eq!(schedule.kind, ScheduleKind::Every);
+        assert_eq!(schedule.every_ms, Some(90 * 60 * 1000));
+        assert!(schedule.until.is_some());
+
+        assert!(CronWhen::Once("tomorrow".into()).to_schedule().is_err());
+        let bad_every = CronWhen::Every {
+            every: "often".into(),
+            until: None,
+        };
+        assert!(bad_every.to_schedule().is_err());
+        assert!(CronWhen::from_flags(None, None, None, None).is_err());
+    }
+
+    #[test]
+    fn format_interval_uses_largest_units() {
+        assert_eq!(format_interval(600_000), "10m");
+        assert_eq!(format_interval(5_400_000), "1h30m");
+        assert_eq!(format_interval(90_061_000), "1d1h1m1s");
+        assert_eq!(format_interval(1_500), "1500ms");
+    }
+
+    #[test]
+    fn cron_delivery_sets_failure_alert() {
+        let mut payload = CronPayload::default();
+        let delivery = CronDelivery {
+            alert_channel: Some("telegram".into()),
+            alert_to: Some("42".into()),
+            alert_after: 5,
+            ..Default::default()
+        };
+        assert!(delivery.is_set());
+        delivery.apply(&mut payload).unwrap();
+        assert!(!payload.deliver);
+        let alert = payload.on_failure.unwrap();
+        assert_eq!(
+            (alert.channel.as_str(), alert.to.as_str()),
+            ("telegram", "42")
+        );
+        assert_eq!(alert.after, 5);
+    }
+
+    #[test]
+    fn history_table_summarises_runs() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+        let runs = [
+            CronRun {
+                started_at: start,
+                finished_at: start + chrono::Duration::seconds(12),
+                status: JobStatus::Ok,
+                output: "All quiet.\nNothing else.".into(),
+                error: None,
+                input_tokens: 300,
+                output_tokens: 20,
+            },
+            CronRun {
+                started_at: start,
+                finished_at: start,
+                status: JobStatus::Error,
+                output: String::new(),
+                error: Some("provider unavailable".into()),
+                input_tokens: 0,
+                output_tokens: 0,
+            },
+        ];
+        let rendered = history_table(&runs).to_string();
+        assert!(rendered.contains("12s"));
+        assert!(rendered.contains("300/20"));
+        assert!(rendered.contains("All quiet...."));
+        assert!(rendered.contains("provider unavailable"));
+        assert!(!rendered.contains("Nothing else"));
+    }
+
     #[test]
     fn cron_list_with_empty_store() {
         // Smoke test: should not panic.
@@ -550,12 +1015,14 @@ mod tests {
                 every_ms: None,
                 expr: Some("0 9 * * *".into()),
                 tz: Some("UTC".into()),
+                ..Default::default()
             },
             payload: CronPayload::default(),
             state: CronJobState::default(),
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         };
 
         clawft_services::cron_service::storage::append_create_sync(&path, &job).unwrap();
@@ -586,6 +1053,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         };
 
         clawft_services::cron_service::storage::append_create_sync(&path, &job).unwrap();
@@ -622,6 +1090,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         };
 
         clawft_services::cron_service::storage::append_create_sync(&path, &make("j1", "a"))
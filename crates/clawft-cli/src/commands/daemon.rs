@@ -0,0 +1,434 @@
+//! Running the gateway in the background (Unix).
+//!
+//! `weft gateway --daemon` starts a detached copy of the gateway: a new
+//! session without a terminal, stdio on `/dev/null`, and the structured
+//! log file (`weft logs`) enabled in place of terminal output. It returns
+//! once the gateway has written its pid file, or reports that it exited
+//! during startup. The async runtime is already running when arguments
+//! are parsed, so the copy is spawned (with `--detached`) rather than
+//! forked.
+//!
+//! `weft gateway stop` sends SIGTERM to the process named in the pid file
+//! and waits for it to exit. `weft gateway status` asks the gateway over
+//! its control socket (see [`super::control`]). A pid file whose process
+//! is gone is stale: both commands report and empty it.
+//!
+//! A running gateway holds an exclusive `flock` on its pid file
+//! ([`PidLock`]) until it exits, so two gateways started at the same time
+//! cannot both run. The file is emptied rather than removed, since
+//! unlinking a locked file would let the next gateway lock a new one.
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+
+use super::control::{self, ControlRequest, ControlResponse};
+
+/// How long `--daemon` waits for the gateway to write its pid file.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often startup and `stop` check on the gateway process.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the pid file says about the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidState {
+    /// No pid file.
+    Missing,
+    /// The pid file names a live process.
+    Running(u32),
+    /// The pid file names a process that is gone, or cannot be parsed
+    /// (pid 0).
+    Stale(u32),
+}
+
+/// Read the pid file at `path`. An empty file (left by a gateway that
+/// shut down cleanly) counts as missing.
+pub fn pid_state(path: &Path) -> PidState {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return PidState::Missing;
+    };
+    if contents.trim().is_empty() {
+        return PidState::Missing;
+    }
+    match contents.trim().parse::<u32>() {
+        Ok(pid) if pid > 0 && process_alive(pid) => PidState::Running(pid),
+        Ok(pid) => PidState::Stale(pid),
+        Err(_) => PidState::Stale(0),
+    }
+}
+
+/// Whether process `pid` exists and has not exited. A zombie (exited,
+/// not yet reaped by its parent) counts as exited.
+fn process_alive(pid: u32) -> bool {
+    let Ok(raw) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 checks for existence; EPERM means it exists as another user.
+    let exists = matches!(kill(Pid::from_raw(raw), None), Ok(()) | Err(Errno::EPERM));
+    exists && !is_zombie(pid)
+}
+
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: u32) -> bool {
+    // `/proc/<pid>/stat` is `pid (comm) state ...`; comm may hold spaces.
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            let (_, rest) = stat.rsplit_once(')')?;
+            rest.trim_start().chars().next()
+        })
+        == Some('Z')
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_zombie(_pid: u32) -> bool {
+    false
+}
+
+fn pid_file() -> anyhow::Result<PathBuf> {
+    super::gateway::pid_file_path()
+        .ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))
+}
+
+/// Fail if a gateway is already running; empty a stale pid file.
+///
+/// A quick check before spawning; [`PidLock::acquire`] in the gateway is
+/// what actually keeps a second one from running.
+pub fn ensure_not_running(path: &Path) -> anyhow::Result<()> {
+    match pid_state(path) {
+        PidState::Running(pid) if pid != std::process::id() => anyhow::bail!(
+            "a gateway is already running (pid {pid}); stop it with `weft gateway stop`"
+        ),
+        PidState::Stale(pid) => {
+            tracing::info!(pid, path = %path.display(), "clearing stale gateway pid file");
+            clear_stale(path);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Empty the pid file at `path` unless a gateway holds its lock.
+fn clear_stale(path: &Path) {
+    if let Ok(mut lock) = PidLock::acquire(path) {
+        lock.clear();
+    }
+}
+
+/// Exclusive lock on the gateway pid file, held while the gateway runs.
+///
+/// The kernel drops the lock when the process exits, however it exits, so
+/// a crashed gateway never blocks the next one.
+pub struct PidLock {
+    file: Flock<File>,
+}
+
+impl PidLock {
+    /// Lock the pid file at `path`, creating it if needed. Fails if another
+    /// gateway holds the lock.
+    pub fn acquire(path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("cannot open {}: {e}", path.display()))?;
+        match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+            Ok(file) => Ok(Self { file }),
+            Err((_, Errno::EWOULDBLOCK)) => match pid_state(path) {
+                PidState::Running(pid) => anyhow::bail!(
+                    "a gateway is already running (pid {pid}); stop it with `weft gateway stop`"
+                ),
+                _ => anyhow::bail!("another gateway is starting; check `weft gateway status`"),
+            },
+            Err((_, e)) => anyhow::bail!("cannot lock {}: {e}", path.display()),
+        }
+    }
+
+    /// Replace the file's contents with the current process id.
+    pub fn write_pid(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        write!(self.file, "{}", std::process::id())?;
+        self.file.flush()
+    }
+
+    /// Empty the pid file on shutdown. The file itself stays (and stays
+    /// locked until exit): removing it would let a new gateway lock a fresh
+    /// file while this one is still shutting down.
+    pub fn clear(&mut self) {
+        let _ = self.file.set_len(0);
+    }
+}
+
+/// Start the gateway in the background with the current arguments and
+/// wait until it is up.
+pub async fn start() -> anyhow::Result<()> {
+    let path = pid_file()?;
+    ensure_not_running(&path)?;
+
+    let mut args: Vec<OsString> = std::env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--daemon")
+        .collect();
+    args.push("--detached".into());
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // SAFETY: setsid is async-signal-safe and touches no memory shared
+    // with the parent.
+    unsafe {
+        command.pre_exec(|| {
+            nix::unistd::setsid()
+                .map(drop)
+                .map_err(std::io::Error::from)
+        });
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("failed to start the gateway: {e}"))?;
+    let pid = child.id();
+
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!(
+                "the gateway exited during startup ({status}); see `weft logs` or run \
+                 `weft gateway` in the foreground"
+            );
+        }
+        if pid_state(&path) == PidState::Running(pid) {
+            println!("Gateway started in the background (pid {pid}).");
+            println!("  Logs:  weft logs --follow");
+            println!("  Stop:  weft gateway stop");
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            println!(
+                "Gateway (pid {pid}) is still starting; check on it with `weft gateway status`."
+            );
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Send SIGTERM to the running gateway and wait up to `timeout` for it
+/// to exit.
+pub async fn stop(timeout: Duration) -> anyhow::Result<()> {
+    let path = pid_file()?;
+    let pid = match pid_state(&path) {
+        PidState::Missing => {
+            println!("Gateway is not running.");
+            return Ok(());
+        }
+        PidState::Stale(pid) => {
+            clear_stale(&path);
+            println!("Gateway is not running (cleared stale pid file for pid {pid}).");
+            return Ok(());
+        }
+        PidState::Running(pid) => pid,
+    };
+
+    kill(Pid::from_raw(pid as i32), Signal::SIGTERM)
+        .map_err(|e| anyhow::anyhow!("failed to signal the gateway (pid {pid}): {e}"))?;
+    println!("Stopping gateway (pid {pid})...");
+    let deadline = Instant::now() + timeout;
+    while process_alive(pid) {
+        if Instant::now() >= deadline {
+            anyhow::bail!(
+                "the gateway (pid {pid}) did not exit within {}s",
+                timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    // The gateway empties its pid file on shutdown, unless it crashed.
+    if pid_state(&path) == PidState::Stale(pid) {
+        clear_stale(&path);
+    }
+    println!("Gateway stopped.");
+    Ok(())
+}
+
+/// Report whether a gateway is running, and for how long. Fails when none
+/// is, so the command exits non-zero.
+pub async fn status() -> anyhow::Result<()> {
+    let path = pid_file()?;
+    let socket = control::socket_path();
+    let answer = match &socket {
+        Some(socket) => control::send(socket, &ControlRequest::Status).await,
+        None => Err(anyhow::anyhow!("cannot determine home directory")),
+    };
+
+    match (answer, pid_state(&path)) {
+        (
+            Ok(ControlResponse::Running {
+                pid,
+                started_at,
+                version,
+            }),
+            _,
+        ) => {
+            let uptime = (Utc::now() - started_at).to_std().unwrap_or_default();
+            println!(
+                "Gateway is running (pid {pid}, up {}, version {version}).",
+                format_uptime(uptime)
+            );
+            println!(
+                "  Started: {}",
+                started_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            );
+            Ok(())
+        }
+        (Ok(other), PidState::Running(pid)) => {
+            println!("Gateway is running (pid {pid}) but gave an unexpected answer: {other:?}");
+            Ok(())
+        }
+        (Err(e), PidState::Running(pid)) => {
+            println!("Gateway is running (pid {pid}) but its control socket is not answering: {e}");
+            Ok(())
+        }
+        (_, PidState::Stale(pid)) => {
+            clear_stale(&path);
+            anyhow::bail!("gateway is not running (cleared stale pid file for pid {pid})")
+        }
+        (_, PidState::Missing) => anyhow::bail!("gateway is not running"),
+    }
+}
+
+/// `3d 4h`, `4h 5m`, `5m 6s` or `6s`.
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes, seconds) =
+        (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_pid_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clawft_daemon_{name}_{}_{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("gateway.pid")
+    }
+
+    #[test]
+    fn pid_file_states() {
+        let path = temp_pid_file("states");
+        assert_eq!(pid_state(&path), PidState::Missing);
+
+        std::fs::write(&path, std::process::id().to_string()).unwrap();
+        assert_eq!(pid_state(&path), PidState::Running(std::process::id()));
+
+        // A child that has exited and been reaped leaves a stale pid.
+        let mut child = Command::new("true").spawn().unwrap();
+        let gone = child.id();
+        child.wait().unwrap();
+        std::fs::write(&path, format!("{gone}\n")).unwrap();
+        assert_eq!(pid_state(&path), PidState::Stale(gone));
+
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(pid_state(&path), PidState::Stale(0));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn stale_pid_file_is_cleared_before_start() {
+        let path = temp_pid_file("stale");
+        let mut child = Command::new("true").spawn().unwrap();
+        let gone = child.id();
+        child.wait().unwrap();
+        std::fs::write(&path, gone.to_string()).unwrap();
+
+        ensure_not_running(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        // Our own pid is not "another" gateway.
+        std::fs::write(&path, std::process::id().to_string()).unwrap();
+        ensure_not_running(&path).unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn live_gateway_blocks_start() {
+        let path = temp_pid_file("live");
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        std::fs::write(&path, child.id().to_string()).unwrap();
+
+        let err = ensure_not_running(&path).unwrap_err();
+        assert!(err.to_string().contains("already running"), "{err}");
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn pid_lock_is_exclusive_until_dropped() {
+        let path = temp_pid_file("lock");
+        let mut lock = PidLock::acquire(&path).unwrap();
+        lock.write_pid().unwrap();
+        assert_eq!(pid_state(&path), PidState::Running(std::process::id()));
+
+        // A second open file description conflicts, even in this process.
+        let err = PidLock::acquire(&path).err().unwrap();
+        assert!(err.to_string().contains("already running"), "{err}");
+
+        lock.clear();
+        assert_eq!(pid_state(&path), PidState::Missing);
+        assert!(PidLock::acquire(&path).is_err());
+
+        drop(lock);
+        PidLock::acquire(&path).unwrap();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn uptime_shows_two_largest_units() {
+        assert_eq!(format_uptime(Duration::from_secs(6)), "6s");
+        assert_eq!(format_uptime(Duration::from_secs(5 * 60 + 6)), "5m 6s");
+        assert_eq!(
+            format_uptime(Duration::from_secs(4 * 3600 + 5 * 60)),
+            "4h 5m"
+        );
+        assert_eq!(
+            format_uptime(Duration::from_secs(3 * 86_400 + 4 * 3600)),
+            "3d 4h"
+        );
+    }
+}
@@ -0,0 +1,834 @@
+//! `weft doctor` -- check the environment and config for problems.
+//!
+//! Runs a set of independent checks and reports each as pass, warn or
+//! fail:
+//!
+//! - the config file parses, and has no fields clawft does not know;
+//! - each configured LLM provider (and the default model's provider)
+//!   answers its models listing with the configured key;
+//! - the tokens of enabled Telegram, Slack and Discord channels are
+//!   accepted (`getMe`, `auth.test`, `users/@me`);
+//! - the programs stdio MCP servers and the shell tools run are on `PATH`;
+//! - the agent workspace is a writable directory;
+//! - every enabled cron job's schedule is valid.
+//!
+//! Every check goes through the [`Platform`], so tests run them against
+//! the mock platform. The command exits with status 1 when any check
+//! fails.
+//!
+//! # Example
+//!
+//! ```text
+//! weft doctor
+//! weft doctor --json
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Args;
+use serde::Serialize;
+
+use clawft_core::pipeline::llm_adapter::{default_provider_config, named_provider_config};
+use clawft_llm::LlmProviderConfig;
+use clawft_platform::{NativePlatform, Platform};
+use clawft_types::config::Config;
+
+use super::{discover_config_path, expand_workspace};
+
+/// How long a provider or channel API may take to answer.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Providers with a section under `providers` in the config.
+const CONFIGURABLE_PROVIDERS: &[&str] = &[
+    "openai",
+    "anthropic",
+    "groq",
+    "deepseek",
+    "openrouter",
+    "gemini",
+    "xai",
+    "azure",
+];
+
+/// Arguments for the `weft doctor` subcommand.
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Print the results as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Config file path (overrides auto-discovery).
+    #[arg(short, long)]
+    pub config: Option<String>,
+}
+
+/// How a check ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Nothing wrong.
+    Pass,
+    /// Works, but probably not as intended.
+    Warn,
+    /// Broken; something will fail at runtime.
+    Fail,
+}
+
+/// The result of one check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// What was checked, e.g. `"provider openai"`.
+    pub name: String,
+    /// How it ended.
+    pub status: Status,
+    /// What was found.
+    pub message: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn pass(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Pass, message)
+    }
+
+    fn warn(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Warn, message)
+    }
+
+    fn fail(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(name, Status::Fail, message)
+    }
+}
+
+/// Run the doctor command.
+pub async fn run(args: DoctorArgs) -> anyhow::Result<()> {
+    let platform = NativePlatform::new();
+    let path = match args.config {
+        Some(path) => Some(PathBuf::from(path)),
+        None => discover_config_path(&platform),
+    };
+
+    let (mut checks, config) = check_config(&platform, path.as_deref()).await;
+    if let Some(config) = config {
+        checks.extend(check_providers(&platform, &config).await);
+        checks.extend(check_channels(&platform, &config).await);
+        checks.extend(check_binaries(&platform, &config).await);
+        let workspace = expand_workspace(&config.agents.defaults.workspace);
+        checks.push(check_workspace(&platform, &workspace).await);
+        #[cfg(feature = "services")]
+        if let Some(home) = platform.fs().home_dir() {
+            let store = home.join(".clawft").join("cron.jsonl");
+            checks.extend(check_cron(&platform, &store).await);
+        }
+    }
+
+    let failed = checks.iter().any(|c| c.status == Status::Fail);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        print_checks(&checks);
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print one line per check, then the totals.
+fn print_checks(checks: &[Check]) {
+    println!("weft doctor");
+    println!("===========");
+    println!();
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in checks {
+        let label = match check.status {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        println!("  {label}  {:<width$}  {}", check.name, check.message);
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    println!();
+    println!(
+        "{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        count(Status::Fail)
+    );
+}
+
+// ── Checks ──────────────────────────────────────────────────────
+
+/// Parse the config file at `path`, warning about fields clawft ignores.
+///
+/// Returns the config the remaining checks run against, or `None` when it
+/// does not parse. Without a config file the defaults are used.
+pub async fn check_config<P: Platform>(
+    platform: &P,
+    path: Option<&Path>,
+) -> (Vec<Check>, Option<Config>) {
+    let resolve = |mut config: Config| {
+        config
+            .providers
+            .resolve_api_keys(|name| platform.env().get_var(name));
+        config
+    };
+    let Some(path) = path else {
+        let check = Check::warn("config", "no config file found; using defaults");
+        return (vec![check], Some(resolve(Config::default())));
+    };
+    if !platform.fs().exists(path).await {
+        let check = Check::fail("config", format!("{} does not exist", path.display()));
+        return (vec![check], None);
+    }
+    let contents = match platform.fs().read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            let check = Check::fail("config", format!("cannot read {}: {e}", path.display()));
+            return (vec![check], None);
+        }
+    };
+    let raw: serde_json::Value = match serde_json::from_str(&contents) {
+        Ok(raw) => raw,
+        Err(e) => {
+            let check = Check::fail(
+                "config",
+                format!("{} is not valid JSON: {e}", path.display()),
+            );
+            return (vec![check], None);
+        }
+    };
+
+    let raw = clawft_platform::config_loader::normalize_keys(raw);
+    let mut unknown = Vec::new();
+    let config: Config =
+        match serde_ignored::deserialize(raw, |field| unknown.push(field.to_string())) {
+            Ok(config) => config,
+            Err(e) => {
+                let check = Check::fail("config", format!("{}: {e}", path.display()));
+                return (vec![check], None);
+            }
+        };
+
+    let mut checks = vec![Check::pass("config", format!("{} parsed", path.display()))];
+    checks.extend(
+        unknown
+            .into_iter()
+            .map(|field| Check::warn("config", format!("unknown field `{field}` is ignored"))),
+    );
+    (checks, Some(resolve(config)))
+}
+
+/// Ask each configured provider, and the default model's provider, for its
+/// models.
+pub async fn check_providers<P: Platform>(platform: &P, config: &Config) -> Vec<Check> {
+    let default = default_provider_config(config);
+    let mut names = vec![default.name.clone()];
+    for name in CONFIGURABLE_PROVIDERS {
+        if !api_key_from_config(config, name).is_empty() && !names.iter().any(|n| n == name) {
+            names.push((*name).to_string());
+        }
+    }
+
+    let mut checks = Vec::with_capacity(names.len());
+    for name in names {
+        let provider = if name == default.name {
+            default.clone()
+        } else {
+            match named_provider_config(config, &name) {
+                Some(provider) => provider,
+                None => continue,
+            }
+        };
+        let key = api_key_from_config(config, &name);
+        let key = if key.is_empty() {
+            platform.env().get_var(&provider.api_key_env)
+        } else {
+            Some(key.to_string())
+        };
+        checks.push(probe_provider(platform, &provider, key.as_deref()).await);
+    }
+    checks
+}
+
+/// The API key `config.providers` holds for `name`, possibly empty.
+fn api_key_from_config<'a>(config: &'a Config, name: &str) -> &'a str {
+    let provider = match name {
+        "openai" => &config.providers.openai,
+        "anthropic" => &config.providers.anthropic,
+        "groq" => &config.providers.groq,
+        "deepseek" => &config.providers.deepseek,
+        "openrouter" => &config.providers.openrouter,
+        "gemini" => &config.providers.gemini,
+        "xai" => &config.providers.xai,
+        "azure" => &config.providers.azure,
+        _ => return "",
+    };
+    provider.api_key.expose()
+}
+
+/// List `provider`'s models to see that it is reachable and takes `key`.
+async fn probe_provider<P: Platform>(
+    platform: &P,
+    provider: &LlmProviderConfig,
+    key: Option<&str>,
+) -> Check {
+    let name = format!("provider {}", provider.name);
+    let local = matches!(provider.name.as_str(), "local" | "ollama");
+    let key = key.filter(|k| !k.is_empty());
+    if key.is_none() && !local {
+        return Check::fail(
+            name,
+            format!(
+                "no API key; set {} or providers.{}.apiKey",
+                provider.api_key_env, provider.name
+            ),
+        );
+    }
+    if provider.azure.is_some() {
+        if provider.base_url.is_empty() {
+            return Check::fail(name, "providers.azure.apiBase is not set");
+        }
+        // Azure deployments cannot be listed with an API key.
+        return Check::pass(name, "API key set (Azure is not probed)");
+    }
+
+    let mut headers = provider.headers.clone();
+    if let Some(key) = key {
+        if provider.name == "anthropic" {
+            headers.insert("x-api-key".into(), key.into());
+        } else {
+            headers.insert("Authorization".into(), format!("Bearer {key}"));
+        }
+    }
+    let url = format!("{}/models", provider.base_url.trim_end_matches('/'));
+    let fetch = platform.http().get(&url, &headers);
+    match tokio::time::timeout(PROBE_TIMEOUT, fetch).await {
+        Ok(Ok(resp)) if resp.is_success() => {
+            Check::pass(name, format!("{} answered", provider.base_url))
+        }
+        Ok(Ok(resp)) if matches!(resp.status, 401 | 403) => {
+            Check::fail(name, format!("API key rejected (HTTP {})", resp.status))
+        }
+        Ok(Ok(resp)) => Check::warn(name, format!("{url} answered HTTP {}", resp.status)),
+        Ok(Err(e)) => Check::fail(name, format!("{} unreachable: {e}", provider.base_url)),
+        Err(_) => Check::fail(
+            name,
+            format!(
+                "{} did not answer within {}s",
+                provider.base_url,
+                PROBE_TIMEOUT.as_secs()
+            ),
+        ),
+    }
+}
+
+/// Check the tokens of enabled Telegram, Slack and Discord channels with
+/// each API's cheapest authenticated call.
+pub async fn check_channels<P: Platform>(platform: &P, config: &Config) -> Vec<Check> {
+    let channels = &config.channels;
+    let env = platform.env();
+    let mut checks = Vec::new();
+
+    if channels.telegram.enabled {
+        let token = env.resolve_secret(
+            &channels.telegram.token,
+            channels.telegram.token_env.as_deref(),
+        );
+        checks.push(match token {
+            None => Check::fail("channel telegram", "enabled without a token"),
+            Some(token) => {
+                let url = format!("https://api.telegram.org/bot{}/getMe", token.expose());
+                probe_channel(
+                    platform,
+                    "channel telegram",
+                    &url,
+                    None,
+                    HashMap::new(),
+                    |body| body["result"]["username"].as_str().map(|u| format!("@{u}")),
+                )
+                .await
+            }
+        });
+    }
+
+    if channels.slack.enabled {
+        let token = env.resolve_secret(
+            &channels.slack.bot_token,
+            channels.slack.bot_token_env.as_deref(),
+        );
+        checks.push(match token {
+            None => Check::fail("channel slack", "enabled without a bot token"),
+            Some(token) => {
+                let headers = HashMap::from([(
+                    "Authorization".to_string(),
+                    format!("Bearer {}", token.expose()),
+                )]);
+                probe_channel(
+                    platform,
+                    "channel slack",
+                    "https://slack.com/api/auth.test",
+                    Some(b""),
+                    headers,
+                    |body| {
+                        let user = body["user"].as_str()?;
+                        let team = body["team"].as_str().unwrap_or("?");
+                        Some(format!("{user} in {team}"))
+                    },
+                )
+                .await
+            }
+        });
+    }
+
+    if channels.discord.enabled {
+        let token = env.resolve_secret(
+            &channels.discord.token,
+            channels.discord.token_env.as_deref(),
+        );
+        checks.push(match token {
+            None => Check::fail("channel discord", "enabled without a token"),
+            Some(token) => {
+                let headers = HashMap::from([(
+                    "Authorization".to_string(),
+                    format!("Bot {}", token.expose()),
+                )]);
+                probe_channel(
+                    platform,
+                    "channel discord",
+                    "https://discord.com/api/v10/users/@me",
+                    None,
+                    headers,
+                    |body| body["username"].as_str().map(str::to_string),
+                )
+                .await
+            }
+        });
+    }
+
+    checks
+}
+
+/// Call a channel API that identifies the token's account. `identity`
+/// names the account from the JSON reply. Telegram and Slack report a
+/// rejected token as `"ok": false` in the body.
+async fn probe_channel<P: Platform>(
+    platform: &P,
+    name: &str,
+    url: &str,
+    body: Option<&[u8]>,
+    headers: HashMap<String, String>,
+    identity: impl Fn(&serde_json::Value) -> Option<String>,
+) -> Check {
+    let method = if body.is_some() { "POST" } else { "GET" };
+    let call = platform.http().request(method, url, &headers, body);
+    let resp = match tokio::time::timeout(PROBE_TIMEOUT, call).await {
+        Ok(Ok(resp)) => resp,
+        Ok(Err(e)) => return Check::fail(name, format!("API unreachable: {e}")),
+        Err(_) => {
+            return Check::fail(
+                name,
+                format!("API did not answer within {}s", PROBE_TIMEOUT.as_secs()),
+            );
+        }
+    };
+    let json = resp.json::<serde_json::Value>().unwrap_or_default();
+    if matches!(resp.status, 401 | 403) || json["ok"] == serde_json::Value::Bool(false) {
+        let detail = json["error"]
+            .as_str()
+            .or_else(|| json["description"].as_str())
+            .map(|d| format!(": {d}"))
+            .unwrap_or_default();
+        return Check::fail(
+            name,
+            format!("token rejected (HTTP {}{detail})", resp.status),
+        );
+    }
+    if !resp.is_success() {
+        return Check::warn(name, format!("API answered HTTP {}", resp.status));
+    }
+    match identity(&json) {
+        Some(who) => Check::pass(name, format!("token valid ({who})")),
+        None => Check::pass(name, "token valid"),
+    }
+}
+
+/// Check that the programs stdio MCP servers and the shell tools run are
+/// on `PATH`.
+pub async fn check_binaries<P: Platform>(platform: &P, config: &Config) -> Vec<Check> {
+    // Program -> what needs it, in a stable order.
+    let mut needed: Vec<(String, Vec<String>)> = vec![("sh".into(), vec!["shell tools".into()])];
+    let mut servers: Vec<_> = config
+        .tools
+        .mcp_servers
+        .iter()
+        .filter(|(_, server)| !server.command.is_empty())
+        .collect();
+    servers.sort_by(|a, b| a.0.cmp(b.0));
+    for (server, mcp) in servers {
+        let user = format!("MCP server {server}");
+        match needed
+            .iter_mut()
+            .find(|(program, _)| *program == mcp.command)
+        {
+            Some((_, users)) => users.push(user),
+            None => needed.push((mcp.command.clone(), vec![user])),
+        }
+    }
+
+    let mut checks = Vec::with_capacity(needed.len());
+    for (program, users) in needed {
+        let name = format!("binary {program}");
+        let users = users.join(", ");
+        checks.push(match find_program(platform, &program).await {
+            Some(path) => Check::pass(name, format!("{} (for {users})", path.display())),
+            None => Check::fail(name, format!("not found on PATH; needed by {users}")),
+        });
+    }
+    checks
+}
+
+/// Where `program` would be run from: itself when it is a path, otherwise
+/// the first `PATH` entry holding it.
+async fn find_program<P: Platform>(platform: &P, program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return platform.fs().exists(&path).await.then_some(path);
+    }
+    let path_var = platform.env().get_var("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        let candidate = dir.join(program);
+        if platform.fs().exists(&candidate).await {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Check that the agent workspace is a directory clawft can write to.
+pub async fn check_workspace<P: Platform>(platform: &P, workspace: &Path) -> Check {
+    let fs = platform.fs();
+    if !fs.exists(workspace).await {
+        return Check::warn(
+            "workspace",
+            format!(
+                "{} does not exist yet; it is created on first use",
+                workspace.display()
+            ),
+        );
+    }
+    match fs.metadata(workspace).await {
+        Ok(meta) if !meta.is_dir => {
+            return Check::fail(
+                "workspace",
+                format!("{} is not a directory", workspace.display()),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return Check::fail(
+                "workspace",
+                format!("cannot inspect {}: {e}", workspace.display()),
+            );
+        }
+    }
+    let probe = workspace.join(".weft-doctor-probe");
+    if let Err(e) = fs.write_string(&probe, "").await {
+        return Check::fail(
+            "workspace",
+            format!("{} is not writable: {e}", workspace.display()),
+        );
+    }
+    let _ = fs.remove_file(&probe).await;
+    Check::pass("workspace", format!("{} is writable", workspace.display()))
+}
+
+/// Check the schedule of every enabled job in the cron store at `store`.
+#[cfg(feature = "services")]
+pub async fn check_cron<P: Platform>(platform: &P, store: &Path) -> Vec<Check> {
+    use clawft_services::cron_service::scheduler::next_fire;
+    use clawft_services::cron_service::storage::replay_events;
+
+    if !platform.fs().exists(store).await {
+        return Vec::new();
+    }
+    let contents = match platform.fs().read_to_string(store).await {
+        Ok(contents) => contents,
+        Err(e) => {
+            return vec![Check::fail(
+                "cron",
+                format!("cannot read {}: {e}", store.display()),
+            )];
+        }
+    };
+    let now = chrono::Utc::now();
+    let mut jobs = replay_events(&contents);
+    jobs.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut checks = Vec::new();
+    for job in jobs.into_iter().filter(|j| j.enabled) {
+        let name = format!("cron {}", job.name);
+        checks.push(match next_fire(&job.schedule, &now) {
+            Ok(Some(next)) => Check::pass(name, format!("next run {}", next.to_rfc3339())),
+            Ok(None) => Check::warn(name, "enabled, but the schedule never fires again"),
+            Err(e) => Check::fail(name, e.to_string()),
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::env::Environment;
+    use clawft_platform::testing::{MockPlatform, MockResponse};
+    use clawft_types::config::MCPServerConfig;
+
+    fn platform_with_config(json: &str) -> MockPlatform {
+        let platform = MockPlatform::new();
+        platform.fs.seed("/home/test/.clawft/config.json", json);
+        platform
+    }
+
+    async fn load(platform: &MockPlatform) -> Config {
+        check_config(platform, Some(Path::new("/home/test/.clawft/config.json")))
+            .await
+            .1
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn config_warns_about_unknown_fields() {
+        let platform = platform_with_config(
+            r#"{"agents": {"defaults": {"model": "openai/gpt-4o", "modle": "x"}}, "chanels": {}}"#,
+        );
+        let (checks, config) =
+            check_config(&platform, Some(Path::new("/home/test/.clawft/config.json"))).await;
+        assert_eq!(config.unwrap().agents.defaults.model, "openai/gpt-4o");
+        assert_eq!(checks[0].status, Status::Pass);
+        let warnings: Vec<_> = checks[1..].iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(
+            warnings,
+            [
+                "unknown field `agents.defaults.modle` is ignored",
+                "unknown field `chanels` is ignored"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn config_fails_on_bad_json_and_bad_types() {
+        let platform = platform_with_config("{ not json");
+        let (checks, config) =
+            check_config(&platform, Some(Path::new("/home/test/.clawft/config.json"))).await;
+        assert!(config.is_none());
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].message.contains("not valid JSON"), "{checks:?}");
+
+        let platform = platform_with_config(r#"{"gateway": {"port": "eighty"}}"#);
+        let (checks, config) =
+            check_config(&platform, Some(Path::new("/home/test/.clawft/config.json"))).await;
+        assert!(config.is_none());
+        assert_eq!(checks[0].status, Status::Fail);
+
+        let (checks, config) = check_config(&MockPlatform::new(), None).await;
+        assert!(config.is_some());
+        assert_eq!(checks[0].status, Status::Warn);
+    }
+
+    #[tokio::test]
+    async fn providers_are_probed_with_their_keys() {
+        let platform = platform_with_config(
+            r#"{"agents": {"defaults": {"model": "anthropic/claude-sonnet-4-5"}},
+                "providers": {"anthropic": {"apiKey": "sk-ant"}, "groq": {"apiKey": "gsk"}}}"#,
+        );
+        platform.http.respond(
+            "GET https://api.anthropic.com/v1/models",
+            MockResponse::json(200, &serde_json::json!({"data": []})),
+        );
+        platform.http.respond(
+            "GET https://api.groq.com/openai/v1/models",
+            MockResponse::text(401, "invalid api key"),
+        );
+        let config = load(&platform).await;
+
+        let checks = check_providers(&platform, &config).await;
+        assert_eq!(checks.len(), 2, "{checks:?}");
+        assert_eq!(checks[0].name, "provider anthropic");
+        assert_eq!(checks[0].status, Status::Pass);
+        assert_eq!(checks[1].name, "provider groq");
+        assert_eq!(checks[1].status, Status::Fail);
+        assert!(checks[1].message.contains("rejected"));
+
+        let requests = platform.http.requests();
+        assert_eq!(requests[0].headers["x-api-key"], "sk-ant");
+        assert_eq!(requests[0].headers["anthropic-version"], "2023-06-01");
+        assert_eq!(requests[1].headers["Authorization"], "Bearer gsk");
+    }
+
+    #[tokio::test]
+    async fn default_provider_without_key_fails() {
+        let platform =
+            platform_with_config(r#"{"agents": {"defaults": {"model": "openai/gpt-4o"}}}"#);
+        let config = load(&platform).await;
+        let checks = check_providers(&platform, &config).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].message.contains("OPENAI_API_KEY"));
+        assert!(platform.http.requests().is_empty());
+
+        // An unreachable provider fails too.
+        platform.env.set_var("OPENAI_API_KEY", "sk");
+        platform.http.respond(
+            "https://api.openai.com/*",
+            MockResponse::error("connection refused"),
+        );
+        let config = load(&platform).await;
+        let checks = check_providers(&platform, &config).await;
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].message.contains("unreachable"));
+    }
+
+    #[tokio::test]
+    async fn channel_tokens_are_verified() {
+        let platform = platform_with_config(
+            r#"{"channels": {
+                "telegram": {"enabled": true, "token": "123:abc"},
+                "slack": {"enabled": true, "botTokenEnv": "SLACK_BOT_TOKEN"},
+                "discord": {"enabled": true}
+            }}"#,
+        );
+        platform.env.set_var("SLACK_BOT_TOKEN", "xoxb-1");
+        platform.http.respond(
+            "GET https://api.telegram.org/bot123:abc/getMe",
+            MockResponse::json(
+                200,
+                &serde_json::json!({"ok": true, "result": {"username": "clawbot"}}),
+            ),
+        );
+        platform.http.respond(
+            "POST https://slack.com/api/auth.test",
+            MockResponse::json(
+                200,
+                &serde_json::json!({"ok": false, "error": "invalid_auth"}),
+            ),
+        );
+        let config = load(&platform).await;
+
+        let checks = check_channels(&platform, &config).await;
+        assert_eq!(checks[0].status, Status::Pass);
+        assert_eq!(checks[0].message, "token valid (@clawbot)");
+        assert_eq!(checks[1].status, Status::Fail);
+        assert_eq!(checks[1].message, "token rejected (HTTP 200: invalid_auth)");
+        assert_eq!(checks[2].status, Status::Fail);
+        assert_eq!(checks[2].message, "enabled without a token");
+        let slack = &platform.http.requests()[1];
+        assert_eq!(slack.headers["Authorization"], "Bearer xoxb-1");
+    }
+
+    #[tokio::test]
+    async fn binaries_are_looked_up_on_path() {
+        let platform = MockPlatform::new();
+        platform.env.set_var("PATH", "/usr/local/bin:/usr/bin");
+        platform.fs.seed("/usr/bin/sh", "");
+        platform.fs.seed("/usr/local/bin/npx", "");
+        let mut config = Config::default();
+        for (name, command) in [("github", "npx"), ("fetch", "npx"), ("db", "docker")] {
+            config.tools.mcp_servers.insert(
+                name.into(),
+                MCPServerConfig {
+                    command: command.into(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let checks = check_binaries(&platform, &config).await;
+        let summary: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.status)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("binary sh", Status::Pass),
+                ("binary docker", Status::Fail),
+                ("binary npx", Status::Pass),
+            ]
+        );
+        assert_eq!(
+            checks[1].message,
+            "not found on PATH; needed by MCP server db"
+        );
+        assert_eq!(
+            checks[2].message,
+            "/usr/local/bin/npx (for MCP server fetch, MCP server github)"
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_must_be_a_writable_directory() {
+        let platform = MockPlatform::new();
+        let ws = Path::new("/home/test/.clawft/workspace");
+        assert_eq!(check_workspace(&platform, ws).await.status, Status::Warn);
+
+        platform.fs.seed_dir(ws);
+        assert_eq!(check_workspace(&platform, ws).await.status, Status::Pass);
+        // The probe file is cleaned up.
+        assert!(platform.fs.files().is_empty());
+
+        platform.fs.seed("/home/test/notes", "a file");
+        let check = check_workspace(&platform, Path::new("/home/test/notes")).await;
+        assert_eq!(check.status, Status::Fail);
+        assert!(check.message.contains("not a directory"));
+    }
+
+    #[tokio::test]
+    async fn cron_schedules_are_validated() {
+        use clawft_types::cron::{CronJob, CronPayload, CronSchedule, ScheduleKind};
+
+        let job = |id: &str, expr: &str, enabled: bool| CronJob {
+            id: id.into(),
+            name: id.into(),
+            enabled,
+            schedule: CronSchedule {
+                kind: ScheduleKind::Cron,
+                expr: Some(expr.into()),
+                ..Default::default()
+            },
+            payload: CronPayload::default(),
+            state: Default::default(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            delete_after_run: false,
+            created_by: None,
+        };
+        let store = Path::new("/home/test/.clawft/cron.jsonl");
+        let platform = MockPlatform::new();
+        assert!(check_cron(&platform, store).await.is_empty());
+
+        let mut lines = String::new();
+        for job in [
+            job("daily", "0 0 9 * * *", true),
+            job("typo", "0 0 25 * * *", true),
+            job("off", "not cron", false),
+        ] {
+            let event = serde_json::json!({"type": "create", "job": job});
+            lines.push_str(&format!("{event}\n"));
+        }
+        platform.fs.seed(store, lines);
+
+        let checks = check_cron(&platform, store).await;
+        let summary: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.status)).collect();
+        assert_eq!(
+            summary,
+            [("cron daily", Status::Pass), ("cron typo", Status::Fail)]
+        );
+    }
+}
@@ -11,22 +11,73 @@
 //! 1. Load config & bootstrap AppContext (bus, sessions, tools, pipeline)
 //! 2. Register + init enabled channel factories
 //! 3. Start all channels (each in its own tokio task)
-//! 4. Start background services (CronService, HeartbeatService)
-//! 5. Spawn the agent loop (consumes inbound, produces outbound)
-//! 6. Spawn the outbound dispatch loop (routes outbound to channels)
-//! 7. Wait for Ctrl+C, then gracefully shut everything down
+//! 4. Start background services (CronService, HeartbeatService, and
+//!    BackupService when `backup.enabled`) and the bus backpressure watch
+//! 5. Spawn the session retention pass, if `agents.sessions` sets limits
+//! 6. Spawn the agent loop (consumes inbound, produces outbound)
+//! 7. Spawn the outbound dispatch loop (routes outbound to channels)
+//! 8. Wait for Ctrl+C or SIGTERM, then shut down in order (below)
 //! ```
 //!
+//! Shutdown runs as ordered stages, each with a deadline after which it is
+//! forced and the next stage starts:
+//!
+//! 1. `schedulers`: stop cron, the heartbeat and scheduled backups, so no
+//!    job fires while the rest shuts down.
+//! 2. `bus`: wait for queued messages and running turns to finish and
+//!    their replies to be dispatched, then stop the agent and dispatch
+//!    loops. Forcing abandons the turns still running.
+//! 3. `channels`: stop every channel (and the API server).
+//! 4. `ledgers`: flush pending usage records.
+//! 5. `mcp`: send SIGTERM to MCP server processes, and SIGKILL to those
+//!    still running a few seconds later.
+//!
+//! The gateway then prints which stages completed cleanly. A second
+//! Ctrl+C or SIGTERM exits at once.
+//!
+//! While running, SIGHUP (sent by `weft channels reload`) re-reads the
+//! config and reloads channels in place: newly enabled channels start,
+//! removed ones stop, and only channels whose config changed restart.
+//! The same reload also applies agent defaults, routing rules, permissions
+//! and the spawn depth to the next turns; other changed sections are
+//! logged as needing a restart. With `--watch-config` the reload runs
+//! whenever the config file changes on disk, and `weft config reload`
+//! triggers it through the control socket and prints what changed.
+//!
+//! On Unix, `--daemon` runs the gateway in the background with its log in
+//! the structured log file; `weft gateway stop` and `weft gateway status`
+//! manage it (see [`super::daemon`]). Only one gateway runs per home
+//! directory: a live pid file stops a second one from starting.
+//!
+//! Replies stream into channels that can edit messages (Slack, Discord):
+//! the partial reply is posted and edited as it grows, at most once every
+//! 1.5 seconds, and the final text replaces it.
+//!
+//! With `delegation.serve` enabled, peer gateways can hand this one tasks
+//! through the API. They arrive on the `delegation` channel, run like any
+//! other message, and their answers are collected for the peer instead of
+//! being dispatched to a channel.
+//!
 //! # Example
 //!
 //! ```text
 //! weft gateway
 //! weft gateway --config /path/to/config.json
+//! weft gateway --watch-config
+//! weft gateway --daemon
+//! weft gateway status
+//! weft gateway stop
 //! ```
 
+#[cfg(all(feature = "services", feature = "channels"))]
+use std::path::Path;
 use std::sync::Arc;
+#[cfg(feature = "channels")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "channels")]
+use std::time::Duration;
 
-use clap::Args;
+use clap::{Args, Subcommand};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
@@ -34,25 +85,66 @@ use tracing::{debug, error, info, warn};
 use clawft_channels::PluginHost;
 #[cfg(feature = "channels")]
 use clawft_channels::discord::DiscordChannelFactory;
+#[cfg(feature = "irc")]
+use clawft_channels::irc::IrcChannelFactory;
 #[cfg(feature = "channels")]
 use clawft_channels::slack::SlackChannelFactory;
 #[cfg(feature = "channels")]
 use clawft_channels::telegram::TelegramChannelFactory;
 #[cfg(all(feature = "channels", feature = "api"))]
 use clawft_channels::web::{WebChannelFactory, WebPublisher};
+#[cfg(feature = "channels")]
+use clawft_core::agent::dispatch::DispatchMetrics;
+#[cfg(feature = "channels")]
+use clawft_core::agent::sink::{ResponseSink, ResponseSinkFactory};
+use clawft_core::agent::skills_v2::SkillRegistry;
+#[cfg(feature = "services")]
+use clawft_core::agent::templates::TemplateName;
+#[cfg(feature = "services")]
+use clawft_core::agent::trace::TurnOutcome;
+#[cfg(all(feature = "services", feature = "channels"))]
+use clawft_core::agent::turns::ActiveTurns;
+#[cfg(feature = "services")]
+use clawft_core::agent::turns::{TurnObserver, TurnReport};
 use clawft_core::bootstrap::AppContext;
+#[cfg(feature = "channels")]
+use clawft_core::config_reload::{ConfigReloader, ReloadReport};
+#[cfg(feature = "channels")]
+use clawft_core::outbox::DeferredOutbox;
+#[cfg(feature = "channels")]
+use clawft_core::runtime::shutdown::{ShutdownCoordinator, Stage};
+#[cfg(feature = "channels")]
+use clawft_core::session::RetentionPolicy;
+#[cfg(feature = "channels")]
+use clawft_core::session::retention::run_retention;
 use clawft_platform::NativePlatform;
 #[cfg(feature = "services")]
 use clawft_services::cron_service::CronService;
 #[cfg(feature = "services")]
-use clawft_services::heartbeat::HeartbeatService;
+use clawft_services::cron_service::history::{self, CronHistory};
+#[cfg(all(feature = "services", feature = "channels"))]
+use clawft_services::heartbeat::{
+    ChannelHealth, HeartbeatProbe, PendingJob, StatusSnapshot, UsageDay, status,
+};
+#[cfg(feature = "services")]
+use clawft_services::heartbeat::{HeartbeatReplies, HeartbeatService};
+#[cfg(feature = "services")]
+use clawft_services::mcp::transport::Termination;
+#[cfg(feature = "services")]
+use clawft_tools::schedule_tool::ScheduleTaskTool;
+#[cfg(feature = "services")]
+use clawft_types::cron::{CronRun, JobStatus};
+#[cfg(feature = "channels")]
+use clawft_types::event::InboundMessage;
 
 #[cfg(feature = "channels")]
 use crate::markdown::dispatch::MarkdownDispatcher;
 
+#[cfg(all(feature = "channels", unix))]
+use super::control::{ControlRequest, ControlResponse};
+use super::load_config;
 #[cfg(feature = "channels")]
 use super::make_channel_host;
-use super::load_config;
 
 #[cfg(feature = "api")]
 use clawft_services::api::{AgentInfo, ApiState};
@@ -63,6 +155,10 @@ use clawft_services::api::bridge::{
 };
 #[cfg(feature = "api")]
 use clawft_services::api::broadcaster::TopicBroadcaster;
+#[cfg(feature = "api")]
+use clawft_services::api::remote_tasks::{self, RemoteTasks};
+#[cfg(all(feature = "api", feature = "channels"))]
+use clawft_services::api::{ChannelAccess, ChannelMetricsInfo, ChannelStatusInfo};
 
 /// Arguments for the `weft gateway` subcommand.
 #[derive(Args)]
@@ -74,6 +170,34 @@ pub struct GatewayArgs {
     /// Enable intelligent routing (requires vector-memory feature).
     #[arg(long)]
     pub intelligent_routing: bool,
+
+    /// Reload channels automatically when the config file changes.
+    #[arg(long)]
+    pub watch_config: bool,
+
+    /// Run in the background, logging to the structured log file (Unix).
+    #[arg(long)]
+    pub daemon: bool,
+
+    /// Set on the background copy started by `--daemon`.
+    #[arg(long, hide = true)]
+    pub detached: bool,
+
+    #[command(subcommand)]
+    pub action: Option<GatewayAction>,
+}
+
+/// Subcommands for managing a background gateway.
+#[derive(Subcommand)]
+pub enum GatewayAction {
+    /// Stop the running gateway (SIGTERM) and wait for it to exit.
+    Stop {
+        /// Seconds to wait for the gateway to exit.
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Show whether a gateway is running, and its uptime.
+    Status,
 }
 
 /// Resolve the cron JSONL storage path.
@@ -95,12 +219,394 @@ fn resolve_cron_storage_path() -> std::path::PathBuf {
     std::path::PathBuf::from("cron.jsonl")
 }
 
+/// Records the outcome of cron-triggered turns with the cron service and
+/// sends the failure alerts it returns.
+///
+/// Returns the observer to attach to the agent loop.
+#[cfg(feature = "services")]
+fn spawn_cron_run_recorder(
+    svc: Arc<CronService>,
+    bus: Arc<clawft_core::bus::MessageBus>,
+) -> Arc<dyn TurnObserver> {
+    let (runs, mut received) = tokio::sync::mpsc::unbounded_channel::<(String, CronRun)>();
+    tokio::spawn(async move {
+        while let Some((job_id, run)) = received.recv().await {
+            match svc.record_outcome(&job_id, run).await {
+                Ok(Some(alert)) => {
+                    if let Err(e) = bus.dispatch_outbound(alert) {
+                        warn!(job_id, error = %e, "failed to send cron failure alert");
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!(job_id, error = %e, "failed to record cron run"),
+            }
+        }
+    });
+    Arc::new(CronRunObserver { runs })
+}
+
+/// Passes the outcome of each turn started by a cron job to
+/// [`spawn_cron_run_recorder`].
+#[cfg(feature = "services")]
+struct CronRunObserver {
+    runs: tokio::sync::mpsc::UnboundedSender<(String, CronRun)>,
+}
+
+#[cfg(feature = "services")]
+impl TurnObserver for CronRunObserver {
+    fn on_turn_end(&self, msg: &clawft_types::event::InboundMessage, report: &TurnReport) {
+        if msg.channel != "cron" {
+            return;
+        }
+        let Some(job_id) = msg.metadata.get("job_id").and_then(|v| v.as_str()) else {
+            return;
+        };
+        let status = match report.outcome {
+            TurnOutcome::Completed => JobStatus::Ok,
+            TurnOutcome::Cancelled => JobStatus::Skipped,
+            TurnOutcome::Error => JobStatus::Error,
+        };
+        let run = CronRun {
+            started_at: report.started_at,
+            finished_at: report.finished_at,
+            status,
+            output: report.output.clone(),
+            error: report.error.clone(),
+            input_tokens: report.input_tokens,
+            output_tokens: report.output_tokens,
+        };
+        let _ = self.runs.send((job_id.to_string(), run));
+    }
+}
+
+/// Gathers the status the heartbeat sends with its prompt and tells it
+/// which sessions have a turn running.
+#[cfg(all(feature = "services", feature = "channels"))]
+struct GatewayProbe {
+    cron: Option<Arc<CronService>>,
+    plugin_host: Arc<PluginHost>,
+    usage: Arc<clawft_llm::UsageTracker>,
+    turns: Arc<ActiveTurns>,
+    held_replies: Arc<AtomicUsize>,
+}
+
+#[cfg(all(feature = "services", feature = "channels"))]
+#[async_trait::async_trait]
+impl HeartbeatProbe for GatewayProbe {
+    async fn snapshot(&self) -> StatusSnapshot {
+        let mut snapshot = StatusSnapshot::new(chrono::Utc::now());
+        if let Some(cron) = &self.cron {
+            let mut jobs: Vec<PendingJob> = cron
+                .list_jobs()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|job| job.enabled)
+                .filter_map(|job| {
+                    Some(PendingJob {
+                        next_run: job.state.next_run_at?,
+                        name: job.name,
+                    })
+                })
+                .collect();
+            jobs.sort_by_key(|job| job.next_run);
+            jobs.truncate(status::MAX_PENDING_JOBS);
+            snapshot.pending_jobs = jobs;
+        }
+        snapshot.channels = self
+            .plugin_host
+            .metrics()
+            .await
+            .into_iter()
+            .map(|m| ChannelHealth {
+                status: m.status.unwrap_or_else(|| "unknown".into()),
+                channel: m.channel,
+                send_failures: m.send_failures,
+            })
+            .collect();
+        if let Some(ledger) = self.usage.ledger().map(Path::to_path_buf) {
+            let yesterday = chrono::Local::now().date_naive().pred_opt();
+            let summary = tokio::task::spawn_blocking(move || {
+                clawft_llm::usage::summarize_day(&ledger, yesterday?).ok()
+            })
+            .await
+            .ok()
+            .flatten();
+            snapshot.usage_yesterday = summary.map(|s| UsageDay {
+                requests: s.totals.requests,
+                input_tokens: s.totals.input_tokens,
+                output_tokens: s.totals.output_tokens,
+                cost_usd: s.totals.cost_usd,
+            });
+        }
+        snapshot.held_replies = self.held_replies.load(Ordering::Relaxed);
+        snapshot
+    }
+
+    fn is_busy(&self, session_key: &str) -> bool {
+        self.turns.is_running(session_key)
+    }
+}
+
+/// Records the agent's replies to heartbeats so the heartbeat can back
+/// off while there is nothing to report.
+#[cfg(feature = "services")]
+struct HeartbeatReplyObserver {
+    replies: Arc<HeartbeatReplies>,
+}
+
+#[cfg(feature = "services")]
+impl TurnObserver for HeartbeatReplyObserver {
+    fn on_turn_end(&self, msg: &clawft_types::event::InboundMessage, report: &TurnReport) {
+        if msg.channel == "heartbeat" && report.outcome == TurnOutcome::Completed {
+            self.replies.record(&report.output);
+        }
+    }
+}
+
+/// Path of the pid file written by a running gateway.
+///
+/// `weft channels reload` and `weft gateway stop` read it to find the
+/// process to signal.
+pub fn pid_file_path() -> Option<std::path::PathBuf> {
+    dirs::home_dir().map(|h| h.join(".clawft").join("state").join("gateway.pid"))
+}
+
+/// Write the current process id to [`pid_file_path`], best-effort.
+///
+/// On Unix the pid is written through the gateway's
+/// [`PidLock`](super::daemon::PidLock) instead.
+#[cfg(all(feature = "channels", not(unix)))]
+fn write_pid_file() -> Option<std::path::PathBuf> {
+    let path = pid_file_path()?;
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match std::fs::write(&path, std::process::id().to_string()) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "failed to write gateway pid file");
+            None
+        }
+    }
+}
+
+/// Channels the gateway should run for `config`, keyed by channel name.
+///
+/// Used both at startup and on SIGHUP, so the two always agree on which
+/// channels are enabled and with what settings.
+#[cfg(feature = "channels")]
+fn desired_channels(
+    config: &clawft_types::config::Config,
+    web_enabled: bool,
+) -> anyhow::Result<std::collections::HashMap<String, serde_json::Value>> {
+    let mut desired = std::collections::HashMap::new();
+
+    let telegram = &config.channels.telegram;
+    let telegram_has_token =
+        !telegram.token.is_empty() || telegram.token_env.as_ref().is_some_and(|v| !v.is_empty());
+    if telegram.enabled && telegram_has_token {
+        desired.insert("telegram".to_owned(), serde_json::to_value(telegram)?);
+    }
+
+    let slack = &config.channels.slack;
+    let slack_has_token =
+        !slack.bot_token.is_empty() || slack.bot_token_env.as_ref().is_some_and(|v| !v.is_empty());
+    if slack.enabled && slack_has_token {
+        desired.insert("slack".to_owned(), serde_json::to_value(slack)?);
+    }
+
+    let discord = &config.channels.discord;
+    let discord_has_token =
+        !discord.token.is_empty() || discord.token_env.as_ref().is_some_and(|v| !v.is_empty());
+    if discord.enabled && discord_has_token {
+        desired.insert("discord".to_owned(), serde_json::to_value(discord)?);
+    }
+
+    // IRC (config lives under `channels.irc`, carried in the extras map)
+    #[cfg(feature = "irc")]
+    if let Some(irc_config) = config.channels.extra.get("irc")
+        && irc_config
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    {
+        desired.insert("irc".to_owned(), irc_config.clone());
+    }
+
+    if web_enabled {
+        desired.insert("web".to_owned(), serde_json::json!({}));
+    }
+
+    Ok(desired)
+}
+
+/// Everything a config reload needs. SIGHUP, `--watch-config` and the
+/// control socket all reload through it.
+#[cfg(feature = "channels")]
+struct ReloadTarget {
+    plugin_host: Arc<PluginHost>,
+    reloader: ConfigReloader,
+    config_path: Option<String>,
+    web_enabled: bool,
+}
+
+#[cfg(feature = "channels")]
+impl ReloadTarget {
+    /// Re-read the config, apply the sections that change live and reload
+    /// channels to match it.
+    ///
+    /// A config that fails to load or validate changes nothing. Sections
+    /// that need a restart are logged and left as they are.
+    async fn reload(&self, platform: &NativePlatform) -> anyhow::Result<ReloadReport> {
+        let config = load_config(platform, self.config_path.as_deref())
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to load config: {e}"))?;
+        let desired = desired_channels(&config, self.web_enabled)
+            .map_err(|e| anyhow::anyhow!("invalid channel config: {e}"))?;
+        let report = self.reloader.reload(&config)?;
+        if !report.applied.is_empty() {
+            info!(sections = ?report.applied, "config changes applied");
+        }
+        if !report.restart_required.is_empty() {
+            warn!(
+                sections = ?report.restart_required,
+                "config changes need a gateway restart and were not applied"
+            );
+        }
+
+        let channels = self.plugin_host.reload(&desired).await;
+        info!(
+            started = ?channels.started,
+            stopped = ?channels.stopped,
+            restarted = ?channels.restarted,
+            unchanged = channels.unchanged.len(),
+            "channels reloaded"
+        );
+        for (name, e) in &channels.failed {
+            error!(channel = %name, error = %e, "channel failed to reload");
+        }
+        Ok(report)
+    }
+
+    /// [`reload`](Self::reload), logging a failure.
+    async fn reload_or_log(&self, platform: &NativePlatform) {
+        if let Err(e) = self.reload(platform).await {
+            error!(error = %e, "config reload aborted");
+        }
+    }
+}
+
+/// Spawn a task that reloads the config whenever the config file changes.
+///
+/// The file's directory is watched rather than the file itself so saves
+/// that replace the file (write-to-temp + rename) are still seen.
+#[cfg(feature = "channels")]
+fn spawn_reload_on_config_change(
+    target: Arc<ReloadTarget>,
+    cancel: CancellationToken,
+) -> anyhow::Result<()> {
+    use clawft_platform::Platform;
+    use clawft_platform::watch::{WatchOptions, concerns};
+
+    let platform = NativePlatform::new();
+    let config_file = match target.config_path {
+        Some(ref path) => std::path::PathBuf::from(path),
+        None => super::discover_config_path(&platform)
+            .ok_or_else(|| anyhow::anyhow!("--watch-config: no config file found to watch"))?,
+    };
+    let dir = match config_file.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => std::path::PathBuf::from("."),
+    };
+    let watcher = platform
+        .watcher()
+        .ok_or_else(|| anyhow::anyhow!("file watching is not supported on this platform"))?;
+    let mut handle = watcher
+        .watch(std::slice::from_ref(&dir), &WatchOptions::default())
+        .map_err(|e| anyhow::anyhow!("failed to watch {}: {e}", dir.display()))?;
+    info!(path = %config_file.display(), "watching config file for changes");
+
+    tokio::spawn(async move {
+        loop {
+            let batch = tokio::select! {
+                _ = cancel.cancelled() => break,
+                batch = handle.recv() => match batch {
+                    Some(batch) => batch,
+                    None => break,
+                },
+            };
+            if batch.iter().any(|e| concerns(e, &config_file)) {
+                info!("config file changed, reloading");
+                target.reload_or_log(&platform).await;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Spawn a task that reloads the config each time the process gets SIGHUP.
+#[cfg(all(feature = "channels", unix))]
+fn spawn_reload_on_sighup(
+    target: Arc<ReloadTarget>,
+    cancel: CancellationToken,
+) -> anyhow::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = signal(SignalKind::hangup())
+        .map_err(|e| anyhow::anyhow!("failed to install SIGHUP handler: {e}"))?;
+    tokio::spawn(async move {
+        let platform = NativePlatform::new();
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                received = hangup.recv() => {
+                    if received.is_none() {
+                        break;
+                    }
+                }
+            }
+            info!("received SIGHUP, reloading");
+            target.reload_or_log(&platform).await;
+        }
+    });
+    Ok(())
+}
+
+/// Sleep until `deadline`, or forever when there is none.
+#[cfg(feature = "channels")]
+async fn sleep_until(deadline: Option<chrono::DateTime<chrono::Utc>>) {
+    match deadline {
+        Some(at) => {
+            let wait = (at - chrono::Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 /// Run the gateway command.
 ///
 /// Loads configuration, bootstraps the [`AppContext`], registers all
 /// enabled channels, starts them, then runs the agent loop and outbound
 /// dispatch loop until Ctrl+C triggers graceful shutdown.
 pub async fn run(args: GatewayArgs) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        match args.action {
+            Some(GatewayAction::Stop { timeout }) => {
+                return super::daemon::stop(Duration::from_secs(timeout)).await;
+            }
+            Some(GatewayAction::Status) => return super::daemon::status().await,
+            None if args.daemon => return super::daemon::start().await,
+            None => {}
+        }
+    }
+    #[cfg(not(unix))]
+    if args.daemon || args.action.is_some() {
+        anyhow::bail!("running the gateway in the background is only supported on Unix");
+    }
+
     // If the channels feature is disabled, bail early with a helpful message.
     #[cfg(not(feature = "channels"))]
     {
@@ -121,24 +627,56 @@ pub async fn run(args: GatewayArgs) -> anyhow::Result<()> {
 #[cfg(feature = "channels")]
 async fn run_with_channels(args: GatewayArgs) -> anyhow::Result<()> {
     let platform = Arc::new(NativePlatform::new());
-    let config = load_config(&*platform, args.config.as_deref()).await?;
-    run_with_config(config, args.intelligent_routing, None).await
+    let mut config = load_config(&*platform, args.config.as_deref()).await?;
+    if args.detached {
+        // Nobody sees the terminal output of a background gateway.
+        config.gateway.logs.enabled = true;
+    }
+    run_with_config(
+        config,
+        args.config,
+        args.intelligent_routing,
+        args.watch_config,
+        None,
+    )
+    .await
 }
 
 /// Run the gateway with a pre-loaded [`Config`].
 ///
 /// This is the shared inner function used by both `weft gateway` and
 /// `weft ui`. The `static_dir` parameter, when `Some`, enables SPA-style
-/// static file serving for the built frontend.
+/// static file serving for the built frontend. `config_path` is the
+/// explicit config file (if any) re-read when the gateway receives SIGHUP
+/// or, with `watch_config`, whenever that file changes.
 #[cfg(feature = "channels")]
 pub async fn run_with_config(
     config: clawft_types::config::Config,
+    config_path: Option<String>,
     intelligent_routing: bool,
+    watch_config: bool,
     static_dir: Option<String>,
 ) -> anyhow::Result<()> {
+    let started_at = chrono::Utc::now();
+    #[cfg(unix)]
+    let mut pid_lock = match pid_file_path() {
+        Some(path) => Some(super::daemon::PidLock::acquire(&path)?),
+        None => None,
+    };
+    if config.gateway.logs.enabled {
+        let home =
+            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+        crate::log_sink::install(&config.gateway.logs, crate::log_sink::log_path(&home))?;
+    }
     info!("starting weft gateway");
 
-    let platform = Arc::new(NativePlatform::new());
+    if let Some(quiet) = &config.gateway.quiet_hours {
+        quiet
+            .validate()
+            .map_err(|e| anyhow::anyhow!("invalid gateway.quietHours: {e}"))?;
+    }
+
+    let platform = Arc::new(super::platform_for_config(&config)?);
 
     // ── Bootstrap AppContext (bus, sessions, tools, pipeline) ────────
     let mut ctx = AppContext::new(config.clone(), platform.clone())
@@ -146,7 +684,19 @@ pub async fn run_with_config(
         .map_err(|e| anyhow::anyhow!("failed to bootstrap app context: {e}"))?;
 
     // Register core tools (built-in + MCP proxied + delegation).
-    super::register_core_tools(ctx.tools_mut(), &config, platform.clone()).await;
+    let memory = ctx.memory_backend().clone();
+    let questions = ctx.questions().clone();
+    let usage = ctx.usage().clone();
+    #[cfg_attr(not(feature = "services"), allow(unused_variables))]
+    let mcp_servers = super::register_core_tools(
+        ctx.tools_mut(),
+        &config,
+        platform.clone(),
+        memory,
+        Some(questions),
+        Some(usage),
+    )
+    .await;
 
     // Register message tool (needs bus reference, cannot go in register_all).
     let bus_ref = ctx.bus().clone();
@@ -155,6 +705,39 @@ pub async fn run_with_config(
             bus_ref,
         )));
 
+    // The cron service is started with the other background services
+    // below; it is created here so `schedule_task` can add jobs to it.
+    // Both it and the heartbeat publish through the bus overflow policy,
+    // which sheds their events when the inbound queue is full.
+    #[cfg(feature = "services")]
+    let cron_service =
+        match CronService::new(resolve_cron_storage_path(), ctx.bus().event_sender()).await {
+            Ok(cron_service) => {
+                let mut cron_service =
+                    cron_service.with_quiet_hours(config.gateway.quiet_hours.clone());
+                if let Some(home) = dirs::home_dir() {
+                    cron_service = cron_service.with_history(CronHistory::new(
+                        history::history_dir(&home),
+                        history::DEFAULT_RETENTION,
+                    ));
+                }
+                let svc = Arc::new(cron_service);
+                let schedule_task = &config.tools.schedule_task;
+                if schedule_task.enabled {
+                    ctx.tools_mut().register(Arc::new(ScheduleTaskTool::new(
+                        svc.clone(),
+                        schedule_task.max_pending,
+                    )));
+                }
+                info!("cron service initialized");
+                Some(svc)
+            }
+            Err(e) => {
+                warn!(error = %e, "failed to initialize cron service, skipping");
+                None
+            }
+        };
+
     info!(tools = ctx.tools().len(), "tool registry initialized");
 
     // Wire the live LLM-backed pipeline so real provider calls work.
@@ -177,10 +760,18 @@ pub async fn run_with_config(
 
     // Clone shared references before consuming AppContext.
     let bus = ctx.bus().clone();
+    let usage = ctx.usage().clone();
 
-    // ── Cancellation token (shared by all background tasks) ─────────
+    // ── Cancellation tokens ──────────────────────────────────────────
+    // Cron and the heartbeat stop first on shutdown; everything else
+    // shares `cancel`.
+    let schedulers = CancellationToken::new();
     let cancel = CancellationToken::new();
 
+    // Channel metrics are shared by the plugin host (which records them)
+    // and the API server (which serves them), so create them up front.
+    let channel_metrics = Arc::new(clawft_channels::MetricsRegistry::new());
+
     // ── API server (optional, feature-gated) ────────────────────────
     //
     // The broadcaster is created here so it can be shared between the
@@ -195,10 +786,36 @@ pub async fn run_with_config(
     #[cfg(not(feature = "api"))]
     let api_broadcaster: Option<()> = None;
 
+    // Tasks from peer gateways: served through the API, finished by the
+    // agent loop reporting their turns.
+    #[cfg(feature = "api")]
+    let remote_tasks = {
+        use clawft_platform::Platform;
+        let serve = &config.delegation.serve;
+        let token = platform
+            .env()
+            .resolve_secret(&serve.token, serve.token_env.as_deref());
+        if serve.enabled && token.is_none() {
+            warn!("delegation.serve is enabled without a token; not serving delegated tasks");
+        }
+        if serve.enabled && !config.gateway.api_enabled {
+            warn!("delegation.serve needs gateway.api_enabled; not serving delegated tasks");
+        }
+        let token = token.filter(|_| config.gateway.api_enabled);
+        Arc::new(RemoteTasks::new(serve, token, ctx.bus().clone()).with_usage(ctx.usage().clone()))
+    };
+
     #[cfg(feature = "api")]
     let api_handle: Option<tokio::task::JoinHandle<()>> = if config.gateway.api_enabled {
         let broadcaster = api_broadcaster.clone().expect("broadcaster created above");
-        let api_state = build_api_state(&ctx, &config, broadcaster);
+        let api_state = build_api_state(
+            &ctx,
+            &config,
+            broadcaster,
+            channel_metrics.clone(),
+            mcp_servers.clone(),
+            remote_tasks.clone(),
+        );
         let cors_origins = config.gateway.cors_origins.clone();
         let api_host = config.gateway.host.clone();
         let port = config.gateway.api_port;
@@ -233,76 +850,53 @@ pub async fn run_with_config(
 
     // ── Channel setup ───────────────────────────────────────────────
     let host = make_channel_host(bus.clone());
-    let plugin_host = Arc::new(PluginHost::new(host));
-
-    let mut any_channel = false;
-
-    // Telegram
-    let telegram_has_token = !config.channels.telegram.token.is_empty()
-        || config.channels.telegram.token_env.as_ref().is_some_and(|v| !v.is_empty());
-    if config.channels.telegram.enabled && telegram_has_token {
-        plugin_host
-            .register_factory(Arc::new(TelegramChannelFactory))
-            .await;
-        let telegram_config = serde_json::to_value(&config.channels.telegram)?;
-        plugin_host
-            .init_channel("telegram", &telegram_config)
-            .await
-            .map_err(|e| anyhow::anyhow!("failed to init telegram channel: {e}"))?;
-        info!("telegram channel initialized");
-        any_channel = true;
-    }
-
-    // Slack
-    let slack_has_token = !config.channels.slack.bot_token.is_empty()
-        || config.channels.slack.bot_token_env.as_ref().is_some_and(|v| !v.is_empty());
-    if config.channels.slack.enabled && slack_has_token {
-        plugin_host
-            .register_factory(Arc::new(SlackChannelFactory))
-            .await;
-        let slack_config = serde_json::to_value(&config.channels.slack)?;
-        plugin_host
-            .init_channel("slack", &slack_config)
-            .await
-            .map_err(|e| anyhow::anyhow!("failed to init slack channel: {e}"))?;
-        info!("slack channel initialized");
-        any_channel = true;
-    }
-
-    // Discord
-    let discord_has_token = !config.channels.discord.token.is_empty()
-        || config.channels.discord.token_env.as_ref().is_some_and(|v| !v.is_empty());
-    if config.channels.discord.enabled && discord_has_token {
-        plugin_host
-            .register_factory(Arc::new(DiscordChannelFactory))
-            .await;
-        let discord_config = serde_json::to_value(&config.channels.discord)?;
-        plugin_host
-            .init_channel("discord", &discord_config)
-            .await
-            .map_err(|e| anyhow::anyhow!("failed to init discord channel: {e}"))?;
-        info!("discord channel initialized");
-        any_channel = true;
-    }
-
-    // Web channel — register when the API (and its broadcaster) is enabled.
+    let plugin_host = Arc::new(PluginHost::with_metrics(host, channel_metrics));
+
+    // Register every factory this build supports; which channels actually
+    // run is decided by `desired_channels`, so SIGHUP can enable a channel
+    // that was off at startup.
+    plugin_host
+        .register_factory(Arc::new(TelegramChannelFactory))
+        .await;
+    plugin_host
+        .register_factory(Arc::new(SlackChannelFactory))
+        .await;
+    plugin_host
+        .register_factory(Arc::new(DiscordChannelFactory))
+        .await;
+    #[cfg(feature = "irc")]
+    plugin_host
+        .register_factory(Arc::new(IrcChannelFactory))
+        .await;
+
+    // Web channel — available when the API (and its broadcaster) is enabled.
     #[cfg(feature = "api")]
-    if config.gateway.api_enabled
-        && let Some(ref broadcaster) = api_broadcaster
-    {
-        let publisher: Arc<dyn WebPublisher> = Arc::new(BroadcasterPublisher {
-            broadcaster: broadcaster.clone(),
-        });
-        plugin_host
-            .register_factory(Arc::new(WebChannelFactory::new(publisher)))
-            .await;
+    let web_enabled = match api_broadcaster {
+        Some(ref broadcaster) if config.gateway.api_enabled => {
+            let publisher: Arc<dyn WebPublisher> = Arc::new(BroadcasterPublisher {
+                broadcaster: broadcaster.clone(),
+            });
+            plugin_host
+                .register_factory(Arc::new(WebChannelFactory::new(publisher)))
+                .await;
+            true
+        }
+        _ => false,
+    };
+    #[cfg(not(feature = "api"))]
+    let web_enabled = false;
+
+    let desired = desired_channels(&config, web_enabled)?;
+    let mut names: Vec<&String> = desired.keys().collect();
+    names.sort();
+    for name in names {
         plugin_host
-            .init_channel("web", &serde_json::json!({}))
+            .init_channel(name, &desired[name])
             .await
-            .map_err(|e| anyhow::anyhow!("failed to init web channel: {e}"))?;
-        info!("web channel initialized");
-        any_channel = true;
+            .map_err(|e| anyhow::anyhow!("failed to init {name} channel: {e}"))?;
+        info!(channel = %name, "channel initialized");
     }
+    let any_channel = !desired.is_empty();
 
     if !any_channel && !config.gateway.api_enabled {
         anyhow::bail!(
@@ -328,54 +922,75 @@ pub async fn run_with_config(
         anyhow::bail!("no channels started successfully and API is disabled");
     }
 
+    // ── Backpressure watch ───────────────────────────────────────────
+    let backpressure_warn_secs = config.agents.dispatch.backpressure_warn_secs;
+    if backpressure_warn_secs > 0 {
+        let bus = bus.clone();
+        let watch_cancel = cancel.clone();
+        tokio::spawn(async move {
+            clawft_core::bus::watch_backpressure(
+                &bus,
+                std::time::Duration::from_secs(backpressure_warn_secs),
+                &watch_cancel,
+            )
+            .await;
+        });
+    }
+
     // ── Background services ──────────────────────────────────────────
 
+    // Replies held for quiet hours, counted by the dispatch loop for the
+    // heartbeat's status snapshot.
+    let held_replies = Arc::new(AtomicUsize::new(0));
+
     #[cfg(feature = "services")]
-    let (cron_handle, heartbeat_handle) = {
-        // CronService
-        let inbound_tx = bus.inbound_sender();
-        let cron_storage = resolve_cron_storage_path();
-        let cron_handle = match CronService::new(cron_storage, inbound_tx.clone()).await {
-            Ok(cron_service) => {
-                let cron_cancel = cancel.clone();
-                let svc = std::sync::Arc::new(cron_service);
+    let heartbeat_cron = cron_service.clone();
+    #[cfg(feature = "services")]
+    let (cron_handle, heartbeat, cron_observer) = {
+        let inbound_tx = bus.event_sender();
+        let (cron_handle, cron_observer) = match cron_service {
+            Some(svc) => {
+                let cron_cancel = schedulers.clone();
                 let svc_clone = svc.clone();
-                info!("cron service initialized");
-                Some(tokio::spawn(async move {
+                let handle = tokio::spawn(async move {
                     if let Err(e) = svc_clone.start(cron_cancel).await {
                         error!(error = %e, "cron service exited with error");
                     }
-                }))
-            }
-            Err(e) => {
-                warn!(error = %e, "failed to initialize cron service, skipping");
-                None
+                });
+                (
+                    Some(handle),
+                    Some(spawn_cron_run_recorder(svc, bus.clone())),
+                )
             }
+            None => (None, None),
         };
 
-        // HeartbeatService
-        let heartbeat_handle = if config.gateway.heartbeat_interval_minutes > 0 {
-            let svc = HeartbeatService::new(
-                config.gateway.heartbeat_interval_minutes,
-                config.gateway.heartbeat_prompt.clone(),
-                inbound_tx,
+        // HeartbeatService, spawned once the agent loop can report busy
+        // sessions.
+        let heartbeat = if config.gateway.heartbeat_interval_minutes > 0 {
+            let prompt = ctx.templates().render(
+                TemplateName::Heartbeat,
+                None,
+                &[("prompt", &config.gateway.heartbeat_prompt)],
             );
-            let hb_cancel = cancel.clone();
-            info!(
-                interval_minutes = config.gateway.heartbeat_interval_minutes,
-                "heartbeat service started"
-            );
-            Some(tokio::spawn(async move {
-                if let Err(e) = svc.start(hb_cancel).await {
-                    error!(error = %e, "heartbeat service exited with error");
-                }
-            }))
+            Some(
+                HeartbeatService::new(
+                    config.gateway.heartbeat_interval_minutes,
+                    prompt,
+                    inbound_tx,
+                )
+                .with_delivery(config.gateway.heartbeat_delivery())
+                .with_backoff(
+                    config.gateway.heartbeat_backoff_after,
+                    config.gateway.heartbeat_max_interval_minutes,
+                ),
+            )
         } else {
             debug!("heartbeat service disabled (interval=0)");
             None
         };
 
-        (cron_handle, heartbeat_handle)
+        (cron_handle, heartbeat, cron_observer)
     };
 
     #[cfg(not(feature = "services"))]
@@ -387,9 +1002,122 @@ pub async fn run_with_config(
         (None, None)
     };
 
+    // Scheduled backups of memory, sessions, config and skills.
+    #[cfg(feature = "services")]
+    let backup_handle = if config.backup.enabled {
+        match super::workspace_cmd::backup_service(&config, platform.as_ref()) {
+            Ok(svc) => {
+                let backup_cancel = schedulers.clone();
+                Some(tokio::spawn(async move {
+                    if let Err(e) = svc.start(backup_cancel).await {
+                        error!(error = %e, "backup service exited with error");
+                    }
+                }))
+            }
+            Err(e) => {
+                warn!(error = %e, "backup service disabled");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(not(feature = "services"))]
+    let backup_handle: Option<tokio::task::JoinHandle<()>> = None;
+
     // ── Agent loop (inbound processing) ─────────────────────────────
-    let agent = ctx.into_agent_loop().with_cancel(cancel.clone());
+    let sessions = ctx.sessions().clone();
+    let reloader = ctx.config_reloader();
+    #[cfg(feature = "services")]
+    let heartbeat_usage = ctx.usage().clone();
+    let agent = ctx
+        .into_agent_loop()
+        .with_cancel(cancel.clone())
+        .with_response_sinks(Arc::new(ProgressiveSinks {
+            host: plugin_host.clone(),
+        }));
+    #[cfg(feature = "services")]
+    let agent = match cron_observer {
+        Some(observer) => agent.with_turn_observer(observer),
+        None => agent,
+    };
+    #[cfg(feature = "services")]
+    let (agent, heartbeat_handle) = match heartbeat {
+        Some(svc) => {
+            let svc = svc.with_probe(Arc::new(GatewayProbe {
+                cron: heartbeat_cron,
+                plugin_host: plugin_host.clone(),
+                usage: heartbeat_usage,
+                turns: agent.active_turns(),
+                held_replies: held_replies.clone(),
+            }));
+            let agent = agent.with_turn_observer(Arc::new(HeartbeatReplyObserver {
+                replies: svc.replies(),
+            }));
+            let hb_cancel = schedulers.clone();
+            info!(
+                interval_minutes = config.gateway.heartbeat_interval_minutes,
+                "heartbeat service started"
+            );
+            let handle = tokio::spawn(async move {
+                if let Err(e) = svc.start(hb_cancel).await {
+                    error!(error = %e, "heartbeat service exited with error");
+                }
+            });
+            (agent, Some(handle))
+        }
+        None => (agent, None),
+    };
+    #[cfg(feature = "api")]
+    let agent = if remote_tasks.enabled() {
+        info!(agents = ?config.delegation.serve.agents, "serving delegated tasks");
+        agent.with_turn_observer(remote_tasks.clone())
+    } else {
+        agent
+    };
+    // Automatic skill activation. Workspace skills are only trusted by
+    // `weft agent --trust-project-skills`, so the gateway uses user skills.
+    let agent = if config.agents.skills.auto_activate {
+        let (_, user_dir) = super::agent::discover_skill_dirs();
+        match SkillRegistry::discover_with_trust(None, user_dir.as_deref(), Vec::new(), false).await
+        {
+            Ok(registry) => {
+                info!(skills = registry.len(), "skill auto-activation enabled");
+                agent.with_skill_registry(Arc::new(tokio::sync::RwLock::new(registry)))
+            }
+            Err(e) => {
+                warn!(error = %e, "skill discovery failed; auto-activation disabled");
+                agent
+            }
+        }
+    } else {
+        agent
+    };
+
+    // ── Session retention ───────────────────────────────────────────
+    // Archives idle and excess sessions, skipping any with a turn running.
+    if let Some(policy) = RetentionPolicy::from_config(&config.agents.sessions) {
+        let turns = agent.active_turns();
+        let interval = config.agents.sessions.retention_interval_minutes.max(1) * 60;
+        let retention_cancel = cancel.clone();
+        info!(
+            max_idle_days = config.agents.sessions.max_idle_days,
+            max_sessions = config.agents.sessions.max_sessions,
+            "session retention enabled"
+        );
+        tokio::spawn(async move {
+            run_retention(
+                &sessions,
+                policy,
+                std::time::Duration::from_secs(interval),
+                &turns,
+                &retention_cancel,
+            )
+            .await;
+        });
+    }
 
+    let dispatch_metrics = agent.dispatch_metrics();
     let agent_handle = tokio::spawn(async move {
         if let Err(e) = agent.run().await {
             error!(error = %e, "agent loop exited with error");
@@ -407,71 +1135,35 @@ pub async fn run_with_config(
     let dispatch_broadcaster = api_broadcaster.clone();
 
     let dispatch_handle = tokio::spawn(async move {
+        // Replies to scheduled prompts wait here during their quiet hours.
+        let mut outbox = DeferredOutbox::new();
         loop {
-            tokio::select! {
+            held_replies.store(outbox.len(), Ordering::Relaxed);
+            let next_release = outbox.next_release();
+            let ready = tokio::select! {
                 biased;
 
                 _ = cancel_for_dispatch.cancelled() => {
+                    if !outbox.is_empty() {
+                        warn!(held = outbox.len(), "dropping messages held for quiet hours");
+                    }
                     info!("outbound dispatch loop shutting down");
                     break;
                 }
 
+                _ = sleep_until(next_release) => outbox.release_due(chrono::Utc::now()),
+
                 msg = bus_for_dispatch.consume_outbound() => {
                     match msg {
-                        Some(mut outbound) => {
-                            debug!(
-                                channel = %outbound.channel,
-                                chat_id = %outbound.chat_id,
-                                "dispatching outbound message"
-                            );
-                            // Convert markdown to channel-specific format.
-                            outbound.content = md_dispatcher.convert(
-                                &outbound.channel,
-                                &outbound.content,
-                            );
-
-                            // Route through the plugin host — all channels
-                            // (telegram, slack, discord, web) are registered.
-                            if let Err(e) = plugin_host_for_dispatch
-                                .send_to_channel(&outbound)
-                                .await
-                            {
-                                error!(
-                                    channel = %outbound.channel,
-                                    chat_id = %outbound.chat_id,
-                                    error = %e,
-                                    "outbound dispatch failed"
+                        Some(outbound) => {
+                            let ready = outbox.admit(outbound, chrono::Utc::now());
+                            if ready.is_none() {
+                                info!(
+                                    until = ?outbox.next_release(),
+                                    "holding outbound message for quiet hours"
                                 );
                             }
-
-                            // For non-web channels, also broadcast to
-                            // WebSocket/SSE subscribers so the web dashboard
-                            // can display messages from all channels.
-                            // (Web channel messages are already broadcast by
-                            // WebChannel::send().)
-                            #[cfg(feature = "api")]
-                            if outbound.channel != "web"
-                                && let Some(ref bc) = dispatch_broadcaster
-                            {
-                                let topic = format!("sessions:{}", outbound.chat_id);
-                                let msg = serde_json::json!({
-                                    "type": "message",
-                                    "role": "assistant",
-                                    "content": &outbound.content,
-                                    "session_key": &outbound.chat_id,
-                                    "channel": &outbound.channel,
-                                    "timestamp": chrono::Utc::now().to_rfc3339()
-                                });
-                                let bc = bc.clone();
-                                let chat_id = outbound.chat_id.clone();
-                                tokio::spawn(async move {
-                                    bc.publish(&topic, msg).await;
-                                    bc.publish("sessions", serde_json::json!({
-                                        "type": "message_added",
-                                        "session_key": &chat_id
-                                    })).await;
-                                });
-                            }
+                            ready.into_iter().collect::<Vec<_>>()
                         }
                         None => {
                             info!("outbound bus closed, dispatch loop exiting");
@@ -479,6 +1171,66 @@ pub async fn run_with_config(
                         }
                     }
                 }
+            };
+
+            for mut outbound in ready {
+                // Answers to delegated tasks are collected by the turn
+                // observer; there is no channel to send them to.
+                #[cfg(feature = "api")]
+                if outbound.channel == remote_tasks::CHANNEL {
+                    continue;
+                }
+                debug!(
+                    channel = %outbound.channel,
+                    chat_id = %outbound.chat_id,
+                    "dispatching outbound message"
+                );
+                // Convert markdown to channel-specific format.
+                outbound.content = md_dispatcher.convert(&outbound.channel, &outbound.content);
+
+                // Route through the plugin host — all channels
+                // (telegram, slack, discord, web) are registered.
+                if let Err(e) = plugin_host_for_dispatch.send_to_channel(&outbound).await {
+                    error!(
+                        channel = %outbound.channel,
+                        chat_id = %outbound.chat_id,
+                        error = %e,
+                        "outbound dispatch failed"
+                    );
+                }
+
+                // For non-web channels, also broadcast to
+                // WebSocket/SSE subscribers so the web dashboard
+                // can display messages from all channels.
+                // (Web channel messages are already broadcast by
+                // WebChannel::send().)
+                #[cfg(feature = "api")]
+                if outbound.channel != "web"
+                    && let Some(ref bc) = dispatch_broadcaster
+                {
+                    let topic = format!("sessions:{}", outbound.chat_id);
+                    let msg = serde_json::json!({
+                        "type": "message",
+                        "role": "assistant",
+                        "content": &outbound.content,
+                        "session_key": &outbound.chat_id,
+                        "channel": &outbound.channel,
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    });
+                    let bc = bc.clone();
+                    let chat_id = outbound.chat_id.clone();
+                    tokio::spawn(async move {
+                        bc.publish(&topic, msg).await;
+                        bc.publish(
+                            "sessions",
+                            serde_json::json!({
+                                "type": "message_added",
+                                "session_key": &chat_id
+                            }),
+                        )
+                        .await;
+                    });
+                }
             }
         }
     });
@@ -490,63 +1242,285 @@ pub async fn run_with_config(
         if started_count == 1 { "" } else { "s" }
     );
 
+    // ── Config reload (SIGHUP, --watch-config, control socket) ──────
+    let reload_target = Arc::new(ReloadTarget {
+        plugin_host: plugin_host.clone(),
+        reloader,
+        config_path,
+        web_enabled,
+    });
+    if watch_config {
+        spawn_reload_on_config_change(reload_target.clone(), cancel.clone())?;
+    }
+    #[cfg(unix)]
+    {
+        spawn_reload_on_sighup(reload_target.clone(), cancel.clone())?;
+        if let Some(path) = super::control::socket_path() {
+            let target = reload_target.clone();
+            let served = super::control::spawn_control_server(
+                path,
+                move |request| {
+                    let target = target.clone();
+                    async move {
+                        match request {
+                            ControlRequest::ReloadConfig => {
+                                match target.reload(&NativePlatform::new()).await {
+                                    Ok(report) => ControlResponse::Reloaded { report },
+                                    Err(e) => ControlResponse::Error {
+                                        message: e.to_string(),
+                                    },
+                                }
+                            }
+                            ControlRequest::Status => ControlResponse::Running {
+                                pid: std::process::id(),
+                                started_at,
+                                version: env!("CARGO_PKG_VERSION").into(),
+                            },
+                        }
+                    }
+                },
+                cancel.clone(),
+            );
+            if let Err(e) = served {
+                warn!(error = %e, "control socket unavailable, `weft config reload` will not work");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (reload_target, started_at);
+    #[cfg(unix)]
+    if let Some(lock) = pid_lock.as_mut()
+        && let Err(e) = lock.write_pid()
+    {
+        warn!(error = %e, "failed to write gateway pid file");
+    }
+    #[cfg(not(unix))]
+    let pid_file = write_pid_file();
+
     // ── Wait for shutdown signal ────────────────────────────────────
-    tokio::signal::ctrl_c().await?;
+    let signal = shutdown_signal().await?;
+    #[cfg(unix)]
+    if let Some(lock) = pid_lock.as_mut() {
+        lock.clear();
+    }
+    #[cfg(not(unix))]
+    if let Some(ref path) = pid_file {
+        let _ = std::fs::remove_file(path);
+    }
     eprintln!("\nshutting down...");
-    info!("received shutdown signal");
+    info!(signal, "received shutdown signal");
 
-    // Spawn a force-exit handler: second Ctrl+C or 10s timeout kills the process.
+    // A second signal skips whatever is left of the shutdown.
     tokio::spawn(async {
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                eprintln!("forced exit");
-            }
-            _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
-                eprintln!("shutdown timed out, forcing exit");
-            }
-        }
+        let _ = shutdown_signal().await;
+        eprintln!("forced exit");
         std::process::exit(1);
     });
 
-    // 1. Cancel the dispatch loop and channel tasks.
-    cancel.cancel();
-
-    // 2. Stop all channels (cancels their tasks).
-    let stop_results = plugin_host.stop_all().await;
-    for (name, result) in &stop_results {
-        match result {
-            Ok(()) => info!(channel = %name, "channel stopped"),
-            Err(e) => {
-                warn!(channel = %name, error = %e, "channel stop error");
+    let schedulers_tasks: Vec<_> = cron_handle
+        .into_iter()
+        .chain(heartbeat_handle)
+        .chain(backup_handle)
+        .collect();
+    let schedulers_aborts: Vec<_> = schedulers_tasks.iter().map(|h| h.abort_handle()).collect();
+    let loop_aborts = [agent_handle.abort_handle(), dispatch_handle.abort_handle()];
+    let force_cancel = cancel.clone();
+
+    let shutdown = ShutdownCoordinator::new()
+        .then(
+            Stage::new("schedulers", SCHEDULER_STOP_DEADLINE, async move {
+                schedulers.cancel();
+                for handle in schedulers_tasks {
+                    let _ = handle.await;
+                }
+                Ok(())
+            })
+            .on_timeout(move || schedulers_aborts.iter().for_each(|h| h.abort())),
+        )
+        .then(
+            Stage::new("bus", BUS_DRAIN_DEADLINE, async move {
+                wait_until_drained(&bus, &dispatch_metrics).await;
+                cancel.cancel();
+                let _ = agent_handle.await;
+                let _ = dispatch_handle.await;
+                Ok(())
+            })
+            .on_timeout(move || {
+                force_cancel.cancel();
+                loop_aborts.iter().for_each(|h| h.abort());
+            }),
+        )
+        .then(Stage::new("channels", CHANNEL_STOP_DEADLINE, async move {
+            let mut failed = Vec::new();
+            for (name, result) in plugin_host.stop_all().await {
+                match result {
+                    Ok(()) => info!(channel = %name, "channel stopped"),
+                    Err(e) => {
+                        warn!(channel = %name, error = %e, "channel stop error");
+                        failed.push(format!("{name}: {e}"));
+                    }
+                }
             }
-        }
+            if let Some(h) = api_handle {
+                let _ = h.await;
+            }
+            if failed.is_empty() {
+                Ok(())
+            } else {
+                Err(failed.join("; "))
+            }
+        }))
+        .then(Stage::new("ledgers", LEDGER_FLUSH_DEADLINE, async move {
+            usage.flush().map_err(|e| format!("usage ledger: {e}"))
+        }));
+    #[cfg(feature = "services")]
+    let shutdown = shutdown.then(Stage::new(
+        "mcp",
+        MCP_TERM_GRACE + Duration::from_secs(2),
+        async move {
+            let killed: Vec<_> = mcp_servers
+                .shutdown(MCP_TERM_GRACE)
+                .await
+                .into_iter()
+                .filter(|(_, ended)| *ended == Termination::Killed)
+                .map(|(server, _)| server)
+                .collect();
+            if killed.is_empty() {
+                Ok(())
+            } else {
+                Err(format!("killed after SIGTERM: {}", killed.join(", ")))
+            }
+        },
+    ));
+    let report = shutdown.run().await;
+    eprint!("shutdown report:\n{report}");
+
+    if !report.is_clean() {
+        warn!("gateway shutdown forced or incomplete");
     }
+    info!("gateway shutdown complete");
+    Ok(())
+}
+
+/// How long cron and the heartbeat get to stop on shutdown.
+#[cfg(feature = "channels")]
+const SCHEDULER_STOP_DEADLINE: Duration = Duration::from_secs(5);
+
+/// How long queued messages and running turns get to finish on shutdown.
+#[cfg(feature = "channels")]
+const BUS_DRAIN_DEADLINE: Duration = Duration::from_secs(15);
+
+/// How long the channels get to stop on shutdown.
+#[cfg(feature = "channels")]
+const CHANNEL_STOP_DEADLINE: Duration = Duration::from_secs(10);
+
+/// How long flushing the usage ledger may take on shutdown.
+#[cfg(feature = "channels")]
+const LEDGER_FLUSH_DEADLINE: Duration = Duration::from_secs(5);
 
-    // 3. Drop the plugin host so its Arc<ChannelHost> (which holds a bus
-    //    clone) is released. Then drop our bus reference. Once all senders
-    //    are gone, the agent loop's consume_inbound() returns None and exits.
-    drop(plugin_host);
-    drop(bus);
+/// How long MCP servers get to exit after SIGTERM before they are killed.
+#[cfg(feature = "services")]
+const MCP_TERM_GRACE: Duration = Duration::from_secs(3);
+
+/// How often the shutdown drain checks whether the bus is idle.
+#[cfg(feature = "channels")]
+const DRAIN_POLL: Duration = Duration::from_millis(100);
+
+/// Wait for Ctrl+C or, on Unix, SIGTERM. Returns which arrived.
+#[cfg(feature = "channels")]
+async fn shutdown_signal() -> std::io::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result.map(|()| "interrupt"),
+            _ = terminate.recv() => Ok("terminate"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.map(|()| "interrupt")
+    }
+}
 
-    // 4. Await background services.
-    if let Some(h) = cron_handle {
-        let _ = h.await;
+/// Wait until no message is queued on the bus or in the agent's session
+/// queues and no turn is running, as seen on two polls in a row (a
+/// message taken off the bus is briefly in neither).
+#[cfg(feature = "channels")]
+async fn wait_until_drained(bus: &clawft_core::bus::MessageBus, dispatch: &DispatchMetrics) {
+    let idle = || {
+        let stats = bus.stats();
+        stats.inbound_depth == 0
+            && stats.outbound_depth == 0
+            && dispatch
+                .snapshot()
+                .values()
+                .all(|channel| channel.queued == 0 && channel.active == 0)
+    };
+    let mut idle_polls = 0;
+    while idle_polls < 2 {
+        idle_polls = if idle() { idle_polls + 1 } else { 0 };
+        if idle_polls < 2 {
+            tokio::time::sleep(DRAIN_POLL).await;
+        }
     }
-    if let Some(h) = heartbeat_handle {
-        let _ = h.await;
+}
+
+/// Opens a [`ProgressiveReply`](clawft_channels::ProgressiveReply) on the
+/// plugin host for every inbound message, so replies are shown while they
+/// stream.
+#[cfg(feature = "channels")]
+struct ProgressiveSinks {
+    host: Arc<PluginHost>,
+}
+
+#[cfg(feature = "channels")]
+impl ResponseSinkFactory for ProgressiveSinks {
+    fn open(&self, msg: &InboundMessage) -> Option<Arc<dyn ResponseSink>> {
+        let reply = self.host.open_progressive(&msg.channel, &msg.chat_id);
+        let (text, mut updates) = tokio::sync::watch::channel(String::new());
+        let host = self.host.clone();
+        // Updates that arrive while an edit is in flight collapse into the
+        // latest text; the task ends when the sink is dropped.
+        tokio::spawn(async move {
+            while updates.changed().await.is_ok() {
+                let text = updates.borrow_and_update().clone();
+                host.update_progressive(&reply, &text).await;
+            }
+        });
+        Some(Arc::new(ProgressiveSink {
+            text,
+            after_tool: AtomicBool::new(false),
+        }))
     }
+}
 
-    // 5. Await background tasks.
-    let _ = dispatch_handle.await;
-    let _ = agent_handle.await;
+/// Feeds the text of the current completion to a progressive reply.
+#[cfg(feature = "channels")]
+struct ProgressiveSink {
+    text: tokio::sync::watch::Sender<String>,
+    /// Set while the tool notice is shown; the next delta replaces it.
+    after_tool: AtomicBool,
+}
 
-    // 6. Await API server (if running).
-    if let Some(h) = api_handle {
-        let _ = h.await;
+#[cfg(feature = "channels")]
+impl ResponseSink for ProgressiveSink {
+    fn on_text_delta(&self, delta: &str) {
+        if self.after_tool.swap(false, Ordering::Relaxed) {
+            self.text.send_replace(delta.to_owned());
+        } else {
+            self.text.send_modify(|text| text.push_str(delta));
+        }
     }
 
-    info!("gateway shutdown complete");
-    Ok(())
+    fn on_tool_call_started(&self, _id: &str, name: &str) {
+        self.after_tool.store(true, Ordering::Relaxed);
+        self.text.send_replace(format!("Running {name}..."));
+    }
+
+    fn on_complete(&self, _text: &str) {}
 }
 
 /// Bridges [`TopicBroadcaster`] to the [`WebPublisher`] trait so the
@@ -564,18 +1538,74 @@ impl WebPublisher for BroadcasterPublisher {
     }
 }
 
+/// Serves live channel counters from the plugin host's
+/// [`MetricsRegistry`](clawft_channels::MetricsRegistry) on top of the
+/// config-derived [`ChannelBridge`].
+#[cfg(all(feature = "api", feature = "channels"))]
+struct LiveChannelAccess {
+    bridge: ChannelBridge,
+    metrics: Arc<clawft_channels::MetricsRegistry>,
+    prometheus: bool,
+}
+
+#[cfg(all(feature = "api", feature = "channels"))]
+impl ChannelAccess for LiveChannelAccess {
+    fn list_channels(&self) -> Vec<ChannelStatusInfo> {
+        let snapshots = self.metrics.snapshot();
+        let mut list = self.bridge.list_channels();
+        for info in &mut list {
+            if let Some(snap) = snapshots.iter().find(|s| s.channel == info.name) {
+                info.message_count = snap.inbound_received + snap.outbound_sent;
+                info.last_activity = snap
+                    .last_inbound
+                    .max(snap.last_outbound)
+                    .map(|t| t.to_rfc3339());
+            }
+        }
+        list
+    }
+
+    fn channel_metrics(&self) -> Vec<ChannelMetricsInfo> {
+        self.metrics
+            .snapshot()
+            .into_iter()
+            .map(|s| ChannelMetricsInfo {
+                channel: s.channel,
+                status: s.status,
+                inbound_received: s.inbound_received,
+                outbound_sent: s.outbound_sent,
+                send_failures: s.send_failures,
+                reconnects: s.reconnects,
+                last_inbound: s.last_inbound.map(|t| t.to_rfc3339()),
+                last_outbound: s.last_outbound.map(|t| t.to_rfc3339()),
+            })
+            .collect()
+    }
+
+    fn prometheus_metrics(&self) -> Option<String> {
+        self.prometheus
+            .then(|| clawft_channels::metrics::render_prometheus(&self.metrics.snapshot()))
+    }
+}
+
 /// Build an [`ApiState`] from an [`AppContext`] by extracting shared Arc
 /// references and wrapping them in bridge implementations.
 ///
 /// The `broadcaster` parameter is the shared [`TopicBroadcaster`] that is
 /// also passed to the outbound dispatch loop for publishing events.
 ///
+/// `mcp_servers` are the MCP servers whose health the API reports, and
+/// `remote_tasks` the tasks peer gateways delegated to this one.
+///
 /// Must be called BEFORE `ctx.into_agent_loop()`, which consumes the context.
 #[cfg(all(feature = "api", feature = "channels"))]
 fn build_api_state(
     ctx: &AppContext<NativePlatform>,
     config: &clawft_types::config::Config,
     broadcaster: Arc<TopicBroadcaster>,
+    channel_metrics: Arc<clawft_channels::MetricsRegistry>,
+    mcp_servers: crate::mcp_tools::McpServers,
+    remote_tasks: Arc<RemoteTasks>,
 ) -> ApiState {
     use clawft_services::api::auth::TokenStore;
 
@@ -585,8 +1615,11 @@ fn build_api_state(
     let skill_bridge = SkillBridge::new(ctx.skills().clone());
     let memory_bridge = MemoryBridge::new(ctx.memory().clone());
     let config_bridge = ConfigBridge::new(config.clone());
-    let channel_bridge =
-        ChannelBridge::from_config(&config.channels, config.gateway.api_enabled);
+    let channel_bridge = LiveChannelAccess {
+        bridge: ChannelBridge::from_config(&config.channels, config.gateway.api_enabled),
+        metrics: channel_metrics,
+        prometheus: config.gateway.metrics_enabled,
+    };
 
     // Discover agents from the 3-level hierarchy (workspace > user > builtin).
     let user_agents_dir = dirs::home_dir().map(|h| h.join(".clawft").join("agents"));
@@ -635,7 +1668,9 @@ fn build_api_state(
         config: Arc::new(config_bridge),
         channels: Arc::new(channel_bridge),
         voice: Arc::new(voice_bridge),
+        mcp: mcp_servers,
         broadcaster,
+        remote_tasks,
     }
 }
 
@@ -648,6 +1683,10 @@ mod tests {
         let args = GatewayArgs {
             config: None,
             intelligent_routing: false,
+            watch_config: false,
+            daemon: false,
+            detached: false,
+            action: None,
         };
         assert!(args.config.is_none());
     }
@@ -657,10 +1696,59 @@ mod tests {
         let args = GatewayArgs {
             config: Some("/tmp/gw-config.json".into()),
             intelligent_routing: false,
+            watch_config: false,
+            daemon: false,
+            detached: false,
+            action: None,
         };
         assert_eq!(args.config.as_deref(), Some("/tmp/gw-config.json"));
     }
 
+    #[test]
+    fn gateway_args_parse_watch_config() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            args: GatewayArgs,
+        }
+
+        let cli = Cli::parse_from(["weft", "--watch-config"]);
+        assert!(cli.args.watch_config);
+        let cli = Cli::parse_from(["weft"]);
+        assert!(!cli.args.watch_config);
+    }
+
+    #[cfg(feature = "channels")]
+    #[tokio::test]
+    async fn drain_waits_for_queued_replies() {
+        let bus = Arc::new(clawft_core::bus::MessageBus::new());
+        let dispatch = DispatchMetrics::new();
+        bus.dispatch_outbound(clawft_types::event::OutboundMessage {
+            channel: "slack".into(),
+            chat_id: "C1".into(),
+            content: "done".into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: Default::default(),
+        })
+        .unwrap();
+
+        let drained = tokio::time::timeout(
+            Duration::from_millis(300),
+            wait_until_drained(&bus, &dispatch),
+        );
+        assert!(drained.await.is_err(), "a reply is still queued");
+
+        let consumer = bus.clone();
+        tokio::spawn(async move { consumer.consume_outbound().await });
+        tokio::time::timeout(Duration::from_secs(5), wait_until_drained(&bus, &dispatch))
+            .await
+            .expect("drained once the reply is dispatched");
+    }
+
     #[cfg(feature = "services")]
     #[test]
     fn resolve_cron_storage_path_returns_valid() {
@@ -0,0 +1,349 @@
+//! `weft logs` -- read the gateway's structured log file.
+//!
+//! Reads `~/.clawft/state/logs.jsonl`, which the gateway writes when
+//! `gateway.logs.enabled` is set (see [`crate::log_sink`]).
+//!
+//! # Examples
+//!
+//! ```text
+//! weft logs
+//! weft logs --level warn --since 1h
+//! weft logs --session telegram:12345 --follow
+//! weft logs --channel slack --json
+//! ```
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::Args;
+use tracing::Level;
+
+use crate::log_sink::{self, LogFilter, LogRecord};
+
+/// How often `--follow` polls for new records.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Arguments for the `weft logs` subcommand.
+#[derive(Args)]
+pub struct LogsArgs {
+    /// Number of records to show.
+    #[arg(short = 'n', long, default_value_t = 50)]
+    pub lines: usize,
+
+    /// Keep printing new records as they are logged.
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Only records for this session (e.g. `telegram:12345`).
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Only records for this channel.
+    #[arg(long)]
+    pub channel: Option<String>,
+
+    /// Only records at this level or more severe.
+    #[arg(long, value_parser = parse_level)]
+    pub level: Option<Level>,
+
+    /// Only records newer than this: an age such as `30m`, `1h` or `2d`,
+    /// or an RFC 3339 timestamp.
+    #[arg(long, value_parser = parse_since_now)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Print records as JSON lines.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Config file path (overrides auto-discovery).
+    #[arg(short, long)]
+    pub config: Option<String>,
+}
+
+/// Run the `weft logs` command.
+pub async fn run(args: LogsArgs) -> anyhow::Result<()> {
+    let platform = clawft_platform::NativePlatform::new();
+    let config = super::load_config(&platform, args.config.as_deref()).await?;
+    let home =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("cannot determine home directory"))?;
+    let path = log_sink::log_path(&home);
+
+    let filter = LogFilter {
+        session: args.session,
+        channel: args.channel,
+        level: args.level,
+        since: args.since,
+    };
+    let max_files = config.gateway.logs.max_files;
+
+    let records = log_sink::recent(&path, max_files, &filter, args.lines)?;
+    if records.is_empty() && !args.follow {
+        if !path.exists() && !config.gateway.logs.enabled {
+            println!("No logs recorded. Set gateway.logs.enabled to write them.");
+        } else {
+            println!("No matching log records.");
+        }
+        return Ok(());
+    }
+    for record in &records {
+        print_record(record, args.json);
+    }
+    if args.follow {
+        follow(&path, &filter, args.json).await?;
+    }
+    Ok(())
+}
+
+/// Print records appended to the log at `path` until interrupted.
+async fn follow(path: &Path, filter: &LogFilter, json: bool) -> anyhow::Result<()> {
+    let mut follower = Follower::new(path);
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        for record in follower.poll()?.iter().filter(|r| filter.matches(r)) {
+            print_record(record, json);
+        }
+    }
+}
+
+/// Reads the records appended to a log file, across rotations.
+struct Follower<'a> {
+    path: &'a Path,
+    offset: u64,
+    id: Option<(u64, u64)>,
+}
+
+impl<'a> Follower<'a> {
+    /// Start at the current end of the file.
+    fn new(path: &'a Path) -> Self {
+        let meta = std::fs::metadata(path).ok();
+        Self {
+            path,
+            offset: meta.as_ref().map_or(0, |m| m.len()),
+            id: meta.as_ref().and_then(file_id),
+        }
+    }
+
+    /// The records appended since the last poll.
+    ///
+    /// The file has been rotated when it shrank below the offset or the
+    /// path now names a different file; the rest of the rotated file is
+    /// read first, then the new one from the start.
+    fn poll(&mut self) -> std::io::Result<Vec<LogRecord>> {
+        let meta = std::fs::metadata(self.path).ok();
+        let size = meta.as_ref().map_or(0, |m| m.len());
+        let id = meta.as_ref().and_then(file_id);
+        let replaced = matches!((self.id, id), (Some(old), Some(new)) if old != new);
+
+        let mut records = Vec::new();
+        if size < self.offset || replaced {
+            let rotated = log_sink::rotated_path(self.path, 1);
+            (records, _) = log_sink::read_from(&rotated, self.offset)?;
+            self.offset = 0;
+        }
+        self.id = id;
+
+        let (appended, next) = log_sink::read_from(self.path, self.offset)?;
+        self.offset = next;
+        records.extend(appended);
+        Ok(records)
+    }
+}
+
+/// Device and inode of a file, which change when the path is rotated.
+#[cfg(unix)]
+fn file_id(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Without file identity, rotation is only detected by the file shrinking.
+#[cfg(not(unix))]
+fn file_id(_meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn print_record(record: &LogRecord, json: bool) {
+    if json {
+        if let Ok(line) = serde_json::to_string(record) {
+            println!("{line}");
+        }
+    } else {
+        println!("{}", format_line(record));
+    }
+}
+
+/// One record as a single line:
+/// `<time>  <LEVEL>  [<session> agent=<a> tool=<t>]  <message>  <k=v ...>`.
+fn format_line(record: &LogRecord) -> String {
+    let mut context = Vec::new();
+    match (&record.session, &record.channel) {
+        (Some(session), _) => context.push(session.clone()),
+        (None, Some(channel)) => context.push(channel.clone()),
+        (None, None) => {}
+    }
+    if let Some(agent) = &record.agent {
+        context.push(format!("agent={agent}"));
+    }
+    if let Some(tool) = &record.tool {
+        context.push(format!("tool={tool}"));
+    }
+
+    let mut line = format!(
+        "{}  {:<5}  ",
+        record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        record.level,
+    );
+    if !context.is_empty() {
+        line.push_str(&format!("[{}]  ", context.join(" ")));
+    }
+    line.push_str(&record.message);
+    for (key, value) in &record.fields {
+        match value {
+            serde_json::Value::String(s) => line.push_str(&format!("  {key}={s}")),
+            other => line.push_str(&format!("  {key}={other}")),
+        }
+    }
+    line
+}
+
+fn parse_level(s: &str) -> Result<Level, String> {
+    s.parse()
+        .map_err(|_| format!("unknown level '{s}' (expected error, warn, info, debug or trace)"))
+}
+
+fn parse_since_now(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_since(s, Utc::now())
+}
+
+/// Parse a `--since` value relative to `now`: `<n>s`, `<n>m`, `<n>h`,
+/// `<n>d` or `<n>w`, or an RFC 3339 timestamp.
+fn parse_since(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let invalid = || format!("invalid age '{s}' (expected e.g. 30m, 1h, 2d or a timestamp)");
+    let unit = s.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = s[..s.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    let age = match unit {
+        's' => chrono::Duration::try_seconds(amount),
+        'm' => chrono::Duration::try_minutes(amount),
+        'h' => chrono::Duration::try_hours(amount),
+        'd' => chrono::Duration::try_days(amount),
+        'w' => chrono::Duration::try_weeks(amount),
+        _ => None,
+    }
+    .filter(|age| *age >= chrono::Duration::zero())
+    .ok_or_else(invalid)?;
+    Ok(now - age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since_accepts_ages_and_timestamps() {
+        let now = Utc::now();
+        assert_eq!(
+            parse_since("1h", now).unwrap(),
+            now - chrono::Duration::hours(1)
+        );
+        assert_eq!(
+            parse_since("30m", now).unwrap(),
+            now - chrono::Duration::minutes(30)
+        );
+        assert_eq!(
+            parse_since("2d", now).unwrap(),
+            now - chrono::Duration::days(2)
+        );
+        assert_eq!(
+            parse_since("2026-01-02T03:04:05Z", now)
+                .unwrap()
+                .to_rfc3339(),
+            "2026-01-02T03:04:05+00:00"
+        );
+        for bad in ["", "h", "1y", "-1h", "soon"] {
+            assert!(parse_since(bad, now).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn level_names_parse_case_insensitively() {
+        assert_eq!(parse_level("warn").unwrap(), Level::WARN);
+        assert_eq!(parse_level("ERROR").unwrap(), Level::ERROR);
+        assert!(parse_level("loud").is_err());
+    }
+
+    #[test]
+    fn line_shows_context_message_and_fields() {
+        let mut fields = serde_json::Map::new();
+        fields.insert("message".into(), "tool failed".into());
+        fields.insert("session".into(), "telegram:1".into());
+        fields.insert("channel".into(), "telegram".into());
+        fields.insert("tool".into(), "exec".into());
+        fields.insert("attempt".into(), 2.into());
+        fields.insert("error".into(), "timeout".into());
+        let timestamp = DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let record = LogRecord::new(timestamp, Level::WARN, "clawft_core", fields);
+
+        assert_eq!(
+            format_line(&record),
+            "2026-01-02 03:04:05  WARN   [telegram:1 tool=exec]  tool failed  attempt=2  error=timeout"
+        );
+    }
+
+    #[test]
+    fn follower_reopens_a_rotated_file_that_grew_past_the_offset() {
+        let dir = std::env::temp_dir().join(format!("clawft-logs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("logs.jsonl");
+        let line = |message: &str| {
+            let mut fields = serde_json::Map::new();
+            fields.insert("message".into(), message.into());
+            let record = LogRecord::new(Utc::now(), Level::INFO, "clawft_core", fields);
+            format!("{}\n", serde_json::to_string(&record).unwrap())
+        };
+        let append = |path: &Path, text: String| {
+            use std::io::Write;
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .unwrap()
+                .write_all(text.as_bytes())
+                .unwrap();
+        };
+        let messages = |records: Vec<LogRecord>| -> Vec<String> {
+            records.into_iter().map(|r| r.message).collect()
+        };
+
+        append(&path, line("old"));
+        let mut follower = Follower::new(&path);
+        append(&path, line("first"));
+        assert_eq!(messages(follower.poll().unwrap()), ["first"]);
+
+        // Rotate with an unread record, then grow the new file past the
+        // old offset before the next poll.
+        append(&path, line("last before rotation"));
+        std::fs::rename(&path, log_sink::rotated_path(&path, 1)).unwrap();
+        let long = "x".repeat(512);
+        append(&path, line(&format!("new {long}")));
+        append(&path, line("newer"));
+
+        assert_eq!(
+            messages(follower.poll().unwrap()),
+            [
+                "last before rotation".to_string(),
+                format!("new {long}"),
+                "newer".into()
+            ]
+        );
+        assert!(follower.poll().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,461 @@
+//! `clawft://` resources for `weft mcp-server`: sessions and agent memory,
+//! with updates for the clients subscribed to them.
+//!
+//! - `clawft://sessions/{id}` -- a session as JSON; `id` is the
+//!   percent-encoded session key (`clawft://sessions/telegram%3A42`).
+//! - `clawft://memory/{agent}` -- an agent's long-term memory as markdown.
+//!   All agents share the workspace memory, which is served as
+//!   `clawft://memory/default`.
+//!
+//! Changes are sent as resource URIs for
+//! [`McpServerShell::set_resource_updates`]: memory written through this
+//! server right away ([`NotifyingMemory`]), and changes made by other
+//! processes, such as the gateway, once [`watch_resources`] sees the
+//! memory directory or a session file change.
+//!
+//! [`McpServerShell::set_resource_updates`]: clawft_services::mcp::server::McpServerShell::set_resource_updates
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use clawft_core::agent::memory::{
+    LONG_TERM_NAMESPACE, MemoryBackend, PluginError, SQLITE_DB_NAME, VECTOR_FILE_NAME, read_entries,
+};
+use clawft_core::session::SessionManager;
+use clawft_platform::watch::{FileEvent, WatchHandle, WatchOptions};
+use clawft_platform::{NativePlatform, Platform};
+use clawft_services::mcp::resources::{
+    Resource, ResourceContents, ResourceError, ResourceProvider, ResourceTemplate, decode_path,
+    encode_segment,
+};
+
+/// URI scheme of the resources.
+pub const SCHEME: &str = "clawft";
+
+/// URI of the long-term memory, shared by every agent.
+pub const MEMORY_URI: &str = "clawft://memory/default";
+
+/// The agent whose memory is served.
+const DEFAULT_AGENT: &str = "default";
+
+/// URI of the session with key `key`.
+pub fn session_uri(key: &str) -> String {
+    format!("{SCHEME}://sessions/{}", encode_segment(key))
+}
+
+// ── ClawftResourceProvider ─────────────────────────────────────────────
+
+/// [`ResourceProvider`] serving the sessions and the long-term memory.
+///
+/// Sessions are read from the store on every request rather than through
+/// the manager's cache, so sessions written by other processes are current.
+pub struct ClawftResourceProvider {
+    memory: Arc<dyn MemoryBackend>,
+    sessions: Arc<SessionManager<NativePlatform>>,
+    max_bytes: u64,
+}
+
+impl ClawftResourceProvider {
+    /// Serve `memory` and `sessions`, refusing reads over `max_bytes`.
+    pub fn new(
+        memory: Arc<dyn MemoryBackend>,
+        sessions: Arc<SessionManager<NativePlatform>>,
+        max_bytes: u64,
+    ) -> Self {
+        Self {
+            memory,
+            sessions,
+            max_bytes,
+        }
+    }
+
+    async fn read_text(&self, uri: &str) -> Result<(String, &'static str), ResourceError> {
+        let not_found = || ResourceError::NotFound(uri.to_string());
+        let io = |reason: String| ResourceError::Io {
+            uri: uri.to_string(),
+            reason,
+        };
+
+        let path = uri
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .ok_or_else(not_found)?;
+        match path.split_once('/') {
+            Some(("memory", DEFAULT_AGENT)) => {
+                let text = read_entries(self.memory.as_ref(), LONG_TERM_NAMESPACE)
+                    .await
+                    .map_err(|e| io(e.to_string()))?;
+                Ok((text, "text/markdown"))
+            }
+            Some(("sessions", id)) => {
+                let key = decode_path(id).ok_or_else(not_found)?;
+                let session = self
+                    .sessions
+                    .store()
+                    .load(&key)
+                    .await
+                    .map_err(|e| io(e.to_string()))?
+                    .ok_or_else(not_found)?;
+                let text = serde_json::to_string_pretty(&session).map_err(|e| io(e.to_string()))?;
+                Ok((text, "application/json"))
+            }
+            _ => Err(not_found()),
+        }
+    }
+}
+
+#[async_trait]
+impl ResourceProvider for ClawftResourceProvider {
+    fn scheme(&self) -> &str {
+        SCHEME
+    }
+
+    async fn list_resources(&self) -> Vec<Resource> {
+        let mut resources = vec![Resource {
+            uri: MEMORY_URI.to_string(),
+            name: format!("memory/{DEFAULT_AGENT}"),
+            description: Some("Long-term memory shared by the agents".into()),
+            mime_type: Some("text/markdown".into()),
+            size: None,
+        }];
+        let keys = self.sessions.list_sessions().await.unwrap_or_else(|e| {
+            warn!(error = %e, "failed to list sessions for MCP resources");
+            Vec::new()
+        });
+        resources.extend(keys.into_iter().map(|key| Resource {
+            uri: session_uri(&key),
+            name: format!("sessions/{key}"),
+            description: Some("Conversation session".into()),
+            mime_type: Some("application/json".into()),
+            size: None,
+        }));
+        resources
+    }
+
+    fn templates(&self) -> Vec<ResourceTemplate> {
+        vec![
+            ResourceTemplate {
+                uri_template: format!("{SCHEME}://sessions/{{id}}"),
+                name: "Session".into(),
+                description: Some("A conversation session by its percent-encoded key".into()),
+                mime_type: Some("application/json".into()),
+            },
+            ResourceTemplate {
+                uri_template: format!("{SCHEME}://memory/{{agent}}"),
+                name: "Agent memory".into(),
+                description: Some("An agent's long-term memory".into()),
+                mime_type: Some("text/markdown".into()),
+            },
+        ]
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<ResourceContents, ResourceError> {
+        let (text, mime_type) = self.read_text(uri).await?;
+        let size = text.len() as u64;
+        if size > self.max_bytes {
+            return Err(ResourceError::TooLarge {
+                uri: uri.to_string(),
+                size,
+                limit: self.max_bytes,
+            });
+        }
+        Ok(ResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+        })
+    }
+}
+
+// ── NotifyingMemory ────────────────────────────────────────────────────
+
+/// A [`MemoryBackend`] that sends [`MEMORY_URI`] to `updates` whenever
+/// long-term memory is written through it.
+pub struct NotifyingMemory {
+    inner: Arc<dyn MemoryBackend>,
+    updates: mpsc::Sender<String>,
+}
+
+impl NotifyingMemory {
+    /// Wrap `inner`, sending updates to `updates`.
+    pub fn new(inner: Arc<dyn MemoryBackend>, updates: mpsc::Sender<String>) -> Self {
+        Self { inner, updates }
+    }
+
+    fn changed(&self, namespace: Option<&str>) {
+        if namespace.unwrap_or(LONG_TERM_NAMESPACE) == LONG_TERM_NAMESPACE {
+            // Updates are advisory; one that does not fit is dropped.
+            let _ = self.updates.try_send(MEMORY_URI.to_string());
+        }
+    }
+}
+
+#[async_trait]
+impl MemoryBackend for NotifyingMemory {
+    async fn store(
+        &self,
+        key: &str,
+        value: &str,
+        namespace: Option<&str>,
+        ttl_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+    ) -> Result<(), PluginError> {
+        self.inner
+            .store(key, value, namespace, ttl_seconds, tags)
+            .await?;
+        self.changed(namespace);
+        Ok(())
+    }
+
+    async fn retrieve(
+        &self,
+        key: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<String>, PluginError> {
+        self.inner.retrieve(key, namespace).await
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(String, String, f64)>, PluginError> {
+        self.inner.search(query, namespace, limit).await
+    }
+
+    async fn delete(&self, key: &str, namespace: Option<&str>) -> Result<bool, PluginError> {
+        let deleted = self.inner.delete(key, namespace).await?;
+        if deleted {
+            self.changed(namespace);
+        }
+        Ok(deleted)
+    }
+
+    fn ranks_search(&self) -> bool {
+        self.inner.ranks_search()
+    }
+
+    async fn list(&self, namespace: Option<&str>) -> Result<Vec<(String, String)>, PluginError> {
+        self.inner.list(namespace).await
+    }
+
+    async fn replace_if_unchanged(
+        &self,
+        namespace: Option<&str>,
+        expected: &[(String, String)],
+        entries: &[(String, String)],
+    ) -> Result<bool, PluginError> {
+        let replaced = self
+            .inner
+            .replace_if_unchanged(namespace, expected, entries)
+            .await?;
+        if replaced {
+            self.changed(namespace);
+        }
+        Ok(replaced)
+    }
+}
+
+// ── Watching ───────────────────────────────────────────────────────────
+
+/// Watch `memory_dir` and the session files in `sessions_dir`, sending the
+/// URIs of the resources that changed to `updates`, until `updates` is
+/// closed.
+pub fn watch_resources(
+    platform: &NativePlatform,
+    memory_dir: PathBuf,
+    sessions_dir: PathBuf,
+    updates: mpsc::Sender<String>,
+) {
+    let Some(watcher) = platform.watcher() else {
+        return;
+    };
+    let dirs = [memory_dir, sessions_dir];
+    for dir in &dirs {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            warn!(dir = %dir.display(), error = %e, "cannot watch for resource changes");
+            return;
+        }
+    }
+    let handle = match watcher.watch(&dirs, &WatchOptions::default()) {
+        Ok(handle) => handle,
+        Err(e) => {
+            warn!(error = %e, "failed to watch memory and sessions, only local memory writes are notified");
+            return;
+        }
+    };
+    let [memory_dir, sessions_dir] = dirs;
+    tokio::spawn(forward_resource_changes(
+        handle,
+        memory_dir,
+        sessions_dir,
+        updates,
+    ));
+}
+
+async fn forward_resource_changes(
+    mut handle: WatchHandle,
+    memory_dir: PathBuf,
+    sessions_dir: PathBuf,
+    updates: mpsc::Sender<String>,
+) {
+    while let Some(batch) = handle.recv().await {
+        for uri in changed_resources(&batch, &memory_dir, &sessions_dir) {
+            debug!(%uri, "MCP resource changed");
+            if updates.send(uri).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The resources the files in `batch` hold, each once.
+fn changed_resources(batch: &[FileEvent], memory_dir: &Path, sessions_dir: &Path) -> Vec<String> {
+    let mut uris = Vec::new();
+    for event in batch {
+        let (Some(dir), Some(name)) = (event.path.parent(), event.path.file_name()) else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        let uri = if dir == memory_dir && is_memory_file(&name) {
+            MEMORY_URI.to_string()
+        } else if dir == sessions_dir
+            && let Some(key) = name.strip_suffix(".jsonl").and_then(decode_path)
+        {
+            session_uri(&key)
+        } else {
+            continue;
+        };
+        if !uris.contains(&uri) {
+            uris.push(uri);
+        }
+    }
+    uris
+}
+
+/// Whether a file in the memory directory holds long-term memory: the
+/// markdown file, or the SQLite or vector backend's data.
+fn is_memory_file(name: &str) -> bool {
+    name == "MEMORY.md" || name == VECTOR_FILE_NAME || name.starts_with(SQLITE_DB_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_core::agent::memory::{HISTORY_NAMESPACE, VectorMemoryBackend};
+    use clawft_platform::watch::FileEventKind;
+    use clawft_types::session::Session;
+
+    fn temp_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("clawft-mcp-resources-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn memory(dir: &Path) -> Arc<dyn MemoryBackend> {
+        let platform = Arc::new(NativePlatform::new());
+        Arc::new(
+            VectorMemoryBackend::open(platform, dir.join(VECTOR_FILE_NAME))
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn serves_memory_and_sessions() {
+        let dir = temp_dir();
+        let memory = memory(&dir).await;
+        memory
+            .store("tea", "Prefers green tea", None, None, None)
+            .await
+            .unwrap();
+        let sessions =
+            SessionManager::with_dir(Arc::new(NativePlatform::new()), dir.join("sessions"));
+        let mut session = Session::new("telegram:42");
+        session.add_message("user", "hello", None);
+        sessions.save_session(&session).await.unwrap();
+
+        let provider = ClawftResourceProvider::new(memory, Arc::new(sessions), 1024);
+        let uris: Vec<String> = provider
+            .list_resources()
+            .await
+            .into_iter()
+            .map(|r| r.uri)
+            .collect();
+        assert_eq!(uris, [MEMORY_URI, "clawft://sessions/telegram%3A42"]);
+        assert_eq!(provider.templates().len(), 2);
+
+        let memory = provider.read_resource(MEMORY_URI).await.unwrap();
+        assert!(memory.text.contains("Prefers green tea"), "{}", memory.text);
+        assert_eq!(memory.mime_type.as_deref(), Some("text/markdown"));
+        let session = provider
+            .read_resource("clawft://sessions/telegram%3A42")
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&session.text).unwrap();
+        assert_eq!(json["key"], "telegram:42");
+        assert_eq!(json["messages"][0]["content"], "hello");
+
+        for uri in [
+            "clawft://memory/coach",
+            "clawft://sessions/slack%3A1",
+            "clawft://sessions/%ZZ",
+            "clawft://other",
+            "memory://MEMORY.md",
+        ] {
+            assert!(
+                matches!(
+                    provider.read_resource(uri).await,
+                    Err(ResourceError::NotFound(_))
+                ),
+                "{uri}"
+            );
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn notifying_memory_reports_long_term_writes() {
+        let dir = temp_dir();
+        let (tx, mut rx) = mpsc::channel(4);
+        let memory = NotifyingMemory::new(memory(&dir).await, tx);
+
+        memory
+            .store("s1", "a summary", Some(HISTORY_NAMESPACE), None, None)
+            .await
+            .unwrap();
+        assert!(!memory.delete("missing", None).await.unwrap());
+        assert!(rx.try_recv().is_err());
+
+        memory.store("k", "a fact", None, None, None).await.unwrap();
+        assert_eq!(rx.try_recv().unwrap(), MEMORY_URI);
+        assert!(memory.delete("k", None).await.unwrap());
+        assert_eq!(rx.try_recv().unwrap(), MEMORY_URI);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn changed_files_map_to_resources() {
+        let memory_dir = Path::new("/ws/memory");
+        let sessions_dir = Path::new("/ws/sessions");
+        let event = |path: &str| FileEvent::new(path, FileEventKind::Modified);
+        let batch = [
+            event("/ws/memory/MEMORY.md"),
+            event("/ws/memory/memory.db-wal"),
+            event("/ws/memory/HISTORY.md"),
+            event("/ws/sessions/telegram%3A42.jsonl"),
+            event("/ws/sessions/archive/slack%3A1.jsonl"),
+            event("/ws/sessions/sessions.db"),
+        ];
+        assert_eq!(
+            changed_resources(&batch, memory_dir, sessions_dir),
+            [MEMORY_URI, "clawft://sessions/telegram%3A42"]
+        );
+    }
+}
@@ -1,17 +1,38 @@
-//! `weft mcp-server` -- run clawft as an MCP server over stdio.
+//! `weft mcp-server` -- run clawft as an MCP server over stdio or HTTP.
 //!
 //! Exposes all registered tools (builtin + MCP-proxied) as an MCP tool
 //! server, reading JSON-RPC requests from stdin and writing responses to
 //! stdout. This allows MCP clients (like Claude Desktop, Cursor, etc.) to
 //! use clawft tools natively.
 //!
+//! With `--http`, the server instead speaks the Streamable HTTP transport
+//! on `http://<addr>/mcp`, so remote clients, and several clients at once,
+//! can connect. The address defaults to `mcpServer.host`/`mcpServer.port`
+//! (loopback); `mcpServer.authToken` and `mcpServer.allowedOrigins` guard
+//! it.
+//!
+//! Besides tools, the server offers:
+//!
+//! - **Resources** -- workspace files (`workspace://`), memory documents
+//!   (`memory://`) and session transcripts (`session://`), read-only and
+//!   capped at `--max-resource-bytes`; and the sessions and agent memory
+//!   (`clawft://sessions/{id}`, `clawft://memory/{agent}`), which clients
+//!   can subscribe to (see [`mcp_resources`](super::mcp_resources)).
+//! - **Prompts** -- the prompt templates (`template__<name>`) and the
+//!   user-invocable skills (`skill__<name>`).
+//!
+//! Skill directories are watched; when a skill changes, the skill tools
+//! are refreshed and the client is sent `tools` and `prompts`
+//! `list_changed` notifications.
+//!
 //! # Lifecycle
 //!
 //! ```text
 //! 1. Load config & build tool registry (same as `weft agent`)
 //! 2. Create BuiltinToolProvider wrapping tool registry
 //! 3. Build middleware pipeline (security, permissions, audit)
-//! 4. Create McpServerShell and run on stdin/stdout
+//! 4. Add resource and prompt providers, start the skill watcher
+//! 5. Create McpServerShell and run on stdin/stdout (or HTTP)
 //! ```
 //!
 //! # Example
@@ -19,15 +40,26 @@
 //! ```text
 //! weft mcp-server
 //! weft mcp-server --config /path/to/config.json
+//! weft mcp-server --http
+//! weft mcp-server --http 0.0.0.0:18791
 //! ```
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use clap::Args;
-use tracing::info;
+use tokio::sync::{RwLock, mpsc, watch};
+use tracing::{info, warn};
 
-use clawft_core::agent::skills_v2::SkillRegistry;
+use clawft_core::agent::helpers::render_template;
+use clawft_core::agent::memory::{MemoryBackend, MemoryStore};
+use clawft_core::agent::skill_watcher::{SkillWatcherConfig, SkillWatcherHandle, start_watching};
+use clawft_core::agent::skills_v2::{SharedSkillRegistry, SkillRegistry};
+use clawft_core::agent::templates::{PromptTemplates, TemplateName};
+use clawft_core::runtime::{self, ProgressReporter, ToolProgress};
+use clawft_core::session::SessionManager;
 use clawft_core::tools::registry::ToolRegistry;
 use clawft_platform::NativePlatform;
 use clawft_services::mcp::BuiltinToolProvider;
@@ -37,10 +69,18 @@ use clawft_services::mcp::composite::CompositeToolProvider;
 use clawft_services::mcp::middleware::{
     AuditLog, Middleware, PermissionFilter, ResultGuard, SecurityGuard,
 };
+use clawft_services::mcp::progress::{self, Progress, ProgressSender};
+use clawft_services::mcp::prompts::{
+    GetPromptResult, Prompt, PromptArgument, PromptError, PromptProvider, check_required,
+};
 use clawft_services::mcp::provider::skills_to_tool_definitions;
-use clawft_services::mcp::server::McpServerShell;
+use clawft_services::mcp::resources::{DEFAULT_MAX_RESOURCE_BYTES, DirResourceProvider};
+use clawft_services::mcp::server::http::{self as mcp_http, HttpOptions};
+use clawft_services::mcp::server::{ListChanged, McpServerShell};
+use clawft_types::config::McpServeConfig;
 
 use super::load_config;
+use super::mcp_resources::{ClawftResourceProvider, NotifyingMemory, watch_resources};
 
 /// Arguments for the `weft mcp-server` subcommand.
 #[derive(Args)]
@@ -48,22 +88,52 @@ pub struct McpServerArgs {
     /// Config file path (overrides auto-discovery).
     #[arg(short, long)]
     pub config: Option<String>,
+
+    /// Largest file, in bytes, served as a resource.
+    #[arg(long, default_value_t = DEFAULT_MAX_RESOURCE_BYTES)]
+    pub max_resource_bytes: u64,
+
+    /// Serve over Streamable HTTP instead of stdio, on ADDR (host:port)
+    /// or, if omitted, on the configured `mcpServer` address.
+    #[arg(long, value_name = "ADDR")]
+    pub http: Option<Option<String>>,
 }
 
+/// Prompt name prefix of the prompt templates.
+const TEMPLATE_PROMPT_PREFIX: &str = "template__";
+
+/// Prompt name prefix of the skills.
+const SKILL_PROMPT_PREFIX: &str = "skill__";
+
 /// Run the MCP server command.
 ///
 /// Loads configuration, builds the tool registry (identical to `weft agent`),
-/// wraps it in a [`BuiltinToolProvider`], and serves tools over stdio using
-/// [`McpServerShell`] with the full middleware pipeline.
+/// wraps it in a [`BuiltinToolProvider`], and serves tools over stdio (or
+/// HTTP with `--http`) using [`McpServerShell`] with the full middleware
+/// pipeline.
 pub async fn run(args: McpServerArgs) -> anyhow::Result<()> {
     info!("starting weft mcp-server");
 
-    let platform = Arc::new(NativePlatform::new());
-    let config = load_config(&*platform, args.config.as_deref()).await?;
+    let config = load_config(&NativePlatform::new(), args.config.as_deref()).await?;
+    let platform = Arc::new(super::platform_for_config(&config)?);
 
     // ── Build tool registry (shared core tools) ────────────────────
+    // Memory written by the tools is announced to resource subscribers.
+    let (updates_tx, updates_rx) = mpsc::channel(32);
+    let memory: Arc<dyn MemoryBackend> = Arc::new(NotifyingMemory::new(
+        super::open_memory_backend(&config, platform.clone()).await?,
+        updates_tx.clone(),
+    ));
     let mut registry = ToolRegistry::new();
-    super::register_core_tools(&mut registry, &config, platform.clone()).await;
+    super::register_core_tools(
+        &mut registry,
+        &config,
+        platform.clone(),
+        memory.clone(),
+        None,
+        None,
+    )
+    .await;
 
     let tool_count = registry.len();
     let tool_names = registry.list();
@@ -100,38 +170,18 @@ pub async fn run(args: McpServerArgs) -> anyhow::Result<()> {
 
     let skill_count = skill_registry.len();
     if skill_count > 0 {
-        let skill_defs = skills_to_tool_definitions(
-            &skill_registry.list().into_iter().cloned().collect::<Vec<_>>(),
-        );
-
-        // Build a lookup from skill name -> instructions for the dispatcher.
-        let instructions_map: std::collections::HashMap<String, String> = skill_registry
-            .list()
-            .into_iter()
-            .map(|s| (s.name.clone(), s.instructions.clone()))
-            .collect();
-        let instructions_map = Arc::new(instructions_map);
-
-        let skill_provider = SkillToolProvider::new(skill_defs, move |name, _args| {
-            let map = instructions_map.clone();
-            let name = name.to_string();
-            Box::pin(async move {
-                match map.get(&name) {
-                    Some(instructions) => Ok(instructions.clone()),
-                    None => Err(format!("skill '{name}' not found")),
-                }
-            })
-        });
-
         info!(
             skills = skill_count,
             names = ?skill_registry.names(),
             "skill tools registered for MCP server"
         );
-        composite.register(Box::new(skill_provider));
     } else {
-        info!("no skills found, skipping skill tool registration");
+        info!("no skills found yet, skill tools will appear when skills are added");
     }
+    let skills: SharedSkillRegistry = Arc::new(RwLock::new(skill_registry));
+    let skill_provider = build_skill_provider(skills.clone()).await;
+    let skill_tools = skill_provider.tools_handle();
+    composite.register(Box::new(skill_provider));
 
     // ── Build middleware pipeline ────────────────────────────────────
     let security_guard = build_security_guard(&config.tools);
@@ -142,12 +192,83 @@ pub async fn run(args: McpServerArgs) -> anyhow::Result<()> {
         Box::new(AuditLog),
     ];
 
-    // ── Create McpServerShell and run on stdin/stdout ────────────────
+    // ── Create McpServerShell with resources and prompts ─────────────
     let mut shell = McpServerShell::new(composite);
     for mw in middlewares {
         shell.add_middleware(mw);
     }
 
+    let max_bytes = args.max_resource_bytes;
+    let workspace = config.workspace_path();
+    shell.add_resource_provider(Box::new(
+        DirResourceProvider::new("workspace", &workspace, "Workspace file")
+            .with_max_bytes(max_bytes),
+    ));
+    let memory_dir = match MemoryStore::new(platform.clone()) {
+        Ok(store) => {
+            shell.add_resource_provider(Box::new(
+                DirResourceProvider::new("memory", store.memory_dir(), "Agent memory document")
+                    .with_max_bytes(max_bytes),
+            ));
+            Some(store.memory_dir().to_path_buf())
+        }
+        Err(e) => {
+            warn!(error = %e, "memory directory unavailable, not serving memory resources");
+            None
+        }
+    };
+    match SessionManager::from_config(platform.clone(), &config.agents.sessions).await {
+        Ok(sessions) => {
+            let sessions_dir = sessions.sessions_dir().clone();
+            shell.add_resource_provider(Box::new(
+                DirResourceProvider::new("session", &sessions_dir, "Session transcript")
+                    .with_max_bytes(max_bytes),
+            ));
+            shell.add_resource_provider(Box::new(ClawftResourceProvider::new(
+                memory,
+                Arc::new(sessions),
+                max_bytes,
+            )));
+            if let Some(memory_dir) = memory_dir {
+                watch_resources(&platform, memory_dir, sessions_dir, updates_tx);
+            }
+        }
+        Err(e) => warn!(error = %e, "sessions unavailable, not serving sessions"),
+    }
+    shell.set_resource_updates(updates_rx);
+
+    let templates = PromptTemplates::load(&config.agents, &workspace.join("templates"), None)
+        .map_err(|e| anyhow::anyhow!("failed to load prompt templates: {e}"))?;
+    shell.set_prompt_provider(Box::new(AgentPromptProvider {
+        templates: Arc::new(templates),
+        skills: skills.clone(),
+    }));
+
+    // ── Watch skills, notifying the client of list changes ───────────
+    let (changes_tx, changes_rx) = mpsc::channel(8);
+    shell.set_list_changed(changes_rx);
+    let _skill_watcher = watch_skills(
+        ws_skills_dir,
+        user_skills_dir,
+        skills,
+        skill_tools,
+        changes_tx,
+    );
+
+    // ── Run on HTTP, if asked ────────────────────────────────────────
+    if let Some(addr) = args.http {
+        let addr = addr
+            .unwrap_or_else(|| format!("{}:{}", config.mcp_server.host, config.mcp_server.port));
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to bind MCP server to {addr}: {e}"))?;
+        info!(tools = tool_count, names = ?tool_names, %addr, "MCP server ready");
+        mcp_http::serve(listener, shell, http_options(&config.mcp_server)).await?;
+        return Ok(());
+    }
+
+    // ── Run on stdin/stdout ──────────────────────────────────────────
+
     info!(
         tools = tool_count,
         names = ?tool_names,
@@ -162,6 +283,187 @@ pub async fn run(args: McpServerArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// HTTP access control from the `mcpServer` config section.
+fn http_options(config: &McpServeConfig) -> HttpOptions {
+    HttpOptions {
+        auth_token: (!config.auth_token.is_empty()).then(|| config.auth_token.expose().to_string()),
+        allowed_origins: config.allowed_origins.clone(),
+    }
+}
+
+/// Build a [`SkillToolProvider`] over the shared skill registry. Calls are
+/// answered from the registry as it is at call time.
+async fn build_skill_provider(skills: SharedSkillRegistry) -> SkillToolProvider {
+    let skill_defs = skill_tool_definitions(&*skills.read().await);
+    SkillToolProvider::new(skill_defs, move |name, _args| {
+        let skills = skills.clone();
+        let name = name.to_string();
+        Box::pin(async move {
+            match skills.read().await.get(&name) {
+                Some(skill) => Ok(skill.instructions.clone()),
+                None => Err(format!("skill '{name}' not found")),
+            }
+        })
+    })
+}
+
+fn skill_tool_definitions(registry: &SkillRegistry) -> Vec<ToolDefinition> {
+    skills_to_tool_definitions(&registry.list().into_iter().cloned().collect::<Vec<_>>())
+}
+
+/// Watch the skill directories. After each reload the skill tools are
+/// refreshed and `changes` receives [`ListChanged::Tools`] and
+/// [`ListChanged::Prompts`]. Watching stops when the handle is dropped.
+fn watch_skills(
+    workspace_dir: Option<PathBuf>,
+    user_dir: Option<PathBuf>,
+    skills: SharedSkillRegistry,
+    skill_tools: Arc<std::sync::RwLock<Vec<ToolDefinition>>>,
+    changes: mpsc::Sender<ListChanged>,
+) -> Option<SkillWatcherHandle> {
+    let config = SkillWatcherConfig {
+        workspace_dir,
+        user_dir,
+        // Same trust as `SkillRegistry::discover` above.
+        trust_workspace: true,
+        ..Default::default()
+    };
+    let handle = match start_watching(config, skills.clone()) {
+        Ok(handle) => handle,
+        Err(e) => {
+            warn!(error = %e, "failed to watch skill directories, skill changes need a restart");
+            return None;
+        }
+    };
+    tokio::spawn(forward_skill_reloads(
+        handle.reloads(),
+        skills,
+        skill_tools,
+        changes,
+    ));
+    Some(handle)
+}
+
+async fn forward_skill_reloads(
+    mut reloads: watch::Receiver<u64>,
+    skills: SharedSkillRegistry,
+    skill_tools: Arc<std::sync::RwLock<Vec<ToolDefinition>>>,
+    changes: mpsc::Sender<ListChanged>,
+) {
+    while reloads.changed().await.is_ok() {
+        let defs = skill_tool_definitions(&*skills.read().await);
+        info!(skills = defs.len(), "skills changed, notifying MCP client");
+        *skill_tools.write().expect("skill tool list lock poisoned") = defs;
+        for change in [ListChanged::Tools, ListChanged::Prompts] {
+            if changes.send(change).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// [`PromptProvider`] serving the prompt templates and the user-invocable
+/// skills.
+struct AgentPromptProvider {
+    templates: Arc<PromptTemplates>,
+    skills: SharedSkillRegistry,
+}
+
+impl AgentPromptProvider {
+    fn template_prompt(name: TemplateName) -> Prompt {
+        Prompt {
+            name: format!("{TEMPLATE_PROMPT_PREFIX}{}", name.as_str()),
+            description: Some(name.description().to_string()),
+            arguments: name
+                .variables()
+                .iter()
+                .map(|var| PromptArgument {
+                    name: var.to_string(),
+                    description: None,
+                    required: true,
+                })
+                .collect(),
+        }
+    }
+
+    fn skill_prompt(skill: &clawft_types::skill::SkillDefinition) -> Prompt {
+        let mut arguments: Vec<PromptArgument> = skill
+            .variables
+            .iter()
+            .map(|var| PromptArgument {
+                name: var.clone(),
+                description: None,
+                required: true,
+            })
+            .collect();
+        arguments.push(PromptArgument {
+            name: "args".into(),
+            description: skill.argument_hint.clone(),
+            required: false,
+        });
+        Prompt {
+            name: format!("{SKILL_PROMPT_PREFIX}{}", skill.name),
+            description: Some(skill.description.clone()),
+            arguments,
+        }
+    }
+}
+
+#[async_trait]
+impl PromptProvider for AgentPromptProvider {
+    async fn list_prompts(&self) -> Vec<Prompt> {
+        let mut prompts: Vec<Prompt> = TemplateName::ALL
+            .into_iter()
+            .map(Self::template_prompt)
+            .collect();
+        let skills = self.skills.read().await;
+        prompts.extend(
+            skills
+                .names()
+                .into_iter()
+                .filter_map(|name| skills.get(name))
+                .filter(|skill| skill.user_invocable)
+                .map(Self::skill_prompt),
+        );
+        prompts
+    }
+
+    async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<GetPromptResult, PromptError> {
+        let not_found = || PromptError::NotFound(name.to_string());
+
+        if let Some(template) = name.strip_prefix(TEMPLATE_PROMPT_PREFIX) {
+            let template = TemplateName::parse(template).ok_or_else(not_found)?;
+            let prompt = Self::template_prompt(template);
+            check_required(&prompt, arguments)?;
+            let extra: Vec<(&str, &str)> = template
+                .variables()
+                .iter()
+                .filter_map(|var| arguments.get(*var).map(|v| (*var, v.as_str())))
+                .collect();
+            let text = self.templates.render(template, None, &extra);
+            return Ok(GetPromptResult::user_text(prompt.description, text));
+        }
+
+        let skill_name = name
+            .strip_prefix(SKILL_PROMPT_PREFIX)
+            .ok_or_else(not_found)?;
+        let skills = self.skills.read().await;
+        let skill = skills
+            .get(skill_name)
+            .filter(|skill| skill.user_invocable)
+            .ok_or_else(not_found)?;
+        let prompt = Self::skill_prompt(skill);
+        check_required(&prompt, arguments)?;
+        let args = arguments.get("args").map(String::as_str).unwrap_or("");
+        let text = render_template(&skill.instructions, args, arguments);
+        Ok(GetPromptResult::user_text(prompt.description, text))
+    }
+}
+
 /// Build [`ToolDefinition`] list from a populated [`ToolRegistry`].
 fn build_tool_definitions(registry: &ToolRegistry) -> Vec<ToolDefinition> {
     let schemas = registry.schemas();
@@ -199,8 +501,15 @@ fn build_builtin_provider(
     BuiltinToolProvider::new(tool_defs, move |name, args| {
         let reg = reg_clone.clone();
         let name = name.to_string();
+        // Set when the client asked for progress on this call.
+        let progress = progress::current();
         Box::pin(async move {
-            match reg.execute(&name, args, None).await {
+            let run = reg.execute(&name, args, None);
+            let result = match progress {
+                Some(sender) => runtime::with_progress(Arc::new(McpProgress(sender)), run).await,
+                None => run.await,
+            };
+            match result {
                 Ok(value) => Ok(serde_json::to_string(&value).unwrap_or_default()),
                 Err(e) => Err(e.to_string()),
             }
@@ -208,6 +517,19 @@ fn build_builtin_provider(
     })
 }
 
+/// Passes the progress tools report to the MCP client.
+struct McpProgress(ProgressSender);
+
+impl ProgressReporter for McpProgress {
+    fn report(&self, progress: ToolProgress) {
+        self.0.report(Progress {
+            progress: progress.progress,
+            total: progress.total,
+            message: progress.message,
+        });
+    }
+}
+
 /// Build a [`SecurityGuard`] middleware from the tools configuration.
 ///
 /// Translates the CLI-level `CommandPolicyConfig` and `UrlPolicyConfig`
@@ -274,7 +596,11 @@ mod tests {
 
     #[test]
     fn mcp_server_args_defaults() {
-        let args = McpServerArgs { config: None };
+        let args = McpServerArgs {
+            config: None,
+            max_resource_bytes: DEFAULT_MAX_RESOURCE_BYTES,
+            http: None,
+        };
         assert!(args.config.is_none());
     }
 
@@ -282,10 +608,24 @@ mod tests {
     fn mcp_server_args_with_config() {
         let args = McpServerArgs {
             config: Some("/tmp/config.json".into()),
+            max_resource_bytes: DEFAULT_MAX_RESOURCE_BYTES,
+            http: None,
         };
         assert_eq!(args.config.as_deref(), Some("/tmp/config.json"));
     }
 
+    #[test]
+    fn http_options_from_config() {
+        let mut config = McpServeConfig::default();
+        assert!(http_options(&config).auth_token.is_none());
+
+        config.auth_token = "tok".into();
+        config.allowed_origins = vec!["https://app.example.com".into()];
+        let options = http_options(&config);
+        assert_eq!(options.auth_token.as_deref(), Some("tok"));
+        assert_eq!(options.allowed_origins, config.allowed_origins);
+    }
+
     #[test]
     fn build_tool_definitions_from_registry() {
         use clawft_core::tools::registry::{Tool, ToolError as CoreToolError, ToolRegistry};
@@ -391,6 +731,66 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn builtin_provider_forwards_tool_progress() {
+        use clawft_core::tools::registry::{Tool, ToolError as CoreToolError};
+        use clawft_services::mcp::ToolProvider;
+
+        struct StepsTool;
+
+        #[async_trait::async_trait]
+        impl Tool for StepsTool {
+            fn name(&self) -> &str {
+                "steps"
+            }
+            fn description(&self) -> &str {
+                "Reports two steps"
+            }
+            fn parameters(&self) -> serde_json::Value {
+                serde_json::json!({ "type": "object" })
+            }
+            async fn execute(
+                &self,
+                _args: serde_json::Value,
+            ) -> Result<serde_json::Value, CoreToolError> {
+                for step in [1.0, 2.0] {
+                    runtime::report_progress(
+                        ToolProgress::new(step)
+                            .with_total(2.0)
+                            .with_message("working"),
+                    );
+                }
+                Ok(serde_json::json!("done"))
+            }
+        }
+
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(StepsTool));
+        let defs = build_tool_definitions(&registry);
+        let provider = build_builtin_provider(defs, registry);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sender = ProgressSender::new(serde_json::json!("tok"), tx);
+        let result = progress::scope(sender, provider.call_tool("steps", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+
+        for step in [1.0, 2.0] {
+            let note = rx.try_recv().unwrap();
+            assert_eq!(note["method"], "notifications/progress");
+            assert_eq!(note["params"]["progressToken"], "tok");
+            assert_eq!(note["params"]["progress"], step);
+            assert_eq!(note["params"]["total"], 2.0);
+            assert_eq!(note["params"]["message"], "working");
+        }
+        assert!(rx.try_recv().is_err());
+
+        // Outside a progress scope the reports go nowhere.
+        let result = provider.call_tool("steps", serde_json::json!({})).await;
+        assert!(!result.unwrap().is_error);
+    }
+
     #[tokio::test]
     async fn builtin_provider_not_found() {
         use clawft_services::mcp::ToolProvider;
@@ -411,4 +811,244 @@ mod tests {
             other => panic!("expected NotFound, got: {other}"),
         }
     }
+
+    async fn prompt_provider() -> AgentPromptProvider {
+        let mut config = clawft_types::config::AgentsConfig::default();
+        config.defaults.workspace = "/ws".into();
+
+        let mut registry = SkillRegistry::discover(None, None, Vec::new())
+            .await
+            .unwrap();
+        let mut review = clawft_types::skill::SkillDefinition::new("review", "Review a PR");
+        review.user_invocable = true;
+        review.variables = vec!["focus".into()];
+        review.instructions = "Review $ARGUMENTS, focusing on ${focus}.".into();
+        registry.upsert(review);
+        registry.upsert(clawft_types::skill::SkillDefinition::new(
+            "internal",
+            "Model-only skill",
+        ));
+
+        AgentPromptProvider {
+            templates: Arc::new(PromptTemplates::builtin(&config)),
+            skills: Arc::new(RwLock::new(registry)),
+        }
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn text(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            clawft_services::mcp::ContentBlock::Text { text } => text,
+        }
+    }
+
+    #[tokio::test]
+    async fn prompts_list_templates_and_user_invocable_skills() {
+        let provider = prompt_provider().await;
+        let prompts = provider.list_prompts().await;
+        let names: Vec<&str> = prompts.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "template__system",
+                "template__tool_error",
+                "template__summarizer",
+                "template__result_summarizer",
+                "template__heartbeat",
+                "skill__review",
+            ]
+        );
+
+        let tool_error = &prompts[1];
+        let tool_error_args: Vec<&str> = tool_error
+            .arguments
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(tool_error_args, ["tool", "error"]);
+        assert!(tool_error.arguments.iter().all(|a| a.required));
+
+        let review = &prompts[5];
+        assert_eq!(review.description.as_deref(), Some("Review a PR"));
+        assert!(review.arguments[0].required);
+        assert_eq!(review.arguments[1].name, "args");
+        assert!(!review.arguments[1].required);
+    }
+
+    #[tokio::test]
+    async fn prompts_render_templates_and_skills() {
+        let provider = prompt_provider().await;
+
+        let result = provider
+            .get_prompt("template__system", &HashMap::new())
+            .await
+            .unwrap();
+        assert!(text(&result).contains("Workspace: /ws"));
+
+        let result = provider
+            .get_prompt(
+                "template__tool_error",
+                &args(&[("tool", "exec"), ("error", "boom")]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(text(&result), "boom");
+
+        let result = provider
+            .get_prompt(
+                "skill__review",
+                &args(&[("args", "#42"), ("focus", "tests")]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(text(&result), "Review #42, focusing on tests.");
+    }
+
+    #[tokio::test]
+    async fn prompts_reject_unknown_names_and_missing_arguments() {
+        let provider = prompt_provider().await;
+        for name in ["template__nope", "skill__nope", "skill__internal", "system"] {
+            assert!(
+                matches!(
+                    provider.get_prompt(name, &HashMap::new()).await,
+                    Err(PromptError::NotFound(_))
+                ),
+                "{name}"
+            );
+        }
+        assert!(matches!(
+            provider
+                .get_prompt("template__tool_error", &args(&[("tool", "exec")]))
+                .await,
+            Err(PromptError::MissingArgument { ref argument, .. }) if argument == "error"
+        ));
+        assert!(matches!(
+            provider.get_prompt("skill__review", &HashMap::new()).await,
+            Err(PromptError::MissingArgument { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn skill_reloads_refresh_tools_and_notify() {
+        let provider = prompt_provider().await;
+        let skills = provider.skills.clone();
+        let skill_tools = Arc::new(std::sync::RwLock::new(Vec::new()));
+        let (reload_tx, reload_rx) = watch::channel(0u64);
+        let (changes_tx, mut changes_rx) = mpsc::channel(8);
+        let task = tokio::spawn(forward_skill_reloads(
+            reload_rx,
+            skills,
+            skill_tools.clone(),
+            changes_tx,
+        ));
+
+        reload_tx.send_modify(|n| *n += 1);
+        assert_eq!(changes_rx.recv().await, Some(ListChanged::Tools));
+        assert_eq!(changes_rx.recv().await, Some(ListChanged::Prompts));
+        assert_eq!(skill_tools.read().unwrap().len(), 2);
+
+        drop(reload_tx);
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_write_notifies_subscribed_client() {
+        use clawft_core::agent::memory::VectorMemoryBackend;
+        use clawft_core::tools::registry::ToolRegistry;
+        use clawft_platform::NativePlatform;
+        use clawft_tools::memory_tool::MemoryWriteTool;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let dir = std::env::temp_dir().join(format!("clawft-mcp-server-{}", uuid::Uuid::new_v4()));
+        let platform = Arc::new(NativePlatform::new());
+        let backend = VectorMemoryBackend::open(platform.clone(), dir.join("vectors.json"))
+            .await
+            .unwrap();
+        let (updates_tx, updates_rx) = mpsc::channel(8);
+        let memory: Arc<dyn MemoryBackend> =
+            Arc::new(NotifyingMemory::new(Arc::new(backend), updates_tx));
+
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(MemoryWriteTool::new(memory.clone(), 0.85)));
+        let mut composite = CompositeToolProvider::new();
+        composite.register(Box::new(build_builtin_provider(
+            build_tool_definitions(&registry),
+            registry,
+        )));
+        let mut shell = McpServerShell::new(composite);
+        let sessions = SessionManager::with_dir(platform, dir.join("sessions"));
+        shell.add_resource_provider(Box::new(ClawftResourceProvider::new(
+            memory,
+            Arc::new(sessions),
+            DEFAULT_MAX_RESOURCE_BYTES,
+        )));
+        shell.set_resource_updates(updates_rx);
+
+        let (client, server_io) = tokio::io::duplex(8192);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let server = tokio::spawn(async move {
+            shell
+                .run(BufReader::new(server_read), server_write)
+                .await
+                .unwrap();
+        });
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_read).lines();
+        let mut send = async |id: u64, method: &str, params: serde_json::Value| {
+            let line = serde_json::json!({
+                "jsonrpc": "2.0", "id": id, "method": method, "params": params
+            });
+            client_write
+                .write_all(format!("{line}\n").as_bytes())
+                .await
+                .unwrap();
+        };
+
+        send(1, "initialize", serde_json::json!({})).await;
+        send(
+            2,
+            "resources/subscribe",
+            serde_json::json!({ "uri": "clawft://memory/default" }),
+        )
+        .await;
+        send(
+            3,
+            "tools/call",
+            serde_json::json!({
+                "name": "builtin__memory_write",
+                "arguments": { "content": "Prefers green tea" }
+            }),
+        )
+        .await;
+
+        let mut messages = Vec::new();
+        while messages.len() < 4 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            messages.push(serde_json::from_str::<serde_json::Value>(&line).unwrap());
+        }
+        assert_eq!(
+            messages[0]["result"]["capabilities"]["resources"]["subscribe"],
+            true
+        );
+        assert_eq!(messages[1]["id"], 2);
+        assert_eq!(messages[2]["id"], 3);
+        assert_eq!(messages[2]["result"]["isError"], false, "{}", messages[2]);
+        assert_eq!(
+            messages[3],
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": "clawft://memory/default" }
+            })
+        );
+
+        server.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
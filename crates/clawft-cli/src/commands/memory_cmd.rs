@@ -1,8 +1,10 @@
-//! `weft memory` -- read, search, export, and import agent memory files.
+//! `weft memory` -- read, search, export, and import agent memory.
 //!
-//! Provides commands to inspect the long-term memory (`MEMORY.md`) and
-//! session history (`HISTORY.md`) files managed by the agent, as well
-//! as a substring search across both.
+//! Provides commands to inspect the long-term memory and session history
+//! kept by the configured memory backend (`agents.memory.backend`), a
+//! search across both, `compact`, which merges similar long-term entries
+//! and drops stale ones, and `migrate`, which copies the markdown files
+//! into a SQLite memory database.
 //!
 //! Export and import support JSON format with optional WITNESS chain
 //! validation for tamper detection.
@@ -15,76 +17,92 @@
 //! weft memory search "authentication" --limit 5
 //! weft memory export --agent my-agent --output /tmp/memory.json
 //! weft memory import --agent my-agent --input /tmp/memory.json
+//! weft memory compact --days 60 --dry-run
+//! weft memory migrate
 //! ```
 
 use std::path::Path;
 use std::sync::Arc;
 
-use clawft_core::agent::memory::MemoryStore;
+use clawft_core::agent::memory::hygiene::{EntryMerger, compact};
+use clawft_core::agent::memory::{
+    HISTORY_NAMESPACE, LONG_TERM_NAMESPACE, MemoryBackend, MemoryStore, read_entries,
+};
+use clawft_core::bootstrap::build_live_pipeline;
 use clawft_platform::NativePlatform;
-use clawft_types::config::Config;
+use clawft_types::config::{Config, MemoryBackendKind};
 
-/// Read and display the contents of `MEMORY.md`.
-///
-/// Initialises a [`MemoryStore`] via the native platform, prints the
-/// resolved file path, then outputs the file contents (or a placeholder
-/// if the file is empty / does not exist).
-pub async fn memory_show(_config: &Config) -> anyhow::Result<()> {
+/// Open the configured memory backend and print where it keeps `namespace`.
+async fn open_backend(config: &Config, namespace: &str) -> anyhow::Result<Arc<dyn MemoryBackend>> {
     let platform = Arc::new(NativePlatform::new());
-    let store = MemoryStore::new(platform)
+    let store = MemoryStore::new(platform.clone())
         .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
 
-    println!("Memory file: {}", store.memory_path().display());
+    match config.agents.memory.backend {
+        MemoryBackendKind::Markdown => {
+            let path = if namespace == HISTORY_NAMESPACE {
+                store.history_path()
+            } else {
+                store.memory_path()
+            };
+            println!("Memory file: {}", path.display());
+        }
+        kind => println!(
+            "Memory backend: {} ({})",
+            format!("{kind:?}").to_lowercase(),
+            store.memory_dir().display()
+        ),
+    }
     println!();
 
-    let content = store
-        .read_long_term()
+    super::open_memory_backend(config, platform).await
+}
+
+/// Print every entry of `namespace`, or `placeholder` if there are none.
+async fn show_namespace(config: &Config, namespace: &str, placeholder: &str) -> anyhow::Result<()> {
+    let backend = open_backend(config, namespace).await?;
+    let content = read_entries(backend.as_ref(), namespace)
         .await
-        .map_err(|e| anyhow::anyhow!("failed to read memory: {e}"))?;
+        .map_err(|e| anyhow::anyhow!("failed to read {namespace}: {e}"))?;
 
     if content.is_empty() {
-        println!("(no memory entries)");
+        println!("{placeholder}");
     } else {
         println!("{content}");
     }
     Ok(())
 }
 
-/// Read and display the contents of `HISTORY.md`.
-///
-/// Initialises a [`MemoryStore`] via the native platform, prints the
-/// resolved file path, then outputs the file contents (or a placeholder
-/// if the file is empty / does not exist).
-pub async fn memory_history(_config: &Config) -> anyhow::Result<()> {
-    let platform = Arc::new(NativePlatform::new());
-    let store = MemoryStore::new(platform)
-        .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
-
-    println!("History file: {}", store.history_path().display());
-    println!();
-
-    let content = store
-        .read_history()
-        .await
-        .map_err(|e| anyhow::anyhow!("failed to read history: {e}"))?;
+/// Display long-term memory (`MEMORY.md` with the markdown backend).
+pub async fn memory_show(config: &Config) -> anyhow::Result<()> {
+    show_namespace(config, LONG_TERM_NAMESPACE, "(no memory entries)").await
+}
 
-    if content.is_empty() {
-        println!("(no history entries)");
-    } else {
-        println!("{content}");
-    }
-    Ok(())
+/// Display session history (`HISTORY.md` with the markdown backend).
+pub async fn memory_history(config: &Config) -> anyhow::Result<()> {
+    show_namespace(config, HISTORY_NAMESPACE, "(no history entries)").await
 }
 
-/// Search memory and history files for paragraphs matching `query`.
+/// Search long-term memory and history for entries matching `query`.
 ///
-/// Results are printed numbered, one per paragraph, capped at `limit`.
-pub async fn memory_search(query: &str, limit: usize, _config: &Config) -> anyhow::Result<()> {
+/// Results are ranked by the configured backend and printed numbered,
+/// capped at `limit`.
+pub async fn memory_search(query: &str, limit: usize, config: &Config) -> anyhow::Result<()> {
     let platform = Arc::new(NativePlatform::new());
-    let store = MemoryStore::new(platform)
-        .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
-
-    let results = store.search(query, limit).await;
+    let backend = super::open_memory_backend(config, platform).await?;
+
+    // Search the two namespaces separately so that entry metadata, kept
+    // in a namespace of its own, never shows up.
+    let mut results = Vec::new();
+    for namespace in [LONG_TERM_NAMESPACE, HISTORY_NAMESPACE] {
+        let found = backend
+            .search(query, Some(namespace), Some(limit))
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to search memory: {e}"))?;
+        results.extend(found);
+    }
+    results.sort_by(|a, b| b.2.total_cmp(&a.2));
+    results.truncate(limit);
 
     if results.is_empty() {
         println!("No results for \"{query}\"");
@@ -95,8 +113,8 @@ pub async fn memory_search(query: &str, limit: usize, _config: &Config) -> anyho
             if results.len() == 1 { "" } else { "s" },
             query,
         );
-        for (i, paragraph) in results.iter().enumerate() {
-            println!("{}. {}", i + 1, paragraph);
+        for (i, (_, entry, _)) in results.iter().enumerate() {
+            println!("{}. {}", i + 1, entry);
             println!();
         }
     }
@@ -105,27 +123,25 @@ pub async fn memory_search(query: &str, limit: usize, _config: &Config) -> anyho
 
 /// Export agent memory to a file.
 ///
-/// Reads the memory store (MEMORY.md + HISTORY.md) for the specified agent
-/// and writes a JSON export file. The format parameter controls the output:
+/// Reads long-term memory and history from the configured backend for the
+/// specified agent and writes a JSON export file. The format parameter
+/// controls the output:
 /// - "json": Plain JSON with memory and history content.
 /// - "rvf": Reserved for future RVF segment format (currently falls back to JSON).
 pub async fn memory_export(
     agent_id: &str,
     output_path: &str,
     format: &str,
-    _config: &Config,
+    config: &Config,
 ) -> anyhow::Result<()> {
     let platform = Arc::new(NativePlatform::new());
-    let store = MemoryStore::new(platform)
-        .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
+    let backend = super::open_memory_backend(config, platform).await?;
 
-    let memory = store
-        .read_long_term()
+    let memory = read_entries(backend.as_ref(), LONG_TERM_NAMESPACE)
         .await
         .map_err(|e| anyhow::anyhow!("failed to read memory: {e}"))?;
 
-    let history = store
-        .read_history()
+    let history = read_entries(backend.as_ref(), HISTORY_NAMESPACE)
         .await
         .map_err(|e| anyhow::anyhow!("failed to read history: {e}"))?;
 
@@ -156,6 +172,108 @@ pub async fn memory_export(
     Ok(())
 }
 
+/// Merge similar long-term entries and drop those not referenced for `days`
+/// (default `agents.memory.decay_days`).
+///
+/// Merging asks `agents.memory.model` (or the default model) to rewrite
+/// each group of similar entries as one; `no_merge` skips it. With
+/// `dry_run` the plan is printed and nothing changes.
+pub async fn memory_compact(
+    days: Option<u32>,
+    no_merge: bool,
+    dry_run: bool,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let platform = Arc::new(NativePlatform::new());
+    let backend = super::open_memory_backend(config, platform).await?;
+
+    let mut memory_config = config.agents.memory.clone();
+    if let Some(days) = days {
+        memory_config.decay_days = days;
+    }
+    let merger = (!no_merge).then(|| {
+        let model = memory_config
+            .model
+            .clone()
+            .unwrap_or_else(|| config.agents.defaults.model.clone());
+        let pipeline = build_live_pipeline(config);
+        EntryMerger::new(pipeline.default_pipeline().transport.clone(), model)
+    });
+
+    let report = compact(
+        backend.as_ref(),
+        &memory_config,
+        merger.as_ref(),
+        chrono::Utc::now(),
+        dry_run,
+    )
+    .await?;
+
+    let (merge_verb, drop_verb) = if dry_run {
+        ("Would merge", "Would drop")
+    } else {
+        ("Merged", "Dropped")
+    };
+    if !report.merged.is_empty() {
+        println!(
+            "{merge_verb} {} group(s) of similar entries:",
+            report.merged.len()
+        );
+        for group in &report.merged {
+            for source in &group.sources {
+                println!("  - {source}");
+            }
+            if let Some(merged) = &group.merged {
+                println!("  = {merged}");
+            }
+            println!();
+        }
+    }
+    let dropped = report.dropped.len();
+    println!(
+        "{drop_verb} {dropped} entr{} not referenced in {} days.",
+        if dropped == 1 { "y" } else { "ies" },
+        memory_config.decay_days
+    );
+    for entry in &report.dropped {
+        println!("  - {entry}");
+    }
+    Ok(())
+}
+
+/// Copy `MEMORY.md` and `HISTORY.md` into `memory.db` in the same directory.
+///
+/// Entries keep their keys, so running it again overwrites rather than
+/// duplicates them.
+#[cfg(feature = "sqlite-memory")]
+pub async fn memory_migrate() -> anyhow::Result<()> {
+    use clawft_core::agent::memory::{
+        MarkdownMemoryBackend, SQLITE_DB_NAME, SqliteMemoryBackend, migrate,
+    };
+
+    let platform = Arc::new(NativePlatform::new());
+    let store = MemoryStore::new(platform)
+        .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
+    let db_path = store.memory_dir().join(SQLITE_DB_NAME);
+    let markdown = MarkdownMemoryBackend::new(Arc::new(store));
+    let sqlite = SqliteMemoryBackend::open(&db_path)?;
+
+    let copied = migrate(&markdown, &sqlite).await?;
+    println!(
+        "Imported {copied} entr{} into {}",
+        if copied == 1 { "y" } else { "ies" },
+        db_path.display()
+    );
+    println!("  Set agents.memory.backend = \"sqlite\" to use it.");
+    Ok(())
+}
+
+/// Copy the markdown memory files into SQLite (unavailable in this build).
+#[cfg(not(feature = "sqlite-memory"))]
+pub async fn memory_migrate() -> anyhow::Result<()> {
+    anyhow::bail!("weft was built without SQLite memory support (feature `sqlite-memory`)")
+}
+
 /// Import agent memory from a file.
 ///
 /// Reads a previously exported JSON file and prints a summary. The actual
@@ -3,19 +3,34 @@
 //! Each subcommand is implemented in its own module:
 //!
 //! - [`agent`] -- Interactive agent session or single-message mode.
+//! - [`complete_cmd`] -- Candidates for shell completion (`weft __complete`).
+//! - [`config_validate`] -- Config file validation (`weft config validate`).
+//! - [`daemon`] -- Background gateway (`--daemon`, `stop`, `status`).
+//! - [`doctor`] -- Environment and config checks.
 //! - [`gateway`] -- Channel gateway (Telegram, Slack, etc.) + agent loop.
 //! - [`help_cmd`] -- Topic-aware help (`weft help [topic]`).
+//! - [`logs_cmd`] -- The gateway's structured log file.
 //! - [`status`] -- Configuration diagnostics.
 
 pub mod agent;
 pub mod agents_cmd;
 pub mod analyze_cmd;
 pub mod assess_cmd;
+pub mod audit_cmd;
 pub mod channels;
+pub mod complete_cmd;
 pub mod config_cmd;
+pub mod config_validate;
+pub mod control;
 pub mod cron;
+#[cfg(unix)]
+pub mod daemon;
+pub mod doctor;
 pub mod gateway;
 pub mod help_cmd;
+pub mod logs_cmd;
+#[cfg(feature = "services")]
+pub mod mcp_resources;
 #[cfg(feature = "services")]
 pub mod mcp_server;
 pub mod memory_cmd;
@@ -26,6 +41,7 @@ pub mod security_cmd;
 pub mod sessions;
 pub mod skills_cmd;
 pub mod status;
+pub mod tasks_cmd;
 pub mod tools_cmd;
 #[cfg(feature = "api")]
 pub mod ui_cmd;
@@ -36,9 +52,13 @@ pub mod workspace_cmd;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use clawft_core::agent::memory::{MemoryBackend, MemoryStore, open_backend};
+use clawft_core::agent::questions::PendingQuestions;
+use clawft_core::agent::secrets::Secrets;
 use clawft_core::tools::registry::ToolRegistry;
 use clawft_platform::Platform;
 use clawft_types::config::Config;
+use tracing::warn;
 
 /// Load configuration from the given path override or via auto-discovery.
 ///
@@ -72,7 +92,12 @@ pub async fn load_config<P: Platform>(
             .map_err(|e| anyhow::anyhow!("failed to load config: {e}"))?
     };
 
-    let config: Config = serde_json::from_value(raw)?;
+    let mut config: Config = serde_json::from_value(raw)?;
+    // Resolve provider keys through the platform environment so `.env`
+    // values and scoped overrides apply, not just the process environment.
+    config
+        .providers
+        .resolve_api_keys(|name| platform.env().get_var(name));
     Ok(config)
 }
 
@@ -86,12 +111,37 @@ pub fn expand_workspace(raw: &str) -> PathBuf {
     PathBuf::from(raw)
 }
 
+/// Build the native platform for a loaded config.
+///
+/// Applies the `http` section (timeouts, proxy, extra CA, user-agent) to
+/// the platform's HTTP client, which is shared by providers and tools.
+pub fn platform_for_config(config: &Config) -> anyhow::Result<clawft_platform::NativePlatform> {
+    let http = clawft_platform::http::HttpClientConfig::from(&config.http);
+    clawft_platform::NativePlatform::with_http_config(&http)
+        .map_err(|e| anyhow::anyhow!("invalid http config: {e}"))
+}
+
 /// Discover the config file path (for display in `weft status`).
 pub fn discover_config_path<P: Platform>(platform: &P) -> Option<PathBuf> {
     let home = platform.fs().home_dir();
     clawft_platform::config_loader::discover_config_path(platform.env(), home)
 }
 
+/// Open the memory backend selected by `agents.memory.backend`.
+///
+/// Commands that bootstrap an [`AppContext`](clawft_core::bootstrap::AppContext)
+/// should use its backend instead, so the tools and the agent share one.
+pub async fn open_memory_backend<P: Platform + 'static>(
+    config: &Config,
+    platform: Arc<P>,
+) -> anyhow::Result<Arc<dyn MemoryBackend>> {
+    let store = MemoryStore::new(platform)
+        .map_err(|e| anyhow::anyhow!("failed to initialize memory store: {e}"))?;
+    open_backend(Arc::new(store), &config.agents.memory)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to open memory backend: {e}"))
+}
+
 /// Register the core set of tools into a [`ToolRegistry`].
 ///
 /// This is the shared tool setup used by `weft agent`, `weft gateway`, and
@@ -100,30 +150,54 @@ pub fn discover_config_path<P: Platform>(platform: &P) -> Option<PathBuf> {
 /// 1. Builds security policies (command + URL) from config.
 /// 2. Registers all built-in tools via [`clawft_tools::register_all`].
 /// 3. Registers MCP server tools (proxied from configured MCP servers).
-/// 4. Registers the delegation tool (feature-gated).
+///    Their sampling requests are put to the user through `questions`
+///    when a server's policy is `ask`.
+/// 4. Registers the delegation tools (feature-gated). `delegate_remote`
+///    records what its tasks cost on peer gateways in `usage`.
 ///
 /// Callers that need additional tools (e.g. `MessageTool` with a bus reference)
 /// should register them separately after calling this function.
+///
+/// Returns the connected MCP servers, for health reporting.
 pub async fn register_core_tools<P: Platform + 'static>(
     registry: &mut ToolRegistry,
     config: &Config,
     platform: Arc<P>,
-) {
+    memory: Arc<dyn MemoryBackend>,
+    questions: Option<Arc<PendingQuestions>>,
+    usage: Option<Arc<clawft_llm::UsageTracker>>,
+) -> crate::mcp_tools::McpServers {
     let command_policy = agent::build_command_policy(&config.tools.command_policy);
     let url_policy = agent::build_url_policy(&config.tools.url_policy);
     let workspace = expand_workspace(&config.agents.defaults.workspace);
     let web_search_config = agent::build_web_search_config(&config.tools);
+    if !config.tools.http.secrets.is_empty() {
+        warn!(
+            "tools.http.secrets is deprecated; move the secrets to tools.secrets \
+             and grant them to http_request"
+        );
+    }
+    let secrets = Secrets::from_config(&config.tools.all_secrets(), |name| {
+        platform.env().get_var(name)
+    });
 
     clawft_tools::register_all(
         registry,
-        platform,
+        platform.clone(),
         workspace,
         command_policy,
         url_policy,
         web_search_config,
+        memory,
+        &config.agents.memory,
+        &config.tools.exec_tool,
+        &config.tools.http,
+        Arc::new(secrets),
+        &config.tools.sqlite,
+        &config.tools.desktop,
     );
 
-    let _mcp_sessions = crate::mcp_tools::register_mcp_tools(config, registry).await;
+    let mcp_servers = crate::mcp_tools::register_mcp_tools(config, registry, questions).await;
 
     // Pass the Anthropic provider API key from config as a fallback for delegation.
     let anthropic_key = config.providers.anthropic.api_key.expose();
@@ -133,6 +207,16 @@ pub async fn register_core_tools<P: Platform + 'static>(
         Some(anthropic_key)
     };
     crate::mcp_tools::register_delegation(&config.delegation, registry, config_api_key);
+    #[cfg(feature = "delegate")]
+    crate::remote_delegate::register_remote_delegation(
+        &config.delegation,
+        registry,
+        platform.as_ref(),
+        usage,
+    );
+    #[cfg(not(feature = "delegate"))]
+    let _ = usage;
+    mcp_servers
 }
 
 /// Build an `Arc<ChannelHost>` implementation that bridges the channel
@@ -163,8 +247,12 @@ impl clawft_channels::ChannelHost for BusChannelHost {
         &self,
         msg: clawft_types::event::InboundMessage,
     ) -> Result<(), clawft_types::error::ChannelError> {
+        // Waits for room when the bus is full, so adapters slow down
+        // instead of dropping user messages.
         self.bus
-            .publish_inbound(msg)
+            .publish(msg)
+            .await
+            .map(|_| ())
             .map_err(|e| clawft_types::error::ChannelError::Other(e.to_string()))
     }
 
@@ -193,6 +281,7 @@ impl clawft_channels::ChannelHost for BusChannelHost {
             content: content.to_owned(),
             timestamp: chrono::Utc::now(),
             media,
+            attachments: vec![],
             metadata,
         };
         self.deliver_inbound(msg).await
@@ -203,6 +292,16 @@ impl clawft_channels::ChannelHost for BusChannelHost {
 mod tests {
     use super::*;
 
+    #[test]
+    fn platform_for_config_applies_http_section() {
+        assert!(platform_for_config(&Config::default()).is_ok());
+
+        let mut config = Config::default();
+        config.http.ca_cert_path = Some("/nonexistent/clawft/ca.pem".into());
+        let err = platform_for_config(&config).err().unwrap();
+        assert!(err.to_string().contains("invalid http config"));
+    }
+
     #[test]
     fn expand_workspace_tilde() {
         let expanded = expand_workspace("~/.clawft/workspace");
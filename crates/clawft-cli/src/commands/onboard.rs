@@ -9,6 +9,8 @@ use std::path::{Path, PathBuf};
 use clap::Args;
 use serde_json::json;
 
+use clawft_core::workspace::{WorkspaceManager, WorkspaceTemplate};
+
 /// Arguments for `weft onboard`.
 #[derive(Args)]
 pub struct OnboardArgs {
@@ -19,6 +21,11 @@ pub struct OnboardArgs {
     /// Override the config directory (default: ~/.clawft).
     #[arg(long)]
     pub dir: Option<String>,
+
+    /// Workspace template to lay out the new workspace with (see
+    /// `weft workspace create --template`).
+    #[arg(long, default_value = "default")]
+    pub template: String,
 }
 
 /// Subdirectories created under the config root.
@@ -54,7 +61,10 @@ pub async fn run(args: OnboardArgs) -> anyhow::Result<()> {
     }
 
     // Create directory structure
-    create_directory_structure(&config_dir)?;
+    let template = WorkspaceManager::new()
+        .and_then(|mgr| mgr.resolve_template(&args.template))
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    create_directory_structure(&config_dir, &template)?;
 
     // Collect provider configuration
     let provider_config = if args.yes {
@@ -90,14 +100,36 @@ fn resolve_config_dir(override_dir: Option<&str>) -> anyhow::Result<PathBuf> {
 }
 
 /// Create the directory structure under the config root.
-fn create_directory_structure(config_dir: &Path) -> anyhow::Result<()> {
+///
+/// A new (or empty) workspace is laid out from `template`; an existing one
+/// is left as it is.
+fn create_directory_structure(
+    config_dir: &Path,
+    template: &WorkspaceTemplate,
+) -> anyhow::Result<()> {
     // Main config dir
     std::fs::create_dir_all(config_dir)?;
     println!("  Created {}", config_dir.display());
 
-    // Workspace subdirectory
+    // Workspace subdirectory, from the template unless it already has files
     let workspace_dir = config_dir.join("workspace");
-    std::fs::create_dir_all(&workspace_dir)?;
+    let is_empty = std::fs::read_dir(&workspace_dir)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(true);
+    if is_empty {
+        let notes = template
+            .apply(&workspace_dir, "workspace", &Default::default())
+            .map_err(|e| anyhow::anyhow!("failed to apply workspace template: {e}"))?;
+        println!("  Applied workspace template '{}'", template.name());
+        if let Some(notes) = notes {
+            println!("  {notes}");
+        }
+    } else {
+        println!(
+            "  Workspace {} already exists, template not applied",
+            workspace_dir.display()
+        );
+    }
 
     // Subdirs under workspace
     for subdir in WORKSPACE_DIRS {
@@ -328,12 +360,26 @@ mod tests {
     #[test]
     fn create_directory_structure_creates_all_dirs() {
         let tmp = tempdir("create-dirs");
-        create_directory_structure(&tmp).unwrap();
+        create_directory_structure(&tmp, &WorkspaceTemplate::builtin()).unwrap();
 
         assert!(tmp.join("workspace").is_dir());
         assert!(tmp.join("workspace/sessions").is_dir());
         assert!(tmp.join("workspace/memory").is_dir());
         assert!(tmp.join("workspace/skills").is_dir());
+        assert!(tmp.join("workspace/CLAWFT.md").is_file());
+        assert!(tmp.join("workspace/.clawft/config.json").is_file());
+    }
+
+    #[test]
+    fn create_directory_structure_keeps_existing_workspace() {
+        let tmp = tempdir("create-dirs-existing");
+        std::fs::create_dir_all(tmp.join("workspace")).unwrap();
+        std::fs::write(tmp.join("workspace/USER.md"), "mine").unwrap();
+
+        create_directory_structure(&tmp, &WorkspaceTemplate::builtin()).unwrap();
+
+        assert!(!tmp.join("workspace/CLAWFT.md").exists());
+        assert!(tmp.join("workspace/skills").is_dir());
     }
 
     #[test]
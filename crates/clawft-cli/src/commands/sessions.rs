@@ -1,24 +1,37 @@
 //! `weft sessions` -- manage conversation sessions.
 //!
-//! Provides subcommands for listing, inspecting, and deleting sessions.
-//! Sessions are JSONL files stored under `~/.clawft/workspace/sessions/`
-//! (or `~/.nanobot/workspace/sessions/` as fallback).
+//! Provides subcommands for listing, inspecting, exporting, forking,
+//! deleting, restoring archived, and migrating sessions. Sessions live under `~/.clawft/workspace/sessions/` (or
+//! `~/.nanobot/workspace/sessions/` as fallback), as JSONL files or, with
+//! `agents.sessions.backend = "sqlite"`, in `sessions.db`.
 //!
 //! # Examples
 //!
 //! ```text
 //! weft sessions list
+//! weft sessions list --prefix telegram: --limit 20
+//! weft sessions list --archived
 //! weft sessions inspect telegram:12345
+//! weft sessions inspect telegram:12345 --tools
+//! weft sessions inspect telegram:12345 --trace --turn 3
+//! weft sessions export telegram:12345 --format md --output chat.md
+//! weft sessions fork telegram:12345 --at 6
+//! weft sessions restore telegram:12345
 //! weft sessions delete telegram:12345
+//! weft sessions migrate
 //! ```
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use comfy_table::{Table, presets::UTF8_FULL};
 
-use clawft_core::session::SessionManager;
-use clawft_platform::NativePlatform;
+use clawft_core::agent::trace::{self, TurnTrace};
+use clawft_core::session::{SessionManager, SessionQuery};
+use clawft_core::tools::audit::{AuditFilter, Redactor};
+use clawft_platform::{NativePlatform, Platform};
 use clawft_types::config::Config;
+use clawft_types::session::{ExportFormat, ExportOptions, Session};
 
 /// Actions that can be performed on sessions (used in tests).
 #[cfg(test)]
@@ -56,47 +69,67 @@ fn message_content_preview(msg: &serde_json::Value, max_len: usize) -> String {
     }
 }
 
-/// List all sessions.
-pub async fn sessions_list(_config: &Config) -> anyhow::Result<()> {
+/// Open the session manager for the configured backend.
+async fn open_sessions(config: &Config) -> anyhow::Result<SessionManager<NativePlatform>> {
     let platform = Arc::new(NativePlatform::new());
-    let mgr = SessionManager::new(platform).await?;
+    Ok(SessionManager::from_config(platform, &config.agents.sessions).await?)
+}
 
-    let keys = mgr.list_sessions().await?;
+/// List the sessions matching `query`.
+pub async fn sessions_list(query: &SessionQuery, config: &Config) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
+    let page = mgr.list(query).await?;
 
-    if keys.is_empty() {
-        println!("No sessions found.");
+    if page.sessions.is_empty() {
+        let kind = if query.archived { "archived " } else { "" };
+        println!("No {kind}sessions found.");
         println!("  Dir: {}", mgr.sessions_dir().display());
         return Ok(());
     }
 
     let mut table = Table::new();
     table.load_preset(UTF8_FULL);
-    table.set_header(["SESSION KEY", "MESSAGES", "LAST UPDATED"]);
-
-    for key in &keys {
-        match mgr.load_session(key).await {
-            Ok(session) => {
-                let msg_count = session.messages.len().to_string();
-                let updated = format_datetime(&session.updated_at);
-                table.add_row([key.as_str(), &msg_count, &updated]);
-            }
-            Err(_) => {
-                // Session file may be corrupt; show what we can.
-                table.add_row([key.as_str(), "?", "?"]);
-            }
-        }
+    table.set_header(["SESSION KEY", "MESSAGES", "LAST UPDATED", "FORKED FROM"]);
+
+    for summary in &page.sessions {
+        let msg_count = summary.message_count.to_string();
+        let updated = format_datetime(&summary.updated_at);
+        let parent = summary.parent.as_deref().unwrap_or("-");
+        table.add_row([summary.key.as_str(), &msg_count, &updated, parent]);
     }
 
     println!("{table}");
-    println!("  {} session(s)", keys.len());
+    if page.sessions.len() < page.total {
+        println!(
+            "  {}-{} of {} session(s)",
+            query.offset + 1,
+            query.offset + page.sessions.len(),
+            page.total
+        );
+    } else {
+        println!("  {} session(s)", page.total);
+    }
     println!("  Dir: {}", mgr.sessions_dir().display());
     Ok(())
 }
 
-/// Inspect a single session, displaying its messages.
-pub async fn sessions_inspect(session_id: String, _config: &Config) -> anyhow::Result<()> {
-    let platform = Arc::new(NativePlatform::new());
-    let mgr = SessionManager::new(platform).await?;
+/// How many audit records `sessions inspect --tools` shows.
+const INSPECT_TOOL_LIMIT: usize = 200;
+
+/// Inspect a single session, displaying its messages, or with `tools` its
+/// tool executions from the audit log, or with `trace` the trace of turn
+/// `turn` (default: the latest traced turn).
+pub async fn sessions_inspect(
+    session_id: String,
+    tools: bool,
+    trace: bool,
+    turn: Option<usize>,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
+    if trace {
+        return inspect_trace(&mgr, &session_id, turn).await;
+    }
 
     let session = mgr
         .load_session(&session_id)
@@ -120,6 +153,10 @@ pub async fn sessions_inspect(session_id: String, _config: &Config) -> anyhow::R
         }
     }
 
+    if tools {
+        return inspect_tools(&session_id, config).await;
+    }
+
     if session.messages.is_empty() {
         println!("\n  (no messages)");
         return Ok(());
@@ -147,10 +184,330 @@ pub async fn sessions_inspect(session_id: String, _config: &Config) -> anyhow::R
     Ok(())
 }
 
+/// Print the tool executions recorded for a session.
+async fn inspect_tools(session_id: &str, config: &Config) -> anyhow::Result<()> {
+    let store = super::audit_cmd::open_audit_store(config).await?;
+    let filter = AuditFilter {
+        session_key: Some(session_id.to_string()),
+        tool: None,
+    };
+    let records = store.recent(&filter, INSPECT_TOOL_LIMIT).await?;
+
+    if records.is_empty() {
+        println!("\n  (no tool executions recorded)");
+        return Ok(());
+    }
+
+    println!("\nTool executions ({}):", records.len());
+    println!("{}", super::audit_cmd::audit_table(&records));
+    Ok(())
+}
+
+/// Print the trace of turn `turn` of a session (or of its latest traced
+/// turn).
+async fn inspect_trace(
+    mgr: &SessionManager<NativePlatform>,
+    session_id: &str,
+    turn: Option<usize>,
+) -> anyhow::Result<()> {
+    let platform = NativePlatform::new();
+    let fs = platform.fs();
+    match trace::read_trace(fs, mgr.sessions_dir(), session_id, turn).await? {
+        Some(trace) => {
+            print!("{}", format_trace(&trace));
+            Ok(())
+        }
+        None => {
+            let traced = trace::traced_turns(fs, mgr.sessions_dir(), session_id).await;
+            let which = turn.map_or_else(|| "any turn".into(), |n| format!("turn {n}"));
+            if traced.is_empty() {
+                anyhow::bail!(
+                    "no trace for {which} of '{session_id}' \
+                     (enable agents.sessions.trace or run `weft agent --trace`)"
+                );
+            }
+            let traced: Vec<String> = traced.iter().map(|n| n.to_string()).collect();
+            anyhow::bail!(
+                "no trace for {which} of '{session_id}'; traced turns: {}",
+                traced.join(", ")
+            )
+        }
+    }
+}
+
+/// Render a turn trace for the terminal.
+fn format_trace(trace: &TurnTrace) -> String {
+    use super::audit_cmd::{args_preview, format_bytes};
+    use std::fmt::Write;
+
+    let outcome = serde_json::to_value(trace.outcome)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let settings = &trace.settings;
+    let context = &trace.context;
+    let mut out = String::new();
+    let _ = writeln!(out, "Turn {} of {}", trace.turn, trace.session_key);
+    let _ = writeln!(out, "  Started:  {}", format_datetime(&trace.started_at));
+    let _ = writeln!(out, "  Duration: {}ms", trace.duration_ms);
+    let _ = writeln!(out, "  Outcome:  {outcome}");
+    if let Some(error) = &trace.error {
+        let _ = writeln!(out, "  Error:    {error}");
+    }
+    let _ = writeln!(out, "  Agent:    {}", trace.agent_id);
+    let _ = writeln!(
+        out,
+        "  Model:    {} (max_tokens {}, temperature {}, max {} tool iterations)",
+        settings.model, settings.max_tokens, settings.temperature, settings.max_tool_iterations
+    );
+    if let Some(allowed) = &settings.allowed_tools {
+        let _ = writeln!(out, "  Allowed tools: {}", allowed.join(", "));
+    }
+    if let Some(decision) = &trace.skill {
+        let scores = decision
+            .candidates
+            .iter()
+            .map(|c| format!("{} {:.2}", c.skill, c.score))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(
+            out,
+            "  Skill:    {} (threshold {:.2}{}{})",
+            decision.activated.as_deref().unwrap_or("none"),
+            decision.threshold,
+            if scores.is_empty() { "" } else { "; " },
+            scores
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\nContext ({} of {} tokens, {} dropped):",
+        context.tokens, context.budget, context.dropped_tokens
+    );
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(["#", "ROLE", "TOKENS", "CHARS", "CONTENT"]);
+    for (i, section) in context.sections.iter().enumerate() {
+        let mut preview: String = section.preview.chars().take(60).collect();
+        if section.chars > 60 {
+            preview.push_str("...");
+        }
+        table.add_row([
+            (i + 1).to_string(),
+            section.role.clone(),
+            section.tokens.to_string(),
+            section.chars.to_string(),
+            preview.replace('\n', " "),
+        ]);
+    }
+    let _ = writeln!(out, "{table}");
+    if !context.tools.is_empty() {
+        let _ = writeln!(out, "  Tools: {}", context.tools.join(", "));
+    }
+
+    let _ = writeln!(out, "\nCompletions ({}):", trace.completions.len());
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header([
+        "ITER", "MODEL", "STOP", "DURATION", "IN", "OUT", "RETRIES", "FAILOVER",
+    ]);
+    for completion in &trace.completions {
+        let failover: Vec<String> = completion
+            .failover
+            .iter()
+            .map(|event| {
+                let provider = event["provider"].as_str().unwrap_or("?");
+                let reason = event["reason"].as_str().unwrap_or("?");
+                format!("{provider} ({reason})")
+            })
+            .collect();
+        table.add_row([
+            completion.iteration.to_string(),
+            completion.model.clone().unwrap_or_else(|| "-".into()),
+            completion.stop_reason.clone(),
+            format!("{}ms", completion.duration_ms),
+            completion.usage.input_tokens.to_string(),
+            completion.usage.output_tokens.to_string(),
+            completion.retries.to_string(),
+            if failover.is_empty() {
+                "-".into()
+            } else {
+                failover.join(", ")
+            },
+        ]);
+    }
+    let _ = writeln!(out, "{table}");
+
+    if !trace.tool_calls.is_empty() {
+        let _ = writeln!(out, "\nTool calls ({}):", trace.tool_calls.len());
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(["ITER", "TOOL", "DURATION", "STATUS", "RESULT", "ARGS"]);
+        for call in &trace.tool_calls {
+            let status = match &call.error {
+                Some(error) => format!("error: {error}"),
+                None if call.success => "ok".into(),
+                None => "error".into(),
+            };
+            table.add_row([
+                call.iteration.to_string(),
+                call.tool.clone(),
+                format!("{}ms", call.duration_ms),
+                status,
+                format_bytes(call.result_bytes),
+                args_preview(&call.args, 60),
+            ]);
+        }
+        let _ = writeln!(out, "{table}");
+    }
+
+    let usage = &trace.usage;
+    let _ = write!(
+        out,
+        "\nUsage: {} input, {} output tokens",
+        usage.input_tokens, usage.output_tokens
+    );
+    if usage.cache_read_tokens > 0 || usage.cache_write_tokens > 0 {
+        let _ = write!(
+            out,
+            " (cache: {} read, {} written)",
+            usage.cache_read_tokens, usage.cache_write_tokens
+        );
+    }
+    out.push('\n');
+    out
+}
+
+/// Export a session as markdown or JSON, to `output` or stdout.
+///
+/// Tool call arguments pass through the audit redaction rules
+/// (`tools.audit.redact_fields`). With `output`, attachments the session
+/// references are copied into a sibling `<name>_files/` directory, or with
+/// `zip` into `<name>_files.zip`, and the export links to them.
+pub async fn sessions_export(
+    session_id: String,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+    zip: bool,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
+    let session = mgr
+        .load_session(&session_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to load session '{}': {e}", session_id))?;
+    let redactor = Redactor::new(&config.tools.audit.redact_fields);
+
+    let Some(output) = output else {
+        if zip {
+            anyhow::bail!("--zip requires --output");
+        }
+        let redact = |args: &serde_json::Value| redactor.redact(args);
+        let options = ExportOptions::default().with_redactor(&redact);
+        print!("{}", session.export_with(format, &options));
+        return Ok(());
+    };
+
+    let bundle = write_export(&session, format, &redactor, &output, zip)?;
+    println!("Exported '{}' to {}", session.key, output.display());
+    if let Some(bundle) = bundle {
+        println!("  Attachments: {}", bundle.display());
+    }
+    Ok(())
+}
+
+/// Write the export of `session` to `output` and bundle its attachments
+/// next to it. Returns where the attachments went, if there were any.
+fn write_export(
+    session: &Session,
+    format: ExportFormat,
+    redactor: &Redactor,
+    output: &Path,
+    zip: bool,
+) -> anyhow::Result<Option<PathBuf>> {
+    let parent = output
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "session".into());
+    let dir_name = format!("{stem}_files");
+    let dir = parent.join(&dir_name);
+
+    let attachments: Vec<_> = session
+        .attachments()
+        .into_iter()
+        .filter_map(|a| a.source.clone().map(|source| (source, a)))
+        .collect();
+
+    let redact = |args: &serde_json::Value| redactor.redact(args);
+    let mut options = ExportOptions::default().with_redactor(&redact);
+    let mut bundle = None;
+    if !attachments.is_empty() {
+        if zip && dir.exists() {
+            anyhow::bail!(
+                "{} already exists; move it before bundling into a zip",
+                dir.display()
+            );
+        }
+        std::fs::create_dir_all(&dir)?;
+        for (source, attachment) in &attachments {
+            if let Err(e) = std::fs::copy(source, dir.join(&attachment.bundle_name)) {
+                eprintln!("warning: attachment {} not bundled: {e}", source.display());
+            }
+        }
+        bundle = Some(if zip {
+            let archive = parent.join(format!("{dir_name}.zip"));
+            clawft_platform::archive::create(&archive, std::slice::from_ref(&dir))?;
+            std::fs::remove_dir_all(&dir)?;
+            archive
+        } else {
+            dir
+        });
+        options = options.with_attachments_dir(dir_name);
+    }
+
+    std::fs::write(output, session.export_with(format, &options))?;
+    Ok(bundle)
+}
+
+/// Fork a session at message `at` (default: its whole history) and print
+/// the new session's key.
+///
+/// The fork shares the workspace memory with its parent; only the
+/// conversation history diverges.
+pub async fn sessions_fork(
+    session_id: String,
+    at: Option<usize>,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
+
+    let at = match at {
+        Some(at) => at,
+        None => mgr
+            .load_session(&session_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to load session '{}': {e}", session_id))?
+            .messages
+            .len(),
+    };
+    let fork = mgr
+        .fork(&session_id, at)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to fork session '{}': {e}", session_id))?;
+
+    println!("Forked '{session_id}' at message {at} into '{}'.", fork.key);
+    println!("  Continue it with: weft agent --session {}", fork.key);
+    Ok(())
+}
+
 /// Delete a session.
-pub async fn sessions_delete(session_id: String, _config: &Config) -> anyhow::Result<()> {
-    let platform = Arc::new(NativePlatform::new());
-    let mgr = SessionManager::new(platform).await?;
+pub async fn sessions_delete(session_id: String, config: &Config) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
 
     mgr.delete_session(&session_id)
         .await
@@ -160,6 +517,47 @@ pub async fn sessions_delete(session_id: String, _config: &Config) -> anyhow::Re
     Ok(())
 }
 
+/// Move an archived session back to the live sessions.
+pub async fn sessions_restore(session_id: String, config: &Config) -> anyhow::Result<()> {
+    let mgr = open_sessions(config).await?;
+
+    let restored = mgr
+        .restore(&session_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to restore session '{}': {e}", session_id))?;
+    if !restored {
+        anyhow::bail!("no archived session '{session_id}'");
+    }
+
+    println!("Session '{}' restored.", session_id);
+    Ok(())
+}
+
+/// Import the JSONL session files into `sessions.db` in the same directory.
+///
+/// Sessions already in the database are skipped, so running it again only
+/// picks up new files.
+#[cfg(feature = "sqlite-sessions")]
+pub async fn sessions_migrate() -> anyhow::Result<()> {
+    use clawft_core::session::{SQLITE_DB_NAME, SqliteSessionStore, migrate};
+
+    let platform = Arc::new(NativePlatform::new());
+    let files = SessionManager::new(platform).await?;
+    let db_path = files.sessions_dir().join(SQLITE_DB_NAME);
+    let sqlite = SqliteSessionStore::open(&db_path)?;
+
+    let copied = migrate(files.store(), &sqlite).await?;
+    println!("Imported {copied} session(s) into {}", db_path.display());
+    println!("  Set agents.sessions.backend = \"sqlite\" to use it.");
+    Ok(())
+}
+
+/// Import the JSONL session files into SQLite (unavailable in this build).
+#[cfg(not(feature = "sqlite-sessions"))]
+pub async fn sessions_migrate() -> anyhow::Result<()> {
+    anyhow::bail!("weft was built without SQLite session support (feature `sqlite-sessions`)")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,4 +694,211 @@ mod tests {
         let msg = serde_json::json!({"role": "user", "content": 42});
         assert_eq!(message_content_preview(&msg, 80), "");
     }
+
+    // ── format_trace ─────────────────────────────────────────────────
+
+    #[test]
+    fn format_trace_shows_every_part_of_the_turn() {
+        use clawft_core::agent::skill_activation::{SkillDecision, SkillScore};
+        use clawft_core::agent::trace::{
+            CompletionTrace, ContextSection, ToolCallTrace, TraceContext, TraceSettings,
+            TurnOutcome,
+        };
+        use clawft_types::config::SkillScoring;
+        use clawft_types::provider::Usage;
+
+        let usage = Usage {
+            input_tokens: 120,
+            output_tokens: 30,
+            ..Default::default()
+        };
+        let trace = TurnTrace {
+            session_key: "telegram:42".into(),
+            turn: 3,
+            agent_id: "default".into(),
+            started_at: chrono::Utc::now(),
+            duration_ms: 1500,
+            settings: TraceSettings {
+                model: "openai/gpt-4o".into(),
+                max_tokens: 4096,
+                temperature: 0.7,
+                max_tool_iterations: 10,
+                allowed_tools: Some(vec!["read_file".into()]),
+            },
+            skill: Some(SkillDecision {
+                scoring: SkillScoring::Keyword,
+                threshold: 0.5,
+                activated: Some("calendar".into()),
+                candidates: vec![SkillScore {
+                    skill: "calendar".into(),
+                    score: 1.0,
+                }],
+            }),
+            context: TraceContext {
+                budget: 100_000,
+                tokens: 900,
+                dropped_tokens: 0,
+                sections: vec![ContextSection {
+                    role: "system".into(),
+                    chars: 3000,
+                    tokens: 800,
+                    preview: "You are a helpful assistant.".into(),
+                }],
+                tools: vec!["read_file".into()],
+            },
+            completions: vec![CompletionTrace {
+                iteration: 0,
+                model: Some("anthropic/claude".into()),
+                duration_ms: 900,
+                stop_reason: "tool_use".into(),
+                usage,
+                retries: 2,
+                failover: vec![serde_json::json!({
+                    "provider": "openai", "model": "gpt-4o", "reason": "circuit_open"
+                })],
+            }],
+            tool_calls: vec![ToolCallTrace {
+                iteration: 0,
+                id: "call-1".into(),
+                tool: "read_file".into(),
+                args: serde_json::json!({"path": "notes.md"}),
+                duration_ms: 12,
+                success: false,
+                error: Some("file not found".into()),
+                result_bytes: 0,
+            }],
+            usage,
+            outcome: TurnOutcome::Completed,
+            error: None,
+        };
+
+        let text = format_trace(&trace);
+        for expected in [
+            "Turn 3 of telegram:42",
+            "Outcome:  completed",
+            "openai/gpt-4o (max_tokens 4096",
+            "Allowed tools: read_file",
+            "Skill:    calendar (threshold 0.50; calendar 1.00)",
+            "Context (900 of 100000 tokens, 0 dropped)",
+            "You are a helpful assistant.",
+            "anthropic/claude",
+            "openai (circuit_open)",
+            "error: file not found",
+            "notes.md",
+            "Usage: 120 input, 30 output tokens",
+        ] {
+            assert!(text.contains(expected), "missing {expected:?} in:\n{text}");
+        }
+    }
+
+    fn export_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clawft-sessions-export-{}-{name}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn session_with_attachment(dir: &Path) -> Session {
+        let source = dir.join("source.txt");
+        std::fs::write(&source, "report body").unwrap();
+        let mut session = Session::new("cli:export");
+        let attachments = [clawft_types::event::Attachment::from_path(
+            &source,
+            "text/plain",
+        )];
+        session.add_message("user", "see file", Session::attachment_extras(&attachments));
+        let call = serde_json::json!({
+            "id": "c1",
+            "type": "function",
+            "function": {"name": "http", "arguments": "{\"password\":\"hunter2\"}"},
+        });
+        let extras = std::collections::HashMap::from([(
+            "tool_calls".to_string(),
+            serde_json::json!([call]),
+        )]);
+        session.add_message("assistant", "", Some(extras));
+        session
+    }
+
+    #[test]
+    fn write_export_bundles_attachments_into_sibling_dir() {
+        let dir = export_dir("dir");
+        let session = session_with_attachment(&dir);
+        let output = dir.join("chat.md");
+
+        let bundle = write_export(
+            &session,
+            ExportFormat::Markdown,
+            &Redactor::default(),
+            &output,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(bundle, dir.join("chat_files"));
+        assert_eq!(
+            std::fs::read_to_string(bundle.join("source.txt")).unwrap(),
+            "report body"
+        );
+        let md = std::fs::read_to_string(&output).unwrap();
+        assert!(md.contains("[source.txt](chat_files/source.txt)"));
+        assert!(!md.contains("hunter2"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_export_bundles_attachments_into_zip() {
+        let dir = export_dir("zip");
+        let session = session_with_attachment(&dir);
+        let output = dir.join("chat.json");
+
+        let bundle = write_export(
+            &session,
+            ExportFormat::Json,
+            &Redactor::default(),
+            &output,
+            true,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(bundle, dir.join("chat_files.zip"));
+        assert!(!dir.join("chat_files").exists());
+        let unpacked = dir.join("unpacked");
+        clawft_platform::archive::extract(&bundle, &unpacked, &Default::default()).unwrap();
+        assert!(unpacked.join("chat_files/source.txt").is_file());
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(
+            doc["messages"][0]["attachments"][0]["bundle_path"],
+            "chat_files/source.txt"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_export_without_attachments_writes_only_transcript() {
+        let dir = export_dir("plain");
+        let mut session = Session::new("cli:plain");
+        session.add_message("user", "hi", None);
+        let output = dir.join("plain.md");
+
+        let bundle = write_export(
+            &session,
+            ExportFormat::Markdown,
+            &Redactor::default(),
+            &output,
+            false,
+        )
+        .unwrap();
+
+        assert!(bundle.is_none());
+        assert!(!dir.join("plain_files").exists());
+        assert!(std::fs::read_to_string(&output).unwrap().contains("hi"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -6,7 +6,8 @@
 //!   source annotation.
 //! - `weft skills show <name>` -- show skill details (description, variables,
 //!   instructions preview).
-//! - `weft skills install <path>` -- copy a skill to the user skills dir.
+//! - `weft skills install <path>` -- copy a skill directory, or unpack a
+//!   `.zip` / `.tar.gz` skill bundle, into the user skills dir.
 //! - `weft skills remove <name>` -- remove a user-installed skill.
 //! - `weft skills search <query>` -- search ClawHub for skills.
 //! - `weft skills publish <path>` -- publish a skill to ClawHub.
@@ -19,6 +20,7 @@ use clap::{Args, Subcommand};
 use comfy_table::{Table, presets};
 
 use clawft_core::agent::skills_v2::SkillRegistry;
+use clawft_platform::archive::{ArchiveFormat, ExtractLimits};
 use clawft_rpc::{DaemonClient, Request};
 use clawft_types::skill::{SkillDefinition, SkillFormat};
 
@@ -43,7 +45,8 @@ pub enum SkillsAction {
 
     /// Install a skill from a local path.
     Install {
-        /// Path to a skill directory (containing SKILL.md or skill.json).
+        /// Path to a skill directory (containing SKILL.md or skill.json),
+        /// or a `.zip` / `.tar.gz` archive of one.
         path: String,
     },
 
@@ -248,7 +251,7 @@ pub async fn run(args: SkillsArgs) -> anyhow::Result<()> {
 }
 
 /// Discover workspace and user skill directories.
-fn discover_skill_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
+pub(crate) fn discover_skill_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
     let user_dir = dirs::home_dir().map(|h| h.join(".clawft").join("skills"));
 
     // Walk upward from cwd to find .clawft/skills/
@@ -397,6 +400,9 @@ fn skills_install(source_path: &str, user_dir: Option<&Path>) -> anyhow::Result<
     if !source.exists() {
         anyhow::bail!("source path does not exist: {source_path}");
     }
+    if source.is_file() {
+        return install_from_archive(&source, user_dir);
+    }
 
     // Determine skill name from source directory name.
     let skill_name = source
@@ -425,6 +431,102 @@ fn skills_install(source_path: &str, user_dir: Option<&Path>) -> anyhow::Result<
     Ok(())
 }
 
+/// Limits for unpacking skill bundles; skills are small text-and-script
+/// trees, so anything larger is almost certainly not a skill.
+fn skill_archive_limits() -> ExtractLimits {
+    ExtractLimits {
+        max_entries: 1_000,
+        max_file_size: 10 * 1024 * 1024,
+        max_total_size: 50 * 1024 * 1024,
+    }
+}
+
+/// Install a skill from a `.zip` / `.tar.gz` bundle.
+///
+/// The bundle is unpacked into a staging directory under `user_dir` and
+/// moved into place only once it is known to hold a skill. The skill may
+/// sit at the archive root (named after the archive) or in a single
+/// top-level directory (named after that directory).
+fn install_from_archive(archive: &Path, user_dir: &Path) -> anyhow::Result<()> {
+    let file_name = archive
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("invalid archive path: {}", archive.display()))?;
+    if ArchiveFormat::from_path(archive).is_none() {
+        anyhow::bail!(
+            "unsupported skill file '{file_name}': expected a directory, .zip, .tar.gz or .tgz"
+        );
+    }
+
+    std::fs::create_dir_all(user_dir)
+        .map_err(|e| anyhow::anyhow!("failed to create user skills directory: {e}"))?;
+    let staging = user_dir.join(format!(".install-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+
+    let result = (|| {
+        clawft_platform::archive::extract(archive, &staging, &skill_archive_limits())
+            .map_err(|e| anyhow::anyhow!("failed to unpack {file_name}: {e}"))?;
+
+        let Some((root, nested_name)) = locate_skill_root(&staging)? else {
+            anyhow::bail!("{file_name} does not contain a skill (no SKILL.md or skill.json)");
+        };
+        let skill_name =
+            nested_name.unwrap_or_else(|| ArchiveFormat::strip_extension(file_name).to_string());
+        validate_skill_name(&skill_name)?;
+
+        let dest = user_dir.join(&skill_name);
+        if dest.exists() {
+            anyhow::bail!(
+                "skill '{skill_name}' already exists at {}. Remove it first.",
+                dest.display()
+            );
+        }
+        std::fs::rename(&root, &dest)
+            .map_err(|e| anyhow::anyhow!("failed to install skill: {e}"))?;
+        println!("Installed skill '{skill_name}' to {}", dest.display());
+        Ok(())
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Find the skill inside an unpacked bundle.
+///
+/// Returns the skill directory and, when it is a nested directory, its name.
+fn locate_skill_root(staging: &Path) -> anyhow::Result<Option<(PathBuf, Option<String>)>> {
+    let is_skill = |dir: &Path| dir.join("SKILL.md").is_file() || dir.join("skill.json").is_file();
+    if is_skill(staging) {
+        return Ok(Some((staging.to_path_buf(), None)));
+    }
+    let entries: Vec<_> = std::fs::read_dir(staging)?.collect::<Result<_, _>>()?;
+    if let [only] = entries.as_slice()
+        && only.file_type()?.is_dir()
+        && is_skill(&only.path())
+    {
+        let name = only.file_name().to_string_lossy().into_owned();
+        return Ok(Some((only.path(), Some(name))));
+    }
+    Ok(None)
+}
+
+/// Reject skill names that could escape the skills directory or hide.
+fn validate_skill_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_')
+    {
+        anyhow::bail!(
+            "skill name '{name}' contains invalid characters. \
+             Only alphanumeric characters, '.', '-', and '_' are allowed, \
+             and the name must not start with '.'."
+        );
+    }
+    Ok(())
+}
+
 // ── Remove ───────────────────────────────────────────────────────────
 
 /// Remove a user-installed skill from `~/.clawft/skills/<name>/`.
@@ -1245,6 +1347,69 @@ mod tests {
         let _ = std::fs::remove_dir_all(&user_dir);
     }
 
+    #[test]
+    fn skills_install_from_archive_with_top_level_dir() {
+        let src = temp_dir("install_archive_src");
+        let user_dir = temp_dir("install_archive_user");
+        create_skill_md(&src, "bundled", "From an archive");
+
+        for ext in ["zip", "tar.gz"] {
+            let archive = src.join(format!("download.{ext}"));
+            clawft_platform::archive::create(&archive, &[src.join("bundled")]).unwrap();
+
+            skills_install(archive.to_str().unwrap(), Some(&user_dir)).unwrap();
+            assert!(user_dir.join("bundled").join("SKILL.md").exists(), "{ext}");
+            // Staging is cleaned up.
+            assert_eq!(std::fs::read_dir(&user_dir).unwrap().count(), 1, "{ext}");
+            std::fs::remove_dir_all(user_dir.join("bundled")).unwrap();
+        }
+
+        let _ = std::fs::remove_dir_all(&src);
+        let _ = std::fs::remove_dir_all(&user_dir);
+    }
+
+    #[test]
+    fn skills_install_from_archive_at_root_uses_archive_name() {
+        let src = temp_dir("install_archive_root_src");
+        let user_dir = temp_dir("install_archive_root_user");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("SKILL.md"), "---\nname: flat\n---\n").unwrap();
+        let archive = src.join("flat-skill.zip");
+        clawft_platform::archive::create(&archive, &[src.join("SKILL.md")]).unwrap();
+
+        skills_install(archive.to_str().unwrap(), Some(&user_dir)).unwrap();
+        assert!(user_dir.join("flat-skill").join("SKILL.md").exists());
+
+        let _ = std::fs::remove_dir_all(&src);
+        let _ = std::fs::remove_dir_all(&user_dir);
+    }
+
+    #[test]
+    fn skills_install_rejects_non_skill_archive_and_cleans_up() {
+        let src = temp_dir("install_archive_bad_src");
+        let user_dir = temp_dir("install_archive_bad_user");
+        std::fs::create_dir_all(src.join("junk")).unwrap();
+        std::fs::write(src.join("junk").join("README"), "not a skill").unwrap();
+        let archive = src.join("junk.tar.gz");
+        clawft_platform::archive::create(&archive, &[src.join("junk")]).unwrap();
+
+        let msg = skills_install(archive.to_str().unwrap(), Some(&user_dir))
+            .unwrap_err()
+            .to_string();
+        assert!(msg.contains("does not contain a skill"), "{msg}");
+        assert_eq!(std::fs::read_dir(&user_dir).unwrap().count(), 0);
+
+        let plain = src.join("notes.txt");
+        std::fs::write(&plain, "x").unwrap();
+        let msg = skills_install(plain.to_str().unwrap(), Some(&user_dir))
+            .unwrap_err()
+            .to_string();
+        assert!(msg.contains("unsupported skill file"), "{msg}");
+
+        let _ = std::fs::remove_dir_all(&src);
+        let _ = std::fs::remove_dir_all(&user_dir);
+    }
+
     #[test]
     fn skills_remove_success() {
         let user_dir = temp_dir("remove_user");
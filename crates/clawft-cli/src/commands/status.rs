@@ -2,7 +2,9 @@
 //!
 //! Discovers the active configuration file, parses it, and displays
 //! a summary of the current settings. With `--detailed`, also shows
-//! channel and tool configuration.
+//! channel and tool configuration, checks that the default model's
+//! provider serves it, shows the health of MCP servers as reported by a
+//! running gateway, and shows today's LLM usage and spend.
 //!
 //! # Example
 //!
@@ -11,12 +13,55 @@
 //! weft status --detailed
 //! ```
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 use clap::Args;
+use serde::Deserialize;
 
-use clawft_platform::NativePlatform;
+use clawft_core::pipeline::llm_adapter::default_provider_config;
+use clawft_llm::ProviderRouter;
+use clawft_llm::error::ProviderError;
+use clawft_llm::router::{check_model, provider_for_config};
+use clawft_llm::usage::{self, DaySummary};
+use clawft_platform::{NativePlatform, Platform};
+use clawft_types::config::{Config, ProviderConfig, RateLimitConfig};
 
 use super::{discover_config_path, load_config};
 
+/// How long to wait for a running gateway to answer.
+const LIVE_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An MCP server's health as served by the gateway's `/api/mcp/health`.
+#[derive(Debug, Deserialize)]
+struct LiveMcpHealth {
+    server: String,
+    status: String,
+    circuit: String,
+    in_flight: usize,
+    calls: u64,
+    failures: u64,
+    timeouts: u64,
+    rejected: u64,
+    #[serde(default)]
+    latency_ms: Option<LiveLatency>,
+    #[serde(default)]
+    recent_errors: Vec<LiveError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveLatency {
+    p50: u64,
+    p95: u64,
+    p99: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveError {
+    at: String,
+    message: String,
+}
+
 /// Arguments for the `weft status` subcommand.
 #[derive(Args)]
 pub struct StatusArgs {
@@ -120,13 +165,18 @@ pub async fn run(args: StatusArgs) -> anyhow::Result<()> {
 
         println!();
         println!("Providers:");
-        print_provider("  anthropic", config.providers.anthropic.api_key.expose());
-        print_provider("  openai", config.providers.openai.api_key.expose());
-        print_provider("  openrouter", config.providers.openrouter.api_key.expose());
-        print_provider("  deepseek", config.providers.deepseek.api_key.expose());
-        print_provider("  groq", config.providers.groq.api_key.expose());
-        print_provider("  gemini", config.providers.gemini.api_key.expose());
-        print_provider("  custom", config.providers.custom.api_key.expose());
+        print_provider("  anthropic", &config.providers.anthropic);
+        print_provider("  openai", &config.providers.openai);
+        print_provider("  openrouter", &config.providers.openrouter);
+        print_provider("  deepseek", &config.providers.deepseek);
+        print_provider("  groq", &config.providers.groq);
+        print_provider("  gemini", &config.providers.gemini);
+        print_provider("  azure", &config.providers.azure);
+        print_provider("  custom", &config.providers.custom);
+
+        println!();
+        println!("Model check:");
+        println!("  {}", check_default_model(&config).await);
 
         println!();
         println!("Tools:");
@@ -155,6 +205,25 @@ pub async fn run(args: StatusArgs) -> anyhow::Result<()> {
                     println!("    {name}: (unconfigured)");
                 }
             }
+            println!();
+            println!("  MCP health:");
+            print_mcp_health(&platform, &config).await;
+        }
+
+        println!();
+        println!("Usage today:");
+        match dirs::home_dir().map(|home| usage::ledger_path(&home)) {
+            Some(path) => {
+                let today = chrono::Local::now().date_naive();
+                match usage::summarize_day(&path, today) {
+                    Ok(summary) => print_usage(&summary),
+                    Err(e) => println!("  unavailable ({}: {e})", path.display()),
+                }
+            }
+            None => println!("  unavailable (home directory not found)"),
+        }
+        if let Some(budget) = config.agents.usage.session_budget_usd {
+            println!("  Session budget: ${budget:.2}");
         }
     }
 
@@ -162,6 +231,99 @@ pub async fn run(args: StatusArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Fetch MCP server health from a running gateway and print it, or a note
+/// saying why it is unavailable.
+async fn print_mcp_health(platform: &NativePlatform, config: &Config) {
+    if !config.gateway.api_enabled {
+        println!("    unavailable (gateway.api_enabled is false)");
+        return;
+    }
+    let url = super::channels::api_url(config, "/api/mcp/health");
+    let headers = HashMap::new();
+    let fetch = platform.http().get(&url, &headers);
+    let health = match tokio::time::timeout(LIVE_HEALTH_TIMEOUT, fetch).await {
+        Ok(Ok(resp)) if resp.is_success() => resp.json::<Vec<LiveMcpHealth>>().ok(),
+        _ => None,
+    };
+    match health {
+        Some(servers) if servers.is_empty() => println!("    no servers connected"),
+        Some(servers) => {
+            for server in &servers {
+                for line in describe_mcp_health(server) {
+                    println!("    {line}");
+                }
+            }
+        }
+        None => println!("    unavailable (no gateway answering at {url})"),
+    }
+}
+
+/// One line for the server, then one per recent error.
+fn describe_mcp_health(h: &LiveMcpHealth) -> Vec<String> {
+    let mut summary = format!("{}: {}", h.server, h.status);
+    if h.circuit != "closed" {
+        summary.push_str(&format!(" (circuit {})", h.circuit.replace('_', "-")));
+    }
+    summary.push_str(&format!(", {} calls, {} in flight", h.calls, h.in_flight));
+    if let Some(l) = &h.latency_ms {
+        summary.push_str(&format!(", p50/p95/p99 {}/{}/{} ms", l.p50, l.p95, l.p99));
+    }
+    if h.failures > 0 {
+        summary.push_str(&format!(
+            ", {} failed ({} timed out)",
+            h.failures, h.timeouts
+        ));
+    }
+    if h.rejected > 0 {
+        summary.push_str(&format!(", {} rejected", h.rejected));
+    }
+    let mut lines = vec![summary];
+    lines.extend(
+        h.recent_errors
+            .iter()
+            .map(|e| format!("  {}: {}", e.at, e.message)),
+    );
+    lines
+}
+
+/// Print a day's usage totals, then one line per model.
+fn print_usage(summary: &DaySummary) {
+    let t = &summary.totals;
+    if t.requests == 0 {
+        println!("  no LLM calls recorded");
+        return;
+    }
+    println!(
+        "  Total: {} calls, {} in / {} out tokens, ${:.4}",
+        t.requests, t.input_tokens, t.output_tokens, t.cost_usd
+    );
+    for (model, m) in &summary.by_model {
+        println!(
+            "    {model}: {} calls, {} tokens, ${:.4}",
+            m.requests,
+            m.total_tokens(),
+            m.cost_usd
+        );
+    }
+}
+
+/// Ask the default model's provider whether it serves the model, so a typo
+/// or an unpulled local model shows up before the first message fails.
+async fn check_default_model(config: &Config) -> String {
+    let provider_config = default_provider_config(config);
+    let (prefix, bare) = ProviderRouter::strip_prefix(&config.agents.defaults.model);
+    let model = match prefix {
+        Some(prefix) if prefix == provider_config.name => bare,
+        _ => config.agents.defaults.model.clone(),
+    };
+    let provider = provider_for_config(provider_config, None);
+    match check_model(provider.as_ref(), &model).await {
+        Ok(()) => format!("{model}: ok ({})", provider.name()),
+        Err(ProviderError::ModelNotFound(msg)) => format!("{model}: NOT FOUND -- {msg}"),
+        Err(e) => format!("{model}: could not check ({e})"),
+    }
+}
+
 /// Print channel status: name, enabled/disabled, configured/unconfigured.
 fn print_channel_status(label: &str, enabled: bool, has_credentials: bool) {
     let status = match (enabled, has_credentials) {
@@ -173,14 +335,37 @@ fn print_channel_status(label: &str, enabled: bool, has_credentials: bool) {
     println!("{label}: {status}");
 }
 
-/// Print provider status, masking the API key.
-fn print_provider(label: &str, api_key: &str) {
+/// Print provider status, masking the API key, plus any rate limits.
+fn print_provider(label: &str, provider: &ProviderConfig) {
+    let api_key = provider.api_key.expose();
     if api_key.is_empty() {
         println!("{label}: not configured");
     } else {
         let masked = mask_key(api_key);
         println!("{label}: {masked}");
     }
+    if let Some(limits) = describe_rate_limit(&provider.rate_limit) {
+        println!("    rate limit: {limits}");
+    }
+}
+
+/// Summarize configured rate limits, or `None` when unlimited.
+fn describe_rate_limit(limits: &RateLimitConfig) -> Option<String> {
+    if limits.is_unlimited() {
+        return None;
+    }
+    let mut parts = Vec::new();
+    if let Some(rpm) = limits.requests_per_minute {
+        parts.push(format!("{rpm} req/min"));
+    }
+    if let Some(tpm) = limits.tokens_per_minute {
+        parts.push(format!("{tpm} tokens/min"));
+    }
+    if let Some(max) = limits.max_concurrent {
+        parts.push(format!("{max} concurrent"));
+    }
+    parts.push(format!("max wait {}s", limits.max_queue_wait_secs));
+    Some(parts.join(", "))
 }
 
 /// Mask an API key, showing only the first 4 and last 4 characters.
@@ -234,9 +419,74 @@ mod tests {
         print_channel_status("test", false, false);
     }
 
+    #[test]
+    fn mcp_health_lines_summarize_the_server() {
+        let health: LiveMcpHealth = serde_json::from_value(serde_json::json!({
+            "server": "docs",
+            "status": "down",
+            "connected": true,
+            "circuit": "open",
+            "in_flight": 0,
+            "calls": 12,
+            "failures": 5,
+            "timeouts": 4,
+            "rejected": 3,
+            "consecutive_failures": 5,
+            "latency_ms": {"p50": 40, "p95": 120, "p99": 300},
+            "recent_errors": [{"at": "2026-10-18T09:00:00Z", "message": "mcp call timed out after 30s"}]
+        }))
+        .unwrap();
+        let lines = describe_mcp_health(&health);
+        assert_eq!(
+            lines[0],
+            "docs: down (circuit open), 12 calls, 0 in flight, \
+             p50/p95/p99 40/120/300 ms, 5 failed (4 timed out), 3 rejected"
+        );
+        assert_eq!(
+            lines[1],
+            "  2026-10-18T09:00:00Z: mcp call timed out after 30s"
+        );
+
+        let healthy: LiveMcpHealth = serde_json::from_value(serde_json::json!({
+            "server": "wiki", "status": "up", "circuit": "closed", "in_flight": 1,
+            "calls": 0, "failures": 0, "timeouts": 0, "rejected": 0
+        }))
+        .unwrap();
+        assert_eq!(
+            describe_mcp_health(&healthy),
+            ["wiki: up, 0 calls, 1 in flight"]
+        );
+    }
+
+    #[test]
+    fn print_usage_does_not_panic() {
+        print_usage(&DaySummary::default());
+        let mut summary = DaySummary::default();
+        summary.totals.requests = 1;
+        summary.by_model.insert("gpt-4o".into(), summary.totals);
+        print_usage(&summary);
+    }
+
     #[test]
     fn print_provider_does_not_panic() {
-        print_provider("test", "");
-        print_provider("test", "sk-test-key-12345678");
+        let mut provider = ProviderConfig::default();
+        print_provider("test", &provider);
+        provider.api_key = "sk-test-key-12345678".into();
+        provider.rate_limit.max_concurrent = Some(2);
+        print_provider("test", &provider);
+    }
+
+    #[test]
+    fn describe_rate_limit_lists_set_limits() {
+        assert_eq!(describe_rate_limit(&RateLimitConfig::default()), None);
+        let limits = RateLimitConfig {
+            requests_per_minute: Some(50),
+            max_concurrent: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            describe_rate_limit(&limits).as_deref(),
+            Some("50 req/min, 4 concurrent, max wait 30s")
+        );
     }
 }
@@ -0,0 +1,115 @@
+//! `weft tasks` -- inspect the agent's workspace task list.
+//!
+//! Shows the tasks the agent keeps with the `task_list` tool, stored in
+//! `<workspace>/.clawft/tasks.json`. Subtasks are listed under their
+//! parent.
+//!
+//! # Examples
+//!
+//! ```text
+//! weft tasks list
+//! weft tasks list --open
+//! ```
+
+use std::sync::Arc;
+
+use comfy_table::{Table, presets::UTF8_FULL};
+
+use clawft_core::agent::tasks::{Task, TaskList, TaskStore};
+use clawft_platform::NativePlatform;
+use clawft_types::config::Config;
+
+use super::expand_workspace;
+
+/// Print the workspace task list; only pending and in-progress tasks
+/// when `open_only` is set.
+pub async fn tasks_list(open_only: bool, config: &Config) -> anyhow::Result<()> {
+    let workspace = expand_workspace(&config.agents.defaults.workspace);
+    let store = TaskStore::new(Arc::new(NativePlatform::new()), &workspace);
+    let tasks = store.load().await?;
+
+    let rows = ordered(&tasks, open_only);
+    if rows.is_empty() {
+        if tasks.tasks.is_empty() {
+            println!("No tasks in {}.", store.path().display());
+        } else {
+            println!("No open tasks ({} closed).", tasks.tasks.len());
+        }
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(["ID", "STATUS", "TITLE", "NOTES", "UPDATED"]);
+    for (depth, task) in rows {
+        table.add_row([
+            task.id.clone(),
+            task.status.as_str().to_string(),
+            format!("{}{}", "  ".repeat(depth), task.title),
+            task.notes.clone().unwrap_or_default(),
+            task.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+        ]);
+    }
+    println!("{table}");
+    println!("{} open, {} total", tasks.open().count(), tasks.tasks.len());
+    Ok(())
+}
+
+/// Tasks in display order with their nesting depth: each root followed by
+/// its subtasks. Tasks whose parent is hidden or missing are roots.
+fn ordered(tasks: &TaskList, open_only: bool) -> Vec<(usize, &Task)> {
+    let shown: Vec<&Task> = tasks
+        .tasks
+        .iter()
+        .filter(|t| !open_only || t.status.is_open())
+        .collect();
+    let is_shown = |id: &str| shown.iter().any(|t| t.id == id);
+
+    let mut out = Vec::with_capacity(shown.len());
+    let mut stack: Vec<(usize, &Task)> = shown
+        .iter()
+        .rev()
+        .filter(|t| t.parent.as_deref().is_none_or(|p| !is_shown(p)))
+        .map(|t| (0, *t))
+        .collect();
+    while let Some((depth, task)) = stack.pop() {
+        out.push((depth, task));
+        stack.extend(
+            shown
+                .iter()
+                .rev()
+                .filter(|t| t.parent.as_deref() == Some(task.id.as_str()))
+                .map(|t| (depth + 1, *t)),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_core::agent::tasks::TaskStatus;
+
+    #[test]
+    fn ordered_nests_subtasks_under_parents() {
+        let mut tasks = TaskList::default();
+        tasks.add("release", None, None).unwrap();
+        tasks.add("docs", None, None).unwrap();
+        tasks.add("tests", Some("1"), None).unwrap();
+        tasks.add("changelog", Some("1"), None).unwrap();
+        tasks.get_mut("1").unwrap().status = TaskStatus::Done;
+
+        let all: Vec<(usize, &str)> = ordered(&tasks, false)
+            .into_iter()
+            .map(|(d, t)| (d, t.id.as_str()))
+            .collect();
+        assert_eq!(all, [(0, "1"), (1, "3"), (1, "4"), (0, "2")]);
+
+        // With the parent hidden, its open subtasks become roots.
+        let open: Vec<(usize, &str)> = ordered(&tasks, true)
+            .into_iter()
+            .map(|(d, t)| (d, t.id.as_str()))
+            .collect();
+        assert_eq!(open, [(0, "2"), (0, "3"), (0, "4")]);
+    }
+}
@@ -4,16 +4,21 @@
 //!
 //! - `weft tools list` -- list all registered tools with source annotation.
 //! - `weft tools show <name>` -- show tool details and parameter schema.
+//! - `weft tools schema <name>` -- print a tool's parameter schema as JSON.
+//! - `weft tools run <name>` -- execute a tool directly and print its result.
 //! - `weft tools mcp` -- list configured MCP servers and tool counts.
 //! - `weft tools search <query>` -- search tools by name or description.
 //! - `weft tools deny <pattern>` -- add a glob pattern to the tool denylist.
 //! - `weft tools allow <pattern>` -- remove a pattern from the tool denylist.
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use clap::{Args, Subcommand};
 use comfy_table::{Table, presets};
 
+use clawft_core::pipeline::permissions::PermissionResolver;
 use clawft_core::tools::registry::ToolRegistry;
 use clawft_rpc::{DaemonClient, Request};
 use clawft_types::config::Config;
@@ -45,6 +50,38 @@ pub enum ToolsAction {
         config: Option<String>,
     },
 
+    /// Print a tool's parameter schema as JSON.
+    Schema {
+        /// Tool name.
+        name: String,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Execute a tool directly, as the agent would, and print its result.
+    Run {
+        /// Tool name.
+        name: String,
+
+        /// Tool arguments as a JSON object (default: `{}`).
+        #[arg(long, conflicts_with = "params_file")]
+        params: Option<String>,
+
+        /// Read the tool arguments from a JSON file.
+        #[arg(long)]
+        params_file: Option<PathBuf>,
+
+        /// Apply this agent's tool allowlist.
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
     /// List configured MCP servers and their tool counts.
     Mcp {
         /// Config file path (overrides auto-discovery).
@@ -128,7 +165,7 @@ pub async fn run(args: ToolsArgs) -> anyhow::Result<()> {
             }
             eprintln!("{DAEMON_FALLBACK_WARNING}");
             let (cfg, platform) = load_platform_config(config.as_deref()).await?;
-            let registry = build_registry(&cfg, platform).await;
+            let registry = build_registry(&cfg, platform).await?;
             tools_list(&registry)
         }
         ToolsAction::Show { name, config } => {
@@ -139,16 +176,40 @@ pub async fn run(args: ToolsArgs) -> anyhow::Result<()> {
             }
             eprintln!("{DAEMON_FALLBACK_WARNING}");
             let (cfg, platform) = load_platform_config(config.as_deref()).await?;
-            let registry = build_registry(&cfg, platform).await;
+            let registry = build_registry(&cfg, platform).await?;
             tools_show(&name, &registry)
         }
+        ToolsAction::Schema { name, config } => {
+            let (cfg, platform) = load_platform_config(config.as_deref()).await?;
+            let registry = build_registry(&cfg, platform).await?;
+            tools_schema(&name, &registry)
+        }
+        ToolsAction::Run {
+            name,
+            params,
+            params_file,
+            agent,
+            config,
+        } => {
+            let args = read_params(params.as_deref(), params_file.as_deref())?;
+            let (cfg, platform) = load_platform_config(config.as_deref()).await?;
+            let allowed = match agent.as_deref() {
+                Some(agent) => agent_allowlist(&cfg, agent)?,
+                None => Vec::new(),
+            };
+            let registry = build_registry(&cfg, platform).await?;
+            let (result, elapsed) = tools_run(&registry, &cfg, &name, args, &allowed).await?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+            eprintln!("{name} completed in {elapsed:.1?}");
+            Ok(())
+        }
         ToolsAction::Mcp { config } => {
             if let Some(result) = try_daemon_rpc("tools.mcp", serde_json::json!({})).await {
                 return print_daemon_result(&result);
             }
             eprintln!("{DAEMON_FALLBACK_WARNING}");
             let (cfg, platform) = load_platform_config(config.as_deref()).await?;
-            let registry = build_registry(&cfg, platform).await;
+            let registry = build_registry(&cfg, platform).await?;
             tools_mcp(&cfg, &registry)
         }
         ToolsAction::Search { query, config } => {
@@ -159,7 +220,7 @@ pub async fn run(args: ToolsArgs) -> anyhow::Result<()> {
             }
             eprintln!("{DAEMON_FALLBACK_WARNING}");
             let (cfg, platform) = load_platform_config(config.as_deref()).await?;
-            let registry = build_registry(&cfg, platform).await;
+            let registry = build_registry(&cfg, platform).await?;
             tools_search(&query, &registry)
         }
         ToolsAction::Deny { pattern, config } => {
@@ -198,19 +259,55 @@ async fn load_platform_config(
 async fn build_registry(
     config: &Config,
     platform: Arc<clawft_platform::NativePlatform>,
-) -> ToolRegistry {
+) -> anyhow::Result<ToolRegistry> {
+    let memory = super::open_memory_backend(config, platform.clone()).await?;
     let mut registry = ToolRegistry::new();
-    super::register_core_tools(&mut registry, config, platform).await;
-    registry
+    super::register_core_tools(&mut registry, config, platform, memory, None, None).await;
+    Ok(registry)
+}
+
+/// Parse tool arguments from `--params` or `--params-file`; `{}` when
+/// neither is given.
+fn read_params(
+    params: Option<&str>,
+    params_file: Option<&Path>,
+) -> anyhow::Result<serde_json::Value> {
+    let (text, origin) = match (params, params_file) {
+        (Some(text), _) => (text.to_string(), "--params".to_string()),
+        (None, Some(path)) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+            (text, path.display().to_string())
+        }
+        (None, None) => return Ok(serde_json::json!({})),
+    };
+    let args: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("invalid JSON in {origin}: {e}"))?;
+    if !args.is_object() {
+        anyhow::bail!("tool arguments in {origin} must be a JSON object");
+    }
+    Ok(args)
+}
+
+/// The tool allowlist of the named agent, discovered as the agent loop
+/// does. Empty means unrestricted.
+fn agent_allowlist(config: &Config, agent: &str) -> anyhow::Result<Vec<String>> {
+    clawft_core::bootstrap::discover_agents(config)
+        .and_then(|agents| agents.get(agent).map(|def| def.allowed_tools.clone()))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "agent not found: {agent}\nUse 'weft agents list' to see available agents."
+            )
+        })
 }
 
 /// Classify a tool's source from its name.
 ///
 /// - Contains `__` -> `mcp:{server}` (prefix before first `__`).
-/// - Equals `delegate_task` -> `delegation`.
+/// - Equals `delegate_task` or `delegate_remote` -> `delegation`.
 /// - Otherwise -> `builtin`.
 fn classify_source(name: &str) -> String {
-    if name == "delegate_task" {
+    if name == "delegate_task" || name == "delegate_remote" {
         return "delegation".into();
     }
     if let Some((server, _)) = name.split_once("__") {
@@ -308,6 +405,41 @@ fn tools_show(name: &str, registry: &ToolRegistry) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Print the parameter schema of a specific tool.
+fn tools_schema(name: &str, registry: &ToolRegistry) -> anyhow::Result<()> {
+    let tool = registry.get(name).ok_or_else(|| {
+        anyhow::anyhow!("tool not found: {name}\nUse 'weft tools list' to see available tools.")
+    })?;
+    println!("{}", serde_json::to_string_pretty(&tool.parameters())?);
+    Ok(())
+}
+
+/// Execute a tool with the permissions the agent loop grants the local CLI
+/// user, limited to `allowed` when it is non-empty.
+///
+/// Returns the tool's result and how long it took.
+async fn tools_run(
+    registry: &ToolRegistry,
+    config: &Config,
+    name: &str,
+    args: serde_json::Value,
+    allowed: &[String],
+) -> anyhow::Result<(serde_json::Value, Duration)> {
+    let permissions = PermissionResolver::new(&config.routing, None).resolve("local", "cli", false);
+    let started = Instant::now();
+    let result = if allowed.is_empty() {
+        registry.execute(name, args, Some(&permissions)).await
+    } else {
+        registry
+            .scoped(allowed)
+            .execute(name, args, Some(&permissions))
+            .await
+    };
+    let elapsed = started.elapsed();
+    let result = result.map_err(|e| anyhow::anyhow!("{name} failed after {elapsed:.1?}: {e}"))?;
+    Ok((result, elapsed))
+}
+
 /// List configured MCP servers with tool counts.
 fn tools_mcp(config: &Config, registry: &ToolRegistry) -> anyhow::Result<()> {
     let servers = &config.tools.mcp_servers;
@@ -532,6 +664,7 @@ mod tests {
     #[test]
     fn classify_delegation() {
         assert_eq!(classify_source("delegate_task"), "delegation");
+        assert_eq!(classify_source("delegate_remote"), "delegation");
     }
 
     // ── truncate ─────────────────────────────────────────────────────
@@ -677,4 +810,164 @@ mod tests {
         let result = tools_allow("anything", Some("/nonexistent/path/config.json"));
         assert!(result.is_err());
     }
+
+    // ── schema / run ─────────────────────────────────────────────────
+
+    /// A registry with the file tools rooted at a fresh temp workspace.
+    fn file_tools_registry(label: &str) -> (ToolRegistry, PathBuf) {
+        use clawft_tools::file_tools::{ReadFileTool, WriteFileTool};
+
+        let workspace =
+            std::env::temp_dir().join(format!("clawft_tools_run_{label}_{}", std::process::id()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let workspace = workspace.canonicalize().unwrap();
+        let platform = Arc::new(clawft_platform::NativePlatform::new());
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(ReadFileTool::new(
+            platform.clone(),
+            workspace.clone(),
+        )));
+        registry.register(Arc::new(WriteFileTool::new(platform, workspace.clone())));
+        (registry, workspace)
+    }
+
+    #[test]
+    fn read_params_defaults_to_empty_object() {
+        assert_eq!(read_params(None, None).unwrap(), serde_json::json!({}));
+    }
+
+    #[test]
+    fn read_params_from_string_and_file() {
+        let args = read_params(Some(r#"{"path": "a.txt"}"#), None).unwrap();
+        assert_eq!(args["path"], "a.txt");
+
+        let dir = std::env::temp_dir().join(format!("clawft_tools_params_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("params.json");
+        std::fs::write(&file, r#"{"path": "b.txt"}"#).unwrap();
+        let args = read_params(None, Some(&file)).unwrap();
+        assert_eq!(args["path"], "b.txt");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_params_rejects_bad_json() {
+        let err = read_params(Some("{not json"), None).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid JSON in --params"),
+            "{err}"
+        );
+        let err = read_params(Some("[1, 2]"), None).unwrap_err();
+        assert!(err.to_string().contains("must be a JSON object"), "{err}");
+    }
+
+    #[test]
+    fn schema_of_unknown_tool_fails() {
+        let (registry, workspace) = file_tools_registry("schema");
+        assert!(tools_schema("read_file", &registry).is_ok());
+        let err = tools_schema("nope", &registry).unwrap_err();
+        assert!(err.to_string().contains("tool not found: nope"));
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[tokio::test]
+    async fn run_executes_file_tools_in_workspace() {
+        let (registry, workspace) = file_tools_registry("exec");
+        let config = Config::default();
+
+        let (result, _) = tools_run(
+            &registry,
+            &config,
+            "write_file",
+            serde_json::json!({ "path": "notes.txt", "content": "hello" }),
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["bytes"], 5);
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("notes.txt")).unwrap(),
+            "hello"
+        );
+
+        let (result, _) = tools_run(
+            &registry,
+            &config,
+            "read_file",
+            serde_json::json!({ "path": "notes.txt" }),
+            &[],
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["content"], "hello");
+
+        let err = tools_run(
+            &registry,
+            &config,
+            "read_file",
+            serde_json::json!({ "path": "../outside.txt" }),
+            &[],
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().starts_with("read_file failed after"),
+            "{err}"
+        );
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[tokio::test]
+    async fn run_applies_agent_allowlist() {
+        let (registry, workspace) = file_tools_registry("allow");
+        let config = Config::default();
+        let allowed = vec!["read_*".to_string()];
+
+        let err = tools_run(
+            &registry,
+            &config,
+            "write_file",
+            serde_json::json!({ "path": "x.txt", "content": "x" }),
+            &allowed,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("permission denied"), "{err}");
+        assert!(!workspace.join("x.txt").exists());
+
+        std::fs::write(workspace.join("y.txt"), "y").unwrap();
+        let (result, _) = tools_run(
+            &registry,
+            &config,
+            "read_file",
+            serde_json::json!({ "path": "y.txt" }),
+            &allowed,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["content"], "y");
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[tokio::test]
+    async fn run_applies_configured_denylist() {
+        let (registry, workspace) = file_tools_registry("deny");
+        let mut config = Config::default();
+        config.routing.permissions.admin.tool_denylist = Some(vec!["write_file".into()]);
+
+        let err = tools_run(
+            &registry,
+            &config,
+            "write_file",
+            serde_json::json!({ "path": "x.txt", "content": "x" }),
+            &[],
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("explicitly denied"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
 }
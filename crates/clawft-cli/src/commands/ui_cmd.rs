@@ -76,7 +76,14 @@ pub async fn run(args: UiArgs) -> anyhow::Result<()> {
 
         // Delegate to the gateway with the pre-loaded (mutated) config.
         let intelligent_routing = false;
-        super::gateway::run_with_config(config, intelligent_routing, args.ui_dir).await
+        super::gateway::run_with_config(
+            config,
+            args.config,
+            intelligent_routing,
+            false,
+            args.ui_dir,
+        )
+        .await
     }
 }
 
@@ -1,7 +1,7 @@
 //! `weft workspace` -- workspace lifecycle management.
 //!
 //! Provides subcommands for creating, listing, loading, inspecting,
-//! deleting, and configuring workspaces.
+//! deleting, configuring, backing up and restoring workspaces.
 //!
 //! Per ADR-021, commands attempt RPC to the kernel daemon first and fall
 //! back to local file I/O when the daemon is unavailable.
@@ -10,6 +10,7 @@
 //!
 //! ```text
 //! weft workspace create my-project
+//! weft workspace create papers --template research --var topic=rust
 //! weft workspace list
 //! weft workspace load my-project
 //! weft workspace status
@@ -17,9 +18,12 @@
 //! weft workspace config set agents.defaults.model openai/gpt-4o
 //! weft workspace config get agents.defaults.model
 //! weft workspace config reset
+//! weft workspace backup
+//! weft workspace restore --from ~/.clawft/backups/clawft-backup-20260101T030000.000Z.tar.gz --dry-run
 //! ```
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use clap::{Args, Subcommand};
 use comfy_table::{Table, presets::UTF8_FULL};
@@ -27,6 +31,9 @@ use comfy_table::{Table, presets::UTF8_FULL};
 use clawft_core::workspace::{WorkspaceManager, WorkspaceStatus};
 use clawft_rpc::{DaemonClient, Request};
 
+#[cfg(feature = "services")]
+use super::audit_cmd::format_bytes;
+
 /// Arguments for the `weft workspace` subcommand.
 #[derive(Args)]
 pub struct WorkspaceArgs {
@@ -45,6 +52,16 @@ pub enum WorkspaceAction {
         /// Parent directory for the workspace (defaults to current directory).
         #[arg(long)]
         dir: Option<String>,
+
+        /// Template to create the workspace from: `default`, the name of a
+        /// template in ~/.clawft/workspace-templates/, or a path to a
+        /// template directory or archive.
+        #[arg(long, default_value = "default")]
+        template: String,
+
+        /// Template variable, as KEY=VALUE (repeatable).
+        #[arg(long = "var", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
     },
 
     /// List all registered workspaces.
@@ -78,6 +95,30 @@ pub enum WorkspaceAction {
         #[command(subcommand)]
         action: WorkspaceConfigAction,
     },
+
+    /// Back up memory, sessions, config and skills now.
+    #[cfg(feature = "services")]
+    Backup {
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Restore memory, sessions, config and skills from a backup.
+    #[cfg(feature = "services")]
+    Restore {
+        /// Backup archive to restore from.
+        #[arg(long = "from")]
+        from: PathBuf,
+
+        /// Show what would change without writing anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt.
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
 }
 
 /// Subcommands for `weft workspace config`.
@@ -109,7 +150,15 @@ const NO_DAEMON_WARNING: &str =
 /// Run the workspace command.
 pub async fn run(args: WorkspaceArgs) -> anyhow::Result<()> {
     match args.action {
-        WorkspaceAction::Create { name, dir } => ws_create_rpc(&name, dir.as_deref()).await,
+        WorkspaceAction::Create {
+            name,
+            dir,
+            template,
+            vars,
+        } => {
+            let vars = vars.into_iter().collect();
+            ws_create_rpc(&name, dir.as_deref(), &template, &vars).await
+        }
         WorkspaceAction::List { all } => ws_list_rpc(all).await,
         WorkspaceAction::Load { name_or_path } => ws_load_rpc(&name_or_path).await,
         WorkspaceAction::Status => ws_status_rpc().await,
@@ -119,26 +168,56 @@ pub async fn run(args: WorkspaceArgs) -> anyhow::Result<()> {
             WorkspaceConfigAction::Get { key } => ws_config_get_rpc(&key).await,
             WorkspaceConfigAction::Reset => ws_config_reset_rpc().await,
         },
+        #[cfg(feature = "services")]
+        WorkspaceAction::Backup { config } => workspace_backup(config.as_deref()).await,
+        #[cfg(feature = "services")]
+        WorkspaceAction::Restore { from, dry_run, yes } => {
+            workspace_restore(&from, dry_run, yes).await
+        }
     }
 }
 
 // ── RPC-first wrappers ─────────────────────────────────────────
 
-async fn ws_create_rpc(name: &str, dir: Option<&str>) -> anyhow::Result<()> {
+/// Parse a `KEY=VALUE` template variable.
+fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{arg}'")),
+    }
+}
+
+async fn ws_create_rpc(
+    name: &str,
+    dir: Option<&str>,
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> anyhow::Result<()> {
     if let Some(mut client) = DaemonClient::connect().await {
-        let params = serde_json::json!({ "name": name, "dir": dir });
+        let params = serde_json::json!({
+            "name": name,
+            "dir": dir,
+            "template": template,
+            "vars": vars,
+        });
         let req = Request::with_params("workspace.create", params);
         let resp = client.call(req).await?;
         let data = resp.into_result()?;
         if let Some(path) = data["path"].as_str() {
             println!("Workspace '{name}' created at {path}");
+            if let Some(notes) = data["notes"].as_str() {
+                println!();
+                println!("{notes}");
+            }
         } else {
             println!("{}", serde_json::to_string_pretty(&data)?);
         }
         return Ok(());
     }
     eprintln!("{NO_DAEMON_WARNING}");
-    workspace_create(name, dir)
+    workspace_create(name, dir, template, vars)
 }
 
 async fn ws_list_rpc(show_all: bool) -> anyhow::Result<()> {
@@ -290,8 +369,13 @@ async fn ws_config_reset_rpc() -> anyhow::Result<()> {
     workspace_config_reset()
 }
 
-/// Create a new workspace.
-fn workspace_create(name: &str, dir: Option<&str>) -> anyhow::Result<()> {
+/// Create a new workspace from a template.
+fn workspace_create(
+    name: &str,
+    dir: Option<&str>,
+    template: &str,
+    vars: &HashMap<String, String>,
+) -> anyhow::Result<()> {
     let parent = match dir {
         Some(d) => PathBuf::from(d),
         None => std::env::current_dir()?,
@@ -299,19 +383,24 @@ fn workspace_create(name: &str, dir: Option<&str>) -> anyhow::Result<()> {
 
     let mut mgr = WorkspaceManager::new()
         .map_err(|e| anyhow::anyhow!("failed to initialize workspace manager: {e}"))?;
+    let template = mgr
+        .resolve_template(template)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
 
-    let ws_path = mgr
-        .create(name, &parent)
+    let created = mgr
+        .create_from_template(name, &parent, &template, vars)
         .map_err(|e| anyhow::anyhow!("failed to create workspace: {e}"))?;
 
-    println!("Workspace '{}' created at {}", name, ws_path.display());
-    println!();
-    println!("Directories created:");
-    for subdir in &["sessions", "memory", "skills", "agents", "hooks"] {
-        println!("  .clawft/{subdir}/");
+    println!(
+        "Workspace '{}' created at {} (template: {})",
+        name,
+        created.path.display(),
+        template.name()
+    );
+    if let Some(notes) = created.notes {
+        println!();
+        println!("{notes}");
     }
-    println!("  .clawft/config.json");
-    println!("  CLAWFT.md");
 
     Ok(())
 }
@@ -496,6 +585,159 @@ fn workspace_config_reset() -> anyhow::Result<()> {
     Ok(())
 }
 
+// ── Backups ────────────────────────────────────────────────────
+
+/// The data directory backups are taken of (`~/.clawft`).
+#[cfg(feature = "services")]
+fn clawft_home() -> anyhow::Result<PathBuf> {
+    dirs::home_dir()
+        .map(|home| home.join(".clawft"))
+        .ok_or_else(|| anyhow::anyhow!("could not determine home directory"))
+}
+
+/// The backup service `config.backup` describes.
+///
+/// S3 credentials are resolved from the config or their `*_env`
+/// variables; when they are missing, or the binary was built without the
+/// `backup-s3` feature, backups are kept locally only.
+#[cfg(feature = "services")]
+pub(crate) fn backup_service<P: clawft_platform::Platform>(
+    config: &clawft_types::config::Config,
+    platform: &P,
+) -> anyhow::Result<clawft_services::backup::BackupService> {
+    let service = clawft_services::backup::BackupService::new(&config.backup, clawft_home()?);
+    let Some(s3) = &config.backup.s3 else {
+        return Ok(service);
+    };
+
+    #[cfg(feature = "backup-s3")]
+    {
+        use clawft_services::backup::s3::S3Uploader;
+
+        let access_key_id = if s3.access_key_id.is_empty() {
+            s3.access_key_id_env
+                .as_deref()
+                .and_then(|name| platform.env().get_var(name))
+                .unwrap_or_default()
+        } else {
+            s3.access_key_id.clone()
+        };
+        let secret = platform
+            .env()
+            .resolve_secret(&s3.secret_access_key, s3.secret_access_key_env.as_deref());
+        match secret {
+            Some(secret) if !access_key_id.is_empty() => {
+                Ok(service.with_upload(S3Uploader::new(s3, access_key_id, secret)))
+            }
+            _ => {
+                tracing::warn!(
+                    bucket = %s3.bucket,
+                    "backup.s3 has no credentials; keeping backups locally only"
+                );
+                Ok(service)
+            }
+        }
+    }
+    #[cfg(not(feature = "backup-s3"))]
+    {
+        let _ = platform;
+        tracing::warn!(
+            bucket = %s3.bucket,
+            "backup.s3 is set but weft was built without the backup-s3 feature; \
+             keeping backups locally only"
+        );
+        Ok(service)
+    }
+}
+
+/// Take a backup now.
+#[cfg(feature = "services")]
+async fn workspace_backup(config_path: Option<&str>) -> anyhow::Result<()> {
+    let platform = clawft_platform::NativePlatform::new();
+    let config = super::load_config(&platform, config_path).await?;
+    let service = backup_service(&config, &platform)?;
+    let path = service.backup().await?;
+    println!("Backup written to {}", path.display());
+    Ok(())
+}
+
+/// Restore from `archive`, or with `dry_run` only show what would change.
+#[cfg(feature = "services")]
+async fn workspace_restore(
+    archive: &Path,
+    dry_run: bool,
+    skip_confirm: bool,
+) -> anyhow::Result<()> {
+    use clawft_services::backup::{RestoreChange, RestorePlan};
+
+    let home = clawft_home()?;
+    let plan = RestorePlan::prepare(archive, &home)?;
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    table.set_header(vec!["CHANGE", "PATH", "SIZE"]);
+    for file in &plan.files {
+        let (change, size) = match file.change {
+            RestoreChange::Create => ("create", format_bytes(file.size)),
+            RestoreChange::Overwrite => (
+                "overwrite",
+                format!(
+                    "{} -> {}",
+                    format_bytes(file.current_size.unwrap_or(0)),
+                    format_bytes(file.size)
+                ),
+            ),
+            RestoreChange::Unchanged => ("unchanged", format_bytes(file.size)),
+        };
+        table.add_row(vec![change, &file.path, &size]);
+    }
+    println!("{table}");
+    for path in &plan.skipped {
+        println!("Skipping {path} (not part of a backup)");
+    }
+
+    let changes = plan
+        .files
+        .iter()
+        .filter(|f| f.change != RestoreChange::Unchanged)
+        .count();
+    let overwrites = plan.overwrites().count();
+    if dry_run {
+        println!(
+            "Dry run: {changes} file(s) would be restored, {overwrites} overwritten, into {}.",
+            home.display()
+        );
+        return Ok(());
+    }
+    if changes == 0 {
+        println!(
+            "Nothing to restore; {} already matches the backup.",
+            home.display()
+        );
+        return Ok(());
+    }
+
+    if !skip_confirm {
+        eprint!(
+            "Restore {changes} file(s) into {} ({overwrites} overwritten)? [y/N] ",
+            home.display()
+        );
+        use std::io::Write;
+        std::io::stderr().flush().ok();
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let written = plan.apply()?;
+    println!("Restored {written} file(s) into {}.", home.display());
+    Ok(())
+}
+
 /// Set a value at a dot-separated path in a JSON object.
 ///
 /// Creates intermediate objects as needed. The `value` is stored as a
@@ -2,6 +2,42 @@
 //!
 //! Generates completions for bash, zsh, fish, and PowerShell.
 //! Requires the `completions` feature to be enabled.
+//!
+//! The bash, zsh and fish scripts also complete session ids, agent and
+//! skill names, cron job ids and channel names, by calling the hidden
+//! `weft __complete <kind>` (see [`crate::commands::complete_cmd`]).
+
+use clap::ValueEnum;
+
+use crate::commands::complete_cmd::CompleteKind;
+
+/// Positional arguments completed with `__complete`: command,
+/// subcommand, and the kind of value the argument takes.
+const DYNAMIC_ARGS: &[(&str, &str, CompleteKind)] = &[
+    ("sessions", "inspect", CompleteKind::Sessions),
+    ("sessions", "export", CompleteKind::Sessions),
+    ("sessions", "delete", CompleteKind::Sessions),
+    ("sessions", "restore", CompleteKind::Sessions),
+    ("sessions", "fork", CompleteKind::Sessions),
+    ("agents", "show", CompleteKind::Agents),
+    ("agents", "use", CompleteKind::Agents),
+    ("cron", "remove", CompleteKind::CronJobs),
+    ("cron", "enable", CompleteKind::CronJobs),
+    ("cron", "disable", CompleteKind::CronJobs),
+    ("cron", "run", CompleteKind::CronJobs),
+    ("cron", "history", CompleteKind::CronJobs),
+    ("skills", "show", CompleteKind::Skills),
+    ("skills", "remove", CompleteKind::Skills),
+];
+
+/// Options whose values are completed with `__complete`, wherever they
+/// appear.
+const DYNAMIC_OPTIONS: &[(&str, CompleteKind)] = &[
+    ("session", CompleteKind::Sessions),
+    ("agent", CompleteKind::Agents),
+    ("channel", CompleteKind::Channels),
+    ("alert-channel", CompleteKind::Channels),
+];
 
 /// Shell types supported for completion generation.
 #[derive(Clone, Debug)]
@@ -42,23 +78,82 @@ pub fn generate_completions(shell: &Shell, cmd: &mut clap::Command) {
     let bin_name = cmd.get_name().to_string();
     let subcommands: Vec<String> = cmd
         .get_subcommands()
+        .filter(|s| !s.is_hide_set())
         .map(|s| s.get_name().to_string())
         .collect();
 
+    let dynamic = dynamic_args(cmd);
+
     match shell {
         Shell::Bash => {
             println!("# {bin_name} bash completion");
             println!("_{bin_name}() {{");
-            println!("    local commands=\"{}\"", subcommands.join(" "));
+            // Split on whitespace only: session ids contain `:`, which is
+            // in COMP_WORDBREAKS.
+            println!("    local line=\"${{COMP_LINE:0:COMP_POINT}}\"");
+            println!("    local -a words");
+            println!("    read -ra words <<< \"$line\"");
+            println!("    [[ \"$line\" == *\" \" ]] && words+=(\"\")");
+            println!("    local cword=$((${{#words[@]}} - 1))");
+            println!("    local cur=\"${{words[cword]}}\"");
+            println!("    local kind=\"\"");
+            println!("    case \"${{words[cword-1]}}\" in");
+            for (kind, options) in group_options() {
+                let patterns: Vec<String> = options.iter().map(|o| format!("--{o}")).collect();
+                println!("        {}) kind={kind} ;;", patterns.join("|"));
+            }
+            println!("    esac");
+            if !dynamic.is_empty() {
+                println!("    if [[ -z \"$kind\" && $cword -eq 3 ]]; then");
+                println!("        case \"${{words[1]}} ${{words[2]}}\" in");
+                for (kind, paths) in group_args(&dynamic) {
+                    let patterns: Vec<String> = paths.iter().map(|p| format!("\"{p}\"")).collect();
+                    println!("            {}) kind={kind} ;;", patterns.join("|"));
+                }
+                println!("        esac");
+                println!("    fi");
+            }
+            println!("    if [[ -n \"$kind\" ]]; then");
             println!(
-                "    COMPREPLY=($(compgen -W \"$commands\" -- \"${{COMP_WORDS[COMP_CWORD]}}\"))"
+                "        COMPREPLY=($(compgen -W \"$({bin_name} __complete \"$kind\" 2>/dev/null)\" -- \"$cur\"))"
             );
+            println!("        if [[ \"$cur\" == *:* && \"$COMP_WORDBREAKS\" == *:* ]]; then");
+            println!("            local colon_prefix=\"${{cur%\"${{cur##*:}}\"}}\"");
+            println!("            COMPREPLY=(\"${{COMPREPLY[@]#\"$colon_prefix\"}}\")");
+            println!("        fi");
+            println!("        return");
+            println!("    fi");
+            println!("    local commands=\"{}\"", subcommands.join(" "));
+            println!("    COMPREPLY=($(compgen -W \"$commands\" -- \"$cur\"))");
             println!("}}");
             println!("complete -F _{bin_name} {bin_name}");
         }
         Shell::Zsh => {
             println!("#compdef {bin_name}");
             println!("_{}() {{", bin_name);
+            println!("    local kind");
+            println!("    case \"${{words[CURRENT-1]}}\" in");
+            for (kind, options) in group_options() {
+                let patterns: Vec<String> = options.iter().map(|o| format!("--{o}")).collect();
+                println!("        {}) kind={kind} ;;", patterns.join("|"));
+            }
+            println!("    esac");
+            if !dynamic.is_empty() {
+                println!("    if [[ -z $kind && $CURRENT -eq 4 ]]; then");
+                println!("        case \"${{words[2]}} ${{words[3]}}\" in");
+                for (kind, paths) in group_args(&dynamic) {
+                    let patterns: Vec<String> = paths.iter().map(|p| format!("\"{p}\"")).collect();
+                    println!("            {}) kind={kind} ;;", patterns.join("|"));
+                }
+                println!("        esac");
+                println!("    fi");
+            }
+            println!("    if [[ -n $kind ]]; then");
+            println!("        local -a candidates");
+            println!("        candidates=(${{(f)\"$({bin_name} __complete $kind 2>/dev/null)\"}})");
+            println!("        compadd -a candidates");
+            println!("        return");
+            println!("    fi");
             println!(
                 "    local commands=({})",
                 subcommands
@@ -74,6 +169,18 @@ pub fn generate_completions(shell: &Shell, cmd: &mut clap::Command) {
             for cmd_name in &subcommands {
                 println!("complete -c {bin_name} -n '__fish_use_subcommand' -a '{cmd_name}'");
             }
+            for (command, kind, subs) in group_args_by_command(&dynamic) {
+                println!(
+                    "complete -c {bin_name} -n '__fish_seen_subcommand_from {command}; and __fish_seen_subcommand_from {}' -f -a '({bin_name} __complete {kind} 2>/dev/null)'",
+                    subs.join(" ")
+                );
+            }
+            for (option, kind) in DYNAMIC_OPTIONS {
+                println!(
+                    "complete -c {bin_name} -l {option} -x -a '({bin_name} __complete {} 2>/dev/null)'",
+                    kind_name(*kind)
+                );
+            }
         }
         Shell::PowerShell => {
             println!("# {bin_name} PowerShell completion");
@@ -92,6 +199,70 @@ pub fn generate_completions(shell: &Shell, cmd: &mut clap::Command) {
     }
 }
 
+/// The entries of [`DYNAMIC_ARGS`] whose subcommand `cmd` has.
+fn dynamic_args(cmd: &clap::Command) -> Vec<(&'static str, &'static str, CompleteKind)> {
+    DYNAMIC_ARGS
+        .iter()
+        .copied()
+        .filter(|(command, sub, _)| {
+            cmd.find_subcommand(command)
+                .is_some_and(|c| c.find_subcommand(sub).is_some())
+        })
+        .collect()
+}
+
+/// `weft __complete` name of `kind`, e.g. `cron-jobs`.
+fn kind_name(kind: CompleteKind) -> String {
+    kind.to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// `(kind, ["command sub", ...])`, in first-seen order.
+fn group_args(dynamic: &[(&str, &str, CompleteKind)]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for (command, sub, kind) in dynamic {
+        let kind = kind_name(*kind);
+        let path = format!("{command} {sub}");
+        match groups.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, paths)) => paths.push(path),
+            None => groups.push((kind, vec![path])),
+        }
+    }
+    groups
+}
+
+/// `(command, kind, [sub, ...])`, in first-seen order.
+fn group_args_by_command<'a>(
+    dynamic: &[(&'a str, &'a str, CompleteKind)],
+) -> Vec<(&'a str, String, Vec<&'a str>)> {
+    let mut groups: Vec<(&str, String, Vec<&str>)> = Vec::new();
+    for (command, sub, kind) in dynamic {
+        let kind = kind_name(*kind);
+        match groups
+            .iter_mut()
+            .find(|(c, k, _)| c == command && *k == kind)
+        {
+            Some((_, _, subs)) => subs.push(sub),
+            None => groups.push((command, kind, vec![sub])),
+        }
+    }
+    groups
+}
+
+/// `(kind, [option, ...])` from [`DYNAMIC_OPTIONS`], in first-seen order.
+fn group_options() -> Vec<(String, Vec<&'static str>)> {
+    let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+    for (option, kind) in DYNAMIC_OPTIONS {
+        let kind = kind_name(*kind);
+        match groups.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, options)) => options.push(option),
+            None => groups.push((kind, vec![option])),
+        }
+    }
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +307,47 @@ mod tests {
         generate_completions(&Shell::Fish, &mut cmd);
     }
 
+    #[test]
+    fn dynamic_args_need_the_subcommand() {
+        let cmd = clap::Command::new("weft")
+            .subcommand(
+                clap::Command::new("sessions")
+                    .subcommand(clap::Command::new("inspect"))
+                    .subcommand(clap::Command::new("list")),
+            )
+            .subcommand(clap::Command::new("cron").subcommand(clap::Command::new("run")));
+        let dynamic = dynamic_args(&cmd);
+        assert_eq!(
+            dynamic,
+            [
+                ("sessions", "inspect", CompleteKind::Sessions),
+                ("cron", "run", CompleteKind::CronJobs),
+            ]
+        );
+        assert_eq!(
+            group_args_by_command(&dynamic),
+            [
+                ("sessions", "sessions".to_string(), vec!["inspect"]),
+                ("cron", "cron-jobs".to_string(), vec!["run"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn dynamic_targets_exist_in_weft() {
+        use clap::CommandFactory;
+
+        let cmd = crate::Cli::command();
+        assert_eq!(dynamic_args(&cmd).len(), DYNAMIC_ARGS.len());
+    }
+
+    #[test]
+    fn options_are_grouped_by_kind() {
+        let groups = group_options();
+        assert!(groups.contains(&("channels".to_string(), vec!["channel", "alert-channel"])));
+        assert!(groups.contains(&("sessions".to_string(), vec!["session"])));
+    }
+
     #[test]
     fn generate_completions_powershell() {
         let mut cmd = clap::Command::new("test").subcommand(clap::Command::new("sub1"));
@@ -43,7 +43,7 @@ pub fn topic_help(topic: &str) -> String {
              \n\
              weft skills list             List all skills (workspace, user, builtin)\n\
              weft skills show <name>      Show skill details\n\
-             weft skills install <path>   Install a skill from a local path\n\
+             weft skills install <path>   Install a skill from a directory or .zip/.tar.gz\n\
              \n\
              Interactive commands:\n\
              \n\
@@ -76,6 +76,8 @@ pub fn topic_help(topic: &str) -> String {
              \n\
              weft tools list              List all registered tools with source\n\
              weft tools show <name>       Show tool details and parameter schema\n\
+             weft tools schema <name>     Print a tool's parameter schema as JSON\n\
+             weft tools run <name>        Run a tool directly (--params, --agent)\n\
              weft tools mcp               List MCP servers and tool counts\n\
              weft tools search <query>    Search tools by name or description\n\
              weft tools deny <pattern>    Add a glob pattern to the tool denylist\n\
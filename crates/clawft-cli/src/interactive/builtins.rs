@@ -6,13 +6,24 @@
 //! - `/skills` -- list available skills
 //! - `/use <skill>` -- activate a skill
 //! - `/agent <name>` -- switch agent
-//! - `/clear` -- clear context
+//! - `/model [name]` -- show or switch the model for this session
+//! - `/tools` -- list available tools
+//! - `/usage` -- show token usage and cost
+//! - `/clear` -- clear the conversation history
+//! - `/save <session>` -- copy the conversation to another session
+//! - `/load <session>` -- continue a stored session
+//! - `/cancel` -- cancel the running turn
 //! - `/status` -- show current agent, model, skills
 //! - `/quit` -- exit
 
 use tracing::warn;
 
-use super::registry::{InteractiveContext, SlashCommand, SlashCommandRegistry};
+use super::registry::{InteractiveContext, SessionAction, SlashCommand, SlashCommandRegistry};
+
+/// Slash commands the agent loop handles itself (`/fork`, `/plan`). The
+/// REPL sends them on as messages even when unknown commands are not
+/// forwarded.
+pub const AGENT_COMMANDS: &[&str] = &["fork", "plan"];
 
 /// Register all built-in slash commands into the given registry.
 pub fn register_builtins(registry: &mut SlashCommandRegistry) {
@@ -24,6 +35,11 @@ pub fn register_builtins(registry: &mut SlashCommandRegistry) {
     registry.register(Box::new(StatusCommand));
     registry.register(Box::new(QuitCommand));
     registry.register(Box::new(ToolsCommand));
+    registry.register(Box::new(ModelCommand));
+    registry.register(Box::new(UsageCommand));
+    registry.register(Box::new(SaveCommand));
+    registry.register(Box::new(LoadCommand));
+    registry.register(Box::new(CancelCommand));
 }
 
 /// Register skill-contributed commands into the registry.
@@ -127,6 +143,13 @@ impl SlashCommand for HelpCommand {
                  Use /tools to list all registered tools."
                     .into(),
             ),
+            "sessions" => Ok(
+                "The conversation is stored as a session and resumed on the next run.\n\
+                 Use /clear to forget it, /save <name> to keep a copy, and\n\
+                 /load <name> to continue another one (see `weft sessions list`).\n\
+                 Use /fork [n] to branch the conversation at message n."
+                    .into(),
+            ),
             _ => Ok(format!("No help available for topic: {topic}")),
         }
     }
@@ -139,11 +162,18 @@ fn format_general_help() -> String {
     output.push_str("  /skills           -- List available skills\n");
     output.push_str("  /use <skill>      -- Activate a skill\n");
     output.push_str("  /agent <name>     -- Switch agent\n");
+    output.push_str("  /model [name]     -- Show or switch the model\n");
     output.push_str("  /tools            -- List available tools\n");
-    output.push_str("  /clear            -- Clear context\n");
+    output.push_str("  /usage            -- Show token usage and cost\n");
+    output.push_str("  /clear            -- Clear the conversation history\n");
+    output.push_str("  /save <session>   -- Copy the conversation to another session\n");
+    output.push_str("  /load <session>   -- Continue a stored session\n");
+    output.push_str("  /fork [n]         -- Branch the conversation at message n\n");
+    output.push_str("  /plan <task>      -- Plan a task before running it\n");
+    output.push_str("  /cancel           -- Cancel the running turn (or press Ctrl-C)\n");
     output.push_str("  /status           -- Show current agent, model, skills\n");
     output.push_str("  /quit             -- Exit the session\n");
-    output.push_str("\nTopics: skills, agents, tools");
+    output.push_str("\nTopics: skills, agents, tools, sessions");
     output
 }
 
@@ -284,9 +314,98 @@ impl SlashCommand for ToolsCommand {
     }
 }
 
+// ── /model ────────────────────────────────────────────────────────────────
+
+/// `/model [name]` -- show the model or switch it for the rest of the
+/// session (`/model default` switches back).
+struct ModelCommand;
+
+impl SlashCommand for ModelCommand {
+    fn name(&self) -> &str {
+        "model"
+    }
+
+    fn description(&self) -> &str {
+        "Show or switch the model"
+    }
+
+    fn execute(&self, args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
+        let model = args.trim();
+
+        if model.is_empty() {
+            let mut output = format!("Model: {}\n", ctx.model);
+            if !ctx.model_ids.is_empty() {
+                output.push_str("Available models:\n");
+                for id in &ctx.model_ids {
+                    let marker = if id == &ctx.default_model {
+                        " (default)"
+                    } else {
+                        ""
+                    };
+                    output.push_str(&format!("  - {id}{marker}\n"));
+                }
+            }
+            return Ok(output);
+        }
+
+        if model == "default" {
+            ctx.model = ctx.default_model.clone();
+            return Ok(format!("Switched to default model: {}", ctx.model));
+        }
+
+        if !model.contains('/') && !ctx.model_ids.iter().any(|id| id == model) {
+            return Ok(format!(
+                "Unknown model: {model}\n\
+                 Use provider/model (e.g. openai/gpt-4o), or /model to list models."
+            ));
+        }
+
+        ctx.model = model.to_string();
+        Ok(format!("Switched to model: {model}"))
+    }
+}
+
+// ── /usage ────────────────────────────────────────────────────────────────
+
+/// `/usage` -- show token usage and cost for the session and the process.
+struct UsageCommand;
+
+impl SlashCommand for UsageCommand {
+    fn name(&self) -> &str {
+        "usage"
+    }
+
+    fn description(&self) -> &str {
+        "Show token usage and cost"
+    }
+
+    fn execute(&self, _args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
+        let Some(usage) = &ctx.usage else {
+            return Ok("Usage tracking is not available.".into());
+        };
+
+        let line = |totals: clawft_llm::usage::UsageTotals| {
+            format!(
+                "{} requests, {} tokens ({} in / {} out), ${:.4}",
+                totals.requests,
+                totals.total_tokens(),
+                totals.input_tokens,
+                totals.output_tokens,
+                totals.cost_usd,
+            )
+        };
+        Ok(format!(
+            "Session: {}\n\
+             Total:   {}",
+            line(usage.session_totals(ctx.session_key())),
+            line(usage.totals()),
+        ))
+    }
+}
+
 // ── /clear ────────────────────────────────────────────────────────────────
 
-/// `/clear` -- clear session context.
+/// `/clear` -- clear the conversation history and the active skill.
 struct ClearCommand;
 
 impl SlashCommand for ClearCommand {
@@ -295,15 +414,90 @@ impl SlashCommand for ClearCommand {
     }
 
     fn description(&self) -> &str {
-        "Clear context"
+        "Clear the conversation history"
     }
 
     fn execute(&self, _args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
         ctx.active_skill.clear();
+        ctx.action = Some(SessionAction::Clear);
         Ok("[session cleared]".into())
     }
 }
 
+// ── /save ─────────────────────────────────────────────────────────────────
+
+/// `/save <session>` -- copy the conversation under another session key.
+struct SaveCommand;
+
+impl SlashCommand for SaveCommand {
+    fn name(&self) -> &str {
+        "save"
+    }
+
+    fn description(&self) -> &str {
+        "Copy the conversation to another session"
+    }
+
+    fn execute(&self, args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
+        let key = args.trim();
+        if key.is_empty() {
+            return Ok("Usage: /save <session>".into());
+        }
+        if key == ctx.session_key() {
+            return Ok(format!("Already in session: {key}"));
+        }
+        ctx.action = Some(SessionAction::Save(key.to_string()));
+        Ok(format!("[session saved as {key}]"))
+    }
+}
+
+// ── /load ─────────────────────────────────────────────────────────────────
+
+/// `/load <session>` -- continue a stored session.
+struct LoadCommand;
+
+impl SlashCommand for LoadCommand {
+    fn name(&self) -> &str {
+        "load"
+    }
+
+    fn description(&self) -> &str {
+        "Continue a stored session"
+    }
+
+    fn execute(&self, args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
+        let key = args.trim();
+        if key.is_empty() {
+            return Ok(format!(
+                "Current session: {}\nUsage: /load <session>",
+                ctx.session_key()
+            ));
+        }
+        ctx.action = Some(SessionAction::Load(key.to_string()));
+        Ok(format!("[session] continuing in {key}"))
+    }
+}
+
+// ── /cancel ───────────────────────────────────────────────────────────────
+
+/// `/cancel` -- cancel the running turn.
+struct CancelCommand;
+
+impl SlashCommand for CancelCommand {
+    fn name(&self) -> &str {
+        "cancel"
+    }
+
+    fn description(&self) -> &str {
+        "Cancel the running turn"
+    }
+
+    fn execute(&self, _args: &str, ctx: &mut InteractiveContext) -> anyhow::Result<String> {
+        ctx.action = Some(SessionAction::Cancel);
+        Ok("[cancelling...]".into())
+    }
+}
+
 // ── /status ───────────────────────────────────────────────────────────────
 
 /// `/status` -- show current agent, model, and active skills.
@@ -380,6 +574,7 @@ mod tests {
         ctx.tool_names = vec!["read_file".into(), "write_file".into()];
         ctx.skill_names = vec!["research".into(), "coding".into()];
         ctx.agent_names = vec!["researcher".into(), "coder".into()];
+        ctx.model_ids = vec!["test-model/v1".into(), "openai/gpt-4o-mini".into()];
         ctx
     }
 
@@ -561,6 +756,115 @@ mod tests {
         let output = cmd.execute("", &mut ctx).unwrap();
         assert!(output.contains("cleared"));
         assert!(ctx.active_skill.is_empty());
+        assert_eq!(ctx.action, Some(SessionAction::Clear));
+    }
+
+    // ── /model tests ───────────────────────────────────────────────────
+
+    #[test]
+    fn model_lists_known_models() {
+        let cmd = ModelCommand;
+        let mut ctx = test_ctx();
+        let output = cmd.execute("", &mut ctx).unwrap();
+        assert!(output.contains("Model: test-model/v1"));
+        assert!(output.contains("test-model/v1 (default)"));
+        assert!(output.contains("openai/gpt-4o-mini"));
+    }
+
+    #[test]
+    fn model_switches_and_resets() {
+        let cmd = ModelCommand;
+        let mut ctx = test_ctx();
+        let output = cmd.execute("openai/gpt-4o-mini", &mut ctx).unwrap();
+        assert!(output.contains("Switched to model: openai/gpt-4o-mini"));
+        assert_eq!(ctx.pinned_model(), Some("openai/gpt-4o-mini"));
+
+        cmd.execute("anthropic/claude-sonnet-4", &mut ctx).unwrap();
+        assert_eq!(ctx.model, "anthropic/claude-sonnet-4");
+
+        cmd.execute("default", &mut ctx).unwrap();
+        assert_eq!(ctx.model, "test-model/v1");
+        assert_eq!(ctx.pinned_model(), None);
+    }
+
+    #[test]
+    fn model_rejects_unknown_bare_name() {
+        let cmd = ModelCommand;
+        let mut ctx = test_ctx();
+        let output = cmd.execute("gpt5", &mut ctx).unwrap();
+        assert!(output.contains("Unknown model"));
+        assert_eq!(ctx.model, "test-model/v1");
+    }
+
+    // ── /usage tests ───────────────────────────────────────────────────
+
+    #[test]
+    fn usage_shows_session_and_total() {
+        use clawft_llm::usage::{PriceTable, UsageTracker};
+
+        let cmd = UsageCommand;
+        let mut ctx = test_ctx();
+        assert!(cmd.execute("", &mut ctx).unwrap().contains("not available"));
+
+        let tracker = UsageTracker::new(PriceTable::default());
+        let usage = clawft_llm::types::Usage {
+            input_tokens: 100,
+            output_tokens: 20,
+            ..Default::default()
+        };
+        tracker.record("local", "m", ctx.session_key(), &usage);
+        tracker.record("local", "m", "telegram:1", &usage);
+        ctx.usage = Some(std::sync::Arc::new(tracker));
+
+        let output = cmd.execute("", &mut ctx).unwrap();
+        assert!(
+            output.contains("Session: 1 requests, 120 tokens (100 in / 20 out)"),
+            "{output}"
+        );
+        assert!(
+            output.contains("Total:   2 requests, 240 tokens"),
+            "{output}"
+        );
+    }
+
+    // ── /save, /load and /cancel tests ─────────────────────────────────
+
+    #[test]
+    fn save_requests_a_copy() {
+        let cmd = SaveCommand;
+        let mut ctx = test_ctx();
+        assert!(cmd.execute("", &mut ctx).unwrap().contains("Usage"));
+        assert!(ctx.action.is_none());
+
+        let key = ctx.session_key().to_string();
+        let output = cmd.execute(&key, &mut ctx).unwrap();
+        assert!(output.contains("Already in session"));
+        assert!(ctx.action.is_none());
+
+        cmd.execute("cli:notes", &mut ctx).unwrap();
+        assert_eq!(ctx.action, Some(SessionAction::Save("cli:notes".into())));
+    }
+
+    #[test]
+    fn load_requests_a_switch() {
+        let cmd = LoadCommand;
+        let mut ctx = test_ctx();
+        let output = cmd.execute("", &mut ctx).unwrap();
+        assert!(output.contains("Current session: cli:cli-session"));
+        assert!(ctx.action.is_none());
+
+        cmd.execute("cli:notes", &mut ctx).unwrap();
+        assert_eq!(ctx.action, Some(SessionAction::Load("cli:notes".into())));
+        // The switch happens only once the session is found.
+        assert_eq!(ctx.session, None);
+    }
+
+    #[test]
+    fn cancel_requests_cancellation() {
+        let cmd = CancelCommand;
+        let mut ctx = test_ctx();
+        cmd.execute("", &mut ctx).unwrap();
+        assert_eq!(ctx.action, Some(SessionAction::Cancel));
     }
 
     // ── /status tests ──────────────────────────────────────────────────
@@ -613,7 +917,15 @@ mod tests {
         assert!(reg.has("clear"));
         assert!(reg.has("status"));
         assert!(reg.has("quit"));
-        assert_eq!(reg.len(), 8);
+        assert!(reg.has("model"));
+        assert!(reg.has("usage"));
+        assert!(reg.has("save"));
+        assert!(reg.has("load"));
+        assert!(reg.has("cancel"));
+        assert_eq!(reg.len(), 13);
+        for name in AGENT_COMMANDS {
+            assert!(!reg.has(name), "/{name} belongs to the agent loop");
+        }
     }
 
     #[test]
@@ -0,0 +1,183 @@
+//! Tab completion for the `weft agent` REPL.
+//!
+//! Completes command names after `/`, and the argument of the commands
+//! that take a name: models for `/model`, skills for `/use`, agents for
+//! `/agent` and help topics for `/help`.
+
+use rustyline::Helper;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+
+use super::registry::parse_slash;
+
+/// Help topics offered after `/help`.
+const HELP_TOPICS: &[&str] = &["skills", "agents", "tools", "sessions"];
+
+/// The names the REPL can complete.
+#[derive(Debug, Clone, Default)]
+pub struct Completions {
+    /// Command names without the `/`.
+    pub commands: Vec<String>,
+    /// Model identifiers.
+    pub models: Vec<String>,
+    /// Skill names.
+    pub skills: Vec<String>,
+    /// Agent names.
+    pub agents: Vec<String>,
+}
+
+impl Completions {
+    /// Candidates for the word under the cursor in `line[..pos]`.
+    ///
+    /// Returns the byte offset the candidates replace from and the sorted
+    /// candidates; no candidates outside slash commands.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let Some(line) = line.get(..pos) else {
+            return (pos, Vec::new());
+        };
+        let Some(word) = line.strip_prefix('/') else {
+            return (pos, Vec::new());
+        };
+
+        // Still typing the command name.
+        if !word.contains(char::is_whitespace) {
+            return (0, matching(self.commands.iter(), word, "/"));
+        }
+
+        let Some((name, arg)) = parse_slash(line) else {
+            return (pos, Vec::new());
+        };
+        // Only the first argument is completed.
+        if arg.contains(char::is_whitespace)
+            || (!arg.is_empty() && line.ends_with(char::is_whitespace))
+        {
+            return (pos, Vec::new());
+        }
+        let start = pos - arg.len();
+        let candidates = match name {
+            "model" => matching(
+                self.models.iter().map(String::as_str).chain(["default"]),
+                arg,
+                "",
+            ),
+            "use" => matching(self.skills.iter(), arg, ""),
+            "agent" => matching(self.agents.iter(), arg, ""),
+            "help" => matching(HELP_TOPICS.iter(), arg, ""),
+            _ => Vec::new(),
+        };
+        (start, candidates)
+    }
+}
+
+/// The `names` starting with `prefix`, sorted and deduplicated, each
+/// preceded by `lead`.
+fn matching<I, S>(names: I, prefix: &str, lead: &str) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut out: Vec<String> = names
+        .into_iter()
+        .filter(|n| n.as_ref().starts_with(prefix))
+        .map(|n| format!("{lead}{}", n.as_ref()))
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+impl Completer for Completions {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for Completions {
+    type Hint = String;
+}
+
+impl Highlighter for Completions {}
+
+impl Validator for Completions {}
+
+impl Helper for Completions {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completions() -> Completions {
+        Completions {
+            commands: vec![
+                "help".into(),
+                "model".into(),
+                "save".into(),
+                "skills".into(),
+                "use".into(),
+            ],
+            models: vec![
+                "openai/gpt-4o".into(),
+                "openai/gpt-4o-mini".into(),
+                "anthropic/claude".into(),
+            ],
+            skills: vec!["research".into(), "review".into()],
+            agents: vec!["coder".into()],
+        }
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let c = completions();
+        assert_eq!(
+            c.candidates("/s", 2),
+            (0, vec!["/save".into(), "/skills".into()])
+        );
+        assert_eq!(c.candidates("/", 1).1.len(), 5);
+        assert_eq!(c.candidates("/x", 2), (0, vec![]));
+    }
+
+    #[test]
+    fn completes_model_ids() {
+        let c = completions();
+        let line = "/model openai/gpt";
+        assert_eq!(
+            c.candidates(line, line.len()),
+            (7, vec!["openai/gpt-4o".into(), "openai/gpt-4o-mini".into()])
+        );
+        let (start, all) = c.candidates("/model ", 7);
+        assert_eq!(start, 7);
+        assert_eq!(all.len(), 4);
+        assert!(all.contains(&"default".to_string()));
+    }
+
+    #[test]
+    fn completes_skills_agents_and_topics() {
+        let c = completions();
+        assert_eq!(
+            c.candidates("/use re", 7),
+            (5, vec!["research".into(), "review".into()])
+        );
+        assert_eq!(c.candidates("/agent c", 8), (7, vec!["coder".into()]));
+        assert_eq!(c.candidates("/help se", 8), (6, vec!["sessions".into()]));
+    }
+
+    #[test]
+    fn ignores_messages_and_extra_words() {
+        let c = completions();
+        assert_eq!(c.candidates("hello /s", 8), (8, vec![]));
+        assert_eq!(c.candidates("/model a b", 10), (10, vec![]));
+        assert_eq!(c.candidates("/model a ", 9), (9, vec![]));
+        assert_eq!(c.candidates("/save no", 8), (6, vec![]));
+        // Cursor in the middle of the line completes the text before it.
+        assert_eq!(c.candidates("/us trailing", 3), (0, vec!["/use".into()]));
+    }
+}
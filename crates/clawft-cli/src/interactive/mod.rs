@@ -2,7 +2,16 @@
 //!
 //! Provides a [`SlashCommandRegistry`] that manages built-in and custom
 //! slash commands, plus an [`InteractiveContext`] holding the session state
-//! needed by command handlers.
+//! needed by command handlers. Commands that change the stored session
+//! leave a [`SessionAction`] for [`session::apply_action`]. Input comes
+//! from a [`reader::LineReader`], with Tab [`completion`].
+//!
+//! [`SlashCommandRegistry`]: registry::SlashCommandRegistry
+//! [`InteractiveContext`]: registry::InteractiveContext
+//! [`SessionAction`]: registry::SessionAction
 
 pub mod builtins;
+pub mod completion;
+pub mod reader;
 pub mod registry;
+pub mod session;
@@ -0,0 +1,213 @@
+//! Line input for the `weft agent` REPL.
+//!
+//! A [`LineReader`] owns a rustyline editor on a dedicated thread, since
+//! reading a line blocks. The REPL asks for a line at the prompt with
+//! [`LineReader::read_line`]. While a turn runs it calls
+//! [`LineReader::watch`] so lines typed meanwhile (such as `/cancel`)
+//! arrive through [`LineReader::next_watched`]; watching reads complete
+//! lines only, so it never competes with the editor for input.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+
+use rustyline::Editor;
+use rustyline::config::Config;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use tokio::sync::mpsc as tokio_mpsc;
+
+use super::completion::Completions;
+
+/// What reading a line produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadEvent {
+    /// A line, without its newline.
+    Line(String),
+    /// Ctrl-C at the prompt.
+    Interrupted,
+    /// End of input (Ctrl-D or a closed pipe).
+    Eof,
+}
+
+/// Requests to the reader thread.
+enum Request {
+    /// Read a line with the editor after printing the prompt.
+    Prompt(String),
+    /// Read complete lines typed while a turn runs.
+    Watch,
+    /// Stop watching; answered by [`Reply::WatchEnded`].
+    EndWatch,
+}
+
+/// Answers from the reader thread.
+enum Reply {
+    Event(ReadEvent),
+    WatchEnded,
+}
+
+/// Reads REPL input on a background thread.
+pub struct LineReader {
+    requests: mpsc::Sender<Request>,
+    replies: tokio_mpsc::UnboundedReceiver<Reply>,
+    /// Lines typed during a turn and not consumed by it.
+    queued: VecDeque<String>,
+}
+
+impl LineReader {
+    /// Start the reader thread with tab completion from `completions`.
+    pub fn spawn(completions: Completions) -> anyhow::Result<Self> {
+        let config = Config::builder().auto_add_history(true).build();
+        let mut editor = Editor::<Completions, DefaultHistory>::with_config(config)?;
+        editor.set_helper(Some(completions));
+
+        let (requests, request_rx) = mpsc::channel();
+        let (reply_tx, replies) = tokio_mpsc::unbounded_channel();
+        std::thread::Builder::new()
+            .name("weft-repl-input".into())
+            .spawn(move || serve(editor, request_rx, reply_tx))?;
+        Ok(Self {
+            requests,
+            replies,
+            queued: VecDeque::new(),
+        })
+    }
+
+    /// Print `prompt` and read a line, or return a line typed during the
+    /// last turn that the turn did not consume.
+    pub async fn read_line(&mut self, prompt: &str) -> ReadEvent {
+        if let Some(line) = self.queued.pop_front() {
+            println!("{prompt}{line}");
+            return ReadEvent::Line(line);
+        }
+        if self.requests.send(Request::Prompt(prompt.into())).is_err() {
+            return ReadEvent::Eof;
+        }
+        loop {
+            match self.replies.recv().await {
+                Some(Reply::Event(event)) => return event,
+                Some(Reply::WatchEnded) => continue,
+                None => return ReadEvent::Eof,
+            }
+        }
+    }
+
+    /// Start reading lines typed while a turn runs.
+    pub fn watch(&self) {
+        let _ = self.requests.send(Request::Watch);
+    }
+
+    /// The next line typed while watching. Pending forever once input has
+    /// ended, so it can sit in a `select!` next to the turn.
+    pub async fn next_watched(&mut self) -> String {
+        loop {
+            match self.replies.recv().await {
+                Some(Reply::Event(ReadEvent::Line(line))) => return line,
+                Some(_) => continue,
+                None => std::future::pending::<()>().await,
+            }
+        }
+    }
+
+    /// Stop watching. Lines typed since the last [`next_watched`] are
+    /// kept for the next [`read_line`].
+    ///
+    /// [`next_watched`]: Self::next_watched
+    /// [`read_line`]: Self::read_line
+    pub async fn end_watch(&mut self) {
+        if self.requests.send(Request::EndWatch).is_err() {
+            return;
+        }
+        while let Some(reply) = self.replies.recv().await {
+            match reply {
+                Reply::Event(ReadEvent::Line(line)) => self.queued.push_back(line),
+                Reply::Event(_) => {}
+                Reply::WatchEnded => break,
+            }
+        }
+    }
+
+    /// Keep `line` for the next [`read_line`](Self::read_line).
+    pub fn requeue(&mut self, line: String) {
+        self.queued.push_back(line);
+    }
+}
+
+/// The reader thread: answers requests until the REPL drops its end.
+fn serve(
+    mut editor: Editor<Completions, DefaultHistory>,
+    requests: mpsc::Receiver<Request>,
+    replies: tokio_mpsc::UnboundedSender<Reply>,
+) {
+    while let Ok(request) = requests.recv() {
+        let reply = match request {
+            Request::Prompt(prompt) => Reply::Event(match editor.readline(&prompt) {
+                Ok(line) => ReadEvent::Line(line),
+                Err(ReadlineError::Interrupted) => ReadEvent::Interrupted,
+                Err(ReadlineError::Eof) => ReadEvent::Eof,
+                Err(e) => {
+                    tracing::warn!("failed to read input: {e}");
+                    ReadEvent::Eof
+                }
+            }),
+            Request::Watch => {
+                if !watch(&requests, &replies) {
+                    return;
+                }
+                Reply::WatchEnded
+            }
+            Request::EndWatch => Reply::WatchEnded,
+        };
+        if replies.send(reply).is_err() {
+            return;
+        }
+    }
+}
+
+/// Forward complete lines from a terminal until [`Request::EndWatch`].
+/// Returns `false` when the REPL has gone away.
+///
+/// The terminal is in canonical mode between prompts, so stdin only
+/// becomes readable once a whole line was entered, and reading it never
+/// takes input meant for the next prompt.
+#[cfg(unix)]
+fn watch(requests: &mpsc::Receiver<Request>, replies: &tokio_mpsc::UnboundedSender<Reply>) -> bool {
+    use std::io::{BufRead, IsTerminal};
+    use std::os::fd::AsFd;
+
+    use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return requests.recv().is_ok();
+    }
+    loop {
+        let mut fds = [PollFd::new(stdin.as_fd(), PollFlags::POLLIN)];
+        if poll(&mut fds, PollTimeout::from(100u8)).unwrap_or(0) > 0 {
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                // Input ended; wait for the turn to end.
+                Ok(0) | Err(_) => return requests.recv().is_ok(),
+                Ok(_) => {
+                    let line = line.trim_end_matches(['\r', '\n']).to_string();
+                    if replies.send(Reply::Event(ReadEvent::Line(line))).is_err() {
+                        return false;
+                    }
+                }
+            }
+        }
+        match requests.try_recv() {
+            Ok(_) => return true,
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Without `poll`, lines are only read at the prompt.
+#[cfg(not(unix))]
+fn watch(
+    requests: &mpsc::Receiver<Request>,
+    _replies: &tokio_mpsc::UnboundedSender<Reply>,
+) -> bool {
+    requests.recv().is_ok()
+}
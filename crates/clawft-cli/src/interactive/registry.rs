@@ -13,6 +13,13 @@
 //! ```
 
 use std::collections::HashMap;
+use std::sync::Arc;
+
+use clawft_llm::UsageTracker;
+
+/// Session the REPL talks to unless `--session`, `/fork` or `/load`
+/// switches it.
+pub const DEFAULT_SESSION: &str = "cli:cli-session";
 
 /// Mutable context passed to slash commands during execution.
 ///
@@ -28,6 +35,23 @@ pub struct InteractiveContext {
     /// Current model identifier.
     pub model: String,
 
+    /// The configured model. Messages pin [`model`](Self::model) only
+    /// when `/model` changed it.
+    pub default_model: String,
+
+    /// Model identifiers known to the router (for `/model` and completion).
+    pub model_ids: Vec<String>,
+
+    /// Session messages go to (`None` = [`DEFAULT_SESSION`]).
+    pub session: Option<String>,
+
+    /// Token usage and cost accounting (for `/usage`).
+    pub usage: Option<Arc<UsageTracker>>,
+
+    /// Session change a command asked for, carried out by the REPL after
+    /// the command returns.
+    pub action: Option<SessionAction>,
+
     /// Registered tool names (for `/tools` display).
     pub tool_names: Vec<String>,
 
@@ -44,12 +68,53 @@ impl InteractiveContext {
         Self {
             active_agent: String::new(),
             active_skill: String::new(),
+            default_model: model.clone(),
             model,
+            model_ids: Vec::new(),
+            session: None,
+            usage: None,
+            action: None,
             tool_names: Vec::new(),
             skill_names: Vec::new(),
             agent_names: Vec::new(),
         }
     }
+
+    /// Key of the session messages go to.
+    pub fn session_key(&self) -> &str {
+        self.session.as_deref().unwrap_or(DEFAULT_SESSION)
+    }
+
+    /// The model `/model` switched to, if it differs from the default.
+    pub fn pinned_model(&self) -> Option<&str> {
+        (self.model != self.default_model).then_some(self.model.as_str())
+    }
+}
+
+/// A change to the conversation that needs the session store or the agent
+/// loop, so the REPL carries it out rather than the command itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionAction {
+    /// Drop the current session's history.
+    Clear,
+    /// Copy the current session under a new key.
+    Save(String),
+    /// Continue in another stored session.
+    Load(String),
+    /// Cancel the running turn.
+    Cancel,
+}
+
+/// Split a slash command into its name (without the `/`) and arguments.
+///
+/// Returns `None` if `input` does not start with `/` or names no command.
+pub fn parse_slash(input: &str) -> Option<(&str, &str)> {
+    let without_slash = input.trim().strip_prefix('/')?;
+    let (name, args) = match without_slash.split_once(char::is_whitespace) {
+        Some((n, a)) => (n, a.trim()),
+        None => (without_slash, ""),
+    };
+    (!name.is_empty()).then_some((name, args))
 }
 
 /// Trait for a slash command handler.
@@ -116,17 +181,7 @@ impl SlashCommandRegistry {
         input: &str,
         ctx: &mut InteractiveContext,
     ) -> Option<anyhow::Result<String>> {
-        let input = input.trim();
-        if !input.starts_with('/') {
-            return None;
-        }
-
-        let without_slash = &input[1..];
-        let (name, args) = match without_slash.split_once(char::is_whitespace) {
-            Some((n, a)) => (n, a.trim()),
-            None => (without_slash, ""),
-        };
-
+        let (name, args) = parse_slash(input)?;
         let cmd = self.commands.get(name)?;
         Some(cmd.execute(args, ctx))
     }
@@ -327,6 +382,31 @@ mod tests {
         assert!(reg.is_empty());
     }
 
+    #[test]
+    fn parse_slash_splits_name_and_args() {
+        assert_eq!(
+            parse_slash("/model openai/gpt-4o"),
+            Some(("model", "openai/gpt-4o"))
+        );
+        assert_eq!(parse_slash("  /save   notes  "), Some(("save", "notes")));
+        assert_eq!(parse_slash("/clear"), Some(("clear", "")));
+        assert_eq!(parse_slash("/"), None);
+        assert_eq!(parse_slash("/ help"), None);
+        assert_eq!(parse_slash("hello /help"), None);
+    }
+
+    #[test]
+    fn interactive_context_session_and_model() {
+        let mut ctx = InteractiveContext::new("anthropic/claude".into());
+        assert_eq!(ctx.session_key(), DEFAULT_SESSION);
+        assert_eq!(ctx.pinned_model(), None);
+
+        ctx.session = Some("cli:notes".into());
+        ctx.model = "openai/gpt-4o".into();
+        assert_eq!(ctx.session_key(), "cli:notes");
+        assert_eq!(ctx.pinned_model(), Some("openai/gpt-4o"));
+    }
+
     #[test]
     fn interactive_context_new() {
         let ctx = InteractiveContext::new("model/v1".into());
@@ -0,0 +1,203 @@
+//! Carrying out the [`SessionAction`]s slash commands ask for.
+//!
+//! Commands run synchronously against the [`InteractiveContext`]; the
+//! changes that need the session store or the agent loop are recorded in
+//! [`InteractiveContext::action`] and applied here by the REPL.
+
+use clawft_core::agent::turns::ActiveTurns;
+use clawft_core::session::SessionManager;
+use clawft_platform::Platform;
+use clawft_types::session::Session;
+
+use super::registry::{InteractiveContext, SessionAction};
+
+/// Apply `action` to the current session.
+///
+/// - `Clear` replaces the session with an empty one under the same key.
+/// - `Save` copies the session to a new key; an existing session there is
+///   never overwritten.
+/// - `Load` switches to a stored session.
+/// - `Cancel` cancels the turn running for the session.
+pub async fn apply_action<P: Platform>(
+    action: SessionAction,
+    ctx: &mut InteractiveContext,
+    sessions: &SessionManager<P>,
+    turns: &ActiveTurns,
+) -> anyhow::Result<()> {
+    match action {
+        SessionAction::Clear => {
+            sessions
+                .save_session(&Session::new(ctx.session_key()))
+                .await?;
+        }
+        SessionAction::Save(key) => {
+            clawft_core::security::validate_session_id(&key)?;
+            if sessions.load_session(&key).await.is_ok() {
+                anyhow::bail!("session already exists: {key}");
+            }
+            let mut session = sessions.get_or_create(ctx.session_key()).await?;
+            session.key = key;
+            sessions.save_session(&session).await?;
+        }
+        SessionAction::Load(key) => {
+            sessions.load_session(&key).await?;
+            ctx.session = Some(key);
+        }
+        SessionAction::Cancel => {
+            if !turns.cancel(ctx.session_key()) {
+                anyhow::bail!("no turn is running");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use clawft_platform::NativePlatform;
+
+    use super::*;
+    use crate::interactive::registry::DEFAULT_SESSION;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clawft-interactive-session-{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn sessions_with_history(dir: &std::path::Path) -> SessionManager<NativePlatform> {
+        let sessions = SessionManager::with_dir(Arc::new(NativePlatform::new()), dir.to_path_buf());
+        let mut session = Session::new(DEFAULT_SESSION);
+        session.add_message("user", "hello", None);
+        session.add_message("assistant", "hi there", None);
+        sessions.save_session(&session).await.unwrap();
+        sessions
+    }
+
+    fn ctx() -> InteractiveContext {
+        InteractiveContext::new("test-model/v1".into())
+    }
+
+    #[tokio::test]
+    async fn clear_empties_the_current_session() {
+        let dir = temp_dir();
+        let sessions = sessions_with_history(&dir).await;
+        let mut ctx = ctx();
+
+        apply_action(
+            SessionAction::Clear,
+            &mut ctx,
+            &sessions,
+            &ActiveTurns::new(),
+        )
+        .await
+        .unwrap();
+        let session = sessions.load_session(DEFAULT_SESSION).await.unwrap();
+        assert!(session.messages.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn save_copies_without_switching_or_overwriting() {
+        let dir = temp_dir();
+        let sessions = sessions_with_history(&dir).await;
+        let mut ctx = ctx();
+        let turns = ActiveTurns::new();
+
+        apply_action(
+            SessionAction::Save("cli:notes".into()),
+            &mut ctx,
+            &sessions,
+            &turns,
+        )
+        .await
+        .unwrap();
+        let copy = sessions.load_session("cli:notes").await.unwrap();
+        assert_eq!(copy.messages.len(), 2);
+        assert_eq!(ctx.session_key(), DEFAULT_SESSION);
+
+        let err = apply_action(
+            SessionAction::Save("cli:notes".into()),
+            &mut ctx,
+            &sessions,
+            &turns,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+        let err = apply_action(
+            SessionAction::Save("../x".into()),
+            &mut ctx,
+            &sessions,
+            &turns,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("traversal"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn load_switches_only_to_stored_sessions() {
+        let dir = temp_dir();
+        let sessions = sessions_with_history(&dir).await;
+        let mut ctx = ctx();
+        let turns = ActiveTurns::new();
+        sessions
+            .save_session(&Session::new("cli:notes"))
+            .await
+            .unwrap();
+
+        apply_action(
+            SessionAction::Load("cli:notes".into()),
+            &mut ctx,
+            &sessions,
+            &turns,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.session_key(), "cli:notes");
+
+        let err = apply_action(
+            SessionAction::Load("cli:missing".into()),
+            &mut ctx,
+            &sessions,
+            &turns,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+        assert_eq!(ctx.session_key(), "cli:notes");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cancel_cancels_the_sessions_turn() {
+        let dir = temp_dir();
+        let sessions = sessions_with_history(&dir).await;
+        let mut ctx = ctx();
+        let turns = Arc::new(ActiveTurns::new());
+
+        let err = apply_action(SessionAction::Cancel, &mut ctx, &sessions, &turns)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no turn"), "{err}");
+
+        let turn = turns.begin(DEFAULT_SESSION);
+        apply_action(SessionAction::Cancel, &mut ctx, &sessions, &turns)
+            .await
+            .unwrap();
+        assert!(turn.token().is_cancelled());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,632 @@
+//! Structured JSONL log file for the gateway.
+//!
+//! When `gateway.logs.enabled` is set, every tracing event at or above the
+//! configured level is written to `~/.clawft/state/logs.jsonl` as one
+//! [`LogRecord`] per line, alongside the usual terminal output. Fields
+//! naming the session, channel, agent and tool, whether on the event or
+//! on an enclosing span such as the agent loop's `turn`, are lifted into
+//! their own columns so `weft logs` can filter on them.
+//!
+//! The [`layer`] is part of the subscriber from the start but writes
+//! nothing until [`install`] is called with the loaded config. The file
+//! rotates by size like the tool audit log: `logs.jsonl.1` is the newest
+//! rotated file.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use tracing::{Event, Id, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+use clawft_types::config::LogSinkConfig;
+
+/// File name of the log, inside `~/.clawft/state/`.
+pub const LOG_FILE: &str = "logs.jsonl";
+
+/// Path of the log file under the user's home directory.
+pub fn log_path(home: &Path) -> PathBuf {
+    home.join(".clawft").join("state").join(LOG_FILE)
+}
+
+/// One logged event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// When the event was logged.
+    pub timestamp: DateTime<Utc>,
+
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`.
+    pub level: String,
+
+    /// Module that logged the event.
+    pub target: String,
+
+    /// The event's message.
+    pub message: String,
+
+    /// Session key, e.g. `telegram:12345`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+
+    /// Channel name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+
+    /// Agent handling the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    /// Tool being run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+
+    /// Every other field of the event and its spans.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+impl LogRecord {
+    /// A record from the collected `fields` of an event, taking the
+    /// message and the well-known columns out of them. `session_key` and
+    /// `agent_id` are accepted for `session` and `agent`.
+    pub fn new(
+        timestamp: DateTime<Utc>,
+        level: Level,
+        target: &str,
+        mut fields: Map<String, Value>,
+    ) -> Self {
+        let mut take = |name: &str| {
+            fields.remove(name).map(|value| match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+        };
+        let message = take("message").unwrap_or_default();
+        let session = take("session").or(take("session_key"));
+        let channel = take("channel");
+        let agent = take("agent").or(take("agent_id"));
+        let tool = take("tool");
+        Self {
+            timestamp,
+            level: level.to_string(),
+            target: target.to_string(),
+            message,
+            session,
+            channel,
+            agent,
+            tool,
+            fields,
+        }
+    }
+}
+
+/// Which records `weft logs` shows.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Only records for this session.
+    pub session: Option<String>,
+
+    /// Only records for this channel.
+    pub channel: Option<String>,
+
+    /// Only records at this level or more severe.
+    pub level: Option<Level>,
+
+    /// Only records logged at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl LogFilter {
+    /// Whether `record` passes the filter.
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.session
+            .as_deref()
+            .is_none_or(|s| record.session.as_deref() == Some(s))
+            && self
+                .channel
+                .as_deref()
+                .is_none_or(|c| record.channel.as_deref() == Some(c))
+            && self.level.is_none_or(|min| {
+                record
+                    .level
+                    .parse::<Level>()
+                    .is_ok_and(|level| level <= min)
+            })
+            && self.since.is_none_or(|since| record.timestamp >= since)
+    }
+}
+
+/// An append-only file that rotates once it grows past a size limit.
+///
+/// When appending a line would take the file past `max_bytes`, the file
+/// is renamed to `<name>.1` (shifting older files to `.2`, `.3`, ...) and
+/// a new one is started. At most `max_files` rotated files are kept.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    open: Mutex<Option<OpenFile>>,
+}
+
+struct OpenFile {
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// A file at `path`, rotated at `max_bytes` keeping `max_files`.
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            max_files,
+            open: Mutex::new(None),
+        }
+    }
+
+    /// Path of rotated file `n` (1 is the newest).
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        rotated_path(&self.path, n)
+    }
+
+    /// Append `line`, which should end with a newline.
+    pub fn append(&self, line: &str) -> io::Result<()> {
+        let mut open = self
+            .open
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?;
+        let len = line.len() as u64;
+        let current = match open.take() {
+            Some(current) => current,
+            None => self.open()?,
+        };
+        let current = if current.size > 0 && current.size + len > self.max_bytes {
+            drop(current);
+            self.rotate()?;
+            self.open()?
+        } else {
+            current
+        };
+        let current = open.insert(current);
+        current.file.write_all(line.as_bytes())?;
+        current.size += len;
+        Ok(())
+    }
+
+    fn open(&self) -> io::Result<OpenFile> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok(OpenFile { file, size })
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for n in (1..self.max_files).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))
+    }
+}
+
+/// Path of rotated file `n` of the log at `path` (1 is the newest).
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// The last `limit` records passing `filter` in the log at `path` and its
+/// `max_files` rotated files, oldest first. Malformed lines are skipped.
+pub fn recent(
+    path: &Path,
+    max_files: usize,
+    filter: &LogFilter,
+    limit: usize,
+) -> io::Result<Vec<LogRecord>> {
+    let files = (1..=max_files)
+        .rev()
+        .map(|n| rotated_path(path, n))
+        .chain(std::iter::once(path.to_path_buf()));
+
+    let mut out = VecDeque::with_capacity(limit.min(1024));
+    for path in files {
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str::<LogRecord>(&line?) {
+                Ok(record) if filter.matches(&record) => {
+                    if out.len() == limit {
+                        out.pop_front();
+                    }
+                    if limit > 0 {
+                        out.push_back(record);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(out.into())
+}
+
+/// The complete lines of the file at `path` after byte `offset`, parsed,
+/// and the offset just past the last of them. A missing file has none.
+pub fn read_from(path: &Path, offset: u64) -> io::Result<(Vec<LogRecord>, u64)> {
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), offset)),
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(offset))?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+
+    // A partly written last line is left for the next read.
+    let complete = text.rfind('\n').map_or(0, |i| i + 1);
+    let records = text[..complete]
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok((records, offset + complete as u64))
+}
+
+/// The installed sink: where records go and the least severe level kept.
+struct Sink {
+    file: RotatingFile,
+    level: Level,
+}
+
+static SINK: OnceLock<Sink> = OnceLock::new();
+
+/// Start writing the log file described by `config` to `path`.
+///
+/// Does nothing when a sink is already installed. Fails on an unknown
+/// level name.
+pub fn install(config: &LogSinkConfig, path: PathBuf) -> anyhow::Result<()> {
+    let level = config
+        .level
+        .parse::<Level>()
+        .map_err(|_| anyhow::anyhow!("invalid gateway.logs.level: {}", config.level))?;
+    let file = RotatingFile::new(path, config.max_file_bytes, config.max_files);
+    let _ = SINK.set(Sink { file, level });
+    Ok(())
+}
+
+/// The tracing layer feeding the log file once [`install`] was called.
+///
+/// Spans are kept up to `INFO` even when the file's level is stricter, so
+/// a warning inside a turn is still attributed to its session.
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    JsonlLayer.with_filter(filter_fn(|meta| {
+        SINK.get().is_some_and(|sink| {
+            let max = if meta.is_span() {
+                sink.level.max(Level::INFO)
+            } else {
+                sink.level
+            };
+            *meta.level() <= max
+        })
+    }))
+}
+
+/// Writes events to the installed [`Sink`].
+struct JsonlLayer;
+
+/// Fields recorded on a span, kept in its extensions.
+struct SpanFields(Map<String, Value>);
+
+impl<S> Layer<S> for JsonlLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+            values.record(&mut FieldVisitor(&mut fields.0));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(sink) = SINK.get() else {
+            return;
+        };
+        let mut fields = Map::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(span_fields) = span.extensions().get::<SpanFields>() {
+                    fields.extend(span_fields.0.clone());
+                }
+            }
+        }
+        event.record(&mut FieldVisitor(&mut fields));
+
+        let meta = event.metadata();
+        let record = LogRecord::new(Utc::now(), *meta.level(), meta.target(), fields);
+        if let Ok(mut line) = serde_json::to_string(&record) {
+            line.push('\n');
+            // Logging must never take the gateway down; a failed write
+            // only loses the record.
+            let _ = sink.file.append(&line);
+        }
+    }
+}
+
+/// Collects tracing fields as JSON values.
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0.insert(field.name().into(), value.to_string().into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-log-sink-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn record(level: Level, session: Option<&str>, channel: Option<&str>) -> LogRecord {
+        let mut fields = Map::new();
+        fields.insert("message".into(), "hello".into());
+        if let Some(session) = session {
+            fields.insert("session".into(), session.into());
+        }
+        if let Some(channel) = channel {
+            fields.insert("channel".into(), channel.into());
+        }
+        LogRecord::new(Utc::now(), level, "clawft_core::agent", fields)
+    }
+
+    #[test]
+    fn record_lifts_known_fields() {
+        let mut fields = Map::new();
+        fields.insert("message".into(), "tool failed".into());
+        fields.insert("session_key".into(), "telegram:1".into());
+        fields.insert("agent_id".into(), "coder".into());
+        fields.insert("tool".into(), "exec".into());
+        fields.insert("attempt".into(), 2.into());
+        let record = LogRecord::new(Utc::now(), Level::WARN, "clawft_core", fields);
+
+        assert_eq!(record.level, "WARN");
+        assert_eq!(record.message, "tool failed");
+        assert_eq!(record.session.as_deref(), Some("telegram:1"));
+        assert_eq!(record.agent.as_deref(), Some("coder"));
+        assert_eq!(record.tool.as_deref(), Some("exec"));
+        assert_eq!(record.channel, None);
+        assert_eq!(record.fields.len(), 1);
+        assert_eq!(record.fields["attempt"], 2);
+    }
+
+    #[test]
+    fn filter_matches_session_channel_level_and_since() {
+        let warn = record(Level::WARN, Some("telegram:1"), Some("telegram"));
+        let info = record(Level::INFO, Some("slack:2"), Some("slack"));
+        let bare = record(Level::ERROR, None, None);
+
+        assert!(LogFilter::default().matches(&warn));
+
+        let session = LogFilter {
+            session: Some("telegram:1".into()),
+            ..Default::default()
+        };
+        assert!(session.matches(&warn));
+        assert!(!session.matches(&info));
+        assert!(!session.matches(&bare));
+
+        let channel = LogFilter {
+            channel: Some("slack".into()),
+            ..Default::default()
+        };
+        assert!(channel.matches(&info));
+        assert!(!channel.matches(&warn));
+
+        let level = LogFilter {
+            level: Some(Level::WARN),
+            ..Default::default()
+        };
+        assert!(level.matches(&warn));
+        assert!(level.matches(&bare));
+        assert!(!level.matches(&info));
+
+        let since = LogFilter {
+            since: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(!since.matches(&warn));
+        let since = LogFilter {
+            since: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(since.matches(&warn));
+    }
+
+    #[test]
+    fn rotates_past_size_limit() {
+        let dir = temp_dir();
+        let path = dir.join(LOG_FILE);
+        let file = RotatingFile::new(&path, 100, 2);
+        let line = format!("{}\n", "x".repeat(39));
+
+        // Two 40-byte lines fit; the third would pass 100 bytes.
+        file.append(&line).unwrap();
+        file.append(&line).unwrap();
+        assert!(!file.rotated_path(1).exists());
+        file.append(&line).unwrap();
+        assert_eq!(fs::metadata(file.rotated_path(1)).unwrap().len(), 80);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 40);
+
+        // Only `max_files` rotated files are kept.
+        for _ in 0..6 {
+            file.append(&line).unwrap();
+        }
+        assert!(file.rotated_path(2).exists());
+        assert!(!file.rotated_path(3).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn line_larger_than_limit_starts_a_new_file() {
+        let dir = temp_dir();
+        let path = dir.join(LOG_FILE);
+        let file = RotatingFile::new(&path, 10, 0);
+        file.append("short\n").unwrap();
+        file.append(&format!("{}\n", "y".repeat(20))).unwrap();
+        // With no rotated files kept, the old file is dropped.
+        assert!(!file.rotated_path(1).exists());
+        assert_eq!(fs::metadata(&path).unwrap().len(), 21);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reads_recent_records_across_rotated_files() {
+        let dir = temp_dir();
+        let path = dir.join(LOG_FILE);
+        let file = RotatingFile::new(&path, 400, 3);
+        for i in 0..10 {
+            let session = format!("telegram:{}", i % 2);
+            let mut record = record(Level::INFO, Some(&session), Some("telegram"));
+            record.message = format!("event {i}");
+            let mut line = serde_json::to_string(&record).unwrap();
+            line.push('\n');
+            file.append(&line).unwrap();
+        }
+        assert!(file.rotated_path(1).exists());
+
+        let filter = LogFilter {
+            session: Some("telegram:1".into()),
+            ..Default::default()
+        };
+        let records = recent(&path, 3, &filter, 2).unwrap();
+        let messages: Vec<_> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["event 7", "event 9"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn layer_writes_events_with_span_fields() {
+        use tracing_subscriber::prelude::*;
+
+        let dir = temp_dir();
+        let path = dir.join(LOG_FILE);
+        let config = LogSinkConfig {
+            enabled: true,
+            level: "warn".into(),
+            ..Default::default()
+        };
+        install(&config, path.clone()).unwrap();
+
+        let subscriber = tracing_subscriber::registry().with(layer());
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("turn", session = "telegram:1", channel = "telegram");
+            span.in_scope(|| {
+                tracing::info!("below the level");
+                tracing::warn!(tool = "exec", code = 2, "tool failed");
+            });
+        });
+
+        let records = recent(&path, 0, &LogFilter::default(), 10).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.level, "WARN");
+        assert_eq!(record.message, "tool failed");
+        assert_eq!(record.session.as_deref(), Some("telegram:1"));
+        assert_eq!(record.channel.as_deref(), Some("telegram"));
+        assert_eq!(record.tool.as_deref(), Some("exec"));
+        assert_eq!(record.fields["code"], 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn read_from_leaves_partial_lines() {
+        let dir = temp_dir();
+        let path = dir.join(LOG_FILE);
+        let full = serde_json::to_string(&record(Level::INFO, None, None)).unwrap();
+        fs::write(&path, format!("{full}\n{{\"partial\"")).unwrap();
+
+        let (records, offset) = read_from(&path, 0).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(offset, full.len() as u64 + 1);
+        let (records, again) = read_from(&path, offset).unwrap();
+        assert!(records.is_empty());
+        assert_eq!(again, offset);
+        assert_eq!(read_from(&dir.join("missing"), 7).unwrap().1, 7);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
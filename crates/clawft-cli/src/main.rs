@@ -4,19 +4,27 @@
 //!
 //! - `weft agent` -- Start an interactive agent session or send a single message.
 //! - `weft gateway` -- Start channels + agent loop (Telegram, Slack, etc.).
-//! - `weft mcp-server` -- Run as an MCP tool server over stdio.
+//! - `weft mcp-server` -- Run as an MCP tool server over stdio or HTTP.
 //! - `weft status` -- Show configuration status and diagnostics.
+//! - `weft doctor` -- Check the environment and config for problems.
+//! - `weft logs` -- Read the gateway's structured log file.
 //! - `weft channels` -- Inspect channel configuration status.
 //! - `weft cron` -- Manage scheduled (cron) jobs.
 
 use clap::{CommandFactory, Parser, Subcommand};
+use tracing_subscriber::prelude::*;
 
 mod commands;
 mod completions;
 mod help_text;
 pub mod interactive;
+mod log_sink;
 mod markdown;
+#[cfg(feature = "services")]
+mod mcp_sampling;
 mod mcp_tools;
+#[cfg(feature = "delegate")]
+mod remote_delegate;
 
 /// clawft AI assistant CLI.
 #[derive(Parser)]
@@ -37,6 +45,7 @@ struct Cli {
 
 /// Top-level subcommands.
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
 enum Commands {
     /// Start an interactive agent session or send a single message.
     Agent(commands::agent::AgentArgs),
@@ -44,13 +53,19 @@ enum Commands {
     /// Start the gateway (channels + agent loop).
     Gateway(commands::gateway::GatewayArgs),
 
-    /// Run as an MCP tool server over stdio.
+    /// Run as an MCP tool server over stdio or HTTP.
     #[cfg(feature = "services")]
     McpServer(commands::mcp_server::McpServerArgs),
 
     /// Show configuration status.
     Status(commands::status::StatusArgs),
 
+    /// Check the environment and config for problems.
+    Doctor(commands::doctor::DoctorArgs),
+
+    /// Read the gateway's structured log file.
+    Logs(commands::logs_cmd::LogsArgs),
+
     /// Inspect channel configuration.
     Channels {
         #[command(subcommand)]
@@ -69,12 +84,24 @@ enum Commands {
         action: SessionsCmd,
     },
 
+    /// View the tool execution audit log.
+    Audit {
+        #[command(subcommand)]
+        action: AuditCmd,
+    },
+
     /// Read and search agent memory.
     Memory {
         #[command(subcommand)]
         action: MemoryCmd,
     },
 
+    /// Inspect the agent's workspace task list.
+    Tasks {
+        #[command(subcommand)]
+        action: TasksCmd,
+    },
+
     /// Show resolved configuration.
     Config {
         #[command(subcommand)]
@@ -127,13 +154,37 @@ enum Commands {
         /// Shell to generate for (bash, zsh, fish, powershell).
         shell: String,
     },
+
+    /// Print completion candidates (used by the completion scripts).
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// What to complete.
+        #[arg(value_enum)]
+        kind: commands::complete_cmd::CompleteKind,
+    },
 }
 
 /// Subcommands for `weft sessions`.
 #[derive(Subcommand)]
 enum SessionsCmd {
-    /// List all sessions.
+    /// List sessions.
     List {
+        /// Only sessions whose key starts with this prefix (e.g. "telegram:").
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Maximum number of sessions to show.
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Number of sessions to skip.
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+
+        /// List archived sessions instead of live ones.
+        #[arg(long)]
+        archived: bool,
+
         /// Config file path (overrides auto-discovery).
         #[arg(short, long)]
         config: Option<String>,
@@ -144,6 +195,41 @@ enum SessionsCmd {
         /// Session key to inspect.
         session_id: String,
 
+        /// Show the session's tool executions from the audit log.
+        #[arg(long)]
+        tools: bool,
+
+        /// Show the recorded trace of a turn (see `agents.sessions.trace`).
+        #[arg(long, conflicts_with = "tools")]
+        trace: bool,
+
+        /// Turn to show with --trace (default: the latest traced turn).
+        #[arg(long, requires = "trace")]
+        turn: Option<usize>,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Export a session as a markdown transcript or JSON document.
+    Export {
+        /// Session key to export.
+        session_id: String,
+
+        /// Output format: md or json.
+        #[arg(long, default_value = "md")]
+        format: String,
+
+        /// File to write (default: stdout). Attachments are copied into a
+        /// sibling `<name>_files/` directory.
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Bundle attachments into `<name>_files.zip` instead of a directory.
+        #[arg(long)]
+        zip: bool,
+
         /// Config file path (overrides auto-discovery).
         #[arg(short, long)]
         config: Option<String>,
@@ -158,6 +244,76 @@ enum SessionsCmd {
         #[arg(short, long)]
         config: Option<String>,
     },
+
+    /// Move an archived session back to the live sessions.
+    Restore {
+        /// Session key to restore.
+        session_id: String,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Branch a session into a new one that shares its history up to a
+    /// message.
+    Fork {
+        /// Session key to fork.
+        session_id: String,
+
+        /// Number of messages to keep (default: the whole history).
+        #[arg(long)]
+        at: Option<usize>,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Import JSONL session files into the SQLite session database.
+    Migrate,
+}
+
+/// Subcommands for `weft audit`.
+#[derive(Subcommand)]
+enum AuditCmd {
+    /// Show the most recent tool executions.
+    Tail {
+        /// Number of records to show.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+
+        /// Only executions in this session.
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Only executions of this tool.
+        #[arg(long)]
+        tool: Option<String>,
+
+        /// Keep printing new executions as they are recorded.
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+}
+
+/// Subcommands for `weft tasks`.
+#[derive(Subcommand)]
+enum TasksCmd {
+    /// List the tasks kept with the task_list tool.
+    List {
+        /// Only pending and in-progress tasks.
+        #[arg(long)]
+        open: bool,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
 }
 
 /// Subcommands for `weft memory`.
@@ -228,6 +384,30 @@ enum MemoryCmd {
         #[arg(short, long)]
         config: Option<String>,
     },
+
+    /// Merge similar long-term entries and drop entries not referenced
+    /// recently.
+    Compact {
+        /// Drop entries not referenced for this many days (default:
+        /// agents.memory.decayDays).
+        #[arg(long)]
+        days: Option<u32>,
+
+        /// Only drop stale entries; do not ask the model to merge.
+        #[arg(long)]
+        no_merge: bool,
+
+        /// Show what would change without changing anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// Copy MEMORY.md and HISTORY.md into the SQLite memory database.
+    Migrate,
 }
 
 /// Subcommands for `weft config`.
@@ -245,6 +425,24 @@ enum ConfigCmd {
         /// Section name (e.g., "agents", "gateway", "channels").
         name: String,
 
+        /// Config file path (overrides auto-discovery).
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Ask a running gateway to re-read its config and apply the changes
+    /// that need no restart.
+    Reload,
+
+    /// Check the config file for unknown fields and inconsistent settings.
+    Validate {
+        /// Also exit non-zero when there are only warnings.
+        #[arg(long)]
+        strict: bool,
+
+        /// Print the findings as JSON.
+        #[arg(long)]
+        json: bool,
+
         /// Config file path (overrides auto-discovery).
         #[arg(short, long)]
         config: Option<String>,
@@ -254,16 +452,20 @@ enum ConfigCmd {
 /// Subcommands for `weft channels`.
 #[derive(Subcommand)]
 enum ChannelsAction {
-    /// Show channel status table.
+    /// Show channel status table and live metrics from a running gateway.
     Status {
         /// Config file path (overrides auto-discovery).
         #[arg(short, long)]
         config: Option<String>,
     },
+
+    /// Ask a running gateway to reload channels from its config (SIGHUP).
+    Reload,
 }
 
 /// Subcommands for `weft cron`.
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // parsed once per invocation
 enum CronAction {
     /// List all cron jobs.
     List {
@@ -279,13 +481,73 @@ enum CronAction {
         name: String,
 
         /// Cron expression (e.g. "0 9 * * Mon-Fri").
+        #[arg(
+            long,
+            required_unless_present_any = ["at", "every"],
+            conflicts_with_all = ["at", "every"]
+        )]
+        schedule: Option<String>,
+
+        /// Run once at this RFC 3339 time (e.g. "2026-03-01T09:45:00Z").
+        #[arg(long, conflicts_with = "every")]
+        at: Option<String>,
+
+        /// Run repeatedly at this interval (e.g. "10m", "1h30m").
         #[arg(long)]
-        schedule: String,
+        every: Option<String>,
+
+        /// Last time an --every job may run (RFC 3339).
+        #[arg(long, requires = "every", conflicts_with_all = ["schedule", "at"])]
+        until: Option<String>,
 
         /// Agent prompt to execute when the job fires.
         #[arg(long)]
         prompt: String,
 
+        /// Channel to send the reply on (requires --to).
+        #[arg(long, requires = "to")]
+        channel: Option<String>,
+
+        /// Conversation (chat ID) on --channel to send the reply to.
+        #[arg(long, requires = "channel")]
+        to: Option<String>,
+
+        /// Hold the reply during this window, e.g. "22:00-07:00".
+        #[arg(long)]
+        quiet_hours: Option<String>,
+
+        /// Timezone of --quiet-hours ("UTC" or a fixed offset like "+02:00").
+        #[arg(long, default_value = "UTC", requires = "quiet_hours")]
+        timezone: String,
+
+        /// Channel to alert on when the job keeps failing (requires
+        /// --alert-to).
+        #[arg(long, requires = "alert_to")]
+        alert_channel: Option<String>,
+
+        /// Conversation (chat ID) on --alert-channel to send alerts to.
+        #[arg(long, requires = "alert_channel")]
+        alert_to: Option<String>,
+
+        /// Consecutive failures before the first alert; later alerts back
+        /// off exponentially.
+        #[arg(long, default_value_t = 3, requires = "alert_channel")]
+        alert_after: u32,
+
+        /// IANA timezone the schedule is evaluated in, e.g.
+        /// "America/New_York" (default UTC).
+        #[arg(long)]
+        tz: Option<String>,
+
+        /// Delay each run by up to this many seconds.
+        #[arg(long, default_value_t = 0)]
+        jitter: u64,
+
+        /// Runs missed while the gateway was down: skip (default),
+        /// run_once or run_all.
+        #[arg(long, value_parser = ["skip", "run_once", "run_all"])]
+        catch_up: Option<String>,
+
         /// Config file path (overrides auto-discovery).
         #[arg(short, long)]
         config: Option<String>,
@@ -330,22 +592,37 @@ enum CronAction {
         #[arg(short, long)]
         config: Option<String>,
     },
+
+    /// Show the recent runs of a cron job.
+    History {
+        /// Job ID to show.
+        job_id: String,
+
+        /// Number of most recent runs to show.
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    // Terminal output follows RUST_LOG; the structured log file keeps its
+    // own level once the gateway installs it.
     let default_filter = if cli.verbose { "debug" } else { "warn" };
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| default_filter.into()),
-        )
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| default_filter.into());
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(env_filter))
+        .with(log_sink::layer())
         .init();
 
-    // Check for updates (non-blocking, cached 24h)
-    clawft_rpc::version_check::check_for_updates();
+    // Check for updates (non-blocking, cached 24h). Not while completing:
+    // the notice would land in the middle of the command line.
+    if !matches!(cli.command, Commands::Complete { .. }) {
+        clawft_rpc::version_check::check_for_updates();
+    }
 
     match cli.command {
         Commands::Agent(args) => commands::agent::run(args).await?,
@@ -353,13 +630,17 @@ async fn main() -> anyhow::Result<()> {
         #[cfg(feature = "services")]
         Commands::McpServer(args) => commands::mcp_server::run(args).await?,
         Commands::Status(args) => commands::status::run(args).await?,
+        Commands::Doctor(args) => commands::doctor::run(args).await?,
+        Commands::Logs(args) => commands::logs_cmd::run(args).await?,
         Commands::Channels { action } => {
             let platform = clawft_platform::NativePlatform::new();
             match action {
                 ChannelsAction::Status { config } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
                     commands::channels::channels_status(&cfg);
+                    commands::channels::channels_live_metrics(&platform, &cfg).await;
                 }
+                ChannelsAction::Reload => commands::channels::channels_reload()?,
             }
         }
         Commands::Cron { action } => {
@@ -372,11 +653,39 @@ async fn main() -> anyhow::Result<()> {
                 CronAction::Add {
                     name,
                     schedule,
+                    at,
+                    every,
+                    until,
                     prompt,
+                    channel,
+                    to,
+                    quiet_hours,
+                    timezone,
+                    alert_channel,
+                    alert_to,
+                    alert_after,
+                    tz,
+                    jitter,
+                    catch_up,
                     config,
                 } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
-                    commands::cron::cron_add(name, schedule, prompt, &cfg).await?;
+                    let delivery = commands::cron::CronDelivery {
+                        channel,
+                        to,
+                        quiet_hours,
+                        timezone,
+                        alert_channel,
+                        alert_to,
+                        alert_after,
+                    };
+                    let timing = commands::cron::CronTiming {
+                        tz,
+                        jitter_secs: jitter,
+                        catch_up,
+                    };
+                    let when = commands::cron::CronWhen::from_flags(schedule, at, every, until)?;
+                    commands::cron::cron_add(name, when, prompt, delivery, timing, &cfg).await?;
                 }
                 CronAction::Remove { job_id, config } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
@@ -394,23 +703,101 @@ async fn main() -> anyhow::Result<()> {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
                     commands::cron::cron_run(job_id, &cfg).await?;
                 }
+                CronAction::History { job_id, limit } => {
+                    commands::cron::cron_history(&job_id, limit)?;
+                }
             }
         }
         Commands::Sessions { action } => {
             let platform = clawft_platform::NativePlatform::new();
             match action {
-                SessionsCmd::List { config } => {
+                SessionsCmd::List {
+                    prefix,
+                    limit,
+                    offset,
+                    archived,
+                    config,
+                } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
-                    commands::sessions::sessions_list(&cfg).await?;
+                    let query = clawft_core::session::SessionQuery {
+                        key_prefix: prefix,
+                        updated_since: None,
+                        offset,
+                        limit,
+                        archived,
+                    };
+                    commands::sessions::sessions_list(&query, &cfg).await?;
                 }
-                SessionsCmd::Inspect { session_id, config } => {
+                SessionsCmd::Inspect {
+                    session_id,
+                    tools,
+                    trace,
+                    turn,
+                    config,
+                } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
-                    commands::sessions::sessions_inspect(session_id, &cfg).await?;
+                    commands::sessions::sessions_inspect(session_id, tools, trace, turn, &cfg)
+                        .await?;
+                }
+                SessionsCmd::Export {
+                    session_id,
+                    format,
+                    output,
+                    zip,
+                    config,
+                } => {
+                    let format = format.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    commands::sessions::sessions_export(session_id, format, output, zip, &cfg)
+                        .await?;
                 }
                 SessionsCmd::Delete { session_id, config } => {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
                     commands::sessions::sessions_delete(session_id, &cfg).await?;
                 }
+                SessionsCmd::Restore { session_id, config } => {
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    commands::sessions::sessions_restore(session_id, &cfg).await?;
+                }
+                SessionsCmd::Fork {
+                    session_id,
+                    at,
+                    config,
+                } => {
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    commands::sessions::sessions_fork(session_id, at, &cfg).await?;
+                }
+                SessionsCmd::Migrate => {
+                    commands::sessions::sessions_migrate().await?;
+                }
+            }
+        }
+        Commands::Audit { action } => {
+            let platform = clawft_platform::NativePlatform::new();
+            match action {
+                AuditCmd::Tail {
+                    lines,
+                    session,
+                    tool,
+                    follow,
+                    config,
+                } => {
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    let filter = clawft_core::tools::audit::AuditFilter {
+                        session_key: session,
+                        tool,
+                    };
+                    commands::audit_cmd::audit_tail(filter, lines, follow, &cfg).await?;
+                }
+            }
+        }
+        Commands::Tasks { action } => {
+            let platform = clawft_platform::NativePlatform::new();
+            match action {
+                TasksCmd::List { open, config } => {
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    commands::tasks_cmd::tasks_list(open, &cfg).await?;
+                }
             }
         }
         Commands::Memory { action } => {
@@ -450,6 +837,18 @@ async fn main() -> anyhow::Result<()> {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
                     commands::memory_cmd::memory_import(&agent, &input, skip_verify, &cfg).await?;
                 }
+                MemoryCmd::Compact {
+                    days,
+                    no_merge,
+                    dry_run,
+                    config,
+                } => {
+                    let cfg = commands::load_config(&platform, config.as_deref()).await?;
+                    commands::memory_cmd::memory_compact(days, no_merge, dry_run, &cfg).await?;
+                }
+                MemoryCmd::Migrate => {
+                    commands::memory_cmd::memory_migrate().await?;
+                }
             }
         }
         Commands::Config { action } => {
@@ -463,6 +862,17 @@ async fn main() -> anyhow::Result<()> {
                     let cfg = commands::load_config(&platform, config.as_deref()).await?;
                     commands::config_cmd::config_section(&cfg, &name);
                 }
+                ConfigCmd::Reload => {
+                    commands::config_cmd::config_reload().await?;
+                }
+                ConfigCmd::Validate {
+                    strict,
+                    json,
+                    config,
+                } => {
+                    commands::config_validate::config_validate(config.as_deref(), strict, json)
+                        .await?;
+                }
             }
         }
         Commands::Skills(args) => commands::skills_cmd::run(args).await?,
@@ -506,6 +916,7 @@ async fn main() -> anyhow::Result<()> {
                 std::process::exit(1);
             }
         },
+        Commands::Complete { kind } => commands::complete_cmd::run(kind).await?,
     }
 
     Ok(())
@@ -536,6 +947,8 @@ mod tests {
         assert!(sub_names.contains(&"gateway"));
         assert!(sub_names.contains(&"mcp-server"));
         assert!(sub_names.contains(&"status"));
+        assert!(sub_names.contains(&"doctor"));
+        assert!(sub_names.contains(&"logs"));
         assert!(sub_names.contains(&"channels"));
         assert!(sub_names.contains(&"cron"));
         assert!(sub_names.contains(&"sessions"));
@@ -681,6 +1094,60 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_tools_schema_parses() {
+        let result = Cli::try_parse_from(["weft", "tools", "schema", "read_file"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cli_tools_run_parses() {
+        let cli = Cli::try_parse_from([
+            "weft",
+            "tools",
+            "run",
+            "read_file",
+            "--params",
+            r#"{"path":"a.txt"}"#,
+            "--agent",
+            "reviewer",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Tools(args) => match args.action {
+                commands::tools_cmd::ToolsAction::Run {
+                    name,
+                    params,
+                    params_file,
+                    agent,
+                    ..
+                } => {
+                    assert_eq!(name, "read_file");
+                    assert_eq!(params.as_deref(), Some(r#"{"path":"a.txt"}"#));
+                    assert!(params_file.is_none());
+                    assert_eq!(agent.as_deref(), Some("reviewer"));
+                }
+                _ => panic!("expected Run"),
+            },
+            _ => panic!("expected Tools"),
+        }
+    }
+
+    #[test]
+    fn cli_tools_run_params_conflict() {
+        let result = Cli::try_parse_from([
+            "weft",
+            "tools",
+            "run",
+            "read_file",
+            "--params",
+            "{}",
+            "--params-file",
+            "p.json",
+        ]);
+        assert!(result.is_err());
+    }
+
     // ── Agents subcommand parsing ──────────────────────────────────
 
     #[test]
@@ -728,12 +1195,77 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_gateway_daemon_stop_and_status_parse() {
+        let cli = Cli::try_parse_from(["weft", "gateway", "--daemon"]).unwrap();
+        let Commands::Gateway(args) = cli.command else {
+            panic!("expected gateway");
+        };
+        assert!(args.daemon && args.action.is_none());
+
+        let cli = Cli::try_parse_from(["weft", "gateway", "stop", "--timeout", "5"]).unwrap();
+        let Commands::Gateway(args) = cli.command else {
+            panic!("expected gateway");
+        };
+        assert!(matches!(
+            args.action,
+            Some(commands::gateway::GatewayAction::Stop { timeout: 5 })
+        ));
+
+        let cli = Cli::try_parse_from(["weft", "gateway", "status"]).unwrap();
+        let Commands::Gateway(args) = cli.command else {
+            panic!("expected gateway");
+        };
+        assert!(matches!(
+            args.action,
+            Some(commands::gateway::GatewayAction::Status)
+        ));
+    }
+
     #[test]
     fn cli_status_detailed_flag() {
         let result = Cli::try_parse_from(["weft", "status", "--detailed"]);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_doctor_json_flag() {
+        let result = Cli::try_parse_from(["weft", "doctor", "--json"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cli_logs_filters_parse() {
+        let cli = Cli::try_parse_from([
+            "weft",
+            "logs",
+            "--follow",
+            "--session",
+            "telegram:1",
+            "--channel",
+            "telegram",
+            "--level",
+            "warn",
+            "--since",
+            "1h",
+            "--json",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Logs(args) => {
+                assert!(args.follow && args.json);
+                assert_eq!(args.session.as_deref(), Some("telegram:1"));
+                assert_eq!(args.channel.as_deref(), Some("telegram"));
+                assert_eq!(args.level, Some(tracing::Level::WARN));
+                assert!(args.since.is_some());
+                assert_eq!(args.lines, 50);
+            }
+            _ => panic!("expected logs"),
+        }
+        assert!(Cli::try_parse_from(["weft", "logs", "--level", "loud"]).is_err());
+        assert!(Cli::try_parse_from(["weft", "logs", "--since", "soon"]).is_err());
+    }
+
     #[test]
     fn cli_channels_status_parses() {
         let result = Cli::try_parse_from(["weft", "channels", "status"]);
@@ -747,6 +1279,58 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_channels_reload_parses() {
+        let cli = Cli::try_parse_from(["weft", "channels", "reload"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Channels {
+                action: ChannelsAction::Reload
+            }
+        ));
+    }
+
+    #[test]
+    fn cli_complete_is_hidden_and_parses() {
+        let cli = Cli::try_parse_from(["weft", "__complete", "cron-jobs"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Complete {
+                kind: commands::complete_cmd::CompleteKind::CronJobs
+            }
+        ));
+        assert!(Cli::try_parse_from(["weft", "__complete", "files"]).is_err());
+        let help = Cli::command().render_help().to_string();
+        assert!(!help.contains("__complete"));
+    }
+
+    #[test]
+    fn cli_config_validate_parses() {
+        let cli = Cli::try_parse_from([
+            "weft",
+            "config",
+            "validate",
+            "--strict",
+            "-c",
+            "/tmp/c.json",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Config {
+                action:
+                    ConfigCmd::Validate {
+                        strict,
+                        json,
+                        config,
+                    },
+            } => {
+                assert!(strict && !json);
+                assert_eq!(config.as_deref(), Some("/tmp/c.json"));
+            }
+            _ => panic!("expected config validate"),
+        }
+    }
+
     #[test]
     fn cli_cron_list_parses() {
         let result = Cli::try_parse_from(["weft", "cron", "list"]);
@@ -769,6 +1353,99 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_cron_add_once_and_interval_flags_parse() {
+        let with = |extra: &[&str]| {
+            let mut args = vec!["weft", "cron", "add", "--name", "n", "--prompt", "hi"];
+            args.extend_from_slice(extra);
+            Cli::try_parse_from(args)
+        };
+        let time = "2026-03-01T09:45:00Z";
+        assert!(with(&["--at", time]).is_ok());
+        assert!(with(&["--every", "10m"]).is_ok());
+        assert!(with(&["--every", "10m", "--until", time]).is_ok());
+        assert!(with(&[]).is_err());
+        assert!(with(&["--schedule", "0 9 * * *", "--every", "10m"]).is_err());
+        assert!(with(&["--at", time, "--every", "10m"]).is_err());
+        assert!(with(&["--at", time, "--until", time]).is_err());
+        assert!(with(&["--schedule", "0 9 * * *", "--until", time]).is_err());
+    }
+
+    #[test]
+    fn cli_cron_add_delivery_flags_parse() {
+        let base = [
+            "weft",
+            "cron",
+            "add",
+            "--name",
+            "digest",
+            "--schedule",
+            "0 8 * * *",
+            "--prompt",
+            "hi",
+        ];
+        let with = |extra: &[&'static str]| {
+            Cli::try_parse_from(base.iter().chain(extra).copied().collect::<Vec<_>>())
+        };
+        assert!(
+            with(&[
+                "--channel",
+                "telegram",
+                "--to",
+                "42",
+                "--quiet-hours",
+                "22:00-07:00",
+                "--timezone",
+                "+02:00",
+            ])
+            .is_ok()
+        );
+        // A channel needs a conversation and vice versa.
+        assert!(with(&["--channel", "telegram"]).is_err());
+        assert!(with(&["--to", "42"]).is_err());
+        assert!(with(&["--timezone", "+02:00"]).is_err());
+        assert!(with(&["--alert-channel", "telegram", "--alert-to", "42"]).is_ok());
+        assert!(with(&["--alert-channel", "telegram"]).is_err());
+        assert!(with(&["--alert-after", "5"]).is_err());
+    }
+
+    #[test]
+    fn cli_cron_history_parses() {
+        let result = Cli::try_parse_from(["weft", "cron", "history", "job-123", "--limit", "5"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cli_cron_add_timing_flags_parse() {
+        let base = [
+            "weft",
+            "cron",
+            "add",
+            "--name",
+            "standup",
+            "--schedule",
+            "0 9 * * *",
+            "--prompt",
+            "hi",
+        ];
+        let with = |extra: &[&'static str]| {
+            Cli::try_parse_from(base.iter().chain(extra).copied().collect::<Vec<_>>())
+        };
+        assert!(
+            with(&[
+                "--tz",
+                "America/New_York",
+                "--jitter",
+                "60",
+                "--catch-up",
+                "run_once",
+            ])
+            .is_ok()
+        );
+        assert!(with(&["--catch-up", "sometimes"]).is_err());
+        assert!(with(&["--jitter", "-5"]).is_err());
+    }
+
     #[test]
     fn cli_cron_remove_parses() {
         let result = Cli::try_parse_from(["weft", "cron", "remove", "job-123"]);
@@ -831,6 +1508,23 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(feature = "services")]
+    #[test]
+    fn cli_mcp_server_http() {
+        let cli = Cli::try_parse_from(["weft", "mcp-server", "--http"]).unwrap();
+        match cli.command {
+            Commands::McpServer(args) => assert_eq!(args.http, Some(None)),
+            _ => panic!("expected mcp-server"),
+        }
+        let cli = Cli::try_parse_from(["weft", "mcp-server", "--http", "0.0.0.0:9000"]).unwrap();
+        match cli.command {
+            Commands::McpServer(args) => {
+                assert_eq!(args.http, Some(Some("0.0.0.0:9000".into())));
+            }
+            _ => panic!("expected mcp-server"),
+        }
+    }
+
     #[cfg(feature = "services")]
     #[test]
     fn cli_mcp_server_verbose() {
@@ -861,12 +1555,67 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_workspace_create_with_template_and_vars() {
+        let result = Cli::try_parse_from([
+            "weft",
+            "workspace",
+            "create",
+            "papers",
+            "--template",
+            "research",
+            "--var",
+            "topic=rust",
+            "--var",
+            "owner=ana",
+        ]);
+        assert!(result.is_ok());
+
+        let result = Cli::try_parse_from(["weft", "workspace", "create", "p", "--var", "topic"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn cli_sessions_export_parses() {
+        let result = Cli::try_parse_from([
+            "weft",
+            "sessions",
+            "export",
+            "telegram:42",
+            "--format",
+            "json",
+            "--output",
+            "chat.json",
+            "--zip",
+        ]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cli_sessions_inspect_trace_parses() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(["weft", "sessions", "inspect", "cli:1"].iter().chain(args))
+        };
+        assert!(parse(&["--trace"]).is_ok());
+        assert!(parse(&["--trace", "--turn", "3"]).is_ok());
+        // --turn only selects a trace, and traces and tool listings are
+        // separate views.
+        assert!(parse(&["--turn", "3"]).is_err());
+        assert!(parse(&["--trace", "--tools"]).is_err());
+    }
+
     #[test]
     fn cli_workspace_list_parses() {
         let result = Cli::try_parse_from(["weft", "workspace", "list"]);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_tasks_list_parses() {
+        assert!(Cli::try_parse_from(["weft", "tasks", "list"]).is_ok());
+        assert!(Cli::try_parse_from(["weft", "tasks", "list", "--open"]).is_ok());
+    }
+
     #[test]
     fn cli_workspace_list_all() {
         let result = Cli::try_parse_from(["weft", "workspace", "list", "--all"]);
@@ -928,6 +1677,28 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn cli_workspace_backup_parses() {
+        let result = Cli::try_parse_from(["weft", "workspace", "backup"]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cli_workspace_restore_parses() {
+        let result = Cli::try_parse_from([
+            "weft",
+            "workspace",
+            "restore",
+            "--from",
+            "/tmp/clawft-backup.tar.gz",
+            "--dry-run",
+        ]);
+        assert!(result.is_ok());
+        // The archive is required.
+        let result = Cli::try_parse_from(["weft", "workspace", "restore"]);
+        assert!(result.is_err());
+    }
+
     // ── Help subcommand parsing ───────────────────────────────────
 
     #[test]
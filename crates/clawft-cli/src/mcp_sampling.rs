@@ -0,0 +1,532 @@
+//! MCP sampling through the configured LLM providers.
+//!
+//! An MCP server whose `sampling.approval` is not `deny` is told that
+//! clawft supports sampling, and may then ask it to run model calls
+//! (`sampling/createMessage`). [`ProviderSampler`] runs them through the
+//! provider pipeline once the server's approval policy lets it:
+//!
+//! - `auto` runs every request.
+//! - `ask` puts the request to the user in the conversation whose tool call
+//!   led to it ([`CallOrigins`]) and runs it on a yes. Requests with no
+//!   such conversation, or no answer in time, are refused.
+//!
+//! Each request's `maxTokens` is capped by `sampling.maxTokens`, and the
+//! server's total usage by `sampling.tokenBudget`. A hint in the server's
+//! model preferences selects the first `sampling.models` entry containing
+//! it; without a match the pipeline's router picks the model. Stop
+//! sequences and `includeContext` are not supported and are ignored.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::info;
+
+use clawft_core::agent::questions::PendingQuestions;
+use clawft_core::pipeline::traits::{ChatRequest, LlmMessage, PipelineRegistry};
+use clawft_llm::{ContentPart, ImagePart};
+use clawft_services::mcp::prompts::PromptRole;
+use clawft_services::mcp::sampling::{
+    CreateMessageRequest, CreateMessageResult, ModelPreferences, SamplingContent, SamplingError,
+    SamplingHandler,
+};
+use clawft_types::config::{McpSamplingConfig, SamplingApproval};
+use clawft_types::provider::{ContentBlock, StopReason};
+
+/// Longest excerpt of the prompt shown when asking for approval, in chars.
+const EXCERPT_CHARS: usize = 300;
+
+/// Sessions with a tool call in flight on one MCP server, most recent
+/// last. A sampling request arriving meanwhile is taken to come from the
+/// latest one.
+#[derive(Default)]
+pub struct CallOrigins {
+    sessions: Mutex<Vec<String>>,
+}
+
+impl CallOrigins {
+    /// Record a call made from session `session_key` until the returned
+    /// guard is dropped.
+    pub fn enter(self: &Arc<Self>, session_key: String) -> OriginGuard {
+        self.lock().push(session_key.clone());
+        OriginGuard {
+            origins: self.clone(),
+            session_key,
+        }
+    }
+
+    /// Session of the most recent call still in flight.
+    pub fn latest(&self) -> Option<String> {
+        self.lock().last().cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A call recorded in [`CallOrigins`]; removes it when dropped.
+pub struct OriginGuard {
+    origins: Arc<CallOrigins>,
+    session_key: String,
+}
+
+impl Drop for OriginGuard {
+    fn drop(&mut self) {
+        let mut sessions = self.origins.lock();
+        if let Some(at) = sessions.iter().rposition(|key| *key == self.session_key) {
+            sessions.remove(at);
+        }
+    }
+}
+
+/// Answers one MCP server's sampling requests with the provider pipeline.
+pub struct ProviderSampler {
+    server: String,
+    config: McpSamplingConfig,
+    pipeline: Arc<PipelineRegistry>,
+    questions: Option<Arc<PendingQuestions>>,
+    origins: Arc<CallOrigins>,
+    /// Tokens used so far, against `config.token_budget`.
+    spent: AtomicU64,
+}
+
+impl ProviderSampler {
+    /// Create a sampler for server `server`. Without `questions`, the `ask`
+    /// policy refuses every request.
+    pub fn new(
+        server: impl Into<String>,
+        config: McpSamplingConfig,
+        pipeline: Arc<PipelineRegistry>,
+        questions: Option<Arc<PendingQuestions>>,
+        origins: Arc<CallOrigins>,
+    ) -> Self {
+        Self {
+            server: server.into(),
+            config,
+            pipeline,
+            questions,
+            origins,
+            spent: AtomicU64::new(0),
+        }
+    }
+
+    /// Tokens the server has used so far.
+    pub fn spent(&self) -> u64 {
+        self.spent.load(Ordering::Relaxed)
+    }
+
+    /// The `maxTokens` to grant a request asking for `requested`.
+    fn grant(&self, requested: u32) -> Result<u32, SamplingError> {
+        let mut granted = requested.min(self.config.max_tokens);
+        if self.config.token_budget > 0 {
+            let left = self.config.token_budget.saturating_sub(self.spent());
+            if left == 0 {
+                return Err(SamplingError::BudgetExhausted(format!(
+                    "MCP server '{}' used its {} tokens",
+                    self.server, self.config.token_budget
+                )));
+            }
+            granted = granted.min(u32::try_from(left).unwrap_or(u32::MAX));
+        }
+        Ok(granted.max(1))
+    }
+
+    /// Apply the approval policy to `request`.
+    async fn approve(
+        &self,
+        request: &CreateMessageRequest,
+        max_tokens: u32,
+    ) -> Result<(), SamplingError> {
+        match self.config.approval {
+            SamplingApproval::Auto => Ok(()),
+            SamplingApproval::Deny => Err(SamplingError::Rejected(format!(
+                "sampling is disabled for MCP server '{}'",
+                self.server
+            ))),
+            SamplingApproval::Ask => {
+                let (Some(questions), Some(session)) = (&self.questions, self.origins.latest())
+                else {
+                    return Err(SamplingError::Rejected(
+                        "no conversation to ask the user for approval in".into(),
+                    ));
+                };
+                let question = approval_question(&self.server, request, max_tokens);
+                let timeout = Duration::from_secs(self.config.ask_timeout_secs);
+                match questions.ask(&session, &question, timeout).await {
+                    Some(answer) if is_approval(&answer) => Ok(()),
+                    Some(_) => Err(SamplingError::Rejected("the user declined".into())),
+                    None => Err(SamplingError::Rejected("no answer from the user".into())),
+                }
+            }
+        }
+    }
+
+    /// The configured model the server's hints select, if any.
+    fn model_for(&self, preferences: Option<&ModelPreferences>) -> Option<String> {
+        let hints = preferences?.hints.iter().filter_map(|h| h.name.as_deref());
+        for hint in hints {
+            let hint = hint.to_ascii_lowercase();
+            if let Some(model) = self
+                .config
+                .models
+                .iter()
+                .find(|model| model.to_ascii_lowercase().contains(&hint))
+            {
+                return Some(model.clone());
+            }
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for ProviderSampler {
+    async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> Result<CreateMessageResult, SamplingError> {
+        let max_tokens = self.grant(request.max_tokens)?;
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system) = request.system_prompt.as_deref().filter(|s| !s.is_empty()) {
+            messages.push(message("system", system.to_string(), None));
+        }
+        for m in &request.messages {
+            messages.push(to_llm_message(m.role, &m.content)?);
+        }
+        self.approve(&request, max_tokens).await?;
+
+        let model = self.model_for(request.model_preferences.as_ref());
+        let chat = ChatRequest {
+            messages,
+            tools: vec![],
+            model: model.clone(),
+            max_tokens: Some(i32::try_from(max_tokens).unwrap_or(i32::MAX)),
+            temperature: request.temperature,
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let response = self
+            .pipeline
+            .complete(&chat)
+            .await
+            .map_err(|e| SamplingError::Failed(e.to_string()))?;
+
+        let used = u64::from(response.usage.input_tokens) + u64::from(response.usage.output_tokens);
+        self.spent.fetch_add(used, Ordering::Relaxed);
+        let model = response
+            .metadata
+            .get("model")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or(model)
+            .unwrap_or_default();
+        info!(server = %self.server, %model, tokens = used, "ran MCP sampling request");
+
+        let text = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("");
+        let stop_reason = match response.stop_reason {
+            StopReason::MaxTokens => "maxTokens",
+            StopReason::StopSequence => "stopSequence",
+            _ => "endTurn",
+        };
+        Ok(CreateMessageResult::text(
+            text,
+            model,
+            Some(stop_reason.into()),
+        ))
+    }
+}
+
+/// A pipeline message with optional multimodal `parts`.
+fn message(role: &str, content: String, parts: Option<Vec<ContentPart>>) -> LlmMessage {
+    LlmMessage {
+        role: role.into(),
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        parts,
+        cache: false,
+    }
+}
+
+/// Convert one sampling message. Images are sent as inline parts; audio is
+/// not supported.
+fn to_llm_message(
+    role: PromptRole,
+    content: &SamplingContent,
+) -> Result<LlmMessage, SamplingError> {
+    let role = match role {
+        PromptRole::User => "user",
+        PromptRole::Assistant => "assistant",
+    };
+    match content {
+        SamplingContent::Text { text } => Ok(message(role, text.clone(), None)),
+        SamplingContent::Image { data, mime_type } => Ok(message(
+            role,
+            String::new(),
+            Some(vec![ContentPart::Image(ImagePart::base64(
+                mime_type.clone(),
+                data.clone(),
+            ))]),
+        )),
+        SamplingContent::Audio { .. } => Err(SamplingError::Invalid(
+            "audio content is not supported".into(),
+        )),
+    }
+}
+
+/// The question put to the user under the `ask` policy.
+fn approval_question(server: &str, request: &CreateMessageRequest, max_tokens: u32) -> String {
+    let prompt = request
+        .messages
+        .iter()
+        .rev()
+        .find_map(|m| m.content.as_text())
+        .unwrap_or("(no text)");
+    let mut excerpt: String = prompt.chars().take(EXCERPT_CHARS).collect();
+    if excerpt.len() < prompt.len() {
+        excerpt.push_str("...");
+    }
+    format!(
+        "MCP server '{server}' wants to run a model call (up to {max_tokens} tokens) \
+         with this prompt:\n\n{excerpt}\n\nReply \"yes\" to allow it; anything else declines."
+    )
+}
+
+/// Whether the user's answer approves the request.
+fn is_approval(answer: &str) -> bool {
+    let word = answer
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_ascii_lowercase();
+    matches!(word.as_str(), "yes" | "y" | "ok" | "allow" | "approve")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+    use clawft_core::bus::MessageBus;
+    use clawft_core::pipeline::assembler::TokenBudgetAssembler;
+    use clawft_core::pipeline::classifier::KeywordClassifier;
+    use clawft_core::pipeline::learner::NoopLearner;
+    use clawft_core::pipeline::router::StaticRouter;
+    use clawft_core::pipeline::scorer::NoopScorer;
+    use clawft_core::pipeline::traits::{LlmTransport, Pipeline, TransportRequest};
+    use clawft_services::mcp::sampling::{ModelHint, SamplingMessage};
+    use clawft_types::event::InboundMessage;
+    use clawft_types::provider::{LlmResponse, Usage};
+
+    /// Records each request and replies "sampled" using 10 + 5 tokens.
+    #[derive(Default)]
+    struct Recording {
+        requests: Mutex<Vec<TransportRequest>>,
+    }
+
+    #[async_trait]
+    impl LlmTransport for Recording {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(LlmResponse {
+                id: "s".into(),
+                content: vec![ContentBlock::Text {
+                    text: "sampled".into(),
+                }],
+                stop_reason: StopReason::MaxTokens,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    ..Default::default()
+                },
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    fn pipeline(transport: Arc<Recording>) -> Arc<PipelineRegistry> {
+        Arc::new(PipelineRegistry::new(Pipeline {
+            classifier: Arc::new(KeywordClassifier::new()),
+            router: Arc::new(StaticRouter::new("test".into(), "test-model".into())),
+            assembler: Arc::new(TokenBudgetAssembler::new(4096)),
+            transport,
+            scorer: Arc::new(NoopScorer::new()),
+            learner: Arc::new(NoopLearner::new()),
+        }))
+    }
+
+    fn sampler(
+        config: McpSamplingConfig,
+        transport: Arc<Recording>,
+        questions: Option<Arc<PendingQuestions>>,
+        origins: Arc<CallOrigins>,
+    ) -> ProviderSampler {
+        ProviderSampler::new("docs", config, pipeline(transport), questions, origins)
+    }
+
+    fn request(text: &str, max_tokens: u32) -> CreateMessageRequest {
+        CreateMessageRequest {
+            messages: vec![SamplingMessage {
+                role: PromptRole::User,
+                content: SamplingContent::Text { text: text.into() },
+            }],
+            model_preferences: None,
+            system_prompt: Some("Be brief.".into()),
+            temperature: None,
+            max_tokens,
+            stop_sequences: vec![],
+        }
+    }
+
+    fn inbound(content: &str) -> InboundMessage {
+        InboundMessage {
+            channel: "telegram".into(),
+            sender_id: "u".into(),
+            chat_id: "42".into(),
+            content: content.into(),
+            timestamp: Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn auto_runs_requests_within_the_caps() {
+        let transport = Arc::new(Recording::default());
+        let config = McpSamplingConfig {
+            approval: SamplingApproval::Auto,
+            max_tokens: 200,
+            ..Default::default()
+        };
+        let sampler = sampler(config, transport.clone(), None, Arc::default());
+
+        let result = sampler
+            .create_message(request("Summarize this.", 1000))
+            .await
+            .unwrap();
+        assert_eq!(result.content.as_text(), Some("sampled"));
+        assert_eq!(result.model, "test-model");
+        assert_eq!(result.stop_reason.as_deref(), Some("maxTokens"));
+        assert_eq!(sampler.spent(), 15);
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[0].max_tokens, Some(200));
+        assert_eq!(requests[0].messages[0].role, "system");
+        assert_eq!(requests[0].messages[1].content, "Summarize this.");
+    }
+
+    #[tokio::test]
+    async fn deny_refuses_without_calling_the_model() {
+        let transport = Arc::new(Recording::default());
+        let sampler = sampler(
+            McpSamplingConfig::default(),
+            transport.clone(),
+            None,
+            Arc::default(),
+        );
+        let err = sampler.create_message(request("hi", 10)).await.unwrap_err();
+        assert!(matches!(err, SamplingError::Rejected(_)));
+        assert!(transport.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn budget_stops_further_requests() {
+        let transport = Arc::new(Recording::default());
+        let config = McpSamplingConfig {
+            approval: SamplingApproval::Auto,
+            token_budget: 15,
+            ..Default::default()
+        };
+        let sampler = sampler(config, transport.clone(), None, Arc::default());
+
+        sampler.create_message(request("one", 100)).await.unwrap();
+        assert_eq!(transport.requests.lock().unwrap()[0].max_tokens, Some(15));
+        let err = sampler
+            .create_message(request("two", 100))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SamplingError::BudgetExhausted(_)));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ask_puts_the_request_to_the_calling_conversation() {
+        let bus = Arc::new(MessageBus::new());
+        let questions = Arc::new(PendingQuestions::new(bus.clone()));
+        let origins = Arc::new(CallOrigins::default());
+        let transport = Arc::new(Recording::default());
+        let config = McpSamplingConfig {
+            approval: SamplingApproval::Ask,
+            ..Default::default()
+        };
+        let sampler = Arc::new(sampler(
+            config,
+            transport.clone(),
+            Some(questions.clone()),
+            origins.clone(),
+        ));
+
+        // No tool call in flight: nobody to ask.
+        let err = sampler.create_message(request("hi", 10)).await.unwrap_err();
+        assert!(matches!(err, SamplingError::Rejected(_)));
+
+        let _call = origins.enter("telegram:42".into());
+        for (answer, approved) in [("Yes.", true), ("no", false)] {
+            let asking = sampler.clone();
+            let run =
+                tokio::spawn(async move { asking.create_message(request("Summarize", 10)).await });
+            let question = bus.consume_outbound().await.unwrap();
+            assert_eq!(question.chat_id, "42");
+            assert!(question.content.contains("MCP server 'docs'"));
+            assert!(question.content.contains("Summarize"));
+            assert!(questions.answer(&inbound(answer)));
+            assert_eq!(run.await.unwrap().is_ok(), approved);
+        }
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn hints_select_a_configured_model() {
+        let config = McpSamplingConfig {
+            models: vec!["openai/gpt-4o".into(), "anthropic/claude-sonnet-4".into()],
+            ..Default::default()
+        };
+        let sampler = sampler(config, Arc::default(), None, Arc::default());
+        let prefs = |name: &str| ModelPreferences {
+            hints: vec![ModelHint {
+                name: Some(name.into()),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            sampler.model_for(Some(&prefs("Sonnet"))).as_deref(),
+            Some("anthropic/claude-sonnet-4")
+        );
+        assert_eq!(sampler.model_for(Some(&prefs("gemini"))), None);
+        assert_eq!(sampler.model_for(None), None);
+    }
+
+    #[test]
+    fn origins_track_the_latest_call_in_flight() {
+        let origins = Arc::new(CallOrigins::default());
+        let first = origins.enter("slack:C1".into());
+        let second = origins.enter("telegram:42".into());
+        assert_eq!(origins.latest().as_deref(), Some("telegram:42"));
+        drop(second);
+        assert_eq!(origins.latest().as_deref(), Some("slack:C1"));
+        drop(first);
+        assert_eq!(origins.latest(), None);
+    }
+}
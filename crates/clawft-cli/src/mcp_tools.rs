@@ -4,11 +4,40 @@
 //! [`Tool`](clawft_core::tools::registry::Tool) trait, allowing MCP
 //! tools to be invoked by the agent loop just like built-in tools.
 //!
+//! Each server's `expose`, `prefix`, `descriptions` and `maxTools`
+//! settings decide which of its tools are registered and under what name.
+//! The tools go into the registry's
+//! [`DynamicTools`](clawft_core::tools::registry::DynamicTools) under the
+//! server's name, and are listed and filtered again whenever the server
+//! sends `notifications/tools/list_changed`.
+//!
+//! Sessions are [supervised](clawft_services::mcp::supervisor): when a
+//! server's process dies or its HTTP endpoint stops answering, it is
+//! restarted or reconnected with backoff, and its tools are listed again
+//! once it is back. Calls made meanwhile fail with the retryable
+//! [`ToolError::Unavailable`].
+//!
+//! Calls go through the server's [`ServerHealth`], which enforces its
+//! `callTimeoutSecs`, `maxConcurrentCalls` and `circuit` settings: calls
+//! that run too long fail with [`ToolError::Timeout`], and while a failing
+//! server's circuit is open its tools fail at once with
+//! [`ToolError::Unavailable`]. [`register_mcp_tools`] returns the servers'
+//! [`McpHealthRegistry`] for status reporting.
+//!
+//! Servers allowed to sample (`sampling.approval` other than `deny`) get a
+//! [`ProviderSampler`](crate::mcp_sampling::ProviderSampler), and the
+//! wrappers record which session each call comes from so the sampler
+//! knows whom to ask.
+//!
 //! Requires the `services` feature. When the feature is off, a no-op stub
 //! is provided for [`register_mcp_tools`].
 
 #[cfg(feature = "services")]
-use std::sync::Arc;
+use std::collections::HashSet;
+#[cfg(feature = "services")]
+use std::sync::{Arc, Weak};
+#[cfg(feature = "services")]
+use std::time::Duration;
 
 #[cfg(feature = "services")]
 use async_trait::async_trait;
@@ -16,13 +45,29 @@ use async_trait::async_trait;
 use tracing::warn;
 
 #[cfg(feature = "services")]
-use clawft_core::tools::registry::{Tool, ToolError};
+use crate::mcp_sampling::{CallOrigins, ProviderSampler};
+#[cfg(feature = "services")]
+use clawft_core::agent::questions::PendingQuestions;
+#[cfg(feature = "services")]
+use clawft_core::security::validate_mcp_tool_name_strict;
+#[cfg(feature = "services")]
+use clawft_core::tools::registry::{DynamicTools, Tool, ToolError, matches_any_pattern};
+#[cfg(feature = "services")]
+use clawft_services::error::ServiceError;
 #[cfg(feature = "services")]
-use clawft_services::mcp::transport::{HttpTransport, StdioTransport};
+use clawft_services::mcp::ToolDefinition;
 #[cfg(feature = "services")]
-use clawft_services::mcp::{McpSession, ToolDefinition};
+use clawft_services::mcp::health::{CallLimits, McpHealthRegistry, ServerHealth};
 #[cfg(feature = "services")]
-use clawft_types::config::MCPServerConfig;
+use clawft_services::mcp::sampling::SamplingHandler;
+#[cfg(feature = "services")]
+use clawft_services::mcp::supervisor::{
+    McpConnector, ReconnectPolicy, SessionEvent, SupervisedSession,
+};
+#[cfg(feature = "services")]
+use clawft_services::mcp::transport::{HttpTransport, McpTransport, StdioTransport};
+#[cfg(feature = "services")]
+use clawft_types::config::{MCPServerConfig, SamplingApproval};
 
 // -- All MCP tool types and functions below are gated behind the `services` feature. --
 
@@ -88,32 +133,57 @@ fn try_extract_text_blocks(raw: &serde_json::Value) -> Option<String> {
 #[cfg(feature = "services")]
 /// Wraps an MCP tool definition for use in the `ToolRegistry`.
 ///
-/// Each wrapper holds a reference to the shared [`McpSession`] for its
-/// server and delegates execution to [`McpSession::call_tool`].
-/// The tool name is prefixed with `{server_name}__` to avoid collisions
-/// when multiple MCP servers expose tools with the same base name.
+/// Each wrapper holds a reference to the shared [`SupervisedSession`] for
+/// its server and delegates execution to [`SupervisedSession::call_tool`],
+/// within the limits of the server's shared [`ServerHealth`].
+/// The tool name is prefixed (`{server_name}__` by default) to avoid
+/// collisions when multiple MCP servers expose tools with the same base
+/// name.
 pub struct McpToolWrapper {
-    /// Namespaced tool name: `"{server}__{tool}"`.
+    /// Namespaced tool name, e.g. `"{server}__{tool}"`.
     full_name: String,
     /// The tool definition from the MCP server.
     tool_def: ToolDefinition,
-    /// Shared session (with completed handshake) for this MCP server.
-    session: Arc<McpSession>,
+    /// Shared, reconnecting session for this MCP server.
+    session: Arc<SupervisedSession>,
+    /// Sessions with a call in flight on this server, for sampling.
+    origins: Arc<CallOrigins>,
+    /// Call limits and health of this server.
+    health: Arc<ServerHealth>,
 }
 
 #[cfg(feature = "services")]
 impl McpToolWrapper {
-    /// Create a new wrapper.
-    ///
-    /// The tool will be registered as `"{server_name}__{tool_def.name}"`.
-    pub fn new(server_name: &str, tool_def: ToolDefinition, session: Arc<McpSession>) -> Self {
-        let full_name = format!("{}__{}", server_name, tool_def.name);
+    /// Create a wrapper registered as `full_name` (see [`expose_tools`]),
+    /// with the default call limits.
+    pub fn named(
+        full_name: String,
+        tool_def: ToolDefinition,
+        session: Arc<SupervisedSession>,
+    ) -> Self {
+        let limits = CallLimits::from_config(&MCPServerConfig::default());
+        let health = Arc::new(ServerHealth::new(session.server(), limits));
         Self {
             full_name,
             tool_def,
             session,
+            origins: Arc::default(),
+            health,
         }
     }
+
+    /// Run calls within `health`'s limits, shared with the server's other
+    /// tools.
+    pub fn with_health(mut self, health: Arc<ServerHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Record the calling session of each call in `origins`.
+    pub fn with_origins(mut self, origins: Arc<CallOrigins>) -> Self {
+        self.origins = origins;
+        self
+    }
 }
 
 #[cfg(feature = "services")]
@@ -132,11 +202,16 @@ impl Tool for McpToolWrapper {
     }
 
     async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let _origin = clawft_core::runtime::session_key().map(|key| self.origins.enter(key));
         let raw = self
-            .session
-            .call_tool(&self.tool_def.name, args)
+            .health
+            .call(self.session.call_tool(&self.tool_def.name, args))
             .await
-            .map_err(|e| ToolError::ExecutionFailed(e.to_string()))?;
+            .map_err(|e| match e {
+                ServiceError::McpUnavailable(reason) => ToolError::Unavailable(reason),
+                ServiceError::McpTimeout(secs) => ToolError::Timeout(secs),
+                e => ToolError::ExecutionFailed(e.to_string()),
+            })?;
 
         // Extract text from MCP content blocks.
         match extract_mcp_tool_result(&raw) {
@@ -147,44 +222,224 @@ impl Tool for McpToolWrapper {
 }
 
 #[cfg(feature = "services")]
-/// Create an MCP session from server configuration.
+/// Apply a server's `expose`, `prefix`, `descriptions` and `max_tools`
+/// settings to the tools it lists.
 ///
-/// Chooses the transport based on config fields:
-/// - If `command` is non-empty, spawns a child process via [`StdioTransport`].
-/// - If `url` is non-empty (and command is empty), uses [`HttpTransport`].
-/// - If both are empty, returns `None` with a warning log.
+/// Returns the tools to register with their registered names, with
+/// descriptions replaced where configured. A tool whose name lacks the
+/// `__` separator (see [`validate_mcp_tool_name_strict`]), or collides
+/// with a name in `taken` or an earlier tool of the same server, is
+/// skipped with a warning.
+pub fn expose_tools(
+    server_name: &str,
+    config: &MCPServerConfig,
+    tools: Vec<ToolDefinition>,
+    taken: &HashSet<String>,
+) -> Vec<(String, ToolDefinition)> {
+    let advertised = tools.len();
+    let prefix = config
+        .prefix
+        .clone()
+        .unwrap_or_else(|| format!("{server_name}__"));
+    let mut seen = HashSet::new();
+    let mut exposed = Vec::new();
+    for mut tool in tools {
+        let allowed =
+            config.expose.allow.is_empty() || matches_any_pattern(&tool.name, &config.expose.allow);
+        if !allowed || matches_any_pattern(&tool.name, &config.expose.deny) {
+            continue;
+        }
+        let full_name = format!("{prefix}{}", tool.name);
+        if let Err(e) = validate_mcp_tool_name_strict(&full_name) {
+            warn!(server = %server_name, tool = %tool.name, error = %e, "skipping MCP tool");
+            continue;
+        }
+        if taken.contains(&full_name) || !seen.insert(full_name.clone()) {
+            warn!(
+                server = %server_name,
+                tool = %full_name,
+                "skipping MCP tool: name already registered"
+            );
+            continue;
+        }
+        if let Some(description) = config.descriptions.get(&tool.name) {
+            tool.description = description.clone();
+        }
+        exposed.push((full_name, tool));
+    }
+
+    if config.max_tools > 0 && exposed.len() > config.max_tools {
+        warn!(
+            server = %server_name,
+            advertised,
+            exposed = exposed.len(),
+            max_tools = config.max_tools,
+            "MCP server has more tools than maxTools; registering the first ones only"
+        );
+        exposed.truncate(config.max_tools);
+    }
+    exposed
+}
+
+#[cfg(feature = "services")]
+/// List a server's tools and replace its group in `dynamic` with the ones
+/// [`expose_tools`] lets through. `builtin` holds the names of tools not
+/// from MCP servers; the wrappers record their callers in `origins` and
+/// run their calls through `health`.
+///
+/// Returns the number of tools registered.
+pub async fn sync_mcp_tools(
+    server_name: &str,
+    config: &MCPServerConfig,
+    session: &Arc<SupervisedSession>,
+    origins: &Arc<CallOrigins>,
+    health: &Arc<ServerHealth>,
+    dynamic: &DynamicTools,
+    builtin: &HashSet<String>,
+) -> clawft_services::error::Result<usize> {
+    let tools = session.list_tools().await?;
+    let mut taken = dynamic.names_except(server_name);
+    taken.extend(builtin.iter().cloned());
+    let wrappers: Vec<Arc<dyn Tool>> = expose_tools(server_name, config, tools, &taken)
+        .into_iter()
+        .map(|(name, tool)| {
+            Arc::new(
+                McpToolWrapper::named(name, tool, session.clone())
+                    .with_origins(origins.clone())
+                    .with_health(health.clone()),
+            ) as Arc<dyn Tool>
+        })
+        .collect();
+    let count = wrappers.len();
+    dynamic.replace(server_name, wrappers);
+    Ok(count)
+}
+
+#[cfg(feature = "services")]
+/// Run [`sync_mcp_tools`] again whenever the server sends
+/// `notifications/tools/list_changed`, and after it reconnects.
 ///
-/// After creating the transport, performs the MCP initialize handshake
-/// via [`McpSession::connect`] so that subsequent `tools/list` and
-/// `tools/call` requests are accepted by the server.
-pub async fn create_mcp_client(server_name: &str, config: &MCPServerConfig) -> Option<McpSession> {
-    let transport: Box<dyn clawft_services::mcp::transport::McpTransport> =
+/// The task ends once the session is dropped.
+pub fn watch_mcp_tools(
+    server_name: String,
+    config: MCPServerConfig,
+    session: &Arc<SupervisedSession>,
+    origins: Arc<CallOrigins>,
+    health: Arc<ServerHealth>,
+    dynamic: DynamicTools,
+    builtin: Arc<HashSet<String>>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut events = session.subscribe();
+    let session: Weak<SupervisedSession> = Arc::downgrade(session);
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(SessionEvent::Notification(n))
+                    if n.method == "notifications/tools/list_changed" => {}
+                // The restarted server may offer different tools.
+                Ok(SessionEvent::Reconnected) => {}
+                // Missed notifications may have included a change.
+                Err(RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(RecvError::Closed) => break,
+            }
+            let Some(session) = session.upgrade() else {
+                break;
+            };
+            match sync_mcp_tools(
+                &server_name,
+                &config,
+                &session,
+                &origins,
+                &health,
+                &dynamic,
+                &builtin,
+            )
+            .await
+            {
+                Ok(count) => tracing::info!(
+                    server = %server_name,
+                    tools = count,
+                    "MCP tool list changed, tools registered again"
+                ),
+                Err(e) => warn!(
+                    server = %server_name,
+                    error = %e,
+                    "failed to list MCP tools after change"
+                ),
+            }
+        }
+    });
+}
+
+#[cfg(feature = "services")]
+/// Opens the transport a server's configuration asks for: a child process
+/// via [`StdioTransport`] when `command` is set, else [`HttpTransport`].
+struct ConfigConnector {
+    config: MCPServerConfig,
+}
+
+#[cfg(feature = "services")]
+#[async_trait]
+impl McpConnector for ConfigConnector {
+    async fn connect(&self) -> clawft_services::error::Result<Box<dyn McpTransport>> {
+        let config = &self.config;
         if !config.command.is_empty() {
-            match StdioTransport::new(&config.command, &config.args, &config.env).await {
-                Ok(transport) => Box::new(transport),
-                Err(e) => {
-                    warn!(
-                        server = %server_name,
-                        error = %e,
-                        "failed to spawn MCP stdio transport"
-                    );
-                    return None;
-                }
+            let mut transport =
+                StdioTransport::new(&config.command, &config.args, &config.env).await?;
+            if config.call_timeout_secs > 0 {
+                transport =
+                    transport.with_request_timeout(Duration::from_secs(config.call_timeout_secs));
             }
-        } else if !config.url.is_empty() {
-            Box::new(HttpTransport::new(config.url.clone()))
+            Ok(Box::new(transport))
         } else {
-            warn!(server = %server_name, "MCP server has no command or URL, skipping");
-            return None;
-        };
+            Ok(Box::new(HttpTransport::new(config.url.clone())))
+        }
+    }
+}
 
-    match McpSession::connect(transport).await {
+#[cfg(feature = "services")]
+/// Create a supervised MCP session from server configuration.
+///
+/// Chooses the transport based on config fields:
+/// - If `command` is non-empty, spawns a child process via [`StdioTransport`].
+/// - If `url` is non-empty (and command is empty), uses [`HttpTransport`].
+/// - If both are empty, returns `None` with a warning log.
+///
+/// After creating the transport, performs the MCP initialize handshake so
+/// that subsequent `tools/list` and `tools/call` requests are accepted by
+/// the server. If the connection is later lost, the session restarts the
+/// server (or reconnects) and handshakes again. With `sampling`, the
+/// session advertises the sampling capability and answers the server's
+/// `sampling/createMessage` requests with it.
+pub async fn create_mcp_client(
+    server_name: &str,
+    config: &MCPServerConfig,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+) -> Option<SupervisedSession> {
+    if config.command.is_empty() && config.url.is_empty() {
+        warn!(server = %server_name, "MCP server has no command or URL, skipping");
+        return None;
+    }
+    let connector = ConfigConnector {
+        config: config.clone(),
+    };
+    match SupervisedSession::connect_with_sampling(
+        server_name,
+        Box::new(connector),
+        ReconnectPolicy::default(),
+        sampling,
+    )
+    .await
+    {
         Ok(session) => Some(session),
         Err(e) => {
             warn!(
                 server = %server_name,
                 error = %e,
-                "MCP initialize handshake failed"
+                "failed to connect to MCP server"
             );
             None
         }
@@ -196,23 +451,52 @@ pub async fn create_mcp_client(server_name: &str, config: &MCPServerConfig) -> O
 ///
 /// For each MCP server in the config:
 /// - Creates a client session (always).
-/// - If `internal_only` is false, lists tools and registers them in the registry.
+/// - If `internal_only` is false, lists tools and registers those its
+///   exposure settings allow (see [`expose_tools`]), keeping them in sync
+///   with the server's tool list (see [`watch_mcp_tools`]).
 /// - If `internal_only` is true, the session is created but tools are NOT
 ///   registered (the server is available for internal use only).
 ///
-/// Returns a map of all sessions (both internal and external) keyed by
-/// server name. Callers can use these sessions for internal MCP calls.
+/// Servers whose `sampling.approval` is not `deny` may run model calls
+/// through the providers configured in `config`; under `ask`, approval is
+/// sought through `questions` (refused without it).
+///
+/// Returns the registry of all sessions (both internal and external) with
+/// their health. Callers can use these sessions for internal MCP calls.
 pub async fn register_mcp_tools(
     config: &clawft_types::config::Config,
     registry: &mut clawft_core::tools::registry::ToolRegistry,
-) -> std::collections::HashMap<String, Arc<McpSession>> {
-    let mut sessions = std::collections::HashMap::new();
+    questions: Option<Arc<PendingQuestions>>,
+) -> McpServers {
+    let servers = Arc::new(McpHealthRegistry::default());
+    let builtin: Arc<HashSet<String>> = Arc::new(registry.list().into_iter().collect());
+    let dynamic = registry.dynamic().clone();
+    let mut pipeline = None;
 
     for (server_name, server_config) in &config.tools.mcp_servers {
-        match create_mcp_client(server_name, server_config).await {
+        let origins = Arc::new(CallOrigins::default());
+        let sampling = (server_config.sampling.approval != SamplingApproval::Deny).then(|| {
+            let pipeline = pipeline
+                .get_or_insert_with(|| {
+                    Arc::new(clawft_core::bootstrap::build_live_pipeline(config))
+                })
+                .clone();
+            Arc::new(ProviderSampler::new(
+                server_name.clone(),
+                server_config.sampling.clone(),
+                pipeline,
+                questions.clone(),
+                origins.clone(),
+            )) as Arc<dyn SamplingHandler>
+        });
+        match create_mcp_client(server_name, server_config, sampling).await {
             Some(session) => {
                 let session = Arc::new(session);
-                sessions.insert(server_name.clone(), session.clone());
+                let health = Arc::new(ServerHealth::new(
+                    server_name.clone(),
+                    CallLimits::from_config(server_config),
+                ));
+                servers.register(health.clone(), session.clone());
 
                 if server_config.internal_only {
                     tracing::info!(
@@ -222,19 +506,32 @@ pub async fn register_mcp_tools(
                     continue;
                 }
 
-                match session.list_tools().await {
-                    Ok(tools) => {
-                        let count = tools.len();
-                        for tool_def in tools {
-                            let wrapper =
-                                McpToolWrapper::new(server_name, tool_def, session.clone());
-                            registry.register(Arc::new(wrapper));
-                        }
+                match sync_mcp_tools(
+                    server_name,
+                    server_config,
+                    &session,
+                    &origins,
+                    &health,
+                    &dynamic,
+                    &builtin,
+                )
+                .await
+                {
+                    Ok(count) => {
                         tracing::info!(
                             server = %server_name,
                             tools = count,
                             "registered MCP tools"
                         );
+                        watch_mcp_tools(
+                            server_name.clone(),
+                            server_config.clone(),
+                            &session,
+                            origins,
+                            health,
+                            dynamic.clone(),
+                            builtin.clone(),
+                        );
                     }
                     Err(e) => {
                         tracing::warn!(
@@ -251,19 +548,25 @@ pub async fn register_mcp_tools(
         }
     }
 
-    sessions
+    servers
 }
 
+/// The MCP servers [`register_mcp_tools`] connected, with their health.
+#[cfg(feature = "services")]
+pub type McpServers = Arc<McpHealthRegistry>;
+
+/// Without the `services` feature there are no MCP servers.
+#[cfg(not(feature = "services"))]
+pub type McpServers = ();
+
 /// No-op: MCP tools require the `services` feature.
-///
-/// Returns an empty sessions map.
 #[cfg(not(feature = "services"))]
 pub async fn register_mcp_tools(
     _config: &clawft_types::config::Config,
     _registry: &mut clawft_core::tools::registry::ToolRegistry,
-) -> std::collections::HashMap<String, std::sync::Arc<()>> {
+    _questions: Option<std::sync::Arc<clawft_core::agent::questions::PendingQuestions>>,
+) -> McpServers {
     // MCP services feature not compiled in.
-    std::collections::HashMap::new()
 }
 
 /// Register the delegation tool if an Anthropic API key is available.
@@ -365,34 +668,68 @@ pub fn register_delegation(
 
 #[cfg(all(test, feature = "services"))]
 mod tests {
-    use std::collections::HashMap;
-
     use super::*;
-    use clawft_services::mcp::transport::McpTransport;
-    use clawft_services::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+    use clawft_core::tools::registry::ToolRegistry;
+    use clawft_services::mcp::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+    use std::collections::VecDeque;
+    use std::time::Duration;
+    use tokio::sync::{Semaphore, broadcast, watch};
 
     /// A minimal mock transport for testing within this crate.
     ///
     /// The `MockTransport` in `clawft-services` is `#[cfg(test)]`-gated
     /// and therefore unavailable outside that crate, so we provide our own.
+    /// Clones share their state.
+    #[derive(Clone)]
     struct TestTransport {
-        responses: tokio::sync::Mutex<Vec<JsonRpcResponse>>,
+        responses: Arc<tokio::sync::Mutex<Vec<JsonRpcResponse>>>,
+        notifications: broadcast::Sender<JsonRpcNotification>,
+        closed: Arc<watch::Sender<bool>>,
+        /// How long each `tools/call` takes to answer.
+        call_delay: Duration,
     }
 
     impl TestTransport {
         fn new(responses: Vec<JsonRpcResponse>) -> Self {
             Self {
-                responses: tokio::sync::Mutex::new(responses),
+                responses: Arc::new(tokio::sync::Mutex::new(responses)),
+                notifications: broadcast::channel(4).0,
+                closed: Arc::new(watch::Sender::new(false)),
+                call_delay: Duration::ZERO,
             }
         }
+
+        /// Take `delay` to answer each tool call, as a hung server would.
+        fn slow(mut self, delay: Duration) -> Self {
+            self.call_delay = delay;
+            self
+        }
+
+        /// Die, as a crashed server process would.
+        fn close(&self) {
+            self.closed.send_replace(true);
+        }
+
+        /// Send a notification as the server.
+        fn notify(&self, method: &str) {
+            let _ = self
+                .notifications
+                .send(JsonRpcNotification::new(method, serde_json::json!({})));
+        }
     }
 
     #[async_trait]
     impl McpTransport for TestTransport {
         async fn send_request(
             &self,
-            _request: JsonRpcRequest,
+            request: JsonRpcRequest,
         ) -> clawft_services::error::Result<JsonRpcResponse> {
+            if request.method == "tools/call" {
+                tokio::time::sleep(self.call_delay).await;
+            }
+            if self.is_closed() {
+                return Err(ServiceError::McpTransport("server exited".into()));
+            }
             let mut responses = self.responses.lock().await;
             if responses.is_empty() {
                 Err(clawft_services::error::ServiceError::McpTransport(
@@ -410,6 +747,77 @@ mod tests {
         ) -> clawft_services::error::Result<()> {
             Ok(())
         }
+
+        fn subscribe(&self) -> Option<broadcast::Receiver<JsonRpcNotification>> {
+            Some(self.notifications.subscribe())
+        }
+
+        fn is_closed(&self) -> bool {
+            *self.closed.borrow()
+        }
+
+        async fn closed(&self) {
+            let _ = self.closed.subscribe().wait_for(|closed| *closed).await;
+        }
+    }
+
+    /// Hands out `transports` in order, as successive server starts, each
+    /// once `starts` has a permit for it; fails once they run out.
+    struct TestConnector {
+        transports: tokio::sync::Mutex<VecDeque<TestTransport>>,
+        starts: Arc<Semaphore>,
+    }
+
+    #[async_trait]
+    impl McpConnector for TestConnector {
+        async fn connect(&self) -> clawft_services::error::Result<Box<dyn McpTransport>> {
+            self.starts.acquire().await.unwrap().forget();
+            match self.transports.lock().await.pop_front() {
+                Some(transport) => Ok(Box::new(transport)),
+                None => Err(ServiceError::McpTransport("server is down".into())),
+            }
+        }
+    }
+
+    /// A session over `transports`, reconnecting quickly.
+    async fn supervise(name: &str, transports: Vec<TestTransport>) -> Arc<SupervisedSession> {
+        let starts = Arc::new(Semaphore::new(transports.len()));
+        supervise_gated(name, transports, starts).await
+    }
+
+    /// Like [`supervise`], with server starts limited by `starts`.
+    async fn supervise_gated(
+        name: &str,
+        transports: Vec<TestTransport>,
+        starts: Arc<Semaphore>,
+    ) -> Arc<SupervisedSession> {
+        let connector = TestConnector {
+            transports: tokio::sync::Mutex::new(transports.into()),
+            starts,
+        };
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+        };
+        Arc::new(
+            SupervisedSession::connect(name, Box::new(connector), policy)
+                .await
+                .unwrap(),
+        )
+    }
+
+    /// Wait until `done` holds.
+    async fn eventually(what: &str, done: impl Fn() -> bool) {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !done() {
+            assert!(tokio::time::Instant::now() < deadline, "{what}");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Health tracking with the default limits.
+    fn health() -> ServerHealth {
+        ServerHealth::new("srv", CallLimits::from_config(&MCPServerConfig::default()))
     }
 
     fn make_tool_def() -> ToolDefinition {
@@ -441,21 +849,291 @@ mod tests {
 
     /// Create a mock session that has already completed the initialize handshake.
     ///
-    /// Prepends an init response so `McpSession::connect` succeeds, then
-    /// the remaining `responses` are available for subsequent requests.
-    async fn make_session(responses: Vec<JsonRpcResponse>) -> Arc<McpSession> {
+    /// Prepends an init response so the handshake succeeds, then the
+    /// remaining `responses` are available for subsequent requests.
+    async fn make_session(responses: Vec<JsonRpcResponse>) -> Arc<SupervisedSession> {
+        supervise("srv", vec![make_server(responses)]).await
+    }
+
+    /// A server transport that handshakes, then answers `responses`.
+    fn make_server(responses: Vec<JsonRpcResponse>) -> TestTransport {
         let mut all = vec![make_init_response(1)];
         all.extend(responses);
-        let transport = TestTransport::new(all);
-        Arc::new(McpSession::connect(Box::new(transport)).await.unwrap())
+        TestTransport::new(all)
+    }
+
+    /// A `tools/list` response advertising tools named `names`.
+    fn make_tools_response(id: u64, names: &[&str]) -> JsonRpcResponse {
+        let tools: Vec<serde_json::Value> = names
+            .iter()
+            .map(|name| {
+                serde_json::json!({
+                    "name": name,
+                    "description": format!("{name} from the server"),
+                    "inputSchema": {"type": "object"}
+                })
+            })
+            .collect();
+        JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id,
+            result: Some(serde_json::json!({ "tools": tools })),
+            error: None,
+        }
+    }
+
+    fn tool_defs(names: &[&str]) -> Vec<ToolDefinition> {
+        names
+            .iter()
+            .map(|name| ToolDefinition {
+                name: name.to_string(),
+                description: format!("{name} from the server"),
+                input_schema: serde_json::json!({"type": "object"}),
+            })
+            .collect()
+    }
+
+    fn exposed_names(exposed: &[(String, ToolDefinition)]) -> Vec<&str> {
+        exposed.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // ── Tool exposure tests ─────────────────────────────────────────────
+
+    #[test]
+    fn expose_tools_filters_renames_and_describes() {
+        let mut config = MCPServerConfig {
+            prefix: Some("gh__".into()),
+            ..Default::default()
+        };
+        config.expose.allow = vec!["issue_*".into(), "search".into()];
+        config.expose.deny = vec!["*_delete".into()];
+        config
+            .descriptions
+            .insert("search".into(), "Search GitHub code.".into());
+        let tools = tool_defs(&["issue_create", "issue_delete", "search", "repo_list"]);
+
+        let exposed = expose_tools("github", &config, tools, &HashSet::new());
+        assert_eq!(exposed_names(&exposed), ["gh__issue_create", "gh__search"]);
+        assert_eq!(exposed[0].1.description, "issue_create from the server");
+        assert_eq!(exposed[1].1.description, "Search GitHub code.");
+        // The server is still called with its own name.
+        assert_eq!(exposed[1].1.name, "search");
+    }
+
+    #[test]
+    fn expose_tools_skips_invalid_and_colliding_names() {
+        let tools = tool_defs(&["search", "fetch", "fetch", "index"]);
+        let taken: HashSet<String> = ["docs__search".to_string()].into();
+        let exposed = expose_tools("docs", &MCPServerConfig::default(), tools, &taken);
+        assert_eq!(exposed_names(&exposed), ["docs__fetch", "docs__index"]);
+
+        // A prefix without the `__` separator fails strict validation.
+        let config = MCPServerConfig {
+            prefix: Some("docs_".into()),
+            ..Default::default()
+        };
+        assert!(expose_tools("docs", &config, tool_defs(&["fetch"]), &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn expose_tools_caps_tool_count() {
+        let names: Vec<String> = (0..250).map(|i| format!("tool{i}")).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let exposed = expose_tools(
+            "big",
+            &MCPServerConfig::default(),
+            tool_defs(&names),
+            &HashSet::new(),
+        );
+        assert_eq!(exposed.len(), 100);
+        assert_eq!(exposed[0].0, "big__tool0");
+
+        let config = MCPServerConfig {
+            max_tools: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            expose_tools("big", &config, tool_defs(&names), &HashSet::new()).len(),
+            250
+        );
+    }
+
+    #[tokio::test]
+    async fn colliding_servers_register_once_and_follow_list_changes() {
+        let registry = ToolRegistry::new();
+        let dynamic = registry.dynamic().clone();
+        let builtin: Arc<HashSet<String>> = Arc::new(["docs__search".to_string()].into());
+
+        // "docs" advertises a name taken by a built-in tool and a duplicate.
+        let docs_transport = TestTransport::new(vec![
+            make_init_response(1),
+            make_tools_response(2, &["search", "fetch", "fetch"]),
+            make_tools_response(3, &["fetch", "summarize"]),
+        ]);
+        let docs = supervise("docs", vec![docs_transport.clone()]).await;
+        let docs_config = MCPServerConfig::default();
+        let count = sync_mcp_tools(
+            "docs",
+            &docs_config,
+            &docs,
+            &Arc::default(),
+            &Arc::new(health()),
+            &dynamic,
+            &builtin,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count, 1);
+
+        // "wiki" shares the prefix, so its "fetch" collides with docs'.
+        let wiki = make_session(vec![make_tools_response(2, &["fetch", "index"])]).await;
+        let wiki_config = MCPServerConfig {
+            prefix: Some("docs__".into()),
+            ..Default::default()
+        };
+        sync_mcp_tools(
+            "wiki",
+            &wiki_config,
+            &wiki,
+            &Arc::default(),
+            &Arc::new(health()),
+            &dynamic,
+            &builtin,
+        )
+        .await
+        .unwrap();
+        assert_eq!(registry.list(), ["docs__fetch", "docs__index"]);
+
+        // The server changes its tools and says so.
+        watch_mcp_tools(
+            "docs".into(),
+            docs_config,
+            &docs,
+            Arc::default(),
+            Arc::new(health()),
+            dynamic.clone(),
+            builtin.clone(),
+        );
+        docs_transport.notify("notifications/message");
+        docs_transport.notify("notifications/tools/list_changed");
+        eventually("tools not re-listed", || registry.has("docs__summarize")).await;
+        assert_eq!(
+            registry.list(),
+            ["docs__fetch", "docs__index", "docs__summarize"]
+        );
+        let fetch = registry.get("docs__fetch").unwrap();
+        assert_eq!(fetch.description(), "fetch from the server");
+    }
+
+    #[tokio::test]
+    async fn dead_server_is_restarted_and_its_tools_registered_again() {
+        let registry = ToolRegistry::new();
+        let dynamic = registry.dynamic().clone();
+        let builtin: Arc<HashSet<String>> = Arc::new(HashSet::new());
+        let mut config = MCPServerConfig::default();
+        config.expose.deny = vec!["admin_*".into()];
+
+        let first = make_server(vec![make_tools_response(2, &["search", "admin_reset"])]);
+        let restarted = make_server(vec![
+            make_tools_response(2, &["search", "fetch", "admin_wipe"]),
+            JsonRpcResponse {
+                jsonrpc: "2.0".into(),
+                id: 3,
+                result: Some(serde_json::json!({
+                    "content": [{"type": "text", "text": "fetched"}]
+                })),
+                error: None,
+            },
+        ]);
+        // The restart waits for a second permit.
+        let starts = Arc::new(Semaphore::new(1));
+        let docs = supervise_gated("docs", vec![first.clone(), restarted], starts.clone()).await;
+        sync_mcp_tools(
+            "docs",
+            &config,
+            &docs,
+            &Arc::default(),
+            &Arc::new(health()),
+            &dynamic,
+            &builtin,
+        )
+        .await
+        .unwrap();
+        assert_eq!(registry.list(), ["docs__search"]);
+
+        let mut events = docs.subscribe();
+        watch_mcp_tools(
+            "docs".into(),
+            config,
+            &docs,
+            Arc::default(),
+            Arc::new(health()),
+            dynamic.clone(),
+            builtin,
+        );
+        first.close();
+        let search = registry.get("docs__search").unwrap();
+        let err = search.execute(serde_json::json!({})).await.unwrap_err();
+        assert!(err.is_retryable(), "{err}");
+        assert!(matches!(err, ToolError::Unavailable(_)), "{err}");
+
+        // The server comes back.
+        starts.add_permits(1);
+        while !matches!(events.recv().await, Ok(SessionEvent::Reconnected)) {}
+        eventually("tools not re-registered", || registry.has("docs__fetch")).await;
+        assert_eq!(registry.list(), ["docs__fetch", "docs__search"]);
+        let fetch = registry.get("docs__fetch").unwrap();
+        let result = fetch.execute(serde_json::json!({})).await.unwrap();
+        assert_eq!(result["output"], "fetched");
     }
 
     // ── McpToolWrapper unit tests ───────────────────────────────────────
 
+    #[tokio::test]
+    async fn hung_server_times_out_then_fails_fast() {
+        let server = make_server(vec![]).slow(Duration::from_secs(5));
+        let session = supervise("slow", vec![server]).await;
+        let health = Arc::new(ServerHealth::new(
+            "slow",
+            CallLimits {
+                timeout: Some(Duration::from_millis(50)),
+                max_concurrent: 0,
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+        ));
+        let wrapper = McpToolWrapper::named("slow__echo".into(), make_tool_def(), session)
+            .with_health(health.clone());
+
+        for _ in 0..2 {
+            let err = wrapper.execute(serde_json::json!({})).await.unwrap_err();
+            assert!(matches!(err, ToolError::Timeout(_)), "{err}");
+        }
+
+        // The circuit is open: the call fails at once, retryably.
+        let started = tokio::time::Instant::now();
+        let err = wrapper.execute(serde_json::json!({})).await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert!(matches!(err, ToolError::Unavailable(_)), "{err}");
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("retry in 60s"), "{err}");
+
+        let snap = health.snapshot(true);
+        assert_eq!(snap.status, "down");
+        assert_eq!((snap.timeouts, snap.rejected), (2, 1));
+    }
+
     #[tokio::test]
     async fn wrapper_name_is_namespaced() {
         let session = make_session(vec![]).await;
-        let wrapper = McpToolWrapper::new("myserver", make_tool_def(), session);
+        let (name, tool) = expose_tools(
+            "myserver",
+            &MCPServerConfig::default(),
+            vec![make_tool_def()],
+            &HashSet::new(),
+        )
+        .remove(0);
+        let wrapper = McpToolWrapper::named(name, tool, session);
 
         assert_eq!(wrapper.name(), "myserver__echo");
     }
@@ -463,7 +1141,7 @@ mod tests {
     #[tokio::test]
     async fn wrapper_description_delegates() {
         let session = make_session(vec![]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         assert_eq!(wrapper.description(), "Echo input");
     }
@@ -471,7 +1149,7 @@ mod tests {
     #[tokio::test]
     async fn wrapper_parameters_returns_schema() {
         let session = make_session(vec![]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let params = wrapper.parameters();
         assert_eq!(params["type"], "object");
@@ -491,7 +1169,7 @@ mod tests {
             error: None,
         };
         let session = make_session(vec![response]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({"text": "hello"})).await;
         assert!(result.is_ok());
@@ -502,7 +1180,7 @@ mod tests {
     async fn wrapper_execute_maps_transport_error() {
         // Session with no remaining responses will produce a transport error.
         let session = make_session(vec![]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({"text": "hello"})).await;
         assert!(result.is_err());
@@ -527,7 +1205,7 @@ mod tests {
             }),
         };
         let session = make_session(vec![response]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({})).await;
         assert!(result.is_err());
@@ -544,7 +1222,7 @@ mod tests {
         // Verify McpToolWrapper can be used as a `dyn Tool` trait object.
         fn accepts_tool(_t: &dyn Tool) {}
         let session = make_session(vec![]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
         accepts_tool(&wrapper);
     }
 
@@ -558,7 +1236,7 @@ mod tests {
             url: "http://localhost:19876".into(),
             ..Default::default()
         };
-        let result = create_mcp_client("test", &config).await;
+        let result = create_mcp_client("test", &config, None).await;
         // Handshake fails against a non-existent server.
         assert!(result.is_none());
     }
@@ -566,7 +1244,7 @@ mod tests {
     #[tokio::test]
     async fn create_client_empty_returns_none() {
         let config = MCPServerConfig::default();
-        let result = create_mcp_client("test", &config).await;
+        let result = create_mcp_client("test", &config, None).await;
         assert!(result.is_none());
     }
 
@@ -577,7 +1255,7 @@ mod tests {
             command: "__nonexistent_binary_clawft_test__".into(),
             ..Default::default()
         };
-        let result = create_mcp_client("test", &config).await;
+        let result = create_mcp_client("test", &config, None).await;
         assert!(result.is_none());
     }
 
@@ -591,7 +1269,7 @@ mod tests {
             url: "http://localhost:19876".into(),
             ..Default::default()
         };
-        let result = create_mcp_client("test", &config).await;
+        let result = create_mcp_client("test", &config, None).await;
         // Command spawn fails, so we get None.
         assert!(result.is_none());
     }
@@ -709,7 +1387,7 @@ mod tests {
             error: None,
         };
         let session = make_session(vec![response]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({})).await.unwrap();
         assert_eq!(result["output"], "line1\nline2");
@@ -727,7 +1405,7 @@ mod tests {
             error: None,
         };
         let session = make_session(vec![response]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({})).await;
         assert!(result.is_err());
@@ -749,7 +1427,7 @@ mod tests {
             error: None,
         };
         let session = make_session(vec![response]).await;
-        let wrapper = McpToolWrapper::new("srv", make_tool_def(), session);
+        let wrapper = McpToolWrapper::named("srv__echo".into(), make_tool_def(), session);
 
         let result = wrapper.execute(serde_json::json!({})).await.unwrap();
         // Falls back to raw JSON wrapped in output.
@@ -0,0 +1,442 @@
+//! Delegating tasks to peer gateways.
+//!
+//! Every peer under `delegation.remotes` whose token resolves becomes a
+//! choice of the `delegate_remote` tool. The tool hands the task to the
+//! peer's `/api/delegation/tasks` endpoint through a [`RemoteDelegator`],
+//! waits for the answer, and records what the task cost on the peer in this
+//! gateway's usage tracker, under the calling session and provider
+//! `<provider>@<peer>`, so `weft usage` and session budgets see it.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tracing::{info, warn};
+
+use clawft_core::tools::registry::{Tool, ToolError, ToolRegistry};
+use clawft_llm::{Usage, UsageTracker};
+use clawft_platform::Platform;
+use clawft_services::delegation::claude::DelegationError;
+use clawft_services::delegation::remote::RemoteDelegator;
+use clawft_types::delegation::{
+    DelegationConfig, RemoteTaskRequest, RemoteTaskState, RemoteTaskStatus,
+};
+
+/// The `delegate_remote` tool.
+pub struct RemoteDelegateTool {
+    peers: BTreeMap<String, RemoteDelegator>,
+    usage: Option<Arc<UsageTracker>>,
+}
+
+impl RemoteDelegateTool {
+    /// Delegate to `peers`, recording their usage in `usage`.
+    pub fn new(peers: Vec<RemoteDelegator>, usage: Option<Arc<UsageTracker>>) -> Self {
+        Self {
+            peers: peers
+                .into_iter()
+                .map(|peer| (peer.name().to_string(), peer))
+                .collect(),
+            usage,
+        }
+    }
+
+    /// Add the peer's usage of a finished task to the local tracker.
+    fn record_usage(&self, peer: &str, status: &RemoteTaskStatus) -> f64 {
+        let Some(tracker) = &self.usage else {
+            return status.usage.iter().map(|u| u.cost_usd).sum();
+        };
+        let session = clawft_core::runtime::session_key().unwrap_or_default();
+        status
+            .usage
+            .iter()
+            .map(|u| {
+                let tokens = Usage {
+                    input_tokens: u32::try_from(u.input_tokens).unwrap_or(u32::MAX),
+                    output_tokens: u32::try_from(u.output_tokens).unwrap_or(u32::MAX),
+                    ..Default::default()
+                };
+                let provider = format!("{}@{peer}", u.provider);
+                tracker.record_cost(&provider, &u.model, &session, &tokens, u.cost_usd)
+            })
+            .sum()
+    }
+}
+
+#[async_trait]
+impl Tool for RemoteDelegateTool {
+    fn name(&self) -> &str {
+        "delegate_remote"
+    }
+
+    fn description(&self) -> &str {
+        "Hand a task to another clawft gateway and wait for its answer. \
+         The peer runs the task with its own agents and tools."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "peer": {
+                    "type": "string",
+                    "enum": self.peers.keys().collect::<Vec<_>>(),
+                    "description": "Gateway to delegate to"
+                },
+                "task": {
+                    "type": "string",
+                    "description": "What the peer should do, with all the context it needs"
+                },
+                "agent": {
+                    "type": "string",
+                    "description": "Agent on the peer to run the task, if the peer allows choosing"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Tool-loop iterations the task may use"
+                },
+                "max_tokens": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Tokens the task may use"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Seconds the task may run"
+                }
+            },
+            "required": ["peer", "task"]
+        })
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value, ToolError> {
+        let peer = args
+            .get("peer")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: peer".into()))?;
+        let task = args
+            .get("task")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: task".into()))?;
+        let delegator = self.peers.get(peer).ok_or_else(|| {
+            ToolError::InvalidArgs(format!(
+                "unknown peer '{peer}' (expected one of: {})",
+                self.peers.keys().cloned().collect::<Vec<_>>().join(", ")
+            ))
+        })?;
+
+        let request = RemoteTaskRequest {
+            prompt: task.to_string(),
+            agent: args.get("agent").and_then(|v| v.as_str()).map(String::from),
+            max_iterations: args
+                .get("max_iterations")
+                .and_then(|v| v.as_u64())
+                .map(|n| u32::try_from(n).unwrap_or(u32::MAX)),
+            max_tokens: args.get("max_tokens").and_then(|v| v.as_u64()),
+            timeout_secs: args.get("timeout_secs").and_then(|v| v.as_u64()),
+        };
+
+        info!(peer, agent = ?request.agent, "delegating task to peer");
+        let status = match delegator.run(&request).await {
+            Ok(status) => status,
+            Err(DelegationError::Timeout { elapsed }) => {
+                return Err(ToolError::Timeout(elapsed.as_secs()));
+            }
+            Err(e) => return Err(ToolError::ExecutionFailed(format!("peer '{peer}': {e}"))),
+        };
+        let cost_usd = self.record_usage(peer, &status);
+        if status.state != RemoteTaskState::Completed {
+            warn!(peer, task = %status.id, state = ?status.state, "delegated task did not complete");
+        }
+
+        Ok(json!({
+            "peer": peer,
+            "task_id": status.id,
+            "state": status.state,
+            "answer": status.answer,
+            "error": status.error,
+            "usage": status.usage,
+            "cost_usd": cost_usd,
+        }))
+    }
+}
+
+/// Register `delegate_remote` when `config.remotes` has a peer with a
+/// token. Peers without one are skipped with a warning.
+pub fn register_remote_delegation<P: Platform>(
+    config: &DelegationConfig,
+    registry: &mut ToolRegistry,
+    platform: &P,
+    usage: Option<Arc<UsageTracker>>,
+) {
+    let mut peers = Vec::new();
+    for (name, remote) in &config.remotes {
+        let Some(token) = platform
+            .env()
+            .resolve_secret(&remote.token, remote.token_env.as_deref())
+        else {
+            warn!(peer = %name, "no token for delegation peer, skipping");
+            continue;
+        };
+        peers.push(RemoteDelegator::new(name, remote, token));
+    }
+    if peers.is_empty() {
+        return;
+    }
+    info!(peers = peers.len(), "remote delegation tool registered");
+    registry.register(Arc::new(RemoteDelegateTool::new(peers, usage)));
+}
+
+#[cfg(all(test, feature = "api"))]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use clawft_core::agent::context::ContextBuilder;
+    use clawft_core::agent::loop_core::AgentLoop;
+    use clawft_core::agent::memory::MemoryStore;
+    use clawft_core::agent::skills::SkillsLoader;
+    use clawft_core::bus::MessageBus;
+    use clawft_core::pipeline::assembler::TokenBudgetAssembler;
+    use clawft_core::pipeline::classifier::KeywordClassifier;
+    use clawft_core::pipeline::learner::NoopLearner;
+    use clawft_core::pipeline::permissions::PermissionResolver;
+    use clawft_core::pipeline::router::StaticRouter;
+    use clawft_core::pipeline::scorer::NoopScorer;
+    use clawft_core::pipeline::traits::{
+        LlmTransport, Pipeline, PipelineRegistry, TransportRequest,
+    };
+    use clawft_core::session::SessionManager;
+    use clawft_llm::PriceTable;
+    use clawft_llm::usage::PriceOverride;
+    use clawft_platform::NativePlatform;
+    use clawft_services::api::ApiState;
+    use clawft_services::api::auth::TokenStore;
+    use clawft_services::api::bridge::{
+        AgentBridge, BusBridge, ChannelBridge, ConfigBridge, MemoryBridge, SessionBridge,
+        SkillBridge, ToolBridge, VoiceBridge,
+    };
+    use clawft_services::api::broadcaster::TopicBroadcaster;
+    use clawft_services::api::remote_tasks::RemoteTasks;
+    use clawft_types::config::Config;
+    use clawft_types::delegation::{DelegationServeConfig, RemoteDelegateConfig};
+    use clawft_types::provider::{ContentBlock, LlmResponse, StopReason};
+    use clawft_types::secret::SecretString;
+    use tokio_util::sync::CancellationToken;
+
+    /// The peer's model: answers every task using 1000 + 200 tokens.
+    struct Answering;
+
+    #[async_trait]
+    impl LlmTransport for Answering {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "r".into(),
+                content: vec![ContentBlock::Text {
+                    text: "all 12 tests pass".into(),
+                }],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage {
+                    input_tokens: 1000,
+                    output_tokens: 200,
+                    ..Default::default()
+                },
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    /// A gateway serving delegated tasks on a local port.
+    struct Peer {
+        url: String,
+        cancel: CancellationToken,
+        dir: PathBuf,
+    }
+
+    impl Drop for Peer {
+        fn drop(&mut self) {
+            self.cancel.cancel();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    async fn start_peer(serve: DelegationServeConfig) -> Peer {
+        let dir = std::env::temp_dir().join(format!("clawft-peer-{}", uuid::Uuid::new_v4()));
+        let platform = Arc::new(NativePlatform::new());
+        let bus = Arc::new(MessageBus::new());
+        let config = Config::default();
+        let cancel = CancellationToken::new();
+
+        let sessions = Arc::new(SessionManager::with_dir(
+            platform.clone(),
+            dir.join("sessions"),
+        ));
+        let memory = Arc::new(MemoryStore::new(platform.clone()).unwrap());
+        let skills = Arc::new(SkillsLoader::new(platform.clone()).unwrap());
+        let tools = Arc::new(ToolRegistry::new());
+        // $1 per 1K tokens both ways: each task costs $1.20.
+        let prices = HashMap::from([(
+            "test-model".to_string(),
+            PriceOverride {
+                input_per_1k: 1.0,
+                output_per_1k: 1.0,
+                cache_read_per_1k: None,
+                cache_write_per_1k: None,
+            },
+        )]);
+        let usage = Arc::new(UsageTracker::new(PriceTable::with_overrides(&prices)));
+        let tasks = Arc::new(
+            RemoteTasks::new(&serve, Some(SecretString::new("s3cret")), bus.clone())
+                .with_usage(usage.clone()),
+        );
+
+        let pipeline = PipelineRegistry::new(Pipeline {
+            classifier: Arc::new(KeywordClassifier::new()),
+            router: Arc::new(StaticRouter::new("test".into(), "test-model".into())),
+            assembler: Arc::new(TokenBudgetAssembler::new(4096)),
+            transport: Arc::new(Answering),
+            scorer: Arc::new(NoopScorer::new()),
+            learner: Arc::new(NoopLearner::new()),
+        });
+        let agent = AgentLoop::new(
+            config.agents.clone(),
+            platform.clone(),
+            bus.clone(),
+            pipeline,
+            tools.clone(),
+            ContextBuilder::new(
+                config.agents.clone(),
+                memory.clone(),
+                skills.clone(),
+                platform.clone(),
+            ),
+            sessions.clone(),
+            PermissionResolver::default_resolver(),
+        )
+        .with_usage_tracker(usage)
+        .with_turn_observer(tasks.clone())
+        .with_cancel(cancel.clone());
+        tokio::spawn(async move { agent.run().await });
+
+        let state = ApiState {
+            tools: Arc::new(ToolBridge::new(tools)),
+            sessions: Arc::new(SessionBridge::new(sessions)),
+            agents: Arc::new(AgentBridge::empty()),
+            bus: Arc::new(BusBridge::new(bus)),
+            auth: Arc::new(TokenStore::new()),
+            skills: Arc::new(SkillBridge::new(skills)),
+            memory: Arc::new(MemoryBridge::new(memory)),
+            config: Arc::new(ConfigBridge::new(config.clone())),
+            channels: Arc::new(ChannelBridge::from_config(&config.channels, true)),
+            voice: Arc::new(VoiceBridge::new(
+                config.voice.clone(),
+                config.providers.clone(),
+            )),
+            mcp: Arc::default(),
+            broadcaster: Arc::new(TopicBroadcaster::new()),
+            remote_tasks: tasks,
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let shutdown = cancel.clone().cancelled_owned();
+        tokio::spawn(async move {
+            clawft_services::api::serve(listener, state, &[], None, shutdown).await
+        });
+        Peer { url, cancel, dir }
+    }
+
+    fn delegator(peer: &Peer, token: &str) -> RemoteDelegator {
+        let config = RemoteDelegateConfig {
+            url: peer.url.clone(),
+            token: SecretString::default(),
+            token_env: None,
+            timeout_secs: 30,
+            poll_interval_secs: 1,
+        };
+        RemoteDelegator::new("lab", &config, SecretString::new(token))
+            .with_poll_interval(Duration::from_millis(20))
+    }
+
+    #[tokio::test]
+    async fn delegates_to_a_peer_and_records_its_usage() {
+        let peer = start_peer(DelegationServeConfig {
+            enabled: true,
+            agents: vec!["tester".into()],
+            ..Default::default()
+        })
+        .await;
+        let usage = Arc::new(UsageTracker::new(PriceTable::default()));
+        let tool = RemoteDelegateTool::new(vec![delegator(&peer, "s3cret")], Some(usage.clone()));
+        assert_eq!(tool.parameters()["properties"]["peer"]["enum"][0], "lab");
+
+        let result = clawft_core::runtime::with_session_key(
+            "telegram:42",
+            tool.execute(json!({
+                "peer": "lab",
+                "task": "Run the test suite and report failures.",
+                "max_iterations": 3,
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["state"], "completed");
+        assert_eq!(result["answer"], "all 12 tests pass");
+        assert_eq!(result["usage"][0]["input_tokens"], 1000);
+        assert!((result["cost_usd"].as_f64().unwrap() - 1.2).abs() < 1e-9);
+
+        // The peer's cost lands in the calling session, under the peer.
+        let totals = usage.session_totals("telegram:42");
+        assert_eq!(totals.input_tokens, 1000);
+        assert_eq!(totals.output_tokens, 200);
+        assert!((totals.cost_usd - 1.2).abs() < 1e-9);
+        let snapshot = usage.snapshot();
+        assert_eq!(snapshot[0].0.provider, "test@lab");
+        assert_eq!(snapshot[0].0.model, "test-model");
+    }
+
+    #[tokio::test]
+    async fn peers_refuse_bad_tokens_and_unlisted_agents() {
+        let peer = start_peer(DelegationServeConfig {
+            enabled: true,
+            agents: vec!["tester".into()],
+            ..Default::default()
+        })
+        .await;
+
+        let tool = RemoteDelegateTool::new(vec![delegator(&peer, "guess")], None);
+        let err = tool
+            .execute(json!({ "peer": "lab", "task": "cat ~/.ssh/id_rsa" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("401"), "{err}");
+
+        let tool = RemoteDelegateTool::new(vec![delegator(&peer, "s3cret")], None);
+        let err = tool
+            .execute(json!({ "peer": "lab", "task": "deploy", "agent": "admin" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("400"), "{err}");
+        assert!(err.to_string().contains("admin"), "{err}");
+
+        let err = tool
+            .execute(json!({ "peer": "prod", "task": "deploy" }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn peers_that_do_not_serve_answer_not_found() {
+        let peer = start_peer(DelegationServeConfig::default()).await;
+        let tool = RemoteDelegateTool::new(vec![delegator(&peer, "s3cret")], None);
+        let err = tool
+            .execute(json!({ "peer": "lab", "task": "deploy" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+    }
+}
@@ -533,3 +533,100 @@ fn memory_search_succeeds() {
 
     assert!(output.status.success(), "weft memory search should exit 0");
 }
+
+// ── 14. Gateway daemon lifecycle ────────────────────────────────────
+
+/// `weft` with `HOME` in a fresh temp dir holding a gateway config that
+/// enables only the API, on an ephemeral port.
+#[cfg(unix)]
+fn weft_in_home(home: &std::path::Path) -> Command {
+    let config = home.join(".clawft").join("config.json");
+    let mut cmd = weft_bin();
+    cmd.env("HOME", home)
+        .env("CLAWFT_CONFIG", &config)
+        .env("WEFTOS_NO_UPDATE_CHECK", "1");
+    cmd
+}
+
+#[cfg(unix)]
+#[test]
+fn gateway_daemon_start_status_stop() {
+    let home = std::env::temp_dir().join(format!(
+        "clawft_daemon_it_{}_{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    let clawft = home.join(".clawft");
+    std::fs::create_dir_all(&clawft).unwrap();
+    std::fs::write(
+        clawft.join("config.json"),
+        r#"{"gateway":{"apiEnabled":true,"apiPort":0,"host":"127.0.0.1"}}"#,
+    )
+    .unwrap();
+    let pid_file = clawft.join("state").join("gateway.pid");
+    // A stopped gateway leaves its pid file empty rather than removing it.
+    let pid_file_empty = || {
+        std::fs::read_to_string(&pid_file)
+            .ok()
+            .is_none_or(|s| s.trim().is_empty())
+    };
+    let run = |args: &[&str]| {
+        let output = weft_in_home(&home)
+            .args(args)
+            .output()
+            .expect("failed to run weft");
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        (output.status.success(), text)
+    };
+
+    let (ok, text) = run(&["gateway", "--daemon"]);
+    assert!(ok, "weft gateway --daemon should exit 0, got: {text}");
+    assert!(
+        !pid_file_empty(),
+        "daemon should write {}",
+        pid_file.display()
+    );
+
+    let (ok, text) = run(&["gateway", "--daemon"]);
+    assert!(!ok, "a second daemon should be refused");
+    assert!(text.contains("already running"), "got: {text}");
+
+    let (ok, text) = run(&["gateway", "status"]);
+    assert!(ok, "status should exit 0 while running, got: {text}");
+    assert!(
+        text.contains("Gateway is running") && text.contains("up "),
+        "got: {text}"
+    );
+
+    let (ok, text) = run(&["gateway", "stop", "--timeout", "20"]);
+    assert!(ok, "stop should exit 0, got: {text}");
+    assert!(text.contains("Gateway stopped"), "got: {text}");
+    assert!(pid_file_empty(), "stop should clear the pid file");
+    assert!(
+        clawft.join("state").join("logs.jsonl").exists(),
+        "the daemon should log to the structured log file"
+    );
+
+    let (ok, text) = run(&["gateway", "status"]);
+    assert!(!ok, "status should exit 1 when stopped");
+    assert!(text.contains("not running"), "got: {text}");
+
+    // A pid file left by a crashed gateway is detected and cleared.
+    let mut gone = Command::new("true").spawn().unwrap();
+    let gone_pid = gone.id();
+    gone.wait().unwrap();
+    std::fs::write(&pid_file, gone_pid.to_string()).unwrap();
+    let (ok, text) = run(&["gateway", "stop"]);
+    assert!(ok, "stop with a stale pid file should exit 0, got: {text}");
+    assert!(text.contains("stale pid file"), "got: {text}");
+    assert!(pid_file_empty());
+
+    let _ = std::fs::remove_dir_all(&home);
+}
@@ -20,6 +20,8 @@ fn make_msg(role: &str, content: &str) -> LlmMessage {
         content: content.into(),
         tool_call_id: None,
         tool_calls: None,
+        parts: None,
+        cache: false,
     }
 }
 
@@ -57,6 +59,8 @@ fn make_request(content: &str) -> ChatRequest {
             content: content.into(),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         }],
         tools: vec![],
         model: None,
@@ -64,6 +68,7 @@ fn make_request(content: &str) -> ChatRequest {
         temperature: None,
         auth_context: None,
         complexity_boost: 0.0,
+        cache: false,
     }
 }
 
@@ -76,6 +81,10 @@ fn make_response(text: &str, output_tokens: u32) -> LlmResponse {
             input_tokens: 50,
             output_tokens,
             total_tokens: 0,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         },
         metadata: HashMap::new(),
     }
@@ -76,6 +76,12 @@ pub struct AgentDefinition {
     #[serde(default)]
     pub variables: HashMap<String, String>,
 
+    /// Prompt templates this agent overrides, by template name (`system`,
+    /// `tool_error`, `summarizer`, `heartbeat`).
+    /// See [`templates`](super::templates).
+    #[serde(default)]
+    pub templates: HashMap<String, String>,
+
     /// Source path (YAML file or directory) -- not serialised.
     #[serde(skip)]
     pub source_path: Option<PathBuf>,
@@ -544,6 +550,7 @@ mod tests {
             allowed_tools: vec![],
             max_turns: None,
             variables: HashMap::new(),
+            templates: HashMap::new(),
             source_path: None,
         }];
 
@@ -577,6 +584,7 @@ mod tests {
             allowed_tools: vec![],
             max_turns: None,
             variables: HashMap::new(),
+            templates: HashMap::new(),
             source_path: None,
         }];
 
@@ -601,6 +609,7 @@ mod tests {
                 allowed_tools: vec![],
                 max_turns: None,
                 variables: HashMap::new(),
+                templates: HashMap::new(),
                 source_path: None,
             },
             AgentDefinition {
@@ -612,6 +621,7 @@ mod tests {
                 allowed_tools: vec![],
                 max_turns: None,
                 variables: HashMap::new(),
+                templates: HashMap::new(),
                 source_path: None,
             },
         ];
@@ -653,6 +663,7 @@ mod tests {
                 m.insert("lang".into(), "rust".into());
                 m
             },
+            templates: HashMap::new(),
             source_path: Some(PathBuf::from("/tmp/test")),
         };
 
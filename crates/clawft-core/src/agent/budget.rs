@@ -0,0 +1,435 @@
+//! Per-turn and per-day resource budgets.
+//!
+//! The agent loop builds a [`TurnBudget`] from `agents.budget` for every
+//! turn and checks it between LLM calls and before each tool batch: tokens
+//! used, wall-clock time, and tool executions are counted per turn, while
+//! spend is counted per local calendar day in a [`DailySpend`] shared by
+//! all sessions. When a limit trips, the turn ends with the
+//! [`BudgetExceeded`] message instead of a model reply.
+//!
+//! Daily spend is persisted to [`BUDGET_STATE_FILE`] in the state
+//! directory so that restarting the process does not reset it; the total
+//! resets at local midnight.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use clawft_types::config::BudgetConfig;
+use clawft_types::provider::Usage;
+
+/// File name of the daily spend state, inside `~/.clawft/state/`.
+pub const BUDGET_STATE_FILE: &str = "budget.json";
+
+/// Source of the current date for a [`DailySpend`].
+///
+/// Production code uses [`LocalClock`]; tests substitute a clock they set
+/// by hand to cross midnight.
+pub trait Clock: Send + Sync {
+    /// Today's date.
+    fn today(&self) -> NaiveDate;
+}
+
+/// The local wall clock (days end at local midnight, as in the usage
+/// ledger).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalClock;
+
+impl Clock for LocalClock {
+    fn today(&self) -> NaiveDate {
+        chrono::Local::now().date_naive()
+    }
+}
+
+/// A budget limit that was reached.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetExceeded {
+    /// The turn's LLM calls used more than `max_tokens_per_turn`.
+    Tokens { used: u64, limit: u64 },
+    /// The turn ran longer than `max_turn_secs`.
+    WallTime { limit_secs: u64 },
+    /// The next tool batch would exceed `max_tool_calls_per_turn`.
+    ToolCalls { limit: u32 },
+    /// Today's spend reached `max_cost_per_day_usd`.
+    DailyCost { spent: f64, limit: f64 },
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tokens { used, limit } => write!(
+                f,
+                "This turn was stopped after using {used} tokens, over its limit of \
+                 {limit}. Raise agents.budget.max_tokens_per_turn to allow longer turns."
+            ),
+            Self::WallTime { limit_secs } => write!(
+                f,
+                "This turn was stopped after running for its limit of {limit_secs}s. \
+                 Raise agents.budget.max_turn_secs to allow longer turns."
+            ),
+            Self::ToolCalls { limit } => write!(
+                f,
+                "This turn was stopped at its limit of {limit} tool executions. \
+                 Raise agents.budget.max_tool_calls_per_turn to allow more."
+            ),
+            Self::DailyCost { spent, limit } => write!(
+                f,
+                "Today's spending limit has been reached (${spent:.4} of ${limit:.2}), \
+                 so no further LLM calls will be made until midnight. Raise \
+                 agents.budget.max_cost_per_day_usd to continue."
+            ),
+        }
+    }
+}
+
+// ── Turn budget ─────────────────────────────────────────────────────────
+
+/// Limits and counters for one turn.
+pub struct TurnBudget {
+    limits: BudgetConfig,
+    daily: Option<Arc<DailySpend>>,
+    started_ms: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    tool_calls: u32,
+}
+
+impl TurnBudget {
+    /// Start a turn under `limits`. The daily cost limit is only checked
+    /// when `daily` is given.
+    pub fn new(limits: &BudgetConfig, daily: Option<Arc<DailySpend>>) -> Self {
+        Self {
+            limits: limits.clone(),
+            daily,
+            started_ms: crate::runtime::now_millis(),
+            input_tokens: 0,
+            output_tokens: 0,
+            tool_calls: 0,
+        }
+    }
+
+    /// A turn without limits.
+    pub fn unlimited() -> Self {
+        Self::new(&BudgetConfig::default(), None)
+    }
+
+    /// Count a completion's tokens against the turn.
+    pub fn record_usage(&mut self, usage: &Usage) {
+        self.input_tokens += u64::from(usage.input_tokens);
+        self.output_tokens += u64::from(usage.output_tokens);
+    }
+
+    /// Prompt tokens the turn has used so far.
+    pub fn input_tokens(&self) -> u64 {
+        self.input_tokens
+    }
+
+    /// Completion tokens the turn has used so far.
+    pub fn output_tokens(&self) -> u64 {
+        self.output_tokens
+    }
+
+    /// Whether the turn may make another LLM call.
+    pub fn check(&self) -> Result<(), BudgetExceeded> {
+        let used = self.input_tokens + self.output_tokens;
+        if let Some(limit) = self.limits.max_tokens_per_turn
+            && used > limit
+        {
+            return Err(BudgetExceeded::Tokens { used, limit });
+        }
+        if self.remaining_time() == Some(Duration::ZERO) {
+            return Err(self.time_exceeded());
+        }
+        if let Some(limit) = self.limits.max_cost_per_day_usd
+            && let Some(daily) = &self.daily
+        {
+            let spent = daily.spent_today();
+            if spent >= limit {
+                return Err(BudgetExceeded::DailyCost { spent, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Count a batch of `count` tool executions, unless it would take the
+    /// turn past its limit.
+    pub fn admit_tool_calls(&mut self, count: usize) -> Result<(), BudgetExceeded> {
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        if let Some(limit) = self.limits.max_tool_calls_per_turn
+            && self.tool_calls.saturating_add(count) > limit
+        {
+            return Err(BudgetExceeded::ToolCalls { limit });
+        }
+        self.tool_calls = self.tool_calls.saturating_add(count);
+        Ok(())
+    }
+
+    /// Time left before the wall-clock limit, if there is one.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        let limit = self.limits.max_turn_secs?.saturating_mul(1000);
+        let elapsed = crate::runtime::now_millis().saturating_sub(self.started_ms);
+        Some(Duration::from_millis(limit.saturating_sub(elapsed)))
+    }
+
+    /// Await `fut` within the wall-clock limit; `None` if time ran out
+    /// first (and `fut` was dropped).
+    pub async fn within<F: std::future::Future>(&self, fut: F) -> Option<F::Output> {
+        match self.remaining_time() {
+            Some(remaining) => crate::runtime::timeout(remaining, fut).await,
+            None => Some(fut.await),
+        }
+    }
+
+    /// The error for a turn that ran out of time.
+    pub fn time_exceeded(&self) -> BudgetExceeded {
+        BudgetExceeded::WallTime {
+            limit_secs: self.limits.max_turn_secs.unwrap_or_default(),
+        }
+    }
+}
+
+// ── Daily spend ─────────────────────────────────────────────────────────
+
+/// The day's running cost, as persisted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct DayTotal {
+    day: Option<NaiveDate>,
+    cost_usd: f64,
+}
+
+/// Spend so far today, across sessions, optionally persisted.
+pub struct DailySpend {
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "native")]
+    path: Option<std::path::PathBuf>,
+    total: Mutex<DayTotal>,
+}
+
+impl DailySpend {
+    /// In-memory daily spend on the local clock.
+    pub fn new() -> Self {
+        Self::with_clock(Arc::new(LocalClock))
+    }
+
+    /// In-memory daily spend on `clock`.
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            #[cfg(feature = "native")]
+            path: None,
+            total: Mutex::new(DayTotal::default()),
+        }
+    }
+
+    /// Load the total from `path` (if it exists) and save it there after
+    /// every change. An unreadable file is logged and starts from zero.
+    #[cfg(feature = "native")]
+    pub fn with_state_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        let path = path.into();
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => match serde_json::from_str(&raw) {
+                Ok(total) => self.total = Mutex::new(total),
+                Err(e) => tracing::warn!(path = %path.display(), "ignoring budget state: {e}"),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!(path = %path.display(), "cannot read budget state: {e}"),
+        }
+        self.path = Some(path);
+        self
+    }
+
+    /// Dollars spent today.
+    pub fn spent_today(&self) -> f64 {
+        let today = self.clock.today();
+        self.total
+            .lock()
+            .map(|total| {
+                if total.day == Some(today) {
+                    total.cost_usd
+                } else {
+                    0.0
+                }
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Add `cost_usd` to today's total.
+    pub fn add(&self, cost_usd: f64) {
+        if cost_usd <= 0.0 {
+            return;
+        }
+        let today = self.clock.today();
+        let Ok(mut total) = self.total.lock() else {
+            return;
+        };
+        if total.day != Some(today) {
+            *total = DayTotal {
+                day: Some(today),
+                cost_usd: 0.0,
+            };
+        }
+        total.cost_usd += cost_usd;
+        self.save(&total);
+    }
+
+    #[cfg(feature = "native")]
+    fn save(&self, total: &DayTotal) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_vec(total)?)
+        };
+        if let Err(e) = write() {
+            tracing::warn!(path = %path.display(), "cannot save budget state: {e}");
+        }
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn save(&self, _total: &DayTotal) {}
+}
+
+impl Default for DailySpend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clock set by hand.
+    struct MockClock(Mutex<NaiveDate>);
+
+    impl MockClock {
+        fn at(day: &str) -> Arc<Self> {
+            Arc::new(Self(Mutex::new(day.parse().unwrap())))
+        }
+
+        fn set(&self, day: &str) {
+            *self.0.lock().unwrap() = day.parse().unwrap();
+        }
+    }
+
+    impl Clock for MockClock {
+        fn today(&self) -> NaiveDate {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn limits() -> BudgetConfig {
+        BudgetConfig {
+            max_tokens_per_turn: Some(1_000),
+            max_turn_secs: Some(60),
+            max_tool_calls_per_turn: Some(3),
+            max_cost_per_day_usd: Some(1.0),
+        }
+    }
+
+    #[test]
+    fn token_budget_trips_once_exceeded() {
+        let mut budget = TurnBudget::new(&limits(), None);
+        budget.record_usage(&Usage {
+            input_tokens: 600,
+            output_tokens: 400,
+            ..Default::default()
+        });
+        assert_eq!(budget.check(), Ok(()));
+        budget.record_usage(&Usage {
+            input_tokens: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            budget.check(),
+            Err(BudgetExceeded::Tokens {
+                used: 1_001,
+                limit: 1_000
+            })
+        );
+    }
+
+    #[test]
+    fn wall_time_budget_trips_after_the_limit() {
+        let mut budget = TurnBudget::new(&limits(), None);
+        assert!(budget.remaining_time().unwrap() > Duration::from_secs(59));
+        assert_eq!(budget.check(), Ok(()));
+
+        budget.started_ms -= 61_000;
+        assert_eq!(budget.remaining_time(), Some(Duration::ZERO));
+        assert_eq!(
+            budget.check(),
+            Err(BudgetExceeded::WallTime { limit_secs: 60 })
+        );
+        assert_eq!(TurnBudget::unlimited().remaining_time(), None);
+    }
+
+    #[test]
+    fn tool_call_budget_refuses_a_batch_that_would_exceed_it() {
+        let mut budget = TurnBudget::new(&limits(), None);
+        assert_eq!(budget.admit_tool_calls(2), Ok(()));
+        assert_eq!(
+            budget.admit_tool_calls(2),
+            Err(BudgetExceeded::ToolCalls { limit: 3 })
+        );
+        assert_eq!(budget.admit_tool_calls(1), Ok(()));
+        assert!(budget.admit_tool_calls(1).is_err());
+        assert!(TurnBudget::unlimited().admit_tool_calls(10_000).is_ok());
+    }
+
+    #[test]
+    fn daily_cost_budget_resets_at_midnight() {
+        let clock = MockClock::at("2026-03-14");
+        let daily = Arc::new(DailySpend::with_clock(clock.clone()));
+        let budget = TurnBudget::new(&limits(), Some(daily.clone()));
+
+        daily.add(0.6);
+        assert_eq!(budget.check(), Ok(()));
+        daily.add(0.4);
+        assert!(matches!(
+            budget.check(),
+            Err(BudgetExceeded::DailyCost { limit, .. }) if limit == 1.0
+        ));
+
+        clock.set("2026-03-15");
+        assert_eq!(daily.spent_today(), 0.0);
+        assert_eq!(budget.check(), Ok(()));
+        daily.add(0.25);
+        assert_eq!(daily.spent_today(), 0.25);
+    }
+
+    #[test]
+    fn daily_spend_survives_a_restart() {
+        let dir = std::env::temp_dir().join(format!("clawft-budget-{}", std::process::id()));
+        let path = dir.join(BUDGET_STATE_FILE);
+        let clock = MockClock::at("2026-03-14");
+
+        let daily = DailySpend::with_clock(clock.clone()).with_state_file(&path);
+        daily.add(0.75);
+        drop(daily);
+
+        let reopened = DailySpend::with_clock(clock.clone()).with_state_file(&path);
+        assert_eq!(reopened.spent_today(), 0.75);
+        clock.set("2026-03-15");
+        assert_eq!(reopened.spent_today(), 0.0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn messages_name_the_setting_to_raise() {
+        let message = BudgetExceeded::ToolCalls { limit: 3 }.to_string();
+        assert!(message.contains("agents.budget.max_tool_calls_per_turn"));
+        let message = BudgetExceeded::DailyCost {
+            spent: 1.2,
+            limit: 1.0,
+        }
+        .to_string();
+        assert!(message.contains("$1.2000 of $1.00"), "{message}");
+    }
+}
@@ -0,0 +1,311 @@
+//! History compaction.
+//!
+//! A session keeps every message on disk, but only the last
+//! `memory_window` of them are sent to the model. Rather than silently
+//! dropping the older ones, [`AgentLoop`](super::loop_core::AgentLoop)
+//! periodically asks a (usually cheaper) model to fold them into a rolling
+//! summary. The summary lives in the session metadata under
+//! [`COMPACTION_KEY`], and [`ContextBuilder`](super::context::ContextBuilder)
+//! sends it ahead of the recent history.
+//!
+//! Compaction runs at most once every `compaction.min_turns` user turns;
+//! messages evicted in between are folded in by the next run. Tool results
+//! are cut to `compaction.tool_result_chars` before summarizing, while user
+//! and assistant text is passed whole.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use clawft_types::config::CompactionConfig;
+use clawft_types::session::Session;
+
+use crate::pipeline::traits::LlmMessage;
+
+/// Session metadata key holding the [`CompactionState`].
+pub const COMPACTION_KEY: &str = "compaction";
+
+/// Heading of the system message that carries the summary.
+pub const SUMMARY_HEADING: &str = "# Conversation Summary";
+
+/// Tokens allowed for message framing and the transcript headings.
+const PROMPT_OVERHEAD_TOKENS: usize = 32;
+
+/// Rolling summary of the messages that have left the context window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompactionState {
+    /// Summary of `messages[..summarized_through]`.
+    pub summary: String,
+
+    /// Number of leading session messages the summary covers.
+    pub summarized_through: usize,
+
+    /// User turn count when the summary was last written.
+    pub turn: usize,
+}
+
+impl CompactionState {
+    /// Read the state from the session metadata.
+    ///
+    /// Returns `None` when there is none, or when it covers more messages
+    /// than the session holds (the session was cleared since).
+    pub fn load(session: &Session) -> Option<Self> {
+        let value = session.metadata.get(COMPACTION_KEY)?.clone();
+        let state: Self = serde_json::from_value(value).ok()?;
+        (state.summarized_through <= session.messages.len()).then_some(state)
+    }
+
+    /// Write the state into the session metadata.
+    pub fn store(&self, session: &mut Session) {
+        if let Ok(value) = serde_json::to_value(self) {
+            session.metadata.insert(COMPACTION_KEY.into(), value);
+        }
+    }
+}
+
+/// Number of user messages in `session`.
+pub fn user_turns(session: &Session) -> usize {
+    session
+        .messages
+        .iter()
+        .filter(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        .count()
+}
+
+/// The messages that should be summarized now, if any.
+///
+/// Everything before the last `window` messages has left the context;
+/// the part not yet covered by the summary is due, unless the previous
+/// compaction was fewer than `min_turns` user turns ago.
+pub fn due_span(
+    session: &Session,
+    window: usize,
+    config: &CompactionConfig,
+) -> Option<Range<usize>> {
+    if !config.enabled {
+        return None;
+    }
+    let state = CompactionState::load(session).unwrap_or_default();
+    let end = session.messages.len().saturating_sub(window);
+    if end <= state.summarized_through {
+        return None;
+    }
+    if state.summarized_through > 0 && user_turns(session) < state.turn + config.min_turns {
+        return None;
+    }
+    Some(state.summarized_through..end)
+}
+
+/// Render session messages as transcript lines for the summarizer.
+///
+/// Tool results are cut to `tool_result_chars` characters; tool calls are
+/// reduced to the names of the tools called.
+pub fn transcript(messages: &[serde_json::Value], tool_result_chars: usize) -> Vec<String> {
+    messages
+        .iter()
+        .filter_map(|m| {
+            let role = m.get("role").and_then(|v| v.as_str()).unwrap_or("user");
+            let content = m.get("content").and_then(|v| v.as_str()).unwrap_or("");
+            let text = if role == "tool" {
+                truncate_chars(content, tool_result_chars)
+            } else {
+                content.to_string()
+            };
+            let calls: Vec<&str> = m
+                .get("tool_calls")
+                .and_then(|v| v.as_array())
+                .map(|calls| {
+                    calls
+                        .iter()
+                        .filter_map(|c| c.pointer("/function/name").and_then(|n| n.as_str()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            if text.trim().is_empty() && calls.is_empty() {
+                return None;
+            }
+            let mut line = format!("{role}: {text}");
+            if !calls.is_empty() {
+                line.push_str(&format!(" [called {}]", calls.join(", ")));
+            }
+            Some(line)
+        })
+        .collect()
+}
+
+/// Build the summarization prompt for `model`, leaving `max_summary_tokens`
+/// for the reply. `instructions` is the rendered `summarizer` template.
+///
+/// If the previous summary and the transcript do not fit the model's input
+/// budget, the oldest transcript lines are left out. Returns the messages
+/// and the number of lines left out.
+pub fn summary_request(
+    instructions: &str,
+    previous: &str,
+    lines: &[String],
+    model: &str,
+    max_summary_tokens: usize,
+) -> (Vec<LlmMessage>, usize) {
+    let count = |text: &str| clawft_llm::tokens::count_text(text, model);
+    let budget = clawft_llm::tokens::input_budget(model, max_summary_tokens);
+    let mut used = count(instructions) + count(previous) + PROMPT_OVERHEAD_TOKENS;
+    let mut kept = 0;
+    for line in lines.iter().rev() {
+        let tokens = count(line) + 1;
+        if used + tokens > budget {
+            break;
+        }
+        used += tokens;
+        kept += 1;
+    }
+    let omitted = lines.len() - kept;
+
+    let mut body = String::new();
+    if !previous.trim().is_empty() {
+        body.push_str("Earlier summary:\n");
+        body.push_str(previous);
+        body.push_str("\n\n");
+    }
+    body.push_str("New transcript:\n");
+    body.push_str(&lines[omitted..].join("\n"));
+
+    let message = |role: &str, content: String| LlmMessage {
+        role: role.into(),
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        parts: None,
+        cache: false,
+    };
+    (
+        vec![
+            message("system", instructions.into()),
+            message("user", body),
+        ],
+        omitted,
+    )
+}
+
+/// Cut `text` to `max` characters, marking the cut.
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}... [truncated]", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session_with(turns: usize) -> Session {
+        let mut session = Session::new("test:1");
+        for i in 0..turns {
+            session.add_message("user", &format!("question {i}"), None);
+            session.add_message("assistant", &format!("answer {i}"), None);
+        }
+        session
+    }
+
+    #[test]
+    fn nothing_due_while_history_fits_the_window() {
+        let session = session_with(3);
+        assert_eq!(due_span(&session, 6, &CompactionConfig::default()), None);
+        assert_eq!(
+            due_span(&session, 4, &CompactionConfig::default()),
+            Some(0..2)
+        );
+    }
+
+    #[test]
+    fn disabled_compaction_is_never_due() {
+        let config = CompactionConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(due_span(&session_with(10), 4, &config), None);
+    }
+
+    #[test]
+    fn min_turns_guards_repeat_compaction() {
+        let config = CompactionConfig {
+            min_turns: 3,
+            ..Default::default()
+        };
+        let mut session = session_with(5);
+        CompactionState {
+            summary: "earlier".into(),
+            summarized_through: 4,
+            turn: 4,
+        }
+        .store(&mut session);
+
+        // Two messages evicted since, but only one user turn has passed.
+        assert_eq!(due_span(&session, 4, &config), None);
+
+        for i in 5..7 {
+            session.add_message("user", &format!("question {i}"), None);
+            session.add_message("assistant", &format!("answer {i}"), None);
+        }
+        assert_eq!(due_span(&session, 4, &config), Some(4..10));
+    }
+
+    #[test]
+    fn stale_state_is_ignored() {
+        let mut session = session_with(1);
+        CompactionState {
+            summary: "old".into(),
+            summarized_through: 40,
+            turn: 20,
+        }
+        .store(&mut session);
+        assert_eq!(CompactionState::load(&session), None);
+    }
+
+    #[test]
+    fn transcript_cuts_tool_results_but_not_user_text() {
+        let long = "x".repeat(500);
+        let messages = vec![
+            json!({"role": "user", "content": long}),
+            json!({"role": "assistant", "content": "", "tool_calls": [
+                {"id": "c1", "function": {"name": "read_file", "arguments": "{}"}}
+            ]}),
+            json!({"role": "tool", "content": long, "tool_call_id": "c1"}),
+            json!({"role": "assistant", "content": "  "}),
+        ];
+        let lines = transcript(&messages, 20);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], format!("user: {long}"));
+        assert_eq!(lines[1], "assistant:  [called read_file]");
+        assert_eq!(lines[2], format!("tool: {}... [truncated]", "x".repeat(20)));
+    }
+
+    #[test]
+    fn summary_request_includes_previous_summary() {
+        let lines = vec!["user: hi".to_string(), "assistant: hello".to_string()];
+        let (messages, omitted) =
+            summary_request("Summarize.", "they like tea", &lines, "gpt-4o", 256);
+        assert_eq!(omitted, 0);
+        assert_eq!(messages[0].role, "system");
+        assert_eq!(messages[0].content, "Summarize.");
+        assert!(
+            messages[1]
+                .content
+                .contains("Earlier summary:\nthey like tea")
+        );
+        assert!(messages[1].content.ends_with("user: hi\nassistant: hello"));
+    }
+
+    #[test]
+    fn summary_request_drops_oldest_lines_over_budget() {
+        // gpt-4 has an 8K window; reserving 4K for the reply leaves 4K.
+        let lines: Vec<String> = (0..10)
+            .map(|i| format!("user: {i} {}", "word ".repeat(600)))
+            .collect();
+        let (messages, omitted) = summary_request("Summarize.", "", &lines, "gpt-4", 4_096);
+        assert!(omitted > 0 && omitted < lines.len(), "omitted {omitted}");
+        assert!(messages[1].content.contains("user: 9 "));
+        assert!(!messages[1].content.contains("user: 0 "));
+    }
+}
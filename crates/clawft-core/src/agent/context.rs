@@ -9,9 +9,20 @@
 //! 1. **System prompt** (role=`"system"`) -- identity and instructions
 //! 2. **Active skill prompts** (role=`"system"`) -- prefixed with `# Skill: {name}`
 //! 3. **Memory context** (role=`"system"`) -- prefixed with `# Relevant Memory:`
-//! 4. **Conversation history** -- recent messages from the session
+//! 4. **Open tasks** (role=`"system"`) -- the workspace task list's open
+//!    entries, prefixed with `# Open Tasks`, when there are any
+//! 5. **Conversation summary** (role=`"system"`) -- the rolling summary of
+//!    compacted history, prefixed with `# Conversation Summary`
+//! 6. **Conversation history** -- recent messages from the session
 //!
-//! The current user message is **not** added here; the caller appends it.
+//! The assembled list is then fitted to the model's token budget by
+//! priority tier (see [`context_budget`](super::context_budget)): tool
+//! results are cut first, then older history, memory, the summary and skill
+//! prompts. The system prompt is never cut.
+//!
+//! The current user message is **not** added here; the caller appends the
+//! one built by [`ContextBuilder::build_user_message`], which turns image
+//! attachments into image parts for multimodal models.
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -24,14 +35,20 @@ use std::time::SystemTime;
 use crate::runtime::Mutex;
 use tracing::{debug, warn};
 
+use clawft_llm::{ContentPart, ImagePart};
 use clawft_platform::Platform;
 use clawft_types::config::AgentsConfig;
+use clawft_types::event::{Attachment, AttachmentData, InboundMessage};
 use clawft_types::session::Session;
 
 use super::agents::AgentDefinition;
+use super::compaction::{CompactionState, SUMMARY_HEADING};
+use super::context_budget::{ContextReport, ContextTier, fit_to_budget};
 use super::helpers::render_template;
-use super::memory::MemoryStore;
+use super::memory::{LONG_TERM_NAMESPACE, MemoryBackend, MemoryStore, read_entries};
 use super::skills::SkillsLoader;
+use super::tasks::{SUMMARY_MAX_BYTES, TaskList, tasks_path};
+use super::templates::{PromptTemplates, TemplateName};
 
 /// Cached bootstrap file entry with modification-time tracking.
 #[cfg(feature = "native")]
@@ -74,10 +91,15 @@ pub use crate::pipeline::traits::LlmMessage;
 pub struct ContextBuilder<P: Platform> {
     config: AgentsConfig,
     memory: Arc<MemoryStore<P>>,
+    /// Backend that long-term memory is read from, when not the markdown
+    /// files of `memory`.
+    memory_backend: Option<Arc<dyn MemoryBackend>>,
     skills: Arc<SkillsLoader<P>>,
     platform: Arc<P>,
     bootstrap_cache: BootstrapCache,
     compression_config: Option<CompressionConfig>,
+    /// Prompt templates; the built-in ones unless replaced.
+    templates: Arc<PromptTemplates>,
 }
 
 impl<P: Platform> ContextBuilder<P> {
@@ -95,9 +117,11 @@ impl<P: Platform> ContextBuilder<P> {
         skills: Arc<SkillsLoader<P>>,
         platform: Arc<P>,
     ) -> Self {
+        let templates = Arc::new(PromptTemplates::builtin(&config));
         Self {
             config,
             memory,
+            memory_backend: None,
             skills,
             platform,
             bootstrap_cache: {
@@ -107,6 +131,7 @@ impl<P: Platform> ContextBuilder<P> {
                 { Arc::new(Mutex::new(())) }
             },
             compression_config: None,
+            templates,
         }
     }
 
@@ -182,6 +207,12 @@ impl<P: Platform> ContextBuilder<P> {
     ///
     /// The returned string is suitable for a `role="system"` message.
     pub async fn build_system_prompt(&self) -> String {
+        self.system_prompt(None).await
+    }
+
+    /// [`build_system_prompt`](Self::build_system_prompt) for `agent`,
+    /// whose `system` template override applies when it has one.
+    async fn system_prompt(&self, agent: Option<&str>) -> String {
         let workspace = &self.config.defaults.workspace;
         let model = &self.config.defaults.model;
 
@@ -249,20 +280,7 @@ impl<P: Platform> ContextBuilder<P> {
             ));
         } else {
             // Default identity when no SOUL.md or IDENTITY.md exists
-            parts.push(format!(
-                "# clawft\n\n\
-                You are clawft, a helpful AI assistant. You have access to tools that allow you to:\n\
-                - Read, write, and edit files\n\
-                - Execute shell commands\n\
-                - Search the web and fetch web pages\n\
-                - Send messages to users on chat channels\n\n\
-                ## Configuration\n\
-                Model: {model}\n\
-                Workspace: {workspace}\n\
-                Memory: {workspace}/memory/MEMORY.md\n\
-                History: {workspace}/memory/HISTORY.md\n\
-                Skills: {workspace}/skills/"
-            ));
+            parts.push(self.templates.render(TemplateName::System, agent, &[]));
         }
 
         // Append remaining bootstrap files (AGENTS.md, USER.md, TOOLS.md)
@@ -281,7 +299,9 @@ impl<P: Platform> ContextBuilder<P> {
     /// 1. System prompt (role=`"system"`)
     /// 2. Active skill prompts (role=`"system"`, one per skill)
     /// 3. Long-term memory context (role=`"system"`, if non-empty)
-    /// 4. Conversation history from `session.get_history(memory_window)`
+    /// 4. Open tasks from the workspace task list (role=`"system"`, if any)
+    /// 5. Rolling summary of compacted history (role=`"system"`, if any)
+    /// 6. Conversation history from `session.get_history(memory_window)`
     ///
     /// The current user message is **not** included -- the caller adds
     /// it after calling this method.
@@ -295,6 +315,19 @@ impl<P: Platform> ContextBuilder<P> {
         session: &Session,
         active_skills: &[String],
     ) -> Vec<LlmMessage> {
+        self.build_messages_with_report(session, active_skills)
+            .await
+            .0
+    }
+
+    /// Like [`build_messages`](Self::build_messages), also returning how the
+    /// context was fitted to the model's token budget: the final token
+    /// count and every item cut, lowest priority first.
+    pub async fn build_messages_with_report(
+        &self,
+        session: &Session,
+        active_skills: &[String],
+    ) -> (Vec<LlmMessage>, ContextReport) {
         let system_prompt = self.build_system_prompt().await;
         self.build_messages_inner(session, system_prompt, active_skills, None)
             .await
@@ -305,6 +338,25 @@ impl<P: Platform> ContextBuilder<P> {
         &self.config
     }
 
+    /// Read long-term memory through `backend` (`agents.memory.backend`)
+    /// instead of `MEMORY.md`.
+    pub fn with_memory_backend(mut self, backend: Arc<dyn MemoryBackend>) -> Self {
+        self.memory_backend = Some(backend);
+        self
+    }
+
+    /// Use `templates` (see [`PromptTemplates::load`]) instead of the
+    /// built-in prompt templates.
+    pub fn with_templates(mut self, templates: Arc<PromptTemplates>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// The prompt templates in use.
+    pub fn templates(&self) -> &Arc<PromptTemplates> {
+        &self.templates
+    }
+
     /// Enable context compression with the given configuration.
     ///
     /// When compression is enabled, [`build_messages_compressed`](Self::build_messages_compressed)
@@ -353,7 +405,7 @@ impl<P: Platform> ContextBuilder<P> {
         agent: &AgentDefinition,
         args: &str,
     ) -> String {
-        let base = self.build_system_prompt().await;
+        let base = self.system_prompt(Some(&agent.name)).await;
 
         match &agent.system_prompt {
             Some(template) => {
@@ -385,23 +437,30 @@ impl<P: Platform> ContextBuilder<P> {
         extra_skill_instructions: Option<&str>,
     ) -> Vec<LlmMessage> {
         let system_prompt = self.build_system_prompt_for_agent(agent, args).await;
-        self.build_messages_inner(session, system_prompt, &agent.skills, extra_skill_instructions)
-            .await
+        self.build_messages_inner(
+            session,
+            system_prompt,
+            &agent.skills,
+            extra_skill_instructions,
+        )
+        .await
+        .0
     }
 
     /// Core message assembly logic shared by [`build_messages`] and
     /// [`build_messages_for_agent`].
     ///
     /// Assembles messages in order: system prompt, skill prompts,
-    /// optional extra instructions, memory context, and conversation
-    /// history.
+    /// optional extra instructions, memory context, rolling summary, and
+    /// conversation history, then fits them to the model's token budget
+    /// (see [`fit_to_budget`]).
     async fn build_messages_inner(
         &self,
         session: &Session,
         system_prompt: String,
         active_skills: &[String],
         extra_instructions: Option<&str>,
-    ) -> Vec<LlmMessage> {
+    ) -> (Vec<LlmMessage>, ContextReport) {
         let mut messages = Vec::new();
 
         // 1. System prompt
@@ -410,6 +469,8 @@ impl<P: Platform> ContextBuilder<P> {
             content: system_prompt,
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         });
 
         // 2. Active skill prompts
@@ -422,6 +483,8 @@ impl<P: Platform> ContextBuilder<P> {
                             content: format!("# Skill: {}\n\n{}", skill.name, prompt),
                             tool_call_id: None,
                             tool_calls: None,
+                            parts: None,
+                            cache: false,
                         });
                     }
                 }
@@ -433,6 +496,8 @@ impl<P: Platform> ContextBuilder<P> {
                                 content: format!("# Skill: {}\n\n{}", skill.name, prompt),
                                 tool_call_id: None,
                                 tool_calls: None,
+                                parts: None,
+                                cache: false,
                             });
                         }
                     }
@@ -456,17 +521,33 @@ impl<P: Platform> ContextBuilder<P> {
                 content: format!("# Skill Instructions\n\n{instructions}"),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             });
         }
 
+        // The system prompt and skill instructions are identical from turn to
+        // turn: let the provider cache them (and the tool definitions before
+        // them). Memory and history change, so the prefix ends here.
+        if let Some(last) = messages.last_mut() {
+            last.cache = true;
+        }
+        let skills_end = messages.len();
+
         // 4. Memory context
-        match self.memory.read_long_term().await {
+        let long_term = match &self.memory_backend {
+            Some(backend) => read_entries(backend.as_ref(), LONG_TERM_NAMESPACE).await,
+            None => self.memory.read_long_term().await,
+        };
+        match long_term {
             Ok(memory) if !memory.trim().is_empty() => {
                 messages.push(LlmMessage {
                     role: "system".into(),
                     content: format!("# Relevant Memory:\n\n{memory}"),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 });
             }
             Ok(_) => {}
@@ -475,7 +556,37 @@ impl<P: Platform> ContextBuilder<P> {
             }
         }
 
-        // 5. Conversation history (truncated to memory_window)
+        // Open tasks from the workspace task list (memory tier)
+        if let Some(tasks) = self.open_tasks_summary().await {
+            messages.push(LlmMessage {
+                role: "system".into(),
+                content: tasks,
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            });
+        }
+
+        let memory_end = messages.len();
+
+        // 5. Rolling summary of history that has left the window
+        if let Some(state) = CompactionState::load(session)
+            && !state.summary.trim().is_empty()
+        {
+            messages.push(LlmMessage {
+                role: "system".into(),
+                content: format!("{SUMMARY_HEADING}\n\n{}", state.summary),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            });
+        }
+
+        let summary_end = messages.len();
+
+        // 6. Conversation history (truncated to memory_window)
         let window = self.config.defaults.memory_window.max(0) as usize;
         let history = session.get_history(window);
         for msg in history {
@@ -494,10 +605,116 @@ impl<P: Platform> ContextBuilder<P> {
                 content,
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             });
         }
 
-        messages
+        // 7. Cut the lowest-priority items until the model's window fits.
+        let tiers: Vec<ContextTier> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| match i {
+                0 => ContextTier::System,
+                i if i < skills_end => ContextTier::Skill,
+                i if i < memory_end => ContextTier::Memory,
+                i if i < summary_end => ContextTier::Summary,
+                _ if m.role == "tool" => ContextTier::ToolResult,
+                _ => ContextTier::Recent,
+            })
+            .collect();
+        let defaults = &self.config.defaults;
+        let report = fit_to_budget(
+            &mut messages,
+            &tiers,
+            &defaults.model,
+            defaults.max_tokens.max(0) as usize,
+        );
+        debug!(
+            model = %report.model,
+            tokens = report.tokens,
+            budget = report.budget,
+            dropped = report.dropped.len(),
+            dropped_tokens = report.dropped_tokens(),
+            "assembled context"
+        );
+
+        (messages, report)
+    }
+
+    /// The open-task summary of the workspace task list, if it has open
+    /// tasks.
+    async fn open_tasks_summary(&self) -> Option<String> {
+        let home = self.platform.fs().home_dir()?;
+        let path = tasks_path(&expand_workspace(&self.config.defaults.workspace, &home));
+        let text = self.platform.fs().read_to_string(&path).await.ok()?;
+        match TaskList::parse(&text) {
+            Ok(tasks) => tasks.summary(SUMMARY_MAX_BYTES),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to read task list for context");
+                None
+            }
+        }
+    }
+
+    /// Build the LLM message for the user's current turn.
+    ///
+    /// Image attachments are appended after the text as image parts.
+    /// Other attachments, and images that cannot be read, are named in a
+    /// short note appended to the text so the model knows they were sent.
+    pub async fn build_user_message(&self, msg: &InboundMessage) -> LlmMessage {
+        let mut content = msg.content.clone();
+        let mut images = Vec::new();
+        for attachment in &msg.attachments {
+            match attachment_image(attachment).await {
+                Some(image) => images.push(ContentPart::Image(image)),
+                None => {
+                    if !content.is_empty() {
+                        content.push_str("\n\n");
+                    }
+                    content.push_str(&format!(
+                        "[Attached file: {} ({})]",
+                        attachment.filename, attachment.mime_type
+                    ));
+                }
+            }
+        }
+
+        let parts = (!images.is_empty()).then(|| {
+            let text = (!content.is_empty()).then(|| ContentPart::text(content.clone()));
+            text.into_iter().chain(images).collect()
+        });
+        LlmMessage {
+            role: "user".into(),
+            content,
+            tool_call_id: None,
+            tool_calls: None,
+            parts,
+            cache: false,
+        }
+    }
+}
+
+/// Load an image attachment as an inline image part.
+///
+/// Returns `None` for non-image attachments and for files that cannot be
+/// read (file-backed attachments are only readable on native).
+async fn attachment_image(attachment: &Attachment) -> Option<ImagePart> {
+    if !attachment.is_image() {
+        return None;
+    }
+    match &attachment.data {
+        AttachmentData::Bytes { data } => Some(ImagePart::from_bytes(&attachment.mime_type, data)),
+        #[cfg(feature = "native")]
+        AttachmentData::Path { path } => match tokio::fs::read(path).await {
+            Ok(bytes) => Some(ImagePart::from_bytes(&attachment.mime_type, &bytes)),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to read image attachment");
+                None
+            }
+        },
+        #[cfg(not(feature = "native"))]
+        AttachmentData::Path { .. } => None,
     }
 }
 
@@ -656,6 +873,8 @@ pub fn compress_context(
             ),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         });
     }
 
@@ -713,9 +932,22 @@ mod tests {
                 model: "test-model/v1".into(),
                 max_tokens: 4096,
                 temperature: 0.5,
+                top_p: None,
                 max_tool_iterations: 10,
                 memory_window: 5,
+                max_parallel_tools: 4,
             },
+            dispatch: Default::default(),
+            usage: Default::default(),
+            budget: Default::default(),
+            memory: Default::default(),
+            cache: Default::default(),
+            sessions: Default::default(),
+            compaction: Default::default(),
+            planning: Default::default(),
+            templates: Default::default(),
+            skills: Default::default(),
+            interactive: Default::default(),
         }
     }
 
@@ -761,6 +993,65 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    fn inbound_with(attachments: Vec<Attachment>) -> InboundMessage {
+        InboundMessage {
+            channel: "telegram".into(),
+            sender_id: "u1".into(),
+            chat_id: "c1".into(),
+            content: "What is this?".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn user_message_without_attachments_is_plain_text() {
+        let (ctx, dir, _, _) = setup("user_plain").await;
+        let msg = ctx.build_user_message(&inbound_with(vec![])).await;
+        assert_eq!(msg.role, "user");
+        assert_eq!(msg.content, "What is this?");
+        assert!(msg.parts.is_none());
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn user_message_turns_image_attachments_into_parts() {
+        let (ctx, dir, _, _) = setup("user_images").await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let shot = dir.join("shot.png");
+        tokio::fs::write(&shot, b"png").await.unwrap();
+
+        let msg = ctx
+            .build_user_message(&inbound_with(vec![
+                Attachment::from_bytes("a.jpg", "image/jpeg", b"jpg".to_vec()),
+                Attachment::from_path(&shot, "image/png"),
+                Attachment::from_bytes("notes.pdf", "application/pdf", b"%PDF".to_vec()),
+                Attachment::from_path(dir.join("missing.png"), "image/png"),
+            ]))
+            .await;
+
+        assert_eq!(
+            msg.content,
+            "What is this?\n\n[Attached file: notes.pdf (application/pdf)]\n\n\
+             [Attached file: missing.png (image/png)]"
+        );
+        let parts = msg.parts.expect("image parts");
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].as_text(), Some(msg.content.as_str()));
+        assert_eq!(
+            parts[1],
+            ContentPart::Image(ImagePart::base64("image/jpeg", "anBn"))
+        );
+        assert_eq!(
+            parts[2],
+            ContentPart::Image(ImagePart::base64("image/png", "cG5n"))
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn build_messages_includes_system_prompt() {
         let (ctx, dir, _, _) = setup("sys_msg").await;
@@ -797,6 +1088,39 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn build_messages_reads_memory_from_backend() {
+        use crate::agent::memory::{MarkdownMemoryBackend, append_entries};
+
+        let (ctx, dir, memory, _) = setup("memory_backend").await;
+        memory.write_long_term("fact from MEMORY.md").await.unwrap();
+
+        let other = Arc::new(MemoryStore::with_paths(
+            dir.join("other").join("MEMORY.md"),
+            dir.join("other").join("HISTORY.md"),
+            Arc::new(NativePlatform::new()),
+        ));
+        let backend = Arc::new(MarkdownMemoryBackend::new(other));
+        append_entries(
+            backend.as_ref(),
+            LONG_TERM_NAMESPACE,
+            "fact from the backend",
+        )
+        .await
+        .unwrap();
+        let ctx = ctx.with_memory_backend(backend);
+
+        let messages = ctx.build_messages(&Session::new("test:2"), &[]).await;
+        let memory_msg = messages
+            .iter()
+            .find(|m| m.content.contains("Relevant Memory"))
+            .unwrap();
+        assert!(memory_msg.content.contains("fact from the backend"));
+        assert!(!memory_msg.content.contains("MEMORY.md"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn build_messages_skips_empty_memory() {
         let (ctx, dir, _, _) = setup("no_mem").await;
@@ -894,6 +1218,50 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn build_messages_truncates_long_tool_results_first() {
+        let (_, dir, memory, skills) = setup("tool_budget").await;
+        memory.write_long_term("a long-term fact").await.unwrap();
+        let mut config = test_config();
+        config.defaults.model = "openai/gpt-4".into();
+        config.defaults.max_tokens = 1_024;
+        config.defaults.memory_window = 50;
+        let ctx = ContextBuilder::new(config, memory, skills, Arc::new(NativePlatform::new()));
+
+        // Three 4K+ token tool outputs overflow gpt-4's 8K window.
+        let mut session = Session::new("test:tools");
+        for i in 0..3 {
+            session.add_message("user", &format!("question {i}"), None);
+            let output = format!("output {i} {}", "lorem ipsum ".repeat(2_000));
+            session.add_message("tool", &output, None);
+            session.add_message("assistant", &format!("answer {i}"), None);
+        }
+
+        let (messages, report) = ctx.build_messages_with_report(&session, &[]).await;
+
+        assert!(report.tokens <= report.budget, "{report:?}");
+        assert!(!report.dropped.is_empty());
+        assert!(
+            report
+                .dropped
+                .iter()
+                .all(|d| d.tier == ContextTier::ToolResult && d.truncated)
+        );
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert!(contents.contains(&"question 0"));
+        assert!(contents.contains(&"answer 2"));
+        assert!(contents.iter().any(|c| c.contains("Relevant Memory")));
+        let tool: Vec<&str> = messages
+            .iter()
+            .filter(|m| m.role == "tool")
+            .map(|m| m.content.as_str())
+            .collect();
+        assert!(tool[0].starts_with("[tool result omitted"));
+        assert!(tool[2].starts_with("output 2 "));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn build_messages_order_is_correct() {
         let (ctx, dir, memory, skills) = setup("order").await;
@@ -937,6 +1305,86 @@ mod tests {
         assert_eq!(messages[3].role, "user");
         assert_eq!(messages[3].content, "hello");
 
+        // The stable prefix (system prompt + skills) ends at the skill.
+        let cached: Vec<bool> = messages.iter().map(|m| m.cache).collect();
+        assert_eq!(cached, vec![false, true, false, false]);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn build_messages_places_summary_between_memory_and_history() {
+        let (ctx, dir, memory, _) = setup("summary").await;
+        memory.write_long_term("a long-term fact").await.unwrap();
+
+        let mut session = Session::new("test:summary");
+        for i in 0..8 {
+            session.add_message("user", &format!("msg {i}"), None);
+        }
+        CompactionState {
+            summary: "The user asked for a weekly report.".into(),
+            summarized_through: 3,
+            turn: 3,
+        }
+        .store(&mut session);
+
+        let messages = ctx.build_messages(&session, &[]).await;
+
+        // system, memory, summary, then the last 5 history messages
+        assert_eq!(messages.len(), 8);
+        assert!(messages[1].content.contains("Relevant Memory"));
+        assert_eq!(messages[2].role, "system");
+        assert_eq!(
+            messages[2].content,
+            "# Conversation Summary\n\nThe user asked for a weekly report."
+        );
+        assert_eq!(messages[3].content, "msg 3");
+        assert_eq!(messages[7].content, "msg 7");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn build_messages_includes_open_tasks() {
+        use crate::agent::tasks::{TaskStatus, TaskStore};
+
+        let dir = temp_dir("tasks");
+        let platform = Arc::new(NativePlatform::new());
+        let mut config = test_config();
+        config.defaults.workspace = dir.to_string_lossy().into_owned();
+        let memory = Arc::new(MemoryStore::with_paths(
+            dir.join("memory/MEMORY.md"),
+            dir.join("memory/HISTORY.md"),
+            platform.clone(),
+        ));
+        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
+        let ctx = ContextBuilder::new(config, memory, skills, platform.clone());
+        let session = Session::new("test:tasks");
+
+        // No task file: nothing added.
+        assert_eq!(ctx.build_messages(&session, &[]).await.len(), 1);
+
+        let store = TaskStore::new(platform, &dir);
+        store
+            .update(|tasks| {
+                tasks.add("migrate the database", None, None)?;
+                tasks.add("update the docs", None, None)?;
+                tasks.get_mut("2").unwrap().status = TaskStatus::Done;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        let messages = ctx.build_messages(&session, &[]).await;
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].content.starts_with("# Open Tasks"));
+        assert!(
+            messages[1]
+                .content
+                .contains("- [ ] 1: migrate the database")
+        );
+        assert!(!messages[1].content.contains("update the docs"));
+
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
@@ -1026,6 +1474,8 @@ mod tests {
             content: "test content".into(),
             tool_call_id: Some("tc-1".into()),
             tool_calls: None,
+            parts: None,
+            cache: false,
         };
         assert_eq!(msg.role, "system");
         assert_eq!(msg.content, "test content");
@@ -1034,7 +1484,7 @@ mod tests {
 
     // ── Agent definition integration tests ───────────────────────────
 
-    use crate::agent::agents::AgentDefinition;
+    use crate::agent::agents::{AgentDefinition, AgentRegistry};
     use std::collections::HashMap;
 
     fn test_agent() -> AgentDefinition {
@@ -1049,6 +1499,7 @@ mod tests {
             allowed_tools: vec!["read_file".into()],
             max_turns: Some(5),
             variables: vars,
+            templates: HashMap::new(),
             source_path: None,
         }
     }
@@ -1087,6 +1538,27 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    #[tokio::test]
+    async fn build_system_prompt_for_agent_uses_its_system_template() {
+        let (ctx, dir, _, _) = setup("agent_template").await;
+        let mut agent = test_agent();
+        agent.system_prompt = None;
+        agent.templates.insert(
+            "system".into(),
+            "You are {{agent}}. Answer in German.".into(),
+        );
+        let registry = AgentRegistry::discover(None, None, vec![agent.clone()]).unwrap();
+        let templates =
+            PromptTemplates::load(ctx.config(), &dir.join("templates"), Some(&registry)).unwrap();
+        let ctx = ctx.with_templates(Arc::new(templates));
+
+        let prompt = ctx.build_system_prompt_for_agent(&agent, "").await;
+        assert!(prompt.starts_with("You are researcher. Answer in German."));
+        assert!(ctx.build_system_prompt().await.starts_with("# clawft"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[tokio::test]
     async fn build_messages_for_agent_includes_agent_system_prompt() {
         let (ctx, dir, _, _) = setup("agent_msgs").await;
@@ -1217,6 +1689,8 @@ mod tests {
             content: content.into(),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         }
     }
 
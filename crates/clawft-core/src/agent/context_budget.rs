@@ -0,0 +1,376 @@
+//! Token-budgeted context assembly.
+//!
+//! [`ContextBuilder`](super::context::ContextBuilder) tags every message it
+//! assembles with a [`ContextTier`]. [`fit_to_budget`] then cuts the lowest
+//! tiers first until the prompt fits the model's input budget (its context
+//! window from the model registry, minus the tokens reserved for the
+//! reply):
+//!
+//! 1. Tool results are replaced by a short placeholder, oldest first.
+//! 2. Recent messages are dropped, oldest first.
+//! 3. Long-term memory, then the rolling summary, then skill instructions
+//!    are dropped.
+//!
+//! The system prompt (including bootstrap files such as `CLAWFT.md`) is
+//! never cut. Dropped messages are noted in the prompt, and everything cut
+//! is listed in the returned [`ContextReport`].
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::pipeline::traits::LlmMessage;
+
+/// Session metadata key holding the last turn's [`ContextReport`].
+pub const CONTEXT_REPORT_KEY: &str = "context";
+
+/// Tokens set aside for the note listing dropped messages.
+const NOTE_TOKENS: usize = 48;
+
+/// Priority of a context message. Variants are declared from lowest to
+/// highest priority; lower tiers are cut first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextTier {
+    /// Tool output in the conversation history.
+    ToolResult,
+    /// User and assistant messages in the conversation history.
+    Recent,
+    /// Long-term memory.
+    Memory,
+    /// Rolling summary of compacted history.
+    Summary,
+    /// Skill prompts and skill instructions.
+    Skill,
+    /// The system prompt. Never cut.
+    System,
+}
+
+/// One message cut from the context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroppedItem {
+    /// Tier of the message.
+    pub tier: ContextTier,
+
+    /// Tokens saved.
+    pub tokens: usize,
+
+    /// Whether the message was replaced by a placeholder rather than
+    /// removed.
+    pub truncated: bool,
+}
+
+/// How an assembled context was fitted to the model's budget.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextReport {
+    /// Model the budget was derived from.
+    pub model: String,
+
+    /// Input tokens available for the context.
+    pub budget: usize,
+
+    /// Tokens in the final context.
+    pub tokens: usize,
+
+    /// Messages cut to fit, in the order they were cut.
+    pub dropped: Vec<DroppedItem>,
+}
+
+impl ContextReport {
+    /// Total tokens saved by cutting messages.
+    pub fn dropped_tokens(&self) -> usize {
+        self.dropped.iter().map(|d| d.tokens).sum()
+    }
+}
+
+/// Tokens `message` adds to a request for `model`.
+pub fn message_tokens(message: &LlmMessage, model: &str) -> usize {
+    let chat = clawft_llm::ChatMessage {
+        role: message.role.clone(),
+        content: Some(match &message.parts {
+            Some(parts) => clawft_llm::MessageContent::Parts(parts.clone()),
+            None => message.content.clone().into(),
+        }),
+        tool_call_id: message.tool_call_id.clone(),
+        tool_calls: None,
+        cache: false,
+        reasoning: None,
+    };
+    let calls = message.tool_calls.as_ref().map_or(0, |calls| {
+        calls
+            .iter()
+            .map(|c| clawft_llm::tokens::count_text(&c.to_string(), model))
+            .sum()
+    });
+    clawft_llm::tokens::count_message(&chat, model) + calls
+}
+
+/// Cut `messages` until they fit `model`'s context window with
+/// `reserved_output` tokens left for the reply.
+///
+/// `tiers[i]` is the tier of `messages[i]`. See the module docs for the
+/// order in which tiers are cut. If the list still does not fit once only
+/// the system prompt is left, a warning is logged and the rest is left to
+/// the provider.
+pub fn fit_to_budget(
+    messages: &mut Vec<LlmMessage>,
+    tiers: &[ContextTier],
+    model: &str,
+    reserved_output: usize,
+) -> ContextReport {
+    debug_assert_eq!(messages.len(), tiers.len());
+    let budget = clawft_llm::tokens::input_budget(model, reserved_output);
+    let mut counts: Vec<usize> = messages.iter().map(|m| message_tokens(m, model)).collect();
+    let mut total = counts.iter().sum::<usize>() + clawft_llm::tokens::count_messages(&[], model);
+    let mut dropped = Vec::new();
+
+    // 1. Shrink tool results to a placeholder, oldest first.
+    for i in (0..messages.len()).filter(|&i| tiers[i] == ContextTier::ToolResult) {
+        if total <= budget {
+            break;
+        }
+        let message = &mut messages[i];
+        message.content = format!(
+            "[tool result omitted to fit the context window ({} tokens)]",
+            counts[i]
+        );
+        message.parts = None;
+        let shrunk = message_tokens(message, model);
+        if shrunk >= counts[i] {
+            continue;
+        }
+        total -= counts[i] - shrunk;
+        dropped.push(DroppedItem {
+            tier: ContextTier::ToolResult,
+            tokens: counts[i] - shrunk,
+            truncated: true,
+        });
+        counts[i] = shrunk;
+    }
+
+    // 2. Remove whole messages, lowest tier first, oldest first. Tool
+    //    results count as history here and go in order with the messages
+    //    around them.
+    let mut removed = vec![false; messages.len()];
+    let limit = budget.saturating_sub(NOTE_TOKENS);
+    for tier in [
+        ContextTier::Recent,
+        ContextTier::Memory,
+        ContextTier::Summary,
+        ContextTier::Skill,
+    ] {
+        for i in 0..messages.len() {
+            if total <= limit {
+                break;
+            }
+            let in_tier = tiers[i] == tier
+                || (tier == ContextTier::Recent && tiers[i] == ContextTier::ToolResult);
+            if !in_tier || removed[i] {
+                continue;
+            }
+            removed[i] = true;
+            total -= counts[i];
+            dropped.push(DroppedItem {
+                tier: tiers[i],
+                tokens: counts[i],
+                truncated: false,
+            });
+        }
+    }
+
+    if let Some(first) = removed.iter().position(|&r| r) {
+        let mut index = 0;
+        messages.retain(|_| {
+            index += 1;
+            !removed[index - 1]
+        });
+        let note = LlmMessage {
+            role: "system".into(),
+            content: dropped_note(&dropped),
+            tool_call_id: None,
+            tool_calls: None,
+            parts: None,
+            cache: false,
+        };
+        total += message_tokens(&note, model);
+        messages.insert(first, note);
+    }
+
+    if total > budget {
+        warn!(
+            model = %model,
+            tokens = total,
+            budget,
+            "context exceeds the model's window even after trimming"
+        );
+    }
+
+    ContextReport {
+        model: model.to_string(),
+        budget,
+        tokens: total,
+        dropped,
+    }
+}
+
+/// One-line note telling the model what was removed from its context.
+fn dropped_note(dropped: &[DroppedItem]) -> String {
+    let removed = |tiers: &[ContextTier]| {
+        dropped
+            .iter()
+            .filter(|d| !d.truncated && tiers.contains(&d.tier))
+            .count()
+    };
+    let mut parts = Vec::new();
+    let history = removed(&[ContextTier::Recent, ContextTier::ToolResult]);
+    if history > 0 {
+        parts.push(format!("{history} earlier messages"));
+    }
+    if removed(&[ContextTier::Memory]) > 0 {
+        parts.push("long-term memory".to_string());
+    }
+    if removed(&[ContextTier::Summary]) > 0 {
+        parts.push("the conversation summary".to_string());
+    }
+    let skills = removed(&[ContextTier::Skill]);
+    if skills > 0 {
+        parts.push(format!("{skills} skill instructions"));
+    }
+    format!(
+        "[Context trimmed to fit the model's window; omitted: {}.]",
+        parts.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(role: &str, content: String) -> LlmMessage {
+        LlmMessage {
+            role: role.into(),
+            content,
+            tool_call_id: None,
+            tool_calls: None,
+            parts: None,
+            cache: false,
+        }
+    }
+
+    /// `n` to `1.5 * n` thousand tokens of filler (exact BPE counts come
+    /// in lower than the `chars / 4` estimate).
+    fn filler(n: usize) -> String {
+        "lorem ipsum ".repeat(n * 500)
+    }
+
+    /// System prompt, a skill, memory, summary, then `turns` rounds of
+    /// user / tool / assistant history with one 3K+ token tool output each.
+    fn context(turns: usize) -> (Vec<LlmMessage>, Vec<ContextTier>) {
+        let mut messages = vec![
+            msg("system", "You are helpful.".into()),
+            msg("system", "# Skill: review\n\nBe thorough.".into()),
+            msg("system", "# Relevant Memory:\n\nThe user is Sam.".into()),
+            msg("system", "# Conversation Summary\n\nEarlier work.".into()),
+        ];
+        let mut tiers = vec![
+            ContextTier::System,
+            ContextTier::Skill,
+            ContextTier::Memory,
+            ContextTier::Summary,
+        ];
+        for i in 0..turns {
+            messages.push(msg("user", format!("question {i}")));
+            tiers.push(ContextTier::Recent);
+            messages.push(msg("tool", format!("output {i} {}", filler(3))));
+            tiers.push(ContextTier::ToolResult);
+            messages.push(msg("assistant", format!("answer {i}")));
+            tiers.push(ContextTier::Recent);
+        }
+        (messages, tiers)
+    }
+
+    #[test]
+    fn everything_that_fits_is_kept() {
+        let (mut messages, tiers) = context(1);
+        let report = fit_to_budget(&mut messages, &tiers, "gpt-4o", 4_096);
+        assert_eq!(messages.len(), 7);
+        assert!(report.dropped.is_empty());
+        assert!(report.tokens > 1_000 && report.tokens <= report.budget);
+    }
+
+    #[test]
+    fn tool_results_are_truncated_before_messages_are_dropped() {
+        // gpt-4: 8K window, 1K reserved. Three 3K tool outputs overflow it.
+        let (mut messages, tiers) = context(3);
+        let report = fit_to_budget(&mut messages, &tiers, "gpt-4", 1_024);
+
+        assert_eq!(messages.len(), 13, "no message removed");
+        assert!(report.tokens <= report.budget);
+        assert!(
+            report
+                .dropped
+                .iter()
+                .all(|d| d.tier == ContextTier::ToolResult)
+        );
+        assert!(report.dropped.iter().all(|d| d.truncated));
+        // The oldest output goes first; the newest survives.
+        assert!(messages[5].content.starts_with("[tool result omitted"));
+        assert!(messages[11].content.starts_with("output 2 "));
+        assert_eq!(messages[4].content, "question 0");
+    }
+
+    #[test]
+    fn recent_messages_go_before_memory_and_summary() {
+        let (mut messages, tiers) = context(2);
+        // Large user messages that no amount of tool truncation can save.
+        messages[4].content = format!("question 0 {}", filler(4));
+        messages[7].content = format!("question 1 {}", filler(4));
+        let report = fit_to_budget(&mut messages, &tiers, "gpt-4", 1_024);
+
+        assert!(report.tokens <= report.budget);
+        let tiers_cut: Vec<ContextTier> = report.dropped.iter().map(|d| d.tier).collect();
+        assert_eq!(tiers_cut[0], ContextTier::ToolResult);
+        assert!(tiers_cut.contains(&ContextTier::Recent));
+        assert!(!tiers_cut.contains(&ContextTier::Memory));
+        assert!(!tiers_cut.contains(&ContextTier::Summary));
+
+        assert!(messages[2].content.contains("Relevant Memory"));
+        assert!(messages[3].content.contains("Conversation Summary"));
+        assert!(messages[4].content.starts_with("[Context trimmed"));
+        assert!(messages[4].content.contains("earlier messages"));
+        assert_eq!(messages.last().unwrap().content, "answer 1");
+    }
+
+    #[test]
+    fn memory_summary_and_skills_are_cut_in_that_order() {
+        let (mut messages, tiers) = context(0);
+        messages[1].content = format!("# Skill: review\n\n{}", filler(4));
+        messages[2].content = format!("# Relevant Memory:\n\n{}", filler(4));
+        messages[3].content = format!("# Conversation Summary\n\n{}", filler(4));
+        let report = fit_to_budget(&mut messages, &tiers, "gpt-4", 1_024);
+
+        let tiers_cut: Vec<ContextTier> = report.dropped.iter().map(|d| d.tier).collect();
+        assert_eq!(tiers_cut, vec![ContextTier::Memory, ContextTier::Summary]);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].content, "You are helpful.");
+        assert!(messages[1].content.starts_with("# Skill: review"));
+        assert_eq!(
+            messages[2].content,
+            "[Context trimmed to fit the model's window; omitted: long-term memory, \
+             the conversation summary.]"
+        );
+    }
+
+    #[test]
+    fn system_prompt_is_never_dropped() {
+        let mut messages = vec![
+            msg("system", "word ".repeat(20_000)),
+            msg("user", "hi".into()),
+        ];
+        let tiers = [ContextTier::System, ContextTier::Recent];
+        let report = fit_to_budget(&mut messages, &tiers, "gpt-4", 1_024);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[1].content.starts_with("[Context trimmed"));
+        assert!(report.tokens > report.budget);
+    }
+}
@@ -0,0 +1,589 @@
+//! Inbound dispatch policy between the message bus and the agent loop.
+//!
+//! Messages that share a session key (`channel:chat_id`) are processed
+//! strictly in arrival order, while up to
+//! [`DispatchConfig::max_concurrent_sessions`] different sessions are
+//! processed at the same time. Waiting messages are held in per-session
+//! queues with both a per-session and a global cap. A message that does
+//! not fit its session queue is shed and answered with a short busy reply;
+//! when the global cap is reached dispatch stops reading the bus instead,
+//! so the backpressure reaches the producers (see [`crate::bus`]) and no
+//! user message is dropped for it.
+//!
+//! ```text
+//! bus.consume_inbound()  (paused while max_total_queue messages wait)
+//!   |
+//!   v
+//! SessionScheduler::admit ──(queue full)──> busy reply
+//!   |
+//!   v
+//! per-session FIFO ──> ready sessions ──(free slot)──> handler(msg)
+//! ```
+//!
+//! Control messages such as "stop" must not wait behind the turn they
+//! target: [`run_dispatch`] offers every message to an `intercept`
+//! callback first, and messages it handles skip the queues entirely.
+//!
+//! [`SessionScheduler`] holds the pure bookkeeping; [`run_dispatch`]
+//! drives it from the bus on native targets.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use clawft_types::config::DispatchConfig;
+use clawft_types::event::{InboundMessage, OutboundMessage};
+use serde::Serialize;
+
+/// Per-channel dispatch counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChannelDispatchStats {
+    /// Messages waiting in session queues.
+    pub queued: usize,
+    /// Messages currently being processed.
+    pub active: usize,
+    /// Messages completed since startup.
+    pub processed: u64,
+    /// Messages shed because queues were full.
+    pub shed: u64,
+}
+
+/// Shared, thread-safe dispatch metrics keyed by channel name.
+#[derive(Debug, Default)]
+pub struct DispatchMetrics {
+    channels: Mutex<HashMap<String, ChannelDispatchStats>>,
+}
+
+impl DispatchMetrics {
+    /// Create an empty metrics registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of the counters for every channel seen so far.
+    pub fn snapshot(&self) -> HashMap<String, ChannelDispatchStats> {
+        self.channels.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Number of messages waiting for `channel`.
+    pub fn queue_depth(&self, channel: &str) -> usize {
+        self.channels
+            .lock()
+            .ok()
+            .and_then(|m| m.get(channel).map(|s| s.queued))
+            .unwrap_or(0)
+    }
+
+    fn update(&self, channel: &str, f: impl FnOnce(&mut ChannelDispatchStats)) {
+        if let Ok(mut m) = self.channels.lock() {
+            f(m.entry(channel.to_owned()).or_default());
+        }
+    }
+}
+
+/// Outcome of offering a message to the scheduler.
+#[derive(Debug)]
+pub enum Admission {
+    /// The message was queued and will be processed in session order.
+    Accepted,
+    /// The queues were full; the message is handed back to the caller.
+    Shed(Box<InboundMessage>),
+}
+
+/// Per-session FIFO scheduler with a bound on concurrent sessions.
+///
+/// Invariant: a session key is in `ready` exactly when it is not running
+/// and its queue is non-empty.
+pub struct SessionScheduler {
+    max_concurrent: usize,
+    max_session_queue: usize,
+    max_total_queue: usize,
+    queues: HashMap<String, VecDeque<InboundMessage>>,
+    running: HashSet<String>,
+    ready: VecDeque<String>,
+    total_queued: usize,
+    metrics: std::sync::Arc<DispatchMetrics>,
+}
+
+impl SessionScheduler {
+    /// Create a scheduler from the dispatch configuration.
+    pub fn new(config: &DispatchConfig, metrics: std::sync::Arc<DispatchMetrics>) -> Self {
+        Self {
+            max_concurrent: config.max_concurrent_sessions.max(1),
+            max_session_queue: config.max_session_queue,
+            max_total_queue: config.max_total_queue,
+            queues: HashMap::new(),
+            running: HashSet::new(),
+            ready: VecDeque::new(),
+            total_queued: 0,
+            metrics,
+        }
+    }
+
+    /// Queue a message, or hand it back if the caps are reached.
+    pub fn admit(&mut self, msg: InboundMessage) -> Admission {
+        let key = msg.session_key();
+        let session_len = self.queues.get(&key).map_or(0, VecDeque::len);
+        if self.total_queued >= self.max_total_queue || session_len >= self.max_session_queue {
+            self.metrics.update(&msg.channel, |s| s.shed += 1);
+            return Admission::Shed(Box::new(msg));
+        }
+
+        self.metrics.update(&msg.channel, |s| s.queued += 1);
+        let queue = self.queues.entry(key.clone()).or_default();
+        queue.push_back(msg);
+        self.total_queued += 1;
+        if queue.len() == 1 && !self.running.contains(&key) {
+            self.ready.push_back(key);
+        }
+        Admission::Accepted
+    }
+
+    /// Take the next message to process, if a slot is free.
+    ///
+    /// The message's session is marked running until [`finish`](Self::finish)
+    /// is called for it, so no other message of that session is released
+    /// in the meantime.
+    pub fn next_runnable(&mut self) -> Option<InboundMessage> {
+        if self.running.len() >= self.max_concurrent {
+            return None;
+        }
+        let key = self.ready.pop_front()?;
+        let queue = self.queues.get_mut(&key)?;
+        let msg = queue.pop_front()?;
+        if queue.is_empty() {
+            self.queues.remove(&key);
+        }
+        self.total_queued -= 1;
+        self.metrics.update(&msg.channel, |s| {
+            s.queued = s.queued.saturating_sub(1);
+            s.active += 1;
+        });
+        self.running.insert(key);
+        Some(msg)
+    }
+
+    /// Mark the in-flight message of `session_key` as done.
+    ///
+    /// If more messages are waiting for the session it goes to the back of
+    /// the ready queue, so busy sessions cannot starve others.
+    pub fn finish(&mut self, session_key: &str, channel: &str) {
+        if !self.running.remove(session_key) {
+            return;
+        }
+        self.metrics.update(channel, |s| {
+            s.active = s.active.saturating_sub(1);
+            s.processed += 1;
+        });
+        if self.queues.contains_key(session_key) {
+            self.ready.push_back(session_key.to_owned());
+        }
+    }
+
+    /// Number of sessions currently being processed.
+    pub fn running(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Number of messages waiting across all sessions.
+    pub fn queued(&self) -> usize {
+        self.total_queued
+    }
+}
+
+/// Build the reply sent for a shed message.
+pub fn busy_reply(msg: &InboundMessage, text: &str) -> OutboundMessage {
+    OutboundMessage {
+        channel: msg.channel.clone(),
+        chat_id: msg.chat_id.clone(),
+        content: text.to_owned(),
+        reply_to: None,
+        media: vec![],
+        attachments: vec![],
+        metadata: HashMap::new(),
+    }
+}
+
+/// Consume the bus and run `handler` under the dispatch policy.
+///
+/// Each message is first passed to `intercept`; when it returns `true`
+/// the message was handled on the spot and is not queued.
+///
+/// Returns when the inbound channel is closed or `cancel` fires. In both
+/// cases in-flight messages are allowed to finish; queued messages that
+/// have not started are dropped on cancellation.
+#[cfg(feature = "native")]
+pub async fn run_dispatch<I, H, Fut>(
+    bus: &crate::bus::MessageBus,
+    config: &DispatchConfig,
+    metrics: std::sync::Arc<DispatchMetrics>,
+    cancel: Option<&clawft_plugin::CancellationToken>,
+    intercept: I,
+    handler: H,
+) where
+    I: Fn(&InboundMessage) -> bool,
+    H: Fn(InboundMessage) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    use futures_util::StreamExt;
+    use futures_util::stream::FuturesUnordered;
+    use tracing::{debug, info, warn};
+
+    let mut scheduler = SessionScheduler::new(config, metrics);
+    let mut in_flight = FuturesUnordered::new();
+    let mut accepting = true;
+    let mut cancelled_flag = false;
+    let cancelled = async {
+        match cancel {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(cancelled);
+
+    loop {
+        while !cancelled_flag && let Some(msg) = scheduler.next_runnable() {
+            let key = msg.session_key();
+            let channel = msg.channel.clone();
+            debug!(session = %key, "dispatching inbound message");
+            let fut = handler(msg);
+            in_flight.push(async move {
+                fut.await;
+                (key, channel)
+            });
+        }
+
+        if !accepting && in_flight.is_empty() {
+            break;
+        }
+
+        tokio::select! {
+            biased;
+            _ = &mut cancelled, if !cancelled_flag => {
+                info!(
+                    dropped = scheduler.queued(),
+                    "agent loop cancelled via token, draining in-flight messages"
+                );
+                cancelled_flag = true;
+                accepting = false;
+            }
+            Some((key, channel)) = in_flight.next(), if !in_flight.is_empty() => {
+                scheduler.finish(&key, &channel);
+            }
+            msg = bus.consume_inbound(), if accepting && scheduler.queued() < config.max_total_queue.max(1) => match msg {
+                Some(msg) if intercept(&msg) => {
+                    debug!(session = %msg.session_key(), "inbound message intercepted");
+                }
+                Some(msg) => {
+                    if let Admission::Shed(msg) = scheduler.admit(msg) {
+                        warn!(
+                            channel = %msg.channel,
+                            chat_id = %msg.chat_id,
+                            "dispatch queue full, shedding message"
+                        );
+                        if !config.busy_reply.is_empty()
+                            && let Err(e) = bus.dispatch_outbound(busy_reply(&msg, &config.busy_reply))
+                        {
+                            warn!(error = %e, "failed to send busy reply");
+                        }
+                    }
+                }
+                None => {
+                    info!("inbound channel closed, agent loop exiting");
+                    accepting = false;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn inbound(chat_id: &str, content: &str) -> InboundMessage {
+        InboundMessage {
+            channel: "telegram".into(),
+            sender_id: chat_id.into(),
+            chat_id: chat_id.into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn config(concurrent: usize, per_session: usize, total: usize) -> DispatchConfig {
+        DispatchConfig {
+            max_concurrent_sessions: concurrent,
+            max_session_queue: per_session,
+            max_total_queue: total,
+            busy_reply: "busy".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn same_session_is_serialized() {
+        let mut s = SessionScheduler::new(&config(4, 10, 100), Arc::default());
+        s.admit(inbound("a", "1"));
+        s.admit(inbound("a", "2"));
+        assert_eq!(s.next_runnable().unwrap().content, "1");
+        assert!(s.next_runnable().is_none(), "second message must wait");
+        s.finish("telegram:a", "telegram");
+        assert_eq!(s.next_runnable().unwrap().content, "2");
+    }
+
+    #[test]
+    fn concurrency_is_bounded() {
+        let mut s = SessionScheduler::new(&config(2, 10, 100), Arc::default());
+        for chat in ["a", "b", "c"] {
+            s.admit(inbound(chat, chat));
+        }
+        assert!(s.next_runnable().is_some());
+        assert!(s.next_runnable().is_some());
+        assert!(s.next_runnable().is_none());
+        assert_eq!(s.running(), 2);
+        s.finish("telegram:a", "telegram");
+        assert_eq!(s.next_runnable().unwrap().chat_id, "c");
+    }
+
+    #[test]
+    fn finished_busy_session_goes_to_back() {
+        let mut s = SessionScheduler::new(&config(1, 10, 100), Arc::default());
+        s.admit(inbound("a", "a1"));
+        s.admit(inbound("a", "a2"));
+        s.admit(inbound("b", "b1"));
+        assert_eq!(s.next_runnable().unwrap().content, "a1");
+        s.finish("telegram:a", "telegram");
+        assert_eq!(s.next_runnable().unwrap().content, "b1");
+        s.finish("telegram:b", "telegram");
+        assert_eq!(s.next_runnable().unwrap().content, "a2");
+    }
+
+    #[test]
+    fn caps_shed_and_count() {
+        let metrics = Arc::new(DispatchMetrics::new());
+        let mut s = SessionScheduler::new(&config(1, 1, 2), metrics.clone());
+        assert!(matches!(s.admit(inbound("a", "1")), Admission::Accepted));
+        assert!(matches!(s.admit(inbound("a", "2")), Admission::Shed(_)));
+        assert!(matches!(s.admit(inbound("b", "1")), Admission::Accepted));
+        assert!(matches!(s.admit(inbound("c", "1")), Admission::Shed(_)));
+
+        let stats = &metrics.snapshot()["telegram"];
+        assert_eq!(stats.queued, 2);
+        assert_eq!(stats.shed, 2);
+        assert_eq!(metrics.queue_depth("telegram"), 2);
+        assert_eq!(metrics.queue_depth("slack"), 0);
+
+        s.next_runnable().unwrap();
+        let stats = &metrics.snapshot()["telegram"];
+        assert_eq!((stats.queued, stats.active), (1, 1));
+        s.finish("telegram:a", "telegram");
+        assert_eq!(metrics.snapshot()["telegram"].processed, 1);
+    }
+
+    #[cfg(feature = "native")]
+    mod simulation {
+        use super::*;
+        use crate::bus::MessageBus;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        /// Slow mock agent: records per-session order and peak concurrency.
+        #[derive(Default)]
+        struct SlowAgent {
+            current: AtomicUsize,
+            peak: AtomicUsize,
+            log: Mutex<Vec<(String, String)>>,
+        }
+
+        impl SlowAgent {
+            async fn handle(&self, msg: InboundMessage) {
+                let now = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push((msg.chat_id.clone(), msg.content.clone()));
+                self.current.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        #[tokio::test]
+        async fn ordering_and_concurrency_bounds() {
+            let bus = MessageBus::new();
+            for i in 0..4 {
+                for chat in ["alice", "bob", "carol", "dave", "erin"] {
+                    bus.publish_inbound(inbound(chat, &i.to_string())).unwrap();
+                }
+            }
+            let bus = Arc::new(bus);
+            let agent = SlowAgent::default();
+            let metrics = Arc::new(DispatchMetrics::new());
+            let cancel = clawft_plugin::CancellationToken::new();
+
+            let cfg = config(3, 10, 100);
+            let run = run_dispatch(
+                &bus,
+                &cfg,
+                metrics.clone(),
+                Some(&cancel),
+                |_| false,
+                |m| agent.handle(m),
+            );
+            let stop = async {
+                while metrics
+                    .snapshot()
+                    .get("telegram")
+                    .is_none_or(|s| s.processed < 20)
+                {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                cancel.cancel();
+            };
+            tokio::join!(run, stop);
+
+            assert!(agent.peak.load(Ordering::SeqCst) <= 3);
+            assert!(
+                agent.peak.load(Ordering::SeqCst) >= 2,
+                "sessions should overlap"
+            );
+            let log = agent.log.lock().unwrap();
+            assert_eq!(log.len(), 20);
+            for chat in ["alice", "bob", "carol", "dave", "erin"] {
+                let seen: Vec<&str> = log
+                    .iter()
+                    .filter(|(c, _)| c == chat)
+                    .map(|(_, m)| m.as_str())
+                    .collect();
+                assert_eq!(seen, ["0", "1", "2", "3"], "order for {chat}");
+            }
+        }
+
+        #[tokio::test]
+        async fn overflow_sends_busy_reply() {
+            let bus = Arc::new(MessageBus::new());
+            for i in 0..3 {
+                bus.publish_inbound(inbound("alice", &i.to_string()))
+                    .unwrap();
+            }
+            let agent = SlowAgent::default();
+            let metrics = Arc::new(DispatchMetrics::new());
+            let cancel = clawft_plugin::CancellationToken::new();
+
+            // One in flight + one queued; the third is shed.
+            let cfg = config(1, 1, 10);
+            let run = run_dispatch(
+                &bus,
+                &cfg,
+                metrics.clone(),
+                Some(&cancel),
+                |_| false,
+                |m| agent.handle(m),
+            );
+            let stop = async {
+                while metrics
+                    .snapshot()
+                    .get("telegram")
+                    .is_none_or(|s| s.processed + s.shed < 3)
+                {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                cancel.cancel();
+            };
+            tokio::join!(run, stop);
+
+            let reply = bus.consume_outbound().await.unwrap();
+            assert_eq!(reply.chat_id, "alice");
+            assert_eq!(reply.content, "busy");
+            assert_eq!(metrics.snapshot()["telegram"].shed, 1);
+            assert_eq!(agent.log.lock().unwrap().len(), 2);
+        }
+
+        #[tokio::test]
+        async fn global_cap_pauses_instead_of_shedding() {
+            let bus = Arc::new(MessageBus::new());
+            for chat in ["a", "b", "c", "d", "e", "f"] {
+                bus.publish_inbound(inbound(chat, chat)).unwrap();
+            }
+            let agent = SlowAgent::default();
+            let metrics = Arc::new(DispatchMetrics::new());
+            let cancel = clawft_plugin::CancellationToken::new();
+
+            // One in flight, at most two waiting; the rest stay on the bus.
+            let cfg = config(1, 10, 2);
+            let run = run_dispatch(
+                &bus,
+                &cfg,
+                metrics.clone(),
+                Some(&cancel),
+                |_| false,
+                |m| agent.handle(m),
+            );
+            let stop = async {
+                while metrics
+                    .snapshot()
+                    .get("telegram")
+                    .is_none_or(|s| s.processed < 6)
+                {
+                    assert!(metrics.queue_depth("telegram") <= 2);
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                cancel.cancel();
+            };
+            tokio::join!(run, stop);
+
+            assert_eq!(metrics.snapshot()["telegram"].shed, 0);
+            assert_eq!(agent.log.lock().unwrap().len(), 6);
+        }
+
+        #[tokio::test]
+        async fn intercepted_messages_skip_the_session_queue() {
+            let bus = Arc::new(MessageBus::new());
+            for content in ["work", "more work", "stop"] {
+                bus.publish_inbound(inbound("alice", content)).unwrap();
+            }
+            let agent = SlowAgent::default();
+            let metrics = Arc::new(DispatchMetrics::new());
+            let cancel = clawft_plugin::CancellationToken::new();
+            let intercepted = Mutex::new(Vec::new());
+
+            let cfg = config(1, 10, 10);
+            let run = run_dispatch(
+                &bus,
+                &cfg,
+                metrics.clone(),
+                Some(&cancel),
+                |m| {
+                    let stop = m.content == "stop";
+                    if stop {
+                        // Only the first message has started by now.
+                        let seen = agent.log.lock().unwrap().len();
+                        intercepted.lock().unwrap().push(seen);
+                    }
+                    stop
+                },
+                |m| agent.handle(m),
+            );
+            let stop = async {
+                while metrics
+                    .snapshot()
+                    .get("telegram")
+                    .is_none_or(|s| s.processed < 2)
+                {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                cancel.cancel();
+            };
+            tokio::join!(run, stop);
+
+            assert_eq!(*intercepted.lock().unwrap(), vec![0]);
+            let log = agent.log.lock().unwrap();
+            let seen: Vec<&str> = log.iter().map(|(_, m)| m.as_str()).collect();
+            assert_eq!(seen, ["work", "more work"]);
+        }
+    }
+}
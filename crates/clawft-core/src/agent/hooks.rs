@@ -0,0 +1,625 @@
+//! Agent loop hooks.
+//!
+//! A [`Hook`] is called by the [`AgentLoop`](super::loop_core::AgentLoop)
+//! around every tool call and LLM completion:
+//!
+//! | Event | Called with | May change |
+//! |-------|-------------|------------|
+//! | [`on_pre_tool`](Hook::on_pre_tool) | the call about to run | its arguments |
+//! | [`on_post_tool`](Hook::on_post_tool) | the call and its result | the result |
+//! | [`on_pre_completion`](Hook::on_pre_completion) | the request about to be sent | the request |
+//! | [`on_post_completion`](Hook::on_post_completion) | the model's response | the response |
+//!
+//! Each may also veto. A vetoed tool call does not run: the model gets a
+//! tool error naming the hook and its reason, so it can explain the policy
+//! or try something else. A vetoed completion ends the turn with that
+//! reason as the reply.
+//!
+//! Hooks live in a [`HookRegistry`] and run in registration order; the
+//! first veto stops the rest. A hook that takes longer than the registry's
+//! timeout is skipped (with a warning) and has no effect on the decision.
+//!
+//! Two built-in hooks are enabled from the `hooks` config section:
+//! [`CommandBlocker`] and, on native targets, [`PromptLogger`].
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::Value;
+use tracing::warn;
+
+use clawft_types::config::HooksConfig;
+use clawft_types::error::ClawftError;
+use clawft_types::provider::LlmResponse;
+
+use crate::pipeline::traits::ChatRequest;
+use crate::tools::registry::ToolError;
+
+/// What a hook call is about.
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    /// The agent handling the turn (`"default"` unless a channel set one).
+    pub agent_id: &'a str,
+    /// The session the turn belongs to.
+    pub session_key: &'a str,
+    /// Index of the completion within the turn's tool loop (0 first).
+    pub iteration: usize,
+}
+
+/// A tool call the model asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    /// Call ID assigned by the model.
+    pub id: String,
+    /// Tool name.
+    pub name: String,
+    /// Arguments.
+    pub input: Value,
+}
+
+/// A hook's answer for one event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookDecision {
+    /// Let the call go ahead (with any changes the hook made).
+    Continue,
+    /// Stop the call, for the given reason.
+    Veto(String),
+}
+
+/// A veto, with the hook that issued it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookVeto {
+    /// Name of the vetoing hook.
+    pub hook: String,
+    /// Why it vetoed.
+    pub reason: String,
+}
+
+impl fmt::Display for HookVeto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blocked by hook '{}': {}", self.hook, self.reason)
+    }
+}
+
+impl HookVeto {
+    /// The tool error the model sees in place of the vetoed call's result.
+    pub fn into_tool_error(self, tool: &str) -> ToolError {
+        ToolError::PermissionDenied {
+            tool: tool.to_string(),
+            reason: self.to_string(),
+        }
+    }
+}
+
+/// Extension point around tool calls and LLM completions.
+///
+/// Every method defaults to [`HookDecision::Continue`] without changes, so
+/// a hook implements only the events it cares about.
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+pub trait Hook: Send + Sync {
+    /// Name used in logs and veto messages.
+    fn name(&self) -> &str;
+
+    /// Before a tool runs. `call.input` may be rewritten.
+    async fn on_pre_tool(&self, _ctx: &HookContext<'_>, _call: &mut ToolCall) -> HookDecision {
+        HookDecision::Continue
+    }
+
+    /// After a tool ran. `result` may be rewritten; a veto replaces it
+    /// with an error.
+    async fn on_post_tool(
+        &self,
+        _ctx: &HookContext<'_>,
+        _call: &ToolCall,
+        _result: &mut Result<Value, ToolError>,
+    ) -> HookDecision {
+        HookDecision::Continue
+    }
+
+    /// Before a request goes to the LLM. Messages may be added, removed
+    /// or rewritten.
+    async fn on_pre_completion(
+        &self,
+        _ctx: &HookContext<'_>,
+        _request: &mut ChatRequest,
+    ) -> HookDecision {
+        HookDecision::Continue
+    }
+
+    /// After the LLM responded. The response may be rewritten.
+    async fn on_post_completion(
+        &self,
+        _ctx: &HookContext<'_>,
+        _response: &mut LlmResponse,
+    ) -> HookDecision {
+        HookDecision::Continue
+    }
+}
+
+/// Default time one hook may take on one event.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ordered set of hooks with a per-hook timeout.
+pub struct HookRegistry {
+    hooks: Vec<Arc<dyn Hook>>,
+    timeout: Duration,
+}
+
+impl Default for HookRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_HOOK_TIMEOUT)
+    }
+}
+
+impl HookRegistry {
+    /// An empty registry whose hooks each get `timeout` per event.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            hooks: Vec::new(),
+            timeout,
+        }
+    }
+
+    /// A registry with the built-in hooks enabled in `config`.
+    ///
+    /// Fails with [`ClawftError::ConfigInvalid`] if a command blocker
+    /// pattern is not a valid regular expression or the prompt log cannot
+    /// be opened.
+    pub fn from_config(config: &HooksConfig) -> clawft_types::Result<Self> {
+        let mut registry = Self::new(Duration::from_millis(config.timeout_ms));
+
+        let blocker = &config.command_blocker;
+        if blocker.enabled {
+            registry.register(Arc::new(CommandBlocker::new(
+                &blocker.patterns,
+                blocker.tools.clone(),
+            )?));
+        }
+
+        #[cfg(feature = "native")]
+        if config.prompt_logger.enabled {
+            let path = match &config.prompt_logger.path {
+                Some(path) => std::path::PathBuf::from(path),
+                None => dirs::home_dir()
+                    .ok_or_else(|| ClawftError::ConfigInvalid {
+                        reason: "hooks.promptLogger: cannot determine home directory".into(),
+                    })?
+                    .join(".clawft")
+                    .join("state")
+                    .join(PROMPT_LOG_FILE),
+            };
+            registry.register(Arc::new(PromptLogger::open(path)?));
+        }
+
+        Ok(registry)
+    }
+
+    /// Add `hook` after the ones already registered.
+    pub fn register(&mut self, hook: Arc<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    /// Number of registered hooks.
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Whether no hooks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Names of the registered hooks, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.hooks.iter().map(|h| h.name()).collect()
+    }
+
+    /// Run [`Hook::on_pre_tool`] on every hook.
+    pub async fn pre_tool(
+        &self,
+        ctx: &HookContext<'_>,
+        call: &mut ToolCall,
+    ) -> Result<(), HookVeto> {
+        for hook in &self.hooks {
+            let decision = crate::runtime::timeout(self.timeout, hook.on_pre_tool(ctx, call)).await;
+            self.settle(hook.as_ref(), "pre_tool", decision)?;
+        }
+        Ok(())
+    }
+
+    /// Run [`Hook::on_post_tool`] on every hook.
+    pub async fn post_tool(
+        &self,
+        ctx: &HookContext<'_>,
+        call: &ToolCall,
+        result: &mut Result<Value, ToolError>,
+    ) -> Result<(), HookVeto> {
+        for hook in &self.hooks {
+            let decision =
+                crate::runtime::timeout(self.timeout, hook.on_post_tool(ctx, call, result)).await;
+            self.settle(hook.as_ref(), "post_tool", decision)?;
+        }
+        Ok(())
+    }
+
+    /// Run [`Hook::on_pre_completion`] on every hook.
+    pub async fn pre_completion(
+        &self,
+        ctx: &HookContext<'_>,
+        request: &mut ChatRequest,
+    ) -> Result<(), HookVeto> {
+        for hook in &self.hooks {
+            let decision =
+                crate::runtime::timeout(self.timeout, hook.on_pre_completion(ctx, request)).await;
+            self.settle(hook.as_ref(), "pre_completion", decision)?;
+        }
+        Ok(())
+    }
+
+    /// Run [`Hook::on_post_completion`] on every hook.
+    pub async fn post_completion(
+        &self,
+        ctx: &HookContext<'_>,
+        response: &mut LlmResponse,
+    ) -> Result<(), HookVeto> {
+        for hook in &self.hooks {
+            let decision =
+                crate::runtime::timeout(self.timeout, hook.on_post_completion(ctx, response)).await;
+            self.settle(hook.as_ref(), "post_completion", decision)?;
+        }
+        Ok(())
+    }
+
+    /// Turn one hook's decision (`None` if it timed out) into a result.
+    fn settle(
+        &self,
+        hook: &dyn Hook,
+        event: &str,
+        decision: Option<HookDecision>,
+    ) -> Result<(), HookVeto> {
+        match decision {
+            Some(HookDecision::Continue) => Ok(()),
+            Some(HookDecision::Veto(reason)) => Err(HookVeto {
+                hook: hook.name().to_string(),
+                reason,
+            }),
+            None => {
+                warn!(
+                    hook = hook.name(),
+                    event,
+                    timeout_ms = self.timeout.as_millis() as u64,
+                    "hook timed out; skipped"
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+// ── Built-in: command blocker ──────────────────────────────────────────
+
+/// Vetoes tool calls whose `command` argument matches a regular
+/// expression, e.g. any shell command touching `/prod`.
+pub struct CommandBlocker {
+    patterns: Vec<Regex>,
+    tools: Vec<String>,
+}
+
+impl CommandBlocker {
+    /// Block commands matching any of `patterns` in the listed `tools`.
+    pub fn new(patterns: &[String], tools: Vec<String>) -> clawft_types::Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).map_err(|e| ClawftError::ConfigInvalid {
+                    reason: format!("hooks.commandBlocker: invalid pattern '{p}': {e}"),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns, tools })
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl Hook for CommandBlocker {
+    fn name(&self) -> &str {
+        "command_blocker"
+    }
+
+    async fn on_pre_tool(&self, _ctx: &HookContext<'_>, call: &mut ToolCall) -> HookDecision {
+        if !self.tools.iter().any(|t| t == &call.name) {
+            return HookDecision::Continue;
+        }
+        let Some(command) = call.input.get("command").and_then(|v| v.as_str()) else {
+            return HookDecision::Continue;
+        };
+        match self.patterns.iter().find(|p| p.is_match(command)) {
+            Some(pattern) => HookDecision::Veto(format!(
+                "command matches blocked pattern `{}`; this policy is set by the operator",
+                pattern.as_str()
+            )),
+            None => HookDecision::Continue,
+        }
+    }
+}
+
+// ── Built-in: prompt logger ────────────────────────────────────────────
+
+/// File name of the prompt log, inside `~/.clawft/state/`.
+pub const PROMPT_LOG_FILE: &str = "prompts.jsonl";
+
+#[cfg(feature = "native")]
+pub use prompt_log::PromptLogger;
+
+#[cfg(feature = "native")]
+mod prompt_log {
+    use std::fs::{self, File, OpenOptions};
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use serde_json::json;
+    use tracing::warn;
+
+    use clawft_types::error::ClawftError;
+
+    use super::{Hook, HookContext, HookDecision};
+    use crate::pipeline::traits::ChatRequest;
+
+    /// Appends a JSON line for every LLM request: time, agent, session,
+    /// model, message count and the newest message.
+    ///
+    /// Meant to be shipped to a log collector; it never vetoes.
+    pub struct PromptLogger {
+        path: PathBuf,
+        file: Mutex<File>,
+    }
+
+    impl PromptLogger {
+        /// Open (or create) the log at `path` for appending.
+        pub fn open(path: impl Into<PathBuf>) -> clawft_types::Result<Self> {
+            let path = path.into();
+            let invalid = |e: std::io::Error| ClawftError::ConfigInvalid {
+                reason: format!("hooks.promptLogger: cannot open {}: {e}", path.display()),
+            };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(invalid)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(invalid)?;
+            Ok(Self {
+                path,
+                file: Mutex::new(file),
+            })
+        }
+
+        /// Where the log is written.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    #[cfg_attr(not(feature = "browser"), async_trait)]
+    #[cfg_attr(feature = "browser", async_trait(?Send))]
+    impl Hook for PromptLogger {
+        fn name(&self) -> &str {
+            "prompt_logger"
+        }
+
+        async fn on_pre_completion(
+            &self,
+            ctx: &HookContext<'_>,
+            request: &mut ChatRequest,
+        ) -> HookDecision {
+            let last = request.messages.last();
+            let entry = json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "agent_id": ctx.agent_id,
+                "session_key": ctx.session_key,
+                "iteration": ctx.iteration,
+                "model": request.model,
+                "message_count": request.messages.len(),
+                "role": last.map(|m| m.role.as_str()),
+                "content": last.map(|m| m.content.as_str()),
+            });
+            let written = self
+                .file
+                .lock()
+                .map_err(|_| std::io::Error::other("prompt log lock poisoned"))
+                .and_then(|mut file| writeln!(file, "{entry}"));
+            if let Err(e) = written {
+                warn!(path = %self.path.display(), error = %e, "failed to write prompt log");
+            }
+            HookDecision::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CTX: HookContext<'static> = HookContext {
+        agent_id: "default",
+        session_key: "cli:test",
+        iteration: 0,
+    };
+
+    fn shell_call(command: &str) -> ToolCall {
+        ToolCall {
+            id: "call-1".into(),
+            name: "exec_shell".into(),
+            input: json!({ "command": command }),
+        }
+    }
+
+    /// Appends its name to every command, or vetoes when told to.
+    struct Tagger {
+        name: &'static str,
+        veto: bool,
+    }
+
+    #[async_trait]
+    impl Hook for Tagger {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn on_pre_tool(&self, _ctx: &HookContext<'_>, call: &mut ToolCall) -> HookDecision {
+            if self.veto {
+                return HookDecision::Veto("not today".into());
+            }
+            let command = call.input["command"].as_str().unwrap_or_default();
+            call.input["command"] = json!(format!("{command} {}", self.name));
+            HookDecision::Continue
+        }
+    }
+
+    fn tagger(name: &'static str, veto: bool) -> Arc<dyn Hook> {
+        Arc::new(Tagger { name, veto })
+    }
+
+    struct Sleeper;
+
+    #[async_trait]
+    impl Hook for Sleeper {
+        fn name(&self) -> &str {
+            "sleeper"
+        }
+
+        async fn on_pre_tool(&self, _ctx: &HookContext<'_>, _call: &mut ToolCall) -> HookDecision {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            HookDecision::Veto("too late".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn hooks_run_in_order_and_stop_at_first_veto() {
+        let mut registry = HookRegistry::default();
+        registry.register(tagger("a", false));
+        registry.register(tagger("b", false));
+        let mut call = shell_call("ls");
+        registry.pre_tool(&CTX, &mut call).await.unwrap();
+        assert_eq!(call.input["command"], "ls a b");
+
+        registry.register(tagger("c", true));
+        registry.register(tagger("d", false));
+        let mut call = shell_call("ls");
+        let veto = registry.pre_tool(&CTX, &mut call).await.unwrap_err();
+        assert_eq!(veto.hook, "c");
+        assert_eq!(call.input["command"], "ls a b");
+        assert_eq!(
+            veto.into_tool_error("exec_shell").to_string(),
+            "permission denied for tool 'exec_shell': blocked by hook 'c': not today"
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_hook_is_skipped() {
+        let mut registry = HookRegistry::new(Duration::from_millis(20));
+        registry.register(Arc::new(Sleeper));
+        registry.register(tagger("a", false));
+        let mut call = shell_call("ls");
+        registry.pre_tool(&CTX, &mut call).await.unwrap();
+        assert_eq!(call.input["command"], "ls a");
+    }
+
+    #[tokio::test]
+    async fn command_blocker_vetoes_matching_commands() {
+        let blocker = CommandBlocker::new(&[r"/prod\b".into()], vec!["exec_shell".into()]).unwrap();
+
+        let decision = blocker
+            .on_pre_tool(&CTX, &mut shell_call("rm -rf /prod/data"))
+            .await;
+        let HookDecision::Veto(reason) = decision else {
+            panic!("expected a veto, got {decision:?}");
+        };
+        assert!(reason.contains(r"/prod\b"), "{reason}");
+
+        let allowed = blocker
+            .on_pre_tool(&CTX, &mut shell_call("ls /production"))
+            .await;
+        assert_eq!(allowed, HookDecision::Continue);
+
+        let mut other_tool = shell_call("cat /prod/x");
+        other_tool.name = "read_file".into();
+        assert_eq!(
+            blocker.on_pre_tool(&CTX, &mut other_tool).await,
+            HookDecision::Continue
+        );
+    }
+
+    #[test]
+    fn command_blocker_rejects_bad_patterns() {
+        let err = CommandBlocker::new(&["(".into()], vec![]).err().unwrap();
+        assert!(matches!(err, ClawftError::ConfigInvalid { .. }), "{err}");
+    }
+
+    #[test]
+    fn from_config_registers_enabled_builtins() {
+        let mut config = HooksConfig::default();
+        assert!(HookRegistry::from_config(&config).unwrap().is_empty());
+
+        config.command_blocker.enabled = true;
+        config.command_blocker.patterns = vec!["/prod".into()];
+        let path = std::env::temp_dir().join(format!(
+            "clawft_hooks_config_{}/prompts.jsonl",
+            std::process::id()
+        ));
+        config.prompt_logger.enabled = true;
+        config.prompt_logger.path = Some(path.to_string_lossy().into_owned());
+        let registry = HookRegistry::from_config(&config).unwrap();
+        assert_eq!(registry.names(), vec!["command_blocker", "prompt_logger"]);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn prompt_logger_appends_one_line_per_request() {
+        use crate::pipeline::traits::LlmMessage;
+
+        let dir = std::env::temp_dir().join(format!("clawft_prompt_log_{}", std::process::id()));
+        let logger = PromptLogger::open(dir.join("prompts.jsonl")).unwrap();
+        let mut request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "deploy to staging".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: None,
+            temperature: None,
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        for _ in 0..2 {
+            let decision = logger.on_pre_completion(&CTX, &mut request).await;
+            assert_eq!(decision, HookDecision::Continue);
+        }
+
+        let log = std::fs::read_to_string(logger.path()).unwrap();
+        let lines: Vec<Value> = log
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["session_key"], "cli:test");
+        assert_eq!(lines[0]["model"], "test-model");
+        assert_eq!(lines[0]["content"], "deploy to staging");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -10,6 +10,9 @@
 //! Session lookup / creation
 //!   |
 //!   v
+//! History compaction (summarize messages evicted from memory_window)
+//!   |
+//!   v
 //! ContextBuilder.build_messages()
 //!   |
 //!   v
@@ -18,33 +21,76 @@
 //!   v
 //! Tool execution loop (up to max_tool_iterations)
 //!   |  - Extract tool calls from LLM response
-//!   |  - Execute each tool via ToolRegistry
+//!   |  - Execute tools via ToolRegistry (concurrently when parallel-safe)
 //!   |  - Append tool results to context
 //!   |  - Re-invoke LLM if stop_reason == ToolUse
 //!   |
 //!   v
 //! Outbound Message (dispatched to MessageBus)
 //! ```
+//!
+//! While a turn runs, its text deltas and tool calls are reported to the
+//! message's [`ResponseSink`] (see [`sink`](super::sink)). Every tool call
+//! and completion passes through the registered [`hooks`](super::hooks),
+//! which may modify or veto it. A turn can be cancelled mid-flight (see
+//! [`turns`](super::turns)); the session then records what was said
+//! before the cancellation, with a marker.
+//!
+//! In planning mode (`/plan <task>`, or `agents.planning.enabled`) a turn
+//! drafts a plan instead and waits for the user's approval; the approved
+//! plan then runs one step at a time, each step's output checked against
+//! its success criteria (see [`planning`](crate::planning)).
 
 use std::sync::Arc;
 
+use clawft_llm::UsageTracker;
+use clawft_llm::structured::MAX_STRUCTURED_RETRIES;
 use clawft_plugin::CancellationToken;
-use tracing::{debug, error, info, warn};
+use futures_util::StreamExt;
+use tracing::{Instrument, debug, error, info, info_span, warn};
 
 use clawft_platform::Platform;
-use clawft_types::config::AgentsConfig;
+use clawft_types::config::{AgentsConfig, ResultStrategy, ToolResultsConfig};
+use clawft_types::delivery::ProactiveDelivery;
 use clawft_types::error::ClawftError;
 use clawft_types::event::{InboundMessage, OutboundMessage};
-use clawft_types::provider::ContentBlock;
-use clawft_types::routing::AuthContext;
+use clawft_types::provider::{ContentBlock, LlmResponse};
+use clawft_types::routing::{AuthContext, RuleAction};
+use clawft_types::session::Session;
 
+use crate::agent_routing::{ROUTING_TRACE_KEY, RoutingRules};
 use crate::bus::MessageBus;
+use crate::config_reload::{LiveConfig, LiveSettings};
 use crate::pipeline::permissions::PermissionResolver;
-use crate::pipeline::traits::{ChatRequest, LlmMessage, PipelineRegistry};
+use crate::pipeline::router::split_provider_model;
+use crate::pipeline::traits::{
+    ChatRequest, LlmMessage, PipelineRegistry, StreamCallback, TransportRequest,
+};
+use crate::planning::{
+    self, Plan, PlanReply, PlanState, PlanStatus, PlanStep, StepOutcome, StepVerdict,
+};
 use crate::session::SessionManager;
-use crate::tools::registry::ToolRegistry;
+use crate::tools::audit::{Redactor, ToolAuditor};
+use crate::tools::registry::{ScopedTools, ToolError, ToolRegistry};
 
+use super::agents::AgentRegistry;
+use super::budget::{BudgetExceeded, DailySpend, TurnBudget};
+use super::compaction::{self, CompactionState};
 use super::context::ContextBuilder;
+use super::context_budget;
+use super::dispatch::DispatchMetrics;
+use super::hooks::{HookContext, HookRegistry, ToolCall};
+#[cfg(feature = "native")]
+use super::questions::PendingQuestions;
+use super::secrets::{SECRET_USE_TOOL, SecretUseRequest, Secrets};
+use super::sink::{BufferingSink, ResponseSink, ResponseSinkFactory};
+use super::skill_activation::{SkillActivator, SkillDecision};
+use super::skills_v2::SharedSkillRegistry;
+use super::subagent::{self, SPAWN_AGENT_TOOL, SpawnRequest, SpawnResult, SpawnUsage};
+use super::templates::TemplateName;
+use super::tool_results;
+use super::trace::{self, TraceRecorder, TraceSettings, TurnOutcome};
+use super::turns::{ActiveTurns, TurnObserver, TurnReport, is_stop_command};
 use super::verification;
 
 // ---------------------------------------------------------------------------
@@ -69,9 +115,6 @@ pub trait AutoDelegation: Send + Sync {
     fn should_delegate(&self, content: &str) -> Option<serde_json::Value>;
 }
 
-/// Maximum size in bytes for a single tool result.
-const MAX_TOOL_RESULT_BYTES: usize = 65_536;
-
 /// System prompt injected for voice-mode sessions.
 ///
 /// Instructs the LLM to respond in natural conversational language suitable
@@ -95,6 +138,16 @@ Rules:
 - Do not narrate your actions (\"Let me search for...\"). Just provide the answer.
 - Sound warm and natural, not robotic or formal.";
 
+/// System prompt injected for scheduled (heartbeat and cron) prompts.
+///
+/// Nobody is waiting for these replies, so the agent may decline to send
+/// one; see [`clawft_types::delivery::NO_NOTIFY_MARKER`].
+const PROACTIVE_TASK_PROMPT: &str = "\
+# Scheduled Task
+
+This message is a scheduled task, not a message from the user. Your reply is sent to the user as a notification.
+If nothing needs the user's attention, end your reply with NO_NOTIFY and it will not be sent.";
+
 /// Result from the tool loop, including hallucination counters.
 #[derive(Debug)]
 struct ToolLoopResult {
@@ -104,6 +157,13 @@ struct ToolLoopResult {
     hallucinations: usize,
     /// Number of write claims that passed verification.
     verified_successes: usize,
+    /// Whether `text` was streamed to the sink as it was generated.
+    streamed: bool,
+    /// Whether the turn was cancelled; `text` is then the assistant text
+    /// produced before that, possibly empty.
+    cancelled: bool,
+    /// The budget limit that ended the turn; `text` is then its notice.
+    exceeded: Option<String>,
 }
 
 /// The core agent loop that processes inbound messages.
@@ -115,8 +175,9 @@ struct ToolLoopResult {
 /// # Processing flow
 ///
 /// 1. **Consume**: Pull the next [`InboundMessage`] from the [`MessageBus`].
-/// 2. **Session**: Look up or create a [`Session`](clawft_types::session::Session)
-///    keyed by `channel:chat_id`.
+/// 2. **Session**: Look up or create a [`Session`]
+///    keyed by `channel:chat_id`, and fold messages that have left the
+///    history window into its rolling summary (see [`compaction`]).
 /// 3. **Context**: Build the LLM message list via
 ///    [`ContextBuilder::build_messages`].
 /// 4. **Pipeline**: Run the assembled context through the 6-stage pipeline
@@ -135,7 +196,6 @@ pub struct AgentLoop<P: Platform> {
     tools: Arc<ToolRegistry>,
     context: ContextBuilder<P>,
     sessions: Arc<SessionManager<P>>,
-    permission_resolver: PermissionResolver,
     cancel: Option<CancellationToken>,
     /// Optional pre-LLM auto-delegation router.
     ///
@@ -143,6 +203,48 @@ pub struct AgentLoop<P: Platform> {
     /// before the local LLM is invoked. If a rule matches, the
     /// `delegate_task` tool is called directly, bypassing the LLM.
     auto_delegation: Option<Arc<dyn AutoDelegation>>,
+    /// Per-channel queue depth and throughput counters for inbound dispatch.
+    dispatch_metrics: Arc<DispatchMetrics>,
+    /// Optional token usage and cost accounting.
+    ///
+    /// When set, every completion is recorded against the session, and
+    /// `usage.session_budget_usd` is enforced before each LLM call.
+    usage: Option<Arc<UsageTracker>>,
+    /// Today's spend across sessions, for `budget.max_cost_per_day_usd`.
+    ///
+    /// Fed from the usage tracker's costs, so it needs one to be attached.
+    daily_spend: Option<Arc<DailySpend>>,
+    /// Optional per-message response sinks for streaming replies.
+    ///
+    /// Messages without a sink are answered with non-streaming completions.
+    sinks: Option<Arc<dyn ResponseSinkFactory>>,
+    /// Optional tool execution audit log.
+    audit: Option<Arc<ToolAuditor>>,
+    /// Secrets for `secret_use`, scrubbed from every tool result.
+    secrets: Option<Arc<Secrets>>,
+    /// Post-processing of oversized tool results (`tools.results`).
+    tool_results: ToolResultsConfig,
+    /// Redaction rules for turn traces (`tools.audit.redact_fields`).
+    trace_redactor: Redactor,
+    /// Skills activated automatically from message content
+    /// (`agents.skills.autoActivate`).
+    skills: Option<SkillActivator>,
+    /// Agent definitions, consulted for the addressed agent's
+    /// `allowed_tools` on each turn.
+    agents: Option<Arc<AgentRegistry>>,
+    /// Hooks run around every tool call and completion.
+    hooks: Arc<HookRegistry>,
+    /// Defaults, routing rules, permissions and spawn depth, which a
+    /// config reload may change. Each turn works from the snapshot taken
+    /// when it starts.
+    live: Arc<LiveConfig>,
+    /// Turns in flight, for cancellation.
+    turns: Arc<ActiveTurns>,
+    /// Told how each turn ended.
+    turn_observers: Vec<Arc<dyn TurnObserver>>,
+    /// Questions asked mid-turn; their sessions' next messages answer them.
+    #[cfg(feature = "native")]
+    questions: Option<Arc<PendingQuestions>>,
 }
 
 impl<P: Platform> AgentLoop<P> {
@@ -168,6 +270,13 @@ impl<P: Platform> AgentLoop<P> {
         sessions: Arc<SessionManager<P>>,
         permission_resolver: PermissionResolver,
     ) -> Self {
+        let live = Arc::new(LiveConfig::new(LiveSettings {
+            defaults: config.defaults.clone(),
+            routing_rules: None,
+            permissions: Arc::new(permission_resolver),
+            max_spawn_depth: 0,
+            pinned_model: None,
+        }));
         Self {
             config,
             platform,
@@ -176,9 +285,24 @@ impl<P: Platform> AgentLoop<P> {
             tools,
             context,
             sessions,
-            permission_resolver,
             cancel: None,
             auto_delegation: None,
+            dispatch_metrics: Arc::new(DispatchMetrics::new()),
+            usage: None,
+            daily_spend: None,
+            sinks: None,
+            audit: None,
+            secrets: None,
+            tool_results: ToolResultsConfig::default(),
+            trace_redactor: Redactor::default(),
+            skills: None,
+            agents: None,
+            hooks: Arc::new(HookRegistry::default()),
+            live,
+            turns: Arc::new(ActiveTurns::new()),
+            turn_observers: Vec::new(),
+            #[cfg(feature = "native")]
+            questions: None,
         }
     }
 
@@ -198,11 +322,128 @@ impl<P: Platform> AgentLoop<P> {
         self
     }
 
-    /// Get a reference to the agent configuration.
+    /// Attach a usage tracker that records every completion and enforces
+    /// the per-session budget.
+    pub fn with_usage_tracker(mut self, usage: Arc<UsageTracker>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Attach the daily spend counter checked against
+    /// `budget.max_cost_per_day_usd`.
+    pub fn with_daily_spend(mut self, daily: Arc<DailySpend>) -> Self {
+        self.daily_spend = Some(daily);
+        self
+    }
+
+    /// Attach a factory that opens a [`ResponseSink`] per inbound message,
+    /// so replies are streamed to it as they are generated.
+    pub fn with_response_sinks(mut self, sinks: Arc<dyn ResponseSinkFactory>) -> Self {
+        self.sinks = Some(sinks);
+        self
+    }
+
+    /// Attach an audit log that records every tool execution.
+    pub fn with_tool_audit(mut self, audit: Arc<ToolAuditor>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Attach the secrets `secret_use` fills into tool arguments. Their
+    /// values are scrubbed from every tool result (see
+    /// [`secrets`](super::secrets)).
+    pub fn with_secrets(mut self, secrets: Arc<Secrets>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Post-process oversized tool results with `config` instead of the
+    /// defaults (see [`tool_results`](super::tool_results)).
+    pub fn with_tool_results(mut self, config: ToolResultsConfig) -> Self {
+        self.tool_results = config;
+        self
+    }
+
+    /// Redact turn traces (see [`trace`](super::trace)) with `redactor`
+    /// instead of the default rules.
+    pub fn with_trace_redactor(mut self, redactor: Redactor) -> Self {
+        self.trace_redactor = redactor;
+        self
+    }
+
+    /// Attach the skills that `agents.skills.autoActivate` chooses from.
+    pub fn with_skill_registry(mut self, registry: SharedSkillRegistry) -> Self {
+        self.skills = Some(SkillActivator::new(registry, self.config.skills.clone()));
+        self
+    }
+
+    /// Attach agent definitions. A message addressed to an agent (the
+    /// `agent` metadata key) may only use that agent's `allowed_tools`.
+    pub fn with_agents(mut self, agents: Arc<AgentRegistry>) -> Self {
+        self.agents = Some(agents);
+        self
+    }
+
+    /// Attach message routing rules. The first rule matching a message
+    /// routes it to an agent, drops it, or holds it for confirmation.
+    pub fn with_routing_rules(self, rules: Arc<RoutingRules>) -> Self {
+        self.live.update(|live| live.routing_rules = Some(rules));
+        self
+    }
+
+    /// Let `spawn_agent` run sub-agents up to `depth` levels deep
+    /// (`delegation.max_spawn_depth`). Sub-agents are chosen from the
+    /// definitions attached with [`with_agents`](Self::with_agents).
+    pub fn with_max_spawn_depth(self, depth: u32) -> Self {
+        self.live
+            .update(|live| live.max_spawn_depth = depth as usize);
+        self
+    }
+
+    /// Share `live` settings with a [`ConfigReloader`](crate::config_reload::ConfigReloader),
+    /// replacing those the loop was built with.
+    pub fn with_live_config(mut self, live: Arc<LiveConfig>) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Attach hooks to run around every tool call and completion.
+    pub fn with_hooks(mut self, hooks: HookRegistry) -> Self {
+        self.hooks = Arc::new(hooks);
+        self
+    }
+
+    /// Report how each turn ends to `observer`, as well as to any
+    /// observers attached before.
+    pub fn with_turn_observer(mut self, observer: Arc<dyn TurnObserver>) -> Self {
+        self.turn_observers.push(observer);
+        self
+    }
+
+    /// Hand each session's messages to the question waiting there, if any,
+    /// instead of queueing them.
+    #[cfg(feature = "native")]
+    pub fn with_questions(mut self, questions: Arc<PendingQuestions>) -> Self {
+        self.questions = Some(questions);
+        self
+    }
+
+    /// The attached usage tracker, if any.
+    pub fn usage_tracker(&self) -> Option<&Arc<UsageTracker>> {
+        self.usage.as_ref()
+    }
+
+    /// Get a reference to the agent configuration the loop was built
+    /// with. Reloaded settings are in [`live_config`](Self::live_config).
     pub fn config(&self) -> &AgentsConfig {
         &self.config
     }
 
+    /// The settings a config reload may change.
+    pub fn live_config(&self) -> Arc<LiveConfig> {
+        self.live.clone()
+    }
+
     /// Get a reference to the platform.
     pub fn platform(&self) -> &Arc<P> {
         &self.platform
@@ -213,42 +454,87 @@ impl<P: Platform> AgentLoop<P> {
         &self.bus
     }
 
+    /// The turns in flight. Cancel one to abort it, e.g. on Ctrl-C.
+    pub fn active_turns(&self) -> Arc<ActiveTurns> {
+        self.turns.clone()
+    }
+
+    /// Shared inbound dispatch metrics (queue depths per channel).
+    pub fn dispatch_metrics(&self) -> Arc<DispatchMetrics> {
+        self.dispatch_metrics.clone()
+    }
+
     /// Run the agent loop, consuming messages until the bus is closed or
     /// the optional [`CancellationToken`] is triggered.
     ///
     /// This is the main entrypoint. It pulls messages from the inbound
     /// channel and processes each one through the full pipeline. Errors
     /// on individual messages are logged but do not terminate the loop.
+    ///
+    /// On native targets messages are scheduled by
+    /// [`run_dispatch`](super::dispatch::run_dispatch): strictly ordered
+    /// within a session, with up to `dispatch.max_concurrent_sessions`
+    /// sessions in flight.
+    #[cfg(feature = "native")]
     pub async fn run(&self) -> clawft_types::Result<()> {
-        info!("agent loop started, waiting for messages");
+        info!(
+            max_concurrent_sessions = self.config.dispatch.max_concurrent_sessions,
+            "agent loop started, waiting for messages"
+        );
 
-        loop {
-            let msg = if let Some(ref token) = self.cancel {
-                #[cfg(feature = "native")]
+        super::dispatch::run_dispatch(
+            &self.bus,
+            &self.config.dispatch,
+            self.dispatch_metrics.clone(),
+            self.cancel.as_ref(),
+            |msg| {
+                if let Some(questions) = &self.questions
+                    && questions.answer(msg)
                 {
-                    tokio::select! {
-                        biased;
-                        _ = token.cancelled() => {
-                            info!("agent loop cancelled via token, exiting");
-                            break;
-                        }
-                        msg = self.bus.consume_inbound() => msg,
-                    }
+                    return true;
                 }
-                #[cfg(not(feature = "native"))]
-                {
-                    // On browser, poll cancellation between messages.
-                    if token.is_cancelled() {
-                        info!("agent loop cancelled via token, exiting");
-                        break;
-                    }
-                    self.bus.consume_inbound().await
+                if !is_stop_command(&msg.content) {
+                    return false;
                 }
-            } else {
-                self.bus.consume_inbound().await
-            };
+                if let Err(e) = self.handle_stop_command(msg) {
+                    warn!(error = %e, "failed to handle stop command");
+                }
+                true
+            },
+            |msg| async move {
+                debug!(
+                    channel = %msg.channel,
+                    chat_id = %msg.chat_id,
+                    "processing inbound message"
+                );
+                if let Err(e) = self.process_message(msg).await {
+                    error!("failed to process message: {}", e);
+                }
+            },
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Run the agent loop, consuming messages until the bus is closed or
+    /// the optional [`CancellationToken`] is triggered.
+    ///
+    /// On browser targets messages are processed one at a time.
+    #[cfg(not(feature = "native"))]
+    pub async fn run(&self) -> clawft_types::Result<()> {
+        info!("agent loop started, waiting for messages");
 
-            match msg {
+        loop {
+            // On browser, poll cancellation between messages.
+            if let Some(ref token) = self.cancel
+                && token.is_cancelled()
+            {
+                info!("agent loop cancelled via token, exiting");
+                break;
+            }
+
+            match self.bus.consume_inbound().await {
                 Some(msg) => {
                     debug!(
                         channel = %msg.channel,
@@ -273,10 +559,46 @@ impl<P: Platform> AgentLoop<P> {
     ///
     /// Handles session lookup, context building, pipeline invocation,
     /// the tool execution loop, session persistence, and outbound dispatch.
+    /// Runs in a `turn` span carrying the session, channel and agent, so
+    /// every event logged during the turn can be attributed to them.
     async fn process_message(&self, msg: InboundMessage) -> clawft_types::Result<()> {
+        let span = info_span!(
+            "turn",
+            session = %msg.session_key(),
+            channel = %msg.channel,
+            agent = agent_id(&msg),
+        );
+        self.process_turn(msg).instrument(span).await
+    }
+
+    async fn process_turn(&self, msg: InboundMessage) -> clawft_types::Result<()> {
         let session_key = msg.session_key();
+        let settings = self.turn_settings(&msg);
+
+        // 0. A stop command cancels the session's turn. On native targets
+        //    dispatch intercepts it before it could queue behind that turn.
+        if is_stop_command(&msg.content) {
+            return self.handle_stop_command(&msg);
+        }
 
-        // 0. Pre-LLM auto-delegation check.
+        // 0a. `/fork [n]` branches the conversation without calling the LLM.
+        if let Some(at) = parse_fork_command(&msg.content) {
+            return self.run_fork_command(&msg, &session_key, at).await;
+        }
+
+        // 0b. Planning mode: `/plan <task>` (or any message, when enabled)
+        //     drafts a plan for approval; the next message answers it.
+        if self.run_plan_mode(&settings, &msg, &session_key).await? {
+            return Ok(());
+        }
+
+        // 0c. Routing rules may hand the message to an agent, drop it, or
+        //     hold it until the sender confirms.
+        let Some(msg) = self.apply_routing_rules(&settings, msg).await? else {
+            return Ok(());
+        };
+
+        // 0d. Pre-LLM auto-delegation check.
         //    If an AutoDelegation router is configured and the message matches
         //    a delegation rule, invoke `delegate_task` directly and skip the
         //    local LLM pipeline entirely.
@@ -287,18 +609,44 @@ impl<P: Platform> AgentLoop<P> {
                 task = %msg.content,
                 "auto-delegation triggered, invoking delegate_task"
             );
-            return self.run_auto_delegation(&msg, delegate_args).await;
+            return self
+                .run_auto_delegation(&settings, &msg, delegate_args)
+                .await;
         }
 
+        // 0e. Activate the skill the message matches, unless one was
+        //     chosen explicitly (`/use` in the REPL).
+        let (msg, skill_decision) = self.activate_skill(msg).await;
+
         // 1. Get or create session
         let mut session = self.sessions.get_or_create(&session_key).await?;
+        if let Some(trace) = msg.metadata.get(ROUTING_TRACE_KEY) {
+            session
+                .metadata
+                .insert(ROUTING_TRACE_KEY.into(), trace.clone());
+        }
+
+        // 1b. Summarize history evicted from the window. The summary is
+        //     stored with the session; the messages themselves are kept.
+        self.compact_history(&settings, &mut session, &session_key)
+            .await;
 
         // 2. Build context messages from memory, skills, and history BEFORE
         //    adding the user message to session (to avoid duplicate).
-        let context_messages = self.context.build_messages(&session, &[]).await;
+        let (context_messages, context_report) =
+            self.context.build_messages_with_report(&session, &[]).await;
+        if let Ok(report) = serde_json::to_value(&context_report) {
+            session
+                .metadata
+                .insert(context_budget::CONTEXT_REPORT_KEY.into(), report);
+        }
 
         // 3. Add user message to session (after building context)
-        session.add_message("user", &msg.content, None);
+        session.add_message(
+            "user",
+            &msg.content,
+            Session::attachment_extras(&msg.attachments),
+        );
 
         // 4. Context messages are already pipeline::traits::LlmMessage (B2 unification).
         let mut messages: Vec<LlmMessage> = context_messages;
@@ -318,6 +666,8 @@ impl<P: Platform> AgentLoop<P> {
                 content: format!("# Active Skill Instructions\n\n{instructions}"),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             });
         }
 
@@ -333,40 +683,39 @@ impl<P: Platform> AgentLoop<P> {
                 content: VOICE_MODE_PROMPT.into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             });
         }
 
-        // 5. Add current user message
-        messages.push(LlmMessage {
-            role: "user".into(),
-            content: msg.content.clone(),
-            tool_call_id: None,
-            tool_calls: None,
-        });
+        // 4d. Scheduled prompts may end their reply with NO_NOTIFY to
+        //     stay silent.
+        if ProactiveDelivery::from_metadata(&msg.metadata).is_some() {
+            messages.push(LlmMessage {
+                role: "system".into(),
+                content: PROACTIVE_TASK_PROMPT.into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            });
+        }
+
+        // 5. Add current user message (image attachments become image parts)
+        messages.push(self.context.build_user_message(&msg).await);
 
         // 6. Resolve auth context from inbound message identity.
         //    CLI channel gets admin permissions; other channels get zero-trust
         //    defaults with the sender_id and channel attached.
-        let auth_context = self.resolve_auth_context(&msg);
-
-        // 7. Resolve tool schemas -- filter by allowed_tools if present
-        //    in the inbound message metadata (skill-based injection).
-        let tool_schemas = match msg
-            .metadata
-            .get("allowed_tools")
-            .and_then(|v| v.as_array())
-        {
-            Some(tools_arr) => {
-                let allowed: Vec<String> = tools_arr
-                    .iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect();
-                if allowed.is_empty() {
-                    self.tools.schemas()
-                } else {
-                    debug!(allowed_tools = ?allowed, "filtering tools for skill");
-                    self.tools.schemas_for_tools(&allowed)
-                }
+        let auth_context = self.resolve_auth_context(&settings, &msg);
+
+        // 7. Resolve tool schemas -- limited to this turn's allowlist (the
+        //    active skill's allowed_tools intersected with the agent's).
+        let allowed_tools = self.turn_allowed_tools(&msg);
+        let tool_schemas = match &allowed_tools {
+            Some(allowed) => {
+                debug!(allowed_tools = ?allowed, "scoping tools for this turn");
+                self.tools.scoped(allowed).schemas()
             }
             None => self.tools.schemas(),
         };
@@ -391,15 +740,82 @@ impl<P: Platform> AgentLoop<P> {
         let request = ChatRequest {
             messages,
             tools: tool_schemas,
-            model: Some(self.config.defaults.model.clone()),
-            max_tokens: Some(self.config.defaults.max_tokens),
-            temperature: Some(self.config.defaults.temperature),
+            model: Some(settings.defaults.model.clone()),
+            max_tokens: Some(settings.defaults.max_tokens),
+            temperature: Some(settings.defaults.temperature),
             auth_context: Some(auth_context),
             complexity_boost,
+            cache: false,
         };
 
-        // 10. Execute pipeline + tool loop
-        let tool_result = self.run_tool_loop(request).await?;
+        // 9b. Trace the turn when tracing is on for all sessions or asked
+        //     for by the message.
+        let recorder = self.trace_requested(&msg).then(|| {
+            let turn = session
+                .messages
+                .iter()
+                .filter(|m| m["role"] == "user")
+                .count();
+            let recorder = TraceRecorder::new(
+                &session_key,
+                turn,
+                agent_id(&msg),
+                TraceSettings {
+                    model: settings.defaults.model.clone(),
+                    max_tokens: settings.defaults.max_tokens,
+                    temperature: settings.defaults.temperature,
+                    max_tool_iterations: self.turn_iterations(&settings, &msg),
+                    allowed_tools: allowed_tools.clone(),
+                },
+                self.trace_redactor.clone(),
+            );
+            if let Some(decision) = skill_decision {
+                recorder.skill(decision);
+            }
+            recorder.context(&request, &context_report);
+            recorder
+        });
+
+        // 10. Execute pipeline + tool loop, reporting progress to the sink
+        let sink: Arc<dyn ResponseSink> = self
+            .sinks
+            .as_ref()
+            .and_then(|sinks| sinks.open(&msg))
+            .unwrap_or_else(|| Arc::new(BufferingSink::new()));
+        let turn = self.turns.begin(&session_key);
+        let mut budget = self.turn_budget(&msg);
+        let started_at = chrono::Utc::now();
+        let tool_result = self
+            .run_tool_loop(
+                &settings,
+                request,
+                &session_key,
+                agent_id(&msg),
+                allowed_tools.as_deref(),
+                turn.token(),
+                self.turn_iterations(&settings, &msg),
+                &mut budget,
+                &sink,
+                recorder.as_ref(),
+            )
+            .await;
+        drop(turn);
+        self.report_turn(&msg, started_at, &tool_result, &budget);
+        if let Some(recorder) = recorder {
+            let (outcome, error) = match &tool_result {
+                Ok(result) if result.cancelled => (TurnOutcome::Cancelled, None),
+                Ok(_) => (TurnOutcome::Completed, None),
+                Err(e) => (TurnOutcome::Error, Some(e.to_string())),
+            };
+            self.write_trace(recorder.finish(outcome, error)).await;
+        }
+        let tool_result = tool_result?;
+
+        if tool_result.cancelled {
+            return self
+                .finish_cancelled_turn(&msg, session, tool_result.text)
+                .await;
+        }
 
         // 11. Update hallucination score if any write verifications occurred.
         if tool_result.hallucinations > 0 || tool_result.verified_successes > 0 {
@@ -429,21 +845,86 @@ impl<P: Platform> AgentLoop<P> {
         self.sessions.save_session(&session).await?;
 
         // 14. Dispatch outbound
-        let outbound = OutboundMessage {
+        sink.on_complete(&tool_result.text);
+        let mut outbound = OutboundMessage {
             channel: msg.channel.clone(),
             chat_id: msg.chat_id.clone(),
             content: tool_result.text,
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: Default::default(),
         };
-        self.bus.dispatch_outbound(outbound)?;
+        if tool_result.streamed {
+            outbound
+                .metadata
+                .insert(OutboundMessage::STREAMED_KEY.into(), true.into());
+        }
+        self.dispatch_reply(&msg, outbound)?;
 
         debug!(session_key = %session_key, "message processed successfully");
 
         Ok(())
     }
 
+    /// Cancel the in-flight turn of `msg`'s session. The cancelled turn
+    /// sends its own reply; a reply is sent here only when nothing was
+    /// running.
+    fn handle_stop_command(&self, msg: &InboundMessage) -> clawft_types::Result<()> {
+        let session_key = msg.session_key();
+        if self.turns.cancel(&session_key) {
+            info!(session_key = %session_key, "stop command received, cancelling turn");
+            return Ok(());
+        }
+        self.bus.dispatch_outbound(OutboundMessage {
+            channel: msg.channel.clone(),
+            chat_id: msg.chat_id.clone(),
+            content: "Nothing is running in this conversation.".into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: Default::default(),
+        })
+    }
+
+    /// Record a cancelled turn and tell the user.
+    ///
+    /// The session keeps the user message and whatever the assistant said
+    /// before the cancellation, followed by [`CANCELLED_TURN_MARKER`], so
+    /// the next turn sees a complete exchange.
+    async fn finish_cancelled_turn(
+        &self,
+        msg: &InboundMessage,
+        mut session: Session,
+        partial: String,
+    ) -> clawft_types::Result<()> {
+        let content = if partial.is_empty() {
+            CANCELLED_TURN_MARKER.to_string()
+        } else {
+            format!("{partial}\n\n{CANCELLED_TURN_MARKER}")
+        };
+        let extras = std::collections::HashMap::from([(
+            OutboundMessage::CANCELLED_KEY.to_string(),
+            serde_json::json!(true),
+        )]);
+        session.add_message("assistant", &content, Some(extras));
+        self.sessions.save_session(&session).await?;
+
+        let mut outbound = OutboundMessage {
+            channel: msg.channel.clone(),
+            chat_id: msg.chat_id.clone(),
+            content: "Cancelled.".into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: Default::default(),
+        };
+        outbound
+            .metadata
+            .insert(OutboundMessage::CANCELLED_KEY.into(), true.into());
+        self.dispatch_reply(msg, outbound)
+    }
+
     /// Execute auto-delegation: invoke `delegate_task` directly and dispatch
     /// the result as an outbound message.
     ///
@@ -452,6 +933,7 @@ impl<P: Platform> AgentLoop<P> {
     /// sub-agent) rather than the local LLM.
     async fn run_auto_delegation(
         &self,
+        settings: &LiveSettings,
         msg: &InboundMessage,
         delegate_args: serde_json::Value,
     ) -> clawft_types::Result<()> {
@@ -459,18 +941,30 @@ impl<P: Platform> AgentLoop<P> {
 
         // Save user message to session for history.
         let mut session = self.sessions.get_or_create(&session_key).await?;
-        session.add_message("user", &msg.content, None);
+        session.add_message(
+            "user",
+            &msg.content,
+            Session::attachment_extras(&msg.attachments),
+        );
 
         // Resolve auth context for permission checks.
-        let auth = self.resolve_auth_context(msg);
-        let permissions = Some(&auth.permissions);
+        let auth = self.resolve_auth_context(settings, msg);
 
         // Invoke delegate_task tool directly.
-        let response_text = match self
-            .tools
-            .execute("delegate_task", delegate_args, permissions)
-            .await
-        {
+        let ctx = HookContext {
+            agent_id: agent_id(msg),
+            session_key: &session_key,
+            iteration: 0,
+        };
+        let call = ToolCall {
+            id: "auto-delegation".into(),
+            name: "delegate_task".into(),
+            input: delegate_args,
+        };
+        let result = self
+            .execute_tool(settings, &ctx, call, None, Some(&auth))
+            .await;
+        let response_text = match result {
             Ok(result) => {
                 // Extract the response text from the delegation result.
                 if let Some(response) = result.get("response").and_then(|v| v.as_str()) {
@@ -500,891 +994,4456 @@ impl<P: Platform> AgentLoop<P> {
             content: response_text,
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: Default::default(),
         };
-        self.bus.dispatch_outbound(outbound)?;
+        self.dispatch_reply(msg, outbound)?;
 
         debug!(session_key = %session_key, "auto-delegated message processed");
         Ok(())
     }
 
-    /// Resolve [`AuthContext`] from the inbound message's sender identity.
+    /// The tools a message's turn may use, or `None` when unrestricted.
     ///
-    /// Resolve permissions for an inbound message using the 5-layer
-    /// [`PermissionResolver`].
-    ///
-    /// Resolution order (highest priority wins):
-    /// 1. Built-in level defaults
-    /// 2. Global config level overrides
-    /// 3. Workspace config level overrides
-    /// 4. Per-user overrides
-    /// 5. Per-channel overrides (highest priority)
-    ///
-    /// CLI channel messages always receive admin-level (Level 2)
-    /// permissions via the resolver's `cli_default_level`.
-    fn resolve_auth_context(&self, msg: &InboundMessage) -> AuthContext {
-        // Channel plugins set "allow_from_match" in metadata when the sender
-        // passed the channel's allow_from verification. This promotes the
-        // sender from zero-trust to at least user-level permissions.
-        let allow_from_match = msg
+    /// The active skill's `allowed_tools` (from message metadata) is
+    /// intersected with the addressed agent's definition; an empty list on
+    /// either side does not restrict.
+    fn turn_allowed_tools(&self, msg: &InboundMessage) -> Option<Vec<String>> {
+        let skill_tools: Vec<String> = msg
             .metadata
-            .get("allow_from_match")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-        self.permission_resolver
-            .resolve_auth_context(&msg.sender_id, &msg.channel, allow_from_match)
-    }
-
-    /// Resolve the workspace path from config, expanding `~` to home dir.
-    fn workspace_path(&self) -> std::path::PathBuf {
-        let raw = &self.config.defaults.workspace;
-        if let Some(rest) = raw.strip_prefix("~/")
-            && let Some(home) = self.platform.fs().home_dir()
-        {
-            return home.join(rest);
+            .get("allowed_tools")
+            .and_then(|v| v.as_array())
+            .map(|tools| {
+                tools
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let agent_tools = self
+            .agents
+            .as_ref()
+            .and_then(|agents| agents.get(agent_id(msg)))
+            .map(|agent| agent.allowed_tools.as_slice())
+            .unwrap_or_default();
+
+        if skill_tools.is_empty() && agent_tools.is_empty() {
+            return None;
         }
-        std::path::PathBuf::from(raw)
+        Some(crate::security::intersect_allowed_tools(
+            &skill_tools,
+            agent_tools,
+        ))
     }
 
-    /// Execute the tool loop: call LLM, execute tools, repeat.
-    ///
-    /// After each LLM call, checks if the response contains tool-use
-    /// requests. If so, executes each tool via the `ToolRegistry`, appends
-    /// tool results to the message list, and re-invokes the pipeline.
-    /// Continues until the LLM returns a text response or the maximum
-    /// iteration limit is reached.
+    /// Apply `routing.rules` to an inbound message.
     ///
-    /// Post-write verification checks whether files claimed by write/edit
-    /// tools actually exist on disk. Hallucinated results are replaced with
-    /// error messages so the LLM can retry.
-    async fn run_tool_loop(
+    /// Returns the message to process -- addressed to an agent (the
+    /// `agent` metadata key) when a rule routed it -- or `None` when a rule
+    /// dropped it or holds it for confirmation. Messages already addressed
+    /// to an agent are left alone. A held message is resolved by the
+    /// sender's next message: a "yes" releases it, anything else discards
+    /// it and is routed as usual.
+    async fn apply_routing_rules(
         &self,
-        mut request: ChatRequest,
-    ) -> clawft_types::Result<ToolLoopResult> {
-        let max_iterations = self.config.defaults.max_tool_iterations.max(1) as usize;
-        let mut total_hallucinations: usize = 0;
-        let mut total_verified: usize = 0;
-        let workspace = self.workspace_path();
+        settings: &LiveSettings,
+        mut msg: InboundMessage,
+    ) -> clawft_types::Result<Option<InboundMessage>> {
+        let Some(rules) = settings.routing_rules.as_ref().filter(|r| !r.is_empty()) else {
+            return Ok(Some(msg));
+        };
+        if msg.metadata.contains_key("agent") {
+            return Ok(Some(msg));
+        }
 
-        for iteration in 0..max_iterations {
-            let response = self.pipeline.complete(&request).await?;
+        let session_key = msg.session_key();
+        let mut session = self.sessions.get_or_create(&session_key).await?;
+        if let Some(held) = session.metadata.remove(PENDING_ROUTE_KEY) {
+            self.sessions.save_session(&session).await?;
+            if is_confirmation(&msg.content) {
+                match serde_json::from_value::<InboundMessage>(held) {
+                    Ok(held) => return Ok(Some(held)),
+                    Err(e) => warn!(error = %e, "discarding unreadable held message"),
+                }
+            } else {
+                debug!(session = %session_key, "held message not confirmed, discarded");
+            }
+        }
 
-            // Extract tool calls from the response
-            let tool_calls: Vec<(String, String, serde_json::Value)> = response
-                .content
-                .iter()
-                .filter_map(|block| {
-                    if let ContentBlock::ToolUse { id, name, input } = block {
-                        Some((id.clone(), name.clone(), input.clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        let Some(decision) = rules.evaluate(&msg) else {
+            return Ok(Some(msg));
+        };
+        info!(
+            session = %session_key,
+            rule = decision.rule,
+            action = decision.action.kind(),
+            "routing rule matched"
+        );
+        let trace = serde_json::to_value(decision.trace(chrono::Utc::now()))?;
+        match decision.action {
+            RuleAction::Route(agent) => {
+                msg.metadata.insert("agent".into(), agent.clone().into());
+                msg.metadata.insert(ROUTING_TRACE_KEY.into(), trace);
+                Ok(Some(msg))
+            }
+            RuleAction::Drop => {
+                session.metadata.insert(ROUTING_TRACE_KEY.into(), trace);
+                self.sessions.save_session(&session).await?;
+                Ok(None)
+            }
+            RuleAction::Confirm(agent) => {
+                msg.metadata.insert("agent".into(), agent.clone().into());
+                msg.metadata.insert(ROUTING_TRACE_KEY.into(), trace.clone());
+                session
+                    .metadata
+                    .insert(PENDING_ROUTE_KEY.into(), serde_json::to_value(&msg)?);
+                session.metadata.insert(ROUTING_TRACE_KEY.into(), trace);
+                self.sessions.save_session(&session).await?;
+                self.bus.dispatch_outbound(OutboundMessage {
+                    channel: msg.channel.clone(),
+                    chat_id: msg.chat_id.clone(),
+                    content: format!(
+                        "This message will be handled by the {agent} agent. Reply \"yes\" to go ahead."
+                    ),
+                    reply_to: None,
+                    media: vec![],
+                    attachments: vec![],
+                    metadata: std::collections::HashMap::new(),
+                })?;
+                Ok(None)
+            }
+        }
+    }
 
-            if tool_calls.is_empty() {
-                // No tool calls -- extract text response and return
-                let text = response
-                    .content
-                    .iter()
-                    .filter_map(|block| {
-                        if let ContentBlock::Text { text } = block {
-                            Some(text.as_str())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("");
+    /// Handle `/fork [n]`: fork the message's session after `n` messages
+    /// (default: all of them) and reply with the new session's key.
+    ///
+    /// The reply carries the key under [`FORKED_SESSION_KEY`] so clients
+    /// can switch to the fork.
+    async fn run_fork_command(
+        &self,
+        msg: &InboundMessage,
+        session_key: &str,
+        at: Result<Option<usize>, String>,
+    ) -> clawft_types::Result<()> {
+        let mut metadata = std::collections::HashMap::new();
+        let content = match at {
+            Err(arg) => format!("Usage: /fork [message-index] (got '{arg}')"),
+            Ok(at) => {
+                let session = self.sessions.get_or_create(session_key).await?;
+                let at = at.unwrap_or(session.messages.len());
+                match self.sessions.fork(session_key, at).await {
+                    Ok(fork) => {
+                        metadata.insert(FORKED_SESSION_KEY.into(), fork.key.clone().into());
+                        format!(
+                            "Forked this conversation at message {at} into session `{}`.",
+                            fork.key
+                        )
+                    }
+                    Err(e) => format!("Could not fork: {e}"),
+                }
+            }
+        };
+        self.bus.dispatch_outbound(OutboundMessage {
+            channel: msg.channel.clone(),
+            chat_id: msg.chat_id.clone(),
+            content,
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata,
+        })?;
+        Ok(())
+    }
 
-                debug!(iteration, "tool loop complete, returning text response");
-                return Ok(ToolLoopResult {
-                    text,
-                    hallucinations: total_hallucinations,
-                    verified_successes: total_verified,
-                });
+    /// Handle a planning-mode message: a `/plan <task>` command, an answer
+    /// to the plan waiting in the session, or -- with
+    /// `agents.planning.enabled` -- any other message that is not a
+    /// command. Returns `false` when the message is none of these and
+    /// takes a normal turn.
+    async fn run_plan_mode(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session_key: &str,
+    ) -> clawft_types::Result<bool> {
+        let command = planning::parse_plan_command(&msg.content);
+        let mut session = self.sessions.get_or_create(session_key).await?;
+        let task = match (command, PlanState::load(&session)) {
+            (Some(""), _) => {
+                self.reply(msg, "Usage: /plan <task>".into(), Default::default())?;
+                return Ok(true);
+            }
+            // A new `/plan` replaces any plan still waiting.
+            (Some(task), _) => task.to_string(),
+            (None, Some(state)) => {
+                session.add_message(
+                    "user",
+                    &msg.content,
+                    Session::attachment_extras(&msg.attachments),
+                );
+                let reply = self.answer_plan(settings, msg, &mut session, state).await?;
+                self.finish_plan_turn(msg, session, reply).await?;
+                return Ok(true);
             }
+            (None, None)
+                if self.config.planning.enabled && !msg.content.trim_start().starts_with('/') =>
+            {
+                msg.content.clone()
+            }
+            (None, None) => return Ok(false),
+        };
 
-            debug!(
-                iteration,
-                tool_count = tool_calls.len(),
-                "executing tool calls"
-            );
+        session.add_message(
+            "user",
+            &msg.content,
+            Session::attachment_extras(&msg.attachments),
+        );
+        let reply = match self.draft_plan(settings, msg, session_key, &task).await {
+            Ok(plan) => {
+                let state = PlanState::new(task, plan);
+                state.store(&mut session);
+                approval_request(&state.plan)
+            }
+            Err(e) => {
+                PlanState::clear(&mut session);
+                format!("Could not draft a plan: {e}")
+            }
+        };
+        self.finish_plan_turn(msg, session, reply).await?;
+        Ok(true)
+    }
 
-            // Append the assistant message (with tool_calls) to the conversation
-            // so the next LLM request sees the correct message sequence:
-            //   ... user -> assistant (tool_use) -> tool results -> ...
-            let assistant_tool_calls: Vec<serde_json::Value> = tool_calls
-                .iter()
-                .map(|(id, name, input)| {
-                    serde_json::json!({
-                        "id": id,
-                        "type": "function",
-                        "function": {
-                            "name": name,
-                            "arguments": serde_json::to_string(input).unwrap_or_default(),
+    /// Act on the user's answer to the plan in `state`: run it, drop it,
+    /// or revise it as asked. Returns the reply.
+    async fn answer_plan(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session: &mut Session,
+        mut state: PlanState,
+    ) -> clawft_types::Result<String> {
+        match planning::parse_plan_reply(&msg.content) {
+            PlanReply::Approve => self.execute_plan(settings, msg, session, state).await,
+            PlanReply::Reject => {
+                PlanState::clear(session);
+                Ok("Plan discarded.".into())
+            }
+            PlanReply::Modify(changes) => {
+                let request = format!(
+                    "Task: {}\n\nYour earlier plan:\n{}\n\nThe user asked for these changes: \
+                     {changes}\n\nDraft the revised plan.",
+                    state.task,
+                    state.plan.render()
+                );
+                let session_key = session.key.clone();
+                Ok(
+                    match self.draft_plan(settings, msg, &session_key, &request).await {
+                        Ok(plan) => {
+                            state.plan = plan;
+                            state.status = PlanStatus::AwaitingApproval;
+                            state.next_step = 0;
+                            state.store(session);
+                            approval_request(&state.plan)
                         }
-                    })
-                })
-                .collect();
+                        Err(e) => format!(
+                            "Could not revise the plan: {e}\n\nThe previous plan still stands."
+                        ),
+                    },
+                )
+            }
+        }
+    }
 
-            // Extract any text content from the response for the assistant message
-            let assistant_text: String = response
-                .content
-                .iter()
-                .filter_map(|block| {
-                    if let ContentBlock::Text { text } = block {
-                        Some(text.as_str())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("");
+    /// Run the approved plan in `state` step by step, checking each step's
+    /// output against its success criteria. Returns the reply.
+    ///
+    /// The state is saved after every step, so an interrupted plan resumes
+    /// at the step it stopped at once approved again. A failed step ends
+    /// the run with a revised plan for the remaining work, which needs
+    /// approval, until `agents.planning.max_replans` revisions were made.
+    async fn execute_plan(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session: &mut Session,
+        mut state: PlanState,
+    ) -> clawft_types::Result<String> {
+        let session_key = session.key.clone();
+        state.status = PlanStatus::Executing;
+        state.store(session);
+        self.sessions.save_session(session).await?;
+
+        let turn = self.turns.begin(&session_key);
+        let mut report = Vec::new();
+        while state.next_step < state.plan.steps.len() {
+            let index = state.next_step;
+            info!(session_key = %session_key, step = index + 1, "running plan step");
+            let result = self
+                .run_plan_step(settings, msg, session, &state.plan, index, turn.token())
+                .await?;
+            if result.cancelled {
+                state.status = PlanStatus::AwaitingApproval;
+                state.store(session);
+                report.push(format!(
+                    "Cancelled at step {}. Reply \"continue\" to resume the plan.",
+                    index + 1
+                ));
+                return Ok(report.join("\n\n"));
+            }
 
-            request.messages.push(LlmMessage {
-                role: "assistant".into(),
-                content: assistant_text,
-                tool_call_id: None,
-                tool_calls: Some(assistant_tool_calls),
-            });
+            let step = &state.plan.steps[index];
+            let verdict = self
+                .check_plan_step(settings, msg, &session_key, step, &result.text)
+                .await
+                .unwrap_or_else(|e| StepVerdict {
+                    passed: false,
+                    reason: format!("the result could not be checked ({e})"),
+                });
+            let outcome = StepOutcome {
+                step: index,
+                passed: verdict.passed,
+                output: result.text,
+                reason: verdict.reason,
+            };
+            state.outcomes.push(outcome.clone());
+
+            if outcome.passed {
+                report.push(format!(
+                    "Step {} done: {}\n{}",
+                    index + 1,
+                    step.description,
+                    outcome.output
+                ));
+                state.next_step += 1;
+                state.store(session);
+                self.sessions.save_session(session).await?;
+                continue;
+            }
 
-            // Execute all tool calls in parallel and append results in order.
-            let permissions = request
-                .auth_context
-                .as_ref()
-                .map(|ctx| &ctx.permissions);
+            warn!(
+                session_key = %session_key,
+                step = index + 1,
+                reason = %outcome.reason,
+                "plan step failed"
+            );
+            report.push(format!(
+                "Step {} failed: {}\n{}",
+                index + 1,
+                outcome.reason,
+                outcome.output
+            ));
+            if state.replans >= self.config.planning.max_replans {
+                PlanState::clear(session);
+                report.push(format!(
+                    "Giving up: the plan was already revised {} time(s).",
+                    state.replans
+                ));
+                return Ok(report.join("\n\n"));
+            }
+            let request = state.replan_request(&outcome);
+            match self.draft_plan(settings, msg, &session_key, &request).await {
+                Ok(plan) => {
+                    state.revise(plan);
+                    state.store(session);
+                    report.push(format!("Revised {}", approval_request(&state.plan)));
+                }
+                Err(e) => {
+                    PlanState::clear(session);
+                    report.push(format!("Could not draft a revised plan: {e}"));
+                }
+            }
+            return Ok(report.join("\n\n"));
+        }
 
-            let futures: Vec<_> = tool_calls
-                .iter()
-                .map(|(id, name, input)| {
-                    let tools = &self.tools;
-                    async move {
-                        let result = tools.execute(name, input.clone(), permissions).await;
-                        let result_json = match result {
-                            Ok(val) => {
-                                let truncated =
-                                    crate::security::truncate_result(val, MAX_TOOL_RESULT_BYTES);
-                                serde_json::to_string(&truncated).unwrap_or_default()
-                            }
-                            Err(e) => {
-                                error!(tool = %name, error = %e, "tool execution failed");
-                                serde_json::json!({"error": e.to_string()}).to_string()
-                            }
-                        };
-                        (id.clone(), name.clone(), result_json)
-                    }
-                })
-                .collect();
+        PlanState::clear(session);
+        report.push(format!("Plan complete: {}", state.plan.goal));
+        Ok(report.join("\n\n"))
+    }
 
-            let results = futures_util::future::join_all(futures).await;
+    /// Carry out step `index` of `plan` as a tool-using turn.
+    async fn run_plan_step(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session: &Session,
+        plan: &Plan,
+        index: usize,
+        cancel: &CancellationToken,
+    ) -> clawft_types::Result<ToolLoopResult> {
+        let (mut messages, _) = self.context.build_messages_with_report(session, &[]).await;
+        messages.push(text_message(
+            "user",
+            planning::step_instruction(plan, index),
+        ));
+        let allowed_tools = self.turn_allowed_tools(msg);
+        let tools = match &allowed_tools {
+            Some(allowed) => self.tools.scoped(allowed).schemas(),
+            None => self.tools.schemas(),
+        };
+        let request = ChatRequest {
+            messages,
+            tools,
+            model: Some(settings.defaults.model.clone()),
+            max_tokens: Some(settings.defaults.max_tokens),
+            temperature: Some(settings.defaults.temperature),
+            auth_context: Some(self.resolve_auth_context(settings, msg)),
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let sink: Arc<dyn ResponseSink> = Arc::new(BufferingSink::new());
+        let mut budget = self.turn_budget(msg);
+        self.run_tool_loop(
+            settings,
+            request,
+            &session.key,
+            agent_id(msg),
+            allowed_tools.as_deref(),
+            cancel,
+            self.turn_iterations(settings, msg),
+            &mut budget,
+            &sink,
+            None,
+        )
+        .await
+    }
 
-            // Post-write verification: check that claimed writes exist on disk.
-            let verification_results = verification::verify_write_results(
-                self.platform.fs(),
-                &workspace,
-                &results,
-            )
-            .await;
+    /// Draft a plan for `request`, which holds the task and, for a
+    /// revision, what happened so far.
+    async fn draft_plan(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session_key: &str,
+        request: &str,
+    ) -> Result<Plan, String> {
+        let tools = match self.turn_allowed_tools(msg) {
+            Some(allowed) => allowed,
+            None => self.tools.list(),
+        };
+        let messages = vec![
+            text_message("system", planning::plan_prompt(&tools)),
+            text_message("user", request.to_string()),
+        ];
+        self.complete_structured(
+            settings,
+            msg,
+            session_key,
+            messages,
+            &planning::plan_schema(),
+        )
+        .await
+    }
 
-            // Build a set of hallucinated tool call IDs for result replacement.
-            let hallucinated_ids: std::collections::HashSet<String> = verification_results
-                .iter()
-                .filter(|v| !v.verified)
-                .map(|v| v.tool_call_id.clone())
-                .collect();
+    /// Ask the model whether `output` meets `step`'s success criteria.
+    async fn check_plan_step(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session_key: &str,
+        step: &PlanStep,
+        output: &str,
+    ) -> Result<StepVerdict, String> {
+        let messages = vec![
+            text_message("system", planning::verdict_prompt().to_string()),
+            text_message(
+                "user",
+                format!(
+                    "Step: {}\nSuccess criterion: {}\n\nOutput of the step:\n{output}",
+                    step.description, step.success_criteria
+                ),
+            ),
+        ];
+        self.complete_structured(
+            settings,
+            msg,
+            session_key,
+            messages,
+            &planning::verdict_schema(),
+        )
+        .await
+    }
 
-            // Count verification outcomes.
-            for vr in &verification_results {
-                if vr.verified {
-                    total_verified += 1;
-                } else {
-                    total_hallucinations += 1;
-                    warn!(
-                        tool_call_id = %vr.tool_call_id,
-                        path = %vr.claimed_path.display(),
-                        "VERIFICATION FAILED: file does not exist (hallucinated write)"
-                    );
-                }
+    /// Complete `messages` with the planning model and parse the reply
+    /// against `schema`. A reply that does not match is sent back with
+    /// the errors, up to [`MAX_STRUCTURED_RETRIES`] times.
+    async fn complete_structured<T: serde::de::DeserializeOwned>(
+        &self,
+        settings: &LiveSettings,
+        msg: &InboundMessage,
+        session_key: &str,
+        mut messages: Vec<LlmMessage>,
+        schema: &serde_json::Value,
+    ) -> Result<T, String> {
+        let model = self
+            .config
+            .planning
+            .model
+            .clone()
+            .unwrap_or_else(|| settings.defaults.model.clone());
+        let mut last_error = String::new();
+        for attempt in 0..=MAX_STRUCTURED_RETRIES {
+            if let Some(refusal) = self.budget_refusal(session_key) {
+                return Err(refusal);
             }
-
-            for (id, _name, result_json) in &results {
-                let content = if hallucinated_ids.contains(id) {
-                    // Replace the success result with a verification failure error.
-                    serde_json::json!({
-                        "error": "VERIFICATION FAILED: the file you claimed to write does not exist on disk. The write was hallucinated. Please retry the write operation."
-                    }).to_string()
-                } else {
-                    result_json.clone()
-                };
-
-                request.messages.push(LlmMessage {
-                    role: "tool".into(),
-                    content,
-                    tool_call_id: Some(id.clone()),
-                    tool_calls: None,
-                });
+            let request = ChatRequest {
+                messages: messages.clone(),
+                tools: vec![],
+                model: Some(model.clone()),
+                max_tokens: Some(settings.defaults.max_tokens),
+                temperature: Some(0.0),
+                auth_context: Some(self.resolve_auth_context(settings, msg)),
+                complexity_boost: 0.0,
+                cache: false,
+            };
+            let response = self
+                .pipeline
+                .complete_for(agent_id(msg), &request)
+                .await
+                .map_err(|e| e.to_string())?;
+            self.record_usage(settings, session_key, request.model.as_deref(), &response);
+            let text = response_text(&response);
+            match planning::parse_structured(&text, schema) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    debug!(session_key, attempt, error = %e, "structured reply did not match");
+                    messages.push(text_message("assistant", text));
+                    messages.push(text_message(
+                        "user",
+                        format!(
+                            "That reply was not usable: {e}. Reply again with only the JSON object."
+                        ),
+                    ));
+                    last_error = e;
+                }
             }
         }
-
-        Err(ClawftError::Provider {
-            message: format!("max tool iterations ({}) exceeded", max_iterations),
-        })
+        Err(last_error)
     }
 
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::agent::memory::MemoryStore;
-    use crate::agent::skills::SkillsLoader;
-    use crate::pipeline::traits::{
-        AssembledContext, LearningBackend, LearningSignal, LlmTransport, ModelRouter, Pipeline,
-        QualityScore, QualityScorer, ResponseOutcome, RoutingDecision, TaskClassifier, TaskProfile,
-        TaskType, Trajectory, TransportRequest,
-    };
-    use crate::tools::registry::Tool;
-    use async_trait::async_trait;
-    use clawft_platform::NativePlatform;
-    use clawft_types::config::AgentDefaults;
-    use clawft_types::provider::{LlmResponse, StopReason, Usage};
-    use std::collections::HashMap;
-    use std::path::PathBuf;
-    use std::sync::atomic::{AtomicU64, Ordering};
-
-    static TEST_COUNTER: AtomicU64 = AtomicU64::new(0);
-
-    fn temp_dir(prefix: &str) -> PathBuf {
-        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
-        let pid = std::process::id();
-        std::env::temp_dir().join(format!("clawft_loop_test_{prefix}_{pid}_{id}"))
+    /// Record a planning-mode reply in the session, save it, and send it.
+    async fn finish_plan_turn(
+        &self,
+        msg: &InboundMessage,
+        mut session: Session,
+        reply: String,
+    ) -> clawft_types::Result<()> {
+        session.add_message("assistant", &reply, None);
+        self.sessions.save_session(&session).await?;
+        self.reply(msg, reply, Default::default())
     }
 
-    fn test_config() -> AgentsConfig {
-        AgentsConfig {
-            defaults: AgentDefaults {
-                workspace: "~/.clawft/workspace".into(),
-                model: "test-model".into(),
-                max_tokens: 4096,
-                temperature: 0.5,
-                max_tool_iterations: 10,
-                memory_window: 50,
+    /// Send `content` back to where `msg` came from.
+    fn reply(
+        &self,
+        msg: &InboundMessage,
+        content: String,
+        metadata: std::collections::HashMap<String, serde_json::Value>,
+    ) -> clawft_types::Result<()> {
+        self.dispatch_reply(
+            msg,
+            OutboundMessage {
+                channel: msg.channel.clone(),
+                chat_id: msg.chat_id.clone(),
+                content,
+                reply_to: None,
+                media: vec![],
+                attachments: vec![],
+                metadata,
             },
-        }
+        )
     }
 
-    // -- Mock pipeline stages --
-
-    struct MockClassifier;
-    impl TaskClassifier for MockClassifier {
-        fn classify(&self, _request: &ChatRequest) -> TaskProfile {
-            TaskProfile {
-                task_type: TaskType::Chat,
-                complexity: 0.3,
-                keywords: vec![],
+    /// Dispatch the reply to `msg`. Replies to scheduled prompts follow
+    /// their [`ProactiveDelivery`] rules: a `NO_NOTIFY` reply is dropped,
+    /// others are readdressed to the target and tagged with quiet hours.
+    fn dispatch_reply(
+        &self,
+        msg: &InboundMessage,
+        outbound: OutboundMessage,
+    ) -> clawft_types::Result<()> {
+        let Some(delivery) = ProactiveDelivery::from_metadata(&msg.metadata) else {
+            return self.bus.dispatch_outbound(outbound);
+        };
+        match delivery.route(outbound) {
+            Some(outbound) => self.bus.dispatch_outbound(outbound),
+            None => {
+                info!(session_key = %msg.session_key(), "scheduled reply suppressed (NO_NOTIFY)");
+                Ok(())
             }
         }
     }
 
-    struct MockRouter;
-    #[async_trait]
-    impl ModelRouter for MockRouter {
-        async fn route(&self, _request: &ChatRequest, _profile: &TaskProfile) -> RoutingDecision {
-            RoutingDecision {
-                provider: "test".into(),
-                model: "test-model".into(),
+    /// Resolve [`AuthContext`] from the inbound message's sender identity.
+    ///
+    /// Resolve permissions for an inbound message using the 5-layer
+    /// [`PermissionResolver`].
+    ///
+    /// Resolution order (highest priority wins):
+    /// 1. Built-in level defaults
+    /// 2. Global config level overrides
+    /// 3. Workspace config level overrides
+    /// 4. Per-user overrides
+    /// 5. Per-channel overrides (highest priority)
+    ///
+    /// CLI channel messages always receive admin-level (Level 2)
+    /// permissions via the resolver's `cli_default_level`.
+    /// Score `msg` against the registered skills and activate the best
+    /// match by adding its instructions and `allowed-tools` to the message
+    /// metadata, as the REPL's `/use` does.
+    ///
+    /// Returns the decision for the turn trace, or `None` when activation
+    /// is off or the message already carries a skill. Ties go to the
+    /// addressed agent's first listed skill.
+    async fn activate_skill(
+        &self,
+        mut msg: InboundMessage,
+    ) -> (InboundMessage, Option<SkillDecision>) {
+        let Some(activator) = self
+            .skills
+            .as_ref()
+            .filter(|_| self.config.skills.auto_activate)
+        else {
+            return (msg, None);
+        };
+        if msg.metadata.contains_key("skill_instructions")
+            || msg.metadata.contains_key("allowed_tools")
+        {
+            return (msg, None);
+        }
+
+        let default_skill = self
+            .agents
+            .as_ref()
+            .and_then(|agents| agents.get(agent_id(&msg)))
+            .and_then(|agent| agent.skills.first().cloned());
+        let (decision, skill) = activator
+            .select(&msg.content, default_skill.as_deref())
+            .await;
+        if let Some(skill) = skill {
+            info!(
+                skill = %skill.name,
+                score = decision.candidates.first().map(|c| c.score).unwrap_or_default(),
+                "skill activated"
+            );
+            if !skill.instructions.is_empty() {
+                msg.metadata.insert(
+                    "skill_instructions".into(),
+                    serde_json::json!(skill.instructions),
+                );
+            }
+            if !skill.allowed_tools.is_empty() {
+                msg.metadata.insert(
+                    "allowed_tools".into(),
+                    serde_json::json!(skill.allowed_tools),
+                );
+            }
+        }
+        (msg, Some(decision))
+    }
+
+    /// Whether `msg`'s turn is traced: `agents.sessions.trace` is set or
+    /// the message asks for it ([`InboundMessage::TRACE_KEY`]).
+    fn trace_requested(&self, msg: &InboundMessage) -> bool {
+        self.config.sessions.trace
+            || msg
+                .metadata
+                .get(InboundMessage::TRACE_KEY)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+    }
+
+    /// Tell the turn observers how the turn answering `msg` ended.
+    fn report_turn(
+        &self,
+        msg: &InboundMessage,
+        started_at: chrono::DateTime<chrono::Utc>,
+        result: &clawft_types::Result<ToolLoopResult>,
+        budget: &TurnBudget,
+    ) {
+        if self.turn_observers.is_empty() {
+            return;
+        }
+        let (outcome, error, output) = match result {
+            Ok(result) if result.cancelled => (TurnOutcome::Cancelled, None, result.text.clone()),
+            Ok(result) => match &result.exceeded {
+                Some(limit) => (TurnOutcome::Error, Some(limit.clone()), result.text.clone()),
+                None => (TurnOutcome::Completed, None, result.text.clone()),
+            },
+            Err(e) => (TurnOutcome::Error, Some(e.to_string()), String::new()),
+        };
+        let report = TurnReport {
+            started_at,
+            finished_at: chrono::Utc::now(),
+            outcome,
+            error,
+            output,
+            input_tokens: budget.input_tokens(),
+            output_tokens: budget.output_tokens(),
+        };
+        for observer in &self.turn_observers {
+            observer.on_turn_end(msg, &report);
+        }
+    }
+
+    /// Write a finished turn trace next to the sessions. Failures are
+    /// logged; tracing never fails a turn.
+    async fn write_trace(&self, turn: trace::TurnTrace) {
+        let dir = self.sessions.sessions_dir();
+        match trace::write_trace(self.platform.fs(), dir, &turn).await {
+            Ok(path) => debug!(path = %path.display(), "turn trace written"),
+            Err(e) => {
+                warn!(session_key = %turn.session_key, error = %e, "failed to write turn trace")
+            }
+        }
+    }
+
+    /// The live settings for `msg`'s turn. A model the local CLI pins
+    /// with [`InboundMessage::MODEL_KEY`] replaces the default and the
+    /// routed model.
+    fn turn_settings(&self, msg: &InboundMessage) -> Arc<LiveSettings> {
+        let settings = self.live.snapshot();
+        let pinned = msg
+            .metadata
+            .get(InboundMessage::MODEL_KEY)
+            .and_then(|v| v.as_str())
+            .filter(|model| msg.channel == "cli" && !model.is_empty());
+        match pinned {
+            Some(model) => {
+                debug!(model, "model pinned for this turn");
+                let mut settings = (*settings).clone();
+                settings.defaults.model = model.to_string();
+                settings.pinned_model = Some(model.to_string());
+                Arc::new(settings)
+            }
+            None => settings,
+        }
+    }
+
+    fn resolve_auth_context(&self, settings: &LiveSettings, msg: &InboundMessage) -> AuthContext {
+        // Channel plugins set "allow_from_match" in metadata when the sender
+        // passed the channel's allow_from verification. This promotes the
+        // sender from zero-trust to at least user-level permissions.
+        let allow_from_match = msg
+            .metadata
+            .get("allow_from_match")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        settings
+            .permissions
+            .resolve_auth_context(&msg.sender_id, &msg.channel, allow_from_match)
+    }
+
+    /// Resolve the workspace path from config, expanding `~` to home dir.
+    fn workspace_path(&self, settings: &LiveSettings) -> std::path::PathBuf {
+        let raw = &settings.defaults.workspace;
+        if let Some(rest) = raw.strip_prefix("~/")
+            && let Some(home) = self.platform.fs().home_dir()
+        {
+            return home.join(rest);
+        }
+        std::path::PathBuf::from(raw)
+    }
+
+    /// Tool-loop iterations a turn may use (`max_tool_iterations`).
+    fn max_tool_iterations(&self, settings: &LiveSettings) -> usize {
+        settings.defaults.max_tool_iterations.max(1) as usize
+    }
+
+    /// Tool-loop iterations `msg`'s turn may use: `max_tool_iterations`,
+    /// or fewer if the message's
+    /// [`TurnLimits`](clawft_types::event::TurnLimits) ask for it.
+    fn turn_iterations(&self, settings: &LiveSettings, msg: &InboundMessage) -> usize {
+        let max = self.max_tool_iterations(settings);
+        msg.turn_limits()
+            .max_iterations
+            .map_or(max, |n| max.min(n.max(1) as usize))
+    }
+
+    /// The budget for `msg`'s turn: `agents.budget`, unless the local CLI
+    /// asked for it to be lifted, tightened by the message's
+    /// [`TurnLimits`](clawft_types::event::TurnLimits).
+    fn turn_budget(&self, msg: &InboundMessage) -> TurnBudget {
+        let waived = msg.channel == "cli"
+            && msg
+                .metadata
+                .get(InboundMessage::NO_BUDGET_KEY)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+        if waived {
+            debug!("budget lifted for this turn");
+            return TurnBudget::unlimited();
+        }
+        let requested = msg.turn_limits();
+        let tighten = |limit: Option<u64>, requested: Option<u64>| match (limit, requested) {
+            (Some(limit), Some(requested)) => Some(limit.min(requested)),
+            (limit, requested) => limit.or(requested),
+        };
+        let mut limits = self.config.budget.clone();
+        limits.max_tokens_per_turn = tighten(limits.max_tokens_per_turn, requested.max_tokens);
+        limits.max_turn_secs = tighten(limits.max_turn_secs, requested.max_secs);
+        TurnBudget::new(&limits, self.daily_spend.clone())
+    }
+
+    /// Refusal message if `session_key` has spent its configured budget.
+    fn budget_refusal(&self, session_key: &str) -> Option<String> {
+        let usage = self.usage.as_ref()?;
+        let budget = self.config.usage.session_budget_usd?;
+        let spent = usage.session_totals(session_key).cost_usd;
+        if spent < budget {
+            return None;
+        }
+        warn!(
+            session_key,
+            spent, budget, "session budget exhausted, refusing LLM call"
+        );
+        Some(format!(
+            "This session has reached its spending limit (${spent:.4} of ${budget:.2}), \
+             so no further LLM calls will be made. Start a new session or raise \
+             agents.usage.session_budget_usd to continue."
+        ))
+    }
+
+    /// Record a completion's token usage against `session_key`.
+    ///
+    /// The provider and model come from the response metadata stamped by
+    /// the pipeline, falling back to the requested model.
+    fn record_usage(
+        &self,
+        settings: &LiveSettings,
+        session_key: &str,
+        model: Option<&str>,
+        response: &LlmResponse,
+    ) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        let meta = |key: &str| response.metadata.get(key).and_then(|v| v.as_str());
+        let provider = meta("provider").unwrap_or("unknown");
+        let model = meta("model").or(model).unwrap_or(&settings.defaults.model);
+        let cost = usage.record(provider, model, session_key, &response.usage);
+        if let Some(daily) = &self.daily_spend {
+            daily.add(cost);
+        }
+        debug!(
+            session_key,
+            provider,
+            model,
+            input_tokens = response.usage.input_tokens,
+            output_tokens = response.usage.output_tokens,
+            cost_usd = cost,
+            "recorded llm usage"
+        );
+    }
+
+    /// Fold messages that have left the history window into the session's
+    /// rolling summary, when compaction is due.
+    ///
+    /// The summary is written by `compaction.model` (or the default model)
+    /// directly through the default pipeline's transport. Failures are
+    /// logged and leave the previous summary in place; the turn proceeds
+    /// either way.
+    async fn compact_history(
+        &self,
+        settings: &LiveSettings,
+        session: &mut Session,
+        session_key: &str,
+    ) {
+        let config = &self.config.compaction;
+        let window = settings.defaults.memory_window.max(0) as usize;
+        let Some(span) = compaction::due_span(session, window, config) else {
+            return;
+        };
+        if self.budget_refusal(session_key).is_some() {
+            return;
+        }
+
+        let model = config.model.as_deref().unwrap_or(&settings.defaults.model);
+        let previous = CompactionState::load(session)
+            .map(|state| state.summary)
+            .unwrap_or_default();
+        let lines =
+            compaction::transcript(&session.messages[span.clone()], config.tool_result_chars);
+        let instructions = self
+            .context
+            .templates()
+            .render(TemplateName::Summarizer, None, &[]);
+        let (messages, omitted) = compaction::summary_request(
+            &instructions,
+            &previous,
+            &lines,
+            model,
+            config.max_summary_tokens,
+        );
+        if omitted > 0 {
+            warn!(
+                session_key,
+                omitted, "evicted history exceeds the summarizer's window, oldest lines left out"
+            );
+        }
+
+        let (provider, model_name) = split_provider_model(model);
+        let request = TransportRequest {
+            provider,
+            model: model_name,
+            messages,
+            tools: vec![],
+            max_tokens: Some(i32::try_from(config.max_summary_tokens).unwrap_or(i32::MAX)),
+            temperature: Some(0.0),
+            cache: true,
+        };
+        let transport = &self.pipeline.default_pipeline().transport;
+        let mut response = match transport.complete(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(session_key, error = %e, "history compaction failed");
+                return;
+            }
+        };
+        response
+            .metadata
+            .entry("provider".into())
+            .or_insert_with(|| request.provider.clone().into());
+        self.record_usage(settings, session_key, Some(&request.model), &response);
+
+        let summary: String = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if summary.trim().is_empty() {
+            warn!(session_key, "history compaction returned an empty summary");
+            return;
+        }
+
+        CompactionState {
+            summary: summary.trim().to_string(),
+            summarized_through: span.end,
+            turn: compaction::user_turns(session),
+        }
+        .store(session);
+        info!(
+            session_key,
+            model,
+            messages = span.len(),
+            summarized_through = span.end,
+            "compacted session history"
+        );
+    }
+
+    /// Shorten a result larger than `tools.results.max_bytes` for the
+    /// model, after spilling it whole to the workspace (see
+    /// [`tool_results`](super::tool_results)). Smaller results pass through.
+    async fn process_result(
+        &self,
+        settings: &LiveSettings,
+        session_key: &str,
+        workspace: &std::path::Path,
+        call_id: &str,
+        tool: &str,
+        value: serde_json::Value,
+    ) -> serde_json::Value {
+        let config = &self.tool_results;
+        let original_bytes = tool_results::json_len(&value);
+        if original_bytes <= config.max_bytes {
+            return value;
+        }
+
+        let spilled = match tool_results::spill(
+            self.platform.as_ref(),
+            workspace,
+            tool,
+            call_id,
+            &value,
+            config.keep_spill_files,
+        )
+        .await
+        {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!(tool, error = %e, "could not write tool result spill file");
+                None
+            }
+        };
+        let (strategy, content) = match tool_results::strategy_for(config, tool) {
+            ResultStrategy::Summarize => {
+                match self
+                    .summarize_result(settings, session_key, tool, &value)
+                    .await
+                {
+                    Some(summary) => (ResultStrategy::Summarize, summary.into()),
+                    None => (ResultStrategy::Structured, value),
+                }
+            }
+            strategy => (strategy, value),
+        };
+        debug!(
+            tool,
+            original_bytes,
+            ?strategy,
+            spilled = ?spilled,
+            "post-processed oversized tool result"
+        );
+        tool_results::envelope(
+            strategy,
+            original_bytes,
+            spilled.as_deref(),
+            content,
+            config.max_bytes,
+        )
+    }
+
+    /// Summarize an oversized tool result with `tools.results.summary_model`
+    /// (or the default model), directly through the default pipeline's
+    /// transport. Returns `None`, after logging why, when no summary could
+    /// be written.
+    async fn summarize_result(
+        &self,
+        settings: &LiveSettings,
+        session_key: &str,
+        tool: &str,
+        value: &serde_json::Value,
+    ) -> Option<String> {
+        if self.budget_refusal(session_key).is_some() {
+            return None;
+        }
+        let config = &self.tool_results;
+        let model = config
+            .summary_model
+            .as_deref()
+            .unwrap_or(&settings.defaults.model);
+        let instructions = self.context.templates().render(
+            TemplateName::ResultSummarizer,
+            None,
+            &[("tool", tool)],
+        );
+        let messages =
+            tool_results::summary_request(&instructions, value, model, config.max_summary_tokens);
+
+        let (provider, model_name) = split_provider_model(model);
+        let request = TransportRequest {
+            provider,
+            model: model_name,
+            messages,
+            tools: vec![],
+            max_tokens: Some(i32::try_from(config.max_summary_tokens).unwrap_or(i32::MAX)),
+            temperature: Some(0.0),
+            cache: true,
+        };
+        let transport = &self.pipeline.default_pipeline().transport;
+        let mut response = match transport.complete(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(session_key, tool, error = %e, "tool result summary failed");
+                return None;
+            }
+        };
+        response
+            .metadata
+            .entry("provider".into())
+            .or_insert_with(|| request.provider.clone().into());
+        self.record_usage(settings, session_key, Some(&request.model), &response);
+
+        let summary: String = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if summary.trim().is_empty() {
+            warn!(session_key, tool, "tool result summary was empty");
+            return None;
+        }
+        Some(summary.trim().to_string())
+    }
+
+    /// Execute the tool loop: call LLM, execute tools, repeat.
+    ///
+    /// After each LLM call, checks if the response contains tool-use
+    /// requests. If so, executes the tools via the `ToolRegistry` --
+    /// concurrently, up to `max_parallel_tools` at a time, unless one of
+    /// them is not parallel-safe -- appends one tool result per call id in
+    /// the order the model issued them, and re-invokes the pipeline.
+    /// Continues until the LLM returns a text response or the maximum
+    /// iteration limit is reached.
+    ///
+    /// Post-write verification checks whether files claimed by write/edit
+    /// tools actually exist on disk. Hallucinated results are replaced with
+    /// error messages so the LLM can retry.
+    ///
+    /// When `sink` wants deltas, completions are streamed into it, and it
+    /// is told about each tool call before the batch runs. `budget` is
+    /// checked before every LLM call and tool batch, and is left holding
+    /// the turn's token counts.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_tool_loop(
+        &self,
+        settings: &LiveSettings,
+        mut request: ChatRequest,
+        session_key: &str,
+        agent_id: &str,
+        allowed_tools: Option<&[String]>,
+        cancel: &CancellationToken,
+        max_iterations: usize,
+        budget: &mut TurnBudget,
+        sink: &Arc<dyn ResponseSink>,
+        trace: Option<&TraceRecorder>,
+    ) -> clawft_types::Result<ToolLoopResult> {
+        let mut total_hallucinations: usize = 0;
+        let mut total_verified: usize = 0;
+        let workspace = self.workspace_path(settings);
+        let scope = allowed_tools.map(|allowed| self.tools.scoped(allowed));
+        // Whose template overrides apply; see `agent_id` for "default".
+        let template_agent = (agent_id != "default").then_some(agent_id);
+        // Assistant text written so far, kept if the turn is cancelled.
+        let mut partial = String::new();
+        let cancelled = |partial: String, hallucinations, verified_successes| {
+            info!(session_key, "turn cancelled");
+            Ok(ToolLoopResult {
+                text: partial,
+                hallucinations,
+                verified_successes,
+                streamed: false,
+                cancelled: true,
+                exceeded: None,
+            })
+        };
+        let exceeded = |exceeded: BudgetExceeded, hallucinations, verified_successes| {
+            warn!(session_key, %exceeded, "turn budget exceeded");
+            Ok(ToolLoopResult {
+                text: exceeded.to_string(),
+                hallucinations,
+                verified_successes,
+                streamed: false,
+                cancelled: false,
+                exceeded: Some(exceeded.to_string()),
+            })
+        };
+
+        for iteration in 0..max_iterations {
+            if cancel.is_cancelled() {
+                return cancelled(partial, total_hallucinations, total_verified);
+            }
+            if let Some(refusal) = self.budget_refusal(session_key) {
+                return Ok(ToolLoopResult {
+                    exceeded: Some(refusal.clone()),
+                    text: refusal,
+                    hallucinations: total_hallucinations,
+                    verified_successes: total_verified,
+                    streamed: false,
+                    cancelled: false,
+                });
+            }
+            if let Err(e) = budget.check() {
+                return exceeded(e, total_hallucinations, total_verified);
+            }
+
+            let ctx = HookContext {
+                agent_id,
+                session_key,
+                iteration,
+            };
+            if let Err(veto) = self.hooks.pre_completion(&ctx, &mut request).await {
+                warn!(%veto, "completion vetoed");
+                return Ok(ToolLoopResult {
+                    text: format!("This request was {veto}."),
+                    hallucinations: total_hallucinations,
+                    verified_successes: total_verified,
+                    streamed: false,
+                    cancelled: false,
+                    exceeded: None,
+                });
+            }
+
+            let streamed = sink.wants_deltas();
+            let completion_started = crate::runtime::now_millis();
+            let completion = async {
+                let callback = streamed.then(|| {
+                    let sink = sink.clone();
+                    Box::new(move |delta: &str| {
+                        sink.on_text_delta(delta);
+                        true
+                    }) as StreamCallback
+                });
+                match (&settings.pinned_model, callback) {
+                    (Some(model), callback) => {
+                        self.pipeline
+                            .complete_pinned(agent_id, model, &request, callback)
+                            .await
+                    }
+                    (None, Some(callback)) => {
+                        self.pipeline
+                            .complete_stream_for(agent_id, &request, callback)
+                            .await
+                    }
+                    (None, None) => self.pipeline.complete_for(agent_id, &request).await,
+                }
+            };
+            let completion = crate::runtime::until_cancelled(cancel, completion);
+            let Some(response) = budget.within(completion).await else {
+                return exceeded(budget.time_exceeded(), total_hallucinations, total_verified);
+            };
+            let Some(response) = response else {
+                return cancelled(partial, total_hallucinations, total_verified);
+            };
+            let mut response = response?;
+            if let Some(trace) = trace {
+                let elapsed = crate::runtime::now_millis().saturating_sub(completion_started);
+                trace.completion(iteration, elapsed, &response);
+            }
+            self.record_usage(settings, session_key, request.model.as_deref(), &response);
+            budget.record_usage(&response.usage);
+            if let Err(veto) = self.hooks.post_completion(&ctx, &mut response).await {
+                warn!(%veto, "completion vetoed");
+                return Ok(ToolLoopResult {
+                    text: format!("This response was {veto}."),
+                    hallucinations: total_hallucinations,
+                    verified_successes: total_verified,
+                    streamed: false,
+                    cancelled: false,
+                    exceeded: None,
+                });
+            }
+
+            // Extract tool calls from the response
+            let tool_calls: Vec<(String, String, serde_json::Value)> = response
+                .content
+                .iter()
+                .filter_map(|block| {
+                    if let ContentBlock::ToolUse { id, name, input } = block {
+                        Some((id.clone(), name.clone(), input.clone()))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            if tool_calls.is_empty() {
+                // No tool calls -- extract text response and return
+                let text = response
+                    .content
+                    .iter()
+                    .filter_map(|block| {
+                        if let ContentBlock::Text { text } = block {
+                            Some(text.as_str())
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("");
+
+                debug!(iteration, "tool loop complete, returning text response");
+                return Ok(ToolLoopResult {
+                    text,
+                    hallucinations: total_hallucinations,
+                    verified_successes: total_verified,
+                    streamed,
+                    cancelled: false,
+                    exceeded: None,
+                });
+            }
+
+            if let Err(e) = budget.admit_tool_calls(tool_calls.len()) {
+                return exceeded(e, total_hallucinations, total_verified);
+            }
+            debug!(
+                iteration,
+                tool_count = tool_calls.len(),
+                "executing tool calls"
+            );
+
+            // Append the assistant message (with tool_calls) to the conversation
+            // so the next LLM request sees the correct message sequence:
+            //   ... user -> assistant (tool_use) -> tool results -> ...
+            let assistant_tool_calls: Vec<serde_json::Value> = tool_calls
+                .iter()
+                .map(|(id, name, input)| {
+                    serde_json::json!({
+                        "id": id,
+                        "type": "function",
+                        "function": {
+                            "name": name,
+                            "arguments": serde_json::to_string(input).unwrap_or_default(),
+                        }
+                    })
+                })
+                .collect();
+
+            // Extract any text content from the response for the assistant message
+            let assistant_text: String = response
+                .content
+                .iter()
+                .filter_map(|block| {
+                    if let ContentBlock::Text { text } = block {
+                        Some(text.as_str())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("");
+            if !assistant_text.is_empty() {
+                if !partial.is_empty() {
+                    partial.push_str("\n\n");
+                }
+                partial.push_str(&assistant_text);
+            }
+
+            request.messages.push(LlmMessage {
+                role: "assistant".into(),
+                content: assistant_text,
+                tool_call_id: None,
+                tool_calls: Some(assistant_tool_calls),
+                parts: None,
+                cache: false,
+            });
+
+            for (id, name, _) in &tool_calls {
+                sink.on_tool_call_started(id, name);
+            }
+
+            // Execute the tool calls concurrently, at most `max_parallel_tools`
+            // at a time, and append results in the order the model issued
+            // them. A call to any non-parallel-safe tool serializes the batch.
+            let concurrency = if tool_calls
+                .iter()
+                .all(|(_, name, _)| self.tools.is_parallel_safe(name))
+            {
+                settings.defaults.max_parallel_tools.max(1) as usize
+            } else {
+                1
+            };
+            let auth = request.auth_context.as_ref();
+
+            let futures: Vec<_> = tool_calls
+                .iter()
+                .map(|(id, name, input)| {
+                    let call = ToolCall {
+                        id: id.clone(),
+                        name: name.clone(),
+                        input: input.clone(),
+                    };
+                    let scope = scope.as_ref();
+                    let workspace = workspace.as_path();
+                    async move {
+                        let started = crate::runtime::now_millis();
+                        let traced = trace.map(|_| call.clone());
+                        let result = self.execute_tool(settings, &ctx, call, scope, auth).await;
+                        if let (Some(trace), Some(call)) = (trace, traced) {
+                            let elapsed = crate::runtime::now_millis().saturating_sub(started);
+                            trace.tool_call(iteration, &call, elapsed, &result);
+                        }
+                        let result_json = match result {
+                            Ok(val) => {
+                                let val = self
+                                    .process_result(settings, session_key, workspace, id, name, val)
+                                    .await;
+                                serde_json::to_string(&val).unwrap_or_default()
+                            }
+                            Err(e) => {
+                                error!(tool = %name, error = %e, "tool execution failed");
+                                let error = self.context.templates().render(
+                                    TemplateName::ToolError,
+                                    template_agent,
+                                    &[("tool", name.as_str()), ("error", &e.to_string())],
+                                );
+                                if e.is_retryable() {
+                                    serde_json::json!({ "error": error, "retryable": true })
+                                        .to_string()
+                                } else {
+                                    serde_json::json!({ "error": error }).to_string()
+                                }
+                            }
+                        };
+                        (id.clone(), name.clone(), result_json)
+                    }
+                })
+                .collect();
+
+            // Cancellation drops the batch, killing subprocesses the tools
+            // started with `kill_on_drop`.
+            let batch = futures_util::stream::iter(futures)
+                .buffered(concurrency)
+                .collect::<Vec<_>>();
+            let batch = crate::runtime::until_cancelled(cancel, batch);
+            let Some(results) = budget.within(batch).await else {
+                return exceeded(budget.time_exceeded(), total_hallucinations, total_verified);
+            };
+            let Some(results) = results else {
+                return cancelled(partial, total_hallucinations, total_verified);
+            };
+
+            // Post-write verification: check that claimed writes exist on disk.
+            let verification_results = verification::verify_write_results(
+                self.platform.fs(),
+                &workspace,
+                &results,
+            )
+            .await;
+
+            // Build a set of hallucinated tool call IDs for result replacement.
+            let hallucinated_ids: std::collections::HashSet<String> = verification_results
+                .iter()
+                .filter(|v| !v.verified)
+                .map(|v| v.tool_call_id.clone())
+                .collect();
+
+            // Count verification outcomes.
+            for vr in &verification_results {
+                if vr.verified {
+                    total_verified += 1;
+                } else {
+                    total_hallucinations += 1;
+                    warn!(
+                        tool_call_id = %vr.tool_call_id,
+                        path = %vr.claimed_path.display(),
+                        "VERIFICATION FAILED: file does not exist (hallucinated write)"
+                    );
+                }
+            }
+
+            for (id, _name, result_json) in &results {
+                let content = if hallucinated_ids.contains(id) {
+                    // Replace the success result with a verification failure error.
+                    serde_json::json!({
+                        "error": "VERIFICATION FAILED: the file you claimed to write does not exist on disk. The write was hallucinated. Please retry the write operation."
+                    }).to_string()
+                } else {
+                    result_json.clone()
+                };
+
+                request.messages.push(LlmMessage {
+                    role: "tool".into(),
+                    content,
+                    tool_call_id: Some(id.clone()),
+                    tool_calls: None,
+                    parts: None,
+                    cache: false,
+                });
+            }
+        }
+
+        Err(ClawftError::Provider {
+            message: format!("max tool iterations ({}) exceeded", max_iterations),
+        })
+    }
+
+    /// Run one tool call: pre-tool hooks, the tool itself (through `scope`
+    /// when the turn has an allowlist), post-tool hooks, then the audit
+    /// log. A hook veto becomes the call's error. `spawn_agent` and
+    /// `secret_use` calls are run here, by [`spawn_agent`](Self::spawn_agent)
+    /// and [`use_secret`](Self::use_secret). Secret values are scrubbed
+    /// from the result before the post-tool hooks see it. Tools run with
+    /// the turn's session key and agent id visible through
+    /// [`crate::runtime::session_key`] and [`crate::runtime::agent_id`].
+    async fn execute_tool(
+        &self,
+        settings: &LiveSettings,
+        ctx: &HookContext<'_>,
+        mut call: ToolCall,
+        scope: Option<&ScopedTools<'_>>,
+        auth: Option<&AuthContext>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let started = crate::runtime::now_millis();
+        let permissions = auth.map(|auth| &auth.permissions);
+        let result = match self.hooks.pre_tool(ctx, &mut call).await {
+            Err(veto) => {
+                warn!(tool = %call.name, %veto, "tool call vetoed");
+                Err(veto.into_tool_error(&call.name))
+            }
+            Ok(()) => {
+                let input = call.input.clone();
+                let mut result = if call.name == SPAWN_AGENT_TOOL {
+                    let authorized = match scope {
+                        Some(scope) => scope.authorize(&call.name, permissions),
+                        None => self.tools.authorize(&call.name, permissions),
+                    };
+                    match authorized {
+                        Ok(_) => {
+                            let parent_scope = scope.map(|scope| scope.allowed());
+                            Box::pin(self.spawn_agent(settings, ctx, input, parent_scope, auth))
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    }
+                } else if call.name == SECRET_USE_TOOL {
+                    let authorized = match scope {
+                        Some(scope) => scope.authorize(&call.name, permissions),
+                        None => self.tools.authorize(&call.name, permissions),
+                    };
+                    match authorized {
+                        Ok(_) => self.use_secret(ctx, &call.id, input, scope, auth).await,
+                        Err(e) => Err(e),
+                    }
+                } else {
+                    let run = async {
+                        match scope {
+                            Some(scope) => scope.execute(&call.name, input, permissions).await,
+                            None => self.tools.execute(&call.name, input, permissions).await,
+                        }
+                    };
+                    let run = crate::runtime::with_session_key(ctx.session_key, run);
+                    crate::runtime::with_agent_id(ctx.agent_id, run).await
+                };
+                if let Some(secrets) = &self.secrets
+                    && secrets.scrub_result(&mut result)
+                {
+                    warn!(tool = %call.name, "secret value scrubbed from tool result");
+                }
+                if let Err(veto) = self.hooks.post_tool(ctx, &call, &mut result).await {
+                    warn!(tool = %call.name, %veto, "tool result vetoed");
+                    result = Err(veto.into_tool_error(&call.name));
+                }
+                result
+            }
+        };
+        if let Some(audit) = &self.audit {
+            let elapsed = crate::runtime::now_millis().saturating_sub(started);
+            audit
+                .record(
+                    ctx.agent_id,
+                    ctx.session_key,
+                    &call.name,
+                    &call.input,
+                    elapsed,
+                    &result,
+                )
+                .await;
+        }
+        result
+    }
+
+    /// Run a `secret_use` call: the requested tool, with the call's
+    /// `{{secret:NAME}}` references filled in.
+    ///
+    /// The inner call is authorized as if the model had made it directly,
+    /// and pre-tool hooks see it with its references, never the values.
+    /// The result is scrubbed by the caller like any other.
+    async fn use_secret(
+        &self,
+        ctx: &HookContext<'_>,
+        call_id: &str,
+        args: serde_json::Value,
+        scope: Option<&ScopedTools<'_>>,
+        auth: Option<&AuthContext>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let request = SecretUseRequest::from_args(args)?;
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            ToolError::ExecutionFailed("no secrets are configured in tools.secrets".into())
+        })?;
+        let permissions = auth.map(|auth| &auth.permissions);
+        let mut inner = ToolCall {
+            id: call_id.to_string(),
+            name: request.tool,
+            input: request.arguments,
+        };
+        match scope {
+            Some(scope) => scope.authorize(&inner.name, permissions)?,
+            None => self.tools.authorize(&inner.name, permissions)?,
+        };
+        if let Err(veto) = self.hooks.pre_tool(ctx, &mut inner).await {
+            warn!(tool = %inner.name, %veto, "tool call vetoed");
+            return Err(veto.into_tool_error(&inner.name));
+        }
+        let mut input = inner.input;
+        secrets.interpolate(&inner.name, &mut input)?;
+        let run = async {
+            match scope {
+                Some(scope) => scope.execute(&inner.name, input, permissions).await,
+                None => self.tools.execute(&inner.name, input, permissions).await,
+            }
+        };
+        let run = crate::runtime::with_session_key(ctx.session_key, run);
+        crate::runtime::with_agent_id(ctx.agent_id, run).await
+    }
+
+    /// Run a `spawn_agent` call made in the turn described by `parent`.
+    ///
+    /// The sub-agent gets a fresh session under the parent's, its
+    /// definition's system prompt, model and skills, and the tools its
+    /// definition allows -- narrowed by the call's `allowed_tools` and by
+    /// the parent turn's own allowlist, so spawning never widens access.
+    /// It runs under `agents.budget` with the call's `max_tokens` and
+    /// `max_iterations` on top. The result is a [`SpawnResult`].
+    async fn spawn_agent(
+        &self,
+        settings: &LiveSettings,
+        parent: &HookContext<'_>,
+        args: serde_json::Value,
+        parent_scope: Option<&[String]>,
+        auth: Option<&AuthContext>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let request = SpawnRequest::from_args(args)?;
+        if subagent::spawn_depth(parent.session_key) >= settings.max_spawn_depth {
+            return Err(ToolError::PermissionDenied {
+                tool: SPAWN_AGENT_TOOL.into(),
+                reason: format!(
+                    "sub-agents may only be nested {} level(s) deep",
+                    settings.max_spawn_depth
+                ),
+            });
+        }
+        let agents = self.agents.as_deref();
+        let Some(agent) = agents.and_then(|agents| agents.get(&request.agent)) else {
+            let known: Vec<&str> = agents
+                .map(|a| a.list().into_iter().map(|d| d.name.as_str()).collect())
+                .unwrap_or_default();
+            return Err(ToolError::InvalidArgs(format!(
+                "unknown agent '{}' (defined agents: {})",
+                request.agent,
+                known.join(", ")
+            )));
+        };
+
+        let session_key = subagent::child_session_key(parent.session_key, &agent.name);
+        let mut session = Session::new(&session_key);
+        session
+            .metadata
+            .insert(Session::PARENT_KEY.into(), parent.session_key.into());
+        session
+            .metadata
+            .insert("agent".into(), agent.name.clone().into());
+        info!(
+            parent = parent.session_key,
+            parent_agent = parent.agent_id,
+            session = %session_key,
+            agent = %agent.name,
+            "spawning sub-agent"
+        );
+
+        let mut messages = self
+            .context
+            .build_messages_for_agent(&session, agent, &request.task, None)
+            .await;
+        messages.push(LlmMessage {
+            role: "user".into(),
+            content: request.task.clone(),
+            tool_call_id: None,
+            tool_calls: None,
+            parts: None,
+            cache: false,
+        });
+        session.add_message("user", &request.task, None);
+
+        let requested = request.allowed_tools.unwrap_or_default();
+        let allowed = subagent::narrow_tools(
+            self.tools.list(),
+            [requested.as_slice(), agent.allowed_tools.as_slice()]
+                .into_iter()
+                .filter(|list| !list.is_empty())
+                .chain(parent_scope),
+        );
+        let tools = match &allowed {
+            Some(allowed) => self.tools.scoped(allowed).schemas(),
+            None => self.tools.schemas(),
+        };
+
+        let chat = ChatRequest {
+            messages,
+            tools,
+            model: Some(
+                agent
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| settings.defaults.model.clone()),
+            ),
+            max_tokens: Some(settings.defaults.max_tokens),
+            temperature: Some(settings.defaults.temperature),
+            auth_context: auth.cloned(),
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let mut limits = self.config.budget.clone();
+        if let Some(max_tokens) = request.max_tokens {
+            limits.max_tokens_per_turn = Some(
+                limits
+                    .max_tokens_per_turn
+                    .map_or(max_tokens, |limit| limit.min(max_tokens)),
+            );
+        }
+        let mut budget = TurnBudget::new(&limits, self.daily_spend.clone());
+        let max_iterations = request
+            .max_iterations
+            .or(agent.max_turns)
+            .map_or(self.max_tool_iterations(settings), |n| n.max(1) as usize);
+        let sink: Arc<dyn ResponseSink> = Arc::new(BufferingSink::new());
+
+        // The parent's cancellation drops this future, so the sub-agent
+        // needs no token of its own.
+        let outcome = self
+            .run_tool_loop(
+                settings,
+                chat,
+                &session_key,
+                &agent.name,
+                allowed.as_deref(),
+                &CancellationToken::new(),
+                max_iterations,
+                &mut budget,
+                &sink,
+                None,
+            )
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("agent '{}': {e}", agent.name)))?;
+
+        session.add_message("assistant", &outcome.text, None);
+        if let Err(e) = self.sessions.save_session(&session).await {
+            warn!(session = %session_key, error = %e, "failed to save sub-agent session");
+        }
+        let result = SpawnResult {
+            agent: agent.name.clone(),
+            session_key,
+            answer: outcome.text,
+            usage: SpawnUsage {
+                input_tokens: budget.input_tokens(),
+                output_tokens: budget.output_tokens(),
+            },
+        };
+        serde_json::to_value(result).map_err(|e| ToolError::ExecutionFailed(e.to_string()))
+    }
+}
+
+/// Stored in the session in place of (or after) the reply of a turn that
+/// was cancelled.
+pub const CANCELLED_TURN_MARKER: &str = "[This turn was cancelled before it finished.]";
+
+/// Outbound metadata key carrying the key of a session created by
+/// `/fork`.
+pub const FORKED_SESSION_KEY: &str = "forked_session";
+
+/// Parse a `/fork [n]` command: `None` if `content` is not one,
+/// `Some(Err(arg))` if the index does not parse.
+fn parse_fork_command(content: &str) -> Option<Result<Option<usize>, String>> {
+    let rest = content.trim().strip_prefix("/fork")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let arg = rest.trim();
+    if arg.is_empty() {
+        return Some(Ok(None));
+    }
+    Some(arg.parse().map(Some).map_err(|_| arg.to_string()))
+}
+
+/// The message presenting `plan` for approval.
+fn approval_request(plan: &Plan) -> String {
+    format!(
+        "{}\n\nReply \"approve\" to run this plan, \"reject\" to drop it, or describe what to \
+         change.",
+        plan.render()
+    )
+}
+
+/// A plain text message.
+fn text_message(role: &str, content: String) -> LlmMessage {
+    LlmMessage {
+        role: role.into(),
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        parts: None,
+        cache: false,
+    }
+}
+
+/// The text blocks of `response`, joined.
+fn response_text(response: &LlmResponse) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Session metadata key holding a message a `confirm` routing rule is
+/// waiting on.
+const PENDING_ROUTE_KEY: &str = "routing_pending";
+
+/// Whether `content` confirms a held message.
+fn is_confirmation(content: &str) -> bool {
+    let word = content
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_ascii_lowercase();
+    matches!(word.as_str(), "yes" | "y" | "ok" | "confirm")
+}
+
+/// The agent a message is addressed to, for the tool audit log: the
+/// `agent` metadata key when a channel set one, otherwise `"default"`.
+fn agent_id(msg: &InboundMessage) -> &str {
+    msg.metadata
+        .get("agent")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::MemoryStore;
+    use crate::agent::skills::SkillsLoader;
+    use crate::pipeline::traits::{
+        AssembledContext, LearningBackend, LearningSignal, LlmTransport, ModelRouter, Pipeline,
+        QualityScore, QualityScorer, ResponseOutcome, RoutingDecision, TaskClassifier, TaskProfile,
+        TaskType, Trajectory, TransportRequest,
+    };
+    use crate::tools::registry::Tool;
+    use async_trait::async_trait;
+    use clawft_platform::NativePlatform;
+    use clawft_types::config::AgentDefaults;
+    use clawft_types::provider::{LlmResponse, StopReason, Usage};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static TEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        std::env::temp_dir().join(format!("clawft_loop_test_{prefix}_{pid}_{id}"))
+    }
+
+    fn buffering_sink() -> Arc<dyn ResponseSink> {
+        Arc::new(BufferingSink::new())
+    }
+
+    fn make_chat_request(content: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: content.into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        }
+    }
+
+    fn test_config() -> AgentsConfig {
+        AgentsConfig {
+            defaults: AgentDefaults {
+                workspace: "~/.clawft/workspace".into(),
+                model: "test-model".into(),
+                max_tokens: 4096,
+                temperature: 0.5,
+                top_p: None,
+                max_tool_iterations: 10,
+                memory_window: 50,
+                max_parallel_tools: 4,
+            },
+            dispatch: Default::default(),
+            usage: Default::default(),
+            budget: Default::default(),
+            memory: Default::default(),
+            cache: Default::default(),
+            sessions: Default::default(),
+            compaction: Default::default(),
+            planning: Default::default(),
+            templates: Default::default(),
+            skills: Default::default(),
+            interactive: Default::default(),
+        }
+    }
+
+    // -- Mock pipeline stages --
+
+    struct MockClassifier;
+    impl TaskClassifier for MockClassifier {
+        fn classify(&self, _request: &ChatRequest) -> TaskProfile {
+            TaskProfile {
+                task_type: TaskType::Chat,
+                complexity: 0.3,
+                keywords: vec![],
+            }
+        }
+    }
+
+    struct MockRouter;
+    #[async_trait]
+    impl ModelRouter for MockRouter {
+        async fn route(&self, _request: &ChatRequest, _profile: &TaskProfile) -> RoutingDecision {
+            RoutingDecision {
+                provider: "test".into(),
+                model: "test-model".into(),
                 reason: "mock".into(),
                 ..Default::default()
             }
         }
-        fn update(&self, _d: &RoutingDecision, _o: &ResponseOutcome) {}
+        fn update(&self, _d: &RoutingDecision, _o: &ResponseOutcome) {}
+    }
+
+    struct MockAssembler;
+    #[async_trait]
+    impl crate::pipeline::traits::ContextAssembler for MockAssembler {
+        async fn assemble(
+            &self,
+            request: &ChatRequest,
+            _profile: &TaskProfile,
+        ) -> AssembledContext {
+            AssembledContext {
+                messages: request.messages.clone(),
+                token_estimate: 100,
+                truncated: false,
+            }
+        }
+    }
+
+    /// Transport that returns a fixed text response.
+    struct MockTransport {
+        response_text: String,
+    }
+
+    impl MockTransport {
+        fn new(text: &str) -> Self {
+            Self {
+                response_text: text.into(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmTransport for MockTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "mock-resp".into(),
+                content: vec![ContentBlock::Text {
+                    text: self.response_text.clone(),
+                }],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    total_tokens: 0,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
+                },
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    /// Transport that returns a tool call first, then text.
+    struct MockToolTransport {
+        call_count: std::sync::atomic::AtomicUsize,
+    }
+
+    impl MockToolTransport {
+        fn new() -> Self {
+            Self {
+                call_count: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmTransport for MockToolTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let count = self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            if count == 0 {
+                // First call: request a tool use
+                Ok(LlmResponse {
+                    id: "mock-tool-resp".into(),
+                    content: vec![ContentBlock::ToolUse {
+                        id: "call-1".into(),
+                        name: "echo".into(),
+                        input: serde_json::json!({"text": "hello"}),
+                    }],
+                    stop_reason: StopReason::ToolUse,
+                    usage: Usage {
+                        input_tokens: 10,
+                        output_tokens: 5,
+                        total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                    metadata: HashMap::new(),
+                })
+            } else {
+                // Second call: return text
+                Ok(LlmResponse {
+                    id: "mock-final-resp".into(),
+                    content: vec![ContentBlock::Text {
+                        text: "tool result processed".into(),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                    usage: Usage {
+                        input_tokens: 20,
+                        output_tokens: 8,
+                        total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                    metadata: HashMap::new(),
+                })
+            }
+        }
+    }
+
+    /// Transport that always returns tool calls (to test max iterations).
+    struct InfiniteToolTransport;
+
+    #[async_trait]
+    impl LlmTransport for InfiniteToolTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "infinite".into(),
+                content: vec![ContentBlock::ToolUse {
+                    id: "call-inf".into(),
+                    name: "echo".into(),
+                    input: serde_json::json!({"text": "loop"}),
+                }],
+                stop_reason: StopReason::ToolUse,
+                usage: Usage {
+                    input_tokens: 5,
+                    output_tokens: 3,
+                    total_tokens: 0,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
+                },
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    struct MockScorer;
+    impl QualityScorer for MockScorer {
+        fn score(&self, _req: &ChatRequest, _resp: &LlmResponse) -> QualityScore {
+            QualityScore {
+                overall: 1.0,
+                relevance: 1.0,
+                coherence: 1.0,
+            }
+        }
+    }
+
+    struct MockLearner;
+    impl LearningBackend for MockLearner {
+        fn record(&self, _t: &Trajectory) {}
+        fn adapt(&self, _s: &LearningSignal) {}
+    }
+
+    fn make_pipeline(transport: Arc<dyn LlmTransport>) -> PipelineRegistry {
+        let pipeline = Pipeline {
+            classifier: Arc::new(MockClassifier),
+            router: Arc::new(MockRouter),
+            assembler: Arc::new(MockAssembler),
+            transport,
+            scorer: Arc::new(MockScorer),
+            learner: Arc::new(MockLearner),
+        };
+        PipelineRegistry::new(pipeline)
+    }
+
+    // -- Mock tool --
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+        fn description(&self) -> &str {
+            "Echo back the input text"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" }
+                },
+                "required": ["text"]
+            })
+        }
+        async fn execute(
+            &self,
+            args: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
+            let text = args
+                .get("text")
+                .and_then(|v| v.as_str())
+                .unwrap_or("(no text)");
+            Ok(serde_json::json!({"output": text}))
+        }
+    }
+
+    /// Helper to create an AgentLoop with the given transport.
+    async fn make_agent_loop(
+        transport: Arc<dyn LlmTransport>,
+        prefix: &str,
+    ) -> (AgentLoop<NativePlatform>, PathBuf) {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        make_agent_loop_with_tools(transport, prefix, tools, test_config()).await
+    }
+
+    async fn make_agent_loop_with_tools(
+        transport: Arc<dyn LlmTransport>,
+        prefix: &str,
+        tools: ToolRegistry,
+        config: AgentsConfig,
+    ) -> (AgentLoop<NativePlatform>, PathBuf) {
+        let dir = temp_dir(prefix);
+        let platform = Arc::new(NativePlatform::new());
+        let bus = Arc::new(MessageBus::new());
+
+        let sessions_dir = dir.join("sessions");
+        let sessions = SessionManager::with_dir(platform.clone(), sessions_dir);
+
+        let memory = Arc::new(MemoryStore::with_paths(
+            dir.join("memory").join("MEMORY.md"),
+            dir.join("memory").join("HISTORY.md"),
+            platform.clone(),
+        ));
+        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
+        let context = ContextBuilder::new(config.clone(), memory, skills, platform.clone());
+
+        let pipeline = make_pipeline(transport);
+
+        let agent = AgentLoop::new(
+            config,
+            platform,
+            bus,
+            pipeline,
+            Arc::new(tools),
+            context,
+            Arc::new(sessions),
+            PermissionResolver::default_resolver(),
+        );
+        (agent, dir)
+    }
+
+    #[test]
+    fn new_creates_agent_loop_with_all_deps() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let transport = Arc::new(MockTransport::new("hello"));
+            let (agent, dir) = make_agent_loop(transport, "new_all").await;
+
+            assert_eq!(agent.config().defaults.model, "test-model");
+            assert_eq!(agent.config().defaults.max_tokens, 4096);
+            assert_eq!(agent.config().defaults.max_tool_iterations, 10);
+
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+        });
+    }
+
+    #[test]
+    fn config_accessor_returns_config() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let transport = Arc::new(MockTransport::new("hello"));
+            let (agent, dir) = make_agent_loop(transport, "config_acc").await;
+
+            assert_eq!(agent.config().defaults.memory_window, 50);
+            assert_eq!(agent.config().defaults.workspace, "~/.clawft/workspace");
+
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+        });
+    }
+
+    #[test]
+    fn platform_accessor_returns_platform() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let transport = Arc::new(MockTransport::new("hello"));
+            let (agent, dir) = make_agent_loop(transport, "platform_acc").await;
+
+            // Verify the platform reference is accessible
+            let _p = agent.platform();
+
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+        });
+    }
+
+    #[tokio::test]
+    async fn process_message_produces_outbound() {
+        let transport = Arc::new(MockTransport::new("Hello from LLM!"));
+        let (agent, dir) = make_agent_loop(transport, "process_msg").await;
+
+        // Publish an inbound message
+        let inbound = InboundMessage {
+            channel: "test".into(),
+            sender_id: "user1".into(),
+            chat_id: "chat1".into(),
+            content: "hi there".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        agent.bus.publish_inbound(inbound).unwrap();
+
+        // Process it
+        let msg = agent.bus.consume_inbound().await.unwrap();
+        agent.process_message(msg).await.unwrap();
+
+        // Check outbound
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(outbound.channel, "test");
+        assert_eq!(outbound.chat_id, "chat1");
+        assert_eq!(outbound.content, "Hello from LLM!");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    fn scheduled_prompt(delivery: &ProactiveDelivery) -> InboundMessage {
+        let mut metadata = HashMap::new();
+        delivery.insert_into(&mut metadata);
+        InboundMessage {
+            channel: "heartbeat".into(),
+            sender_id: "system".into(),
+            chat_id: "heartbeat".into(),
+            content: "check my calendar".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata,
+        }
+    }
+
+    #[tokio::test]
+    async fn scheduled_reply_goes_to_delivery_target() {
+        use clawft_types::delivery::{DeliveryTarget, QuietHours};
+
+        let transport = Arc::new(MockTransport::new("Standup moved to 10:00."));
+        let (agent, dir) = make_agent_loop(transport, "proactive_target").await;
+        let delivery = ProactiveDelivery {
+            target: Some(DeliveryTarget {
+                channel: "telegram".into(),
+                chat_id: "42".into(),
+            }),
+            quiet_hours: Some(QuietHours::parse("22:00-07:00", "UTC").unwrap()),
+        };
+
+        agent
+            .process_message(scheduled_prompt(&delivery))
+            .await
+            .unwrap();
+
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(outbound.channel, "telegram");
+        assert_eq!(outbound.chat_id, "42");
+        assert_eq!(outbound.content, "Standup moved to 10:00.");
+        assert_eq!(
+            QuietHours::from_metadata(&outbound.metadata),
+            delivery.quiet_hours
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn scheduled_reply_with_no_notify_is_dropped() {
+        let transport = Arc::new(MockTransport::new("Nothing new today.\n\nNO_NOTIFY"));
+        let (agent, dir) = make_agent_loop(transport, "proactive_suppress").await;
+
+        agent
+            .process_message(scheduled_prompt(&ProactiveDelivery::default()))
+            .await
+            .unwrap();
+
+        let next = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            agent.bus.consume_outbound(),
+        )
+        .await;
+        assert!(next.is_err(), "suppressed reply was dispatched");
+
+        // The reply is still part of the conversation.
+        let session = agent
+            .sessions
+            .load_session("heartbeat:heartbeat")
+            .await
+            .unwrap();
+        let last = session.messages.last().unwrap();
+        assert!(last["content"].as_str().unwrap().ends_with("NO_NOTIFY"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn process_message_executes_tool_loop() {
+        let transport = Arc::new(MockToolTransport::new());
+        let (agent, dir) = make_agent_loop(transport, "tool_loop").await;
+
+        let inbound = InboundMessage {
+            channel: "test".into(),
+            sender_id: "user1".into(),
+            chat_id: "chat1".into(),
+            content: "use echo tool".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        agent.bus.publish_inbound(inbound).unwrap();
+
+        let msg = agent.bus.consume_inbound().await.unwrap();
+        agent.process_message(msg).await.unwrap();
+
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(outbound.content, "tool result processed");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn run_tool_loop_respects_max_iterations() {
+        let transport = Arc::new(InfiniteToolTransport);
+        let (agent, dir) = make_agent_loop(transport, "max_iter").await;
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "loop forever".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await;
+        assert!(result.is_err());
+        let err_msg = result.unwrap_err().to_string();
+        assert!(
+            err_msg.contains("max tool iterations"),
+            "error should mention max iterations: {}",
+            err_msg
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn turn_limits_only_tighten_the_configured_ones() {
+        let mut config = test_config();
+        config.budget.max_tokens_per_turn = Some(1000);
+        let (agent, dir) = make_agent_loop_with_tools(
+            Arc::new(MockTransport::new("hi")),
+            "turn_limits",
+            ToolRegistry::new(),
+            config,
+        )
+        .await;
+        let settings = agent.live_config().snapshot();
+        let mut msg = make_inbound("delegation", "peer");
+        assert_eq!(agent.turn_iterations(&settings, &msg), 10);
+        assert!(agent.turn_budget(&msg).remaining_time().is_none());
+
+        msg.metadata.insert(
+            InboundMessage::LIMITS_KEY.into(),
+            serde_json::json!({"max_iterations": 3, "max_tokens": 5000, "max_secs": 30}),
+        );
+        assert_eq!(agent.turn_iterations(&settings, &msg), 3);
+        let mut budget = agent.turn_budget(&msg);
+        assert!(budget.remaining_time().unwrap() <= std::time::Duration::from_secs(30));
+        budget.record_usage(&Usage {
+            input_tokens: 600,
+            output_tokens: 500,
+            ..Default::default()
+        });
+        assert!(
+            budget.check().is_err(),
+            "the configured 1000 tokens still apply"
+        );
+
+        msg.metadata.insert(
+            InboundMessage::LIMITS_KEY.into(),
+            serde_json::json!({"max_iterations": 50}),
+        );
+        assert_eq!(agent.turn_iterations(&settings, &msg), 10);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn cli_messages_can_pin_the_turn_model() {
+        let (agent, dir) =
+            make_agent_loop(Arc::new(MockTransport::new("hi")), "pinned_model").await;
+        let default_model = agent.live_config().snapshot().defaults.model.clone();
+
+        let mut msg = make_inbound("cli", "user");
+        assert!(agent.turn_settings(&msg).pinned_model.is_none());
+
+        msg.metadata.insert(
+            InboundMessage::MODEL_KEY.into(),
+            "openai/gpt-4o-mini".into(),
+        );
+        let settings = agent.turn_settings(&msg);
+        assert_eq!(settings.pinned_model.as_deref(), Some("openai/gpt-4o-mini"));
+        assert_eq!(settings.defaults.model, "openai/gpt-4o-mini");
+        assert_eq!(
+            agent.live_config().snapshot().defaults.model,
+            default_model,
+            "the shared settings are untouched"
+        );
+
+        msg.channel = "telegram".into();
+        assert!(agent.turn_settings(&msg).pinned_model.is_none());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn run_exits_when_bus_closes() {
+        let transport = Arc::new(MockTransport::new("hello"));
+        let (agent, dir) = make_agent_loop(transport, "bus_close").await;
+
+        // Drop the inbound sender by dropping a cloned bus reference.
+        // We need to drop all inbound senders. The bus holds one internally.
+        // Simplest: publish a message, consume it, then drop the bus.
+        // Since the agent holds an Arc<MessageBus>, we cannot fully drop it.
+        // Instead, test that run() exits when the channel is closed by
+        // spawning run in a background task and sending a message that
+        // processes, then dropping the bus's sender.
+
+        // We cannot easily test the full `run()` loop exit here since the
+        // bus is shared via Arc. Instead test the contract: consume_inbound
+        // returns None when all senders are dropped.
+        // This is already tested in bus.rs. Here we verify the struct compiles.
+        assert!(
+            agent
+                .bus()
+                .inbound_sender()
+                .send(InboundMessage {
+                    channel: "test".into(),
+                    sender_id: "u".into(),
+                    chat_id: "c".into(),
+                    content: "msg".into(),
+                    timestamp: chrono::Utc::now(),
+                    media: vec![],
+                    attachments: vec![],
+                    metadata: HashMap::new(),
+                })
+                .await
+                .is_ok()
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn process_message_saves_session() {
+        let transport = Arc::new(MockTransport::new("saved response"));
+        let (agent, dir) = make_agent_loop(transport, "session_save").await;
+
+        let inbound = InboundMessage {
+            channel: "telegram".into(),
+            sender_id: "user1".into(),
+            chat_id: "chat42".into(),
+            content: "remember this".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        agent.bus.publish_inbound(inbound).unwrap();
+
+        let msg = agent.bus.consume_inbound().await.unwrap();
+        agent.process_message(msg).await.unwrap();
+
+        // Verify session was saved with both messages
+        let session = agent
+            .sessions
+            .get_or_create("telegram:chat42")
+            .await
+            .unwrap();
+        // Session should have user message + assistant message
+        assert!(session.messages.len() >= 2);
+        // ... and the report of how the context fit the model's budget.
+        let report = &session.metadata[context_budget::CONTEXT_REPORT_KEY];
+        assert!(report["tokens"].as_u64().unwrap() > 0);
+        assert_eq!(report["dropped"], serde_json::json!([]));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Transport that records every request and answers summarization
+    /// prompts with a canned summary.
+    struct CompactionTransport {
+        requests: std::sync::Mutex<Vec<TransportRequest>>,
+    }
+
+    impl CompactionTransport {
+        fn requests(&self) -> Vec<TransportRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LlmTransport for CompactionTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let text = {
+                let mut requests = self.requests.lock().unwrap();
+                requests.push(request.clone());
+                if request.model == "cheap-model" {
+                    format!("summary {}", requests.len())
+                } else {
+                    "answer".to_string()
+                }
+            };
+            MockTransport::new(&text).complete(request).await
+        }
+    }
+
+    #[tokio::test]
+    async fn history_beyond_window_is_compacted_into_a_summary() {
+        let transport = Arc::new(CompactionTransport {
+            requests: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut config = test_config();
+        config.defaults.memory_window = 4;
+        config.compaction.model = Some("cheap/cheap-model".into());
+        config.compaction.min_turns = 2;
+        let (agent, dir) = make_agent_loop_with_tools(
+            transport.clone(),
+            "compaction",
+            ToolRegistry::new(),
+            config,
+        )
+        .await;
+
+        let mut session = agent.sessions.get_or_create("test:c1").await.unwrap();
+        for i in 0..6 {
+            session.add_message("user", &format!("question {i}"), None);
+            session.add_message("assistant", &format!("answer {i}"), None);
+        }
+        agent.sessions.save_session(&session).await.unwrap();
+
+        let send = |content: &str| InboundMessage {
+            channel: "test".into(),
+            sender_id: "user1".into(),
+            chat_id: "c1".into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+
+        // Turn 1: the 8 messages outside the window are summarized first.
+        agent.process_message(send("next")).await.unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].provider, "cheap");
+        let prompt = &requests[0].messages[1].content;
+        assert!(prompt.contains("user: question 0"));
+        assert!(prompt.contains("assistant: answer 3"));
+        assert!(!prompt.contains("question 4"));
+
+        let main = &requests[1].messages;
+        let at = main
+            .iter()
+            .position(|m| m.content.starts_with("# Conversation Summary"))
+            .expect("summary message");
+        assert_eq!(main[at].role, "system");
+        assert!(main[at].content.ends_with("summary 1"));
+        assert!(main[..at].iter().all(|m| m.role == "system"));
+        assert_eq!(main[at + 1].content, "question 4");
+        assert_eq!(main.last().unwrap().content, "next");
+
+        // Turn 2: within min_turns of the last compaction, so no summary call.
+        agent.process_message(send("again")).await.unwrap();
+        assert_eq!(transport.requests().len(), 3);
+
+        // Turn 3: compaction folds the newly evicted span into the summary.
+        agent.process_message(send("more")).await.unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[3].model, "cheap-model");
+        let body = &requests[3].messages[1].content;
+        assert!(body.starts_with("Earlier summary:\nsummary 1"), "{body}");
+        assert!(body.contains("user: question 4") && body.contains("assistant: answer 5"));
+        assert!(!body.contains("user: next"));
+
+        // Every original message is still on disk, alongside the summary.
+        let fresh = SessionManager::with_dir(agent.platform.clone(), dir.join("sessions"));
+        let stored = fresh.load_session("test:c1").await.unwrap();
+        assert_eq!(stored.messages.len(), 18);
+        assert_eq!(stored.messages[0]["content"], "question 0");
+        let state = CompactionState::load(&stored).unwrap();
+        assert_eq!(state.summary, "summary 4");
+        assert_eq!(state.summarized_through, 12);
+
+        for _ in 0..3 {
+            agent.bus.consume_outbound().await.unwrap();
+        }
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Transport that streams scripted deltas: a tool call, then a reply.
+    /// Non-streaming calls fail.
+    struct StreamingTransport {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmTransport for StreamingTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Err(ClawftError::Provider {
+                message: "expected a streaming call".into(),
+            })
+        }
+
+        async fn complete_stream(
+            &self,
+            _request: &TransportRequest,
+            mut callback: crate::pipeline::traits::StreamCallback,
+        ) -> clawft_types::Result<LlmResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let deltas = if call == 0 {
+                ["Let me ", "check."]
+            } else {
+                ["Hel", "lo!"]
+            };
+            for delta in deltas {
+                callback(delta);
+            }
+            let mut content = vec![ContentBlock::Text {
+                text: deltas.concat(),
+            }];
+            let stop_reason = if call == 0 {
+                content.push(ContentBlock::ToolUse {
+                    id: "call_1".into(),
+                    name: "echo".into(),
+                    input: serde_json::json!({"text": "hi"}),
+                });
+                StopReason::ToolUse
+            } else {
+                StopReason::EndTurn
+            };
+            Ok(LlmResponse {
+                id: format!("stream-{call}"),
+                content,
+                stop_reason,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    /// Sink that records every event, opened for every message.
+    #[derive(Default)]
+    struct RecordingSink {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl ResponseSink for RecordingSink {
+        fn on_text_delta(&self, delta: &str) {
+            self.events.lock().unwrap().push(format!("delta:{delta}"));
+        }
+        fn on_tool_call_started(&self, id: &str, name: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("tool:{id}:{name}"));
+        }
+        fn on_complete(&self, text: &str) {
+            self.events.lock().unwrap().push(format!("complete:{text}"));
+        }
+    }
+
+    struct RecordingSinks(Arc<RecordingSink>);
+
+    impl ResponseSinkFactory for RecordingSinks {
+        fn open(&self, _msg: &InboundMessage) -> Option<Arc<dyn ResponseSink>> {
+            Some(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn streaming_sink_receives_deltas_and_tool_calls() {
+        let transport = Arc::new(StreamingTransport {
+            calls: Default::default(),
+        });
+        let (agent, dir) = make_agent_loop(transport, "stream_sink").await;
+        let sink = Arc::new(RecordingSink::default());
+        let agent = agent.with_response_sinks(Arc::new(RecordingSinks(sink.clone())));
+
+        agent
+            .process_message(make_inbound("test", "user1"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *sink.events.lock().unwrap(),
+            vec![
+                "delta:Let me ",
+                "delta:check.",
+                "tool:call_1:echo",
+                "delta:Hel",
+                "delta:lo!",
+                "complete:Hello!",
+            ]
+        );
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(outbound.content, "Hello!");
+        assert!(outbound.is_streamed());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn replies_without_a_sink_are_not_streamed() {
+        let transport = Arc::new(MockTransport::new("plain"));
+        let (agent, dir) = make_agent_loop(transport, "no_sink").await;
+
+        agent
+            .process_message(make_inbound("test", "user1"))
+            .await
+            .unwrap();
+
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(outbound.content, "plain");
+        assert!(!outbound.is_streamed());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn session_budget_stops_llm_calls() {
+        let transport = Arc::new(MockTransport::new("answer"));
+        let (agent, dir) = make_agent_loop(transport, "budget").await;
+        // $1 per 1K tokens both ways: each mock call (10 in, 5 out) costs $0.015.
+        let pricing = HashMap::from([(
+            "test-model".to_string(),
+            clawft_llm::usage::PriceOverride {
+                input_per_1k: 1.0,
+                output_per_1k: 1.0,
+                cache_read_per_1k: None,
+                cache_write_per_1k: None,
+            },
+        )]);
+        let tracker = Arc::new(UsageTracker::new(clawft_llm::PriceTable::with_overrides(
+            &pricing,
+        )));
+        let mut agent = agent.with_usage_tracker(tracker.clone());
+        agent.config.usage.session_budget_usd = Some(0.02);
+
+        let send = |chat_id: &str| InboundMessage {
+            channel: "test".into(),
+            sender_id: "user1".into(),
+            chat_id: chat_id.into(),
+            content: "hi".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+
+        // Two calls fit under $0.02 (the second crosses it); the third is refused.
+        for expected in ["answer", "answer"] {
+            agent.process_message(send("chat1")).await.unwrap();
+            assert_eq!(
+                agent.bus.consume_outbound().await.unwrap().content,
+                expected
+            );
+        }
+        agent.process_message(send("chat1")).await.unwrap();
+        let refusal = agent.bus.consume_outbound().await.unwrap().content;
+        assert!(refusal.contains("spending limit"), "{refusal}");
+
+        let totals = tracker.session_totals("test:chat1");
+        assert_eq!(totals.requests, 2);
+        assert_eq!(totals.input_tokens, 20);
+        assert!((totals.cost_usd - 0.03).abs() < 1e-9);
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot[0].0.provider, "test");
+        assert_eq!(snapshot[0].0.model, "test-model");
+
+        // Other sessions have their own budget.
+        agent.process_message(send("chat2")).await.unwrap();
+        assert_eq!(
+            agent.bus.consume_outbound().await.unwrap().content,
+            "answer"
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn agent_loop_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<AgentLoop<NativePlatform>>();
+    }
+
+    // ── GAP-19: Tool result truncation verification ───────────────────
+
+    /// Transport that returns a tool call for a tool producing oversized output.
+    struct OversizedToolTransport {
+        call_count: std::sync::atomic::AtomicUsize,
+    }
+
+    impl OversizedToolTransport {
+        fn new() -> Self {
+            Self {
+                call_count: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmTransport for OversizedToolTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let count = self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            if count == 0 {
+                // First call: request a tool call that will produce oversized output
+                Ok(LlmResponse {
+                    id: "oversized-resp".into(),
+                    content: vec![ContentBlock::ToolUse {
+                        id: "call-big".into(),
+                        name: "big_output".into(),
+                        input: serde_json::json!({}),
+                    }],
+                    stop_reason: StopReason::ToolUse,
+                    usage: Usage {
+                        input_tokens: 10,
+                        output_tokens: 5,
+                        total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                    metadata: HashMap::new(),
+                })
+            } else {
+                // Second call: verify the tool result was included (truncated)
+                // and return text. Check message list for truncation.
+                let last_msg = request.messages.last();
+                let content_text = last_msg
+                    .map(|m| m.content.as_str())
+                    .unwrap_or("no tool result");
+
+                Ok(LlmResponse {
+                    id: "final-resp".into(),
+                    content: vec![ContentBlock::Text {
+                        text: format!("tool_result_len:{}", content_text.len()),
+                    }],
+                    stop_reason: StopReason::EndTurn,
+                    usage: Usage {
+                        input_tokens: 20,
+                        output_tokens: 8,
+                        total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                    metadata: HashMap::new(),
+                })
+            }
+        }
+    }
+
+    /// Tool that produces output exceeding `tools.results.max_bytes`.
+    struct BigOutputTool;
+
+    #[async_trait]
+    impl Tool for BigOutputTool {
+        fn name(&self) -> &str {
+            "big_output"
+        }
+        fn description(&self) -> &str {
+            "Returns a very large output"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": {}
+            })
+        }
+        async fn execute(
+            &self,
+            _args: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
+            // Produce output far exceeding the default 64KB limit
+            let big_string = "x".repeat(200_000);
+            Ok(serde_json::json!({"data": big_string}))
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_result_truncation_enforced() {
+        let dir = temp_dir("truncation");
+        let platform = Arc::new(NativePlatform::new());
+        let bus = Arc::new(MessageBus::new());
+
+        let sessions_dir = dir.join("sessions");
+        let sessions = SessionManager::with_dir(platform.clone(), sessions_dir);
+
+        let memory = Arc::new(MemoryStore::with_paths(
+            dir.join("memory").join("MEMORY.md"),
+            dir.join("memory").join("HISTORY.md"),
+            platform.clone(),
+        ));
+        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
+        let context = ContextBuilder::new(test_config(), memory, skills, platform.clone());
+
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(BigOutputTool));
+
+        let pipeline = make_pipeline(Arc::new(OversizedToolTransport::new()));
+
+        let mut config = test_config();
+        let workspace = dir.join("workspace");
+        config.defaults.workspace = workspace.display().to_string();
+        let agent = AgentLoop::new(
+            config,
+            platform,
+            bus,
+            pipeline,
+            Arc::new(tools),
+            context,
+            Arc::new(sessions),
+            PermissionResolver::default_resolver(),
+        );
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "trigger big tool".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+
+        let settings = agent.live_config().snapshot();
+        let tool_result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+        let result = &tool_result.text;
+
+        // The tool result should have been truncated to `tools.results.max_bytes`.
+        // The response tells us the length of the tool result message.
+        assert!(
+            result.starts_with("tool_result_len:"),
+            "response should contain truncated tool result length: {result}"
+        );
+        let len_str = result.strip_prefix("tool_result_len:").unwrap();
+        let result_len: usize = len_str.parse().unwrap();
+        let max_bytes = ToolResultsConfig::default().max_bytes;
+        assert!(
+            result_len <= max_bytes,
+            "tool result ({result_len} bytes) should be truncated to <= {max_bytes} bytes"
+        );
+
+        // The full result was spilled to the workspace.
+        let spilled: Vec<_> = std::fs::read_dir(workspace.join(tool_results::SPILL_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(spilled.len(), 1);
+        let full: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&spilled[0]).unwrap()).unwrap();
+        assert_eq!(full["data"].as_str().unwrap().len(), 200_000);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    struct MockAssembler;
-    #[async_trait]
-    impl crate::pipeline::traits::ContextAssembler for MockAssembler {
-        async fn assemble(
-            &self,
-            request: &ChatRequest,
-            _profile: &TaskProfile,
-        ) -> AssembledContext {
-            AssembledContext {
-                messages: request.messages.clone(),
-                token_estimate: 100,
-                truncated: false,
-            }
-        }
-    }
+    #[tokio::test]
+    async fn tool_executions_are_audited() {
+        use crate::tools::audit::{AuditFilter, AuditStore, JsonlAuditLog, Redactor};
+
+        let dir = temp_dir("audit");
+        let platform = Arc::new(NativePlatform::new());
+        let sessions = SessionManager::with_dir(platform.clone(), dir.join("sessions"));
+        let memory = Arc::new(MemoryStore::with_paths(
+            dir.join("memory").join("MEMORY.md"),
+            dir.join("memory").join("HISTORY.md"),
+            platform.clone(),
+        ));
+        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
+        let context = ContextBuilder::new(test_config(), memory, skills, platform.clone());
+
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(BigOutputTool));
+
+        let log = Arc::new(JsonlAuditLog::new(dir.join("audit.jsonl"), 1024 * 1024, 1));
+        let mut config = test_config();
+        config.defaults.workspace = dir.join("workspace").display().to_string();
+        let agent = AgentLoop::new(
+            config,
+            platform,
+            Arc::new(MessageBus::new()),
+            make_pipeline(Arc::new(OversizedToolTransport::new())),
+            Arc::new(tools),
+            context,
+            Arc::new(sessions),
+            PermissionResolver::default_resolver(),
+        )
+        .with_tool_audit(Arc::new(ToolAuditor::new(log.clone(), Redactor::default())));
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "trigger big tool".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let settings = agent.live_config().snapshot();
+        agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "researcher",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let records = log.recent(&AuditFilter::default(), 10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.tool, "big_output");
+        assert_eq!(record.agent_id, "researcher");
+        assert_eq!(record.session_key, "test:chat1");
+        assert!(record.success);
+        // The size is of the full result, before truncation for the model.
+        assert!(record.result_bytes > ToolResultsConfig::default().max_bytes as u64);
 
-    /// Transport that returns a fixed text response.
-    struct MockTransport {
-        response_text: String,
+        let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    impl MockTransport {
-        fn new(text: &str) -> Self {
-            Self {
-                response_text: text.into(),
-            }
-        }
+    /// Transport whose model calls `echo` after one retry and a failover,
+    /// then answers.
+    struct ScriptedTurnTransport {
+        call_count: std::sync::atomic::AtomicUsize,
     }
 
     #[async_trait]
-    impl LlmTransport for MockTransport {
+    impl LlmTransport for ScriptedTurnTransport {
         async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let count = self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let mut metadata = HashMap::new();
+            let (content, stop_reason) = if count == 0 {
+                metadata.insert("model".into(), serde_json::json!("fallback/test-model"));
+                metadata.insert("retries".into(), serde_json::json!(1));
+                metadata.insert(
+                    "failover".into(),
+                    serde_json::json!([{"provider": "primary", "model": "test-model", "reason": "error"}]),
+                );
+                let call = ContentBlock::ToolUse {
+                    id: "call-1".into(),
+                    name: "echo".into(),
+                    input: serde_json::json!({"text": "ping", "password": "hunter2"}),
+                };
+                (vec![call], StopReason::ToolUse)
+            } else {
+                (
+                    vec![ContentBlock::Text {
+                        text: "pong".into(),
+                    }],
+                    StopReason::EndTurn,
+                )
+            };
             Ok(LlmResponse {
-                id: "mock-resp".into(),
-                content: vec![ContentBlock::Text {
-                    text: self.response_text.clone(),
-                }],
-                stop_reason: StopReason::EndTurn,
+                id: format!("resp-{count}"),
+                content,
+                stop_reason,
                 usage: Usage {
-                    input_tokens: 10,
-                    output_tokens: 5,
-                    total_tokens: 0,
+                    input_tokens: 100,
+                    output_tokens: 10,
+                    ..Default::default()
                 },
-                metadata: HashMap::new(),
+                metadata,
             })
         }
     }
 
-    /// Transport that returns a tool call first, then text.
-    struct MockToolTransport {
-        call_count: std::sync::atomic::AtomicUsize,
+    #[tokio::test]
+    async fn traced_turn_records_settings_context_calls_and_usage() {
+        let transport = Arc::new(ScriptedTurnTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) = make_agent_loop(transport, "trace_turn").await;
+        let mut metadata = HashMap::new();
+        metadata.insert(InboundMessage::TRACE_KEY.into(), serde_json::json!(true));
+        let inbound = |metadata| InboundMessage {
+            channel: "cli".into(),
+            sender_id: "user1".into(),
+            chat_id: "chat1".into(),
+            content: "say ping".into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata,
+        };
+        agent.process_message(inbound(metadata)).await.unwrap();
+
+        let fs = agent.platform.fs();
+        let sessions_dir = agent.sessions.sessions_dir();
+        let trace = trace::read_trace(fs, sessions_dir, "cli:chat1", None)
+            .await
+            .unwrap()
+            .expect("trace written");
+        assert_eq!(trace.turn, 1);
+        assert_eq!(trace.agent_id, "default");
+        assert_eq!(trace.outcome, TurnOutcome::Completed);
+        assert_eq!(trace.settings.model, "test-model");
+        assert_eq!(trace.settings.max_tokens, 4096);
+        assert_eq!(trace.settings.max_tool_iterations, 10);
+
+        // Context: the system prompt first, the user message last.
+        let sections = &trace.context.sections;
+        assert_eq!(sections.first().unwrap().role, "system");
+        let user = sections.last().unwrap();
+        assert_eq!(
+            (user.role.as_str(), user.preview.as_str()),
+            ("user", "say ping")
+        );
+        assert!(sections.iter().all(|s| s.tokens > 0));
+        assert_eq!(trace.context.tools, ["echo"]);
+
+        // Completions, with the provider's retry and failover.
+        assert_eq!(trace.completions.len(), 2);
+        let first = &trace.completions[0];
+        assert_eq!(first.stop_reason, "tool_use");
+        assert_eq!(first.model.as_deref(), Some("fallback/test-model"));
+        assert_eq!(first.retries, 1);
+        assert_eq!(first.failover[0]["provider"], "primary");
+        assert_eq!(trace.completions[1].iteration, 1);
+
+        // The tool call, with credentials masked as in the audit log.
+        assert_eq!(trace.tool_calls.len(), 1);
+        let call = &trace.tool_calls[0];
+        assert_eq!((call.id.as_str(), call.tool.as_str()), ("call-1", "echo"));
+        assert!(call.success);
+        assert_eq!(call.args["text"], "ping");
+        assert_eq!(call.args["password"], crate::tools::audit::REDACTED);
+
+        assert_eq!(trace.usage.input_tokens, 200);
+        assert_eq!(trace.usage.output_tokens, 20);
+
+        // Without the flag (and with `agents.sessions.trace` off) the next
+        // turn is not traced.
+        agent
+            .process_message(inbound(HashMap::new()))
+            .await
+            .unwrap();
+        assert_eq!(
+            trace::traced_turns(fs, sessions_dir, "cli:chat1").await,
+            [1]
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    impl MockToolTransport {
-        fn new() -> Self {
-            Self {
-                call_count: std::sync::atomic::AtomicUsize::new(0),
-            }
-        }
+    /// Transport whose model calls `big_output` and then replies with
+    /// the tool result it was given.
+    struct HallucinatedToolTransport {
+        call_count: std::sync::atomic::AtomicUsize,
     }
 
     #[async_trait]
-    impl LlmTransport for MockToolTransport {
-        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+    impl LlmTransport for HallucinatedToolTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
             let count = self
                 .call_count
                 .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            if count == 0 {
-                // First call: request a tool use
-                Ok(LlmResponse {
-                    id: "mock-tool-resp".into(),
-                    content: vec![ContentBlock::ToolUse {
-                        id: "call-1".into(),
-                        name: "echo".into(),
-                        input: serde_json::json!({"text": "hello"}),
-                    }],
-                    stop_reason: StopReason::ToolUse,
-                    usage: Usage {
-                        input_tokens: 10,
-                        output_tokens: 5,
-                        total_tokens: 0,
-                    },
-                    metadata: HashMap::new(),
-                })
+            let (content, stop_reason) = if count == 0 {
+                let call = ContentBlock::ToolUse {
+                    id: "call-1".into(),
+                    name: "big_output".into(),
+                    input: serde_json::json!({"api_token": "t0ps3cret"}),
+                };
+                (vec![call], StopReason::ToolUse)
             } else {
-                // Second call: return text
-                Ok(LlmResponse {
-                    id: "mock-final-resp".into(),
-                    content: vec![ContentBlock::Text {
-                        text: "tool result processed".into(),
-                    }],
-                    stop_reason: StopReason::EndTurn,
-                    usage: Usage {
-                        input_tokens: 20,
-                        output_tokens: 8,
-                        total_tokens: 0,
-                    },
-                    metadata: HashMap::new(),
-                })
-            }
+                let text = request.messages.last().unwrap().content.clone();
+                (vec![ContentBlock::Text { text }], StopReason::EndTurn)
+            };
+            Ok(LlmResponse {
+                id: format!("resp-{count}"),
+                content,
+                stop_reason,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
         }
     }
 
-    /// Transport that always returns tool calls (to test max iterations).
-    struct InfiniteToolTransport;
+    #[tokio::test]
+    async fn disallowed_tool_call_is_denied_and_audited() {
+        use crate::tools::audit::{AuditFilter, AuditStore, JsonlAuditLog, Redactor};
+
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        tools.register(Arc::new(BigOutputTool));
+        let transport = Arc::new(HallucinatedToolTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport, "allowlist", tools, test_config()).await;
+        let log = Arc::new(JsonlAuditLog::new(dir.join("audit.jsonl"), 1024 * 1024, 1));
+        let agent =
+            agent.with_tool_audit(Arc::new(ToolAuditor::new(log.clone(), Redactor::default())));
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "hi".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let allowed = vec!["echo".to_string()];
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "default",
+                Some(&allowed),
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        // The model sees a structured denial instead of the tool's output.
+        let denial: serde_json::Value = serde_json::from_str(&result.text).unwrap();
+        assert!(
+            denial["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("permission denied for tool 'big_output'"),
+            "{denial}"
+        );
+
+        let records = log.recent(&AuditFilter::default(), 10).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tool, "big_output");
+        assert!(!records[0].success);
+        assert!(
+            records[0]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("permission denied")
+        );
+        assert_eq!(records[0].args["api_token"], crate::tools::audit::REDACTED);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Reports the session key and agent id it runs under, as `big_output`.
+    struct SessionKeyTool;
 
     #[async_trait]
-    impl LlmTransport for InfiniteToolTransport {
-        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
-            Ok(LlmResponse {
-                id: "infinite".into(),
-                content: vec![ContentBlock::ToolUse {
-                    id: "call-inf".into(),
-                    name: "echo".into(),
-                    input: serde_json::json!({"text": "loop"}),
-                }],
-                stop_reason: StopReason::ToolUse,
-                usage: Usage {
-                    input_tokens: 5,
-                    output_tokens: 3,
-                    total_tokens: 0,
-                },
-                metadata: HashMap::new(),
-            })
+    impl Tool for SessionKeyTool {
+        fn name(&self) -> &str {
+            "big_output"
+        }
+        fn description(&self) -> &str {
+            "Report the calling session"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+        async fn execute(
+            &self,
+            _args: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
+            Ok(serde_json::json!({
+                "session": crate::runtime::session_key(),
+                "agent": crate::runtime::agent_id(),
+            }))
         }
     }
 
-    struct MockScorer;
-    impl QualityScorer for MockScorer {
-        fn score(&self, _req: &ChatRequest, _resp: &LlmResponse) -> QualityScore {
-            QualityScore {
-                overall: 1.0,
-                relevance: 1.0,
-                coherence: 1.0,
-            }
-        }
+    #[tokio::test]
+    async fn tools_see_the_calling_session_key() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(SessionKeyTool));
+        let transport = Arc::new(HallucinatedToolTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport, "session_key", tools, test_config()).await;
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "hi".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_str(&result.text).unwrap();
+        assert_eq!(output["session"], "test:chat1");
+        assert_eq!(output["agent"], "default");
+        assert_eq!(crate::runtime::session_key(), None);
+        assert_eq!(crate::runtime::agent_id(), None);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    struct MockLearner;
-    impl LearningBackend for MockLearner {
-        fn record(&self, _t: &Trajectory) {}
-        fn adapt(&self, _s: &LearningSignal) {}
+    /// Transport that makes one tool call, then answers with the last
+    /// message (the tool result).
+    struct OneCallTransport {
+        name: &'static str,
+        input: serde_json::Value,
+        call_count: std::sync::atomic::AtomicUsize,
     }
 
-    fn make_pipeline(transport: Arc<dyn LlmTransport>) -> PipelineRegistry {
-        let pipeline = Pipeline {
-            classifier: Arc::new(MockClassifier),
-            router: Arc::new(MockRouter),
-            assembler: Arc::new(MockAssembler),
-            transport,
-            scorer: Arc::new(MockScorer),
-            learner: Arc::new(MockLearner),
-        };
-        PipelineRegistry::new(pipeline)
+    impl OneCallTransport {
+        fn new(name: &'static str, input: serde_json::Value) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                input,
+                call_count: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
     }
 
-    // -- Mock tool --
+    #[async_trait]
+    impl LlmTransport for OneCallTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let count = self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let (content, stop_reason) = if count == 0 {
+                let call = ContentBlock::ToolUse {
+                    id: "call-1".into(),
+                    name: self.name.into(),
+                    input: self.input.clone(),
+                };
+                (vec![call], StopReason::ToolUse)
+            } else {
+                let text = request.messages.last().unwrap().content.clone();
+                (vec![ContentBlock::Text { text }], StopReason::EndTurn)
+            };
+            Ok(LlmResponse {
+                id: format!("resp-{count}"),
+                content,
+                stop_reason,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
 
-    struct EchoTool;
+    /// Records the arguments it is called with and returns them, as
+    /// `store`.
+    #[derive(Default)]
+    struct StoreTool {
+        calls: std::sync::Mutex<Vec<serde_json::Value>>,
+    }
 
     #[async_trait]
-    impl Tool for EchoTool {
+    impl Tool for StoreTool {
         fn name(&self) -> &str {
-            "echo"
+            "store"
         }
         fn description(&self) -> &str {
-            "Echo back the input text"
+            "Store the arguments"
         }
         fn parameters(&self) -> serde_json::Value {
-            serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "text": { "type": "string" }
-                },
-                "required": ["text"]
-            })
+            serde_json::json!({"type": "object"})
         }
         async fn execute(
             &self,
             args: serde_json::Value,
         ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
-            let text = args
-                .get("text")
-                .and_then(|v| v.as_str())
-                .unwrap_or("(no text)");
-            Ok(serde_json::json!({"output": text}))
+            self.calls.lock().unwrap().push(args.clone());
+            Ok(serde_json::json!({"stored": args}))
         }
     }
 
-    /// Helper to create an AgentLoop with the given transport.
-    async fn make_agent_loop(
-        transport: Arc<dyn LlmTransport>,
-        prefix: &str,
-    ) -> (AgentLoop<NativePlatform>, PathBuf) {
-        let dir = temp_dir(prefix);
-        let platform = Arc::new(NativePlatform::new());
-        let bus = Arc::new(MessageBus::new());
-
-        let sessions_dir = dir.join("sessions");
-        let sessions = SessionManager::with_dir(platform.clone(), sessions_dir);
-
-        let memory = Arc::new(MemoryStore::with_paths(
-            dir.join("memory").join("MEMORY.md"),
-            dir.join("memory").join("HISTORY.md"),
-            platform.clone(),
-        ));
-        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
-        let context = ContextBuilder::new(test_config(), memory, skills, platform.clone());
-
+    /// Run one turn of an agent with `store`, `secret_use` and a `github`
+    /// secret granted to `granted`, whose model makes the call `name`
+    /// with `input`. Returns the turn's final text (the tool result) and
+    /// the arguments `store` received.
+    async fn run_secret_turn(
+        granted: &str,
+        name: &'static str,
+        input: serde_json::Value,
+    ) -> (String, Vec<serde_json::Value>) {
+        use crate::agent::secrets::SecretUseTool;
+        use clawft_types::config::SecretRef;
+
+        let store = Arc::new(StoreTool::default());
         let mut tools = ToolRegistry::new();
-        tools.register(Arc::new(EchoTool));
+        tools.register(store.clone());
+        tools.register(Arc::new(SecretUseTool));
+        let secrets = HashMap::from([(
+            "github".to_string(),
+            SecretRef {
+                value: "ghp_abc123".into(),
+                tools: vec![granted.to_string()],
+                ..Default::default()
+            },
+        )]);
+        let transport = OneCallTransport::new(name, input);
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport, "secrets", tools, test_config()).await;
+        let agent = agent.with_secrets(Arc::new(Secrets::from_config(&secrets, |_| None)));
 
-        let pipeline = make_pipeline(transport);
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "hi".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let calls = store.calls.lock().unwrap().clone();
+        (result.text, calls)
+    }
 
-        let agent = AgentLoop::new(
-            test_config(),
-            platform,
-            bus,
-            pipeline,
-            Arc::new(tools),
-            context,
-            Arc::new(sessions),
-            PermissionResolver::default_resolver(),
+    #[tokio::test]
+    async fn secret_use_fills_in_secrets_but_never_returns_them() {
+        let (text, calls) = run_secret_turn(
+            "store",
+            "secret_use",
+            serde_json::json!({
+                "tool": "store",
+                "arguments": {"content": "token = {{secret:github}}"}
+            }),
+        )
+        .await;
+
+        // The tool got the value; the model got the tool result without it.
+        assert_eq!(
+            calls,
+            [serde_json::json!({"content": "token = ghp_abc123"})]
         );
-        (agent, dir)
+        assert!(!text.contains("ghp_abc123"), "{text}");
+        let output: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(output["stored"]["content"], "token = [REDACTED]");
     }
 
-    #[test]
-    fn new_creates_agent_loop_with_all_deps() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let transport = Arc::new(MockTransport::new("hello"));
-            let (agent, dir) = make_agent_loop(transport, "new_all").await;
+    #[tokio::test]
+    async fn secret_values_are_scrubbed_from_any_tool_result() {
+        let (text, calls) = run_secret_turn(
+            "store",
+            "store",
+            serde_json::json!({"content": "pasted ghp_abc123"}),
+        )
+        .await;
+        assert_eq!(calls.len(), 1);
+        let output: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(output["stored"]["content"], "pasted [REDACTED]");
+    }
 
-            assert_eq!(agent.config().defaults.model, "test-model");
-            assert_eq!(agent.config().defaults.max_tokens, 4096);
-            assert_eq!(agent.config().defaults.max_tool_iterations, 10);
+    #[tokio::test]
+    async fn secret_use_refuses_tools_the_secret_is_not_granted_to() {
+        let (text, calls) = run_secret_turn(
+            "write_file",
+            "secret_use",
+            serde_json::json!({
+                "tool": "store",
+                "arguments": {"content": "{{secret:github}}"}
+            }),
+        )
+        .await;
+        assert!(calls.is_empty());
+        assert!(text.contains("not granted to tool 'store'"), "{text}");
+        assert!(!text.contains("ghp_abc123"), "{text}");
+    }
 
-            let _ = tokio::fs::remove_dir_all(&dir).await;
-        });
+    /// Vetoes `big_output` calls and, when `block_completions` is set,
+    /// every completion.
+    struct PolicyHook {
+        block_completions: bool,
     }
 
-    #[test]
-    fn config_accessor_returns_config() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let transport = Arc::new(MockTransport::new("hello"));
-            let (agent, dir) = make_agent_loop(transport, "config_acc").await;
+    #[async_trait]
+    impl crate::agent::hooks::Hook for PolicyHook {
+        fn name(&self) -> &str {
+            "policy"
+        }
 
-            assert_eq!(agent.config().defaults.memory_window, 50);
-            assert_eq!(agent.config().defaults.workspace, "~/.clawft/workspace");
+        async fn on_pre_tool(
+            &self,
+            _ctx: &HookContext<'_>,
+            call: &mut ToolCall,
+        ) -> crate::agent::hooks::HookDecision {
+            if call.name == "big_output" {
+                crate::agent::hooks::HookDecision::Veto("output too large for this channel".into())
+            } else {
+                crate::agent::hooks::HookDecision::Continue
+            }
+        }
 
-            let _ = tokio::fs::remove_dir_all(&dir).await;
-        });
+        async fn on_pre_completion(
+            &self,
+            _ctx: &HookContext<'_>,
+            _request: &mut ChatRequest,
+        ) -> crate::agent::hooks::HookDecision {
+            if self.block_completions {
+                crate::agent::hooks::HookDecision::Veto("model calls are paused".into())
+            } else {
+                crate::agent::hooks::HookDecision::Continue
+            }
+        }
     }
 
-    #[test]
-    fn platform_accessor_returns_platform() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let transport = Arc::new(MockTransport::new("hello"));
-            let (agent, dir) = make_agent_loop(transport, "platform_acc").await;
-
-            // Verify the platform reference is accessible
-            let _p = agent.platform();
-
-            let _ = tokio::fs::remove_dir_all(&dir).await;
-        });
+    fn hooks_with(hook: PolicyHook) -> HookRegistry {
+        let mut hooks = HookRegistry::default();
+        hooks.register(Arc::new(hook));
+        hooks
     }
 
     #[tokio::test]
-    async fn process_message_produces_outbound() {
-        let transport = Arc::new(MockTransport::new("Hello from LLM!"));
-        let (agent, dir) = make_agent_loop(transport, "process_msg").await;
-
-        // Publish an inbound message
-        let inbound = InboundMessage {
-            channel: "test".into(),
-            sender_id: "user1".into(),
-            chat_id: "chat1".into(),
-            content: "hi there".into(),
-            timestamp: chrono::Utc::now(),
-            media: vec![],
-            metadata: HashMap::new(),
-        };
-        agent.bus.publish_inbound(inbound).unwrap();
-
-        // Process it
-        let msg = agent.bus.consume_inbound().await.unwrap();
-        agent.process_message(msg).await.unwrap();
+    async fn hook_veto_reaches_the_model_as_a_tool_error() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(BigOutputTool));
+        let transport = Arc::new(HallucinatedToolTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport, "hook_veto", tools, test_config()).await;
+        let agent = agent.with_hooks(hooks_with(PolicyHook {
+            block_completions: false,
+        }));
+
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                make_chat_request("hi"),
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
 
-        // Check outbound
-        let outbound = agent.bus.consume_outbound().await.unwrap();
-        assert_eq!(outbound.channel, "test");
-        assert_eq!(outbound.chat_id, "chat1");
-        assert_eq!(outbound.content, "Hello from LLM!");
+        let denial: serde_json::Value = serde_json::from_str(&result.text).unwrap();
+        assert_eq!(
+            denial["error"],
+            "permission denied for tool 'big_output': blocked by hook 'policy': \
+             output too large for this channel"
+        );
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    #[tokio::test]
-    async fn process_message_executes_tool_loop() {
-        let transport = Arc::new(MockToolTransport::new());
-        let (agent, dir) = make_agent_loop(transport, "tool_loop").await;
-
-        let inbound = InboundMessage {
-            channel: "test".into(),
-            sender_id: "user1".into(),
-            chat_id: "chat1".into(),
-            content: "use echo tool".into(),
-            timestamp: chrono::Utc::now(),
-            media: vec![],
-            metadata: HashMap::new(),
-        };
-        agent.bus.publish_inbound(inbound).unwrap();
-
-        let msg = agent.bus.consume_inbound().await.unwrap();
-        agent.process_message(msg).await.unwrap();
-
-        let outbound = agent.bus.consume_outbound().await.unwrap();
-        assert_eq!(outbound.content, "tool result processed");
+    /// Named like [`BigOutputTool`] so [`HallucinatedToolTransport`]
+    /// calls it; always fails as briefly unavailable.
+    struct UnavailableTool;
 
-        let _ = tokio::fs::remove_dir_all(&dir).await;
+    #[async_trait]
+    impl Tool for UnavailableTool {
+        fn name(&self) -> &str {
+            "big_output"
+        }
+        fn description(&self) -> &str {
+            "Backed by a reconnecting server"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+        async fn execute(
+            &self,
+            _args: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
+            Err(crate::tools::registry::ToolError::Unavailable(
+                "MCP server 'docs' is reconnecting".into(),
+            ))
+        }
     }
 
     #[tokio::test]
-    async fn run_tool_loop_respects_max_iterations() {
-        let transport = Arc::new(InfiniteToolTransport);
-        let (agent, dir) = make_agent_loop(transport, "max_iter").await;
-
-        let request = ChatRequest {
-            messages: vec![LlmMessage {
-                role: "user".into(),
-                content: "loop forever".into(),
-                tool_call_id: None,
-                tool_calls: None,
-            }],
-            tools: vec![],
-            model: Some("test-model".into()),
-            max_tokens: Some(4096),
-            temperature: Some(0.5),
-            auth_context: None,
-            complexity_boost: 0.0,
-        };
+    async fn unavailable_tool_error_is_marked_retryable() {
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(UnavailableTool));
+        let transport = Arc::new(HallucinatedToolTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport, "unavailable", tools, test_config()).await;
+
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                make_chat_request("hi"),
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
 
-        let result = agent.run_tool_loop(request).await;
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
+        let error: serde_json::Value = serde_json::from_str(&result.text).unwrap();
+        assert_eq!(error["retryable"], true);
         assert!(
-            err_msg.contains("max tool iterations"),
-            "error should mention max iterations: {}",
-            err_msg
+            error["error"]
+                .as_str()
+                .unwrap()
+                .contains("MCP server 'docs' is reconnecting"),
+            "{error}"
         );
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
     #[tokio::test]
-    async fn run_exits_when_bus_closes() {
-        let transport = Arc::new(MockTransport::new("hello"));
-        let (agent, dir) = make_agent_loop(transport, "bus_close").await;
-
-        // Drop the inbound sender by dropping a cloned bus reference.
-        // We need to drop all inbound senders. The bus holds one internally.
-        // Simplest: publish a message, consume it, then drop the bus.
-        // Since the agent holds an Arc<MessageBus>, we cannot fully drop it.
-        // Instead, test that run() exits when the channel is closed by
-        // spawning run in a background task and sending a message that
-        // processes, then dropping the bus's sender.
+    async fn hook_veto_on_completion_ends_the_turn() {
+        let transport = Arc::new(HallucinatedToolTransport {
+            call_count: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (agent, dir) = make_agent_loop(transport.clone(), "hook_completion").await;
+        let agent = agent.with_hooks(hooks_with(PolicyHook {
+            block_completions: true,
+        }));
+
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                make_chat_request("hi"),
+                "test:chat1",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
 
-        // We cannot easily test the full `run()` loop exit here since the
-        // bus is shared via Arc. Instead test the contract: consume_inbound
-        // returns None when all senders are dropped.
-        // This is already tested in bus.rs. Here we verify the struct compiles.
-        assert!(
-            agent
-                .bus()
-                .inbound_sender()
-                .send(InboundMessage {
-                    channel: "test".into(),
-                    sender_id: "u".into(),
-                    chat_id: "c".into(),
-                    content: "msg".into(),
-                    timestamp: chrono::Utc::now(),
-                    media: vec![],
-                    metadata: HashMap::new(),
-                })
-                .await
-                .is_ok()
+        assert_eq!(
+            result.text,
+            "This request was blocked by hook 'policy': model calls are paused."
+        );
+        assert_eq!(
+            transport
+                .call_count
+                .load(std::sync::atomic::Ordering::Relaxed),
+            0
         );
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
     #[tokio::test]
-    async fn process_message_saves_session() {
-        let transport = Arc::new(MockTransport::new("saved response"));
-        let (agent, dir) = make_agent_loop(transport, "session_save").await;
+    async fn turn_allowlist_intersects_skill_and_agent() {
+        use crate::agent::agents::AgentDefinition;
+
+        let (agent, dir) = make_agent_loop(Arc::new(MockTransport::new("ok")), "turn_allow").await;
+        let researcher = AgentDefinition {
+            name: "researcher".into(),
+            description: String::new(),
+            model: None,
+            system_prompt: None,
+            skills: vec![],
+            allowed_tools: vec!["read_file".into(), "web_search".into()],
+            max_turns: None,
+            variables: HashMap::new(),
+            templates: HashMap::new(),
+            source_path: None,
+        };
+        let agent = agent.with_agents(Arc::new(
+            AgentRegistry::discover(None, None, vec![researcher]).unwrap(),
+        ));
 
-        let inbound = InboundMessage {
-            channel: "telegram".into(),
-            sender_id: "user1".into(),
-            chat_id: "chat42".into(),
-            content: "remember this".into(),
+        let mut msg = InboundMessage {
+            channel: "cli".into(),
+            sender_id: "local".into(),
+            chat_id: "c".into(),
+            content: "hi".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
-        agent.bus.publish_inbound(inbound).unwrap();
+        assert_eq!(agent.turn_allowed_tools(&msg), None);
 
-        let msg = agent.bus.consume_inbound().await.unwrap();
-        agent.process_message(msg).await.unwrap();
+        msg.metadata.insert("agent".into(), "researcher".into());
+        assert_eq!(
+            agent.turn_allowed_tools(&msg).unwrap(),
+            ["read_file", "web_search"]
+        );
 
-        // Verify session was saved with both messages
-        let session = agent
-            .sessions
-            .get_or_create("telegram:chat42")
+        msg.metadata.insert(
+            "allowed_tools".into(),
+            serde_json::json!(["web_search", "exec"]),
+        );
+        assert_eq!(agent.turn_allowed_tools(&msg).unwrap(), ["web_search"]);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn auto_activated_skill_sets_instructions_tools_and_trace() {
+        use crate::agent::skills_v2::SkillRegistry;
+        use clawft_types::skill::{SkillDefinition, SkillTriggers};
+
+        let mut config = test_config();
+        config.skills.auto_activate = true;
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(EchoTool));
+        let (agent, dir) = make_agent_loop_with_tools(
+            Arc::new(MockTransport::new("ok")),
+            "skill_activation",
+            tools,
+            config,
+        )
+        .await;
+        let mut weather = SkillDefinition::new("weather", "Weather forecasts");
+        weather.instructions = "Answer with the forecast.".into();
+        weather.allowed_tools = vec!["echo".into()];
+        weather.triggers = SkillTriggers {
+            keywords: vec!["forecast".into()],
+            examples: vec![],
+        };
+        let mut registry = SkillRegistry::discover(None, None, Vec::new())
             .await
             .unwrap();
-        // Session should have user message + assistant message
-        assert!(session.messages.len() >= 2);
+        registry.upsert(weather);
+        let agent = agent.with_skill_registry(Arc::new(crate::runtime::RwLock::new(registry)));
+
+        let inbound = |content: &str| InboundMessage {
+            channel: "cli".into(),
+            sender_id: "local".into(),
+            chat_id: "c".into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        let (msg, decision) = agent.activate_skill(inbound("What's the forecast?")).await;
+        assert_eq!(decision.unwrap().activated.as_deref(), Some("weather"));
+        assert_eq!(
+            msg.metadata["skill_instructions"],
+            "Answer with the forecast."
+        );
+        assert_eq!(agent.turn_allowed_tools(&msg).unwrap(), ["echo"]);
+
+        let (msg, decision) = agent.activate_skill(inbound("hello")).await;
+        assert_eq!(decision.unwrap().activated, None);
+        assert!(!msg.metadata.contains_key("skill_instructions"));
+
+        // An explicitly chosen skill is left alone.
+        let mut explicit = inbound("forecast please");
+        explicit
+            .metadata
+            .insert("skill_instructions".into(), "Be brief.".into());
+        let (msg, decision) = agent.activate_skill(explicit).await;
+        assert!(decision.is_none());
+        assert_eq!(msg.metadata["skill_instructions"], "Be brief.");
+
+        // The decision is part of the turn trace.
+        let mut traced = inbound("forecast for Oslo");
+        traced
+            .metadata
+            .insert(InboundMessage::TRACE_KEY.into(), serde_json::json!(true));
+        agent.process_message(traced).await.unwrap();
+        let trace = trace::read_trace(
+            agent.platform.fs(),
+            agent.sessions.sessions_dir(),
+            "cli:c",
+            None,
+        )
+        .await
+        .unwrap()
+        .expect("trace written");
+        assert_eq!(trace.skill.unwrap().activated.as_deref(), Some("weather"));
+        assert_eq!(trace.settings.allowed_tools.unwrap(), ["echo"]);
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
-    #[test]
-    fn agent_loop_is_send() {
-        fn assert_send<T: Send>() {}
-        assert_send::<AgentLoop<NativePlatform>>();
+    /// Transport that answers each request with the next scripted text.
+    struct ScriptedTransport {
+        replies: std::sync::Mutex<std::collections::VecDeque<&'static str>>,
     }
 
-    // ── GAP-19: Tool result truncation verification ───────────────────
-
-    /// Transport that returns a tool call for a tool producing oversized output.
-    struct OversizedToolTransport {
-        call_count: std::sync::atomic::AtomicUsize,
+    impl ScriptedTransport {
+        fn new(replies: &[&'static str]) -> Arc<Self> {
+            Arc::new(Self {
+                replies: std::sync::Mutex::new(replies.iter().copied().collect()),
+            })
+        }
     }
 
-    impl OversizedToolTransport {
-        fn new() -> Self {
-            Self {
-                call_count: std::sync::atomic::AtomicUsize::new(0),
-            }
+    #[async_trait]
+    impl LlmTransport for ScriptedTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let text = self
+                .replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("unscripted request");
+            Ok(LlmResponse {
+                id: "resp".into(),
+                content: vec![ContentBlock::Text { text: text.into() }],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
         }
     }
 
+    #[tokio::test]
+    async fn plan_mode_replans_after_a_failing_step() {
+        let transport = ScriptedTransport::new(&[
+            // The plan.
+            r#"{"goal": "Ship the report", "steps": [
+                {"description": "Gather the numbers", "tool": "echo",
+                 "success_criteria": "the numbers are listed"},
+                {"description": "Write the summary", "success_criteria": "a summary exists"}
+            ]}"#,
+            // Step 1 and its verdict.
+            "Numbers: 1, 2, 3",
+            "```json\n{\"passed\": true, \"reason\": \"the numbers are listed\"}\n```",
+            // Step 2 and its verdict.
+            "I could not write it",
+            r#"{"passed": false, "reason": "no summary was written"}"#,
+            // The revised plan: the first draft misses a criterion.
+            r#"{"goal": "Finish the report", "steps": [{"description": "Write the summary"}]}"#,
+            r#"{"goal": "Finish the report", "steps": [
+                {"description": "Write the summary again", "success_criteria": "a summary exists"}
+            ]}"#,
+        ]);
+        let (agent, dir) = make_agent_loop(transport, "plan").await;
+        let msg = |content: &str| InboundMessage {
+            channel: "cli".into(),
+            sender_id: "user".into(),
+            chat_id: "c1".into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        let state = || async {
+            let session = agent.sessions.get_or_create("cli:c1").await.unwrap();
+            PlanState::load(&session)
+        };
+
+        // The plan is presented and nothing runs until it is approved.
+        agent
+            .process_message(msg("/plan ship the report"))
+            .await
+            .unwrap();
+        let presented = agent.bus.consume_outbound().await.unwrap().content;
+        assert!(
+            presented.contains("1. Gather the numbers (tool: echo)"),
+            "{presented}"
+        );
+        assert!(presented.contains("\"approve\""), "{presented}");
+        let pending = state().await.unwrap();
+        assert_eq!(pending.status, PlanStatus::AwaitingApproval);
+        assert_eq!(pending.task, "ship the report");
+
+        // Step 1 passes, step 2 fails, and a revised plan awaits approval.
+        agent.process_message(msg("approve")).await.unwrap();
+        let report = agent.bus.consume_outbound().await.unwrap().content;
+        assert!(
+            report.contains("Step 1 done: Gather the numbers"),
+            "{report}"
+        );
+        assert!(
+            report.contains("Step 2 failed: no summary was written"),
+            "{report}"
+        );
+        assert!(
+            report.contains("Revised Plan: Finish the report"),
+            "{report}"
+        );
+        let revised = state().await.unwrap();
+        assert_eq!(revised.status, PlanStatus::AwaitingApproval);
+        assert_eq!(revised.replans, 1);
+        assert_eq!(revised.next_step, 0);
+        assert_eq!(revised.plan.steps[0].description, "Write the summary again");
+        let passed: Vec<bool> = revised.outcomes.iter().map(|o| o.passed).collect();
+        assert_eq!(passed, [true, false]);
+
+        // Rejecting drops the plan.
+        agent.process_message(msg("reject")).await.unwrap();
+        assert_eq!(
+            agent.bus.consume_outbound().await.unwrap().content,
+            "Plan discarded."
+        );
+        assert!(state().await.is_none());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Transport that calls the `reload` tool once, then answers, and
+    /// records the `max_tokens` of every request.
+    struct ReloadingTransport {
+        max_tokens: std::sync::Mutex<Vec<Option<i32>>>,
+    }
+
     #[async_trait]
-    impl LlmTransport for OversizedToolTransport {
+    impl LlmTransport for ReloadingTransport {
         async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
-            let count = self
-                .call_count
-                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            if count == 0 {
-                // First call: request a tool call that will produce oversized output
-                Ok(LlmResponse {
-                    id: "oversized-resp".into(),
-                    content: vec![ContentBlock::ToolUse {
-                        id: "call-big".into(),
-                        name: "big_output".into(),
-                        input: serde_json::json!({}),
-                    }],
-                    stop_reason: StopReason::ToolUse,
-                    usage: Usage {
-                        input_tokens: 10,
-                        output_tokens: 5,
-                        total_tokens: 0,
-                    },
-                    metadata: HashMap::new(),
-                })
+            let mut seen = self.max_tokens.lock().unwrap();
+            seen.push(request.max_tokens);
+            let content = if seen.len() == 1 {
+                ContentBlock::ToolUse {
+                    id: "call-1".into(),
+                    name: "reload".into(),
+                    input: serde_json::json!({}),
+                }
             } else {
-                // Second call: verify the tool result was included (truncated)
-                // and return text. Check message list for truncation.
-                let last_msg = request.messages.last();
-                let content_text = last_msg
-                    .map(|m| m.content.as_str())
-                    .unwrap_or("no tool result");
-
-                Ok(LlmResponse {
-                    id: "final-resp".into(),
-                    content: vec![ContentBlock::Text {
-                        text: format!("tool_result_len:{}", content_text.len()),
-                    }],
-                    stop_reason: StopReason::EndTurn,
-                    usage: Usage {
-                        input_tokens: 20,
-                        output_tokens: 8,
-                        total_tokens: 0,
-                    },
-                    metadata: HashMap::new(),
-                })
-            }
+                ContentBlock::Text {
+                    text: "done".into(),
+                }
+            };
+            Ok(LlmResponse {
+                id: "resp".into(),
+                content: vec![content],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
         }
     }
 
-    /// Tool that produces output exceeding MAX_TOOL_RESULT_BYTES.
-    struct BigOutputTool;
+    /// Tool that changes the live settings, like a config reload landing
+    /// in the middle of a turn.
+    struct ReloadTool(Arc<LiveConfig>);
 
     #[async_trait]
-    impl Tool for BigOutputTool {
+    impl Tool for ReloadTool {
         fn name(&self) -> &str {
-            "big_output"
+            "reload"
         }
         fn description(&self) -> &str {
-            "Returns a very large output"
+            "Reload the config"
         }
         fn parameters(&self) -> serde_json::Value {
-            serde_json::json!({
-                "type": "object",
-                "properties": {}
-            })
+            serde_json::json!({"type": "object"})
         }
         async fn execute(
             &self,
             _args: serde_json::Value,
         ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
-            // Produce output far exceeding 64KB (MAX_TOOL_RESULT_BYTES)
-            let big_string = "x".repeat(200_000);
-            Ok(serde_json::json!({"data": big_string}))
+            self.0.update(|live| {
+                live.defaults.max_tokens = 7;
+                live.defaults.max_tool_iterations = 1;
+            });
+            Ok(serde_json::json!("reloaded"))
         }
     }
 
     #[tokio::test]
-    async fn tool_result_truncation_enforced() {
-        let dir = temp_dir("truncation");
-        let platform = Arc::new(NativePlatform::new());
-        let bus = Arc::new(MessageBus::new());
+    async fn in_flight_turn_keeps_its_settings_across_a_reload() {
+        let live = Arc::new(LiveConfig::new(LiveSettings {
+            defaults: test_config().defaults,
+            routing_rules: None,
+            permissions: Arc::new(PermissionResolver::default_resolver()),
+            max_spawn_depth: 0,
+            pinned_model: None,
+        }));
+        let transport = Arc::new(ReloadingTransport {
+            max_tokens: std::sync::Mutex::new(vec![]),
+        });
+        let mut tools = ToolRegistry::new();
+        tools.register(Arc::new(ReloadTool(live.clone())));
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport.clone(), "reload", tools, test_config()).await;
+        let agent = agent.with_live_config(live);
+        let msg = |content: &str| InboundMessage {
+            channel: "cli".into(),
+            sender_id: "user".into(),
+            chat_id: "chat".into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
 
-        let sessions_dir = dir.join("sessions");
-        let sessions = SessionManager::with_dir(platform.clone(), sessions_dir);
+        // The reload lowers the iteration limit to 1 mid-turn, but the turn
+        // finishes its second iteration with the limits it started with.
+        agent.process_message(msg("first")).await.unwrap();
+        let reply = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(reply.content, "done");
 
-        let memory = Arc::new(MemoryStore::with_paths(
-            dir.join("memory").join("MEMORY.md"),
-            dir.join("memory").join("HISTORY.md"),
-            platform.clone(),
-        ));
-        let skills = Arc::new(SkillsLoader::with_dir(dir.join("skills"), platform.clone()));
-        let context = ContextBuilder::new(test_config(), memory, skills, platform.clone());
+        // The next turn starts with the reloaded settings.
+        agent.process_message(msg("second")).await.unwrap();
+        assert_eq!(
+            *transport.max_tokens.lock().unwrap(),
+            [Some(4096), Some(4096), Some(7)]
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn routing_rules_route_drop_and_confirm() {
+        let (agent, dir) = make_agent_loop(Arc::new(MockTransport::new("ok")), "rules").await;
+        let rules: Vec<clawft_types::routing::RoutingRule> = serde_json::from_str(
+            r#"[
+                {"name": "oncall", "match": {"chat": "oncall"}, "action": {"route": "sre"}},
+                {"match": {"sender": "spam-*"}, "action": "drop"},
+                {"name": "invoices", "match": {"content": "(?i)invoice"},
+                 "action": {"confirm": "finance"}}
+            ]"#,
+        )
+        .unwrap();
+        let agent = agent.with_routing_rules(Arc::new(RoutingRules::compile(&rules).unwrap()));
+        let msg = |sender: &str, chat: &str, content: &str| InboundMessage {
+            channel: "test".into(),
+            sender_id: sender.into(),
+            chat_id: chat.into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        let trace = |key: &str| {
+            let sessions = agent.sessions.clone();
+            let key = key.to_string();
+            async move {
+                let session = sessions.get_or_create(&key).await.unwrap();
+                session.metadata.get(ROUTING_TRACE_KEY).cloned()
+            }
+        };
+        let no_outbound = || async {
+            tokio::time::timeout(
+                std::time::Duration::from_millis(20),
+                agent.bus.consume_outbound(),
+            )
+            .await
+            .is_err()
+        };
+
+        // Routed: processed, trace names the rule and agent.
+        agent
+            .process_message(msg("u1", "oncall", "disk full"))
+            .await
+            .unwrap();
+        assert_eq!(agent.bus.consume_outbound().await.unwrap().content, "ok");
+        let t = trace("test:oncall").await.unwrap();
+        assert_eq!(
+            (t["rule"].as_str(), t["agent"].as_str()),
+            (Some("oncall"), Some("sre"))
+        );
+
+        // Dropped: no reply, nothing stored but the trace.
+        agent
+            .process_message(msg("spam-bot", "c2", "buy now"))
+            .await
+            .unwrap();
+        assert!(no_outbound().await);
+        let session = agent.sessions.get_or_create("test:c2").await.unwrap();
+        assert!(session.messages.is_empty());
+        assert_eq!(session.metadata[ROUTING_TRACE_KEY]["action"], "drop");
+
+        // Confirm: held until "yes", then processed as the finance agent.
+        agent
+            .process_message(msg("u3", "c3", "Pay the invoice"))
+            .await
+            .unwrap();
+        let prompt = agent.bus.consume_outbound().await.unwrap();
+        assert!(prompt.content.contains("finance"), "{}", prompt.content);
+        assert_eq!(trace("test:c3").await.unwrap()["action"], "confirm");
+        agent
+            .process_message(msg("u3", "c3", "Yes!"))
+            .await
+            .unwrap();
+        assert_eq!(agent.bus.consume_outbound().await.unwrap().content, "ok");
+        let session = agent.sessions.get_or_create("test:c3").await.unwrap();
+        assert_eq!(session.messages[0]["content"], "Pay the invoice");
+        assert!(!session.metadata.contains_key(PENDING_ROUTE_KEY));
+
+        // A reply other than "yes" discards the held message.
+        agent
+            .process_message(msg("u4", "c4", "invoice"))
+            .await
+            .unwrap();
+        agent.bus.consume_outbound().await.unwrap();
+        agent
+            .process_message(msg("u4", "c4", "never mind"))
+            .await
+            .unwrap();
+        assert_eq!(agent.bus.consume_outbound().await.unwrap().content, "ok");
+        let session = agent.sessions.get_or_create("test:c4").await.unwrap();
+        assert_eq!(session.messages[0]["content"], "never mind");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Transport scripting the parent and its sub-agents by the agent
+    /// named in the system prompt. The parent spawns the agent named in
+    /// the user's message and replies with the raw `spawn_agent` result;
+    /// `researcher` calls `echo` and reports what it found; `looper` tries
+    /// to spawn itself and replies with what happened.
+    struct SubAgentTransport;
 
+    #[async_trait]
+    impl LlmTransport for SubAgentTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            let system = &request.messages[0].content;
+            let last = request.messages.last().unwrap();
+            let call = |name: &str, input: serde_json::Value| ContentBlock::ToolUse {
+                id: format!("call-{name}"),
+                name: name.into(),
+                input,
+            };
+            let content = match (last.role.as_str(), system) {
+                ("tool", s) if s.contains("# Agent: researcher") => ContentBlock::Text {
+                    text: format!("found {}", last.content),
+                },
+                (_, s) if s.contains("# Agent: researcher") => {
+                    call("echo", serde_json::json!({"text": "RFC 9110"}))
+                }
+                (_, s) if s.contains("# Agent: looper") && last.role != "tool" => call(
+                    SPAWN_AGENT_TOOL,
+                    serde_json::json!({"agent": "looper", "task": "again"}),
+                ),
+                ("tool", _) => ContentBlock::Text {
+                    text: last.content.clone(),
+                },
+                _ => call(
+                    SPAWN_AGENT_TOOL,
+                    serde_json::json!({"agent": last.content, "task": "find the HTTP RFC"}),
+                ),
+            };
+            let stop_reason = match content {
+                ContentBlock::ToolUse { .. } => StopReason::ToolUse,
+                _ => StopReason::EndTurn,
+            };
+            Ok(LlmResponse {
+                id: "sub".into(),
+                content: vec![content],
+                stop_reason,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    ..Default::default()
+                },
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn spawn_agent_runs_sub_agent_and_reports_back() {
+        use crate::agent::agents::AgentDefinition;
+        use crate::agent::subagent::{SpawnAgentTool, SpawnResult};
+        use crate::tools::audit::{AuditFilter, AuditStore, JsonlAuditLog, Redactor};
+
+        let definition = |name: &str, tools: &[&str]| AgentDefinition {
+            name: name.into(),
+            description: String::new(),
+            model: None,
+            system_prompt: Some(format!("You are {name}.")),
+            skills: vec![],
+            allowed_tools: tools.iter().map(|t| t.to_string()).collect(),
+            max_turns: None,
+            variables: HashMap::new(),
+            templates: HashMap::new(),
+            source_path: None,
+        };
+        let agents = AgentRegistry::discover(
+            None,
+            None,
+            vec![
+                definition("researcher", &["echo"]),
+                definition("looper", &[SPAWN_AGENT_TOOL]),
+            ],
+        )
+        .unwrap();
         let mut tools = ToolRegistry::new();
-        tools.register(Arc::new(BigOutputTool));
+        tools.register(Arc::new(EchoTool));
+        tools.register(Arc::new(SpawnAgentTool::new(vec![
+            "looper".into(),
+            "researcher".into(),
+        ])));
+        let (agent, dir) =
+            make_agent_loop_with_tools(Arc::new(SubAgentTransport), "spawn", tools, test_config())
+                .await;
+        let log = Arc::new(JsonlAuditLog::new(dir.join("audit.jsonl"), 1024 * 1024, 1));
+        let agent = agent
+            .with_agents(Arc::new(agents))
+            .with_max_spawn_depth(1)
+            .with_tool_audit(Arc::new(ToolAuditor::new(log.clone(), Redactor::default())));
+        let ask = |content: &str| InboundMessage {
+            content: content.into(),
+            ..make_inbound("cli", "local")
+        };
+        let reply = || async {
+            let content = agent.bus.consume_outbound().await.unwrap().content;
+            serde_json::from_str::<serde_json::Value>(&content).unwrap()
+        };
+
+        // The researcher runs in its own session and reports back.
+        agent.process_message(ask("researcher")).await.unwrap();
+        let result: SpawnResult = serde_json::from_value(reply().await).unwrap();
+        assert_eq!(result.agent, "researcher");
+        assert_eq!(result.answer, r#"found {"output":"RFC 9110"}"#);
+        assert_eq!(
+            (result.usage.input_tokens, result.usage.output_tokens),
+            (20, 10)
+        );
+        assert!(
+            result
+                .session_key
+                .starts_with("cli:test-chat:sub-researcher-"),
+            "{}",
+            result.session_key
+        );
+        let child = agent
+            .sessions
+            .get_or_create(&result.session_key)
+            .await
+            .unwrap();
+        assert_eq!(child.parent(), Some("cli:test-chat"));
+        assert_eq!(child.messages[0]["content"], "find the HTTP RFC");
+        assert_eq!(child.messages.len(), 2);
 
-        let pipeline = make_pipeline(Arc::new(OversizedToolTransport::new()));
+        // The audit log links the child's calls to the parent turn.
+        let records = log.recent(&AuditFilter::default(), 10).await.unwrap();
+        let calls: Vec<_> = records
+            .iter()
+            .map(|r| (r.tool.as_str(), r.agent_id.as_str(), r.session_key.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                ("echo", "researcher", result.session_key.as_str()),
+                (SPAWN_AGENT_TOOL, "default", "cli:test-chat"),
+            ]
+        );
 
-        let agent = AgentLoop::new(
-            test_config(),
-            platform,
-            bus,
-            pipeline,
-            Arc::new(tools),
-            context,
-            Arc::new(sessions),
-            PermissionResolver::default_resolver(),
+        // A sub-agent cannot nest deeper than max_spawn_depth.
+        agent.process_message(ask("looper")).await.unwrap();
+        let result: SpawnResult = serde_json::from_value(reply().await).unwrap();
+        assert!(
+            result.answer.contains("may only be nested 1 level(s) deep"),
+            "{}",
+            result.answer
         );
 
-        let request = ChatRequest {
-            messages: vec![LlmMessage {
-                role: "user".into(),
-                content: "trigger big tool".into(),
-                tool_call_id: None,
-                tool_calls: None,
-            }],
-            tools: vec![],
-            model: Some("test-model".into()),
-            max_tokens: Some(4096),
-            temperature: Some(0.5),
-            auth_context: None,
-            complexity_boost: 0.0,
-        };
+        // Agents are checked against the definitions.
+        agent.process_message(ask("ghost")).await.unwrap();
+        let error = reply().await["error"].as_str().unwrap().to_string();
+        assert!(error.contains("unknown agent 'ghost'"), "{error}");
 
-        let tool_result = agent.run_tool_loop(request).await.unwrap();
-        let result = &tool_result.text;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
 
-        // The tool result should have been truncated to MAX_TOOL_RESULT_BYTES (65536).
-        // The response tells us the length of the tool result message.
-        assert!(
-            result.starts_with("tool_result_len:"),
-            "response should contain truncated tool result length: {result}"
-        );
-        let len_str = result.strip_prefix("tool_result_len:").unwrap();
-        let result_len: usize = len_str.parse().unwrap();
+    #[test]
+    fn fork_command_parsing() {
+        assert_eq!(parse_fork_command("/fork"), Some(Ok(None)));
+        assert_eq!(parse_fork_command("  /fork 3 "), Some(Ok(Some(3))));
+        assert_eq!(parse_fork_command("/fork x"), Some(Err("x".into())));
+        assert_eq!(parse_fork_command("/forklift"), None);
+        assert_eq!(parse_fork_command("fork 3"), None);
+    }
+
+    #[tokio::test]
+    async fn fork_command_branches_the_session() {
+        let (agent, dir) = make_agent_loop(Arc::new(MockTransport::new("ok")), "fork").await;
+        let mut session = agent.sessions.get_or_create("test:c1").await.unwrap();
+        session.add_message("user", "question", None);
+        session.add_message("assistant", "answer", None);
+        agent.sessions.save_session(&session).await.unwrap();
+
+        let mut msg = make_inbound("test", "user1");
+        msg.chat_id = "c1".into();
+        msg.content = "/fork 1".into();
+        agent.process_message(msg.clone()).await.unwrap();
+
+        let reply = agent.bus.consume_outbound().await.unwrap();
+        let fork_key = reply.metadata[FORKED_SESSION_KEY].as_str().unwrap();
+        assert!(reply.content.contains(fork_key));
+        let fork = agent.sessions.load_session(fork_key).await.unwrap();
+        assert_eq!(fork.messages.len(), 1);
+        assert_eq!(fork.parent(), Some("test:c1"));
+
+        // The original is untouched and the command is not recorded.
+        let original = agent.sessions.load_session("test:c1").await.unwrap();
+        assert_eq!(original.messages.len(), 2);
+
+        // Messages naming the fork continue it.
+        msg.content = "hello fork".into();
+        msg.metadata
+            .insert(InboundMessage::SESSION_KEY.into(), fork_key.into());
+        agent.process_message(msg).await.unwrap();
+        agent.bus.consume_outbound().await.unwrap();
+        let fork = agent.sessions.load_session(fork_key).await.unwrap();
+        assert_eq!(fork.messages.len(), 3);
+
+        msg = make_inbound("test", "user1");
+        msg.chat_id = "c1".into();
+        msg.content = "/fork 9".into();
+        agent.process_message(msg).await.unwrap();
+        let reply = agent.bus.consume_outbound().await.unwrap();
         assert!(
-            result_len <= MAX_TOOL_RESULT_BYTES,
-            "tool result ({result_len} bytes) should be truncated to <= {} bytes",
-            MAX_TOOL_RESULT_BYTES
+            reply.content.starts_with("Could not fork"),
+            "{}",
+            reply.content
         );
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
@@ -1441,6 +5500,10 @@ mod tests {
                         input_tokens: 15,
                         output_tokens: 10,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1456,6 +5519,10 @@ mod tests {
                         input_tokens: 25,
                         output_tokens: 12,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1516,6 +5583,10 @@ mod tests {
                         input_tokens: 20,
                         output_tokens: 15,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1530,6 +5601,10 @@ mod tests {
                         input_tokens: 30,
                         output_tokens: 10,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1556,6 +5631,7 @@ mod tests {
             content: "please use the echo tool".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -1636,6 +5712,7 @@ mod tests {
             content: "use echo twice".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -1697,6 +5774,516 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
 
+    /// Tool that sleeps for `delay_ms`, tracking how many calls overlap
+    /// and the order in which they finish.
+    struct SlowTool {
+        parallel: bool,
+        active: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+        finished: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl SlowTool {
+        fn new(parallel: bool) -> Arc<Self> {
+            Arc::new(Self {
+                parallel,
+                active: std::sync::atomic::AtomicUsize::new(0),
+                peak: std::sync::atomic::AtomicUsize::new(0),
+                finished: std::sync::Mutex::new(Vec::new()),
+            })
+        }
+
+        fn peak(&self) -> usize {
+            self.peak.load(std::sync::atomic::Ordering::SeqCst)
+        }
+
+        fn finished(&self) -> Vec<String> {
+            self.finished.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "slow"
+        }
+        fn description(&self) -> &str {
+            "Sleeps, then echoes its label"
+        }
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+        async fn execute(
+            &self,
+            args: serde_json::Value,
+        ) -> Result<serde_json::Value, crate::tools::registry::ToolError> {
+            use std::sync::atomic::Ordering::SeqCst;
+            let now = self.active.fetch_add(1, SeqCst) + 1;
+            self.peak.fetch_max(now, SeqCst);
+            let delay = args["delay_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            self.active.fetch_sub(1, SeqCst);
+
+            let label = args["label"].as_str().unwrap_or_default().to_string();
+            self.finished.lock().unwrap().push(label.clone());
+            Ok(serde_json::json!({"label": label}))
+        }
+        fn parallel_safe(&self) -> bool {
+            self.parallel
+        }
+    }
+
+    /// Transport whose first response issues three `slow` calls, the
+    /// earliest with the longest delay, and whose second returns text.
+    struct ThreeSlowCallsTransport {
+        call_count: std::sync::atomic::AtomicUsize,
+        recorded_requests: std::sync::Mutex<Vec<Vec<LlmMessage>>>,
+    }
+
+    impl ThreeSlowCallsTransport {
+        fn new() -> Self {
+            Self {
+                call_count: std::sync::atomic::AtomicUsize::new(0),
+                recorded_requests: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn snapshots(&self) -> Vec<Vec<LlmMessage>> {
+            self.recorded_requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LlmTransport for ThreeSlowCallsTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            self.recorded_requests
+                .lock()
+                .unwrap()
+                .push(request.messages.clone());
+
+            let count = self
+                .call_count
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let (content, stop_reason) = if count == 0 {
+                let call = |id: &str, label: &str, delay_ms: u64| ContentBlock::ToolUse {
+                    id: id.into(),
+                    name: "slow".into(),
+                    input: serde_json::json!({"label": label, "delay_ms": delay_ms}),
+                };
+                (
+                    vec![
+                        call("call-a", "a", 150),
+                        call("call-b", "b", 75),
+                        call("call-c", "c", 0),
+                    ],
+                    StopReason::ToolUse,
+                )
+            } else {
+                (
+                    vec![ContentBlock::Text {
+                        text: "done".into(),
+                    }],
+                    StopReason::EndTurn,
+                )
+            };
+            Ok(LlmResponse {
+                id: format!("slow-resp-{count}"),
+                content,
+                stop_reason,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    /// Run one turn against [`ThreeSlowCallsTransport`] and return the
+    /// `(tool_call_id, content)` of the tool results sent back to the LLM.
+    async fn run_three_slow_calls(
+        tool: Arc<SlowTool>,
+        config: AgentsConfig,
+        prefix: &str,
+    ) -> Vec<(String, String)> {
+        let transport = Arc::new(ThreeSlowCallsTransport::new());
+        let mut tools = ToolRegistry::new();
+        tools.register(tool);
+        let (agent, dir) =
+            make_agent_loop_with_tools(transport.clone(), prefix, tools, config).await;
+
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "run three slow calls".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: Some(4096),
+            temperature: Some(0.5),
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        let settings = agent.live_config().snapshot();
+        let result = agent
+            .run_tool_loop(
+                &settings,
+                request,
+                "test:slow",
+                "default",
+                None,
+                &CancellationToken::new(),
+                agent.max_tool_iterations(&settings),
+                &mut TurnBudget::unlimited(),
+                &buffering_sink(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.text, "done");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        let snapshots = transport.snapshots();
+        assert_eq!(snapshots.len(), 2, "all three calls run in one iteration");
+        let second_call = &snapshots[1];
+        let assistant = second_call
+            .iter()
+            .find(|m| m.role == "assistant" && m.tool_calls.is_some())
+            .expect("assistant tool_calls message");
+        let issued: Vec<&str> = assistant
+            .tool_calls
+            .as_ref()
+            .unwrap()
+            .iter()
+            .filter_map(|tc| tc["id"].as_str())
+            .collect();
+        assert_eq!(issued, ["call-a", "call-b", "call-c"]);
+
+        second_call
+            .iter()
+            .filter(|m| m.role == "tool")
+            .map(|m| (m.tool_call_id.clone().unwrap(), m.content.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn parallel_tool_calls_run_concurrently_in_issue_order() {
+        let tool = SlowTool::new(true);
+        let results = run_three_slow_calls(tool.clone(), test_config(), "parallel_tools").await;
+
+        assert_eq!(tool.peak(), 3, "all three calls should overlap");
+        assert_eq!(tool.finished(), ["c", "b", "a"]);
+
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["call-a", "call-b", "call-c"]);
+        for ((_, content), label) in results.iter().zip(["a", "b", "c"]) {
+            assert!(
+                content.contains(&format!("\"label\":\"{label}\"")),
+                "{content}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_tool_calls_respect_concurrency_limit() {
+        let mut config = test_config();
+        config.defaults.max_parallel_tools = 2;
+        let tool = SlowTool::new(true);
+        let results = run_three_slow_calls(tool.clone(), config, "parallel_limit").await;
+
+        assert_eq!(tool.peak(), 2);
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["call-a", "call-b", "call-c"]);
+    }
+
+    #[tokio::test]
+    async fn non_parallel_safe_tool_runs_sequentially() {
+        let tool = SlowTool::new(false);
+        let results = run_three_slow_calls(tool.clone(), test_config(), "serial_tools").await;
+
+        assert_eq!(tool.peak(), 1);
+        assert_eq!(tool.finished(), ["a", "b", "c"]);
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["call-a", "call-b", "call-c"]);
+    }
+
+    /// `echo` that asks the session's user first and records the answer.
+    #[derive(Default)]
+    struct AskingTool {
+        questions: std::sync::OnceLock<Arc<PendingQuestions>>,
+        answer: std::sync::Mutex<Option<String>>,
+    }
+
+    #[async_trait]
+    impl Tool for AskingTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Asks before echoing"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+            let key = crate::runtime::session_key().unwrap_or_default();
+            let answer = self.questions.get().unwrap().ask(
+                &key,
+                "Go ahead?",
+                std::time::Duration::from_secs(5),
+            );
+            let answer = answer.await;
+            *self.answer.lock().unwrap() = answer.clone();
+            Ok(serde_json::json!({"output": answer}))
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch_answers_a_question_asked_mid_turn() {
+        let tool = Arc::new(AskingTool::default());
+        let mut tools = ToolRegistry::new();
+        tools.register(tool.clone());
+        let (agent, dir) = make_agent_loop_with_tools(
+            Arc::new(MockToolTransport::new()),
+            "question_mid_turn",
+            tools,
+            test_config(),
+        )
+        .await;
+        let questions = Arc::new(PendingQuestions::new(agent.bus.clone()));
+        let _ = tool.questions.set(questions.clone());
+        let agent = Arc::new(agent.with_questions(questions.clone()));
+        let bus = agent.bus.clone();
+        let running = tokio::spawn({
+            let agent = agent.clone();
+            async move { agent.run().await }
+        });
+
+        bus.publish_inbound(make_inbound("cli", "local")).unwrap();
+        let question = bus.consume_outbound().await.unwrap();
+        assert_eq!(question.content, "Go ahead?");
+        assert_eq!(question.chat_id, "test-chat");
+        assert!(questions.is_waiting("cli:test-chat"));
+
+        // The turn that asked is still running, yet the answer gets through.
+        let mut answer = make_inbound("cli", "local");
+        answer.content = "yes".into();
+        bus.publish_inbound(answer).unwrap();
+        let reply = tokio::time::timeout(std::time::Duration::from_secs(5), bus.consume_outbound())
+            .await
+            .expect("turn should finish once answered")
+            .unwrap();
+        assert_eq!(reply.content, "tool result processed");
+        assert_eq!(tool.answer.lock().unwrap().as_deref(), Some("yes"));
+
+        running.abort();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Transport that says something and then calls `slow` with a delay
+    /// far longer than any test should wait.
+    struct StuckToolTransport;
+
+    #[async_trait]
+    impl LlmTransport for StuckToolTransport {
+        async fn complete(&self, _request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "stuck".into(),
+                content: vec![
+                    ContentBlock::Text {
+                        text: "Let me look.".into(),
+                    },
+                    ContentBlock::ToolUse {
+                        id: "call-stuck".into(),
+                        name: "slow".into(),
+                        input: serde_json::json!({"label": "stuck", "delay_ms": 60_000}),
+                    },
+                ],
+                stop_reason: StopReason::ToolUse,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_command_cancels_the_running_turn() {
+        let tool = SlowTool::new(true);
+        let mut tools = ToolRegistry::new();
+        tools.register(tool.clone());
+        let (agent, dir) = make_agent_loop_with_tools(
+            Arc::new(StuckToolTransport),
+            "cancel_turn",
+            tools,
+            test_config(),
+        )
+        .await;
+        let agent = Arc::new(agent);
+        let key = "cli:test-chat";
+
+        let turn = tokio::spawn({
+            let agent = agent.clone();
+            async move { agent.process_message(make_inbound("cli", "local")).await }
+        });
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while tool.active.load(std::sync::atomic::Ordering::SeqCst) == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("the slow tool should start");
+        assert!(agent.active_turns().is_running(key));
+
+        let mut stop = make_inbound("cli", "local");
+        stop.content = "stop".into();
+        agent.process_message(stop).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), turn)
+            .await
+            .expect("turn should end promptly")
+            .unwrap()
+            .unwrap();
+        assert!(!agent.active_turns().is_running(key));
+        assert!(tool.finished().is_empty(), "the tool call was abandoned");
+
+        // Only the cancelled turn replies; the stop command itself is silent.
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert!(outbound.is_cancelled());
+
+        let session = agent.sessions.load_session(key).await.unwrap();
+        let last = session.messages.last().unwrap();
+        assert_eq!(last["role"], "assistant");
+        assert_eq!(
+            last["content"],
+            format!("Let me look.\n\n{CANCELLED_TURN_MARKER}")
+        );
+
+        // With nothing running, a stop command says so.
+        let mut stop = make_inbound("cli", "local");
+        stop.content = "/cancel".into();
+        agent.process_message(stop).await.unwrap();
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert!(!outbound.is_cancelled());
+        assert!(outbound.content.contains("Nothing is running"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn tool_call_budget_ends_the_turn_unless_lifted() {
+        for lifted in [false, true] {
+            let mut config = test_config();
+            config.budget.max_tool_calls_per_turn = Some(2);
+            let tool = SlowTool::new(true);
+            let mut tools = ToolRegistry::new();
+            tools.register(tool.clone());
+            let (agent, dir) = make_agent_loop_with_tools(
+                Arc::new(ThreeSlowCallsTransport::new()),
+                &format!("budget_tools_{lifted}"),
+                tools,
+                config,
+            )
+            .await;
+
+            // `--no-budget` in the CLI sets this key.
+            let mut msg = make_inbound("cli", "local");
+            msg.metadata
+                .insert(InboundMessage::NO_BUDGET_KEY.into(), lifted.into());
+            agent.process_message(msg).await.unwrap();
+            let outbound = agent.bus.consume_outbound().await.unwrap();
+
+            if lifted {
+                assert_eq!(outbound.content, "done");
+                assert_eq!(tool.finished().len(), 3);
+            } else {
+                assert_eq!(
+                    outbound.content,
+                    BudgetExceeded::ToolCalls { limit: 2 }.to_string()
+                );
+                assert!(tool.finished().is_empty(), "no call in the batch ran");
+            }
+            let _ = tokio::fs::remove_dir_all(&dir).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn wall_time_budget_interrupts_a_stuck_tool() {
+        let mut config = test_config();
+        config.budget.max_turn_secs = Some(1);
+        let mut tools = ToolRegistry::new();
+        tools.register(SlowTool::new(true));
+        let (agent, dir) =
+            make_agent_loop_with_tools(Arc::new(StuckToolTransport), "budget_time", tools, config)
+                .await;
+
+        let started = std::time::Instant::now();
+        agent
+            .process_message(make_inbound("cli", "local"))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        let outbound = agent.bus.consume_outbound().await.unwrap();
+        assert_eq!(
+            outbound.content,
+            BudgetExceeded::WallTime { limit_secs: 1 }.to_string()
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver(std::sync::Mutex<Vec<TurnReport>>);
+
+    impl TurnObserver for RecordingObserver {
+        fn on_turn_end(&self, _msg: &InboundMessage, report: &TurnReport) {
+            self.0.lock().unwrap().push(report.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn turn_observer_sees_completions_and_budget_failures() {
+        let observer = Arc::new(RecordingObserver::default());
+        let (agent, dir) = make_agent_loop(Arc::new(MockTransport::new("hi")), "observer_ok").await;
+        let agent = agent.with_turn_observer(observer.clone());
+        agent
+            .process_message(make_inbound("cli", "local"))
+            .await
+            .unwrap();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        let mut config = test_config();
+        config.budget.max_tool_calls_per_turn = Some(2);
+        let mut tools = ToolRegistry::new();
+        tools.register(SlowTool::new(true));
+        let (agent, dir) = make_agent_loop_with_tools(
+            Arc::new(ThreeSlowCallsTransport::new()),
+            "observer_budget",
+            tools,
+            config,
+        )
+        .await;
+        let agent = agent.with_turn_observer(observer.clone());
+        agent
+            .process_message(make_inbound("cli", "local"))
+            .await
+            .unwrap();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        let reports = observer.0.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].outcome, TurnOutcome::Completed);
+        assert_eq!(reports[0].output, "hi");
+        assert!(reports[0].error.is_none());
+        assert!(reports[0].finished_at >= reports[0].started_at);
+        let limit = BudgetExceeded::ToolCalls { limit: 2 }.to_string();
+        assert_eq!(reports[1].outcome, TurnOutcome::Error);
+        assert_eq!(reports[1].error.as_deref(), Some(limit.as_str()));
+    }
+
     /// TEST-04: E2e test verifying a direct text response (no tool use)
     /// flows through the full pipeline correctly.
     #[tokio::test]
@@ -1711,6 +6298,7 @@ mod tests {
             content: "what is 2+2?".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -1786,6 +6374,10 @@ mod tests {
                         input_tokens: 10,
                         output_tokens: 5,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1801,6 +6393,10 @@ mod tests {
                         input_tokens: 20,
                         output_tokens: 12,
                         total_tokens: 0,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     },
                     metadata: HashMap::new(),
                 })
@@ -1821,6 +6417,7 @@ mod tests {
             content: "try a tool".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -1863,6 +6460,7 @@ mod tests {
             content: "test message".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         }
     }
@@ -2158,6 +6756,7 @@ mod tests {
             content: "run a swarm security review".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -2192,6 +6791,7 @@ mod tests {
             content: "hello world".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
@@ -2221,6 +6821,7 @@ mod tests {
             content: "run a swarm task".into(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         agent.bus.publish_inbound(inbound).unwrap();
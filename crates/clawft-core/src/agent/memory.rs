@@ -4,20 +4,79 @@
 //! - `MEMORY.md` -- long-term facts (append-only, periodically consolidated)
 //! - `HISTORY.md` -- session summaries (grep-searchable log)
 //!
+//! A third file, `MEMORY_META.md`, holds the creation and last-reference
+//! times of the long-term entries (see [`hygiene`]).
+//!
 //! File locations follow the discovery chain:
 //! `~/.clawft/workspace/memory/` with fallback to `~/.nanobot/workspace/memory/`.
 //!
 //! All I/O goes through the [`Platform`] filesystem trait so the module
 //! remains WASM-compatible and testable with mock filesystems.
-
-use std::path::PathBuf;
+//!
+//! # Backends
+//!
+//! The memory tools, the context builder and `weft memory` reach memory
+//! through the [`MemoryBackend`] trait, in two namespaces:
+//! [`LONG_TERM_NAMESPACE`] and [`HISTORY_NAMESPACE`]. Each paragraph is one
+//! entry. `agents.memory.backend` selects the implementation (see
+//! [`open_backend`]):
+//!
+//! - [`MarkdownMemoryBackend`] -- the two files above (default)
+//! - `SqliteMemoryBackend` -- `memory.db` with FTS5 full-text search
+//!   (feature `sqlite-memory`)
+//! - `VectorMemoryBackend` -- hash embeddings ranked by cosine similarity
+//!   (feature `vector-memory`)
+//!
+//! [`recall::recall`] ranks entries of either namespace by relevance to a
+//! query, whichever backend is in use. [`sections`] organizes long-term
+//! memory under markdown headings and writes it safely alongside
+//! concurrent writers.
+
+pub mod hygiene;
+pub mod markdown;
+pub mod recall;
+pub mod sections;
+#[cfg(feature = "sqlite-memory")]
+pub mod sqlite;
+#[cfg(feature = "vector-memory")]
+pub mod vector;
+
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 use clawft_platform::Platform;
+use clawft_types::config::{MemoryBackendKind, MemoryConfig};
 use clawft_types::{ClawftError, Result};
 
+pub use clawft_plugin::{MemoryBackend, PluginError};
+pub use markdown::MarkdownMemoryBackend;
+#[cfg(feature = "sqlite-memory")]
+pub use sqlite::SqliteMemoryBackend;
+#[cfg(feature = "vector-memory")]
+pub use vector::VectorMemoryBackend;
+
+/// Namespace of long-term memory (`MEMORY.md`), used when a backend call
+/// names none.
+pub const LONG_TERM_NAMESPACE: &str = "memory";
+
+/// Namespace of session summaries (`HISTORY.md`).
+pub const HISTORY_NAMESPACE: &str = "history";
+
+/// Namespace of the long-term entries' metadata (`MEMORY_META.md`), keyed
+/// like the entries themselves.
+pub const META_NAMESPACE: &str = "memory-meta";
+
+/// File name of the entry metadata inside the memory directory.
+pub const META_FILE_NAME: &str = "MEMORY_META.md";
+
+/// File name of the SQLite database inside the memory directory.
+pub const SQLITE_DB_NAME: &str = "memory.db";
+
+/// File name of the vector backend's entries inside the memory directory.
+pub const VECTOR_FILE_NAME: &str = "vectors.json";
+
 /// Long-term and session memory store.
 ///
 /// Wraps two markdown files (`MEMORY.md` and `HISTORY.md`) behind the
@@ -103,12 +162,13 @@ impl<P: Platform> MemoryStore<P> {
 
     /// Write (overwrite) long-term memory.
     ///
-    /// Creates parent directories if they do not exist.
+    /// Creates parent directories if they do not exist. The file is
+    /// replaced atomically, so a crash never leaves `MEMORY.md` truncated.
     pub async fn write_long_term(&self, content: &str) -> Result<()> {
         let clean = crate::security::sanitize_content(content);
         self.platform
             .fs()
-            .write_string(&self.memory_path, &clean)
+            .write_atomic(&self.memory_path, &clean)
             .await
             .map_err(ClawftError::Io)
     }
@@ -148,6 +208,35 @@ impl<P: Platform> MemoryStore<P> {
             .map_err(ClawftError::Io)
     }
 
+    /// Write (overwrite) the history file.
+    ///
+    /// Like [`write_long_term`](Self::write_long_term), the file is
+    /// replaced atomically.
+    pub async fn write_history(&self, content: &str) -> Result<()> {
+        let clean = crate::security::sanitize_content(content);
+        self.platform
+            .fs()
+            .write_atomic(&self.history_path, &clean)
+            .await
+            .map_err(ClawftError::Io)
+    }
+
+    /// Read the entry metadata file (`MEMORY_META.md`).
+    ///
+    /// Returns an empty string if the file does not exist yet.
+    pub async fn read_meta(&self) -> Result<String> {
+        self.read_file_or_empty(&self.meta_path()).await
+    }
+
+    /// Write (overwrite) the entry metadata file.
+    pub async fn write_meta(&self, content: &str) -> Result<()> {
+        self.platform
+            .fs()
+            .write_atomic(&self.meta_path(), content)
+            .await
+            .map_err(ClawftError::Io)
+    }
+
     /// Substring search across both memory files.
     ///
     /// Splits content by double-newline into paragraphs, then returns
@@ -193,6 +282,22 @@ impl<P: Platform> MemoryStore<P> {
         &self.history_path
     }
 
+    /// Path to the entry metadata file.
+    pub fn meta_path(&self) -> PathBuf {
+        self.memory_dir().join(META_FILE_NAME)
+    }
+
+    /// Directory holding the memory files, and the SQLite and vector
+    /// backends' data.
+    pub fn memory_dir(&self) -> &Path {
+        self.memory_path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// The platform the files are accessed through.
+    pub fn platform(&self) -> &Arc<P> {
+        &self.platform
+    }
+
     /// Read a file, returning empty string on "not found".
     async fn read_file_or_empty(&self, path: &std::path::Path) -> Result<String> {
         if !self.platform.fs().exists(path).await {
@@ -206,6 +311,143 @@ impl<P: Platform> MemoryStore<P> {
     }
 }
 
+/// Open the backend selected by `agents.memory.backend`.
+///
+/// The SQLite and vector backends keep their data next to the markdown
+/// files, as [`SQLITE_DB_NAME`] and [`VECTOR_FILE_NAME`]. Selecting one
+/// the crate was built without is an error.
+pub async fn open_backend<P: Platform + 'static>(
+    store: Arc<MemoryStore<P>>,
+    config: &MemoryConfig,
+) -> Result<Arc<dyn MemoryBackend>> {
+    match config.backend {
+        MemoryBackendKind::Markdown => Ok(Arc::new(MarkdownMemoryBackend::new(store))),
+        #[cfg(feature = "sqlite-memory")]
+        MemoryBackendKind::Sqlite => {
+            let path = store.memory_dir().join(SQLITE_DB_NAME);
+            Ok(Arc::new(SqliteMemoryBackend::open(path)?))
+        }
+        #[cfg(not(feature = "sqlite-memory"))]
+        MemoryBackendKind::Sqlite => Err(ClawftError::ConfigInvalid {
+            reason: "memory backend \"sqlite\" requires the sqlite-memory feature".into(),
+        }),
+        #[cfg(feature = "vector-memory")]
+        MemoryBackendKind::Vector => {
+            let path = store.memory_dir().join(VECTOR_FILE_NAME);
+            let backend = VectorMemoryBackend::open(store.platform().clone(), path).await?;
+            Ok(Arc::new(backend))
+        }
+        #[cfg(not(feature = "vector-memory"))]
+        MemoryBackendKind::Vector => Err(ClawftError::ConfigInvalid {
+            reason: "memory backend \"vector\" requires the vector-memory feature".into(),
+        }),
+    }
+}
+
+/// Key of an entry stored without an explicit one: a hash of its text, so
+/// writing the same paragraph twice keeps a single entry.
+pub fn entry_key(text: &str) -> String {
+    use std::hash::Hasher;
+
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(text.trim().as_bytes());
+    format!("m{:016x}", hasher.finish())
+}
+
+/// The non-empty paragraphs of `content`, trimmed.
+pub fn paragraphs(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+}
+
+/// The entries of `namespace` joined into one markdown text, the way
+/// `MEMORY.md` reads.
+pub async fn read_entries(backend: &dyn MemoryBackend, namespace: &str) -> Result<String> {
+    let entries = backend.list(Some(namespace)).await.map_err(backend_error)?;
+    let values: Vec<String> = entries.into_iter().map(|(_, value)| value).collect();
+    Ok(values.join("\n\n"))
+}
+
+/// Store each paragraph of `content` as an entry of `namespace`, keyed by
+/// [`entry_key`]. Returns how many paragraphs were stored.
+pub async fn append_entries(
+    backend: &dyn MemoryBackend,
+    namespace: &str,
+    content: &str,
+) -> Result<usize> {
+    let mut stored = 0;
+    for paragraph in paragraphs(content) {
+        backend
+            .store(
+                &entry_key(paragraph),
+                paragraph,
+                Some(namespace),
+                None,
+                None,
+            )
+            .await
+            .map_err(backend_error)?;
+        stored += 1;
+    }
+    Ok(stored)
+}
+
+/// Replace every entry of `namespace` with the paragraphs of `content`.
+pub async fn replace_entries(
+    backend: &dyn MemoryBackend,
+    namespace: &str,
+    content: &str,
+) -> Result<usize> {
+    let existing = backend.list(Some(namespace)).await.map_err(backend_error)?;
+    for (key, _) in existing {
+        backend
+            .delete(&key, Some(namespace))
+            .await
+            .map_err(backend_error)?;
+    }
+    append_entries(backend, namespace, content).await
+}
+
+/// Copy long-term memory, history and the entry metadata from one backend
+/// to another.
+///
+/// Entries keep their keys, so running it again overwrites rather than
+/// duplicates. Returns the number of memory and history entries copied.
+pub async fn migrate(from: &dyn MemoryBackend, to: &dyn MemoryBackend) -> Result<usize> {
+    let mut copied = 0;
+    for namespace in [LONG_TERM_NAMESPACE, HISTORY_NAMESPACE, META_NAMESPACE] {
+        let entries = from.list(Some(namespace)).await.map_err(backend_error)?;
+        for (key, value) in entries {
+            to.store(&key, &value, Some(namespace), None, None)
+                .await
+                .map_err(backend_error)?;
+            if namespace != META_NAMESPACE {
+                copied += 1;
+            }
+        }
+    }
+    info!(copied, "migrated memory");
+    Ok(copied)
+}
+
+/// Convert a backend error for callers that use [`ClawftError`].
+pub fn backend_error(e: PluginError) -> ClawftError {
+    match e {
+        PluginError::Io(e) => ClawftError::Io(e),
+        other => ClawftError::Io(std::io::Error::other(other)),
+    }
+}
+
+/// Convert a [`ClawftError`] for the [`MemoryBackend`] trait.
+fn plugin_error(e: ClawftError) -> PluginError {
+    match e {
+        ClawftError::Io(e) => PluginError::Io(e),
+        other => PluginError::ExecutionFailed(other.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,4 +700,216 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&dir).await;
     }
+
+    // -- backends ----------------------------------------------------------
+
+    /// Behaviour every backend shares, checked against each of them.
+    async fn check_conformance(backend: &dyn MemoryBackend) {
+        assert!(backend.list(None).await.unwrap().is_empty());
+        assert_eq!(backend.retrieve("missing", None).await.unwrap(), None);
+
+        // Storing under an existing key replaces the value.
+        for (key, value) in [
+            ("sky", "The sky is blue."),
+            ("grass", "Grass is green."),
+            ("sky", "The sky is grey today."),
+        ] {
+            backend.store(key, value, None, None, None).await.unwrap();
+        }
+        assert_eq!(
+            backend.retrieve("sky", None).await.unwrap().as_deref(),
+            Some("The sky is grey today.")
+        );
+        let mut keys: Vec<String> = backend
+            .list(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["grass", "sky"]);
+
+        // Namespaces are separate; none means long-term memory.
+        backend
+            .store(
+                "sky",
+                "Session 1 talked about the ocean.",
+                Some(HISTORY_NAMESPACE),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            backend
+                .retrieve("sky", Some(LONG_TERM_NAMESPACE))
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("The sky is grey today.")
+        );
+        assert_eq!(
+            backend.list(Some(HISTORY_NAMESPACE)).await.unwrap().len(),
+            1
+        );
+
+        // Search ranks the matching entry first and honours the namespace.
+        let hits = backend.search("grass", None, Some(5)).await.unwrap();
+        assert_eq!(hits[0].0, "grass");
+        assert_eq!(hits[0].1, "Grass is green.");
+        let hits = backend.search("ocean", None, Some(5)).await.unwrap();
+        assert_eq!(hits[0].1, "Session 1 talked about the ocean.");
+        let hits = backend
+            .search("ocean", Some(LONG_TERM_NAMESPACE), Some(5))
+            .await
+            .unwrap();
+        assert!(hits.iter().all(|(_, v, _)| !v.contains("ocean")));
+        assert!(backend.search("the", None, Some(1)).await.unwrap().len() <= 1);
+
+        assert!(backend.delete("grass", None).await.unwrap());
+        assert!(!backend.delete("grass", None).await.unwrap());
+        assert_eq!(backend.retrieve("grass", None).await.unwrap(), None);
+
+        // Paragraph helpers used by the tools and `weft memory`.
+        let ns = LONG_TERM_NAMESPACE;
+        assert_eq!(
+            replace_entries(backend, ns, "first fact\n\nsecond fact")
+                .await
+                .unwrap(),
+            2
+        );
+        append_entries(backend, ns, "second fact\n\nthird fact")
+            .await
+            .unwrap();
+        assert_eq!(
+            read_entries(backend, ns).await.unwrap(),
+            "first fact\n\nsecond fact\n\nthird fact"
+        );
+
+        // Compare-and-replace writes only over the listing it expects.
+        let listed = backend.list(Some(ns)).await.unwrap();
+        let mut reordered = listed.clone();
+        reordered.swap(0, 2);
+        reordered.insert(1, (entry_key("new fact"), "new fact".into()));
+        assert!(
+            backend
+                .replace_if_unchanged(Some(ns), &listed, &reordered)
+                .await
+                .unwrap()
+        );
+        assert_eq!(backend.list(Some(ns)).await.unwrap(), reordered);
+        assert!(
+            !backend
+                .replace_if_unchanged(Some(ns), &listed, &[])
+                .await
+                .unwrap()
+        );
+        assert_eq!(backend.list(Some(ns)).await.unwrap(), reordered);
+    }
+
+    #[tokio::test]
+    async fn markdown_backend_conforms() {
+        let dir = temp_dir("conform_md");
+        let backend = MarkdownMemoryBackend::new(Arc::new(test_store(&dir)));
+        check_conformance(&backend).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(feature = "sqlite-memory")]
+    #[tokio::test]
+    async fn sqlite_backend_conforms() {
+        let dir = temp_dir("conform_sqlite");
+        let backend = SqliteMemoryBackend::open(dir.join(SQLITE_DB_NAME)).unwrap();
+        check_conformance(&backend).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(feature = "vector-memory")]
+    #[tokio::test]
+    async fn vector_backend_conforms() {
+        let dir = temp_dir("conform_vector");
+        let platform = Arc::new(NativePlatform::new());
+        let path = dir.join(VECTOR_FILE_NAME);
+        let backend = VectorMemoryBackend::open(platform.clone(), &path)
+            .await
+            .unwrap();
+        check_conformance(&backend).await;
+
+        // Entries survive reopening.
+        let reopened = VectorMemoryBackend::open(platform, &path).await.unwrap();
+        assert_eq!(
+            read_entries(&reopened, LONG_TERM_NAMESPACE).await.unwrap(),
+            "third fact\n\nnew fact\n\nsecond fact\n\nfirst fact"
+        );
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn markdown_backend_keeps_plain_paragraphs_plain() {
+        let dir = temp_dir("md_plain");
+        let store = Arc::new(test_store(&dir));
+        store
+            .write_long_term("# Notes\n\nThe sky is blue.\n")
+            .await
+            .unwrap();
+        let backend = MarkdownMemoryBackend::new(store.clone());
+
+        append_entries(&backend, LONG_TERM_NAMESPACE, "Grass is green.")
+            .await
+            .unwrap();
+        backend
+            .store("tz", "UTC+2", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.read_long_term().await.unwrap(),
+            "# Notes\n\nThe sky is blue.\n\nGrass is green.\n\n<!-- key: tz -->\nUTC+2\n\n"
+        );
+        assert_eq!(
+            read_entries(&backend, LONG_TERM_NAMESPACE).await.unwrap(),
+            "# Notes\n\nThe sky is blue.\n\nGrass is green.\n\nUTC+2"
+        );
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(feature = "sqlite-memory")]
+    #[tokio::test]
+    async fn migrate_copies_markdown_into_sqlite() {
+        let dir = temp_dir("migrate");
+        let store = Arc::new(test_store(&dir));
+        store
+            .write_long_term("Deploys run on Fridays.\n\nThe API key rotates monthly.")
+            .await
+            .unwrap();
+        store
+            .append_history("Discussed the deploy schedule.")
+            .await
+            .unwrap();
+        let markdown = MarkdownMemoryBackend::new(store);
+        let sqlite = SqliteMemoryBackend::open(dir.join(SQLITE_DB_NAME)).unwrap();
+
+        assert_eq!(migrate(&markdown, &sqlite).await.unwrap(), 3);
+        // Running it again overwrites instead of duplicating.
+        assert_eq!(migrate(&markdown, &sqlite).await.unwrap(), 3);
+        assert_eq!(
+            read_entries(&sqlite, LONG_TERM_NAMESPACE).await.unwrap(),
+            "Deploys run on Fridays.\n\nThe API key rotates monthly."
+        );
+        let hits = sqlite.search("deploy", None, None).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(not(feature = "sqlite-memory"))]
+    #[tokio::test]
+    async fn sqlite_backend_needs_feature() {
+        let dir = temp_dir("needs_feature");
+        let config = MemoryConfig {
+            backend: MemoryBackendKind::Sqlite,
+            ..Default::default()
+        };
+        let result = open_backend(Arc::new(test_store(&dir)), &config).await;
+        assert!(matches!(result, Err(ClawftError::ConfigInvalid { .. })));
+    }
 }
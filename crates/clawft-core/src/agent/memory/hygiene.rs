@@ -0,0 +1,671 @@
+//! Memory hygiene: duplicate suppression and decay.
+//!
+//! The model tends to restate the same fact ("user prefers dark mode") in
+//! session after session, and every copy is sent with every request.
+//! [`remember`] compares each new long-term entry with the stored ones and
+//! folds near-duplicates into the entry already there instead of adding
+//! them. Every entry carries an [`EntryMeta`] -- when it was created, when
+//! it was last referenced and how many duplicates it absorbed -- stored in
+//! [`META_NAMESPACE`] under the entry's key. [`compact`] uses it to merge
+//! similar entries with the help of a model and to drop entries nobody has
+//! referenced for `agents.memory.decay_days`.
+//!
+//! [`similarity`] compares entries by their words, lowercased and without
+//! punctuation or common stop words. With the `vector-memory` feature the
+//! words are embedded with the local hash embedder and compared by cosine
+//! similarity; otherwise by the overlap of the two word sets. Entries with
+//! no words left are only similar to identical text.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use clawft_types::config::MemoryConfig;
+use clawft_types::provider::ContentBlock;
+use clawft_types::{ClawftError, Result};
+
+use super::sections::{self, Edit};
+use super::{
+    LONG_TERM_NAMESPACE, META_NAMESPACE, MemoryBackend, backend_error, entry_key, paragraphs,
+};
+use crate::pipeline::router::split_provider_model;
+use crate::pipeline::traits::{LlmMessage, LlmTransport, TransportRequest};
+
+/// Words that carry no meaning of their own when comparing entries.
+pub(super) const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "has", "have", "in", "is", "it", "of",
+    "on", "or", "that", "the", "this", "to", "was", "were", "with",
+];
+
+/// Instructions for the model that merges similar entries.
+const MERGE_PROMPT: &str = "You maintain an assistant's long-term memory. The numbered \
+entries below overlap. Rewrite them as one entry that keeps every distinct fact, preferring \
+the most specific wording. Reply with the merged entry only, in one paragraph.";
+
+/// Maximum length of a merged entry, in tokens.
+const MERGE_MAX_TOKENS: i32 = 512;
+
+/// Bookkeeping kept for each long-term entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryMeta {
+    /// When the entry was first written.
+    pub created_at: DateTime<Utc>,
+
+    /// When the entry was last written again or matched a memory search.
+    pub last_referenced: DateTime<Utc>,
+
+    /// Number of duplicate writes and merged entries folded into it.
+    #[serde(default)]
+    pub merged: u32,
+}
+
+impl EntryMeta {
+    /// Metadata of an entry written at `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            created_at: now,
+            last_referenced: now,
+            merged: 0,
+        }
+    }
+}
+
+/// Outcome of [`remember`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Remembered {
+    /// Number of entries stored.
+    pub added: usize,
+
+    /// Keys of the existing entries that new paragraphs duplicated.
+    pub duplicates: Vec<String>,
+}
+
+/// Similarity of two entries, from 0.0 (unrelated) to 1.0 (the same words).
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (words_a, words_b) = (words(a), words(b));
+    if words_a.is_empty() || words_b.is_empty() {
+        let same = a.trim().to_lowercase() == b.trim().to_lowercase();
+        return if same { 1.0 } else { 0.0 };
+    }
+    if words_a == words_b {
+        return 1.0;
+    }
+    word_similarity(&words_a, &words_b)
+}
+
+/// Cosine similarity of the hash embeddings of the two word sets.
+#[cfg(feature = "vector-memory")]
+fn word_similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    use crate::embeddings::hash_embedder::HashEmbedder;
+
+    let embedder = HashEmbedder::default_dimension();
+    let join = |words: &BTreeSet<String>| words.iter().cloned().collect::<Vec<_>>().join(" ");
+    let (x, y) = (
+        embedder.compute_embedding(&join(a)),
+        embedder.compute_embedding(&join(b)),
+    );
+    // Both embeddings are unit length, so the dot product is the cosine.
+    let cosine: f32 = x.iter().zip(&y).map(|(p, q)| p * q).sum();
+    f64::from(cosine).clamp(0.0, 1.0)
+}
+
+/// Share of the words of either set that both have (Jaccard index).
+#[cfg(not(feature = "vector-memory"))]
+fn word_similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// The distinct words of `text`, lowercased, without stop words.
+fn words(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Store each paragraph of `content` as a long-term entry, unless an entry
+/// at least `threshold` similar is already stored.
+///
+/// A duplicate is not stored; instead the existing entry is marked as
+/// referenced now and its `merged` count goes up. The write merges with
+/// concurrent ones (see [`sections::write`]).
+pub async fn remember(
+    backend: &dyn MemoryBackend,
+    content: &str,
+    threshold: f64,
+) -> Result<Remembered> {
+    let edit = Edit::Append {
+        section: None,
+        content,
+    };
+    sections::write(backend, &edit, threshold).await
+}
+
+/// Replace all of long-term memory with the paragraphs of `content`,
+/// suppressing duplicates among them as [`remember`] does.
+pub async fn replace(
+    backend: &dyn MemoryBackend,
+    content: &str,
+    threshold: f64,
+) -> Result<Remembered> {
+    sections::write(backend, &Edit::Overwrite { content }, threshold).await
+}
+
+/// Mark long-term entries as referenced now, so that decay spares them.
+pub async fn touch(backend: &dyn MemoryBackend, keys: &[String]) -> Result<()> {
+    let now = Utc::now();
+    for key in keys {
+        let mut meta = read_meta(backend, key)
+            .await?
+            .unwrap_or_else(|| EntryMeta::new(now));
+        meta.last_referenced = now;
+        write_meta(backend, key, &meta).await?;
+    }
+    Ok(())
+}
+
+/// Read the metadata of the long-term entry `key`.
+///
+/// `None` when it has none: it was written before metadata was kept, or
+/// the metadata is unreadable.
+pub async fn read_meta(backend: &dyn MemoryBackend, key: &str) -> Result<Option<EntryMeta>> {
+    let value = backend
+        .retrieve(key, Some(META_NAMESPACE))
+        .await
+        .map_err(backend_error)?;
+    Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+}
+
+pub(super) async fn write_meta(
+    backend: &dyn MemoryBackend,
+    key: &str,
+    meta: &EntryMeta,
+) -> Result<()> {
+    let value = serde_json::to_string(meta)?;
+    backend
+        .store(key, &value, Some(META_NAMESPACE), None, None)
+        .await
+        .map_err(backend_error)
+}
+
+/// Delete long-term entries together with their metadata.
+async fn forget(backend: &dyn MemoryBackend, keys: &[String]) -> Result<()> {
+    for key in keys {
+        backend
+            .delete(key, Some(LONG_TERM_NAMESPACE))
+            .await
+            .map_err(backend_error)?;
+    }
+    forget_meta(backend, keys).await
+}
+
+/// Delete the metadata of long-term entries.
+pub(super) async fn forget_meta(backend: &dyn MemoryBackend, keys: &[String]) -> Result<()> {
+    for key in keys {
+        backend
+            .delete(key, Some(META_NAMESPACE))
+            .await
+            .map_err(backend_error)?;
+    }
+    Ok(())
+}
+
+/// The metadata of every long-term entry that has any.
+async fn all_meta(backend: &dyn MemoryBackend) -> Result<HashMap<String, EntryMeta>> {
+    let entries = backend
+        .list(Some(META_NAMESPACE))
+        .await
+        .map_err(backend_error)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, serde_json::from_str(&value).ok()?)))
+        .collect())
+}
+
+/// Merges similar entries into one with a model.
+pub struct EntryMerger {
+    transport: Arc<dyn LlmTransport>,
+    model: String,
+}
+
+impl EntryMerger {
+    /// Merge with `model` (`provider/model`) through `transport`.
+    pub fn new(transport: Arc<dyn LlmTransport>, model: impl Into<String>) -> Self {
+        Self {
+            transport,
+            model: model.into(),
+        }
+    }
+
+    /// Ask the model for one entry saying what all of `entries` say.
+    pub async fn merge(&self, entries: &[&str]) -> Result<String> {
+        let list: Vec<String> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| format!("{}. {entry}", i + 1))
+            .collect();
+        let message = |role: &str, content: String| LlmMessage {
+            role: role.into(),
+            content,
+            tool_call_id: None,
+            tool_calls: None,
+            parts: None,
+            cache: false,
+        };
+        let (provider, model) = split_provider_model(&self.model);
+        let request = TransportRequest {
+            provider,
+            model,
+            messages: vec![
+                message("system", MERGE_PROMPT.into()),
+                message("user", list.join("\n")),
+            ],
+            tools: vec![],
+            max_tokens: Some(MERGE_MAX_TOKENS),
+            temperature: Some(0.0),
+            cache: true,
+        };
+
+        let response = self.transport.complete(&request).await?;
+        let text: String = response
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let merged = paragraphs(&text).collect::<Vec<_>>().join("\n");
+        if merged.is_empty() {
+            return Err(ClawftError::Provider {
+                message: "memory merge returned no text".into(),
+            });
+        }
+        Ok(merged)
+    }
+}
+
+/// Similar entries that [`compact`] merged, or would merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedGroup {
+    /// The entries merged.
+    pub sources: Vec<String>,
+
+    /// The entry replacing them; `None` on a dry run.
+    pub merged: Option<String>,
+}
+
+/// Outcome of [`compact`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactReport {
+    /// Groups of similar entries merged into one.
+    pub merged: Vec<MergedGroup>,
+
+    /// Entries dropped for not being referenced within the decay period.
+    pub dropped: Vec<String>,
+}
+
+/// Compact long-term memory.
+///
+/// First, when a `merger` is given, every group of entries at least
+/// `merge_threshold` similar to each other is replaced by one entry the
+/// model writes; the new entry inherits the earliest creation and the
+/// latest reference time of the group. A group the model fails to merge is
+/// left alone. Then entries not referenced within `decay_days` of `now` are
+/// dropped. Entries without metadata count as referenced now.
+///
+/// With `dry_run` nothing is changed and no model is called; the report
+/// lists what would happen.
+pub async fn compact(
+    backend: &dyn MemoryBackend,
+    config: &MemoryConfig,
+    merger: Option<&EntryMerger>,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<CompactReport> {
+    let mut report = CompactReport::default();
+
+    if let Some(merger) = merger {
+        let entries = backend
+            .list(Some(LONG_TERM_NAMESPACE))
+            .await
+            .map_err(backend_error)?;
+        let metas = all_meta(backend).await?;
+        for group in similar_groups(&entries, config.merge_threshold) {
+            let sources: Vec<&str> = group.iter().map(|&i| entries[i].1.as_str()).collect();
+            if dry_run {
+                report.merged.push(MergedGroup {
+                    sources: sources.iter().map(|s| s.to_string()).collect(),
+                    merged: None,
+                });
+                continue;
+            }
+            let merged = match merger.merge(&sources).await {
+                Ok(merged) => merged,
+                Err(e) => {
+                    warn!(error = %e, entries = group.len(), "failed to merge memory entries");
+                    continue;
+                }
+            };
+
+            let mut meta = EntryMeta::new(now);
+            meta.merged = group.len() as u32 - 1;
+            for key in group.iter().map(|&i| &entries[i].0) {
+                if let Some(old) = metas.get(key) {
+                    meta.created_at = meta.created_at.min(old.created_at);
+                    meta.merged += old.merged;
+                }
+            }
+            meta.last_referenced = group
+                .iter()
+                .filter_map(|&i| metas.get(&entries[i].0))
+                .map(|old| old.last_referenced)
+                .max()
+                .unwrap_or(now);
+
+            let key = entry_key(&merged);
+            let replaced: Vec<String> = group
+                .iter()
+                .map(|&i| entries[i].0.clone())
+                .filter(|old| *old != key)
+                .collect();
+            forget(backend, &replaced).await?;
+            backend
+                .store(&key, &merged, Some(LONG_TERM_NAMESPACE), None, None)
+                .await
+                .map_err(backend_error)?;
+            write_meta(backend, &key, &meta).await?;
+            report.merged.push(MergedGroup {
+                sources: sources.iter().map(|s| s.to_string()).collect(),
+                merged: Some(merged),
+            });
+        }
+    }
+
+    let entries = backend
+        .list(Some(LONG_TERM_NAMESPACE))
+        .await
+        .map_err(backend_error)?;
+    let metas = all_meta(backend).await?;
+    let cutoff = now - Duration::days(i64::from(config.decay_days));
+    let mut stale = Vec::new();
+    for (key, text) in &entries {
+        match metas.get(key) {
+            Some(meta) if meta.last_referenced < cutoff => {
+                stale.push(key.clone());
+                report.dropped.push(text.clone());
+            }
+            Some(_) => {}
+            None if !dry_run => write_meta(backend, key, &EntryMeta::new(now)).await?,
+            None => {}
+        }
+    }
+    if !dry_run {
+        forget(backend, &stale).await?;
+        // Metadata of entries deleted by other means.
+        for key in metas.keys() {
+            if !entries.iter().any(|(k, _)| k == key) {
+                backend
+                    .delete(key, Some(META_NAMESPACE))
+                    .await
+                    .map_err(backend_error)?;
+            }
+        }
+        info!(
+            merged = report.merged.len(),
+            dropped = report.dropped.len(),
+            "compacted long-term memory"
+        );
+    }
+    Ok(report)
+}
+
+/// Groups of two or more entries similar to the first entry of the group,
+/// each entry in at most one group.
+fn similar_groups(entries: &[(String, String)], threshold: f64) -> Vec<Vec<usize>> {
+    let mut grouped = vec![false; entries.len()];
+    let mut groups = Vec::new();
+    for i in 0..entries.len() {
+        if grouped[i] {
+            continue;
+        }
+        let mut group = vec![i];
+        for j in i + 1..entries.len() {
+            if !grouped[j] && similarity(&entries[i].1, &entries[j].1) >= threshold {
+                group.push(j);
+            }
+        }
+        if group.len() > 1 {
+            for &j in &group {
+                grouped[j] = true;
+            }
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::{MarkdownMemoryBackend, MemoryStore};
+    use clawft_platform::NativePlatform;
+    use clawft_types::provider::{LlmResponse, StopReason};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        std::env::temp_dir().join(format!("clawft_hygiene_{prefix}_{pid}_{id}"))
+    }
+
+    fn backend(dir: &std::path::Path) -> MarkdownMemoryBackend<NativePlatform> {
+        let store = MemoryStore::with_paths(
+            dir.join("MEMORY.md"),
+            dir.join("HISTORY.md"),
+            Arc::new(NativePlatform::new()),
+        );
+        MarkdownMemoryBackend::new(Arc::new(store))
+    }
+
+    async fn texts(backend: &dyn MemoryBackend) -> Vec<String> {
+        let entries = backend.list(Some(LONG_TERM_NAMESPACE)).await.unwrap();
+        entries.into_iter().map(|(_, text)| text).collect()
+    }
+
+    /// Transport that answers every merge request with the same text.
+    struct FixedMerge(&'static str);
+
+    #[async_trait::async_trait]
+    impl LlmTransport for FixedMerge {
+        async fn complete(&self, _request: &TransportRequest) -> Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "merge".into(),
+                content: vec![ContentBlock::Text {
+                    text: self.0.into(),
+                }],
+                stop_reason: StopReason::EndTurn,
+                usage: Default::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn similarity_ignores_case_punctuation_and_stop_words() {
+        assert_eq!(
+            similarity("User prefers dark mode.", "The user prefers DARK mode!"),
+            1.0
+        );
+        assert!(similarity("User prefers dark mode", "User prefers light mode") < 0.85);
+        assert!(similarity("Deploys run from ops/deploy.sh", "User prefers dark mode") < 0.3);
+        assert_eq!(similarity("---", "---"), 1.0);
+        assert_eq!(similarity("---", "***"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn remember_suppresses_duplicates() {
+        let dir = temp_dir("dedup");
+        let backend = backend(&dir);
+
+        let first = remember(&backend, "User prefers dark mode.", 0.85)
+            .await
+            .unwrap();
+        assert_eq!(first.added, 1);
+
+        let again = remember(
+            &backend,
+            "the user prefers dark mode\n\nUser prefers light mode in the morning",
+            0.85,
+        )
+        .await
+        .unwrap();
+        assert_eq!(again.added, 1);
+        assert_eq!(again.duplicates, [entry_key("User prefers dark mode.")]);
+        assert_eq!(
+            texts(&backend).await,
+            [
+                "User prefers dark mode.",
+                "User prefers light mode in the morning"
+            ]
+        );
+
+        // A threshold above 1.0 turns suppression off.
+        let off = remember(&backend, "User prefers dark mode!", 1.1)
+            .await
+            .unwrap();
+        assert_eq!(off.added, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_recorded_on_the_existing_entry() {
+        let dir = temp_dir("meta");
+        let backend = backend(&dir);
+        let key = entry_key("Build with cargo xtask dist");
+
+        remember(&backend, "Build with cargo xtask dist", 0.85)
+            .await
+            .unwrap();
+        let created = read_meta(&backend, &key).await.unwrap().unwrap();
+        assert_eq!(created.merged, 0);
+        assert_eq!(created.created_at, created.last_referenced);
+
+        remember(&backend, "build with `cargo xtask dist`", 0.85)
+            .await
+            .unwrap();
+        remember(&backend, "Build with cargo xtask dist.", 0.85)
+            .await
+            .unwrap();
+        let meta = read_meta(&backend, &key).await.unwrap().unwrap();
+        assert_eq!(meta.merged, 2);
+        assert_eq!(meta.created_at, created.created_at);
+        assert!(meta.last_referenced >= created.last_referenced);
+
+        // The metadata stays out of long-term memory.
+        assert_eq!(texts(&backend).await, ["Build with cargo xtask dist"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn replace_drops_old_entries_and_their_metadata() {
+        let dir = temp_dir("replace");
+        let backend = backend(&dir);
+        remember(&backend, "old fact", 0.85).await.unwrap();
+
+        let report = replace(&backend, "new fact\n\nNew fact.", 0.85)
+            .await
+            .unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(texts(&backend).await, ["new fact"]);
+        assert!(
+            read_meta(&backend, &entry_key("old fact"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn compact_merges_similar_entries_then_drops_stale_ones() {
+        let dir = temp_dir("compact");
+        let backend = backend(&dir);
+        remember(
+            &backend,
+            "Staging deploys need VPN\n\nStaging deploys need the VPN and a ticket\n\nUses tabs",
+            1.1,
+        )
+        .await
+        .unwrap();
+        // "Uses tabs" was last referenced long ago; an entry written by
+        // hand has no metadata at all.
+        let tabs = entry_key("Uses tabs");
+        let mut meta = read_meta(&backend, &tabs).await.unwrap().unwrap();
+        meta.last_referenced = Utc::now() - Duration::days(90);
+        write_meta(&backend, &tabs, &meta).await.unwrap();
+        backend
+            .store(
+                &entry_key("Hand-written note"),
+                "Hand-written note",
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let config = MemoryConfig::default();
+        let merger = EntryMerger::new(
+            Arc::new(FixedMerge("Staging deploys need the VPN and a ticket.")),
+            "test/model",
+        );
+
+        let preview = compact(&backend, &config, Some(&merger), Utc::now(), true)
+            .await
+            .unwrap();
+        assert_eq!(preview.merged.len(), 1);
+        assert_eq!(preview.merged[0].merged, None);
+        assert_eq!(preview.dropped, ["Uses tabs"]);
+        assert_eq!(texts(&backend).await.len(), 4);
+
+        let report = compact(&backend, &config, Some(&merger), Utc::now(), false)
+            .await
+            .unwrap();
+        assert_eq!(report.merged[0].sources.len(), 2);
+        assert_eq!(report.dropped, ["Uses tabs"]);
+        assert_eq!(
+            texts(&backend).await,
+            [
+                "Hand-written note",
+                "Staging deploys need the VPN and a ticket."
+            ]
+        );
+
+        let merged = read_meta(
+            &backend,
+            &entry_key("Staging deploys need the VPN and a ticket."),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(merged.merged, 1);
+        assert!(merged.created_at <= merged.last_referenced);
+        assert!(
+            read_meta(&backend, &entry_key("Hand-written note"))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(read_meta(&backend, &tabs).await.unwrap().is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
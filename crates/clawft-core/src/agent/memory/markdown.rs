@@ -0,0 +1,274 @@
+//! Markdown memory backend: `MEMORY.md`, `HISTORY.md` and the entry
+//! metadata in `MEMORY_META.md`.
+//!
+//! Each paragraph of a file is one entry. Entries stored under their
+//! [`entry_key`] are written as plain paragraphs, so the files stay
+//! readable and hand-editable; an entry stored under any other key is
+//! preceded by a `<!-- key: ... -->` line. Blank lines inside a stored
+//! value are collapsed so that it stays one paragraph.
+//!
+//! TTLs and tags are not kept. Search is a case-insensitive substring
+//! match, in document order.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use clawft_platform::Platform;
+use clawft_plugin::{MemoryBackend, PluginError};
+
+use super::{
+    HISTORY_NAMESPACE, LONG_TERM_NAMESPACE, META_NAMESPACE, MemoryStore, entry_key, paragraphs,
+    plugin_error,
+};
+use crate::runtime::Mutex;
+
+/// Opening of the line that carries an explicit key.
+const KEY_PREFIX: &str = "<!-- key: ";
+
+/// Closing of the line that carries an explicit key.
+const KEY_SUFFIX: &str = " -->";
+
+/// Memory backend over the markdown files of a [`MemoryStore`].
+pub struct MarkdownMemoryBackend<P: Platform> {
+    store: Arc<MemoryStore<P>>,
+    /// Serializes read-modify-write updates of the files.
+    write_lock: Mutex<()>,
+}
+
+/// Which of the two files a namespace maps to.
+#[derive(Debug, Clone, Copy)]
+enum File {
+    LongTerm,
+    History,
+    Meta,
+}
+
+/// One paragraph of a memory file.
+struct Entry {
+    key: String,
+    text: String,
+}
+
+impl<P: Platform> MarkdownMemoryBackend<P> {
+    /// Create a backend over the files of `store`.
+    pub fn new(store: Arc<MemoryStore<P>>) -> Self {
+        Self {
+            store,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    async fn read(&self, file: File) -> Result<Vec<Entry>, PluginError> {
+        let content = match file {
+            File::LongTerm => self.store.read_long_term().await,
+            File::History => self.store.read_history().await,
+            File::Meta => self.store.read_meta().await,
+        }
+        .map_err(plugin_error)?;
+        Ok(parse(&content))
+    }
+
+    async fn write(&self, file: File, entries: &[Entry]) -> Result<(), PluginError> {
+        let content = render(entries);
+        match file {
+            File::LongTerm => self.store.write_long_term(&content).await,
+            File::History => self.store.write_history(&content).await,
+            File::Meta => self.store.write_meta(&content).await,
+        }
+        .map_err(plugin_error)
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl<P: Platform + 'static> MemoryBackend for MarkdownMemoryBackend<P> {
+    async fn store(
+        &self,
+        key: &str,
+        value: &str,
+        namespace: Option<&str>,
+        _ttl_seconds: Option<u64>,
+        _tags: Option<Vec<String>>,
+    ) -> Result<(), PluginError> {
+        let file = file_for(namespace)?;
+        if key.is_empty() || key.contains('\n') || key.contains(KEY_SUFFIX.trim()) {
+            return Err(PluginError::ExecutionFailed(format!(
+                "invalid memory key {key:?}"
+            )));
+        }
+        let text = paragraphs(value).collect::<Vec<_>>().join("\n");
+
+        let _guard = self.write_lock.lock().await;
+        let mut entries = self.read(file).await?;
+        match entries.iter_mut().find(|e| e.key == key) {
+            Some(entry) => entry.text = text,
+            None => entries.push(Entry {
+                key: key.to_string(),
+                text,
+            }),
+        }
+        self.write(file, &entries).await
+    }
+
+    async fn retrieve(
+        &self,
+        key: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<String>, PluginError> {
+        let entries = self.read(file_for(namespace)?).await?;
+        Ok(entries.into_iter().find(|e| e.key == key).map(|e| e.text))
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(String, String, f64)>, PluginError> {
+        let limit = limit.unwrap_or(usize::MAX);
+        if query.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let files = match namespace {
+            Some(_) => vec![file_for(namespace)?],
+            None => vec![File::LongTerm, File::History],
+        };
+
+        let query = query.to_lowercase();
+        let mut results = Vec::new();
+        for file in files {
+            for entry in self.read(file).await? {
+                if entry.text.to_lowercase().contains(&query) {
+                    results.push((entry.key, entry.text, 1.0));
+                    if results.len() >= limit {
+                        return Ok(results);
+                    }
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    async fn delete(&self, key: &str, namespace: Option<&str>) -> Result<bool, PluginError> {
+        let file = file_for(namespace)?;
+        let _guard = self.write_lock.lock().await;
+        let mut entries = self.read(file).await?;
+        let before = entries.len();
+        entries.retain(|e| e.key != key);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.write(file, &entries).await?;
+        Ok(true)
+    }
+
+    async fn list(&self, namespace: Option<&str>) -> Result<Vec<(String, String)>, PluginError> {
+        let entries = self.read(file_for(namespace)?).await?;
+        Ok(entries.into_iter().map(|e| (e.key, e.text)).collect())
+    }
+
+    /// Compares and rewrites the file under the write lock, replacing it
+    /// atomically.
+    async fn replace_if_unchanged(
+        &self,
+        namespace: Option<&str>,
+        expected: &[(String, String)],
+        entries: &[(String, String)],
+    ) -> Result<bool, PluginError> {
+        let file = file_for(namespace)?;
+        let _guard = self.write_lock.lock().await;
+        let current = self.read(file).await?;
+        let unchanged = current.len() == expected.len()
+            && current
+                .iter()
+                .zip(expected)
+                .all(|(entry, (key, text))| entry.key == *key && entry.text == *text);
+        if !unchanged {
+            return Ok(false);
+        }
+        let entries: Vec<Entry> = entries
+            .iter()
+            .map(|(key, value)| Entry {
+                key: key.clone(),
+                text: paragraphs(value).collect::<Vec<_>>().join("\n"),
+            })
+            .collect();
+        self.write(file, &entries).await?;
+        Ok(true)
+    }
+}
+
+/// The file holding `namespace`; only the built-in namespaces exist.
+fn file_for(namespace: Option<&str>) -> Result<File, PluginError> {
+    match namespace.unwrap_or(LONG_TERM_NAMESPACE) {
+        LONG_TERM_NAMESPACE => Ok(File::LongTerm),
+        HISTORY_NAMESPACE => Ok(File::History),
+        META_NAMESPACE => Ok(File::Meta),
+        other => Err(PluginError::NotImplemented(format!(
+            "markdown memory has no namespace \"{other}\""
+        ))),
+    }
+}
+
+fn parse(content: &str) -> Vec<Entry> {
+    paragraphs(content)
+        .map(|paragraph| {
+            let (first, rest) = paragraph.split_once('\n').unwrap_or((paragraph, ""));
+            if let Some(key) = first
+                .strip_prefix(KEY_PREFIX)
+                .and_then(|k| k.strip_suffix(KEY_SUFFIX))
+            {
+                return Entry {
+                    key: key.to_string(),
+                    text: rest.trim().to_string(),
+                };
+            }
+            Entry {
+                key: entry_key(paragraph),
+                text: paragraph.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn render(entries: &[Entry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        if entry.key != entry_key(&entry.text) {
+            out.push_str(KEY_PREFIX);
+            out.push_str(&entry.key);
+            out.push_str(KEY_SUFFIX);
+            out.push('\n');
+        }
+        out.push_str(&entry.text);
+        out.push_str("\n\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_paragraphs_are_keyed_by_their_text() {
+        let entries = parse("# Notes\n\nThe sky is blue.\n\n<!-- key: tz -->\nUTC+2\n\n");
+        let keys: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                entry_key("# Notes").as_str(),
+                &entry_key("The sky is blue."),
+                "tz"
+            ]
+        );
+        assert_eq!(entries[2].text, "UTC+2");
+
+        let rendered = render(&entries);
+        assert_eq!(
+            rendered,
+            "# Notes\n\nThe sky is blue.\n\n<!-- key: tz -->\nUTC+2\n\n"
+        );
+    }
+}
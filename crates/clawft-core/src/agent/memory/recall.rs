@@ -0,0 +1,399 @@
+//! Relevance-ranked recall of memory entries.
+//!
+//! [`recall`] answers a question like "what do I know about the user's
+//! travel preferences" with the few entries that match it best, from
+//! long-term memory, session history or both. Backends that rank their
+//! own search results ([`MemoryBackend::ranks_search`]) are asked
+//! directly: SQLite ranks by FTS5 BM25 and the vector backend by cosine
+//! similarity. For the others, such as the markdown files, every entry is
+//! listed and ranked here with BM25 ([`bm25_scores`]).
+//!
+//! Scores are only comparable within one call: their scale depends on the
+//! backend.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use clawft_types::Result;
+
+use super::hygiene::STOP_WORDS;
+use super::{HISTORY_NAMESPACE, LONG_TERM_NAMESPACE, MemoryBackend, backend_error};
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+
+/// BM25 document length normalization.
+const B: f64 = 0.75;
+
+/// Query words at least this long also match longer words they start
+/// ("travel" matches "travelling").
+const MIN_PREFIX_LEN: usize = 3;
+
+/// Where a recalled entry is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Long-term memory ([`LONG_TERM_NAMESPACE`]).
+    Memory,
+    /// Session summaries ([`HISTORY_NAMESPACE`]).
+    History,
+}
+
+impl Source {
+    /// The backend namespace holding this source.
+    pub fn namespace(self) -> &'static str {
+        match self {
+            Self::Memory => LONG_TERM_NAMESPACE,
+            Self::History => HISTORY_NAMESPACE,
+        }
+    }
+}
+
+/// Limits for [`recall`].
+#[derive(Debug, Clone)]
+pub struct RecallOptions {
+    /// Maximum number of entries returned.
+    pub limit: usize,
+    /// Maximum total size of the returned entries, in bytes. An entry
+    /// that alone exceeds it is cut short.
+    pub max_bytes: usize,
+    /// Sources searched.
+    pub sources: Vec<Source>,
+}
+
+impl Default for RecallOptions {
+    fn default() -> Self {
+        Self {
+            limit: 5,
+            max_bytes: 8 * 1024,
+            sources: vec![Source::Memory, Source::History],
+        }
+    }
+}
+
+/// One entry returned by [`recall`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Recalled {
+    /// Key of the entry in its namespace.
+    pub key: String,
+    /// Entry text.
+    pub content: String,
+    /// Relevance; higher is better.
+    pub score: f64,
+    /// Where the entry is kept.
+    pub source: Source,
+}
+
+/// Outcome of [`recall`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recall {
+    /// Matching entries, best first.
+    pub entries: Vec<Recalled>,
+    /// Whether entries were dropped or cut short to fit `max_bytes`.
+    pub truncated: bool,
+}
+
+/// The entries of `options.sources` most relevant to `query`.
+pub async fn recall(
+    backend: &dyn MemoryBackend,
+    query: &str,
+    options: &RecallOptions,
+) -> Result<Recall> {
+    if query.trim().is_empty() || options.limit == 0 {
+        return Ok(Recall::default());
+    }
+
+    let mut ranked = Vec::new();
+    if backend.ranks_search() {
+        for &source in &options.sources {
+            let results = backend
+                .search(query, Some(source.namespace()), Some(options.limit))
+                .await
+                .map_err(backend_error)?;
+            ranked.extend(results.into_iter().map(|(key, content, score)| Recalled {
+                key,
+                content,
+                score,
+                source,
+            }));
+        }
+    } else {
+        let mut entries = Vec::new();
+        for &source in &options.sources {
+            let listed = backend
+                .list(Some(source.namespace()))
+                .await
+                .map_err(backend_error)?;
+            entries.extend(listed.into_iter().map(|(key, text)| (source, key, text)));
+        }
+        let texts: Vec<&str> = entries.iter().map(|(_, _, text)| text.as_str()).collect();
+        let scores = bm25_scores(query, &texts);
+        ranked.extend(
+            entries
+                .into_iter()
+                .zip(scores)
+                .filter(|(_, score)| *score > 0.0)
+                .map(|((source, key, content), score)| Recalled {
+                    key,
+                    content,
+                    score,
+                    source,
+                }),
+        );
+    }
+    // Stable, so equal scores keep memory ahead of history.
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked.truncate(options.limit);
+
+    Ok(fit(ranked, options.max_bytes))
+}
+
+/// Keep the leading entries that fit in `max_bytes`, cutting the first
+/// one short if it alone is too long.
+fn fit(ranked: Vec<Recalled>, max_bytes: usize) -> Recall {
+    let mut entries = Vec::new();
+    let mut used = 0;
+    let mut truncated = false;
+    for mut entry in ranked {
+        if used + entry.content.len() > max_bytes {
+            if entries.is_empty() {
+                let mut end = max_bytes;
+                while !entry.content.is_char_boundary(end) {
+                    end -= 1;
+                }
+                entry.content.truncate(end);
+                entries.push(entry);
+            }
+            truncated = true;
+            break;
+        }
+        used += entry.content.len();
+        entries.push(entry);
+    }
+    Recall { entries, truncated }
+}
+
+/// Lowercased words of `text`, without stop words.
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+fn matches(term: &str, token: &str) -> bool {
+    token == term || (term.chars().count() >= MIN_PREFIX_LEN && token.starts_with(term))
+}
+
+/// Okapi BM25 score of each of `docs` for `query`, in order. Documents
+/// sharing no word with the query score 0.
+pub fn bm25_scores(query: &str, docs: &[&str]) -> Vec<f64> {
+    let mut terms = tokens(query);
+    terms.sort();
+    terms.dedup();
+    let docs: Vec<Vec<String>> = docs.iter().map(|d| tokens(d)).collect();
+    if terms.is_empty() || docs.is_empty() {
+        return vec![0.0; docs.len()];
+    }
+
+    let n = docs.len() as f64;
+    let avg_len = (docs.iter().map(Vec::len).sum::<usize>() as f64 / n).max(1.0);
+    // Per document, how often each term occurs.
+    let freqs: Vec<HashMap<&str, usize>> = docs
+        .iter()
+        .map(|doc| {
+            let mut freq = HashMap::new();
+            for term in &terms {
+                let count = doc.iter().filter(|token| matches(term, token)).count();
+                if count > 0 {
+                    freq.insert(term.as_str(), count);
+                }
+            }
+            freq
+        })
+        .collect();
+    let idf: HashMap<&str, f64> = terms
+        .iter()
+        .map(|term| {
+            let df = freqs
+                .iter()
+                .filter(|f| f.contains_key(term.as_str()))
+                .count() as f64;
+            (term.as_str(), (1.0 + (n - df + 0.5) / (df + 0.5)).ln())
+        })
+        .collect();
+
+    docs.iter()
+        .zip(&freqs)
+        .map(|(doc, freq)| {
+            let norm = K1 * (1.0 - B + B * doc.len() as f64 / avg_len);
+            freq.iter()
+                .map(|(term, &tf)| {
+                    let tf = tf as f64;
+                    idf[term] * tf * (K1 + 1.0) / (tf + norm)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::{MarkdownMemoryBackend, MemoryStore, append_entries};
+    use clawft_platform::NativePlatform;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        std::env::temp_dir().join(format!("clawft_recall_{prefix}_{pid}_{id}"))
+    }
+
+    const MEMORY: &str = "The user prefers window seats on long flights.\n\n\
+        The user's travel preferences: trains over planes within Europe, and never red-eye flights.\n\n\
+        The deploy script lives in ops/deploy.sh.\n\n\
+        The user likes dark mode in every editor.";
+
+    const HISTORY: &str = "Booked a train from Paris to Berlin for the user's trip.\n\n\
+        Fixed the flaky integration test in the auth module.";
+
+    async fn seed(backend: &dyn MemoryBackend) {
+        append_entries(backend, LONG_TERM_NAMESPACE, MEMORY)
+            .await
+            .unwrap();
+        append_entries(backend, HISTORY_NAMESPACE, HISTORY)
+            .await
+            .unwrap();
+    }
+
+    fn markdown(dir: &Path) -> MarkdownMemoryBackend<NativePlatform> {
+        let store = MemoryStore::with_paths(
+            dir.join("MEMORY.md"),
+            dir.join("HISTORY.md"),
+            Arc::new(NativePlatform::new()),
+        );
+        MarkdownMemoryBackend::new(Arc::new(store))
+    }
+
+    /// Checks shared by every backend: the travel entries come first, the
+    /// unrelated ones not at all, and history is searched too.
+    async fn assert_travel_recall(backend: &dyn MemoryBackend) {
+        let found = recall(
+            backend,
+            "travel preferences for flights",
+            &RecallOptions::default(),
+        )
+        .await
+        .unwrap();
+        let contents: Vec<&str> = found.entries.iter().map(|e| e.content.as_str()).collect();
+        assert!(
+            contents[0].contains("travel preferences"),
+            "best match first: {contents:?}"
+        );
+        assert!(!contents.iter().any(|c| c.contains("dark mode")));
+        assert!(found.entries.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let history = recall(
+            backend,
+            "train trip",
+            &RecallOptions {
+                sources: vec![Source::History],
+                ..RecallOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].source, Source::History);
+        assert!(history.entries[0].content.contains("Paris to Berlin"));
+    }
+
+    #[test]
+    fn bm25_prefers_rarer_and_repeated_terms() {
+        let docs = [
+            "deploy the service",
+            "deploy deploy rollback",
+            "unrelated note",
+            "the service is up",
+        ];
+        let scores = bm25_scores("deploy rollback", &docs);
+        assert!(scores[1] > scores[0]);
+        assert!(scores[0] > 0.0);
+        assert_eq!(scores[2], 0.0);
+        assert_eq!(scores[3], 0.0);
+        // Prefix matching: "travel" matches "travelling".
+        assert!(bm25_scores("travel", &["travelling light"])[0] > 0.0);
+        assert_eq!(bm25_scores("the", &["the"]), vec![0.0]);
+    }
+
+    #[test]
+    fn fit_caps_total_size() {
+        let entry = |content: &str, score| Recalled {
+            key: content.into(),
+            content: content.into(),
+            score,
+            source: Source::Memory,
+        };
+        let fitted = fit(vec![entry("aaaa", 2.0), entry("bbbb", 1.0)], 6);
+        assert_eq!(fitted.entries.len(), 1);
+        assert!(fitted.truncated);
+
+        let cut = fit(vec![entry("héllo world", 1.0)], 2);
+        assert_eq!(cut.entries[0].content, "h");
+        assert!(cut.truncated);
+    }
+
+    #[tokio::test]
+    async fn markdown_backend_is_ranked_with_bm25() {
+        let dir = temp_dir("markdown");
+        let backend = markdown(&dir);
+        assert!(!backend.ranks_search());
+        seed(&backend).await;
+        assert_travel_recall(&backend).await;
+
+        // Entries matching any query word are ranked, not only those
+        // containing the whole query as the backend's own search requires.
+        let found = recall(&backend, "window flights", &RecallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(found.entries.len(), 2);
+        assert!(found.entries[0].content.contains("window seats"));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[cfg(feature = "sqlite-memory")]
+    #[tokio::test]
+    async fn sqlite_backend_uses_fts_ranking() {
+        use crate::agent::memory::SqliteMemoryBackend;
+
+        let dir = temp_dir("sqlite");
+        let backend = SqliteMemoryBackend::open(dir.join("memory.db")).unwrap();
+        assert!(backend.ranks_search());
+        seed(&backend).await;
+        assert_travel_recall(&backend).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "vector-memory")]
+    #[tokio::test]
+    async fn vector_backend_uses_cosine_similarity() {
+        use crate::agent::memory::VectorMemoryBackend;
+
+        let dir = temp_dir("vector");
+        let backend =
+            VectorMemoryBackend::open(Arc::new(NativePlatform::new()), dir.join("vectors.json"))
+                .await
+                .unwrap();
+        assert!(backend.ranks_search());
+        seed(&backend).await;
+        assert_travel_recall(&backend).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
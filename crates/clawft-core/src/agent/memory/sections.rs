@@ -0,0 +1,441 @@
+//! Sections of long-term memory and writes that survive concurrent writers.
+//!
+//! An entry whose first line is a markdown heading (`## Preferences`)
+//! opens a section, which runs up to the next heading of the same or a
+//! higher level. [`write`] appends to memory or to one section, or
+//! replaces a section or all of memory; [`section`] reads a section back.
+//! Section names match headings case-insensitively, at any level; a
+//! missing section is created as a `##` heading at the end of memory.
+//!
+//! Writes are optimistic. The new entries are computed from a listing of
+//! long-term memory and committed with
+//! [`MemoryBackend::replace_if_unchanged`], which refuses if another writer
+//! -- a parallel tool call, or the gateway and the CLI at once -- changed
+//! memory in between. The edit is then applied again to the fresh listing,
+//! merging the two writes, up to [`MAX_ATTEMPTS`] times.
+//!
+//! Paragraphs are checked for duplicates the way
+//! [`remember`](super::hygiene::remember) does, against the entries that
+//! stay in memory; headings are only compared with identical text.
+
+use chrono::Utc;
+use tracing::debug;
+
+use clawft_types::{ClawftError, Result};
+
+use super::hygiene::{self, EntryMeta, Remembered, similarity};
+use super::{LONG_TERM_NAMESPACE, MemoryBackend, backend_error, entry_key, paragraphs};
+
+/// Times [`write`] tries to commit before giving up.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// A change to long-term memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit<'a> {
+    /// Add the paragraphs of `content` at the end of memory, or of
+    /// `section`.
+    Append {
+        /// Section to add to, if any.
+        section: Option<&'a str>,
+        /// The paragraphs to add.
+        content: &'a str,
+    },
+    /// Replace the body of `section`, keeping its heading, with the
+    /// paragraphs of `content`.
+    ReplaceSection {
+        /// The section to replace.
+        section: &'a str,
+        /// Its new body.
+        content: &'a str,
+    },
+    /// Replace all of memory with the paragraphs of `content`.
+    Overwrite {
+        /// The new memory.
+        content: &'a str,
+    },
+}
+
+/// Level and title of the heading on the first line of `text`.
+fn heading(text: &str) -> Option<(usize, &str)> {
+    let line = text.lines().next()?.trim_start();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// The index range of `name` in `entries`, heading included.
+fn span(entries: &[(String, String)], name: &str) -> Option<(usize, usize)> {
+    let name = name.trim().to_lowercase();
+    let (start, level) = entries.iter().enumerate().find_map(|(i, (_, text))| {
+        let (level, title) = heading(text)?;
+        (title.to_lowercase() == name).then_some((i, level))
+    })?;
+    let end = entries[start + 1..]
+        .iter()
+        .position(|(_, text)| heading(text).is_some_and(|(l, _)| l <= level))
+        .map_or(entries.len(), |offset| start + 1 + offset);
+    Some((start, end))
+}
+
+/// The entries of section `name`, its heading first; `None` when memory
+/// has no such section.
+pub fn section<'a>(entries: &'a [(String, String)], name: &str) -> Option<&'a [(String, String)]> {
+    span(entries, name).map(|(start, end)| &entries[start..end])
+}
+
+/// The titles of every section, in order.
+pub fn titles(entries: &[(String, String)]) -> Vec<String> {
+    entries
+        .iter()
+        .filter_map(|(_, text)| heading(text).map(|(_, title)| title.to_string()))
+        .collect()
+}
+
+/// What an edit did to the listing it was applied to.
+#[derive(Debug, Default)]
+struct Applied {
+    entries: Vec<(String, String)>,
+    added: Vec<String>,
+    duplicates: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl Applied {
+    /// The key of the entry `paragraph` duplicates, if any.
+    fn duplicate_of(&self, paragraph: &str, threshold: f64) -> Option<String> {
+        let key = entry_key(paragraph);
+        if self.entries.iter().any(|(k, _)| *k == key) {
+            return Some(key);
+        }
+        if heading(paragraph).is_some() {
+            return None;
+        }
+        self.entries
+            .iter()
+            .filter(|(_, text)| heading(text).is_none())
+            .map(|(key, text)| (key, similarity(paragraph, text)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .filter(|(_, score)| *score >= threshold)
+            .map(|(key, _)| key.clone())
+    }
+
+    /// Insert the paragraphs of `content` from `at`, skipping duplicates.
+    fn insert(&mut self, mut at: usize, content: &str, threshold: f64) {
+        for paragraph in paragraphs(content) {
+            if let Some(key) = self.duplicate_of(paragraph, threshold) {
+                debug!(key, "suppressed duplicate memory entry");
+                self.duplicates.push(key);
+                continue;
+            }
+            let key = entry_key(paragraph);
+            self.entries
+                .insert(at, (key.clone(), paragraph.to_string()));
+            self.added.push(key);
+            at += 1;
+        }
+    }
+
+    /// The end of section `name`, after creating it at the end of memory
+    /// if it is missing.
+    fn section_end(&mut self, name: &str) -> usize {
+        if let Some((_, end)) = span(&self.entries, name) {
+            return end;
+        }
+        let heading = format!("## {}", name.trim());
+        let key = entry_key(&heading);
+        self.entries.push((key.clone(), heading));
+        self.added.push(key);
+        self.entries.len()
+    }
+}
+
+/// Apply `edit` to the listing `current`.
+fn apply(edit: &Edit<'_>, current: &[(String, String)], threshold: f64) -> Applied {
+    let mut applied = Applied {
+        entries: current.to_vec(),
+        ..Applied::default()
+    };
+    match *edit {
+        Edit::Append {
+            section: None,
+            content,
+        } => {
+            let end = applied.entries.len();
+            applied.insert(end, content, threshold);
+        }
+        Edit::Append {
+            section: Some(name),
+            content,
+        } => {
+            let end = applied.section_end(name);
+            applied.insert(end, content, threshold);
+        }
+        Edit::ReplaceSection {
+            section: name,
+            content,
+        } => {
+            if let Some((start, end)) = span(&applied.entries, name) {
+                let body: Vec<_> = applied.entries.drain(start + 1..end).collect();
+                applied.removed.extend(body.into_iter().map(|(key, _)| key));
+                // A heading written together with its first paragraph
+                // keeps only the heading line.
+                let (key, text) = &applied.entries[start];
+                let line = text.lines().next().unwrap_or_default().trim().to_string();
+                if line != *text {
+                    applied.removed.push(key.clone());
+                    let key = entry_key(&line);
+                    applied.added.push(key.clone());
+                    applied.entries[start] = (key, line);
+                }
+            }
+            let end = applied.section_end(name);
+            applied.insert(end, content, threshold);
+        }
+        Edit::Overwrite { content } => {
+            applied.removed = std::mem::take(&mut applied.entries)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect();
+            applied.insert(0, content, threshold);
+        }
+    }
+    applied
+}
+
+/// Apply `edit` to long-term memory, merging with writes made meanwhile.
+///
+/// Each stored paragraph gets fresh metadata, duplicates are recorded on
+/// the entry they duplicate, and removed entries lose theirs.
+///
+/// # Errors
+///
+/// [`ClawftError::Retry`] when memory changed under every one of
+/// [`MAX_ATTEMPTS`] attempts; backend errors as they come.
+pub async fn write(
+    backend: &dyn MemoryBackend,
+    edit: &Edit<'_>,
+    threshold: f64,
+) -> Result<Remembered> {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let current = backend
+            .list(Some(LONG_TERM_NAMESPACE))
+            .await
+            .map_err(backend_error)?;
+        let applied = apply(edit, &current, threshold);
+        let committed = backend
+            .replace_if_unchanged(Some(LONG_TERM_NAMESPACE), &current, &applied.entries)
+            .await
+            .map_err(backend_error)?;
+        if !committed {
+            debug!(attempt, "memory changed during write, merging");
+            continue;
+        }
+
+        let now = Utc::now();
+        let gone: Vec<String> = applied
+            .removed
+            .into_iter()
+            .filter(|key| !applied.entries.iter().any(|(k, _)| k == key))
+            .collect();
+        hygiene::forget_meta(backend, &gone).await?;
+        for key in &applied.added {
+            // An entry written back unchanged keeps its history.
+            if !current.iter().any(|(k, _)| k == key) {
+                hygiene::write_meta(backend, key, &EntryMeta::new(now)).await?;
+            }
+        }
+        for key in &applied.duplicates {
+            let mut meta = hygiene::read_meta(backend, key)
+                .await?
+                .unwrap_or_else(|| EntryMeta::new(now));
+            meta.last_referenced = now;
+            meta.merged += 1;
+            hygiene::write_meta(backend, key, &meta).await?;
+        }
+        return Ok(Remembered {
+            added: applied.added.len(),
+            duplicates: applied.duplicates,
+        });
+    }
+    Err(ClawftError::Retry {
+        source: "long-term memory kept changing during the write".into(),
+        attempts: MAX_ATTEMPTS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::memory::{MarkdownMemoryBackend, MemoryStore};
+    use clawft_platform::NativePlatform;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn backend() -> (
+        std::path::PathBuf,
+        Arc<MarkdownMemoryBackend<NativePlatform>>,
+    ) {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("clawft_sections_{}_{id}", std::process::id()));
+        let store = MemoryStore::with_paths(
+            dir.join("MEMORY.md"),
+            dir.join("HISTORY.md"),
+            Arc::new(NativePlatform::new()),
+        );
+        (dir, Arc::new(MarkdownMemoryBackend::new(Arc::new(store))))
+    }
+
+    fn listing(texts: &[&str]) -> Vec<(String, String)> {
+        texts
+            .iter()
+            .map(|text| (entry_key(text), text.to_string()))
+            .collect()
+    }
+
+    fn texts(entries: &[(String, String)]) -> Vec<&str> {
+        entries.iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    #[test]
+    fn sections_run_to_the_next_heading_of_their_level() {
+        let entries = listing(&[
+            "intro",
+            "## Preferences",
+            "dark mode",
+            "### Travel",
+            "aisle seats",
+            "## Projects",
+            "clawft",
+        ]);
+        assert_eq!(
+            texts(section(&entries, "preferences").unwrap()),
+            ["## Preferences", "dark mode", "### Travel", "aisle seats"]
+        );
+        assert_eq!(
+            texts(section(&entries, "Travel").unwrap()),
+            ["### Travel", "aisle seats"]
+        );
+        assert_eq!(
+            texts(section(&entries, "Projects").unwrap()),
+            ["## Projects", "clawft"]
+        );
+        assert!(section(&entries, "Missing").is_none());
+        assert_eq!(titles(&entries), ["Preferences", "Travel", "Projects"]);
+        // Not headings: no space after the hashes, or more than six.
+        assert!(heading("#hashtag").is_none());
+        assert!(heading("####### deep").is_none());
+    }
+
+    #[test]
+    fn edits_place_entries_in_their_section() {
+        let current = listing(&["## Preferences", "dark mode", "## Projects", "clawft"]);
+
+        let appended = apply(
+            &Edit::Append {
+                section: Some("preferences"),
+                content: "aisle seats\n\nDark mode.",
+            },
+            &current,
+            0.85,
+        );
+        assert_eq!(
+            texts(&appended.entries),
+            [
+                "## Preferences",
+                "dark mode",
+                "aisle seats",
+                "## Projects",
+                "clawft"
+            ]
+        );
+        assert_eq!(appended.duplicates, [entry_key("dark mode")]);
+
+        let created = apply(
+            &Edit::Append {
+                section: Some("Contacts"),
+                content: "Ana: ana@example.com",
+            },
+            &current,
+            0.85,
+        );
+        assert_eq!(
+            texts(&created.entries)[4..],
+            ["## Contacts", "Ana: ana@example.com"]
+        );
+        assert_eq!(created.added.len(), 2);
+
+        let replaced = apply(
+            &Edit::ReplaceSection {
+                section: "Preferences",
+                content: "light mode",
+            },
+            &current,
+            0.85,
+        );
+        assert_eq!(
+            texts(&replaced.entries),
+            ["## Preferences", "light mode", "## Projects", "clawft"]
+        );
+        assert_eq!(replaced.removed, [entry_key("dark mode")]);
+    }
+
+    #[test]
+    fn replacing_a_section_splits_a_heading_from_its_paragraph() {
+        let current = listing(&["## Preferences\ndark mode", "## Projects"]);
+        let replaced = apply(
+            &Edit::ReplaceSection {
+                section: "Preferences",
+                content: "light mode",
+            },
+            &current,
+            0.85,
+        );
+        assert_eq!(
+            texts(&replaced.entries),
+            ["## Preferences", "light mode", "## Projects"]
+        );
+        assert_eq!(replaced.removed, [entry_key("## Preferences\ndark mode")]);
+    }
+
+    #[tokio::test]
+    async fn interleaved_section_writes_lose_nothing() {
+        let (dir, backend) = backend();
+        let writes = (0..6).map(|i| {
+            let backend = backend.clone();
+            tokio::spawn(async move {
+                let section = ["Alpha", "Beta", "Gamma"][i % 3];
+                let content = format!("fact number {i} about {section}");
+                let edit = Edit::Append {
+                    section: Some(section),
+                    content: &content,
+                };
+                write(backend.as_ref(), &edit, 0.95).await.unwrap()
+            })
+        });
+        let mut added = 0;
+        for written in futures_util::future::join_all(writes).await {
+            let written = written.unwrap();
+            assert!(written.duplicates.is_empty());
+            added += written.added;
+        }
+        // Six facts, and each of the three headings exactly once.
+        assert_eq!(added, 9);
+
+        let entries = backend.list(None).await.unwrap();
+        assert_eq!(titles(&entries).len(), 3);
+        for (i, name) in ["Alpha", "Beta", "Gamma"].iter().enumerate() {
+            let body = &section(&entries, name).unwrap()[1..];
+            assert_eq!(body.len(), 2, "{name}: {:?}", texts(body));
+            for n in (i..6).step_by(3) {
+                let fact = format!("fact number {n} about {name}");
+                assert!(body.iter().any(|(_, text)| *text == fact), "lost {fact}");
+            }
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
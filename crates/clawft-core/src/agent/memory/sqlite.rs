@@ -0,0 +1,382 @@
+//! SQLite memory backend (feature `sqlite-memory`).
+//!
+//! [`SqliteMemoryBackend`] keeps every entry as a row of one database file,
+//! indexed by an FTS5 table so search ranks entries by BM25 instead of
+//! matching substrings. Each query word matches as a prefix, and an entry
+//! needs only one of the words to match; entries matching more words rank
+//! higher. Expired entries (see `ttl_seconds`) are skipped by every read
+//! and purged on the next write.
+//!
+//! Database calls run on the blocking thread pool.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+use tracing::debug;
+
+use clawft_plugin::{MemoryBackend, PluginError};
+use clawft_types::error::ClawftError;
+
+use super::LONG_TERM_NAMESPACE;
+
+/// How long a writer waits for another connection's write lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    namespace TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    tags TEXT NOT NULL DEFAULT '[]',
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER,
+    UNIQUE (namespace, key)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts
+    USING fts5(value, content = 'entries', content_rowid = 'id');
+CREATE TRIGGER IF NOT EXISTS entries_ai AFTER INSERT ON entries BEGIN
+    INSERT INTO entries_fts (rowid, value) VALUES (new.id, new.value);
+END;
+CREATE TRIGGER IF NOT EXISTS entries_ad AFTER DELETE ON entries BEGIN
+    INSERT INTO entries_fts (entries_fts, rowid, value) VALUES ('delete', old.id, old.value);
+END;
+CREATE TRIGGER IF NOT EXISTS entries_au AFTER UPDATE OF value ON entries BEGIN
+    INSERT INTO entries_fts (entries_fts, rowid, value) VALUES ('delete', old.id, old.value);
+    INSERT INTO entries_fts (rowid, value) VALUES (new.id, new.value);
+END;
+";
+
+/// Memory backend backed by a SQLite database with full-text search.
+pub struct SqliteMemoryBackend {
+    /// Path of the database file.
+    path: PathBuf,
+
+    /// The connection, shared with blocking tasks.
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteMemoryBackend {
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> clawft_types::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&path).map_err(storage_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(storage_error)?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(storage_error)?;
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        debug!(path = %path.display(), "opened sqlite memory backend");
+        Ok(Self {
+            path,
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, PluginError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| {
+                PluginError::Io(std::io::Error::other("memory database lock poisoned"))
+            })?;
+            f(&mut conn).map_err(|e| PluginError::Io(std::io::Error::other(e)))
+        })
+        .await
+        .map_err(|e| PluginError::Io(std::io::Error::other(e)))?
+    }
+}
+
+#[async_trait]
+impl MemoryBackend for SqliteMemoryBackend {
+    async fn store(
+        &self,
+        key: &str,
+        value: &str,
+        namespace: Option<&str>,
+        ttl_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+    ) -> Result<(), PluginError> {
+        let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE).to_string();
+        let key = key.to_string();
+        let value = crate::security::sanitize_content(value);
+        let tags = serde_json::to_string(&tags.unwrap_or_default())?;
+        let now = Utc::now().timestamp();
+        let expires_at = ttl_seconds
+            .and_then(|ttl| i64::try_from(ttl).ok())
+            .and_then(|ttl| now.checked_add(ttl));
+
+        self.with_conn(move |conn| {
+            conn.execute(
+                "DELETE FROM entries WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                params![now],
+            )?;
+            conn.execute(
+                "INSERT INTO entries (namespace, key, value, tags, created_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (namespace, key) DO UPDATE SET
+                     value = excluded.value,
+                     tags = excluded.tags,
+                     expires_at = excluded.expires_at",
+                params![namespace, key, value, tags, now, expires_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn retrieve(
+        &self,
+        key: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<String>, PluginError> {
+        let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE).to_string();
+        let key = key.to_string();
+        let now = Utc::now().timestamp();
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT value FROM entries
+                 WHERE namespace = ?1 AND key = ?2
+                   AND (expires_at IS NULL OR expires_at > ?3)",
+                params![namespace, key, now],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+        .await
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(String, String, f64)>, PluginError> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let namespace = namespace.map(str::to_string);
+        let limit = limit.map_or(-1, |l| l as i64);
+        let now = Utc::now().timestamp();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.key, e.value, bm25(entries_fts) AS rank
+                 FROM entries_fts JOIN entries e ON e.id = entries_fts.rowid
+                 WHERE entries_fts MATCH ?1
+                   AND (?2 IS NULL OR e.namespace = ?2)
+                   AND (e.expires_at IS NULL OR e.expires_at > ?3)
+                 ORDER BY rank
+                 LIMIT ?4",
+            )?;
+            // bm25() is lower for better matches; flip it so that higher
+            // scores rank first, as the trait promises.
+            stmt.query_map(params![fts_query, namespace, now, limit], |row| {
+                Ok((row.get(0)?, row.get(1)?, -row.get::<_, f64>(2)?))
+            })?
+            .collect()
+        })
+        .await
+    }
+
+    fn ranks_search(&self) -> bool {
+        true
+    }
+
+    async fn delete(&self, key: &str, namespace: Option<&str>) -> Result<bool, PluginError> {
+        let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE).to_string();
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            let deleted = conn.execute(
+                "DELETE FROM entries WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+            )?;
+            Ok(deleted > 0)
+        })
+        .await
+    }
+
+    async fn list(&self, namespace: Option<&str>) -> Result<Vec<(String, String)>, PluginError> {
+        let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE).to_string();
+        let now = Utc::now().timestamp();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT key, value FROM entries
+                 WHERE namespace = ?1 AND (expires_at IS NULL OR expires_at > ?2)
+                 ORDER BY id",
+            )?;
+            stmt.query_map(params![namespace, now], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect()
+        })
+        .await
+    }
+
+    /// Compares and rewrites in one immediate transaction, which also
+    /// holds off writers on other connections.
+    async fn replace_if_unchanged(
+        &self,
+        namespace: Option<&str>,
+        expected: &[(String, String)],
+        entries: &[(String, String)],
+    ) -> Result<bool, PluginError> {
+        let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE).to_string();
+        let expected = expected.to_vec();
+        let entries: Vec<(String, String)> = entries
+            .iter()
+            .map(|(key, value)| (key.clone(), crate::security::sanitize_content(value)))
+            .collect();
+        let now = Utc::now().timestamp();
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            tx.execute(
+                "DELETE FROM entries WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                params![now],
+            )?;
+            let current: Vec<(String, String)> = tx
+                .prepare(
+                    "SELECT key, value FROM entries
+                     WHERE namespace = ?1 AND (expires_at IS NULL OR expires_at > ?2)
+                     ORDER BY id",
+                )?
+                .query_map(params![namespace, now], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            if current != expected {
+                return Ok(false);
+            }
+            let common = current
+                .iter()
+                .zip(&entries)
+                .take_while(|(a, b)| a == b)
+                .count();
+            for (key, _) in &current[common..] {
+                tx.execute(
+                    "DELETE FROM entries WHERE namespace = ?1 AND key = ?2",
+                    params![namespace, key],
+                )?;
+            }
+            for (key, value) in &entries[common..] {
+                tx.execute(
+                    "INSERT INTO entries (namespace, key, value, created_at)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![namespace, key, value, now],
+                )?;
+            }
+            tx.commit()?;
+            Ok(true)
+        })
+        .await
+    }
+}
+
+/// Build an FTS5 query from free text: every word becomes a quoted prefix
+/// term, and any of them may match. `None` when the text has no words.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{w}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+fn storage_error(e: rusqlite::Error) -> ClawftError {
+    ClawftError::Io(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_db(prefix: &str) -> PathBuf {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        std::env::temp_dir()
+            .join(format!("clawft_sqlite_memory_{prefix}_{pid}_{id}"))
+            .join("memory.db")
+    }
+
+    fn cleanup(db: &Path) {
+        let _ = std::fs::remove_dir_all(db.parent().unwrap());
+    }
+
+    #[test]
+    fn fts_query_quotes_words() {
+        assert_eq!(
+            fts_query("auth \"flow\" (OR)").as_deref(),
+            Some("\"auth\"* OR \"flow\"* OR \"OR\"*")
+        );
+        assert_eq!(fts_query("  -- "), None);
+    }
+
+    #[tokio::test]
+    async fn search_ranks_entries_matching_more_words_first() {
+        let db = temp_db("rank");
+        let backend = SqliteMemoryBackend::open(&db).unwrap();
+        for (key, value) in [
+            ("a", "The deploy script lives in ops/deploy.sh"),
+            ("b", "Authentication uses OAuth with PKCE"),
+            ("c", "The authentication flow redirects to /callback"),
+        ] {
+            backend.store(key, value, None, None, None).await.unwrap();
+        }
+
+        let results = backend
+            .search("authentication flow", None, None)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = results.iter().map(|(k, _, _)| k.as_str()).collect();
+        assert_eq!(keys, ["c", "b"]);
+        assert!(results[0].2 > results[1].2);
+
+        // Words match as prefixes.
+        let results = backend.search("auth", None, None).await.unwrap();
+        assert_eq!(results.len(), 2);
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_hidden() {
+        let db = temp_db("ttl");
+        let backend = SqliteMemoryBackend::open(&db).unwrap();
+        backend
+            .store("gone", "short-lived note", None, Some(0), None)
+            .await
+            .unwrap();
+        backend
+            .store("kept", "long-lived note", None, Some(3600), None)
+            .await
+            .unwrap();
+
+        assert_eq!(backend.retrieve("gone", None).await.unwrap(), None);
+        let listed = backend.list(None).await.unwrap();
+        assert_eq!(
+            listed,
+            [("kept".to_string(), "long-lived note".to_string())]
+        );
+        let results = backend.search("note", None, None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        cleanup(&db);
+    }
+}
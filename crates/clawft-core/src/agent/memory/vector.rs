@@ -0,0 +1,222 @@
+//! Vector memory backend (feature `vector-memory`).
+//!
+//! [`VectorMemoryBackend`] embeds every entry with the local
+//! [`HashEmbedder`] and keeps the entries in a [`VectorStore`], so search
+//! ranks them by cosine similarity to the query instead of requiring the
+//! exact words. The whole store is written to a JSON file after every
+//! change and loaded when the backend opens.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use tracing::debug;
+
+use clawft_platform::Platform;
+use clawft_plugin::{MemoryBackend, PluginError};
+use clawft_types::{ClawftError, Result};
+
+use super::LONG_TERM_NAMESPACE;
+use crate::embeddings::hash_embedder::HashEmbedder;
+use crate::runtime::Mutex;
+use crate::vector_store::{VectorEntry, VectorStore};
+
+/// Results scoring at or below this similarity share no words with the
+/// query and are dropped.
+const MIN_SCORE: f32 = 0.1;
+
+/// Memory backend that ranks entries by embedding similarity.
+pub struct VectorMemoryBackend<P: Platform> {
+    platform: Arc<P>,
+    /// JSON file the entries are persisted to.
+    path: PathBuf,
+    embedder: HashEmbedder,
+    store: Mutex<VectorStore>,
+}
+
+impl<P: Platform> VectorMemoryBackend<P> {
+    /// Open the backend, loading the entries saved at `path` if it exists.
+    pub async fn open(platform: Arc<P>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut store = VectorStore::new();
+        if platform.fs().exists(&path).await {
+            let content = platform
+                .fs()
+                .read_to_string(&path)
+                .await
+                .map_err(ClawftError::Io)?;
+            let entries: Vec<VectorEntry> = serde_json::from_str(&content)?;
+            for entry in entries {
+                store.add_with_timestamp(
+                    entry.id,
+                    entry.text,
+                    entry.embedding,
+                    entry.tags,
+                    entry.metadata,
+                    entry.timestamp,
+                );
+            }
+        }
+        debug!(path = %path.display(), entries = store.len(), "opened vector memory backend");
+        Ok(Self {
+            platform,
+            path,
+            embedder: HashEmbedder::default_dimension(),
+            store: Mutex::new(store),
+        })
+    }
+
+    async fn save(&self, store: &VectorStore) -> std::result::Result<(), PluginError> {
+        let content = serde_json::to_string(store.entries())?;
+        self.platform
+            .fs()
+            .write_atomic(&self.path, &content)
+            .await
+            .map_err(PluginError::Io)
+    }
+
+    /// Embed `text` by its words alone; the embedder splits on whitespace,
+    /// so punctuation would otherwise make "blue." and "blue" unrelated.
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let words: String = text
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect();
+        self.embedder.compute_embedding(&words)
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl<P: Platform + 'static> MemoryBackend for VectorMemoryBackend<P> {
+    async fn store(
+        &self,
+        key: &str,
+        value: &str,
+        namespace: Option<&str>,
+        ttl_seconds: Option<u64>,
+        tags: Option<Vec<String>>,
+    ) -> std::result::Result<(), PluginError> {
+        let id = entry_id(namespace, key)?;
+        let value = crate::security::sanitize_content(value);
+        let mut metadata = HashMap::new();
+        if let Some(ttl) = ttl_seconds {
+            let expires_at = (Utc::now().timestamp() as u64).saturating_add(ttl);
+            metadata.insert("expires_at".to_string(), json!(expires_at));
+        }
+        let embedding = self.embed(&value);
+
+        let mut store = self.store.lock().await;
+        let now = Utc::now().timestamp() as u64;
+        let expired: Vec<String> = store
+            .entries()
+            .iter()
+            .filter(|e| !is_live(e, now))
+            .map(|e| e.id.clone())
+            .collect();
+        for expired_id in expired {
+            store.remove(&expired_id);
+        }
+        store.remove(&id);
+        store.add(id, value, embedding, tags.unwrap_or_default(), metadata);
+        self.save(&store).await
+    }
+
+    async fn retrieve(
+        &self,
+        key: &str,
+        namespace: Option<&str>,
+    ) -> std::result::Result<Option<String>, PluginError> {
+        let id = entry_id(namespace, key)?;
+        let store = self.store.lock().await;
+        Ok(live_entries(&store)
+            .find(|e| e.id == id)
+            .map(|e| e.text.clone()))
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        namespace: Option<&str>,
+        limit: Option<usize>,
+    ) -> std::result::Result<Vec<(String, String, f64)>, PluginError> {
+        let embedding = self.embed(query);
+        let store = self.store.lock().await;
+        let live: HashMap<&str, &VectorEntry> =
+            live_entries(&store).map(|e| (e.id.as_str(), e)).collect();
+
+        let results = store
+            .search(&embedding, store.len())
+            .into_iter()
+            .filter(|r| r.score > MIN_SCORE && live.contains_key(r.id.as_str()))
+            .filter_map(|r| {
+                let (ns, key) = r.id.split_once(':')?;
+                namespace
+                    .is_none_or(|wanted| wanted == ns)
+                    .then(|| (key.to_string(), r.text, f64::from(r.score)))
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(results)
+    }
+
+    fn ranks_search(&self) -> bool {
+        true
+    }
+
+    async fn delete(
+        &self,
+        key: &str,
+        namespace: Option<&str>,
+    ) -> std::result::Result<bool, PluginError> {
+        let id = entry_id(namespace, key)?;
+        let mut store = self.store.lock().await;
+        if !store.remove(&id) {
+            return Ok(false);
+        }
+        self.save(&store).await?;
+        Ok(true)
+    }
+
+    async fn list(
+        &self,
+        namespace: Option<&str>,
+    ) -> std::result::Result<Vec<(String, String)>, PluginError> {
+        let prefix = format!("{}:", namespace.unwrap_or(LONG_TERM_NAMESPACE));
+        let store = self.store.lock().await;
+        Ok(live_entries(&store)
+            .filter_map(|e| {
+                let key = e.id.strip_prefix(&prefix)?;
+                Some((key.to_string(), e.text.clone()))
+            })
+            .collect())
+    }
+}
+
+/// Store id of an entry: `namespace:key`. Namespaces may not contain `:`.
+fn entry_id(namespace: Option<&str>, key: &str) -> std::result::Result<String, PluginError> {
+    let namespace = namespace.unwrap_or(LONG_TERM_NAMESPACE);
+    if namespace.contains(':') {
+        return Err(PluginError::ExecutionFailed(format!(
+            "invalid memory namespace {namespace:?}"
+        )));
+    }
+    Ok(format!("{namespace}:{key}"))
+}
+
+/// Entries whose TTL has not run out.
+fn live_entries(store: &VectorStore) -> impl Iterator<Item = &VectorEntry> {
+    let now = Utc::now().timestamp() as u64;
+    store.entries().iter().filter(move |e| is_live(e, now))
+}
+
+fn is_live(entry: &VectorEntry, now: u64) -> bool {
+    entry
+        .metadata
+        .get("expires_at")
+        .and_then(|v| v.as_u64())
+        .is_none_or(|expires_at| expires_at > now)
+}
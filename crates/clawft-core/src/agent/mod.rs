@@ -1,14 +1,30 @@
-//! Agent subsystem: loop, context, memory, skills, agent definitions, sandbox.
+//! Agent subsystem: loop, budgets, hooks, context, memory, task list, skills and their activation, agent definitions, prompt templates, sub-agents, secrets, tool result post-processing, sandbox, turn traces, questions to the user mid-turn.
 
 pub mod agents;
+pub mod budget;
+pub mod compaction;
 pub mod context;
+pub mod context_budget;
+pub mod dispatch;
 pub mod helpers;
+pub mod hooks;
 pub mod loop_core;
 pub mod memory;
+#[cfg(feature = "native")]
+pub mod questions;
 pub mod sandbox;
+pub mod secrets;
+pub mod sink;
+pub mod skill_activation;
 #[cfg(feature = "native")]
 pub mod skill_watcher;
 pub mod skill_autogen;
 pub mod skills;
 pub mod skills_v2;
+pub mod subagent;
+pub mod tasks;
+pub mod templates;
+pub mod tool_results;
+pub mod trace;
+pub mod turns;
 pub mod verification;
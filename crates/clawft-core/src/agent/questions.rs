@@ -0,0 +1,163 @@
+//! Questions put to the user in the middle of a turn.
+//!
+//! Work running inside a turn sometimes needs the user's go-ahead, such as
+//! an MCP server asking to run a model call. [`PendingQuestions::ask`]
+//! sends the question to the conversation the turn belongs to and waits.
+//! The session's next message would normally queue behind the turn that
+//! asked, so dispatch offers every message to [`PendingQuestions::answer`]
+//! first, the same way stop commands skip the queue.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clawft_types::event::{InboundMessage, OutboundMessage};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+use crate::bus::MessageBus;
+
+/// Questions waiting for an answer, by session key.
+pub struct PendingQuestions {
+    bus: Arc<MessageBus>,
+    waiting: Mutex<HashMap<String, oneshot::Sender<String>>>,
+}
+
+impl PendingQuestions {
+    /// Create a registry that sends its questions through `bus`.
+    pub fn new(bus: Arc<MessageBus>) -> Self {
+        Self {
+            bus,
+            waiting: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send `question` to the conversation of session `session_key`
+    /// (`"<channel>:<chat_id>"`) and wait up to `timeout` for the next
+    /// message there.
+    ///
+    /// Returns `None` when the session has no conversation to ask in
+    /// (scheduled `cron` turns), the question cannot be sent, a later
+    /// question in the same session replaces it, or no answer comes in
+    /// time.
+    pub async fn ask(
+        &self,
+        session_key: &str,
+        question: &str,
+        timeout: Duration,
+    ) -> Option<String> {
+        let (channel, chat_id) = session_key
+            .split_once(':')
+            .filter(|(channel, _)| *channel != "cron")?;
+        let (tx, rx) = oneshot::channel();
+        self.lock().insert(session_key.to_string(), tx);
+
+        let outbound = OutboundMessage {
+            channel: channel.to_string(),
+            chat_id: chat_id.to_string(),
+            content: question.to_string(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        if let Err(e) = self.bus.dispatch_outbound(outbound) {
+            warn!(session = %session_key, error = %e, "failed to send question");
+            self.lock().remove(session_key);
+            return None;
+        }
+
+        let answer = crate::runtime::timeout(timeout, rx).await;
+        if answer.is_none() {
+            // Clear our entry, unless a later question replaced it.
+            let mut waiting = self.lock();
+            if waiting.get(session_key).is_some_and(|tx| tx.is_closed()) {
+                waiting.remove(session_key);
+            }
+        }
+        answer?.ok()
+    }
+
+    /// Give `msg` to the question waiting in its session, if any. Returns
+    /// whether the message was taken as an answer.
+    pub fn answer(&self, msg: &InboundMessage) -> bool {
+        let Some(tx) = self.lock().remove(&msg.session_key()) else {
+            return false;
+        };
+        tx.send(msg.content.clone()).is_ok()
+    }
+
+    /// Whether a question is waiting in session `session_key`.
+    pub fn is_waiting(&self, session_key: &str) -> bool {
+        self.lock().contains_key(session_key)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<String>>> {
+        self.waiting.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn message(channel: &str, chat_id: &str, content: &str) -> InboundMessage {
+        InboundMessage {
+            channel: channel.into(),
+            sender_id: "u".into(),
+            chat_id: chat_id.into(),
+            content: content.into(),
+            timestamp: Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn next_message_in_the_session_answers() {
+        let bus = Arc::new(MessageBus::new());
+        let questions = Arc::new(PendingQuestions::new(bus.clone()));
+
+        let asking = questions.clone();
+        let ask = tokio::spawn(async move {
+            asking
+                .ask("telegram:42", "Allow it?", Duration::from_secs(5))
+                .await
+        });
+
+        let sent = bus.consume_outbound().await.unwrap();
+        assert_eq!(sent.channel, "telegram");
+        assert_eq!(sent.chat_id, "42");
+        assert_eq!(sent.content, "Allow it?");
+        assert!(questions.is_waiting("telegram:42"));
+
+        assert!(!questions.answer(&message("telegram", "7", "yes")));
+        assert!(questions.answer(&message("telegram", "42", "yes")));
+        assert_eq!(ask.await.unwrap().as_deref(), Some("yes"));
+        assert!(!questions.is_waiting("telegram:42"));
+        assert!(!questions.answer(&message("telegram", "42", "again")));
+    }
+
+    #[tokio::test]
+    async fn unanswered_questions_time_out() {
+        let questions = PendingQuestions::new(Arc::new(MessageBus::new()));
+        let answer = questions
+            .ask("slack:C1", "Allow it?", Duration::from_millis(20))
+            .await;
+        assert_eq!(answer, None);
+        assert!(!questions.is_waiting("slack:C1"));
+    }
+
+    #[tokio::test]
+    async fn cron_turns_are_not_asked() {
+        let bus = Arc::new(MessageBus::new());
+        let questions = PendingQuestions::new(bus.clone());
+        let answer = questions
+            .ask("cron:job-1", "Allow it?", Duration::from_secs(5))
+            .await;
+        assert_eq!(answer, None);
+        assert_eq!(bus.stats().outbound_depth, 0);
+    }
+}
@@ -0,0 +1,527 @@
+//! Named secrets the agent can use without seeing them.
+//!
+//! Secrets are defined in `tools.secrets` ([`SecretRef`]). The model only
+//! ever learns their names and descriptions, from [`SECRET_LIST_TOOL`].
+//! To use one, it calls [`SECRET_USE_TOOL`] with another tool's name and
+//! arguments, writing `{{secret:NAME}}` where the value belongs. The
+//! [`AgentLoop`](super::loop_core::AgentLoop) fills the value in just
+//! before that tool runs, provided the secret grants it that tool
+//! ([`SecretRef::tools`]). `http_request` resolves references in its
+//! headers through the same store and grants.
+//!
+//! The loop also scrubs every tool result, whichever tool produced it:
+//! any secret value appearing in it is replaced with `[REDACTED]` before
+//! the model, hooks or the audit log see it ([`Secrets::scrub_result`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use clawft_types::config::SecretRef;
+use clawft_types::secret::SecretString;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::subagent::SPAWN_AGENT_TOOL;
+use crate::tools::registry::{Tool, ToolError, matches_any_pattern};
+
+/// Name of the tool that runs another tool with secrets filled in.
+pub const SECRET_USE_TOOL: &str = "secret_use";
+
+/// Name of the tool that lists the configured secrets.
+pub const SECRET_LIST_TOOL: &str = "secret_list";
+
+/// Opening of a secret reference in a tool argument.
+const SECRET_OPEN: &str = "{{secret:";
+
+/// What secret values are replaced with in tool results.
+const REDACTED: &str = "[REDACTED]";
+
+/// One configured secret.
+#[derive(Debug, Clone)]
+struct Secret {
+    description: String,
+    /// `None` when neither the config nor its env var has a value.
+    value: Option<SecretString>,
+    value_env: Option<String>,
+    tools: Vec<String>,
+}
+
+/// The configured secrets, resolved.
+#[derive(Debug, Clone, Default)]
+pub struct Secrets {
+    secrets: BTreeMap<String, Secret>,
+}
+
+impl Secrets {
+    /// Resolve `tools.secrets`, reading `value_env` variables with `lookup`.
+    ///
+    /// Secrets without a value are kept, so `secret_list` can report them
+    /// as unavailable.
+    pub fn from_config(
+        config: &HashMap<String, SecretRef>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let secrets = config
+            .iter()
+            .map(|(name, secret)| {
+                let value = secret.value.resolve(secret.value_env.as_deref(), &lookup);
+                let secret = Secret {
+                    description: secret.description.clone(),
+                    value,
+                    value_env: secret.value_env.clone(),
+                    tools: secret.tools.clone(),
+                };
+                (name.clone(), secret)
+            })
+            .collect();
+        Self { secrets }
+    }
+
+    /// Whether no secrets are configured.
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Names, descriptions and granted tools of the secrets, sorted by
+    /// name. Never the values.
+    pub fn list(&self) -> Value {
+        let secrets: Vec<Value> = self
+            .secrets
+            .iter()
+            .map(|(name, secret)| {
+                json!({
+                    "name": name,
+                    "description": secret.description,
+                    "tools": secret.tools,
+                    "available": secret.value.is_some(),
+                })
+            })
+            .collect();
+        json!({ "secrets": secrets })
+    }
+
+    /// Replace `{{secret:NAME}}` references in the string values of
+    /// `args`, which are about to be passed to `tool`. Returns how many
+    /// references were replaced.
+    ///
+    /// Fails when a secret is unknown, has no value, or does not grant
+    /// `tool`; `args` may then be partly filled in and must be dropped.
+    pub fn interpolate(&self, tool: &str, args: &mut Value) -> Result<usize, ToolError> {
+        match args {
+            Value::String(text) if text.contains(SECRET_OPEN) => {
+                let (filled, count) = self.interpolate_str(tool, text)?;
+                *text = filled;
+                Ok(count)
+            }
+            Value::Array(items) => items
+                .iter_mut()
+                .try_fold(0, |n, item| Ok(n + self.interpolate(tool, item)?)),
+            Value::Object(fields) => fields
+                .values_mut()
+                .try_fold(0, |n, field| Ok(n + self.interpolate(tool, field)?)),
+            _ => Ok(0),
+        }
+    }
+
+    fn interpolate_str(&self, tool: &str, value: &str) -> Result<(String, usize), ToolError> {
+        let mut out = String::with_capacity(value.len());
+        let mut count = 0;
+        let mut rest = value;
+        while let Some(start) = rest.find(SECRET_OPEN) {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + SECRET_OPEN.len()..];
+            let end = after.find("}}").ok_or_else(|| {
+                ToolError::InvalidArgs("unterminated {{secret:...}} reference".into())
+            })?;
+            let name = after[..end].trim();
+            out.push_str(self.value_for(tool, name)?.expose());
+            count += 1;
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        Ok((out, count))
+    }
+
+    fn value_for(&self, tool: &str, name: &str) -> Result<&SecretString, ToolError> {
+        let secret = self.secrets.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.secrets.keys().map(String::as_str).collect();
+            let known = if names.is_empty() {
+                "no secrets are configured in tools.secrets".to_string()
+            } else {
+                format!("configured secrets: {}", names.join(", "))
+            };
+            ToolError::InvalidArgs(format!("unknown secret '{name}' ({known})"))
+        })?;
+        if !matches_any_pattern(tool, &secret.tools) {
+            return Err(ToolError::PermissionDenied {
+                tool: SECRET_USE_TOOL.into(),
+                reason: format!("secret '{name}' is not granted to tool '{tool}'"),
+            });
+        }
+        secret.value.as_ref().ok_or_else(|| {
+            let hint = match &secret.value_env {
+                Some(var) => format!("set {var}"),
+                None => "set its value in tools.secrets".to_string(),
+            };
+            ToolError::ExecutionFailed(format!("secret '{name}' has no value ({hint})"))
+        })
+    }
+
+    /// Secret values, longest first, so a secret containing another is
+    /// replaced as a whole.
+    fn values(&self) -> Vec<&str> {
+        let mut values: Vec<&str> = self
+            .secrets
+            .values()
+            .filter_map(|s| s.value.as_ref())
+            .map(SecretString::expose)
+            .filter(|v| !v.is_empty())
+            .collect();
+        values.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        values.dedup();
+        values
+    }
+
+    /// `text` with every secret value replaced, or `None` when it contains
+    /// none.
+    fn scrub_text(values: &[&str], text: &str) -> Option<String> {
+        if !values.iter().any(|v| text.contains(v)) {
+            return None;
+        }
+        let mut scrubbed = text.to_string();
+        for value in values {
+            scrubbed = scrubbed.replace(value, REDACTED);
+        }
+        Some(scrubbed)
+    }
+
+    fn scrub_value(values: &[&str], value: &mut Value) -> bool {
+        match value {
+            Value::String(text) => match Self::scrub_text(values, text) {
+                Some(scrubbed) => {
+                    *text = scrubbed;
+                    true
+                }
+                None => false,
+            },
+            Value::Array(items) => items
+                .iter_mut()
+                .fold(false, |hit, item| Self::scrub_value(values, item) | hit),
+            Value::Object(fields) => {
+                let mut hit = false;
+                if fields.keys().any(|k| Self::scrub_text(values, k).is_some()) {
+                    *fields = std::mem::take(fields)
+                        .into_iter()
+                        .map(|(k, v)| (Self::scrub_text(values, &k).unwrap_or(k), v))
+                        .collect();
+                    hit = true;
+                }
+                fields
+                    .values_mut()
+                    .fold(hit, |hit, field| Self::scrub_value(values, field) | hit)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `text` contains the value of any secret.
+    pub fn contains_secret(&self, text: &str) -> bool {
+        self.values().iter().any(|v| text.contains(v))
+    }
+
+    /// Replace every secret value in the strings (and object keys) of
+    /// `value`. Returns whether anything was replaced.
+    pub fn scrub(&self, value: &mut Value) -> bool {
+        let values = self.values();
+        !values.is_empty() && Self::scrub_value(&values, value)
+    }
+
+    /// Scrub a tool result: the value on success, the message on failure.
+    /// Returns whether anything was replaced.
+    pub fn scrub_result(&self, result: &mut Result<Value, ToolError>) -> bool {
+        let values = self.values();
+        if values.is_empty() {
+            return false;
+        }
+        let scrub = |text: &mut String| match Self::scrub_text(&values, text) {
+            Some(scrubbed) => {
+                *text = scrubbed;
+                true
+            }
+            None => false,
+        };
+        match result {
+            Ok(value) => Self::scrub_value(&values, value),
+            Err(
+                ToolError::NotFound(text)
+                | ToolError::InvalidArgs(text)
+                | ToolError::ExecutionFailed(text)
+                | ToolError::FileNotFound(text)
+                | ToolError::InvalidPath(text),
+            ) => scrub(text),
+            Err(ToolError::PermissionDenied { tool, reason }) => scrub(tool) | scrub(reason),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Arguments of a `secret_use` call.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SecretUseRequest {
+    /// The tool to run.
+    pub tool: String,
+
+    /// Its arguments, with `{{secret:NAME}}` references.
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+impl SecretUseRequest {
+    /// Parse and check `secret_use` arguments.
+    pub fn from_args(args: Value) -> Result<Self, ToolError> {
+        let request: Self =
+            serde_json::from_value(args).map_err(|e| ToolError::InvalidArgs(e.to_string()))?;
+        if [SECRET_USE_TOOL, SECRET_LIST_TOOL, SPAWN_AGENT_TOOL].contains(&request.tool.as_str()) {
+            return Err(ToolError::InvalidArgs(format!(
+                "{} cannot be run through {SECRET_USE_TOOL}",
+                request.tool
+            )));
+        }
+        if !request.arguments.is_object() {
+            return Err(ToolError::InvalidArgs("arguments must be an object".into()));
+        }
+        if !request.arguments.to_string().contains(SECRET_OPEN) {
+            return Err(ToolError::InvalidArgs(format!(
+                "arguments reference no {{{{secret:NAME}}}}; call {} directly",
+                request.tool
+            )));
+        }
+        Ok(request)
+    }
+}
+
+/// The `secret_use` tool as the model sees it.
+///
+/// Calls are run by the agent loop, which owns the tool registry and the
+/// caller's permissions; [`execute`](Tool::execute) outside a turn fails.
+pub struct SecretUseTool;
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl Tool for SecretUseTool {
+    fn name(&self) -> &str {
+        SECRET_USE_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Run another tool with configured secrets filled into its arguments. Write \
+         {{secret:NAME}} where a secret value belongs; the value is inserted when the tool \
+         runs and is never shown to you. Each secret can only be passed to the tools it is \
+         granted to; see secret_list."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tool": {
+                    "type": "string",
+                    "description": "The tool to run"
+                },
+                "arguments": {
+                    "type": "object",
+                    "description": "The tool's arguments, with {{secret:NAME}} references"
+                }
+            },
+            "required": ["tool", "arguments"]
+        })
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, _args: Value) -> Result<Value, ToolError> {
+        Err(ToolError::ExecutionFailed(
+            "secret_use can only run inside an agent turn".into(),
+        ))
+    }
+}
+
+/// List the configured secrets: names, descriptions and granted tools.
+pub struct SecretListTool {
+    secrets: Arc<Secrets>,
+}
+
+impl SecretListTool {
+    /// A tool listing `secrets`.
+    pub fn new(secrets: Arc<Secrets>) -> Self {
+        Self { secrets }
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl Tool for SecretListTool {
+    fn name(&self) -> &str {
+        SECRET_LIST_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "List the secrets you can use with secret_use: their names, what they are for, and \
+         the tools each may be passed to. Values are never shown."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+
+    async fn execute(&self, _args: Value) -> Result<Value, ToolError> {
+        Ok(self.secrets.list())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets() -> Secrets {
+        let config: HashMap<String, SecretRef> = serde_json::from_value(json!({
+            "github": {
+                "description": "GitHub token",
+                "valueEnv": "GITHUB_TOKEN",
+                "tools": ["write_file", "mcp_github__*"]
+            },
+            "db": { "value": "hunter2", "tools": ["write_file"] },
+            "unset": { "valueEnv": "MISSING", "tools": ["write_file"] },
+            "ungranted": { "value": "nope-value" }
+        }))
+        .unwrap();
+        Secrets::from_config(&config, |name| {
+            (name == "GITHUB_TOKEN").then(|| "ghp_abc123".to_string())
+        })
+    }
+
+    #[test]
+    fn interpolates_nested_references_for_granted_tools() {
+        let secrets = secrets();
+        let mut args = json!({
+            "path": "config.toml",
+            "content": "token = \"{{secret:github}}\"\npassword = \"{{secret: db }}\"",
+            "extra": [{"auth": "Bearer {{secret:github}}"}, 7]
+        });
+        assert_eq!(secrets.interpolate("write_file", &mut args).unwrap(), 3);
+        assert_eq!(
+            args["content"],
+            "token = \"ghp_abc123\"\npassword = \"hunter2\""
+        );
+        assert_eq!(args["extra"][0]["auth"], "Bearer ghp_abc123");
+        assert_eq!(args["path"], "config.toml");
+
+        let mut args = json!({"token": "{{secret:github}}"});
+        assert_eq!(
+            secrets
+                .interpolate("mcp_github__create_issue", &mut args)
+                .unwrap(),
+            1
+        );
+        assert_eq!(args["token"], "ghp_abc123");
+    }
+
+    #[test]
+    fn interpolation_refuses_unknown_ungranted_and_unset_secrets() {
+        let secrets = secrets();
+        let err = |tool: &str, text: &str| {
+            secrets
+                .interpolate(tool, &mut json!({ "text": text }))
+                .unwrap_err()
+        };
+        assert!(matches!(
+            err("write_file", "{{secret:nope}}"),
+            ToolError::InvalidArgs(m) if m.contains("configured secrets: db, github")
+        ));
+        assert!(matches!(
+            err("write_file", "{{secret:github"),
+            ToolError::InvalidArgs(_)
+        ));
+        assert!(matches!(
+            err("exec_shell", "{{secret:github}}"),
+            ToolError::PermissionDenied { reason, .. } if reason.contains("not granted")
+        ));
+        assert!(matches!(
+            err("write_file", "{{secret:ungranted}}"),
+            ToolError::PermissionDenied { .. }
+        ));
+        assert!(matches!(
+            err("write_file", "{{secret:unset}}"),
+            ToolError::ExecutionFailed(m) if m.contains("set MISSING")
+        ));
+    }
+
+    #[test]
+    fn scrubs_values_keys_and_errors() {
+        let secrets = secrets();
+        let mut value = json!({
+            "content": "token = ghp_abc123, again ghp_abc123",
+            "hunter2": ["nested hunter2"],
+            "count": 3
+        });
+        assert!(secrets.scrub(&mut value));
+        assert_eq!(
+            value,
+            json!({
+                "content": "token = [REDACTED], again [REDACTED]",
+                "[REDACTED]": ["nested [REDACTED]"],
+                "count": 3
+            })
+        );
+        assert!(!secrets.scrub(&mut json!({"clean": "nothing here"})));
+        assert!(secrets.contains_secret("Bearer ghp_abc123"));
+        assert!(!secrets.contains_secret("Bearer {{secret:github}}"));
+
+        let mut result = Err(ToolError::ExecutionFailed("bad token ghp_abc123".into()));
+        assert!(secrets.scrub_result(&mut result));
+        assert!(
+            matches!(result, Err(ToolError::ExecutionFailed(m)) if m == "bad token [REDACTED]")
+        );
+    }
+
+    #[test]
+    fn list_never_includes_values() {
+        let listed = secrets().list();
+        let text = listed.to_string();
+        for value in ["ghp_abc123", "hunter2", "nope-value"] {
+            assert!(!text.contains(value), "{text}");
+        }
+        let names: Vec<&str> = listed["secrets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["db", "github", "ungranted", "unset"]);
+        assert_eq!(listed["secrets"][1]["description"], "GitHub token");
+        assert_eq!(listed["secrets"][3]["available"], false);
+    }
+
+    #[test]
+    fn secret_use_request_checks_args() {
+        let request = SecretUseRequest::from_args(json!({
+            "tool": "write_file",
+            "arguments": {"path": "a", "content": "{{secret:db}}"}
+        }))
+        .unwrap();
+        assert_eq!(request.tool, "write_file");
+
+        for args in [
+            json!({"tool": "write_file"}),
+            json!({"tool": "write_file", "arguments": {"content": "plain"}}),
+            json!({"tool": "write_file", "arguments": "{{secret:db}}"}),
+            json!({"tool": "secret_use", "arguments": {"x": "{{secret:db}}"}}),
+            json!({"tool": "spawn_agent", "arguments": {"task": "{{secret:db}}"}}),
+        ] {
+            assert!(SecretUseRequest::from_args(args.clone()).is_err(), "{args}");
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Response sinks.
+//!
+//! A [`ResponseSink`] receives an agent turn's reply while
+//! [`AgentLoop`](super::loop_core::AgentLoop) produces it: text deltas as
+//! the model streams them, a notice for each tool call, and the final
+//! text. Sinks are opened per inbound message by a
+//! [`ResponseSinkFactory`]; the interactive CLI prints deltas as they
+//! arrive, and the gateway progressively edits the reply in channels that
+//! support it.
+//!
+//! The final reply is always dispatched to the bus as well. When the sink
+//! took deltas, the outbound message is marked
+//! [`streamed`](clawft_types::event::OutboundMessage::is_streamed) so
+//! consumers that already showed the text do not show it twice.
+//!
+//! Messages without a sink get a [`BufferingSink`], which takes no deltas:
+//! the loop then uses non-streaming completions, as it always has.
+
+use std::sync::{Arc, Mutex};
+
+use clawft_types::event::InboundMessage;
+
+/// Receives the reply to one inbound message as it is produced.
+///
+/// Methods are called from the agent loop (text deltas from inside the
+/// transport's stream callback), so implementations must not block.
+pub trait ResponseSink: Send + Sync {
+    /// Whether the loop should stream completions into this sink.
+    ///
+    /// When `false`, no deltas are delivered and only
+    /// [`on_complete`](Self::on_complete) is called.
+    fn wants_deltas(&self) -> bool {
+        true
+    }
+
+    /// A fragment of assistant text, in order.
+    ///
+    /// Text the model writes before calling tools is streamed too; the
+    /// final reply is the text of the last completion only.
+    fn on_text_delta(&self, delta: &str);
+
+    /// The model called tool `name`; it is about to run.
+    fn on_tool_call_started(&self, id: &str, name: &str);
+
+    /// The turn finished with reply `text`.
+    fn on_complete(&self, text: &str);
+}
+
+/// Opens a [`ResponseSink`] for each inbound message.
+pub trait ResponseSinkFactory: Send + Sync {
+    /// The sink for the reply to `msg`, or `None` to use a
+    /// [`BufferingSink`].
+    fn open(&self, msg: &InboundMessage) -> Option<Arc<dyn ResponseSink>>;
+}
+
+/// Sink for non-streaming consumers: takes no deltas and keeps the final
+/// reply.
+#[derive(Debug, Default)]
+pub struct BufferingSink {
+    text: Mutex<String>,
+}
+
+impl BufferingSink {
+    /// Create an empty buffering sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The reply received so far.
+    pub fn text(&self) -> String {
+        self.text.lock().map(|t| t.clone()).unwrap_or_default()
+    }
+}
+
+impl ResponseSink for BufferingSink {
+    fn wants_deltas(&self) -> bool {
+        false
+    }
+
+    fn on_text_delta(&self, delta: &str) {
+        if let Ok(mut text) = self.text.lock() {
+            text.push_str(delta);
+        }
+    }
+
+    fn on_tool_call_started(&self, _id: &str, _name: &str) {
+        if let Ok(mut text) = self.text.lock() {
+            text.clear();
+        }
+    }
+
+    fn on_complete(&self, reply: &str) {
+        if let Ok(mut text) = self.text.lock() {
+            *text = reply.to_string();
+        }
+    }
+}
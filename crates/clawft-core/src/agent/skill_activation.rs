@@ -0,0 +1,384 @@
+//! Automatic skill activation.
+//!
+//! Scores an inbound message against the [`SkillTriggers`] of every
+//! registered skill and activates the best match at or above
+//! `agents.skills.threshold` for the turn. Two scoring modes exist:
+//!
+//! - **Keyword** -- a keyword (or keyword phrase) found in the message
+//!   scores 1.0; otherwise the score is the share of an example's words
+//!   that also appear in the message, for the best example.
+//! - **Embedding** -- cosine similarity between the message embedding and
+//!   the centroid of the skill's example and keyword embeddings. Requires
+//!   the `vector-memory` feature; without it keyword scoring is used.
+//!
+//! Skills without triggers, and skills that set
+//! `disable-model-invocation`, are never activated automatically. When
+//! several skills share the top score, the caller's default skill wins,
+//! then the alphabetically first name.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use clawft_types::config::{SkillActivationConfig, SkillScoring};
+use clawft_types::skill::{SkillDefinition, SkillTriggers};
+
+use super::skills_v2::SharedSkillRegistry;
+
+/// Candidates kept in a [`SkillDecision`].
+const MAX_CANDIDATES: usize = 3;
+
+/// Words ignored when comparing a message with example messages.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "at", "be", "can", "could", "do", "for", "from", "i", "in", "is",
+    "it", "me", "my", "of", "on", "or", "please", "the", "to", "us", "we", "with", "you",
+];
+
+/// The outcome of scoring a message against the registered skills.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillDecision {
+    /// Scoring mode actually used.
+    pub scoring: SkillScoring,
+
+    /// Minimum score for activation.
+    pub threshold: f64,
+
+    /// The activated skill, if any scored at or above the threshold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activated: Option<String>,
+
+    /// The best-scoring skills, highest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<SkillScore>,
+}
+
+/// One skill's score for a message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillScore {
+    /// Skill name.
+    pub skill: String,
+
+    /// Score from 0.0 to 1.0.
+    pub score: f64,
+}
+
+/// Picks the skill to activate for a message.
+pub struct SkillActivator {
+    registry: SharedSkillRegistry,
+    config: SkillActivationConfig,
+}
+
+impl SkillActivator {
+    /// Create an activator over `registry` with the `agents.skills`
+    /// settings.
+    pub fn new(registry: SharedSkillRegistry, config: SkillActivationConfig) -> Self {
+        Self { registry, config }
+    }
+
+    /// The scoring mode in effect: embedding scoring falls back to
+    /// keyword scoring without the `vector-memory` feature.
+    pub fn scoring(&self) -> SkillScoring {
+        if cfg!(feature = "vector-memory") {
+            self.config.scoring
+        } else {
+            SkillScoring::Keyword
+        }
+    }
+
+    /// Score `message` against every eligible skill.
+    ///
+    /// `default_skill` breaks ties; without it `agents.skills.defaultSkill`
+    /// does. Returns the decision and, when a skill was activated, its
+    /// definition.
+    pub async fn select(
+        &self,
+        message: &str,
+        default_skill: Option<&str>,
+    ) -> (SkillDecision, Option<SkillDefinition>) {
+        let scoring = self.scoring();
+        let registry = self.registry.read().await;
+        let scores = registry
+            .list()
+            .into_iter()
+            .filter(|skill| !skill.disable_model_invocation && !skill.triggers.is_empty())
+            .map(|skill| SkillScore {
+                skill: skill.name.clone(),
+                score: score(scoring, message, &skill.triggers),
+            })
+            .collect();
+        let default_skill = default_skill.or(self.config.default_skill.as_deref());
+        let decision = decide(scoring, self.config.threshold, scores, default_skill);
+        let skill = decision
+            .activated
+            .as_deref()
+            .and_then(|name| registry.get(name))
+            .cloned();
+        (decision, skill)
+    }
+}
+
+/// Rank `scores` and activate the best one at or above `threshold`.
+fn decide(
+    scoring: SkillScoring,
+    threshold: f64,
+    mut scores: Vec<SkillScore>,
+    default_skill: Option<&str>,
+) -> SkillDecision {
+    scores.retain(|s| s.score > 0.0);
+    scores.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| {
+                let a_default = Some(a.skill.as_str()) == default_skill;
+                let b_default = Some(b.skill.as_str()) == default_skill;
+                b_default.cmp(&a_default)
+            })
+            .then_with(|| a.skill.cmp(&b.skill))
+    });
+    scores.truncate(MAX_CANDIDATES);
+    let activated = scores
+        .first()
+        .filter(|best| best.score >= threshold)
+        .map(|best| best.skill.clone());
+    SkillDecision {
+        scoring,
+        threshold,
+        activated,
+        candidates: scores,
+    }
+}
+
+/// Score `message` against one skill's triggers.
+fn score(scoring: SkillScoring, message: &str, triggers: &SkillTriggers) -> f64 {
+    match scoring {
+        #[cfg(feature = "vector-memory")]
+        SkillScoring::Embedding => embedding_score(message, triggers),
+        _ => keyword_score(message, triggers),
+    }
+}
+
+/// Keyword scoring: 1.0 for a keyword found in the message, otherwise the
+/// best share of an example's words found in the message.
+pub fn keyword_score(message: &str, triggers: &SkillTriggers) -> f64 {
+    let words = tokens(message);
+    let keyword_hit = triggers.keywords.iter().any(|keyword| {
+        let phrase = tokens(keyword);
+        !phrase.is_empty() && words.windows(phrase.len()).any(|w| w == phrase.as_slice())
+    });
+    if keyword_hit {
+        return 1.0;
+    }
+
+    let present: HashSet<&str> = words.iter().map(String::as_str).collect();
+    triggers
+        .examples
+        .iter()
+        .map(|example| {
+            let example: HashSet<String> = tokens(example).into_iter().collect();
+            if example.is_empty() {
+                return 0.0;
+            }
+            let found = example
+                .iter()
+                .filter(|w| present.contains(w.as_str()))
+                .count();
+            found as f64 / example.len() as f64
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Embedding scoring: similarity between the message and the centroid of
+/// the trigger embeddings, clamped to 0.0 to 1.0.
+#[cfg(feature = "vector-memory")]
+pub fn embedding_score(message: &str, triggers: &SkillTriggers) -> f64 {
+    use crate::embeddings::hash_embedder::HashEmbedder;
+
+    let embedder = HashEmbedder::default_dimension();
+    let Some(centroid) = centroid(&embedder, triggers) else {
+        return 0.0;
+    };
+    let query = embedder.compute_embedding(&tokens(message).join(" "));
+    let dot: f32 = query.iter().zip(&centroid).map(|(a, b)| a * b).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(&query) * norm(&centroid);
+    if denom == 0.0 {
+        return 0.0;
+    }
+    f64::from(dot / denom).clamp(0.0, 1.0)
+}
+
+/// The mean embedding of a skill's examples and keywords, or `None` when
+/// it has neither.
+#[cfg(feature = "vector-memory")]
+pub fn centroid(
+    embedder: &crate::embeddings::hash_embedder::HashEmbedder,
+    triggers: &SkillTriggers,
+) -> Option<Vec<f32>> {
+    let embeddings: Vec<Vec<f32>> = triggers
+        .examples
+        .iter()
+        .chain(&triggers.keywords)
+        .map(|text| tokens(text).join(" "))
+        .filter(|text| !text.is_empty())
+        .map(|text| embedder.compute_embedding(&text))
+        .collect();
+    let first = embeddings.first()?;
+    let mut centroid = vec![0.0f32; first.len()];
+    for embedding in &embeddings {
+        for (sum, value) in centroid.iter_mut().zip(embedding) {
+            *sum += value;
+        }
+    }
+    let count = embeddings.len() as f32;
+    for value in &mut centroid {
+        *value /= count;
+    }
+    Some(centroid)
+}
+
+/// Lowercased words of `text`, without stopwords.
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::skills_v2::SkillRegistry;
+    use crate::runtime::RwLock;
+    use std::sync::Arc;
+
+    fn skill(name: &str, keywords: &[&str], examples: &[&str]) -> SkillDefinition {
+        let mut skill = SkillDefinition::new(name, name);
+        skill.instructions = format!("{name} instructions");
+        skill.triggers = SkillTriggers {
+            keywords: keywords.iter().map(|s| s.to_string()).collect(),
+            examples: examples.iter().map(|s| s.to_string()).collect(),
+        };
+        skill
+    }
+
+    async fn activator(threshold: f64, default_skill: Option<&str>) -> SkillActivator {
+        let mut registry = SkillRegistry::discover(None, None, Vec::new())
+            .await
+            .unwrap();
+        registry.upsert(skill(
+            "calendar",
+            &["calendar"],
+            &["schedule team meeting tomorrow"],
+        ));
+        registry.upsert(skill("weather", &["forecast"], &["will it rain today"]));
+        registry.upsert(skill("planner", &["agenda"], &[]));
+        let mut manual = skill("deploy", &["deploy"], &[]);
+        manual.disable_model_invocation = true;
+        registry.upsert(manual);
+        registry.upsert(SkillDefinition::new("untriggered", "no triggers"));
+        SkillActivator::new(
+            Arc::new(RwLock::new(registry)),
+            SkillActivationConfig {
+                auto_activate: true,
+                scoring: SkillScoring::Keyword,
+                threshold,
+                default_skill: default_skill.map(String::from),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn activates_best_match() {
+        let activator = activator(0.5, None).await;
+        let (decision, skill) = activator
+            .select("Can you check the forecast for Berlin?", None)
+            .await;
+        assert_eq!(decision.activated.as_deref(), Some("weather"));
+        assert_eq!(skill.unwrap().instructions, "weather instructions");
+        assert_eq!(decision.candidates[0].score, 1.0);
+
+        let (decision, _) = activator.select("Will it rain on Sunday?", None).await;
+        assert_eq!(decision.activated.as_deref(), Some("weather"));
+        assert!((decision.candidates[0].score - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn no_match_activates_nothing() {
+        let activator = activator(0.5, None).await;
+        let (decision, skill) = activator.select("tell me a joke", None).await;
+        assert_eq!(decision.activated, None);
+        assert!(decision.candidates.is_empty());
+        assert!(skill.is_none());
+
+        // Skills without triggers or with model invocation disabled are
+        // never candidates.
+        let (decision, _) = activator.select("deploy untriggered", None).await;
+        assert_eq!(decision.activated, None);
+    }
+
+    #[tokio::test]
+    async fn threshold_is_inclusive() {
+        // "schedule meeting" covers 2 of the example's 4 words.
+        let (decision, _) = activator(0.5, None)
+            .await
+            .select("schedule a meeting", None)
+            .await;
+        assert_eq!(decision.activated.as_deref(), Some("calendar"));
+        assert_eq!(decision.candidates[0].score, 0.5);
+
+        let (decision, _) = activator(0.51, None)
+            .await
+            .select("schedule a meeting", None)
+            .await;
+        assert_eq!(decision.activated, None);
+        assert_eq!(decision.candidates[0].skill, "calendar");
+    }
+
+    #[tokio::test]
+    async fn ties_prefer_default_skill() {
+        let message = "put it on the calendar agenda";
+        let (decision, _) = activator(0.5, None).await.select(message, None).await;
+        assert_eq!(decision.activated.as_deref(), Some("calendar"));
+
+        let (decision, _) = activator(0.5, Some("planner"))
+            .await
+            .select(message, None)
+            .await;
+        assert_eq!(decision.activated.as_deref(), Some("planner"));
+
+        // The caller's default (the agent's skill) beats the configured one.
+        let (decision, _) = activator(0.5, Some("planner"))
+            .await
+            .select(message, Some("calendar"))
+            .await;
+        assert_eq!(decision.activated.as_deref(), Some("calendar"));
+    }
+
+    #[test]
+    fn keyword_phrases_match_whole_words() {
+        let triggers = SkillTriggers {
+            keywords: vec!["book a meeting".into()],
+            examples: vec![],
+        };
+        assert_eq!(
+            keyword_score("Please book a meeting with Sam", &triggers),
+            1.0
+        );
+        assert_eq!(keyword_score("a meeting book", &triggers), 0.0);
+        assert_eq!(keyword_score("bookkeeping meetings", &triggers), 0.0);
+    }
+
+    #[cfg(feature = "vector-memory")]
+    #[test]
+    fn embedding_scores_examples_above_unrelated_text() {
+        let triggers = SkillTriggers {
+            keywords: vec![],
+            examples: vec!["schedule team meeting tomorrow".into()],
+        };
+        let close = embedding_score("schedule team meeting tomorrow", &triggers);
+        let far = embedding_score("quantum chromodynamics lecture notes", &triggers);
+        assert!(close > 0.99);
+        assert!(close > far);
+    }
+}
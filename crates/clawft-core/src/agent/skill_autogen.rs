@@ -726,6 +726,8 @@ mod tests {
                     content: content.into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 }],
                 tools: vec![],
                 model: None,
@@ -733,6 +735,7 @@ mod tests {
                 temperature: None,
                 auth_context: None,
                 complexity_boost: 0.0,
+                cache: false,
             },
             routing: RoutingDecision::default(),
             response: LlmResponse {
@@ -745,6 +748,10 @@ mod tests {
                     input_tokens: 5,
                     output_tokens: 2,
                     total_tokens: 0,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 },
                 metadata: std::collections::HashMap::new(),
             },
@@ -10,6 +10,8 @@
 //! 2. Debounces rapid changes (default: 500ms).
 //! 3. Acquires a write lock on [`SharedSkillRegistry`] only during rebuild.
 //! 4. Releases the write lock immediately after rebuild completes.
+//! 5. Bumps the reload counter, so subscribers (e.g. the MCP server's
+//!    `listChanged` notifications) see that the skill set changed.
 //!
 //! # Concurrency
 //!
@@ -81,9 +83,17 @@ impl SkillWatcherConfig {
 pub struct SkillWatcherHandle {
     /// Sends a shutdown signal to the watcher task.
     shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    /// Number of successful rebuilds so far.
+    reloads: tokio::sync::watch::Receiver<u64>,
 }
 
 impl SkillWatcherHandle {
+    /// Subscribe to registry reloads. The value is the number of
+    /// successful rebuilds, and changes after each one.
+    pub fn reloads(&self) -> tokio::sync::watch::Receiver<u64> {
+        self.reloads.clone()
+    }
+
     /// Stop the watcher gracefully.
     pub fn stop(mut self) {
         if let Some(tx) = self.shutdown_tx.take() {
@@ -115,6 +125,7 @@ pub fn start_watching(
 ) -> Result<SkillWatcherHandle, notify::Error> {
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
     let (event_tx, mut event_rx) = mpsc::channel::<Event>(100);
+    let (reload_tx, reload_rx) = tokio::sync::watch::channel(0u64);
 
     // Create OS file watcher.
     let mut watcher = RecommendedWatcher::new(
@@ -203,6 +214,7 @@ pub fn start_watching(
                                 count = reg.len(),
                                 "skill registry reloaded after file change"
                             );
+                            reload_tx.send_modify(|n| *n += 1);
                         }
                         Err(e) => {
                             warn!(error = %e, "skill registry rebuild failed");
@@ -220,6 +232,7 @@ pub fn start_watching(
 
     Ok(SkillWatcherHandle {
         shutdown_tx: Some(shutdown_tx),
+        reloads: reload_rx,
     })
 }
 
@@ -331,6 +344,8 @@ mod tests {
         };
 
         let handle = start_watching(config, registry.clone()).unwrap();
+        let reloads = handle.reloads();
+        assert_eq!(*reloads.borrow(), 0);
 
         // Add a new skill
         create_skill_md(&dir, "added", "Added skill");
@@ -344,6 +359,7 @@ mod tests {
             assert!(reg.get("original").is_some());
             assert!(reg.get("added").is_some());
         }
+        assert!(*reloads.borrow() >= 1);
 
         handle.stop();
         let _ = std::fs::remove_dir_all(&dir);
@@ -407,7 +423,10 @@ mod tests {
 
         {
             let reg = registry.read().await;
-            assert_eq!(reg.get("mutable").unwrap().description, "Original description");
+            assert_eq!(
+                reg.get("mutable").unwrap().description,
+                "Original description"
+            );
         }
 
         let config = SkillWatcherConfig {
@@ -429,7 +448,10 @@ mod tests {
 
         {
             let reg = registry.read().await;
-            assert_eq!(reg.get("mutable").unwrap().description, "Updated description");
+            assert_eq!(
+                reg.get("mutable").unwrap().description,
+                "Updated description"
+            );
         }
 
         handle.stop();
@@ -471,7 +493,10 @@ mod tests {
         let prev = registry.upsert(updated);
         assert!(prev.is_some());
         assert_eq!(prev.unwrap().description, "A dynamically added skill");
-        assert_eq!(registry.get("dynamic").unwrap().description, "Updated description");
+        assert_eq!(
+            registry.get("dynamic").unwrap().description,
+            "Updated description"
+        );
 
         // Remove
         let removed = registry.remove("dynamic");
@@ -489,14 +514,19 @@ mod tests {
         std::fs::create_dir_all(&dir).unwrap();
         create_skill_md(&dir, "first", "First skill");
 
-        let mut registry = SkillRegistry::discover(Some(&dir), None, vec![]).await.unwrap();
+        let mut registry = SkillRegistry::discover(Some(&dir), None, vec![])
+            .await
+            .unwrap();
         assert_eq!(registry.len(), 1);
 
         // Add another skill to disk
         create_skill_md(&dir, "second", "Second skill");
 
         // Rebuild
-        registry.rebuild(Some(&dir), None, vec![], true).await.unwrap();
+        registry
+            .rebuild(Some(&dir), None, vec![], true)
+            .await
+            .unwrap();
         assert_eq!(registry.len(), 2);
         assert!(registry.get("first").is_some());
         assert!(registry.get("second").is_some());
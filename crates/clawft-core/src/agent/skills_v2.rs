@@ -18,6 +18,11 @@
 //!   - WebSearch
 //!   - Read
 //! user-invocable: true
+//! triggers:
+//!   keywords:
+//!     - research
+//!   examples:
+//!     - find out what is known about solid-state batteries
 //! ---
 //!
 //! You are a research assistant. Given a {{topic}}, ...
@@ -38,7 +43,7 @@ use std::sync::Arc;
 use crate::runtime::RwLock;
 use tracing::{debug, warn};
 
-use clawft_types::skill::{SkillDefinition, SkillFormat};
+use clawft_types::skill::{SkillDefinition, SkillFormat, SkillTriggers};
 use clawft_types::{ClawftError, Result};
 
 use crate::security::{
@@ -128,6 +133,15 @@ pub fn parse_skill_md(content: &str, source_path: Option<&Path>) -> Result<Skill
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let triggers = match fields.get("triggers") {
+        Some(value) => serde_json::from_value::<SkillTriggers>(value.clone()).map_err(|e| {
+            ClawftError::PluginLoadFailed {
+                plugin: format!("SKILL.md: invalid 'triggers': {e}"),
+            }
+        })?,
+        None => SkillTriggers::default(),
+    };
+
     // Collect remaining fields as metadata.
     let known_keys: &[&str] = &[
         "name",
@@ -142,6 +156,7 @@ pub fn parse_skill_md(content: &str, source_path: Option<&Path>) -> Result<Skill
         "user_invocable",
         "disable-model-invocation",
         "disable_model_invocation",
+        "triggers",
     ];
     let metadata: HashMap<String, serde_json::Value> = fields
         .into_iter()
@@ -167,6 +182,7 @@ pub fn parse_skill_md(content: &str, source_path: Option<&Path>) -> Result<Skill
         allowed_tools,
         user_invocable,
         disable_model_invocation,
+        triggers,
         instructions: sanitized_body,
         format: SkillFormat::SkillMd,
         source_path: source_path.map(PathBuf::from),
@@ -309,6 +325,7 @@ fn extract_string_list(
 /// 1. **Workspace skills**: `.clawft/skills/` in the project root
 /// 2. **User skills**: `~/.clawft/skills/`
 /// 3. **Built-in skills**: compiled into the binary
+#[derive(Clone)]
 pub struct SkillRegistry {
     skills: HashMap<String, SkillDefinition>,
 }
@@ -693,6 +710,20 @@ deep research at the requested {{depth}} level.
         assert!(!skill.user_invocable);
         assert_eq!(skill.instructions, "Do the thing.");
         assert!(skill.source_path.is_none());
+        assert!(skill.triggers.is_empty());
+    }
+
+    #[test]
+    fn parse_skill_md_triggers() {
+        let content = "---\nname: calendar\ndescription: Calendar\ntriggers:\n  keywords: [meeting, calendar]\n  examples:\n    - book a meeting with Sam\n---\n\nUse the calendar.";
+        let skill = parse_skill_md(content, None).unwrap();
+
+        assert_eq!(skill.triggers.keywords, vec!["meeting", "calendar"]);
+        assert_eq!(skill.triggers.examples, vec!["book a meeting with Sam"]);
+        assert!(!skill.metadata.contains_key("triggers"));
+
+        let bad = "---\nname: calendar\ntriggers:\n  keywords: 3\n---\n\nBody";
+        assert!(parse_skill_md(bad, None).is_err());
     }
 
     #[test]
@@ -0,0 +1,256 @@
+//! Sub-agent delegation with the `spawn_agent` tool.
+//!
+//! A model hands a subtask to another agent definition by calling
+//! [`SPAWN_AGENT_TOOL`]. The [`AgentLoop`](super::loop_core::AgentLoop)
+//! runs the sub-agent to completion as a turn of its own: a fresh session
+//! keyed under the parent's (`<parent>:sub-<agent>-<id>`, see
+//! [`child_session_key`]) with the parent recorded under
+//! [`Session::PARENT_KEY`](clawft_types::session::Session::PARENT_KEY),
+//! the sub-agent's own system prompt, skills and tool allowlist, and its
+//! own iteration and token limits. Its final answer and token usage come
+//! back to the parent as the tool result ([`SpawnResult`]).
+//!
+//! The sub-agent's tool calls are audited under its own agent name and
+//! session key, so the audit log links them to the parent turn through
+//! the key prefix. Sub-agents may spawn sub-agents of their own, up to
+//! `delegation.max_spawn_depth` levels.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::tools::registry::{Tool, ToolError, matches_any_pattern};
+
+/// Name of the sub-agent tool.
+pub const SPAWN_AGENT_TOOL: &str = "spawn_agent";
+
+/// Separator between a parent session key and a sub-agent's suffix.
+const CHILD_SEGMENT: &str = ":sub-";
+
+/// Arguments of a `spawn_agent` call.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpawnRequest {
+    /// Agent definition to run.
+    pub agent: String,
+
+    /// The subtask, sent to the sub-agent as its user message.
+    pub task: String,
+
+    /// Narrows the sub-agent's tools further than its definition does.
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+
+    /// Tool-loop iterations the sub-agent may use.
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+
+    /// Prompt plus completion tokens the sub-agent may use.
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+}
+
+impl SpawnRequest {
+    /// Parse and check `spawn_agent` arguments.
+    pub fn from_args(args: Value) -> Result<Self, ToolError> {
+        let request: Self =
+            serde_json::from_value(args).map_err(|e| ToolError::InvalidArgs(e.to_string()))?;
+        if request.task.trim().is_empty() {
+            return Err(ToolError::InvalidArgs("task must not be empty".into()));
+        }
+        if request.max_iterations == Some(0) || request.max_tokens == Some(0) {
+            return Err(ToolError::InvalidArgs(
+                "max_iterations and max_tokens must be positive".into(),
+            ));
+        }
+        Ok(request)
+    }
+}
+
+/// Token usage of a sub-agent run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpawnUsage {
+    /// Prompt tokens across the sub-agent's LLM calls.
+    pub input_tokens: u64,
+    /// Completion tokens across the sub-agent's LLM calls.
+    pub output_tokens: u64,
+}
+
+/// What a `spawn_agent` call returns to the parent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnResult {
+    /// The agent that ran.
+    pub agent: String,
+    /// The sub-agent's session, for following up on its work.
+    pub session_key: String,
+    /// The sub-agent's final answer.
+    pub answer: String,
+    /// Tokens the sub-agent used.
+    pub usage: SpawnUsage,
+}
+
+/// How many sub-agent levels deep `session_key` is: 0 for a session a
+/// channel opened, 1 for a sub-agent it spawned, and so on.
+pub fn spawn_depth(session_key: &str) -> usize {
+    session_key.matches(CHILD_SEGMENT).count()
+}
+
+/// A new session key for a sub-agent running `agent` under `parent`.
+pub fn child_session_key(parent: &str, agent: &str) -> String {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!("{parent}{CHILD_SEGMENT}{agent}-{}", &id[..8])
+}
+
+/// The tools a sub-agent may use: those of `registered` matching every
+/// one of the `allowlists` (names or glob patterns), or `None` when there
+/// are no allowlists to apply.
+pub(crate) fn narrow_tools<'a>(
+    registered: Vec<String>,
+    allowlists: impl IntoIterator<Item = &'a [String]>,
+) -> Option<Vec<String>> {
+    let allowlists: Vec<&[String]> = allowlists.into_iter().collect();
+    if allowlists.is_empty() {
+        return None;
+    }
+    Some(
+        registered
+            .into_iter()
+            .filter(|name| {
+                allowlists
+                    .iter()
+                    .all(|list| matches_any_pattern(name, list))
+            })
+            .collect(),
+    )
+}
+
+/// The `spawn_agent` tool as the model sees it.
+///
+/// Calls are run by the agent loop, which owns everything a sub-agent
+/// turn needs; [`execute`](Tool::execute) outside a turn fails.
+pub struct SpawnAgentTool {
+    agents: Vec<String>,
+}
+
+impl SpawnAgentTool {
+    /// A tool that can spawn any of `agents`.
+    pub fn new(agents: Vec<String>) -> Self {
+        Self { agents }
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl Tool for SpawnAgentTool {
+    fn name(&self) -> &str {
+        SPAWN_AGENT_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Hand a self-contained subtask to another agent. It starts with a fresh \
+         conversation, works on the task with its own tools, and returns its final \
+         answer and token usage."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "agent": {
+                    "type": "string",
+                    "enum": self.agents,
+                    "description": "The agent to hand the task to"
+                },
+                "task": {
+                    "type": "string",
+                    "description": "Everything the agent needs to know to do the task"
+                },
+                "allowed_tools": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Restrict the agent to these tools"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "description": "Maximum tool-loop iterations"
+                },
+                "max_tokens": {
+                    "type": "integer",
+                    "description": "Maximum tokens the agent may use"
+                }
+            },
+            "required": ["agent", "task"]
+        })
+    }
+
+    async fn execute(&self, _args: Value) -> Result<Value, ToolError> {
+        Err(ToolError::ExecutionFailed(
+            "spawn_agent can only run inside an agent turn".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_request_parses_and_checks_args() {
+        let request = SpawnRequest::from_args(json!({
+            "agent": "researcher",
+            "task": "find the RFC",
+            "allowed_tools": ["web_search"],
+            "max_iterations": 3
+        }))
+        .unwrap();
+        assert_eq!(request.agent, "researcher");
+        assert_eq!(request.allowed_tools, Some(vec!["web_search".to_string()]));
+        assert_eq!(request.max_iterations, Some(3));
+        assert_eq!(request.max_tokens, None);
+
+        for bad in [
+            json!({ "task": "no agent" }),
+            json!({ "agent": "researcher", "task": "  " }),
+            json!({ "agent": "researcher", "task": "t", "max_tokens": 0 }),
+        ] {
+            let err = SpawnRequest::from_args(bad.clone()).unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{bad}: {err}");
+        }
+    }
+
+    #[test]
+    fn narrow_tools_intersects_allowlists() {
+        let registered = || {
+            ["read_file", "web_fetch", "web_search", "write_file"]
+                .map(String::from)
+                .to_vec()
+        };
+        let list = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(narrow_tools(registered(), []), None);
+        let agent = list(&["web_*", "read_file"]);
+        let parent = list(&["web_search", "read_file", "write_file"]);
+        assert_eq!(
+            narrow_tools(registered(), [agent.as_slice(), parent.as_slice()]),
+            Some(list(&["read_file", "web_search"]))
+        );
+        // Disjoint allowlists leave nothing rather than everything.
+        let other = list(&["write_file"]);
+        assert_eq!(
+            narrow_tools(registered(), [agent.as_slice(), other.as_slice()]),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn child_keys_nest_under_the_parent() {
+        let child = child_session_key("slack:C1", "researcher");
+        assert!(child.starts_with("slack:C1:sub-researcher-"), "{child}");
+        let grandchild = child_session_key(&child, "writer");
+        assert!(grandchild.starts_with(&child));
+
+        assert_eq!(spawn_depth("slack:C1"), 0);
+        assert_eq!(spawn_depth(&child), 1);
+        assert_eq!(spawn_depth(&grandchild), 2);
+        crate::security::validate_session_id(&grandchild).unwrap();
+    }
+}
@@ -0,0 +1,523 @@
+//! Workspace task list: the agent's scratchpad for long, multi-turn work.
+//!
+//! The `task_list` tool keeps tasks in [`TASKS_FILE`] under the
+//! workspace's `.clawft/` directory. Each task has an id, a title, a
+//! [`TaskStatus`], an optional parent (for subtasks) and free-form notes.
+//! While any task is open, the context builder adds
+//! [`TaskList::summary`] to the prompt so the model sees where it left
+//! off.
+//!
+//! Parallel tool calls share the file. [`TaskStore::update`] serializes
+//! writers within the process, re-reads the file just before writing and
+//! applies only the tasks the update changed, so a write from another
+//! process in between is merged rather than overwritten. The file is
+//! replaced atomically.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use chrono::{DateTime, Utc};
+use clawft_platform::Platform;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::Mutex;
+
+/// File name of the task list, inside the workspace's `.clawft/`
+/// directory.
+pub const TASKS_FILE: &str = "tasks.json";
+
+/// Default size limit of the open-task summary added to the context.
+pub const SUMMARY_MAX_BYTES: usize = 2048;
+
+/// Location of the task list for `workspace`.
+pub fn tasks_path(workspace: &Path) -> PathBuf {
+    workspace.join(".clawft").join(TASKS_FILE)
+}
+
+/// Progress of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Pending,
+    InProgress,
+    Done,
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Parse a status name as used by the tool (`pending`, `in_progress`,
+    /// `done`, `cancelled`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "done" => Some(Self::Done),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// The status name, as accepted by [`parse`](Self::parse).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Done => "done",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the task still needs work.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Pending | Self::InProgress)
+    }
+
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Done => "[x]",
+            Self::Cancelled => "[-]",
+        }
+    }
+}
+
+/// One entry in the task list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+    pub status: TaskStatus,
+    /// Id of the task this is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Task {
+    /// Record that the task was just changed.
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Errors from reading or changing the task list.
+#[derive(Debug, thiserror::Error)]
+pub enum TaskError {
+    #[error("no task with id '{0}'")]
+    NotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("task list is corrupt: {0}")]
+    Corrupt(#[from] serde_json::Error),
+    #[error("task list I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The contents of [`TASKS_FILE`], in creation order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskList {
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+}
+
+impl TaskList {
+    /// Parse the JSON contents of a task file.
+    pub fn parse(text: &str) -> Result<Self, TaskError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Tasks that are pending or in progress.
+    pub fn open(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|t| t.status.is_open())
+    }
+
+    /// The next free id: one more than the highest numeric id.
+    pub fn next_id(&self) -> String {
+        let max = self
+            .tasks
+            .iter()
+            .filter_map(|t| t.id.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        (max + 1).to_string()
+    }
+
+    /// Add a task and return its id. `parent`, when given, must exist.
+    pub fn add(
+        &mut self,
+        title: &str,
+        parent: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<String, TaskError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(TaskError::Invalid("task title must not be empty".into()));
+        }
+        if let Some(parent) = parent
+            && self.get(parent).is_none()
+        {
+            return Err(TaskError::NotFound(parent.to_string()));
+        }
+        let now = Utc::now();
+        let id = self.next_id();
+        self.tasks.push(Task {
+            id: id.clone(),
+            title: title.to_string(),
+            status: TaskStatus::Pending,
+            parent: parent.map(str::to_string),
+            notes: notes.map(str::to_string).filter(|n| !n.trim().is_empty()),
+            created_at: now,
+            updated_at: now,
+        });
+        Ok(id)
+    }
+
+    /// Apply the tasks in `ours` that differ from `base` on top of this
+    /// list (the file as it is now).
+    ///
+    /// Tasks changed in `ours` replace their stored version; tasks other
+    /// writers changed are kept. A task added in `ours` whose id was
+    /// taken in the meantime gets a fresh id, and subtasks added with it
+    /// follow.
+    pub fn merge(&mut self, base: &TaskList, ours: TaskList) {
+        let base: HashMap<&str, &Task> = base.tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+        let mut renamed: HashMap<String, String> = HashMap::new();
+        let mut added: HashSet<String> = HashSet::new();
+        for mut task in ours.tasks {
+            match base.get(task.id.as_str()) {
+                Some(before) if **before == task => continue,
+                Some(_) => {}
+                None => {
+                    if self.get(&task.id).is_some() {
+                        let old = std::mem::replace(&mut task.id, self.next_id());
+                        renamed.insert(old, task.id.clone());
+                    }
+                    added.insert(task.id.clone());
+                }
+            }
+            if added.contains(&task.id)
+                && let Some(parent) = task.parent.as_ref().and_then(|p| renamed.get(p))
+            {
+                task.parent = Some(parent.clone());
+            }
+            match self.get_mut(&task.id) {
+                Some(stored) => *stored = task,
+                None => self.tasks.push(task),
+            }
+        }
+    }
+
+    /// A short listing of the open tasks for the prompt, at most
+    /// `max_bytes` long, or `None` when nothing is open.
+    ///
+    /// Subtasks are indented under their open parent. When the listing
+    /// does not fit, it ends with a line counting the tasks left out.
+    pub fn summary(&self, max_bytes: usize) -> Option<String> {
+        let open: Vec<&Task> = self.open().collect();
+        if open.is_empty() {
+            return None;
+        }
+        let open_ids: HashSet<&str> = open.iter().map(|t| t.id.as_str()).collect();
+
+        // Depth-first order: each open root, then its open subtasks.
+        let mut ordered: Vec<(usize, &Task)> = Vec::with_capacity(open.len());
+        let mut stack: Vec<(usize, &Task)> = open
+            .iter()
+            .rev()
+            .filter(|t| t.parent.as_deref().is_none_or(|p| !open_ids.contains(p)))
+            .map(|t| (0, *t))
+            .collect();
+        while let Some((depth, task)) = stack.pop() {
+            ordered.push((depth, task));
+            stack.extend(
+                open.iter()
+                    .rev()
+                    .filter(|t| t.parent.as_deref() == Some(task.id.as_str()))
+                    .map(|t| (depth + 1, *t)),
+            );
+        }
+
+        let done = self.tasks.len() - open.len();
+        let mut out = format!(
+            "# Open Tasks\n\nFrom the task_list tool ({} open, {done} closed). \
+             Update tasks as you finish them.\n",
+            open.len()
+        );
+        for (shown, (depth, task)) in ordered.iter().enumerate() {
+            let mut line = format!(
+                "\n{}- {} {}: {}",
+                "  ".repeat(*depth),
+                task.status.marker(),
+                task.id,
+                task.title
+            );
+            if let Some(note) = task.notes.as_deref().and_then(|n| n.lines().next()) {
+                line.push_str(" -- ");
+                line.push_str(&truncate(note, 120));
+            }
+            let remaining = ordered.len() - shown;
+            let more = format!("\n... and {remaining} more open tasks (task_list list shows all)");
+            // Leave room for the "more" line unless this is the last task.
+            let reserve = if remaining > 1 { more.len() } else { 0 };
+            if out.len() + line.len() + reserve > max_bytes {
+                if out.len() + more.len() <= max_bytes {
+                    out.push_str(&more);
+                }
+                break;
+            }
+            out.push_str(&line);
+        }
+        Some(out)
+    }
+}
+
+/// `text` cut to at most `max` characters, with an ellipsis when cut.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Writer lock per task file, shared by every store in the process.
+fn file_lock(path: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<std::sync::Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+    let mut locks = LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    locks.entry(path.to_path_buf()).or_default().clone()
+}
+
+/// Reads and updates a workspace's task list.
+pub struct TaskStore<P: Platform> {
+    platform: Arc<P>,
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl<P: Platform> TaskStore<P> {
+    /// The task list of `workspace`.
+    pub fn new(platform: Arc<P>, workspace: &Path) -> Self {
+        Self::with_path(platform, tasks_path(workspace))
+    }
+
+    /// A task list stored at `path`.
+    pub fn with_path(platform: Arc<P>, path: PathBuf) -> Self {
+        let lock = file_lock(&path);
+        Self {
+            platform,
+            path,
+            lock,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the task list; empty when the file does not exist yet.
+    pub async fn load(&self) -> Result<TaskList, TaskError> {
+        let fs = self.platform.fs();
+        if !fs.exists(&self.path).await {
+            return Ok(TaskList::default());
+        }
+        let text = fs.read_to_string(&self.path).await?;
+        if text.trim().is_empty() {
+            return Ok(TaskList::default());
+        }
+        TaskList::parse(&text)
+    }
+
+    /// Change the task list with `f` and save it.
+    ///
+    /// `f` sees the current list. Nothing is written when it fails or
+    /// changes nothing. Otherwise the file is read again and the tasks `f`
+    /// changed are merged into it (see [`TaskList::merge`]) before it is
+    /// replaced. Returns `f`'s result and the saved list.
+    pub async fn update<T>(
+        &self,
+        f: impl FnOnce(&mut TaskList) -> Result<T, TaskError>,
+    ) -> Result<(T, TaskList), TaskError> {
+        let _guard = self.lock.lock().await;
+        let base = self.load().await?;
+        let mut ours = base.clone();
+        let out = f(&mut ours)?;
+        if ours == base {
+            return Ok((out, base));
+        }
+        let mut latest = self.load().await?;
+        latest.merge(&base, ours);
+        let json = serde_json::to_string_pretty(&latest)?;
+        self.platform.fs().write_atomic(&self.path, &json).await?;
+        Ok((out, latest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(titles: &[&str]) -> TaskList {
+        let mut list = TaskList::default();
+        for title in titles {
+            list.add(title, None, None).unwrap();
+        }
+        list
+    }
+
+    #[test]
+    fn add_assigns_sequential_ids_and_checks_parent() {
+        let mut tasks = list(&["plan", "build"]);
+        let sub = tasks
+            .add("parser", Some("2"), Some("start with CSV"))
+            .unwrap();
+        assert_eq!(sub, "3");
+        assert_eq!(tasks.get("3").unwrap().parent.as_deref(), Some("2"));
+        assert!(matches!(
+            tasks.add("orphan", Some("9"), None),
+            Err(TaskError::NotFound(_))
+        ));
+        assert!(matches!(
+            tasks.add("  ", None, None),
+            Err(TaskError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn merge_keeps_changes_from_both_writers() {
+        let base = list(&["plan", "build"]);
+
+        // Another writer completed task 1 and added task 3.
+        let mut latest = base.clone();
+        latest.get_mut("1").unwrap().status = TaskStatus::Done;
+        latest.add("deploy", None, None).unwrap();
+
+        // We started task 2 and added a task (also numbered 3) with a
+        // subtask, from the same base.
+        let mut ours = base.clone();
+        ours.get_mut("2").unwrap().status = TaskStatus::InProgress;
+        ours.add("test", None, None).unwrap();
+        ours.add("unit tests", Some("3"), None).unwrap();
+
+        latest.merge(&base, ours);
+        let titles: Vec<(&str, &str)> = latest
+            .tasks
+            .iter()
+            .map(|t| (t.id.as_str(), t.title.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                ("1", "plan"),
+                ("2", "build"),
+                ("3", "deploy"),
+                ("4", "test"),
+                ("5", "unit tests")
+            ]
+        );
+        assert_eq!(latest.get("1").unwrap().status, TaskStatus::Done);
+        assert_eq!(latest.get("2").unwrap().status, TaskStatus::InProgress);
+        assert_eq!(latest.get("5").unwrap().parent.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn merge_ignores_unchanged_tasks() {
+        let base = list(&["plan"]);
+        let mut latest = base.clone();
+        latest.get_mut("1").unwrap().notes = Some("from elsewhere".into());
+
+        latest.merge(&base, base.clone());
+        assert_eq!(
+            latest.get("1").unwrap().notes.as_deref(),
+            Some("from elsewhere")
+        );
+    }
+
+    #[test]
+    fn summary_nests_open_subtasks() {
+        let mut tasks = list(&["ship release", "write changelog"]);
+        tasks
+            .add("run tests", Some("1"), Some("cargo test\nthen clippy"))
+            .unwrap();
+        tasks.get_mut("1").unwrap().status = TaskStatus::InProgress;
+        tasks.get_mut("2").unwrap().status = TaskStatus::Done;
+
+        let summary = tasks.summary(SUMMARY_MAX_BYTES).unwrap();
+        assert!(summary.starts_with("# Open Tasks"));
+        assert!(summary.contains("(2 open, 1 closed)"));
+        assert!(summary.contains("\n- [~] 1: ship release\n  - [ ] 3: run tests -- cargo test"));
+        assert!(!summary.contains("changelog"));
+        assert!(!summary.contains("clippy"));
+
+        tasks.get_mut("1").unwrap().status = TaskStatus::Done;
+        tasks.get_mut("3").unwrap().status = TaskStatus::Cancelled;
+        assert_eq!(tasks.summary(SUMMARY_MAX_BYTES), None);
+    }
+
+    #[test]
+    fn summary_is_bounded() {
+        let titles: Vec<String> = (0..100)
+            .map(|i| format!("task number {i} with a longish title"))
+            .collect();
+        let titles: Vec<&str> = titles.iter().map(String::as_str).collect();
+        let tasks = list(&titles);
+
+        let summary = tasks.summary(600).unwrap();
+        assert!(summary.len() <= 600, "{} bytes", summary.len());
+        assert!(summary.contains("- [ ] 1: task number 0"));
+        assert!(summary.ends_with("more open tasks (task_list list shows all)"));
+
+        let shown = summary.matches("\n- [ ]").count();
+        assert!(summary.contains(&format!("... and {} more", 100 - shown)));
+
+        // Everything fits: no "more" line.
+        let small = list(&["one", "two"]).summary(600).unwrap();
+        assert!(!small.contains("more open tasks"));
+    }
+
+    #[tokio::test]
+    async fn parallel_updates_are_not_lost() {
+        use clawft_platform::NativePlatform;
+
+        let dir = std::env::temp_dir().join(format!("clawft_tasks_test_{}", std::process::id()));
+        let platform = Arc::new(NativePlatform::new());
+        let store = Arc::new(TaskStore::new(platform.clone(), &dir));
+
+        let handles: Vec<_> = (0..20)
+            .map(|i| {
+                // Separate stores for the same file share the writer lock.
+                let store = TaskStore::new(platform.clone(), &dir);
+                tokio::spawn(async move {
+                    store
+                        .update(|tasks| tasks.add(&format!("task {i}"), None, None))
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let tasks = store.load().await.unwrap();
+        assert_eq!(tasks.tasks.len(), 20);
+        let ids: HashSet<&str> = tasks.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids.len(), 20);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,490 @@
+//! Named prompt templates with `{{variable}}` substitution.
+//!
+//! The prompts the agent sends on its own behalf are templates that can be
+//! replaced without code changes:
+//!
+//! | Template            | Used for                                              | Extra variables |
+//! |---------------------|-------------------------------------------------------|-----------------|
+//! | `system`            | Default identity prompt (no `SOUL.md`/`IDENTITY.md`)  | --              |
+//! | `tool_error`        | Error text the model sees when a tool fails           | `tool`, `error` |
+//! | `summarizer`        | Instructions for history compaction                   | --              |
+//! | `result_summarizer` | Instructions for summarizing an oversized tool result | `tool`          |
+//! | `heartbeat`         | Message posted on each gateway heartbeat              | `prompt`        |
+//!
+//! Every template may use `{{agent}}`, `{{date}}`, `{{workspace}}`,
+//! `{{model}}`, and the user-defined `agents.templates.vars`. Templates
+//! are read from `<workspace>/templates/<name>.md`, falling back to the
+//! built-in defaults; an agent definition may override any of them by
+//! name. All templates are checked when they are loaded, so a misspelled
+//! variable fails startup instead of rendering as an empty string.
+//!
+//! # Escaping
+//!
+//! `\{{` in a template is a literal `{{`. Variable values are inserted as
+//! they are and never parsed again, so user content -- an error message,
+//! a config value -- cannot smuggle in template syntax. [`escape`] makes
+//! arbitrary text safe to embed in a template source.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use tracing::{debug, warn};
+
+use clawft_types::config::AgentsConfig;
+use clawft_types::{ClawftError, Result};
+
+use super::agents::AgentRegistry;
+
+/// Variables every template may use.
+pub const COMMON_VARIABLES: &[&str] = &["agent", "date", "workspace", "model"];
+
+/// Value of `{{agent}}` outside a named agent.
+pub const DEFAULT_AGENT_NAME: &str = "clawft";
+
+/// A template the agent renders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateName {
+    /// The default identity prompt.
+    System,
+    /// The error text the model sees when a tool fails.
+    ToolError,
+    /// Instructions for the history summarizer.
+    Summarizer,
+    /// Instructions for summarizing an oversized tool result.
+    ResultSummarizer,
+    /// The message posted on each heartbeat.
+    Heartbeat,
+}
+
+impl TemplateName {
+    /// All templates.
+    pub const ALL: [Self; 5] = [
+        Self::System,
+        Self::ToolError,
+        Self::Summarizer,
+        Self::ResultSummarizer,
+        Self::Heartbeat,
+    ];
+
+    /// The template's name, which is also its file stem.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::ToolError => "tool_error",
+            Self::Summarizer => "summarizer",
+            Self::ResultSummarizer => "result_summarizer",
+            Self::Heartbeat => "heartbeat",
+        }
+    }
+
+    /// Look a template up by name.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == name)
+    }
+
+    /// What the template is used for.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::System => "Default identity prompt",
+            Self::ToolError => "Error text the model sees when a tool fails",
+            Self::Summarizer => "Instructions for history compaction",
+            Self::ResultSummarizer => "Instructions for summarizing an oversized tool result",
+            Self::Heartbeat => "Message posted on each gateway heartbeat",
+        }
+    }
+
+    /// Variables this template may use besides [`COMMON_VARIABLES`].
+    pub fn variables(self) -> &'static [&'static str] {
+        match self {
+            Self::ToolError => &["tool", "error"],
+            Self::ResultSummarizer => &["tool"],
+            Self::Heartbeat => &["prompt"],
+            Self::System | Self::Summarizer => &[],
+        }
+    }
+
+    /// The built-in template.
+    pub fn default_source(self) -> &'static str {
+        match self {
+            Self::System => DEFAULT_SYSTEM,
+            Self::ToolError => "{{error}}",
+            Self::Summarizer => DEFAULT_SUMMARIZER,
+            Self::ResultSummarizer => DEFAULT_RESULT_SUMMARIZER,
+            Self::Heartbeat => "{{prompt}}",
+        }
+    }
+}
+
+const DEFAULT_SYSTEM: &str = "\
+# clawft
+
+You are clawft, a helpful AI assistant. You have access to tools that allow you to:
+- Read, write, and edit files
+- Execute shell commands
+- Search the web and fetch web pages
+- Send messages to users on chat channels
+
+## Configuration
+Model: {{model}}
+Workspace: {{workspace}}
+Memory: {{workspace}}/memory/MEMORY.md
+History: {{workspace}}/memory/HISTORY.md
+Skills: {{workspace}}/skills/";
+
+const DEFAULT_SUMMARIZER: &str = "You maintain the running summary of a conversation between \
+a user and an AI assistant. Merge the earlier summary, if any, with the new transcript into \
+one updated summary. Keep every fact, decision, open task and commitment the assistant made. \
+Drop greetings and raw tool output. Reply with the summary only.";
+
+const DEFAULT_RESULT_SUMMARIZER: &str = "The output of the `{{tool}}` tool is too long to pass \
+to the assistant that called it. Summarize it for that assistant: what the output contains, \
+the facts and numbers that answer the likely question, every error and warning, and the \
+identifiers (names, paths, IDs, URLs) needed to follow up. Reply with the summary only.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Var(String),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    /// Parse `source`. Fails on an unclosed `{{` or a placeholder that is
+    /// not a variable name (letters, digits and `_`).
+    pub fn parse(source: &str) -> std::result::Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("\\{{") {
+                text.push_str("{{");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("{{") {
+                let end = after
+                    .find("}}")
+                    .ok_or_else(|| "unclosed `{{`".to_string())?;
+                let name = after[..end].trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!("`{{{{{}}}}}` is not a variable", &after[..end]));
+                }
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Var(name.to_string()));
+                rest = &after[end + 2..];
+            } else {
+                let ch = rest.chars().next().unwrap_or_default();
+                text.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    /// The variables the template uses, in order of appearance.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|s| match s {
+            Segment::Var(name) => Some(name.as_str()),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Substitute `vars`. Values are inserted verbatim; a variable without
+    /// a value renders as an empty string.
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Var(name) => out.push_str(vars.get(name.as_str()).copied().unwrap_or("")),
+            }
+        }
+        out
+    }
+}
+
+/// Make `text` safe to embed in a template source: it renders as itself,
+/// with no placeholders.
+pub fn escape(text: &str) -> String {
+    text.replace("{{", "\\{{")
+}
+
+/// The templates in use, with each agent's overrides.
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    templates: HashMap<TemplateName, PromptTemplate>,
+    overrides: HashMap<String, HashMap<TemplateName, PromptTemplate>>,
+    vars: HashMap<String, String>,
+    workspace: String,
+    model: String,
+}
+
+impl PromptTemplates {
+    /// The built-in templates, with the variables of `config`.
+    pub fn builtin(config: &AgentsConfig) -> Self {
+        let templates = TemplateName::ALL
+            .into_iter()
+            .map(|name| {
+                let template =
+                    PromptTemplate::parse(name.default_source()).expect("built-in templates parse");
+                (name, template)
+            })
+            .collect();
+        Self {
+            templates,
+            overrides: HashMap::new(),
+            vars: config.templates.vars.clone(),
+            workspace: config.defaults.workspace.clone(),
+            model: config.defaults.model.clone(),
+        }
+    }
+
+    /// Load the templates in `dir` over the built-in ones, and the
+    /// overrides of each agent in `agents`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClawftError::ConfigInvalid`] when a template does not
+    /// parse or uses an unknown variable, or an agent overrides a template
+    /// that does not exist.
+    pub fn load(config: &AgentsConfig, dir: &Path, agents: Option<&AgentRegistry>) -> Result<Self> {
+        let mut templates = Self::builtin(config);
+        for name in TemplateName::ALL {
+            let path = dir.join(format!("{}.md", name.as_str()));
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let origin = path.display().to_string();
+            let template = templates.compile(name, &source, &origin)?;
+            debug!(template = name.as_str(), path = %origin, "loaded prompt template");
+            templates.templates.insert(name, template);
+        }
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                if path.extension().is_some_and(|e| e == "md")
+                    && TemplateName::parse(stem).is_none()
+                {
+                    warn!(path = %path.display(), "ignoring file that names no prompt template");
+                }
+            }
+        }
+
+        for agent in agents.map(|a| a.list()).unwrap_or_default() {
+            let mut overrides = HashMap::new();
+            for (key, source) in &agent.templates {
+                let origin = format!("agent '{}'", agent.name);
+                let name = TemplateName::parse(key).ok_or_else(|| ClawftError::ConfigInvalid {
+                    reason: format!(
+                        "{origin}: unknown template '{key}' (templates: {})",
+                        TemplateName::ALL.map(TemplateName::as_str).join(", ")
+                    ),
+                })?;
+                overrides.insert(name, templates.compile(name, source, &origin)?);
+            }
+            if !overrides.is_empty() {
+                templates.overrides.insert(agent.name.clone(), overrides);
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Parse `source` as template `name` and check its variables.
+    fn compile(&self, name: TemplateName, source: &str, origin: &str) -> Result<PromptTemplate> {
+        let invalid = |problem: String| ClawftError::ConfigInvalid {
+            reason: format!("{origin}: {} template: {problem}", name.as_str()),
+        };
+        let template = PromptTemplate::parse(source).map_err(invalid)?;
+        for var in template.variables() {
+            let known = COMMON_VARIABLES.contains(&var)
+                || name.variables().contains(&var)
+                || self.vars.contains_key(var);
+            if !known {
+                return Err(invalid(format!("unknown variable `{{{{{var}}}}}`")));
+            }
+        }
+        Ok(template)
+    }
+
+    /// Render template `name` for `agent` (`None` outside a named agent),
+    /// using the agent's override when it has one. `extra` supplies the
+    /// template's own variables.
+    pub fn render(
+        &self,
+        name: TemplateName,
+        agent: Option<&str>,
+        extra: &[(&str, &str)],
+    ) -> String {
+        let template = agent
+            .and_then(|agent| self.overrides.get(agent))
+            .and_then(|overrides| overrides.get(&name))
+            .unwrap_or_else(|| &self.templates[&name]);
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let mut vars: HashMap<&str, &str> = self
+            .vars
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        vars.insert("agent", agent.unwrap_or(DEFAULT_AGENT_NAME));
+        vars.insert("date", &date);
+        vars.insert("workspace", &self.workspace);
+        vars.insert("model", &self.model);
+        vars.extend(extra.iter().copied());
+        template.render(&vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::agents::AgentDefinition;
+
+    fn config() -> AgentsConfig {
+        let mut config = AgentsConfig::default();
+        config.defaults.workspace = "/ws".into();
+        config.defaults.model = "test/model".into();
+        config
+            .templates
+            .vars
+            .insert("company".into(), "Acme".into());
+        config
+    }
+
+    fn temp_dir(prefix: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-{prefix}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn agent(name: &str, templates: &[(&str, &str)]) -> AgentDefinition {
+        AgentDefinition {
+            name: name.into(),
+            description: String::new(),
+            model: None,
+            system_prompt: None,
+            skills: vec![],
+            allowed_tools: vec![],
+            max_turns: None,
+            variables: HashMap::new(),
+            templates: templates
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            source_path: None,
+        }
+    }
+
+    fn registry_of(agents: Vec<AgentDefinition>) -> AgentRegistry {
+        AgentRegistry::discover(None, None, agents).unwrap()
+    }
+
+    #[test]
+    fn builtins_render_with_common_variables() {
+        let templates = PromptTemplates::builtin(&config());
+        let system = templates.render(TemplateName::System, None, &[]);
+        assert!(system.starts_with("# clawft\n\nYou are clawft"));
+        assert!(system.contains("Model: test/model"));
+        assert!(system.contains("Memory: /ws/memory/MEMORY.md"));
+        assert_eq!(
+            templates.render(TemplateName::ToolError, None, &[("error", "boom")]),
+            "boom"
+        );
+    }
+
+    #[test]
+    fn workspace_files_and_agent_overrides_replace_builtins() {
+        let dir = temp_dir("templates");
+        std::fs::write(
+            dir.join("system.md"),
+            "You are {{ agent }} at {{company}}. Always answer in German. Today is {{date}}.",
+        )
+        .unwrap();
+        let registry = registry_of(vec![agent(
+            "support",
+            &[("tool_error", "{{tool}} failed: {{error}}")],
+        )]);
+
+        let templates = PromptTemplates::load(&config(), &dir, Some(&registry)).unwrap();
+        let system = templates.render(TemplateName::System, None, &[]);
+        assert!(
+            system.starts_with("You are clawft at Acme. Always answer in German. Today is 2"),
+            "{system}"
+        );
+        let extra = [("tool", "read_file"), ("error", "not found")];
+        assert_eq!(
+            templates.render(TemplateName::ToolError, Some("support"), &extra),
+            "read_file failed: not found"
+        );
+        assert_eq!(
+            templates.render(TemplateName::ToolError, Some("other"), &extra),
+            "not found"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unknown_variables_and_templates_are_rejected() {
+        let dir = temp_dir("templates-bad");
+        std::fs::write(dir.join("summarizer.md"), "Summarize for {{user_name}}.").unwrap();
+        let err = PromptTemplates::load(&config(), &dir, None).unwrap_err();
+        assert!(err.to_string().contains("`{{user_name}}`"), "{err}");
+
+        // `error` belongs to `tool_error`, not `heartbeat`.
+        let registry = registry_of(vec![agent("a", &[("heartbeat", "{{error}}")])]);
+        let empty = temp_dir("templates-empty");
+        let err = PromptTemplates::load(&config(), &empty, Some(&registry)).unwrap_err();
+        assert!(err.to_string().contains("agent 'a': heartbeat"), "{err}");
+
+        let registry = registry_of(vec![agent("a", &[("greeting", "hi")])]);
+        let err = PromptTemplates::load(&config(), &empty, Some(&registry)).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown template 'greeting'"),
+            "{err}"
+        );
+
+        assert!(PromptTemplate::parse("Hello {{name").is_err());
+        assert!(PromptTemplate::parse("Hello {{first name}}").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&empty);
+    }
+
+    #[test]
+    fn user_content_is_never_template_syntax() {
+        let templates = PromptTemplates::builtin(&config());
+        // A value that looks like a placeholder is inserted as it is.
+        let hostile = "no such file: {{workspace}} {{company}} \\{{model}}";
+        assert_eq!(
+            templates.render(TemplateName::ToolError, None, &[("error", hostile)]),
+            hostile
+        );
+
+        // Escaped text embedded in a template source renders as itself.
+        for text in [
+            "{{model}}",
+            "\\{{model}}",
+            "{{{x}}}",
+            "a {{ b }} c",
+            "plain",
+        ] {
+            let template = PromptTemplate::parse(&escape(text)).unwrap();
+            assert_eq!(template.variables().count(), 0, "{text}");
+            assert_eq!(template.render(&HashMap::new()), text);
+        }
+        let template = PromptTemplate::parse("literal \\{{model}} and {{model}}").unwrap();
+        assert_eq!(
+            template.render(&HashMap::from([("model", "m")])),
+            "literal {{model}} and m"
+        );
+    }
+}
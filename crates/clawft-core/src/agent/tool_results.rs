@@ -0,0 +1,520 @@
+//! Post-processing of tool results too large to hand to the model.
+//!
+//! A result whose JSON form is larger than `tools.results.max_bytes` is
+//! written whole to a spill file in `<workspace>/.clawft/tool-results/`,
+//! and the model gets a shortened version naming that file, so it can
+//! `read_file` the details (with `start_line`/`end_line` for big files).
+//! How the result is shortened is configured per tool:
+//!
+//! - [`ResultStrategy::Truncate`] cuts it at the limit
+//!   ([`truncate_result`]).
+//! - [`ResultStrategy::Structured`] keeps the shape of JSON ([`shape`]):
+//!   every object key, the first elements of each array and the start of
+//!   each string.
+//! - [`ResultStrategy::Summarize`] has a model summarize it. The agent loop
+//!   makes that call, with the prompt from [`summary_request`], and falls
+//!   back to `structured` when it fails.
+//!
+//! Text results are spilled as they are and JSON is pretty-printed, so
+//! the file reads well line by line.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use clawft_platform::Platform;
+use clawft_types::config::{ResultStrategy, ToolResultsConfig};
+
+use crate::pipeline::traits::LlmMessage;
+use crate::security::truncate_result;
+use crate::tools::registry::matches_any_pattern;
+
+/// Spill directory, relative to the workspace.
+pub const SPILL_DIR: &str = ".clawft/tool-results";
+
+/// Shortest [`shape`] cuts a string to, in characters.
+const MIN_STRING_CHARS: usize = 16;
+
+/// Tokens allowed for message framing in the summary prompt.
+const PROMPT_OVERHEAD_TOKENS: usize = 32;
+
+/// Bytes of result per token of the summarizer's input budget. Dense JSON
+/// runs close to this, so the input stays within the budget.
+const BYTES_PER_TOKEN: usize = 3;
+
+/// The strategy for `tool`: its exact entry in `tools.results.tools`, else
+/// the longest matching pattern, else the default strategy.
+pub fn strategy_for(config: &ToolResultsConfig, tool: &str) -> ResultStrategy {
+    if let Some(strategy) = config.tools.get(tool) {
+        return *strategy;
+    }
+    let mut patterns: Vec<_> = config
+        .tools
+        .iter()
+        .filter(|(pattern, _)| matches_any_pattern(tool, std::slice::from_ref(pattern)))
+        .collect();
+    patterns.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(b.0)));
+    patterns
+        .first()
+        .map_or(config.strategy, |(_, strategy)| **strategy)
+}
+
+/// Size of `value`'s JSON form, in bytes.
+pub fn json_len(value: &Value) -> usize {
+    serde_json::to_string(value).map_or(0, |s| s.len())
+}
+
+/// Shorten `value` to fit `max_bytes` while keeping its structure.
+///
+/// A string holding a JSON object or array is parsed first. Long strings
+/// and arrays are cut evenly, halving their limits until the result fits;
+/// if every object key still does not fit, containers below a shrinking
+/// depth are replaced by a note of their size. Cuts are marked in place,
+/// so the model knows what it is missing.
+pub fn shape(value: Value, max_bytes: usize) -> Value {
+    let value = match value {
+        Value::String(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(parsed @ (Value::Object(_) | Value::Array(_))) => parsed,
+            _ => Value::String(text),
+        },
+        other => other,
+    };
+    if json_len(&value) <= max_bytes {
+        return value;
+    }
+    if value.is_string() {
+        return truncate_result(value, max_bytes);
+    }
+
+    let mut limits = Limits {
+        chars: max_bytes,
+        items: longest_array(&value).max(1),
+        depth: depth(&value),
+    };
+    loop {
+        let shaped = limits.apply(&value, 0);
+        if json_len(&shaped) <= max_bytes {
+            return shaped;
+        }
+        if !limits.tighten() {
+            return truncate_result(shaped, max_bytes);
+        }
+    }
+}
+
+/// Cut limits for [`shape`].
+struct Limits {
+    /// Characters kept of each string.
+    chars: usize,
+    /// Elements kept of each array.
+    items: usize,
+    /// Nesting depth below which containers are replaced by a note.
+    depth: usize,
+}
+
+impl Limits {
+    /// Cut harder; `false` once nothing is left to cut.
+    fn tighten(&mut self) -> bool {
+        if self.chars > MIN_STRING_CHARS || self.items > 1 {
+            self.chars = (self.chars / 2).max(MIN_STRING_CHARS);
+            self.items = (self.items / 2).max(1);
+            true
+        } else if self.depth > 1 {
+            self.depth -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn apply(&self, value: &Value, level: usize) -> Value {
+        match value {
+            Value::String(text) => match text.char_indices().nth(self.chars) {
+                Some((end, _)) => {
+                    let rest = text[end..].chars().count();
+                    Value::String(format!("{}... [{rest} more chars]", &text[..end]))
+                }
+                None => value.clone(),
+            },
+            Value::Array(items) if level >= self.depth => {
+                Value::String(format!("[{} items]", items.len()))
+            }
+            Value::Object(map) if level >= self.depth => {
+                Value::String(format!("{{{} keys}}", map.len()))
+            }
+            Value::Array(items) => {
+                let mut kept: Vec<Value> = items
+                    .iter()
+                    .take(self.items)
+                    .map(|item| self.apply(item, level + 1))
+                    .collect();
+                if items.len() > self.items {
+                    kept.push(Value::String(format!(
+                        "... [{} more items]",
+                        items.len() - self.items
+                    )));
+                }
+                Value::Array(kept)
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), self.apply(value, level + 1)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+fn longest_array(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(longest_array)
+            .max()
+            .unwrap_or(0)
+            .max(items.len()),
+        Value::Object(map) => map.values().map(longest_array).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Write `value` to a new spill file and return its path relative to
+/// `workspace`, for `read_file`.
+///
+/// Files are named `<millis>-<tool>-<call id>`, so they sort oldest
+/// first; beyond `keep` files (`0` for no limit) the oldest are removed.
+pub async fn spill<P: Platform>(
+    platform: &P,
+    workspace: &Path,
+    tool: &str,
+    call_id: &str,
+    value: &Value,
+    keep: usize,
+) -> std::io::Result<PathBuf> {
+    let (content, extension) = match value {
+        Value::String(text) => (text.clone(), "txt"),
+        other => (
+            serde_json::to_string_pretty(other).map_err(std::io::Error::other)?,
+            "json",
+        ),
+    };
+    let name = format!(
+        "{}-{}-{}.{extension}",
+        crate::runtime::now_millis(),
+        file_safe(tool),
+        file_safe(call_id)
+    );
+    let relative = Path::new(SPILL_DIR).join(name);
+    let dir = workspace.join(SPILL_DIR);
+    let fs = platform.fs();
+    fs.create_dir_all(&dir).await?;
+    fs.write_string(&workspace.join(&relative), &content)
+        .await?;
+
+    if keep > 0 {
+        let mut files = fs.list_dir(&dir).await?;
+        files.sort();
+        let excess = files.len().saturating_sub(keep);
+        for old in &files[..excess] {
+            if let Err(e) = fs.remove_file(old).await {
+                tracing::debug!(path = %old.display(), error = %e, "could not remove spill file");
+            }
+        }
+    }
+    Ok(relative)
+}
+
+/// `text` with anything but ASCII letters, digits, `-` and `_` replaced,
+/// at most 64 characters.
+fn file_safe(text: &str) -> String {
+    text.chars()
+        .take(64)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// What the model gets in place of an oversized result, fitting
+/// `max_bytes`.
+///
+/// `content` is the result itself, shortened here with `strategy`, or for
+/// [`ResultStrategy::Summarize`] the summary text. `full_result` is the
+/// spill file, when it could be written.
+pub fn envelope(
+    strategy: ResultStrategy,
+    original_bytes: usize,
+    full_result: Option<&Path>,
+    content: Value,
+    max_bytes: usize,
+) -> Value {
+    let (key, note) = match strategy {
+        ResultStrategy::Summarize => ("summary", "Result summarized"),
+        _ => ("result", "Result shortened"),
+    };
+    let mut envelope = serde_json::json!({
+        "original_bytes": original_bytes,
+        "strategy": strategy,
+    });
+    envelope["note"] = match full_result {
+        Some(_) => format!(
+            "{note}; the full {original_bytes} bytes are in `full_result`, use read_file for the details."
+        ),
+        None => format!("{note}; the full result could not be saved."),
+    }
+    .into();
+    if let Some(path) = full_result {
+        envelope["full_result"] = path.display().to_string().into();
+    }
+    envelope[key] = Value::Null;
+
+    let budget = max_bytes.saturating_sub(json_len(&envelope));
+    envelope[key] = match strategy {
+        ResultStrategy::Structured => shape(content, budget),
+        _ => truncate_result(content, budget),
+    };
+    envelope
+}
+
+/// Build the summary prompt for `model`, leaving `max_summary_tokens` for
+/// the reply. `instructions` is the rendered `result_summarizer` template.
+///
+/// A result too big for the model's input budget is [`shape`]d to fit
+/// first.
+pub fn summary_request(
+    instructions: &str,
+    value: &Value,
+    model: &str,
+    max_summary_tokens: usize,
+) -> Vec<LlmMessage> {
+    let budget = clawft_llm::tokens::input_budget(model, max_summary_tokens).saturating_sub(
+        clawft_llm::tokens::count_text(instructions, model) + PROMPT_OVERHEAD_TOKENS,
+    );
+    let body = match shape(value.clone(), budget * BYTES_PER_TOKEN) {
+        Value::String(text) => text,
+        other => serde_json::to_string_pretty(&other).unwrap_or_default(),
+    };
+
+    let message = |role: &str, content: String| LlmMessage {
+        role: role.into(),
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        parts: None,
+        cache: false,
+    };
+    vec![
+        message("system", instructions.into()),
+        message("user", body),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::NativePlatform;
+    use serde_json::json;
+
+    fn nested() -> Value {
+        json!({
+            "repo": "clawft",
+            "issues": (0..200).map(|i| json!({
+                "number": i,
+                "title": format!("issue {i}"),
+                "body": "lorem ipsum ".repeat(100),
+                "labels": ["bug", "triage"],
+                "author": { "login": format!("user{i}"), "bio": "x".repeat(500) },
+            })).collect::<Vec<_>>(),
+            "meta": { "total": 200, "next": null, "query": { "state": "open" } },
+        })
+    }
+
+    #[test]
+    fn shape_keeps_keys_and_trims_values() {
+        let value = nested();
+        assert!(json_len(&value) > 100_000);
+
+        let shaped = shape(value, 8_000);
+        assert!(json_len(&shaped) <= 8_000, "{}", json_len(&shaped));
+
+        // Every key survives, at every level.
+        assert_eq!(shaped["repo"], "clawft");
+        assert_eq!(shaped["meta"]["query"]["state"], "open");
+        assert_eq!(shaped["meta"]["next"], Value::Null);
+        let issues = shaped["issues"].as_array().unwrap();
+        assert!(issues.len() > 1 && issues.len() < 200);
+        let first = &issues[0];
+        for key in ["number", "title", "body", "labels", "author"] {
+            assert!(first.get(key).is_some(), "missing {key}: {first}");
+        }
+        assert_eq!(first["number"], 0);
+        assert!(first["author"]["login"].is_string());
+
+        // Cuts are marked where they happen.
+        let marker = issues.last().unwrap().as_str().unwrap();
+        assert!(marker.starts_with("... ["), "{marker}");
+        assert!(marker.ends_with("more items]"), "{marker}");
+        let body = first["body"].as_str().unwrap();
+        assert!(body.starts_with("lorem ipsum"));
+        assert!(body.ends_with("more chars]"), "{body}");
+    }
+
+    #[test]
+    fn shape_parses_json_text_and_leaves_small_values() {
+        let small = json!({ "a": [1, 2, 3] });
+        assert_eq!(shape(small.clone(), 1_000), small);
+
+        let text = Value::String(nested().to_string());
+        let shaped = shape(text, 4_000);
+        assert!(shaped.is_object());
+        assert_eq!(shaped["repo"], "clawft");
+        assert!(json_len(&shaped) <= 4_000);
+    }
+
+    #[test]
+    fn shape_notes_deep_containers_when_keys_alone_do_not_fit() {
+        let wide: serde_json::Map<String, Value> = (0..50)
+            .map(|i| {
+                (
+                    format!("key{i:02}"),
+                    json!({ "inner": { "deep": [i, i, i] } }),
+                )
+            })
+            .collect();
+        let shaped = shape(Value::Object(wide), 1_200);
+        assert!(json_len(&shaped) <= 1_200, "{}", json_len(&shaped));
+        assert_eq!(shaped.as_object().unwrap().len(), 50);
+        assert_eq!(shaped["key00"], "{1 keys}");
+    }
+
+    #[test]
+    fn strategy_prefers_exact_names_over_patterns() {
+        let mut config = ToolResultsConfig::default();
+        config
+            .tools
+            .insert("mcp_*".into(), ResultStrategy::Structured);
+        config
+            .tools
+            .insert("mcp_github__*".into(), ResultStrategy::Summarize);
+        config
+            .tools
+            .insert("mcp_github__search".into(), ResultStrategy::Truncate);
+
+        assert_eq!(strategy_for(&config, "exec"), ResultStrategy::Truncate);
+        assert_eq!(
+            strategy_for(&config, "mcp_jira__get"),
+            ResultStrategy::Structured
+        );
+        // The more specific pattern wins.
+        assert_eq!(
+            strategy_for(&config, "mcp_github__issues"),
+            ResultStrategy::Summarize
+        );
+        assert_eq!(
+            strategy_for(&config, "mcp_github__search"),
+            ResultStrategy::Truncate
+        );
+    }
+
+    #[tokio::test]
+    async fn spill_writes_full_result_and_prunes_old_files() {
+        let workspace = std::env::temp_dir().join(format!(
+            "clawft_tool_results_{}_{}",
+            std::process::id(),
+            crate::runtime::now_millis()
+        ));
+        let platform = NativePlatform::new();
+
+        let value = nested();
+        let path = spill(
+            &platform,
+            &workspace,
+            "mcp_github__list",
+            "call/1",
+            &value,
+            2,
+        )
+        .await
+        .unwrap();
+        assert!(path.starts_with(SPILL_DIR));
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(name.ends_with("-mcp_github__list-call_1.json"), "{name}");
+        let written = std::fs::read_to_string(workspace.join(&path)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&written).unwrap(), value);
+        // Pretty-printed, so it can be read a few lines at a time.
+        assert!(written.lines().count() > 1000);
+
+        // Spill files sort by their millisecond timestamp.
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        let text = Value::String("line\n".repeat(10));
+        let text_path = spill(&platform, &workspace, "exec", "2", &text, 2)
+            .await
+            .unwrap();
+        assert!(text_path.to_str().unwrap().ends_with("-exec-2.txt"));
+        assert_eq!(
+            std::fs::read_to_string(workspace.join(&text_path)).unwrap(),
+            "line\n".repeat(10)
+        );
+
+        // A third file overflows `keep`, removing the oldest.
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        spill(&platform, &workspace, "exec", "3", &text, 2)
+            .await
+            .unwrap();
+        let files = std::fs::read_dir(workspace.join(SPILL_DIR))
+            .unwrap()
+            .count();
+        assert_eq!(files, 2);
+        assert!(!workspace.join(&path).exists());
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[test]
+    fn envelope_fits_and_names_the_spill_file() {
+        let value = nested();
+        let original = json_len(&value);
+        let spilled = Path::new(SPILL_DIR).join("1-gh-1.json");
+
+        let out = envelope(
+            ResultStrategy::Structured,
+            original,
+            Some(&spilled),
+            value.clone(),
+            4_096,
+        );
+        assert!(json_len(&out) <= 4_096);
+        assert_eq!(out["original_bytes"], original);
+        assert_eq!(out["strategy"], "structured");
+        assert_eq!(out["full_result"], ".clawft/tool-results/1-gh-1.json");
+        assert!(out["note"].as_str().unwrap().contains("read_file"));
+        assert_eq!(out["result"]["repo"], "clawft");
+
+        let summary = envelope(
+            ResultStrategy::Summarize,
+            original,
+            None,
+            "200 open issues".into(),
+            4_096,
+        );
+        assert_eq!(summary["summary"], "200 open issues");
+        assert!(summary.get("full_result").is_none());
+
+        let truncated = envelope(ResultStrategy::Truncate, original, None, value, 4_096);
+        assert!(json_len(&truncated) <= 4_096);
+        assert!(truncated["result"]["_truncated_json"].is_string());
+    }
+}
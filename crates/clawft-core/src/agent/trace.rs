@@ -0,0 +1,594 @@
+//! Per-turn traces for debugging.
+//!
+//! With `agents.sessions.trace` set, or [`InboundMessage::TRACE_KEY`] in a
+//! message's metadata, the agent loop records a [`TurnTrace`] while it
+//! answers the message: the model and settings it resolved, the context
+//! messages it sent (with their sizes), the automatic skill activation
+//! decision, every completion with its provider retries and failovers,
+//! every tool call with its duration, and the turn's total usage. The trace is written as one JSON document to
+//! `<sessions>/traces/<session>/turn-<n>.json`, where `n` counts the
+//! session's user messages.
+//!
+//! Tool arguments, tool errors and context previews go through the audit
+//! log's [`Redactor`]: credential-like fields are masked and long strings
+//! truncated, exactly as in `audit.jsonl`.
+//!
+//! [`InboundMessage::TRACE_KEY`]: clawft_types::event::InboundMessage::TRACE_KEY
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use clawft_platform::fs::FileSystem;
+use clawft_types::provider::{LlmResponse, Usage};
+use percent_encoding::{NON_ALPHANUMERIC, percent_encode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::pipeline::traits::ChatRequest;
+use crate::tools::audit::Redactor;
+use crate::tools::registry::ToolError;
+
+use super::context_budget::{self, ContextReport};
+use super::hooks::ToolCall;
+use super::skill_activation::SkillDecision;
+
+/// Directory under the sessions directory holding turn traces.
+pub const TRACES_DIR: &str = "traces";
+
+/// Everything the agent loop did to answer one message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnTrace {
+    /// Session the turn belongs to.
+    pub session_key: String,
+
+    /// Turn number: the count of user messages in the session, this one
+    /// included.
+    pub turn: usize,
+
+    /// Agent that answered.
+    pub agent_id: String,
+
+    /// When the turn started.
+    pub started_at: DateTime<Utc>,
+
+    /// Wall-clock time of the whole turn.
+    pub duration_ms: u64,
+
+    /// Settings the turn ran with.
+    pub settings: TraceSettings,
+
+    /// The automatic skill activation decision, when it ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<SkillDecision>,
+
+    /// The context sent with the first completion.
+    pub context: TraceContext,
+
+    /// Completions, in order.
+    pub completions: Vec<CompletionTrace>,
+
+    /// Tool calls, in the order they finished.
+    pub tool_calls: Vec<ToolCallTrace>,
+
+    /// Usage summed over all completions.
+    pub usage: Usage,
+
+    /// How the turn ended.
+    pub outcome: TurnOutcome,
+
+    /// The error that ended the turn, for [`TurnOutcome::Error`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Model and limits resolved for a turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSettings {
+    /// Model requested; the router may pick another (see
+    /// [`CompletionTrace::model`]).
+    pub model: String,
+
+    /// Maximum tokens per completion.
+    pub max_tokens: i32,
+
+    /// Sampling temperature.
+    pub temperature: f64,
+
+    /// Maximum tool-use iterations.
+    pub max_tool_iterations: usize,
+
+    /// The turn's tool allowlist, when it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+/// The context sent to the model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraceContext {
+    /// Input tokens available for the context.
+    pub budget: usize,
+
+    /// Tokens in the context after fitting it to the budget.
+    pub tokens: usize,
+
+    /// Tokens cut to fit the budget.
+    pub dropped_tokens: usize,
+
+    /// The messages sent, in order.
+    pub sections: Vec<ContextSection>,
+
+    /// Tools offered to the model.
+    pub tools: Vec<String>,
+}
+
+/// One message of the context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSection {
+    /// Message role.
+    pub role: String,
+
+    /// Length of the message text in characters.
+    pub chars: usize,
+
+    /// Estimated tokens.
+    pub tokens: usize,
+
+    /// The message text, truncated.
+    pub preview: String,
+}
+
+/// One completion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionTrace {
+    /// Tool-loop iteration (0-based).
+    pub iteration: usize,
+
+    /// Model that answered, when the provider reported it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Time until the completion finished.
+    pub duration_ms: u64,
+
+    /// Why the model stopped.
+    pub stop_reason: String,
+
+    /// Usage of this completion.
+    pub usage: Usage,
+
+    /// Failed attempts the provider retried.
+    #[serde(default)]
+    pub retries: u32,
+
+    /// Providers passed over before one answered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover: Vec<Value>,
+}
+
+/// One tool call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallTrace {
+    /// Tool-loop iteration (0-based) that issued the call.
+    pub iteration: usize,
+
+    /// Tool call ID.
+    pub id: String,
+
+    /// Tool name.
+    pub tool: String,
+
+    /// Redacted, truncated arguments.
+    pub args: Value,
+
+    /// Time the call took, hooks included.
+    pub duration_ms: u64,
+
+    /// Whether the call succeeded.
+    pub success: bool,
+
+    /// Truncated error message, on failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Size of the serialized result, on success.
+    pub result_bytes: u64,
+}
+
+/// How a turn ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnOutcome {
+    /// A reply was produced (possibly a budget or veto notice).
+    Completed,
+    /// The turn was cancelled.
+    Cancelled,
+    /// The turn failed with an error.
+    Error,
+}
+
+/// Collects a [`TurnTrace`] while a turn runs.
+///
+/// Recording methods take `&self` so concurrent tool calls can report
+/// into the same trace.
+pub struct TraceRecorder {
+    trace: Mutex<TurnTrace>,
+    redactor: Redactor,
+    started_ms: u64,
+}
+
+impl TraceRecorder {
+    /// Start tracing turn `turn` of `session_key`.
+    pub fn new(
+        session_key: &str,
+        turn: usize,
+        agent_id: &str,
+        settings: TraceSettings,
+        redactor: Redactor,
+    ) -> Self {
+        Self {
+            trace: Mutex::new(TurnTrace {
+                session_key: session_key.to_string(),
+                turn,
+                agent_id: agent_id.to_string(),
+                started_at: Utc::now(),
+                duration_ms: 0,
+                settings,
+                skill: None,
+                context: TraceContext::default(),
+                completions: Vec::new(),
+                tool_calls: Vec::new(),
+                usage: Usage::default(),
+                outcome: TurnOutcome::Completed,
+                error: None,
+            }),
+            redactor,
+            started_ms: crate::runtime::now_millis(),
+        }
+    }
+
+    /// Record the automatic skill activation decision.
+    pub fn skill(&self, decision: SkillDecision) {
+        self.lock().skill = Some(decision);
+    }
+
+    /// Record the context of the turn's first request.
+    pub fn context(&self, request: &ChatRequest, report: &ContextReport) {
+        let model = request.model.as_deref().unwrap_or_default();
+        let sections = request
+            .messages
+            .iter()
+            .map(|message| ContextSection {
+                role: message.role.clone(),
+                chars: message.content.chars().count(),
+                tokens: context_budget::message_tokens(message, model),
+                preview: self.truncate(&message.content),
+            })
+            .collect();
+        let tools = request
+            .tools
+            .iter()
+            .filter_map(|schema| schema.pointer("/function/name")?.as_str())
+            .map(str::to_string)
+            .collect();
+        self.lock().context = TraceContext {
+            budget: report.budget,
+            tokens: report.tokens,
+            dropped_tokens: report.dropped_tokens(),
+            sections,
+            tools,
+        };
+    }
+
+    /// Record a completion of tool-loop `iteration` that took
+    /// `duration_ms`.
+    pub fn completion(&self, iteration: usize, duration_ms: u64, response: &LlmResponse) {
+        let stop_reason = serde_json::to_value(response.stop_reason)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let completion = CompletionTrace {
+            iteration,
+            model: response
+                .metadata
+                .get("model")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            duration_ms,
+            stop_reason,
+            usage: response.usage,
+            retries: response
+                .metadata
+                .get("retries")
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as u32,
+            failover: response
+                .metadata
+                .get("failover")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let mut trace = self.lock();
+        let usage = &mut trace.usage;
+        usage.input_tokens += response.usage.input_tokens;
+        usage.output_tokens += response.usage.output_tokens;
+        usage.total_tokens += response.usage.total();
+        usage.cache_read_tokens += response.usage.cache_read_tokens;
+        usage.cache_write_tokens += response.usage.cache_write_tokens;
+        usage.reasoning_tokens += response.usage.reasoning_tokens;
+        trace.completions.push(completion);
+    }
+
+    /// Record `call`, issued in tool-loop `iteration`, which took
+    /// `duration_ms` and ended with `result`.
+    pub fn tool_call(
+        &self,
+        iteration: usize,
+        call: &ToolCall,
+        duration_ms: u64,
+        result: &Result<Value, ToolError>,
+    ) {
+        let (success, error, result_bytes) = match result {
+            Ok(value) => (
+                true,
+                None,
+                serde_json::to_string(value).map_or(0, |s| s.len() as u64),
+            ),
+            Err(e) => (false, Some(self.truncate(&e.to_string())), 0),
+        };
+        self.lock().tool_calls.push(ToolCallTrace {
+            iteration,
+            id: call.id.clone(),
+            tool: call.name.clone(),
+            args: self.redactor.summarize(&call.input),
+            duration_ms,
+            success,
+            error,
+            result_bytes,
+        });
+    }
+
+    /// Finish the trace with `outcome`.
+    pub fn finish(self, outcome: TurnOutcome, error: Option<String>) -> TurnTrace {
+        let elapsed = crate::runtime::now_millis().saturating_sub(self.started_ms);
+        let mut trace = self
+            .trace
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        trace.duration_ms = elapsed;
+        trace.outcome = outcome;
+        trace.error = error.map(|e| {
+            self.redactor
+                .summarize(&Value::String(e))
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        });
+        trace
+    }
+
+    fn truncate(&self, text: &str) -> String {
+        match self.redactor.summarize(&Value::String(text.to_string())) {
+            Value::String(s) => s,
+            _ => String::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TurnTrace> {
+        self.trace
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Directory holding the traces of session `key`.
+pub fn trace_dir(sessions_dir: &Path, key: &str) -> PathBuf {
+    let encoded = percent_encode(key.as_bytes(), NON_ALPHANUMERIC).to_string();
+    sessions_dir.join(TRACES_DIR).join(encoded)
+}
+
+/// Path of the trace of turn `turn` of session `key`.
+pub fn trace_path(sessions_dir: &Path, key: &str, turn: usize) -> PathBuf {
+    trace_dir(sessions_dir, key).join(format!("turn-{turn}.json"))
+}
+
+/// Write `trace` under `sessions_dir`, returning its path.
+pub async fn write_trace(
+    fs: &dyn FileSystem,
+    sessions_dir: &Path,
+    trace: &TurnTrace,
+) -> clawft_types::Result<PathBuf> {
+    let path = trace_path(sessions_dir, &trace.session_key, trace.turn);
+    fs.create_dir_all(&trace_dir(sessions_dir, &trace.session_key))
+        .await?;
+    fs.write_atomic(&path, &serde_json::to_string_pretty(trace)?)
+        .await?;
+    Ok(path)
+}
+
+/// Turn numbers with a trace for session `key`, in ascending order.
+pub async fn traced_turns(fs: &dyn FileSystem, sessions_dir: &Path, key: &str) -> Vec<usize> {
+    let mut turns: Vec<usize> = fs
+        .list_dir(&trace_dir(sessions_dir, key))
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| {
+            path.file_name()?
+                .to_str()?
+                .strip_prefix("turn-")?
+                .strip_suffix(".json")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    turns.sort_unstable();
+    turns
+}
+
+/// Read the trace of turn `turn` of session `key`, or of its latest traced
+/// turn when `turn` is `None`. `Ok(None)` when there is no such trace.
+pub async fn read_trace(
+    fs: &dyn FileSystem,
+    sessions_dir: &Path,
+    key: &str,
+    turn: Option<usize>,
+) -> clawft_types::Result<Option<TurnTrace>> {
+    let turn = match turn {
+        Some(turn) => turn,
+        None => match traced_turns(fs, sessions_dir, key).await.last() {
+            Some(&turn) => turn,
+            None => return Ok(None),
+        },
+    };
+    let path = trace_path(sessions_dir, key, turn);
+    if !fs.exists(&path).await {
+        return Ok(None);
+    }
+    let content = fs.read_to_string(&path).await?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use clawft_platform::NativePlatform;
+    use clawft_platform::Platform;
+    use clawft_types::provider::{ContentBlock, StopReason};
+
+    use super::*;
+    use crate::pipeline::traits::LlmMessage;
+
+    fn settings() -> TraceSettings {
+        TraceSettings {
+            model: "test-model".into(),
+            max_tokens: 1024,
+            temperature: 0.5,
+            max_tool_iterations: 4,
+            allowed_tools: None,
+        }
+    }
+
+    fn response(metadata: HashMap<String, Value>) -> LlmResponse {
+        LlmResponse {
+            id: "r1".into(),
+            content: vec![ContentBlock::Text { text: "ok".into() }],
+            stop_reason: StopReason::EndTurn,
+            usage: Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                ..Default::default()
+            },
+            metadata,
+        }
+    }
+
+    #[test]
+    fn recorder_redacts_and_truncates_like_the_audit_log() {
+        let recorder = TraceRecorder::new("cli:1", 1, "default", settings(), Redactor::default());
+        let call = ToolCall {
+            id: "call-1".into(),
+            name: "exec".into(),
+            input: serde_json::json!({ "command": "x".repeat(500), "api_key": "sk-123" }),
+        };
+        recorder.tool_call(
+            0,
+            &call,
+            12,
+            &Err(ToolError::ExecutionFailed("y".repeat(500))),
+        );
+        let request = ChatRequest {
+            messages: vec![LlmMessage {
+                role: "system".into(),
+                content: "z".repeat(1000),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: Some("test-model".into()),
+            max_tokens: None,
+            temperature: None,
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+        recorder.context(&request, &ContextReport::default());
+
+        let trace = recorder.finish(TurnOutcome::Completed, None);
+        let call = &trace.tool_calls[0];
+        assert_eq!(call.args["api_key"], crate::tools::audit::REDACTED);
+        assert!(call.args["command"].as_str().unwrap().len() < 500);
+        assert!(call.error.as_ref().unwrap().len() < 500);
+        assert!(!call.success);
+        let section = &trace.context.sections[0];
+        assert_eq!(section.chars, 1000);
+        assert!(section.preview.len() < 1000);
+    }
+
+    #[test]
+    fn completions_carry_provider_events_and_sum_usage() {
+        let recorder = TraceRecorder::new("cli:1", 1, "default", settings(), Redactor::default());
+        let mut metadata = HashMap::new();
+        metadata.insert("model".into(), serde_json::json!("openrouter/gpt-4o"));
+        metadata.insert("retries".into(), serde_json::json!(2));
+        metadata.insert(
+            "failover".into(),
+            serde_json::json!([{"provider": "openai", "model": "gpt-4o", "reason": "error"}]),
+        );
+        recorder.completion(0, 40, &response(metadata));
+        recorder.completion(1, 20, &response(HashMap::new()));
+
+        let trace = recorder.finish(TurnOutcome::Completed, None);
+        assert_eq!(trace.completions.len(), 2);
+        let first = &trace.completions[0];
+        assert_eq!(first.model.as_deref(), Some("openrouter/gpt-4o"));
+        assert_eq!(first.retries, 2);
+        assert_eq!(first.failover[0]["provider"], "openai");
+        assert_eq!(first.stop_reason, "end_turn");
+        assert_eq!(trace.usage.input_tokens, 20);
+        assert_eq!(trace.usage.output_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn traces_round_trip_and_latest_is_found() {
+        let platform = NativePlatform::new();
+        let dir = std::env::temp_dir().join(format!("clawft-trace-{}", uuid::Uuid::new_v4()));
+        for turn in [1, 3, 2] {
+            let recorder = TraceRecorder::new(
+                "telegram:42",
+                turn,
+                "default",
+                settings(),
+                Redactor::default(),
+            );
+            let trace = recorder.finish(TurnOutcome::Completed, None);
+            write_trace(platform.fs(), &dir, &trace).await.unwrap();
+        }
+
+        let fs = platform.fs();
+        assert_eq!(traced_turns(fs, &dir, "telegram:42").await, [1, 2, 3]);
+        let latest = read_trace(fs, &dir, "telegram:42", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.turn, 3);
+        let second = read_trace(fs, &dir, "telegram:42", Some(2))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.turn, 2);
+        assert!(
+            read_trace(fs, &dir, "telegram:42", Some(9))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(read_trace(fs, &dir, "other", None).await.unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,182 @@
+//! In-flight agent turns and their cancellation.
+//!
+//! Every turn the [`AgentLoop`](super::loop_core::AgentLoop) runs is
+//! registered in [`ActiveTurns`] under its session key, with a fresh
+//! [`CancellationToken`]. The loop checks the token between steps and
+//! abandons the pending LLM call or tool batch when it fires; tool futures
+//! are dropped, which kills subprocesses started with `kill_on_drop`.
+//!
+//! Turns are cancelled by a stop command sent to the same session (see
+//! [`is_stop_command`]) or, in the interactive CLI, by Ctrl-C via
+//! [`ActiveTurns::cancel_all`].
+//!
+//! When a turn ends, a [`TurnObserver`] attached to the loop gets a
+//! [`TurnReport`]; the gateway uses it to keep cron job run history.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use clawft_plugin::CancellationToken;
+use clawft_types::event::InboundMessage;
+
+use super::trace::TurnOutcome;
+
+/// Messages that cancel the session's in-flight turn (matched
+/// case-insensitively, ignoring surrounding whitespace).
+pub const STOP_COMMANDS: &[&str] = &["stop", "cancel", "/stop", "/cancel"];
+
+/// Whether `content` is a stop command.
+pub fn is_stop_command(content: &str) -> bool {
+    let content = content.trim();
+    STOP_COMMANDS
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(content))
+}
+
+/// Cancellation tokens of the turns currently running, by session key.
+#[derive(Default)]
+pub struct ActiveTurns {
+    turns: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl ActiveTurns {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a turn for `session_key`. It stays registered until the
+    /// returned guard is dropped.
+    pub fn begin(self: &Arc<Self>, session_key: &str) -> TurnGuard {
+        let token = CancellationToken::new();
+        if let Ok(mut turns) = self.turns.lock() {
+            turns.insert(session_key.to_string(), token.clone());
+        }
+        TurnGuard {
+            turns: self.clone(),
+            session_key: session_key.to_string(),
+            token,
+        }
+    }
+
+    /// Cancel the turn running for `session_key`. Returns `false` when
+    /// there is none.
+    pub fn cancel(&self, session_key: &str) -> bool {
+        let token = self
+            .turns
+            .lock()
+            .ok()
+            .and_then(|t| t.get(session_key).cloned());
+        match token {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every running turn. Returns how many there were.
+    pub fn cancel_all(&self) -> usize {
+        let Ok(turns) = self.turns.lock() else {
+            return 0;
+        };
+        for token in turns.values() {
+            token.cancel();
+        }
+        turns.len()
+    }
+
+    /// Whether a turn is running for `session_key`.
+    pub fn is_running(&self, session_key: &str) -> bool {
+        self.turns
+            .lock()
+            .map(|t| t.contains_key(session_key))
+            .unwrap_or(false)
+    }
+}
+
+/// A registered turn; unregisters it when dropped.
+pub struct TurnGuard {
+    turns: Arc<ActiveTurns>,
+    session_key: String,
+    token: CancellationToken,
+}
+
+impl TurnGuard {
+    /// The turn's cancellation token.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        if let Ok(mut turns) = self.turns.turns.lock() {
+            turns.remove(&self.session_key);
+        }
+    }
+}
+
+/// How a turn ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnReport {
+    /// When the turn started.
+    pub started_at: DateTime<Utc>,
+    /// When it ended.
+    pub finished_at: DateTime<Utc>,
+    /// [`TurnOutcome::Error`] when the turn failed or hit a budget limit.
+    pub outcome: TurnOutcome,
+    /// Why the turn failed.
+    pub error: Option<String>,
+    /// The reply (the budget notice when a limit was hit).
+    pub output: String,
+    /// Prompt tokens used by the turn's completions.
+    pub input_tokens: u64,
+    /// Completion tokens used by the turn's completions.
+    pub output_tokens: u64,
+}
+
+/// Told about every turn that reaches the model once it ends.
+///
+/// Called from the agent loop, so implementations must not block.
+pub trait TurnObserver: Send + Sync {
+    /// The turn answering `msg` ended.
+    fn on_turn_end(&self, msg: &InboundMessage, report: &TurnReport);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_commands() {
+        assert!(is_stop_command("stop"));
+        assert!(is_stop_command("  Cancel\n"));
+        assert!(is_stop_command("/STOP"));
+        assert!(!is_stop_command("stop the deployment"));
+        assert!(!is_stop_command("/stopwatch"));
+    }
+
+    #[test]
+    fn cancel_reaches_only_the_session_turn() {
+        let turns = Arc::new(ActiveTurns::new());
+        let a = turns.begin("telegram:a");
+        let b = turns.begin("telegram:b");
+
+        assert!(turns.cancel("telegram:a"));
+        assert!(a.token().is_cancelled());
+        assert!(!b.token().is_cancelled());
+        assert!(!turns.cancel("telegram:c"));
+
+        assert_eq!(turns.cancel_all(), 2);
+        assert!(b.token().is_cancelled());
+
+        drop(a);
+        assert!(!turns.is_running("telegram:a"));
+        assert!(turns.is_running("telegram:b"));
+        drop(b);
+        assert_eq!(turns.cancel_all(), 0);
+    }
+}
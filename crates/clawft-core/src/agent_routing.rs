@@ -11,11 +11,31 @@
 //! 3. If no route matches and no catch-all: reject with error.
 //! 4. Anonymous messages (empty `sender_id`) route to catch-all or
 //!    a dedicated "anonymous" agent with reduced permissions.
+//!
+//! # Routing rules
+//!
+//! [`RoutingRules`] evaluates `routing.rules`: ordered rules matching on
+//! channel, conversation and sender patterns, and a regex over the message
+//! text. The first matching rule decides the message's fate -- route it to
+//! an agent, drop it, or hold it until the sender confirms -- and is
+//! recorded as a [`RoutingTrace`].
 
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use clawft_types::agent_routing::{AgentRoute, AgentRoutingConfig};
+use clawft_types::error::ClawftError;
 use clawft_types::event::InboundMessage;
+use clawft_types::routing::{RoutingRule, RuleAction, RuleMatch};
+
+use crate::tools::registry::glob_matches;
+
+/// Session metadata key holding the [`RoutingTrace`] of the last rule
+/// that fired for the session. Inbound messages routed by a rule carry
+/// it under the same key.
+pub const ROUTING_TRACE_KEY: &str = "routing_trace";
 
 /// Result of routing an inbound message to an agent.
 #[non_exhaustive]
@@ -126,6 +146,126 @@ impl AgentRouter {
     }
 }
 
+// ── Routing rules ───────────────────────────────────────────────────────
+
+/// Record of a routing rule firing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingTrace {
+    /// Rule name, or `rules[<index>]` for unnamed rules.
+    pub rule: String,
+    /// `"route"`, `"drop"`, or `"confirm"`.
+    pub action: String,
+    /// Agent the message was handed to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// When the rule fired.
+    pub at: DateTime<Utc>,
+}
+
+/// The first rule matching a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleDecision<'a> {
+    /// Rule name, or `rules[<index>]` for unnamed rules.
+    pub rule: &'a str,
+    /// What to do with the message.
+    pub action: &'a RuleAction,
+}
+
+impl RuleDecision<'_> {
+    /// Trace of this decision, stamped `at`.
+    pub fn trace(&self, at: DateTime<Utc>) -> RoutingTrace {
+        RoutingTrace {
+            rule: self.rule.to_owned(),
+            action: self.action.kind().to_owned(),
+            agent: self.action.agent().map(str::to_owned),
+            at,
+        }
+    }
+}
+
+struct CompiledRule {
+    name: String,
+    matcher: RuleMatch,
+    content: Option<Regex>,
+    action: RuleAction,
+}
+
+impl CompiledRule {
+    fn matches(&self, msg: &InboundMessage) -> bool {
+        let m = &self.matcher;
+        m.channel.as_ref().is_none_or(|c| *c == msg.channel)
+            && m.chat
+                .as_ref()
+                .is_none_or(|p| glob_matches(p, &msg.chat_id))
+            && m.sender
+                .as_ref()
+                .is_none_or(|p| glob_matches(p, &msg.sender_id))
+            && self
+                .content
+                .as_ref()
+                .is_none_or(|re| re.is_match(&msg.content))
+    }
+}
+
+/// Compiled `routing.rules`, evaluated first-match-wins.
+pub struct RoutingRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl RoutingRules {
+    /// Compile the configured rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClawftError::ConfigInvalid`] if a `content` pattern is not
+    /// a valid regex.
+    pub fn compile(rules: &[RoutingRule]) -> Result<Self, ClawftError> {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                let content = rule
+                    .matcher
+                    .content
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| ClawftError::ConfigInvalid {
+                        reason: format!("routing.rules[{i}].match.content: {e}"),
+                    })?;
+                Ok(CompiledRule {
+                    name: rule.name.clone().unwrap_or_else(|| format!("rules[{i}]")),
+                    matcher: rule.matcher.clone(),
+                    content,
+                    action: rule.action.clone(),
+                })
+            })
+            .collect::<Result<_, ClawftError>>()?;
+        Ok(Self { rules })
+    }
+
+    /// The first rule matching `msg`, if any.
+    pub fn evaluate(&self, msg: &InboundMessage) -> Option<RuleDecision<'_>> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(msg))
+            .map(|rule| RuleDecision {
+                rule: &rule.name,
+                action: &rule.action,
+            })
+    }
+
+    /// Number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,6 +281,7 @@ mod tests {
             content: "test".into(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         }
     }
@@ -276,4 +417,123 @@ mod tests {
             RoutingResult::CatchAll("fallback".into())
         );
     }
+
+    /// Rule fixture: the examples from the routing rules documentation.
+    const RULES_FIXTURE: &str = r#"[
+        {"name": "oncall", "match": {"channel": "slack", "chat": "C0ONCALL*"},
+         "action": {"route": "sre"}},
+        {"name": "ceo-dm", "match": {"channel": "telegram", "sender": "ceo-*"},
+         "action": {"route": "exec-assistant"}},
+        {"name": "invoices", "match": {"content": "(?i)\\binvoices?\\b"},
+         "action": {"confirm": "finance"}},
+        {"match": {"channel": "email", "sender": "*@spam.example"},
+         "action": "drop"}
+    ]"#;
+
+    fn fixture_rules() -> RoutingRules {
+        let rules: Vec<RoutingRule> = serde_json::from_str(RULES_FIXTURE).unwrap();
+        RoutingRules::compile(&rules).unwrap()
+    }
+
+    fn message(channel: &str, sender_id: &str, chat_id: &str, content: &str) -> InboundMessage {
+        InboundMessage {
+            content: content.into(),
+            ..make_msg(channel, sender_id, chat_id)
+        }
+    }
+
+    #[test]
+    fn rules_first_match_wins() {
+        let rules = fixture_rules();
+        assert_eq!(rules.len(), 4);
+
+        // (channel, sender, chat, content, expected (rule, action))
+        type Case = (
+            &'static str,
+            &'static str,
+            &'static str,
+            &'static str,
+            Option<(&'static str, RuleAction)>,
+        );
+        let cases: [Case; 8] = [
+            (
+                "slack",
+                "u1",
+                "C0ONCALL",
+                "disk full",
+                Some(("oncall", RuleAction::Route("sre".into()))),
+            ),
+            // The earlier oncall rule wins over the invoice rule.
+            (
+                "slack",
+                "u1",
+                "C0ONCALL-eu",
+                "invoice server down",
+                Some(("oncall", RuleAction::Route("sre".into()))),
+            ),
+            (
+                "telegram",
+                "ceo-jane",
+                "dm-1",
+                "book a flight",
+                Some(("ceo-dm", RuleAction::Route("exec-assistant".into()))),
+            ),
+            (
+                "discord",
+                "u2",
+                "general",
+                "Where is the Invoice for March?",
+                Some(("invoices", RuleAction::Confirm("finance".into()))),
+            ),
+            // Word boundaries: "invoiced" alone is no match.
+            ("discord", "u2", "general", "we invoiced them", None),
+            (
+                "email",
+                "promo@spam.example",
+                "inbox",
+                "win big",
+                Some(("rules[3]", RuleAction::Drop)),
+            ),
+            ("email", "friend@example.com", "inbox", "hello", None),
+            // Channel must match exactly.
+            ("slack-dev", "u1", "C0ONCALL", "hi", None),
+        ];
+
+        for (channel, sender, chat, content, expected) in cases {
+            let msg = message(channel, sender, chat, content);
+            let got = rules.evaluate(&msg).map(|d| (d.rule, d.action.clone()));
+            assert_eq!(got, expected, "{channel}/{sender}/{chat}: {content:?}");
+        }
+    }
+
+    #[test]
+    fn rule_decision_trace() {
+        let rules = fixture_rules();
+        let msg = message("discord", "u2", "general", "invoice please");
+        let at = Utc::now();
+        let trace = rules.evaluate(&msg).unwrap().trace(at);
+        assert_eq!(
+            trace,
+            RoutingTrace {
+                rule: "invoices".into(),
+                action: "confirm".into(),
+                agent: Some("finance".into()),
+                at,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_content_regex_fails_to_compile() {
+        let rules = vec![RoutingRule {
+            name: None,
+            matcher: RuleMatch {
+                content: Some("(".into()),
+                ..Default::default()
+            },
+            action: RuleAction::Drop,
+        }];
+        let err = RoutingRules::compile(&rules).err().unwrap();
+        assert!(err.to_string().contains("routing.rules[0].match.content"));
+    }
 }
@@ -20,25 +20,37 @@
 
 use std::sync::Arc;
 
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use clawft_llm::usage::{PriceTable, UsageTracker};
 use clawft_platform::Platform;
 use clawft_types::config::Config;
 
+use crate::agent::agents::AgentRegistry;
+use crate::agent::budget::DailySpend;
 use crate::agent::context::ContextBuilder;
+use crate::agent::hooks::HookRegistry;
 use crate::agent::loop_core::{AgentLoop, AutoDelegation};
-use crate::agent::memory::MemoryStore;
+use crate::agent::memory::{MemoryBackend, MemoryStore};
+#[cfg(feature = "native")]
+use crate::agent::questions::PendingQuestions;
+use crate::agent::secrets::{SecretListTool, SecretUseTool, Secrets};
 use crate::agent::skills::SkillsLoader;
+use crate::agent::subagent::SpawnAgentTool;
+use crate::agent::templates::PromptTemplates;
 use crate::bus::MessageBus;
+use crate::config_reload::{ConfigReloader, LiveConfig, LiveSettings};
 use crate::pipeline::assembler::TokenBudgetAssembler;
 use crate::pipeline::classifier::KeywordClassifier;
 use crate::pipeline::cost_tracker::CostTracker;
 use crate::pipeline::rate_limiter::RateLimiter;
 use crate::pipeline::router::StaticRouter;
+use crate::pipeline::stages::StageRegistry;
 use crate::pipeline::tiered_router::TieredRouter;
 use crate::pipeline::traits::{ModelRouter, Pipeline, PipelineRegistry};
 use crate::pipeline::transport::OpenAiCompatTransport;
 use crate::session::SessionManager;
+use crate::tools::audit::ToolAuditor;
 use crate::tools::registry::ToolRegistry;
 
 /// Fully initialized application context.
@@ -68,17 +80,51 @@ pub struct AppContext<P: Platform> {
     /// Pipeline registry with all 6 stages wired.
     pipeline: PipelineRegistry,
 
+    /// Custom pipeline stages and `pipeline.stages` orders, handed to the
+    /// pipeline when the agent loop is built.
+    stages: StageRegistry,
+
     /// Context builder for assembling LLM prompts.
     context: ContextBuilder<P>,
 
     /// Shared memory store reference (for external access).
     memory: Arc<MemoryStore<P>>,
 
+    /// Memory backend selected by `agents.memory.backend`.
+    memory_backend: Arc<dyn MemoryBackend>,
+
     /// Shared skills loader reference (for external access).
     skills: Arc<SkillsLoader<P>>,
 
     /// Optional auto-delegation router for pre-LLM routing.
     auto_delegation: Option<Arc<dyn AutoDelegation>>,
+
+    /// Token usage and cost accounting shared with the agent loop.
+    usage: Arc<UsageTracker>,
+
+    /// Today's spend, when `agents.budget.max_cost_per_day_usd` is set.
+    daily_spend: Option<Arc<DailySpend>>,
+
+    /// Tool execution audit log, when enabled.
+    audit: Option<Arc<ToolAuditor>>,
+
+    /// Secrets from `tools.secrets` (and the deprecated
+    /// `tools.http.secrets`), when any are configured.
+    secrets: Option<Arc<Secrets>>,
+
+    /// Agent definitions, for per-agent tool allowlists.
+    agents: Option<Arc<AgentRegistry>>,
+
+    /// Settings a config reload may change (defaults, compiled
+    /// `routing.rules`, permissions, spawn depth).
+    live: Arc<LiveConfig>,
+
+    /// Hooks run around every tool call and completion.
+    hooks: HookRegistry,
+
+    /// Questions tools ask the user mid-turn, answered through dispatch.
+    #[cfg(feature = "native")]
+    questions: Arc<PendingQuestions>,
 }
 
 impl<P: Platform> AppContext<P> {
@@ -87,28 +133,39 @@ impl<P: Platform> AppContext<P> {
     /// This is the primary constructor. It:
     /// 1. Creates the [`MessageBus`]
     /// 2. Initializes the [`SessionManager`] (discovers/creates sessions dir)
-    /// 3. Initializes the [`MemoryStore`] (discovers memory dir)
+    /// 3. Initializes the [`MemoryStore`] (discovers memory dir) and the
+    ///    configured memory backend
     /// 4. Initializes the [`SkillsLoader`] (discovers skills dir)
     /// 5. Creates the [`ContextBuilder`]
     /// 6. Creates an empty [`ToolRegistry`] (caller registers tools after)
     /// 7. Wires the default Level 0 pipeline (keyword classifier, static
     ///    router, token budget assembler, stub transport, noop scorer,
-    ///    noop learner)
+    ///    noop learner) and checks the configured stage orders
     ///
     /// # Errors
     ///
-    /// Returns [`ClawftError`] if the home directory cannot be determined
-    /// or the sessions directory cannot be created.
-    pub async fn new(config: Config, platform: Arc<P>) -> clawft_types::Result<Self> {
+    /// Returns [`ClawftError`](clawft_types::ClawftError) if the home
+    /// directory cannot be determined, the sessions directory cannot be
+    /// created, or a built-in hook in `hooks` is misconfigured, or the
+    /// memory backend cannot be opened, or a `pipeline.stages` order
+    /// removes or reorders a built-in stage, or a prompt template uses an
+    /// unknown variable.
+    pub async fn new(config: Config, platform: Arc<P>) -> clawft_types::Result<Self>
+    where
+        P: 'static,
+    {
         info!("bootstrapping application context");
 
         // 1. Message bus
-        let bus = Arc::new(MessageBus::new());
+        let bus = Arc::new(MessageBus::with_capacity(
+            config.agents.dispatch.bus_capacity.max(1),
+        ));
         debug!("message bus created");
 
         // 2. Session manager
-        let sessions = SessionManager::new(platform.clone()).await?;
-        debug!("session manager initialized");
+        let sessions =
+            SessionManager::from_config(platform.clone(), &config.agents.sessions).await?;
+        debug!(backend = ?config.agents.sessions.backend, "session manager initialized");
 
         // 3. Memory store
         let memory = Arc::new(MemoryStore::new(platform.clone())?);
@@ -116,6 +173,9 @@ impl<P: Platform> AppContext<P> {
             memory_path = %memory.memory_path().display(),
             "memory store initialized"
         );
+        let memory_backend =
+            crate::agent::memory::open_backend(memory.clone(), &config.agents.memory).await?;
+        debug!(backend = ?config.agents.memory.backend, "memory backend opened");
 
         // 4. Skills loader
         let mut skills_loader = SkillsLoader::new(platform.clone())?;
@@ -144,15 +204,72 @@ impl<P: Platform> AppContext<P> {
             memory.clone(),
             skills.clone(),
             platform.clone(),
-        );
+        )
+        .with_memory_backend(memory_backend.clone());
         debug!("context builder created");
 
-        // 6. Tool registry (empty -- caller adds tools)
-        let tools = Arc::new(ToolRegistry::new());
+        // 6. Tool registry (empty -- caller adds tools; `spawn_agent` and
+        //    the secret tools are added below when configured)
+        let mut tools = ToolRegistry::new();
 
         // 7. Default Level 0 pipeline
         let pipeline = build_default_pipeline(&config);
-        debug!("default pipeline wired");
+        let stages = StageRegistry::from_config(&config.pipeline)?;
+        debug!(stages = ?stages.names(), "default pipeline wired");
+
+        // 8. Usage tracker and daily spend (both in the state directory)
+        let usage = Arc::new(build_usage_tracker(&config));
+        let daily_spend = build_daily_spend(&config);
+
+        // 9. Tool audit log (JSONL in the state directory, or the session
+        //    database with the SQLite backend)
+        let audit = build_tool_auditor(&config, sessions.sessions_dir());
+
+        // 10. Agent definitions (workspace and user `agents/` directories),
+        //     which `spawn_agent` can hand subtasks to
+        let agents = discover_agents(&config);
+        if let Some(agents) = &agents
+            && config.delegation.max_spawn_depth > 0
+        {
+            let names = agents.list().into_iter().map(|d| d.name.clone()).collect();
+            tools.register(Arc::new(SpawnAgentTool::new(names)));
+        }
+
+        // 10a. Named secrets, usable through `secret_use` without the
+        //      model seeing their values
+        let secrets = Secrets::from_config(&config.tools.all_secrets(), |name| {
+            platform.env().get_var(name)
+        });
+        let secrets = (!secrets.is_empty()).then(|| {
+            let secrets = Arc::new(secrets);
+            tools.register(Arc::new(SecretUseTool));
+            tools.register(Arc::new(SecretListTool::new(secrets.clone())));
+            secrets
+        });
+        let tools = Arc::new(tools);
+
+        // 10b. Prompt templates from the workspace `templates/` directory,
+        //      with the agents' overrides, checked for unknown variables
+        let templates = PromptTemplates::load(
+            &config.agents,
+            &config.workspace_path().join("templates"),
+            agents.as_deref(),
+        )?;
+        let context = context.with_templates(Arc::new(templates));
+
+        // 11. Reloadable settings, with the message routing rules checked
+        //     against the agent definitions
+        let live = Arc::new(LiveConfig::new(LiveSettings::from_config(
+            &config,
+            agents.as_deref(),
+        )?));
+
+        // 12. Built-in hooks enabled in `hooks` (caller may add more)
+        let hooks = HookRegistry::from_config(&config.hooks)?;
+        debug!(hooks = ?hooks.names(), "hooks registered");
+
+        #[cfg(feature = "native")]
+        let questions = Arc::new(PendingQuestions::new(bus.clone()));
 
         info!("bootstrap complete");
 
@@ -163,10 +280,21 @@ impl<P: Platform> AppContext<P> {
             sessions: Arc::new(sessions),
             tools,
             pipeline,
+            stages,
             context,
             memory,
+            memory_backend,
             skills,
             auto_delegation: None,
+            usage,
+            daily_spend,
+            audit,
+            secrets,
+            agents,
+            live,
+            hooks,
+            #[cfg(feature = "native")]
+            questions,
         })
     }
 
@@ -179,11 +307,17 @@ impl<P: Platform> AppContext<P> {
             &self.config.routing,
             None, // workspace config not yet supported
         );
+        let missing = self.stages.missing();
+        if !missing.is_empty() {
+            warn!(?missing, "configured pipeline stages are not registered");
+        }
+        let mut pipeline = self.pipeline;
+        pipeline.set_stages(self.stages);
         let mut agent = AgentLoop::new(
             self.config.agents,
             self.platform,
             self.bus,
-            self.pipeline,
+            pipeline,
             self.tools.clone(),
             self.context,
             self.sessions.clone(),
@@ -192,7 +326,38 @@ impl<P: Platform> AppContext<P> {
         if let Some(delegation) = self.auto_delegation {
             agent = agent.with_auto_delegation(delegation);
         }
+        if let Some(audit) = self.audit {
+            agent = agent.with_tool_audit(audit);
+        }
+        if let Some(secrets) = self.secrets {
+            agent = agent.with_secrets(secrets);
+        }
+        agent = agent.with_tool_results(self.config.tools.results.clone());
+        agent = agent.with_trace_redactor(crate::tools::audit::Redactor::new(
+            &self.config.tools.audit.redact_fields,
+        ));
+        if let Some(agents) = self.agents {
+            agent = agent.with_agents(agents);
+        }
+        if let Some(daily) = self.daily_spend {
+            agent = agent.with_daily_spend(daily);
+        }
+        #[cfg(feature = "native")]
+        {
+            agent = agent.with_questions(self.questions);
+        }
         agent
+            .with_hooks(self.hooks)
+            .with_usage_tracker(self.usage)
+            .with_live_config(self.live)
+    }
+
+    /// A [`ConfigReloader`] that applies re-read configs to the agent loop
+    /// this context becomes.
+    ///
+    /// Must be called BEFORE [`into_agent_loop`](Self::into_agent_loop).
+    pub fn config_reloader(&self) -> ConfigReloader {
+        ConfigReloader::new(self.config.clone(), self.live.clone(), self.agents.clone())
     }
 
     /// Get a reference to the root configuration.
@@ -213,6 +378,14 @@ impl<P: Platform> AppContext<P> {
         &self.bus
     }
 
+    /// Questions put to the user mid-turn, e.g. to approve an MCP server's
+    /// model call. The agent loop this context becomes delivers the
+    /// answers.
+    #[cfg(feature = "native")]
+    pub fn questions(&self) -> &Arc<PendingQuestions> {
+        &self.questions
+    }
+
     /// Get a mutable reference to the tool registry.
     ///
     /// Call this to register tools before converting to an agent loop.
@@ -225,6 +398,18 @@ impl<P: Platform> AppContext<P> {
         Arc::get_mut(&mut self.tools).expect("tools already shared -- register tools before cloning Arc")
     }
 
+    /// Get a mutable reference to the hook registry, to register hooks
+    /// before converting to an agent loop.
+    pub fn hooks_mut(&mut self) -> &mut HookRegistry {
+        &mut self.hooks
+    }
+
+    /// Get a mutable reference to the pipeline stage registry, to register
+    /// custom stages before converting to an agent loop.
+    pub fn pipeline_stages_mut(&mut self) -> &mut StageRegistry {
+        &mut self.stages
+    }
+
     /// Get a reference to the tool registry.
     pub fn tools(&self) -> &Arc<ToolRegistry> {
         &self.tools
@@ -245,11 +430,29 @@ impl<P: Platform> AppContext<P> {
         &self.memory
     }
 
+    /// Get the memory backend selected by `agents.memory.backend`.
+    ///
+    /// The memory tools should be built on this backend so that they and
+    /// the context builder see the same entries.
+    pub fn memory_backend(&self) -> &Arc<dyn MemoryBackend> {
+        &self.memory_backend
+    }
+
     /// Get a reference to the shared skills loader.
     pub fn skills(&self) -> &Arc<SkillsLoader<P>> {
         &self.skills
     }
 
+    /// Get the prompt templates, e.g. to render the heartbeat message.
+    pub fn templates(&self) -> &Arc<PromptTemplates> {
+        self.context.templates()
+    }
+
+    /// Get a reference to the shared usage tracker.
+    pub fn usage(&self) -> &Arc<UsageTracker> {
+        &self.usage
+    }
+
     /// Set an auto-delegation router for pre-LLM routing.
     ///
     /// When set, inbound messages are checked against delegation rules
@@ -296,6 +499,105 @@ pub fn build_live_pipeline(config: &Config) -> PipelineRegistry {
     crate::pipeline::llm_adapter::build_live_pipeline(config)
 }
 
+/// Build the usage tracker from `agents.usage`.
+///
+/// The ledger lives at `~/.clawft/state/usage.jsonl` on native targets
+/// when `agents.usage.ledger` is enabled; otherwise usage is only kept
+/// in memory.
+fn build_usage_tracker(config: &Config) -> UsageTracker {
+    let usage = &config.agents.usage;
+    let tracker = UsageTracker::new(PriceTable::with_overrides(&usage.pricing))
+        .with_flush_interval(std::time::Duration::from_secs(usage.flush_interval_secs));
+
+    #[cfg(feature = "native")]
+    if usage.ledger
+        && let Some(home) = dirs::home_dir()
+    {
+        let path = clawft_llm::usage::ledger_path(&home);
+        debug!(ledger = %path.display(), "usage ledger enabled");
+        return tracker.with_ledger(path);
+    }
+
+    tracker
+}
+
+/// Build the daily spend counter when `agents.budget` has a daily limit.
+///
+/// On native targets the total is kept in `~/.clawft/state/budget.json`
+/// so that it survives restarts.
+fn build_daily_spend(config: &Config) -> Option<Arc<DailySpend>> {
+    config.agents.budget.max_cost_per_day_usd?;
+    let daily = DailySpend::new();
+
+    #[cfg(feature = "native")]
+    if let Some(home) = dirs::home_dir() {
+        let path = home
+            .join(".clawft")
+            .join("state")
+            .join(crate::agent::budget::BUDGET_STATE_FILE);
+        debug!(path = %path.display(), "daily budget state enabled");
+        return Some(Arc::new(daily.with_state_file(path)));
+    }
+
+    Some(Arc::new(daily))
+}
+
+/// Build the tool audit log from `tools.audit`.
+///
+/// Native targets only; `None` when auditing is disabled or its store
+/// cannot be opened.
+fn build_tool_auditor(config: &Config, sessions_dir: &std::path::Path) -> Option<Arc<ToolAuditor>> {
+    #[cfg(feature = "native")]
+    {
+        use clawft_types::config::SessionBackend;
+
+        use crate::tools::audit::{Redactor, open_store};
+
+        let audit = &config.tools.audit;
+        let sqlite = config.agents.sessions.backend == SessionBackend::Sqlite;
+        let home = dirs::home_dir()?;
+        match open_store(audit, sqlite, sessions_dir, &home) {
+            Ok(store) => {
+                let redactor = Redactor::new(&audit.redact_fields);
+                store.map(|store| Arc::new(ToolAuditor::new(store, redactor)))
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "tool audit log unavailable");
+                None
+            }
+        }
+    }
+    #[cfg(not(feature = "native"))]
+    {
+        let _ = (config, sessions_dir);
+        None
+    }
+}
+
+/// Discover agent definitions from `<workspace>/agents` and
+/// `~/.clawft/agents`, so the agent loop can apply their tool allowlists.
+///
+/// Returns `None` when there are none or discovery fails.
+pub fn discover_agents(config: &Config) -> Option<Arc<AgentRegistry>> {
+    let workspace_dir = Some(config.workspace_path().join("agents")).filter(|d| d.is_dir());
+    #[cfg(feature = "native")]
+    let user_dir = dirs::home_dir().map(|h| h.join(".clawft").join("agents"));
+    #[cfg(not(feature = "native"))]
+    let user_dir: Option<std::path::PathBuf> = None;
+
+    match AgentRegistry::discover(workspace_dir.as_deref(), user_dir.as_deref(), Vec::new()) {
+        Ok(registry) if !registry.is_empty() => {
+            debug!(count = registry.len(), "agent definitions loaded");
+            Some(Arc::new(registry))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to discover agent definitions");
+            None
+        }
+    }
+}
+
 /// Build the default pipeline from configuration.
 ///
 /// Uses the appropriate router based on `config.routing.mode`:
@@ -361,9 +663,22 @@ mod tests {
                     model: "deepseek/deepseek-chat".into(),
                     max_tokens: 4096,
                     temperature: 0.7,
+                    top_p: None,
                     max_tool_iterations: 10,
                     memory_window: 50,
+                    max_parallel_tools: 4,
                 },
+                dispatch: Default::default(),
+                usage: Default::default(),
+                budget: Default::default(),
+                memory: Default::default(),
+                cache: Default::default(),
+                sessions: Default::default(),
+                compaction: Default::default(),
+                planning: Default::default(),
+                templates: Default::default(),
+                skills: Default::default(),
+                interactive: Default::default(),
             },
             ..Config::default()
         }
@@ -410,6 +725,24 @@ mod tests {
         assert!(ctx.tools().is_empty());
     }
 
+    #[tokio::test]
+    async fn secrets_register_secret_tools() {
+        let mut config = test_config();
+        config.tools.secrets.insert(
+            "github".into(),
+            clawft_types::config::SecretRef {
+                value: "ghp_abc123".into(),
+                tools: vec!["write_file".into()],
+                ..Default::default()
+            },
+        );
+        let platform = Arc::new(NativePlatform::new());
+        let ctx = AppContext::new(config, platform).await.unwrap();
+        assert!(ctx.tools().has("secret_use"));
+        assert!(ctx.tools().has("secret_list"));
+        assert!(ctx.secrets.is_some());
+    }
+
     #[tokio::test]
     async fn tools_mut_allows_registration() {
         let platform = Arc::new(NativePlatform::new());
@@ -524,6 +857,7 @@ mod tests {
             content: "hello".into(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         assert!(tx.send(msg).await.is_ok());
@@ -560,10 +894,13 @@ mod tests {
                 content: "test".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             max_tokens: Some(10),
             temperature: Some(0.0),
+            cache: false,
         };
 
         let result = pipeline.transport.complete(&transport_req).await;
@@ -622,10 +959,13 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            cache: false,
         };
 
         let result = pipeline.transport.complete(&transport_req).await;
@@ -7,11 +7,21 @@
 //! On native, uses tokio bounded MPSC channels.
 //! On browser/WASM, uses futures-channel unbounded MPSC channels.
 //!
+//! When the inbound queue is full, [`MessageBus::publish`] applies the
+//! producer's [`OverflowPolicy`]: messages from users wait for room, while
+//! heartbeat and cron events are shed and counted, since the next tick
+//! produces them again. [`MessageBus::stats`] reports queue depths and
+//! the shed counts; on native, [`watch_backpressure`] logs a warning while
+//! the inbound queue stays nearly full.
+//!
 //! Ported from Python `nanobot/bus/queue.py`.
 
+use std::collections::HashMap;
+
 #[cfg(feature = "native")]
 use tokio::sync::Mutex;
 
+use serde::Serialize;
 use tracing::debug;
 
 use clawft_types::error::ClawftError;
@@ -21,6 +31,54 @@ use clawft_types::event::{InboundMessage, OutboundMessage};
 #[cfg(feature = "native")]
 const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
 
+/// Channels whose inbound messages are shed when the bus is full.
+pub const SHEDDABLE_CHANNELS: &[&str] = &["heartbeat", "cron"];
+
+/// What [`MessageBus::publish`] does with a message when the inbound
+/// queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for room. Messages from users are never dropped.
+    Block,
+    /// Drop the message and count it in [`BusStats::shed`].
+    Shed,
+}
+
+impl OverflowPolicy {
+    /// Policy for messages published on `channel`.
+    pub fn for_channel(channel: &str) -> Self {
+        if SHEDDABLE_CHANNELS.contains(&channel) {
+            Self::Shed
+        } else {
+            Self::Block
+        }
+    }
+}
+
+/// Outcome of [`MessageBus::publish`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// The message is on the inbound queue.
+    Queued,
+    /// The queue was full and the message was dropped.
+    Shed,
+}
+
+/// Snapshot of the bus queues.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BusStats {
+    /// Capacity of each queue; 0 when the queues are unbounded.
+    pub capacity: usize,
+    /// Messages waiting on the inbound queue.
+    pub inbound_depth: usize,
+    /// Messages waiting on the outbound queue.
+    pub outbound_depth: usize,
+    /// Publishes that had to wait for room on the inbound queue.
+    pub blocked_sends: u64,
+    /// Messages shed because the inbound queue was full, by channel.
+    pub shed: HashMap<String, u64>,
+}
+
 // ---------------------------------------------------------------------------
 // Native implementation (tokio channels)
 // ---------------------------------------------------------------------------
@@ -31,6 +89,8 @@ pub struct MessageBus {
     inbound_rx: Mutex<tokio::sync::mpsc::Receiver<InboundMessage>>,
     outbound_tx: tokio::sync::mpsc::Sender<OutboundMessage>,
     outbound_rx: Mutex<tokio::sync::mpsc::Receiver<OutboundMessage>>,
+    blocked_sends: std::sync::atomic::AtomicU64,
+    shed: std::sync::Mutex<HashMap<String, u64>>,
 }
 
 #[cfg(feature = "native")]
@@ -55,9 +115,89 @@ impl MessageBus {
             inbound_rx: Mutex::new(inbound_rx),
             outbound_tx,
             outbound_rx: Mutex::new(outbound_rx),
+            blocked_sends: Default::default(),
+            shed: Default::default(),
         }
     }
 
+    /// Publish an inbound message under its channel's [`OverflowPolicy`].
+    ///
+    /// When the inbound queue is full, messages from users wait for room
+    /// and heartbeat/cron events are dropped with a warning. Returns an
+    /// error only if the channel is closed.
+    pub async fn publish(&self, msg: InboundMessage) -> Result<Delivery, ClawftError> {
+        use std::sync::atomic::Ordering;
+        use tokio::sync::mpsc::error::TrySendError;
+
+        let msg = match self.inbound_tx.try_send(msg) {
+            Ok(()) => return Ok(Delivery::Queued),
+            Err(TrySendError::Closed(_)) => {
+                return Err(ClawftError::Channel("inbound channel closed".into()));
+            }
+            Err(TrySendError::Full(msg)) => msg,
+        };
+
+        match OverflowPolicy::for_channel(&msg.channel) {
+            OverflowPolicy::Shed => {
+                tracing::warn!(
+                    channel = %msg.channel,
+                    chat_id = %msg.chat_id,
+                    capacity = self.inbound_tx.max_capacity(),
+                    "inbound queue full, shedding event"
+                );
+                if let Ok(mut shed) = self.shed.lock() {
+                    *shed.entry(msg.channel).or_default() += 1;
+                }
+                Ok(Delivery::Shed)
+            }
+            OverflowPolicy::Block => {
+                self.blocked_sends.fetch_add(1, Ordering::Relaxed);
+                debug!(
+                    channel = %msg.channel,
+                    chat_id = %msg.chat_id,
+                    "inbound queue full, waiting for room"
+                );
+                self.inbound_tx
+                    .send(msg)
+                    .await
+                    .map_err(|e| ClawftError::Channel(format!("inbound channel closed: {e}")))?;
+                Ok(Delivery::Queued)
+            }
+        }
+    }
+
+    /// Current queue depths and overflow counters.
+    pub fn stats(&self) -> BusStats {
+        BusStats {
+            capacity: self.inbound_tx.max_capacity(),
+            inbound_depth: self.inbound_tx.max_capacity() - self.inbound_tx.capacity(),
+            outbound_depth: self.outbound_tx.max_capacity() - self.outbound_tx.capacity(),
+            blocked_sends: self
+                .blocked_sends
+                .load(std::sync::atomic::Ordering::Relaxed),
+            shed: self.shed.lock().map(|m| m.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Sender for internal event producers such as heartbeat and cron.
+    ///
+    /// Messages sent on it are forwarded through [`publish`](Self::publish),
+    /// so they follow their channel's overflow policy and show up in
+    /// [`stats`](Self::stats). The forwarding task ends once every clone
+    /// of the sender is dropped.
+    pub fn event_sender(self: &std::sync::Arc<Self>) -> tokio::sync::mpsc::Sender<InboundMessage> {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(EVENT_FORWARD_CAPACITY);
+        let bus = self.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if bus.publish(msg).await.is_err() {
+                    break;
+                }
+            }
+        });
+        tx
+    }
+
     /// Publish an inbound message (from a channel adapter) to the bus.
     ///
     /// Uses `try_send` to avoid requiring the caller to be async.
@@ -168,6 +308,115 @@ impl MessageBus {
     }
 }
 
+/// Buffer between event producers and [`MessageBus::event_sender`]'s
+/// forwarding task.
+#[cfg(feature = "native")]
+const EVENT_FORWARD_CAPACITY: usize = 16;
+
+/// Fraction of the inbound capacity at which the queue counts as backed up.
+const BACKPRESSURE_THRESHOLD: f64 = 0.8;
+
+/// A change reported by [`BackpressureMonitor::observe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// The inbound queue has been backed up for `for_duration`.
+    Sustained {
+        /// How long the queue has been backed up.
+        for_duration: std::time::Duration,
+    },
+    /// The queue drained after a reported [`Sustained`](Self::Sustained).
+    Recovered,
+}
+
+/// Detects an inbound queue that stays at least 80% full.
+///
+/// Fed with periodic depth samples; reports once the queue has been backed
+/// up for `sustain`, again every further `sustain` it stays so, and once
+/// when it drains.
+#[derive(Debug)]
+pub struct BackpressureMonitor {
+    sustain: std::time::Duration,
+    high_since: Option<std::time::Duration>,
+    last_report: Option<std::time::Duration>,
+}
+
+impl BackpressureMonitor {
+    /// Create a monitor that reports after `sustain` of backpressure.
+    pub fn new(sustain: std::time::Duration) -> Self {
+        Self {
+            sustain,
+            high_since: None,
+            last_report: None,
+        }
+    }
+
+    /// Record a sample taken at `now` (time since any fixed origin).
+    pub fn observe(
+        &mut self,
+        depth: usize,
+        capacity: usize,
+        now: std::time::Duration,
+    ) -> Option<Backpressure> {
+        let backed_up = capacity > 0 && depth as f64 >= capacity as f64 * BACKPRESSURE_THRESHOLD;
+        if !backed_up {
+            self.high_since = None;
+            return self.last_report.take().map(|_| Backpressure::Recovered);
+        }
+
+        let since = *self.high_since.get_or_insert(now);
+        let due = match self.last_report {
+            Some(last) => now.saturating_sub(last) >= self.sustain,
+            None => now.saturating_sub(since) >= self.sustain,
+        };
+        if !due {
+            return None;
+        }
+        self.last_report = Some(now);
+        Some(Backpressure::Sustained {
+            for_duration: now.saturating_sub(since),
+        })
+    }
+}
+
+/// Sample the bus every second and log sustained inbound backpressure.
+///
+/// Logs a structured warning with the [`BusStats`] once the inbound queue
+/// has stayed at least 80% full for `sustain` (and every `sustain` after
+/// that), and an info line when it drains. Returns when `cancel` fires.
+#[cfg(feature = "native")]
+pub async fn watch_backpressure(
+    bus: &MessageBus,
+    sustain: std::time::Duration,
+    cancel: &clawft_plugin::CancellationToken,
+) {
+    let start = tokio::time::Instant::now();
+    let mut monitor = BackpressureMonitor::new(sustain);
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            _ = ticker.tick() => {}
+        }
+        let stats = bus.stats();
+        match monitor.observe(stats.inbound_depth, stats.capacity, start.elapsed()) {
+            Some(Backpressure::Sustained { for_duration }) => tracing::warn!(
+                inbound_depth = stats.inbound_depth,
+                outbound_depth = stats.outbound_depth,
+                capacity = stats.capacity,
+                blocked_sends = stats.blocked_sends,
+                shed = ?stats.shed,
+                sustained_secs = for_duration.as_secs(),
+                "sustained message bus backpressure"
+            ),
+            Some(Backpressure::Recovered) => tracing::info!(
+                inbound_depth = stats.inbound_depth,
+                "message bus backpressure cleared"
+            ),
+            None => {}
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Browser implementation (futures-channel)
 // ---------------------------------------------------------------------------
@@ -235,6 +484,18 @@ impl MessageBus {
         })
     }
 
+    /// Publish an inbound message. The browser queues are unbounded, so
+    /// the message is always queued.
+    pub async fn publish(&self, msg: InboundMessage) -> Result<Delivery, ClawftError> {
+        self.publish_inbound_async(msg).await?;
+        Ok(Delivery::Queued)
+    }
+
+    /// Queue statistics. Unbounded queues report no depths.
+    pub fn stats(&self) -> BusStats {
+        BusStats::default()
+    }
+
     /// Consume the next inbound message from the bus.
     pub async fn consume_inbound(&self) -> Option<InboundMessage> {
         use futures_util::StreamExt;
@@ -317,6 +578,16 @@ impl MessageBus {
         Err(ClawftError::Channel("no channel backend available".into()))
     }
 
+    /// Always returns an error (no channel backend).
+    pub async fn publish(&self, _msg: InboundMessage) -> Result<Delivery, ClawftError> {
+        Err(ClawftError::Channel("no channel backend available".into()))
+    }
+
+    /// Empty statistics (no channel backend).
+    pub fn stats(&self) -> BusStats {
+        BusStats::default()
+    }
+
     /// Always returns `None` (no channel backend).
     pub async fn consume_inbound(&self) -> Option<InboundMessage> {
         None
@@ -361,6 +632,7 @@ mod tests {
             content: content.into(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         }
     }
@@ -372,6 +644,7 @@ mod tests {
             content: content.into(),
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         }
     }
@@ -595,4 +868,162 @@ mod tests {
         assert_eq!(msg_b.content, "b");
         assert_eq!(msg_c.content, "c");
     }
+
+    fn make_event(channel: &str, content: &str) -> InboundMessage {
+        InboundMessage {
+            channel: channel.into(),
+            chat_id: channel.into(),
+            ..make_inbound(content)
+        }
+    }
+
+    #[test]
+    fn overflow_policy_sheds_only_internal_events() {
+        assert_eq!(
+            OverflowPolicy::for_channel("heartbeat"),
+            OverflowPolicy::Shed
+        );
+        assert_eq!(OverflowPolicy::for_channel("cron"), OverflowPolicy::Shed);
+        for channel in ["telegram", "slack", "api", "cli", "test"] {
+            assert_eq!(OverflowPolicy::for_channel(channel), OverflowPolicy::Block);
+        }
+    }
+
+    #[test]
+    fn backpressure_monitor_reports_sustained_and_recovery() {
+        let secs = std::time::Duration::from_secs;
+        let mut m = BackpressureMonitor::new(secs(10));
+
+        // Below 80% full: nothing to report.
+        assert_eq!(m.observe(7, 10, secs(0)), None);
+        // Backed up, but not for long enough yet.
+        assert_eq!(m.observe(8, 10, secs(1)), None);
+        assert_eq!(m.observe(10, 10, secs(10)), None);
+        assert_eq!(
+            m.observe(9, 10, secs(11)),
+            Some(Backpressure::Sustained {
+                for_duration: secs(10)
+            })
+        );
+        // Reported again only after another full period.
+        assert_eq!(m.observe(9, 10, secs(15)), None);
+        assert_eq!(
+            m.observe(9, 10, secs(21)),
+            Some(Backpressure::Sustained {
+                for_duration: secs(20)
+            })
+        );
+        assert_eq!(m.observe(2, 10, secs(22)), Some(Backpressure::Recovered));
+        assert_eq!(m.observe(2, 10, secs(23)), None);
+
+        // A short spike that drains before being reported stays quiet.
+        assert_eq!(m.observe(9, 10, secs(30)), None);
+        assert_eq!(m.observe(0, 10, secs(31)), None);
+        // Unbounded queues never report.
+        assert_eq!(m.observe(100, 0, secs(100)), None);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn publish_sheds_events_but_blocks_user_messages_under_load() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let bus = Arc::new(MessageBus::with_capacity(4));
+
+        // Users and internal events publish concurrently into a small queue.
+        let users = {
+            let bus = bus.clone();
+            tokio::spawn(async move {
+                for i in 0..40 {
+                    let delivery = bus.publish(make_inbound(&format!("user-{i}"))).await;
+                    assert_eq!(delivery.unwrap(), Delivery::Queued);
+                }
+            })
+        };
+        let events = {
+            let bus = bus.clone();
+            tokio::spawn(async move {
+                let mut shed = 0u64;
+                for i in 0..40 {
+                    let channel = if i % 2 == 0 { "heartbeat" } else { "cron" };
+                    let delivery = bus.publish(make_event(channel, &format!("{channel}-{i}")));
+                    if delivery.await.unwrap() == Delivery::Shed {
+                        shed += 1;
+                    }
+                    tokio::task::yield_now().await;
+                }
+                shed
+            })
+        };
+
+        // Slow consumer: reads until both producers are done and the queue
+        // has stayed empty for a while.
+        let mut received = Vec::new();
+        loop {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            match tokio::time::timeout(Duration::from_millis(100), bus.consume_inbound()).await {
+                Ok(Some(msg)) => received.push(msg),
+                _ if users.is_finished() && events.is_finished() => break,
+                _ => {}
+            }
+        }
+        users.await.unwrap();
+        let events_shed = events.await.unwrap();
+
+        // Every user message arrived, in order.
+        let user_msgs: Vec<&str> = received
+            .iter()
+            .filter(|m| m.channel == "test")
+            .map(|m| m.content.as_str())
+            .collect();
+        let expected: Vec<String> = (0..40).map(|i| format!("user-{i}")).collect();
+        assert_eq!(user_msgs, expected);
+
+        // Only heartbeat and cron events were shed, and all were counted.
+        let stats = bus.stats();
+        assert!(events_shed > 0, "a full queue should shed some events");
+        assert!(
+            stats
+                .shed
+                .keys()
+                .all(|c| SHEDDABLE_CHANNELS.contains(&c.as_str()))
+        );
+        assert_eq!(stats.shed.values().sum::<u64>(), events_shed);
+        let events_received = received.iter().filter(|m| m.channel != "test").count();
+        assert_eq!(events_received as u64 + events_shed, 40);
+        assert!(stats.blocked_sends > 0, "user messages should have waited");
+        assert_eq!(stats.inbound_depth, 0);
+        assert_eq!(stats.capacity, 4);
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn stats_report_queue_depths() {
+        let bus = MessageBus::with_capacity(8);
+        for i in 0..3 {
+            bus.publish(make_inbound(&i.to_string())).await.unwrap();
+        }
+        bus.dispatch_outbound(make_outbound("out")).unwrap();
+        let stats = bus.stats();
+        assert_eq!((stats.inbound_depth, stats.outbound_depth), (3, 1));
+        assert_eq!(stats.blocked_sends, 0);
+        assert!(stats.shed.is_empty());
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn event_sender_forwards_through_overflow_policy() {
+        let bus = std::sync::Arc::new(MessageBus::with_capacity(1));
+        bus.publish(make_inbound("user")).await.unwrap();
+
+        let tx = bus.event_sender();
+        tx.send(make_event("heartbeat", "tick")).await.unwrap();
+        drop(tx);
+        while bus.stats().shed.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(bus.stats().shed["heartbeat"], 1);
+        assert_eq!(bus.consume_inbound().await.unwrap().content, "user");
+    }
 }
@@ -0,0 +1,429 @@
+//! Applying config changes to a running gateway.
+//!
+//! Some settings are read afresh by every turn and can change while the
+//! gateway runs: `agents.defaults` (except the workspace), `routing.rules`,
+//! `routing.permissions` (which tools each sender may use) and
+//! `delegation.max_spawn_depth`. These make up [`LiveSettings`], held in a
+//! [`LiveConfig`] the agent loop shares. A turn takes a snapshot when it
+//! starts and keeps it to the end, so a reload never changes the model,
+//! limits or permissions of a turn already running.
+//!
+//! [`ConfigReloader`] re-reads a config against the one the gateway is
+//! running with: [`diff_config`] sorts each changed section into one that
+//! is applied live, one handled by the channel host (`channels`), or one
+//! that only takes effect after a restart (everything else, such as the
+//! gateway's bind address or the providers). The last kind is reported and
+//! otherwise ignored.
+
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use clawft_types::ClawftError;
+use clawft_types::config::{AgentDefaults, Config};
+
+use crate::agent::agents::AgentRegistry;
+use crate::agent_routing::RoutingRules;
+use crate::pipeline::permissions::PermissionResolver;
+use crate::routing_validation::{ValidationSeverity, validate_routing_rules};
+
+/// How a change to a config section takes effect in a running gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionReload {
+    /// Applied to turns that start after the reload.
+    Live,
+    /// Applied by reloading the channels whose settings changed.
+    Channels,
+    /// Ignored until the gateway restarts.
+    Restart,
+}
+
+/// Sections with a known [`SectionReload`], by dotted path. A section not
+/// listed takes the class of its nearest listed ancestor, or
+/// [`SectionReload::Restart`].
+const SECTIONS: [(&str, SectionReload); 6] = [
+    ("agents.defaults", SectionReload::Live),
+    // The context builder and memory are opened on the workspace at startup.
+    ("agents.defaults.workspace", SectionReload::Restart),
+    ("routing.rules", SectionReload::Live),
+    ("routing.permissions", SectionReload::Live),
+    ("delegation.max_spawn_depth", SectionReload::Live),
+    ("channels", SectionReload::Channels),
+];
+
+/// A changed config section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionChange {
+    /// Dotted path of the section, e.g. `agents.defaults.temperature`.
+    pub path: String,
+    /// How the change takes effect.
+    pub reload: SectionReload,
+}
+
+/// The sections that differ between `old` and `new`, and how each takes
+/// effect. Sections are as fine-grained as their classes: a changed
+/// `agents.defaults.model` is reported as such, a changed `gateway` as a
+/// whole.
+pub fn diff_config(old: &Config, new: &Config) -> Vec<SectionChange> {
+    let mut changes = Vec::new();
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => diff_value("", &old, &new, &mut changes),
+        (Err(e), _) | (_, Err(e)) => {
+            warn!(error = %e, "config did not serialize, treating it as changed");
+            changes.push(SectionChange {
+                path: String::new(),
+                reload: SectionReload::Restart,
+            });
+        }
+    }
+    changes
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, changes: &mut Vec<SectionChange>) {
+    if old == new {
+        return;
+    }
+    let has_listed_children = path.is_empty()
+        || SECTIONS.iter().any(|(section, _)| {
+            section
+                .strip_prefix(path)
+                .is_some_and(|r| r.starts_with('.'))
+        });
+    if has_listed_children && let (Value::Object(old), Value::Object(new)) = (old, new) {
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{path}.{key}")
+            };
+            let missing = Value::Null;
+            let old = old.get(key).unwrap_or(&missing);
+            let new = new.get(key).unwrap_or(&missing);
+            diff_value(&child, old, new, changes);
+        }
+        return;
+    }
+    changes.push(SectionChange {
+        path: path.to_string(),
+        reload: section_reload(path),
+    });
+}
+
+/// The class of the section at `path`: that of the longest listed section
+/// that is `path` or contains it.
+fn section_reload(path: &str) -> SectionReload {
+    SECTIONS
+        .iter()
+        .filter(|(section, _)| {
+            path.strip_prefix(section)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+        .max_by_key(|(section, _)| section.len())
+        .map_or(SectionReload::Restart, |(_, reload)| *reload)
+}
+
+/// Copy the sections that change without a restart from `new` into
+/// `running`, leaving the rest as they are. Must agree with [`SECTIONS`].
+fn adopt_reloadable(running: &mut Config, new: &Config) {
+    let workspace = std::mem::take(&mut running.agents.defaults.workspace);
+    running.agents.defaults = AgentDefaults {
+        workspace,
+        ..new.agents.defaults.clone()
+    };
+    running.routing.rules = new.routing.rules.clone();
+    running.routing.permissions = new.routing.permissions.clone();
+    running.delegation.max_spawn_depth = new.delegation.max_spawn_depth;
+    running.channels = new.channels.clone();
+}
+
+/// The settings a turn resolves when it starts.
+#[derive(Clone)]
+pub struct LiveSettings {
+    /// `agents.defaults`: model, sampling, token and iteration limits.
+    pub defaults: AgentDefaults,
+
+    /// Compiled `routing.rules`, if any are configured.
+    pub routing_rules: Option<Arc<RoutingRules>>,
+
+    /// Permissions from `routing.permissions`.
+    pub permissions: Arc<PermissionResolver>,
+
+    /// `delegation.max_spawn_depth`; 0 refuses every `spawn_agent` call.
+    pub max_spawn_depth: usize,
+
+    /// A `provider/model` that answers the turn instead of the routed
+    /// model. Never read from the config: a turn sets it on its own copy
+    /// when its message pins a model.
+    pub pinned_model: Option<String>,
+}
+
+impl LiveSettings {
+    /// Resolve the live settings of `config`. Routing rules are checked
+    /// against the defined `agents`.
+    ///
+    /// Fails with [`ClawftError::ConfigInvalid`] when a routing rule names
+    /// an undefined agent or has an invalid pattern.
+    pub fn from_config(
+        config: &Config,
+        agents: Option<&AgentRegistry>,
+    ) -> clawft_types::Result<Self> {
+        Ok(Self {
+            defaults: config.agents.defaults.clone(),
+            routing_rules: build_routing_rules(config, agents)?,
+            permissions: Arc::new(PermissionResolver::new(&config.routing, None)),
+            max_spawn_depth: config.delegation.max_spawn_depth as usize,
+            pinned_model: None,
+        })
+    }
+}
+
+/// Compile `routing.rules` after checking them against the defined agents.
+///
+/// Returns `None` when no rules are configured. Warnings are logged;
+/// errors (an undefined agent, an invalid regex) fail with
+/// [`ClawftError::ConfigInvalid`].
+fn build_routing_rules(
+    config: &Config,
+    agents: Option<&AgentRegistry>,
+) -> clawft_types::Result<Option<Arc<RoutingRules>>> {
+    let rules = &config.routing.rules;
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    let names: Vec<&str> = agents
+        .map(|a| a.list().into_iter().map(|d| d.name.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut errors = Vec::new();
+    for diagnostic in validate_routing_rules(rules, &names) {
+        match diagnostic.severity {
+            ValidationSeverity::Error => errors.push(diagnostic.to_string()),
+            _ => tracing::warn!("{diagnostic}"),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ClawftError::ConfigInvalid {
+            reason: errors.join("; "),
+        });
+    }
+
+    let rules = RoutingRules::compile(rules)?;
+    debug!(count = rules.len(), "routing rules compiled");
+    Ok(Some(Arc::new(rules)))
+}
+
+/// The current [`LiveSettings`], shared by the agent loop and whatever
+/// reloads them.
+pub struct LiveConfig {
+    current: RwLock<Arc<LiveSettings>>,
+}
+
+impl LiveConfig {
+    /// Start from `settings`.
+    pub fn new(settings: LiveSettings) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(settings)),
+        }
+    }
+
+    /// The settings as they are now. Later changes do not affect the
+    /// returned snapshot.
+    pub fn snapshot(&self) -> Arc<LiveSettings> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the settings for turns that start from now on.
+    pub fn replace(&self, settings: LiveSettings) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(settings);
+    }
+
+    /// Change some of the settings for turns that start from now on.
+    pub fn update(&self, change: impl FnOnce(&mut LiveSettings)) {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        change(Arc::make_mut(&mut current));
+    }
+}
+
+/// What a reload did with each changed section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// Sections applied to new turns.
+    pub applied: Vec<String>,
+    /// Channel sections, for the channel host to reload.
+    pub channels: Vec<String>,
+    /// Sections that changed but need a restart to take effect.
+    pub restart_required: Vec<String>,
+}
+
+impl ReloadReport {
+    /// Whether the config differed from the running one at all.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.channels.is_empty() && self.restart_required.is_empty()
+    }
+}
+
+/// Applies re-read configs to a running gateway's [`LiveConfig`].
+pub struct ConfigReloader {
+    /// The config the gateway runs with: as started, plus every change
+    /// applied since.
+    running: Mutex<Config>,
+    live: Arc<LiveConfig>,
+    agents: Option<Arc<AgentRegistry>>,
+}
+
+impl ConfigReloader {
+    /// A reloader for a gateway started with `config`, whose turns read
+    /// `live`. Routing rules are checked against `agents`.
+    pub fn new(config: Config, live: Arc<LiveConfig>, agents: Option<Arc<AgentRegistry>>) -> Self {
+        Self {
+            running: Mutex::new(config),
+            live,
+            agents,
+        }
+    }
+
+    /// Apply the live sections of `new` and report what changed.
+    ///
+    /// Sections that need a restart stay as they are, and are reported
+    /// again by every reload until then. An invalid live section fails the
+    /// whole reload, leaving the running settings untouched.
+    pub fn reload(&self, new: &Config) -> clawft_types::Result<ReloadReport> {
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut report = ReloadReport::default();
+        for change in diff_config(&running, new) {
+            match change.reload {
+                SectionReload::Live => report.applied.push(change.path),
+                SectionReload::Channels => report.channels.push(change.path),
+                SectionReload::Restart => report.restart_required.push(change.path),
+            }
+        }
+
+        let mut updated = running.clone();
+        adopt_reloadable(&mut updated, new);
+        if !report.applied.is_empty() {
+            let settings = LiveSettings::from_config(&updated, self.agents.as_deref())?;
+            self.live.replace(settings);
+        }
+        *running = updated;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_types::routing::{RoutingRule, RuleAction};
+
+    fn paths(changes: &[SectionChange], reload: SectionReload) -> Vec<&str> {
+        changes
+            .iter()
+            .filter(|c| c.reload == reload)
+            .map(|c| c.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn changes_are_classified_by_section() {
+        let old = Config::default();
+        assert!(diff_config(&old, &old).is_empty());
+
+        let mut new = old.clone();
+        new.agents.defaults.temperature = 0.1;
+        new.agents.defaults.workspace = "/elsewhere".into();
+        new.agents.dispatch.max_concurrent_sessions += 1;
+        new.routing
+            .permissions
+            .users
+            .insert("alice".into(), Default::default());
+        new.routing.mode = "tiered".into();
+        new.delegation.max_spawn_depth = 5;
+        new.delegation.max_turns += 1;
+        new.channels.slack.enabled = true;
+        new.gateway.port += 1;
+
+        let changes = diff_config(&old, &new);
+        assert_eq!(
+            paths(&changes, SectionReload::Live),
+            [
+                "agents.defaults.temperature",
+                "delegation.max_spawn_depth",
+                "routing.permissions",
+            ]
+        );
+        assert_eq!(paths(&changes, SectionReload::Channels), ["channels"]);
+        assert_eq!(
+            paths(&changes, SectionReload::Restart),
+            [
+                "agents.defaults.workspace",
+                "agents.dispatch",
+                "delegation.max_turns",
+                "gateway",
+                "routing.mode",
+            ]
+        );
+    }
+
+    #[test]
+    fn reload_applies_live_sections_and_keeps_the_rest() {
+        let config = Config::default();
+        let live = Arc::new(LiveConfig::new(
+            LiveSettings::from_config(&config, None).unwrap(),
+        ));
+        let reloader = ConfigReloader::new(config.clone(), live.clone(), None);
+        let before = live.snapshot();
+
+        let mut new = config.clone();
+        new.agents.defaults.model = "openai/gpt-4o".into();
+        new.agents.defaults.workspace = "/elsewhere".into();
+        new.gateway.host = "192.0.2.1".into();
+        let report = reloader.reload(&new).unwrap();
+        assert_eq!(report.applied, ["agents.defaults.model"]);
+        assert_eq!(
+            report.restart_required,
+            ["agents.defaults.workspace", "gateway"]
+        );
+
+        let after = live.snapshot();
+        assert_eq!(after.defaults.model, "openai/gpt-4o");
+        assert_eq!(after.defaults.workspace, config.agents.defaults.workspace);
+        // Snapshots taken earlier keep what they saw.
+        assert_eq!(before.defaults.model, config.agents.defaults.model);
+
+        // Unapplied sections are reported until the restart.
+        let again = reloader.reload(&new).unwrap();
+        assert!(again.applied.is_empty());
+        assert_eq!(again.restart_required, report.restart_required);
+    }
+
+    #[test]
+    fn invalid_routing_rules_leave_settings_untouched() {
+        let config = Config::default();
+        let live = Arc::new(LiveConfig::new(
+            LiveSettings::from_config(&config, None).unwrap(),
+        ));
+        let reloader = ConfigReloader::new(config.clone(), live.clone(), None);
+
+        let mut new = config.clone();
+        new.agents.defaults.temperature = 0.0;
+        new.routing.rules.push(RoutingRule {
+            name: Some("to-nowhere".into()),
+            matcher: Default::default(),
+            action: RuleAction::Route("ghost".into()),
+        });
+        let err = reloader.reload(&new).unwrap_err();
+        assert!(err.to_string().contains("ghost"), "{err}");
+        assert_eq!(
+            live.snapshot().defaults.temperature,
+            config.agents.defaults.temperature
+        );
+    }
+}
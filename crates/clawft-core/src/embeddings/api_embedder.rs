@@ -1,15 +1,20 @@
 //! API-based embedder using an OpenAI-compatible embeddings endpoint.
 //!
-//! [`ApiEmbedder`] generates embeddings by calling a remote LLM provider's
-//! `/embeddings` endpoint. When no API key is configured (or the call fails),
-//! it falls back to a deterministic SHA-256-based pseudo-embedding for
-//! development and testing.
+//! [`ApiEmbedder`] generates embeddings through a
+//! [`clawft_llm::EmbeddingsProvider`], by default an
+//! [`OpenAiCompatEmbeddings`] client for the configured `/embeddings`
+//! endpoint. When no API key is configured (or the call fails), it falls
+//! back to a deterministic SHA-256-based pseudo-embedding for development
+//! and testing.
 //!
 //! This module is gated behind the `rvf` feature flag.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use clawft_llm::LlmProviderConfig;
+use clawft_llm::embeddings::{EmbeddingsOptions, EmbeddingsProvider, OpenAiCompatEmbeddings};
 use sha2::{Digest, Sha256};
 use tracing::{debug, warn};
 
@@ -49,8 +54,9 @@ impl Default for ApiEmbedderConfig {
 ///
 /// # API Mode
 ///
-/// When a valid API key is available, sends requests to the provider's
-/// `/embeddings` endpoint in the OpenAI-compatible format.
+/// When a valid API key is available, sends requests through the
+/// [`EmbeddingsProvider`], which batches large inputs and checks that every
+/// vector has the expected dimension.
 ///
 /// # Fallback Mode
 ///
@@ -59,8 +65,8 @@ impl Default for ApiEmbedderConfig {
 /// meaningful but provide consistent, reproducible vectors for testing
 /// and development workflows.
 pub struct ApiEmbedder {
-    config: ApiEmbedderConfig,
-    http: reqwest::Client,
+    provider: Option<Arc<dyn EmbeddingsProvider>>,
+    model: String,
     dim: usize,
 }
 
@@ -68,13 +74,51 @@ impl ApiEmbedder {
     /// Create a new `ApiEmbedder` with the given configuration.
     pub fn new(config: ApiEmbedderConfig) -> Self {
         let dim = config.dimension.unwrap_or(DEFAULT_DIMENSION);
+        let provider = (!config.api_key_env.is_empty()).then(|| {
+            let llm_config = LlmProviderConfig {
+                name: "embeddings".into(),
+                base_url: config.base_url,
+                api_key_env: config.api_key_env,
+                model_prefix: None,
+                default_model: Some(config.model.clone()),
+                headers: config.headers,
+                timeout_secs: None,
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
+                reasoning_effort: None,
+                rate_limit: Default::default(),
+                azure: None,
+            };
+            let client = OpenAiCompatEmbeddings::new(llm_config).with_options(EmbeddingsOptions {
+                dimensions: config.dimension,
+                ..EmbeddingsOptions::default()
+            });
+            Arc::new(client) as Arc<dyn EmbeddingsProvider>
+        });
         Self {
-            config,
-            http: reqwest::Client::new(),
+            provider,
+            model: config.model,
             dim,
         }
     }
 
+    /// Create an `ApiEmbedder` backed by an existing provider.
+    ///
+    /// `model` is passed to the provider as-is. Vectors whose length is not
+    /// `dimension` are treated as a failed call and replaced by the hash
+    /// fallback, so stored vectors always share one dimension.
+    pub fn with_provider(
+        provider: Arc<dyn EmbeddingsProvider>,
+        model: impl Into<String>,
+        dimension: usize,
+    ) -> Self {
+        Self {
+            provider: Some(provider),
+            model: model.into(),
+            dim: dimension,
+        }
+    }
+
     /// Create an `ApiEmbedder` with default configuration (OpenAI, 384-dim).
     pub fn with_defaults() -> Self {
         Self::new(ApiEmbedderConfig::default())
@@ -91,85 +135,26 @@ impl ApiEmbedder {
         })
     }
 
-    /// Resolve the API key from the environment.
-    fn resolve_api_key(&self) -> Option<String> {
-        if self.config.api_key_env.is_empty() {
-            return None;
-        }
-        std::env::var(&self.config.api_key_env).ok()
-    }
-
-    /// Call the remote embeddings API for a single text.
-    async fn call_api(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
-        let api_key = self.resolve_api_key().ok_or_else(|| {
-            EmbeddingError::Internal(format!("API key not set: {}", self.config.api_key_env))
-        })?;
+    /// Embed `texts` through the provider.
+    async fn call_api(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| EmbeddingError::Internal("no embeddings provider configured".into()))?;
 
-        let base = self.config.base_url.trim_end_matches('/');
-        let url = format!("{base}/embeddings");
-
-        let mut body = serde_json::json!({
-            "input": texts,
-            "model": self.config.model,
-        });
-
-        if let Some(dim) = self.config.dimension {
-            body["dimensions"] = serde_json::json!(dim);
-        }
-
-        let mut req = self
-            .http
-            .post(&url)
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json");
-
-        for (k, v) in &self.config.headers {
-            req = req.header(k.as_str(), v.as_str());
-        }
-
-        let response = req
-            .json(&body)
-            .send()
+        let response = provider
+            .embed(texts, &self.model)
             .await
-            .map_err(|e| EmbeddingError::Internal(format!("HTTP request failed: {e}")))?;
+            .map_err(|e| EmbeddingError::Internal(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body_text = response.text().await.unwrap_or_default();
+        let dim = response.dimension();
+        if !response.embeddings.is_empty() && dim != self.dim {
             return Err(EmbeddingError::Internal(format!(
-                "API error HTTP {status}: {body_text}"
+                "provider returned {dim}-dimensional embeddings, expected {}",
+                self.dim
             )));
         }
-
-        let resp_json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| EmbeddingError::Internal(format!("failed to parse API response: {e}")))?;
-
-        // Parse OpenAI-format response: { data: [{ embedding: [...] }, ...] }
-        let data = resp_json
-            .get("data")
-            .and_then(|d| d.as_array())
-            .ok_or_else(|| EmbeddingError::Internal("response missing 'data' array".into()))?;
-
-        let mut results = Vec::with_capacity(data.len());
-        for item in data {
-            let embedding = item
-                .get("embedding")
-                .and_then(|e| e.as_array())
-                .ok_or_else(|| {
-                    EmbeddingError::Internal("response item missing 'embedding'".into())
-                })?;
-
-            let vec: Vec<f32> = embedding
-                .iter()
-                .map(|v| v.as_f64().unwrap_or(0.0) as f32)
-                .collect();
-
-            results.push(vec);
-        }
-
-        Ok(results)
+        Ok(response.embeddings)
     }
 
     /// Generate a deterministic pseudo-embedding from text using SHA-256.
@@ -218,9 +203,9 @@ impl ApiEmbedder {
 impl Embedder for ApiEmbedder {
     async fn embed(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
         // Try API first, fall back to hash
-        match self.call_api(&[text]).await {
+        match self.call_api(&[text.to_string()]).await {
             Ok(mut results) if !results.is_empty() => {
-                debug!(model = %self.config.model, "API embedding succeeded");
+                debug!(model = %self.model, "API embedding succeeded");
                 Ok(results.remove(0))
             }
             Ok(_) => {
@@ -235,12 +220,10 @@ impl Embedder for ApiEmbedder {
     }
 
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
-        let text_refs: Vec<&str> = texts.iter().map(|s| s.as_str()).collect();
-
-        match self.call_api(&text_refs).await {
+        match self.call_api(texts).await {
             Ok(results) if results.len() == texts.len() => {
                 debug!(
-                    model = %self.config.model,
+                    model = %self.model,
                     count = texts.len(),
                     "batch API embedding succeeded"
                 );
@@ -258,7 +241,7 @@ impl Embedder for ApiEmbedder {
     }
 
     fn name(&self) -> &str {
-        &self.config.model
+        &self.model
     }
 }
 
@@ -382,4 +365,44 @@ mod tests {
         let emb = embedder.embed("test").await.unwrap();
         assert_eq!(emb.len(), embedder.dimension());
     }
+
+    /// Returns a `dim`-long vector of ones per text.
+    struct FixedProvider {
+        dim: usize,
+    }
+
+    #[async_trait]
+    impl EmbeddingsProvider for FixedProvider {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn embed(
+            &self,
+            texts: &[String],
+            model: &str,
+        ) -> clawft_llm::Result<clawft_llm::EmbeddingResponse> {
+            Ok(clawft_llm::EmbeddingResponse {
+                embeddings: vec![vec![1.0; self.dim]; texts.len()],
+                model: model.to_string(),
+                usage: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn provider_vectors_are_used() {
+        let embedder = ApiEmbedder::with_provider(Arc::new(FixedProvider { dim: 8 }), "m", 8);
+        let texts = vec!["a".to_string(), "b".to_string()];
+        let batch = embedder.embed_batch(&texts).await.unwrap();
+        assert_eq!(batch, vec![vec![1.0; 8]; 2]);
+        assert_eq!(embedder.name(), "m");
+    }
+
+    #[tokio::test]
+    async fn wrong_provider_dimension_falls_back() {
+        let embedder = ApiEmbedder::with_provider(Arc::new(FixedProvider { dim: 4 }), "m", 8);
+        let emb = embedder.embed("hello").await.unwrap();
+        assert_eq!(emb, embedder.hash_fallback("hello"));
+    }
 }
@@ -5,18 +5,28 @@
 //! - Wrapped in markdown fences (`` ```json ... ``` ``)
 //! - Trailing commas after the last element in objects/arrays
 //! - Unquoted keys (`{name: "value"}` instead of `{"name": "value"}`)
-//! - Truncated output (missing closing brackets/braces)
+//! - Truncated output (the model hit `max_tokens` mid-object)
+//! - Values of the wrong type for the tool's schema (`"5"` for a number)
 //!
-//! This module provides a lightweight repair pass (~100 lines of logic) that
-//! attempts to fix these common issues before handing the string to
-//! `serde_json::from_str`. It is **not** a full JSON parser -- it handles
-//! the most common failure modes observed in tool-call responses from GPT-4,
-//! Claude, and similar models.
+//! This module provides a lightweight repair pass that attempts to fix these
+//! common issues before handing the string to `serde_json::from_str`. It is
+//! **not** a full JSON parser -- it handles the most common failure modes
+//! observed in tool-call responses from GPT-4, Claude, and similar models.
+//!
+//! Truncated output is completed on a best-effort basis: an open string
+//! value is closed, a cut-off `true`/`false`/`null` is finished, and
+//! anything that cannot be finished (a dangling key, a half-written
+//! number) is dropped back to the last complete value before the open
+//! brackets are closed. Given a JSON-Schema-like descriptor, obvious type
+//! mismatches are then coerced (see [`coerce_to_schema`]).
+//!
+//! Every change is listed in a [`RepairReport`], so callers can log what
+//! was altered instead of silently accepting a "fixed" value.
 //!
 //! # Usage
 //!
 //! ```ignore
-//! use clawft_core::json_repair::repair_json;
+//! use clawft_core::json_repair::{parse_with_report, repair_json};
 //!
 //! let malformed = r#"```json
 //! {"name": "test", "value": 42,}
@@ -24,8 +34,90 @@
 //!
 //! let repaired = repair_json(malformed);
 //! let value: serde_json::Value = serde_json::from_str(&repaired).unwrap();
+//!
+//! let (value, report) = parse_with_report(r#"{"path": "/tmp/a"#).unwrap();
+//! assert!(report.repaired_truncation);
 //! ```
 
+use std::fmt;
+
+use serde_json::Value;
+
+/// One change made while repairing JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Repair {
+    /// Markdown code fences around the JSON were removed.
+    StrippedFences,
+    /// Commas before `]` or `}` were removed.
+    RemovedTrailingCommas { count: usize },
+    /// Bare object keys were quoted.
+    QuotedKeys { count: usize },
+    /// An unterminated string value was closed.
+    ClosedString,
+    /// A cut-off token was completed (e.g. `tr` to `true`, `1.` to `1`).
+    CompletedToken { from: String, to: String },
+    /// An incomplete tail that could not be finished was dropped.
+    DroppedIncomplete { text: String },
+    /// Missing closing brackets were appended.
+    ClosedBrackets { closers: String },
+    /// A value was converted to the type its schema expects.
+    Coerced {
+        /// JSON Pointer to the value (`""` is the root).
+        path: String,
+        from: &'static str,
+        to: &'static str,
+    },
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StrippedFences => write!(f, "stripped markdown fences"),
+            Self::RemovedTrailingCommas { count } => write!(f, "removed {count} trailing comma(s)"),
+            Self::QuotedKeys { count } => write!(f, "quoted {count} bare key(s)"),
+            Self::ClosedString => write!(f, "closed an unterminated string"),
+            Self::CompletedToken { from, to } => write!(f, "completed `{from}` as `{to}`"),
+            Self::DroppedIncomplete { text } => write!(f, "dropped incomplete `{text}`"),
+            Self::ClosedBrackets { closers } => write!(f, "appended `{closers}`"),
+            Self::Coerced { path, from, to } => {
+                let path = if path.is_empty() { "/" } else { path };
+                write!(f, "coerced {path} from {from} to {to}")
+            }
+        }
+    }
+}
+
+/// Everything [`repair_with_report`] (and schema coercion) changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepairReport {
+    /// The changes, in the order they were made.
+    pub repairs: Vec<Repair>,
+    /// The input looked truncated and was completed.
+    pub repaired_truncation: bool,
+}
+
+impl RepairReport {
+    /// Whether the input was used as-is.
+    pub fn is_empty(&self) -> bool {
+        self.repairs.is_empty()
+    }
+}
+
+impl fmt::Display for RepairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.repairs.is_empty() {
+            return write!(f, "no repairs");
+        }
+        for (i, repair) in self.repairs.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{repair}")?;
+        }
+        Ok(())
+    }
+}
+
 /// Attempt to repair malformed JSON from LLM output.
 ///
 /// Applies the following transformations in order:
@@ -33,15 +125,40 @@
 /// 1. **Strip markdown fences**: Removes leading/trailing `` ```json `` / `` ``` `` markers.
 /// 2. **Fix trailing commas**: Removes commas before `]` and `}`.
 /// 3. **Fix unquoted keys**: Adds quotes around bare object keys.
-/// 4. **Close truncated structures**: Appends missing `]` and `}` to balance brackets.
+/// 4. **Complete truncated structures**: Closes an open string, finishes or
+///    drops a cut-off token, and appends missing `]` and `}`.
 ///
 /// Returns the repaired string. If the input is already valid JSON, it is
 /// returned unchanged (modulo whitespace from fence stripping).
 pub fn repair_json(input: &str) -> String {
+    repair_with_report(input).0
+}
+
+/// [`repair_json`], also returning what was changed.
+pub fn repair_with_report(input: &str) -> (String, RepairReport) {
+    let mut report = RepairReport::default();
+
     let stripped = strip_markdown_fences(input);
+    if stripped != input.trim() {
+        report.repairs.push(Repair::StrippedFences);
+    }
+
     let no_trailing = fix_trailing_commas(&stripped);
+    let count = stripped.matches(',').count() - no_trailing.matches(',').count();
+    if count > 0 {
+        report.repairs.push(Repair::RemovedTrailingCommas { count });
+    }
+
     let quoted_keys = fix_unquoted_keys(&no_trailing);
-    close_truncated(&quoted_keys)
+    let count = (quoted_keys.matches('"').count() - no_trailing.matches('"').count()) / 2;
+    if count > 0 {
+        report.repairs.push(Repair::QuotedKeys { count });
+    }
+
+    let before = report.repairs.len();
+    let closed = close_truncated(&quoted_keys, &mut report.repairs);
+    report.repaired_truncation = report.repairs.len() > before;
+    (closed, report)
 }
 
 /// Try to parse JSON, falling back to repair if initial parse fails.
@@ -49,14 +166,30 @@ pub fn repair_json(input: &str) -> String {
 /// This is the recommended entry point for tool-call argument parsing.
 /// It avoids the repair overhead when the JSON is already valid.
 pub fn parse_with_repair(input: &str) -> Result<serde_json::Value, serde_json::Error> {
+    parse_with_report(input).map(|(value, _)| value)
+}
+
+/// [`parse_with_repair`], also returning what was changed to make the
+/// input parse (an empty report on the fast path).
+pub fn parse_with_report(input: &str) -> Result<(Value, RepairReport), serde_json::Error> {
     // Fast path: try parsing as-is first.
     if let Ok(val) = serde_json::from_str(input) {
-        return Ok(val);
+        return Ok((val, RepairReport::default()));
     }
 
     // Slow path: repair and retry.
-    let repaired = repair_json(input);
-    serde_json::from_str(&repaired)
+    let (repaired, report) = repair_with_report(input);
+    serde_json::from_str(&repaired).map(|val| (val, report))
+}
+
+/// [`parse_with_report`], then [`coerce_to_schema`] against `schema`.
+pub fn parse_with_schema(
+    input: &str,
+    schema: &Value,
+) -> Result<(Value, RepairReport), serde_json::Error> {
+    let (mut value, mut report) = parse_with_report(input)?;
+    coerce_to_schema(&mut value, schema, &mut report);
+    Ok((value, report))
 }
 
 // ---------------------------------------------------------------------------
@@ -255,70 +388,322 @@ fn is_key_char(c: char) -> bool {
 }
 
 // ---------------------------------------------------------------------------
-// Step 4: Close truncated structures
+// Step 4: Complete truncated structures
 // ---------------------------------------------------------------------------
 
-/// Append missing closing brackets, braces, and string quotes to balance
-/// the JSON structure.
+/// What an open container expects next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    Key,
+    Colon,
+    Value,
+    Comma,
+}
+
+/// An open object or array.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    closer: char,
+    expect: Expect,
+}
+
+/// Container nesting, plus the last point the input could be cut at and
+/// still be completed by appending closers.
+#[derive(Default)]
+struct Nesting {
+    stack: Vec<Frame>,
+    safe_len: usize,
+    safe_closers: String,
+}
+
+impl Nesting {
+    fn closers(&self) -> String {
+        self.stack.iter().rev().map(|f| f.closer).collect()
+    }
+
+    fn mark_safe(&mut self, at: usize) {
+        self.safe_len = at;
+        self.safe_closers = self.closers();
+    }
+
+    fn expects_key(&self) -> bool {
+        self.stack.last().is_some_and(|f| f.expect == Expect::Key)
+    }
+
+    fn expect(&mut self, expect: Expect) {
+        if let Some(top) = self.stack.last_mut() {
+            top.expect = expect;
+        }
+    }
+
+    /// A value ended at byte `at`.
+    fn value_done(&mut self, at: usize) {
+        self.expect(Expect::Comma);
+        self.mark_safe(at);
+    }
+
+    fn open(&mut self, closer: char, expect: Expect, at: usize) {
+        self.expect(Expect::Comma);
+        self.stack.push(Frame { closer, expect });
+        self.mark_safe(at);
+    }
+}
+
+/// Complete JSON that was cut off, recording each change in `repairs`.
 ///
 /// Scans through the input tracking open/close pairs while respecting
-/// string literals, then appends any missing closers. Also closes
-/// unterminated string literals.
-fn close_truncated(input: &str) -> String {
-    let mut stack: Vec<char> = Vec::new();
+/// string literals. If the input ends inside a structure, an open string
+/// value is closed and a cut-off token at the end is completed when that
+/// is unambiguous; otherwise the input is cut back to the last complete
+/// value. The missing closers are then appended. Input that is not
+/// truncated is returned unchanged.
+fn close_truncated(input: &str, repairs: &mut Vec<Repair>) -> String {
+    let mut nesting = Nesting::default();
     let mut in_string = false;
+    let mut string_is_key = false;
     let mut escape_next = false;
+    let mut token_start: Option<usize> = None;
 
-    for c in input.chars() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        if c == '\\' && in_string {
-            escape_next = true;
-            continue;
-        }
-
-        if c == '"' {
-            in_string = !in_string;
+    for (i, c) in input.char_indices() {
+        if in_string {
+            if escape_next {
+                escape_next = false;
+            } else if c == '\\' {
+                escape_next = true;
+            } else if c == '"' {
+                in_string = false;
+                if string_is_key {
+                    nesting.expect(Expect::Colon);
+                } else {
+                    nesting.value_done(i + 1);
+                }
+            }
             continue;
         }
 
-        if in_string {
-            continue;
+        if token_start.is_some() {
+            if !(c.is_whitespace() || matches!(c, ',' | ':' | ']' | '}')) {
+                continue;
+            }
+            token_start = None;
+            nesting.value_done(i);
         }
 
         match c {
-            '{' => stack.push('}'),
-            '[' => stack.push(']'),
+            '"' => {
+                in_string = true;
+                string_is_key = nesting.expects_key();
+            }
+            '{' => nesting.open('}', Expect::Key, i + 1),
+            '[' => nesting.open(']', Expect::Value, i + 1),
             '}' | ']' => {
-                if stack.last() == Some(&c) {
-                    stack.pop();
+                if nesting.stack.last().is_some_and(|f| f.closer == c) {
+                    nesting.stack.pop();
+                    nesting.value_done(i + 1);
                 }
             }
-            _ => {}
+            ',' => {
+                let next = match nesting.stack.last() {
+                    Some(f) if f.closer == '}' => Expect::Key,
+                    _ => Expect::Value,
+                };
+                nesting.expect(next);
+            }
+            ':' => nesting.expect(Expect::Value),
+            c if c.is_whitespace() => {}
+            _ => token_start = Some(i),
         }
     }
 
-    if !in_string && stack.is_empty() {
+    if !in_string && nesting.stack.is_empty() {
         return input.to_string();
     }
 
-    let mut result = input.to_string();
+    // Finish the value the input stops in, when that is unambiguous.
+    let finished = if in_string && !string_is_key {
+        let mut text = input.to_string();
+        drop_partial_escape(&mut text, escape_next);
+        text.push('"');
+        repairs.push(Repair::ClosedString);
+        Some(text)
+    } else if let Some(start) = token_start {
+        let token = &input[start..];
+        complete_token(token).map(|completed| {
+            if completed != token {
+                repairs.push(Repair::CompletedToken {
+                    from: token.to_string(),
+                    to: completed.to_string(),
+                });
+            }
+            format!("{}{completed}", &input[..start])
+        })
+    } else {
+        None
+    };
 
-    // Close unclosed string literal.
-    if in_string {
-        result.push('"');
-    }
+    let (mut result, closers) = match finished {
+        Some(text) => (text, nesting.closers()),
+        None => {
+            let dropped = input[nesting.safe_len..].trim();
+            if !dropped.is_empty() {
+                repairs.push(Repair::DroppedIncomplete {
+                    text: dropped.to_string(),
+                });
+            }
+            let kept = input[..nesting.safe_len].to_string();
+            (kept, nesting.safe_closers)
+        }
+    };
 
-    // Append structural closers in reverse order.
-    for closer in stack.into_iter().rev() {
-        result.push(closer);
+    if !closers.is_empty() {
+        result.push_str(&closers);
+        repairs.push(Repair::ClosedBrackets { closers });
     }
     result
 }
 
+/// Remove a dangling backslash or incomplete `\uXXXX` escape from the end
+/// of an unterminated string.
+fn drop_partial_escape(text: &mut String, dangling_backslash: bool) {
+    if dangling_backslash {
+        text.pop();
+        return;
+    }
+    if let Some(at) = text.rfind("\\u") {
+        let digits = &text[at + 2..];
+        if digits.len() < 4 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            text.truncate(at);
+        }
+    }
+}
+
+/// The complete form of a token cut off at the end of the input: itself
+/// if already complete, a finished `true`/`false`/`null`, or a number
+/// without its dangling `.`, exponent, or sign.
+fn complete_token(token: &str) -> Option<&str> {
+    let is_number = |t: &str| serde_json::from_str::<serde_json::Number>(t).is_ok();
+    if is_number(token) {
+        return Some(token);
+    }
+    if let Some(literal) = ["true", "false", "null"]
+        .into_iter()
+        .find(|lit| lit.starts_with(token))
+    {
+        return Some(literal);
+    }
+    let trimmed = token.trim_end_matches(['.', 'e', 'E', '+', '-']);
+    is_number(trimmed).then_some(trimmed)
+}
+
+// ---------------------------------------------------------------------------
+// Schema-guided coercion
+// ---------------------------------------------------------------------------
+
+/// Coerce obvious type mismatches in `value` against a JSON-Schema-like
+/// `schema`, recording each change in `report`.
+///
+/// Only `type` (a name or a list of names), `properties`, and `items` are
+/// consulted. A value that already has one of the allowed types is left
+/// alone. Otherwise these conversions are tried, for each allowed type in
+/// order:
+///
+/// - a numeric string to `number` or `integer` (`"5"` to `5`)
+/// - `"true"`/`"false"` to `boolean`
+/// - a number or boolean to `string`
+/// - any single non-null value to a one-element `array`
+///
+/// Anything else is left for the consumer to reject.
+pub fn coerce_to_schema(value: &mut Value, schema: &Value, report: &mut RepairReport) {
+    coerce_at(value, schema, String::new(), &mut report.repairs);
+}
+
+fn coerce_at(value: &mut Value, schema: &Value, path: String, repairs: &mut Vec<Repair>) {
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(tys)) => tys.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty()
+        && !types.iter().any(|ty| has_type(value, ty))
+        && let Some((coerced, to)) = types.iter().find_map(|ty| coerce(value, ty))
+    {
+        repairs.push(Repair::Coerced {
+            path: path.clone(),
+            from: type_name(value),
+            to,
+        });
+        *value = coerced;
+    }
+
+    match value {
+        Value::Object(map) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            for (key, sub) in properties {
+                if let Some(field) = map.get_mut(key) {
+                    let key = key.replace('~', "~0").replace('/', "~1");
+                    coerce_at(field, sub, format!("{path}/{key}"), repairs);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let Some(sub) = schema.get("items") else {
+                return;
+            };
+            for (i, item) in items.iter_mut().enumerate() {
+                coerce_at(item, sub, format!("{path}/{i}"), repairs);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// `value` converted to `ty`, when the conversion is unambiguous.
+fn coerce(value: &Value, ty: &str) -> Option<(Value, &'static str)> {
+    match (ty, value) {
+        ("integer", Value::String(s)) => {
+            let n: i64 = s.trim().parse().ok()?;
+            Some((n.into(), "integer"))
+        }
+        ("number", Value::String(s)) => {
+            let n: serde_json::Number = serde_json::from_str(s.trim()).ok()?;
+            Some((Value::Number(n), "number"))
+        }
+        ("boolean", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Some((true.into(), "boolean")),
+            "false" => Some((false.into(), "boolean")),
+            _ => None,
+        },
+        ("string", Value::Number(_) | Value::Bool(_)) => {
+            Some((Value::String(value.to_string()), "string"))
+        }
+        ("array", v) if !v.is_null() && !v.is_array() => {
+            Some((Value::Array(vec![v.clone()]), "array"))
+        }
+        _ => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -327,6 +712,128 @@ fn close_truncated(input: &str) -> String {
 mod tests {
     use super::*;
 
+    /// Fixture corpus of malformed model output.
+    const CORPUS_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/json_repair/corpus.jsonl"
+    );
+
+    /// Fixture corpus for schema-guided coercion.
+    const SCHEMA_CORPUS_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/json_repair/schema_corpus.jsonl"
+    );
+
+    fn load_corpus(path: &str) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .expect("fixture corpus should exist")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("fixture line should be JSON"))
+            .collect()
+    }
+
+    // ── fixture corpus ─────────────────────────────────────────────────
+
+    #[test]
+    fn corpus_repairs_to_expected_values() {
+        let corpus = load_corpus(CORPUS_PATH);
+        assert!(corpus.len() > 20);
+        for case in corpus {
+            let name = case["name"].as_str().unwrap();
+            let input = case["input"].as_str().unwrap();
+            let result = parse_with_report(input);
+            if case["error"] == true {
+                assert!(result.is_err(), "{name}: expected a parse error");
+                continue;
+            }
+            let (value, report) = result.unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(value, case["expected"], "{name}");
+            assert_eq!(
+                report.repaired_truncation, case["truncated"],
+                "{name}: {report}"
+            );
+        }
+    }
+
+    #[test]
+    fn schema_corpus_coerces_expected_fields() {
+        for case in load_corpus(SCHEMA_CORPUS_PATH) {
+            let name = case["name"].as_str().unwrap();
+            let input = case["input"].as_str().unwrap();
+            let (value, report) =
+                parse_with_schema(input, &case["schema"]).unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(value, case["expected"], "{name}");
+            let coerced: Vec<&str> = report
+                .repairs
+                .iter()
+                .filter_map(|r| match r {
+                    Repair::Coerced { path, .. } => Some(path.as_str()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                serde_json::json!(coerced),
+                case["coerced"],
+                "{name}: {report}"
+            );
+        }
+    }
+
+    // ── repair report ──────────────────────────────────────────────────
+
+    #[test]
+    fn report_lists_each_repair_in_order() {
+        let (repaired, report) =
+            repair_with_report("```json\n{name: \"x\", tags: [\"a\",], size: 1.");
+        assert_eq!(repaired, r#"{"name": "x", "tags": ["a"], "size": 1}"#);
+        assert_eq!(
+            report.repairs,
+            vec![
+                Repair::StrippedFences,
+                Repair::RemovedTrailingCommas { count: 1 },
+                Repair::QuotedKeys { count: 3 },
+                Repair::CompletedToken {
+                    from: "1.".into(),
+                    to: "1".into()
+                },
+                Repair::ClosedBrackets {
+                    closers: "}".into()
+                },
+            ]
+        );
+        assert!(report.repaired_truncation);
+        assert_eq!(
+            report.to_string(),
+            "stripped markdown fences; removed 1 trailing comma(s); quoted 3 bare key(s); \
+             completed `1.` as `1`; appended `}`"
+        );
+    }
+
+    #[test]
+    fn valid_json_has_an_empty_report() {
+        let (_, report) = parse_with_report(r#"{"a": [1, 2]}"#).unwrap();
+        assert!(report.is_empty());
+        assert!(!report.repaired_truncation);
+        assert_eq!(report.to_string(), "no repairs");
+    }
+
+    #[test]
+    fn dropped_tail_is_reported() {
+        let (repaired, report) = repair_with_report(r#"{"a": 1, "b": "#);
+        assert_eq!(repaired, r#"{"a": 1}"#);
+        assert_eq!(
+            report.repairs,
+            vec![
+                Repair::DroppedIncomplete {
+                    text: r#", "b":"#.into()
+                },
+                Repair::ClosedBrackets {
+                    closers: "}".into()
+                },
+            ]
+        );
+    }
+
     // ── strip_markdown_fences ──────────────────────────────────────────
 
     #[test]
@@ -453,28 +960,28 @@ mod tests {
     #[test]
     fn missing_close_brace() {
         let input = r#"{"a": 1"#;
-        let fixed = close_truncated(input);
+        let fixed = close_truncated(input, &mut Vec::new());
         assert_eq!(fixed, r#"{"a": 1}"#);
     }
 
     #[test]
     fn missing_close_bracket() {
         let input = "[1, 2, 3";
-        let fixed = close_truncated(input);
+        let fixed = close_truncated(input, &mut Vec::new());
         assert_eq!(fixed, "[1, 2, 3]");
     }
 
     #[test]
     fn missing_nested_closers() {
         let input = r#"{"a": [1, 2, {"b": 3"#;
-        let fixed = close_truncated(input);
+        let fixed = close_truncated(input, &mut Vec::new());
         assert_eq!(fixed, r#"{"a": [1, 2, {"b": 3}]}"#);
     }
 
     #[test]
     fn balanced_unchanged() {
         let input = r#"{"a": [1, 2]}"#;
-        let fixed = close_truncated(input);
+        let fixed = close_truncated(input, &mut Vec::new());
         assert_eq!(fixed, input);
     }
 
@@ -483,7 +990,7 @@ mod tests {
         // Brace inside a string should not be counted.
         // The string is unclosed, so we close the string first, then the object.
         let input = r#"{"a": "hello {world"#;
-        let fixed = close_truncated(input);
+        let fixed = close_truncated(input, &mut Vec::new());
         assert_eq!(fixed, r#"{"a": "hello {world"}"#);
     }
 
@@ -31,7 +31,9 @@ pub mod bootstrap;
 pub mod bus;
 pub mod clawft_md;
 pub mod config_merge;
+pub mod config_reload;
 pub mod json_repair;
+pub mod outbox;
 pub mod pipeline;
 pub mod planning;
 pub mod routing_validation;
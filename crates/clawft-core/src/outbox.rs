@@ -0,0 +1,166 @@
+//! Outbound messages held back during quiet hours.
+//!
+//! Replies to scheduled prompts carry the [`QuietHours`] window that
+//! applies to them (see [`ProactiveDelivery`](clawft_types::delivery::ProactiveDelivery)).
+//! The gateway passes every outbound message through a [`DeferredOutbox`]:
+//! messages outside their window come straight back, the rest are held
+//! until the window ends. The caller supplies the current time, so the
+//! outbox itself never reads the clock.
+
+use chrono::{DateTime, Utc};
+use clawft_types::delivery::QuietHours;
+use clawft_types::event::OutboundMessage;
+
+/// Outbound messages waiting for their quiet hours to end.
+#[derive(Debug, Default)]
+pub struct DeferredOutbox {
+    /// Held messages with their release times, in arrival order.
+    held: Vec<(DateTime<Utc>, OutboundMessage)>,
+}
+
+impl DeferredOutbox {
+    /// An empty outbox.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `msg` if it may be sent at `now`; otherwise hold it until
+    /// its quiet hours end.
+    pub fn admit(&mut self, msg: OutboundMessage, now: DateTime<Utc>) -> Option<OutboundMessage> {
+        let release =
+            QuietHours::from_metadata(&msg.metadata).map_or(now, |quiet| quiet.release_at(now));
+        if release <= now {
+            return Some(msg);
+        }
+        self.held.push((release, msg));
+        None
+    }
+
+    /// Take the held messages whose release time has come, oldest first.
+    pub fn release_due(&mut self, now: DateTime<Utc>) -> Vec<OutboundMessage> {
+        let (due, held) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition(|(release, _)| *release <= now);
+        self.held = held;
+        due.into_iter().map(|(_, msg)| msg).collect()
+    }
+
+    /// The earliest release time of a held message.
+    pub fn next_release(&self) -> Option<DateTime<Utc>> {
+        self.held.iter().map(|(release, _)| *release).min()
+    }
+
+    /// Number of held messages.
+    pub fn len(&self) -> usize {
+        self.held.len()
+    }
+
+    /// Whether no messages are held.
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// A hand-advanced clock.
+    struct MockClock(DateTime<Utc>);
+
+    impl MockClock {
+        fn at(ts: &str) -> Self {
+            Self(
+                DateTime::parse_from_rfc3339(ts)
+                    .unwrap()
+                    .with_timezone(&Utc),
+            )
+        }
+
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+
+        fn advance(&mut self, minutes: i64) {
+            self.0 += chrono::Duration::minutes(minutes);
+        }
+    }
+
+    fn reply(content: &str, quiet: Option<&QuietHours>) -> OutboundMessage {
+        let mut metadata = HashMap::new();
+        if let Some(quiet) = quiet {
+            metadata.insert(
+                QuietHours::METADATA_KEY.to_string(),
+                serde_json::to_value(quiet).unwrap(),
+            );
+        }
+        OutboundMessage {
+            channel: "telegram".into(),
+            chat_id: "42".into(),
+            content: content.into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn messages_without_quiet_hours_pass_through() {
+        let clock = MockClock::at("2026-05-01T23:00:00Z");
+        let mut outbox = DeferredOutbox::new();
+        assert!(outbox.admit(reply("hi", None), clock.now()).is_some());
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn messages_outside_the_window_pass_through() {
+        let quiet = QuietHours::parse("22:00-07:00", "UTC").unwrap();
+        let clock = MockClock::at("2026-05-01T09:00:00Z");
+        let mut outbox = DeferredOutbox::new();
+        assert!(
+            outbox
+                .admit(reply("hi", Some(&quiet)), clock.now())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn messages_inside_the_window_wait_until_it_ends() {
+        let quiet = QuietHours::parse("22:00-07:00", "+02:00").unwrap();
+        // 23:30 local.
+        let mut clock = MockClock::at("2026-05-01T21:30:00Z");
+        let mut outbox = DeferredOutbox::new();
+
+        assert!(
+            outbox
+                .admit(reply("first", Some(&quiet)), clock.now())
+                .is_none()
+        );
+        clock.advance(60);
+        assert!(
+            outbox
+                .admit(reply("second", Some(&quiet)), clock.now())
+                .is_none()
+        );
+        assert_eq!(outbox.len(), 2);
+        // 07:00 local on May 2nd.
+        let end = MockClock::at("2026-05-02T05:00:00Z").now();
+        assert_eq!(outbox.next_release(), Some(end));
+
+        clock.advance(5 * 60);
+        assert!(outbox.release_due(clock.now()).is_empty());
+
+        clock.advance(90);
+        let released: Vec<_> = outbox
+            .release_due(clock.now())
+            .into_iter()
+            .map(|m| m.content)
+            .collect();
+        assert_eq!(released, ["first", "second"]);
+        assert!(outbox.is_empty());
+        assert_eq!(outbox.next_release(), None);
+    }
+}
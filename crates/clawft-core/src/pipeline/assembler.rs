@@ -148,6 +148,8 @@ mod tests {
             content: content.into(),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         }
     }
 
@@ -168,6 +170,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -173,6 +173,8 @@ mod tests {
                 content: user_content.into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -180,6 +182,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -191,12 +194,16 @@ mod tests {
                     content: system.into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "user".into(),
                     content: user.into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
             ],
             tools: vec![],
@@ -205,6 +212,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -307,6 +315,8 @@ mod tests {
                 content: "You are helpful. Implement code.".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -314,6 +324,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         // Only the last user message is scanned; system messages are ignored.
         let profile = classifier.classify(&req);
@@ -337,18 +348,24 @@ mod tests {
                     content: "Research Rust crates".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "assistant".into(),
                     content: "Here are some crates...".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "user".into(),
                     content: "Now implement the function".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
             ],
             tools: vec![],
@@ -357,6 +374,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let profile = classifier.classify(&req);
         assert_eq!(profile.task_type, TaskType::CodeGeneration);
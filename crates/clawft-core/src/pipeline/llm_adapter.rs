@@ -19,14 +19,15 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 use tracing::debug;
 
 use clawft_llm::{
-    ChatMessage, ChatRequest as LlmChatRequest, ChatResponse, OpenAiCompatProvider,
-    LlmProviderConfig, ProviderRouter,
+    CachedProvider, ChatMessage, ChatRequest as LlmChatRequest, ChatResponse, ContentPart,
+    LlmCache, LlmProviderConfig, MessageContent, ProviderRouter,
 };
 use clawft_types::config::Config;
 
@@ -57,36 +58,66 @@ use super::transport::{LlmProvider, OpenAiCompatTransport};
 pub struct ClawftLlmAdapter {
     /// The wrapped clawft-llm provider.
     provider: Arc<dyn clawft_llm::Provider>,
+
+    /// Nucleus sampling threshold applied to every request
+    /// (`agents.defaults.top_p`).
+    top_p: Option<f64>,
 }
 
 impl ClawftLlmAdapter {
     /// Wrap a provider in the adapter.
     pub fn new(provider: Arc<dyn clawft_llm::Provider>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            top_p: None,
+        }
+    }
+
+    /// Send `top_p` with every request.
+    pub fn with_top_p(mut self, top_p: Option<f64>) -> Self {
+        self.top_p = top_p;
+        self
     }
 }
 
-#[async_trait]
-impl LlmProvider for ClawftLlmAdapter {
-    async fn complete(
+impl ClawftLlmAdapter {
+    /// Convert a pipeline call into a clawft-llm request and send it.
+    /// `cache` marks the request as allowed to be served from the response
+    /// cache.
+    async fn send(
         &self,
         model: &str,
         messages: &[serde_json::Value],
         tools: &[serde_json::Value],
         max_tokens: Option<i32>,
         temperature: Option<f64>,
+        cache: bool,
     ) -> Result<serde_json::Value, String> {
         // -- Inbound conversion: Value messages -> ChatMessage ---------------
         let chat_messages: Vec<ChatMessage> =
             messages.iter().map(convert_value_to_message).collect();
+        let cache_tools = caches_tools(&chat_messages, tools);
 
         let request = LlmChatRequest {
             model: model.to_string(),
             messages: chat_messages,
             max_tokens,
             temperature,
+            top_p: self.top_p,
+            stop: Vec::new(),
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: HashMap::new(),
             tools: tools.to_vec(),
+            tool_choice: None,
             stream: None,
+            response_format: None,
+            cache,
+            cache_tools,
+            timeout: None,
+            reasoning_effort: None,
+            max_reasoning_tokens: None,
         };
 
         debug!(
@@ -94,6 +125,7 @@ impl LlmProvider for ClawftLlmAdapter {
             model = %model,
             messages = request.messages.len(),
             tools = request.tools.len(),
+            cache,
             "adapter forwarding request to clawft-llm provider"
         );
 
@@ -104,6 +136,33 @@ impl LlmProvider for ClawftLlmAdapter {
             Err(e) => Err(e.to_string()),
         }
     }
+}
+
+#[async_trait]
+impl LlmProvider for ClawftLlmAdapter {
+    async fn complete(
+        &self,
+        model: &str,
+        messages: &[serde_json::Value],
+        tools: &[serde_json::Value],
+        max_tokens: Option<i32>,
+        temperature: Option<f64>,
+    ) -> Result<serde_json::Value, String> {
+        self.send(model, messages, tools, max_tokens, temperature, false)
+            .await
+    }
+
+    async fn complete_cacheable(
+        &self,
+        model: &str,
+        messages: &[serde_json::Value],
+        tools: &[serde_json::Value],
+        max_tokens: Option<i32>,
+        temperature: Option<f64>,
+    ) -> Result<serde_json::Value, String> {
+        self.send(model, messages, tools, max_tokens, temperature, true)
+            .await
+    }
 
     async fn complete_stream(
         &self,
@@ -116,14 +175,28 @@ impl LlmProvider for ClawftLlmAdapter {
     ) -> Result<serde_json::Value, String> {
         let chat_messages: Vec<ChatMessage> =
             messages.iter().map(convert_value_to_message).collect();
+        let cache_tools = caches_tools(&chat_messages, tools);
 
         let request = LlmChatRequest {
             model: model.to_string(),
             messages: chat_messages,
             max_tokens,
             temperature,
+            top_p: self.top_p,
+            stop: Vec::new(),
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: HashMap::new(),
             tools: tools.to_vec(),
+            tool_choice: None,
             stream: Some(true),
+            response_format: None,
+            cache: false,
+            cache_tools,
+            timeout: None,
+            reasoning_effort: None,
+            max_reasoning_tokens: None,
         };
 
         debug!(
@@ -142,60 +215,29 @@ impl LlmProvider for ClawftLlmAdapter {
         let stream_handle =
             tokio::spawn(async move { provider.complete_stream(&request, chunk_tx).await });
 
-        // Forward text deltas to the pipeline's string-based channel
-        // and accumulate them for the final response
-        let mut full_text = String::new();
-        let mut finish_reason = None;
-        let mut usage = None;
+        // Forward text deltas to the pipeline's string-based channel and
+        // fold every chunk (including tool-call deltas) into the response.
+        let mut acc = clawft_llm::StreamAccumulator::new();
 
         while let Some(chunk) = chunk_rx.recv().await {
-            match chunk {
-                clawft_llm::StreamChunk::TextDelta { text } => {
-                    full_text.push_str(&text);
-                    if tx.send(text).await.is_err() {
-                        // Receiver dropped, stop streaming
-                        break;
-                    }
-                }
-                clawft_llm::StreamChunk::Done {
-                    finish_reason: fr,
-                    usage: u,
-                } => {
-                    if fr.is_some() {
-                        finish_reason = fr;
-                    }
-                    if u.is_some() {
-                        usage = u;
-                    }
-                }
-                clawft_llm::StreamChunk::ToolCallDelta { .. } => {
-                    // Tool call deltas are not forwarded as text, but we
-                    // could extend this in the future
-                }
+            acc.push(&chunk);
+            if let clawft_llm::StreamChunk::TextDelta { text } = chunk
+                && tx.send(text).await.is_err()
+            {
+                // Receiver dropped, stop streaming
+                break;
             }
         }
+        drop(chunk_rx);
 
-        // Wait for the stream task to complete
-        let _ = stream_handle.await;
-
-        // Build a synthetic ChatResponse from the accumulated text
-        let fr = finish_reason.unwrap_or_else(|| "stop".into());
-        let llm_usage = usage.map(|u| clawft_llm::Usage {
-            input_tokens: u.input_tokens,
-            output_tokens: u.output_tokens,
-            total_tokens: u.total_tokens,
-        });
+        // Wait for the stream task to complete and surface its failure
+        match stream_handle.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(e.to_string()),
+            Err(e) => return Err(format!("streaming task failed: {e}")),
+        }
 
-        let response = ChatResponse {
-            id: "stream-response".into(),
-            model: model.to_string(),
-            choices: vec![clawft_llm::types::Choice {
-                index: 0,
-                message: ChatMessage::assistant(full_text),
-                finish_reason: Some(fr),
-            }],
-            usage: llm_usage,
-        };
+        let response = acc.finish(model);
 
         Ok(convert_response_to_value(&response))
     }
@@ -207,10 +249,17 @@ impl LlmProvider for ClawftLlmAdapter {
 
 /// Convert a `serde_json::Value` message into a [`ChatMessage`].
 ///
-/// Extracts `role`, `content`, `tool_call_id`, and `tool_calls` fields.
+/// Extracts `role`, `content` (or `parts`), `tool_call_id`, and `tool_calls`
+/// fields.
 /// When an assistant message has tool_calls and empty/null content, content
 /// is set to `None` so that the API receives `"content": null` instead of
 /// `"content": ""` (which Anthropic's endpoint rejects).
+/// Tool definitions precede the system prompt in the prompt prefix, so they
+/// are cached whenever the caller marked a cacheable prefix.
+fn caches_tools(messages: &[ChatMessage], tools: &[serde_json::Value]) -> bool {
+    !tools.is_empty() && messages.iter().any(|m| m.cache)
+}
+
 fn convert_value_to_message(value: &serde_json::Value) -> ChatMessage {
     let role = value["role"].as_str().unwrap_or("user").to_string();
     let content_str = value["content"].as_str().map(String::from);
@@ -228,11 +277,19 @@ fn convert_value_to_message(value: &serde_json::Value) -> ChatMessage {
             }).ok()
         });
 
+    // Multimodal messages carry their parts in `parts` (see
+    // `LlmMessage::parts`) or, in OpenAI form, as a `content` array.
+    let parts: Option<Vec<ContentPart>> = value
+        .get("parts")
+        .or_else(|| value.get("content").filter(|c| c.is_array()))
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+
     // Use None for content when it's empty/missing and tool_calls are present,
     // so the serialised request sends "content": null (required by Anthropic).
-    let content = match content_str {
-        Some(s) if s.is_empty() && tool_calls.is_some() => None,
-        other => other,
+    let content = match (parts, content_str) {
+        (Some(parts), _) => Some(MessageContent::Parts(parts)),
+        (None, Some(s)) if s.is_empty() && tool_calls.is_some() => None,
+        (None, other) => other.map(MessageContent::Text),
     };
 
     ChatMessage {
@@ -240,6 +297,11 @@ fn convert_value_to_message(value: &serde_json::Value) -> ChatMessage {
         content,
         tool_call_id,
         tool_calls,
+        cache: value
+            .get("cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        reasoning: None,
     }
 }
 
@@ -256,6 +318,9 @@ fn convert_response_to_value(response: &ChatResponse) -> serde_json::Value {
             if let Some(ref tcs) = c.message.tool_calls {
                 msg["tool_calls"] = serde_json::to_value(tcs).unwrap_or_default();
             }
+            if let Some(ref reasoning) = c.message.reasoning {
+                msg["reasoning"] = serde_json::json!(reasoning);
+            }
             serde_json::json!({
                 "index": c.index,
                 "message": msg,
@@ -265,19 +330,39 @@ fn convert_response_to_value(response: &ChatResponse) -> serde_json::Value {
         .collect();
 
     let usage = response.usage.as_ref().map(|u| {
-        serde_json::json!({
+        let mut usage = serde_json::json!({
             "prompt_tokens": u.input_tokens,
             "completion_tokens": u.output_tokens,
             "total_tokens": u.total_tokens,
-        })
+        });
+        if u.cached {
+            usage["cached"] = serde_json::json!(true);
+        }
+        if u.cache_read_tokens > 0 {
+            usage["cache_read_tokens"] = serde_json::json!(u.cache_read_tokens);
+        }
+        if u.cache_write_tokens > 0 {
+            usage["cache_write_tokens"] = serde_json::json!(u.cache_write_tokens);
+        }
+        if u.reasoning_tokens > 0 {
+            usage["reasoning_tokens"] = serde_json::json!(u.reasoning_tokens);
+        }
+        usage
     });
 
-    serde_json::json!({
+    let mut value = serde_json::json!({
         "id": response.id,
         "model": response.model,
         "choices": choices,
         "usage": usage,
-    })
+    });
+    if !response.failover.is_empty() {
+        value["failover"] = serde_json::to_value(&response.failover).unwrap_or_default();
+    }
+    if response.retries > 0 {
+        value["retries"] = serde_json::json!(response.retries);
+    }
+    value
 }
 
 // ---------------------------------------------------------------------------
@@ -294,12 +379,39 @@ fn convert_response_to_value(response: &ChatResponse) -> serde_json::Value {
 ///    from [`clawft_llm::config::builtin_providers()`].
 /// 3. If the application config (`config.providers`) has an API key or
 ///    base URL override for that provider, apply it.
-/// 4. Create an [`OpenAiCompatProvider`] and wrap it in a [`ClawftLlmAdapter`].
+/// 4. Create the provider (see [`clawft_llm::router::provider_for_config`])
+///    and wrap it in a [`ClawftLlmAdapter`].
 ///
 /// Falls back to the first built-in provider (OpenAI) when no prefix matches.
 pub fn create_adapter_from_config(config: &Config) -> Arc<dyn LlmProvider> {
     let model = &config.agents.defaults.model;
-    let (prefix, _bare_model) = ProviderRouter::strip_prefix(model);
+    let provider_config = default_provider_config(config);
+
+    debug!(
+        provider = %provider_config.name,
+        base_url = %provider_config.base_url,
+        model = %model,
+        "creating LLM adapter from config"
+    );
+
+    // Wrap in RetryPolicy so transient errors (5xx, rate-limit, timeout)
+    // are retried with exponential backoff at the provider level.
+    let retry = clawft_llm::retry::RetryConfig::default();
+    let provider = clawft_llm::router::provider_for_config(provider_config, None);
+    let provider = clawft_llm::retry::RetryPolicy::new(provider, retry);
+    let provider = with_response_cache(Box::new(provider), config);
+    Arc::new(ClawftLlmAdapter::new(Arc::from(provider)).with_top_p(config.agents.defaults.top_p))
+}
+
+/// The provider config for `config.agents.defaults.model`: the built-in
+/// config matching its prefix (OpenAI when none matches), with overrides
+/// from `config.providers` applied.
+///
+/// This is steps 1-3 of [`create_adapter_from_config`], for callers that
+/// need the provider itself, such as `weft status` checking that the
+/// configured model exists.
+pub fn default_provider_config(config: &Config) -> LlmProviderConfig {
+    let (prefix, _bare_model) = ProviderRouter::strip_prefix(&config.agents.defaults.model);
 
     let builtins = clawft_llm::config::builtin_providers();
 
@@ -315,20 +427,40 @@ pub fn create_adapter_from_config(config: &Config) -> Arc<dyn LlmProvider> {
 
     // Apply overrides from the application config's providers section.
     apply_config_overrides(&mut provider_config, config, prefix.as_deref());
+    provider_config
+}
 
-    debug!(
-        provider = %provider_config.name,
-        base_url = %provider_config.base_url,
-        model = %model,
-        "creating LLM adapter from config"
-    );
+/// The built-in config of the provider called `name`, with overrides from
+/// `config.providers` applied, or `None` when there is no such built-in
+/// provider.
+pub fn named_provider_config(config: &Config, name: &str) -> Option<LlmProviderConfig> {
+    let mut provider_config = clawft_llm::config::builtin_providers()
+        .into_iter()
+        .find(|c| c.name == name)?;
+    apply_config_overrides(&mut provider_config, config, Some(name));
+    Some(provider_config)
+}
 
-    let provider = OpenAiCompatProvider::new(provider_config);
-    // Wrap in RetryPolicy so transient errors (5xx, rate-limit, timeout)
-    // are retried with exponential backoff at the provider level.
-    let retrying =
-        clawft_llm::retry::RetryPolicy::new(provider, clawft_llm::retry::RetryConfig::default());
-    Arc::new(ClawftLlmAdapter::new(Arc::new(retrying)))
+/// Put the response cache from `agents.cache` in front of `provider`.
+///
+/// The cache only answers requests that opt in at temperature 0, so it is
+/// transparent to conversational calls. With `persist` set, entries are
+/// also kept under `~/.clawft/state/llm_cache`.
+fn with_response_cache(
+    provider: Box<dyn clawft_llm::Provider>,
+    config: &Config,
+) -> Box<dyn clawft_llm::Provider> {
+    let settings = &config.agents.cache;
+    if !settings.enabled {
+        return provider;
+    }
+    let mut cache = LlmCache::new(settings.capacity, Duration::from_secs(settings.ttl_secs));
+    if settings.persist
+        && let Some(home) = dirs::home_dir()
+    {
+        cache = cache.with_dir(clawft_llm::cache::cache_dir(&home));
+    }
+    Box::new(CachedProvider::new(provider, Arc::new(cache)))
 }
 
 /// Apply API key and base URL overrides from the application config to a
@@ -336,7 +468,10 @@ pub fn create_adapter_from_config(config: &Config) -> Arc<dyn LlmProvider> {
 ///
 /// The application config stores provider credentials in
 /// `config.providers.<name>`, where each entry has `api_key` and optionally
-/// `api_base`. If present, these override the built-in defaults.
+/// `api_base`. If present, these override the built-in defaults. The
+/// provider's `rate_limit` is always taken from the application config, and
+/// Azure deployments and API version are merged into the built-in Azure
+/// settings.
 fn apply_config_overrides(
     llm_config: &mut LlmProviderConfig,
     app_config: &Config,
@@ -353,6 +488,7 @@ fn apply_config_overrides(
         "openrouter" => &app_config.providers.openrouter,
         "gemini" => &app_config.providers.gemini,
         "xai" => &app_config.providers.xai,
+        "azure" => &app_config.providers.azure,
         "mistral" | "together" => return, // supported builtins with no config override
         _ => return,
     };
@@ -370,6 +506,28 @@ fn apply_config_overrides(
             llm_config.headers.insert(k.clone(), v.clone());
         }
     }
+
+    llm_config.rate_limit = app_provider.rate_limit.clone();
+
+    if app_provider.timeout_secs.is_some() {
+        llm_config.timeout_secs = app_provider.timeout_secs;
+    }
+    if app_provider.connect_timeout_secs.is_some() {
+        llm_config.connect_timeout_secs = app_provider.connect_timeout_secs;
+    }
+    if app_provider.read_timeout_secs.is_some() {
+        llm_config.read_timeout_secs = app_provider.read_timeout_secs;
+    }
+    if app_provider.reasoning_effort.is_some() {
+        llm_config.reasoning_effort = app_provider.reasoning_effort;
+    }
+
+    if let Some(azure) = llm_config.azure.as_mut() {
+        azure.deployments.extend(app_provider.deployments.clone());
+        if let Some(ref version) = app_provider.api_version {
+            azure.api_version = version.clone();
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -404,13 +562,12 @@ fn create_adapter_for_provider(
     // Check for an explicit API key in the app config.
     let app_api_key = resolve_app_api_key(provider_name, config);
 
-    let provider = if let Some(key) = app_api_key {
-        OpenAiCompatProvider::with_api_key(provider_config, key)
-    } else {
-        OpenAiCompatProvider::new(provider_config)
-    };
+    let provider: Arc<dyn clawft_llm::Provider> = Arc::from(with_response_cache(
+        clawft_llm::router::provider_for_config(provider_config, app_api_key),
+        config,
+    ));
 
-    Arc::new(ClawftLlmAdapter::new(Arc::new(provider)))
+    Arc::new(ClawftLlmAdapter::new(provider).with_top_p(config.agents.defaults.top_p))
 }
 
 /// Resolve an explicit API key from the application config for a provider.
@@ -426,6 +583,7 @@ fn resolve_app_api_key(provider_name: &str, config: &Config) -> Option<String> {
         "openrouter" => &config.providers.openrouter.api_key,
         "gemini" => &config.providers.gemini.api_key,
         "xai" => &config.providers.xai.api_key,
+        "azure" => &config.providers.azure.api_key,
         _ => return None,
     };
     if key.is_empty() { None } else { Some(key.expose().to_string()) }
@@ -575,9 +733,22 @@ mod tests {
                     model: "anthropic/claude-opus-4-5".into(),
                     max_tokens: 4096,
                     temperature: 0.7,
+                    top_p: None,
                     max_tool_iterations: 10,
                     memory_window: 50,
+                    max_parallel_tools: 4,
                 },
+                dispatch: Default::default(),
+                usage: Default::default(),
+                budget: Default::default(),
+                memory: Default::default(),
+                cache: Default::default(),
+                sessions: Default::default(),
+                compaction: Default::default(),
+                planning: Default::default(),
+                templates: Default::default(),
+                skills: Default::default(),
+                interactive: Default::default(),
             },
             ..Config::default()
         }
@@ -593,7 +764,7 @@ mod tests {
         });
         let msg = convert_value_to_message(&value);
         assert_eq!(msg.role, "user");
-        assert_eq!(msg.content.as_deref(), Some("Hello, world!"));
+        assert_eq!(msg.text().as_deref(), Some("Hello, world!"));
         assert!(msg.tool_call_id.is_none());
         assert!(msg.tool_calls.is_none());
     }
@@ -607,10 +778,60 @@ mod tests {
         });
         let msg = convert_value_to_message(&value);
         assert_eq!(msg.role, "tool");
-        assert_eq!(msg.content.as_deref(), Some("result data"));
+        assert_eq!(msg.text().as_deref(), Some("result data"));
         assert_eq!(msg.tool_call_id.as_deref(), Some("call-123"));
     }
 
+    #[test]
+    fn adapter_converts_multimodal_parts() {
+        let message = crate::pipeline::traits::LlmMessage {
+            role: "user".into(),
+            content: "What is this?".into(),
+            tool_call_id: None,
+            tool_calls: None,
+            parts: Some(vec![
+                ContentPart::text("What is this?"),
+                ContentPart::Image(clawft_llm::ImagePart::base64("image/png", "cG5n")),
+            ]),
+            cache: false,
+        };
+        let msg = convert_value_to_message(&serde_json::to_value(&message).unwrap());
+        assert_eq!(
+            msg.content,
+            Some(MessageContent::Parts(message.parts.unwrap()))
+        );
+
+        // OpenAI-style content arrays are accepted too.
+        let value = serde_json::json!({
+            "role": "user",
+            "content": [{"type": "image_url", "image_url": {"url": "https://x/a.png"}}],
+        });
+        let msg = convert_value_to_message(&value);
+        let images: Vec<_> = msg.content.as_ref().unwrap().images().collect();
+        assert_eq!(images, [&clawft_llm::ImagePart::url("https://x/a.png")]);
+    }
+
+    #[test]
+    fn adapter_carries_cache_marker() {
+        let message = crate::pipeline::traits::LlmMessage {
+            role: "system".into(),
+            content: "You are clawft.".into(),
+            tool_call_id: None,
+            tool_calls: None,
+            parts: None,
+            cache: true,
+        };
+        let msg = convert_value_to_message(&serde_json::to_value(&message).unwrap());
+        assert!(msg.cache);
+        let tools = [serde_json::json!({"type": "function", "function": {"name": "t"}})];
+        assert!(caches_tools(std::slice::from_ref(&msg), &tools));
+        assert!(!caches_tools(std::slice::from_ref(&msg), &[]));
+
+        let plain = convert_value_to_message(&serde_json::json!({"role": "user", "content": "hi"}));
+        assert!(!plain.cache);
+        assert!(!caches_tools(&[plain], &tools));
+    }
+
     #[test]
     fn adapter_converts_message_defaults() {
         // Missing role and content should fall back to defaults.
@@ -635,7 +856,13 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 5,
                 total_tokens: 15,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             }),
+            failover: Vec::new(),
+            retries: 0,
         }
     }
 
@@ -659,6 +886,32 @@ mod tests {
         assert_eq!(usage["total_tokens"], 15);
     }
 
+    #[test]
+    fn adapter_converts_prompt_cache_usage() {
+        let mut response = make_text_response();
+        let usage = response.usage.as_mut().unwrap();
+        usage.cache_read_tokens = 8;
+        usage.cache_write_tokens = 2;
+        let value = convert_response_to_value(&response);
+        assert_eq!(value["usage"]["cache_read_tokens"], 8);
+        assert_eq!(value["usage"]["cache_write_tokens"], 2);
+
+        let plain = convert_response_to_value(&make_text_response());
+        assert!(plain["usage"].get("cache_read_tokens").is_none());
+    }
+
+    #[test]
+    fn adapter_keeps_reasoning_out_of_content() {
+        let mut response = make_text_response();
+        response.choices[0].message.reasoning = Some("weighing options".into());
+        response.usage.as_mut().unwrap().reasoning_tokens = 12;
+        let value = convert_response_to_value(&response);
+        let message = &value["choices"][0]["message"];
+        assert_eq!(message["reasoning"], "weighing options");
+        assert!(!message["content"].to_string().contains("weighing"));
+        assert_eq!(value["usage"]["reasoning_tokens"], 12);
+    }
+
     #[test]
     fn adapter_converts_response_no_usage() {
         let response = ChatResponse {
@@ -670,6 +923,8 @@ mod tests {
                 finish_reason: Some("stop".into()),
             }],
             usage: None,
+            failover: Vec::new(),
+            retries: 0,
         };
         let value = convert_response_to_value(&response);
         assert!(value["usage"].is_null());
@@ -697,6 +952,8 @@ mod tests {
                             arguments: r#"{"city":"London"}"#.into(),
                         },
                     }]),
+                    cache: false,
+                    reasoning: None,
                 },
                 finish_reason: Some("tool_calls".into()),
             }],
@@ -704,7 +961,13 @@ mod tests {
                 input_tokens: 15,
                 output_tokens: 8,
                 total_tokens: 23,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             }),
+            failover: Vec::new(),
+            retries: 0,
         };
 
         let value = convert_response_to_value(&response);
@@ -773,7 +1036,7 @@ mod tests {
             let content = request
                 .messages
                 .last()
-                .map(|m| format!("echo: {}", m.content.as_deref().unwrap_or("")))
+                .map(|m| format!("echo: {}", m.text().unwrap_or_default()))
                 .unwrap_or_else(|| "echo: (empty)".into());
             Ok(ChatResponse {
                 id: "echo-resp".into(),
@@ -787,7 +1050,13 @@ mod tests {
                     input_tokens: 5,
                     output_tokens: 3,
                     total_tokens: 8,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 }),
+                failover: Vec::new(),
+                retries: 0,
             })
         }
     }
@@ -812,6 +1081,46 @@ mod tests {
         assert_eq!(result["usage"]["total_tokens"], 8);
     }
 
+    /// Replies with the `top_p` it was sent.
+    struct TopPProvider;
+
+    #[async_trait]
+    impl clawft_llm::Provider for TopPProvider {
+        fn name(&self) -> &str {
+            "top-p"
+        }
+        async fn complete(&self, request: &LlmChatRequest) -> clawft_llm::Result<ChatResponse> {
+            Ok(ChatResponse {
+                id: "top-p".into(),
+                model: request.model.clone(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: ChatMessage::assistant(format!("{:?}", request.top_p)),
+                    finish_reason: Some("stop".into()),
+                }],
+                usage: None,
+                failover: Vec::new(),
+                retries: 0,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn adapter_sends_configured_top_p() {
+        let messages = vec![serde_json::json!({"role": "user", "content": "hi"})];
+
+        let unset = ClawftLlmAdapter::new(Arc::new(TopPProvider));
+        let result = unset
+            .complete("m", &messages, &[], None, None)
+            .await
+            .unwrap();
+        assert_eq!(result["choices"][0]["message"]["content"], "None");
+
+        let set = ClawftLlmAdapter::new(Arc::new(TopPProvider)).with_top_p(Some(0.9));
+        let result = set.complete("m", &messages, &[], None, None).await.unwrap();
+        assert_eq!(result["choices"][0]["message"]["content"], "Some(0.9)");
+    }
+
     // -- factory tests ------------------------------------------------------
 
     #[test]
@@ -919,6 +1228,96 @@ mod tests {
         assert!(llm_config.headers.contains_key("anthropic-version"));
     }
 
+    #[test]
+    fn overrides_copy_rate_limit() {
+        let mut config = test_config();
+        config.providers.openai.rate_limit.requests_per_minute = Some(60);
+
+        let mut llm_config = clawft_llm::config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "openai")
+            .unwrap();
+
+        apply_config_overrides(&mut llm_config, &config, Some("openai"));
+        assert_eq!(llm_config.rate_limit.requests_per_minute, Some(60));
+    }
+
+    #[test]
+    fn overrides_copy_timeouts() {
+        let mut config = test_config();
+        config.providers.anthropic.timeout_secs = Some(30);
+        config.providers.anthropic.read_timeout_secs = Some(15);
+
+        let mut llm_config = clawft_llm::config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "anthropic")
+            .unwrap();
+
+        apply_config_overrides(&mut llm_config, &config, Some("anthropic"));
+        assert_eq!(llm_config.timeout_secs, Some(30));
+        assert_eq!(llm_config.read_timeout_secs, Some(15));
+        assert_eq!(llm_config.connect_timeout_secs, None);
+    }
+
+    #[test]
+    fn overrides_copy_reasoning_effort() {
+        let mut config = test_config();
+        config.providers.openai.reasoning_effort = Some(clawft_llm::ReasoningEffort::High);
+
+        let mut llm_config = clawft_llm::config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "openai")
+            .unwrap();
+        assert_eq!(llm_config.reasoning_effort, None);
+
+        apply_config_overrides(&mut llm_config, &config, Some("openai"));
+        assert_eq!(
+            llm_config.reasoning_effort,
+            Some(clawft_llm::ReasoningEffort::High)
+        );
+    }
+
+    #[test]
+    fn default_provider_config_follows_model_prefix() {
+        let mut config = test_config();
+        config.agents.defaults.model = "ollama/llama3.2".into();
+        let provider = default_provider_config(&config);
+        assert_eq!(provider.name, "ollama");
+        assert_eq!(provider.base_url, "http://localhost:11434/v1");
+
+        config.agents.defaults.model = "gpt-4o".into();
+        assert_eq!(default_provider_config(&config).name, "openai");
+    }
+
+    #[test]
+    fn named_provider_config_applies_overrides() {
+        let mut config = test_config();
+        config.providers.groq.api_base = Some("https://groq.internal/v1".into());
+        let provider = named_provider_config(&config, "groq").unwrap();
+        assert_eq!(provider.base_url, "https://groq.internal/v1");
+        assert!(named_provider_config(&config, "nonexistent").is_none());
+    }
+
+    #[test]
+    fn overrides_configure_azure_deployments() {
+        let mut config = test_config();
+        config.agents.defaults.model = "azure/gpt-4o".into();
+        config.providers.azure.api_base = Some("https://my-resource.openai.azure.com".into());
+        config
+            .providers
+            .azure
+            .deployments
+            .insert("gpt-4o".into(), "prod-gpt4o".into());
+        config.providers.azure.api_version = Some("2025-01-01-preview".into());
+
+        let provider = default_provider_config(&config);
+        assert_eq!(provider.name, "azure");
+        assert_eq!(provider.base_url, "https://my-resource.openai.azure.com");
+        let azure = provider.azure.unwrap();
+        assert_eq!(azure.deployment("gpt-4o"), "prod-gpt4o");
+        assert_eq!(azure.api_version, "2025-01-01-preview");
+    }
+
     // -- end-to-end: MockProvider -> ClawftLlmAdapter -> OpenAiCompatTransport -
 
     /// End-to-end round-trip test that exercises the full adapter-to-transport path.
@@ -953,17 +1352,22 @@ mod tests {
                     content: "You are a test assistant.".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "user".into(),
                     content: "integration test".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
             ],
             tools: vec![],
             max_tokens: Some(100),
             temperature: Some(0.5),
+            cache: false,
         };
 
         // 5. Call complete and verify
@@ -1004,10 +1408,13 @@ mod tests {
                 content: "this should fail".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             max_tokens: None,
             temperature: None,
+            cache: false,
         };
 
         let result = transport.complete(&request).await;
@@ -1055,6 +1462,8 @@ mod tests {
                                     arguments: r#"{"query":"rust lang"}"#.into(),
                                 },
                             }]),
+                            cache: false,
+                            reasoning: None,
                         },
                         finish_reason: Some("tool_calls".into()),
                     }],
@@ -1062,7 +1471,13 @@ mod tests {
                         input_tokens: 20,
                         output_tokens: 10,
                         total_tokens: 30,
+                        cached: false,
+                        cache_read_tokens: 0,
+                        cache_write_tokens: 0,
+                        reasoning_tokens: 0,
                     }),
+                    failover: Vec::new(),
+                    retries: 0,
                 })
             }
         }
@@ -1079,10 +1494,13 @@ mod tests {
                 content: "search for rust".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![serde_json::json!({"type": "function", "name": "web_search"})],
             max_tokens: Some(100),
             temperature: None,
+            cache: false,
         };
 
         let response = transport
@@ -1106,4 +1524,104 @@ mod tests {
             other => panic!("expected ToolUse block, got: {other:?}"),
         }
     }
+
+    /// Provider that counts calls and always answers "simple".
+    struct CountingProvider(Arc<std::sync::atomic::AtomicU32>);
+
+    #[async_trait]
+    impl clawft_llm::Provider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+        async fn complete(
+            &self,
+            _request: &LlmChatRequest,
+        ) -> clawft_llm::Result<ChatResponse> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ChatResponse {
+                id: "resp".into(),
+                model: "test-model".into(),
+                choices: vec![Choice {
+                    index: 0,
+                    message: ChatMessage::assistant("simple"),
+                    finish_reason: Some("stop".into()),
+                }],
+                usage: Some(LlmUsage {
+                    input_tokens: 7,
+                    output_tokens: 1,
+                    total_tokens: 8,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
+                }),
+                failover: Vec::new(),
+                retries: 0,
+            })
+        }
+    }
+
+    /// Opted-in temperature-0 requests are answered from the response cache
+    /// on repeat; ordinary requests always reach the provider.
+    #[tokio::test]
+    async fn transport_serves_opted_in_requests_from_cache() {
+        use crate::pipeline::traits::{LlmMessage, LlmTransport, TransportRequest};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let provider =
+            with_response_cache(Box::new(CountingProvider(calls.clone())), &test_config());
+        let adapter: Arc<dyn LlmProvider> = Arc::new(ClawftLlmAdapter::new(Arc::from(provider)));
+        let transport = OpenAiCompatTransport::with_provider(adapter);
+
+        let mut request = TransportRequest {
+            provider: "counting".into(),
+            model: "test-model".into(),
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: "classify: hello".into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            max_tokens: Some(5),
+            temperature: Some(0.0),
+            cache: true,
+        };
+
+        let first = transport.complete(&request).await.unwrap();
+        let second = transport.complete(&request).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(!first.usage.cached);
+        assert!(second.usage.cached);
+        assert_eq!(second.usage.input_tokens, 0);
+        assert_eq!(second.content.len(), first.content.len());
+
+        request.cache = false;
+        transport.complete(&request).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Memory merges opt in to the cache, so merging the same entries twice
+    /// reaches the provider once.
+    #[tokio::test]
+    async fn memory_merge_is_served_from_cache() {
+        use crate::agent::memory::hygiene::EntryMerger;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let provider =
+            with_response_cache(Box::new(CountingProvider(calls.clone())), &test_config());
+        let adapter: Arc<dyn LlmProvider> = Arc::new(ClawftLlmAdapter::new(Arc::from(provider)));
+        let transport = Arc::new(OpenAiCompatTransport::with_provider(adapter));
+        let merger = EntryMerger::new(transport, "counting/test-model");
+
+        let entries = ["likes tea", "drinks tea every morning"];
+        let first = merger.merge(&entries).await.unwrap();
+        let second = merger.merge(&entries).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
 }
@@ -1,6 +1,7 @@
 //! 6-stage pluggable pipeline system.
 //!
-//! Stages: Classifier -> Router -> Assembler -> Transport -> Scorer -> Learner
+//! Stages: Classifier -> Router -> Assembler -> Transport -> Scorer -> Learner,
+//! with custom stages inserted between them (see [`stages`]).
 
 pub mod assembler;
 pub mod classifier;
@@ -11,8 +12,10 @@ pub mod llm_adapter;
 pub mod mutation;
 pub mod permissions;
 pub mod rate_limiter;
+pub mod redactor;
 pub mod router;
 pub mod scorer;
+pub mod stages;
 pub mod tiered_router;
 pub mod traits;
 pub mod transport;
@@ -65,6 +68,7 @@ mod factory_tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let resp = clawft_types::provider::LlmResponse {
             id: "test".into(),
@@ -74,6 +78,10 @@ mod factory_tests {
                 input_tokens: 0,
                 output_tokens: 0,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: std::collections::HashMap::new(),
         };
@@ -86,6 +94,7 @@ mod factory_tests {
         let config = PipelineConfig {
             scorer: "fitness".into(),
             learner: "noop".into(),
+            ..Default::default()
         };
         let scorer = build_scorer(&config);
         // FitnessScorer returns different scores for empty responses
@@ -97,6 +106,7 @@ mod factory_tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let resp = clawft_types::provider::LlmResponse {
             id: "test".into(),
@@ -106,6 +116,10 @@ mod factory_tests {
                 input_tokens: 0,
                 output_tokens: 0,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: std::collections::HashMap::new(),
         };
@@ -126,6 +140,7 @@ mod factory_tests {
         let config = PipelineConfig {
             scorer: "noop".into(),
             learner: "trajectory".into(),
+            ..Default::default()
         };
         let _learner = build_learner(&config);
         // TrajectoryLearner constructed without panicking
@@ -136,6 +151,7 @@ mod factory_tests {
         let config = PipelineConfig {
             scorer: "unknown_thing".into(),
             learner: "noop".into(),
+            ..Default::default()
         };
         let scorer = build_scorer(&config);
         let req = traits::ChatRequest {
@@ -146,6 +162,7 @@ mod factory_tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let resp = clawft_types::provider::LlmResponse {
             id: "test".into(),
@@ -155,6 +172,10 @@ mod factory_tests {
                 input_tokens: 0,
                 output_tokens: 0,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: std::collections::HashMap::new(),
         };
@@ -167,6 +188,7 @@ mod factory_tests {
         let config = PipelineConfig {
             scorer: "noop".into(),
             learner: "not_real".into(),
+            ..Default::default()
         };
         let _learner = build_learner(&config);
     }
@@ -0,0 +1,126 @@
+//! Built-in `redactor` pipeline stage.
+//!
+//! Replaces text matching configured regular expressions in message and
+//! response content, e.g. to keep national ID or card numbers out of the
+//! LLM provider's logs. Placed before `transport` it scrubs what is sent;
+//! after it, what comes back. Enabled by `pipeline.redactor.patterns`.
+
+use std::borrow::Cow;
+
+use async_trait::async_trait;
+use clawft_plugin::{PipelineStage, PipelineStageType, PluginError};
+use clawft_types::error::ClawftError;
+use regex::{NoExpand, Regex};
+use serde_json::Value;
+
+/// Name the redactor registers under.
+pub const REDACTOR_STAGE: &str = "redactor";
+
+/// Object keys whose string values hold message or response text: a
+/// message's `content`, and the `text` of content parts and blocks.
+const TEXT_KEYS: [&str; 2] = ["content", "text"];
+
+/// Object keys holding tool schemas and tool call arguments, which are
+/// passed through untouched so tool calls keep working.
+const SKIPPED_KEYS: [&str; 3] = ["tools", "tool_calls", "input"];
+
+/// Pipeline stage that masks text matching any of its patterns.
+pub struct RegexRedactor {
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl RegexRedactor {
+    /// Replace matches of any of `patterns` with `replacement`.
+    pub fn new(patterns: &[String], replacement: &str) -> clawft_types::Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).map_err(|e| ClawftError::ConfigInvalid {
+                    reason: format!("pipeline.redactor: invalid pattern '{p}': {e}"),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            patterns,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    fn redact_text(&self, text: &mut String) {
+        for pattern in &self.patterns {
+            if let Cow::Owned(redacted) = pattern.replace_all(text, NoExpand(&self.replacement)) {
+                *text = redacted;
+            }
+        }
+    }
+
+    /// Redact text fields anywhere in `value`.
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    match field {
+                        _ if SKIPPED_KEYS.contains(&key.as_str()) => {}
+                        Value::String(text) if TEXT_KEYS.contains(&key.as_str()) => {
+                            self.redact_text(text);
+                        }
+                        _ => self.redact(field),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact(item)),
+            _ => {}
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineStage for RegexRedactor {
+    fn name(&self) -> &str {
+        REDACTOR_STAGE
+    }
+
+    fn stage_type(&self) -> PipelineStageType {
+        PipelineStageType::Process
+    }
+
+    async fn process(&self, mut input: Value) -> Result<Value, PluginError> {
+        self.redact(&mut input);
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn redacts_message_and_block_text_only() {
+        let redactor = RegexRedactor::new(&[r"\d{3}-\d{2}-\d{4}".into()], "[SSN]").unwrap();
+        let request = json!({
+            "model": "123-45-6789",
+            "messages": [{"role": "user", "content": "my SSN is 123-45-6789"}],
+            "tools": [{"name": "lookup", "description": "finds 123-45-6789"}]
+        });
+        let out = redactor.process(request).await.unwrap();
+        assert_eq!(out["messages"][0]["content"], "my SSN is [SSN]");
+        assert_eq!(out["model"], "123-45-6789");
+        assert_eq!(out["tools"][0]["description"], "finds 123-45-6789");
+
+        let response = json!({"content": [
+            {"type": "text", "text": "it is 987-65-4321"},
+            {"type": "tool_use", "name": "write_file", "input": {"content": "987-65-4321"}}
+        ]});
+        let out = redactor.process(response).await.unwrap();
+        assert_eq!(out["content"][0]["text"], "it is [SSN]");
+        assert_eq!(out["content"][1]["input"]["content"], "987-65-4321");
+    }
+
+    #[test]
+    fn invalid_pattern_is_a_config_error() {
+        let err = RegexRedactor::new(&["(".into()], "x").err().unwrap();
+        assert!(err.to_string().contains("pipeline.redactor"), "{err}");
+    }
+}
@@ -72,7 +72,7 @@ impl ModelRouter for StaticRouter {
 /// Split a `"provider/model"` string into `(provider, model)`.
 ///
 /// If the string contains no slash, the provider defaults to `"openai"`.
-fn split_provider_model(s: &str) -> (String, String) {
+pub(crate) fn split_provider_model(s: &str) -> (String, String) {
     if let Some(idx) = s.find('/') {
         let provider = &s[..idx];
         let model = &s[idx + 1..];
@@ -95,6 +95,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -102,6 +104,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -131,6 +134,8 @@ mod tests {
                 content: "very complex code generation task".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![serde_json::json!({"type": "function"})],
             model: Some("different-model".into()),
@@ -138,6 +143,7 @@ mod tests {
             temperature: Some(0.0),
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let profile = TaskProfile {
             task_type: TaskType::CodeGeneration,
@@ -157,6 +163,17 @@ mod tests {
                 model: "anthropic/claude-opus-4-5".into(),
                 ..AgentDefaults::default()
             },
+            dispatch: Default::default(),
+            usage: Default::default(),
+            budget: Default::default(),
+            memory: Default::default(),
+            cache: Default::default(),
+            sessions: Default::default(),
+            compaction: Default::default(),
+            planning: Default::default(),
+            templates: Default::default(),
+            skills: Default::default(),
+            interactive: Default::default(),
         };
         let router = StaticRouter::from_config(&config);
         assert_eq!(router.provider(), "anthropic");
@@ -170,6 +187,17 @@ mod tests {
                 model: "gpt-4o".into(),
                 ..AgentDefaults::default()
             },
+            dispatch: Default::default(),
+            usage: Default::default(),
+            budget: Default::default(),
+            memory: Default::default(),
+            cache: Default::default(),
+            sessions: Default::default(),
+            compaction: Default::default(),
+            planning: Default::default(),
+            templates: Default::default(),
+            skills: Default::default(),
+            interactive: Default::default(),
         };
         let router = StaticRouter::from_config(&config);
         assert_eq!(router.provider(), "openai");
@@ -370,6 +370,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -377,6 +379,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -387,6 +390,8 @@ mod tests {
                 content: "Search for rust documentation".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![serde_json::json!({"type": "function", "name": "web_search"})],
             model: None,
@@ -394,6 +399,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -408,6 +414,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 15,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         }
@@ -422,6 +432,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 0,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         }
@@ -438,6 +452,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 4000,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         }
@@ -454,6 +472,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 10,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         }
@@ -480,12 +502,16 @@ mod tests {
                     content: "You are a code reviewer.".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "user".into(),
                     content: "Review my code: fn main() {}".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
             ],
             tools: vec![serde_json::json!({"type": "function"})],
@@ -494,6 +520,7 @@ mod tests {
             temperature: Some(0.0),
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let score = scorer.score(&req, &make_response());
         assert!((score.overall - 1.0).abs() < f32::EPSILON);
@@ -610,6 +637,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 1,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         };
@@ -683,6 +714,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 300,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         };
@@ -704,6 +739,10 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 30,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         };
@@ -0,0 +1,679 @@
+//! Custom pipeline stages and per-agent stage order.
+//!
+//! The six built-in stages always run, in their fixed order; they cannot
+//! be removed or reordered, only wrapped. Custom stages implementing
+//! [`PipelineStage`] are registered by name in a [`StageRegistry`] and
+//! placed between the built-in ones by `pipeline.stages` (or
+//! `pipeline.agentStages` for one agent). A custom stage receives the
+//! value the pipeline holds at its position, as JSON, and returns it,
+//! possibly changed:
+//!
+//! | Position                            | Value                |
+//! |-------------------------------------|----------------------|
+//! | before `assembler`                  | [`ChatRequest`]      |
+//! | between `assembler` and `transport` | [`TransportRequest`] |
+//! | after `transport`                   | [`LlmResponse`]      |
+//!
+//! [`PipelineStageType::PreProcess`] stages must come before `transport`
+//! and [`PipelineStageType::PostProcess`] stages after it. Observers see
+//! the value but their output is ignored. A failing stage fails the turn
+//! or is skipped with a warning, per its [`StageErrorPolicy`].
+//!
+//! [`ChatRequest`]: super::traits::ChatRequest
+//! [`TransportRequest`]: super::traits::TransportRequest
+//! [`LlmResponse`]: clawft_types::provider::LlmResponse
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use clawft_plugin::{PipelineStage, PipelineStageType};
+use clawft_types::config::{PipelineConfig, PipelineStageEntry, StageErrorPolicy};
+use clawft_types::error::ClawftError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+use super::redactor::RegexRedactor;
+
+/// The built-in stages, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinStage {
+    /// Stage 1: task classifier.
+    Classifier,
+    /// Stage 2: model router.
+    Router,
+    /// Stage 3: context assembler.
+    Assembler,
+    /// Stage 4: LLM transport.
+    Transport,
+    /// Stage 5: quality scorer.
+    Scorer,
+    /// Stage 6: learning backend.
+    Learner,
+}
+
+impl BuiltinStage {
+    /// All built-in stages, in order.
+    pub const ALL: [BuiltinStage; 6] = [
+        Self::Classifier,
+        Self::Router,
+        Self::Assembler,
+        Self::Transport,
+        Self::Scorer,
+        Self::Learner,
+    ];
+
+    /// The name used for this stage in `pipeline.stages`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classifier => "classifier",
+            Self::Router => "router",
+            Self::Assembler => "assembler",
+            Self::Transport => "transport",
+            Self::Scorer => "scorer",
+            Self::Learner => "learner",
+        }
+    }
+
+    /// The built-in stage called `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|stage| stage.name() == name)
+    }
+}
+
+/// Index of the gap after `after` (0 is before the first built-in).
+fn gap(after: Option<BuiltinStage>) -> usize {
+    after.map_or(0, |stage| stage as usize + 1)
+}
+
+/// Whether a custom stage in gap `gap` runs before the LLM call.
+fn before_transport(gap: usize) -> bool {
+    gap <= BuiltinStage::Transport as usize
+}
+
+/// A custom stage's place in a plan.
+#[derive(Debug, Clone, PartialEq)]
+struct StageSlot {
+    name: String,
+    on_error: StageErrorPolicy,
+}
+
+/// Custom stages of one stage order, grouped by the built-in stage they
+/// follow.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StagePlan {
+    gaps: [Vec<StageSlot>; BuiltinStage::ALL.len() + 1],
+}
+
+impl StagePlan {
+    /// Build a plan from a configured stage order.
+    ///
+    /// An empty order is the built-in one. Otherwise every built-in stage
+    /// must appear exactly once, in its fixed order; custom stages may sit
+    /// anywhere between them, each at most once.
+    pub fn from_entries(
+        entries: &[PipelineStageEntry],
+        default_policy: StageErrorPolicy,
+    ) -> clawft_types::Result<Self> {
+        Self::build("pipeline.stages", entries, default_policy)
+    }
+
+    fn build(
+        field: &str,
+        entries: &[PipelineStageEntry],
+        default_policy: StageErrorPolicy,
+    ) -> clawft_types::Result<Self> {
+        let mut plan = Self::default();
+        if entries.is_empty() {
+            return Ok(plan);
+        }
+        let invalid = |reason: String| ClawftError::ConfigInvalid {
+            reason: format!("{field}: {reason}"),
+        };
+
+        let mut next = 0;
+        for entry in entries {
+            let name = entry.name();
+            if let Some(builtin) = BuiltinStage::from_name(name) {
+                if entry.on_error().is_some() {
+                    return Err(invalid(format!(
+                        "'{name}' is a built-in stage; its errors always fail the turn"
+                    )));
+                }
+                match BuiltinStage::ALL.get(next) {
+                    Some(expected) if *expected == builtin => next += 1,
+                    Some(expected) => {
+                        return Err(invalid(format!(
+                            "built-in stages cannot be removed or reordered: \
+                             expected '{}' before '{name}'",
+                            expected.name()
+                        )));
+                    }
+                    None => return Err(invalid(format!("'{name}' is listed twice"))),
+                }
+            } else {
+                if plan.slots().any(|(_, slot)| slot.name == name) {
+                    return Err(invalid(format!("'{name}' is listed twice")));
+                }
+                plan.gaps[next].push(StageSlot {
+                    name: name.to_string(),
+                    on_error: entry.on_error().unwrap_or(default_policy),
+                });
+            }
+        }
+        if let Some(missing) = BuiltinStage::ALL.get(next) {
+            return Err(invalid(format!(
+                "built-in stage '{}' is missing",
+                missing.name()
+            )));
+        }
+        Ok(plan)
+    }
+
+    /// Custom stages in run order, with the gap each sits in.
+    fn slots(&self) -> impl Iterator<Item = (usize, &StageSlot)> {
+        self.gaps
+            .iter()
+            .enumerate()
+            .flat_map(|(gap, slots)| slots.iter().map(move |slot| (gap, slot)))
+    }
+
+    /// Names of the stages in run order, built-in ones included.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for (gap, slots) in self.gaps.iter().enumerate() {
+            if gap > 0 {
+                names.push(BuiltinStage::ALL[gap - 1].name());
+            }
+            names.extend(slots.iter().map(|slot| slot.name.as_str()));
+        }
+        names
+    }
+}
+
+/// Check that a stage of type `stage_type` may sit in gap `gap`.
+fn check_placement(
+    name: &str,
+    stage_type: &PipelineStageType,
+    gap: usize,
+) -> clawft_types::Result<()> {
+    let misplaced = match stage_type {
+        PipelineStageType::PreProcess => !before_transport(gap),
+        PipelineStageType::PostProcess => before_transport(gap),
+        _ => false,
+    };
+    if misplaced {
+        let side = if before_transport(gap) {
+            "before"
+        } else {
+            "after"
+        };
+        return Err(ClawftError::ConfigInvalid {
+            reason: format!(
+                "pipeline stage '{name}' is a {stage_type:?} stage and cannot run {side} 'transport'"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Registered custom stages and the stage order for each agent.
+#[derive(Default)]
+pub struct StageRegistry {
+    stages: HashMap<String, Arc<dyn PipelineStage>>,
+    default_plan: StagePlan,
+    agent_plans: HashMap<String, StagePlan>,
+}
+
+impl StageRegistry {
+    /// An empty registry with the built-in stage order.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the stage orders in `config` and register the built-in custom
+    /// stages it enables.
+    ///
+    /// Stages the orders name but nobody has registered yet are reported
+    /// by [`missing`](Self::missing); register them with
+    /// [`register`](Self::register) before the first turn.
+    pub fn from_config(config: &PipelineConfig) -> clawft_types::Result<Self> {
+        let mut registry = Self {
+            default_plan: StagePlan::from_entries(&config.stages, config.on_stage_error)?,
+            ..Self::default()
+        };
+        for (agent, entries) in &config.agent_stages {
+            let field = format!("pipeline.agentStages.{agent}");
+            let plan = StagePlan::build(&field, entries, config.on_stage_error)?;
+            registry.agent_plans.insert(agent.clone(), plan);
+        }
+
+        let redactor = &config.redactor;
+        if !redactor.patterns.is_empty() {
+            registry.register(Arc::new(RegexRedactor::new(
+                &redactor.patterns,
+                &redactor.replacement,
+            )?))?;
+        }
+        Ok(registry)
+    }
+
+    /// Register a custom stage under its name.
+    ///
+    /// Fails when the name belongs to a built-in stage or the stage's type
+    /// does not fit where a stage order places it.
+    pub fn register(&mut self, stage: Arc<dyn PipelineStage>) -> clawft_types::Result<()> {
+        let name = stage.name().to_string();
+        if BuiltinStage::from_name(&name).is_some() {
+            return Err(ClawftError::ConfigInvalid {
+                reason: format!("pipeline stage '{name}' would replace a built-in stage"),
+            });
+        }
+        let stage_type = stage.stage_type();
+        for plan in self.plans() {
+            for (gap, slot) in plan.slots() {
+                if slot.name == name {
+                    check_placement(&name, &stage_type, gap)?;
+                }
+            }
+        }
+        self.stages.insert(name, stage);
+        Ok(())
+    }
+
+    /// Names of the registered custom stages, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.stages.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Stages named in a stage order that are not registered, sorted.
+    pub fn missing(&self) -> Vec<&str> {
+        let mut missing: Vec<&str> = self
+            .plans()
+            .flat_map(|plan| plan.slots())
+            .map(|(_, slot)| slot.name.as_str())
+            .filter(|name| !self.stages.contains_key(*name))
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+
+    /// The stage order for `agent`.
+    pub fn plan(&self, agent: &str) -> &StagePlan {
+        self.agent_plans.get(agent).unwrap_or(&self.default_plan)
+    }
+
+    fn plans(&self) -> impl Iterator<Item = &StagePlan> {
+        std::iter::once(&self.default_plan).chain(self.agent_plans.values())
+    }
+
+    /// Run `agent`'s custom stages placed after `after` (or before the
+    /// first built-in stage) on `value`.
+    ///
+    /// Returns the changed value, or `None` when no stage changed it (no
+    /// stages sit there, or only observers and skipped ones), so callers
+    /// keep their own.
+    pub(crate) async fn run<T: Serialize + DeserializeOwned>(
+        &self,
+        agent: &str,
+        after: Option<BuiltinStage>,
+        value: &T,
+    ) -> clawft_types::Result<Option<T>> {
+        let slots = &self.plan(agent).gaps[gap(after)];
+        if slots.is_empty() {
+            return Ok(None);
+        }
+
+        let mut current = serde_json::to_value(value)?;
+        let mut changed = None;
+        for slot in slots {
+            let outcome = match self.run_stage(&slot.name, current.clone()).await {
+                Ok(Some(output)) => serde_json::from_value::<T>(output.clone())
+                    .map(|parsed| Some((output, parsed)))
+                    .map_err(|e| format!("returned an invalid value: {e}")),
+                Ok(None) => Ok(None),
+                Err(reason) => Err(reason),
+            };
+            match outcome {
+                Ok(Some((output, parsed))) => {
+                    current = output;
+                    changed = Some(parsed);
+                }
+                Ok(None) => {}
+                Err(reason) => match slot.on_error {
+                    StageErrorPolicy::Abort => {
+                        return Err(ClawftError::PipelineStage {
+                            stage: slot.name.clone(),
+                            reason,
+                        });
+                    }
+                    StageErrorPolicy::Skip => {
+                        warn!(stage = %slot.name, %reason, "pipeline stage failed; skipping it");
+                    }
+                },
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Run one stage; `None` means it is an observer.
+    async fn run_stage(
+        &self,
+        name: &str,
+        input: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, String> {
+        let stage = self.stages.get(name).ok_or("stage is not registered")?;
+        let output = stage.process(input).await.map_err(|e| e.to_string())?;
+        if stage.stage_type() == PipelineStageType::Observer {
+            return Ok(None);
+        }
+        Ok(Some(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::assembler::TokenBudgetAssembler;
+    use crate::pipeline::classifier::KeywordClassifier;
+    use crate::pipeline::learner::NoopLearner;
+    use crate::pipeline::router::StaticRouter;
+    use crate::pipeline::scorer::NoopScorer;
+    use crate::pipeline::traits::{
+        ChatRequest, LlmMessage, LlmTransport, Pipeline, PipelineRegistry, TransportRequest,
+    };
+    use async_trait::async_trait;
+    use clawft_plugin::PluginError;
+    use clawft_types::provider::{ContentBlock, LlmResponse, StopReason, Usage};
+    use serde_json::{Value, json};
+
+    /// Replies with the content of the last message it was sent.
+    struct EchoTransport;
+
+    #[async_trait]
+    impl LlmTransport for EchoTransport {
+        async fn complete(&self, request: &TransportRequest) -> clawft_types::Result<LlmResponse> {
+            Ok(LlmResponse {
+                id: "echo".into(),
+                content: vec![ContentBlock::Text {
+                    text: request.messages.last().unwrap().content.clone(),
+                }],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage::default(),
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    /// Appends `-<name>` to the last message, or to the response text.
+    struct Tag(&'static str, PipelineStageType);
+
+    #[async_trait]
+    impl PipelineStage for Tag {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn stage_type(&self) -> PipelineStageType {
+            self.1.clone()
+        }
+
+        async fn process(&self, mut input: Value) -> Result<Value, PluginError> {
+            let text = match input.get_mut("messages") {
+                Some(messages) => {
+                    messages.as_array_mut().unwrap().last_mut().unwrap()["content"].take()
+                }
+                None => input["content"][0]["text"].take(),
+            };
+            let tagged = json!(format!("{}-{}", text.as_str().unwrap(), self.0));
+            match input.get_mut("messages") {
+                Some(messages) => {
+                    messages.as_array_mut().unwrap().last_mut().unwrap()["content"] = tagged
+                }
+                None => input["content"][0]["text"] = tagged,
+            }
+            Ok(input)
+        }
+    }
+
+    /// Fails, or returns something that is not a pipeline value.
+    struct Broken(&'static str, bool);
+
+    #[async_trait]
+    impl PipelineStage for Broken {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn stage_type(&self) -> PipelineStageType {
+            PipelineStageType::Process
+        }
+
+        async fn process(&self, _input: Value) -> Result<Value, PluginError> {
+            if self.1 {
+                Err(PluginError::ExecutionFailed("boom".into()))
+            } else {
+                Ok(json!(42))
+            }
+        }
+    }
+
+    fn entries(names: &[&str]) -> Vec<PipelineStageEntry> {
+        names
+            .iter()
+            .map(|name| PipelineStageEntry::Name(name.to_string()))
+            .collect()
+    }
+
+    /// The built-in order with `before`, `middle` (between assembler and
+    /// transport) and `after` (after transport) inserted.
+    fn order(before: &[&str], middle: &[&str], after: &[&str]) -> Vec<PipelineStageEntry> {
+        let mut names = before.to_vec();
+        names.extend(["classifier", "router", "assembler"]);
+        names.extend(middle);
+        names.push("transport");
+        names.extend(after);
+        names.extend(["scorer", "learner"]);
+        entries(&names)
+    }
+
+    fn registry(config: &PipelineConfig, stages: Vec<Arc<dyn PipelineStage>>) -> PipelineRegistry {
+        let mut custom = StageRegistry::from_config(config).unwrap();
+        for stage in stages {
+            custom.register(stage).unwrap();
+        }
+        let mut registry = PipelineRegistry::new(Pipeline {
+            classifier: Arc::new(KeywordClassifier::new()),
+            router: Arc::new(StaticRouter::from_config(&Default::default())),
+            assembler: Arc::new(TokenBudgetAssembler::new(4096)),
+            transport: Arc::new(EchoTransport),
+            scorer: Arc::new(NoopScorer::new()),
+            learner: Arc::new(NoopLearner::new()),
+        });
+        registry.set_stages(custom);
+        registry
+    }
+
+    fn request(text: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![LlmMessage {
+                role: "user".into(),
+                content: text.into(),
+                tool_call_id: None,
+                tool_calls: None,
+                parts: None,
+                cache: false,
+            }],
+            tools: vec![],
+            model: None,
+            max_tokens: None,
+            temperature: None,
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        }
+    }
+
+    fn text(response: &LlmResponse) -> &str {
+        match &response.content[0] {
+            ContentBlock::Text { text } => text,
+            other => panic!("unexpected block {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stage_orders_keep_builtins_in_place() {
+        type Case = (Vec<PipelineStageEntry>, Result<&'static str, &'static str>);
+        let cases: Vec<Case> = vec![
+            (
+                vec![],
+                Ok("classifier router assembler transport scorer learner"),
+            ),
+            (
+                order(&["scrub"], &["audit"], &["translate"]),
+                Ok("scrub classifier router assembler audit transport translate scorer learner"),
+            ),
+            (
+                entries(&[
+                    "classifier",
+                    "router",
+                    "transport",
+                    "assembler",
+                    "scorer",
+                    "learner",
+                ]),
+                Err("expected 'assembler' before 'transport'"),
+            ),
+            (
+                entries(&["classifier", "router", "assembler", "transport", "scorer"]),
+                Err("'learner' is missing"),
+            ),
+            (
+                order(&["scrub"], &["scrub"], &[]),
+                Err("'scrub' is listed twice"),
+            ),
+            (
+                vec![PipelineStageEntry::Detailed {
+                    name: "classifier".into(),
+                    on_error: Some(StageErrorPolicy::Skip),
+                }],
+                Err("'classifier' is a built-in stage"),
+            ),
+        ];
+        for (entries, expected) in cases {
+            let plan = StagePlan::from_entries(&entries, StageErrorPolicy::Abort);
+            match expected {
+                Ok(names) => assert_eq!(plan.unwrap().names().join(" "), names),
+                Err(reason) => {
+                    let err = plan.unwrap_err().to_string();
+                    assert!(err.contains(reason), "{err}");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_stages_run_where_configured() {
+        let mut config = PipelineConfig {
+            stages: order(&["a"], &["b"], &["post"]),
+            ..Default::default()
+        };
+        config
+            .agent_stages
+            .insert("researcher".into(), order(&["b", "a"], &[], &[]));
+        let registry = registry(
+            &config,
+            vec![
+                Arc::new(Tag("a", PipelineStageType::PreProcess)),
+                Arc::new(Tag("b", PipelineStageType::PreProcess)),
+                Arc::new(Tag("post", PipelineStageType::PostProcess)),
+            ],
+        );
+
+        let response = registry.complete(&request("hi")).await.unwrap();
+        assert_eq!(text(&response), "hi-a-b-post");
+        let response = registry
+            .complete_for("researcher", &request("hi"))
+            .await
+            .unwrap();
+        assert_eq!(text(&response), "hi-b-a");
+    }
+
+    #[tokio::test]
+    async fn failing_stages_abort_or_are_skipped() {
+        let stages = || -> Vec<Arc<dyn PipelineStage>> {
+            vec![
+                Arc::new(Broken("fails", true)),
+                Arc::new(Broken("garbles", false)),
+                Arc::new(Tag("a", PipelineStageType::PreProcess)),
+            ]
+        };
+
+        let skip = PipelineConfig {
+            stages: order(&["fails", "garbles", "a"], &[], &[]),
+            on_stage_error: StageErrorPolicy::Skip,
+            ..Default::default()
+        };
+        let response = registry(&skip, stages())
+            .complete(&request("hi"))
+            .await
+            .unwrap();
+        assert_eq!(text(&response), "hi-a");
+
+        for failing in ["fails", "garbles", "unregistered"] {
+            let mut abort = skip.clone();
+            abort.stages = order(&["a"], &[failing], &[]);
+            abort.stages[4] = PipelineStageEntry::Detailed {
+                name: failing.into(),
+                on_error: Some(StageErrorPolicy::Abort),
+            };
+            let err = registry(&abort, stages())
+                .complete(&request("hi"))
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(&err, ClawftError::PipelineStage { stage, .. } if stage == failing),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn register_checks_names_and_placement() {
+        let config = PipelineConfig {
+            stages: order(&["late"], &[], &["early"]),
+            ..Default::default()
+        };
+        let mut stages = StageRegistry::from_config(&config).unwrap();
+        assert_eq!(stages.missing(), vec!["early", "late"]);
+
+        let err = stages
+            .register(Arc::new(Tag("late", PipelineStageType::PostProcess)))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("cannot run before 'transport'"),
+            "{err}"
+        );
+        let err = stages
+            .register(Arc::new(Tag("early", PipelineStageType::PreProcess)))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("cannot run after 'transport'"),
+            "{err}"
+        );
+        assert!(
+            stages
+                .register(Arc::new(Tag("router", PipelineStageType::Process)))
+                .is_err()
+        );
+
+        stages
+            .register(Arc::new(Tag("late", PipelineStageType::Observer)))
+            .unwrap();
+        assert_eq!(stages.names(), vec!["late"]);
+        assert_eq!(stages.missing(), vec!["early"]);
+    }
+}
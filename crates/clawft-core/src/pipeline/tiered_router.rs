@@ -825,6 +825,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -832,6 +834,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -842,6 +845,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -849,6 +854,7 @@ mod tests {
             temperature: None,
             auth_context: Some(auth),
             complexity_boost: 0.0,
+            cache: false,
         }
     }
 
@@ -12,15 +12,19 @@
 //! 5. **[`QualityScorer`]** -- Score response quality
 //! 6. **[`LearningBackend`]** -- Record the interaction for future learning
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use clawft_llm::ContentPart;
 use clawft_types::provider::LlmResponse;
 use clawft_types::routing::AuthContext;
 
+use super::stages::{BuiltinStage, StageRegistry};
+
 // ── Request / message types ─────────────────────────────────────────────
 
 /// A chat request entering the pipeline.
@@ -56,6 +60,12 @@ pub struct ChatRequest {
     /// sessions into higher-tier models.
     #[serde(default)]
     pub complexity_boost: f32,
+
+    /// Allow the reply to come from the response cache. Meant for
+    /// deterministic classification and scoring prompts sent at
+    /// temperature 0; conversational turns leave it unset.
+    #[serde(default)]
+    pub cache: bool,
 }
 
 /// A single message in a chat conversation.
@@ -76,6 +86,18 @@ pub struct LlmMessage {
     /// round-trip keeps the provider happy.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<serde_json::Value>>,
+
+    /// Text and image parts sent in place of `content` for multimodal
+    /// messages. `content` still carries the text, for token estimates,
+    /// scoring and session history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parts: Option<Vec<ContentPart>>,
+
+    /// This message ends the stable prompt prefix (system prompt, skill
+    /// instructions), so providers with prompt caching may cache everything
+    /// up to and including it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
 }
 
 // ── Classification types ────────────────────────────────────────────────
@@ -189,7 +211,7 @@ pub struct AssembledContext {
 }
 
 /// Request sent to the transport layer.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransportRequest {
     /// Provider name.
     pub provider: String,
@@ -208,6 +230,10 @@ pub struct TransportRequest {
 
     /// Sampling temperature.
     pub temperature: Option<f64>,
+
+    /// Allow a cached reply (see [`clawft_llm::cache`]). Honoured only at
+    /// temperature 0.
+    pub cache: bool,
 }
 
 // ── Learning types ──────────────────────────────────────────────────────
@@ -395,10 +421,12 @@ pub struct Pipeline {
 ///
 /// When a request arrives, the registry classifies it, looks up the
 /// pipeline for that task type (falling back to the default), and
-/// orchestrates the full 6-stage flow.
+/// orchestrates the full 6-stage flow, running the custom stages of its
+/// [`StageRegistry`] between the built-in ones.
 pub struct PipelineRegistry {
     pipelines: HashMap<TaskType, Pipeline>,
     default: Pipeline,
+    stages: StageRegistry,
 }
 
 impl PipelineRegistry {
@@ -407,6 +435,7 @@ impl PipelineRegistry {
         Self {
             pipelines: HashMap::new(),
             default,
+            stages: StageRegistry::new(),
         }
     }
 
@@ -415,6 +444,21 @@ impl PipelineRegistry {
         self.pipelines.insert(task_type, pipeline);
     }
 
+    /// Replace the custom stages and stage orders.
+    pub fn set_stages(&mut self, stages: StageRegistry) {
+        self.stages = stages;
+    }
+
+    /// The custom stages and stage orders.
+    pub fn stages(&self) -> &StageRegistry {
+        &self.stages
+    }
+
+    /// The pipeline used for unregistered task types.
+    pub fn default_pipeline(&self) -> &Pipeline {
+        &self.default
+    }
+
     /// Look up the pipeline for a task type, falling back to the default.
     pub fn get(&self, task_type: &TaskType) -> &Pipeline {
         self.pipelines.get(task_type).unwrap_or(&self.default)
@@ -422,52 +466,16 @@ impl PipelineRegistry {
 
     /// Execute the full pipeline: classify -> route -> assemble -> transport -> score -> learn.
     pub async fn complete(&self, request: &ChatRequest) -> clawft_types::Result<LlmResponse> {
-        // Stage 1: classify using the default pipeline's classifier
-        let profile = self.default.classifier.classify(request);
-
-        // Select the pipeline for this task type
-        let pipeline = self.get(&profile.task_type);
-
-        // Stage 2: route
-        let routing = pipeline.router.route(request, &profile).await;
-
-        // Stage 3: assemble context
-        let context = pipeline.assembler.assemble(request, &profile).await;
-
-        // Stage 4: transport (with latency measurement)
-        let transport_request = TransportRequest {
-            provider: routing.provider.clone(),
-            model: routing.model.clone(),
-            messages: context.messages,
-            tools: request.tools.clone(),
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-        };
-        let start_ms = crate::runtime::now_millis();
-        let response = pipeline.transport.complete(&transport_request).await?;
-        let latency_ms = crate::runtime::now_millis().saturating_sub(start_ms);
-
-        // Stage 5: score
-        let quality = pipeline.scorer.score(request, &response);
-
-        // Stage 6: learn
-        let trajectory = Trajectory {
-            request: request.clone(),
-            routing: routing.clone(),
-            response: response.clone(),
-            quality,
-        };
-        pipeline.learner.record(&trajectory);
-
-        // Update the router with the outcome (now with actual latency)
-        let outcome = ResponseOutcome {
-            success: true,
-            quality: trajectory.quality,
-            latency_ms,
-        };
-        pipeline.router.update(&routing, &outcome);
+        self.run("default", request, None, None).await
+    }
 
-        Ok(response)
+    /// [`complete`](Self::complete) with `agent`'s stage order.
+    pub async fn complete_for(
+        &self,
+        agent: &str,
+        request: &ChatRequest,
+    ) -> clawft_types::Result<LlmResponse> {
+        self.run(agent, request, None, None).await
     }
 
     /// Execute the pipeline with streaming: stages 1-3 run normally, then
@@ -475,53 +483,175 @@ impl PipelineRegistry {
     /// the stream completes.
     ///
     /// The `callback` receives each text delta as it arrives and should
-    /// return `true` to continue or `false` to abort early.
+    /// return `true` to continue or `false` to abort early. Custom stages
+    /// after `transport` change the returned response, not the deltas
+    /// already streamed.
     pub async fn complete_stream(
         &self,
         request: &ChatRequest,
         callback: StreamCallback,
     ) -> clawft_types::Result<LlmResponse> {
-        // Stages 1-3 are identical to non-streaming
-        let profile = self.default.classifier.classify(request);
+        self.run("default", request, Some(callback), None).await
+    }
+
+    /// [`complete_stream`](Self::complete_stream) with `agent`'s stage order.
+    pub async fn complete_stream_for(
+        &self,
+        agent: &str,
+        request: &ChatRequest,
+        callback: StreamCallback,
+    ) -> clawft_types::Result<LlmResponse> {
+        self.run(agent, request, Some(callback), None).await
+    }
+
+    /// [`complete_for`](Self::complete_for), or with a `callback`
+    /// [`complete_stream_for`](Self::complete_stream_for), answered by
+    /// `model` (`provider/model`) instead of the model the router picks.
+    /// The router still runs and learns the outcome.
+    pub async fn complete_pinned(
+        &self,
+        agent: &str,
+        model: &str,
+        request: &ChatRequest,
+        callback: Option<StreamCallback>,
+    ) -> clawft_types::Result<LlmResponse> {
+        self.run(agent, request, callback, Some(model)).await
+    }
+
+    async fn run(
+        &self,
+        agent: &str,
+        request: &ChatRequest,
+        callback: Option<StreamCallback>,
+        pinned: Option<&str>,
+    ) -> clawft_types::Result<LlmResponse> {
+        let mut request = Cow::Borrowed(request);
+        self.run_on_request(agent, None, &mut request).await?;
+
+        // Stage 1: classify using the default pipeline's classifier
+        let profile = self.default.classifier.classify(&request);
+        self.run_on_request(agent, Some(BuiltinStage::Classifier), &mut request)
+            .await?;
+
+        // Select the pipeline for this task type
         let pipeline = self.get(&profile.task_type);
-        let routing = pipeline.router.route(request, &profile).await;
-        let context = pipeline.assembler.assemble(request, &profile).await;
 
-        let transport_request = TransportRequest {
+        // Stage 2: route
+        let mut routing = pipeline.router.route(&request, &profile).await;
+        if let Some(model) = pinned {
+            let (provider, model) = super::router::split_provider_model(model);
+            routing = RoutingDecision {
+                provider,
+                model,
+                reason: "model pinned by the sender".into(),
+                tier: None,
+                ..routing
+            };
+        }
+        self.run_on_request(agent, Some(BuiltinStage::Router), &mut request)
+            .await?;
+
+        // Stage 3: assemble context
+        let context = pipeline.assembler.assemble(&request, &profile).await;
+        let mut transport_request = TransportRequest {
             provider: routing.provider.clone(),
             model: routing.model.clone(),
             messages: context.messages,
             tools: request.tools.clone(),
             max_tokens: request.max_tokens,
             temperature: request.temperature,
+            cache: request.cache,
         };
+        let stages = &self.stages;
+        if let Some(changed) = stages
+            .run(agent, Some(BuiltinStage::Assembler), &transport_request)
+            .await?
+        {
+            transport_request = changed;
+        }
 
-        // Stage 4: streaming transport (with latency measurement)
+        // Stage 4: transport (with latency measurement)
         let start_ms = crate::runtime::now_millis();
-        let response = pipeline
-            .transport
-            .complete_stream(&transport_request, callback)
-            .await?;
+        let mut response = match callback {
+            Some(callback) => {
+                pipeline
+                    .transport
+                    .complete_stream(&transport_request, callback)
+                    .await?
+            }
+            None => pipeline.transport.complete(&transport_request).await?,
+        };
+        stamp_routing(&mut response, &routing);
         let latency_ms = crate::runtime::now_millis().saturating_sub(start_ms);
+        if let Some(changed) = stages
+            .run(agent, Some(BuiltinStage::Transport), &response)
+            .await?
+        {
+            response = changed;
+        }
 
-        // Stages 5-6: score and learn
-        let quality = pipeline.scorer.score(request, &response);
+        // Stage 5: score
+        let quality = pipeline.scorer.score(&request, &response);
+        if let Some(changed) = stages
+            .run(agent, Some(BuiltinStage::Scorer), &response)
+            .await?
+        {
+            response = changed;
+        }
+
+        // Stage 6: learn
         let trajectory = Trajectory {
-            request: request.clone(),
+            request: request.into_owned(),
             routing: routing.clone(),
             response: response.clone(),
             quality,
         };
         pipeline.learner.record(&trajectory);
+
+        // Update the router with the outcome (now with actual latency)
         let outcome = ResponseOutcome {
             success: true,
             quality: trajectory.quality,
             latency_ms,
         };
         pipeline.router.update(&routing, &outcome);
+        if let Some(changed) = stages
+            .run(agent, Some(BuiltinStage::Learner), &response)
+            .await?
+        {
+            response = changed;
+        }
 
         Ok(response)
     }
+
+    /// Run the custom stages after `after` on the chat request. Its auth
+    /// context is not serialized, so it is carried over, never taken from
+    /// a stage.
+    async fn run_on_request(
+        &self,
+        agent: &str,
+        after: Option<BuiltinStage>,
+        request: &mut Cow<'_, ChatRequest>,
+    ) -> clawft_types::Result<()> {
+        if let Some(mut changed) = self.stages.run(agent, after, &**request).await? {
+            changed.auth_context = request.auth_context.clone();
+            *request = Cow::Owned(changed);
+        }
+        Ok(())
+    }
+}
+
+/// Record which provider served a response, and the routed model unless
+/// the transport already reported the model it used.
+fn stamp_routing(response: &mut LlmResponse, routing: &RoutingDecision) {
+    response
+        .metadata
+        .insert("provider".into(), serde_json::json!(routing.provider));
+    response
+        .metadata
+        .entry("model".into())
+        .or_insert_with(|| serde_json::json!(routing.model));
 }
 
 #[cfg(test)]
@@ -539,6 +669,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: Some("gpt-4o".into()),
@@ -546,6 +678,7 @@ mod tests {
             temperature: Some(0.7),
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         assert_eq!(req.messages.len(), 1);
         assert_eq!(req.model.as_deref(), Some("gpt-4o"));
@@ -558,6 +691,8 @@ mod tests {
             content: "result data".into(),
             tool_call_id: Some("call-123".into()),
             tool_calls: None,
+            parts: None,
+            cache: false,
         };
         assert_eq!(msg.tool_call_id.as_deref(), Some("call-123"));
     }
@@ -607,6 +742,8 @@ mod tests {
                 content: "You are a helpful assistant.".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             token_estimate: 50,
             truncated: false,
@@ -624,6 +761,7 @@ mod tests {
             tools: vec![],
             max_tokens: Some(2048),
             temperature: None,
+            cache: false,
         };
         assert_eq!(req.provider, "openai");
         assert!(req.temperature.is_none());
@@ -666,12 +804,16 @@ mod tests {
                     content: "You are helpful.".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
                 LlmMessage {
                     role: "user".into(),
                     content: "Write a function".into(),
                     tool_call_id: None,
                     tool_calls: None,
+                    parts: None,
+                    cache: false,
                 },
             ],
             tools: vec![serde_json::json!({"type": "function", "name": "web_search"})],
@@ -680,6 +822,7 @@ mod tests {
             temperature: Some(0.5),
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let json = serde_json::to_string(&req).unwrap();
         let restored: ChatRequest = serde_json::from_str(&json).unwrap();
@@ -699,6 +842,8 @@ mod tests {
             content: "search results".into(),
             tool_call_id: Some("tc-42".into()),
             tool_calls: None,
+            parts: None,
+            cache: false,
         };
         let json = serde_json::to_string(&msg).unwrap();
         let restored: LlmMessage = serde_json::from_str(&json).unwrap();
@@ -713,6 +858,8 @@ mod tests {
             content: "hello".into(),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(!json.contains("tool_call_id"));
@@ -830,6 +977,10 @@ mod tests {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 0,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 },
                 metadata: HashMap::new(),
             })
@@ -886,6 +1037,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         });
     }
 
@@ -915,6 +1067,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -922,6 +1076,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
 
         let response = registry.complete(&request).await.unwrap();
@@ -953,6 +1108,8 @@ mod tests {
                 content: "write code".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -960,6 +1117,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
 
         // The classifier returns CodeGeneration, so the specialized pipeline is used.
@@ -967,6 +1125,33 @@ mod tests {
         assert_eq!(response.id, "test-resp");
     }
 
+    #[tokio::test]
+    async fn pipeline_registry_complete_pinned_overrides_router() {
+        let registry =
+            PipelineRegistry::new(make_test_pipeline(TaskType::Chat, "openai", "gpt-4o"));
+        let request = ChatRequest {
+            messages: vec![],
+            tools: vec![],
+            model: None,
+            max_tokens: None,
+            temperature: None,
+            auth_context: None,
+            complexity_boost: 0.0,
+            cache: false,
+        };
+
+        let routed = registry.complete(&request).await.unwrap();
+        assert_eq!(routed.metadata["provider"], "openai");
+        assert_eq!(routed.metadata["model"], "gpt-4o");
+
+        let pinned = registry
+            .complete_pinned("default", "anthropic/claude-haiku-4-5", &request, None)
+            .await
+            .unwrap();
+        assert_eq!(pinned.metadata["provider"], "anthropic");
+        assert_eq!(pinned.metadata["model"], "claude-haiku-4-5");
+    }
+
     // ── Phase F: ChatRequest auth_context serde injection prevention tests ──
 
     /// F-01: skip_deserializing prevents auth_context injection via JSON input.
@@ -1012,6 +1197,8 @@ mod tests {
                 content: "hi".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -1019,6 +1206,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
         let json = serde_json::to_string(&req).unwrap();
         // skip_deserializing only affects the Deserialize side.
@@ -1046,6 +1234,8 @@ mod tests {
                 content: "hi".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -1057,6 +1247,7 @@ mod tests {
                 permissions: UserPermissions::default(),
             }),
             complexity_boost: 0.0,
+            cache: false,
         };
 
         // Serialize -- should include auth_context.
@@ -1092,6 +1283,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -1099,6 +1292,7 @@ mod tests {
             temperature: None,
             auth_context: Some(AuthContext::cli_default()),
             complexity_boost: 0.0,
+            cache: false,
         };
 
         // Complete should succeed with auth_context present.
@@ -1118,6 +1312,8 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             model: None,
@@ -1125,6 +1321,7 @@ mod tests {
             temperature: None,
             auth_context: None,
             complexity_boost: 0.0,
+            cache: false,
         };
 
         // Should not panic and should return a valid response.
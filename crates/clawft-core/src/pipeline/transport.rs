@@ -16,7 +16,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use clawft_types::error::ClawftError;
 use clawft_types::provider::{ContentBlock, LlmResponse, StopReason, Usage};
@@ -59,6 +59,22 @@ pub trait LlmProvider: Send + Sync {
         temperature: Option<f64>,
     ) -> Result<serde_json::Value, String>;
 
+    /// Like [`complete`](Self::complete), but the caller accepts a cached
+    /// reply for this request (see [`TransportRequest::cache`]).
+    ///
+    /// The default implementation ignores the hint and calls `complete()`.
+    async fn complete_cacheable(
+        &self,
+        model: &str,
+        messages: &[serde_json::Value],
+        tools: &[serde_json::Value],
+        max_tokens: Option<i32>,
+        temperature: Option<f64>,
+    ) -> Result<serde_json::Value, String> {
+        self.complete(model, messages, tools, max_tokens, temperature)
+            .await
+    }
+
     /// Execute a streaming chat completion, sending text deltas to the channel.
     ///
     /// Each string sent is a text delta from the SSE stream. The function
@@ -190,19 +206,31 @@ impl LlmTransport for OpenAiCompatTransport {
         );
 
         // Call the provider
-        let raw_response = provider
-            .complete(
-                &request.model,
-                &messages,
-                &request.tools,
-                request.max_tokens,
-                request.temperature,
-            )
-            .await
-            .map_err(|e| ClawftError::Provider { message: e })?;
+        let raw_response = if request.cache {
+            provider
+                .complete_cacheable(
+                    &request.model,
+                    &messages,
+                    &request.tools,
+                    request.max_tokens,
+                    request.temperature,
+                )
+                .await
+        } else {
+            provider
+                .complete(
+                    &request.model,
+                    &messages,
+                    &request.tools,
+                    request.max_tokens,
+                    request.temperature,
+                )
+                .await
+        }
+        .map_err(|e| ClawftError::Provider { message: e })?;
 
         // Convert the raw JSON to our LlmResponse
-        convert_response(raw_response)
+        convert_response(raw_response, &request.tools)
     }
 
     #[cfg(feature = "native")]
@@ -276,7 +304,7 @@ impl LlmTransport for OpenAiCompatTransport {
         })?;
 
         match stream_result {
-            Ok(raw_response) => convert_response(raw_response),
+            Ok(raw_response) => convert_response(raw_response, &request.tools),
             Err(e) => {
                 if !full_text.is_empty() {
                     debug!(
@@ -291,6 +319,10 @@ impl LlmTransport for OpenAiCompatTransport {
                             input_tokens: 0,
                             output_tokens: 0,
                             total_tokens: 0,
+                            cached: false,
+                            cache_read_tokens: 0,
+                            cache_write_tokens: 0,
+                            reasoning_tokens: 0,
                         },
                         metadata: HashMap::new(),
                     })
@@ -305,8 +337,13 @@ impl LlmTransport for OpenAiCompatTransport {
 /// Convert a raw OpenAI-format JSON response to an [`LlmResponse`].
 ///
 /// Handles both text responses and tool-call responses, extracting
-/// content blocks, stop reason, and usage statistics.
-fn convert_response(resp: serde_json::Value) -> clawft_types::Result<LlmResponse> {
+/// content blocks, stop reason, and usage statistics. Tool-call arguments
+/// are repaired if malformed and coerced to the tool's parameter schema
+/// from `tools`; any repair is logged.
+fn convert_response(
+    resp: serde_json::Value,
+    tools: &[serde_json::Value],
+) -> clawft_types::Result<LlmResponse> {
     let id = resp
         .get("id")
         .and_then(|v| v.as_str())
@@ -367,8 +404,27 @@ fn convert_response(resp: serde_json::Value) -> clawft_types::Result<LlmResponse
                 .and_then(|v| v.as_str())
                 .unwrap_or("{}");
 
-            let input: serde_json::Value =
-                crate::json_repair::parse_with_repair(arguments).unwrap_or(serde_json::json!({}));
+            let parsed = match tool_parameters(tools, &name) {
+                Some(schema) => crate::json_repair::parse_with_schema(arguments, schema),
+                None => crate::json_repair::parse_with_report(arguments),
+            };
+            let input = match parsed {
+                Ok((input, report)) => {
+                    if !report.is_empty() {
+                        warn!(
+                            tool = %name,
+                            truncated = report.repaired_truncation,
+                            repairs = %report,
+                            "repaired tool call arguments"
+                        );
+                    }
+                    input
+                }
+                Err(e) => {
+                    warn!(tool = %name, error = %e, "unparseable tool call arguments");
+                    serde_json::json!({})
+                }
+            };
 
             content.push(ContentBlock::ToolUse {
                 id: tc_id,
@@ -402,10 +458,37 @@ fn convert_response(resp: serde_json::Value) -> clawft_types::Result<LlmResponse
             .and_then(|u| u.get("total_tokens"))
             .and_then(|v| v.as_u64())
             .unwrap_or(0) as u32,
+        cached: usage_obj
+            .and_then(|u| u.get("cached"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        cache_read_tokens: usage_obj
+            .and_then(|u| u.get("cache_read_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        cache_write_tokens: usage_obj
+            .and_then(|u| u.get("cache_write_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        reasoning_tokens: usage_obj
+            .and_then(|u| u.get("reasoning_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
     };
 
     let mut metadata = HashMap::new();
     metadata.insert("model".into(), serde_json::json!(model));
+    if let Some(failover) = resp.get("failover") {
+        metadata.insert("failover".into(), failover.clone());
+    }
+    if let Some(retries) = resp.get("retries") {
+        metadata.insert("retries".into(), retries.clone());
+    }
+    // Reasoning stays in metadata, out of the content blocks that become
+    // the reply.
+    if let Some(reasoning) = message.get("reasoning").filter(|v| v.is_string()) {
+        metadata.insert("reasoning".into(), reasoning.clone());
+    }
 
     Ok(LlmResponse {
         id,
@@ -416,6 +499,19 @@ fn convert_response(resp: serde_json::Value) -> clawft_types::Result<LlmResponse
     })
 }
 
+/// The parameter schema of tool `name` among OpenAI-format tool
+/// definitions.
+fn tool_parameters<'a>(
+    tools: &'a [serde_json::Value],
+    name: &str,
+) -> Option<&'a serde_json::Value> {
+    tools
+        .iter()
+        .map(|tool| &tool["function"])
+        .find(|function| function["name"] == name)
+        .and_then(|function| function.get("parameters"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,10 +527,13 @@ mod tests {
                 content: "hello".into(),
                 tool_call_id: None,
                 tool_calls: None,
+                parts: None,
+                cache: false,
             }],
             tools: vec![],
             max_tokens: Some(1024),
             temperature: Some(0.7),
+            cache: false,
         }
     }
 
@@ -642,7 +741,7 @@ mod tests {
             },
             "model": "gpt-4o"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.id, "test-id");
         assert_eq!(result.stop_reason, StopReason::EndTurn);
         match &result.content[0] {
@@ -673,7 +772,7 @@ mod tests {
             }],
             "model": "gpt-4o"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.stop_reason, StopReason::ToolUse);
         assert_eq!(result.content.len(), 1);
         match &result.content[0] {
@@ -686,6 +785,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn convert_response_keeps_every_tool_call_in_order() {
+        let call = |id: &str, q: &str| {
+            serde_json::json!({
+                "id": id,
+                "type": "function",
+                "function": {"name": "search", "arguments": format!("{{\"q\": \"{q}\"}}")}
+            })
+        };
+        let resp = serde_json::json!({
+            "id": "tc-multi",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [call("call-1", "a"), call("call-2", "b"), call("call-3", "c")]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "model": "gpt-4o"
+        });
+        let result = convert_response(resp, &[]).unwrap();
+        let calls: Vec<(&str, &str)> = result
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, input, .. } => {
+                    Some((id.as_str(), input["q"].as_str().unwrap()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls, [("call-1", "a"), ("call-2", "b"), ("call-3", "c")]);
+    }
+
     #[test]
     fn convert_response_no_choices() {
         let resp = serde_json::json!({
@@ -693,7 +828,7 @@ mod tests {
             "choices": [],
             "model": "test"
         });
-        let result = convert_response(resp);
+        let result = convert_response(resp, &[]);
         assert!(result.is_err());
     }
 
@@ -711,10 +846,73 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.stop_reason, StopReason::MaxTokens);
     }
 
+    #[test]
+    fn convert_response_keeps_failover_events() {
+        let resp = serde_json::json!({
+            "id": "failed-over",
+            "model": "openai/gpt-4o",
+            "choices": [{
+                "message": {"content": "hi"},
+                "finish_reason": "stop"
+            }],
+            "failover": [{"provider": "openai", "model": "gpt-4o", "reason": "circuit_open"}],
+            "retries": 2
+        });
+        let result = convert_response(resp, &[]).unwrap();
+        assert_eq!(
+            result.metadata["failover"][0]["reason"],
+            serde_json::json!("circuit_open")
+        );
+        assert_eq!(result.metadata["retries"], 2);
+    }
+
+    #[test]
+    fn convert_response_reads_prompt_cache_usage() {
+        let resp = serde_json::json!({
+            "id": "cached-prefix",
+            "model": "claude-sonnet-4-5",
+            "choices": [{
+                "message": {"content": "hi"},
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 1200,
+                "completion_tokens": 10,
+                "cache_read_tokens": 1000,
+                "cache_write_tokens": 100
+            }
+        });
+        let result = convert_response(resp, &[]).unwrap();
+        assert_eq!(result.usage.cache_read_tokens, 1000);
+        assert_eq!(result.usage.cache_write_tokens, 100);
+    }
+
+    #[test]
+    fn convert_response_segregates_reasoning() {
+        let resp = serde_json::json!({
+            "id": "r1",
+            "model": "deepseek-reasoner",
+            "choices": [{
+                "message": {"content": "4", "reasoning": "2 + 2 is 4."},
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 30,
+                "reasoning_tokens": 25
+            }
+        });
+        let result = convert_response(resp, &[]).unwrap();
+        assert_eq!(result.content.len(), 1);
+        assert!(matches!(&result.content[0], ContentBlock::Text { text } if text == "4"));
+        assert_eq!(result.metadata["reasoning"], "2 + 2 is 4.");
+        assert_eq!(result.usage.reasoning_tokens, 25);
+    }
+
     #[test]
     fn convert_response_missing_usage() {
         let resp = serde_json::json!({
@@ -729,7 +927,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.usage.input_tokens, 0);
         assert_eq!(result.usage.output_tokens, 0);
     }
@@ -756,7 +954,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         // Should have both text and tool use blocks
         assert_eq!(result.content.len(), 2);
         assert!(matches!(&result.content[0], ContentBlock::Text { .. }));
@@ -774,6 +972,7 @@ mod tests {
             tools: vec![serde_json::json!({"type": "function"})],
             max_tokens: None,
             temperature: None,
+            cache: false,
         };
         let req2 = make_transport_request();
 
@@ -805,7 +1004,7 @@ mod tests {
             "model": "test"
         });
         // Should not fail -- invalid arguments default to empty object
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         match &result.content[0] {
             ContentBlock::ToolUse { input, .. } => {
                 assert_eq!(*input, serde_json::json!({}));
@@ -814,6 +1013,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn convert_response_repairs_arguments_against_the_tool_schema() {
+        let resp = serde_json::json!({
+            "id": "truncated-args",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "id": "call-1",
+                        "type": "function",
+                        "function": {
+                            "name": "read_file",
+                            "arguments": "{\"path\": \"src/main.rs\", \"limit\": \"20\", \"offs"
+                        }
+                    }]
+                },
+                "finish_reason": "length"
+            }],
+            "model": "test"
+        });
+        let tools = [serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "read_file",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "limit": {"type": "integer"}
+                    }
+                }
+            }
+        })];
+        let result = convert_response(resp, &tools).unwrap();
+        match &result.content[0] {
+            ContentBlock::ToolUse { input, .. } => {
+                assert_eq!(
+                    *input,
+                    serde_json::json!({"path": "src/main.rs", "limit": 20})
+                );
+            }
+            _ => panic!("expected ToolUse"),
+        }
+    }
+
     // ── GAP-17: Tool call parsing edge case tests ─────────────────────
 
     #[test]
@@ -838,7 +1084,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.content.len(), 1);
         match &result.content[0] {
             ContentBlock::ToolUse { id, name, input } => {
@@ -873,7 +1119,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         match &result.content[0] {
             ContentBlock::ToolUse { name, input, .. } => {
                 assert_eq!(name, "list_dir");
@@ -907,7 +1153,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         match &result.content[0] {
             ContentBlock::ToolUse { name, input, .. } => {
                 assert_eq!(name, "complex_tool");
@@ -937,7 +1183,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         match &result.content[0] {
             ContentBlock::ToolUse { id, name, input } => {
                 assert_eq!(id, "call-nofunc");
@@ -984,7 +1230,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.content.len(), 2);
         match &result.content[0] {
             ContentBlock::ToolUse { name, .. } => assert_eq!(name, "read_file"),
@@ -1019,7 +1265,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         // Null content should not produce a Text block
         assert_eq!(result.content.len(), 1);
         assert!(matches!(&result.content[0], ContentBlock::ToolUse { .. }));
@@ -1036,7 +1282,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp);
+        let result = convert_response(resp, &[]);
         assert!(result.is_err(), "should fail when message field is missing");
     }
 
@@ -1055,7 +1301,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         assert_eq!(result.stop_reason, StopReason::EndTurn);
     }
 
@@ -1074,7 +1320,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         // null finish_reason should default to "stop" -> EndTurn
         assert_eq!(result.stop_reason, StopReason::EndTurn);
     }
@@ -1104,7 +1350,7 @@ mod tests {
             }],
             "model": "test"
         });
-        let result = convert_response(resp).unwrap();
+        let result = convert_response(resp, &[]).unwrap();
         match &result.content[0] {
             ContentBlock::ToolUse { input, .. } => {
                 // arguments.as_str() returns None for a JSON object,
@@ -22,12 +22,24 @@
 //! planning_step_timeout = "60s"
 //! circuit_breaker_no_op_limit = 3
 //! ```
+//!
+//! # Planning mode
+//!
+//! The agent loop's plan-then-execute turns (`/plan <task>`, or every
+//! message with `agents.planning.enabled`) use the types below: the model
+//! replies with a [`Plan`] matching [`plan_schema`], the plan waits in the
+//! session as a [`PlanState`] until the user approves it, and each step's
+//! output is checked against its success criteria as a [`StepVerdict`].
 
 use std::time::Duration;
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::warn;
 
+use clawft_types::session::Session;
+
 /// Default maximum planning depth.
 const DEFAULT_MAX_DEPTH: u32 = 10;
 
@@ -284,6 +296,314 @@ impl std::fmt::Debug for PlanningRouter {
     }
 }
 
+// ── Planning mode ───────────────────────────────────────────────────────
+
+/// Session metadata key holding the [`PlanState`].
+pub const PLAN_STATE_KEY: &str = "plan";
+
+/// Most steps a plan may have.
+pub const MAX_PLAN_STEPS: usize = DEFAULT_MAX_DEPTH as usize;
+
+/// Instructions for the planning model.
+const PLAN_PROMPT: &str = "You plan tasks for an AI assistant that has tools. Break the \
+task into the fewest steps that will get it done. For each step give what to do, the tool \
+it mainly relies on (if any), and a success criterion that can be checked from the step's \
+output alone. Do not carry out any step. Reply with a JSON object only, matching this \
+schema:";
+
+/// Instructions for the model that checks a step's output.
+const VERDICT_PROMPT: &str = "You check whether one step of a plan succeeded. Judge only \
+from the step's output against its success criterion. Reply with a JSON object only: \
+{\"passed\": true or false, \"reason\": \"one sentence\"}.";
+
+/// One step of a [`Plan`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    /// What to do.
+    pub description: String,
+
+    /// The tool the step mainly relies on, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+
+    /// How to tell from the step's output that it succeeded.
+    pub success_criteria: String,
+}
+
+/// A plan drafted by the model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    /// The outcome the plan works towards.
+    pub goal: String,
+
+    /// Steps, in execution order.
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    /// The plan as shown to the user.
+    pub fn render(&self) -> String {
+        let mut out = format!("Plan: {}\n", self.goal);
+        for (i, step) in self.steps.iter().enumerate() {
+            out.push_str(&format!("\n{}. {}", i + 1, step.description));
+            if let Some(tool) = &step.tool {
+                out.push_str(&format!(" (tool: {tool})"));
+            }
+            out.push_str(&format!("\n   Done when: {}", step.success_criteria));
+        }
+        out
+    }
+}
+
+/// JSON Schema of a [`Plan`] reply.
+pub fn plan_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "goal": {"type": "string", "minLength": 1},
+            "steps": {
+                "type": "array",
+                "minItems": 1,
+                "maxItems": MAX_PLAN_STEPS,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "description": {"type": "string", "minLength": 1},
+                        "tool": {"type": ["string", "null"]},
+                        "success_criteria": {"type": "string", "minLength": 1}
+                    },
+                    "required": ["description", "success_criteria"]
+                }
+            }
+        },
+        "required": ["goal", "steps"]
+    })
+}
+
+/// The result of checking a step's output against its success criteria.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepVerdict {
+    /// Whether the step succeeded.
+    pub passed: bool,
+
+    /// Why, in one sentence.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// JSON Schema of a [`StepVerdict`] reply.
+pub fn verdict_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "passed": {"type": "boolean"},
+            "reason": {"type": "string"}
+        },
+        "required": ["passed"]
+    })
+}
+
+/// Parse a model reply into `T`, repairing malformed JSON and checking it
+/// against `schema`.
+///
+/// The error lists what is wrong, worded so it can be sent back to the
+/// model.
+pub fn parse_structured<T: DeserializeOwned>(text: &str, schema: &Value) -> Result<T, String> {
+    let value = crate::json_repair::parse_with_schema(text, schema)
+        .map(|(value, _)| value)
+        .map_err(|e| format!("the reply is not valid JSON ({e})"))?;
+    clawft_llm::structured::validate(&value, schema).map_err(|errors| errors.join("; "))?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// The system prompt asking for a plan. `tools` are the names of the
+/// tools the steps may use.
+pub fn plan_prompt(tools: &[String]) -> String {
+    let schema = serde_json::to_string_pretty(&plan_schema()).unwrap_or_default();
+    let mut prompt = format!("{PLAN_PROMPT}\n\n{schema}");
+    if !tools.is_empty() {
+        prompt.push_str(&format!("\n\nAvailable tools: {}", tools.join(", ")));
+    }
+    prompt
+}
+
+/// The system prompt asking for a [`StepVerdict`].
+pub fn verdict_prompt() -> &'static str {
+    VERDICT_PROMPT
+}
+
+/// The instruction that runs step `index` of `plan`.
+pub fn step_instruction(plan: &Plan, index: usize) -> String {
+    let step = &plan.steps[index];
+    let mut out = format!(
+        "We are carrying out an approved plan towards: {}\n\nDo step {} of {} now, and only \
+         this step: {}",
+        plan.goal,
+        index + 1,
+        plan.steps.len(),
+        step.description
+    );
+    if let Some(tool) = &step.tool {
+        out.push_str(&format!("\nExpected tool: {tool}"));
+    }
+    out.push_str(&format!(
+        "\nThe step succeeds when: {}\nFinish with a short report of what you did.",
+        step.success_criteria
+    ));
+    out
+}
+
+/// Lifecycle of a [`PlanState`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    /// Drafted and shown to the user; nothing runs until it is approved.
+    AwaitingApproval,
+    /// Approved; steps from `next_step` on are being carried out.
+    Executing,
+}
+
+/// The outcome of one executed step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepOutcome {
+    /// Index of the step in the plan it belonged to.
+    pub step: usize,
+
+    /// Whether the output met the step's success criteria.
+    pub passed: bool,
+
+    /// What the step reported.
+    pub output: String,
+
+    /// Why it passed or failed.
+    pub reason: String,
+}
+
+/// A plan kept in the session between turns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanState {
+    /// The task the plan was drafted for.
+    pub task: String,
+
+    /// The current plan.
+    pub plan: Plan,
+
+    /// Where the plan stands.
+    pub status: PlanStatus,
+
+    /// Index of the next step to run.
+    pub next_step: usize,
+
+    /// Outcomes of the steps run so far, across revisions.
+    #[serde(default)]
+    pub outcomes: Vec<StepOutcome>,
+
+    /// Revised plans drafted after failed steps.
+    #[serde(default)]
+    pub replans: u32,
+}
+
+impl PlanState {
+    /// A freshly drafted plan, waiting for approval.
+    pub fn new(task: impl Into<String>, plan: Plan) -> Self {
+        Self {
+            task: task.into(),
+            plan,
+            status: PlanStatus::AwaitingApproval,
+            next_step: 0,
+            outcomes: Vec::new(),
+            replans: 0,
+        }
+    }
+
+    /// Read the state from the session metadata.
+    pub fn load(session: &Session) -> Option<Self> {
+        let value = session.metadata.get(PLAN_STATE_KEY)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    /// Write the state into the session metadata.
+    pub fn store(&self, session: &mut Session) {
+        if let Ok(value) = serde_json::to_value(self) {
+            session.metadata.insert(PLAN_STATE_KEY.into(), value);
+        }
+    }
+
+    /// Remove any plan from the session metadata.
+    pub fn clear(session: &mut Session) {
+        session.metadata.remove(PLAN_STATE_KEY);
+    }
+
+    /// Replace the plan with a revision, which then waits for approval
+    /// again. Steps already done are not part of the revision.
+    pub fn revise(&mut self, plan: Plan) {
+        self.plan = plan;
+        self.status = PlanStatus::AwaitingApproval;
+        self.next_step = 0;
+        self.replans += 1;
+    }
+
+    /// The request for a revised plan after the step of `failed` did not
+    /// meet its criteria.
+    pub fn replan_request(&self, failed: &StepOutcome) -> String {
+        let mut out = format!("Task: {}\n\nA plan for it was approved:\n", self.task);
+        out.push_str(&self.plan.render());
+        let done: Vec<&StepOutcome> = self.outcomes.iter().filter(|o| o.passed).collect();
+        if !done.is_empty() {
+            out.push_str("\n\nSteps completed so far:");
+            for outcome in done {
+                out.push_str(&format!("\n- {}", outcome.output));
+            }
+        }
+        out.push_str(&format!(
+            "\n\nStep {} failed: {}\nIts output was: {}\n\nDraft a revised plan for the \
+             remaining work only.",
+            failed.step + 1,
+            failed.reason,
+            failed.output
+        ));
+        out
+    }
+}
+
+/// The user's answer to a plan awaiting approval.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanReply {
+    /// Run the plan.
+    Approve,
+    /// Drop the plan.
+    Reject,
+    /// Revise the plan as described.
+    Modify(String),
+}
+
+/// Read a reply to a plan awaiting approval. Anything that is not a
+/// plain yes or no is taken as a change request.
+pub fn parse_plan_reply(content: &str) -> PlanReply {
+    let word = content
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_ascii_lowercase();
+    match word.as_str() {
+        "approve" | "approved" | "yes" | "y" | "ok" | "go" | "go ahead" | "continue" => {
+            PlanReply::Approve
+        }
+        "reject" | "no" | "n" | "cancel" | "stop" => PlanReply::Reject,
+        _ => PlanReply::Modify(content.trim().to_string()),
+    }
+}
+
+/// Parse a `/plan <task>` command: `None` if `content` is not one,
+/// otherwise the task, possibly empty.
+pub fn parse_plan_command(content: &str) -> Option<&str> {
+    let rest = content.trim().strip_prefix("/plan")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim())
+}
+
 fn serialize_duration_secs<S>(d: &Duration, s: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -456,6 +776,75 @@ mod tests {
         assert_eq!(json, "\"circuit_breaker\"");
     }
 
+    #[test]
+    fn plans_are_parsed_against_the_schema() {
+        let plan: Plan = parse_structured(
+            r#"```json
+            {"goal": "g", "steps": [{"description": "d", "tool": null, "success_criteria": "c"},]}
+            ```"#,
+            &plan_schema(),
+        )
+        .unwrap();
+        assert_eq!(plan.steps[0].tool, None);
+        assert_eq!(plan.steps[0].success_criteria, "c");
+
+        let err = parse_structured::<Plan>(
+            r#"{"goal": "g", "steps": [{"description": "d"}]}"#,
+            &plan_schema(),
+        )
+        .unwrap_err();
+        assert!(err.contains("success_criteria"), "{err}");
+        let err =
+            parse_structured::<Plan>(r#"{"goal": "g", "steps": []}"#, &plan_schema()).unwrap_err();
+        assert!(err.contains("$.steps"), "{err}");
+    }
+
+    #[test]
+    fn plan_commands_and_replies() {
+        assert_eq!(
+            parse_plan_command("/plan tidy the logs"),
+            Some("tidy the logs")
+        );
+        assert_eq!(parse_plan_command(" /plan "), Some(""));
+        assert_eq!(parse_plan_command("/planet"), None);
+        assert_eq!(parse_plan_command("plan it"), None);
+
+        assert_eq!(parse_plan_reply("Approve."), PlanReply::Approve);
+        assert_eq!(parse_plan_reply("continue"), PlanReply::Approve);
+        assert_eq!(parse_plan_reply("No"), PlanReply::Reject);
+        assert_eq!(
+            parse_plan_reply(" skip step 2 "),
+            PlanReply::Modify("skip step 2".into())
+        );
+    }
+
+    #[test]
+    fn plan_state_round_trips_through_the_session() {
+        let mut session = Session::new("cli:c1");
+        let plan = Plan {
+            goal: "g".into(),
+            steps: vec![PlanStep {
+                description: "d".into(),
+                tool: Some("read_file".into()),
+                success_criteria: "c".into(),
+            }],
+        };
+        let mut state = PlanState::new("task", plan.clone());
+        state.store(&mut session);
+        assert_eq!(PlanState::load(&session), Some(state.clone()));
+
+        state.next_step = 1;
+        state.status = PlanStatus::Executing;
+        state.revise(plan);
+        assert_eq!(
+            (state.status, state.next_step, state.replans),
+            (PlanStatus::AwaitingApproval, 0, 1)
+        );
+
+        PlanState::clear(&mut session);
+        assert!(PlanState::load(&session).is_none());
+    }
+
     #[test]
     fn router_accessors() {
         let router = PlanningRouter::with_defaults(PlanningStrategy::PlanAndExecute);
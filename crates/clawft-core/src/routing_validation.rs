@@ -7,10 +7,14 @@
 //!
 //! Validation runs after deserialization, before `TieredRouter` construction.
 //! When `routing.mode` is `"static"`, validation is skipped entirely.
+//!
+//! Message routing rules (`routing.rules`) apply in every mode and are
+//! checked separately by [`validate_routing_rules`], against the agents
+//! the rules may name.
 
 use std::collections::HashSet;
 
-use clawft_types::routing::{ModelTierConfig, PermissionLevelConfig, RoutingConfig};
+use clawft_types::routing::{ModelTierConfig, PermissionLevelConfig, RoutingConfig, RoutingRule};
 
 // ── ValidationSeverity ──────────────────────────────────────────────────
 
@@ -482,6 +486,70 @@ fn validate_fallback_model(config: &RoutingConfig, errors: &mut Vec<ValidationEr
         }
 }
 
+// ── Routing rule validation ─────────────────────────────────────────────
+
+/// Agent name that needs no definition: the agent loop's own defaults.
+const DEFAULT_AGENT: &str = "default";
+
+/// Validate `routing.rules` against the names of the defined agents.
+///
+/// Errors: a rule names an agent that is not defined (other than
+/// `"default"`), or its `content` pattern is not a valid regex.
+/// Warnings: duplicate rule names, and rules that can never match because
+/// an earlier rule has no criteria.
+pub fn validate_routing_rules(rules: &[RoutingRule], agents: &[&str]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut names = HashSet::new();
+    let mut unconditional: Option<usize> = None;
+
+    for (i, rule) in rules.iter().enumerate() {
+        let field = format!("routing.rules[{i}]");
+
+        if let Some(agent) = rule.action.agent()
+            && agent != DEFAULT_AGENT
+            && !agents.contains(&agent)
+        {
+            errors.push(ValidationError {
+                field: format!("{field}.action"),
+                message: format!("agent '{agent}' is not defined"),
+                severity: ValidationSeverity::Error,
+            });
+        }
+
+        if let Some(ref pattern) = rule.matcher.content
+            && let Err(e) = regex::Regex::new(pattern)
+        {
+            errors.push(ValidationError {
+                field: format!("{field}.match.content"),
+                message: format!("invalid regex: {e}"),
+                severity: ValidationSeverity::Error,
+            });
+        }
+
+        if let Some(ref name) = rule.name
+            && !names.insert(name.as_str())
+        {
+            errors.push(ValidationError {
+                field: format!("{field}.name"),
+                message: format!("duplicate rule name '{name}'"),
+                severity: ValidationSeverity::Warning,
+            });
+        }
+
+        if let Some(first) = unconditional {
+            errors.push(ValidationError {
+                field,
+                message: format!("never matches: routing.rules[{first}] matches every message"),
+                severity: ValidationSeverity::Warning,
+            });
+        } else if rule.matcher == Default::default() {
+            unconditional = Some(i);
+        }
+    }
+
+    errors
+}
+
 // ── Workspace ceiling enforcement (FIX-04) ──────────────────────────────
 
 /// Default maximum grantable permission level for workspace configs.
@@ -1636,4 +1704,77 @@ mod tests {
             "workspace tools under global wildcard should pass"
         );
     }
+
+    // ── Test 46: routing rules checked against defined agents ────────
+
+    #[test]
+    fn routing_rules_diagnostics() {
+        use clawft_types::routing::{RuleAction, RuleMatch};
+
+        let rule = |name: Option<&str>, matcher: RuleMatch, action: RuleAction| RoutingRule {
+            name: name.map(Into::into),
+            matcher,
+            action,
+        };
+        let on = |channel: &str| RuleMatch {
+            channel: Some(channel.into()),
+            ..Default::default()
+        };
+        let agents = ["sre", "finance"];
+
+        // (rules, expected (field, severity) diagnostics)
+        type Case = (Vec<RoutingRule>, Vec<(&'static str, ValidationSeverity)>);
+        let cases: Vec<Case> = vec![
+            (
+                vec![
+                    rule(Some("a"), on("slack"), RuleAction::Route("sre".into())),
+                    rule(None, on("email"), RuleAction::Confirm("default".into())),
+                    rule(None, RuleMatch::default(), RuleAction::Drop),
+                ],
+                vec![],
+            ),
+            (
+                vec![rule(None, on("slack"), RuleAction::Route("ghost".into()))],
+                vec![("routing.rules[0].action", ValidationSeverity::Error)],
+            ),
+            (
+                vec![rule(
+                    None,
+                    RuleMatch {
+                        content: Some("(unclosed".into()),
+                        ..Default::default()
+                    },
+                    RuleAction::Drop,
+                )],
+                vec![("routing.rules[0].match.content", ValidationSeverity::Error)],
+            ),
+            (
+                vec![
+                    rule(Some("x"), on("slack"), RuleAction::Drop),
+                    rule(Some("x"), on("email"), RuleAction::Drop),
+                ],
+                vec![("routing.rules[1].name", ValidationSeverity::Warning)],
+            ),
+            (
+                vec![
+                    rule(
+                        None,
+                        RuleMatch::default(),
+                        RuleAction::Route("finance".into()),
+                    ),
+                    rule(None, on("slack"), RuleAction::Drop),
+                ],
+                vec![("routing.rules[1]", ValidationSeverity::Warning)],
+            ),
+        ];
+
+        for (i, (rules, expected)) in cases.into_iter().enumerate() {
+            let errors = validate_routing_rules(&rules, &agents);
+            let got: Vec<(&str, ValidationSeverity)> = errors
+                .iter()
+                .map(|e| (e.field.as_str(), e.severity.clone()))
+                .collect();
+            assert_eq!(got, expected, "case {i}: {errors:?}");
+        }
+    }
 }
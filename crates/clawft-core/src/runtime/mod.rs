@@ -0,0 +1,273 @@
+//! Async runtime abstraction.
+//!
+//! Provides platform-agnostic wrappers for time and async sync primitives.
+//! On native, delegates to tokio. On browser WASM, uses futures-util /
+//! js_sys equivalents.
+//!
+//! [`shutdown`] orders the stages of stopping a long-running process.
+
+#[cfg(feature = "native")]
+pub mod shutdown;
+
+// ── now ───────────────────────────────────────────────────────────────
+
+/// Get current time as milliseconds since epoch.
+///
+/// Safe on both native and browser WASM.
+#[cfg(feature = "native")]
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Get current time as milliseconds since epoch (browser).
+#[cfg(feature = "browser")]
+pub fn now_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Fallback when neither native nor browser is selected.
+#[cfg(not(any(feature = "native", feature = "browser")))]
+pub fn now_millis() -> u64 {
+    0
+}
+
+// ── timeout ───────────────────────────────────────────────────────────
+
+/// Await `fut` for at most `limit`; `None` if it did not finish in time.
+#[cfg(feature = "native")]
+pub async fn timeout<F: std::future::Future>(
+    limit: std::time::Duration,
+    fut: F,
+) -> Option<F::Output> {
+    tokio::time::timeout(limit, fut).await.ok()
+}
+
+/// Await `fut` to completion (no timer on browser WASM; `limit` is
+/// ignored).
+#[cfg(not(feature = "native"))]
+pub async fn timeout<F: std::future::Future>(
+    _limit: std::time::Duration,
+    fut: F,
+) -> Option<F::Output> {
+    Some(fut.await)
+}
+
+// ── cancellation ──────────────────────────────────────────────────────
+
+/// Await `fut` unless `cancel` fires first; `None` if it did (and `fut`
+/// was dropped).
+#[cfg(feature = "native")]
+pub async fn until_cancelled<F: std::future::Future>(
+    cancel: &clawft_plugin::CancellationToken,
+    fut: F,
+) -> Option<F::Output> {
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => None,
+        out = fut => Some(out),
+    }
+}
+
+/// Await `fut` unless `cancel` has already fired (browser WASM tokens
+/// cannot be awaited, so cancellation is only seen between steps).
+#[cfg(not(feature = "native"))]
+pub async fn until_cancelled<F: std::future::Future>(
+    cancel: &clawft_plugin::CancellationToken,
+    fut: F,
+) -> Option<F::Output> {
+    if cancel.is_cancelled() {
+        return None;
+    }
+    Some(fut.await)
+}
+
+// ── tool call session ─────────────────────────────────────────────────
+
+#[cfg(feature = "native")]
+tokio::task_local! {
+    static SESSION_KEY: String;
+}
+
+/// Await `fut` with `session_key` as the current session, so tools it
+/// runs can tell which session called them (see [`session_key`]).
+#[cfg(feature = "native")]
+pub async fn with_session_key<F: std::future::Future>(session_key: &str, fut: F) -> F::Output {
+    SESSION_KEY.scope(session_key.to_string(), fut).await
+}
+
+/// Await `fut` (browser WASM has no task-local storage; tools see no
+/// session).
+#[cfg(not(feature = "native"))]
+pub async fn with_session_key<F: std::future::Future>(_session_key: &str, fut: F) -> F::Output {
+    fut.await
+}
+
+/// Session key of the tool call being run, when the caller set one with
+/// [`with_session_key`].
+#[cfg(feature = "native")]
+pub fn session_key() -> Option<String> {
+    SESSION_KEY.try_with(|key| key.clone()).ok()
+}
+
+/// Always `None` on browser WASM.
+#[cfg(not(feature = "native"))]
+pub fn session_key() -> Option<String> {
+    None
+}
+
+#[cfg(feature = "native")]
+tokio::task_local! {
+    static AGENT_ID: String;
+}
+
+/// Await `fut` with `agent_id` as the current agent, so tools it runs can
+/// tell which agent called them (see [`agent_id`]).
+#[cfg(feature = "native")]
+pub async fn with_agent_id<F: std::future::Future>(agent_id: &str, fut: F) -> F::Output {
+    AGENT_ID.scope(agent_id.to_string(), fut).await
+}
+
+/// Await `fut` (browser WASM has no task-local storage; tools see no
+/// agent).
+#[cfg(not(feature = "native"))]
+pub async fn with_agent_id<F: std::future::Future>(_agent_id: &str, fut: F) -> F::Output {
+    fut.await
+}
+
+/// Id of the agent running the current tool call, when the caller set one
+/// with [`with_agent_id`].
+#[cfg(feature = "native")]
+pub fn agent_id() -> Option<String> {
+    AGENT_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Always `None` on browser WASM.
+#[cfg(not(feature = "native"))]
+pub fn agent_id() -> Option<String> {
+    None
+}
+
+// ── tool call progress ────────────────────────────────────────────────
+
+pub use clawft_plugin::{ProgressReporter, ToolProgress};
+
+#[cfg(feature = "native")]
+tokio::task_local! {
+    static PROGRESS: std::sync::Arc<dyn ProgressReporter>;
+}
+
+/// Await `fut` with `reporter` receiving the progress that tools it runs
+/// report (see [`report_progress`]).
+#[cfg(feature = "native")]
+pub async fn with_progress<F: std::future::Future>(
+    reporter: std::sync::Arc<dyn ProgressReporter>,
+    fut: F,
+) -> F::Output {
+    PROGRESS.scope(reporter, fut).await
+}
+
+/// Await `fut` (browser WASM has no task-local storage; progress is
+/// dropped).
+#[cfg(not(feature = "native"))]
+pub async fn with_progress<F: std::future::Future>(
+    _reporter: std::sync::Arc<dyn ProgressReporter>,
+    fut: F,
+) -> F::Output {
+    fut.await
+}
+
+/// Report how far the tool call being run has got, to the reporter set
+/// with [`with_progress`]. Does nothing when nobody is listening.
+#[cfg(feature = "native")]
+pub fn report_progress(progress: ToolProgress) {
+    let _ = PROGRESS.try_with(|reporter| reporter.report(progress));
+}
+
+/// Does nothing on browser WASM.
+#[cfg(not(feature = "native"))]
+pub fn report_progress(_progress: ToolProgress) {}
+
+// ── Async Mutex re-export ─────────────────────────────────────────────
+
+/// Re-export the appropriate async Mutex.
+///
+/// On native: `tokio::sync::Mutex` (Send-safe).
+/// On browser: `futures_util::lock::Mutex` (single-threaded WASM).
+#[cfg(feature = "native")]
+pub use tokio::sync::Mutex;
+
+#[cfg(not(feature = "native"))]
+pub use futures_util::lock::Mutex;
+
+// ── Async RwLock re-export ────────────────────────────────────────────
+
+/// Re-export the appropriate async RwLock.
+///
+/// On native: `tokio::sync::RwLock`.
+/// On browser: A thin wrapper around `std::sync::RwLock` with async methods.
+#[cfg(feature = "native")]
+pub use tokio::sync::RwLock;
+
+// For browser, provide a minimal async-compatible RwLock wrapper
+// using std::sync::RwLock. In single-threaded WASM, contention is impossible
+// so the sync lock never blocks.
+#[cfg(not(feature = "native"))]
+pub mod rw_lock_wrapper {
+    use std::ops::{Deref, DerefMut};
+
+    /// Async-compatible RwLock for single-threaded WASM.
+    ///
+    /// Wraps `std::sync::RwLock` and provides `.read().await` / `.write().await`
+    /// methods that return the same guard types, making it a drop-in replacement
+    /// for `tokio::sync::RwLock` on browser targets.
+    pub struct RwLock<T>(std::sync::RwLock<T>);
+
+    impl<T> RwLock<T> {
+        /// Create a new RwLock.
+        pub fn new(value: T) -> Self {
+            Self(std::sync::RwLock::new(value))
+        }
+
+        /// Acquire a read lock (async-compatible, resolves immediately on WASM).
+        pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+            RwLockReadGuard(self.0.read().expect("RwLock poisoned"))
+        }
+
+        /// Acquire a write lock (async-compatible, resolves immediately on WASM).
+        pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+            RwLockWriteGuard(self.0.write().expect("RwLock poisoned"))
+        }
+    }
+
+    /// Read guard for the async RwLock wrapper.
+    pub struct RwLockReadGuard<'a, T>(std::sync::RwLockReadGuard<'a, T>);
+
+    impl<T> Deref for RwLockReadGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    /// Write guard for the async RwLock wrapper.
+    pub struct RwLockWriteGuard<'a, T>(std::sync::RwLockWriteGuard<'a, T>);
+
+    impl<T> Deref for RwLockWriteGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            &self.0
+        }
+    }
+
+    impl<T> DerefMut for RwLockWriteGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.0
+        }
+    }
+}
+
+#[cfg(not(feature = "native"))]
+pub use rw_lock_wrapper::RwLock;
@@ -0,0 +1,275 @@
+//! Ordered shutdown of a long-running process.
+//!
+//! A [`ShutdownCoordinator`] runs named [`Stage`]s one after another, each
+//! within its own deadline, so that later stages only start once earlier
+//! ones are done: the gateway stops its schedulers before draining the
+//! bus, and drains the bus before closing the channels that deliver the
+//! replies.
+//!
+//! A stage that overruns its deadline is abandoned (its future dropped),
+//! its optional force action runs, and shutdown moves on to the next
+//! stage. Every stage ends up in the [`ShutdownReport`], so the process
+//! can say what completed cleanly.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+type StageFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// One step of a shutdown.
+pub struct Stage {
+    name: &'static str,
+    deadline: Duration,
+    run: StageFuture,
+    force: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Stage {
+    /// A stage called `name` that runs `run` for at most `deadline`.
+    ///
+    /// `run` fails with a description of what did not stop cleanly.
+    pub fn new(
+        name: &'static str,
+        deadline: Duration,
+        run: impl Future<Output = Result<(), String>> + Send + 'static,
+    ) -> Self {
+        Self {
+            name,
+            deadline,
+            run: Box::pin(run),
+            force: None,
+        }
+    }
+
+    /// Run `force` when the stage overruns its deadline, to stop whatever
+    /// it was waiting for.
+    pub fn on_timeout(mut self, force: impl FnOnce() + Send + 'static) -> Self {
+        self.force = Some(Box::new(force));
+        self
+    }
+}
+
+/// Runs [`Stage`]s in order.
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    stages: Vec<Stage>,
+}
+
+impl ShutdownCoordinator {
+    /// A coordinator with no stages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `stage` after the ones added so far.
+    pub fn then(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Run every stage in order and report how each ended.
+    pub async fn run(self) -> ShutdownReport {
+        let mut stages = Vec::with_capacity(self.stages.len());
+        for stage in self.stages {
+            let started = Instant::now();
+            let outcome = match tokio::time::timeout(stage.deadline, stage.run).await {
+                Ok(Ok(())) => StageOutcome::Clean,
+                Ok(Err(e)) => StageOutcome::Failed(e),
+                Err(_) => {
+                    if let Some(force) = stage.force {
+                        force();
+                    }
+                    StageOutcome::TimedOut
+                }
+            };
+            let elapsed = started.elapsed();
+            match &outcome {
+                StageOutcome::Clean => {
+                    info!(
+                        stage = stage.name,
+                        elapsed_ms = elapsed.as_millis() as u64,
+                        "shutdown stage done"
+                    );
+                }
+                StageOutcome::Failed(e) => {
+                    warn!(stage = stage.name, error = %e, "shutdown stage did not complete cleanly");
+                }
+                StageOutcome::TimedOut => {
+                    warn!(
+                        stage = stage.name,
+                        deadline_ms = stage.deadline.as_millis() as u64,
+                        "shutdown stage timed out, forced"
+                    );
+                }
+            }
+            stages.push(StageReport {
+                name: stage.name,
+                outcome,
+                elapsed,
+            });
+        }
+        ShutdownReport { stages }
+    }
+}
+
+/// How a [`Stage`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageOutcome {
+    /// Everything the stage waited for stopped in time.
+    Clean,
+    /// The stage finished, but something in it did not stop cleanly.
+    Failed(String),
+    /// The deadline passed; the stage was abandoned and forced.
+    TimedOut,
+}
+
+/// One stage in a [`ShutdownReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageReport {
+    /// The stage name.
+    pub name: &'static str,
+    /// How it ended.
+    pub outcome: StageOutcome,
+    /// How long it ran.
+    pub elapsed: Duration,
+}
+
+/// What a shutdown completed, stage by stage in the order they ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// The stages, in order.
+    pub stages: Vec<StageReport>,
+}
+
+impl ShutdownReport {
+    /// Whether every stage completed cleanly.
+    pub fn is_clean(&self) -> bool {
+        self.stages
+            .iter()
+            .all(|stage| stage.outcome == StageOutcome::Clean)
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stage in &self.stages {
+            let seconds = stage.elapsed.as_secs_f64();
+            match &stage.outcome {
+                StageOutcome::Clean => writeln!(f, "  {:<12} ok ({seconds:.1}s)", stage.name)?,
+                StageOutcome::Failed(e) => {
+                    writeln!(f, "  {:<12} incomplete ({seconds:.1}s): {e}", stage.name)?
+                }
+                StageOutcome::TimedOut => writeln!(
+                    f,
+                    "  {:<12} timed out after {seconds:.1}s, forced",
+                    stage.name
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn recorder() -> (Arc<Mutex<Vec<&'static str>>>, impl Fn(&'static str) + Clone) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let push = {
+            let log = log.clone();
+            move |event| log.lock().unwrap().push(event)
+        };
+        (log, push)
+    }
+
+    #[tokio::test]
+    async fn runs_stages_in_order() {
+        let (log, push) = recorder();
+        let stage = |name: &'static str, delay_ms: u64| {
+            let push = push.clone();
+            Stage::new(name, Duration::from_secs(5), async move {
+                push(name);
+                // A slow first stage must still finish before the next.
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                push(name);
+                Ok(())
+            })
+        };
+
+        let report = ShutdownCoordinator::new()
+            .then(stage("schedulers", 30))
+            .then(stage("bus", 0))
+            .then(stage("channels", 0))
+            .run()
+            .await;
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "schedulers",
+                "schedulers",
+                "bus",
+                "bus",
+                "channels",
+                "channels"
+            ]
+        );
+        assert!(report.is_clean());
+        let names: Vec<_> = report.stages.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["schedulers", "bus", "channels"]);
+    }
+
+    #[tokio::test]
+    async fn overrunning_stage_is_forced_and_shutdown_continues() {
+        let (log, push) = recorder();
+        let forced = push.clone();
+        let next = push.clone();
+
+        let report = ShutdownCoordinator::new()
+            .then(
+                Stage::new("bus", Duration::from_millis(50), async move {
+                    std::future::pending::<()>().await;
+                    Ok(())
+                })
+                .on_timeout(move || forced("force bus")),
+            )
+            .then(Stage::new("channels", Duration::from_secs(5), async move {
+                next("channels");
+                Ok(())
+            }))
+            .run()
+            .await;
+
+        assert_eq!(*log.lock().unwrap(), ["force bus", "channels"]);
+        assert_eq!(report.stages[0].outcome, StageOutcome::TimedOut);
+        assert!(report.stages[0].elapsed >= Duration::from_millis(50));
+        assert_eq!(report.stages[1].outcome, StageOutcome::Clean);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn failed_stage_is_reported() {
+        let report = ShutdownCoordinator::new()
+            .then(Stage::new("ledgers", Duration::from_secs(5), async {
+                Err("usage: disk full".to_string())
+            }))
+            .run()
+            .await;
+
+        assert_eq!(
+            report.stages[0].outcome,
+            StageOutcome::Failed("usage: disk full".into())
+        );
+        let text = report.to_string();
+        assert!(text.contains("ledgers"), "{text}");
+        assert!(text.contains("incomplete"), "{text}");
+        assert!(text.contains("usage: disk full"), "{text}");
+    }
+}
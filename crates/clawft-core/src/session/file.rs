@@ -0,0 +1,514 @@
+//! JSONL file session store.
+//!
+//! [`FileSessionStore`] keeps one `{percent_encoded_key}.jsonl` file per
+//! session in a single directory, using the platform filesystem abstraction.
+//!
+//! # JSONL format
+//!
+//! - Line 1: metadata object with `_type`, `created_at`, `updated_at`,
+//!   `metadata`, and `last_consolidated` fields.
+//! - Lines 2+: message objects with `role`, `content`, and `timestamp` fields.
+//!
+//! Archived sessions are moved, unchanged, into the [`ARCHIVE_DIR`]
+//! subdirectory.
+//!
+//! Writes from one process are serialized, so concurrent appends within a
+//! process never lose messages. Separate processes writing the same session
+//! can still overwrite each other's full saves; use the SQLite store when
+//! several processes (e.g. gateway and CLI) share sessions.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, percent_encode};
+use tracing::{debug, warn};
+
+use clawft_platform::Platform;
+use clawft_types::error::ClawftError;
+use clawft_types::session::Session;
+
+use super::store::{SessionPage, SessionQuery, SessionStore, SessionSummary};
+use crate::runtime::Mutex;
+
+/// Subdirectory of the sessions directory holding archived sessions.
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Session store backed by one JSONL file per session.
+pub struct FileSessionStore<P: Platform> {
+    /// Directory holding the session files.
+    dir: PathBuf,
+
+    /// Platform providing filesystem access.
+    platform: Arc<P>,
+
+    /// Serializes writes so concurrent appends cannot race on file creation.
+    write_lock: Mutex<()>,
+}
+
+impl<P: Platform> FileSessionStore<P> {
+    /// Create a store over `dir`. The directory must already exist.
+    pub fn new(platform: Arc<P>, dir: PathBuf) -> Self {
+        Self {
+            dir,
+            platform,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// The directory holding the session files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Compute the filesystem path for a session key.
+    ///
+    /// Uses percent-encoding to safely represent any valid session key as
+    /// a filename. This is reversible: listing decodes back to the original
+    /// key.
+    pub fn session_path(&self, key: &str) -> PathBuf {
+        let encoded = percent_encode(key.as_bytes(), NON_ALPHANUMERIC).to_string();
+        self.dir.join(format!("{encoded}.jsonl"))
+    }
+
+    /// A store over the archive subdirectory.
+    fn archived(&self) -> Self {
+        Self::new(self.platform.clone(), self.dir.join(ARCHIVE_DIR))
+    }
+
+    /// Move the file for `key` from the store `from` to the store `to`.
+    /// A file `to` already has is replaced if `replace` is set, otherwise
+    /// it is an error. Returns `false` when `from` has no file for `key`.
+    async fn move_session(
+        &self,
+        key: &str,
+        from: &Self,
+        to: &Self,
+        replace: bool,
+    ) -> clawft_types::Result<bool> {
+        let source = from.session_path(key);
+        let target = to.session_path(key);
+        let fs = self.platform.fs();
+        let _guard = self.write_lock.lock().await;
+        if !fs.exists(&source).await {
+            return Ok(false);
+        }
+        if !replace && fs.exists(&target).await {
+            return Err(ClawftError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("session already exists: {}", target.display()),
+            )));
+        }
+        fs.create_dir_all(&to.dir).await?;
+        fs.rename(&source, &target).await?;
+        Ok(true)
+    }
+
+    /// Summarize one session, skipping (with a warning) files that cannot
+    /// be read.
+    async fn summary(&self, key: &str) -> Option<SessionSummary> {
+        match self.load(key).await {
+            Ok(session) => session.as_ref().map(SessionSummary::from),
+            Err(e) => {
+                warn!(key, error = %e, "skipping unreadable session file");
+                None
+            }
+        }
+    }
+
+    /// Copy a file written under the old underscore encoding to its
+    /// percent-encoded name. The old file is kept for safety.
+    async fn migrate_legacy_name(&self, key: &str, path: &Path) -> clawft_types::Result<()> {
+        let old_path = self.dir.join(format!("{}.jsonl", key.replace(':', "_")));
+        if self.platform.fs().exists(&old_path).await {
+            warn!(
+                key = key,
+                old = %old_path.display(),
+                new = %path.display(),
+                "migrating session file from old encoding format"
+            );
+            let content = self.platform.fs().read_to_string(&old_path).await?;
+            self.platform.fs().write_atomic(path, &content).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl<P: Platform> SessionStore for FileSessionStore<P> {
+    async fn load(&self, key: &str) -> clawft_types::Result<Option<Session>> {
+        let path = self.session_path(key);
+        if !self.platform.fs().exists(&path).await {
+            self.migrate_legacy_name(key, &path).await?;
+            if !self.platform.fs().exists(&path).await {
+                return Ok(None);
+            }
+        }
+
+        let content = self.platform.fs().read_to_string(&path).await?;
+        let session = parse_jsonl(key, &content, &path)?;
+        debug!(
+            key = key,
+            messages = session.messages.len(),
+            "loaded session from disk"
+        );
+        Ok(Some(session))
+    }
+
+    async fn save(&self, session: &Session) -> clawft_types::Result<()> {
+        let path = self.session_path(&session.key);
+        let content = to_jsonl(session)?;
+
+        // Replace the file atomically so a crash mid-save cannot leave a
+        // truncated session behind.
+        let _guard = self.write_lock.lock().await;
+        self.platform.fs().write_atomic(&path, &content).await?;
+        debug!(key = %session.key, "saved session to disk");
+        Ok(())
+    }
+
+    async fn list(&self, query: &SessionQuery) -> clawft_types::Result<SessionPage> {
+        if query.archived {
+            let archive = self.archived();
+            if !self.platform.fs().exists(&archive.dir).await {
+                return Ok(SessionPage::default());
+            }
+            let live = SessionQuery {
+                archived: false,
+                ..query.clone()
+            };
+            return archive.list(&live).await;
+        }
+        let mut keys = self.keys().await?;
+        keys.retain(|key| query.matches_key(key));
+        let limit = query.limit.unwrap_or(usize::MAX);
+
+        let mut page = SessionPage::default();
+        if let Some(since) = query.updated_since {
+            // Filtering on update time needs every candidate's metadata.
+            let mut matching = Vec::new();
+            for key in &keys {
+                if let Some(summary) = self.summary(key).await
+                    && summary.updated_at >= since
+                {
+                    matching.push(summary);
+                }
+            }
+            page.total = matching.len();
+            page.sessions = matching
+                .into_iter()
+                .skip(query.offset)
+                .take(limit)
+                .collect();
+        } else {
+            // Only the requested page is read from disk.
+            page.total = keys.len();
+            for key in keys.iter().skip(query.offset).take(limit) {
+                if let Some(summary) = self.summary(key).await {
+                    page.sessions.push(summary);
+                }
+            }
+        }
+        Ok(page)
+    }
+
+    /// Derived from `.jsonl` filenames, so no session is read. Files that
+    /// cannot be decoded as valid UTF-8 are skipped with a warning.
+    async fn keys(&self) -> clawft_types::Result<Vec<String>> {
+        let entries = self
+            .platform
+            .fs()
+            .list_dir(&self.dir)
+            .await
+            .map_err(ClawftError::Io)?;
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            if let Some(name) = entry.file_name() {
+                let name = name.to_string_lossy();
+                if let Some(stem) = name.strip_suffix(".jsonl") {
+                    match percent_decode_str(stem).decode_utf8() {
+                        Ok(decoded) => keys.push(decoded.into_owned()),
+                        Err(e) => {
+                            warn!(filename = %name, error = %e, "skipping undecodable session filename");
+                        }
+                    }
+                }
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn delete(&self, key: &str) -> clawft_types::Result<()> {
+        let _guard = self.write_lock.lock().await;
+        for path in [self.session_path(key), self.archived().session_path(key)] {
+            if self.platform.fs().exists(&path).await {
+                self.platform
+                    .fs()
+                    .remove_file(&path)
+                    .await
+                    .map_err(ClawftError::Io)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn archive(&self, key: &str) -> clawft_types::Result<bool> {
+        // A newer live session replaces an older archived one.
+        self.move_session(key, self, &self.archived(), true).await
+    }
+
+    async fn restore(&self, key: &str) -> clawft_types::Result<bool> {
+        self.move_session(key, &self.archived(), self, false).await
+    }
+
+    async fn append_message(
+        &self,
+        key: &str,
+        message: &serde_json::Value,
+    ) -> clawft_types::Result<()> {
+        let path = self.session_path(key);
+        let mut line = serde_json::to_string(message).map_err(ClawftError::Json)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        if self.platform.fs().exists(&path).await {
+            self.platform.fs().append_string(&path, &line).await?;
+        } else {
+            // First message: write the metadata header along with it.
+            let mut session = Session::new(key);
+            session.messages.push(message.clone());
+            self.platform
+                .fs()
+                .write_atomic(&path, &to_jsonl(&session)?)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a session from JSONL content.
+///
+/// Missing metadata fields fall back to defaults, and malformed message
+/// lines are skipped with a warning. Fails only when the file is empty or
+/// the metadata line is not JSON.
+fn parse_jsonl(key: &str, content: &str, path: &Path) -> clawft_types::Result<Session> {
+    let mut lines = content.lines();
+
+    let meta_line = lines.next().ok_or_else(|| ClawftError::ConfigInvalid {
+        reason: format!("session file is empty: {}", path.display()),
+    })?;
+
+    let meta: serde_json::Value = serde_json::from_str(meta_line)?;
+
+    let created_at = meta
+        .get("created_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(Utc::now);
+
+    let updated_at = meta
+        .get("updated_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(Utc::now);
+
+    let metadata: HashMap<String, serde_json::Value> = meta
+        .get("metadata")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    let last_consolidated = meta
+        .get("last_consolidated")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as usize;
+
+    let mut messages = Vec::new();
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(msg) => messages.push(msg),
+            Err(e) => {
+                warn!(
+                    key = key,
+                    error = %e,
+                    "skipping malformed message line in session"
+                );
+            }
+        }
+    }
+
+    Ok(Session {
+        key: key.to_string(),
+        messages,
+        created_at,
+        updated_at,
+        metadata,
+        last_consolidated,
+    })
+}
+
+/// Serialize a session as JSONL: the metadata line, then one line per
+/// message.
+fn to_jsonl(session: &Session) -> clawft_types::Result<String> {
+    let meta = serde_json::json!({
+        "_type": "metadata",
+        "created_at": session.created_at.to_rfc3339(),
+        "updated_at": session.updated_at.to_rfc3339(),
+        "metadata": session.metadata,
+        "last_consolidated": session.last_consolidated,
+    });
+
+    let mut content = serde_json::to_string(&meta).map_err(ClawftError::Json)?;
+    content.push('\n');
+
+    for msg in &session.messages {
+        content.push_str(&serde_json::to_string(msg).map_err(ClawftError::Json)?);
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::NativePlatform;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    async fn temp_store(prefix: &str) -> (FileSessionStore<NativePlatform>, PathBuf) {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let dir = std::env::temp_dir().join(format!("clawft_file_sessions_{prefix}_{pid}_{id}"));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let store = FileSessionStore::new(Arc::new(NativePlatform::new()), dir.clone());
+        (store, dir)
+    }
+
+    #[tokio::test]
+    async fn session_path_uses_percent_encoding() {
+        let store =
+            FileSessionStore::new(Arc::new(NativePlatform::new()), PathBuf::from("/sessions"));
+        assert_eq!(
+            store.session_path("telegram:12345"),
+            PathBuf::from("/sessions/telegram%3A12345.jsonl")
+        );
+    }
+
+    #[tokio::test]
+    async fn list_pages_and_filters_by_prefix() {
+        let (store, dir) = temp_store("list").await;
+        for key in ["slack:1", "telegram:1", "telegram:2", "telegram:3"] {
+            store.save(&Session::new(key)).await.unwrap();
+        }
+
+        let page = store
+            .list(&SessionQuery {
+                key_prefix: Some("telegram:".into()),
+                offset: 1,
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 3);
+        let keys: Vec<&str> = page.sessions.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["telegram:2"]);
+
+        let recent = store
+            .list(&SessionQuery {
+                updated_since: Some(Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(recent.total, 0);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn archive_and_restore_move_the_file() {
+        let (store, dir) = temp_store("archive").await;
+        let mut session = Session::new("slack:1");
+        session.add_message("user", "hello", None);
+        store.save(&session).await.unwrap();
+        store.save(&Session::new("slack:2")).await.unwrap();
+
+        assert!(store.archive("slack:1").await.unwrap());
+        assert!(!store.archive("slack:1").await.unwrap());
+        assert!(store.load("slack:1").await.unwrap().is_none());
+        assert!(dir.join(ARCHIVE_DIR).join("slack%3A1.jsonl").exists());
+        let live = store.list(&SessionQuery::default()).await.unwrap();
+        assert_eq!(live.total, 1);
+        let archived = store
+            .list(&SessionQuery {
+                archived: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(archived.sessions[0].key, "slack:1");
+        assert_eq!(archived.sessions[0].message_count, 1);
+
+        // Restoring over a live session with the same key is refused.
+        store.save(&Session::new("slack:1")).await.unwrap();
+        assert!(store.restore("slack:1").await.is_err());
+        store.delete("slack:1").await.unwrap();
+        assert!(!store.restore("slack:1").await.unwrap());
+
+        store.archive("slack:2").await.unwrap();
+        assert!(store.restore("slack:2").await.unwrap());
+        assert!(store.load("slack:2").await.unwrap().is_some());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_appends_keep_every_message() {
+        let (store, dir) = temp_store("append").await;
+        let store = Arc::new(store);
+
+        let writers: Vec<_> = ["gateway", "cli"]
+            .into_iter()
+            .map(|writer| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    for i in 0..25 {
+                        let msg =
+                            serde_json::json!({"role": "user", "content": format!("{writer}-{i}")});
+                        store.append_message("shared:1", &msg).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        let session = store.load("shared:1").await.unwrap().unwrap();
+        assert_eq!(session.messages.len(), 50);
+        for writer in ["gateway", "cli"] {
+            let own: Vec<String> = session
+                .messages
+                .iter()
+                .filter_map(|m| m["content"].as_str())
+                .filter(|c| c.starts_with(writer))
+                .map(String::from)
+                .collect();
+            let expected: Vec<String> = (0..25).map(|i| format!("{writer}-{i}")).collect();
+            assert_eq!(own, expected);
+        }
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -1,52 +1,73 @@
 //! Session management for conversation persistence.
 //!
 //! Provides [`SessionManager`] which caches active sessions in memory and
-//! persists them to disk as JSONL files using the platform filesystem
-//! abstraction. Each JSONL file has a metadata header line followed by
-//! one line per conversation turn.
+//! persists them through a [`SessionStore`]: JSONL files by default
+//! ([`FileSessionStore`]), or a SQLite database with the `sqlite-sessions`
+//! feature (`agents.sessions.backend = "sqlite"`).
+//!
+//! Sessions past the `agents.sessions` retention limits are archived rather
+//! than deleted (see [`retention`]); a new message to an archived session
+//! restores it.
 //!
 //! Ported from Python `nanobot/session/manager.py`.
 
+pub mod file;
+pub mod retention;
+#[cfg(feature = "sqlite-sessions")]
+pub mod sqlite;
+pub mod store;
+
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use chrono::Utc;
-use percent_encoding::{percent_decode_str, percent_encode, NON_ALPHANUMERIC};
 use crate::runtime::Mutex;
-use tracing::{debug, warn};
+use tracing::debug;
 
 use clawft_platform::Platform;
+use clawft_types::config::{SessionBackend, SessionsConfig};
 use clawft_types::error::ClawftError;
 use clawft_types::session::Session;
 
-/// Manages conversation sessions with in-memory caching and JSONL persistence.
+pub use file::FileSessionStore;
+pub use retention::RetentionPolicy;
+#[cfg(feature = "sqlite-sessions")]
+pub use sqlite::SqliteSessionStore;
+pub use store::{SessionPage, SessionQuery, SessionStore, SessionSummary, migrate};
+
+/// File name of the SQLite database inside the sessions directory.
+pub const SQLITE_DB_NAME: &str = "sessions.db";
+
+/// Manages conversation sessions with in-memory caching and pluggable
+/// persistence.
 ///
 /// Sessions are identified by a string key (typically `"{channel}:{chat_id}"`).
 /// The manager uses a write-through cache: reads check the in-memory cache
-/// first, then fall back to loading from disk. Writes update both the cache
-/// and the JSONL file.
-///
-/// # JSONL format
-///
-/// Each session file is named `{sanitized_key}.jsonl` and contains:
-/// - Line 1: metadata object with `_type`, `created_at`, `updated_at`,
-///   `metadata`, and `last_consolidated` fields.
-/// - Lines 2+: message objects with `role`, `content`, and `timestamp` fields.
+/// first, then fall back to loading from the store. Writes update both the
+/// cache and the store.
 ///
 /// # Platform abstraction
 ///
-/// All filesystem I/O goes through the [`Platform::fs()`] trait, making
-/// SessionManager testable with mock filesystems and WASM-portable.
+/// The default [`FileSessionStore`] does all filesystem I/O through the
+/// [`Platform::fs()`] trait, making SessionManager testable with mock
+/// filesystems and WASM-portable.
 pub struct SessionManager<P: Platform> {
-    /// Directory where JSONL session files are stored.
+    /// Directory holding the sessions (JSONL files or the SQLite database).
     sessions_dir: PathBuf,
 
     /// In-memory cache of active sessions.
     active_sessions: Arc<Mutex<HashMap<String, Session>>>,
 
-    /// Platform providing filesystem access.
-    platform: Arc<P>,
+    /// Where sessions are persisted.
+    backend: Backend<P>,
+}
+
+/// The store behind a [`SessionManager`]. JSONL files are held directly so
+/// the manager works with any platform type, not only `'static` ones.
+enum Backend<P: Platform> {
+    File(FileSessionStore<P>),
+    Custom(Arc<dyn SessionStore>),
 }
 
 impl<P: Platform> SessionManager<P> {
@@ -59,6 +80,62 @@ impl<P: Platform> SessionManager<P> {
     /// If neither exists, defaults to `~/.clawft/workspace/sessions/` and
     /// creates it. Returns an error if the home directory cannot be determined.
     pub async fn new(platform: Arc<P>) -> clawft_types::Result<Self> {
+        let sessions_dir = Self::discover_dir(&platform).await?;
+        Ok(Self::with_dir(platform, sessions_dir))
+    }
+
+    /// Create a session manager with the backend selected in `config`.
+    ///
+    /// The sessions directory is discovered as in [`new`](Self::new). The
+    /// SQLite backend keeps its database there as [`SQLITE_DB_NAME`], and
+    /// is an error when the crate was built without `sqlite-sessions`.
+    pub async fn from_config(
+        platform: Arc<P>,
+        config: &SessionsConfig,
+    ) -> clawft_types::Result<Self> {
+        let sessions_dir = Self::discover_dir(&platform).await?;
+        match config.backend {
+            SessionBackend::File => Ok(Self::with_dir(platform, sessions_dir)),
+            #[cfg(feature = "sqlite-sessions")]
+            SessionBackend::Sqlite => {
+                let store = SqliteSessionStore::open(sessions_dir.join(SQLITE_DB_NAME))?;
+                Ok(Self::with_backend(
+                    sessions_dir,
+                    Backend::Custom(Arc::new(store)),
+                ))
+            }
+            #[cfg(not(feature = "sqlite-sessions"))]
+            SessionBackend::Sqlite => Err(ClawftError::ConfigInvalid {
+                reason: "sessions backend \"sqlite\" requires the sqlite-sessions feature".into(),
+            }),
+        }
+    }
+
+    /// Create a session manager with an explicit sessions directory, using
+    /// JSONL files.
+    ///
+    /// Useful for testing or when the directory is already known.
+    pub fn with_dir(platform: Arc<P>, sessions_dir: PathBuf) -> Self {
+        let store = FileSessionStore::new(platform, sessions_dir.clone());
+        Self::with_backend(sessions_dir, Backend::File(store))
+    }
+
+    /// Create a session manager over an explicit store. `sessions_dir` is
+    /// only reported by [`sessions_dir`](Self::sessions_dir).
+    pub fn with_store(sessions_dir: PathBuf, store: Arc<dyn SessionStore>) -> Self {
+        Self::with_backend(sessions_dir, Backend::Custom(store))
+    }
+
+    fn with_backend(sessions_dir: PathBuf, backend: Backend<P>) -> Self {
+        Self {
+            sessions_dir,
+            active_sessions: Arc::new(Mutex::new(HashMap::new())),
+            backend,
+        }
+    }
+
+    /// Find (or create) the sessions directory under the home directory.
+    async fn discover_dir(platform: &Arc<P>) -> clawft_types::Result<PathBuf> {
         let home = platform
             .fs()
             .home_dir()
@@ -69,12 +146,12 @@ impl<P: Platform> SessionManager<P> {
         let clawft_dir = home.join(".clawft").join("workspace").join("sessions");
         let nanobot_dir = home.join(".nanobot").join("workspace").join("sessions");
 
-        let sessions_dir = if platform.fs().exists(&clawft_dir).await {
+        if platform.fs().exists(&clawft_dir).await {
             debug!(path = %clawft_dir.display(), "using clawft sessions dir");
-            clawft_dir
+            Ok(clawft_dir)
         } else if platform.fs().exists(&nanobot_dir).await {
             debug!(path = %nanobot_dir.display(), "using nanobot sessions dir (fallback)");
-            nanobot_dir
+            Ok(nanobot_dir)
         } else {
             debug!(
                 path = %clawft_dir.display(),
@@ -85,31 +162,24 @@ impl<P: Platform> SessionManager<P> {
                 .create_dir_all(&clawft_dir)
                 .await
                 .map_err(ClawftError::Io)?;
-            clawft_dir
-        };
-
-        Ok(Self {
-            sessions_dir,
-            active_sessions: Arc::new(Mutex::new(HashMap::new())),
-            platform,
-        })
+            Ok(clawft_dir)
+        }
     }
 
-    /// Create a session manager with an explicit sessions directory.
-    ///
-    /// Useful for testing or when the directory is already known.
-    pub fn with_dir(platform: Arc<P>, sessions_dir: PathBuf) -> Self {
-        Self {
-            sessions_dir,
-            active_sessions: Arc::new(Mutex::new(HashMap::new())),
-            platform,
+
+    /// The underlying session store.
+    pub fn store(&self) -> &dyn SessionStore {
+        match &self.backend {
+            Backend::File(store) => store,
+            Backend::Custom(store) => store.as_ref(),
         }
     }
 
     /// Get an existing session or create a new one.
     ///
-    /// Checks the in-memory cache first, then attempts to load from disk.
-    /// If neither succeeds, creates a fresh empty session and caches it.
+    /// Checks the in-memory cache first, then attempts to load from the
+    /// store. If neither succeeds, creates a fresh empty session and caches
+    /// it.
     ///
     /// # Errors
     ///
@@ -125,8 +195,14 @@ impl<P: Platform> SessionManager<P> {
             }
         }
 
-        // Try loading from disk.
-        if let Ok(session) = self.load_session(key).await {
+        // Try loading from the store, bringing the session back from the
+        // archive if that is where it is.
+        let mut loaded = self.load_session(key).await;
+        if loaded.is_err() && matches!(self.store().restore(key).await, Ok(true)) {
+            debug!(key, "restored archived session");
+            loaded = self.load_session(key).await;
+        }
+        if let Ok(session) = loaded {
             let mut cache = self.active_sessions.lock().await;
             cache.insert(key.to_string(), session.clone());
             return Ok(session);
@@ -147,140 +223,33 @@ impl<P: Platform> SessionManager<P> {
         Ok(session)
     }
 
-    /// Load a session from its JSONL file on disk.
-    ///
-    /// Parses the first line as metadata and remaining lines as messages.
-    /// Returns an error if the file does not exist or contains invalid JSON.
+    /// Load a session from the store, bypassing the cache.
     ///
-    /// Includes a migration path: if the percent-encoded file does not exist
-    /// but the old underscore-encoded file does, the content is copied to the
-    /// new filename (the old file is preserved for safety).
+    /// Returns an error if the session does not exist or cannot be read.
     pub async fn load_session(&self, key: &str) -> clawft_types::Result<Session> {
         crate::security::validate_session_id(key)?;
-        let path = self.session_path(key);
-
-        // Migration: try old-format filename if new-format doesn't exist
-        if !self.platform.fs().exists(&path).await {
-            let old_filename = format!("{}.jsonl", key.replace(':', "_"));
-            let old_path = self.sessions_dir.join(&old_filename);
-            if self.platform.fs().exists(&old_path).await {
-                warn!(
-                    key = key,
-                    old = %old_path.display(),
-                    new = %path.display(),
-                    "migrating session file from old encoding format"
-                );
-                // Read from old, write to new, keep old for safety
-                let content = self.platform.fs().read_to_string(&old_path).await?;
-                self.platform.fs().write_string(&path, &content).await?;
-            }
-        }
-
-        let content = self.platform.fs().read_to_string(&path).await?;
-
-        let mut lines = content.lines();
-
-        // Parse metadata line.
-        let meta_line = lines.next().ok_or_else(|| ClawftError::ConfigInvalid {
-            reason: format!("session file is empty: {}", path.display()),
-        })?;
-
-        let meta: serde_json::Value = serde_json::from_str(meta_line)?;
-
-        let created_at = meta
-            .get("created_at")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(Utc::now);
-
-        let updated_at = meta
-            .get("updated_at")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(Utc::now);
-
-        let metadata: HashMap<String, serde_json::Value> = meta
-            .get("metadata")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-
-        let last_consolidated = meta
-            .get("last_consolidated")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as usize;
-
-        // Parse message lines.
-        let mut messages = Vec::new();
-        for line in lines {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            match serde_json::from_str::<serde_json::Value>(trimmed) {
-                Ok(msg) => messages.push(msg),
-                Err(e) => {
-                    warn!(
-                        key = key,
-                        error = %e,
-                        "skipping malformed message line in session"
-                    );
-                }
-            }
-        }
-
-        debug!(
-            key = key,
-            messages = messages.len(),
-            "loaded session from disk"
-        );
-
-        Ok(Session {
-            key: key.to_string(),
-            messages,
-            created_at,
-            updated_at,
-            metadata,
-            last_consolidated,
+        self.store().load(key).await?.ok_or_else(|| {
+            ClawftError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("session not found: {key}"),
+            ))
         })
     }
 
-    /// Save a session to its JSONL file on disk.
+    /// Save a session to the store.
     ///
-    /// Writes the full session: metadata line followed by all message lines.
-    /// Also updates the in-memory cache.
+    /// Writes the full session and updates the in-memory cache.
     pub async fn save_session(&self, session: &Session) -> clawft_types::Result<()> {
-        let path = self.session_path(&session.key);
-
-        let meta = serde_json::json!({
-            "_type": "metadata",
-            "created_at": session.created_at.to_rfc3339(),
-            "updated_at": session.updated_at.to_rfc3339(),
-            "metadata": session.metadata,
-            "last_consolidated": session.last_consolidated,
-        });
-
-        let mut content = serde_json::to_string(&meta).map_err(ClawftError::Json)?;
-        content.push('\n');
-
-        for msg in &session.messages {
-            content.push_str(&serde_json::to_string(msg).map_err(ClawftError::Json)?);
-            content.push('\n');
-        }
-
-        self.platform.fs().write_string(&path, &content).await?;
+        self.store().save(session).await?;
 
-        // Update cache.
         let mut cache = self.active_sessions.lock().await;
         cache.insert(session.key.clone(), session.clone());
-
-        debug!(key = %session.key, "saved session to disk");
-
         Ok(())
     }
 
     /// Append a single conversation turn to a session.
     ///
-    /// Updates both the in-memory cache and appends to the JSONL file on disk.
+    /// Updates the in-memory cache and appends the message to the store.
     /// If the session does not exist yet, it is created via [`get_or_create`].
     pub async fn append_turn(
         &self,
@@ -290,84 +259,45 @@ impl<P: Platform> SessionManager<P> {
     ) -> clawft_types::Result<()> {
         crate::security::validate_session_id(key)?;
         let mut session = self.get_or_create(key).await?;
-        session.add_message(role, content, None);
 
-        // Append message line to file.
         let msg = serde_json::json!({
             "role": role,
             "content": content,
             "timestamp": Utc::now().to_rfc3339(),
         });
-        let mut line = serde_json::to_string(&msg).map_err(ClawftError::Json)?;
-        line.push('\n');
-
-        let path = self.session_path(key);
-
-        // If the file does not exist yet, write the full session (with metadata).
-        if !self.platform.fs().exists(&path).await {
-            self.save_session(&session).await?;
-        } else {
-            self.platform.fs().append_string(&path, &line).await?;
-            // Update cache.
-            let mut cache = self.active_sessions.lock().await;
-            cache.insert(key.to_string(), session);
-        }
+        self.store().append_message(key, &msg).await?;
 
+        session.messages.push(msg);
+        session.updated_at = Utc::now();
+        let mut cache = self.active_sessions.lock().await;
+        cache.insert(key.to_string(), session);
         Ok(())
     }
 
-    /// List all session keys (derived from `.jsonl` filenames on disk).
-    ///
-    /// Decodes percent-encoded filenames back to the original session key.
-    /// Files that cannot be decoded as valid UTF-8 are skipped with a warning.
+    /// List all session keys, sorted.
     pub async fn list_sessions(&self) -> clawft_types::Result<Vec<String>> {
-        let entries = self
-            .platform
-            .fs()
-            .list_dir(&self.sessions_dir)
-            .await
-            .map_err(ClawftError::Io)?;
-
-        let mut keys = Vec::new();
-        for entry in entries {
-            if let Some(name) = entry.file_name() {
-                let name = name.to_string_lossy();
-                if let Some(stem) = name.strip_suffix(".jsonl") {
-                    match percent_decode_str(stem).decode_utf8() {
-                        Ok(decoded) => keys.push(decoded.into_owned()),
-                        Err(e) => {
-                            warn!(filename = %name, error = %e, "skipping undecodable session filename");
-                        }
-                    }
-                }
-            }
-        }
+        self.store().keys().await
+    }
 
-        keys.sort();
-        Ok(keys)
+    /// List sessions matching `query`, one page at a time.
+    pub async fn list(&self, query: &SessionQuery) -> clawft_types::Result<SessionPage> {
+        self.store().list(query).await
     }
 
     /// Remove a session from the in-memory cache.
     ///
-    /// The JSONL file on disk is not deleted; only the cached copy is
-    /// evicted. The next [`get_or_create`] call will reload from disk.
+    /// The stored session is not deleted; only the cached copy is
+    /// evicted. The next [`get_or_create`] call will reload from the store.
     pub async fn invalidate(&self, key: &str) {
         let mut cache = self.active_sessions.lock().await;
         cache.remove(key);
         debug!(key = key, "invalidated session cache entry");
     }
 
-    /// Delete a session file from disk and remove from cache.
+    /// Delete a session from the store and remove it from the cache.
     pub async fn delete_session(&self, key: &str) -> clawft_types::Result<()> {
         crate::security::validate_session_id(key)?;
-        let path = self.session_path(key);
-        if self.platform.fs().exists(&path).await {
-            self.platform
-                .fs()
-                .remove_file(&path)
-                .await
-                .map_err(ClawftError::Io)?;
-        }
+        self.store().delete(key).await?;
         self.invalidate(key).await;
 
         // Chain event marker for session destruction.
@@ -380,20 +310,72 @@ impl<P: Platform> SessionManager<P> {
         Ok(())
     }
 
-    /// Get the sessions directory path.
-    pub fn sessions_dir(&self) -> &PathBuf {
-        &self.sessions_dir
+    /// Move a session to the archive and drop it from the cache. Returns
+    /// `false` when there is no live session with this key.
+    pub async fn archive(&self, key: &str) -> clawft_types::Result<bool> {
+        crate::security::validate_session_id(key)?;
+        let archived = self.store().archive(key).await?;
+        self.invalidate(key).await;
+        Ok(archived)
     }
 
-    /// Compute the filesystem path for a session key.
+    /// Move an archived session back to the live set. Returns `false` when
+    /// there is no archived session with this key.
+    pub async fn restore(&self, key: &str) -> clawft_types::Result<bool> {
+        crate::security::validate_session_id(key)?;
+        self.store().restore(key).await
+    }
+
+    /// Archive the sessions `policy` expires at `now`, leaving alone those
+    /// for which `is_active` is true. Returns the archived keys.
+    pub async fn archive_expired(
+        &self,
+        policy: &RetentionPolicy,
+        now: chrono::DateTime<Utc>,
+        is_active: impl Fn(&str) -> bool,
+    ) -> clawft_types::Result<Vec<String>> {
+        let live = self.store().list(&SessionQuery::default()).await?;
+        let mut archived = Vec::new();
+        for key in policy.expired(&live.sessions, now, &is_active) {
+            // A turn may have started since the listing.
+            if !is_active(&key) && self.archive(&key).await? {
+                archived.push(key);
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Fork session `key` after its first `message_index` messages and
+    /// save the fork (see [`Session::fork_at`]).
     ///
-    /// Uses percent-encoding to safely represent any valid session key as
-    /// a filename. This is reversible: `list_sessions()` decodes back to
-    /// the original key.
-    fn session_path(&self, key: &str) -> PathBuf {
-        let encoded = percent_encode(key.as_bytes(), NON_ALPHANUMERIC).to_string();
-        let filename = format!("{encoded}.jsonl");
-        self.sessions_dir.join(filename)
+    /// The original session is left untouched. Memory (`MEMORY.md`) is
+    /// per workspace, so the fork shares it; only the history diverges.
+    ///
+    /// Returns an error if the session does not exist or `message_index`
+    /// is past its last message.
+    pub async fn fork(&self, key: &str, message_index: usize) -> clawft_types::Result<Session> {
+        let session = match self.active_sessions.lock().await.get(key) {
+            Some(session) => session.clone(),
+            None => self.load_session(key).await?,
+        };
+        let fork = session.fork_at(message_index).ok_or_else(|| {
+            ClawftError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "cannot fork '{key}' at message {message_index}: it has {} messages",
+                    session.messages.len()
+                ),
+            ))
+        })?;
+        crate::security::validate_session_id(&fork.key)?;
+        self.save_session(&fork).await?;
+        debug!(parent = key, fork = %fork.key, message_index, "forked session");
+        Ok(fork)
+    }
+
+    /// Get the sessions directory path.
+    pub fn sessions_dir(&self) -> &PathBuf {
+        &self.sessions_dir
     }
 }
 
@@ -708,18 +690,6 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn session_path_uses_percent_encoding() {
-        let platform = make_platform();
-        let mgr = make_manager(platform);
-
-        let path = mgr.session_path("telegram:12345");
-        assert_eq!(
-            path,
-            PathBuf::from("/mock-home/.clawft/workspace/sessions/telegram%3A12345.jsonl")
-        );
-    }
-
     #[tokio::test]
     async fn roundtrip_key_with_underscores() {
         let platform = make_platform();
@@ -837,6 +807,140 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn concurrent_append_turns_keep_every_message() {
+        let platform = make_platform();
+        let mgr = Arc::new(make_manager(platform));
+
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|writer| {
+                let mgr = mgr.clone();
+                tokio::spawn(async move {
+                    for i in 0..20 {
+                        mgr.append_turn("race:1", "user", &format!("{writer}{i}"))
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        mgr.invalidate("race:1").await;
+        let loaded = mgr.load_session("race:1").await.unwrap();
+        assert_eq!(loaded.messages.len(), 40);
+    }
+
+    #[tokio::test]
+    async fn list_pages_through_sessions() {
+        let platform = make_platform();
+        let mgr = make_manager(platform);
+        for key in ["cli:1", "cli:2", "slack:1"] {
+            mgr.save_session(&Session::new(key)).await.unwrap();
+        }
+
+        let page = mgr
+            .list(&SessionQuery {
+                key_prefix: Some("cli:".into()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.sessions.len(), 1);
+        assert_eq!(page.sessions[0].key, "cli:1");
+    }
+
+    async fn manager_with_history(n: usize) -> SessionManager<MockPlatform> {
+        let mgr = make_manager(make_platform());
+        let mut session = Session::new("cli:1");
+        for i in 0..n {
+            session.add_message("user", &format!("m{i}"), None);
+        }
+        mgr.save_session(&session).await.unwrap();
+        mgr
+    }
+
+    #[tokio::test]
+    async fn fork_at_zero_starts_empty_and_keeps_original() {
+        let mgr = manager_with_history(3).await;
+        let fork = mgr.fork("cli:1", 0).await.unwrap();
+        assert!(fork.messages.is_empty());
+        assert_eq!(fork.parent(), Some("cli:1"));
+
+        mgr.invalidate(&fork.key).await;
+        let loaded = mgr.load_session(&fork.key).await.unwrap();
+        assert_eq!(loaded.parent(), Some("cli:1"));
+        assert_eq!(mgr.load_session("cli:1").await.unwrap().messages.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn fork_at_tail_copies_history_and_lists_parent() {
+        let mgr = manager_with_history(3).await;
+        let fork = mgr.fork("cli:1", 3).await.unwrap();
+        assert_eq!(fork.messages.len(), 3);
+
+        let page = mgr.list(&SessionQuery::default()).await.unwrap();
+        let child = page.sessions.iter().find(|s| s.key == fork.key).unwrap();
+        assert_eq!(child.parent.as_deref(), Some("cli:1"));
+        let parent = page.sessions.iter().find(|s| s.key == "cli:1").unwrap();
+        assert_eq!(parent.parent, None);
+    }
+
+    #[tokio::test]
+    async fn fork_past_the_end_fails() {
+        let mgr = manager_with_history(2).await;
+        let err = mgr.fork("cli:1", 5).await.unwrap_err();
+        assert!(err.to_string().contains("it has 2 messages"), "{err}");
+        assert!(mgr.fork("cli:missing", 0).await.is_err());
+        assert_eq!(mgr.list_sessions().await.unwrap(), ["cli:1"]);
+    }
+
+    #[tokio::test]
+    async fn archive_expired_spares_active_sessions() {
+        let mgr = make_manager(make_platform());
+        let now = Utc::now();
+        for (key, idle_days) in [("slack:new", 1), ("slack:old", 40), ("slack:busy", 50)] {
+            let mut session = Session::new(key);
+            session.add_message("user", "hi", None);
+            session.updated_at = now - chrono::Duration::days(idle_days);
+            mgr.save_session(&session).await.unwrap();
+        }
+        let policy = RetentionPolicy {
+            max_idle: Some(chrono::Duration::days(30)),
+            max_sessions: None,
+        };
+
+        let archived = mgr
+            .archive_expired(&policy, now, |key| key == "slack:busy")
+            .await
+            .unwrap();
+        assert_eq!(archived, ["slack:old"]);
+        let mut live = mgr.list_sessions().await.unwrap();
+        live.sort();
+        assert_eq!(live, ["slack:busy", "slack:new"]);
+
+        // A new message to an archived session brings its history back.
+        let restored = mgr.get_or_create("slack:old").await.unwrap();
+        assert_eq!(restored.messages.len(), 1);
+        assert!(!mgr.restore("slack:old").await.unwrap());
+    }
+
+    #[cfg(not(feature = "sqlite-sessions"))]
+    #[tokio::test]
+    async fn sqlite_backend_needs_feature() {
+        let config = SessionsConfig {
+            backend: SessionBackend::Sqlite,
+            ..Default::default()
+        };
+        let result = SessionManager::from_config(make_platform(), &config).await;
+        assert!(matches!(result, Err(ClawftError::ConfigInvalid { .. })));
+    }
+
     #[tokio::test]
     async fn list_sessions_empty_dir() {
         let platform = make_platform();
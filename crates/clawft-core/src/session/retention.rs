@@ -0,0 +1,167 @@
+//! Session retention: archiving idle sessions and the oldest ones beyond a
+//! cap.
+//!
+//! [`RetentionPolicy`] decides which sessions expire;
+//! [`SessionManager::archive_expired`](super::SessionManager::archive_expired)
+//! archives them, and [`run_retention`] does so periodically in the
+//! gateway. Sessions with a turn in progress are never archived.
+
+use chrono::{DateTime, Duration, Utc};
+use clawft_types::config::SessionsConfig;
+
+use super::store::SessionSummary;
+
+/// Limits on how many sessions stay live, and for how long.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Sessions not updated for longer than this expire.
+    pub max_idle: Option<Duration>,
+
+    /// Beyond this many live sessions, the least recently updated expire.
+    pub max_sessions: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// The policy set by `agents.sessions`, or `None` when no limit is set.
+    pub fn from_config(config: &SessionsConfig) -> Option<Self> {
+        if !config.retention_enabled() {
+            return None;
+        }
+        Some(Self {
+            max_idle: (config.max_idle_days > 0)
+                .then(|| Duration::days(i64::from(config.max_idle_days))),
+            max_sessions: (config.max_sessions > 0).then_some(config.max_sessions),
+        })
+    }
+
+    /// Keys of the `sessions` that expire at `now`, least recently updated
+    /// first. Sessions for which `is_active` is true never expire, but
+    /// still count towards `max_sessions`.
+    pub fn expired(
+        &self,
+        sessions: &[SessionSummary],
+        now: DateTime<Utc>,
+        is_active: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let mut by_age: Vec<&SessionSummary> = sessions.iter().collect();
+        by_age.sort_by_key(|s| s.updated_at);
+
+        let idle = |s: &SessionSummary| self.max_idle.is_some_and(|max| now - s.updated_at > max);
+        let mut expired: Vec<&SessionSummary> = by_age
+            .iter()
+            .copied()
+            .filter(|s| idle(s) && !is_active(&s.key))
+            .collect();
+
+        if let Some(max) = self.max_sessions {
+            let mut excess = (sessions.len() - expired.len()).saturating_sub(max);
+            for s in by_age {
+                if excess == 0 {
+                    break;
+                }
+                if !idle(s) && !is_active(&s.key) {
+                    expired.push(s);
+                    excess -= 1;
+                }
+            }
+            expired.sort_by_key(|s| s.updated_at);
+        }
+        expired.into_iter().map(|s| s.key.clone()).collect()
+    }
+}
+
+/// Archive expired sessions every `interval` until `cancel` fires, leaving
+/// alone sessions with a turn running in `turns`.
+#[cfg(feature = "native")]
+pub async fn run_retention<P: clawft_platform::Platform>(
+    sessions: &super::SessionManager<P>,
+    policy: RetentionPolicy,
+    interval: std::time::Duration,
+    turns: &crate::agent::turns::ActiveTurns,
+    cancel: &clawft_plugin::CancellationToken,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            _ = ticker.tick() => {}
+        }
+        match sessions
+            .archive_expired(&policy, Utc::now(), |key| turns.is_running(key))
+            .await
+        {
+            Ok(archived) if !archived.is_empty() => {
+                tracing::info!(count = archived.len(), keys = ?archived, "archived expired sessions");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "session retention pass failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(key: &str, idle_days: i64, now: DateTime<Utc>) -> SessionSummary {
+        SessionSummary {
+            key: key.into(),
+            created_at: now - Duration::days(idle_days + 1),
+            updated_at: now - Duration::days(idle_days),
+            message_count: 1,
+            parent: None,
+        }
+    }
+
+    #[test]
+    fn policy_follows_config() {
+        assert_eq!(
+            RetentionPolicy::from_config(&SessionsConfig::default()),
+            None
+        );
+        let config = SessionsConfig {
+            max_idle_days: 30,
+            ..Default::default()
+        };
+        assert_eq!(
+            RetentionPolicy::from_config(&config),
+            Some(RetentionPolicy {
+                max_idle: Some(Duration::days(30)),
+                max_sessions: None,
+            })
+        );
+    }
+
+    #[test]
+    fn idle_and_excess_sessions_expire_oldest_first() {
+        let now = Utc::now();
+        let sessions = [
+            summary("slack:new", 0, now),
+            summary("slack:old", 40, now),
+            summary("slack:mid", 10, now),
+            summary("slack:busy", 50, now),
+            summary("slack:week", 7, now),
+        ];
+        let busy = |key: &str| key == "slack:busy";
+
+        let idle = RetentionPolicy {
+            max_idle: Some(Duration::days(30)),
+            max_sessions: None,
+        };
+        assert_eq!(idle.expired(&sessions, now, busy), ["slack:old"]);
+
+        // Five sessions, one idle, one active: keeping two archives the two
+        // oldest of the rest.
+        let capped = RetentionPolicy {
+            max_sessions: Some(2),
+            ..idle
+        };
+        assert_eq!(
+            capped.expired(&sessions, now, busy),
+            ["slack:old", "slack:mid", "slack:week"]
+        );
+
+        let nothing = RetentionPolicy::default();
+        assert!(nothing.expired(&sessions, now, busy).is_empty());
+    }
+}
@@ -0,0 +1,607 @@
+//! SQLite session store (feature `sqlite-sessions`).
+//!
+//! [`SqliteSessionStore`] keeps every session in one database file, in WAL
+//! mode so readers never block the writer. Each message is its own row, so
+//! appends from several processes (gateway and CLI) sharing the database
+//! are all kept, and listing sessions reads only the `sessions` table.
+//! Archived sessions stay in place with their `archived` flag set.
+//!
+//! Database calls run on the blocking thread pool.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use tracing::debug;
+
+use clawft_types::error::ClawftError;
+use clawft_types::session::Session;
+
+use super::store::{SessionPage, SessionQuery, SessionStore, SessionSummary};
+
+/// How long a writer waits for another connection's write lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    key TEXT PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    metadata TEXT NOT NULL DEFAULT '{}',
+    last_consolidated INTEGER NOT NULL DEFAULT 0,
+    message_count INTEGER NOT NULL DEFAULT 0,
+    archived INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS sessions_updated_at ON sessions (updated_at);
+CREATE TABLE IF NOT EXISTS messages (
+    session_key TEXT NOT NULL REFERENCES sessions (key) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (session_key, seq)
+);
+";
+
+/// Session store backed by a SQLite database.
+pub struct SqliteSessionStore {
+    /// Path of the database file.
+    path: PathBuf,
+
+    /// The connection, shared with blocking tasks.
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteSessionStore {
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> clawft_types::Result<Self> {
+        let path = path.into();
+        let conn = Connection::open(&path).map_err(storage_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(storage_error)?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(storage_error)?;
+        conn.pragma_update(None, "foreign_keys", true)
+            .map_err(storage_error)?;
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        add_archived_column(&conn).map_err(storage_error)?;
+        debug!(path = %path.display(), "opened sqlite session store");
+        Ok(Self {
+            path,
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flip the `archived` flag of `key`. Returns `false` when no session
+    /// with the other flag value exists.
+    async fn set_archived(&self, key: &str, archived: bool) -> clawft_types::Result<bool> {
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "UPDATE sessions SET archived = ?2 WHERE key = ?1 AND archived = NOT ?2",
+                params![key, archived],
+            )
+            .map(|changed| changed > 0)
+        })
+        .await
+    }
+
+    /// Run `f` with the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> clawft_types::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| {
+                ClawftError::Io(std::io::Error::other("session database lock poisoned"))
+            })?;
+            f(&mut conn).map_err(storage_error)
+        })
+        .await
+        .map_err(|e| ClawftError::Io(std::io::Error::other(e)))?
+    }
+}
+
+#[async_trait]
+impl SessionStore for SqliteSessionStore {
+    async fn load(&self, key: &str) -> clawft_types::Result<Option<Session>> {
+        let key = key.to_string();
+        let row = self
+            .with_conn(move |conn| {
+                let tx = conn.transaction()?;
+                let header = tx
+                    .query_row(
+                        "SELECT created_at, updated_at, metadata, last_consolidated
+                         FROM sessions WHERE key = ?1 AND archived = 0",
+                        params![key],
+                        |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, String>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, i64>(3)?,
+                            ))
+                        },
+                    )
+                    .optional()?;
+                let Some(header) = header else {
+                    return Ok(None);
+                };
+                let mut stmt =
+                    tx.prepare("SELECT body FROM messages WHERE session_key = ?1 ORDER BY seq")?;
+                let bodies = stmt
+                    .query_map(params![key], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                drop(stmt);
+                tx.commit()?;
+                Ok(Some((key, header, bodies)))
+            })
+            .await?;
+
+        let Some((key, (created_at, updated_at, metadata, last_consolidated), bodies)) = row else {
+            return Ok(None);
+        };
+        let messages = bodies
+            .iter()
+            .map(|body| serde_json::from_str(body))
+            .collect::<Result<Vec<_>, _>>()?;
+        let metadata: HashMap<String, serde_json::Value> = serde_json::from_str(&metadata)?;
+        Ok(Some(Session {
+            key,
+            messages,
+            created_at: parse_time(&created_at),
+            updated_at: parse_time(&updated_at),
+            metadata,
+            last_consolidated: last_consolidated as usize,
+        }))
+    }
+
+    async fn save(&self, session: &Session) -> clawft_types::Result<()> {
+        let key = session.key.clone();
+        let created_at = format_time(session.created_at);
+        let updated_at = format_time(session.updated_at);
+        let metadata = serde_json::to_string(&session.metadata)?;
+        let last_consolidated = session.last_consolidated as i64;
+        let bodies = session
+            .messages
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            tx.execute(
+                "INSERT INTO sessions
+                     (key, created_at, updated_at, metadata, last_consolidated, message_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (key) DO UPDATE SET
+                     created_at = excluded.created_at,
+                     updated_at = excluded.updated_at,
+                     metadata = excluded.metadata,
+                     last_consolidated = excluded.last_consolidated,
+                     message_count = excluded.message_count,
+                     archived = 0",
+                params![
+                    key,
+                    created_at,
+                    updated_at,
+                    metadata,
+                    last_consolidated,
+                    bodies.len() as i64
+                ],
+            )?;
+            tx.execute("DELETE FROM messages WHERE session_key = ?1", params![key])?;
+            {
+                let mut insert = tx
+                    .prepare("INSERT INTO messages (session_key, seq, body) VALUES (?1, ?2, ?3)")?;
+                for (seq, body) in bodies.iter().enumerate() {
+                    insert.execute(params![key, seq as i64, body])?;
+                }
+            }
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn list(&self, query: &SessionQuery) -> clawft_types::Result<SessionPage> {
+        let prefix = query.key_prefix.as_deref().map(like_prefix);
+        let since = query.updated_since.map(format_time);
+        let limit = query.limit.map_or(-1, |l| l as i64);
+        let offset = query.offset as i64;
+        let archived = query.archived;
+
+        let (total, rows) = self
+            .with_conn(move |conn| {
+                let filter = "(?1 IS NULL OR key LIKE ?1 ESCAPE '\\')
+                              AND (?2 IS NULL OR updated_at >= ?2)
+                              AND archived = ?3";
+                let total: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM sessions WHERE {filter}"),
+                    params![prefix, since, archived],
+                    |row| row.get(0),
+                )?;
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT key, created_at, updated_at, message_count,
+                            json_extract(metadata, '$.{parent}')
+                     FROM sessions
+                     WHERE {filter} ORDER BY key LIMIT ?4 OFFSET ?5",
+                    parent = Session::PARENT_KEY,
+                ))?;
+                let rows = stmt
+                    .query_map(params![prefix, since, archived, limit, offset], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                            row.get::<_, i64>(3)?,
+                            row.get::<_, Option<String>>(4)?,
+                        ))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok((total, rows))
+            })
+            .await?;
+
+        Ok(SessionPage {
+            sessions: rows
+                .into_iter()
+                .map(
+                    |(key, created_at, updated_at, count, parent)| SessionSummary {
+                        key,
+                        created_at: parse_time(&created_at),
+                        updated_at: parse_time(&updated_at),
+                        message_count: count as usize,
+                        parent,
+                    },
+                )
+                .collect(),
+            total: total as usize,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> clawft_types::Result<()> {
+        let key = key.to_string();
+        self.with_conn(move |conn| {
+            conn.execute("DELETE FROM sessions WHERE key = ?1", params![key])
+                .map(|_| ())
+        })
+        .await
+    }
+
+    async fn archive(&self, key: &str) -> clawft_types::Result<bool> {
+        self.set_archived(key, true).await
+    }
+
+    async fn restore(&self, key: &str) -> clawft_types::Result<bool> {
+        self.set_archived(key, false).await
+    }
+
+    async fn append_message(
+        &self,
+        key: &str,
+        message: &serde_json::Value,
+    ) -> clawft_types::Result<()> {
+        let key = key.to_string();
+        let body = serde_json::to_string(message)?;
+        let now = format_time(Utc::now());
+
+        self.with_conn(move |conn| {
+            // IMMEDIATE takes the write lock up front, so two writers cannot
+            // both read the same next sequence number.
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            tx.execute(
+                "INSERT INTO sessions (key, created_at, updated_at) VALUES (?1, ?2, ?2)
+                 ON CONFLICT (key) DO UPDATE SET updated_at = excluded.updated_at, archived = 0",
+                params![key, now],
+            )?;
+            tx.execute(
+                "INSERT INTO messages (session_key, seq, body)
+                 VALUES (?1, (SELECT COALESCE(MAX(seq) + 1, 0) FROM messages WHERE session_key = ?1), ?2)",
+                params![key, body],
+            )?;
+            tx.execute(
+                "UPDATE sessions SET message_count = message_count + 1 WHERE key = ?1",
+                params![key],
+            )?;
+            tx.commit()
+        })
+        .await
+    }
+}
+
+/// Add the `archived` column to databases created before it existed.
+fn add_archived_column(conn: &Connection) -> rusqlite::Result<()> {
+    let has_column = conn
+        .prepare("SELECT 1 FROM pragma_table_info('sessions') WHERE name = 'archived'")?
+        .exists([])?;
+    if !has_column {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN archived INTEGER NOT NULL DEFAULT 0")?;
+    }
+    Ok(())
+}
+
+/// Map a SQLite error into the crate error type.
+fn storage_error(e: rusqlite::Error) -> ClawftError {
+    ClawftError::Io(std::io::Error::other(e))
+}
+
+/// Format a timestamp for storage. The fixed width keeps string order
+/// equal to time order, which `updated_since` filtering relies on.
+fn format_time(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Parse a stored RFC 3339 timestamp, falling back to now.
+fn parse_time(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap_or_else(|_| Utc::now())
+}
+
+/// A `LIKE` pattern matching keys that start with `prefix`.
+fn like_prefix(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TEST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_db(prefix: &str) -> PathBuf {
+        let id = TEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let dir = std::env::temp_dir().join(format!("clawft_sqlite_sessions_{prefix}_{pid}_{id}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("sessions.db")
+    }
+
+    fn cleanup(db: &Path) {
+        let _ = std::fs::remove_dir_all(db.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn save_and_load_roundtrip() {
+        let db = temp_db("roundtrip");
+        let store = SqliteSessionStore::open(&db).unwrap();
+
+        let mut session = Session::new("telegram:42");
+        session.add_message("user", "hello", None);
+        session.add_message("assistant", "hi", None);
+        session
+            .metadata
+            .insert("agent".into(), serde_json::json!("default"));
+        session.last_consolidated = 1;
+        store.save(&session).await.unwrap();
+
+        let loaded = store.load("telegram:42").await.unwrap().unwrap();
+        assert_eq!(loaded.messages, session.messages);
+        assert_eq!(loaded.metadata["agent"], "default");
+        assert_eq!(loaded.last_consolidated, 1);
+        assert_eq!(
+            loaded.created_at.timestamp_micros(),
+            session.created_at.timestamp_micros()
+        );
+
+        // Saving a shorter history replaces the old one.
+        session.clear();
+        store.save(&session).await.unwrap();
+        let loaded = store.load("telegram:42").await.unwrap().unwrap();
+        assert!(loaded.messages.is_empty());
+
+        assert!(store.load("missing:1").await.unwrap().is_none());
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn list_pages_and_filters() {
+        let db = temp_db("list");
+        let store = SqliteSessionStore::open(&db).unwrap();
+        for key in [
+            "slack:1",
+            "telegram:1",
+            "telegram:2",
+            "telegram:3",
+            "telegram_x:1",
+        ] {
+            store.save(&Session::new(key)).await.unwrap();
+        }
+
+        let page = store
+            .list(&SessionQuery {
+                key_prefix: Some("telegram:".into()),
+                offset: 1,
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        // `_` in the prefix is literal, so `telegram_x:1` does not match.
+        assert_eq!(page.total, 3);
+        let keys: Vec<&str> = page.sessions.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["telegram:2"]);
+
+        let all = store.list(&SessionQuery::default()).await.unwrap();
+        assert_eq!(all.total, 5);
+        assert_eq!(all.sessions.len(), 5);
+
+        let future = store
+            .list(&SessionQuery {
+                updated_since: Some(Utc::now() + chrono::Duration::hours(1)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(future.total, 0);
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn list_reports_fork_parent() {
+        let db = temp_db("fork");
+        let store = SqliteSessionStore::open(&db).unwrap();
+        let mut parent = Session::new("cli:1");
+        parent.add_message("user", "hi", None);
+        let fork = parent.fork_at(1).unwrap();
+        store.save(&parent).await.unwrap();
+        store.save(&fork).await.unwrap();
+
+        let all = store.list(&SessionQuery::default()).await.unwrap();
+        assert_eq!(all.sessions[0].key, "cli:1");
+        assert_eq!(all.sessions[0].parent, None);
+        assert_eq!(all.sessions[1].key, fork.key);
+        assert_eq!(all.sessions[1].parent.as_deref(), Some("cli:1"));
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn delete_removes_messages() {
+        let db = temp_db("delete");
+        let store = SqliteSessionStore::open(&db).unwrap();
+        let msg = serde_json::json!({"role": "user", "content": "x"});
+        store.append_message("cli:1", &msg).await.unwrap();
+        store.delete("cli:1").await.unwrap();
+        store.delete("cli:1").await.unwrap();
+
+        assert!(store.load("cli:1").await.unwrap().is_none());
+        // A recreated session starts empty.
+        store.append_message("cli:1", &msg).await.unwrap();
+        let loaded = store.load("cli:1").await.unwrap().unwrap();
+        assert_eq!(loaded.messages.len(), 1);
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn archive_flag_hides_and_restores_sessions() {
+        let db = temp_db("archive");
+        // A database created before the `archived` column existed.
+        Connection::open(&db)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE sessions (
+                     key TEXT PRIMARY KEY,
+                     created_at TEXT NOT NULL,
+                     updated_at TEXT NOT NULL,
+                     metadata TEXT NOT NULL DEFAULT '{}',
+                     last_consolidated INTEGER NOT NULL DEFAULT 0,
+                     message_count INTEGER NOT NULL DEFAULT 0
+                 );",
+            )
+            .unwrap();
+        let store = SqliteSessionStore::open(&db).unwrap();
+        let msg = serde_json::json!({"role": "user", "content": "x"});
+        store.append_message("cli:1", &msg).await.unwrap();
+        store.append_message("cli:2", &msg).await.unwrap();
+
+        assert!(store.archive("cli:1").await.unwrap());
+        assert!(!store.archive("cli:1").await.unwrap());
+        assert!(store.load("cli:1").await.unwrap().is_none());
+        assert_eq!(store.list(&SessionQuery::default()).await.unwrap().total, 1);
+        let archived = store
+            .list(&SessionQuery {
+                archived: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(archived.total, 1);
+        assert_eq!(archived.sessions[0].key, "cli:1");
+
+        assert!(store.restore("cli:1").await.unwrap());
+        assert!(!store.restore("cli:1").await.unwrap());
+        assert_eq!(
+            store.load("cli:1").await.unwrap().unwrap().messages.len(),
+            1
+        );
+
+        // Appending to an archived session brings it back with its history.
+        store.archive("cli:2").await.unwrap();
+        store.append_message("cli:2", &msg).await.unwrap();
+        assert_eq!(
+            store.load("cli:2").await.unwrap().unwrap().messages.len(),
+            2
+        );
+        cleanup(&db);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn two_writers_appending_lose_no_messages() {
+        let db = temp_db("writers");
+        // Separate connections, as the gateway and CLI would have.
+        let gateway = Arc::new(SqliteSessionStore::open(&db).unwrap());
+        let cli = Arc::new(SqliteSessionStore::open(&db).unwrap());
+
+        let writers: Vec<_> = [("gateway", gateway.clone()), ("cli", cli)]
+            .into_iter()
+            .map(|(writer, store)| {
+                tokio::spawn(async move {
+                    for i in 0..50 {
+                        let msg =
+                            serde_json::json!({"role": "user", "content": format!("{writer}-{i}")});
+                        store.append_message("shared:1", &msg).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        let session = gateway.load("shared:1").await.unwrap().unwrap();
+        assert_eq!(session.messages.len(), 100);
+        for writer in ["gateway", "cli"] {
+            let own: Vec<String> = session
+                .messages
+                .iter()
+                .filter_map(|m| m["content"].as_str())
+                .filter(|c| c.starts_with(writer))
+                .map(String::from)
+                .collect();
+            let expected: Vec<String> = (0..50).map(|i| format!("{writer}-{i}")).collect();
+            assert_eq!(own, expected);
+        }
+        let listed = gateway.list(&SessionQuery::default()).await.unwrap();
+        assert_eq!(listed.sessions[0].message_count, 100);
+        cleanup(&db);
+    }
+
+    #[tokio::test]
+    async fn migrate_imports_file_sessions_once() {
+        use crate::session::file::FileSessionStore;
+        use crate::session::store::migrate;
+        use clawft_platform::NativePlatform;
+
+        let db = temp_db("migrate");
+        let dir = db.parent().unwrap().join("jsonl");
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = FileSessionStore::new(Arc::new(NativePlatform::new()), dir);
+        let mut session = Session::new("discord:7");
+        session.add_message("user", "old message", None);
+        files.save(&session).await.unwrap();
+        files.save(&Session::new("slack:9")).await.unwrap();
+
+        let sqlite = SqliteSessionStore::open(&db).unwrap();
+        assert_eq!(migrate(&files, &sqlite).await.unwrap(), 2);
+        assert_eq!(migrate(&files, &sqlite).await.unwrap(), 0);
+
+        let loaded = sqlite.load("discord:7").await.unwrap().unwrap();
+        assert_eq!(loaded.messages, session.messages);
+        cleanup(&db);
+    }
+}
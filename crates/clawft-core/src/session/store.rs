@@ -0,0 +1,191 @@
+//! Pluggable session persistence.
+//!
+//! [`SessionStore`] is the storage interface behind
+//! [`SessionManager`](super::SessionManager). Two implementations exist:
+//!
+//! - [`FileSessionStore`](super::file::FileSessionStore): one JSONL file per
+//!   session (the default).
+//! - `SqliteSessionStore` (feature `sqlite-sessions`): a single SQLite
+//!   database in WAL mode, safe for concurrent writers in separate processes
+//!   and able to page through thousands of sessions without reading them.
+//!
+//! [`migrate`] copies sessions between stores, e.g. to import existing JSONL
+//! sessions into SQLite.
+//!
+//! Stores also keep an archive: [`SessionStore::archive`] moves a session
+//! out of the live set without deleting it. Archived sessions are hidden
+//! from [`load`](SessionStore::load) and from listings unless
+//! [`SessionQuery::archived`] asks for them, and
+//! [`restore`](SessionStore::restore) brings them back.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+use clawft_types::session::Session;
+
+/// Storage backend for conversation sessions.
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+pub trait SessionStore: Send + Sync {
+    /// Load a session, or `None` if it does not exist.
+    async fn load(&self, key: &str) -> clawft_types::Result<Option<Session>>;
+
+    /// Store a session, replacing any existing copy.
+    async fn save(&self, session: &Session) -> clawft_types::Result<()>;
+
+    /// List sessions matching `query`, one page at a time.
+    async fn list(&self, query: &SessionQuery) -> clawft_types::Result<SessionPage>;
+
+    /// Keys of the live sessions, sorted. Stores that can list keys without
+    /// reading every session should override this.
+    async fn keys(&self) -> clawft_types::Result<Vec<String>> {
+        let page = self.list(&SessionQuery::default()).await?;
+        Ok(page.sessions.into_iter().map(|s| s.key).collect())
+    }
+
+    /// Delete a session, live or archived. Deleting a missing session is
+    /// not an error.
+    async fn delete(&self, key: &str) -> clawft_types::Result<()>;
+
+    /// Move a live session to the archive. Returns `false` when there is
+    /// no live session with this key.
+    async fn archive(&self, key: &str) -> clawft_types::Result<bool>;
+
+    /// Move an archived session back to the live set. Returns `false` when
+    /// there is no archived session with this key, and fails when a live
+    /// session already has it.
+    async fn restore(&self, key: &str) -> clawft_types::Result<bool>;
+
+    /// Append one message to a session, creating the session if needed.
+    ///
+    /// Concurrent appends to the same session must all be kept.
+    async fn append_message(
+        &self,
+        key: &str,
+        message: &serde_json::Value,
+    ) -> clawft_types::Result<()>;
+}
+
+/// Filters and pagination for [`SessionStore::list`].
+///
+/// Results are ordered by session key.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuery {
+    /// Only sessions whose key starts with this prefix (e.g. `"telegram:"`).
+    pub key_prefix: Option<String>,
+
+    /// Only sessions updated at or after this time.
+    pub updated_since: Option<DateTime<Utc>>,
+
+    /// Number of matching sessions to skip.
+    pub offset: usize,
+
+    /// Maximum number of sessions to return. `None` returns all.
+    pub limit: Option<usize>,
+
+    /// List archived sessions instead of live ones.
+    pub archived: bool,
+}
+
+impl SessionQuery {
+    /// Whether `key` passes the key-prefix filter.
+    pub fn matches_key(&self, key: &str) -> bool {
+        self.key_prefix
+            .as_deref()
+            .is_none_or(|prefix| key.starts_with(prefix))
+    }
+}
+
+/// A session listed without its messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// Session key.
+    pub key: String,
+
+    /// When the session was created.
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was last updated.
+    pub updated_at: DateTime<Utc>,
+
+    /// Number of messages in the session.
+    pub message_count: usize,
+
+    /// The session this one was forked from, if any.
+    pub parent: Option<String>,
+}
+
+impl From<&Session> for SessionSummary {
+    fn from(session: &Session) -> Self {
+        Self {
+            key: session.key.clone(),
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            message_count: session.messages.len(),
+            parent: session.parent().map(String::from),
+        }
+    }
+}
+
+/// One page of [`SessionStore::list`] results.
+#[derive(Debug, Clone, Default)]
+pub struct SessionPage {
+    /// The sessions on this page.
+    pub sessions: Vec<SessionSummary>,
+
+    /// Number of sessions matching the filters, across all pages.
+    pub total: usize,
+}
+
+/// Copy every session in `from` that `to` does not already have.
+///
+/// Sessions already present in `to` are left untouched, so running the
+/// migration twice is harmless. Returns the number of sessions copied.
+pub async fn migrate(
+    from: &dyn SessionStore,
+    to: &dyn SessionStore,
+) -> clawft_types::Result<usize> {
+    let listed = from.list(&SessionQuery::default()).await?;
+    let mut copied = 0;
+    for summary in listed.sessions {
+        if to.load(&summary.key).await?.is_some() {
+            continue;
+        }
+        if let Some(session) = from.load(&summary.key).await? {
+            to.save(&session).await?;
+            copied += 1;
+        }
+    }
+    info!(copied, total = listed.total, "migrated sessions");
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_prefix_filter() {
+        let all = SessionQuery::default();
+        assert!(all.matches_key("slack:1"));
+
+        let telegram = SessionQuery {
+            key_prefix: Some("telegram:".into()),
+            ..Default::default()
+        };
+        assert!(telegram.matches_key("telegram:42"));
+        assert!(!telegram.matches_key("slack:42"));
+    }
+
+    #[test]
+    fn summary_counts_messages() {
+        let mut session = Session::new("cli:1");
+        session.add_message("user", "hi", None);
+        session.add_message("assistant", "hello", None);
+        let summary = SessionSummary::from(&session);
+        assert_eq!(summary.key, "cli:1");
+        assert_eq!(summary.message_count, 2);
+        assert_eq!(summary.updated_at, session.updated_at);
+    }
+}
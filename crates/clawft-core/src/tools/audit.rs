@@ -0,0 +1,820 @@
+//! Tool execution audit log.
+//!
+//! Every tool call the agent loop makes is recorded as an [`AuditRecord`]:
+//! when it ran, for which agent and session, the tool name, a redacted
+//! summary of its arguments, how long it took, and how it ended. Records
+//! go to an [`AuditStore`]: an append-only JSONL file that rotates once it
+//! grows past a size limit ([`JsonlAuditLog`]), or a table in the session
+//! database when the SQLite session backend is enabled
+//! ([`SqliteAuditLog`]).
+//!
+//! Argument values under field names that look like credentials (`token`,
+//! `password`, `secret`, ...) are replaced with [`REDACTED`] before they
+//! are written; see [`Redactor`].
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use super::registry::ToolError;
+
+/// File name of the JSONL audit log, inside `~/.clawft/state/`.
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Replacement for redacted argument values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field name patterns that are always redacted.
+///
+/// Matched as substrings of the field name, lowercased with `_` and `-`
+/// removed, so `api_key`, `X-Api-Key` and `apiKey` all match `apikey`.
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    "token",
+    "password",
+    "passwd",
+    "secret",
+    "apikey",
+    "authorization",
+    "credential",
+    "privatekey",
+];
+
+/// Longest string argument kept verbatim; longer values are cut.
+const MAX_ARG_CHARS: usize = 200;
+
+/// Path of the JSONL audit log under the user's home directory.
+pub fn audit_path(home: &Path) -> PathBuf {
+    home.join(".clawft").join("state").join(AUDIT_FILE)
+}
+
+/// One recorded tool execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the tool finished.
+    pub timestamp: DateTime<Utc>,
+
+    /// Agent that made the call (`"default"` for the main agent).
+    pub agent_id: String,
+
+    /// Session the call belongs to.
+    pub session_key: String,
+
+    /// Tool name.
+    pub tool: String,
+
+    /// Redacted, truncated summary of the arguments.
+    pub args: Value,
+
+    /// Wall-clock execution time in milliseconds.
+    pub duration_ms: u64,
+
+    /// Whether the tool succeeded.
+    pub success: bool,
+
+    /// The error message, when the tool failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Size of the serialized result in bytes (0 on failure).
+    pub result_bytes: u64,
+}
+
+/// Which records [`AuditStore::recent`] returns.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only records for this session.
+    pub session_key: Option<String>,
+
+    /// Only records for this tool.
+    pub tool: Option<String>,
+}
+
+impl AuditFilter {
+    /// Whether `record` passes the filter.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.session_key
+            .as_deref()
+            .is_none_or(|key| record.session_key == key)
+            && self.tool.as_deref().is_none_or(|tool| record.tool == tool)
+    }
+}
+
+/// Redacts credential-like fields from tool arguments.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    /// Normalized patterns (lowercase, no `_` or `-`).
+    patterns: Vec<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl Redactor {
+    /// A redactor for [`DEFAULT_REDACT_PATTERNS`] plus `extra` patterns.
+    pub fn new(extra: &[String]) -> Self {
+        let patterns = DEFAULT_REDACT_PATTERNS
+            .iter()
+            .map(|p| p.to_string())
+            .chain(extra.iter().map(|p| normalize(p)))
+            .filter(|p| !p.is_empty())
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether values under `field` are redacted.
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        let field = normalize(field);
+        self.patterns.iter().any(|p| field.contains(p.as_str()))
+    }
+
+    /// Summarize `args` for the log: sensitive fields are replaced with
+    /// [`REDACTED`] at any depth and long strings are truncated.
+    pub fn summarize(&self, args: &Value) -> Value {
+        self.mask(args, true)
+    }
+
+    /// Replace sensitive fields in `args` with [`REDACTED`] at any depth,
+    /// leaving everything else intact (e.g. for conversation exports).
+    pub fn redact(&self, args: &Value) -> Value {
+        self.mask(args, false)
+    }
+
+    fn mask(&self, args: &Value, truncate: bool) -> Value {
+        match args {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| {
+                        let value = if self.is_sensitive(key) {
+                            Value::String(REDACTED.into())
+                        } else {
+                            self.mask(value, truncate)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.mask(v, truncate)).collect())
+            }
+            Value::String(s) if truncate && s.chars().count() > MAX_ARG_CHARS => {
+                let cut: String = s.chars().take(MAX_ARG_CHARS).collect();
+                Value::String(format!("{cut}..."))
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+fn normalize(field: &str) -> String {
+    field
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Where audit records are kept.
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+pub trait AuditStore: Send + Sync {
+    /// Append one record.
+    async fn append(&self, record: &AuditRecord) -> clawft_types::Result<()>;
+
+    /// The last `limit` records passing `filter`, oldest first.
+    async fn recent(
+        &self,
+        filter: &AuditFilter,
+        limit: usize,
+    ) -> clawft_types::Result<Vec<AuditRecord>>;
+}
+
+/// Records tool executions into an [`AuditStore`].
+pub struct ToolAuditor {
+    store: Arc<dyn AuditStore>,
+    redactor: Redactor,
+}
+
+impl ToolAuditor {
+    /// Create an auditor writing to `store`.
+    pub fn new(store: Arc<dyn AuditStore>, redactor: Redactor) -> Self {
+        Self { store, redactor }
+    }
+
+    /// The underlying store.
+    pub fn store(&self) -> &Arc<dyn AuditStore> {
+        &self.store
+    }
+
+    /// Record one execution of `tool`.
+    ///
+    /// Write failures are logged and otherwise ignored: auditing never
+    /// fails a tool call.
+    pub async fn record(
+        &self,
+        agent_id: &str,
+        session_key: &str,
+        tool: &str,
+        args: &Value,
+        duration_ms: u64,
+        result: &Result<Value, ToolError>,
+    ) {
+        let (success, error, result_bytes) = match result {
+            Ok(value) => (
+                true,
+                None,
+                serde_json::to_string(value).map_or(0, |s| s.len() as u64),
+            ),
+            Err(e) => (false, Some(e.to_string()), 0),
+        };
+        let record = AuditRecord {
+            timestamp: Utc::now(),
+            agent_id: agent_id.to_string(),
+            session_key: session_key.to_string(),
+            tool: tool.to_string(),
+            args: self.redactor.summarize(args),
+            duration_ms,
+            success,
+            error,
+            result_bytes,
+        };
+        if let Err(e) = self.store.append(&record).await {
+            warn!(tool, error = %e, "failed to write tool audit record");
+        }
+    }
+}
+
+/// Open the audit store selected by configuration.
+///
+/// Uses the session database in `sessions_dir` when `sqlite` is set (and
+/// the `sqlite-sessions` feature is compiled in), otherwise the JSONL log
+/// under `home`. Returns `None` when auditing is disabled.
+#[cfg(feature = "native")]
+pub fn open_store(
+    config: &clawft_types::config::ToolAuditConfig,
+    sqlite: bool,
+    sessions_dir: &Path,
+    home: &Path,
+) -> clawft_types::Result<Option<Arc<dyn AuditStore>>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    #[cfg(feature = "sqlite-sessions")]
+    if sqlite {
+        let path = sessions_dir.join(crate::session::SQLITE_DB_NAME);
+        return Ok(Some(Arc::new(SqliteAuditLog::open(path)?)));
+    }
+    #[cfg(not(feature = "sqlite-sessions"))]
+    let _ = (sqlite, sessions_dir);
+
+    Ok(Some(Arc::new(JsonlAuditLog::new(
+        audit_path(home),
+        config.max_file_bytes,
+        config.max_files,
+    ))))
+}
+
+#[cfg(feature = "native")]
+pub use jsonl::JsonlAuditLog;
+
+#[cfg(feature = "native")]
+mod jsonl {
+    use std::collections::VecDeque;
+    use std::fs::{self, OpenOptions};
+    use std::io::{BufRead, BufReader, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use tracing::warn;
+
+    use clawft_types::error::ClawftError;
+
+    use super::{AuditFilter, AuditRecord, AuditStore};
+
+    /// Append-only JSONL audit log with size-based rotation.
+    ///
+    /// When appending a record would take the file past `max_bytes`, the
+    /// file is renamed to `<name>.1` (shifting older files to `.2`, `.3`,
+    /// ...) and a new one is started. At most `max_files` rotated files
+    /// are kept.
+    pub struct JsonlAuditLog {
+        path: PathBuf,
+        max_bytes: u64,
+        max_files: usize,
+        lock: Arc<Mutex<()>>,
+    }
+
+    impl JsonlAuditLog {
+        /// A log at `path`, rotated at `max_bytes` keeping `max_files`.
+        pub fn new(path: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+            Self {
+                path: path.into(),
+                max_bytes,
+                max_files,
+                lock: Arc::new(Mutex::new(())),
+            }
+        }
+
+        /// Path of the current log file.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Path of rotated file `n` (1 is the newest).
+        pub fn rotated_path(&self, n: usize) -> PathBuf {
+            let mut name = self.path.as_os_str().to_owned();
+            name.push(format!(".{n}"));
+            PathBuf::from(name)
+        }
+
+        fn append_blocking(&self, line: &str) -> std::io::Result<()> {
+            let _guard = self
+                .lock
+                .lock()
+                .map_err(|_| std::io::Error::other("audit log lock poisoned"))?;
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let size = fs::metadata(&self.path).map_or(0, |m| m.len());
+            if size > 0 && size + line.len() as u64 > self.max_bytes {
+                self.rotate()?;
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            file.write_all(line.as_bytes())
+        }
+
+        fn rotate(&self) -> std::io::Result<()> {
+            if self.max_files == 0 {
+                return fs::remove_file(&self.path);
+            }
+            let oldest = self.rotated_path(self.max_files);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for n in (1..self.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))
+        }
+
+        fn recent_blocking(
+            &self,
+            filter: &AuditFilter,
+            limit: usize,
+        ) -> std::io::Result<Vec<AuditRecord>> {
+            let _guard = self
+                .lock
+                .lock()
+                .map_err(|_| std::io::Error::other("audit log lock poisoned"))?;
+            let files = (1..=self.max_files)
+                .rev()
+                .map(|n| self.rotated_path(n))
+                .chain(std::iter::once(self.path.clone()));
+
+            let mut out = VecDeque::with_capacity(limit.min(1024));
+            for path in files {
+                let file = match fs::File::open(&path) {
+                    Ok(f) => f,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                };
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<AuditRecord>(&line) {
+                        Ok(record) if filter.matches(&record) => {
+                            if out.len() == limit {
+                                out.pop_front();
+                            }
+                            if limit > 0 {
+                                out.push_back(record);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!(path = %path.display(), error = %e, "skipping malformed audit record");
+                        }
+                    }
+                }
+            }
+            Ok(out.into())
+        }
+
+        fn handle(&self) -> Self {
+            Self {
+                path: self.path.clone(),
+                max_bytes: self.max_bytes,
+                max_files: self.max_files,
+                lock: self.lock.clone(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AuditStore for JsonlAuditLog {
+        async fn append(&self, record: &AuditRecord) -> clawft_types::Result<()> {
+            let mut line = serde_json::to_string(record)?;
+            line.push('\n');
+            let log = self.handle();
+            tokio::task::spawn_blocking(move || log.append_blocking(&line))
+                .await
+                .map_err(|e| ClawftError::Io(std::io::Error::other(e)))??;
+            Ok(())
+        }
+
+        async fn recent(
+            &self,
+            filter: &AuditFilter,
+            limit: usize,
+        ) -> clawft_types::Result<Vec<AuditRecord>> {
+            let log = self.handle();
+            let filter = filter.clone();
+            let records = tokio::task::spawn_blocking(move || log.recent_blocking(&filter, limit))
+                .await
+                .map_err(|e| ClawftError::Io(std::io::Error::other(e)))??;
+            Ok(records)
+        }
+    }
+}
+
+#[cfg(feature = "sqlite-sessions")]
+pub use sqlite::SqliteAuditLog;
+
+#[cfg(feature = "sqlite-sessions")]
+mod sqlite {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use rusqlite::{Connection, params};
+
+    use clawft_types::error::ClawftError;
+
+    use super::{AuditFilter, AuditRecord, AuditStore};
+
+    const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tool_audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        agent_id TEXT NOT NULL,
+        session_key TEXT NOT NULL,
+        tool TEXT NOT NULL,
+        args TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        success INTEGER NOT NULL,
+        error TEXT,
+        result_bytes INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tool_audit_session ON tool_audit (session_key);
+    ";
+
+    /// Audit log kept in the `tool_audit` table of the session database.
+    pub struct SqliteAuditLog {
+        conn: Arc<Mutex<Connection>>,
+    }
+
+    impl SqliteAuditLog {
+        /// Open (creating if needed) the audit table in the database at
+        /// `path`.
+        pub fn open(path: impl Into<PathBuf>) -> clawft_types::Result<Self> {
+            let conn = Connection::open(path.into()).map_err(storage_error)?;
+            conn.busy_timeout(Duration::from_secs(5))
+                .map_err(storage_error)?;
+            conn.pragma_update(None, "journal_mode", "WAL")
+                .map_err(storage_error)?;
+            conn.execute_batch(SCHEMA).map_err(storage_error)?;
+            Ok(Self {
+                conn: Arc::new(Mutex::new(conn)),
+            })
+        }
+
+        async fn with_conn<T, F>(&self, f: F) -> clawft_types::Result<T>
+        where
+            T: Send + 'static,
+            F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        {
+            let conn = self.conn.clone();
+            tokio::task::spawn_blocking(move || {
+                let conn = conn.lock().map_err(|_| {
+                    ClawftError::Io(std::io::Error::other("audit database lock poisoned"))
+                })?;
+                f(&conn).map_err(storage_error)
+            })
+            .await
+            .map_err(|e| ClawftError::Io(std::io::Error::other(e)))?
+        }
+    }
+
+    #[async_trait]
+    impl AuditStore for SqliteAuditLog {
+        async fn append(&self, record: &AuditRecord) -> clawft_types::Result<()> {
+            let record = record.clone();
+            let args = serde_json::to_string(&record.args)?;
+            self.with_conn(move |conn| {
+                conn.execute(
+                    "INSERT INTO tool_audit (timestamp, agent_id, session_key, tool, args,
+                         duration_ms, success, error, result_bytes)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        record.timestamp.to_rfc3339(),
+                        record.agent_id,
+                        record.session_key,
+                        record.tool,
+                        args,
+                        record.duration_ms as i64,
+                        record.success,
+                        record.error,
+                        record.result_bytes as i64,
+                    ],
+                )
+                .map(|_| ())
+            })
+            .await
+        }
+
+        async fn recent(
+            &self,
+            filter: &AuditFilter,
+            limit: usize,
+        ) -> clawft_types::Result<Vec<AuditRecord>> {
+            let filter = filter.clone();
+            let mut records = self
+                .with_conn(move |conn| {
+                    let mut stmt = conn.prepare(
+                        "SELECT timestamp, agent_id, session_key, tool, args, duration_ms,
+                                success, error, result_bytes
+                         FROM tool_audit
+                         WHERE (?1 IS NULL OR session_key = ?1) AND (?2 IS NULL OR tool = ?2)
+                         ORDER BY id DESC LIMIT ?3",
+                    )?;
+                    let rows = stmt.query_map(
+                        params![filter.session_key, filter.tool, limit as i64],
+                        |row| {
+                            let timestamp: String = row.get(0)?;
+                            let args: String = row.get(4)?;
+                            Ok(AuditRecord {
+                                timestamp: DateTime::parse_from_rfc3339(&timestamp)
+                                    .map(|t| t.with_timezone(&Utc))
+                                    .unwrap_or_default(),
+                                agent_id: row.get(1)?,
+                                session_key: row.get(2)?,
+                                tool: row.get(3)?,
+                                args: serde_json::from_str(&args).unwrap_or_default(),
+                                duration_ms: row.get::<_, i64>(5)? as u64,
+                                success: row.get(6)?,
+                                error: row.get(7)?,
+                                result_bytes: row.get::<_, i64>(8)? as u64,
+                            })
+                        },
+                    )?;
+                    rows.collect::<rusqlite::Result<Vec<_>>>()
+                })
+                .await?;
+            records.reverse();
+            Ok(records)
+        }
+    }
+
+    fn storage_error(e: rusqlite::Error) -> ClawftError {
+        ClawftError::Io(std::io::Error::other(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sensitive_fields_are_redacted_at_any_depth() {
+        let redactor = Redactor::default();
+        let args = json!({
+            "url": "https://example.com",
+            "api_key": "sk-123",
+            "headers": {"Authorization": "Bearer abc", "Accept": "text/html"},
+            "accounts": [{"user": "ann", "Password": "hunter2"}],
+            "githubToken": "ghp_x",
+        });
+        let summary = redactor.summarize(&args);
+        assert_eq!(summary["url"], "https://example.com");
+        assert_eq!(summary["api_key"], REDACTED);
+        assert_eq!(summary["headers"]["Authorization"], REDACTED);
+        assert_eq!(summary["headers"]["Accept"], "text/html");
+        assert_eq!(summary["accounts"][0]["user"], "ann");
+        assert_eq!(summary["accounts"][0]["Password"], REDACTED);
+        assert_eq!(summary["githubToken"], REDACTED);
+    }
+
+    #[test]
+    fn sensitive_objects_are_redacted_whole() {
+        let summary = Redactor::default().summarize(&json!({"secrets": {"a": 1}}));
+        assert_eq!(summary["secrets"], REDACTED);
+    }
+
+    #[test]
+    fn extra_patterns_are_normalized() {
+        let redactor = Redactor::new(&["Session-ID".into()]);
+        assert!(redactor.is_sensitive("session_id"));
+        assert!(redactor.is_sensitive("SESSIONID"));
+        assert!(!redactor.is_sensitive("session"));
+    }
+
+    #[test]
+    fn long_strings_are_truncated() {
+        let summary = Redactor::default().summarize(&json!({"content": "x".repeat(500)}));
+        let content = summary["content"].as_str().unwrap();
+        assert_eq!(content.len(), MAX_ARG_CHARS + 3);
+        assert!(content.ends_with("..."));
+    }
+
+    #[test]
+    fn redact_masks_without_truncating() {
+        let args = json!({"token": "t", "content": "x".repeat(500)});
+        let redacted = Redactor::default().redact(&args);
+        assert_eq!(redacted["token"], REDACTED);
+        assert_eq!(redacted["content"], args["content"]);
+    }
+
+    #[test]
+    fn filter_matches_session_and_tool() {
+        let record = record("s1", "read_file");
+        assert!(AuditFilter::default().matches(&record));
+        let by_session = AuditFilter {
+            session_key: Some("s1".into()),
+            tool: None,
+        };
+        assert!(by_session.matches(&record));
+        let other_tool = AuditFilter {
+            session_key: Some("s1".into()),
+            tool: Some("exec".into()),
+        };
+        assert!(!other_tool.matches(&record));
+    }
+
+    fn record(session_key: &str, tool: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            agent_id: "default".into(),
+            session_key: session_key.into(),
+            tool: tool.into(),
+            args: json!({"path": "a.txt"}),
+            duration_ms: 3,
+            success: true,
+            error: None,
+            result_bytes: 10,
+        }
+    }
+
+    #[cfg(feature = "native")]
+    fn temp_log_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("clawft_audit_{name}_{}", std::process::id()))
+            .join(AUDIT_FILE)
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn jsonl_log_round_trips_and_filters() {
+        let path = temp_log_path("roundtrip");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        let log = JsonlAuditLog::new(&path, 1024 * 1024, 3);
+        log.append(&record("s1", "read_file")).await.unwrap();
+        log.append(&record("s2", "exec")).await.unwrap();
+        log.append(&record("s1", "exec")).await.unwrap();
+
+        let all = log.recent(&AuditFilter::default(), 10).await.unwrap();
+        assert_eq!(all.len(), 3);
+        let last = log.recent(&AuditFilter::default(), 1).await.unwrap();
+        assert_eq!(last[0].session_key, "s1");
+        assert_eq!(last[0].tool, "exec");
+        let filter = AuditFilter {
+            session_key: Some("s1".into()),
+            tool: None,
+        };
+        assert_eq!(log.recent(&filter, 10).await.unwrap().len(), 2);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[cfg(feature = "native")]
+    #[tokio::test]
+    async fn jsonl_log_rotates_past_size_limit() {
+        let path = temp_log_path("rotate");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        let line_len = serde_json::to_string(&record("s0", "t")).unwrap().len() as u64 + 1;
+        // Room for two records per file, keeping two rotated files.
+        let log = JsonlAuditLog::new(&path, line_len * 2, 2);
+        for i in 0..7 {
+            log.append(&record(&format!("s{i}"), "t")).await.unwrap();
+        }
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= line_len * 2);
+        assert!(log.rotated_path(1).exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+
+        // s0 and s1 were rotated out; the rest read back in order.
+        let keys: Vec<_> = log
+            .recent(&AuditFilter::default(), 100)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.session_key)
+            .collect();
+        assert_eq!(keys, ["s2", "s3", "s4", "s5", "s6"]);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[cfg(feature = "sqlite-sessions")]
+    #[tokio::test]
+    async fn sqlite_log_round_trips() {
+        let path = temp_log_path("sqlite").with_file_name("sessions.db");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let log = SqliteAuditLog::open(&path).unwrap();
+        log.append(&record("s1", "read_file")).await.unwrap();
+        log.append(&record("s2", "exec")).await.unwrap();
+
+        let all = log.recent(&AuditFilter::default(), 10).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].tool, "read_file");
+        assert_eq!(all[0].args, json!({"path": "a.txt"}));
+        let filter = AuditFilter {
+            session_key: None,
+            tool: Some("exec".into()),
+        };
+        assert_eq!(log.recent(&filter, 10).await.unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn auditor_records_redacted_outcome() {
+        struct Memory(std::sync::Mutex<Vec<AuditRecord>>);
+
+        #[async_trait]
+        impl AuditStore for Memory {
+            async fn append(&self, record: &AuditRecord) -> clawft_types::Result<()> {
+                self.0.lock().unwrap().push(record.clone());
+                Ok(())
+            }
+            async fn recent(
+                &self,
+                _filter: &AuditFilter,
+                _limit: usize,
+            ) -> clawft_types::Result<Vec<AuditRecord>> {
+                Ok(self.0.lock().unwrap().clone())
+            }
+        }
+
+        let auditor = ToolAuditor::new(Arc::new(Memory(Default::default())), Redactor::default());
+        auditor
+            .record(
+                "default",
+                "s1",
+                "web_fetch",
+                &json!({"url": "u", "token": "t"}),
+                12,
+                &Ok(json!({"body": "ok"})),
+            )
+            .await;
+        auditor
+            .record(
+                "default",
+                "s1",
+                "exec",
+                &json!({}),
+                1,
+                &Err(ToolError::ExecutionFailed("boom".into())),
+            )
+            .await;
+
+        let records = auditor
+            .store()
+            .recent(&AuditFilter::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(records[0].args["token"], REDACTED);
+        assert!(records[0].success);
+        assert_eq!(records[0].result_bytes, r#"{"body":"ok"}"#.len() as u64);
+        assert!(!records[1].success);
+        assert_eq!(records[1].error.as_deref(), Some("execution failed: boom"));
+    }
+}
@@ -1,3 +1,4 @@
-//! Tool system: registry, trait, execution.
+//! Tool system: registry, trait, execution, audit.
 
+pub mod audit;
 pub mod registry;
@@ -7,8 +7,8 @@
 //! Tool implementations live in the `clawft-tools` crate; this module
 //! only defines the contract and registry infrastructure.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -54,6 +54,18 @@ pub enum ToolError {
     /// The tool execution exceeded the allowed time limit.
     #[error("timeout after {0}s")]
     Timeout(u64),
+
+    /// What the tool depends on is briefly unavailable (e.g. its MCP
+    /// server is reconnecting); the same call may succeed if retried.
+    #[error("temporarily unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl ToolError {
+    /// Whether the call may succeed if made again unchanged.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Unavailable(_))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -94,7 +106,7 @@ pub struct ToolMetadata {
 ///   `glob_matches("*", "anything")` -> true
 ///   `glob_matches("read_?", "read_a")` -> true
 ///   `glob_matches("read_?", "read_file")` -> false
-fn glob_matches(pattern: &str, text: &str) -> bool {
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
     let pattern: Vec<char> = pattern.chars().collect();
     let text: Vec<char> = text.chars().collect();
     let (plen, tlen) = (pattern.len(), text.len());
@@ -131,7 +143,7 @@ fn glob_matches(pattern: &str, text: &str) -> bool {
 
 /// Check whether a tool name matches any pattern in the given list.
 /// Each entry in `patterns` is either an exact name or a glob pattern.
-fn matches_any_pattern(tool_name: &str, patterns: &[String]) -> bool {
+pub fn matches_any_pattern(tool_name: &str, patterns: &[String]) -> bool {
     patterns.iter().any(|pattern| {
         if pattern.contains('*') || pattern.contains('?') {
             glob_matches(pattern, tool_name)
@@ -330,15 +342,95 @@ pub trait Tool: Send + Sync {
     fn metadata(&self) -> Option<ToolMetadata> {
         None
     }
+
+    /// Whether this tool may run concurrently with other tool calls from
+    /// the same LLM response.
+    ///
+    /// Override to return `false` for tools with side effects that must
+    /// not interleave (shell commands, file writes). When any call in a
+    /// response targets such a tool, the agent loop executes the whole
+    /// batch sequentially. Default: `true`.
+    fn parallel_safe(&self) -> bool {
+        true
+    }
+}
+
+/// Tools that may change after a [`ToolRegistry`] is shared, in named
+/// groups: an MCP server's tools, listed again when the server reports
+/// that they changed.
+///
+/// Clones share the groups. A tool registered directly in the registry
+/// takes precedence over a dynamic tool of the same name.
+#[derive(Clone, Default)]
+pub struct DynamicTools {
+    groups: Arc<RwLock<ToolGroups>>,
+}
+
+type ToolGroups = BTreeMap<String, Vec<Arc<dyn Tool>>>;
+
+impl DynamicTools {
+    /// Replace the tools of `group`. An empty list removes the group.
+    pub fn replace(&self, group: &str, tools: Vec<Arc<dyn Tool>>) {
+        let mut groups = self.groups.write().unwrap_or_else(|e| e.into_inner());
+        if tools.is_empty() {
+            groups.remove(group);
+        } else {
+            groups.insert(group.to_string(), tools);
+        }
+    }
+
+    /// Names of the tools in every group other than `group`.
+    pub fn names_except(&self, group: &str) -> HashSet<String> {
+        let groups = self.groups.read().unwrap_or_else(|e| e.into_inner());
+        groups
+            .iter()
+            .filter(|(name, _)| name.as_str() != group)
+            .flat_map(|(_, tools)| tools.iter().map(|t| t.name().to_string()))
+            .collect()
+    }
+
+    fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        let groups = self.groups.read().unwrap_or_else(|e| e.into_inner());
+        groups
+            .values()
+            .flatten()
+            .find(|tool| tool.name() == name)
+            .cloned()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        let groups = self.groups.read().unwrap_or_else(|e| e.into_inner());
+        groups.values().flatten().any(|tool| tool.name() == name)
+    }
+
+    /// Number of distinct tool names not in `shadowed`.
+    fn count_excluding(&self, shadowed: &HashMap<String, Arc<dyn Tool>>) -> usize {
+        let groups = self.groups.read().unwrap_or_else(|e| e.into_inner());
+        groups
+            .values()
+            .flatten()
+            .map(|tool| tool.name())
+            .filter(|name| !shadowed.contains_key(*name))
+            .collect::<HashSet<&str>>()
+            .len()
+    }
+
+    fn all(&self) -> Vec<Arc<dyn Tool>> {
+        let groups = self.groups.read().unwrap_or_else(|e| e.into_inner());
+        groups.values().flatten().cloned().collect()
+    }
 }
 
 /// Registry of available tools, indexed by name.
 ///
 /// Provides lookup, listing, schema generation in OpenAI function calling
-/// format, and dispatch-by-name execution.
+/// format, and dispatch-by-name execution. Besides the tools registered
+/// up front it serves [`DynamicTools`], which may change while the
+/// registry is shared.
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
     metadata: HashMap<String, ToolMetadata>,
+    dynamic: DynamicTools,
 }
 
 impl ToolRegistry {
@@ -347,7 +439,30 @@ impl ToolRegistry {
         Self {
             tools: HashMap::new(),
             metadata: HashMap::new(),
+            dynamic: DynamicTools::default(),
+        }
+    }
+
+    /// The registry's dynamic tool groups.
+    pub fn dynamic(&self) -> &DynamicTools {
+        &self.dynamic
+    }
+
+    /// Every tool by name: registered tools, then dynamic tools they do
+    /// not shadow.
+    fn entries(&self) -> Vec<(String, Arc<dyn Tool>)> {
+        let mut entries: Vec<(String, Arc<dyn Tool>)> = self
+            .tools
+            .iter()
+            .map(|(name, tool)| (name.clone(), tool.clone()))
+            .collect();
+        let mut seen: HashSet<String> = self.tools.keys().cloned().collect();
+        for tool in self.dynamic.all() {
+            if seen.insert(tool.name().to_string()) {
+                entries.push((tool.name().to_string(), tool));
+            }
         }
+        entries
     }
 
     /// Register a tool in the registry.
@@ -383,12 +498,15 @@ impl ToolRegistry {
 
     /// Check if a tool with the given name is registered.
     pub fn has(&self, name: &str) -> bool {
-        self.tools.contains_key(name)
+        self.tools.contains_key(name) || self.dynamic.contains(name)
     }
 
     /// Look up a tool by name.
     pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
-        self.tools.get(name).cloned()
+        self.tools
+            .get(name)
+            .cloned()
+            .or_else(|| self.dynamic.get(name))
     }
 
     /// Look up metadata for a tool by name.
@@ -396,9 +514,17 @@ impl ToolRegistry {
         self.metadata.get(name)
     }
 
+    /// Whether the named tool may run concurrently with other calls.
+    ///
+    /// Unknown tools are reported as safe; executing them fails with
+    /// [`ToolError::NotFound`] regardless of scheduling.
+    pub fn is_parallel_safe(&self, name: &str) -> bool {
+        self.get(name).is_none_or(|tool| tool.parallel_safe())
+    }
+
     /// List all registered tool names (sorted alphabetically).
     pub fn list(&self) -> Vec<String> {
-        let mut names: Vec<String> = self.tools.keys().cloned().collect();
+        let mut names: Vec<String> = self.entries().into_iter().map(|(name, _)| name).collect();
         names.sort();
         names
     }
@@ -420,8 +546,8 @@ impl ToolRegistry {
     /// The returned vector is sorted by tool name for deterministic output.
     pub fn schemas(&self) -> Vec<serde_json::Value> {
         let mut schemas: Vec<(String, serde_json::Value)> = self
-            .tools
-            .iter()
+            .entries()
+            .into_iter()
             .map(|(name, tool)| {
                 let schema = serde_json::json!({
                     "type": "function",
@@ -431,7 +557,7 @@ impl ToolRegistry {
                         "parameters": tool.parameters(),
                     }
                 });
-                (name.clone(), schema)
+                (name, schema)
             })
             .collect();
 
@@ -448,8 +574,8 @@ impl ToolRegistry {
     /// they need, and only those schemas are sent to the LLM.
     pub fn schemas_for_tools(&self, allowed: &[String]) -> Vec<serde_json::Value> {
         let mut schemas: Vec<(String, serde_json::Value)> = self
-            .tools
-            .iter()
+            .entries()
+            .into_iter()
             .filter(|(name, _)| matches_any_pattern(name, allowed))
             .map(|(name, tool)| {
                 let schema = serde_json::json!({
@@ -460,7 +586,7 @@ impl ToolRegistry {
                         "parameters": tool.parameters(),
                     }
                 });
-                (name.clone(), schema)
+                (name, schema)
             })
             .collect();
 
@@ -482,9 +608,20 @@ impl ToolRegistry {
         args: serde_json::Value,
         permissions: Option<&UserPermissions>,
     ) -> Result<serde_json::Value, ToolError> {
+        let tool = self.authorize(name, permissions)?;
+        debug!(tool = %name, "executing tool");
+        tool.execute(args).await
+    }
+
+    /// Look up a tool and check that the caller may run it, as
+    /// [`execute`](Self::execute) does before running it.
+    pub fn authorize(
+        &self,
+        name: &str,
+        permissions: Option<&UserPermissions>,
+    ) -> Result<Arc<dyn Tool>, ToolError> {
         // Look up the tool first (NotFound fires before PermissionDenied).
         let tool = self
-            .tools
             .get(name)
             .ok_or_else(|| ToolError::NotFound(name.to_string()))?;
 
@@ -493,19 +630,17 @@ impl ToolRegistry {
             let meta = self.metadata.get(name);
             check_tool_permission(name, perms, meta)?;
         }
-
-        debug!(tool = %name, "executing tool");
-        tool.execute(args).await
+        Ok(tool)
     }
 
     /// Return the number of registered tools.
     pub fn len(&self) -> usize {
-        self.tools.len()
+        self.tools.len() + self.dynamic.count_excluding(&self.tools)
     }
 
     /// Return true if no tools are registered.
     pub fn is_empty(&self) -> bool {
-        self.tools.is_empty()
+        self.len() == 0
     }
 
     /// Create a filtered snapshot of this registry containing only tools
@@ -516,37 +651,55 @@ impl ToolRegistry {
     /// they appear in the allow list (deny overrides allow).
     ///
     /// This is used by the kernel supervisor to create per-agent
-    /// tool registries that respect capability restrictions.
+    /// tool registries that respect capability restrictions. Dynamic
+    /// tools are copied as they are now.
     pub fn filtered_tools(&self, allow: &[String], deny: &[String]) -> Self {
         let mut filtered = Self::new();
-        for (name, tool) in &self.tools {
+        for (name, tool) in self.entries() {
             // Check deny list first
-            if deny.iter().any(|d| d == name) {
+            if deny.contains(&name) {
                 continue;
             }
             // Check allow list (empty = all allowed)
-            if !allow.is_empty() && !allow.iter().any(|a| a == name) {
+            if !allow.is_empty() && !allow.contains(&name) {
                 continue;
             }
-            filtered.tools.insert(name.clone(), tool.clone());
-            if let Some(meta) = self.metadata.get(name) {
+            if let Some(meta) = self.metadata.get(&name) {
                 filtered.metadata.insert(name.clone(), meta.clone());
             }
+            filtered.tools.insert(name, tool);
         }
         filtered
     }
 
+    /// A view of this registry limited to tools matching `allowed`.
+    ///
+    /// Unlike [`filtered_tools`](Self::filtered_tools), the view still
+    /// knows about every registered tool: executing one outside the
+    /// allowlist fails with [`ToolError::PermissionDenied`] rather than
+    /// [`ToolError::NotFound`]. An empty `allowed` list allows nothing.
+    /// Entries may be glob patterns, as in
+    /// [`schemas_for_tools`](Self::schemas_for_tools).
+    pub fn scoped(&self, allowed: &[String]) -> ScopedTools<'_> {
+        ScopedTools {
+            registry: self,
+            allowed: allowed.to_vec(),
+        }
+    }
+
     /// Create a snapshot of this registry as a new `ToolRegistry`.
     ///
     /// The returned registry contains clones of all `Arc<dyn Tool>`
     /// handles currently registered. This is useful for passing a
     /// frozen copy of the tool set to components that need shared
     /// access (e.g., wrapped in `Arc<ToolRegistry>`) without requiring
-    /// the original registry to be `Arc`-wrapped itself.
+    /// the original registry to be `Arc`-wrapped itself. The snapshot
+    /// shares the [`DynamicTools`], so it sees their later changes.
     pub fn snapshot(&self) -> Self {
         Self {
             tools: self.tools.clone(),
             metadata: self.metadata.clone(),
+            dynamic: self.dynamic.clone(),
         }
     }
 }
@@ -557,6 +710,72 @@ impl Default for ToolRegistry {
     }
 }
 
+/// A [`ToolRegistry`] restricted to an allowlist, from
+/// [`ToolRegistry::scoped`].
+///
+/// Used per agent turn, so that only allowed tools are offered to the
+/// model and a call to any other tool is refused instead of executed.
+pub struct ScopedTools<'a> {
+    registry: &'a ToolRegistry,
+    allowed: Vec<String>,
+}
+
+impl ScopedTools<'_> {
+    /// The allowlist.
+    pub fn allowed(&self) -> &[String] {
+        &self.allowed
+    }
+
+    /// Whether `name` is within the allowlist.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        matches_any_pattern(name, &self.allowed)
+    }
+
+    /// Names of the registered tools within the allowlist (sorted).
+    pub fn list(&self) -> Vec<String> {
+        self.registry
+            .list()
+            .into_iter()
+            .filter(|name| self.is_allowed(name))
+            .collect()
+    }
+
+    /// Schemas of the allowed tools, as [`ToolRegistry::schemas`].
+    pub fn schemas(&self) -> Vec<serde_json::Value> {
+        self.registry.schemas_for_tools(&self.allowed)
+    }
+
+    /// Execute an allowed tool, as [`ToolRegistry::execute`].
+    ///
+    /// Returns [`ToolError::PermissionDenied`] for a registered tool
+    /// outside the allowlist.
+    pub async fn execute(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+        permissions: Option<&UserPermissions>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let tool = self.authorize(name, permissions)?;
+        debug!(tool = %name, "executing tool");
+        tool.execute(args).await
+    }
+
+    /// Look up an allowed tool, as [`ToolRegistry::authorize`].
+    pub fn authorize(
+        &self,
+        name: &str,
+        permissions: Option<&UserPermissions>,
+    ) -> Result<Arc<dyn Tool>, ToolError> {
+        if self.registry.has(name) && !self.is_allowed(name) {
+            return Err(ToolError::PermissionDenied {
+                tool: name.to_string(),
+                reason: "not in the tool allowlist for this agent or skill".into(),
+            });
+        }
+        self.registry.authorize(name, permissions)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -658,6 +877,32 @@ mod tests {
         }
     }
 
+    /// A tool that opts out of concurrent execution.
+    struct SerialTool;
+
+    #[async_trait]
+    impl Tool for SerialTool {
+        fn name(&self) -> &str {
+            "serial"
+        }
+
+        fn description(&self) -> &str {
+            "A tool that must run alone"
+        }
+
+        fn parameters(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object", "properties": {} })
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+            Ok(serde_json::json!({}))
+        }
+
+        fn parallel_safe(&self) -> bool {
+            false
+        }
+    }
+
     /// Helper: build admin permissions (level 2, wildcard tool_access).
     fn admin_permissions() -> UserPermissions {
         UserPermissions {
@@ -705,6 +950,17 @@ mod tests {
         assert_eq!(tool.description(), "Echo back the input text");
     }
 
+    #[test]
+    fn parallel_safety_follows_the_tool() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool));
+        registry.register(Arc::new(SerialTool));
+
+        assert!(registry.is_parallel_safe("echo"));
+        assert!(!registry.is_parallel_safe("serial"));
+        assert!(registry.is_parallel_safe("unknown"));
+    }
+
     #[test]
     fn get_nonexistent_returns_none() {
         let registry = ToolRegistry::new();
@@ -910,6 +1166,14 @@ mod tests {
 
         let err = ToolError::Timeout(30);
         assert_eq!(err.to_string(), "timeout after 30s");
+
+        let err = ToolError::Unavailable("MCP server 'docs' is reconnecting".into());
+        assert_eq!(
+            err.to_string(),
+            "temporarily unavailable: MCP server 'docs' is reconnecting"
+        );
+        assert!(err.is_retryable());
+        assert!(!ToolError::Timeout(30).is_retryable());
     }
 
     #[test]
@@ -1380,6 +1644,45 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn scoped_registry_denies_tools_outside_allowlist() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool));
+        registry.register(Arc::new(FailTool));
+
+        let scoped = registry.scoped(&["ech*".into()]);
+        assert_eq!(scoped.list(), vec!["echo"]);
+        assert_eq!(scoped.schemas().len(), 1);
+
+        let ok = scoped
+            .execute("echo", serde_json::json!({ "text": "hi" }), None)
+            .await;
+        assert!(ok.is_ok());
+        let denied = scoped.execute("fail", serde_json::json!({}), None).await;
+        assert!(matches!(
+            denied.unwrap_err(),
+            ToolError::PermissionDenied { tool, .. } if tool == "fail"
+        ));
+        let unknown = scoped.execute("nope", serde_json::json!({}), None).await;
+        assert!(matches!(unknown.unwrap_err(), ToolError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn scoped_registry_with_empty_allowlist_allows_nothing() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool));
+
+        let scoped = registry.scoped(&[]);
+        assert!(scoped.schemas().is_empty());
+        let denied = scoped
+            .execute("echo", serde_json::json!({ "text": "hi" }), None)
+            .await;
+        assert!(matches!(
+            denied.unwrap_err(),
+            ToolError::PermissionDenied { .. }
+        ));
+    }
+
     #[tokio::test]
     async fn test_registry_execute_with_low_level_and_metadata() {
         let mut registry = ToolRegistry::new();
@@ -1406,4 +1709,38 @@ mod tests {
             ToolError::PermissionDenied { .. }
         ));
     }
+
+    #[tokio::test]
+    async fn dynamic_tools_join_lookups_and_can_be_replaced() {
+        let mut reg = ToolRegistry::new();
+        reg.register(Arc::new(EchoTool));
+        let shared = reg.snapshot();
+
+        // A dynamic tool named like a registered one is shadowed.
+        reg.dynamic()
+            .replace("srv", vec![Arc::new(AddTool), Arc::new(EchoTool)]);
+        assert_eq!(reg.list(), vec!["add", "echo"]);
+        assert_eq!(reg.len(), 2);
+        // The same name in two groups counts once.
+        reg.dynamic().replace("other", vec![Arc::new(AddTool)]);
+        assert_eq!(reg.len(), 2);
+        assert!(reg.has("add") && !reg.has("fail"));
+        reg.dynamic().replace("other", Vec::new());
+        assert_eq!(reg.schemas().len(), 2);
+        assert_eq!(reg.schemas_for_tools(&["a*".into()]).len(), 1);
+        let result = reg
+            .execute("add", serde_json::json!({"a": 2, "b": 3}), None)
+            .await
+            .unwrap();
+        assert_eq!(result["result"], 5.0);
+        assert_eq!(reg.dynamic().names_except("other").len(), 2);
+        assert!(reg.dynamic().names_except("srv").is_empty());
+
+        // Snapshots share the groups; replacing with nothing removes them.
+        assert!(shared.has("add"));
+        reg.dynamic().replace("srv", vec![Arc::new(FailTool)]);
+        assert_eq!(shared.list(), vec!["echo", "fail"]);
+        reg.dynamic().replace("srv", Vec::new());
+        assert_eq!(shared.list(), vec!["echo"]);
+    }
 }
@@ -4,12 +4,15 @@
 //! for creating, listing, loading, and deleting workspaces.
 //!
 //! Per-agent workspace isolation is in the [`agent`] submodule.
-//! Config merge logic is in the [`config`] submodule.
+//! Config merge logic is in the [`config`] submodule. The files a new
+//! workspace starts with come from a [`template`].
 
 pub mod agent;
 mod config;
+pub mod template;
 
 pub use config::{load_merged_config, load_merged_config_from};
+pub use template::{CreatedWorkspace, WorkspaceTemplate};
 
 use std::path::{Path, PathBuf};
 
@@ -131,7 +134,7 @@ impl WorkspaceManager {
         })
     }
 
-    /// Create a new workspace.
+    /// Create a new workspace from the built-in template.
     ///
     /// Creates:
     /// 1. `.clawft/` and subdirectories (`sessions`, `memory`, `skills`,
@@ -140,54 +143,12 @@ impl WorkspaceManager {
     /// 3. `CLAWFT.md` with a starter template
     /// 4. Registers the workspace in the global registry
     ///
-    /// Returns the absolute path to the workspace root.
+    /// Fails if the target directory already has files in it. Returns the
+    /// absolute path to the workspace root.
     pub fn create(&mut self, name: &str, parent_dir: &Path) -> Result<PathBuf> {
-        let ws_root = parent_dir.join(name);
-        let dot_clawft = ws_root.join(".clawft");
-
-        // Create .clawft/ and subdirectories
-        for subdir in WORKSPACE_SUBDIRS {
-            std::fs::create_dir_all(dot_clawft.join(subdir))?;
-        }
-
-        // Create config.json
-        std::fs::write(dot_clawft.join("config.json"), "{}\n")?;
-
-        // Create MEMORY.md and HISTORY.md (empty)
-        std::fs::write(dot_clawft.join("MEMORY.md"), "")?;
-        std::fs::write(dot_clawft.join("HISTORY.md"), "")?;
-
-        // Create CLAWFT.md
-        let clawft_md = format!(
-            "# {name}\n\n\
-             Workspace created by clawft.\n\n\
-             ## Configuration\n\n\
-             Edit `.clawft/config.json` to customize this workspace.\n"
-        );
-        std::fs::write(ws_root.join("CLAWFT.md"), clawft_md)?;
-
-        // Register in global registry
-        let now = chrono::Utc::now();
-        let entry = WorkspaceEntry {
-            name: name.into(),
-            path: ws_root.clone(),
-            last_accessed: Some(now),
-            created_at: Some(now),
-        };
-        self.registry.register(entry);
-        self.save_registry()?;
-
-        // Chain event marker for workspace creation.
-        crate::chain_event!(
-            "workspace",
-            crate::chain_event::EVENT_KIND_WORKSPACE_CREATE,
-            {
-                "name": name,
-                "path": ws_root.display()
-            }
-        );
-
-        Ok(ws_root)
+        let template = WorkspaceTemplate::builtin();
+        self.create_from_template(name, parent_dir, &template, &Default::default())
+            .map(|created| created.path)
     }
 
     /// List all registered workspaces.
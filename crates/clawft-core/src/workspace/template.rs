@@ -0,0 +1,821 @@
+//! Workspace templates.
+//!
+//! A template describes the files a new workspace starts with. The
+//! built-in `default` template lays out `.clawft/` (`sessions`, `memory`,
+//! `skills`, `agents`, `hooks`, `config.json`, `MEMORY.md`, `HISTORY.md`)
+//! and a starter `CLAWFT.md`. User templates are directories -- or `.zip` /
+//! `.tar.gz` archives of one -- holding a `template.json` manifest:
+//!
+//! ```json
+//! {
+//!   "name": "research",
+//!   "description": "Literature research workspace",
+//!   "variables": [{ "name": "topic", "default": "general" }],
+//!   "files": [
+//!     { "path": "CLAWFT.md", "source": "CLAWFT.md" },
+//!     { "path": "notes/{{topic}}.md", "content": "# {{topic}}\n" },
+//!     { "path": ".clawft/skills/" }
+//!   ],
+//!   "notes": "Open {{workspace_name}} and run `weft agent`."
+//! }
+//! ```
+//!
+//! A file's content comes from `source` (relative to the template
+//! directory) or inline `content`; a path ending in `/` is a directory.
+//! Paths, contents and notes may use `{{variable}}` placeholders:
+//! `workspace_name`, `date`, and the variables the manifest declares. A
+//! declared variable without a default must be given when the template is
+//! applied. `\{{` is a literal `{{`, as in the
+//! [prompt templates](crate::agent::templates).
+//!
+//! Templates are validated when they are loaded: unknown placeholders,
+//! paths that leave the workspace, missing sources and duplicate paths are
+//! all errors. Named user templates live in
+//! `~/.clawft/workspace-templates/<name>` (a directory, `<name>.zip` or
+//! `<name>.tar.gz`).
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde::Deserialize;
+
+use clawft_types::workspace::WorkspaceEntry;
+use clawft_types::{ClawftError, Result};
+
+use super::WorkspaceManager;
+use crate::agent::templates::PromptTemplate;
+
+/// Manifest file name inside a template directory.
+pub const MANIFEST_FILE: &str = "template.json";
+
+/// Name of the built-in template.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Variables every template may use without declaring them.
+pub const BUILTIN_VARIABLES: &[&str] = &["workspace_name", "date"];
+
+/// Starter `CLAWFT.md` of the built-in template.
+const DEFAULT_CLAWFT_MD: &str = "# {{workspace_name}}\n\n\
+Workspace created by clawft.\n\n\
+## Configuration\n\n\
+Edit `.clawft/config.json` to customize this workspace.\n";
+
+/// A `template.json` manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateManifest {
+    /// Template name.
+    pub name: String,
+
+    /// One-line description.
+    #[serde(default)]
+    pub description: String,
+
+    /// Variables the template uses besides [`BUILTIN_VARIABLES`].
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+
+    /// Files and directories to create.
+    pub files: Vec<TemplateFile>,
+
+    /// Text shown after the workspace is created.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// A variable declared by a template.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateVariable {
+    /// Variable name, used as `{{name}}`.
+    pub name: String,
+
+    /// Value used when none is given; `None` makes the variable required.
+    #[serde(default)]
+    pub default: Option<String>,
+
+    /// What the variable is for.
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A file or directory listed in a manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateFile {
+    /// Path relative to the workspace root; a trailing `/` makes it a
+    /// directory.
+    pub path: String,
+
+    /// File holding the content, relative to the template directory.
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Inline content.
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+/// A parsed template entry.
+#[derive(Debug, Clone)]
+enum Entry {
+    Dir(PromptTemplate),
+    File {
+        path: PromptTemplate,
+        content: PromptTemplate,
+    },
+}
+
+/// A validated workspace template, ready to apply.
+#[derive(Debug, Clone)]
+pub struct WorkspaceTemplate {
+    name: String,
+    description: String,
+    variables: Vec<TemplateVariable>,
+    entries: Vec<Entry>,
+    notes: Option<PromptTemplate>,
+}
+
+/// The result of [`WorkspaceManager::create_from_template`].
+#[derive(Debug, Clone)]
+pub struct CreatedWorkspace {
+    /// Absolute path to the workspace root.
+    pub path: PathBuf,
+
+    /// The template's rendered post-create notes.
+    pub notes: Option<String>,
+}
+
+impl WorkspaceTemplate {
+    /// The built-in `default` template.
+    pub fn builtin() -> Self {
+        let dir = |path: &str| TemplateFile {
+            path: path.into(),
+            source: None,
+            content: None,
+        };
+        let file = |path: &str, content: &str| TemplateFile {
+            path: path.into(),
+            source: None,
+            content: Some(content.into()),
+        };
+        let mut files: Vec<TemplateFile> = super::WORKSPACE_SUBDIRS
+            .iter()
+            .map(|subdir| dir(&format!(".clawft/{subdir}/")))
+            .collect();
+        files.extend([
+            file(".clawft/config.json", "{}\n"),
+            file(".clawft/MEMORY.md", ""),
+            file(".clawft/HISTORY.md", ""),
+            file("CLAWFT.md", DEFAULT_CLAWFT_MD),
+        ]);
+        let manifest = TemplateManifest {
+            name: DEFAULT_TEMPLATE.into(),
+            description: "Standard clawft workspace".into(),
+            variables: Vec::new(),
+            files,
+            notes: None,
+        };
+        Self::from_manifest(manifest, None).expect("built-in template is valid")
+    }
+
+    /// Load the template in `path`: a directory holding a
+    /// [`MANIFEST_FILE`], or (with the `native` feature) a `.zip` /
+    /// `.tar.gz` archive of one.
+    pub fn load(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Self::load_dir(path);
+        }
+        #[cfg(feature = "native")]
+        if clawft_platform::archive::ArchiveFormat::from_path(path).is_some() {
+            return Self::load_archive(path);
+        }
+        Err(invalid(format!(
+            "{} is not a template directory or archive",
+            path.display()
+        )))
+    }
+
+    /// Load the template in directory `dir`.
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let text = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| invalid(format!("cannot read {}: {e}", manifest_path.display())))?;
+        let manifest: TemplateManifest = serde_json::from_str(&text)
+            .map_err(|e| invalid(format!("{}: {e}", manifest_path.display())))?;
+        Self::from_manifest(manifest, Some(dir))
+    }
+
+    /// Load the template in a `.zip` / `.tar.gz` archive. The manifest may
+    /// sit at the archive root or in a single top-level directory.
+    #[cfg(feature = "native")]
+    pub fn load_archive(archive: &Path) -> Result<Self> {
+        use clawft_platform::archive::{self, ExtractLimits};
+
+        let limits = ExtractLimits {
+            max_entries: 1_000,
+            max_file_size: 10 * 1024 * 1024,
+            max_total_size: 50 * 1024 * 1024,
+        };
+        let staging = std::env::temp_dir().join(format!(
+            "clawft-workspace-template-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let result = archive::extract(archive, &staging, &limits)
+            .map_err(|e| invalid(format!("failed to unpack {}: {e}", archive.display())))
+            .and_then(|_| {
+                let root = if staging.join(MANIFEST_FILE).is_file() {
+                    staging.clone()
+                } else {
+                    let dirs: Vec<PathBuf> = std::fs::read_dir(&staging)?
+                        .flatten()
+                        .map(|e| e.path())
+                        .filter(|p| p.join(MANIFEST_FILE).is_file())
+                        .collect();
+                    match dirs.as_slice() {
+                        [root] => root.clone(),
+                        _ => {
+                            return Err(invalid(format!(
+                                "{} does not contain a {MANIFEST_FILE}",
+                                archive.display()
+                            )));
+                        }
+                    }
+                };
+                Self::load_dir(&root)
+            });
+        let _ = std::fs::remove_dir_all(&staging);
+        result
+    }
+
+    /// Validate `manifest` and read its sources from `dir`.
+    pub fn from_manifest(manifest: TemplateManifest, dir: Option<&Path>) -> Result<Self> {
+        let name = manifest.name.trim().to_string();
+        if name.is_empty() {
+            return Err(invalid("template name is empty".into()));
+        }
+        let fail = |problem: String| invalid(format!("template '{name}': {problem}"));
+
+        let mut known: HashSet<&str> = BUILTIN_VARIABLES.iter().copied().collect();
+        for var in &manifest.variables {
+            let valid = !var.name.is_empty()
+                && var
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(fail(format!("invalid variable name '{}'", var.name)));
+            }
+            if !known.insert(var.name.as_str()) {
+                return Err(fail(format!(
+                    "variable '{}' is declared twice or shadows a built-in",
+                    var.name
+                )));
+            }
+        }
+        let parse = |what: &str, source: &str| -> Result<PromptTemplate> {
+            let template =
+                PromptTemplate::parse(source).map_err(|e| fail(format!("{what}: {e}")))?;
+            if let Some(var) = template.variables().find(|v| !known.contains(v)) {
+                return Err(fail(format!("{what}: unknown variable `{{{{{var}}}}}`")));
+            }
+            Ok(template)
+        };
+
+        if manifest.files.is_empty() {
+            return Err(fail("lists no files".into()));
+        }
+        let mut seen = HashSet::new();
+        let mut entries = Vec::with_capacity(manifest.files.len());
+        for file in &manifest.files {
+            let raw = file.path.as_str();
+            let Some(rel) = relative_path(raw.trim_end_matches('/')) else {
+                return Err(fail(format!(
+                    "path '{raw}' must be relative and stay inside the workspace"
+                )));
+            };
+            if !seen.insert(rel) {
+                return Err(fail(format!("path '{raw}' is listed twice")));
+            }
+            let path = parse(raw, raw.trim_end_matches('/'))?;
+            if raw.ends_with('/') {
+                if file.source.is_some() || file.content.is_some() {
+                    return Err(fail(format!("directory '{raw}' cannot have content")));
+                }
+                entries.push(Entry::Dir(path));
+                continue;
+            }
+            let content = match (&file.source, &file.content) {
+                (Some(source), None) => {
+                    let dir = dir.ok_or_else(|| {
+                        fail(format!("'{raw}': sources need a template directory"))
+                    })?;
+                    let rel = relative_path(source).ok_or_else(|| {
+                        fail(format!("'{raw}': source '{source}' leaves the template"))
+                    })?;
+                    std::fs::read_to_string(dir.join(rel))
+                        .map_err(|e| fail(format!("'{raw}': cannot read source '{source}': {e}")))?
+                }
+                (None, Some(content)) => content.clone(),
+                _ => {
+                    return Err(fail(format!(
+                        "'{raw}' needs exactly one of `source` and `content`"
+                    )));
+                }
+            };
+            let content = parse(raw, &content)?;
+            entries.push(Entry::File { path, content });
+        }
+        let notes = manifest
+            .notes
+            .as_deref()
+            .map(|notes| parse("notes", notes))
+            .transpose()?;
+
+        Ok(Self {
+            name,
+            description: manifest.description,
+            variables: manifest.variables,
+            entries,
+            notes,
+        })
+    }
+
+    /// The template's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The template's description.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The variables the template declares.
+    pub fn variables(&self) -> &[TemplateVariable] {
+        &self.variables
+    }
+
+    /// Write the template into `dest` for a workspace called
+    /// `workspace_name`, returning the rendered notes.
+    ///
+    /// # Errors
+    ///
+    /// Fails without writing anything when `dest` exists and is not empty,
+    /// a variable in `vars` is not declared, a required variable is
+    /// missing, or a rendered path leaves `dest`.
+    pub fn apply(
+        &self,
+        dest: &Path,
+        workspace_name: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<Option<String>> {
+        let values = self.values(workspace_name, vars)?;
+        let values: HashMap<&str, &str> = values
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        // Render everything before touching the disk.
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for entry in &self.entries {
+            let (path, content) = match entry {
+                Entry::Dir(path) => (path, None),
+                Entry::File { path, content } => (path, Some(content)),
+            };
+            let rendered = path.render(&values);
+            let rel = relative_path(&rendered).ok_or_else(|| {
+                invalid(format!(
+                    "template '{}': path '{rendered}' leaves the workspace",
+                    self.name
+                ))
+            })?;
+            match content {
+                None => dirs.push(dest.join(rel)),
+                Some(content) => files.push((dest.join(rel), content.render(&values))),
+            }
+        }
+
+        if let Ok(mut existing) = std::fs::read_dir(dest)
+            && existing.next().is_some()
+        {
+            return Err(invalid(format!(
+                "{} already exists and is not empty",
+                dest.display()
+            )));
+        }
+
+        std::fs::create_dir_all(dest)?;
+        for dir in dirs {
+            std::fs::create_dir_all(dir)?;
+        }
+        for (path, content) in files {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(self.notes.as_ref().map(|notes| notes.render(&values)))
+    }
+
+    /// Every variable's value: built-ins, then `vars`, then defaults.
+    fn values(
+        &self,
+        workspace_name: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        if let Some(unknown) = vars
+            .keys()
+            .find(|k| !self.variables.iter().any(|v| &v.name == *k))
+        {
+            return Err(invalid(format!(
+                "template '{}' has no variable '{unknown}'",
+                self.name
+            )));
+        }
+        let mut values = HashMap::from([
+            ("workspace_name".to_string(), workspace_name.to_string()),
+            (
+                "date".to_string(),
+                chrono::Local::now().format("%Y-%m-%d").to_string(),
+            ),
+        ]);
+        for var in &self.variables {
+            let value = vars
+                .get(&var.name)
+                .or(var.default.as_ref())
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "template '{}' needs a value for '{}'",
+                        self.name, var.name
+                    ))
+                })?;
+            values.insert(var.name.clone(), value.clone());
+        }
+        Ok(values)
+    }
+}
+
+impl WorkspaceManager {
+    /// Directory holding named user templates
+    /// (`~/.clawft/workspace-templates/`).
+    pub fn templates_root(&self) -> PathBuf {
+        self.registry_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("workspace-templates")
+    }
+
+    /// Find a template by name or path.
+    ///
+    /// `default` is the built-in template. Anything else is tried as a
+    /// path to a template directory or archive, then as a name in
+    /// [`templates_root`](Self::templates_root).
+    pub fn resolve_template(&self, name_or_path: &str) -> Result<WorkspaceTemplate> {
+        if name_or_path == DEFAULT_TEMPLATE {
+            return Ok(WorkspaceTemplate::builtin());
+        }
+        let path = Path::new(name_or_path);
+        if path.exists() {
+            return WorkspaceTemplate::load(path);
+        }
+        let root = self.templates_root();
+        for candidate in [
+            root.join(name_or_path),
+            root.join(format!("{name_or_path}.zip")),
+            root.join(format!("{name_or_path}.tar.gz")),
+            root.join(format!("{name_or_path}.tgz")),
+        ] {
+            if candidate.exists() {
+                return WorkspaceTemplate::load(&candidate);
+            }
+        }
+        Err(invalid(format!(
+            "workspace template not found: {name_or_path} (looked in {})",
+            root.display()
+        )))
+    }
+
+    /// Create workspace `name` under `parent_dir` from `template`, and
+    /// register it.
+    ///
+    /// Refuses a target directory that already has files in it.
+    pub fn create_from_template(
+        &mut self,
+        name: &str,
+        parent_dir: &Path,
+        template: &WorkspaceTemplate,
+        vars: &HashMap<String, String>,
+    ) -> Result<CreatedWorkspace> {
+        let ws_root = parent_dir.join(name);
+        let notes = template.apply(&ws_root, name, vars)?;
+
+        // Register in global registry
+        let now = chrono::Utc::now();
+        let entry = WorkspaceEntry {
+            name: name.into(),
+            path: ws_root.clone(),
+            last_accessed: Some(now),
+            created_at: Some(now),
+        };
+        self.registry.register(entry);
+        self.save_registry()?;
+
+        // Chain event marker for workspace creation.
+        crate::chain_event!(
+            "workspace",
+            crate::chain_event::EVENT_KIND_WORKSPACE_CREATE,
+            {
+                "name": name,
+                "path": ws_root.display(),
+                "template": template.name()
+            }
+        );
+
+        Ok(CreatedWorkspace {
+            path: ws_root,
+            notes,
+        })
+    }
+}
+
+/// `path` as a relative path that cannot leave its base directory.
+fn relative_path(path: &str) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return None;
+            }
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+fn invalid(reason: String) -> ClawftError {
+    ClawftError::ConfigInvalid { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::tests::temp_registry;
+
+    fn write_template(dir: &Path, manifest: serde_json::Value) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn research_manifest() -> serde_json::Value {
+        serde_json::json!({
+            "name": "research",
+            "variables": [
+                { "name": "topic" },
+                { "name": "owner", "default": "nobody" }
+            ],
+            "files": [
+                { "path": "CLAWFT.md", "source": "files/CLAWFT.md" },
+                { "path": "notes/{{topic}}.md", "content": "# {{topic}} ({{owner}})\n" },
+                { "path": ".clawft/skills/" }
+            ],
+            "notes": "Start researching {{topic}} in {{workspace_name}}."
+        })
+    }
+
+    #[test]
+    fn builtin_template_lays_out_the_standard_workspace() {
+        let (dir, _) = temp_registry("tpl-builtin");
+        let dest = dir.join("ws");
+        let notes = WorkspaceTemplate::builtin()
+            .apply(&dest, "ws", &HashMap::new())
+            .unwrap();
+
+        assert!(notes.is_none());
+        for subdir in super::super::WORKSPACE_SUBDIRS {
+            assert!(dest.join(".clawft").join(subdir).is_dir(), "{subdir}");
+        }
+        assert_eq!(
+            std::fs::read_to_string(dest.join(".clawft/config.json")).unwrap(),
+            "{}\n"
+        );
+        assert!(
+            std::fs::read_to_string(dest.join("CLAWFT.md"))
+                .unwrap()
+                .starts_with("# ws\n")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn variables_are_substituted_in_paths_contents_and_notes() {
+        let (dir, registry_path) = temp_registry("tpl-vars");
+        let template_dir = dir.join("research");
+        write_template(&template_dir, research_manifest());
+        std::fs::create_dir_all(template_dir.join("files")).unwrap();
+        std::fs::write(
+            template_dir.join("files/CLAWFT.md"),
+            "# {{workspace_name}}\n\nCreated {{date}}. Literal \\{{topic}}.\n",
+        )
+        .unwrap();
+
+        let mut wm = WorkspaceManager::with_registry_path(registry_path).unwrap();
+        let template = wm.resolve_template(template_dir.to_str().unwrap()).unwrap();
+        let vars = HashMap::from([("topic".to_string(), "{{owner}}-rust".to_string())]);
+        let created = wm
+            .create_from_template("papers", &dir, &template, &vars)
+            .unwrap();
+
+        let ws = dir.join("papers");
+        assert_eq!(created.path, ws);
+        let clawft_md = std::fs::read_to_string(ws.join("CLAWFT.md")).unwrap();
+        assert!(
+            clawft_md.starts_with("# papers\n\nCreated 2"),
+            "{clawft_md}"
+        );
+        assert!(clawft_md.ends_with("Literal {{topic}}.\n"));
+        // Values are inserted verbatim, never expanded again.
+        assert_eq!(
+            std::fs::read_to_string(ws.join("notes/{{owner}}-rust.md")).unwrap(),
+            "# {{owner}}-rust (nobody)\n"
+        );
+        assert!(ws.join(".clawft/skills").is_dir());
+        assert_eq!(
+            created.notes.as_deref(),
+            Some("Start researching {{owner}}-rust in papers.")
+        );
+        assert!(wm.load("papers").is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn non_empty_target_is_refused() {
+        let (dir, registry_path) = temp_registry("tpl-conflict");
+        let ws = dir.join("taken");
+        std::fs::create_dir_all(&ws).unwrap();
+        std::fs::write(ws.join("README.md"), "mine").unwrap();
+
+        let mut wm = WorkspaceManager::with_registry_path(registry_path).unwrap();
+        let err = wm.create("taken", &dir).unwrap_err();
+        assert!(err.to_string().contains("is not empty"), "{err}");
+        assert_eq!(
+            std::fs::read_to_string(ws.join("README.md")).unwrap(),
+            "mine"
+        );
+        assert!(!ws.join(".clawft").exists());
+        assert!(wm.list().is_empty());
+
+        // An existing empty directory is fine.
+        std::fs::create_dir_all(dir.join("empty")).unwrap();
+        assert!(wm.create("empty", &dir).is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn variable_values_are_checked_on_apply() {
+        let (dir, _) = temp_registry("tpl-values");
+        write_template(&dir.join("t"), research_manifest());
+        std::fs::create_dir_all(dir.join("t/files")).unwrap();
+        std::fs::write(dir.join("t/files/CLAWFT.md"), "x").unwrap();
+        let template = WorkspaceTemplate::load(&dir.join("t")).unwrap();
+
+        let err = template
+            .apply(&dir.join("a"), "a", &HashMap::new())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("needs a value for 'topic'"),
+            "{err}"
+        );
+
+        let vars = HashMap::from([
+            ("topic".to_string(), "x".to_string()),
+            ("colour".to_string(), "red".to_string()),
+        ]);
+        let err = template.apply(&dir.join("a"), "a", &vars).unwrap_err();
+        assert!(err.to_string().contains("no variable 'colour'"), "{err}");
+
+        let vars = HashMap::from([("topic".to_string(), "../../escape".to_string())]);
+        let err = template.apply(&dir.join("a"), "a", &vars).unwrap_err();
+        assert!(err.to_string().contains("leaves the workspace"), "{err}");
+        assert!(!dir.join("a").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn manifest_validation() {
+        let load = |manifest: serde_json::Value| {
+            let (dir, _) = temp_registry("tpl-manifest");
+            write_template(&dir, manifest);
+            let result = WorkspaceTemplate::load_dir(&dir);
+            let _ = std::fs::remove_dir_all(&dir);
+            result.map(|_| ()).unwrap_err().to_string()
+        };
+        let file =
+            |path: &str, content: &str| serde_json::json!({ "path": path, "content": content });
+
+        let cases = [
+            (
+                serde_json::json!({ "name": "t", "files": [file("a.md", "{{missing}}")] }),
+                "unknown variable `{{missing}}`",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [file("../a.md", "")] }),
+                "must be relative",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [file("/etc/a", "")] }),
+                "must be relative",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [file("a.md", ""), file("./a.md", "")] }),
+                "listed twice",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [{ "path": "a.md", "source": "nope.md" }] }),
+                "cannot read source 'nope.md'",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [{ "path": "a.md" }] }),
+                "exactly one of `source` and `content`",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [] }),
+                "lists no files",
+            ),
+            (
+                serde_json::json!({
+                    "name": "t",
+                    "variables": [{ "name": "date" }],
+                    "files": [file("a.md", "")]
+                }),
+                "shadows a built-in",
+            ),
+            (
+                serde_json::json!({ "name": "t", "files": [file("a.md", "")], "extra": 1 }),
+                "unknown field `extra`",
+            ),
+        ];
+        for (manifest, expected) in cases {
+            let err = load(manifest);
+            assert!(err.contains(expected), "expected {expected:?} in {err:?}");
+        }
+    }
+
+    #[test]
+    fn named_templates_resolve_from_the_templates_root() {
+        let (dir, registry_path) = temp_registry("tpl-resolve");
+        let wm = WorkspaceManager::with_registry_path(registry_path).unwrap();
+        write_template(
+            &wm.templates_root().join("notes"),
+            serde_json::json!({
+                "name": "notes",
+                "files": [{ "path": "NOTES.md", "content": "" }]
+            }),
+        );
+
+        assert_eq!(wm.resolve_template("default").unwrap().name(), "default");
+        assert_eq!(wm.resolve_template("notes").unwrap().name(), "notes");
+        let err = wm.resolve_template("missing").unwrap_err();
+        assert!(err.to_string().contains("not found: missing"), "{err}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn templates_load_from_archives() {
+        let (dir, _) = temp_registry("tpl-archive");
+        let template_dir = dir.join("notes");
+        write_template(
+            &template_dir,
+            serde_json::json!({
+                "name": "notes",
+                "files": [{ "path": "NOTES.md", "source": "NOTES.md" }]
+            }),
+        );
+        std::fs::write(template_dir.join("NOTES.md"), "# {{workspace_name}}\n").unwrap();
+        let archive = dir.join("notes.tar.gz");
+        clawft_platform::archive::create(&archive, &[template_dir]).unwrap();
+
+        let template = WorkspaceTemplate::load(&archive).unwrap();
+        template
+            .apply(&dir.join("ws"), "ws", &HashMap::new())
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("ws/NOTES.md")).unwrap(),
+            "# ws\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
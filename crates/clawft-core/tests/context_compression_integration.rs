@@ -21,6 +21,8 @@ fn make_msg(role: &str, content: &str) -> LlmMessage {
         content: content.into(),
         tool_call_id: None,
         tool_calls: None,
+        parts: None,
+        cache: false,
     }
 }
 
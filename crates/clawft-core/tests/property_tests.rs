@@ -34,6 +34,8 @@ fn make_msg(role: &str, content: &str) -> LlmMessage {
         content: content.into(),
         tool_call_id: None,
         tool_calls: None,
+        parts: None,
+        cache: false,
     }
 }
 
@@ -44,6 +46,8 @@ fn make_request_with_content(content: &str) -> ChatRequest {
             content: content.into(),
             tool_call_id: None,
             tool_calls: None,
+            parts: None,
+            cache: false,
         }],
         tools: vec![],
         model: None,
@@ -51,6 +55,7 @@ fn make_request_with_content(content: &str) -> ChatRequest {
         temperature: None,
         auth_context: None,
         complexity_boost: 0.0,
+        cache: false,
     }
 }
 
@@ -63,6 +68,10 @@ fn make_response_with_text(text: &str, output_tokens: u32) -> LlmResponse {
             input_tokens: 10,
             output_tokens,
             total_tokens: 0,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         },
         metadata: HashMap::new(),
     }
@@ -258,6 +267,10 @@ fn fitness_scorer_scores_in_unit_range() {
                 input_tokens: rng.gen_range(0..=1000),
                 output_tokens,
                 total_tokens: 0,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         };
@@ -295,6 +308,10 @@ fn fitness_scorer_empty_response_low_score() {
             input_tokens: 10,
             output_tokens: 0,
             total_tokens: 0,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         },
         metadata: HashMap::new(),
     };
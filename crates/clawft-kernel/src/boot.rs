@@ -159,7 +159,10 @@ impl<P: Platform> Kernel<P> {
         config: Config,
         kernel_config: KernelConfig,
         platform: Arc<P>,
-    ) -> KernelResult<Self> {
+    ) -> KernelResult<Self>
+    where
+        P: 'static,
+    {
         let boot_time = Instant::now();
         let mut boot_log = BootLog::new();
 
@@ -2026,9 +2029,22 @@ mod tests {
                     model: "test/model".into(),
                     max_tokens: 1024,
                     temperature: 0.5,
+                    top_p: None,
                     max_tool_iterations: 5,
                     memory_window: 10,
+                    max_parallel_tools: 4,
                 },
+                dispatch: Default::default(),
+                usage: Default::default(),
+                budget: Default::default(),
+                memory: Default::default(),
+                cache: Default::default(),
+                sessions: Default::default(),
+                compaction: Default::default(),
+                planning: Default::default(),
+                templates: Default::default(),
+                skills: Default::default(),
+                interactive: Default::default(),
             },
             ..Config::default()
         }
@@ -441,6 +441,7 @@ impl KernelIpc {
             content: json,
             timestamp: msg.timestamp,
             media: vec![],
+            attachments: vec![],
             metadata: std::collections::HashMap::new(),
         };
 
@@ -0,0 +1,1264 @@
+//! Native Anthropic Messages API provider.
+//!
+//! [`AnthropicProvider`] talks to `POST /v1/messages` directly instead of
+//! going through Anthropic's OpenAI-compatible shim. Requests and responses
+//! keep the OpenAI-shaped [`ChatRequest`]/[`ChatResponse`] types used by the
+//! rest of the crate and are translated at the edge:
+//!
+//! - `system` messages are lifted into the top-level `system` field
+//! - assistant `tool_calls` become `tool_use` content blocks
+//! - `tool` messages become `tool_result` blocks in a user turn
+//! - OpenAI-style function tools and `tool_choice` become Anthropic tool
+//!   definitions and tool choices
+//! - a `json_schema` response format becomes a forced tool whose input schema
+//!   is the requested schema; its input is returned as the reply text
+//! - usage counts prompt-cache reads and writes as input tokens
+//!
+//! Streaming maps Anthropic's event types (`message_start`,
+//! `content_block_start`, `content_block_delta`, `message_delta`,
+//! `message_stop`, `error`) onto [`StreamChunk`] values.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
+
+use crate::config::LlmProviderConfig;
+use crate::error::{ProviderError, Result};
+use crate::openai_compat::error_from_response;
+use crate::provider::Provider;
+use crate::sse::LineBuffer;
+use crate::timeouts::Timeouts;
+use crate::types::{
+    ChatMessage, ChatRequest, ChatResponse, Choice, ContentPart, FunctionCall, ImagePart,
+    ImageSource, MessageContent, ResponseFormat, StreamChunk, ToolCall, Usage,
+};
+use crate::vision::check_images;
+
+/// Default timeout for Anthropic API requests (2 minutes).
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// System instruction added for a `json_object` response format, which the
+/// Messages API has no native switch for.
+const JSON_OBJECT_INSTRUCTION: &str =
+    "Respond with a single valid JSON object and nothing else: no prose, no code fences.";
+
+/// API version sent when the config does not pin one.
+const DEFAULT_API_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires `max_tokens`; used when the request has none.
+const DEFAULT_MAX_TOKENS: i32 = 4096;
+
+/// An LLM provider that speaks the native Anthropic Messages API.
+///
+/// Uses the same [`LlmProviderConfig`] as
+/// [`OpenAiCompatProvider`](crate::openai_compat::OpenAiCompatProvider):
+/// `base_url` is the API root (e.g. `https://api.anthropic.com/v1`), the key
+/// is sent as `x-api-key`, and an `anthropic-version` entry in `headers`
+/// overrides the default API version.
+pub struct AnthropicProvider {
+    config: LlmProviderConfig,
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl AnthropicProvider {
+    /// Create a new provider from configuration.
+    ///
+    /// The API key will be resolved from the environment variable specified
+    /// in `config.api_key_env` at request time.
+    pub fn new(config: LlmProviderConfig) -> Self {
+        Self::build(config, None)
+    }
+
+    /// Create a new provider with an explicit API key.
+    pub fn with_api_key(config: LlmProviderConfig, api_key: String) -> Self {
+        Self::build(config, Some(api_key))
+    }
+
+    fn build(config: LlmProviderConfig, api_key: Option<String>) -> Self {
+        Self {
+            http: Timeouts::new(&config, DEFAULT_TIMEOUT_SECS).client(),
+            config,
+            api_key,
+        }
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::new(&self.config, DEFAULT_TIMEOUT_SECS)
+    }
+
+    /// Returns the provider configuration.
+    pub fn config(&self) -> &LlmProviderConfig {
+        &self.config
+    }
+
+    /// Returns the messages endpoint URL.
+    fn messages_url(&self) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        format!("{base}/messages")
+    }
+
+    /// Resolve the API key: explicit key > environment variable.
+    fn resolve_api_key(&self) -> Result<String> {
+        if let Some(ref key) = self.api_key {
+            return Ok(key.clone());
+        }
+        std::env::var(&self.config.api_key_env).map_err(|_| {
+            ProviderError::NotConfigured(format!("set {} env var", self.config.api_key_env))
+        })
+    }
+
+    /// Build a POST to the messages endpoint with auth and version headers.
+    fn post(&self, body: &Value) -> Result<reqwest::RequestBuilder> {
+        let api_key = self.resolve_api_key()?;
+        let mut req = self
+            .http
+            .post(self.messages_url())
+            .header("x-api-key", api_key)
+            .header("Content-Type", "application/json");
+        if !self.config.headers.contains_key("anthropic-version") {
+            req = req.header("anthropic-version", DEFAULT_API_VERSION);
+        }
+        for (k, v) in &self.config.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        Ok(req.json(body))
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        check_images(request)?;
+        let body = build_request_body(request, false);
+
+        debug!(
+            provider = %self.config.name,
+            model = %request.model,
+            messages = request.messages.len(),
+            "sending anthropic messages request"
+        );
+
+        let response = self
+            .post(&body)?
+            .timeout(self.timeouts().total_for(request))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
+        }
+
+        let body: MessagesResponse = response.json().await.map_err(|e| {
+            if e.is_timeout() {
+                return ProviderError::Timeout;
+            }
+            ProviderError::InvalidResponse(format!("failed to parse response: {e}"))
+        })?;
+        let mut chat_response = body.into_chat_response();
+        if let Some(name) = structured_tool(request) {
+            unwrap_structured_tool(&mut chat_response, name);
+        }
+
+        debug!(
+            provider = %self.config.name,
+            model = %chat_response.model,
+            "anthropic messages response received"
+        );
+
+        Ok(chat_response)
+    }
+
+    async fn complete_stream(
+        &self,
+        request: &ChatRequest,
+        tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<()> {
+        check_images(request)?;
+        let body = build_request_body(request, true);
+
+        debug!(
+            provider = %self.config.name,
+            model = %request.model,
+            messages = request.messages.len(),
+            "sending streaming anthropic messages request"
+        );
+
+        let timeouts = self.timeouts();
+        let response = self
+            .post(&body)?
+            .header("Accept", "text/event-stream")
+            .timeout(timeouts.total_for(request))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
+        }
+
+        let mut byte_stream = response.bytes_stream();
+        let mut lines = LineBuffer::default();
+        let mut state = StreamState {
+            structured_tool: structured_tool(request).map(String::from),
+            ..StreamState::default()
+        };
+
+        while let Some(bytes) = timeouts.next_chunk(&mut byte_stream).await? {
+            lines.push(&bytes);
+
+            while let Some(line) = lines.next_line() {
+                let Some(data) = line.strip_prefix("data:") else {
+                    continue;
+                };
+                let (chunks, finished) = state.handle_event(data.trim())?;
+                for chunk in chunks {
+                    trace!(provider = %self.config.name, chunk = ?chunk, "streaming chunk");
+                    if tx.send(chunk).await.is_err() {
+                        debug!(
+                            provider = %self.config.name,
+                            "stream receiver dropped, stopping"
+                        );
+                        return Ok(());
+                    }
+                }
+                if finished {
+                    debug!(provider = %self.config.name, "streaming complete");
+                    return Ok(());
+                }
+            }
+        }
+
+        // The connection closed without `message_stop`. That is harmless
+        // once the stop reason has arrived; before it, the reply is cut off.
+        if state.stopped {
+            warn!(
+                provider = %self.config.name,
+                "anthropic stream ended without message_stop"
+            );
+            return Ok(());
+        }
+        Err(ProviderError::InvalidResponse(
+            "anthropic stream ended before the message was complete".into(),
+        ))
+    }
+}
+
+impl std::fmt::Debug for AnthropicProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnthropicProvider")
+            .field("name", &self.config.name)
+            .field("base_url", &self.config.base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+// ── Request translation ─────────────────────────────────────────────────
+
+/// Translate a [`ChatRequest`] into a Messages API request body.
+pub(crate) fn build_request_body(request: &ChatRequest, stream: bool) -> Value {
+    let mut system: Vec<(Cow<'_, str>, bool)> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .filter_map(|m| m.text().map(|text| (text, m.cache)))
+        .filter(|(c, _)| !c.is_empty())
+        .collect();
+    if request.response_format == Some(ResponseFormat::JsonObject) {
+        system.push((JSON_OBJECT_INSTRUCTION.into(), false));
+    }
+
+    let mut body = json!({
+        "model": request.model,
+        "max_tokens": request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+        "messages": translate_messages(&request.messages),
+    });
+    if system.iter().any(|(_, cache)| *cache) {
+        // Cache breakpoints need the block form of `system`.
+        let blocks = system
+            .iter()
+            .map(|(text, cache)| {
+                let mut block = json!({ "type": "text", "text": text });
+                if *cache {
+                    block["cache_control"] = ephemeral();
+                }
+                block
+            })
+            .collect();
+        body["system"] = Value::Array(blocks);
+    } else if !system.is_empty() {
+        let texts: Vec<&str> = system.iter().map(|(text, _)| text.as_ref()).collect();
+        body["system"] = Value::String(texts.join("\n\n"));
+    }
+    if let Some(temperature) = request.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = request.top_p {
+        body["top_p"] = json!(top_p);
+    }
+    if !request.stop.is_empty() {
+        body["stop_sequences"] = json!(request.stop);
+    }
+    if !request.tools.is_empty() {
+        let mut tools: Vec<Value> = request.tools.iter().map(translate_tool).collect();
+        if request.cache_tools
+            && let Some(last) = tools.last_mut()
+        {
+            last["cache_control"] = ephemeral();
+        }
+        body["tools"] = Value::Array(tools);
+    }
+    limit_cache_breakpoints(&mut body);
+    if let Some(choice) = request.tool_choice.as_ref().and_then(translate_tool_choice) {
+        body["tool_choice"] = choice;
+    }
+    if let Some(schema) = request
+        .response_format
+        .as_ref()
+        .and_then(ResponseFormat::schema)
+    {
+        let tool = json!({
+            "name": schema.name,
+            "description": schema
+                .description
+                .as_deref()
+                .unwrap_or("Return the final answer as structured data."),
+            "input_schema": schema.schema,
+        });
+        match body["tools"].as_array_mut() {
+            Some(tools) => tools.push(tool),
+            None => body["tools"] = json!([tool]),
+        }
+        body["tool_choice"] = json!({ "type": "tool", "name": schema.name });
+    }
+    if stream {
+        body["stream"] = Value::Bool(true);
+    }
+    body
+}
+
+/// Most cache breakpoints Anthropic accepts in one request.
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
+fn ephemeral() -> Value {
+    json!({ "type": "ephemeral" })
+}
+
+/// Drop the earliest cache breakpoints beyond [`MAX_CACHE_BREAKPOINTS`].
+///
+/// Breakpoints count in prompt order (tools, system, messages); later ones
+/// cover a longer prefix, so they are the ones kept.
+fn limit_cache_breakpoints(body: &mut Value) {
+    let Some(map) = body.as_object_mut() else {
+        return;
+    };
+    let mut sections: [Vec<&mut Value>; 3] = Default::default();
+    for (key, value) in map.iter_mut() {
+        let slot = match key.as_str() {
+            "tools" => 0,
+            "system" => 1,
+            "messages" => 2,
+            _ => continue,
+        };
+        sections[slot] = cache_marked(value);
+    }
+    let mut marked: Vec<&mut Value> = sections.into_iter().flatten().collect();
+    let excess = marked.len().saturating_sub(MAX_CACHE_BREAKPOINTS);
+    for block in marked.drain(..excess) {
+        if let Some(block) = block.as_object_mut() {
+            block.remove("cache_control");
+        }
+    }
+}
+
+/// Blocks under `value` that carry a `cache_control` marker.
+fn cache_marked(value: &mut Value) -> Vec<&mut Value> {
+    if value.get("cache_control").is_some() {
+        return vec![value];
+    }
+    match value {
+        Value::Array(items) => items.iter_mut().flat_map(cache_marked).collect(),
+        Value::Object(map) => map.get_mut("content").map_or_else(Vec::new, cache_marked),
+        _ => Vec::new(),
+    }
+}
+
+/// Name of the tool forced to carry a `json_schema` reply, if any.
+fn structured_tool(request: &ChatRequest) -> Option<&str> {
+    request
+        .response_format
+        .as_ref()
+        .and_then(ResponseFormat::schema)
+        .map(|s| s.name.as_str())
+}
+
+/// Move the forced structured-output tool call into the reply text.
+fn unwrap_structured_tool(response: &mut ChatResponse, name: &str) {
+    for choice in &mut response.choices {
+        let Some(calls) = choice.message.tool_calls.as_mut() else {
+            continue;
+        };
+        let Some(pos) = calls.iter().position(|c| c.function.name == name) else {
+            continue;
+        };
+        let call = calls.remove(pos);
+        choice.message.content = Some(call.function.arguments.into());
+        if calls.is_empty() {
+            choice.message.tool_calls = None;
+            if choice.finish_reason.as_deref() == Some("tool_calls") {
+                choice.finish_reason = Some("stop".into());
+            }
+        }
+    }
+}
+
+/// Convert non-system messages into Anthropic turns.
+///
+/// Every turn uses content blocks. Consecutive messages that map to the
+/// same role are merged, so the results of several parallel tool calls end
+/// up in the single user turn that follows the assistant's `tool_use`
+/// blocks.
+fn translate_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    let mut turns: Vec<(&'static str, Vec<Value>)> = Vec::new();
+
+    for msg in messages {
+        let (role, mut blocks) = match msg.role.as_str() {
+            "system" => continue,
+            "assistant" => ("assistant", assistant_blocks(msg)),
+            "tool" => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": msg.tool_call_id.clone().unwrap_or_default(),
+                    "content": msg.text().unwrap_or_default(),
+                })],
+            ),
+            _ => ("user", content_blocks(msg.content.as_ref())),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        if msg.cache
+            && let Some(last) = blocks.last_mut()
+        {
+            last["cache_control"] = ephemeral();
+        }
+        match turns.last_mut() {
+            Some((last_role, last_blocks)) if *last_role == role => last_blocks.extend(blocks),
+            _ => turns.push((role, blocks)),
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": content }))
+        .collect()
+}
+
+fn content_blocks(content: Option<&MessageContent>) -> Vec<Value> {
+    match content {
+        None => Vec::new(),
+        Some(MessageContent::Text(text)) => text_block(text),
+        Some(MessageContent::Parts(parts)) => parts
+            .iter()
+            .flat_map(|part| match part {
+                ContentPart::Text { text } => text_block(text),
+                ContentPart::Image(image) => vec![image_block(image)],
+            })
+            .collect(),
+    }
+}
+
+fn text_block(text: &str) -> Vec<Value> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    vec![json!({ "type": "text", "text": text })]
+}
+
+fn image_block(image: &ImagePart) -> Value {
+    let source = match &image.source {
+        ImageSource::Url(url) => json!({ "type": "url", "url": url }),
+        ImageSource::Base64 { mime_type, data } => {
+            json!({ "type": "base64", "media_type": mime_type, "data": data })
+        }
+    };
+    json!({ "type": "image", "source": source })
+}
+
+fn assistant_blocks(msg: &ChatMessage) -> Vec<Value> {
+    let mut blocks = content_blocks(msg.content.as_ref());
+    for call in msg.tool_calls.iter().flatten() {
+        // Anthropic wants the input as an object. Arguments that are empty
+        // or not valid JSON become `{}` rather than failing the whole turn.
+        let input = serde_json::from_str::<Value>(&call.function.arguments)
+            .ok()
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+        blocks.push(json!({
+            "type": "tool_use",
+            "id": call.id,
+            "name": call.function.name,
+            "input": input,
+        }));
+    }
+    blocks
+}
+
+/// Convert an OpenAI function tool into an Anthropic tool definition.
+///
+/// Definitions that already carry `input_schema` are passed through.
+fn translate_tool(tool: &Value) -> Value {
+    if tool.get("input_schema").is_some() {
+        return tool.clone();
+    }
+    let function = tool.get("function").unwrap_or(tool);
+    let mut out = json!({
+        "name": function.get("name").cloned().unwrap_or(Value::Null),
+        "input_schema": function
+            .get("parameters")
+            .cloned()
+            .unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+    });
+    if let Some(description) = function.get("description") {
+        out["description"] = description.clone();
+    }
+    out
+}
+
+/// Convert an OpenAI `tool_choice` into an Anthropic one.
+fn translate_tool_choice(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(mode) => match mode.as_str() {
+            "auto" => Some(json!({ "type": "auto" })),
+            "required" | "any" => Some(json!({ "type": "any" })),
+            "none" => Some(json!({ "type": "none" })),
+            _ => None,
+        },
+        Value::Object(map) => match map.get("type").and_then(Value::as_str) {
+            Some("function") => {
+                let name = map.get("function")?.get("name")?;
+                Some(json!({ "type": "tool", "name": name }))
+            }
+            Some("auto" | "any" | "tool" | "none") => Some(choice.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// ── Response translation ────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    id: String,
+    model: String,
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Token usage as reported by the Messages API.
+///
+/// `input_tokens` excludes prompt-cache traffic, which is reported
+/// separately; [`into_usage`](Self::into_usage) adds it back so the
+/// resulting [`Usage`] counts every prompt token.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+}
+
+impl AnthropicUsage {
+    fn into_usage(self) -> Usage {
+        let input =
+            self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens;
+        Usage {
+            input_tokens: input,
+            output_tokens: self.output_tokens,
+            total_tokens: input + self.output_tokens,
+            cached: false,
+            cache_read_tokens: self.cache_read_input_tokens,
+            cache_write_tokens: self.cache_creation_input_tokens,
+            reasoning_tokens: 0,
+        }
+    }
+}
+
+impl MessagesResponse {
+    fn into_chat_response(self) -> ChatResponse {
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in self.content {
+            match block {
+                ContentBlock::Text { text: t } => text.push_str(&t),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    call_type: "function".into(),
+                    function: FunctionCall {
+                        name,
+                        arguments: input.to_string(),
+                    },
+                }),
+                ContentBlock::Other => {}
+            }
+        }
+
+        let message = ChatMessage {
+            role: "assistant".into(),
+            content: if text.is_empty() && !tool_calls.is_empty() {
+                None
+            } else {
+                Some(text.into())
+            },
+            tool_call_id: None,
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+            cache: false,
+            reasoning: None,
+        };
+
+        ChatResponse {
+            id: self.id,
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason: self.stop_reason.as_deref().map(map_stop_reason),
+            }],
+            usage: self.usage.map(AnthropicUsage::into_usage),
+            model: self.model,
+            failover: Vec::new(),
+            retries: 0,
+        }
+    }
+}
+
+/// Map an Anthropic `stop_reason` onto the OpenAI `finish_reason` values.
+fn map_stop_reason(reason: &str) -> String {
+    match reason {
+        "end_turn" | "stop_sequence" => "stop",
+        "max_tokens" => "length",
+        "tool_use" => "tool_calls",
+        other => other,
+    }
+    .to_string()
+}
+
+// ── Streaming ───────────────────────────────────────────────────────────
+
+/// Tracks what a Messages stream has reported so far.
+#[derive(Debug, Default)]
+struct StreamState {
+    /// Whether `message_delta` (carrying the stop reason) has been seen.
+    stopped: bool,
+    /// Usage from `message_start`; output tokens arrive in `message_delta`.
+    usage: AnthropicUsage,
+    /// Content block index -> tool call index, for `tool_use` blocks.
+    tool_indices: HashMap<usize, usize>,
+    /// Name of the forced structured-output tool, whose input is streamed
+    /// as text.
+    structured_tool: Option<String>,
+    /// Content block index of the structured-output tool call.
+    structured_block: Option<usize>,
+}
+
+impl StreamState {
+    /// Handle one `data:` payload. Returns the chunks to emit and whether
+    /// the stream is finished.
+    fn handle_event(&mut self, data: &str) -> Result<(Vec<StreamChunk>, bool)> {
+        if data.is_empty() {
+            return Ok((Vec::new(), false));
+        }
+        let event: Value = serde_json::from_str(data).map_err(|e| {
+            ProviderError::InvalidResponse(format!("failed to parse anthropic event: {e}"))
+        })?;
+
+        let mut chunks = Vec::new();
+        match event["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                if let Ok(usage) = AnthropicUsage::deserialize(&event["message"]["usage"]) {
+                    self.usage = usage;
+                }
+            }
+            "content_block_start" => {
+                let block = &event["content_block"];
+                match block["type"].as_str() {
+                    Some("tool_use")
+                        if self.structured_tool.is_some()
+                            && block["name"].as_str() == self.structured_tool.as_deref() =>
+                    {
+                        self.structured_block = event["index"].as_u64().map(|i| i as usize);
+                    }
+                    Some("tool_use") => {
+                        let index = self.tool_index(&event);
+                        chunks.push(StreamChunk::ToolCallDelta {
+                            index,
+                            id: block["id"].as_str().map(String::from),
+                            name: block["name"].as_str().map(String::from),
+                            arguments: None,
+                        });
+                    }
+                    Some("text") => {
+                        if let Some(text) = block["text"].as_str().filter(|t| !t.is_empty()) {
+                            chunks.push(StreamChunk::TextDelta { text: text.into() });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => {
+                        if let Some(text) = delta["text"].as_str().filter(|t| !t.is_empty()) {
+                            chunks.push(StreamChunk::TextDelta { text: text.into() });
+                        }
+                    }
+                    Some("input_json_delta")
+                        if self.structured_block.is_some()
+                            && event["index"].as_u64().map(|i| i as usize)
+                                == self.structured_block =>
+                    {
+                        if let Some(json) = delta["partial_json"].as_str().filter(|j| !j.is_empty())
+                        {
+                            chunks.push(StreamChunk::TextDelta { text: json.into() });
+                        }
+                    }
+                    Some("input_json_delta") => {
+                        let index = self.tool_index(&event);
+                        chunks.push(StreamChunk::ToolCallDelta {
+                            index,
+                            id: None,
+                            name: None,
+                            arguments: delta["partial_json"].as_str().map(String::from),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            "message_delta" => {
+                self.stopped = true;
+                if let Some(output) = event["usage"]["output_tokens"].as_u64() {
+                    self.usage.output_tokens = output as u32;
+                }
+                let mut finish_reason = event["delta"]["stop_reason"].as_str().map(map_stop_reason);
+                if self.structured_block.is_some() && self.tool_indices.is_empty() {
+                    // The forced tool call was the answer, not a tool request.
+                    finish_reason =
+                        finish_reason.map(|r| if r == "tool_calls" { "stop".into() } else { r });
+                }
+                chunks.push(StreamChunk::Done {
+                    finish_reason,
+                    usage: Some(self.usage.into_usage()),
+                });
+            }
+            "message_stop" => return Ok((chunks, true)),
+            "error" => {
+                let error = &event["error"];
+                let message = error["message"].as_str().unwrap_or("unknown error");
+                return Err(match error["type"].as_str() {
+                    Some("overloaded_error") => ProviderError::ServerError {
+                        status: 529,
+                        body: message.into(),
+                    },
+                    Some("api_error") => ProviderError::ServerError {
+                        status: 500,
+                        body: message.into(),
+                    },
+                    _ => ProviderError::RequestFailed(message.into()),
+                });
+            }
+            // ping, content_block_stop, and future event types.
+            _ => {}
+        }
+        Ok((chunks, false))
+    }
+
+    /// Tool call index for the content block an event refers to.
+    fn tool_index(&mut self, event: &Value) -> usize {
+        let block = event["index"].as_u64().unwrap_or(0) as usize;
+        let next = self.tool_indices.len();
+        *self.tool_indices.entry(block).or_insert(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::StreamAccumulator;
+
+    fn weather_tool() -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Look up the weather",
+                "parameters": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn request_translation_fixture() {
+        let mut request = ChatRequest::new(
+            "claude-sonnet-4-5",
+            vec![
+                ChatMessage::system("You are terse."),
+                ChatMessage::system("Use metric units."),
+                ChatMessage::user("Weather in Paris and Oslo?"),
+                ChatMessage {
+                    role: "assistant".into(),
+                    content: Some("Checking.".into()),
+                    tool_call_id: None,
+                    tool_calls: Some(vec![
+                        ToolCall {
+                            id: "toolu_1".into(),
+                            call_type: "function".into(),
+                            function: FunctionCall {
+                                name: "get_weather".into(),
+                                arguments: r#"{"city":"Paris"}"#.into(),
+                            },
+                        },
+                        ToolCall {
+                            id: "toolu_2".into(),
+                            call_type: "function".into(),
+                            function: FunctionCall {
+                                name: "get_weather".into(),
+                                arguments: String::new(),
+                            },
+                        },
+                    ]),
+                    cache: false,
+                    reasoning: None,
+                },
+                ChatMessage {
+                    role: "tool".into(),
+                    content: Some("18C".into()),
+                    tool_call_id: Some("toolu_1".into()),
+                    tool_calls: None,
+                    cache: false,
+                    reasoning: None,
+                },
+                ChatMessage {
+                    role: "tool".into(),
+                    content: Some("9C".into()),
+                    tool_call_id: Some("toolu_2".into()),
+                    tool_calls: None,
+                    cache: false,
+                    reasoning: None,
+                },
+            ],
+        );
+        request.tools = vec![weather_tool()];
+        request.tool_choice =
+            Some(json!({"type": "function", "function": {"name": "get_weather"}}));
+        request.temperature = Some(0.2);
+
+        let body = build_request_body(&request, false);
+        assert_eq!(
+            body,
+            json!({
+                "model": "claude-sonnet-4-5",
+                "max_tokens": 4096,
+                "system": "You are terse.\n\nUse metric units.",
+                "temperature": 0.2,
+                "messages": [
+                    {"role": "user", "content": [
+                        {"type": "text", "text": "Weather in Paris and Oslo?"}
+                    ]},
+                    {"role": "assistant", "content": [
+                        {"type": "text", "text": "Checking."},
+                        {"type": "tool_use", "id": "toolu_1", "name": "get_weather",
+                         "input": {"city": "Paris"}},
+                        {"type": "tool_use", "id": "toolu_2", "name": "get_weather",
+                         "input": {}}
+                    ]},
+                    {"role": "user", "content": [
+                        {"type": "tool_result", "tool_use_id": "toolu_1", "content": "18C"},
+                        {"type": "tool_result", "tool_use_id": "toolu_2", "content": "9C"}
+                    ]}
+                ],
+                "tools": [{
+                    "name": "get_weather",
+                    "description": "Look up the weather",
+                    "input_schema": {
+                        "type": "object",
+                        "properties": {"city": {"type": "string"}},
+                        "required": ["city"]
+                    }
+                }],
+                "tool_choice": {"type": "tool", "name": "get_weather"}
+            })
+        );
+    }
+
+    #[test]
+    fn sampling_controls_map_to_anthropic_fields() {
+        let request = ChatRequest::new("claude-sonnet-4-5", vec![ChatMessage::user("Label")])
+            .with_top_p(0.8)
+            .with_stop(["</label>"])
+            .with_seed(7)
+            .with_logit_bias("50256", -100.0);
+        let body = build_request_body(&request, false);
+        assert_eq!(body["top_p"], 0.8);
+        assert_eq!(body["stop_sequences"], json!(["</label>"]));
+        // No Anthropic equivalent.
+        assert!(body.get("seed").is_none());
+        assert!(body.get("logit_bias").is_none());
+    }
+
+    #[test]
+    fn cacheable_prefix_gets_cache_control() {
+        let mut request = ChatRequest::new(
+            "claude-sonnet-4-5",
+            vec![
+                ChatMessage::system("You are terse."),
+                ChatMessage::system("# Skill: weather").cacheable(),
+                ChatMessage::system("# Relevant Memory: likes Oslo"),
+                ChatMessage::user("Weather?").cacheable(),
+            ],
+        );
+        request.tools = vec![weather_tool()];
+        request.cache_tools = true;
+
+        let body = build_request_body(&request, false);
+        assert_eq!(
+            body["system"],
+            json!([
+                {"type": "text", "text": "You are terse."},
+                {"type": "text", "text": "# Skill: weather",
+                 "cache_control": {"type": "ephemeral"}},
+                {"type": "text", "text": "# Relevant Memory: likes Oslo"}
+            ])
+        );
+        assert_eq!(
+            body["tools"][0]["cache_control"],
+            json!({"type": "ephemeral"})
+        );
+        assert_eq!(
+            body["messages"][0]["content"][0]["cache_control"],
+            json!({"type": "ephemeral"})
+        );
+
+        // Without markers the system prompt stays a plain string.
+        request.messages.iter_mut().for_each(|m| m.cache = false);
+        request.cache_tools = false;
+        let body = build_request_body(&request, false);
+        assert!(body["system"].is_string());
+        assert!(body["tools"][0].get("cache_control").is_none());
+    }
+
+    #[test]
+    fn cache_breakpoints_are_capped() {
+        let messages = (0..6)
+            .map(|i| ChatMessage::user(format!("turn {i}")).cacheable())
+            .collect::<Vec<_>>();
+        let mut request = ChatRequest::new("claude-sonnet-4-5", messages);
+        request.tools = vec![weather_tool()];
+        request.cache_tools = true;
+
+        let body = build_request_body(&request, false);
+        let blocks = body["messages"][0]["content"].as_array().unwrap();
+        let marked: Vec<usize> = blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.get("cache_control").is_some())
+            .map(|(i, _)| i)
+            .collect();
+        // The latest four breakpoints win; the tool breakpoint is dropped.
+        assert_eq!(marked, vec![2, 3, 4, 5]);
+        assert!(body["tools"][0].get("cache_control").is_none());
+    }
+
+    #[test]
+    fn tool_choice_modes() {
+        assert_eq!(
+            translate_tool_choice(&json!("auto")),
+            Some(json!({"type": "auto"}))
+        );
+        assert_eq!(
+            translate_tool_choice(&json!("required")),
+            Some(json!({"type": "any"}))
+        );
+        assert_eq!(
+            translate_tool_choice(&json!("none")),
+            Some(json!({"type": "none"}))
+        );
+        assert_eq!(translate_tool_choice(&json!("bogus")), None);
+        let native = json!({"type": "any", "disable_parallel_tool_use": true});
+        assert_eq!(translate_tool_choice(&native), Some(native.clone()));
+    }
+
+    #[test]
+    fn streaming_flag_and_native_tools_pass_through() {
+        let mut request = ChatRequest::new("m", vec![ChatMessage::user("hi")]);
+        request.max_tokens = Some(64);
+        let native = json!({"name": "t", "input_schema": {"type": "object"}});
+        request.tools = vec![native.clone()];
+        let body = build_request_body(&request, true);
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["max_tokens"], 64);
+        assert_eq!(body["tools"][0], native);
+        assert!(body.get("system").is_none());
+        assert!(body.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn response_translation_fixture() {
+        let raw = json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "thinking", "thinking": "hmm", "signature": "x"},
+                {"type": "text", "text": "Let me look."},
+                {"type": "tool_use", "id": "toolu_9", "name": "get_weather",
+                 "input": {"city": "Oslo"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {
+                "input_tokens": 10,
+                "output_tokens": 20,
+                "cache_creation_input_tokens": 100,
+                "cache_read_input_tokens": 1000
+            }
+        });
+        let resp: MessagesResponse = serde_json::from_value(raw).unwrap();
+        let resp = resp.into_chat_response();
+
+        assert_eq!(resp.id, "msg_01");
+        assert_eq!(resp.model, "claude-sonnet-4-5");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(choice.message.text().as_deref(), Some("Let me look."));
+        let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.id, "toolu_9");
+        assert_eq!(call.function.name, "get_weather");
+        assert_eq!(call.function.arguments, r#"{"city":"Oslo"}"#);
+
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.input_tokens, 1110);
+        assert_eq!(usage.output_tokens, 20);
+        assert_eq!(usage.total_tokens, 1130);
+        assert_eq!(usage.cache_read_tokens, 1000);
+        assert_eq!(usage.cache_write_tokens, 100);
+    }
+
+    #[test]
+    fn stop_reasons_map_to_finish_reasons() {
+        assert_eq!(map_stop_reason("end_turn"), "stop");
+        assert_eq!(map_stop_reason("stop_sequence"), "stop");
+        assert_eq!(map_stop_reason("max_tokens"), "length");
+        assert_eq!(map_stop_reason("tool_use"), "tool_calls");
+        assert_eq!(map_stop_reason("refusal"), "refusal");
+    }
+
+    #[test]
+    fn stream_events_fixture() {
+        let events = [
+            r#"{"type":"message_start","message":{"id":"msg_1","model":"claude","usage":{"input_tokens":25,"output_tokens":1,"cache_read_input_tokens":5}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Sure"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_a","name":"get_weather","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"city\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"Rome\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":15}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+
+        let mut state = StreamState::default();
+        let mut acc = StreamAccumulator::new();
+        let mut finished = false;
+        for event in events {
+            let (chunks, done) = state.handle_event(event).unwrap();
+            chunks.iter().for_each(|c| acc.push(c));
+            finished = done;
+        }
+        assert!(finished);
+
+        let resp = acc.finish("claude");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.message.text().as_deref(), Some("Sure"));
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.id, "toolu_a");
+        assert_eq!(call.function.arguments, r#"{"city":"Rome"}"#);
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.input_tokens, 30);
+        assert_eq!(usage.output_tokens, 15);
+    }
+
+    #[test]
+    fn image_parts_become_image_blocks() {
+        let request = ChatRequest::new(
+            "claude-sonnet-4-5",
+            vec![ChatMessage::with_parts(
+                "user",
+                vec![
+                    ContentPart::text("Compare these."),
+                    ContentPart::Image(ImagePart::url("https://example.com/a.png")),
+                    ContentPart::Image(ImagePart::base64("image/jpeg", "/9j/4AAQ")),
+                ],
+            )],
+        );
+        let body = build_request_body(&request, false);
+        assert_eq!(
+            body["messages"],
+            json!([{"role": "user", "content": [
+                {"type": "text", "text": "Compare these."},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}},
+                {"type": "image", "source": {
+                    "type": "base64", "media_type": "image/jpeg", "data": "/9j/4AAQ"
+                }}
+            ]}])
+        );
+    }
+
+    #[test]
+    fn json_schema_format_forces_a_tool() {
+        let mut request = ChatRequest::new("m", vec![ChatMessage::user("Who?")]);
+        request.tools = vec![weather_tool()];
+        request.response_format = Some(ResponseFormat::json_schema(
+            "person",
+            json!({"type": "object", "properties": {"name": {"type": "string"}}}),
+        ));
+        let body = build_request_body(&request, false);
+        assert_eq!(body["tools"].as_array().unwrap().len(), 2);
+        assert_eq!(body["tools"][1]["name"], "person");
+        assert_eq!(body["tools"][1]["input_schema"]["type"], "object");
+        assert_eq!(
+            body["tool_choice"],
+            json!({"type": "tool", "name": "person"})
+        );
+
+        let raw = json!({
+            "id": "msg_2",
+            "model": "claude",
+            "content": [{"type": "tool_use", "id": "toolu_p", "name": "person",
+                         "input": {"name": "Ada"}}],
+            "stop_reason": "tool_use"
+        });
+        let mut resp = serde_json::from_value::<MessagesResponse>(raw)
+            .unwrap()
+            .into_chat_response();
+        unwrap_structured_tool(&mut resp, "person");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.message.text().as_deref(), Some(r#"{"name":"Ada"}"#));
+        assert!(choice.message.tool_calls.is_none());
+        assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn json_object_format_adds_an_instruction() {
+        let mut request = ChatRequest::new(
+            "m",
+            vec![ChatMessage::system("Be brief."), ChatMessage::user("hi")],
+        );
+        request.response_format = Some(ResponseFormat::JsonObject);
+        let body = build_request_body(&request, false);
+        assert_eq!(
+            body["system"],
+            format!("Be brief.\n\n{JSON_OBJECT_INSTRUCTION}")
+        );
+        assert!(body.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn structured_tool_input_streams_as_text() {
+        let events = [
+            r#"{"type":"message_start","message":{"usage":{"input_tokens":5,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_p","name":"person","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"name\":"}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"\"Ada\"}"}}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":9}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        let mut state = StreamState {
+            structured_tool: Some("person".into()),
+            ..StreamState::default()
+        };
+        let mut acc = StreamAccumulator::new();
+        for event in events {
+            let (chunks, _) = state.handle_event(event).unwrap();
+            chunks.iter().for_each(|c| acc.push(c));
+        }
+        let resp = acc.finish("claude");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.message.text().as_deref(), Some(r#"{"name":"Ada"}"#));
+        assert!(choice.message.tool_calls.is_none());
+        assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn stream_error_event_is_an_error() {
+        let mut state = StreamState::default();
+        let err = state
+            .handle_event(
+                r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ProviderError::ServerError { status: 529, .. }
+        ));
+    }
+
+    #[test]
+    fn debug_hides_api_key() {
+        let config = crate::config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "anthropic")
+            .unwrap();
+        let provider = AnthropicProvider::with_api_key(config, "sk-ant-secret".into());
+        let debug = format!("{provider:?}");
+        assert!(!debug.contains("sk-ant-secret"));
+        assert!(debug.contains("***"));
+        assert_eq!(
+            provider.messages_url(),
+            "https://api.anthropic.com/v1/messages"
+        );
+    }
+}
@@ -441,6 +441,11 @@ mod tests {
             default_model: Some("test-model".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         }
     }
 
@@ -454,6 +459,11 @@ mod tests {
             default_model: None,
             headers: HashMap::from([("anthropic-version".into(), "2023-06-01".into())]),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         }
     }
 
@@ -0,0 +1,492 @@
+//! Opt-in response caching for deterministic requests.
+//!
+//! Pipelines that re-run the same classification or scoring prompt pay for
+//! identical completions. An [`LlmCache`] stores responses keyed by a hash of
+//! the request, in an in-memory LRU with an optional on-disk copy, and
+//! `CachedProvider` consults it in front of any provider.
+//!
+//! Only requests that ask for it are cached: [`ChatRequest::cache`] must be
+//! set and the temperature must be exactly `0`, so ordinary conversational
+//! calls never see a stored reply. A hit is returned with zero usage marked
+//! `cached: true`, so it costs nothing in the usage ledger.
+//!
+//! ```rust,ignore
+//! use clawft_llm::cache::{CachedProvider, LlmCache};
+//!
+//! let cache = Arc::new(LlmCache::new(512, Duration::from_secs(3600)).with_dir(dir));
+//! let provider = CachedProvider::new(provider, cache);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::types::{
+    ChatMessage, ChatRequest, ChatResponse, ReasoningEffort, ResponseFormat, Usage,
+};
+
+/// Directory name of the on-disk cache inside the state directory.
+pub const CACHE_DIR: &str = "llm_cache";
+
+/// On-disk cache location under a home directory: `~/.clawft/state/llm_cache`.
+pub fn cache_dir(home: &Path) -> PathBuf {
+    home.join(".clawft").join("state").join(CACHE_DIR)
+}
+
+/// The cache key for `request`, or `None` when it must not be cached.
+///
+/// The key is the hex SHA-256 of everything that shapes the reply: model,
+/// messages, tools, tool choice, response format, token limit, sampling
+/// parameters (stop sequences, seed, `top_p`, penalties, logit bias) and
+/// reasoning effort or budget. Requests
+/// that did not opt in, or whose temperature is not exactly `0`, get no key.
+pub fn cache_key(request: &ChatRequest) -> Option<String> {
+    if !request.cache || request.temperature != Some(0.0) {
+        return None;
+    }
+
+    #[derive(Serialize)]
+    struct KeyFields<'a> {
+        model: &'a str,
+        messages: &'a [ChatMessage],
+        tools: &'a [serde_json::Value],
+        tool_choice: Option<&'a serde_json::Value>,
+        response_format: Option<&'a ResponseFormat>,
+        max_tokens: Option<i32>,
+        top_p: Option<f64>,
+        stop: &'a [String],
+        seed: Option<u64>,
+        frequency_penalty: Option<f64>,
+        presence_penalty: Option<f64>,
+        // Sorted, so equal maps hash the same.
+        logit_bias: BTreeMap<&'a str, f32>,
+        reasoning_effort: Option<ReasoningEffort>,
+        max_reasoning_tokens: Option<u32>,
+    }
+
+    let fields = KeyFields {
+        model: &request.model,
+        messages: &request.messages,
+        tools: &request.tools,
+        tool_choice: request.tool_choice.as_ref(),
+        response_format: request.response_format.as_ref(),
+        max_tokens: request.max_tokens,
+        top_p: request.top_p,
+        stop: &request.stop,
+        seed: request.seed,
+        frequency_penalty: request.frequency_penalty,
+        presence_penalty: request.presence_penalty,
+        logit_bias: request
+            .logit_bias
+            .iter()
+            .map(|(token, bias)| (token.as_str(), *bias))
+            .collect(),
+        reasoning_effort: request.reasoning_effort,
+        max_reasoning_tokens: request.max_reasoning_tokens,
+    };
+    let bytes = serde_json::to_vec(&fields).ok()?;
+    let digest = Sha256::digest(&bytes);
+    Some(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// A stored response and when it was stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    stored_at: DateTime<Utc>,
+    response: ChatResponse,
+}
+
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<String, (Entry, u64)>,
+    /// Monotonic use counter; the entry with the lowest stamp is evicted.
+    tick: u64,
+}
+
+/// An LRU cache of chat responses with a TTL and optional disk persistence.
+pub struct LlmCache {
+    capacity: usize,
+    ttl: Duration,
+    dir: Option<PathBuf>,
+    lru: Mutex<Lru>,
+}
+
+impl LlmCache {
+    /// Create an in-memory cache holding up to `capacity` responses, each
+    /// valid for `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            dir: None,
+            lru: Mutex::new(Lru::default()),
+        }
+    }
+
+    /// Also persist entries as JSON files in `dir`, so they survive restarts.
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Number of responses held in memory.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Whether no responses are held in memory.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cached reply for `request`, if it opted in and a fresh entry
+    /// exists. The reply carries zero usage marked `cached: true`.
+    pub fn get(&self, request: &ChatRequest) -> Option<ChatResponse> {
+        let key = cache_key(request)?;
+        let now = Utc::now();
+
+        let mut lru = self.lock();
+        lru.tick += 1;
+        let tick = lru.tick;
+        let memory = match lru.entries.get_mut(&key) {
+            Some((entry, _)) if !self.is_fresh(entry, now) => {
+                lru.entries.remove(&key);
+                None
+            }
+            Some((entry, used)) => {
+                *used = tick;
+                Some(entry.response.clone())
+            }
+            None => None,
+        };
+        if let Some(response) = memory {
+            return Some(as_hit(response));
+        }
+
+        let entry = self.read_disk(&key).filter(|e| self.is_fresh(e, now))?;
+        let response = entry.response.clone();
+        self.insert(&mut lru, key, entry);
+        Some(as_hit(response))
+    }
+
+    /// Store `response` for `request`, if the request opted in.
+    pub fn put(&self, request: &ChatRequest, response: &ChatResponse) {
+        let Some(key) = cache_key(request) else {
+            return;
+        };
+        let entry = Entry {
+            stored_at: Utc::now(),
+            response: response.clone(),
+        };
+        self.write_disk(&key, &entry);
+        let mut lru = self.lock();
+        self.insert(&mut lru, key, entry);
+    }
+
+    fn insert(&self, lru: &mut Lru, key: String, entry: Entry) {
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.entries.insert(key, (entry, tick));
+        while lru.entries.len() > self.capacity {
+            let oldest = lru
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => lru.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    fn is_fresh(&self, entry: &Entry, now: DateTime<Utc>) -> bool {
+        (now - entry.stored_at)
+            .to_std()
+            .is_ok_and(|age| age < self.ttl)
+    }
+
+    fn read_disk(&self, key: &str) -> Option<Entry> {
+        let path = self.dir.as_ref()?.join(format!("{key}.json"));
+        let bytes = std::fs::read(path).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    fn write_disk(&self, key: &str, entry: &Entry) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let result = std::fs::create_dir_all(dir).and_then(|()| {
+            let json = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+            std::fs::write(dir.join(format!("{key}.json")), json)
+        });
+        if let Err(e) = result {
+            warn!(dir = %dir.display(), error = %e, "failed to persist cached LLM response");
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.lru.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A stored response as served from the cache: nothing was billed.
+fn as_hit(mut response: ChatResponse) -> ChatResponse {
+    response.usage = Some(Usage {
+        input_tokens: 0,
+        output_tokens: 0,
+        total_tokens: 0,
+        cached: true,
+        cache_read_tokens: 0,
+        cache_write_tokens: 0,
+        reasoning_tokens: 0,
+    });
+    response
+}
+
+#[cfg(feature = "native")]
+pub use provider::CachedProvider;
+
+#[cfg(feature = "native")]
+mod provider {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+    use tracing::debug;
+
+    use super::LlmCache;
+    use crate::error::Result;
+    use crate::provider::Provider;
+    use crate::types::{ChatRequest, ChatResponse, StreamChunk};
+
+    /// A [`Provider`] that answers opted-in requests from an [`LlmCache`].
+    ///
+    /// Streaming requests always go to the inner provider.
+    pub struct CachedProvider<P> {
+        inner: P,
+        cache: Arc<LlmCache>,
+    }
+
+    impl<P: Provider> CachedProvider<P> {
+        /// Wrap `inner`, serving and storing replies through `cache`.
+        pub fn new(inner: P, cache: Arc<LlmCache>) -> Self {
+            Self { inner, cache }
+        }
+    }
+
+    #[async_trait]
+    impl<P: Provider> Provider for CachedProvider<P> {
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+            if let Some(hit) = self.cache.get(request) {
+                debug!(provider = %self.inner.name(), model = %request.model, "LLM cache hit");
+                return Ok(hit);
+            }
+            let response = self.inner.complete(request).await?;
+            self.cache.put(request, &response);
+            Ok(response)
+        }
+
+        async fn complete_stream(
+            &self,
+            request: &ChatRequest,
+            tx: mpsc::Sender<StreamChunk>,
+        ) -> Result<()> {
+            self.inner.complete_stream(request, tx).await
+        }
+
+        async fn list_models(&self) -> Result<Vec<String>> {
+            self.inner.list_models().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Choice;
+
+    fn request(text: &str) -> ChatRequest {
+        let mut request = ChatRequest::new("gpt-4o-mini", vec![ChatMessage::user(text)]);
+        request.temperature = Some(0.0);
+        request.cache = true;
+        request
+    }
+
+    fn response(text: &str) -> ChatResponse {
+        ChatResponse {
+            id: "resp-1".into(),
+            choices: vec![Choice {
+                index: 0,
+                message: ChatMessage::assistant(text),
+                finish_reason: Some("stop".into()),
+            }],
+            usage: Some(Usage {
+                input_tokens: 12,
+                output_tokens: 3,
+                total_tokens: 15,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
+            }),
+            model: "gpt-4o-mini".into(),
+            failover: Vec::new(),
+            retries: 0,
+        }
+    }
+
+    #[test]
+    fn key_is_stable_and_content_sensitive() {
+        let key = cache_key(&request("classify: hello")).unwrap();
+        assert_eq!(key, cache_key(&request("classify: hello")).unwrap());
+        assert_eq!(key.len(), 64);
+        assert_ne!(key, cache_key(&request("classify: goodbye")).unwrap());
+
+        let mut with_tools = request("classify: hello");
+        with_tools.tools = vec![serde_json::json!({"type": "function"})];
+        assert_ne!(key, cache_key(&with_tools).unwrap());
+
+        let mut other_model = request("classify: hello");
+        other_model.model = "gpt-4o".into();
+        assert_ne!(key, cache_key(&other_model).unwrap());
+    }
+
+    #[test]
+    fn key_covers_sampling_parameters() {
+        let key = cache_key(&request("classify: hello")).unwrap();
+
+        let mut stop = request("classify: hello");
+        stop.stop = vec!["\n".into()];
+        assert_ne!(key, cache_key(&stop).unwrap());
+
+        let mut seed = request("classify: hello");
+        seed.seed = Some(7);
+        assert_ne!(key, cache_key(&seed).unwrap());
+        let mut other_seed = request("classify: hello");
+        other_seed.seed = Some(8);
+        assert_ne!(cache_key(&seed).unwrap(), cache_key(&other_seed).unwrap());
+
+        let mut top_p = request("classify: hello");
+        top_p.top_p = Some(0.5);
+        assert_ne!(key, cache_key(&top_p).unwrap());
+
+        let mut penalty = request("classify: hello");
+        penalty.frequency_penalty = Some(1.0);
+        let mut presence = request("classify: hello");
+        presence.presence_penalty = Some(1.0);
+        assert_ne!(key, cache_key(&penalty).unwrap());
+        assert_ne!(cache_key(&penalty).unwrap(), cache_key(&presence).unwrap());
+
+        let biased = |pairs: &[(&str, f32)]| {
+            let mut r = request("classify: hello");
+            r.logit_bias = pairs.iter().map(|(t, b)| (t.to_string(), *b)).collect();
+            cache_key(&r).unwrap()
+        };
+        let bias = biased(&[("1", 100.0), ("2", -100.0), ("3", 5.0)]);
+        assert_ne!(key, bias);
+        assert_eq!(bias, biased(&[("3", 5.0), ("2", -100.0), ("1", 100.0)]));
+        assert_ne!(bias, biased(&[("1", 100.0), ("2", -100.0)]));
+    }
+
+    #[test]
+    fn key_covers_reasoning_effort_and_budget() {
+        let key = cache_key(&request("classify: hello")).unwrap();
+
+        let mut low = request("classify: hello");
+        low.reasoning_effort = Some(ReasoningEffort::Low);
+        let mut high = request("classify: hello");
+        high.reasoning_effort = Some(ReasoningEffort::High);
+        assert_ne!(cache_key(&low).unwrap(), cache_key(&high).unwrap());
+        assert_ne!(key, cache_key(&low).unwrap());
+
+        let mut budget = request("classify: hello");
+        budget.max_reasoning_tokens = Some(1024);
+        assert_ne!(key, cache_key(&budget).unwrap());
+    }
+
+    #[test]
+    fn key_ignores_stream_flag() {
+        let mut streamed = request("x");
+        streamed.stream = Some(true);
+        assert_eq!(cache_key(&request("x")), cache_key(&streamed));
+    }
+
+    #[test]
+    fn only_opted_in_zero_temperature_requests_are_cached() {
+        let mut not_opted = request("x");
+        not_opted.cache = false;
+        assert_eq!(cache_key(&not_opted), None);
+
+        let mut warm = request("x");
+        warm.temperature = Some(0.7);
+        assert_eq!(cache_key(&warm), None);
+
+        let mut default_temp = request("x");
+        default_temp.temperature = None;
+        assert_eq!(cache_key(&default_temp), None);
+
+        let cache = LlmCache::new(8, Duration::from_secs(60));
+        cache.put(&warm, &response("hi"));
+        assert!(cache.is_empty());
+        assert!(cache.get(&warm).is_none());
+    }
+
+    #[test]
+    fn hit_has_zero_cost_cached_usage() {
+        let cache = LlmCache::new(8, Duration::from_secs(60));
+        cache.put(&request("x"), &response("positive"));
+
+        let hit = cache.get(&request("x")).unwrap();
+        assert_eq!(hit.choices[0].message.text().as_deref(), Some("positive"));
+        let usage = hit.usage.unwrap();
+        assert!(usage.cached);
+        assert_eq!(usage.total(), 0);
+    }
+
+    #[test]
+    fn expired_entries_miss() {
+        let cache = LlmCache::new(8, Duration::ZERO);
+        cache.put(&request("x"), &response("positive"));
+        assert!(cache.get(&request("x")).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let cache = LlmCache::new(2, Duration::from_secs(60));
+        cache.put(&request("a"), &response("a"));
+        cache.put(&request("b"), &response("b"));
+        assert!(cache.get(&request("a")).is_some());
+        cache.put(&request("c"), &response("c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&request("a")).is_some());
+        assert!(cache.get(&request("b")).is_none());
+        assert!(cache.get(&request("c")).is_some());
+    }
+
+    #[test]
+    fn entries_persist_to_disk() {
+        let dir = std::env::temp_dir().join(format!("clawft-llm-cache-{}", uuid::Uuid::new_v4()));
+        LlmCache::new(8, Duration::from_secs(60))
+            .with_dir(&dir)
+            .put(&request("x"), &response("positive"));
+
+        let reopened = LlmCache::new(8, Duration::from_secs(60)).with_dir(&dir);
+        let hit = reopened.get(&request("x")).unwrap();
+        assert_eq!(hit.choices[0].message.text().as_deref(), Some("positive"));
+        assert!(hit.usage.unwrap().cached);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -7,6 +7,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub use clawft_types::config::RateLimitConfig;
+use clawft_types::provider::ReasoningEffort;
+
 /// Configuration for a single LLM provider endpoint.
 ///
 /// Renamed from `ProviderConfig` to avoid collision with
@@ -36,9 +39,73 @@ pub struct LlmProviderConfig {
     #[serde(default)]
     pub headers: HashMap<String, String>,
 
-    /// Request timeout in seconds. Defaults to 120.
+    /// Overall request deadline in seconds, covering the whole response
+    /// (streamed or not). Defaults to 120 (300 for local servers).
     #[serde(default)]
     pub timeout_secs: Option<u64>,
+
+    /// Connection timeout in seconds. Defaults to 10.
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Longest gap between two chunks of a streaming response, in seconds.
+    /// Defaults to 60.
+    #[serde(default)]
+    pub read_timeout_secs: Option<u64>,
+
+    /// Default reasoning effort for requests that set none. Only sent to
+    /// models that accept it.
+    #[serde(default)]
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Client-side request limits. Unlimited by default.
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    /// Azure OpenAI addressing. When set, `base_url` is the resource
+    /// endpoint and requests go to the model's deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azure: Option<AzureConfig>,
+}
+
+/// The Azure OpenAI API version used when none is configured.
+pub const AZURE_DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// How to address an Azure OpenAI resource.
+///
+/// Azure serves models from named deployments at
+/// `{endpoint}/openai/deployments/{deployment}/chat/completions?api-version=...`
+/// and authenticates with an `api-key` header instead of a bearer token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AzureConfig {
+    /// Deployment name for each model name. Models without an entry are
+    /// used as the deployment name directly.
+    #[serde(default)]
+    pub deployments: HashMap<String, String>,
+
+    /// The `api-version` query parameter.
+    #[serde(default = "default_azure_api_version")]
+    pub api_version: String,
+}
+
+fn default_azure_api_version() -> String {
+    AZURE_DEFAULT_API_VERSION.into()
+}
+
+impl Default for AzureConfig {
+    fn default() -> Self {
+        Self {
+            deployments: HashMap::new(),
+            api_version: default_azure_api_version(),
+        }
+    }
+}
+
+impl AzureConfig {
+    /// The deployment that serves `model`.
+    pub fn deployment<'a>(&'a self, model: &'a str) -> &'a str {
+        self.deployments.get(model).map_or(model, String::as_str)
+    }
 }
 
 /// Returns the built-in provider configurations.
@@ -55,6 +122,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("gpt-4o".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "anthropic".into(),
@@ -64,6 +136,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("claude-sonnet-4-5-20250514".into()),
             headers: HashMap::from([("anthropic-version".into(), "2023-06-01".into())]),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "groq".into(),
@@ -73,6 +150,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("llama-3.1-70b-versatile".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "deepseek".into(),
@@ -82,6 +164,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("deepseek-chat".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "mistral".into(),
@@ -91,6 +178,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("mistral-large-latest".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "together".into(),
@@ -100,6 +192,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: None,
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "openrouter".into(),
@@ -109,6 +206,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: None,
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "gemini".into(),
@@ -118,6 +220,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("gemini-2.5-flash".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "xai".into(),
@@ -127,6 +234,26 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("grok-3-mini".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
+        },
+        LlmProviderConfig {
+            name: "azure".into(),
+            // The resource endpoint is per-account and comes from config.
+            base_url: String::new(),
+            api_key_env: "AZURE_OPENAI_API_KEY".into(),
+            model_prefix: Some("azure/".into()),
+            default_model: None,
+            headers: HashMap::new(),
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: Some(AzureConfig::default()),
         },
         // ── Local / air-gapped providers ────────────────────────────
         LlmProviderConfig {
@@ -137,6 +264,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("llama3.2".into()),
             headers: HashMap::new(),
             timeout_secs: Some(300),
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
         LlmProviderConfig {
             name: "ollama".into(),
@@ -146,6 +278,11 @@ pub fn builtin_providers() -> Vec<LlmProviderConfig> {
             default_model: Some("llama3.2".into()),
             headers: HashMap::new(),
             timeout_secs: Some(300),
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         },
     ]
 }
@@ -157,7 +294,7 @@ mod tests {
     #[test]
     fn builtin_providers_count() {
         let providers = builtin_providers();
-        assert_eq!(providers.len(), 11);
+        assert_eq!(providers.len(), 12);
     }
 
     #[test]
@@ -173,6 +310,7 @@ mod tests {
         assert!(names.contains(&"openrouter"));
         assert!(names.contains(&"gemini"));
         assert!(names.contains(&"xai"));
+        assert!(names.contains(&"azure"));
         assert!(names.contains(&"local"));
         assert!(names.contains(&"ollama"));
     }
@@ -243,6 +381,11 @@ mod tests {
             default_model: Some("test-model".into()),
             headers: HashMap::from([("X-Custom".into(), "value".into())]),
             timeout_secs: Some(60),
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         };
         let json = serde_json::to_string(&config).unwrap();
         let parsed: LlmProviderConfig = serde_json::from_str(&json).unwrap();
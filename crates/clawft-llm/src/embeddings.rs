@@ -0,0 +1,432 @@
+//! Text embeddings.
+//!
+//! [`EmbeddingsProvider`] is the embeddings counterpart of
+//! [`Provider`](crate::provider::Provider): it turns a list of texts into
+//! vectors for a given model. [`OpenAiCompatEmbeddings`] implements it for
+//! any endpoint that follows the OpenAI `/embeddings` format, splitting large
+//! inputs into batches that respect both an item limit and an estimated
+//! token limit per request.
+
+use std::ops::Range;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::config::LlmProviderConfig;
+use crate::error::{ProviderError, Result};
+use crate::openai_compat::error_from_response;
+use crate::timeouts::Timeouts;
+use crate::types::Usage;
+
+/// Default timeout for embeddings requests (1 minute).
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// The result of embedding a list of texts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingResponse {
+    /// One vector per input text, in input order. All have the same length.
+    pub embeddings: Vec<Vec<f32>>,
+
+    /// The model that produced the vectors, as reported by the provider.
+    pub model: String,
+
+    /// Token usage summed over all batches, if the provider reported it.
+    /// Embeddings have no output, so only `input_tokens` and `total_tokens`
+    /// are set.
+    pub usage: Option<Usage>,
+}
+
+impl EmbeddingResponse {
+    /// The dimension of the returned vectors (0 when there are none).
+    pub fn dimension(&self) -> usize {
+        self.embeddings.first().map_or(0, Vec::len)
+    }
+}
+
+/// A provider that can embed text.
+#[async_trait]
+pub trait EmbeddingsProvider: Send + Sync {
+    /// Provider name, matching the [`LlmProviderConfig`] it was built from.
+    fn name(&self) -> &str;
+
+    /// Embed `texts` with `model` (the bare model name, without a routing
+    /// prefix).
+    ///
+    /// Returns exactly one vector per text, in order, all of the same
+    /// dimension. An empty input returns an empty response without a request.
+    async fn embed(&self, texts: &[String], model: &str) -> Result<EmbeddingResponse>;
+}
+
+/// Batching and shape options for [`OpenAiCompatEmbeddings`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingsOptions {
+    /// Maximum number of texts per request.
+    pub batch_size: usize,
+
+    /// Maximum estimated input tokens per request. Batches are cut before
+    /// they would exceed it; a single text over the limit is sent alone and
+    /// left for the provider to accept or reject.
+    pub max_batch_tokens: usize,
+
+    /// Requested vector dimension, for models that can shorten their output
+    /// (`text-embedding-3-*`). Responses of any other size are rejected.
+    pub dimensions: Option<usize>,
+}
+
+impl Default for EmbeddingsOptions {
+    /// Defaults stay under OpenAI's limits of 2048 inputs and 300k tokens
+    /// per request.
+    fn default() -> Self {
+        Self {
+            batch_size: 256,
+            max_batch_tokens: 250_000,
+            dimensions: None,
+        }
+    }
+}
+
+/// An [`EmbeddingsProvider`] for OpenAI-compatible `/embeddings` endpoints.
+pub struct OpenAiCompatEmbeddings {
+    config: LlmProviderConfig,
+    options: EmbeddingsOptions,
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatEmbeddings {
+    /// Create an embeddings client from provider configuration.
+    ///
+    /// The API key will be resolved from the environment variable specified
+    /// in `config.api_key_env` at request time.
+    pub fn new(config: LlmProviderConfig) -> Self {
+        Self::build(config, None)
+    }
+
+    /// Create an embeddings client with an explicit API key.
+    pub fn with_api_key(config: LlmProviderConfig, api_key: String) -> Self {
+        Self::build(config, Some(api_key))
+    }
+
+    fn build(config: LlmProviderConfig, api_key: Option<String>) -> Self {
+        Self {
+            http: Timeouts::new(&config, DEFAULT_TIMEOUT_SECS).client(),
+            config,
+            options: EmbeddingsOptions::default(),
+            api_key,
+        }
+    }
+
+    /// Replace the batching options.
+    pub fn with_options(mut self, options: EmbeddingsOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the batching options.
+    pub fn options(&self) -> &EmbeddingsOptions {
+        &self.options
+    }
+
+    /// Returns the embeddings endpoint URL.
+    fn embeddings_url(&self) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        format!("{base}/embeddings")
+    }
+
+    /// Resolve the API key: explicit key > environment variable.
+    fn resolve_api_key(&self) -> Result<String> {
+        if let Some(ref key) = self.api_key {
+            return Ok(key.clone());
+        }
+        std::env::var(&self.config.api_key_env).map_err(|_| {
+            ProviderError::NotConfigured(format!("set {} env var", self.config.api_key_env))
+        })
+    }
+
+    /// Send one batch and return its vectors in input order.
+    async fn embed_batch(
+        &self,
+        api_key: &str,
+        texts: &[String],
+        model: &str,
+    ) -> Result<(Vec<Vec<f32>>, String, Option<Usage>)> {
+        let mut body = serde_json::json!({
+            "input": texts,
+            "model": model,
+            "encoding_format": "float",
+        });
+        if let Some(dimensions) = self.options.dimensions {
+            body["dimensions"] = serde_json::json!(dimensions);
+        }
+
+        let mut req = self
+            .http
+            .post(self.embeddings_url())
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header("Content-Type", "application/json");
+        for (k, v) in &self.config.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+
+        let response = req.json(&body).send().await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, model, response).await);
+        }
+
+        let mut parsed: WireResponse = response.json().await.map_err(|e| {
+            ProviderError::InvalidResponse(format!("failed to parse embeddings response: {e}"))
+        })?;
+        if parsed.data.len() != texts.len() {
+            return Err(ProviderError::InvalidResponse(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                parsed.data.len()
+            )));
+        }
+        parsed.data.sort_by_key(|item| item.index);
+
+        let usage = parsed.usage.map(|u| Usage {
+            input_tokens: u.prompt_tokens,
+            output_tokens: 0,
+            total_tokens: u.total_tokens,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
+        });
+        let vectors = parsed.data.into_iter().map(|item| item.embedding).collect();
+        Ok((
+            vectors,
+            parsed.model.unwrap_or_else(|| model.to_string()),
+            usage,
+        ))
+    }
+}
+
+#[async_trait]
+impl EmbeddingsProvider for OpenAiCompatEmbeddings {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn embed(&self, texts: &[String], model: &str) -> Result<EmbeddingResponse> {
+        if texts.is_empty() {
+            return Ok(EmbeddingResponse {
+                embeddings: Vec::new(),
+                model: model.to_string(),
+                usage: None,
+            });
+        }
+        let api_key = self.resolve_api_key()?;
+        let batches = plan_batches(
+            texts,
+            self.options.batch_size,
+            self.options.max_batch_tokens,
+        );
+
+        debug!(
+            provider = %self.config.name,
+            model = %model,
+            texts = texts.len(),
+            batches = batches.len(),
+            "sending embeddings request"
+        );
+
+        let mut embeddings = Vec::with_capacity(texts.len());
+        let mut usage: Option<Usage> = None;
+        let mut reported_model = model.to_string();
+        for range in batches {
+            let (vectors, batch_model, batch_usage) =
+                self.embed_batch(&api_key, &texts[range], model).await?;
+            embeddings.extend(vectors);
+            reported_model = batch_model;
+            if let Some(b) = batch_usage {
+                let total = usage.get_or_insert_with(Usage::default);
+                total.input_tokens += b.input_tokens;
+                total.total_tokens += b.total_tokens;
+            }
+        }
+
+        check_dimensions(&embeddings, self.options.dimensions)?;
+        Ok(EmbeddingResponse {
+            embeddings,
+            model: reported_model,
+            usage,
+        })
+    }
+}
+
+impl std::fmt::Debug for OpenAiCompatEmbeddings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenAiCompatEmbeddings")
+            .field("name", &self.config.name)
+            .field("base_url", &self.config.base_url)
+            .field("options", &self.options)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WireResponse {
+    data: Vec<WireEmbedding>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<WireUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WireEmbedding {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WireUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+/// Rough token estimate for batching: about four bytes per token, and at
+/// least one token per text.
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4).max(1)
+}
+
+/// Split `texts` into consecutive ranges of at most `batch_size` items and
+/// `max_batch_tokens` estimated tokens each.
+fn plan_batches(texts: &[String], batch_size: usize, max_batch_tokens: usize) -> Vec<Range<usize>> {
+    let batch_size = batch_size.max(1);
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (i, text) in texts.iter().enumerate() {
+        let cost = estimate_tokens(text);
+        let full = i - start >= batch_size || tokens + cost > max_batch_tokens;
+        if i > start && full {
+            batches.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += cost;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Check that every vector has the same dimension, and the requested one if
+/// a dimension was asked for.
+fn check_dimensions(embeddings: &[Vec<f32>], expected: Option<usize>) -> Result<()> {
+    let Some(first) = embeddings.first() else {
+        return Ok(());
+    };
+    let dim = first.len();
+    if dim == 0 {
+        return Err(ProviderError::InvalidResponse(
+            "empty embedding vector".into(),
+        ));
+    }
+    if let Some(expected) = expected
+        && dim != expected
+    {
+        return Err(ProviderError::InvalidResponse(format!(
+            "requested {expected}-dimensional embeddings, got {dim}"
+        )));
+    }
+    if let Some((i, v)) = embeddings.iter().enumerate().find(|(_, v)| v.len() != dim) {
+        return Err(ProviderError::InvalidResponse(format!(
+            "embedding {i} has dimension {}, expected {dim}",
+            v.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lens: &[usize]) -> Vec<String> {
+        lens.iter().map(|&n| "x".repeat(n)).collect()
+    }
+
+    #[test]
+    fn batches_split_on_item_count() {
+        let input = texts(&[4; 5]);
+        assert_eq!(plan_batches(&input, 2, 1000), vec![0..2, 2..4, 4..5]);
+        assert_eq!(plan_batches(&input, 10, 1000), vec![0..5]);
+    }
+
+    #[test]
+    fn batches_split_on_token_budget() {
+        // 40 bytes is 10 estimated tokens per text.
+        let input = texts(&[40, 40, 40, 40]);
+        assert_eq!(plan_batches(&input, 100, 25), vec![0..2, 2..4]);
+        assert_eq!(plan_batches(&input, 100, 10), vec![0..1, 1..2, 2..3, 3..4]);
+    }
+
+    #[test]
+    fn oversized_text_goes_alone() {
+        let input = texts(&[4, 400, 4]);
+        assert_eq!(plan_batches(&input, 100, 10), vec![0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn no_texts_no_batches() {
+        assert!(plan_batches(&[], 10, 10).is_empty());
+        assert_eq!(plan_batches(&texts(&[1, 1]), 0, 10), vec![0..1, 1..2]);
+    }
+
+    #[test]
+    fn dimension_checks() {
+        let same = vec![vec![0.0; 3], vec![1.0; 3]];
+        assert!(check_dimensions(&same, None).is_ok());
+        assert!(check_dimensions(&same, Some(3)).is_ok());
+        assert!(check_dimensions(&[], Some(3)).is_ok());
+
+        let err = check_dimensions(&same, Some(4)).unwrap_err();
+        assert!(err.to_string().contains("requested 4"));
+
+        let mixed = vec![vec![0.0; 3], vec![0.0; 3], vec![0.0; 2]];
+        let err = check_dimensions(&mixed, None).unwrap_err();
+        assert!(err.to_string().contains("embedding 2 has dimension 2"));
+
+        assert!(check_dimensions(&[vec![]], None).is_err());
+    }
+
+    #[test]
+    fn wire_response_is_reordered_by_index() {
+        let mut parsed: WireResponse = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [
+                {"object": "embedding", "index": 1, "embedding": [0.5, 0.5]},
+                {"object": "embedding", "index": 0, "embedding": [1.0, 0.0]}
+            ],
+            "model": "text-embedding-3-small",
+            "usage": {"prompt_tokens": 6, "total_tokens": 6}
+        }))
+        .unwrap();
+        parsed.data.sort_by_key(|item| item.index);
+        assert_eq!(parsed.data[0].embedding, vec![1.0, 0.0]);
+        assert_eq!(parsed.usage.unwrap().prompt_tokens, 6);
+    }
+
+    #[tokio::test]
+    async fn empty_input_makes_no_request() {
+        let config = LlmProviderConfig {
+            api_key_env: "CLAWFT_TEST_UNSET_EMBEDDINGS_KEY".into(),
+            ..crate::config::builtin_providers().remove(0)
+        };
+        let embedder = OpenAiCompatEmbeddings::new(config);
+        let resp = embedder.embed(&[], "text-embedding-3-small").await.unwrap();
+        assert!(resp.embeddings.is_empty());
+        assert_eq!(resp.dimension(), 0);
+    }
+}
@@ -35,13 +35,20 @@ pub enum ProviderError {
     #[error("invalid response: {0}")]
     InvalidResponse(String),
 
-    /// The request timed out.
+    /// The model cannot handle the request (e.g. image input sent to a
+    /// text-only model).
+    #[error("unsupported request: {0}")]
+    Unsupported(String),
+
+    /// The request timed out: connecting, waiting for the overall
+    /// deadline, or waiting for the next chunk of a stream.
     #[error("timeout")]
     Timeout,
 
-    /// An HTTP-level error from reqwest.
+    /// An HTTP-level error from reqwest. Timeouts convert to
+    /// [`ProviderError::Timeout`] instead.
     #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(#[source] reqwest::Error),
 
     /// A JSON serialization/deserialization error.
     #[error("json error: {0}")]
@@ -64,6 +71,16 @@ pub enum ProviderError {
     },
 }
 
+impl From<reqwest::Error> for ProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Self::Timeout
+        } else {
+            Self::Http(err)
+        }
+    }
+}
+
 /// A convenience type alias for provider operations.
 pub type Result<T> = std::result::Result<T, ProviderError>;
 
@@ -5,15 +5,105 @@
 //! If one provider fails with a retryable error after exhausting its retries,
 //! the chain moves to the next provider. Non-retryable errors (auth failure,
 //! model not found) are returned immediately.
+//!
+//! Each provider has a circuit breaker. After
+//! [`CircuitConfig::failure_threshold`] consecutive failures its circuit
+//! opens and the chain skips it without a call for
+//! [`CircuitConfig::cooldown`], so a dead primary does not add its timeout
+//! to every request. Once the cooldown passes the circuit is half-open: one
+//! request probes the provider, closing the circuit on success and reopening
+//! it on failure.
+//!
+//! Fallback providers often name the same model differently. A model map
+//! (see [`FailoverChain::map_model`]) translates the requested model per
+//! provider; without an entry, an `openrouter` fallback is sent the model
+//! prefixed with the primary's name (`gpt-4o` becomes `openai/gpt-4o`).
+//!
+//! Every provider passed over is logged and recorded as a
+//! [`FailoverEvent`] on the response that was eventually returned.
+//!
+//! Streaming requests are forwarded live. A provider that fails mid-stream
+//! is only replaced by the next one if it had not emitted any content yet.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::error::{ProviderError, Result};
-use crate::provider::Provider;
+use crate::provider::{Provider, forward_stream};
+use crate::rate_limit::{Clock, SystemClock};
 use crate::retry::is_retryable;
-use crate::types::{ChatRequest, ChatResponse, StreamChunk};
+use crate::types::{ChatRequest, ChatResponse, FailoverEvent, FailoverReason, StreamChunk};
+
+/// Circuit breaker settings applied to every provider in a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitConfig {
+    /// Consecutive failures that open a provider's circuit.
+    pub failure_threshold: u32,
+
+    /// How long an open circuit skips its provider before a probe.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a provider's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go to the provider.
+    Closed,
+    /// The provider is skipped until the cooldown passes.
+    Open,
+    /// The cooldown has passed; the next request probes the provider.
+    HalfOpen,
+}
+
+/// Health of one provider in a [`FailoverChain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// Provider name.
+    pub provider: String,
+    /// Current circuit state.
+    pub state: CircuitState,
+    /// Failures since the provider last succeeded.
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    failures: u32,
+    open_until: Option<Instant>,
+    /// When the current half-open probe started. A probe older than the
+    /// cooldown is treated as abandoned.
+    probe_started: Option<Instant>,
+}
+
+/// Whether the chain may call a provider.
+enum Admission {
+    Call { probe: bool },
+    Skip(FailoverReason),
+}
+
+/// How a call affects its provider's health.
+enum Outcome {
+    Success,
+    Failure,
+    /// The call failed for a reason that says nothing about the provider's
+    /// health (for example bad credentials).
+    Neutral,
+}
 
 /// A chain of providers that fails over to the next on transient errors.
 ///
@@ -28,11 +118,18 @@ use crate::types::{ChatRequest, ChatResponse, StreamChunk};
 ///     Box::new(RetryPolicy::new(primary, RetryConfig::default())),
 ///     Box::new(RetryPolicy::new(fallback, RetryConfig::default())),
 /// ];
-/// let chain = FailoverChain::new(providers);
+/// let chain = FailoverChain::new(providers)
+///     .unwrap()
+///     .map_model("openrouter", "gpt-4o", "openai/gpt-4o");
 /// let response = chain.complete(&request).await?;
 /// ```
 pub struct FailoverChain {
     providers: Vec<Box<dyn Provider>>,
+    circuit: CircuitConfig,
+    clock: Arc<dyn Clock>,
+    health: Mutex<Vec<Health>>,
+    /// `(provider, requested model)` to the model that provider is sent.
+    models: HashMap<(String, String), String>,
 }
 
 impl FailoverChain {
@@ -44,7 +141,38 @@ impl FailoverChain {
         if providers.is_empty() {
             return None;
         }
-        Some(Self { providers })
+        let health = providers.iter().map(|_| Health::default()).collect();
+        Some(Self {
+            providers,
+            circuit: CircuitConfig::default(),
+            clock: Arc::new(SystemClock),
+            health: Mutex::new(health),
+            models: HashMap::new(),
+        })
+    }
+
+    /// Use `circuit` instead of the default circuit breaker settings.
+    pub fn with_circuit(mut self, circuit: CircuitConfig) -> Self {
+        self.circuit = circuit;
+        self
+    }
+
+    /// Use `clock` to time circuit cooldowns.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Send `to_model` to `provider` when `from_model` is requested.
+    pub fn map_model(
+        mut self,
+        provider: impl Into<String>,
+        from_model: impl Into<String>,
+        to_model: impl Into<String>,
+    ) -> Self {
+        self.models
+            .insert((provider.into(), from_model.into()), to_model.into());
+        self
     }
 
     /// Returns the number of providers in the chain.
@@ -61,6 +189,144 @@ impl FailoverChain {
     pub fn provider_names(&self) -> Vec<&str> {
         self.providers.iter().map(|p| p.name()).collect()
     }
+
+    /// Circuit state of every provider, in chain order.
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        let now = self.clock.now();
+        self.lock_health()
+            .iter()
+            .zip(&self.providers)
+            .map(|(health, provider)| ProviderHealth {
+                provider: provider.name().to_owned(),
+                state: match health.open_until {
+                    None => CircuitState::Closed,
+                    Some(until) if now < until => CircuitState::Open,
+                    Some(_) => CircuitState::HalfOpen,
+                },
+                consecutive_failures: health.failures,
+            })
+            .collect()
+    }
+
+    /// The model `provider` should be sent for `model`.
+    fn model_for<'a>(&'a self, idx: usize, model: &'a str) -> Cow<'a, str> {
+        let name = self.providers[idx].name();
+        if let Some(mapped) = self.models.get(&(name.to_owned(), model.to_owned())) {
+            return Cow::Borrowed(mapped);
+        }
+        if idx > 0 && name == "openrouter" && !model.contains('/') {
+            return Cow::Owned(format!("{}/{model}", self.providers[0].name()));
+        }
+        Cow::Borrowed(model)
+    }
+
+    /// `request` with its model translated for provider `idx`.
+    fn request_for<'a>(&'a self, idx: usize, request: &'a ChatRequest) -> Cow<'a, ChatRequest> {
+        match self.model_for(idx, &request.model) {
+            Cow::Borrowed(model) if model == request.model => Cow::Borrowed(request),
+            model => {
+                let mut mapped = request.clone();
+                mapped.model = model.into_owned();
+                Cow::Owned(mapped)
+            }
+        }
+    }
+
+    fn admit(&self, idx: usize) -> Admission {
+        let now = self.clock.now();
+        let mut health = self.lock_health();
+        let health = &mut health[idx];
+        match health.open_until {
+            None => Admission::Call { probe: false },
+            Some(until) if now < until => Admission::Skip(FailoverReason::CircuitOpen),
+            Some(_) => match health.probe_started {
+                Some(started) if now < started + self.circuit.cooldown => {
+                    Admission::Skip(FailoverReason::ProbeInFlight)
+                }
+                _ => {
+                    health.probe_started = Some(now);
+                    Admission::Call { probe: true }
+                }
+            },
+        }
+    }
+
+    fn settle(&self, idx: usize, probe: bool, outcome: Outcome) {
+        let now = self.clock.now();
+        let mut health = self.lock_health();
+        let health = &mut health[idx];
+        if probe {
+            health.probe_started = None;
+        }
+        match outcome {
+            Outcome::Success => *health = Health::default(),
+            Outcome::Failure => {
+                health.failures += 1;
+                if probe || health.failures >= self.circuit.failure_threshold {
+                    health.open_until = Some(now + self.circuit.cooldown);
+                    warn!(
+                        provider = %self.providers[idx].name(),
+                        consecutive_failures = health.failures,
+                        cooldown_secs = self.circuit.cooldown.as_secs(),
+                        "provider circuit opened"
+                    );
+                }
+            }
+            Outcome::Neutral => {}
+        }
+    }
+
+    fn lock_health(&self) -> MutexGuard<'_, Vec<Health>> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record that provider `idx` was passed over.
+    fn pass_over(
+        &self,
+        events: &mut Vec<FailoverEvent>,
+        idx: usize,
+        model: &str,
+        reason: FailoverReason,
+        error: Option<&ProviderError>,
+    ) {
+        let provider = self.providers[idx].name();
+        match error {
+            Some(err) => warn!(
+                provider = %provider,
+                provider_index = idx,
+                total_providers = self.providers.len(),
+                model = %model,
+                reason = ?reason,
+                error = %err,
+                "provider failed, trying next in failover chain"
+            ),
+            None => info!(
+                provider = %provider,
+                provider_index = idx,
+                model = %model,
+                reason = ?reason,
+                "skipping provider in failover chain"
+            ),
+        }
+        events.push(FailoverEvent {
+            provider: provider.to_owned(),
+            model: model.to_owned(),
+            reason,
+            error: error.map(ToString::to_string),
+        });
+    }
+}
+
+/// The error returned once every provider has been passed over.
+fn exhausted(events: Vec<FailoverEvent>) -> ProviderError {
+    let attempts = events
+        .into_iter()
+        .map(|event| match event.error {
+            Some(error) => format!("{}: {error}", event.provider),
+            None => format!("{}: skipped ({:?})", event.provider, event.reason),
+        })
+        .collect();
+    ProviderError::AllProvidersExhausted { attempts }
 }
 
 #[async_trait]
@@ -74,40 +340,41 @@ impl Provider for FailoverChain {
     }
 
     async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
-        let mut errors: Vec<(String, ProviderError)> = Vec::new();
+        let mut events: Vec<FailoverEvent> = Vec::new();
 
         for (idx, provider) in self.providers.iter().enumerate() {
-            match provider.complete(request).await {
-                Ok(response) => return Ok(response),
-                Err(err) => {
-                    let provider_name = provider.name().to_owned();
+            let request = self.request_for(idx, request);
+            let probe = match self.admit(idx) {
+                Admission::Call { probe } => probe,
+                Admission::Skip(reason) => {
+                    self.pass_over(&mut events, idx, &request.model, reason, None);
+                    continue;
+                }
+            };
 
+            match provider.complete(&request).await {
+                Ok(mut response) => {
+                    self.settle(idx, probe, Outcome::Success);
+                    events.append(&mut response.failover);
+                    response.failover = events;
+                    return Ok(response);
+                }
+                Err(err) => {
                     // If the error is not retryable, don't try fallback providers
                     // for certain error classes
                     if !is_retryable(&err) && !is_failover_eligible(&err) {
+                        self.settle(idx, probe, Outcome::Neutral);
                         return Err(err);
                     }
 
-                    warn!(
-                        provider = %provider_name,
-                        provider_index = idx,
-                        total_providers = self.providers.len(),
-                        error = %err,
-                        "provider failed, trying next in failover chain"
-                    );
-
-                    errors.push((provider_name, err));
+                    self.settle(idx, probe, Outcome::Failure);
+                    let reason = FailoverReason::Error;
+                    self.pass_over(&mut events, idx, &request.model, reason, Some(&err));
                 }
             }
         }
 
-        // All providers exhausted
-        let summary: Vec<String> = errors
-            .iter()
-            .map(|(name, err)| format!("{name}: {err}"))
-            .collect();
-
-        Err(ProviderError::AllProvidersExhausted { attempts: summary })
+        Err(exhausted(events))
     }
 
     async fn complete_stream(
@@ -115,52 +382,58 @@ impl Provider for FailoverChain {
         request: &ChatRequest,
         tx: mpsc::Sender<StreamChunk>,
     ) -> Result<()> {
-        let mut errors: Vec<(String, ProviderError)> = Vec::new();
+        let mut events: Vec<FailoverEvent> = Vec::new();
 
         for (idx, provider) in self.providers.iter().enumerate() {
-            // Buffer chunks per attempt to prevent partial output concatenation.
-            // Only forward buffered chunks to the real sender on success.
-            let (attempt_tx, mut attempt_rx) = mpsc::channel::<StreamChunk>(256);
+            let request = self.request_for(idx, request);
+            let probe = match self.admit(idx) {
+                Admission::Call { probe } => probe,
+                Admission::Skip(reason) => {
+                    self.pass_over(&mut events, idx, &request.model, reason, None);
+                    continue;
+                }
+            };
+
+            // Chunks are forwarded live. A failed attempt can only be handed
+            // to the next provider if it emitted no content yet; otherwise
+            // the consumer would see partial output from two providers.
+            let (result, emitted) = forward_stream(provider.as_ref(), &request, &tx).await;
 
-            match provider.complete_stream(request, attempt_tx).await {
+            match result {
                 Ok(()) => {
-                    // Success: forward all buffered chunks to the real sender.
-                    while let Some(chunk) = attempt_rx.recv().await {
-                        if tx.send(chunk).await.is_err() {
-                            break;
-                        }
-                    }
+                    self.settle(idx, probe, Outcome::Success);
                     return Ok(());
                 }
                 Err(err) => {
-                    let provider_name = provider.name().to_owned();
-
-                    // Discard buffered chunks from this failed attempt by
-                    // dropping attempt_rx (already moved out of scope).
+                    if emitted {
+                        self.settle(idx, probe, Outcome::Failure);
+                        warn!(
+                            provider = %provider.name(),
+                            provider_index = idx,
+                            error = %err,
+                            "provider failed mid-stream after emitting content, not failing over"
+                        );
+                        return Err(err);
+                    }
 
                     if !is_retryable(&err) && !is_failover_eligible(&err) {
+                        self.settle(idx, probe, Outcome::Neutral);
                         return Err(err);
                     }
 
-                    warn!(
-                        provider = %provider_name,
-                        provider_index = idx,
-                        total_providers = self.providers.len(),
-                        error = %err,
-                        "provider streaming failed, trying next in failover chain"
-                    );
-
-                    errors.push((provider_name, err));
+                    self.settle(idx, probe, Outcome::Failure);
+                    let reason = FailoverReason::Error;
+                    self.pass_over(&mut events, idx, &request.model, reason, Some(&err));
                 }
             }
         }
 
-        let summary: Vec<String> = errors
-            .iter()
-            .map(|(name, err)| format!("{name}: {err}"))
-            .collect();
+        Err(exhausted(events))
+    }
 
-        Err(ProviderError::AllProvidersExhausted { attempts: summary })
+    /// Lists the primary provider's models.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.providers[0].list_models().await
     }
 }
 
@@ -177,6 +450,7 @@ fn is_failover_eligible(err: &ProviderError) -> bool {
             | ProviderError::ModelNotFound(_)
             | ProviderError::RequestFailed(_)
             | ProviderError::InvalidResponse(_)
+            | ProviderError::Unsupported(_)
     )
 }
 
@@ -184,6 +458,7 @@ impl std::fmt::Debug for FailoverChain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FailoverChain")
             .field("providers", &self.provider_names())
+            .field("circuit", &self.circuit)
             .finish()
     }
 }
@@ -211,8 +486,14 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 5,
                 total_tokens: 15,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             }),
             model: model.into(),
+            failover: Vec::new(),
+            retries: 0,
         }
     }
 
@@ -289,7 +570,14 @@ mod tests {
         .unwrap();
 
         let resp = chain.complete(&test_request()).await.unwrap();
-        assert!(resp.choices[0].message.content.as_deref().unwrap().contains("primary"));
+        assert!(
+            resp.choices[0]
+                .message
+                .text()
+                .as_deref()
+                .unwrap()
+                .contains("primary")
+        );
     }
 
     #[tokio::test]
@@ -309,7 +597,14 @@ mod tests {
         .unwrap();
 
         let resp = chain.complete(&test_request()).await.unwrap();
-        assert!(resp.choices[0].message.content.as_deref().unwrap().contains("backup"));
+        assert!(
+            resp.choices[0]
+                .message
+                .text()
+                .as_deref()
+                .unwrap()
+                .contains("backup")
+        );
     }
 
     #[tokio::test]
@@ -326,7 +621,14 @@ mod tests {
         .unwrap();
 
         let resp = chain.complete(&test_request()).await.unwrap();
-        assert!(resp.choices[0].message.content.as_deref().unwrap().contains("configured"));
+        assert!(
+            resp.choices[0]
+                .message
+                .text()
+                .as_deref()
+                .unwrap()
+                .contains("configured")
+        );
     }
 
     #[tokio::test]
@@ -410,7 +712,14 @@ mod tests {
         .unwrap();
 
         let resp = chain.complete(&test_request()).await.unwrap();
-        assert!(resp.choices[0].message.content.as_deref().unwrap().contains("p3"));
+        assert!(
+            resp.choices[0]
+                .message
+                .text()
+                .as_deref()
+                .unwrap()
+                .contains("p3")
+        );
     }
 
     #[test]
@@ -455,7 +764,14 @@ mod tests {
         .unwrap();
 
         let resp = chain.complete(&test_request()).await.unwrap();
-        assert!(resp.choices[0].message.content.as_deref().unwrap().contains("free"));
+        assert!(
+            resp.choices[0]
+                .message
+                .text()
+                .as_deref()
+                .unwrap()
+                .contains("free")
+        );
     }
 
     #[test]
@@ -464,4 +780,404 @@ mod tests {
             "bad key".into()
         )));
     }
+
+    /// Streams `chunks` text deltas, then fails with a 503 if `fail` is set.
+    struct StreamProvider {
+        name: String,
+        chunks: usize,
+        fail: bool,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl StreamProvider {
+        fn new(name: &str, chunks: usize, fail: bool) -> (Self, Arc<AtomicU32>) {
+            let calls = Arc::new(AtomicU32::new(0));
+            let provider = Self {
+                name: name.into(),
+                chunks,
+                fail,
+                calls: calls.clone(),
+            };
+            (provider, calls)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for StreamProvider {
+        fn name(&self) -> &str {
+            &self.name
+        }
+        async fn complete(&self, _req: &ChatRequest) -> Result<ChatResponse> {
+            Ok(success_response(&self.name))
+        }
+        async fn complete_stream(
+            &self,
+            _req: &ChatRequest,
+            tx: mpsc::Sender<StreamChunk>,
+        ) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            for i in 0..self.chunks {
+                let text = format!("{}{i} ", self.name);
+                let _ = tx.send(StreamChunk::TextDelta { text }).await;
+            }
+            if self.fail {
+                return Err(ProviderError::ServerError {
+                    status: 503,
+                    body: "dropped".into(),
+                });
+            }
+            let _ = tx
+                .send(StreamChunk::Done {
+                    finish_reason: Some("stop".into()),
+                    usage: None,
+                })
+                .await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_fails_over_when_nothing_was_emitted() {
+        let (broken, broken_calls) = StreamProvider::new("a", 0, true);
+        let (backup, backup_calls) = StreamProvider::new("b", 2, false);
+        let chain = FailoverChain::new(vec![Box::new(broken), Box::new(backup)]).unwrap();
+
+        let mut text = String::new();
+        let resp = chain
+            .complete_streaming(&test_request(), &mut |chunk| {
+                if let StreamChunk::TextDelta { text: t } = chunk {
+                    text.push_str(t);
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(text, "b0 b1 ");
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("b0 b1 "));
+        assert_eq!(broken_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_does_not_fail_over_after_content() {
+        let (broken, _) = StreamProvider::new("a", 1, true);
+        let (backup, backup_calls) = StreamProvider::new("b", 2, false);
+        let chain = FailoverChain::new(vec![Box::new(broken), Box::new(backup)]).unwrap();
+
+        let mut text = String::new();
+        let err = chain
+            .complete_streaming(&test_request(), &mut |chunk| {
+                if let StreamChunk::TextDelta { text: t } = chunk {
+                    text.push_str(t);
+                }
+            })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ProviderError::ServerError { status: 503, .. }
+        ));
+        assert_eq!(text, "a0 ");
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn stream_forwards_chunks_live() {
+        // More chunks than any internal buffer: only works if chunks flow
+        // to the consumer while the provider is still producing.
+        let (long, _) = StreamProvider::new("x", 1000, false);
+        let chain = FailoverChain::new(vec![Box::new(long)]).unwrap();
+
+        let mut count = 0;
+        chain
+            .complete_streaming(&test_request(), &mut |chunk| {
+                if matches!(chunk, StreamChunk::TextDelta { .. }) {
+                    count += 1;
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 1000);
+    }
+
+    /// A clock that only moves when the test advances it.
+    struct ManualClock(std::sync::Mutex<Instant>);
+
+    impl ManualClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self(std::sync::Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+
+        fn sleep(&self, _duration: Duration) -> futures_util::future::BoxFuture<'static, ()> {
+            Box::pin(async {})
+        }
+    }
+
+    /// A provider whose availability the test toggles, recording each call.
+    struct FlappingProvider {
+        name: String,
+        down: Arc<std::sync::atomic::AtomicBool>,
+        models: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl FlappingProvider {
+        fn new(
+            name: &str,
+        ) -> (
+            Self,
+            Arc<std::sync::atomic::AtomicBool>,
+            Arc<std::sync::Mutex<Vec<String>>>,
+        ) {
+            let down = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let models = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let provider = Self {
+                name: name.into(),
+                down: down.clone(),
+                models: models.clone(),
+            };
+            (provider, down, models)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for FlappingProvider {
+        fn name(&self) -> &str {
+            &self.name
+        }
+        async fn complete(&self, req: &ChatRequest) -> Result<ChatResponse> {
+            self.models.lock().unwrap().push(req.model.clone());
+            if self.down.load(Ordering::SeqCst) {
+                return Err(ProviderError::Timeout);
+            }
+            Ok(success_response(&self.name))
+        }
+    }
+
+    fn circuit(failure_threshold: u32) -> CircuitConfig {
+        CircuitConfig {
+            failure_threshold,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    #[tokio::test]
+    async fn failover_events_recorded_on_response() {
+        let chain = FailoverChain::new(vec![
+            Box::new(FailProvider {
+                name: "primary".into(),
+                error: || ProviderError::Timeout,
+            }),
+            Box::new(SuccessProvider {
+                name: "fallback".into(),
+            }),
+        ])
+        .unwrap();
+
+        let resp = chain.complete(&test_request()).await.unwrap();
+        assert_eq!(resp.failover.len(), 1);
+        let event = &resp.failover[0];
+        assert_eq!(event.provider, "primary");
+        assert_eq!(event.model, "test-model");
+        assert_eq!(event.reason, FailoverReason::Error);
+        assert_eq!(event.error.as_deref(), Some("timeout"));
+
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["failover"][0]["reason"], "error");
+    }
+
+    #[tokio::test]
+    async fn primary_success_has_no_failover_events() {
+        let chain = FailoverChain::new(vec![Box::new(SuccessProvider {
+            name: "primary".into(),
+        })])
+        .unwrap();
+        let resp = chain.complete(&test_request()).await.unwrap();
+        assert!(resp.failover.is_empty());
+        assert!(
+            serde_json::to_value(&resp)
+                .unwrap()
+                .get("failover")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_threshold_and_skips_primary() {
+        let (primary, down, calls) = FlappingProvider::new("primary");
+        down.store(true, Ordering::SeqCst);
+        let chain = FailoverChain::new(vec![
+            Box::new(primary),
+            Box::new(SuccessProvider {
+                name: "fallback".into(),
+            }),
+        ])
+        .unwrap()
+        .with_circuit(circuit(2))
+        .with_clock(ManualClock::new());
+
+        for _ in 0..2 {
+            chain.complete(&test_request()).await.unwrap();
+        }
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        assert_eq!(chain.health()[0].state, CircuitState::Open);
+        assert_eq!(chain.health()[0].consecutive_failures, 2);
+
+        // Open circuit: the primary is skipped without a call.
+        let resp = chain.complete(&test_request()).await.unwrap();
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        assert_eq!(resp.failover[0].reason, FailoverReason::CircuitOpen);
+        assert!(resp.failover[0].error.is_none());
+        assert_eq!(chain.health()[1].state, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn flapping_provider_probes_after_cooldown() {
+        let clock = ManualClock::new();
+        let (primary, down, calls) = FlappingProvider::new("primary");
+        down.store(true, Ordering::SeqCst);
+        let chain = FailoverChain::new(vec![
+            Box::new(primary),
+            Box::new(SuccessProvider {
+                name: "fallback".into(),
+            }),
+        ])
+        .unwrap()
+        .with_circuit(circuit(1))
+        .with_clock(clock.clone());
+
+        chain.complete(&test_request()).await.unwrap();
+        assert_eq!(chain.health()[0].state, CircuitState::Open);
+
+        // Cooldown passes while the provider is still down: the probe fails
+        // and the circuit reopens immediately.
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(chain.health()[0].state, CircuitState::HalfOpen);
+        chain.complete(&test_request()).await.unwrap();
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        assert_eq!(chain.health()[0].state, CircuitState::Open);
+        chain.complete(&test_request()).await.unwrap();
+        assert_eq!(calls.lock().unwrap().len(), 2);
+
+        // The provider recovers: the next probe closes the circuit.
+        down.store(false, Ordering::SeqCst);
+        clock.advance(Duration::from_secs(30));
+        let resp = chain.complete(&test_request()).await.unwrap();
+        assert!(resp.failover.is_empty());
+        assert_eq!(resp.model, "primary");
+        assert_eq!(chain.health()[0].state, CircuitState::Closed);
+        assert_eq!(chain.health()[0].consecutive_failures, 0);
+
+        // It goes down again: one failure reopens it with a threshold of 1.
+        down.store(true, Ordering::SeqCst);
+        chain.complete(&test_request()).await.unwrap();
+        assert_eq!(chain.health()[0].state, CircuitState::Open);
+        assert_eq!(calls.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn half_open_admits_a_single_probe() {
+        let clock = ManualClock::new();
+        let chain = FailoverChain::new(vec![Box::new(SuccessProvider { name: "p".into() })])
+            .unwrap()
+            .with_circuit(circuit(1))
+            .with_clock(clock.clone());
+        chain.settle(0, false, Outcome::Failure);
+
+        clock.advance(Duration::from_secs(30));
+        assert!(matches!(chain.admit(0), Admission::Call { probe: true }));
+        assert!(matches!(
+            chain.admit(0),
+            Admission::Skip(FailoverReason::ProbeInFlight)
+        ));
+
+        // A probe that never reports back is abandoned after the cooldown.
+        clock.advance(Duration::from_secs(30));
+        assert!(matches!(chain.admit(0), Admission::Call { probe: true }));
+    }
+
+    #[tokio::test]
+    async fn auth_errors_do_not_open_the_circuit() {
+        let chain = FailoverChain::new(vec![Box::new(FailProvider {
+            name: "p".into(),
+            error: || ProviderError::AuthFailed("bad key".into()),
+        })])
+        .unwrap()
+        .with_circuit(circuit(1));
+
+        chain.complete(&test_request()).await.unwrap_err();
+        assert_eq!(chain.health()[0].state, CircuitState::Closed);
+        assert_eq!(chain.health()[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn all_circuits_open_fails_fast() {
+        let (primary, down, calls) = FlappingProvider::new("primary");
+        down.store(true, Ordering::SeqCst);
+        let chain = FailoverChain::new(vec![Box::new(primary)])
+            .unwrap()
+            .with_circuit(circuit(1))
+            .with_clock(ManualClock::new());
+
+        chain.complete(&test_request()).await.unwrap_err();
+        let err = chain.complete(&test_request()).await.unwrap_err();
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        match err {
+            ProviderError::AllProvidersExhausted { attempts } => {
+                assert_eq!(attempts, vec!["primary: skipped (CircuitOpen)"]);
+            }
+            other => panic!("expected AllProvidersExhausted, got: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn model_map_translates_for_fallback() {
+        let (backup, _, models) = FlappingProvider::new("azure");
+        let chain = FailoverChain::new(vec![
+            Box::new(FailProvider {
+                name: "openai".into(),
+                error: || ProviderError::Timeout,
+            }),
+            Box::new(backup),
+        ])
+        .unwrap()
+        .map_model("azure", "gpt-4o", "gpt-4o-deployment");
+
+        let request = ChatRequest::new("gpt-4o", vec![ChatMessage::user("Hi")]);
+        let resp = chain.complete(&request).await.unwrap();
+        assert_eq!(*models.lock().unwrap(), vec!["gpt-4o-deployment"]);
+        assert_eq!(resp.failover[0].model, "gpt-4o");
+    }
+
+    #[tokio::test]
+    async fn openrouter_fallback_gets_prefixed_model() {
+        let (backup, _, models) = FlappingProvider::new("openrouter");
+        let chain = FailoverChain::new(vec![
+            Box::new(FailProvider {
+                name: "openai".into(),
+                error: || ProviderError::Timeout,
+            }),
+            Box::new(backup),
+        ])
+        .unwrap();
+
+        let request = ChatRequest::new("gpt-4o", vec![ChatMessage::user("Hi")]);
+        chain.complete(&request).await.unwrap();
+        let already_prefixed = ChatRequest::new("openai/gpt-4o", vec![ChatMessage::user("Hi")]);
+        chain.complete(&already_prefixed).await.unwrap();
+        assert_eq!(
+            *models.lock().unwrap(),
+            vec!["openai/gpt-4o", "openai/gpt-4o"]
+        );
+    }
 }
@@ -0,0 +1,1045 @@
+//! Native Google Gemini provider.
+//!
+//! [`GeminiProvider`] calls the `generateContent` and
+//! `streamGenerateContent` endpoints directly. Gemini's OpenAI-compatible
+//! endpoint handles tool calling poorly, so requests are translated from the
+//! OpenAI-shaped [`ChatRequest`] at the edge:
+//!
+//! - `system` messages become `systemInstruction`
+//! - `assistant` turns become `model` contents, with tool calls as
+//!   `functionCall` parts
+//! - `tool` messages become `functionResponse` parts in a user turn
+//! - function tools become `functionDeclarations`, and `tool_choice` becomes
+//!   a `functionCallingConfig`
+//! - sampling settings (`temperature`, `top_p`, `stop`, `seed`, penalties)
+//!   and `max_tokens` go into `generationConfig`, and a JSON
+//!   `response_format` becomes `responseMimeType`/`responseSchema`
+//!
+//! Candidates, `functionCall` parts and `usageMetadata` are mapped back into
+//! a [`ChatResponse`].
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+
+use crate::config::LlmProviderConfig;
+use crate::error::{ProviderError, Result};
+use crate::openai_compat::error_from_response;
+use crate::provider::Provider;
+use crate::sse::LineBuffer;
+use crate::timeouts::Timeouts;
+use crate::types::{
+    ChatMessage, ChatRequest, ChatResponse, Choice, ContentPart, FunctionCall, ImagePart,
+    ImageSource, MessageContent, StreamChunk, ToolCall, Usage,
+};
+use crate::vision::check_images;
+
+/// Default timeout for Gemini API requests (2 minutes).
+const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// An LLM provider that speaks the native Gemini `generateContent` API.
+///
+/// Uses the same [`LlmProviderConfig`] as the other providers. `base_url` is
+/// the API root (`https://generativelanguage.googleapis.com/v1beta`); the
+/// `/openai` suffix of the built-in compat URL is dropped, so the built-in
+/// `gemini` entry works unchanged. The key is sent as `x-goog-api-key`.
+pub struct GeminiProvider {
+    config: LlmProviderConfig,
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+impl GeminiProvider {
+    /// Create a new provider from configuration.
+    ///
+    /// The API key will be resolved from the environment variable specified
+    /// in `config.api_key_env` at request time.
+    pub fn new(config: LlmProviderConfig) -> Self {
+        Self::build(config, None)
+    }
+
+    /// Create a new provider with an explicit API key.
+    pub fn with_api_key(config: LlmProviderConfig, api_key: String) -> Self {
+        Self::build(config, Some(api_key))
+    }
+
+    fn build(config: LlmProviderConfig, api_key: Option<String>) -> Self {
+        Self {
+            http: Timeouts::new(&config, DEFAULT_TIMEOUT_SECS).client(),
+            config,
+            api_key,
+        }
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::new(&self.config, DEFAULT_TIMEOUT_SECS)
+    }
+
+    /// Returns the provider configuration.
+    pub fn config(&self) -> &LlmProviderConfig {
+        &self.config
+    }
+
+    /// URL of `method` (`generateContent` or `streamGenerateContent`) for
+    /// `model`.
+    fn method_url(&self, model: &str, method: &str) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        let root = base.strip_suffix("/openai").unwrap_or(base);
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        format!("{root}/models/{model}:{method}")
+    }
+
+    /// Resolve the API key: explicit key > environment variable.
+    fn resolve_api_key(&self) -> Result<String> {
+        if let Some(ref key) = self.api_key {
+            return Ok(key.clone());
+        }
+        std::env::var(&self.config.api_key_env).map_err(|_| {
+            ProviderError::NotConfigured(format!("set {} env var", self.config.api_key_env))
+        })
+    }
+
+    fn post(&self, url: &str, body: &Value) -> Result<reqwest::RequestBuilder> {
+        let api_key = self.resolve_api_key()?;
+        let mut req = self
+            .http
+            .post(url)
+            .header("x-goog-api-key", api_key)
+            .header("Content-Type", "application/json");
+        for (k, v) in &self.config.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+        Ok(req.json(body))
+    }
+}
+
+#[async_trait]
+impl Provider for GeminiProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        check_images(request)?;
+        let url = self.method_url(&request.model, "generateContent");
+        let body = build_request_body(request);
+
+        debug!(
+            provider = %self.config.name,
+            model = %request.model,
+            messages = request.messages.len(),
+            "sending gemini generateContent request"
+        );
+
+        let response = self
+            .post(&url, &body)?
+            .timeout(self.timeouts().total_for(request))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
+        }
+
+        let body: GenerateContentResponse = response.json().await.map_err(|e| {
+            if e.is_timeout() {
+                return ProviderError::Timeout;
+            }
+            ProviderError::InvalidResponse(format!("failed to parse response: {e}"))
+        })?;
+        Ok(body.into_chat_response(&request.model))
+    }
+
+    async fn complete_stream(
+        &self,
+        request: &ChatRequest,
+        tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<()> {
+        check_images(request)?;
+        let url = format!(
+            "{}?alt=sse",
+            self.method_url(&request.model, "streamGenerateContent")
+        );
+        let body = build_request_body(request);
+
+        debug!(
+            provider = %self.config.name,
+            model = %request.model,
+            messages = request.messages.len(),
+            "sending gemini streamGenerateContent request"
+        );
+
+        let timeouts = self.timeouts();
+        let response = self
+            .post(&url, &body)?
+            .header("Accept", "text/event-stream")
+            .timeout(timeouts.total_for(request))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
+        }
+
+        let mut byte_stream = response.bytes_stream();
+        let mut lines = LineBuffer::default();
+        let mut state = StreamState::default();
+
+        while let Some(bytes) = timeouts.next_chunk(&mut byte_stream).await? {
+            lines.push(&bytes);
+
+            while let Some(line) = lines.next_line() {
+                let Some(data) = line.strip_prefix("data:") else {
+                    continue;
+                };
+                for chunk in state.handle_chunk(data.trim())? {
+                    trace!(provider = %self.config.name, chunk = ?chunk, "streaming chunk");
+                    if tx.send(chunk).await.is_err() {
+                        debug!(
+                            provider = %self.config.name,
+                            "stream receiver dropped, stopping"
+                        );
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        // Gemini has no end-of-stream sentinel; the final chunk carries the
+        // finish reason. A stream that closes before it was cut off.
+        if !state.finished {
+            return Err(ProviderError::InvalidResponse(
+                "gemini stream ended before a finish reason".into(),
+            ));
+        }
+        debug!(provider = %self.config.name, "streaming complete");
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for GeminiProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeminiProvider")
+            .field("name", &self.config.name)
+            .field("base_url", &self.config.base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+// ── Request translation ─────────────────────────────────────────────────
+
+/// Translate a [`ChatRequest`] into a `generateContent` request body.
+pub(crate) fn build_request_body(request: &ChatRequest) -> Value {
+    let mut body = json!({ "contents": translate_messages(&request.messages) });
+
+    let system: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .filter_map(ChatMessage::text)
+        .filter(|c| !c.is_empty())
+        .map(|text| json!({ "text": text }))
+        .collect();
+    if !system.is_empty() {
+        body["systemInstruction"] = json!({ "parts": system });
+    }
+
+    if !request.tools.is_empty() {
+        body["tools"] = translate_tools(&request.tools);
+    }
+    if let Some(config) = request.tool_choice.as_ref().and_then(translate_tool_choice) {
+        body["toolConfig"] = json!({ "functionCallingConfig": config });
+    }
+
+    let mut generation = Map::new();
+    if let Some(temperature) = request.temperature {
+        generation.insert("temperature".into(), json!(temperature));
+    }
+    if let Some(top_p) = request.top_p {
+        generation.insert("topP".into(), json!(top_p));
+    }
+    if !request.stop.is_empty() {
+        generation.insert("stopSequences".into(), json!(request.stop));
+    }
+    if let Some(seed) = request.seed {
+        generation.insert("seed".into(), json!(seed));
+    }
+    if let Some(penalty) = request.frequency_penalty {
+        generation.insert("frequencyPenalty".into(), json!(penalty));
+    }
+    if let Some(penalty) = request.presence_penalty {
+        generation.insert("presencePenalty".into(), json!(penalty));
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        generation.insert("maxOutputTokens".into(), json!(max_tokens));
+    }
+    if let Some(format) = request.response_format.as_ref().filter(|f| f.is_json()) {
+        generation.insert("responseMimeType".into(), json!("application/json"));
+        if let Some(schema) = format.schema() {
+            generation.insert("responseSchema".into(), sanitize_schema(&schema.schema));
+        }
+    }
+    if !generation.is_empty() {
+        body["generationConfig"] = Value::Object(generation);
+    }
+    body
+}
+
+/// Convert non-system messages into Gemini contents.
+///
+/// Consecutive messages with the same Gemini role are merged into one
+/// content, so the responses to parallel function calls share a turn.
+fn translate_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    let mut turns: Vec<(&'static str, Vec<Value>)> = Vec::new();
+
+    for msg in messages {
+        let (role, parts) = match msg.role.as_str() {
+            "system" => continue,
+            "assistant" => ("model", model_parts(msg)),
+            "tool" => ("user", vec![function_response_part(msg, messages)]),
+            _ => ("user", content_parts(msg.content.as_ref())),
+        };
+        if parts.is_empty() {
+            continue;
+        }
+        match turns.last_mut() {
+            Some((last_role, last_parts)) if *last_role == role => last_parts.extend(parts),
+            _ => turns.push((role, parts)),
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(role, parts)| json!({ "role": role, "parts": parts }))
+        .collect()
+}
+
+fn content_parts(content: Option<&MessageContent>) -> Vec<Value> {
+    match content {
+        None => Vec::new(),
+        Some(MessageContent::Text(text)) => text_part(text),
+        Some(MessageContent::Parts(parts)) => parts
+            .iter()
+            .flat_map(|part| match part {
+                ContentPart::Text { text } => text_part(text),
+                ContentPart::Image(image) => vec![image_part(image)],
+            })
+            .collect(),
+    }
+}
+
+fn text_part(text: &str) -> Vec<Value> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    vec![json!({ "text": text })]
+}
+
+/// Inline images become `inlineData`; URLs become `fileData`, which needs
+/// a MIME type, so it is guessed from the extension.
+fn image_part(image: &ImagePart) -> Value {
+    match &image.source {
+        ImageSource::Base64 { mime_type, data } => {
+            json!({ "inlineData": { "mimeType": mime_type, "data": data } })
+        }
+        ImageSource::Url(url) => {
+            json!({ "fileData": { "mimeType": image_mime_from_url(url), "fileUri": url } })
+        }
+    }
+}
+
+fn image_mime_from_url(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let ext = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        Some("heic") => "image/heic",
+        _ => "image/jpeg",
+    }
+}
+
+fn model_parts(msg: &ChatMessage) -> Vec<Value> {
+    let mut parts = content_parts(msg.content.as_ref());
+    for call in msg.tool_calls.iter().flatten() {
+        let args = serde_json::from_str::<Value>(&call.function.arguments)
+            .ok()
+            .filter(Value::is_object)
+            .unwrap_or_else(|| json!({}));
+        parts.push(json!({
+            "functionCall": { "name": call.function.name, "args": args }
+        }));
+    }
+    parts
+}
+
+/// Build the `functionResponse` part for a tool result.
+///
+/// Gemini matches responses by function name rather than call id, so the
+/// name is looked up from the assistant tool call with the same id. The
+/// response must be an object: JSON object results are sent as-is and
+/// anything else is wrapped as `{"content": ...}`.
+fn function_response_part(msg: &ChatMessage, history: &[ChatMessage]) -> Value {
+    let call_id = msg.tool_call_id.as_deref().unwrap_or_default();
+    let name = history
+        .iter()
+        .flat_map(|m| m.tool_calls.iter().flatten())
+        .find(|call| call.id == call_id)
+        .map(|call| call.function.name.clone())
+        .unwrap_or_else(|| call_id.to_string());
+
+    let content = msg.text().unwrap_or_default();
+    let response = serde_json::from_str::<Value>(&content)
+        .ok()
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({ "content": content }));
+
+    json!({ "functionResponse": { "name": name, "response": response } })
+}
+
+/// Collect OpenAI function tools into one `functionDeclarations` entry.
+///
+/// Entries that are already Gemini tools (`functionDeclarations`,
+/// `googleSearch`, ...) are passed through alongside it.
+fn translate_tools(tools: &[Value]) -> Value {
+    let mut declarations = Vec::new();
+    let mut native = Vec::new();
+    for tool in tools {
+        let Some(function) = tool.get("function") else {
+            native.push(tool.clone());
+            continue;
+        };
+        let mut decl = json!({ "name": function.get("name").cloned().unwrap_or(Value::Null) });
+        if let Some(description) = function.get("description") {
+            decl["description"] = description.clone();
+        }
+        if let Some(parameters) = function.get("parameters") {
+            decl["parameters"] = sanitize_schema(parameters);
+        }
+        declarations.push(decl);
+    }
+
+    let mut out = Vec::new();
+    if !declarations.is_empty() {
+        out.push(json!({ "functionDeclarations": declarations }));
+    }
+    out.extend(native);
+    Value::Array(out)
+}
+
+/// Drop JSON Schema keywords Gemini's OpenAPI subset rejects.
+fn sanitize_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(k, _)| !matches!(k.as_str(), "$schema" | "additionalProperties"))
+                .map(|(k, v)| (k.clone(), sanitize_schema(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sanitize_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Convert an OpenAI `tool_choice` into a `functionCallingConfig`.
+fn translate_tool_choice(choice: &Value) -> Option<Value> {
+    match choice {
+        Value::String(mode) => match mode.as_str() {
+            "auto" => Some(json!({ "mode": "AUTO" })),
+            "required" => Some(json!({ "mode": "ANY" })),
+            "none" => Some(json!({ "mode": "NONE" })),
+            _ => None,
+        },
+        Value::Object(map) if map.get("type").and_then(Value::as_str) == Some("function") => {
+            let name = map.get("function")?.get("name")?;
+            Some(json!({ "mode": "ANY", "allowedFunctionNames": [name] }))
+        }
+        _ => None,
+    }
+}
+
+// ── Response translation ────────────────────────────────────────────────
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(default)]
+    model_version: Option<String>,
+    #[serde(default)]
+    response_id: Option<String>,
+    #[serde(default)]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Option<Content>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default)]
+    text: Option<String>,
+    /// Set on thinking-model summary parts, which are not answer text.
+    #[serde(default)]
+    thought: bool,
+    #[serde(default)]
+    function_call: Option<GeminiFunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiFunctionCall {
+    #[serde(default)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+}
+
+impl UsageMetadata {
+    /// Thinking tokens are billed as output, so they count toward it.
+    fn into_usage(self) -> Usage {
+        let output = self.candidates_token_count + self.thoughts_token_count;
+        Usage {
+            input_tokens: self.prompt_token_count,
+            output_tokens: output,
+            total_tokens: if self.total_token_count > 0 {
+                self.total_token_count
+            } else {
+                self.prompt_token_count + output
+            },
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: self.thoughts_token_count,
+        }
+    }
+}
+
+impl GeminiFunctionCall {
+    fn into_tool_call(self) -> ToolCall {
+        ToolCall {
+            id: self
+                .id
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+            call_type: "function".into(),
+            function: FunctionCall {
+                name: self.name,
+                arguments: if self.args.is_null() {
+                    "{}".into()
+                } else {
+                    self.args.to_string()
+                },
+            },
+        }
+    }
+}
+
+impl GenerateContentResponse {
+    fn into_chat_response(self, requested_model: &str) -> ChatResponse {
+        let block_reason = self.prompt_feedback.and_then(|f| f.block_reason);
+        let candidate = self.candidates.into_iter().next();
+
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        let mut finish_reason = None;
+        if let Some(candidate) = candidate {
+            for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+                if let Some(t) = part.text.filter(|_| !part.thought) {
+                    text.push_str(&t);
+                }
+                if let Some(call) = part.function_call {
+                    tool_calls.push(call.into_tool_call());
+                }
+            }
+            finish_reason = candidate
+                .finish_reason
+                .map(|r| map_finish_reason(&r, !tool_calls.is_empty()));
+        }
+        if finish_reason.is_none() && block_reason.is_some() {
+            finish_reason = Some("content_filter".to_string());
+        }
+
+        let message = ChatMessage {
+            role: "assistant".into(),
+            content: if text.is_empty() && !tool_calls.is_empty() {
+                None
+            } else {
+                Some(text.into())
+            },
+            tool_call_id: None,
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+            cache: false,
+            reasoning: None,
+        };
+
+        ChatResponse {
+            id: self
+                .response_id
+                .unwrap_or_else(|| format!("gemini-{}", uuid::Uuid::new_v4())),
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason,
+            }],
+            usage: self.usage_metadata.map(UsageMetadata::into_usage),
+            model: self
+                .model_version
+                .unwrap_or_else(|| requested_model.to_string()),
+            failover: Vec::new(),
+            retries: 0,
+        }
+    }
+}
+
+/// Map a Gemini `finishReason` onto the OpenAI `finish_reason` values.
+///
+/// Gemini reports `STOP` even when the turn ends in function calls.
+fn map_finish_reason(reason: &str, has_tool_calls: bool) -> String {
+    match reason {
+        "STOP" if has_tool_calls => "tool_calls".into(),
+        "STOP" => "stop".into(),
+        "MAX_TOKENS" => "length".into(),
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+            "content_filter".into()
+        }
+        other => other.to_ascii_lowercase(),
+    }
+}
+
+// ── Streaming ───────────────────────────────────────────────────────────
+
+/// Tracks what a `streamGenerateContent` stream has reported so far.
+#[derive(Debug, Default)]
+struct StreamState {
+    /// Function calls seen so far; Gemini sends each one whole.
+    tool_calls: usize,
+    /// Whether a chunk with a finish reason has been seen.
+    finished: bool,
+}
+
+impl StreamState {
+    /// Translate one streamed `GenerateContentResponse` into chunks.
+    fn handle_chunk(&mut self, data: &str) -> Result<Vec<StreamChunk>> {
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        let response: GenerateContentResponse = serde_json::from_str(data).map_err(|e| {
+            ProviderError::InvalidResponse(format!("failed to parse gemini chunk: {e}"))
+        })?;
+
+        let mut chunks = Vec::new();
+        let mut finish_reason = None;
+        if let Some(candidate) = response.candidates.into_iter().next() {
+            for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+                if let Some(text) = part.text.filter(|t| !part.thought && !t.is_empty()) {
+                    chunks.push(StreamChunk::TextDelta { text });
+                }
+                if let Some(call) = part.function_call {
+                    let call = call.into_tool_call();
+                    chunks.push(StreamChunk::ToolCallDelta {
+                        index: self.tool_calls,
+                        id: Some(call.id),
+                        name: Some(call.function.name),
+                        arguments: Some(call.function.arguments),
+                    });
+                    self.tool_calls += 1;
+                }
+            }
+            finish_reason = candidate
+                .finish_reason
+                .map(|r| map_finish_reason(&r, self.tool_calls > 0));
+        }
+        if finish_reason.is_none()
+            && response
+                .prompt_feedback
+                .is_some_and(|f| f.block_reason.is_some())
+        {
+            finish_reason = Some("content_filter".to_string());
+        }
+
+        if finish_reason.is_some() {
+            self.finished = true;
+            chunks.push(StreamChunk::Done {
+                finish_reason,
+                usage: response.usage_metadata.map(UsageMetadata::into_usage),
+            });
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::StreamAccumulator;
+    use crate::types::ResponseFormat;
+
+    fn config() -> LlmProviderConfig {
+        crate::config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "gemini")
+            .unwrap()
+    }
+
+    #[test]
+    fn urls_drop_the_openai_suffix() {
+        let provider = GeminiProvider::with_api_key(config(), "key".into());
+        assert_eq!(
+            provider.method_url("gemini-2.5-flash", "generateContent"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent"
+        );
+        assert_eq!(
+            provider.method_url("models/gemini-2.5-pro", "streamGenerateContent"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-pro:streamGenerateContent"
+        );
+    }
+
+    #[test]
+    fn request_translation_fixture() {
+        let mut request = ChatRequest::new(
+            "gemini-2.5-flash",
+            vec![
+                ChatMessage::system("You are terse."),
+                ChatMessage::user("Weather in Paris?"),
+                ChatMessage {
+                    role: "assistant".into(),
+                    content: None,
+                    tool_call_id: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: "call_1".into(),
+                        call_type: "function".into(),
+                        function: FunctionCall {
+                            name: "get_weather".into(),
+                            arguments: r#"{"city":"Paris"}"#.into(),
+                        },
+                    }]),
+                    cache: false,
+                    reasoning: None,
+                },
+                ChatMessage {
+                    role: "tool".into(),
+                    content: Some("18C and sunny".into()),
+                    tool_call_id: Some("call_1".into()),
+                    tool_calls: None,
+                    cache: false,
+                    reasoning: None,
+                },
+                ChatMessage::user("And tomorrow?"),
+            ],
+        );
+        request.tools = vec![json!({
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Look up the weather",
+                "parameters": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"],
+                    "additionalProperties": false
+                }
+            }
+        })];
+        request.tool_choice = Some(json!("required"));
+        request.temperature = Some(0.3);
+        request.max_tokens = Some(512);
+
+        assert_eq!(
+            build_request_body(&request),
+            json!({
+                "systemInstruction": {"parts": [{"text": "You are terse."}]},
+                "contents": [
+                    {"role": "user", "parts": [{"text": "Weather in Paris?"}]},
+                    {"role": "model", "parts": [
+                        {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                    ]},
+                    {"role": "user", "parts": [
+                        {"functionResponse": {
+                            "name": "get_weather",
+                            "response": {"content": "18C and sunny"}
+                        }},
+                        {"text": "And tomorrow?"}
+                    ]}
+                ],
+                "tools": [{"functionDeclarations": [{
+                    "name": "get_weather",
+                    "description": "Look up the weather",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {"city": {"type": "string"}},
+                        "required": ["city"]
+                    }
+                }]}],
+                "toolConfig": {"functionCallingConfig": {"mode": "ANY"}},
+                "generationConfig": {"temperature": 0.3, "maxOutputTokens": 512}
+            })
+        );
+    }
+
+    #[test]
+    fn sampling_controls_go_into_generation_config() {
+        let request = ChatRequest::new("gemini-2.5-flash", vec![ChatMessage::user("Label")])
+            .with_top_p(0.8)
+            .with_stop(["END"])
+            .with_seed(7)
+            .with_frequency_penalty(0.5)
+            .with_presence_penalty(0.25);
+        assert_eq!(
+            build_request_body(&request)["generationConfig"],
+            json!({
+                "topP": 0.8,
+                "stopSequences": ["END"],
+                "seed": 7,
+                "frequencyPenalty": 0.5,
+                "presencePenalty": 0.25
+            })
+        );
+    }
+
+    #[test]
+    fn image_parts_become_inline_and_file_data() {
+        let request = ChatRequest::new(
+            "gemini-2.5-flash",
+            vec![ChatMessage::with_parts(
+                "user",
+                vec![
+                    ContentPart::text("Compare these."),
+                    ContentPart::Image(ImagePart::base64("image/png", "iVBORw0K")),
+                    ContentPart::Image(ImagePart::url("gs://bucket/photo.WEBP?v=2")),
+                ],
+            )],
+        );
+        assert_eq!(
+            build_request_body(&request)["contents"],
+            json!([{"role": "user", "parts": [
+                {"text": "Compare these."},
+                {"inlineData": {"mimeType": "image/png", "data": "iVBORw0K"}},
+                {"fileData": {"mimeType": "image/webp", "fileUri": "gs://bucket/photo.WEBP?v=2"}}
+            ]}])
+        );
+    }
+
+    #[test]
+    fn response_format_sets_generation_config() {
+        let mut request = ChatRequest::new("m", vec![ChatMessage::user("Who?")]);
+        request.response_format = Some(ResponseFormat::json_schema(
+            "person",
+            json!({
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "additionalProperties": false
+            }),
+        ));
+        let body = build_request_body(&request);
+        assert_eq!(
+            body["generationConfig"],
+            json!({
+                "responseMimeType": "application/json",
+                "responseSchema": {"type": "object", "properties": {"name": {"type": "string"}}}
+            })
+        );
+
+        request.response_format = Some(ResponseFormat::JsonObject);
+        let body = build_request_body(&request);
+        assert_eq!(
+            body["generationConfig"],
+            json!({"responseMimeType": "application/json"})
+        );
+
+        request.response_format = Some(ResponseFormat::Text);
+        assert!(
+            build_request_body(&request)
+                .get("generationConfig")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tool_choice_and_native_tools() {
+        assert_eq!(
+            translate_tool_choice(&json!({"type": "function", "function": {"name": "f"}})),
+            Some(json!({"mode": "ANY", "allowedFunctionNames": ["f"]}))
+        );
+        assert_eq!(
+            translate_tool_choice(&json!("none")),
+            Some(json!({"mode": "NONE"}))
+        );
+        assert_eq!(
+            translate_tool_choice(&json!("auto")),
+            Some(json!({"mode": "AUTO"}))
+        );
+
+        let tools = translate_tools(&[json!({"googleSearch": {}})]);
+        assert_eq!(tools, json!([{"googleSearch": {}}]));
+    }
+
+    #[test]
+    fn object_tool_results_are_sent_as_is() {
+        let history = vec![ChatMessage {
+            role: "tool".into(),
+            content: Some(r#"{"temp": 18}"#.into()),
+            tool_call_id: Some("unknown".into()),
+            tool_calls: None,
+            cache: false,
+            reasoning: None,
+        }];
+        let part = function_response_part(&history[0], &history);
+        assert_eq!(
+            part,
+            json!({"functionResponse": {"name": "unknown", "response": {"temp": 18}}})
+        );
+    }
+
+    #[test]
+    fn response_translation_fixture() {
+        let raw = json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "planning...", "thought": true},
+                        {"text": "Checking both."},
+                        {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}},
+                        {"functionCall": {"id": "fc_2", "name": "get_weather", "args": {"city": "Oslo"}}}
+                    ]
+                },
+                "finishReason": "STOP",
+                "index": 0
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 40,
+                "candidatesTokenCount": 12,
+                "thoughtsTokenCount": 8,
+                "totalTokenCount": 60
+            },
+            "modelVersion": "gemini-2.5-flash",
+            "responseId": "resp-123"
+        });
+        let resp: GenerateContentResponse = serde_json::from_value(raw).unwrap();
+        let resp = resp.into_chat_response("requested");
+
+        assert_eq!(resp.id, "resp-123");
+        assert_eq!(resp.model, "gemini-2.5-flash");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(choice.message.text().as_deref(), Some("Checking both."));
+        let calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].id.starts_with("call_"));
+        assert_eq!(calls[0].function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(calls[1].id, "fc_2");
+
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.input_tokens, 40);
+        assert_eq!(usage.output_tokens, 20);
+        assert_eq!(usage.reasoning_tokens, 8);
+        assert_eq!(usage.total_tokens, 60);
+    }
+
+    #[test]
+    fn blocked_prompt_is_a_content_filter_finish() {
+        let raw = json!({
+            "promptFeedback": {"blockReason": "SAFETY"},
+            "usageMetadata": {"promptTokenCount": 5, "totalTokenCount": 5}
+        });
+        let resp: GenerateContentResponse = serde_json::from_value(raw).unwrap();
+        let resp = resp.into_chat_response("gemini-2.5-flash");
+        assert_eq!(resp.model, "gemini-2.5-flash");
+        assert_eq!(
+            resp.choices[0].finish_reason.as_deref(),
+            Some("content_filter")
+        );
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some(""));
+    }
+
+    #[test]
+    fn finish_reasons() {
+        assert_eq!(map_finish_reason("STOP", false), "stop");
+        assert_eq!(map_finish_reason("MAX_TOKENS", false), "length");
+        assert_eq!(map_finish_reason("SAFETY", false), "content_filter");
+        assert_eq!(
+            map_finish_reason("MALFORMED_FUNCTION_CALL", true),
+            "malformed_function_call"
+        );
+    }
+
+    #[test]
+    fn stream_chunks_fixture() {
+        let chunks = [
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}],"usageMetadata":{"promptTokenCount":9}}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"lo"}]}}]}"#,
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"functionCall":{"name":"lookup","args":{"q":"x"}}}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":9,"candidatesTokenCount":6,"totalTokenCount":15}}"#,
+        ];
+
+        let mut state = StreamState::default();
+        let mut acc = StreamAccumulator::new();
+        for chunk in chunks {
+            assert!(!state.finished);
+            for c in state.handle_chunk(chunk).unwrap() {
+                acc.push(&c);
+            }
+        }
+        assert!(state.finished);
+
+        let resp = acc.finish("gemini-2.5-flash");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.message.text().as_deref(), Some("Hello"));
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.function.name, "lookup");
+        assert_eq!(call.function.arguments, r#"{"q":"x"}"#);
+        assert_eq!(resp.usage.unwrap().total_tokens, 15);
+    }
+
+    #[test]
+    fn debug_hides_api_key() {
+        let provider = GeminiProvider::with_api_key(config(), "AIza-secret".into());
+        let debug = format!("{provider:?}");
+        assert!(!debug.contains("AIza-secret"));
+    }
+}
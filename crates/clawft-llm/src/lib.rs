@@ -8,8 +8,17 @@
 //!
 //! - [`Provider`] trait defines the chat completion interface
 //! - [`OpenAiCompatProvider`] implements it for any OpenAI-compatible API
+//! - [`AnthropicProvider`] implements it natively for the Anthropic Messages API
+//! - [`GeminiProvider`] implements it natively for Gemini `generateContent`
+//! - [`EmbeddingsProvider`] embeds text; [`OpenAiCompatEmbeddings`] batches `/embeddings` calls
 //! - [`ProviderRouter`] routes model names (e.g. "openai/gpt-4o") to providers
 //! - [`LlmProviderConfig`] describes how to connect to a provider
+//! - [`tokens`] counts prompt tokens and checks them against context windows
+//! - [`UsageTracker`] accumulates token usage and cost, with a JSONL ledger
+//! - [`structured`] validates JSON replies against a [`ResponseFormat`] schema
+//! - [`vision`] checks image parts against the model registry and size limit
+//! - [`LlmCache`] serves opted-in, temperature-0 requests from an LRU/disk cache
+//! - [`rate_limit`] enforces per-provider request, token and concurrency limits
 //!
 //! # Quick Start
 //!
@@ -45,23 +54,40 @@
 //!
 //! Source: <https://github.com/weave-logic-ai/weftos>
 
+pub mod cache;
 pub mod config;
 pub mod error;
+pub mod reasoning;
 pub mod sse;
+pub mod stream;
+pub mod structured;
+pub mod tokens;
 pub mod types;
+pub mod usage;
+pub mod vision;
 
+#[cfg(feature = "native")]
+pub mod anthropic;
+#[cfg(feature = "native")]
+pub mod embeddings;
 #[cfg(feature = "native")]
 pub mod failover;
 #[cfg(feature = "native")]
+pub mod gemini;
+#[cfg(feature = "native")]
 pub mod local_provider;
 #[cfg(feature = "native")]
 pub mod openai_compat;
 #[cfg(feature = "native")]
 pub mod provider;
 #[cfg(feature = "native")]
+pub mod rate_limit;
+#[cfg(feature = "native")]
 pub mod retry;
 #[cfg(feature = "native")]
 pub mod router;
+#[cfg(feature = "native")]
+pub mod timeouts;
 
 #[cfg(feature = "native")]
 pub mod eml_retry;
@@ -71,16 +97,30 @@ pub use eml_retry::RetryModel;
 #[cfg(feature = "browser")]
 pub mod browser_transport;
 
-pub use config::LlmProviderConfig;
+pub use cache::LlmCache;
+pub use config::{AzureConfig, LlmProviderConfig};
 /// Backward-compatible alias for [`LlmProviderConfig`].
 #[deprecated(since = "0.2.0", note = "renamed to LlmProviderConfig to avoid collision")]
 pub type ProviderConfig = LlmProviderConfig;
 pub use error::{ProviderError, Result};
 pub use sse::parse_sse_line;
-pub use types::{ChatMessage, ChatRequest, ChatResponse, StreamChunk, ToolCall, Usage};
+pub use stream::StreamAccumulator;
+pub use types::{
+    ChatMessage, ChatRequest, ChatResponse, ContentPart, FailoverEvent, FailoverReason, ImagePart,
+    MessageContent, ReasoningEffort, ResponseFormat, StreamChunk, ToolCall, Usage,
+};
+pub use usage::{PriceTable, UsageTracker};
 
 #[cfg(feature = "native")]
-pub use failover::FailoverChain;
+pub use anthropic::AnthropicProvider;
+#[cfg(feature = "native")]
+pub use cache::CachedProvider;
+#[cfg(feature = "native")]
+pub use embeddings::{EmbeddingResponse, EmbeddingsProvider, OpenAiCompatEmbeddings};
+#[cfg(feature = "native")]
+pub use failover::{CircuitConfig, FailoverChain};
+#[cfg(feature = "native")]
+pub use gemini::GeminiProvider;
 #[cfg(feature = "native")]
 pub use local_provider::LocalProvider;
 #[cfg(feature = "native")]
@@ -88,6 +128,8 @@ pub use openai_compat::OpenAiCompatProvider;
 #[cfg(feature = "native")]
 pub use provider::Provider;
 #[cfg(feature = "native")]
+pub use rate_limit::{RateLimitedProvider, RateLimiter};
+#[cfg(feature = "native")]
 pub use retry::{RetryConfig, RetryPolicy};
 #[cfg(feature = "native")]
 pub use router::ProviderRouter;
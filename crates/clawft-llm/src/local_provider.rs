@@ -22,13 +22,15 @@ use tokio::sync::mpsc;
 use tracing::debug;
 
 use std::collections::HashMap;
-use std::time::Duration;
 
 use crate::config::LlmProviderConfig;
 use crate::error::{ProviderError, Result};
-use crate::provider::Provider;
+use crate::provider::{Provider, parse_model_list};
+use crate::reasoning::{ThinkSplitter, segregate, with_effort};
 use crate::sse::parse_sse_line;
+use crate::timeouts::Timeouts;
 use crate::types::{ChatRequest, ChatResponse, StreamChunk};
+use crate::vision::check_images;
 
 /// Default base URL for Ollama's OpenAI-compatible endpoint.
 pub const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
@@ -92,6 +94,11 @@ impl LocalProvider {
                 default_model: Some(model),
                 headers: HashMap::new(),
                 timeout_secs: Some(DEFAULT_LOCAL_TIMEOUT_SECS),
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
+                reasoning_effort: None,
+                rate_limit: Default::default(),
+                azure: None,
             },
             api_key,
         )
@@ -103,19 +110,29 @@ impl LocalProvider {
     /// variable is not set, requests will be sent without an `Authorization`
     /// header.
     pub fn from_config(config: LlmProviderConfig, api_key: Option<String>) -> Self {
-        let timeout_secs = config
-            .timeout_secs
-            .unwrap_or(DEFAULT_LOCAL_TIMEOUT_SECS);
         Self {
-            http: reqwest::ClientBuilder::new()
-                .timeout(Duration::from_secs(timeout_secs))
-                .build()
-                .expect("failed to build reqwest client"),
+            http: Timeouts::new(&config, DEFAULT_LOCAL_TIMEOUT_SECS).client(),
             config,
             api_key,
         }
     }
 
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::new(&self.config, DEFAULT_LOCAL_TIMEOUT_SECS)
+    }
+
+    /// Map a failed send: timeouts stay timeouts, anything else means the
+    /// server could not be reached.
+    fn send_error(&self, err: reqwest::Error) -> ProviderError {
+        if err.is_timeout() {
+            return ProviderError::Timeout;
+        }
+        ProviderError::RequestFailed(format!(
+            "failed to connect to local LLM server at {}: {err}",
+            self.config.base_url
+        ))
+    }
+
     /// Create a provider pre-configured for Ollama on the default port.
     pub fn ollama() -> Self {
         Self::new(
@@ -175,6 +192,19 @@ impl LocalProvider {
         format!("{base}/models")
     }
 
+    /// Returns Ollama's native model listing URL, `/api/tags` at the server
+    /// root rather than under the OpenAI-compatible `/v1` prefix.
+    fn tags_url(&self) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        let root = base.strip_suffix("/v1").unwrap_or(base);
+        format!("{root}/api/tags")
+    }
+
+    /// Whether the server is Ollama, by provider name or its default port.
+    fn is_ollama(&self) -> bool {
+        self.config.name == "ollama" || self.config.base_url.contains(":11434")
+    }
+
     /// Resolve the API key, returning `None` if no key is available.
     ///
     /// Unlike cloud providers, missing keys are not an error for local providers.
@@ -184,17 +214,22 @@ impl LocalProvider {
         }
         std::env::var(&self.config.api_key_env).ok()
     }
+}
 
-    /// List available models on the local server.
-    ///
-    /// Calls the `/v1/models` endpoint and returns model IDs.
-    ///
-    /// # Errors
-    ///
-    /// Returns [`ProviderError`] if the server is unreachable or returns
-    /// an unexpected response format.
-    pub async fn list_models(&self) -> Result<Vec<String>> {
-        let url = self.models_url();
+#[async_trait]
+impl Provider for LocalProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Lists models from Ollama's `/api/tags`, or from `/v1/models` on
+    /// other servers.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let url = if self.is_ollama() {
+            self.tags_url()
+        } else {
+            self.models_url()
+        };
 
         debug!(
             provider = %self.config.name,
@@ -212,12 +247,7 @@ impl LocalProvider {
             req = req.header(k.as_str(), v.as_str());
         }
 
-        let response = req.send().await.map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "failed to connect to local LLM server at {}: {e}",
-                self.config.base_url
-            ))
-        })?;
+        let response = req.send().await.map_err(|e| self.send_error(e))?;
 
         if !response.status().is_success() {
             let body = response.text().await.unwrap_or_default();
@@ -230,26 +260,7 @@ impl LocalProvider {
             ProviderError::InvalidResponse(format!("failed to parse models response: {e}"))
         })?;
 
-        // OpenAI format: {"data": [{"id": "model-name", ...}, ...]}
-        // Ollama format: {"models": [{"name": "model-name", ...}, ...]}
-        let models = if let Some(data) = body.get("data").and_then(|d| d.as_array()) {
-            data.iter()
-                .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
-                .map(String::from)
-                .collect()
-        } else if let Some(models) = body.get("models").and_then(|m| m.as_array()) {
-            models
-                .iter()
-                .filter_map(|m| {
-                    m.get("name")
-                        .or_else(|| m.get("id"))
-                        .and_then(|n| n.as_str())
-                })
-                .map(String::from)
-                .collect()
-        } else {
-            Vec::new()
-        };
+        let models = parse_model_list(&body);
 
         debug!(
             provider = %self.config.name,
@@ -259,15 +270,9 @@ impl LocalProvider {
 
         Ok(models)
     }
-}
-
-#[async_trait]
-impl Provider for LocalProvider {
-    fn name(&self) -> &str {
-        &self.config.name
-    }
 
     async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        check_images(request)?;
         let url = self.completions_url();
 
         debug!(
@@ -292,12 +297,12 @@ impl Provider for LocalProvider {
             req = req.header(k.as_str(), v.as_str());
         }
 
-        let response = req.json(request).send().await.map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "failed to connect to local LLM server at {}: {e}",
-                self.config.base_url
-            ))
-        })?;
+        let response = req
+            .timeout(self.timeouts().total_for(request))
+            .json(&with_effort(request, self.config.reasoning_effort))
+            .send()
+            .await
+            .map_err(|e| self.send_error(e))?;
 
         let status = response.status();
 
@@ -324,9 +329,13 @@ impl Provider for LocalProvider {
             )));
         }
 
-        let chat_response: ChatResponse = response.json().await.map_err(|e| {
+        let mut chat_response: ChatResponse = response.json().await.map_err(|e| {
+            if e.is_timeout() {
+                return ProviderError::Timeout;
+            }
             ProviderError::InvalidResponse(format!("failed to parse local response: {e}"))
         })?;
+        segregate(&mut chat_response);
 
         debug!(
             provider = %self.config.name,
@@ -343,6 +352,7 @@ impl Provider for LocalProvider {
         request: &ChatRequest,
         tx: mpsc::Sender<StreamChunk>,
     ) -> Result<()> {
+        check_images(request)?;
         let url = self.completions_url();
 
         debug!(
@@ -352,7 +362,7 @@ impl Provider for LocalProvider {
             "sending local streaming chat completion request"
         );
 
-        let mut stream_request = request.clone();
+        let mut stream_request = with_effort(request, self.config.reasoning_effort).into_owned();
         stream_request.stream = Some(true);
 
         let mut req = self
@@ -369,12 +379,13 @@ impl Provider for LocalProvider {
             req = req.header(k.as_str(), v.as_str());
         }
 
-        let response = req.json(&stream_request).send().await.map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "failed to connect to local LLM server at {}: {e}",
-                self.config.base_url
-            ))
-        })?;
+        let timeouts = self.timeouts();
+        let response = req
+            .timeout(timeouts.total_for(request))
+            .json(&stream_request)
+            .send()
+            .await
+            .map_err(|e| self.send_error(e))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -398,14 +409,11 @@ impl Provider for LocalProvider {
         }
 
         // Read the SSE stream line by line
-        use futures_util::StreamExt;
         let mut byte_stream = response.bytes_stream();
         let mut buffer = String::new();
+        let mut splitter = ThinkSplitter::new();
 
-        while let Some(chunk_result) = byte_stream.next().await {
-            let bytes = chunk_result
-                .map_err(|e| ProviderError::RequestFailed(format!("stream read error: {e}")))?;
-
+        while let Some(bytes) = timeouts.next_chunk(&mut byte_stream).await? {
             let text = String::from_utf8_lossy(&bytes);
             buffer.push_str(&text);
 
@@ -418,7 +426,7 @@ impl Provider for LocalProvider {
                     Err(_) => continue,
                 };
 
-                for chunk in chunks {
+                for chunk in chunks.into_iter().flat_map(|c| splitter.split(c)) {
                     if tx.send(chunk).await.is_err() {
                         debug!(
                             provider = %self.config.name,
@@ -433,7 +441,7 @@ impl Provider for LocalProvider {
         if !buffer.trim().is_empty()
             && let Ok(chunks) = parse_sse_line(&buffer)
         {
-            for chunk in chunks {
+            for chunk in chunks.into_iter().flat_map(|c| splitter.split(c)) {
                 let _ = tx.send(chunk).await;
             }
         }
@@ -475,6 +483,11 @@ pub fn local_provider_config(
         default_model: Some(default_model.into()),
         headers: HashMap::new(),
         timeout_secs: Some(DEFAULT_LOCAL_TIMEOUT_SECS),
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
     }
 }
 
@@ -574,6 +587,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tags_url_is_at_server_root() {
+        let provider = LocalProvider::ollama();
+        assert_eq!(provider.tags_url(), "http://localhost:11434/api/tags");
+        assert!(provider.is_ollama());
+        assert!(!LocalProvider::vllm("m".into()).is_ollama());
+    }
+
     // ── API key resolution ──────────────────────────────────────────
 
     #[test]
@@ -637,8 +658,21 @@ mod tests {
             messages: vec![ChatMessage::user("test")],
             max_tokens: Some(2048),
             temperature: Some(0.7),
+            top_p: None,
+            stop: Vec::new(),
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: HashMap::new(),
             tools: Vec::new(),
+            tool_choice: None,
             stream: None,
+            response_format: None,
+            cache: false,
+            cache_tools: false,
+            timeout: None,
+            reasoning_effort: None,
+            max_reasoning_tokens: None,
         };
         let json = serde_json::to_value(&request).unwrap();
         assert_eq!(json["model"], "hermes-3-llama-3.1-8b");
@@ -675,7 +709,7 @@ mod tests {
         assert_eq!(response.model, "llama3.2");
         assert_eq!(response.choices.len(), 1);
         assert_eq!(
-            response.choices[0].message.content.as_deref(),
+            response.choices[0].message.text().as_deref(),
             Some("Rust is a systems programming language.")
         );
         assert_eq!(
@@ -714,7 +748,7 @@ mod tests {
         let response: ChatResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.model, "NousResearch/Hermes-3-Llama-3.1-8B");
         assert_eq!(
-            response.choices[0].message.content.as_deref(),
+            response.choices[0].message.text().as_deref(),
             Some("Hello! How can I help you today?")
         );
     }
@@ -738,10 +772,7 @@ mod tests {
 
         let response: ChatResponse = serde_json::from_str(json).unwrap();
         assert!(response.usage.is_none());
-        assert_eq!(
-            response.choices[0].message.content.as_deref(),
-            Some("Hi!")
-        );
+        assert_eq!(response.choices[0].message.text().as_deref(), Some("Hi!"));
     }
 
     #[test]
@@ -884,6 +915,11 @@ mod tests {
             default_model: Some("test".into()),
             headers: HashMap::new(),
             timeout_secs: Some(60),
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         };
         let provider = LocalProvider::from_config(config, None);
         assert_eq!(provider.config().timeout_secs, Some(60));
@@ -949,6 +985,10 @@ mod tests {
                     input_tokens: 10,
                     output_tokens: 20,
                     total_tokens: 30,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 }),
             }
         );
@@ -8,13 +8,14 @@ use async_trait::async_trait;
 use tokio::sync::mpsc;
 use tracing::{debug, trace, warn};
 
-use std::time::Duration;
-
 use crate::config::LlmProviderConfig;
 use crate::error::{ProviderError, Result};
-use crate::provider::Provider;
-use crate::sse::parse_sse_line;
+use crate::provider::{Provider, parse_model_list};
+use crate::reasoning::{ThinkSplitter, segregate, with_effort};
+use crate::sse::{LineBuffer, parse_sse_line};
+use crate::timeouts::Timeouts;
 use crate::types::{ChatRequest, ChatResponse, StreamChunk};
+use crate::vision::check_images;
 
 /// Default timeout for LLM API requests (2 minutes).
 const DEFAULT_TIMEOUT_SECS: u64 = 120;
@@ -52,12 +53,8 @@ impl OpenAiCompatProvider {
     /// The API key will be resolved from the environment variable specified
     /// in `config.api_key_env` at request time.
     pub fn new(config: LlmProviderConfig) -> Self {
-        let timeout_secs = config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
         Self {
-            http: reqwest::ClientBuilder::new()
-                .timeout(Duration::from_secs(timeout_secs))
-                .build()
-                .expect("failed to build reqwest client"),
+            http: Timeouts::new(&config, DEFAULT_TIMEOUT_SECS).client(),
             config,
             api_key: None,
         }
@@ -68,26 +65,52 @@ impl OpenAiCompatProvider {
     /// This bypasses environment variable lookup and uses the provided key
     /// directly.
     pub fn with_api_key(config: LlmProviderConfig, api_key: String) -> Self {
-        let timeout_secs = config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
         Self {
-            http: reqwest::ClientBuilder::new()
-                .timeout(Duration::from_secs(timeout_secs))
-                .build()
-                .expect("failed to build reqwest client"),
+            http: Timeouts::new(&config, DEFAULT_TIMEOUT_SECS).client(),
             config,
             api_key: Some(api_key),
         }
     }
 
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::new(&self.config, DEFAULT_TIMEOUT_SECS)
+    }
+
     /// Returns the provider configuration.
     pub fn config(&self) -> &LlmProviderConfig {
         &self.config
     }
 
-    /// Returns the chat completions endpoint URL.
-    fn completions_url(&self) -> String {
+    /// Returns the chat completions endpoint URL for `model`.
+    ///
+    /// Azure OpenAI addresses the model's deployment and carries the API
+    /// version as a query parameter.
+    fn completions_url(&self, model: &str) -> String {
         let base = self.config.base_url.trim_end_matches('/');
-        format!("{base}/chat/completions")
+        match &self.config.azure {
+            Some(azure) => format!(
+                "{base}/openai/deployments/{}/chat/completions?api-version={}",
+                azure.deployment(model),
+                azure.api_version
+            ),
+            None => format!("{base}/chat/completions"),
+        }
+    }
+
+    /// Returns the authentication header: a bearer token, or Azure's
+    /// `api-key` header.
+    fn auth_header(&self, api_key: &str) -> (&'static str, String) {
+        if self.config.azure.is_some() {
+            ("api-key", api_key.to_owned())
+        } else {
+            ("Authorization", format!("Bearer {api_key}"))
+        }
+    }
+
+    /// Returns the models listing endpoint URL.
+    fn models_url(&self) -> String {
+        let base = self.config.base_url.trim_end_matches('/');
+        format!("{base}/models")
     }
 
     /// Resolve the API key: explicit key > environment variable.
@@ -107,9 +130,40 @@ impl Provider for OpenAiCompatProvider {
         &self.config.name
     }
 
+    /// Lists models from the `/models` endpoint. Azure deployments cannot
+    /// be listed with an API key, so Azure reports this as unsupported.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        if self.config.azure.is_some() {
+            return Err(ProviderError::Unsupported(
+                "Azure OpenAI deployments cannot be listed".into(),
+            ));
+        }
+        let api_key = self.resolve_api_key()?;
+        let url = self.models_url();
+
+        debug!(provider = %self.config.name, url = %url, "listing models");
+
+        let (auth_name, auth_value) = self.auth_header(&api_key);
+        let mut req = self.http.get(&url).header(auth_name, auth_value);
+        for (k, v) in &self.config.headers {
+            req = req.header(k.as_str(), v.as_str());
+        }
+
+        let response = req.send().await?;
+        if !response.status().is_success() {
+            return Err(error_from_response(&self.config.name, "", response).await);
+        }
+
+        let body: serde_json::Value = response.json().await.map_err(|e| {
+            ProviderError::InvalidResponse(format!("failed to parse models response: {e}"))
+        })?;
+        Ok(parse_model_list(&body))
+    }
+
     async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        check_images(request)?;
         let api_key = self.resolve_api_key()?;
-        let url = self.completions_url();
+        let url = self.completions_url(&request.model);
 
         debug!(
             provider = %self.config.name,
@@ -118,81 +172,35 @@ impl Provider for OpenAiCompatProvider {
             "sending chat completion request"
         );
 
+        let (auth_name, auth_value) = self.auth_header(&api_key);
         let mut req = self
             .http
             .post(&url)
-            .header("Authorization", format!("Bearer {api_key}"))
+            .header(auth_name, auth_value)
             .header("Content-Type", "application/json");
 
         for (k, v) in &self.config.headers {
             req = req.header(k.as_str(), v.as_str());
         }
 
-        let response = req.json(request).send().await?;
+        let response = req
+            .timeout(self.timeouts().total_for(request))
+            .json(&with_effort(request, self.config.reasoning_effort))
+            .send()
+            .await?;
         let status = response.status();
 
         if !status.is_success() {
-            if status.as_u16() == 429 {
-                // Try HTTP Retry-After header first, then body JSON, then default
-                let header_ms = parse_retry_after_header(&response);
-                let body = response.text().await.unwrap_or_default();
-
-                // Some providers (e.g. xAI) use 429 for exhausted credits/quota,
-                // which is not a transient rate limit and should not be retried.
-                if is_quota_exhausted(&body) {
-                    let msg = extract_error_message(&body)
-                        .unwrap_or_else(|| "credits exhausted or spending limit reached".into());
-                    warn!(
-                        provider = %self.config.name,
-                        "quota exhausted (not retryable)"
-                    );
-                    return Err(ProviderError::RequestFailed(msg));
-                }
-
-                let retry_ms = header_ms
-                    .or_else(|| parse_retry_after_ms(&body))
-                    .unwrap_or(1000);
-                warn!(
-                    provider = %self.config.name,
-                    retry_after_ms = retry_ms,
-                    body = %body,
-                    "rate limited"
-                );
-                return Err(ProviderError::RateLimited {
-                    retry_after_ms: retry_ms,
-                });
-            }
-
-            let body = response.text().await.unwrap_or_default();
-
-            if status.as_u16() == 401 || status.as_u16() == 403 {
-                return Err(ProviderError::AuthFailed(body));
-            }
-
-            if status.as_u16() == 404 {
-                return Err(ProviderError::ModelNotFound(format!(
-                    "model '{}': {}",
-                    request.model, body
-                )));
-            }
-
-            // Emit structured ServerError for 5xx, RequestFailed for other codes.
-            let code = status.as_u16();
-            if (500..=599).contains(&code) {
-                return Err(ProviderError::ServerError {
-                    status: code,
-                    body,
-                });
-            }
-
-            return Err(ProviderError::RequestFailed(format!(
-                "HTTP {status}: {body}"
-            )));
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
         }
 
-        let chat_response: ChatResponse = response.json().await.map_err(|e| {
+        let mut chat_response: ChatResponse = response.json().await.map_err(|e| {
+            if e.is_timeout() {
+                return ProviderError::Timeout;
+            }
             ProviderError::InvalidResponse(format!("failed to parse response: {e}"))
         })?;
+        segregate(&mut chat_response);
 
         debug!(
             provider = %self.config.name,
@@ -209,8 +217,9 @@ impl Provider for OpenAiCompatProvider {
         request: &ChatRequest,
         tx: mpsc::Sender<StreamChunk>,
     ) -> Result<()> {
+        check_images(request)?;
         let api_key = self.resolve_api_key()?;
-        let url = self.completions_url();
+        let url = self.completions_url(&request.model);
 
         debug!(
             provider = %self.config.name,
@@ -220,13 +229,14 @@ impl Provider for OpenAiCompatProvider {
         );
 
         // Build a request with stream: true
-        let mut stream_request = request.clone();
+        let mut stream_request = with_effort(request, self.config.reasoning_effort).into_owned();
         stream_request.stream = Some(true);
 
+        let (auth_name, auth_value) = self.auth_header(&api_key);
         let mut req = self
             .http
             .post(&url)
-            .header("Authorization", format!("Bearer {api_key}"))
+            .header(auth_name, auth_value)
             .header("Content-Type", "application/json")
             .header("Accept", "text/event-stream");
 
@@ -234,119 +244,42 @@ impl Provider for OpenAiCompatProvider {
             req = req.header(k.as_str(), v.as_str());
         }
 
-        let response = req.json(&stream_request).send().await?;
+        let timeouts = self.timeouts();
+        let response = req
+            .timeout(timeouts.total_for(request))
+            .json(&stream_request)
+            .send()
+            .await?;
         let status = response.status();
 
         if !status.is_success() {
-            if status.as_u16() == 429 {
-                let header_ms = parse_retry_after_header(&response);
-                let body = response.text().await.unwrap_or_default();
-
-                if is_quota_exhausted(&body) {
-                    let msg = extract_error_message(&body)
-                        .unwrap_or_else(|| "credits exhausted or spending limit reached".into());
-                    warn!(
-                        provider = %self.config.name,
-                        "quota exhausted (not retryable)"
-                    );
-                    return Err(ProviderError::RequestFailed(msg));
-                }
-
-                let retry_ms = header_ms
-                    .or_else(|| parse_retry_after_ms(&body))
-                    .unwrap_or(1000);
-                warn!(
-                    provider = %self.config.name,
-                    retry_after_ms = retry_ms,
-                    body = %body,
-                    "rate limited (streaming)"
-                );
-                return Err(ProviderError::RateLimited {
-                    retry_after_ms: retry_ms,
-                });
-            }
-
-            let body = response.text().await.unwrap_or_default();
-
-            if status.as_u16() == 401 || status.as_u16() == 403 {
-                return Err(ProviderError::AuthFailed(body));
-            }
-
-            if status.as_u16() == 404 {
-                return Err(ProviderError::ModelNotFound(format!(
-                    "model '{}': {}",
-                    request.model, body
-                )));
-            }
-
-            // Emit structured ServerError for 5xx, RequestFailed for other codes.
-            let code = status.as_u16();
-            if (500..=599).contains(&code) {
-                return Err(ProviderError::ServerError {
-                    status: code,
-                    body,
-                });
-            }
-
-            return Err(ProviderError::RequestFailed(format!(
-                "HTTP {status}: {body}"
-            )));
+            return Err(error_from_response(&self.config.name, &request.model, response).await);
         }
 
         // Read the SSE stream line by line
-        use futures_util::StreamExt;
         let mut byte_stream = response.bytes_stream();
-        let mut buffer = String::new();
-
-        while let Some(chunk_result) = byte_stream.next().await {
-            let bytes = chunk_result
-                .map_err(|e| ProviderError::RequestFailed(format!("stream read error: {e}")))?;
+        let mut lines = LineBuffer::default();
+        let mut splitter = ThinkSplitter::new();
 
-            let text = String::from_utf8_lossy(&bytes);
-            buffer.push_str(&text);
+        'read: while let Some(bytes) = timeouts.next_chunk(&mut byte_stream).await? {
+            lines.push(&bytes);
 
             // Process complete lines from the buffer
-            while let Some(newline_pos) = buffer.find('\n') {
-                let line = buffer[..newline_pos].to_string();
-                buffer = buffer[newline_pos + 1..].to_string();
-
-                let chunks = match parse_sse_line(&line) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        warn!(
-                            provider = %self.config.name,
-                            error = %e,
-                            "SSE parse error, skipping line"
-                        );
-                        continue;
-                    }
-                };
-
-                for chunk in chunks {
-                    trace!(
-                        provider = %self.config.name,
-                        chunk = ?chunk,
-                        "streaming chunk"
-                    );
-                    // If the receiver is dropped, stop processing
-                    if tx.send(chunk).await.is_err() {
-                        debug!(
-                            provider = %self.config.name,
-                            "stream receiver dropped, stopping"
-                        );
-                        return Ok(());
-                    }
+            while let Some(line) = lines.next_line() {
+                match self.forward_sse_line(&line, &mut splitter, &tx).await {
+                    LineOutcome::Continue => {}
+                    LineOutcome::Finished => break 'read,
+                    LineOutcome::ReceiverDropped => return Ok(()),
                 }
             }
         }
 
         // Process any remaining data in the buffer
-        if !buffer.trim().is_empty()
-            && let Ok(chunks) = parse_sse_line(&buffer)
+        if let Some(line) = lines.take_rest()
+            && let LineOutcome::ReceiverDropped =
+                self.forward_sse_line(&line, &mut splitter, &tx).await
         {
-            for chunk in chunks {
-                let _ = tx.send(chunk).await;
-            }
+            return Ok(());
         }
 
         debug!(
@@ -358,6 +291,130 @@ impl Provider for OpenAiCompatProvider {
     }
 }
 
+/// What the SSE reader should do after forwarding one line.
+enum LineOutcome {
+    /// Keep reading.
+    Continue,
+    /// The `[DONE]` sentinel was seen; anything after it is ignored.
+    Finished,
+    /// The consumer went away; stop without error.
+    ReceiverDropped,
+}
+
+impl OpenAiCompatProvider {
+    /// Parse one SSE line and forward its chunks to `tx`, with inline
+    /// `<think>` reasoning split out by `splitter`.
+    async fn forward_sse_line(
+        &self,
+        line: &str,
+        splitter: &mut ThinkSplitter,
+        tx: &mpsc::Sender<StreamChunk>,
+    ) -> LineOutcome {
+        let chunks = match parse_sse_line(line) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!(
+                    provider = %self.config.name,
+                    error = %e,
+                    "SSE parse error, skipping line"
+                );
+                return LineOutcome::Continue;
+            }
+        };
+
+        for chunk in chunks.into_iter().flat_map(|c| splitter.split(c)) {
+            trace!(
+                provider = %self.config.name,
+                chunk = ?chunk,
+                "streaming chunk"
+            );
+            // If the receiver is dropped, stop processing
+            if tx.send(chunk).await.is_err() {
+                debug!(
+                    provider = %self.config.name,
+                    "stream receiver dropped, stopping"
+                );
+                return LineOutcome::ReceiverDropped;
+            }
+        }
+
+        if is_done_sentinel(line) {
+            LineOutcome::Finished
+        } else {
+            LineOutcome::Continue
+        }
+    }
+}
+
+/// Whether `line` is the `data: [DONE]` end-of-stream marker.
+fn is_done_sentinel(line: &str) -> bool {
+    line.trim()
+        .strip_prefix("data:")
+        .is_some_and(|rest| rest.trim() == "[DONE]")
+}
+
+/// Map a non-success HTTP response to a [`ProviderError`].
+///
+/// 429 becomes [`RateLimited`](ProviderError::RateLimited) (or a plain
+/// request failure when the body says the quota is exhausted), 401/403
+/// [`AuthFailed`](ProviderError::AuthFailed), 404
+/// [`ModelNotFound`](ProviderError::ModelNotFound) and 5xx
+/// [`ServerError`](ProviderError::ServerError). Shared with the native
+/// Anthropic provider, whose error bodies use the same `error.message` shape.
+pub(crate) async fn error_from_response(
+    provider: &str,
+    model: &str,
+    response: reqwest::Response,
+) -> ProviderError {
+    let status = response.status();
+
+    if status.as_u16() == 429 {
+        // Try HTTP Retry-After header first, then body JSON, then default
+        let header_ms = parse_retry_after_header(&response);
+        let body = response.text().await.unwrap_or_default();
+
+        // Some providers (e.g. xAI) use 429 for exhausted credits/quota,
+        // which is not a transient rate limit and should not be retried.
+        if is_quota_exhausted(&body) {
+            let msg = extract_error_message(&body)
+                .unwrap_or_else(|| "credits exhausted or spending limit reached".into());
+            warn!(provider = %provider, "quota exhausted (not retryable)");
+            return ProviderError::RequestFailed(msg);
+        }
+
+        let retry_ms = header_ms
+            .or_else(|| parse_retry_after_ms(&body))
+            .unwrap_or(1000);
+        warn!(
+            provider = %provider,
+            retry_after_ms = retry_ms,
+            body = %body,
+            "rate limited"
+        );
+        return ProviderError::RateLimited {
+            retry_after_ms: retry_ms,
+        };
+    }
+
+    let body = response.text().await.unwrap_or_default();
+
+    if status.as_u16() == 401 || status.as_u16() == 403 {
+        return ProviderError::AuthFailed(body);
+    }
+
+    if status.as_u16() == 404 {
+        return ProviderError::ModelNotFound(format!("model '{model}': {body}"));
+    }
+
+    // Emit structured ServerError for 5xx, RequestFailed for other codes.
+    let code = status.as_u16();
+    if (500..=599).contains(&code) {
+        return ProviderError::ServerError { status: code, body };
+    }
+
+    ProviderError::RequestFailed(format!("HTTP {status}: {body}"))
+}
+
 /// Check if a 429 response body indicates a permanent quota/credit exhaustion
 /// rather than a transient rate limit. Some providers (xAI, OpenAI) return 429
 /// for billing issues that will never resolve with retries.
@@ -433,7 +490,7 @@ impl std::fmt::Debug for OpenAiCompatProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::LlmProviderConfig;
+    use crate::config::{AzureConfig, LlmProviderConfig};
     use std::collections::HashMap;
 
     fn test_config() -> LlmProviderConfig {
@@ -445,6 +502,11 @@ mod tests {
             default_model: Some("test-model".into()),
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         }
     }
 
@@ -457,6 +519,11 @@ mod tests {
             default_model: None,
             headers: HashMap::from([("anthropic-version".into(), "2023-06-01".into())]),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         }
     }
 
@@ -478,22 +545,73 @@ mod tests {
     fn completions_url_construction() {
         let provider = OpenAiCompatProvider::new(test_config());
         assert_eq!(
-            provider.completions_url(),
+            provider.completions_url("test-model"),
             "https://api.example.com/v1/chat/completions"
         );
     }
 
+    #[test]
+    fn models_url_construction() {
+        let provider = OpenAiCompatProvider::new(test_config());
+        assert_eq!(provider.models_url(), "https://api.example.com/v1/models");
+    }
+
     #[test]
     fn completions_url_strips_trailing_slash() {
         let mut config = test_config();
         config.base_url = "https://api.example.com/v1/".into();
         let provider = OpenAiCompatProvider::new(config);
         assert_eq!(
-            provider.completions_url(),
+            provider.completions_url("test-model"),
             "https://api.example.com/v1/chat/completions"
         );
     }
 
+    fn azure_config() -> LlmProviderConfig {
+        let mut config = test_config();
+        config.name = "azure".into();
+        config.base_url = "https://my-resource.openai.azure.com/".into();
+        config.azure = Some(AzureConfig {
+            deployments: HashMap::from([("gpt-4o".into(), "prod-gpt4o".into())]),
+            api_version: "2024-10-21".into(),
+        });
+        config
+    }
+
+    #[test]
+    fn azure_completions_url_uses_deployment() {
+        let provider = OpenAiCompatProvider::new(azure_config());
+        assert_eq!(
+            provider.completions_url("gpt-4o"),
+            "https://my-resource.openai.azure.com/openai/deployments/prod-gpt4o/chat/completions?api-version=2024-10-21"
+        );
+        // Unmapped models are used as the deployment name.
+        assert_eq!(
+            provider.completions_url("my-deployment"),
+            "https://my-resource.openai.azure.com/openai/deployments/my-deployment/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[test]
+    fn azure_authenticates_with_api_key_header() {
+        let azure = OpenAiCompatProvider::new(azure_config());
+        assert_eq!(azure.auth_header("k"), ("api-key", "k".to_string()));
+        let openai = OpenAiCompatProvider::new(test_config());
+        assert_eq!(
+            openai.auth_header("k"),
+            ("Authorization", "Bearer k".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn azure_models_are_not_listed() {
+        let provider = OpenAiCompatProvider::with_api_key(azure_config(), "k".into());
+        assert!(matches!(
+            provider.list_models().await,
+            Err(ProviderError::Unsupported(_))
+        ));
+    }
+
     #[test]
     fn resolve_api_key_explicit() {
         let provider = OpenAiCompatProvider::with_api_key(test_config(), "sk-explicit".into());
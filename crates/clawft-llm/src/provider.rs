@@ -1,12 +1,14 @@
 //! The core [`Provider`] trait for LLM chat completions.
 //!
 //! All LLM providers implement this trait, which provides a single
-//! `complete` method for executing chat completion requests.
+//! `complete` method for executing chat completion requests, plus
+//! `complete_stream` for providers that can stream their output.
 
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
 use crate::error::{ProviderError, Result};
+use crate::stream::StreamAccumulator;
 use crate::types::{ChatRequest, ChatResponse, StreamChunk};
 
 /// A provider that can execute chat completion requests.
@@ -65,4 +67,134 @@ pub trait Provider: Send + Sync {
             "streaming not supported by this provider".into(),
         ))
     }
+
+    /// Execute a streaming chat completion request, calling `on_chunk` for
+    /// every chunk as it arrives, and return the assembled response.
+    ///
+    /// Text and tool-call deltas are folded into a [`ChatResponse`] by a
+    /// [`StreamAccumulator`], including usage when the provider reports it
+    /// at the end of the stream. Built on
+    /// [`complete_stream`](Provider::complete_stream), so it works for every
+    /// provider that supports streaming.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `complete_stream`. Chunks already passed to
+    /// `on_chunk` are not retracted.
+    async fn complete_streaming(
+        &self,
+        request: &ChatRequest,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c StreamChunk) + Send),
+    ) -> Result<ChatResponse> {
+        let (tx, mut rx) = mpsc::channel::<StreamChunk>(64);
+        let mut acc = StreamAccumulator::new();
+        let receive = async {
+            while let Some(chunk) = rx.recv().await {
+                on_chunk(&chunk);
+                acc.push(&chunk);
+            }
+        };
+        let (result, ()) = tokio::join!(self.complete_stream(request, tx), receive);
+        result?;
+        Ok(acc.finish(&request.model))
+    }
+
+    /// List the models the provider serves, by the names it accepts in
+    /// [`ChatRequest::model`].
+    ///
+    /// The default implementation returns
+    /// [`ProviderError::Unsupported`]. Providers with a model listing
+    /// endpoint should override this.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError`](crate::error::ProviderError) if the listing
+    /// request fails or the provider cannot list its models.
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Err(ProviderError::Unsupported(format!(
+            "{} does not support listing models",
+            self.name()
+        )))
+    }
+}
+
+/// Boxed providers are providers too, so a `Box<dyn Provider>` chosen at
+/// runtime can be wrapped in [`RetryPolicy`](crate::retry::RetryPolicy).
+#[async_trait]
+impl<P: Provider + ?Sized> Provider for Box<P> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        (**self).complete(request).await
+    }
+
+    async fn complete_stream(
+        &self,
+        request: &ChatRequest,
+        tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<()> {
+        (**self).complete_stream(request, tx).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        (**self).list_models().await
+    }
+}
+
+/// Model names from a model listing response.
+///
+/// Accepts both the OpenAI format (`{"data": [{"id": ...}]}`) and Ollama's
+/// `/api/tags` format (`{"models": [{"name": ...}]}`).
+pub(crate) fn parse_model_list(body: &serde_json::Value) -> Vec<String> {
+    if let Some(data) = body.get("data").and_then(|d| d.as_array()) {
+        data.iter()
+            .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
+            .map(String::from)
+            .collect()
+    } else if let Some(models) = body.get("models").and_then(|m| m.as_array()) {
+        models
+            .iter()
+            .filter_map(|m| {
+                m.get("name")
+                    .or_else(|| m.get("id"))
+                    .and_then(|n| n.as_str())
+            })
+            .map(String::from)
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Run one streaming attempt against `provider`, forwarding chunks to `tx`
+/// as they arrive.
+///
+/// Returns the attempt's result together with whether any text or tool-call
+/// content reached `tx`. Wrappers that retry or fail over use the flag to
+/// decide whether a failed attempt can still be restarted: once content has
+/// been emitted, the consumer has seen partial output and a second attempt
+/// would duplicate it.
+pub(crate) async fn forward_stream<P: Provider + ?Sized>(
+    provider: &P,
+    request: &ChatRequest,
+    tx: &mpsc::Sender<StreamChunk>,
+) -> (Result<()>, bool) {
+    let (attempt_tx, mut attempt_rx) = mpsc::channel::<StreamChunk>(64);
+    let tx = tx.clone();
+    let forward = async move {
+        let mut emitted = false;
+        while let Some(chunk) = attempt_rx.recv().await {
+            if !matches!(chunk, StreamChunk::Done { .. }) {
+                emitted = true;
+            }
+            if tx.send(chunk).await.is_err() {
+                // Dropping attempt_rx stops the provider on its next send.
+                break;
+            }
+        }
+        emitted
+    };
+    tokio::join!(provider.complete_stream(request, attempt_tx), forward)
 }
@@ -0,0 +1,708 @@
+//! Client-side rate limiting for LLM providers.
+//!
+//! [`RateLimitedProvider`] wraps any [`Provider`] and admits each request
+//! through a [`RateLimiter`] built from the provider's [`RateLimitConfig`]:
+//! requests per minute, tokens per minute and concurrent requests. Waiting
+//! requests are admitted strictly in arrival order. A request that cannot be
+//! admitted within `max_queue_wait_secs` fails with
+//! [`ProviderError::RateLimited`], which
+//! [`RetryPolicy`](crate::retry::RetryPolicy) already backs off from.
+//!
+//! A 429 from the provider starts a cooldown that doubles on consecutive
+//! 429s and resets after the next success, so the limiter also backs off
+//! when the configured limits are looser than the provider's real ones.
+//!
+//! Limiters are shared per provider name (see [`shared`]), and
+//! [`snapshot`] reports their counters for status output.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use tokio::sync::{Notify, mpsc};
+use tracing::debug;
+
+use crate::config::RateLimitConfig;
+use crate::error::{ProviderError, Result};
+use crate::provider::Provider;
+use crate::tokens;
+use crate::types::{ChatRequest, ChatResponse, StreamChunk};
+
+/// Length of the sliding window for per-minute limits.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Cooldown after the first 429 in a row.
+const MIN_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Upper bound for the adaptive cooldown.
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Source of time for a [`RateLimiter`].
+///
+/// Production code uses [`SystemClock`]; tests substitute a clock they
+/// advance by hand so that waits are deterministic.
+pub trait Clock: Send + Sync {
+    /// The current instant.
+    fn now(&self) -> Instant;
+
+    /// A future that completes once `duration` has elapsed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The real clock, backed by [`Instant::now`] and [`tokio::time::sleep`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Point-in-time limiter counters, for status output.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RateLimitMetrics {
+    /// Requests waiting for admission.
+    pub queued: usize,
+    /// Requests currently in flight.
+    pub in_flight: u32,
+    /// Requests that had to wait before admission (or rejection).
+    pub throttled: u64,
+    /// Requests rejected after waiting `max_queue_wait_secs`.
+    pub rejected: u64,
+    /// 429 responses received from the provider.
+    pub rate_limited: u64,
+    /// Time left in the current 429 cooldown, in milliseconds.
+    pub cooldown_ms: u64,
+}
+
+/// Outcome of one admission check.
+enum Admission {
+    Admit,
+    /// Not yet. `Some` is the known time until the blocking limit frees
+    /// up; `None` means wait for another request to finish or be admitted.
+    Wait(Option<Duration>),
+}
+
+#[derive(Default)]
+struct State {
+    /// Tickets of waiting requests, in arrival order.
+    queue: VecDeque<u64>,
+    next_ticket: u64,
+    /// Start times of requests admitted within the window.
+    requests: VecDeque<Instant>,
+    /// `(ticket, admitted_at, tokens)` reservations within the window.
+    tokens: VecDeque<(u64, Instant, u64)>,
+    in_flight: u32,
+    cooldown_until: Option<Instant>,
+    /// Current adaptive cooldown; zero after a success.
+    backoff: Duration,
+    throttled: u64,
+    rejected: u64,
+    rate_limited: u64,
+}
+
+impl State {
+    fn prune(&mut self, now: Instant) {
+        while self.requests.front().is_some_and(|&at| at + WINDOW <= now) {
+            self.requests.pop_front();
+        }
+        while self
+            .tokens
+            .front()
+            .is_some_and(|&(_, at, _)| at + WINDOW <= now)
+        {
+            self.tokens.pop_front();
+        }
+    }
+}
+
+/// Admits requests to one provider under a [`RateLimitConfig`].
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+impl RateLimiter {
+    /// Create a limiter driven by the system clock.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// Create a limiter driven by `clock`.
+    pub fn with_clock(config: RateLimitConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            state: Mutex::new(State::default()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// The limits this limiter enforces.
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Wait for admission of a request expected to use `tokens` tokens.
+    ///
+    /// The returned permit counts towards the concurrency limit until it is
+    /// dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::RateLimited`] when the request cannot be
+    /// admitted within `max_queue_wait_secs`. `retry_after_ms` is the known
+    /// time until the blocking limit frees up, when there is one.
+    pub async fn acquire(self: &Arc<Self>, tokens: u64) -> Result<RatePermit> {
+        let ticket = {
+            let mut state = self.lock();
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            state.queue.push_back(ticket);
+            ticket
+        };
+        // Leaves the queue if this future is dropped or gives up.
+        let place = QueuePlace {
+            limiter: self,
+            ticket,
+        };
+        let deadline = self.clock.now() + Duration::from_secs(self.config.max_queue_wait_secs);
+        let mut waited = false;
+
+        loop {
+            // Register for wake-ups before checking, so a release between
+            // the check and the wait is not missed.
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let now = self.clock.now();
+            let wait = match self.poll(ticket, tokens, now) {
+                Admission::Admit => {
+                    std::mem::forget(place);
+                    return Ok(RatePermit {
+                        limiter: Arc::clone(self),
+                        ticket,
+                    });
+                }
+                Admission::Wait(wait) => wait,
+            };
+
+            if !waited {
+                waited = true;
+                self.lock().throttled += 1;
+            }
+            let remaining = deadline.saturating_duration_since(now);
+            if remaining.is_zero() || wait.is_some_and(|wait| wait > remaining) {
+                self.lock().rejected += 1;
+                let retry_after = wait.unwrap_or(MIN_COOLDOWN);
+                debug!(
+                    retry_after_ms = retry_after.as_millis() as u64,
+                    "rate limiter rejected request after max queue wait"
+                );
+                drop(place);
+                return Err(ProviderError::RateLimited {
+                    retry_after_ms: retry_after.as_millis() as u64,
+                });
+            }
+
+            tokio::select! {
+                () = self.clock.sleep(wait.unwrap_or(remaining)) => {}
+                () = &mut notified => {}
+            }
+        }
+    }
+
+    /// Record a 429 from the provider and start (or extend) the cooldown.
+    ///
+    /// The cooldown is the larger of the provider's `retry_after` and an
+    /// adaptive backoff that doubles with each consecutive 429.
+    pub fn record_rate_limited(&self, retry_after: Duration) {
+        let now = self.clock.now();
+        let mut state = self.lock();
+        state.rate_limited += 1;
+        state.backoff = if state.backoff.is_zero() {
+            MIN_COOLDOWN
+        } else {
+            (state.backoff * 2).min(MAX_COOLDOWN)
+        };
+        let until = now + state.backoff.max(retry_after);
+        state.cooldown_until = Some(state.cooldown_until.map_or(until, |prev| prev.max(until)));
+    }
+
+    /// Record a successful response, resetting the adaptive cooldown.
+    pub fn record_success(&self) {
+        self.lock().backoff = Duration::ZERO;
+    }
+
+    /// Current counters.
+    pub fn metrics(&self) -> RateLimitMetrics {
+        let now = self.clock.now();
+        let state = self.lock();
+        RateLimitMetrics {
+            queued: state.queue.len(),
+            in_flight: state.in_flight,
+            throttled: state.throttled,
+            rejected: state.rejected,
+            rate_limited: state.rate_limited,
+            cooldown_ms: state.cooldown_until.map_or(0, |until| {
+                until.saturating_duration_since(now).as_millis() as u64
+            }),
+        }
+    }
+
+    fn poll(&self, ticket: u64, tokens: u64, now: Instant) -> Admission {
+        let mut state = self.lock();
+        state.prune(now);
+
+        if state.queue.front() != Some(&ticket) {
+            return Admission::Wait(None);
+        }
+        if let Some(until) = state.cooldown_until
+            && until > now
+        {
+            return Admission::Wait(Some(until - now));
+        }
+        if let Some(max) = limit(self.config.max_concurrent)
+            && u64::from(state.in_flight) >= max
+        {
+            return Admission::Wait(None);
+        }
+        if let Some(rpm) = limit(self.config.requests_per_minute)
+            && state.requests.len() as u64 >= rpm
+        {
+            let oldest = state.requests[0];
+            return Admission::Wait(Some((oldest + WINDOW).saturating_duration_since(now)));
+        }
+        if let Some(tpm) = limit(self.config.tokens_per_minute) {
+            let used: u64 = state.tokens.iter().map(|&(_, _, n)| n).sum();
+            // A request larger than the whole budget still runs once the
+            // window is empty, rather than never.
+            if used > 0 && used + tokens > tpm {
+                let mut freed = 0;
+                for &(_, at, n) in &state.tokens {
+                    freed += n;
+                    if freed == used || used - freed + tokens <= tpm {
+                        return Admission::Wait(Some((at + WINDOW).saturating_duration_since(now)));
+                    }
+                }
+            }
+        }
+
+        state.queue.pop_front();
+        state.requests.push_back(now);
+        state.tokens.push_back((ticket, now, tokens));
+        state.in_flight += 1;
+        drop(state);
+        // The next request in line may fit as well.
+        self.notify.notify_waiters();
+        Admission::Admit
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A configured limit as `u64`; zero counts as unset.
+fn limit(value: Option<u32>) -> Option<u64> {
+    value.filter(|&n| n > 0).map(u64::from)
+}
+
+/// A request's place in the admission queue, removed on drop.
+struct QueuePlace<'a> {
+    limiter: &'a RateLimiter,
+    ticket: u64,
+}
+
+impl Drop for QueuePlace<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().queue.retain(|&t| t != self.ticket);
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+/// An admitted request. Dropping it frees its concurrency slot.
+pub struct RatePermit {
+    limiter: Arc<RateLimiter>,
+    ticket: u64,
+}
+
+impl RatePermit {
+    /// Replace this request's token reservation with the tokens it
+    /// actually used.
+    pub fn settle(&self, used: u64) {
+        let mut state = self.limiter.lock();
+        if let Some(entry) = state.tokens.iter_mut().find(|(t, _, _)| *t == self.ticket) {
+            entry.2 = used;
+        }
+    }
+}
+
+impl Drop for RatePermit {
+    fn drop(&mut self) {
+        {
+            let mut state = self.limiter.lock();
+            state.in_flight = state.in_flight.saturating_sub(1);
+        }
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+type Registry = Mutex<HashMap<String, Arc<RateLimiter>>>;
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// The process-wide limiter for `provider`.
+///
+/// Every provider instance built for the same name shares one limiter, so
+/// limits hold across agents and adapters. A changed `config` replaces the
+/// limiter; requests already holding permits finish under the old one.
+pub fn shared(provider: &str, config: &RateLimitConfig) -> Arc<RateLimiter> {
+    let mut limiters = registry().lock().unwrap_or_else(|e| e.into_inner());
+    match limiters.get(provider) {
+        Some(limiter) if limiter.config() == config => Arc::clone(limiter),
+        _ => {
+            let limiter = Arc::new(RateLimiter::new(config.clone()));
+            limiters.insert(provider.to_owned(), Arc::clone(&limiter));
+            limiter
+        }
+    }
+}
+
+/// Metrics for every shared limiter, sorted by provider name.
+pub fn snapshot() -> Vec<(String, RateLimitMetrics)> {
+    let limiters = registry().lock().unwrap_or_else(|e| e.into_inner());
+    let mut all: Vec<_> = limiters
+        .iter()
+        .map(|(name, limiter)| (name.clone(), limiter.metrics()))
+        .collect();
+    all.sort_by(|a, b| a.0.cmp(&b.0));
+    all
+}
+
+/// Tokens to reserve for `request`: the prompt estimate plus the requested
+/// completion budget, which is how providers count it against their limits.
+fn estimate_tokens(request: &ChatRequest) -> u64 {
+    let prompt = tokens::count_messages(&request.messages, &request.model) as u64;
+    prompt + request.max_tokens.map_or(0, |n| n.max(0) as u64)
+}
+
+/// A [`Provider`] whose requests pass through a [`RateLimiter`].
+pub struct RateLimitedProvider<P> {
+    inner: P,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<P: Provider> RateLimitedProvider<P> {
+    /// Wrap `inner`, admitting its requests through `limiter`.
+    pub fn new(inner: P, limiter: Arc<RateLimiter>) -> Self {
+        Self { inner, limiter }
+    }
+
+    /// The limiter in front of this provider.
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+
+    fn observe<T>(&self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.limiter.record_success(),
+            Err(ProviderError::RateLimited { retry_after_ms }) => self
+                .limiter
+                .record_rate_limited(Duration::from_millis(*retry_after_ms)),
+            Err(_) => {}
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Provider> Provider for RateLimitedProvider<P> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let permit = self.limiter.acquire(estimate_tokens(request)).await?;
+        let result = self.inner.complete(request).await;
+        self.observe(&result);
+        if let Ok(response) = &result
+            && let Some(usage) = &response.usage
+        {
+            permit.settle(u64::from(usage.total_tokens));
+        }
+        result
+    }
+
+    async fn complete_stream(
+        &self,
+        request: &ChatRequest,
+        tx: mpsc::Sender<StreamChunk>,
+    ) -> Result<()> {
+        let _permit = self.limiter.acquire(estimate_tokens(request)).await?;
+        let result = self.inner.complete_stream(request, tx).await;
+        self.observe(&result);
+        result
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.inner.list_models().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChatMessage;
+    use tokio::sync::oneshot;
+    use tokio::task::JoinHandle;
+
+    /// Pending sleeps: wake-up time and the sender that completes them.
+    type Sleepers = Vec<(Instant, oneshot::Sender<()>)>;
+
+    /// A clock that only moves when the test advances it.
+    struct MockClock {
+        state: Mutex<(Instant, Sleepers)>,
+    }
+
+    impl MockClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                state: Mutex::new((Instant::now(), Vec::new())),
+            })
+        }
+
+        fn advance(&self, by: Duration) {
+            let mut state = self.state.lock().unwrap();
+            state.0 += by;
+            let now = state.0;
+            let (due, pending) = std::mem::take(&mut state.1)
+                .into_iter()
+                .partition(|(at, _)| *at <= now);
+            state.1 = pending;
+            for (_, tx) in due {
+                let _ = tx.send(());
+            }
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.state.lock().unwrap().0
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            let (tx, rx) = oneshot::channel();
+            let mut state = self.state.lock().unwrap();
+            let at = state.0 + duration;
+            if duration.is_zero() {
+                let _ = tx.send(());
+            } else {
+                state.1.push((at, tx));
+            }
+            Box::pin(async move {
+                let _ = rx.await;
+            })
+        }
+    }
+
+    fn limiter(config: RateLimitConfig) -> (Arc<RateLimiter>, Arc<MockClock>) {
+        let clock = MockClock::new();
+        let limiter = Arc::new(RateLimiter::with_clock(config, clock.clone()));
+        (limiter, clock)
+    }
+
+    fn spawn_acquire(limiter: &Arc<RateLimiter>, tokens: u64) -> JoinHandle<Result<RatePermit>> {
+        let limiter = Arc::clone(limiter);
+        tokio::spawn(async move { limiter.acquire(tokens).await })
+    }
+
+    /// Let spawned tasks run until they block.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_per_minute_waits_for_the_window() {
+        let (limiter, clock) = limiter(RateLimitConfig {
+            requests_per_minute: Some(2),
+            max_queue_wait_secs: 120,
+            ..Default::default()
+        });
+        drop(limiter.acquire(0).await.unwrap());
+        drop(limiter.acquire(0).await.unwrap());
+
+        let third = spawn_acquire(&limiter, 0);
+        settle().await;
+        assert!(!third.is_finished());
+        assert_eq!(limiter.metrics().queued, 1);
+        assert_eq!(limiter.metrics().throttled, 1);
+
+        clock.advance(Duration::from_secs(59));
+        settle().await;
+        assert!(!third.is_finished());
+
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+        assert!(third.await.unwrap().is_ok());
+        assert_eq!(limiter.metrics().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn known_wait_beyond_max_queue_wait_fails_fast() {
+        let (limiter, _clock) = limiter(RateLimitConfig {
+            requests_per_minute: Some(1),
+            max_queue_wait_secs: 10,
+            ..Default::default()
+        });
+        let _first = limiter.acquire(0).await.unwrap();
+
+        let err = limiter.acquire(0).await.err().unwrap();
+        assert!(matches!(
+            err,
+            ProviderError::RateLimited {
+                retry_after_ms: 60_000
+            }
+        ));
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.rejected, 1);
+        assert_eq!(metrics.queued, 0);
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_admits_in_arrival_order() {
+        let (limiter, _clock) = limiter(RateLimitConfig {
+            max_concurrent: Some(1),
+            ..Default::default()
+        });
+        let first = limiter.acquire(0).await.unwrap();
+
+        let a = spawn_acquire(&limiter, 0);
+        settle().await;
+        let b = spawn_acquire(&limiter, 0);
+        settle().await;
+        let metrics = limiter.metrics();
+        assert_eq!((metrics.queued, metrics.in_flight), (2, 1));
+
+        drop(first);
+        settle().await;
+        assert!(a.is_finished());
+        assert!(!b.is_finished());
+
+        drop(a.await.unwrap().unwrap());
+        settle().await;
+        assert!(b.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrency_wait_times_out_as_rate_limited() {
+        let (limiter, clock) = limiter(RateLimitConfig {
+            max_concurrent: Some(1),
+            max_queue_wait_secs: 5,
+            ..Default::default()
+        });
+        let _held = limiter.acquire(0).await.unwrap();
+
+        let waiting = spawn_acquire(&limiter, 0);
+        settle().await;
+        clock.advance(Duration::from_secs(5));
+        settle().await;
+        let err = waiting.await.unwrap().err().unwrap();
+        assert!(matches!(err, ProviderError::RateLimited { .. }));
+        assert_eq!(limiter.metrics().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn tokens_per_minute_counts_settled_usage() {
+        let (limiter, _clock) = limiter(RateLimitConfig {
+            tokens_per_minute: Some(1_000),
+            ..Default::default()
+        });
+        let first = limiter.acquire(800).await.unwrap();
+        first.settle(100);
+        drop(first);
+
+        // 100 + 800 fits the budget.
+        drop(limiter.acquire(800).await.unwrap());
+
+        // 900 + 500 does not, and the window frees up in 60s > 30s.
+        let err = limiter.acquire(500).await.err().unwrap();
+        assert!(matches!(
+            err,
+            ProviderError::RateLimited {
+                retry_after_ms: 60_000
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn consecutive_429s_double_the_cooldown() {
+        let (limiter, clock) = limiter(RateLimitConfig::default());
+        limiter.record_rate_limited(Duration::ZERO);
+        assert_eq!(limiter.metrics().cooldown_ms, 1_000);
+
+        let waiting = spawn_acquire(&limiter, 0);
+        settle().await;
+        assert!(!waiting.is_finished());
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+        drop(waiting.await.unwrap().unwrap());
+
+        limiter.record_rate_limited(Duration::ZERO);
+        assert_eq!(limiter.metrics().cooldown_ms, 2_000);
+        clock.advance(Duration::from_secs(2));
+
+        limiter.record_success();
+        limiter.record_rate_limited(Duration::from_millis(1_500));
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.cooldown_ms, 1_500);
+        assert_eq!(metrics.rate_limited, 3);
+    }
+
+    struct Throttled;
+
+    #[async_trait]
+    impl Provider for Throttled {
+        fn name(&self) -> &str {
+            "throttled"
+        }
+
+        async fn complete(&self, _request: &ChatRequest) -> Result<ChatResponse> {
+            Err(ProviderError::RateLimited {
+                retry_after_ms: 5_000,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn provider_429_starts_cooldown() {
+        let (limiter, _clock) = limiter(RateLimitConfig {
+            max_concurrent: Some(2),
+            ..Default::default()
+        });
+        let provider = RateLimitedProvider::new(Throttled, Arc::clone(&limiter));
+        let request = ChatRequest::new("test-model", vec![ChatMessage::user("Hi")]);
+
+        let err = provider.complete(&request).await.unwrap_err();
+        assert!(matches!(err, ProviderError::RateLimited { .. }));
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.rate_limited, 1);
+        assert_eq!(metrics.cooldown_ms, 5_000);
+        assert_eq!(metrics.in_flight, 0);
+    }
+}
@@ -0,0 +1,428 @@
+//! Reasoning-model support.
+//!
+//! Reasoning models (OpenAI o-series and GPT-5, DeepSeek-R1 and its
+//! distills) think before they answer. This module decides whether a
+//! reasoning effort may be sent to a model, and keeps the model's
+//! reasoning apart from its reply so it never reaches the user:
+//!
+//! - DeepSeek returns it as `reasoning_content` (some gateways as
+//!   `reasoning`), which deserializes into [`ChatMessage::reasoning`] and
+//!   streams as [`StreamChunk::ReasoningDelta`].
+//! - R1 distills served by local runtimes inline it as a `<think>` block at
+//!   the start of the content. [`segregate`] moves it out of a complete
+//!   response and [`ThinkSplitter`] out of a stream.
+//!
+//! Reasoning tokens are reported in [`Usage::reasoning_tokens`] as a subset
+//! of the output tokens, so cost tracking prices them at the output rate.
+//!
+//! [`Usage::reasoning_tokens`]: crate::types::Usage::reasoning_tokens
+
+use std::borrow::Cow;
+
+use crate::tokens::find_model_spec;
+use crate::types::{
+    ChatMessage, ChatRequest, ChatResponse, MessageContent, ReasoningEffort, StreamChunk,
+};
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Whether `model` accepts the `reasoning_effort` parameter, per the model
+/// registry. Unknown models are assumed not to, since most reject it.
+pub fn accepts_reasoning_effort(model: &str) -> bool {
+    find_model_spec(model).is_some_and(|spec| spec.reasoning_effort)
+}
+
+/// The effort level closest to a reasoning-token budget.
+pub fn effort_for_budget(max_reasoning_tokens: u32) -> ReasoningEffort {
+    match max_reasoning_tokens {
+        0..=2_048 => ReasoningEffort::Low,
+        2_049..=16_384 => ReasoningEffort::Medium,
+        _ => ReasoningEffort::High,
+    }
+}
+
+/// The effort to send with `request`: its own, else one derived from its
+/// reasoning-token budget, else `default`. `None` when the model does not
+/// accept the parameter.
+pub fn effort_for(
+    request: &ChatRequest,
+    default: Option<ReasoningEffort>,
+) -> Option<ReasoningEffort> {
+    if !accepts_reasoning_effort(&request.model) {
+        return None;
+    }
+    request
+        .reasoning_effort
+        .or(request.max_reasoning_tokens.map(effort_for_budget))
+        .or(default)
+}
+
+/// `request` with its reasoning effort resolved by [`effort_for`], ready to
+/// be serialized for an OpenAI-compatible endpoint.
+pub fn with_effort(
+    request: &ChatRequest,
+    default: Option<ReasoningEffort>,
+) -> Cow<'_, ChatRequest> {
+    let effort = effort_for(request, default);
+    if effort == request.reasoning_effort {
+        return Cow::Borrowed(request);
+    }
+    let mut request = request.clone();
+    request.reasoning_effort = effort;
+    Cow::Owned(request)
+}
+
+/// Split inline reasoning off the start of `text`, returning
+/// `(reasoning, answer)`.
+///
+/// Recognizes a leading `<think>...</think>` block; an unterminated
+/// `<think>` makes the whole text reasoning. Returns `None` when `text`
+/// does not start with a `<think>` block.
+pub fn split_think(text: &str) -> Option<(String, String)> {
+    let rest = text.trim_start().strip_prefix(THINK_OPEN)?;
+    let (reasoning, answer) = rest.split_once(THINK_CLOSE).unwrap_or((rest, ""));
+    Some((
+        reasoning.trim().to_string(),
+        answer.trim_start().to_string(),
+    ))
+}
+
+/// Move inline `<think>` reasoning out of each choice's content and into
+/// [`ChatMessage::reasoning`].
+pub fn segregate(response: &mut ChatResponse) {
+    for choice in &mut response.choices {
+        segregate_message(&mut choice.message);
+    }
+}
+
+fn segregate_message(message: &mut ChatMessage) {
+    let Some(MessageContent::Text(text)) = &message.content else {
+        return;
+    };
+    let Some((reasoning, answer)) = split_think(text) else {
+        return;
+    };
+    message.content = Some(MessageContent::Text(answer));
+    if !reasoning.is_empty() {
+        message.reasoning = Some(match message.reasoning.take() {
+            Some(existing) => format!("{existing}\n{reasoning}"),
+            None => reasoning,
+        });
+    }
+}
+
+/// Where a [`ThinkSplitter`] is in the stream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SplitState {
+    /// Nothing decided yet: the text so far could still open a `<think>`.
+    #[default]
+    Start,
+    /// Inside a `<think>` block.
+    Thinking,
+    /// Just past `</think>`; leading whitespace of the answer is dropped.
+    AfterThink,
+    /// Plain reply text.
+    Answer,
+}
+
+/// Rewrites a chunk stream so inline `<think>` reasoning arrives as
+/// [`StreamChunk::ReasoningDelta`] instead of text.
+///
+/// Tags split across chunks are handled by holding back text that could be
+/// the start of a tag until the next chunk decides it.
+#[derive(Debug, Default)]
+pub struct ThinkSplitter {
+    state: SplitState,
+    pending: String,
+}
+
+impl ThinkSplitter {
+    /// Create a splitter for a new stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rewrite one chunk into zero or more chunks.
+    pub fn split(&mut self, chunk: StreamChunk) -> Vec<StreamChunk> {
+        let mut out = Vec::new();
+        match chunk {
+            StreamChunk::TextDelta { text } => {
+                self.pending.push_str(&text);
+                self.drain(&mut out);
+            }
+            reasoning @ StreamChunk::ReasoningDelta { .. } => out.push(reasoning),
+            other => {
+                self.flush(&mut out);
+                out.push(other);
+            }
+        }
+        out
+    }
+
+    /// Process as much of `pending` as can be decided.
+    fn drain(&mut self, out: &mut Vec<StreamChunk>) {
+        loop {
+            match self.state {
+                SplitState::Start => {
+                    let trimmed = self.pending.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(THINK_OPEN) {
+                        self.pending = rest.to_string();
+                        self.state = SplitState::Thinking;
+                    } else if THINK_OPEN.starts_with(trimmed) {
+                        return;
+                    } else {
+                        self.state = SplitState::Answer;
+                    }
+                }
+                SplitState::Thinking => {
+                    if let Some((reasoning, rest)) = self.pending.split_once(THINK_CLOSE) {
+                        push_reasoning(out, reasoning.to_string());
+                        self.pending = rest.to_string();
+                        self.state = SplitState::AfterThink;
+                    } else {
+                        let keep = partial_tag_len(&self.pending, THINK_CLOSE);
+                        let ready = self.pending.len() - keep;
+                        push_reasoning(out, self.pending.drain(..ready).collect());
+                        return;
+                    }
+                }
+                SplitState::AfterThink => {
+                    let answer = self.pending.trim_start();
+                    if answer.is_empty() {
+                        self.pending.clear();
+                        return;
+                    }
+                    self.pending = answer.to_string();
+                    self.state = SplitState::Answer;
+                }
+                SplitState::Answer => {
+                    push_text(out, std::mem::take(&mut self.pending));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Emit whatever is held back, as the current state implies.
+    fn flush(&mut self, out: &mut Vec<StreamChunk>) {
+        let pending = std::mem::take(&mut self.pending);
+        match self.state {
+            SplitState::Thinking => push_reasoning(out, pending),
+            SplitState::AfterThink => {}
+            SplitState::Start | SplitState::Answer => push_text(out, pending),
+        }
+        if self.state == SplitState::Start {
+            self.state = SplitState::Answer;
+        }
+    }
+}
+
+fn push_text(out: &mut Vec<StreamChunk>, text: String) {
+    if !text.is_empty() {
+        out.push(StreamChunk::TextDelta { text });
+    }
+}
+
+fn push_reasoning(out: &mut Vec<StreamChunk>, text: String) {
+    if !text.is_empty() {
+        out.push(StreamChunk::ReasoningDelta { text });
+    }
+}
+
+/// Length of the longest suffix of `text` that is a proper prefix of `tag`.
+fn partial_tag_len(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&n| text.ends_with(&tag[..n]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Choice;
+
+    fn request(model: &str) -> ChatRequest {
+        ChatRequest::new(model, vec![ChatMessage::user("hi")])
+    }
+
+    #[test]
+    fn registry_flags_reasoning_models() {
+        assert!(accepts_reasoning_effort("o3-mini"));
+        assert!(accepts_reasoning_effort("openai/o4-mini"));
+        assert!(accepts_reasoning_effort("gpt-5-mini"));
+        assert!(!accepts_reasoning_effort("gpt-5-chat-latest"));
+        assert!(!accepts_reasoning_effort("gpt-4o"));
+        assert!(!accepts_reasoning_effort("o1-mini"));
+        assert!(!accepts_reasoning_effort("deepseek-reasoner"));
+        assert!(!accepts_reasoning_effort("some-unknown-model"));
+    }
+
+    #[test]
+    fn effort_resolution_order() {
+        let mut req = request("o3");
+        assert_eq!(effort_for(&req, None), None);
+        assert_eq!(
+            effort_for(&req, Some(ReasoningEffort::Low)),
+            Some(ReasoningEffort::Low)
+        );
+
+        req.max_reasoning_tokens = Some(32_000);
+        assert_eq!(
+            effort_for(&req, Some(ReasoningEffort::Low)),
+            Some(ReasoningEffort::High)
+        );
+
+        req.reasoning_effort = Some(ReasoningEffort::Medium);
+        assert_eq!(
+            effort_for(&req, Some(ReasoningEffort::Low)),
+            Some(ReasoningEffort::Medium)
+        );
+    }
+
+    #[test]
+    fn effort_is_serialized_only_for_accepting_models() {
+        let mut req = request("o3");
+        req.reasoning_effort = Some(ReasoningEffort::High);
+        req.max_reasoning_tokens = Some(4_000);
+        let json = serde_json::to_value(with_effort(&req, None).as_ref()).unwrap();
+        assert_eq!(json["reasoning_effort"], "high");
+        assert!(json.get("max_reasoning_tokens").is_none());
+
+        req.model = "gpt-4o".into();
+        let json = serde_json::to_value(with_effort(&req, None).as_ref()).unwrap();
+        assert!(json.get("reasoning_effort").is_none());
+
+        let plain = request("gpt-4o");
+        assert!(matches!(
+            with_effort(&plain, Some(ReasoningEffort::High)),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn budget_maps_to_effort() {
+        assert_eq!(effort_for_budget(1_024), ReasoningEffort::Low);
+        assert_eq!(effort_for_budget(8_000), ReasoningEffort::Medium);
+        assert_eq!(effort_for_budget(50_000), ReasoningEffort::High);
+    }
+
+    #[test]
+    fn split_think_variants() {
+        assert_eq!(
+            split_think("<think>\nadd them\n</think>\n\n4"),
+            Some(("add them".into(), "4".into()))
+        );
+        assert_eq!(
+            split_think("  <think>cut off"),
+            Some(("cut off".into(), String::new()))
+        );
+        assert_eq!(split_think("just an answer"), None);
+        assert_eq!(split_think("quoting <think> mid-text"), None);
+    }
+
+    #[test]
+    fn deepseek_reasoning_content_is_kept_apart() {
+        let json = r#"{
+            "id": "r1",
+            "model": "deepseek-reasoner",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "4",
+                    "reasoning_content": "2 + 2 is 4."
+                },
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 30,
+                "completion_tokens_details": {"reasoning_tokens": 25}
+            }
+        }"#;
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+        let message = &response.choices[0].message;
+        assert_eq!(message.text().as_deref(), Some("4"));
+        assert_eq!(message.reasoning.as_deref(), Some("2 + 2 is 4."));
+        assert_eq!(response.usage.unwrap().reasoning_tokens, 25);
+
+        // Echoing the message back must not send the reasoning.
+        let echoed = serde_json::to_value(message).unwrap();
+        assert!(echoed.get("reasoning_content").is_none());
+        assert!(echoed.get("reasoning").is_none());
+    }
+
+    #[test]
+    fn segregate_moves_think_block_out_of_content() {
+        let mut response = ChatResponse {
+            id: "x".into(),
+            choices: vec![Choice {
+                index: 0,
+                message: ChatMessage::assistant("<think>carry the one</think>\n42"),
+                finish_reason: Some("stop".into()),
+            }],
+            usage: None,
+            model: "deepseek-r1:7b".into(),
+            failover: Vec::new(),
+            retries: 0,
+        };
+        segregate(&mut response);
+        let message = &response.choices[0].message;
+        assert_eq!(message.text().as_deref(), Some("42"));
+        assert_eq!(message.reasoning.as_deref(), Some("carry the one"));
+    }
+
+    fn run(splitter: &mut ThinkSplitter, parts: &[&str]) -> (String, String) {
+        let mut chunks: Vec<StreamChunk> = parts
+            .iter()
+            .flat_map(|p| splitter.split(StreamChunk::TextDelta { text: (*p).into() }))
+            .collect();
+        chunks.extend(splitter.split(StreamChunk::Done {
+            finish_reason: Some("stop".into()),
+            usage: None,
+        }));
+        assert!(matches!(chunks.last(), Some(StreamChunk::Done { .. })));
+        let (mut text, mut reasoning) = (String::new(), String::new());
+        for chunk in chunks {
+            match chunk {
+                StreamChunk::TextDelta { text: t } => text.push_str(&t),
+                StreamChunk::ReasoningDelta { text: t } => reasoning.push_str(&t),
+                _ => {}
+            }
+        }
+        (reasoning, text)
+    }
+
+    #[test]
+    fn splitter_handles_tags_across_chunks() {
+        let mut splitter = ThinkSplitter::new();
+        let (reasoning, text) = run(
+            &mut splitter,
+            &["<thi", "nk>two plus", " two</th", "ink>", "\n\nFour", "."],
+        );
+        assert_eq!(reasoning, "two plus two");
+        assert_eq!(text, "Four.");
+    }
+
+    #[test]
+    fn splitter_passes_plain_text_through() {
+        let mut splitter = ThinkSplitter::new();
+        let (reasoning, text) = run(&mut splitter, &["<", "b>bold</b> and <think> later"]);
+        assert_eq!(reasoning, "");
+        assert_eq!(text, "<b>bold</b> and <think> later");
+
+        let mut splitter = ThinkSplitter::new();
+        let (reasoning, text) = run(&mut splitter, &["<thi"]);
+        assert_eq!((reasoning.as_str(), text.as_str()), ("", "<thi"));
+    }
+
+    #[test]
+    fn splitter_flushes_unterminated_reasoning() {
+        let mut splitter = ThinkSplitter::new();
+        let (reasoning, text) = run(&mut splitter, &["<think>still going</"]);
+        assert_eq!(reasoning, "still going</");
+        assert_eq!(text, "");
+    }
+}
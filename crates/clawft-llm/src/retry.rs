@@ -3,6 +3,8 @@
 //! [`RetryPolicy`] wraps any [`Provider`] and automatically retries failed
 //! requests with configurable exponential backoff. Retries are applied to
 //! transient errors (HTTP 429, 500, 502, 503, 504) and network failures.
+//! A streaming request is only retried if the failed attempt had not emitted
+//! any content yet.
 
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -14,7 +16,7 @@ use tokio::sync::mpsc;
 
 use crate::error::{ProviderError, Result};
 use crate::eml_retry::{error_ordinal, RetryModel};
-use crate::provider::Provider;
+use crate::provider::{Provider, forward_stream};
 use crate::types::{ChatRequest, ChatResponse, StreamChunk};
 
 /// Configuration for retry behavior.
@@ -53,6 +55,7 @@ pub fn is_retryable(err: &ProviderError) -> bool {
         | ProviderError::ModelNotFound(_)
         | ProviderError::NotConfigured(_)
         | ProviderError::InvalidResponse(_)
+        | ProviderError::Unsupported(_)
         | ProviderError::Json(_)
         | ProviderError::AllProvidersExhausted { .. } => false,
     }
@@ -216,7 +219,8 @@ impl<P: Provider> Provider for RetryPolicy<P> {
 
         for attempt in 0..=self.config.max_retries {
             match self.inner.complete(request).await {
-                Ok(response) => {
+                Ok(mut response) => {
+                    response.retries += attempt;
                     if attempt > 0 {
                         debug!(
                             provider = %self.inner.name(),
@@ -280,16 +284,16 @@ impl<P: Provider> Provider for RetryPolicy<P> {
         request: &ChatRequest,
         tx: mpsc::Sender<StreamChunk>,
     ) -> Result<()> {
-        // Streaming retry with buffer-then-commit: each attempt writes to a
-        // temporary channel. Only on success do we forward chunks to the real
-        // sender, preventing partial output from failed attempts.
+        // Chunks are forwarded live. An attempt that fails after emitting
+        // content is not retried, since the consumer already holds partial
+        // output that a second attempt would duplicate.
         let mut last_err = None;
         let mut last_retry: Option<(f64, u32, Duration)> = None;
 
         for attempt in 0..=self.config.max_retries {
-            let (attempt_tx, mut attempt_rx) = mpsc::channel::<StreamChunk>(256);
+            let (result, emitted) = forward_stream(&self.inner, request, &tx).await;
 
-            match self.inner.complete_stream(request, attempt_tx).await {
+            match result {
                 Ok(()) => {
                     if attempt > 0 {
                         debug!(
@@ -308,19 +312,19 @@ impl<P: Provider> Provider for RetryPolicy<P> {
                             );
                         }
                     }
-                    // Forward buffered chunks to the real sender.
-                    while let Some(chunk) = attempt_rx.recv().await {
-                        if tx.send(chunk).await.is_err() {
-                            break;
-                        }
-                    }
                     return Ok(());
                 }
                 Err(err) => {
-                    // Discard buffered partial chunks from this failed attempt.
-                    drop(attempt_rx);
+                    if emitted {
+                        warn!(
+                            provider = %self.inner.name(),
+                            attempt,
+                            error = %err,
+                            "streaming request failed after emitting content, not retrying"
+                        );
+                    }
 
-                    if !is_retryable(&err) || attempt == self.config.max_retries {
+                    if emitted || !is_retryable(&err) || attempt == self.config.max_retries {
                         if let Some((ord, prev_attempt, prev_delay)) =
                             last_retry.take()
                         {
@@ -356,6 +360,10 @@ impl<P: Provider> Provider for RetryPolicy<P> {
             "streaming retry loop exhausted without error".into(),
         )))
     }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.inner.list_models().await
+    }
 }
 
 impl<P: std::fmt::Debug> std::fmt::Debug for RetryPolicy<P> {
@@ -402,8 +410,14 @@ mod tests {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 15,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 }),
                 model: "test-model".into(),
+                failover: Vec::new(),
+                retries: 0,
             }
         }
     }
@@ -580,7 +594,8 @@ mod tests {
         let provider = RetryPolicy::new(mock, fast_retry_config());
 
         let resp = provider.complete(&test_request()).await.unwrap();
-        assert_eq!(resp.choices[0].message.content.as_deref(), Some("Hello!"));
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello!"));
+        assert_eq!(resp.retries, 0);
     }
 
     #[tokio::test]
@@ -592,7 +607,8 @@ mod tests {
         let provider = RetryPolicy::new(mock, fast_retry_config());
 
         let resp = provider.complete(&test_request()).await.unwrap();
-        assert_eq!(resp.choices[0].message.content.as_deref(), Some("Hello!"));
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello!"));
+        assert_eq!(resp.retries, 2);
     }
 
     #[tokio::test]
@@ -659,7 +675,7 @@ mod tests {
         let provider = RetryPolicy::new(mock, config);
 
         let resp = provider.complete(&test_request()).await.unwrap();
-        assert_eq!(resp.choices[0].message.content.as_deref(), Some("Hello!"));
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello!"));
     }
 
     #[test]
@@ -698,7 +714,7 @@ mod tests {
         assert!(provider.retry_model().is_some());
 
         let resp = provider.complete(&test_request()).await.unwrap();
-        assert_eq!(resp.choices[0].message.content.as_deref(), Some("Hello!"));
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello!"));
 
         // Exactly one record: the failed attempt whose next retry
         // succeeded.
@@ -729,4 +745,98 @@ mod tests {
         // until their successor attempt's outcome is known.)
         assert_eq!(model.lock().unwrap().training_sample_count(), 1);
     }
+
+    /// Fails its first `failures` streaming attempts with a 503, each after
+    /// emitting `chunks_before_failure` text deltas.
+    struct FlakyStream {
+        attempts: AtomicU32,
+        failures: u32,
+        chunks_before_failure: usize,
+    }
+
+    #[async_trait]
+    impl Provider for FlakyStream {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn complete(&self, _request: &ChatRequest) -> Result<ChatResponse> {
+            Ok(MockProvider::success_response())
+        }
+
+        async fn complete_stream(
+            &self,
+            _request: &ChatRequest,
+            tx: mpsc::Sender<StreamChunk>,
+        ) -> Result<()> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures {
+                for _ in 0..self.chunks_before_failure {
+                    let _ = tx
+                        .send(StreamChunk::TextDelta {
+                            text: "partial".into(),
+                        })
+                        .await;
+                }
+                return Err(ProviderError::ServerError {
+                    status: 503,
+                    body: "dropped".into(),
+                });
+            }
+            let _ = tx
+                .send(StreamChunk::TextDelta {
+                    text: "complete".into(),
+                })
+                .await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_retries_failures_before_content() {
+        let provider = RetryPolicy::new(
+            FlakyStream {
+                attempts: AtomicU32::new(0),
+                failures: 2,
+                chunks_before_failure: 0,
+            },
+            fast_retry_config(),
+        );
+
+        let resp = provider
+            .complete_streaming(&test_request(), &mut |_| {})
+            .await
+            .unwrap();
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("complete"));
+        assert_eq!(provider.inner.attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_does_not_retry_after_content() {
+        let provider = RetryPolicy::new(
+            FlakyStream {
+                attempts: AtomicU32::new(0),
+                failures: 2,
+                chunks_before_failure: 1,
+            },
+            fast_retry_config(),
+        );
+
+        let mut seen = Vec::new();
+        let err = provider
+            .complete_streaming(&test_request(), &mut |chunk| seen.push(chunk.clone()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ProviderError::ServerError { status: 503, .. }
+        ));
+        assert_eq!(provider.inner.attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            seen,
+            vec![StreamChunk::TextDelta {
+                text: "partial".into()
+            }]
+        );
+    }
 }
@@ -5,9 +5,15 @@
 
 use std::collections::HashMap;
 
+use crate::anthropic::AnthropicProvider;
 use crate::config::{self, LlmProviderConfig};
+use crate::embeddings::{EmbeddingsProvider, OpenAiCompatEmbeddings};
+use crate::error::{ProviderError, Result};
+use crate::gemini::GeminiProvider;
+use crate::local_provider::LocalProvider;
 use crate::openai_compat::OpenAiCompatProvider;
 use crate::provider::Provider;
+use crate::rate_limit::{self, RateLimitedProvider};
 
 /// Routes model names to providers based on prefix matching.
 ///
@@ -31,6 +37,9 @@ use crate::provider::Provider;
 pub struct ProviderRouter {
     /// Provider instances keyed by provider name.
     providers: HashMap<String, Box<dyn Provider>>,
+    /// Embeddings clients keyed by provider name, for providers that have an
+    /// OpenAI-compatible `/embeddings` endpoint.
+    embedders: HashMap<String, Box<dyn EmbeddingsProvider>>,
     /// Mapping of prefix strings to provider names, sorted longest-first
     /// for greedy matching.
     prefix_map: Vec<(String, String)>,
@@ -41,11 +50,16 @@ pub struct ProviderRouter {
 impl ProviderRouter {
     /// Create a router from a list of provider configurations.
     ///
-    /// The first provider in the list becomes the default.
+    /// The first provider in the list becomes the default. Every provider
+    /// speaks the OpenAI-compatible API except `gemini`, which uses the
+    /// native [`GeminiProvider`], and `anthropic`, which uses the native
+    /// [`AnthropicProvider`] when its API key is set (see
+    /// [`provider_for_config`]).
     pub fn from_configs(configs: Vec<LlmProviderConfig>) -> Self {
         let default_provider = configs.first().map(|c| c.name.clone()).unwrap_or_default();
 
         let mut providers: HashMap<String, Box<dyn Provider>> = HashMap::new();
+        let mut embedders: HashMap<String, Box<dyn EmbeddingsProvider>> = HashMap::new();
         let mut prefix_map: Vec<(String, String)> = Vec::new();
 
         for config in configs {
@@ -53,7 +67,13 @@ impl ProviderRouter {
             if let Some(ref prefix) = config.model_prefix {
                 prefix_map.push((prefix.clone(), name.clone()));
             }
-            providers.insert(name, Box::new(OpenAiCompatProvider::new(config)));
+            if has_embeddings(&config) {
+                embedders.insert(
+                    name.clone(),
+                    Box::new(OpenAiCompatEmbeddings::new(config.clone())),
+                );
+            }
+            providers.insert(name, provider_for_config(config, None));
         }
 
         // Sort by prefix length descending for greedy matching
@@ -62,6 +82,7 @@ impl ProviderRouter {
 
         Self {
             providers,
+            embedders,
             prefix_map,
             default_provider,
         }
@@ -94,6 +115,28 @@ impl ProviderRouter {
             .map(|p| (p.as_ref(), model.to_string()))
     }
 
+    /// Route an embedding model name (e.g. "openai/text-embedding-3-small")
+    /// to an embeddings client, using the same prefix rules as
+    /// [`route`](Self::route).
+    ///
+    /// Returns `None` if the resolved provider has no embeddings endpoint.
+    pub fn route_embeddings(&self, model: &str) -> Option<(&dyn EmbeddingsProvider, String)> {
+        for (prefix, provider_name) in &self.prefix_map {
+            if let Some(stripped) = model.strip_prefix(prefix.as_str())
+                && self.providers.contains_key(provider_name)
+            {
+                return self
+                    .embedders
+                    .get(provider_name)
+                    .map(|e| (e.as_ref(), stripped.to_string()));
+            }
+        }
+
+        self.embedders
+            .get(&self.default_provider)
+            .map(|e| (e.as_ref(), model.to_string()))
+    }
+
     /// Split a model string into its optional prefix and the bare model name.
     ///
     /// Uses the first `/` as the separator. If no `/` is present, the entire
@@ -136,12 +179,148 @@ impl ProviderRouter {
     pub fn get_provider(&self, name: &str) -> Option<&dyn Provider> {
         self.providers.get(name).map(|p| p.as_ref())
     }
+
+    /// Confirm that `model` is served by the provider it routes to, so a
+    /// misconfigured model is reported before the first request fails.
+    ///
+    /// See [`check_model`] for how the provider's model list is matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProviderError::NotConfigured`] if no provider matches,
+    /// otherwise the error from [`check_model`].
+    pub async fn validate_model(&self, model: &str) -> Result<()> {
+        let (provider, bare) = self.route(model).ok_or_else(|| {
+            ProviderError::NotConfigured(format!("no provider for model '{model}'"))
+        })?;
+        check_model(provider, &bare).await
+    }
+}
+
+/// How many available models a [`check_model`] error lists.
+const LISTED_MODELS: usize = 10;
+
+/// Confirm that `provider` lists `model`.
+///
+/// Ollama lists models with a tag (`llama3.2:latest`), so an untagged name
+/// matches its `:latest` tag. Providers that cannot list their models are
+/// assumed to serve it.
+///
+/// # Errors
+///
+/// Returns [`ProviderError::ModelNotFound`], naming some available models,
+/// if the listing does not include `model`, or the listing error if the
+/// provider could not be asked.
+pub async fn check_model(provider: &dyn Provider, model: &str) -> Result<()> {
+    let models = match provider.list_models().await {
+        Ok(models) => models,
+        Err(ProviderError::Unsupported(_)) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let listed = |name: &String| name == model || name.strip_suffix(":latest") == Some(model);
+    if models.iter().any(listed) {
+        return Ok(());
+    }
+
+    let mut available = models
+        .iter()
+        .take(LISTED_MODELS)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if models.len() > LISTED_MODELS {
+        available.push_str(&format!(", ... ({} total)", models.len()));
+    }
+    if available.is_empty() {
+        available = "none".into();
+    }
+    Err(ProviderError::ModelNotFound(format!(
+        "model '{model}' is not served by {} (available: {available})",
+        provider.name()
+    )))
+}
+
+/// Build the provider implementation for a config.
+///
+/// `api_key` is an explicit key; without one the provider reads
+/// `config.api_key_env` at request time.
+///
+/// - `gemini` always gets the native `generateContent` provider, since the
+///   OpenAI-compatible endpoint handles tool calling poorly.
+/// - `anthropic` gets the native Messages API provider, which keeps system
+///   prompts, tool-use blocks and prompt-cache usage intact, as long as a key
+///   is available. Without one the OpenAI-compatible path reports the missing
+///   key the same way as every other provider.
+/// - `local` and `ollama` get the [`LocalProvider`], which does not require
+///   an API key.
+/// - Everything else uses the OpenAI-compatible provider.
+///
+/// When `config.rate_limit` sets any limit, the provider is wrapped in a
+/// [`RateLimitedProvider`] sharing the process-wide limiter for its name.
+pub fn provider_for_config(
+    config: LlmProviderConfig,
+    api_key: Option<String>,
+) -> Box<dyn Provider> {
+    let api_key = api_key.filter(|key| !key.is_empty());
+    let limiter = (!config.rate_limit.is_unlimited())
+        .then(|| rate_limit::shared(&config.name, &config.rate_limit));
+    let provider: Box<dyn Provider> = match ProviderKind::for_config(&config, api_key.as_deref()) {
+        ProviderKind::Gemini => Box::new(match api_key {
+            Some(key) => GeminiProvider::with_api_key(config, key),
+            None => GeminiProvider::new(config),
+        }),
+        ProviderKind::Anthropic => Box::new(match api_key {
+            Some(key) => AnthropicProvider::with_api_key(config, key),
+            None => AnthropicProvider::new(config),
+        }),
+        ProviderKind::Local => Box::new(LocalProvider::from_config(config, api_key)),
+        ProviderKind::OpenAiCompat => Box::new(match api_key {
+            Some(key) => OpenAiCompatProvider::with_api_key(config, key),
+            None => OpenAiCompatProvider::new(config),
+        }),
+    };
+    match limiter {
+        Some(limiter) => Box::new(RateLimitedProvider::new(provider, limiter)),
+        None => provider,
+    }
+}
+
+/// Whether a provider serves OpenAI-compatible embeddings. Anthropic has no
+/// embeddings API, and Azure serves embeddings from per-deployment URLs.
+fn has_embeddings(config: &LlmProviderConfig) -> bool {
+    config.name != "anthropic" && config.azure.is_none()
+}
+
+/// Which wire protocol [`provider_for_config`] picks for a config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProviderKind {
+    OpenAiCompat,
+    Anthropic,
+    Gemini,
+    Local,
+}
+
+impl ProviderKind {
+    fn for_config(config: &LlmProviderConfig, api_key: Option<&str>) -> Self {
+        match config.name.as_str() {
+            "gemini" => Self::Gemini,
+            "local" | "ollama" => Self::Local,
+            "anthropic"
+                if api_key.is_some_and(|key| !key.is_empty())
+                    || std::env::var(&config.api_key_env).is_ok_and(|key| !key.is_empty()) =>
+            {
+                Self::Anthropic
+            }
+            _ => Self::OpenAiCompat,
+        }
+    }
 }
 
 impl std::fmt::Debug for ProviderRouter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ProviderRouter")
             .field("providers", &self.providers.keys().collect::<Vec<_>>())
+            .field("embedders", &self.embedders.keys().collect::<Vec<_>>())
             .field("prefix_map", &self.prefix_map)
             .field("default_provider", &self.default_provider)
             .finish()
@@ -163,6 +342,11 @@ mod tests {
                 default_model: Some("gpt-4o".into()),
                 headers: HashMap::new(),
                 timeout_secs: None,
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
+                reasoning_effort: None,
+                rate_limit: Default::default(),
+                azure: None,
             },
             LlmProviderConfig {
                 name: "anthropic".into(),
@@ -172,6 +356,11 @@ mod tests {
                 default_model: None,
                 headers: HashMap::new(),
                 timeout_secs: None,
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
+                reasoning_effort: None,
+                rate_limit: Default::default(),
+                azure: None,
             },
             LlmProviderConfig {
                 name: "groq".into(),
@@ -181,6 +370,11 @@ mod tests {
                 default_model: None,
                 headers: HashMap::new(),
                 timeout_secs: None,
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
+                reasoning_effort: None,
+                rate_limit: Default::default(),
+                azure: None,
             },
         ]
     }
@@ -280,7 +474,7 @@ mod tests {
     fn with_builtins_has_all_providers() {
         let router = ProviderRouter::with_builtins();
         let providers = router.providers();
-        assert_eq!(providers.len(), 11);
+        assert_eq!(providers.len(), 12);
         assert!(providers.contains(&"openai".to_string()));
         assert!(providers.contains(&"anthropic".to_string()));
         assert!(providers.contains(&"groq".to_string()));
@@ -290,6 +484,7 @@ mod tests {
         assert!(providers.contains(&"openrouter".to_string()));
         assert!(providers.contains(&"gemini".to_string()));
         assert!(providers.contains(&"xai".to_string()));
+        assert!(providers.contains(&"azure".to_string()));
         assert!(providers.contains(&"local".to_string()));
         assert!(providers.contains(&"ollama".to_string()));
     }
@@ -326,6 +521,11 @@ mod tests {
             default_model: None,
             headers: HashMap::new(),
             timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
         }];
         let router = ProviderRouter::from_configs(configs);
         // Should still work via default fallback
@@ -333,4 +533,147 @@ mod tests {
         assert_eq!(provider.name(), "custom");
         assert_eq!(model, "some-model");
     }
+
+    #[test]
+    fn anthropic_goes_native_only_with_a_key() {
+        let mut anthropic = test_configs()
+            .into_iter()
+            .find(|c| c.name == "anthropic")
+            .unwrap();
+        anthropic.api_key_env = "CLAWFT_TEST_ROUTER_ANTHROPIC_KEY".into();
+        let kind = |key| ProviderKind::for_config(&anthropic, key);
+
+        temp_env::with_var("CLAWFT_TEST_ROUTER_ANTHROPIC_KEY", Some("sk-ant"), || {
+            assert_eq!(kind(None), ProviderKind::Anthropic);
+        });
+        temp_env::with_var("CLAWFT_TEST_ROUTER_ANTHROPIC_KEY", Some(""), || {
+            assert_eq!(kind(None), ProviderKind::OpenAiCompat);
+        });
+        temp_env::with_var_unset("CLAWFT_TEST_ROUTER_ANTHROPIC_KEY", || {
+            assert_eq!(kind(None), ProviderKind::OpenAiCompat);
+            assert_eq!(kind(Some("")), ProviderKind::OpenAiCompat);
+            assert_eq!(kind(Some("sk-ant")), ProviderKind::Anthropic);
+        });
+
+        let mut openai = test_configs().remove(0);
+        openai.api_key_env = "CLAWFT_TEST_ROUTER_ANTHROPIC_KEY".into();
+        temp_env::with_var("CLAWFT_TEST_ROUTER_ANTHROPIC_KEY", Some("sk"), || {
+            assert_eq!(
+                ProviderKind::for_config(&openai, Some("sk")),
+                ProviderKind::OpenAiCompat
+            );
+        });
+    }
+
+    #[test]
+    fn ollama_needs_no_api_key() {
+        let ollama = config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "ollama")
+            .unwrap();
+        assert_eq!(ollama.base_url, "http://localhost:11434/v1");
+        assert_eq!(ProviderKind::for_config(&ollama, None), ProviderKind::Local);
+
+        let router = ProviderRouter::with_builtins();
+        let (provider, model) = router.route("ollama/llama3.2").unwrap();
+        assert_eq!(provider.name(), "ollama");
+        assert_eq!(model, "llama3.2");
+    }
+
+    struct Listing(std::result::Result<Vec<&'static str>, fn() -> ProviderError>);
+
+    #[async_trait::async_trait]
+    impl Provider for Listing {
+        fn name(&self) -> &str {
+            "listing"
+        }
+        async fn complete(
+            &self,
+            _request: &crate::types::ChatRequest,
+        ) -> Result<crate::types::ChatResponse> {
+            unreachable!()
+        }
+        async fn list_models(&self) -> Result<Vec<String>> {
+            match &self.0 {
+                Ok(models) => Ok(models.iter().map(|m| m.to_string()).collect()),
+                Err(error) => Err(error()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn check_model_matches_listed_and_tagged_names() {
+        let provider = Listing(Ok(vec!["gpt-4o", "llama3.2:latest"]));
+        check_model(&provider, "gpt-4o").await.unwrap();
+        check_model(&provider, "llama3.2").await.unwrap();
+        check_model(&provider, "llama3.2:latest").await.unwrap();
+
+        let err = check_model(&provider, "mistral").await.unwrap_err();
+        match err {
+            ProviderError::ModelNotFound(msg) => {
+                assert!(msg.contains("'mistral'"));
+                assert!(msg.contains("gpt-4o, llama3.2:latest"));
+            }
+            other => panic!("expected ModelNotFound, got: {other}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn check_model_trusts_providers_that_cannot_list() {
+        let unsupported = Listing(Err(|| ProviderError::Unsupported("no listing".into())));
+        check_model(&unsupported, "anything").await.unwrap();
+
+        let unreachable = Listing(Err(|| ProviderError::RequestFailed("refused".into())));
+        assert!(matches!(
+            check_model(&unreachable, "anything").await,
+            Err(ProviderError::RequestFailed(_))
+        ));
+    }
+
+    #[test]
+    fn azure_routes_deployment_ids() {
+        let router = ProviderRouter::with_builtins();
+        let (provider, model) = router.route("azure/prod-gpt4o").unwrap();
+        assert_eq!(provider.name(), "azure");
+        assert_eq!(model, "prod-gpt4o");
+        assert!(
+            router
+                .route_embeddings("azure/text-embedding-3-small")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn gemini_is_always_native() {
+        let gemini = config::builtin_providers()
+            .into_iter()
+            .find(|c| c.name == "gemini")
+            .unwrap();
+        assert_eq!(
+            ProviderKind::for_config(&gemini, None),
+            ProviderKind::Gemini
+        );
+
+        let router = ProviderRouter::with_builtins();
+        let (provider, model) = router.route("gemini/gemini-2.5-flash").unwrap();
+        assert_eq!(provider.name(), "gemini");
+        assert_eq!(model, "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn route_embeddings_by_prefix() {
+        let router = ProviderRouter::from_configs(test_configs());
+
+        let (embedder, model) = router
+            .route_embeddings("openai/text-embedding-3-small")
+            .unwrap();
+        assert_eq!(embedder.name(), "openai");
+        assert_eq!(model, "text-embedding-3-small");
+
+        let (embedder, model) = router.route_embeddings("nomic-embed-text").unwrap();
+        assert_eq!(embedder.name(), "openai");
+        assert_eq!(model, "nomic-embed-text");
+
+        assert!(router.route_embeddings("anthropic/anything").is_none());
+    }
 }
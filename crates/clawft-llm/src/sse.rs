@@ -71,49 +71,100 @@ pub fn parse_sse_line(line: &str) -> Result<Vec<StreamChunk>> {
     Ok(convert_delta_to_chunks(&delta))
 }
 
+/// Splits a byte stream into SSE lines.
+///
+/// Lines are split on raw bytes so a multi-byte character spanning two
+/// network chunks is decoded intact.
+#[cfg(feature = "native")]
+#[derive(Debug, Default)]
+pub(crate) struct LineBuffer {
+    buf: Vec<u8>,
+}
+
+#[cfg(feature = "native")]
+impl LineBuffer {
+    /// Append bytes received from the network.
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Take the next complete line, without its line terminator.
+    pub(crate) fn next_line(&mut self) -> Option<String> {
+        let pos = self.buf.iter().position(|&b| b == b'\n')?;
+        let line: Vec<u8> = self.buf.drain(..=pos).collect();
+        Some(String::from_utf8_lossy(&line).trim_end().to_string())
+    }
+
+    /// Take whatever is left once the stream has ended.
+    pub(crate) fn take_rest(&mut self) -> Option<String> {
+        if self.buf.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.buf);
+        Some(String::from_utf8_lossy(&rest).trim_end().to_string())
+    }
+}
+
 /// Convert a parsed [`StreamDelta`] into a list of [`StreamChunk`] values.
 ///
 /// A single delta can produce:
 /// - A `TextDelta` if the delta has text content
 /// - One or more `ToolCallDelta` chunks if the delta has tool calls
 /// - A `Done` chunk if a finish_reason is present
+/// - A `Done` chunk carrying only usage for the trailing usage chunk some
+///   providers send with an empty `choices` array
 fn convert_delta_to_chunks(delta: &StreamDelta) -> Vec<StreamChunk> {
     let mut chunks = Vec::new();
 
-    if let Some(choice) = delta.choices.first() {
-        // Text content delta
-        if let Some(ref text) = choice.delta.content
-            && !text.is_empty()
-        {
-            chunks.push(StreamChunk::TextDelta { text: text.clone() });
+    let Some(choice) = delta.choices.first() else {
+        if let Some(ref usage) = delta.usage {
+            chunks.push(StreamChunk::Done {
+                finish_reason: None,
+                usage: Some(convert_usage(usage)),
+            });
         }
+        return chunks;
+    };
 
-        // Tool call deltas
-        if let Some(ref tool_calls) = choice.delta.tool_calls {
-            for tc in tool_calls {
-                let (name, arguments) = match &tc.function {
-                    Some(f) => (f.name.clone(), f.arguments.clone()),
-                    None => (None, None),
-                };
-                chunks.push(StreamChunk::ToolCallDelta {
-                    index: tc.index,
-                    id: tc.id.clone(),
-                    name,
-                    arguments,
-                });
-            }
-        }
+    // Reasoning delta (precedes the reply text)
+    if let Some(ref text) = choice.delta.reasoning_content
+        && !text.is_empty()
+    {
+        chunks.push(StreamChunk::ReasoningDelta { text: text.clone() });
+    }
 
-        // Finish reason signals end of stream
-        if choice.finish_reason.is_some() {
-            let usage = delta.usage.as_ref().map(convert_usage);
-            chunks.push(StreamChunk::Done {
-                finish_reason: choice.finish_reason.clone(),
-                usage,
+    // Text content delta
+    if let Some(ref text) = choice.delta.content
+        && !text.is_empty()
+    {
+        chunks.push(StreamChunk::TextDelta { text: text.clone() });
+    }
+
+    // Tool call deltas
+    if let Some(ref tool_calls) = choice.delta.tool_calls {
+        for tc in tool_calls {
+            let (name, arguments) = match &tc.function {
+                Some(f) => (f.name.clone(), f.arguments.clone()),
+                None => (None, None),
+            };
+            chunks.push(StreamChunk::ToolCallDelta {
+                index: tc.index,
+                id: tc.id.clone(),
+                name,
+                arguments,
             });
         }
     }
 
+    // Finish reason signals end of stream
+    if choice.finish_reason.is_some() {
+        let usage = delta.usage.as_ref().map(convert_usage);
+        chunks.push(StreamChunk::Done {
+            finish_reason: choice.finish_reason.clone(),
+            usage,
+        });
+    }
+
     chunks
 }
 
@@ -123,6 +174,18 @@ fn convert_usage(u: &StreamDeltaUsage) -> Usage {
         input_tokens: u.prompt_tokens.unwrap_or(0) as u32,
         output_tokens: u.completion_tokens.unwrap_or(0) as u32,
         total_tokens: u.total_tokens.unwrap_or(0) as u32,
+        cached: false,
+        cache_read_tokens: u
+            .prompt_tokens_details
+            .as_ref()
+            .and_then(|d| d.cached_tokens)
+            .unwrap_or(0) as u32,
+        cache_write_tokens: 0,
+        reasoning_tokens: u
+            .completion_tokens_details
+            .as_ref()
+            .and_then(|d| d.reasoning_tokens)
+            .unwrap_or(0) as u32,
     }
 }
 
@@ -310,6 +373,10 @@ mod tests {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 15,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
                 }),
             }
         );
@@ -403,6 +470,80 @@ mod tests {
         assert!(chunks.is_empty());
     }
 
+    #[cfg(feature = "native")]
+    #[test]
+    fn line_buffer_joins_split_utf8() {
+        let mut lines = LineBuffer::default();
+        let text = "data: wörld\r\ndata: x".as_bytes();
+        let split = text.iter().position(|&b| b == 0xc3).unwrap() + 1;
+        lines.push(&text[..split]);
+        assert_eq!(lines.next_line(), None);
+        lines.push(&text[split..]);
+        assert_eq!(lines.next_line().as_deref(), Some("data: wörld"));
+        assert_eq!(lines.next_line(), None);
+        assert_eq!(lines.take_rest().as_deref(), Some("data: x"));
+        assert_eq!(lines.take_rest(), None);
+    }
+
+    #[test]
+    fn usage_chunk_reports_cached_prompt_tokens() {
+        let line = r#"data: {"id":"chatcmpl-1","choices":[],"usage":{"prompt_tokens":2048,"completion_tokens":2,"total_tokens":2050,"prompt_tokens_details":{"cached_tokens":1920}}}"#;
+        let chunks = parse_sse_line(line).unwrap();
+        match &chunks[..] {
+            [
+                StreamChunk::Done {
+                    usage: Some(usage), ..
+                },
+            ] => {
+                assert_eq!(usage.input_tokens, 2048);
+                assert_eq!(usage.cache_read_tokens, 1920);
+            }
+            other => panic!("expected Done with usage, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reasoning_content_streams_as_reasoning_delta() {
+        let line = r#"data: {"id":"r1","choices":[{"index":0,"delta":{"role":"assistant","content":null,"reasoning_content":"Let me add."},"finish_reason":null}]}"#;
+        assert_eq!(
+            parse_sse_line(line).unwrap(),
+            vec![StreamChunk::ReasoningDelta {
+                text: "Let me add.".into()
+            }]
+        );
+
+        let line = r#"data: {"id":"r1","choices":[],"usage":{"prompt_tokens":9,"completion_tokens":40,"completion_tokens_details":{"reasoning_tokens":32}}}"#;
+        match &parse_sse_line(line).unwrap()[..] {
+            [
+                StreamChunk::Done {
+                    usage: Some(usage), ..
+                },
+            ] => assert_eq!(usage.reasoning_tokens, 32),
+            other => panic!("expected Done with usage, got: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn usage_only_chunk_becomes_done() {
+        let line = r#"data: {"id":"chatcmpl-1","choices":[],"usage":{"prompt_tokens":3,"completion_tokens":2,"total_tokens":5}}"#;
+        let chunks = parse_sse_line(line).unwrap();
+        assert_eq!(
+            chunks,
+            vec![StreamChunk::Done {
+                finish_reason: None,
+                usage: Some(Usage {
+                    input_tokens: 3,
+                    output_tokens: 2,
+                    total_tokens: 5,
+                    cached: false,
+                    cache_read_tokens: 0,
+                    cache_write_tokens: 0,
+                    reasoning_tokens: 0,
+                }),
+            }]
+        );
+    }
+
     #[test]
     fn data_with_trailing_newline() {
         let line = "data: {\"id\":\"chatcmpl-1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}]}\n";
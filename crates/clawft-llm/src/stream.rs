@@ -0,0 +1,254 @@
+//! Assembly of streamed chunks into a complete [`ChatResponse`].
+//!
+//! Streaming providers emit a sequence of [`StreamChunk`] values: text
+//! fragments, tool-call fragments keyed by index, and one or more `Done`
+//! markers. [`StreamAccumulator`] folds those back into the same
+//! [`ChatResponse`] a non-streaming call would have returned.
+
+use std::collections::BTreeMap;
+
+use crate::types::{ChatMessage, ChatResponse, Choice, FunctionCall, StreamChunk, ToolCall, Usage};
+
+/// A tool call being rebuilt from its deltas.
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+/// Folds [`StreamChunk`] values into a final [`ChatResponse`].
+///
+/// Text deltas are concatenated, and reasoning deltas likewise into the
+/// message's separate `reasoning`. Tool-call deltas are grouped by their
+/// `index`: the id and name come from whichever delta carries them (usually
+/// the first) and argument fragments are appended in arrival order. Several
+/// `Done` chunks may arrive (a finish-reason chunk, a usage-only chunk and
+/// the `[DONE]` sentinel); the last non-empty finish reason and usage win.
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    text: String,
+    reasoning: String,
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    done: bool,
+}
+
+impl StreamAccumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold one chunk into the response being built.
+    pub fn push(&mut self, chunk: &StreamChunk) {
+        match chunk {
+            StreamChunk::TextDelta { text } => self.text.push_str(text),
+            StreamChunk::ReasoningDelta { text } => self.reasoning.push_str(text),
+            StreamChunk::ToolCallDelta {
+                index,
+                id,
+                name,
+                arguments,
+            } => {
+                let call = self.tool_calls.entry(*index).or_default();
+                if let Some(id) = id {
+                    call.id = Some(id.clone());
+                }
+                if let Some(name) = name {
+                    call.name.push_str(name);
+                }
+                if let Some(arguments) = arguments {
+                    call.arguments.push_str(arguments);
+                }
+            }
+            StreamChunk::Done {
+                finish_reason,
+                usage,
+            } => {
+                self.done = true;
+                if finish_reason.is_some() {
+                    self.finish_reason.clone_from(finish_reason);
+                }
+                if usage.is_some() {
+                    self.usage.clone_from(usage);
+                }
+            }
+        }
+    }
+
+    /// Whether any text or tool-call content has been seen.
+    pub fn has_content(&self) -> bool {
+        !self.text.is_empty() || !self.tool_calls.is_empty()
+    }
+
+    /// Whether a `Done` chunk has been seen.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Build the final response for `model`.
+    ///
+    /// Tool calls without an id get a generated `call_<index>` id. When the
+    /// provider sent no finish reason it is inferred as `"tool_calls"` or
+    /// `"stop"`.
+    pub fn finish(self, model: impl Into<String>) -> ChatResponse {
+        let tool_calls: Vec<ToolCall> = self
+            .tool_calls
+            .into_iter()
+            .map(|(index, call)| ToolCall {
+                id: call.id.unwrap_or_else(|| format!("call_{index}")),
+                call_type: "function".into(),
+                function: FunctionCall {
+                    name: call.name,
+                    arguments: call.arguments,
+                },
+            })
+            .collect();
+
+        let finish_reason = self.finish_reason.unwrap_or_else(|| {
+            if tool_calls.is_empty() {
+                "stop".into()
+            } else {
+                "tool_calls".into()
+            }
+        });
+
+        let message = ChatMessage {
+            role: "assistant".into(),
+            content: if self.text.is_empty() && !tool_calls.is_empty() {
+                None
+            } else {
+                Some(self.text.into())
+            },
+            tool_call_id: None,
+            tool_calls: if tool_calls.is_empty() {
+                None
+            } else {
+                Some(tool_calls)
+            },
+            cache: false,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+        };
+
+        ChatResponse {
+            id: format!("stream-{}", uuid::Uuid::new_v4()),
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason: Some(finish_reason),
+            }],
+            usage: self.usage,
+            model: model.into(),
+            failover: Vec::new(),
+            retries: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sse::parse_sse_line;
+
+    fn accumulate(lines: &[&str]) -> StreamAccumulator {
+        let mut acc = StreamAccumulator::new();
+        for line in lines {
+            for chunk in parse_sse_line(line).unwrap() {
+                acc.push(&chunk);
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn text_deltas_are_concatenated() {
+        let acc = accumulate(&[
+            r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":"lo"},"finish_reason":"stop"}]}"#,
+            "data: [DONE]",
+        ]);
+        assert!(acc.has_content());
+        assert!(acc.is_done());
+
+        let resp = acc.finish("gpt-test");
+        assert_eq!(resp.model, "gpt-test");
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello"));
+        assert_eq!(resp.choices[0].finish_reason.as_deref(), Some("stop"));
+        assert!(resp.choices[0].message.tool_calls.is_none());
+        assert!(resp.usage.is_none());
+    }
+
+    #[test]
+    fn reasoning_deltas_stay_out_of_the_text() {
+        let acc = accumulate(&[
+            r#"data: {"choices":[{"delta":{"reasoning_content":"2 + 2 "}}]}"#,
+            r#"data: {"choices":[{"delta":{"reasoning_content":"is 4."}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":"4"},"finish_reason":"stop"}]}"#,
+        ]);
+        let resp = acc.finish("deepseek-reasoner");
+        let message = &resp.choices[0].message;
+        assert_eq!(message.text().as_deref(), Some("4"));
+        assert_eq!(message.reasoning.as_deref(), Some("2 + 2 is 4."));
+    }
+
+    #[test]
+    fn interleaved_tool_call_deltas_are_reassembled() {
+        let acc = accumulate(&[
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_a","type":"function","function":{"name":"read_file","arguments":""}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_b","function":{"name":"web_search","arguments":"{\"q\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"path\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"a.rs\"}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":1,"function":{"arguments":"\"rust\"}"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7,"total_tokens":19}}"#,
+            "data: [DONE]",
+        ]);
+
+        let resp = acc.finish("gpt-test");
+        let choice = &resp.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        assert!(choice.message.content.is_none());
+
+        let calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_a");
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a.rs"}"#);
+        assert_eq!(calls[1].id, "call_b");
+        assert_eq!(calls[1].function.name, "web_search");
+        assert_eq!(calls[1].function.arguments, r#"{"q":"rust"}"#);
+
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 7);
+        assert_eq!(usage.total_tokens, 19);
+    }
+
+    #[test]
+    fn missing_finish_reason_and_ids_are_filled_in() {
+        let mut acc = StreamAccumulator::new();
+        acc.push(&StreamChunk::ToolCallDelta {
+            index: 3,
+            id: None,
+            name: Some("exec".into()),
+            arguments: Some("{}".into()),
+        });
+        assert!(!acc.is_done());
+
+        let resp = acc.finish("m");
+        let calls = resp.choices[0].message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_3");
+        assert_eq!(resp.choices[0].finish_reason.as_deref(), Some("tool_calls"));
+    }
+
+    #[test]
+    fn empty_stream_yields_empty_text() {
+        let acc = StreamAccumulator::new();
+        assert!(!acc.has_content());
+        let resp = acc.finish("m");
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some(""));
+        assert_eq!(resp.choices[0].finish_reason.as_deref(), Some("stop"));
+    }
+}
@@ -0,0 +1,564 @@
+//! Structured (JSON) output: validation and retrying helpers.
+//!
+//! Set [`ChatRequest::response_format`] to ask a provider for JSON; each
+//! provider maps it onto its native mechanism. Providers do not always
+//! honour it exactly, so [`complete_json`] and [`complete_structured`]
+//! check the reply against the schema and, on a mismatch, re-ask once with
+//! the validation errors before giving up.
+//!
+//! ```rust,ignore
+//! use clawft_llm::structured::complete_structured;
+//! use clawft_llm::types::ResponseFormat;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Verdict { approve: bool, reason: String }
+//!
+//! let mut request = ChatRequest::new("gpt-4o", messages);
+//! request.response_format = Some(ResponseFormat::json_schema("verdict", schema));
+//! let verdict: Verdict = complete_structured(&provider, &request).await?;
+//! ```
+//!
+//! The validator covers the JSON Schema subset that providers accept for
+//! structured output: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `anyOf`/`oneOf`/`allOf`, local `$ref`s,
+//! and the length and range bounds. `oneOf` is checked like `anyOf`.
+
+use serde_json::Value;
+
+use crate::types::ResponseFormat;
+
+/// Re-asks allowed after a reply fails validation.
+pub const MAX_STRUCTURED_RETRIES: usize = 1;
+
+/// Validate `value` against `schema`.
+///
+/// Returns every violation found, each prefixed with the JSON path of the
+/// offending value (`$.items[2].name: ...`).
+pub fn validate(value: &Value, schema: &Value) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    check(value, schema, schema, "$", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse a reply's text and check it against `format`.
+///
+/// Surrounding whitespace and a Markdown code fence are tolerated.
+pub fn check_response(format: &ResponseFormat, content: &str) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(strip_code_fence(content))
+        .map_err(|e| format!("the reply is not valid JSON ({e})"))?;
+    match format {
+        ResponseFormat::Text => {}
+        ResponseFormat::JsonObject => {
+            if !value.is_object() {
+                return Err(format!("expected a JSON object, got {}", type_name(&value)));
+            }
+        }
+        ResponseFormat::JsonSchema { json_schema } => {
+            validate(&value, &json_schema.schema).map_err(|errors| errors.join("; "))?;
+        }
+    }
+    Ok(value)
+}
+
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    // Drop the info string (e.g. `json`) on the opening line.
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(value: &Value, ty: &str) -> bool {
+    match ty {
+        "integer" => match value {
+            Value::Number(n) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+            }
+            _ => false,
+        },
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn check(value: &Value, schema: &Value, root: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(false) => {
+            errors.push(format!("{path}: no value is allowed here"));
+            return;
+        }
+        Value::Object(map) => map,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) => check(value, target, root, path, errors),
+            None => errors.push(format!("{path}: cannot resolve $ref `{reference}`")),
+        }
+        return;
+    }
+
+    if let Some(ty) = schema.get("type") {
+        let allowed: Vec<&str> = match ty {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(value, t)) {
+            errors.push(format!(
+                "{path}: expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        errors.push(format!(
+            "{path}: {value} is not one of {}",
+            Value::from(options.clone())
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!("{path}: expected {expected}"));
+    }
+
+    for key in ["anyOf", "oneOf"] {
+        if let Some(options) = schema.get(key).and_then(Value::as_array)
+            && !options.iter().any(|option| {
+                let mut scratch = Vec::new();
+                check(value, option, root, path, &mut scratch);
+                scratch.is_empty()
+            })
+        {
+            errors.push(format!("{path}: does not match any of the allowed schemas"));
+        }
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            check(value, sub, root, path, errors);
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(properties) = properties {
+                for (key, sub) in properties {
+                    if let Some(v) = map.get(key) {
+                        check(v, sub, root, &format!("{path}.{key}"), errors);
+                    }
+                }
+            }
+            for key in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !map.contains_key(key) {
+                    errors.push(format!("{path}: missing required property `{key}`"));
+                }
+            }
+            if let Some(additional) = schema.get("additionalProperties") {
+                let extras = map
+                    .iter()
+                    .filter(|(k, _)| !properties.is_some_and(|p| p.contains_key(*k)));
+                for (key, v) in extras {
+                    if additional == &Value::Bool(false) {
+                        errors.push(format!("{path}: unexpected property `{key}`"));
+                    } else {
+                        check(v, additional, root, &format!("{path}.{key}"), errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(item, item_schema, root, &format!("{path}[{i}]"), errors);
+                }
+            }
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && (items.len() as u64) < min
+            {
+                errors.push(format!("{path}: expected at least {min} items"));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                && (items.len() as u64) > max
+            {
+                errors.push(format!("{path}: expected at most {max} items"));
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && len < min
+            {
+                errors.push(format!("{path}: shorter than {min} characters"));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                && len > max
+            {
+                errors.push(format!("{path}: longer than {max} characters"));
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+            if let Some(min) = bound("minimum")
+                && n < min
+            {
+                errors.push(format!("{path}: less than {min}"));
+            }
+            if let Some(max) = bound("maximum")
+                && n > max
+            {
+                errors.push(format!("{path}: greater than {max}"));
+            }
+            if let Some(min) = bound("exclusiveMinimum")
+                && n <= min
+            {
+                errors.push(format!("{path}: not greater than {min}"));
+            }
+            if let Some(max) = bound("exclusiveMaximum")
+                && n >= max
+            {
+                errors.push(format!("{path}: not less than {max}"));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve a document-local `$ref` (`#`, `#/$defs/name`, ...).
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        Some(root)
+    } else {
+        root.pointer(pointer)
+    }
+}
+
+#[cfg(feature = "native")]
+mod helpers {
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+    use tracing::warn;
+
+    use super::{MAX_STRUCTURED_RETRIES, check_response};
+    use crate::error::{ProviderError, Result};
+    use crate::provider::Provider;
+    use crate::types::{ChatMessage, ChatRequest, ChatResponse, ResponseFormat};
+
+    /// Complete `request` and return the reply parsed as JSON.
+    ///
+    /// Uses the request's `response_format`, defaulting to
+    /// [`ResponseFormat::JsonObject`]. A reply that is not valid JSON or
+    /// does not match the schema is sent back to the model with the errors,
+    /// at most [`MAX_STRUCTURED_RETRIES`] times, before failing with
+    /// [`ProviderError::InvalidResponse`].
+    pub async fn complete_json<P: Provider + ?Sized>(
+        provider: &P,
+        request: &ChatRequest,
+    ) -> Result<(ChatResponse, Value)> {
+        complete_checked(provider, request, Ok).await
+    }
+
+    /// Complete `request` and deserialize the reply into `T`.
+    ///
+    /// Like [`complete_json`], but a reply that does not deserialize into
+    /// `T` also counts as a mismatch and is retried.
+    pub async fn complete_structured<T, P>(provider: &P, request: &ChatRequest) -> Result<T>
+    where
+        T: DeserializeOwned,
+        P: Provider + ?Sized,
+    {
+        let (_, value) = complete_checked(provider, request, |value| {
+            serde_json::from_value::<T>(value)
+                .map_err(|e| format!("the JSON does not have the expected structure ({e})"))
+        })
+        .await?;
+        Ok(value)
+    }
+
+    async fn complete_checked<P, R>(
+        provider: &P,
+        request: &ChatRequest,
+        convert: impl Fn(Value) -> std::result::Result<R, String>,
+    ) -> Result<(ChatResponse, R)>
+    where
+        P: Provider + ?Sized,
+    {
+        let format = request
+            .response_format
+            .clone()
+            .unwrap_or(ResponseFormat::JsonObject);
+        let mut request = request.clone();
+        request.response_format = Some(format.clone());
+
+        let mut attempt = 0;
+        loop {
+            let response = provider.complete(&request).await?;
+            let content = response
+                .choices
+                .first()
+                .and_then(|c| c.message.text())
+                .map(String::from)
+                .unwrap_or_default();
+
+            let problem = match check_response(&format, &content).and_then(&convert) {
+                Ok(out) => return Ok((response, out)),
+                Err(problem) => problem,
+            };
+            if attempt >= MAX_STRUCTURED_RETRIES {
+                return Err(ProviderError::InvalidResponse(format!(
+                    "structured output still invalid after {} attempts: {problem}",
+                    attempt + 1
+                )));
+            }
+            attempt += 1;
+            warn!(
+                provider = provider.name(),
+                model = %request.model,
+                problem = %problem,
+                "structured output invalid, asking the model to correct it"
+            );
+            request.messages.push(ChatMessage::assistant(content));
+            request.messages.push(ChatMessage::user(format!(
+                "Your reply was rejected: {problem}. Reply again with only the corrected JSON."
+            )));
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+pub use helpers::{complete_json, complete_structured};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn person_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}, "maxItems": 2},
+                "role": {"enum": ["admin", "user"]},
+                "email": {"type": ["string", "null"]}
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false,
+            "$defs": {"tag": {"type": "string"}}
+        })
+    }
+
+    #[test]
+    fn valid_values_pass() {
+        let value =
+            json!({"name": "Ada", "age": 36, "tags": ["math"], "role": "admin", "email": null});
+        assert_eq!(validate(&value, &person_schema()), Ok(()));
+        // 3.0 is an integer as far as JSON Schema is concerned.
+        assert!(validate(&json!({"name": "Ada", "age": 3.0}), &person_schema()).is_ok());
+    }
+
+    #[test]
+    fn violations_are_reported_with_paths() {
+        let value = json!({"name": "", "age": -1, "tags": ["a", 2, "c"], "role": "root", "x": 1});
+        let errors = validate(&value, &person_schema()).unwrap_err();
+        let expected = [
+            "$.name: shorter than 1 characters",
+            "$.age: less than 0",
+            "$.tags[1]: expected string, got integer",
+            "$.tags: expected at most 2 items",
+            r#"$.role: "root" is not one of ["admin","user"]"#,
+            "$: unexpected property `x`",
+        ];
+        for e in expected {
+            assert!(
+                errors.iter().any(|got| got == e),
+                "missing {e:?} in {errors:?}"
+            );
+        }
+        assert_eq!(errors.len(), expected.len());
+
+        let errors = validate(&json!({"age": "36"}), &person_schema()).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "$.age: expected integer, got string",
+                "$: missing required property `name`"
+            ]
+        );
+    }
+
+    #[test]
+    fn any_of_and_const() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"const": 7}]});
+        assert!(validate(&json!("x"), &schema).is_ok());
+        assert!(validate(&json!(7), &schema).is_ok());
+        assert_eq!(
+            validate(&json!(8), &schema).unwrap_err(),
+            vec!["$: does not match any of the allowed schemas"]
+        );
+    }
+
+    #[test]
+    fn check_response_parses_and_validates() {
+        let format = ResponseFormat::json_schema("person", person_schema());
+        let value =
+            check_response(&format, "```json\n{\"name\": \"Ada\", \"age\": 36}\n```").unwrap();
+        assert_eq!(value["age"], 36);
+
+        let err = check_response(&format, "{\"name\": \"Ada\"}").unwrap_err();
+        assert!(err.contains("missing required property `age`"), "{err}");
+        let err = check_response(&format, "Sure! Here it is.").unwrap_err();
+        assert!(err.starts_with("the reply is not valid JSON"), "{err}");
+        let err = check_response(&ResponseFormat::JsonObject, "[1, 2]").unwrap_err();
+        assert_eq!(err, "expected a JSON object, got array");
+    }
+
+    #[cfg(feature = "native")]
+    mod retry {
+        use std::sync::Mutex;
+
+        use async_trait::async_trait;
+        use serde::Deserialize;
+
+        use super::*;
+        use crate::error::{ProviderError, Result};
+        use crate::provider::Provider;
+        use crate::types::{ChatMessage, ChatRequest, ChatResponse, Choice};
+
+        /// Replies with each scripted text in turn and records the requests.
+        struct Scripted {
+            replies: Mutex<Vec<&'static str>>,
+            seen: Mutex<Vec<ChatRequest>>,
+        }
+
+        impl Scripted {
+            fn new(replies: &[&'static str]) -> Self {
+                Self {
+                    replies: Mutex::new(replies.iter().rev().copied().collect()),
+                    seen: Mutex::new(Vec::new()),
+                }
+            }
+        }
+
+        #[async_trait]
+        impl Provider for Scripted {
+            fn name(&self) -> &str {
+                "scripted"
+            }
+
+            async fn complete(&self, request: &ChatRequest) -> Result<ChatResponse> {
+                self.seen.lock().unwrap().push(request.clone());
+                let text = self
+                    .replies
+                    .lock()
+                    .unwrap()
+                    .pop()
+                    .expect("script exhausted");
+                Ok(ChatResponse {
+                    id: "r".into(),
+                    choices: vec![Choice {
+                        index: 0,
+                        message: ChatMessage::assistant(text),
+                        finish_reason: Some("stop".into()),
+                    }],
+                    usage: None,
+                    model: "m".into(),
+                    failover: Vec::new(),
+                    retries: 0,
+                })
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        fn request() -> ChatRequest {
+            let mut request = ChatRequest::new("m", vec![ChatMessage::user("Who?")]);
+            request.response_format = Some(ResponseFormat::json_schema("person", person_schema()));
+            request
+        }
+
+        #[tokio::test]
+        async fn invalid_then_valid_reply_is_retried_once() {
+            let provider = Scripted::new(&[r#"{"name": "Ada"}"#, r#"{"name": "Ada", "age": 36}"#]);
+            let person: Person = complete_structured(&provider, &request()).await.unwrap();
+            assert_eq!(
+                person,
+                Person {
+                    name: "Ada".into(),
+                    age: 36
+                }
+            );
+
+            let seen = provider.seen.lock().unwrap();
+            assert_eq!(seen.len(), 2);
+            let retry = &seen[1].messages;
+            assert_eq!(retry.len(), 3);
+            assert_eq!(retry[1].text().as_deref(), Some(r#"{"name": "Ada"}"#));
+            let correction = retry[2].text().unwrap();
+            assert!(
+                correction.contains("missing required property `age`"),
+                "{correction}"
+            );
+        }
+
+        #[tokio::test]
+        async fn retry_is_bounded() {
+            let provider = Scripted::new(&["nope", "still nope", "{}"]);
+            let err = complete_json(&provider, &request()).await.unwrap_err();
+            assert!(matches!(err, ProviderError::InvalidResponse(_)), "{err}");
+            assert_eq!(
+                provider.seen.lock().unwrap().len(),
+                1 + MAX_STRUCTURED_RETRIES
+            );
+        }
+
+        #[tokio::test]
+        async fn deserialization_mismatch_counts_as_invalid() {
+            // Valid against a bare `json_object` format, but not a `Person`.
+            let provider = Scripted::new(&[r#"{"who": "Ada"}"#, r#"{"name": "Ada", "age": 1}"#]);
+            let request = ChatRequest::new("m", vec![ChatMessage::user("Who?")]);
+            let person: Person = complete_structured(&provider, &request).await.unwrap();
+            assert_eq!(person.age, 1);
+            let seen = provider.seen.lock().unwrap();
+            assert_eq!(seen[0].response_format, Some(ResponseFormat::JsonObject));
+        }
+    }
+}
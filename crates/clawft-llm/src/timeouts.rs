@@ -0,0 +1,151 @@
+//! Request time limits for the HTTP providers.
+//!
+//! Every request is bounded three ways:
+//!
+//! - **connect** -- establishing the TCP/TLS connection
+//!   ([`LlmProviderConfig::connect_timeout_secs`]).
+//! - **total** -- the whole request, from send to the last byte of the
+//!   response ([`LlmProviderConfig::timeout_secs`], overridable per request
+//!   with [`ChatRequest::timeout`]).
+//! - **idle** -- for streaming responses, the longest gap between two
+//!   chunks ([`LlmProviderConfig::read_timeout_secs`]). A stalled stream
+//!   fails here long before the total deadline.
+//!
+//! Expiry of any of them surfaces as [`ProviderError::Timeout`], which
+//! [`RetryPolicy`](crate::retry::RetryPolicy) retries and
+//! [`FailoverChain`](crate::failover::FailoverChain) counts as a provider
+//! failure.
+
+use std::time::Duration;
+
+use futures_util::{Stream, StreamExt};
+use tracing::warn;
+
+use crate::config::LlmProviderConfig;
+use crate::error::{ProviderError, Result};
+use crate::types::ChatRequest;
+
+/// Default limit on establishing a connection.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Default limit on the gap between two chunks of a streaming response.
+pub const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
+
+/// Resolved time limits for one provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Timeouts {
+    connect: Duration,
+    total: Duration,
+    idle: Duration,
+}
+
+impl Timeouts {
+    /// Limits from `config`, with `default_total_secs` when it sets no
+    /// overall timeout.
+    pub(crate) fn new(config: &LlmProviderConfig, default_total_secs: u64) -> Self {
+        let secs = |value: Option<u64>, default: u64| Duration::from_secs(value.unwrap_or(default));
+        Self {
+            connect: secs(config.connect_timeout_secs, DEFAULT_CONNECT_TIMEOUT_SECS),
+            total: secs(config.timeout_secs, default_total_secs),
+            idle: secs(config.read_timeout_secs, DEFAULT_READ_TIMEOUT_SECS),
+        }
+    }
+
+    /// An HTTP client enforcing the connect and default total limits.
+    pub(crate) fn client(&self) -> reqwest::Client {
+        reqwest::ClientBuilder::new()
+            .connect_timeout(self.connect)
+            .timeout(self.total)
+            .build()
+            .expect("failed to build reqwest client")
+    }
+
+    /// The total limit for `request`: its own override, if set.
+    pub(crate) fn total_for(&self, request: &ChatRequest) -> Duration {
+        request.timeout.unwrap_or(self.total)
+    }
+
+    /// Read the next chunk of a streaming response body, failing with
+    /// [`ProviderError::Timeout`] if none arrives within the idle limit.
+    pub(crate) async fn next_chunk<S, T>(&self, stream: &mut S) -> Result<Option<T>>
+    where
+        S: Stream<Item = reqwest::Result<T>> + Unpin,
+    {
+        match tokio::time::timeout(self.idle, stream.next()).await {
+            Ok(None) => Ok(None),
+            Ok(Some(Ok(chunk))) => Ok(Some(chunk)),
+            Ok(Some(Err(e))) if e.is_timeout() => Err(ProviderError::Timeout),
+            Ok(Some(Err(e))) => Err(ProviderError::RequestFailed(format!(
+                "stream read error: {e}"
+            ))),
+            Err(_) => {
+                warn!(
+                    idle_secs = self.idle.as_secs_f64(),
+                    "stream stalled, no chunk within the read timeout"
+                );
+                Err(ProviderError::Timeout)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LlmProviderConfig {
+        LlmProviderConfig {
+            name: "test".into(),
+            base_url: "http://localhost".into(),
+            api_key_env: "TEST_KEY".into(),
+            model_prefix: None,
+            default_model: None,
+            headers: Default::default(),
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            read_timeout_secs: None,
+            reasoning_effort: None,
+            rate_limit: Default::default(),
+            azure: None,
+        }
+    }
+
+    #[test]
+    fn defaults_and_overrides() {
+        let mut config = config();
+        let timeouts = Timeouts::new(&config, 120);
+        assert_eq!(timeouts.connect, Duration::from_secs(10));
+        assert_eq!(timeouts.total, Duration::from_secs(120));
+        assert_eq!(timeouts.idle, Duration::from_secs(60));
+
+        config.connect_timeout_secs = Some(2);
+        config.timeout_secs = Some(30);
+        config.read_timeout_secs = Some(5);
+        let timeouts = Timeouts::new(&config, 120);
+        assert_eq!(timeouts.connect, Duration::from_secs(2));
+        assert_eq!(timeouts.idle, Duration::from_secs(5));
+
+        let mut request = ChatRequest::new("m", Vec::new());
+        assert_eq!(timeouts.total_for(&request), Duration::from_secs(30));
+        request.timeout = Some(Duration::from_millis(500));
+        assert_eq!(timeouts.total_for(&request), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn stalled_stream_times_out() {
+        let config = LlmProviderConfig {
+            read_timeout_secs: Some(1),
+            reasoning_effort: None,
+            ..config()
+        };
+        let timeouts = Timeouts::new(&config, 120);
+
+        let mut stream = futures_util::stream::pending::<reqwest::Result<u8>>();
+        let err = timeouts.next_chunk(&mut stream).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Timeout));
+
+        let mut stream = futures_util::stream::iter([Ok::<u8, reqwest::Error>(7)]);
+        assert_eq!(timeouts.next_chunk(&mut stream).await.unwrap(), Some(7));
+        assert_eq!(timeouts.next_chunk(&mut stream).await.unwrap(), None);
+    }
+}
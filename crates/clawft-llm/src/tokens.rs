@@ -0,0 +1,252 @@
+//! Token counting and context-window checks.
+//!
+//! Counts are exact BPE counts for OpenAI-family models when the `tiktoken`
+//! feature is enabled (it bundles the `o200k_base` and `cl100k_base`
+//! ranks), and a `chars / 4` estimate otherwise. Context-window sizes come
+//! from the model registry in [`clawft_types::provider::MODELS`].
+//!
+//! ```rust,ignore
+//! use clawft_llm::tokens;
+//!
+//! if !tokens::fits_within("openai/gpt-4o", &messages, 4096) {
+//!     // trim history before sending
+//! }
+//! ```
+
+pub use clawft_types::provider::{ModelSpec, find_model_spec};
+
+use crate::types::ChatMessage;
+
+/// Context window assumed for models missing from the registry.
+pub const DEFAULT_CONTEXT_WINDOW: u32 = 128_000;
+
+/// Output limit assumed for models missing from the registry.
+pub const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 4_096;
+
+/// Fixed per-message overhead of the chat format (role markers and
+/// separators), as documented for OpenAI chat models.
+const TOKENS_PER_MESSAGE: usize = 3;
+
+/// Tokens that prime the assistant's reply after the last message.
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Flat estimate for one image part. Providers charge by resolution; this
+/// is Anthropic's cost for a full-size image, the highest of the lot.
+const IMAGE_TOKENS: usize = 1_600;
+
+/// The tokenizer used to count a model's tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tokenizer {
+    /// `o200k_base`: GPT-4o, GPT-4.1, GPT-5 and the o-series.
+    O200kBase,
+    /// `cl100k_base`: GPT-4, GPT-3.5 and the v3 embedding models.
+    Cl100kBase,
+    /// `chars / 4` estimate for everything else.
+    Heuristic,
+}
+
+impl Tokenizer {
+    /// Pick the tokenizer for a model name (routing prefixes are ignored).
+    ///
+    /// Without the `tiktoken` feature every model uses
+    /// [`Heuristic`](Self::Heuristic).
+    pub fn for_model(model: &str) -> Self {
+        if !cfg!(feature = "tiktoken") {
+            return Self::Heuristic;
+        }
+        let bare = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+        const O200K: &[&str] = &[
+            "gpt-4o",
+            "chatgpt-4o",
+            "gpt-4.1",
+            "gpt-4.5",
+            "gpt-5",
+            "o1",
+            "o3",
+            "o4",
+        ];
+        const CL100K: &[&str] = &["gpt-4", "gpt-3.5", "text-embedding-3", "text-embedding-ada"];
+        if O200K.iter().any(|p| bare.starts_with(p)) {
+            Self::O200kBase
+        } else if CL100K.iter().any(|p| bare.starts_with(p)) {
+            Self::Cl100kBase
+        } else {
+            Self::Heuristic
+        }
+    }
+
+    /// Count the tokens in `text`.
+    pub fn count(self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        match self {
+            #[cfg(feature = "tiktoken")]
+            Self::O200kBase => tiktoken_rs::o200k_base_singleton()
+                .encode_ordinary(text)
+                .len(),
+            #[cfg(feature = "tiktoken")]
+            Self::Cl100kBase => tiktoken_rs::cl100k_base_singleton()
+                .encode_ordinary(text)
+                .len(),
+            _ => estimate_tokens(text),
+        }
+    }
+}
+
+/// Estimate tokens as one per four characters, rounded up.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Count the tokens in `text` for `model`.
+pub fn count_text(text: &str, model: &str) -> usize {
+    Tokenizer::for_model(model).count(text)
+}
+
+/// Count the tokens one message adds to a request for `model`.
+///
+/// Includes the per-message framing, the role, the content, the tool call
+/// id, and each tool call's name and arguments.
+pub fn count_message(message: &ChatMessage, model: &str) -> usize {
+    let tokenizer = Tokenizer::for_model(model);
+    message_tokens(tokenizer, message)
+}
+
+fn message_tokens(tokenizer: Tokenizer, message: &ChatMessage) -> usize {
+    let mut total = TOKENS_PER_MESSAGE + tokenizer.count(&message.role);
+    if let Some(content) = &message.content {
+        total += tokenizer.count(&content.text());
+        total += content.images().count() * IMAGE_TOKENS;
+    }
+    if let Some(id) = &message.tool_call_id {
+        total += tokenizer.count(id);
+    }
+    for call in message.tool_calls.iter().flatten() {
+        total += TOKENS_PER_MESSAGE
+            + tokenizer.count(&call.function.name)
+            + tokenizer.count(&call.function.arguments);
+    }
+    total
+}
+
+/// Count the prompt tokens `messages` use for `model`, including the
+/// tokens that prime the reply.
+pub fn count_messages(messages: &[ChatMessage], model: &str) -> usize {
+    let tokenizer = Tokenizer::for_model(model);
+    messages
+        .iter()
+        .map(|m| message_tokens(tokenizer, m))
+        .sum::<usize>()
+        + REPLY_PRIMING_TOKENS
+}
+
+/// Token limits for `model`, falling back to [`DEFAULT_CONTEXT_WINDOW`] and
+/// [`DEFAULT_MAX_OUTPUT_TOKENS`] for unknown models.
+pub fn model_limits(model: &str) -> ModelSpec {
+    find_model_spec(model).copied().unwrap_or(ModelSpec {
+        prefix: "",
+        context_window: DEFAULT_CONTEXT_WINDOW,
+        max_output_tokens: DEFAULT_MAX_OUTPUT_TOKENS,
+        multimodal: true,
+        reasoning_effort: false,
+    })
+}
+
+/// Largest prompt, in tokens, that leaves room for `reserved_output`
+/// completion tokens on `model`.
+pub fn input_budget(model: &str, reserved_output: usize) -> usize {
+    let reserved = u32::try_from(reserved_output).unwrap_or(u32::MAX);
+    model_limits(model).max_input_tokens(reserved) as usize
+}
+
+/// Whether `messages` plus `reserved_output` completion tokens fit in
+/// `model`'s context window.
+pub fn fits_within(model: &str, messages: &[ChatMessage], reserved_output: usize) -> bool {
+    count_messages(messages, model) <= input_budget(model, reserved_output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heuristic_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes.
+        assert_eq!(estimate_tokens("日本語です"), 2);
+        assert_eq!(count_text("hello world!", "claude-sonnet-4"), 3);
+    }
+
+    #[test]
+    fn unknown_models_get_defaults() {
+        let limits = model_limits("my-local-model");
+        assert_eq!(limits.context_window, DEFAULT_CONTEXT_WINDOW);
+        assert_eq!(input_budget("my-local-model", 1_000), 127_000);
+        assert_eq!(input_budget("openai/gpt-4", 1_000), 7_192);
+    }
+
+    #[test]
+    fn fits_within_respects_reserved_output() {
+        // About 8k BPE tokens, 12k by the heuristic.
+        let big = ChatMessage::user("hello ".repeat(8_000));
+        let messages = vec![ChatMessage::system("sys"), big];
+        assert!(fits_within("claude-3-haiku", &messages, 4_096));
+        // A 16k window minus 4k of reserved output cannot hold it twice.
+        let doubled = [messages.clone(), messages].concat();
+        assert!(!fits_within("gpt-3.5-turbo", &doubled, 4_096));
+    }
+
+    #[test]
+    fn tool_calls_are_counted() {
+        let plain = ChatMessage::assistant("ok");
+        let mut with_call = plain.clone();
+        with_call.tool_calls = Some(vec![crate::types::ToolCall {
+            id: "call_1".into(),
+            call_type: "function".into(),
+            function: crate::types::FunctionCall {
+                name: "read_file".into(),
+                arguments: r#"{"path":"src/main.rs"}"#.into(),
+            },
+        }]);
+        assert!(count_message(&with_call, "m") > count_message(&plain, "m"));
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn bpe_counts_match_reference() {
+        // Reference counts from OpenAI's tiktoken.
+        assert_eq!(Tokenizer::for_model("gpt-4"), Tokenizer::Cl100kBase);
+        assert_eq!(
+            Tokenizer::for_model("openai/gpt-4o-mini"),
+            Tokenizer::O200kBase
+        );
+        assert_eq!(
+            Tokenizer::for_model("claude-sonnet-4"),
+            Tokenizer::Heuristic
+        );
+
+        assert_eq!(count_text("tiktoken is great!", "gpt-4"), 6);
+        assert_eq!(count_text("hello world", "gpt-4"), 2);
+        assert_eq!(count_text("hello world", "gpt-4o"), 2);
+        assert_eq!(
+            count_text("The quick brown fox jumps over the lazy dog.", "gpt-4o"),
+            10
+        );
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn chat_framing_matches_reference() {
+        // OpenAI's cookbook example: these two messages are 3 + 3 per-message
+        // tokens, the roles, the contents, and 3 reply-priming tokens.
+        let messages = vec![
+            ChatMessage::system("You are a helpful assistant."),
+            ChatMessage::user("Hello!"),
+        ];
+        assert_eq!(count_messages(&messages, "gpt-4o"), 19);
+    }
+}
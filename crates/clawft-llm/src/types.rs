@@ -4,6 +4,11 @@
 //! the de facto standard adopted by 19+ providers. They are standalone and
 //! have no dependency on other clawft crates.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde::{Deserialize, Serialize};
 
 /// A message in a chat conversation.
@@ -12,11 +17,12 @@ pub struct ChatMessage {
     /// The role of the message author (e.g. "system", "user", "assistant", "tool").
     pub role: String,
 
-    /// The content of the message. `None` for assistant messages that only
-    /// contain tool calls. When `None`, the field is omitted from JSON
-    /// (some providers reject `"content": null`).
+    /// The content of the message: plain text, or text and image parts.
+    /// `None` for assistant messages that only contain tool calls. When
+    /// `None`, the field is omitted from JSON (some providers reject
+    /// `"content": null`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub content: Option<String>,
+    pub content: Option<MessageContent>,
 
     /// For tool-result messages, the ID of the tool call this is a response to.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -25,6 +31,26 @@ pub struct ChatMessage {
     /// Tool calls requested by the assistant in this message.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
+
+    /// This message ends a stable prompt prefix worth caching (system
+    /// prompt, skill instructions). Providers with explicit prompt caching
+    /// (Anthropic) place a cache breakpoint here; others ignore it. Never
+    /// serialized.
+    #[serde(default, skip_serializing)]
+    pub cache: bool,
+
+    /// Hidden reasoning the model produced before its answer: DeepSeek's
+    /// `reasoning_content`, the `reasoning` field some gateways use, or
+    /// `<think>` blocks split out of the content. Kept apart from `content`
+    /// so it never reaches the user, and never serialized (DeepSeek rejects
+    /// requests that echo it back).
+    #[serde(
+        default,
+        rename = "reasoning_content",
+        alias = "reasoning",
+        skip_serializing
+    )]
+    pub reasoning: Option<String>,
 }
 
 impl ChatMessage {
@@ -32,9 +58,23 @@ impl ChatMessage {
     pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
         Self {
             role: role.into(),
-            content: Some(content.into()),
+            content: Some(MessageContent::Text(content.into())),
+            tool_call_id: None,
+            tool_calls: None,
+            cache: false,
+            reasoning: None,
+        }
+    }
+
+    /// Create a message whose content is a list of text and image parts.
+    pub fn with_parts(role: impl Into<String>, parts: Vec<ContentPart>) -> Self {
+        Self {
+            role: role.into(),
+            content: Some(MessageContent::Parts(parts)),
             tool_call_id: None,
             tool_calls: None,
+            cache: false,
+            reasoning: None,
         }
     }
 
@@ -52,6 +92,237 @@ impl ChatMessage {
     pub fn assistant(content: impl Into<String>) -> Self {
         Self::new("assistant", content)
     }
+
+    /// Mark this message as the end of a cacheable prompt prefix.
+    pub fn cacheable(mut self) -> Self {
+        self.cache = true;
+        self
+    }
+
+    /// The text of the message, with the text parts of a multi-part
+    /// message joined by newlines.
+    pub fn text(&self) -> Option<Cow<'_, str>> {
+        self.content.as_ref().map(MessageContent::text)
+    }
+}
+
+/// Largest image accepted in a message, in decoded bytes.
+///
+/// This is the lowest per-image limit among the supported providers
+/// (Anthropic); larger images are rejected before the request is sent.
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// The content of a [`ChatMessage`].
+///
+/// Serializes as a bare string, or as an OpenAI content-parts array for
+/// multimodal messages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    /// Plain text.
+    Text(String),
+    /// Text and image parts, in order.
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// The text content, with text parts joined by newlines.
+    pub fn text(&self) -> Cow<'_, str> {
+        match self {
+            Self::Text(text) => Cow::Borrowed(text),
+            Self::Parts(parts) => {
+                let mut texts = parts.iter().filter_map(ContentPart::as_text);
+                match (texts.next(), texts.next()) {
+                    (None, _) => Cow::Borrowed(""),
+                    (Some(only), None) => Cow::Borrowed(only),
+                    (Some(first), Some(second)) => {
+                        let mut joined = format!("{first}\n{second}");
+                        for text in texts {
+                            joined.push('\n');
+                            joined.push_str(text);
+                        }
+                        Cow::Owned(joined)
+                    }
+                }
+            }
+        }
+    }
+
+    /// The image parts, in order.
+    pub fn images(&self) -> impl Iterator<Item = &ImagePart> {
+        let parts: &[ContentPart] = match self {
+            Self::Text(_) => &[],
+            Self::Parts(parts) => parts,
+        };
+        parts.iter().filter_map(|part| match part {
+            ContentPart::Image(image) => Some(image),
+            ContentPart::Text { .. } => None,
+        })
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+/// One part of a multi-part message.
+///
+/// Serializes in the OpenAI content-parts format. Inline images travel as
+/// `data:` URLs and are parsed back into [`ImageSource::Base64`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "WirePart", from = "WirePart")]
+pub enum ContentPart {
+    /// A block of text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// An image.
+    Image(ImagePart),
+}
+
+impl ContentPart {
+    /// A text part.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// The text of a text part.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            Self::Image(_) => None,
+        }
+    }
+}
+
+/// An image in a multi-part message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePart {
+    /// Where the image comes from.
+    pub source: ImageSource,
+
+    /// OpenAI detail hint (`"low"`, `"high"` or `"auto"`).
+    pub detail: Option<String>,
+}
+
+impl ImagePart {
+    /// An image the provider fetches from a URL.
+    pub fn url(url: impl Into<String>) -> Self {
+        Self {
+            source: ImageSource::Url(url.into()),
+            detail: None,
+        }
+    }
+
+    /// An inline image from already base64-encoded data.
+    pub fn base64(mime_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            source: ImageSource::Base64 {
+                mime_type: mime_type.into(),
+                data: data.into(),
+            },
+            detail: None,
+        }
+    }
+
+    /// An inline image from raw bytes.
+    pub fn from_bytes(mime_type: impl Into<String>, bytes: &[u8]) -> Self {
+        Self::base64(mime_type, BASE64.encode(bytes))
+    }
+
+    /// Size of an inline image in decoded bytes. `None` for URL images.
+    pub fn byte_len(&self) -> Option<usize> {
+        match &self.source {
+            ImageSource::Url(_) => None,
+            ImageSource::Base64 { data, .. } => {
+                let padding = data.bytes().rev().take_while(|&b| b == b'=').count();
+                Some((data.len() / 4 * 3).saturating_sub(padding))
+            }
+        }
+    }
+
+    /// The image as a URL: the source URL, or a `data:` URL for inline data.
+    pub fn to_url(&self) -> String {
+        match &self.source {
+            ImageSource::Url(url) => url.clone(),
+            ImageSource::Base64 { mime_type, data } => format!("data:{mime_type};base64,{data}"),
+        }
+    }
+}
+
+/// Where an [`ImagePart`] comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageSource {
+    /// A URL the provider fetches.
+    Url(String),
+    /// Base64-encoded image data.
+    Base64 {
+        /// MIME type (e.g. `"image/png"`).
+        mime_type: String,
+        /// Base64-encoded bytes.
+        data: String,
+    },
+}
+
+/// OpenAI wire form of a [`ContentPart`].
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WirePart {
+    Text { text: String },
+    ImageUrl { image_url: WireImageUrl },
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireImageUrl {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl From<ContentPart> for WirePart {
+    fn from(part: ContentPart) -> Self {
+        match part {
+            ContentPart::Text { text } => Self::Text { text },
+            ContentPart::Image(image) => Self::ImageUrl {
+                image_url: WireImageUrl {
+                    url: image.to_url(),
+                    detail: image.detail,
+                },
+            },
+        }
+    }
+}
+
+impl From<WirePart> for ContentPart {
+    fn from(part: WirePart) -> Self {
+        match part {
+            WirePart::Text { text } => Self::Text { text },
+            WirePart::ImageUrl { image_url } => {
+                let source = image_url
+                    .url
+                    .strip_prefix("data:")
+                    .and_then(|rest| rest.split_once(";base64,"))
+                    .map(|(mime_type, data)| ImageSource::Base64 {
+                        mime_type: mime_type.to_string(),
+                        data: data.to_string(),
+                    })
+                    .unwrap_or(ImageSource::Url(image_url.url));
+                Self::Image(ImagePart {
+                    source,
+                    detail: image_url.detail,
+                })
+            }
+        }
+    }
 }
 
 /// A tool call requested by the model.
@@ -95,13 +366,80 @@ pub struct ChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
 
+    /// Nucleus sampling: only tokens within this cumulative probability
+    /// mass are considered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+
+    /// Sequences at which generation stops. The stop sequence itself is
+    /// not included in the reply.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+
+    /// Seed for best-effort deterministic sampling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Penalty (-2.0 to 2.0) on tokens in proportion to how often they
+    /// have already appeared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+
+    /// Penalty (-2.0 to 2.0) on tokens that have appeared at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+
+    /// Bias (-100 to 100) added to the logits of specific token IDs, keyed
+    /// by the token ID as a string.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub logit_bias: HashMap<String, f32>,
+
     /// Tool definitions available to the model.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<serde_json::Value>,
 
+    /// How the model may use `tools`, in OpenAI format: `"auto"`, `"none"`,
+    /// `"required"`, or `{"type": "function", "function": {"name": ...}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+
     /// Whether to stream the response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+
+    /// Constrain the reply to JSON, optionally matching a schema.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+
+    /// Allow the reply to be served from, and stored in, an
+    /// [`LlmCache`](crate::cache::LlmCache). Only honoured at temperature 0.
+    /// Never sent to the provider.
+    #[serde(skip)]
+    pub cache: bool,
+
+    /// Mark the tool definitions as a stable, cacheable prompt prefix.
+    /// Providers with explicit prompt caching (Anthropic) place a cache
+    /// breakpoint after the last tool. Never sent to the provider.
+    #[serde(skip)]
+    pub cache_tools: bool,
+
+    /// Overall deadline for this request, overriding the provider's
+    /// configured `timeout_secs`. Never sent to the provider.
+    #[serde(skip)]
+    pub timeout: Option<std::time::Duration>,
+
+    /// Reasoning effort for reasoning models, overriding the provider's
+    /// configured default. Only sent to models that accept it (see
+    /// [`reasoning::accepts_reasoning_effort`](crate::reasoning::accepts_reasoning_effort)).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Budget of reasoning tokens. Providers that take an effort level
+    /// instead translate it with
+    /// [`reasoning::effort_for_budget`](crate::reasoning::effort_for_budget)
+    /// when no explicit effort is set. Never sent as-is.
+    #[serde(skip)]
+    pub max_reasoning_tokens: Option<u32>,
 }
 
 impl ChatRequest {
@@ -112,10 +450,128 @@ impl ChatRequest {
             messages,
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            stop: Vec::new(),
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: HashMap::new(),
             tools: Vec::new(),
+            tool_choice: None,
             stream: None,
+            response_format: None,
+            cache: false,
+            cache_tools: false,
+            timeout: None,
+            reasoning_effort: None,
+            max_reasoning_tokens: None,
         }
     }
+
+    /// Set the nucleus sampling threshold.
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Stop generating at any of `sequences`.
+    pub fn with_stop<I, S>(mut self, sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stop = sequences.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the sampling seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the frequency penalty.
+    pub fn with_frequency_penalty(mut self, penalty: f64) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set the presence penalty.
+    pub fn with_presence_penalty(mut self, penalty: f64) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Bias the logit of `token` (a token ID) by `bias`.
+    pub fn with_logit_bias(mut self, token: impl Into<String>, bias: f32) -> Self {
+        self.logit_bias.insert(token.into(), bias);
+        self
+    }
+}
+
+/// Requested shape of the model's reply, in OpenAI `response_format` form.
+///
+/// Providers without a native equivalent translate it: Anthropic forces a
+/// tool whose input schema is the requested schema, Gemini sets
+/// `responseMimeType`/`responseSchema`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Free-form text (the default).
+    Text,
+    /// Any syntactically valid JSON object.
+    JsonObject,
+    /// JSON matching a schema.
+    JsonSchema {
+        /// The schema and its name.
+        json_schema: JsonSchemaFormat,
+    },
+}
+
+impl ResponseFormat {
+    /// Request JSON matching `schema`, in strict mode.
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: name.into(),
+                description: None,
+                schema,
+                strict: Some(true),
+            },
+        }
+    }
+
+    /// The schema the reply must match, if any.
+    pub fn schema(&self) -> Option<&JsonSchemaFormat> {
+        match self {
+            Self::JsonSchema { json_schema } => Some(json_schema),
+            _ => None,
+        }
+    }
+
+    /// Whether the reply must be JSON.
+    pub fn is_json(&self) -> bool {
+        !matches!(self, Self::Text)
+    }
+}
+
+/// A named JSON Schema for [`ResponseFormat::JsonSchema`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    /// Schema name (letters, digits, `_` and `-`). Anthropic uses it as the
+    /// name of the forced tool.
+    pub name: String,
+
+    /// What the output represents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The JSON Schema itself.
+    pub schema: serde_json::Value,
+
+    /// Ask the provider to enforce the schema exactly, where supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
 }
 
 /// A chat completion response from an LLM provider (OpenAI format).
@@ -132,6 +588,49 @@ pub struct ChatResponse {
 
     /// The model that generated the response.
     pub model: String,
+
+    /// Providers a [`FailoverChain`](crate::failover::FailoverChain) passed
+    /// over before one answered, in order. Empty when the primary answered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover: Vec<FailoverEvent>,
+
+    /// Failed attempts a [`RetryPolicy`](crate::retry::RetryPolicy)
+    /// retried before this response arrived.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// A provider that was passed over while failing over to the next one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverEvent {
+    /// Name of the provider that was passed over.
+    pub provider: String,
+
+    /// Model the provider was (or would have been) asked for.
+    pub model: String,
+
+    /// Why it was passed over.
+    pub reason: FailoverReason,
+
+    /// The provider's error, for [`FailoverReason::Error`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Why a failover chain passed over a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverReason {
+    /// The request failed with an error that allows failover.
+    Error,
+    /// The provider's circuit is open after repeated failures.
+    CircuitOpen,
+    /// The circuit is half-open and another request is already probing it.
+    ProbeInFlight,
 }
 
 /// A single completion choice within a response.
@@ -159,6 +658,12 @@ pub struct Choice {
 /// - `completion_tokens` -> `output_tokens`
 pub use clawft_types::provider::Usage;
 
+/// How much a reasoning model thinks before answering.
+///
+/// Re-exported from [`clawft_types::provider::ReasoningEffort`] so the same
+/// value can come from user config.
+pub use clawft_types::provider::ReasoningEffort;
+
 // ── Streaming types ─────────────────────────────────────────────────────
 
 /// A single chunk received during SSE streaming of a chat completion.
@@ -173,6 +678,13 @@ pub enum StreamChunk {
         text: String,
     },
 
+    /// A reasoning content delta. Kept apart from the reply text and never
+    /// shown to the user.
+    ReasoningDelta {
+        /// The partial reasoning text.
+        text: String,
+    },
+
     /// A tool call delta (partial tool invocation).
     ToolCallDelta {
         /// Index of the tool call in the tool_calls array.
@@ -231,6 +743,10 @@ pub(crate) struct StreamDeltaContent {
     /// Partial tool calls (if the model is invoking tools).
     #[serde(default)]
     pub tool_calls: Option<Vec<StreamDeltaToolCall>>,
+
+    /// Partial reasoning (DeepSeek `reasoning_content`, or `reasoning`).
+    #[serde(default, alias = "reasoning")]
+    pub reasoning_content: Option<String>,
 }
 
 /// A tool call delta within a streaming choice.
@@ -266,17 +782,49 @@ pub(crate) struct StreamDeltaUsage {
     pub prompt_tokens: Option<i32>,
     pub completion_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<StreamPromptTokensDetails>,
+    #[serde(default)]
+    pub completion_tokens_details: Option<StreamCompletionTokensDetails>,
+}
+
+/// Breakdown of streamed prompt tokens.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct StreamPromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: Option<i32>,
+}
+
+/// Breakdown of streamed completion tokens.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct StreamCompletionTokensDetails {
+    #[serde(default)]
+    pub reasoning_tokens: Option<i32>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn cache_marker_is_never_serialized() {
+        let msg = ChatMessage::system("Long, stable instructions.").cacheable();
+        assert!(msg.cache);
+        let json = serde_json::to_value(&msg).unwrap();
+        assert!(json.get("cache").is_none());
+
+        let mut request = ChatRequest::new("gpt-4o", vec![msg]);
+        request.cache_tools = true;
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("cache_tools").is_none());
+        assert!(json["messages"][0].get("cache").is_none());
+    }
+
     #[test]
     fn chat_message_new_helpers() {
         let sys = ChatMessage::system("You are helpful.");
         assert_eq!(sys.role, "system");
-        assert_eq!(sys.content.as_deref(), Some("You are helpful."));
+        assert_eq!(sys.text().as_deref(), Some("You are helpful."));
         assert!(sys.tool_call_id.is_none());
         assert!(sys.tool_calls.is_none());
 
@@ -295,6 +843,46 @@ mod tests {
         assert_eq!(msg, parsed);
     }
 
+    #[test]
+    fn content_parts_use_openai_format() {
+        let msg = ChatMessage::with_parts(
+            "user",
+            vec![
+                ContentPart::text("What is in this image?"),
+                ContentPart::Image(ImagePart::url("https://example.com/cat.jpg")),
+                ContentPart::Image(ImagePart::from_bytes("image/png", b"png")),
+            ],
+        );
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"role": "user", "content": [
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/cat.jpg"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,cG5n"}}
+            ]})
+        );
+
+        // Data URLs parse back into inline images.
+        let restored: ChatMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, msg);
+        let images: Vec<_> = restored.content.as_ref().unwrap().images().collect();
+        assert_eq!(images[1].byte_len(), Some(3));
+        assert_eq!(restored.text().as_deref(), Some("What is in this image?"));
+    }
+
+    #[test]
+    fn plain_string_content_stays_a_string() {
+        let json = serde_json::to_value(ChatMessage::user("hi")).unwrap();
+        assert_eq!(json["content"], "hi");
+        let parts = MessageContent::Parts(vec![
+            ContentPart::text("a"),
+            ContentPart::Image(ImagePart::url("u")),
+            ContentPart::text("b"),
+        ]);
+        assert_eq!(parts.text(), "a\nb");
+    }
+
     #[test]
     fn chat_message_skips_none_fields() {
         let msg = ChatMessage::user("Hi");
@@ -317,6 +905,8 @@ mod tests {
                     arguments: r#"{"city":"London"}"#.into(),
                 },
             }]),
+            cache: false,
+            reasoning: None,
         };
         let json = serde_json::to_string(&msg).unwrap();
         assert!(json.contains("tool_calls"));
@@ -352,6 +942,38 @@ mod tests {
         assert!(!json.contains("stream"));
         assert!(!json.contains("max_tokens"));
         assert!(!json.contains("temperature"));
+        for field in [
+            "top_p",
+            "stop",
+            "seed",
+            "frequency_penalty",
+            "presence_penalty",
+            "logit_bias",
+        ] {
+            assert!(!json.contains(field), "{field} serialized while unset");
+        }
+    }
+
+    #[test]
+    fn sampling_controls_serialize_when_set() {
+        let req = ChatRequest::new("gpt-4o", vec![ChatMessage::user("Label this")])
+            .with_top_p(0.9)
+            .with_stop(["\n", "END"])
+            .with_seed(42)
+            .with_frequency_penalty(0.5)
+            .with_presence_penalty(-0.25)
+            .with_logit_bias("1234", -100.0)
+            .with_logit_bias("5678", 5.0);
+        let value = serde_json::to_value(&req).unwrap();
+        assert_eq!(value["top_p"], 0.9);
+        assert_eq!(value["stop"], serde_json::json!(["\n", "END"]));
+        assert_eq!(value["seed"], 42);
+        assert_eq!(value["frequency_penalty"], 0.5);
+        assert_eq!(value["presence_penalty"], -0.25);
+        assert_eq!(
+            value["logit_bias"],
+            serde_json::json!({"1234": -100.0, "5678": 5.0})
+        );
     }
 
     #[test]
@@ -361,14 +983,44 @@ mod tests {
             messages: vec![ChatMessage::user("test")],
             max_tokens: Some(100),
             temperature: Some(0.7),
+            top_p: None,
+            stop: Vec::new(),
+            seed: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            logit_bias: HashMap::new(),
             tools: vec![serde_json::json!({"type": "function", "function": {"name": "test"}})],
+            tool_choice: Some(serde_json::json!("auto")),
             stream: Some(true),
+            response_format: Some(ResponseFormat::JsonObject),
+            cache: false,
+            cache_tools: false,
+            timeout: None,
+            reasoning_effort: None,
+            max_reasoning_tokens: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("max_tokens"));
         assert!(json.contains("temperature"));
         assert!(json.contains("tools"));
         assert!(json.contains("stream"));
+        assert!(json.contains(r#""response_format":{"type":"json_object"}"#));
+    }
+
+    #[test]
+    fn json_schema_format_matches_openai_shape() {
+        let format = ResponseFormat::json_schema(
+            "person",
+            serde_json::json!({"type": "object", "properties": {"name": {"type": "string"}}}),
+        );
+        let value = serde_json::to_value(&format).unwrap();
+        assert_eq!(value["type"], "json_schema");
+        assert_eq!(value["json_schema"]["name"], "person");
+        assert_eq!(value["json_schema"]["strict"], true);
+        assert!(value["json_schema"].get("description").is_none());
+        assert_eq!(value["json_schema"]["schema"]["type"], "object");
+        let back: ResponseFormat = serde_json::from_value(value).unwrap();
+        assert_eq!(back, format);
     }
 
     #[test]
@@ -393,7 +1045,7 @@ mod tests {
         let resp: ChatResponse = serde_json::from_str(json).unwrap();
         assert_eq!(resp.id, "chatcmpl-abc123");
         assert_eq!(resp.choices.len(), 1);
-        assert_eq!(resp.choices[0].message.content.as_deref(), Some("Hello!"));
+        assert_eq!(resp.choices[0].message.text().as_deref(), Some("Hello!"));
         assert_eq!(resp.choices[0].finish_reason.as_deref(), Some("stop"));
         let usage = resp.usage.unwrap();
         assert_eq!(usage.input_tokens, 10);
@@ -425,6 +1077,10 @@ mod tests {
             input_tokens: 100,
             output_tokens: 50,
             total_tokens: 150,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         };
         let json = serde_json::to_string(&usage).unwrap();
         let parsed: Usage = serde_json::from_str(&json).unwrap();
@@ -0,0 +1,594 @@
+//! Token usage accounting and cost tracking.
+//!
+//! A [`UsageTracker`] accumulates [`Usage`] per `(provider, model, session)`
+//! and prices it with a [`PriceTable`] (built-in list prices plus config
+//! overrides). When a ledger path is set, every call is also appended to a
+//! JSONL ledger, flushed periodically and on drop, which
+//! [`summarize_day`] reads back for reporting.
+//!
+//! ```rust,ignore
+//! use clawft_llm::usage::{PriceTable, UsageTracker};
+//!
+//! let tracker = UsageTracker::new(PriceTable::default()).with_ledger(path);
+//! let cost = tracker.record("openai", "gpt-4o", "cli:default", &response_usage);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub use clawft_types::config::PriceOverride;
+pub use clawft_types::provider::{ModelPrice, find_model_price};
+
+use crate::types::Usage;
+
+/// File name of the usage ledger inside the state directory.
+pub const LEDGER_FILE: &str = "usage.jsonl";
+
+/// Default interval between ledger flushes.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Ledger location under a home directory: `~/.clawft/state/usage.jsonl`.
+pub fn ledger_path(home: &Path) -> PathBuf {
+    home.join(".clawft").join("state").join(LEDGER_FILE)
+}
+
+fn bare_model(model: &str) -> String {
+    model.rsplit('/').next().unwrap_or(model).to_lowercase()
+}
+
+// ── Pricing ──────────────────────────────────────────────────────────────
+
+/// Model prices: configured overrides first, then the built-in
+/// [`MODEL_PRICES`](clawft_types::provider::MODEL_PRICES).
+///
+/// Models matched by neither are free (local models, unknown gateways).
+#[derive(Debug, Clone, Default)]
+pub struct PriceTable {
+    /// Bare lowercase prefix and its price, longest prefix first.
+    overrides: Vec<(String, PriceOverride)>,
+}
+
+impl PriceTable {
+    /// Create a table from config overrides keyed by model-name prefix.
+    pub fn with_overrides(overrides: &HashMap<String, PriceOverride>) -> Self {
+        let mut overrides: Vec<(String, PriceOverride)> = overrides
+            .iter()
+            .map(|(prefix, price)| (bare_model(prefix), *price))
+            .collect();
+        overrides.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { overrides }
+    }
+
+    /// Price per 1K `(input, output)` tokens for `model`, if known.
+    pub fn rates(&self, model: &str) -> Option<(f64, f64)> {
+        self.price(model)
+            .map(|price| (price.input_per_1k, price.output_per_1k))
+    }
+
+    /// Full price for `model` (including prompt-cache rates), if known.
+    ///
+    /// Overrides without explicit cache rates price cached tokens as
+    /// regular input.
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        let bare = bare_model(model);
+        if let Some((_, price)) = self.overrides.iter().find(|(p, _)| bare.starts_with(p)) {
+            return Some(ModelPrice {
+                prefix: "",
+                input_per_1k: price.input_per_1k,
+                output_per_1k: price.output_per_1k,
+                cache_read_per_1k: price.cache_read_per_1k.unwrap_or(price.input_per_1k),
+                cache_write_per_1k: price.cache_write_per_1k.unwrap_or(price.input_per_1k),
+            });
+        }
+        find_model_price(&bare).copied()
+    }
+
+    /// Cost in US dollars of `usage` on `model`, with prompt-cache reads and
+    /// writes priced at their own rates.
+    pub fn cost(&self, model: &str, usage: &Usage) -> f64 {
+        self.price(model)
+            .map_or(0.0, |price| price.usage_cost(usage))
+    }
+}
+
+// ── Accumulation ─────────────────────────────────────────────────────────
+
+/// What usage is accumulated by.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UsageKey {
+    /// Provider name (e.g. `"openai"`).
+    pub provider: String,
+    /// Model identifier as sent to the provider.
+    pub model: String,
+    /// Session key (e.g. `"telegram:12345"`).
+    pub session: String,
+}
+
+/// Accumulated usage and cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Number of completed LLM calls.
+    pub requests: u64,
+    /// Prompt tokens.
+    pub input_tokens: u64,
+    /// Completion tokens.
+    pub output_tokens: u64,
+    /// Cost in US dollars.
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    /// Total tokens in both directions.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    fn add(&mut self, other: &UsageTotals) {
+        self.requests += other.requests;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+/// One LLM call as written to the ledger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// When the call completed.
+    pub timestamp: DateTime<Utc>,
+    /// Provider name.
+    pub provider: String,
+    /// Model identifier.
+    pub model: String,
+    /// Session key.
+    pub session: String,
+    /// Prompt tokens.
+    pub input_tokens: u32,
+    /// Completion tokens.
+    pub output_tokens: u32,
+    /// Cost in US dollars.
+    pub cost_usd: f64,
+}
+
+impl UsageRecord {
+    fn totals(&self) -> UsageTotals {
+        UsageTotals {
+            requests: 1,
+            input_tokens: u64::from(self.input_tokens),
+            output_tokens: u64::from(self.output_tokens),
+            cost_usd: self.cost_usd,
+        }
+    }
+}
+
+struct TrackerState {
+    totals: HashMap<UsageKey, UsageTotals>,
+    pending: Vec<UsageRecord>,
+    last_flush: DateTime<Utc>,
+}
+
+/// Accumulates token usage and cost per `(provider, model, session)`.
+///
+/// All methods take `&self`; the tracker is shared behind an `Arc`.
+pub struct UsageTracker {
+    prices: PriceTable,
+    ledger: Option<PathBuf>,
+    flush_interval: Duration,
+    state: Mutex<TrackerState>,
+}
+
+impl UsageTracker {
+    /// Create an in-memory tracker using `prices`.
+    pub fn new(prices: PriceTable) -> Self {
+        Self {
+            prices,
+            ledger: None,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            state: Mutex::new(TrackerState {
+                totals: HashMap::new(),
+                pending: Vec::new(),
+                last_flush: Utc::now(),
+            }),
+        }
+    }
+
+    /// Append every recorded call to the JSONL ledger at `path`.
+    pub fn with_ledger(mut self, path: PathBuf) -> Self {
+        self.ledger = Some(path);
+        self
+    }
+
+    /// Set how often pending records are written to the ledger.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// The ledger path, if persistence is enabled.
+    pub fn ledger(&self) -> Option<&Path> {
+        self.ledger.as_deref()
+    }
+
+    /// The price table used for cost computation.
+    pub fn prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// Record one completed call and return its cost in US dollars.
+    ///
+    /// Flushes the ledger when the flush interval has elapsed; flush
+    /// failures are logged and the records retried on the next flush.
+    pub fn record(&self, provider: &str, model: &str, session: &str, usage: &Usage) -> f64 {
+        self.record_cost(
+            provider,
+            model,
+            session,
+            usage,
+            self.prices.cost(model, usage),
+        )
+    }
+
+    /// Record one call whose cost was computed elsewhere, such as a task
+    /// a peer gateway ran and priced, and return `cost_usd`.
+    pub fn record_cost(
+        &self,
+        provider: &str,
+        model: &str,
+        session: &str,
+        usage: &Usage,
+        cost_usd: f64,
+    ) -> f64 {
+        let record = UsageRecord {
+            timestamp: Utc::now(),
+            provider: provider.to_owned(),
+            model: model.to_owned(),
+            session: session.to_owned(),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost_usd,
+        };
+        let cost = record.cost_usd;
+
+        let due = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let key = UsageKey {
+                provider: record.provider.clone(),
+                model: record.model.clone(),
+                session: record.session.clone(),
+            };
+            state.totals.entry(key).or_default().add(&record.totals());
+            if self.ledger.is_none() {
+                return cost;
+            }
+            let elapsed = (record.timestamp - state.last_flush)
+                .to_std()
+                .unwrap_or_default();
+            state.pending.push(record);
+            elapsed >= self.flush_interval
+        };
+
+        if due && let Err(e) = self.flush() {
+            warn!(error = %e, "failed to flush usage ledger");
+        }
+        cost
+    }
+
+    /// Totals for one session across all providers and models.
+    pub fn session_totals(&self, session: &str) -> UsageTotals {
+        self.sum(|key| key.session == session)
+    }
+
+    /// Totals across everything recorded by this tracker.
+    pub fn totals(&self) -> UsageTotals {
+        self.sum(|_| true)
+    }
+
+    /// Whether `session` has spent at least `budget_usd`.
+    pub fn session_exhausted(&self, session: &str, budget_usd: f64) -> bool {
+        self.session_totals(session).cost_usd >= budget_usd
+    }
+
+    /// Per-key totals, sorted by key.
+    pub fn snapshot(&self) -> Vec<(UsageKey, UsageTotals)> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<_> = state.totals.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    fn sum(&self, filter: impl Fn(&UsageKey) -> bool) -> UsageTotals {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut totals = UsageTotals::default();
+        for (_, t) in state.totals.iter().filter(|(k, _)| filter(k)) {
+            totals.add(t);
+        }
+        totals
+    }
+
+    /// Append pending records to the ledger now.
+    ///
+    /// A no-op without a ledger. On failure the records stay pending.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let Some(path) = &self.ledger else {
+            return Ok(());
+        };
+        let pending = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.last_flush = Utc::now();
+            std::mem::take(&mut state.pending)
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if let Err(e) = append_records(path, &pending) {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let newer = std::mem::replace(&mut state.pending, pending);
+            state.pending.extend(newer);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for UsageTracker {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!(error = %e, "failed to flush usage ledger on shutdown");
+        }
+    }
+}
+
+impl std::fmt::Debug for UsageTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsageTracker")
+            .field("ledger", &self.ledger)
+            .field("flush_interval", &self.flush_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+// ── Ledger ───────────────────────────────────────────────────────────────
+
+fn append_records(path: &Path, records: &[UsageRecord]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut buf = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut buf, record)?;
+        buf.push(b'\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&buf)
+}
+
+/// Read every record in the ledger at `path`.
+///
+/// A missing ledger is empty; malformed lines are skipped.
+pub fn read_ledger(path: &Path) -> std::io::Result<Vec<UsageRecord>> {
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!(error = %e, "skipping malformed usage ledger line"),
+        }
+    }
+    Ok(records)
+}
+
+/// Usage totals for one day, overall and per model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaySummary {
+    /// Totals across all models.
+    pub totals: UsageTotals,
+    /// Totals per model.
+    pub by_model: BTreeMap<String, UsageTotals>,
+}
+
+/// Summarize the ledger records whose local date is `day`.
+pub fn summarize_day(path: &Path, day: NaiveDate) -> std::io::Result<DaySummary> {
+    let mut summary = DaySummary::default();
+    for record in read_ledger(path)? {
+        if record.timestamp.with_timezone(&Local).date_naive() != day {
+            continue;
+        }
+        let totals = record.totals();
+        summary.totals.add(&totals);
+        summary
+            .by_model
+            .entry(record.model)
+            .or_default()
+            .add(&totals);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: u32, output: u32) -> Usage {
+        Usage {
+            input_tokens: input,
+            output_tokens: output,
+            total_tokens: input + output,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn temp_ledger() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("clawft-usage-{}", uuid::Uuid::new_v4()))
+            .join(LEDGER_FILE)
+    }
+
+    #[test]
+    fn accumulates_per_key_and_prices_calls() {
+        let tracker = UsageTracker::new(PriceTable::default());
+        // gpt-4o: $2.50/M in, $10/M out.
+        let cost = tracker.record("openai", "gpt-4o", "cli:a", &usage(1_000, 500));
+        assert!(close(cost, 0.0075));
+        tracker.record("openai", "gpt-4o", "cli:a", &usage(3_000, 0));
+        tracker.record("openai", "gpt-4o", "cli:b", &usage(1_000, 500));
+        tracker.record("ollama", "llama3.2", "cli:a", &usage(9_000, 9_000));
+
+        let a = tracker.session_totals("cli:a");
+        assert_eq!(a.requests, 3);
+        assert_eq!(a.input_tokens, 13_000);
+        assert_eq!(a.output_tokens, 9_500);
+        // Local models are free.
+        assert!(close(a.cost_usd, 0.0075 + 0.0075));
+
+        let all = tracker.totals();
+        assert_eq!(all.requests, 4);
+        assert!(close(all.cost_usd, 0.0225));
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(snapshot[0].0.provider, "ollama");
+
+        // Costs priced elsewhere are taken as given.
+        let cost = tracker.record_cost("ollama@lab", "llama3.2", "cli:a", &usage(10, 10), 0.5);
+        assert!(close(cost, 0.5));
+        assert!(close(tracker.session_totals("cli:a").cost_usd, 0.515));
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides = HashMap::from([
+            (
+                "openai/gpt-4o".to_string(),
+                PriceOverride {
+                    input_per_1k: 1.0,
+                    output_per_1k: 2.0,
+                    cache_read_per_1k: Some(0.1),
+                    cache_write_per_1k: None,
+                },
+            ),
+            (
+                "llama3".to_string(),
+                PriceOverride {
+                    input_per_1k: 0.5,
+                    output_per_1k: 0.5,
+                    cache_read_per_1k: None,
+                    cache_write_per_1k: None,
+                },
+            ),
+        ]);
+        let prices = PriceTable::with_overrides(&overrides);
+        assert_eq!(prices.rates("gpt-4o-mini"), Some((1.0, 2.0)));
+        assert!(close(
+            prices.cost("ollama/llama3.2", &usage(2_000, 2_000)),
+            2.0
+        ));
+        // Unmatched models fall back to the list price.
+        assert_eq!(prices.rates("claude-sonnet-4-5"), Some((0.003, 0.015)));
+        assert_eq!(prices.rates("my-finetune"), None);
+
+        // Override cache rates apply; unset ones fall back to the input price.
+        let cached = Usage {
+            cache_read_tokens: 1_000,
+            cache_write_tokens: 1_000,
+            reasoning_tokens: 0,
+            ..usage(3_000, 0)
+        };
+        assert!(close(prices.cost("gpt-4o", &cached), 1.0 + 0.1 + 1.0));
+    }
+
+    #[test]
+    fn prices_anthropic_cache_reads_and_writes() {
+        let tracker = UsageTracker::new(PriceTable::default());
+        // claude-sonnet-4: $3/M in, $0.30/M cache read, $3.75/M cache write.
+        let usage = Usage {
+            cache_read_tokens: 8_000,
+            cache_write_tokens: 1_000,
+            reasoning_tokens: 0,
+            ..usage(10_000, 0)
+        };
+        let cost = tracker.record("anthropic", "claude-sonnet-4-5", "s", &usage);
+        assert!(close(cost, 0.003 + 0.0024 + 0.003_75));
+    }
+
+    #[test]
+    fn budget_is_exhausted_at_the_limit() {
+        let tracker = UsageTracker::new(PriceTable::default());
+        assert!(!tracker.session_exhausted("s", 0.01));
+        tracker.record("openai", "gpt-4o", "s", &usage(1_000, 500));
+        assert!(!tracker.session_exhausted("s", 0.01));
+        tracker.record("openai", "gpt-4o", "s", &usage(1_000, 500));
+        assert!(tracker.session_exhausted("s", 0.01));
+        assert!(!tracker.session_exhausted("other", 0.01));
+    }
+
+    #[test]
+    fn ledger_flushes_periodically_and_on_drop() {
+        let path = temp_ledger();
+        {
+            let tracker = UsageTracker::new(PriceTable::default())
+                .with_ledger(path.clone())
+                .with_flush_interval(Duration::from_secs(3_600));
+            tracker.record("openai", "gpt-4o", "s", &usage(1_000, 500));
+            assert!(read_ledger(&path).unwrap().is_empty());
+            tracker.flush().unwrap();
+            assert_eq!(read_ledger(&path).unwrap().len(), 1);
+            tracker.record("anthropic", "claude-sonnet-4", "s", &usage(1_000, 1_000));
+        }
+        let records = read_ledger(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].model, "claude-sonnet-4");
+
+        // A zero interval writes through on every call.
+        let tracker = UsageTracker::new(PriceTable::default())
+            .with_ledger(path.clone())
+            .with_flush_interval(Duration::ZERO);
+        tracker.record("openai", "gpt-4o", "t", &usage(10, 10));
+        assert_eq!(read_ledger(&path).unwrap().len(), 3);
+
+        let today = Local::now().date_naive();
+        let summary = summarize_day(&path, today).unwrap();
+        assert_eq!(summary.totals.requests, 3);
+        assert_eq!(summary.totals.input_tokens, 2_010);
+        assert!(close(summary.totals.cost_usd, 0.0075 + 0.018 + 0.000_125));
+        assert_eq!(summary.by_model["gpt-4o"].requests, 2);
+        let yesterday = today.pred_opt().unwrap();
+        assert_eq!(summarize_day(&path, yesterday).unwrap().totals.requests, 0);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn malformed_and_missing_ledgers_are_tolerated() {
+        let path = temp_ledger();
+        assert!(read_ledger(&path).unwrap().is_empty());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not json\n\n").unwrap();
+        assert!(read_ledger(&path).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
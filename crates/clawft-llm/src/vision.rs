@@ -0,0 +1,95 @@
+//! Image input checks.
+//!
+//! Image parts are rejected before a request leaves the process when the
+//! model registry ([`clawft_types::provider::MODELS`]) marks the model as
+//! text-only, or when an inline image exceeds [`MAX_IMAGE_BYTES`]. Models
+//! missing from the registry are assumed to accept images and left to the
+//! provider to judge.
+
+use crate::error::{ProviderError, Result};
+use crate::tokens::find_model_spec;
+use crate::types::{ChatRequest, MAX_IMAGE_BYTES};
+
+/// Whether `model` accepts image input.
+pub fn supports_images(model: &str) -> bool {
+    find_model_spec(model).is_none_or(|spec| spec.multimodal)
+}
+
+/// Check the image parts of `request` against the model and size limits.
+///
+/// Returns [`ProviderError::Unsupported`] naming the problem.
+pub fn check_images(request: &ChatRequest) -> Result<()> {
+    let mut images = request
+        .messages
+        .iter()
+        .filter_map(|m| m.content.as_ref())
+        .flat_map(|c| c.images())
+        .peekable();
+    if images.peek().is_none() {
+        return Ok(());
+    }
+    if !supports_images(&request.model) {
+        return Err(ProviderError::Unsupported(format!(
+            "model {} does not accept image input",
+            request.model
+        )));
+    }
+    for image in images {
+        if let Some(len) = image.byte_len().filter(|&len| len > MAX_IMAGE_BYTES) {
+            return Err(ProviderError::Unsupported(format!(
+                "image of {len} bytes exceeds the {MAX_IMAGE_BYTES}-byte limit"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatMessage, ContentPart, ImagePart};
+
+    fn image_request(model: &str, image: ImagePart) -> ChatRequest {
+        ChatRequest::new(
+            model,
+            vec![ChatMessage::with_parts(
+                "user",
+                vec![
+                    ContentPart::text("What is this?"),
+                    ContentPart::Image(image),
+                ],
+            )],
+        )
+    }
+
+    #[test]
+    fn text_only_requests_pass_for_any_model() {
+        let request = ChatRequest::new("gpt-3.5-turbo", vec![ChatMessage::user("hi")]);
+        assert!(check_images(&request).is_ok());
+    }
+
+    #[test]
+    fn images_rejected_for_text_only_models() {
+        let request = image_request("deepseek/deepseek-chat", ImagePart::url("https://x/a.png"));
+        let err = check_images(&request).unwrap_err();
+        assert!(matches!(err, ProviderError::Unsupported(_)));
+        assert!(err.to_string().contains("does not accept image input"));
+
+        let request = image_request("openai/gpt-4o", ImagePart::url("https://x/a.png"));
+        assert!(check_images(&request).is_ok());
+        // Unknown models are left to the provider.
+        let request = image_request("my-local-llava", ImagePart::url("https://x/a.png"));
+        assert!(check_images(&request).is_ok());
+    }
+
+    #[test]
+    fn oversized_inline_images_rejected() {
+        let big = ImagePart::from_bytes("image/png", &vec![0u8; MAX_IMAGE_BYTES + 1]);
+        let err = check_images(&image_request("claude-sonnet-4", big)).unwrap_err();
+        assert!(err.to_string().contains("exceeds"));
+
+        let ok = ImagePart::from_bytes("image/png", &vec![0u8; 1024]);
+        assert_eq!(ok.byte_len(), Some(1024));
+        assert!(check_images(&image_request("claude-sonnet-4", ok)).is_ok());
+    }
+}
@@ -0,0 +1,167 @@
+//! Mock HTTP server tests for [`AnthropicProvider`].
+//!
+//! Uses [`wiremock`] to emulate the Anthropic Messages API, covering the
+//! request headers, the translated request body, the non-streaming
+//! response, the event stream, and error mapping.
+
+use std::collections::HashMap;
+
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use clawft_llm::anthropic::AnthropicProvider;
+use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::error::ProviderError;
+use clawft_llm::provider::Provider;
+use clawft_llm::types::{ChatMessage, ChatRequest, StreamChunk};
+
+fn mock_config(server_url: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        name: "anthropic".into(),
+        base_url: format!("{server_url}/v1"),
+        api_key_env: "MOCK_UNUSED_KEY".into(),
+        model_prefix: Some("anthropic/".into()),
+        default_model: None,
+        headers: HashMap::from([("anthropic-version".into(), "2023-06-01".into())]),
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
+    }
+}
+
+fn test_request() -> ChatRequest {
+    ChatRequest::new(
+        "claude-test",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("Hello")],
+    )
+}
+
+#[tokio::test]
+async fn complete_sends_native_request_and_parses_response() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "sk-ant-test"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(body_partial_json(serde_json::json!({
+            "model": "claude-test",
+            "system": "Be brief.",
+            "max_tokens": 4096,
+            "messages": [{"role": "user", "content": [{"type": "text", "text": "Hello"}]}]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-test",
+            "content": [{"type": "text", "text": "Hi."}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 12, "output_tokens": 3, "cache_read_input_tokens": 4}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider =
+        AnthropicProvider::with_api_key(mock_config(&server.uri()), "sk-ant-test".into());
+    let response = provider.complete(&test_request()).await.unwrap();
+
+    assert_eq!(response.id, "msg_1");
+    assert_eq!(response.choices[0].message.text().as_deref(), Some("Hi."));
+    assert_eq!(response.choices[0].finish_reason.as_deref(), Some("stop"));
+    let usage = response.usage.unwrap();
+    assert_eq!(usage.input_tokens, 16);
+    assert_eq!(usage.output_tokens, 3);
+}
+
+#[tokio::test]
+async fn streaming_events_are_assembled() {
+    let server = MockServer::start().await;
+
+    let events = [
+        (
+            "message_start",
+            r#"{"type":"message_start","message":{"id":"msg_2","model":"claude-test","usage":{"input_tokens":8,"output_tokens":1}}}"#,
+        ),
+        (
+            "content_block_start",
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+        ),
+        (
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+        ),
+        (
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#,
+        ),
+        (
+            "content_block_stop",
+            r#"{"type":"content_block_stop","index":0}"#,
+        ),
+        (
+            "message_delta",
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":2}}"#,
+        ),
+        ("message_stop", r#"{"type":"message_stop"}"#),
+    ];
+    let body: String = events
+        .iter()
+        .map(|(event, data)| format!("event: {event}\ndata: {data}\n\n"))
+        .collect();
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = AnthropicProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let mut texts = Vec::new();
+    let response = provider
+        .complete_streaming(&test_request(), &mut |chunk| {
+            if let StreamChunk::TextDelta { text } = chunk {
+                texts.push(text.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(texts, vec!["Hel", "lo"]);
+    assert_eq!(response.choices[0].message.text().as_deref(), Some("Hello"));
+    assert_eq!(response.choices[0].finish_reason.as_deref(), Some("stop"));
+    let usage = response.usage.unwrap();
+    assert_eq!(usage.input_tokens, 8);
+    assert_eq!(usage.output_tokens, 2);
+}
+
+#[tokio::test]
+async fn overloaded_maps_to_server_error() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(529).set_body_json(serde_json::json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = AnthropicProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let err = provider.complete(&test_request()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ProviderError::ServerError { status: 529, .. }
+    ));
+}
@@ -0,0 +1,119 @@
+//! Mock HTTP server tests for [`OpenAiCompatEmbeddings`].
+//!
+//! Uses [`wiremock`] to emulate an OpenAI-compatible `/embeddings` endpoint
+//! that echoes one vector per input, covering batch splitting, result order,
+//! usage totals, and dimension checks.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::embeddings::{EmbeddingsOptions, EmbeddingsProvider, OpenAiCompatEmbeddings};
+use clawft_llm::error::ProviderError;
+
+fn mock_config(server_url: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        name: "openai".into(),
+        base_url: format!("{server_url}/v1"),
+        api_key_env: "MOCK_UNUSED_KEY".into(),
+        model_prefix: Some("openai/".into()),
+        default_model: None,
+        headers: HashMap::new(),
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
+    }
+}
+
+/// Answers each input `"<n>"` with a `dim`-long vector filled with `n`,
+/// listed in reverse index order.
+struct Echo {
+    dim: usize,
+}
+
+impl Respond for Echo {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        let inputs = body["input"].as_array().unwrap();
+        let data: Vec<Value> = inputs
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, text)| {
+                let n: f32 = text.as_str().unwrap().parse().unwrap();
+                json!({"object": "embedding", "index": index, "embedding": vec![n; self.dim]})
+            })
+            .collect();
+        let tokens = inputs.len() as u32;
+        ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": data,
+            "model": "text-embedding-3-small",
+            "usage": {"prompt_tokens": tokens, "total_tokens": tokens}
+        }))
+    }
+}
+
+fn inputs(n: usize) -> Vec<String> {
+    (0..n).map(|i| i.to_string()).collect()
+}
+
+#[tokio::test]
+async fn batches_are_split_and_reassembled_in_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(Echo { dim: 4 })
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let embedder =
+        OpenAiCompatEmbeddings::with_api_key(mock_config(&server.uri()), "sk-test".into())
+            .with_options(EmbeddingsOptions {
+                batch_size: 2,
+                dimensions: Some(4),
+                ..EmbeddingsOptions::default()
+            });
+    let resp = embedder
+        .embed(&inputs(5), "text-embedding-3-small")
+        .await
+        .unwrap();
+
+    assert_eq!(resp.embeddings.len(), 5);
+    assert_eq!(resp.dimension(), 4);
+    for (i, vector) in resp.embeddings.iter().enumerate() {
+        assert_eq!(vector[0], i as f32);
+    }
+    let usage = resp.usage.unwrap();
+    assert_eq!(usage.input_tokens, 5);
+    assert_eq!(usage.total_tokens, 5);
+}
+
+#[tokio::test]
+async fn wrong_dimension_is_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(Echo { dim: 3 })
+        .mount(&server)
+        .await;
+
+    let embedder = OpenAiCompatEmbeddings::with_api_key(mock_config(&server.uri()), "sk".into())
+        .with_options(EmbeddingsOptions {
+            dimensions: Some(8),
+            ..EmbeddingsOptions::default()
+        });
+    let err = embedder
+        .embed(&inputs(2), "text-embedding-3-small")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::InvalidResponse(_)));
+}
@@ -0,0 +1,165 @@
+//! Mock HTTP server tests for [`GeminiProvider`].
+//!
+//! Uses [`wiremock`] to emulate the Gemini `generateContent` and
+//! `streamGenerateContent` endpoints, covering the auth header, the
+//! translated request body, function-call parsing, the SSE stream, and
+//! error mapping.
+
+use std::collections::HashMap;
+
+use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::error::ProviderError;
+use clawft_llm::gemini::GeminiProvider;
+use clawft_llm::provider::Provider;
+use clawft_llm::types::{ChatMessage, ChatRequest, StreamChunk};
+
+fn mock_config(server_url: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        name: "gemini".into(),
+        base_url: format!("{server_url}/v1beta/openai"),
+        api_key_env: "MOCK_UNUSED_KEY".into(),
+        model_prefix: Some("gemini/".into()),
+        default_model: None,
+        headers: HashMap::new(),
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
+    }
+}
+
+fn test_request() -> ChatRequest {
+    ChatRequest::new(
+        "gemini-test",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("Hello")],
+    )
+}
+
+#[tokio::test]
+async fn complete_sends_native_request_and_parses_function_calls() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-test:generateContent"))
+        .and(header("x-goog-api-key", "AIza-test"))
+        .and(body_partial_json(serde_json::json!({
+            "systemInstruction": {"parts": [{"text": "Be brief."}]},
+            "contents": [{"role": "user", "parts": [{"text": "Hello"}]}]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "greet", "args": {"name": "you"}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 7,
+                "candidatesTokenCount": 4,
+                "totalTokenCount": 11
+            },
+            "modelVersion": "gemini-test-001"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = GeminiProvider::with_api_key(mock_config(&server.uri()), "AIza-test".into());
+    let response = provider.complete(&test_request()).await.unwrap();
+
+    assert_eq!(response.model, "gemini-test-001");
+    let choice = &response.choices[0];
+    assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+    let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+    assert_eq!(call.function.name, "greet");
+    assert_eq!(call.function.arguments, r#"{"name":"you"}"#);
+    assert_eq!(response.usage.unwrap().total_tokens, 11);
+}
+
+#[tokio::test]
+async fn streaming_chunks_are_assembled() {
+    let server = MockServer::start().await;
+
+    let chunks = [
+        r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}]}"#,
+        r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"lo"}]},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":2,"totalTokenCount":7}}"#,
+    ];
+    let body: String = chunks
+        .iter()
+        .map(|c| format!("data: {c}\r\n\r\n"))
+        .collect();
+
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-test:streamGenerateContent"))
+        .and(query_param("alt", "sse"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = GeminiProvider::with_api_key(mock_config(&server.uri()), "k".into());
+    let mut texts = Vec::new();
+    let response = provider
+        .complete_streaming(&test_request(), &mut |chunk| {
+            if let StreamChunk::TextDelta { text } = chunk {
+                texts.push(text.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(texts, vec!["Hel", "lo"]);
+    assert_eq!(response.choices[0].message.text().as_deref(), Some("Hello"));
+    assert_eq!(response.choices[0].finish_reason.as_deref(), Some("stop"));
+    assert_eq!(response.usage.unwrap().output_tokens, 2);
+}
+
+#[tokio::test]
+async fn truncated_stream_is_an_error() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-test:streamGenerateContent"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(
+                    "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi\"}]}}]}\n\n",
+                ),
+        )
+        .mount(&server)
+        .await;
+
+    let provider = GeminiProvider::with_api_key(mock_config(&server.uri()), "k".into());
+    let err = provider
+        .complete_streaming(&test_request(), &mut |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::InvalidResponse(_)));
+}
+
+#[tokio::test]
+async fn not_found_maps_to_model_not_found() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-test:generateContent"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "error": {"code": 404, "message": "models/gemini-test is not found", "status": "NOT_FOUND"}
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = GeminiProvider::with_api_key(mock_config(&server.uri()), "k".into());
+    let err = provider.complete(&test_request()).await.unwrap_err();
+    assert!(matches!(err, ProviderError::ModelNotFound(_)));
+}
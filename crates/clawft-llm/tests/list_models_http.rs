@@ -0,0 +1,137 @@
+//! Mock HTTP server tests for [`Provider::list_models`] and router model
+//! validation.
+//!
+//! Uses [`wiremock`] to emulate an OpenAI-compatible `/v1/models` endpoint
+//! and Ollama's native `/api/tags` endpoint.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::error::ProviderError;
+use clawft_llm::router::{check_model, provider_for_config};
+use clawft_llm::{Provider, ProviderRouter};
+
+fn mock_config(name: &str, server_url: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        name: name.into(),
+        base_url: format!("{server_url}/v1"),
+        api_key_env: "MOCK_UNUSED_KEY".into(),
+        model_prefix: Some(format!("{name}/")),
+        default_model: None,
+        headers: HashMap::new(),
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
+    }
+}
+
+async fn mount_openai_models(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("authorization", "Bearer sk-test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [
+                {"id": "gpt-4o", "object": "model", "owned_by": "openai"},
+                {"id": "gpt-4o-mini", "object": "model", "owned_by": "openai"}
+            ]
+        })))
+        .mount(server)
+        .await;
+}
+
+async fn mount_ollama_tags(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/api/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "models": [
+                {"name": "llama3.2:latest", "model": "llama3.2:latest", "size": 2019393189},
+                {"name": "qwen2.5-coder:7b", "model": "qwen2.5-coder:7b", "size": 4683087332u64}
+            ]
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn openai_compat_lists_models() {
+    let server = MockServer::start().await;
+    mount_openai_models(&server).await;
+
+    let provider =
+        provider_for_config(mock_config("openai", &server.uri()), Some("sk-test".into()));
+    let models = provider.list_models().await.unwrap();
+    assert_eq!(models, vec!["gpt-4o", "gpt-4o-mini"]);
+}
+
+#[tokio::test]
+async fn openai_compat_listing_reports_auth_failure() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "error": {"message": "Incorrect API key provided"}
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = provider_for_config(mock_config("openai", &server.uri()), Some("bad".into()));
+    let err = provider.list_models().await.unwrap_err();
+    assert!(matches!(err, ProviderError::AuthFailed(_)), "got: {err}");
+}
+
+#[tokio::test]
+async fn ollama_lists_tags_without_api_key() {
+    let server = MockServer::start().await;
+    mount_ollama_tags(&server).await;
+
+    let provider = provider_for_config(mock_config("ollama", &server.uri()), None);
+    let models = provider.list_models().await.unwrap();
+    assert_eq!(models, vec!["llama3.2:latest", "qwen2.5-coder:7b"]);
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get("authorization").is_none());
+}
+
+#[tokio::test]
+async fn router_validates_ollama_models() {
+    let server = MockServer::start().await;
+    mount_ollama_tags(&server).await;
+
+    let router = ProviderRouter::from_configs(vec![mock_config("ollama", &server.uri())]);
+    router.validate_model("ollama/llama3.2").await.unwrap();
+    router
+        .validate_model("ollama/qwen2.5-coder:7b")
+        .await
+        .unwrap();
+
+    let err = router.validate_model("ollama/mistral").await.unwrap_err();
+    match err {
+        ProviderError::ModelNotFound(msg) => {
+            assert!(msg.contains("'mistral'"), "{msg}");
+            assert!(msg.contains("llama3.2:latest"), "{msg}");
+        }
+        other => panic!("expected ModelNotFound, got: {other}"),
+    }
+}
+
+#[tokio::test]
+async fn check_model_against_openai_listing() {
+    let server = MockServer::start().await;
+    mount_openai_models(&server).await;
+
+    let provider =
+        provider_for_config(mock_config("openai", &server.uri()), Some("sk-test".into()));
+    check_model(provider.as_ref(), "gpt-4o").await.unwrap();
+    assert!(matches!(
+        check_model(provider.as_ref(), "gpt-5-turbo").await,
+        Err(ProviderError::ModelNotFound(_))
+    ));
+}
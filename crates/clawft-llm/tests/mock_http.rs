@@ -14,17 +14,23 @@
 //! - Malformed JSON response
 //! - Empty choices array
 //! - Custom headers forwarded correctly
+//! - Streaming: text and tool-call delta reassembly, trailing usage chunk,
+//!   `[DONE]` sentinel, and errors before the stream starts
+//! - Structured output: `response_format` is sent and an invalid reply is
+//!   retried once
+//! - Azure OpenAI: deployment URL, `api-version` query and `api-key` header
 
 use std::collections::HashMap;
 
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{body_partial_json, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::config::{AzureConfig, LlmProviderConfig};
 use clawft_llm::error::ProviderError;
 use clawft_llm::openai_compat::OpenAiCompatProvider;
 use clawft_llm::provider::Provider;
-use clawft_llm::types::{ChatMessage, ChatRequest};
+use clawft_llm::structured::complete_structured;
+use clawft_llm::types::{ChatMessage, ChatRequest, ResponseFormat, StreamChunk};
 
 /// Build a `ProviderConfig` pointing at the given mock server URL.
 fn mock_config(server_url: &str) -> LlmProviderConfig {
@@ -36,6 +42,11 @@ fn mock_config(server_url: &str) -> LlmProviderConfig {
         default_model: Some("test-model".into()),
         headers: HashMap::new(),
         timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
     }
 }
 
@@ -87,7 +98,7 @@ async fn complete_success_text_response() {
     assert_eq!(response.model, "test-model");
     assert_eq!(response.choices.len(), 1);
     assert_eq!(
-        response.choices[0].message.content.as_deref(),
+        response.choices[0].message.text().as_deref(),
         Some("Hello! How can I help you?")
     );
     assert_eq!(response.choices[0].message.role, "assistant");
@@ -478,6 +489,12 @@ async fn complete_sends_request_body_correctly() {
         ],
         max_tokens: Some(100),
         temperature: Some(0.5),
+        top_p: None,
+        stop: Vec::new(),
+        seed: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        logit_bias: HashMap::new(),
         tools: vec![serde_json::json!({
             "type": "function",
             "function": {
@@ -485,13 +502,63 @@ async fn complete_sends_request_body_correctly() {
                 "parameters": {"type": "object"}
             }
         })],
+        tool_choice: None,
         stream: None,
+        response_format: None,
+        cache: false,
+        cache_tools: false,
+        timeout: None,
+        reasoning_effort: None,
+        max_reasoning_tokens: None,
     };
 
     let response = provider.complete(&request).await.unwrap();
     assert_eq!(response.id, "body-check");
 }
 
+#[tokio::test]
+async fn complete_sends_sampling_controls_only_when_set() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(text_completion("ok")))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let config = mock_config(&server.uri());
+    let provider = OpenAiCompatProvider::with_api_key(config, "sk-key".into());
+
+    provider.complete(&test_request()).await.unwrap();
+    let constrained = test_request()
+        .with_stop(["\n"])
+        .with_seed(7)
+        .with_top_p(0.5)
+        .with_logit_bias("9906", 10.0);
+    provider.complete(&constrained).await.unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let unset: serde_json::Value = received[0].body_json().unwrap();
+    for field in [
+        "top_p",
+        "stop",
+        "seed",
+        "frequency_penalty",
+        "presence_penalty",
+        "logit_bias",
+    ] {
+        assert!(unset.get(field).is_none(), "{field} sent while unset");
+    }
+
+    let set: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(set["stop"], serde_json::json!(["\n"]));
+    assert_eq!(set["seed"], 7);
+    assert_eq!(set["top_p"], 0.5);
+    assert_eq!(set["logit_bias"], serde_json::json!({"9906": 10.0}));
+    assert!(set.get("frequency_penalty").is_none());
+}
+
 // ── Edge cases ─────────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -504,6 +571,11 @@ async fn complete_missing_api_key_returns_not_configured() {
         default_model: None,
         headers: HashMap::new(),
         timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
     };
     let provider = OpenAiCompatProvider::new(config);
 
@@ -552,8 +624,14 @@ async fn complete_multiple_choices() {
 
     let response = provider.complete(&test_request()).await.unwrap();
     assert_eq!(response.choices.len(), 2);
-    assert_eq!(response.choices[0].message.content.as_deref(), Some("Choice A"));
-    assert_eq!(response.choices[1].message.content.as_deref(), Some("Choice B"));
+    assert_eq!(
+        response.choices[0].message.text().as_deref(),
+        Some("Choice A")
+    );
+    assert_eq!(
+        response.choices[1].message.text().as_deref(),
+        Some("Choice B")
+    );
     assert_eq!(response.choices[0].index, 0);
     assert_eq!(response.choices[1].index, 1);
 }
@@ -589,5 +667,281 @@ async fn complete_usage_without_prompt_finish_reason_null() {
 
     let response = provider.complete(&test_request()).await.unwrap();
     assert!(response.choices[0].finish_reason.is_none());
-    assert_eq!(response.choices[0].message.content.as_deref(), Some("partial response"));
+    assert_eq!(
+        response.choices[0].message.text().as_deref(),
+        Some("partial response")
+    );
+}
+
+// ── Streaming ──────────────────────────────────────────────────────────
+
+/// Serve `lines` as an SSE body, one `data:` event per line.
+async fn mount_sse(server: &MockServer, lines: &[&str]) {
+    let body: String = lines.iter().map(|l| format!("data: {l}\n\n")).collect();
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Accept", "text/event-stream"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn streaming_text_with_trailing_usage() {
+    let server = MockServer::start().await;
+    mount_sse(
+        &server,
+        &[
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":"Hello"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":", wörld"}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":9,"completion_tokens":4,"total_tokens":13}}"#,
+            "[DONE]",
+        ],
+    )
+    .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let mut texts = Vec::new();
+    let response = provider
+        .complete_streaming(&test_request(), &mut |chunk| {
+            if let StreamChunk::TextDelta { text } = chunk {
+                texts.push(text.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(texts, vec!["Hello", ", wörld"]);
+    assert_eq!(response.model, "test-model");
+    let choice = &response.choices[0];
+    assert_eq!(choice.message.role, "assistant");
+    assert_eq!(choice.message.text().as_deref(), Some("Hello, wörld"));
+    assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+    let usage = response.usage.unwrap();
+    assert_eq!(usage.input_tokens, 9);
+    assert_eq!(usage.output_tokens, 4);
+    assert_eq!(usage.total_tokens, 13);
+}
+
+#[tokio::test]
+async fn streaming_reassembles_tool_call_deltas() {
+    let server = MockServer::start().await;
+    mount_sse(
+        &server,
+        &[
+            r#"{"choices":[{"index":0,"delta":{"content":"Let me check."}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"read_file","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"pa"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":1,"id":"call_2","type":"function","function":{"name":"list_directory","arguments":"{}"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"th\":\"src/lib.rs\"}"}}]}}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":20,"completion_tokens":11,"total_tokens":31}}"#,
+            "[DONE]",
+        ],
+    )
+    .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let mut tool_deltas = 0;
+    let response = provider
+        .complete_streaming(&test_request(), &mut |chunk| {
+            if matches!(chunk, StreamChunk::ToolCallDelta { .. }) {
+                tool_deltas += 1;
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(tool_deltas, 4);
+    let choice = &response.choices[0];
+    assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(choice.message.text().as_deref(), Some("Let me check."));
+    let calls = choice.message.tool_calls.as_ref().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].id, "call_1");
+    assert_eq!(calls[0].call_type, "function");
+    assert_eq!(calls[0].function.name, "read_file");
+    assert_eq!(calls[0].function.arguments, r#"{"path":"src/lib.rs"}"#);
+    assert_eq!(calls[1].id, "call_2");
+    assert_eq!(calls[1].function.name, "list_directory");
+    assert_eq!(calls[1].function.arguments, "{}");
+    assert_eq!(response.usage.unwrap().total_tokens, 31);
+}
+
+#[tokio::test]
+async fn streaming_stops_at_done_sentinel() {
+    let server = MockServer::start().await;
+    mount_sse(
+        &server,
+        &[
+            r#"{"choices":[{"index":0,"delta":{"content":"kept"}}]}"#,
+            "[DONE]",
+            r#"{"choices":[{"index":0,"delta":{"content":" ignored"}}]}"#,
+        ],
+    )
+    .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    provider.complete_stream(&test_request(), tx).await.unwrap();
+
+    let mut chunks = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(
+        chunks,
+        vec![
+            StreamChunk::TextDelta {
+                text: "kept".into()
+            },
+            StreamChunk::Done {
+                finish_reason: None,
+                usage: None
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn streaming_error_status_is_returned_before_any_chunk() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("overloaded"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let mut seen = 0;
+    let err = provider
+        .complete_streaming(&test_request(), &mut |_| seen += 1)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        ProviderError::ServerError { status: 503, .. }
+    ));
+    assert_eq!(seen, 0);
+}
+
+// ── Structured output ──────────────────────────────────────────────────
+
+fn text_completion(content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "chatcmpl-structured",
+        "model": "test-model",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": content},
+            "finish_reason": "stop"
+        }]
+    })
+}
+
+#[tokio::test]
+async fn structured_output_sends_response_format_and_retries_invalid_json() {
+    #[derive(serde::Deserialize)]
+    struct City {
+        name: String,
+        population: u64,
+    }
+
+    let server = MockServer::start().await;
+    let format = serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {"name": "city", "strict": true}
+    });
+    // The first reply is missing a required field; the retry is valid.
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(
+            serde_json::json!({"response_format": format}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(text_completion(r#"{"name":"Oslo"}"#)),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(
+            serde_json::json!({"response_format": format}),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(text_completion(r#"{"name":"Oslo","population":709000}"#)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let mut request = test_request();
+    request.response_format = Some(ResponseFormat::json_schema(
+        "city",
+        serde_json::json!({
+            "type": "object",
+            "properties": {"name": {"type": "string"}, "population": {"type": "integer"}},
+            "required": ["name", "population"],
+            "additionalProperties": false
+        }),
+    ));
+
+    let city: City = complete_structured(&provider, &request).await.unwrap();
+    assert_eq!(city.name, "Oslo");
+    assert_eq!(city.population, 709_000);
+}
+
+// ── Azure OpenAI ───────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn azure_complete_targets_deployment_with_api_key_header() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/openai/deployments/prod-gpt4o/chat/completions"))
+        .and(query_param("api-version", "2024-10-21"))
+        .and(header("api-key", "az-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "chatcmpl-azure",
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi from Azure"},
+                "finish_reason": "stop"
+            }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = mock_config(&server.uri());
+    config.name = "azure".into();
+    config.azure = Some(AzureConfig {
+        deployments: HashMap::from([("gpt-4o".into(), "prod-gpt4o".into())]),
+        api_version: "2024-10-21".into(),
+    });
+    let provider = OpenAiCompatProvider::with_api_key(config, "az-key".into());
+
+    let request = ChatRequest::new("gpt-4o", vec![ChatMessage::user("Hello")]);
+    let response = provider.complete(&request).await.unwrap();
+    assert_eq!(
+        response.choices[0].message.text().as_deref(),
+        Some("Hi from Azure")
+    );
+
+    let received = server.received_requests().await.unwrap();
+    assert!(received[0].headers.get("authorization").is_none());
 }
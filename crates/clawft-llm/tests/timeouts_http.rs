@@ -0,0 +1,202 @@
+//! Timeout tests for `OpenAiCompatProvider` against slow servers.
+//!
+//! Uses [`wiremock`] response delays for the overall deadline, and a raw
+//! TCP server that stops mid-stream for the streaming idle timeout.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use clawft_llm::config::LlmProviderConfig;
+use clawft_llm::error::ProviderError;
+use clawft_llm::openai_compat::OpenAiCompatProvider;
+use clawft_llm::provider::Provider;
+use clawft_llm::retry::{RetryConfig, RetryPolicy};
+use clawft_llm::types::{ChatMessage, ChatRequest, StreamChunk};
+
+fn mock_config(server_url: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        name: "slow-provider".into(),
+        base_url: server_url.into(),
+        api_key_env: "MOCK_UNUSED_KEY".into(),
+        model_prefix: None,
+        default_model: Some("test-model".into()),
+        headers: HashMap::new(),
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        reasoning_effort: None,
+        rate_limit: Default::default(),
+        azure: None,
+    }
+}
+
+fn request_with_deadline(deadline: Duration) -> ChatRequest {
+    let mut request = ChatRequest::new("test-model", vec![ChatMessage::user("Hello")]);
+    request.timeout = Some(deadline);
+    request
+}
+
+fn completion() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "id": "chatcmpl-slow",
+        "model": "test-model",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "finally"},
+            "finish_reason": "stop"
+        }]
+    }))
+}
+
+#[tokio::test]
+async fn request_deadline_expires_as_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(completion().set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let started = Instant::now();
+    let err = provider
+        .complete(&request_with_deadline(Duration::from_millis(200)))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ProviderError::Timeout), "got: {err}");
+    assert!(started.elapsed() < Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn configured_deadline_applies_without_override() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(completion().set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let config = LlmProviderConfig {
+        timeout_secs: Some(1),
+        ..mock_config(&server.uri())
+    };
+    let provider = OpenAiCompatProvider::with_api_key(config, "sk".into());
+    let request = ChatRequest::new("test-model", vec![ChatMessage::user("Hello")]);
+    let err = provider.complete(&request).await.unwrap_err();
+    assert!(matches!(err, ProviderError::Timeout), "got: {err}");
+}
+
+#[tokio::test]
+async fn streaming_deadline_expires_as_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string("data: [DONE]\n\n")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+
+    let provider = OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into());
+    let (tx, _rx) = mpsc::channel(8);
+    let err = provider
+        .complete_stream(&request_with_deadline(Duration::from_millis(200)), tx)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::Timeout), "got: {err}");
+}
+
+/// Serve one streaming response that sends a single chunk and then stalls
+/// without closing the connection.
+async fn stalling_stream_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 16 * 1024];
+        let _ = socket.read(&mut buf).await;
+
+        let event = "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hel\"}}]}\n\n";
+        let head = "HTTP/1.1 200 OK\r\n\
+                    content-type: text/event-stream\r\n\
+                    transfer-encoding: chunked\r\n\r\n";
+        let chunk = format!("{:x}\r\n{event}\r\n", event.len());
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(chunk.as_bytes()).await.unwrap();
+        socket.flush().await.unwrap();
+
+        // Hold the connection open well past the idle timeout.
+        tokio::time::sleep(Duration::from_secs(30)).await;
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn stalled_stream_hits_idle_timeout() {
+    let base_url = stalling_stream_server().await;
+    let config = LlmProviderConfig {
+        read_timeout_secs: Some(1),
+        reasoning_effort: None,
+        ..mock_config(&base_url)
+    };
+    let provider = OpenAiCompatProvider::with_api_key(config, "sk".into());
+
+    let (tx, mut rx) = mpsc::channel(8);
+    let request = ChatRequest::new("test-model", vec![ChatMessage::user("Hello")]);
+    let started = Instant::now();
+    let err = provider.complete_stream(&request, tx).await.unwrap_err();
+
+    assert!(matches!(err, ProviderError::Timeout), "got: {err}");
+    // Well before the 120s overall deadline.
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(
+        rx.recv().await,
+        Some(StreamChunk::TextDelta { text: "Hel".into() })
+    );
+}
+
+#[tokio::test]
+async fn retry_policy_retries_timeouts() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(completion().set_delay(Duration::from_secs(5)))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(completion())
+        .mount(&server)
+        .await;
+
+    let provider = RetryPolicy::new(
+        OpenAiCompatProvider::with_api_key(mock_config(&server.uri()), "sk".into()),
+        RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            jitter_fraction: 0.0,
+        },
+    );
+    let response = provider
+        .complete(&request_with_deadline(Duration::from_millis(300)))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.choices[0].message.text().as_deref(),
+        Some("finally")
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
@@ -0,0 +1,730 @@
+//! Safe archive extraction and creation (zip and tar.gz).
+//!
+//! [`extract`] unpacks an archive into a destination directory while
+//! enforcing [`ExtractLimits`] and refusing entries that could escape the
+//! destination: absolute paths, `..` components ("zip-slip"), and links.
+//! Sizes are checked against the bytes actually decompressed, not the sizes
+//! an archive claims in its headers, so decompression bombs stop at the
+//! limit. [`create`] builds an archive from files and directories, and
+//! [`create_filtered`] does so leaving out paths matched by ignore rules.
+//!
+//! These functions do blocking I/O; call them from `spawn_blocking` when
+//! running on an async runtime.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::fs::walk::IgnoreRules;
+
+/// Archive formats understood by this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// A `.zip` archive.
+    Zip,
+    /// A gzip-compressed tarball (`.tar.gz` or `.tgz`).
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Detect the format from a file name's extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+
+    /// Strip this format's extension from a file name (`skill.tar.gz` -> `skill`).
+    pub fn strip_extension(name: &str) -> &str {
+        let lower = name.to_ascii_lowercase();
+        for ext in [".tar.gz", ".tgz", ".zip"] {
+            if lower.ends_with(ext) {
+                return &name[..name.len() - ext.len()];
+            }
+        }
+        name
+    }
+}
+
+/// Limits applied while extracting an archive.
+#[derive(Debug, Clone)]
+pub struct ExtractLimits {
+    /// Maximum number of entries (files and directories).
+    pub max_entries: usize,
+    /// Maximum decompressed size of any single file, in bytes.
+    pub max_file_size: u64,
+    /// Maximum decompressed size of all files combined, in bytes.
+    pub max_total_size: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_file_size: 100 * 1024 * 1024,
+            max_total_size: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// What [`extract`] wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractSummary {
+    /// Regular files written.
+    pub files: usize,
+    /// Directories created.
+    pub dirs: usize,
+    /// Total decompressed bytes written.
+    pub bytes: u64,
+}
+
+/// Errors from archive extraction or creation.
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    /// The file extension is not a supported archive format.
+    #[error("unsupported archive format: {0}")]
+    UnsupportedFormat(PathBuf),
+
+    /// An entry's path is absolute or escapes the destination.
+    #[error("unsafe path in archive: {0}")]
+    UnsafePath(String),
+
+    /// An entry is a link, device or other non-file, non-directory type.
+    #[error("unsupported entry type in archive: {0}")]
+    UnsupportedEntry(String),
+
+    /// The archive has more entries than allowed.
+    #[error("archive has more than {limit} entries")]
+    TooManyEntries {
+        /// The configured entry limit.
+        limit: usize,
+    },
+
+    /// A single file decompresses to more than allowed.
+    #[error("archive entry {path} exceeds {limit} bytes")]
+    FileTooLarge {
+        /// The offending entry.
+        path: String,
+        /// The configured per-file limit.
+        limit: u64,
+    },
+
+    /// All files together decompress to more than allowed.
+    #[error("archive contents exceed {limit} bytes")]
+    TotalTooLarge {
+        /// The configured total limit.
+        limit: u64,
+    },
+
+    /// The zip container is malformed.
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// Reading or writing failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Extract `archive_path` into `dest`, creating `dest` if needed.
+///
+/// The format is chosen from the file extension. On error, `dest` may hold
+/// a partial extraction; callers that need all-or-nothing behaviour should
+/// extract into a staging directory and move it into place on success.
+pub fn extract(
+    archive_path: &Path,
+    dest: &Path,
+    limits: &ExtractLimits,
+) -> Result<ExtractSummary, ArchiveError> {
+    let format = ArchiveFormat::from_path(archive_path)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(archive_path.to_path_buf()))?;
+    std::fs::create_dir_all(dest)?;
+    let file = File::open(archive_path)?;
+    let mut sink = Sink {
+        dest,
+        limits,
+        summary: ExtractSummary::default(),
+    };
+    match format {
+        ArchiveFormat::Zip => extract_zip(file, &mut sink)?,
+        ArchiveFormat::TarGz => extract_tar_gz(file, &mut sink)?,
+    }
+    Ok(sink.summary)
+}
+
+/// Create an archive at `dest_archive` containing `paths`.
+///
+/// Each path is stored under its own file name, so `create("a.zip",
+/// ["/x/skill"])` yields entries `skill/...`. Directories are added
+/// recursively; symlinks inside them are skipped. The format is chosen from
+/// the extension of `dest_archive`. Returns the number of files added.
+pub fn create(dest_archive: &Path, paths: &[PathBuf]) -> Result<usize, ArchiveError> {
+    let format = ArchiveFormat::from_path(dest_archive)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(dest_archive.to_path_buf()))?;
+    let entries = collect_entries(paths, None, None)?;
+    write_archive(dest_archive, format, &entries)
+}
+
+/// Create an archive at `dest_archive` containing `paths`, named by their
+/// path relative to `base`, leaving out entries `ignore` matches.
+///
+/// `ignore` is matched against those relative names (see
+/// [`IgnoreRules::is_ignored`]); an ignored directory is left out with
+/// everything below it. Every path must lie under `base`. Returns the
+/// number of files added.
+pub fn create_filtered(
+    dest_archive: &Path,
+    base: &Path,
+    paths: &[PathBuf],
+    ignore: &IgnoreRules,
+) -> Result<usize, ArchiveError> {
+    let format = ArchiveFormat::from_path(dest_archive)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(dest_archive.to_path_buf()))?;
+    let entries = collect_entries(paths, Some(base), Some(ignore))?;
+    write_archive(dest_archive, format, &entries)
+}
+
+fn write_archive(
+    dest_archive: &Path,
+    format: ArchiveFormat,
+    entries: &[SourceEntry],
+) -> Result<usize, ArchiveError> {
+    let file = File::create(dest_archive)?;
+    match format {
+        ArchiveFormat::Zip => create_zip(file, entries),
+        ArchiveFormat::TarGz => create_tar_gz(file, entries),
+    }
+}
+
+/// Validate an entry name and turn it into a path relative to the
+/// destination. Returns `None` for names that refer to the root itself.
+fn safe_relative(name: &str) -> Result<Option<PathBuf>, ArchiveError> {
+    let unsafe_path = || ArchiveError::UnsafePath(name.to_string());
+    // Archives made on Windows may use backslashes as separators.
+    let normalized = name.replace('\\', "/");
+    // Drive letters ("C:/...") are absolute on Windows even though they
+    // parse as a normal component elsewhere.
+    if normalized.as_bytes().get(1) == Some(&b':') {
+        return Err(unsafe_path());
+    }
+    let mut out = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(unsafe_path());
+            }
+        }
+    }
+    Ok((!out.as_os_str().is_empty()).then_some(out))
+}
+
+/// Shared extraction state: where to write and how much has been written.
+struct Sink<'a> {
+    dest: &'a Path,
+    limits: &'a ExtractLimits,
+    summary: ExtractSummary,
+}
+
+impl Sink<'_> {
+    fn check_entry_count(&self, seen: usize) -> Result<(), ArchiveError> {
+        if seen > self.limits.max_entries {
+            return Err(ArchiveError::TooManyEntries {
+                limit: self.limits.max_entries,
+            });
+        }
+        Ok(())
+    }
+
+    fn dir(&mut self, name: &str) -> Result<(), ArchiveError> {
+        if let Some(rel) = safe_relative(name)? {
+            std::fs::create_dir_all(self.dest.join(rel))?;
+            self.summary.dirs += 1;
+        }
+        Ok(())
+    }
+
+    fn file(
+        &mut self,
+        name: &str,
+        reader: &mut dyn Read,
+        mode: Option<u32>,
+    ) -> Result<(), ArchiveError> {
+        let rel = safe_relative(name)?.ok_or_else(|| ArchiveError::UnsafePath(name.to_string()))?;
+        let path = self.dest.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let remaining_total = self
+            .limits
+            .max_total_size
+            .saturating_sub(self.summary.bytes);
+        let cap = self.limits.max_file_size.min(remaining_total);
+        let mut out = File::create(&path)?;
+        // Read one byte past the cap so overflow is detectable.
+        let written = io::copy(&mut reader.take(cap.saturating_add(1)), &mut out)?;
+        if written > cap {
+            drop(out);
+            let _ = std::fs::remove_file(&path);
+            return Err(if written > self.limits.max_file_size {
+                ArchiveError::FileTooLarge {
+                    path: name.to_string(),
+                    limit: self.limits.max_file_size,
+                }
+            } else {
+                ArchiveError::TotalTooLarge {
+                    limit: self.limits.max_total_size,
+                }
+            });
+        }
+        out.flush()?;
+        set_executable(&path, mode)?;
+
+        self.summary.files += 1;
+        self.summary.bytes += written;
+        Ok(())
+    }
+}
+
+/// Keep the executable bit from the archive so bundled scripts still run.
+#[cfg(unix)]
+fn set_executable(path: &Path, mode: Option<u32>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    if mode.is_some_and(|m| m & 0o111 != 0) {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _mode: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
+
+fn extract_zip(file: File, sink: &mut Sink<'_>) -> Result<(), ArchiveError> {
+    let mut archive = zip::ZipArchive::new(io::BufReader::new(file))?;
+    sink.check_entry_count(archive.len())?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let name = entry.name().to_string();
+        if entry.is_symlink() {
+            return Err(ArchiveError::UnsupportedEntry(name));
+        }
+        if entry.is_dir() {
+            sink.dir(&name)?;
+        } else {
+            let mode = entry.unix_mode();
+            sink.file(&name, &mut entry, mode)?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_tar_gz(file: File, sink: &mut Sink<'_>) -> Result<(), ArchiveError> {
+    let decoder = flate2::read::GzDecoder::new(io::BufReader::new(file));
+    let mut archive = tar::Archive::new(decoder);
+    for (index, entry) in archive.entries()?.enumerate() {
+        sink.check_entry_count(index + 1)?;
+        let mut entry = entry?;
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => sink.dir(&name)?,
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                let mode = entry.header().mode().ok();
+                sink.file(&name, &mut entry, mode)?;
+            }
+            _ => return Err(ArchiveError::UnsupportedEntry(name)),
+        }
+    }
+    Ok(())
+}
+
+/// A file or directory to archive, with its name inside the archive.
+struct SourceEntry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+}
+
+/// Walk `paths`, naming entries relative to `base` (each path's parent
+/// when `None`) and skipping those `ignore` matches.
+fn collect_entries(
+    paths: &[PathBuf],
+    base: Option<&Path>,
+    ignore: Option<&IgnoreRules>,
+) -> Result<Vec<SourceEntry>, ArchiveError> {
+    let mut entries = Vec::new();
+    for root in paths {
+        let base = base.unwrap_or_else(|| root.parent().unwrap_or(Path::new("")));
+        let name_of = |path: &Path| -> Result<String, ArchiveError> {
+            let rel = path
+                .strip_prefix(base)
+                .map_err(|_| ArchiveError::UnsafePath(path.display().to_string()))?;
+            Ok(rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"))
+        };
+        let walk = walkdir::WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|item| match (ignore, name_of(item.path())) {
+                (Some(ignore), Ok(name)) => !ignore.is_ignored(&name, item.file_type().is_dir()),
+                _ => true,
+            });
+        for item in walk {
+            let item = item.map_err(io::Error::other)?;
+            let file_type = item.file_type();
+            if file_type.is_symlink() {
+                continue;
+            }
+            let name = name_of(item.path())?;
+            entries.push(SourceEntry {
+                path: item.path().to_path_buf(),
+                name,
+                is_dir: file_type.is_dir(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> io::Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> io::Result<u32> {
+    Ok(0o644)
+}
+
+fn create_zip(file: File, entries: &[SourceEntry]) -> Result<usize, ArchiveError> {
+    use zip::write::SimpleFileOptions;
+
+    let mut writer = zip::ZipWriter::new(io::BufWriter::new(file));
+    let mut files = 0;
+    for entry in entries {
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .unix_permissions(file_mode(&entry.path)?);
+        if entry.is_dir {
+            writer.add_directory(format!("{}/", entry.name), options)?;
+        } else {
+            writer.start_file(entry.name.as_str(), options)?;
+            io::copy(&mut File::open(&entry.path)?, &mut writer)?;
+            files += 1;
+        }
+    }
+    writer.finish()?.flush()?;
+    Ok(files)
+}
+
+fn create_tar_gz(file: File, entries: &[SourceEntry]) -> Result<usize, ArchiveError> {
+    let encoder =
+        flate2::write::GzEncoder::new(io::BufWriter::new(file), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    let mut files = 0;
+    for entry in entries {
+        if entry.is_dir {
+            builder.append_dir(&entry.name, &entry.path)?;
+        } else {
+            builder.append_path_with_name(&entry.path, &entry.name)?;
+            files += 1;
+        }
+    }
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir =
+            std::env::temp_dir().join(format!("clawft-archive-{tag}-{}-{n}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a zip whose entries are `(name, contents)` exactly as given.
+    fn raw_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, data) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    /// Write a tar.gz with a raw header name, bypassing the builder's own
+    /// path validation so malicious names can be crafted.
+    fn raw_tar_gz(path: &Path, entries: &[(&str, tar::EntryType, &[u8])]) {
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (name, kind, data) in entries {
+            let mut header = tar::Header::new_old();
+            let field = &mut header.as_old_mut().name;
+            field[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*kind);
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn sample_tree(root: &Path) -> PathBuf {
+        let skill = root.join("my-skill");
+        std::fs::create_dir_all(skill.join("scripts")).unwrap();
+        std::fs::write(skill.join("SKILL.md"), "# skill\n").unwrap();
+        std::fs::write(skill.join("scripts/run.sh"), "#!/bin/sh\necho hi\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                skill.join("scripts/run.sh"),
+                std::fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+        skill
+    }
+
+    #[test]
+    fn format_detection() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("a.zip")),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("a.TAR.GZ")),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("a.tgz")),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(ArchiveFormat::from_path(Path::new("a.tar")), None);
+        assert_eq!(ArchiveFormat::strip_extension("skill.tar.gz"), "skill");
+        assert_eq!(ArchiveFormat::strip_extension("skill.zip"), "skill");
+        assert_eq!(ArchiveFormat::strip_extension("skill"), "skill");
+    }
+
+    #[test]
+    fn safe_relative_rejects_escapes() {
+        assert_eq!(safe_relative("a/./b").unwrap(), Some(PathBuf::from("a/b")));
+        assert_eq!(safe_relative("./").unwrap(), None);
+        for bad in ["../x", "a/../../x", "/etc/passwd", "..\\x", "C:/x", "c:\\x"] {
+            assert!(
+                matches!(safe_relative(bad), Err(ArchiveError::UnsafePath(_))),
+                "{bad} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_both_formats() {
+        for ext in ["zip", "tar.gz"] {
+            let work = temp_dir("roundtrip");
+            let skill = sample_tree(&work);
+            let archive = work.join(format!("bundle.{ext}"));
+
+            assert_eq!(create(&archive, &[skill]).unwrap(), 2);
+
+            let out = work.join("out");
+            let summary = extract(&archive, &out, &ExtractLimits::default()).unwrap();
+            assert_eq!(summary.files, 2, "{ext}");
+            assert_eq!(
+                std::fs::read_to_string(out.join("my-skill/SKILL.md")).unwrap(),
+                "# skill\n"
+            );
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = std::fs::metadata(out.join("my-skill/scripts/run.sh"))
+                    .unwrap()
+                    .permissions()
+                    .mode();
+                assert!(mode & 0o100 != 0, "{ext}: executable bit lost");
+            }
+            let _ = std::fs::remove_dir_all(&work);
+        }
+    }
+
+    #[test]
+    fn zip_slip_is_rejected() {
+        let work = temp_dir("zipslip");
+        let archive = work.join("evil.zip");
+        raw_zip(&archive, &[("ok.txt", b"fine"), ("../evil.txt", b"pwned")]);
+
+        let out = work.join("out");
+        let err = extract(&archive, &out, &ExtractLimits::default()).unwrap_err();
+        assert!(matches!(err, ArchiveError::UnsafePath(ref p) if p == "../evil.txt"));
+        assert!(!work.join("evil.txt").exists());
+
+        let archive = work.join("abs.zip");
+        raw_zip(&archive, &[("/tmp/clawft-abs-evil.txt", b"pwned")]);
+        assert!(matches!(
+            extract(&archive, &out, &ExtractLimits::default()),
+            Err(ArchiveError::UnsafePath(_))
+        ));
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn tar_traversal_and_links_are_rejected() {
+        let work = temp_dir("tarslip");
+        let out = work.join("out");
+
+        let archive = work.join("evil.tar.gz");
+        raw_tar_gz(
+            &archive,
+            &[("../evil.txt", tar::EntryType::Regular, b"pwned")],
+        );
+        assert!(matches!(
+            extract(&archive, &out, &ExtractLimits::default()),
+            Err(ArchiveError::UnsafePath(_))
+        ));
+        assert!(!work.join("evil.txt").exists());
+
+        let archive = work.join("link.tar.gz");
+        raw_tar_gz(&archive, &[("link", tar::EntryType::Symlink, b"")]);
+        assert!(matches!(
+            extract(&archive, &out, &ExtractLimits::default()),
+            Err(ArchiveError::UnsupportedEntry(_))
+        ));
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn decompression_bomb_hits_file_limit() {
+        let work = temp_dir("bomb");
+        let archive = work.join("bomb.zip");
+        // 8 MiB of zeros compresses to a few KiB.
+        let zeros = vec![0u8; 8 * 1024 * 1024];
+        raw_zip(&archive, &[("zeros.bin", &zeros)]);
+        assert!(std::fs::metadata(&archive).unwrap().len() < 100 * 1024);
+
+        let limits = ExtractLimits {
+            max_file_size: 1024 * 1024,
+            ..ExtractLimits::default()
+        };
+        let out = work.join("out");
+        let err = extract(&archive, &out, &limits).unwrap_err();
+        assert!(matches!(err, ArchiveError::FileTooLarge { limit, .. } if limit == 1024 * 1024));
+        // The oversized file is not left behind.
+        assert!(!out.join("zeros.bin").exists());
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn total_size_and_entry_count_limits() {
+        let work = temp_dir("limits");
+        let out = work.join("out");
+        let chunk = vec![b'x'; 600];
+
+        let archive = work.join("many.tar.gz");
+        raw_tar_gz(
+            &archive,
+            &[
+                ("a", tar::EntryType::Regular, &chunk),
+                ("b", tar::EntryType::Regular, &chunk),
+            ],
+        );
+        let limits = ExtractLimits {
+            max_total_size: 1000,
+            ..ExtractLimits::default()
+        };
+        assert!(matches!(
+            extract(&archive, &out, &limits),
+            Err(ArchiveError::TotalTooLarge { limit: 1000 })
+        ));
+
+        let limits = ExtractLimits {
+            max_entries: 1,
+            ..ExtractLimits::default()
+        };
+        assert!(matches!(
+            extract(&archive, &out, &limits),
+            Err(ArchiveError::TooManyEntries { limit: 1 })
+        ));
+
+        let archive = work.join("many.zip");
+        raw_zip(&archive, &[("a", b"1"), ("b", b"2")]);
+        assert!(matches!(
+            extract(&archive, &out, &limits),
+            Err(ArchiveError::TooManyEntries { limit: 1 })
+        ));
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn create_filtered_names_entries_from_base_and_skips_ignored() {
+        let work = temp_dir("filtered");
+        let home = work.join("home");
+        std::fs::create_dir_all(home.join("workspace/memory")).unwrap();
+        std::fs::create_dir_all(home.join("skills/notes/keys")).unwrap();
+        std::fs::write(home.join("workspace/memory/MEMORY.md"), "facts").unwrap();
+        std::fs::write(home.join("skills/notes/SKILL.md"), "skill").unwrap();
+        std::fs::write(home.join("skills/notes/.env"), "TOKEN=x").unwrap();
+        std::fs::write(home.join("skills/notes/keys/id.pem"), "key").unwrap();
+
+        let archive = work.join("backup.tar.gz");
+        let ignore = IgnoreRules::new(&[".env", "keys/"]);
+        let files = create_filtered(
+            &archive,
+            &home,
+            &[home.join("workspace/memory"), home.join("skills")],
+            &ignore,
+        )
+        .unwrap();
+        assert_eq!(files, 2);
+
+        let out = work.join("out");
+        extract(&archive, &out, &ExtractLimits::default()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(out.join("workspace/memory/MEMORY.md")).unwrap(),
+            "facts"
+        );
+        assert!(out.join("skills/notes/SKILL.md").exists());
+        assert!(!out.join("skills/notes/.env").exists());
+        assert!(!out.join("skills/notes/keys").exists());
+        let _ = std::fs::remove_dir_all(&work);
+    }
+
+    #[test]
+    fn unsupported_extension_is_rejected() {
+        let work = temp_dir("ext");
+        let path = work.join("thing.rar");
+        std::fs::write(&path, b"").unwrap();
+        assert!(matches!(
+            extract(&path, &work, &ExtractLimits::default()),
+            Err(ArchiveError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            create(&path, &[]),
+            Err(ArchiveError::UnsupportedFormat(_))
+        ));
+        let _ = std::fs::remove_dir_all(&work);
+    }
+}
@@ -13,7 +13,7 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Headers, Request, RequestInit, RequestMode, Response};
 
-use crate::http::{HttpClient, HttpResponse};
+use crate::http::{HttpClient, HttpResponse, NO_REDIRECT_HEADER};
 
 /// HTTP client for browser/WASM targets using the fetch API.
 pub struct BrowserHttpClient;
@@ -64,9 +64,13 @@ impl HttpClient for BrowserHttpClient {
         // Build the Request object.
         let request = Request::new_with_str_and_init(url, &opts).map_err(js_err)?;
 
-        // Set headers on the request.
+        // Set headers on the request. `fetch` always follows redirects,
+        // so the no-redirect marker is dropped rather than sent.
         let req_headers: Headers = request.headers();
         for (key, value) in headers {
+            if key.eq_ignore_ascii_case(NO_REDIRECT_HEADER) {
+                continue;
+            }
             req_headers.set(key, value).map_err(js_err)?;
         }
 
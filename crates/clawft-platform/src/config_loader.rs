@@ -10,6 +10,11 @@
 //!
 //! JSON keys are normalized from camelCase to snake_case before returning,
 //! matching the Python behavior where Pydantic models use snake_case field names.
+//!
+//! Secrets can also live in `~/.clawft/.env`. [`load_dotenv`] parses that
+//! file into a [`LayeredEnvironment`](super::env::LayeredEnvironment) so the
+//! values are visible through the platform environment without ever being
+//! written to the process environment.
 
 use std::path::PathBuf;
 
@@ -236,11 +241,232 @@ pub fn camel_to_snake(name: &str) -> String {
     result
 }
 
+/// A malformed line in a `.env` file.
+#[derive(Debug, thiserror::Error)]
+#[error(".env line {line}: {message}")]
+pub struct DotenvError {
+    /// 1-based line number.
+    pub line: usize,
+    /// What was wrong with the line.
+    pub message: String,
+}
+
+/// Location of the user's dotenv file: `~/.clawft/.env`.
+pub fn dotenv_path(home_dir: Option<PathBuf>) -> Option<PathBuf> {
+    home_dir.map(|home| home.join(".clawft").join(".env"))
+}
+
+/// Parse the contents of a `.env` file into `(key, value)` pairs.
+///
+/// Supported syntax, one assignment per line:
+/// - blank lines and lines starting with `#` are ignored;
+/// - an optional leading `export ` is accepted;
+/// - unquoted values are trimmed and end at ` #` (an inline comment);
+/// - `'single quoted'` values are taken literally;
+/// - `"double quoted"` values understand `\n`, `\r`, `\t`, `\"`, `\\`
+///   and `\$`; other backslashes are kept as-is.
+///
+/// Later assignments to the same key win.
+pub fn parse_dotenv(contents: &str) -> Result<Vec<(String, String)>, DotenvError> {
+    let mut vars = Vec::new();
+    for (idx, raw) in contents.lines().enumerate() {
+        let line = idx + 1;
+        let err = |message: &str| DotenvError {
+            line,
+            message: message.to_string(),
+        };
+
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let assignment = trimmed
+            .strip_prefix("export ")
+            .map(str::trim_start)
+            .unwrap_or(trimmed);
+        let (key, rest) = assignment
+            .split_once('=')
+            .ok_or_else(|| err("expected KEY=VALUE"))?;
+        let key = key.trim_end();
+        if !is_valid_env_key(key) {
+            return Err(err(&format!("invalid variable name '{key}'")));
+        }
+
+        let rest = rest.trim_start();
+        let (value, trailing) = match rest.chars().next() {
+            Some('\'') => {
+                let body = &rest[1..];
+                let end = body
+                    .find('\'')
+                    .ok_or_else(|| err("unterminated single quote"))?;
+                (body[..end].to_string(), &body[end + 1..])
+            }
+            Some('"') => {
+                let (value, consumed) = unescape_double_quoted(&rest[1..])
+                    .ok_or_else(|| err("unterminated double quote"))?;
+                (value, &rest[1 + consumed..])
+            }
+            _ => {
+                let value = match rest.find(" #").or_else(|| rest.find("\t#")) {
+                    Some(pos) => &rest[..pos],
+                    None => rest,
+                };
+                (value.trim_end().to_string(), "")
+            }
+        };
+
+        let trailing = trailing.trim_start();
+        if !trailing.is_empty() && !trailing.starts_with('#') {
+            return Err(err("unexpected characters after closing quote"));
+        }
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// Environment variable names: a letter or `_`, then letters, digits, `_` or `.`.
+fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Unescape a double-quoted value whose opening quote has been stripped.
+///
+/// Returns the value and the number of bytes consumed including the closing
+/// quote, or `None` if the quote is never closed.
+fn unescape_double_quoted(body: &str) -> Option<(String, usize)> {
+    let mut out = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, i + 1)),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, 'r')) => out.push('\r'),
+                Some((_, 't')) => out.push('\t'),
+                Some((_, c @ ('"' | '\\' | '$'))) => out.push(c),
+                Some((_, other)) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => return None,
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+/// Load a `.env` file into `layer` without touching the process environment.
+///
+/// Variables already set in the real environment (or earlier in the layer)
+/// are left alone, matching the usual dotenv convention. A missing file is
+/// not an error. Returns the number of variables applied.
+#[cfg(feature = "native")]
+pub fn load_dotenv<E: super::env::Environment>(
+    path: &std::path::Path,
+    layer: &super::env::LayeredEnvironment<E>,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    // Collapse duplicates first so the last assignment in the file wins.
+    let vars: std::collections::HashMap<String, String> =
+        parse_dotenv(&contents)?.into_iter().collect();
+    let applied = vars
+        .iter()
+        .filter(|(key, value)| layer.set_default(key, value))
+        .count();
+    tracing::debug!(path = %path.display(), applied, "loaded .env file");
+    Ok(applied)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    // ── dotenv tests ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_dotenv_basic_and_comments() {
+        let vars = parse_dotenv(
+            "# leading comment\n\nFOO=bar\n  export BAZ = qux  \nEMPTY=\nURL=http://x/?a=b#frag\nINLINE=value # note\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("BAZ".to_string(), "qux".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("URL".to_string(), "http://x/?a=b#frag".to_string()),
+                ("INLINE".to_string(), "value".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_quoting() {
+        let vars = parse_dotenv(concat!(
+            "SINGLE='keep \\n and $HOME # literally'\n",
+            "DOUBLE=\"line1\\nline2\\t\\\"q\\\" \\\\ \\$x \\z\"\n",
+            "HASH=\"a # b\" # trailing comment\n",
+            "SPACES='  padded  '\n",
+        ))
+        .unwrap();
+        assert_eq!(vars[0].1, "keep \\n and $HOME # literally");
+        assert_eq!(vars[1].1, "line1\nline2\t\"q\" \\ $x \\z");
+        assert_eq!(vars[2].1, "a # b");
+        assert_eq!(vars[3].1, "  padded  ");
+    }
+
+    #[test]
+    fn test_parse_dotenv_errors_report_line() {
+        let err = parse_dotenv("OK=1\nnot an assignment\n").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert_eq!(parse_dotenv("A='open").unwrap_err().line, 1);
+        assert_eq!(parse_dotenv("A=\"open\\\"").unwrap_err().line, 1);
+        assert_eq!(parse_dotenv("A='x' junk").unwrap_err().line, 1);
+        assert_eq!(parse_dotenv("1BAD=x").unwrap_err().line, 1);
+        assert_eq!(parse_dotenv("=x").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn test_load_dotenv_last_assignment_wins_and_respects_env() {
+        use crate::env::Environment;
+
+        let dir = std::env::temp_dir().join(format!("clawft-dotenv-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        std::fs::write(&path, "DUP=first\nDUP=second\nPATH=/dotenv/bin\n").unwrap();
+
+        let layer =
+            crate::env::LayeredEnvironment::new(MockEnv::new().with_var("PATH", "/usr/bin"));
+        let applied = load_dotenv(&path, &layer).unwrap();
+
+        // PATH is already set by the real environment, so only DUP applies.
+        assert_eq!(applied, 1);
+        assert_eq!(layer.get_var("DUP").as_deref(), Some("second"));
+        assert_eq!(layer.get_var("PATH").as_deref(), Some("/usr/bin"));
+
+        assert_eq!(load_dotenv(&dir.join("missing.env"), &layer).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dotenv_path_under_clawft_dir() {
+        assert_eq!(
+            dotenv_path(Some(PathBuf::from("/home/u"))),
+            Some(PathBuf::from("/home/u/.clawft/.env"))
+        );
+        assert_eq!(dotenv_path(None), None);
+    }
+
     // ── camel_to_snake tests ──────────────────────────────────────────
 
     #[test]
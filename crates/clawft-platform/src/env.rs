@@ -3,6 +3,15 @@
 //! Provides a platform-agnostic [`Environment`] trait for reading and writing
 //! environment variables. The native implementation delegates to [`std::env`].
 //! A WASM implementation could use a config map or browser-based storage.
+//!
+//! [`LayeredEnvironment`] stacks an in-memory override map on top of another
+//! environment. Tests and per-agent scopes use it to set variables without
+//! mutating the process-global environment.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use clawft_types::secret::SecretString;
 
 /// Platform-agnostic environment variable access.
 ///
@@ -19,6 +28,122 @@ pub trait Environment: Send + Sync {
 
     /// Remove (unset) an environment variable.
     fn remove_var(&self, name: &str);
+
+    /// Resolve a secret from its explicit value or the variable `env_var`
+    /// in this environment.
+    fn resolve_secret(&self, secret: &SecretString, env_var: Option<&str>) -> Option<SecretString> {
+        secret.resolve(env_var, |name| self.get_var(name))
+    }
+}
+
+impl<T: Environment + ?Sized> Environment for Arc<T> {
+    fn get_var(&self, name: &str) -> Option<String> {
+        (**self).get_var(name)
+    }
+
+    fn set_var(&self, name: &str, value: &str) {
+        (**self).set_var(name, value)
+    }
+
+    fn remove_var(&self, name: &str) {
+        (**self).remove_var(name)
+    }
+}
+
+/// An environment that resolves from an in-memory override map first and
+/// an underlying environment second.
+///
+/// [`set_var`](Environment::set_var) and [`remove_var`](Environment::remove_var)
+/// only touch the override map, so the base is never mutated. Removing a
+/// variable masks it in the base as well. Layers nest: wrap an
+/// `Arc<dyn Environment>` to scope overrides to one agent while sharing
+/// the parent.
+pub struct LayeredEnvironment<E> {
+    /// `None` marks a variable removed in this layer.
+    overrides: RwLock<HashMap<String, Option<String>>>,
+    base: E,
+}
+
+impl<E: Environment> LayeredEnvironment<E> {
+    /// Create an empty layer over `base`.
+    pub fn new(base: E) -> Self {
+        Self {
+            overrides: RwLock::new(HashMap::new()),
+            base,
+        }
+    }
+
+    /// Create a layer over `base` pre-populated with `vars`.
+    pub fn with_vars<I, K, V>(base: E, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let layer = Self::new(base);
+        {
+            let mut overrides = layer.overrides.write().expect("env layer lock poisoned");
+            for (k, v) in vars {
+                overrides.insert(k.into(), Some(v.into()));
+            }
+        }
+        layer
+    }
+
+    /// The environment this layer falls back to.
+    pub fn base(&self) -> &E {
+        &self.base
+    }
+
+    /// Set `name` in this layer only if it is not already visible through
+    /// the layer or the base.
+    ///
+    /// Returns `true` if the value was applied. Used for `.env` files,
+    /// which conventionally never override the real environment.
+    pub fn set_default(&self, name: &str, value: &str) -> bool {
+        let mut overrides = self.overrides.write().expect("env layer lock poisoned");
+        let visible = match overrides.get(name) {
+            Some(v) => v.is_some(),
+            None => self.base.get_var(name).is_some(),
+        };
+        if visible {
+            return false;
+        }
+        overrides.insert(name.to_string(), Some(value.to_string()));
+        true
+    }
+
+    /// Drop all overrides, exposing the base environment again.
+    pub fn clear(&self) {
+        self.overrides
+            .write()
+            .expect("env layer lock poisoned")
+            .clear();
+    }
+}
+
+impl<E: Environment> Environment for LayeredEnvironment<E> {
+    fn get_var(&self, name: &str) -> Option<String> {
+        let overrides = self.overrides.read().expect("env layer lock poisoned");
+        match overrides.get(name) {
+            Some(value) => value.clone(),
+            None => self.base.get_var(name),
+        }
+    }
+
+    fn set_var(&self, name: &str, value: &str) {
+        self.overrides
+            .write()
+            .expect("env layer lock poisoned")
+            .insert(name.to_string(), Some(value.to_string()));
+    }
+
+    fn remove_var(&self, name: &str) {
+        self.overrides
+            .write()
+            .expect("env layer lock poisoned")
+            .insert(name.to_string(), None);
+    }
 }
 
 /// Native environment implementation using [`std::env`].
@@ -55,6 +180,114 @@ mod tests {
     // Note: These tests mutate process-global environment variables, so they
     // use unique variable names to avoid interference with other tests.
 
+    /// Base environment backed by a fixed map, so layer tests never touch
+    /// the real process environment.
+    struct MapEnv(RwLock<HashMap<String, String>>);
+
+    impl MapEnv {
+        fn new(vars: &[(&str, &str)]) -> Self {
+            Self(RwLock::new(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ))
+        }
+    }
+
+    impl Environment for MapEnv {
+        fn get_var(&self, name: &str) -> Option<String> {
+            self.0.read().unwrap().get(name).cloned()
+        }
+
+        fn set_var(&self, name: &str, value: &str) {
+            self.0
+                .write()
+                .unwrap()
+                .insert(name.to_string(), value.to_string());
+        }
+
+        fn remove_var(&self, name: &str) {
+            self.0.write().unwrap().remove(name);
+        }
+    }
+
+    #[test]
+    fn test_layered_override_takes_precedence() {
+        let layer = LayeredEnvironment::with_vars(
+            MapEnv::new(&[("SHARED", "base"), ("BASE_ONLY", "b")]),
+            [("SHARED", "layer"), ("LAYER_ONLY", "l")],
+        );
+
+        assert_eq!(layer.get_var("SHARED").as_deref(), Some("layer"));
+        assert_eq!(layer.get_var("BASE_ONLY").as_deref(), Some("b"));
+        assert_eq!(layer.get_var("LAYER_ONLY").as_deref(), Some("l"));
+        assert!(layer.get_var("NOWHERE").is_none());
+    }
+
+    #[test]
+    fn test_layered_writes_do_not_touch_base() {
+        let layer = LayeredEnvironment::new(MapEnv::new(&[("KEY", "base")]));
+
+        layer.set_var("KEY", "scoped");
+        assert_eq!(layer.get_var("KEY").as_deref(), Some("scoped"));
+        assert_eq!(layer.base().get_var("KEY").as_deref(), Some("base"));
+
+        // Removing masks the base value without deleting it.
+        layer.remove_var("KEY");
+        assert!(layer.get_var("KEY").is_none());
+        assert_eq!(layer.base().get_var("KEY").as_deref(), Some("base"));
+
+        layer.clear();
+        assert_eq!(layer.get_var("KEY").as_deref(), Some("base"));
+    }
+
+    #[test]
+    fn test_layered_set_default_never_overrides() {
+        let layer = LayeredEnvironment::new(MapEnv::new(&[("REAL", "real")]));
+
+        assert!(!layer.set_default("REAL", "dotenv"));
+        assert_eq!(layer.get_var("REAL").as_deref(), Some("real"));
+
+        assert!(layer.set_default("NEW", "dotenv"));
+        assert!(!layer.set_default("NEW", "again"));
+        assert_eq!(layer.get_var("NEW").as_deref(), Some("dotenv"));
+
+        // A variable removed in the layer may be filled by a default.
+        layer.remove_var("REAL");
+        assert!(layer.set_default("REAL", "dotenv"));
+        assert_eq!(layer.get_var("REAL").as_deref(), Some("dotenv"));
+    }
+
+    #[test]
+    fn test_layers_nest_over_shared_parent() {
+        let parent: Arc<dyn Environment> =
+            Arc::new(LayeredEnvironment::new(MapEnv::new(&[("MODEL", "base")])));
+        parent.set_var("REGION", "eu");
+
+        let agent_a = LayeredEnvironment::with_vars(parent.clone(), [("MODEL", "a")]);
+        let agent_b = LayeredEnvironment::new(parent.clone());
+
+        assert_eq!(agent_a.get_var("MODEL").as_deref(), Some("a"));
+        assert_eq!(agent_b.get_var("MODEL").as_deref(), Some("base"));
+        assert_eq!(agent_a.get_var("REGION").as_deref(), Some("eu"));
+        assert_eq!(agent_b.get_var("REGION").as_deref(), Some("eu"));
+    }
+
+    #[test]
+    fn test_resolve_secret_uses_layer() {
+        let layer = LayeredEnvironment::with_vars(MapEnv::new(&[]), [("API_KEY", "layered")]);
+
+        let resolved = layer
+            .resolve_secret(&SecretString::default(), Some("API_KEY"))
+            .unwrap();
+        assert_eq!(resolved.expose(), "layered");
+
+        let explicit = layer
+            .resolve_secret(&SecretString::new("inline"), Some("API_KEY"))
+            .unwrap();
+        assert_eq!(explicit.expose(), "inline");
+    }
+
     #[test]
     fn test_get_var_existing() {
         let env = NativeEnvironment;
@@ -2,10 +2,27 @@
 //!
 //! Provides a platform-agnostic [`FileSystem`] trait for file I/O and a native
 //! implementation backed by [`tokio::fs`]. A WASM implementation would use
-//! WASI filesystem or browser-based storage.
+//! WASI filesystem or browser-based storage. Recursive listing and glob
+//! matching live in [`walk`].
 
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub mod walk;
+
+pub use walk::{WalkEntry, WalkOptions, WalkOutput};
+
+/// Basic information about a filesystem entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Size in bytes (0 for directories on some platforms).
+    pub len: u64,
+    /// Last modification time, when the platform tracks it.
+    pub modified: Option<SystemTime>,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+}
 
 /// Platform-agnostic filesystem operations.
 ///
@@ -17,6 +34,14 @@ pub trait FileSystem: Send + Sync {
     /// Read a file's entire contents as a UTF-8 string.
     async fn read_to_string(&self, path: &Path) -> std::io::Result<String>;
 
+    /// Read a file's entire contents as raw bytes.
+    ///
+    /// The default implementation reads the file as text, so it fails on
+    /// contents that are not valid UTF-8.
+    async fn read_bytes(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        Ok(self.read_to_string(path).await?.into_bytes())
+    }
+
     /// Write a string to a file, creating parent directories if needed.
     ///
     /// Overwrites the file if it already exists.
@@ -41,6 +66,99 @@ pub trait FileSystem: Send + Sync {
 
     /// Get the user's home directory.
     fn home_dir(&self) -> Option<PathBuf>;
+
+    /// Replace a file's contents so readers see either the old or the new
+    /// contents, never a partial write.
+    ///
+    /// Creates parent directories if needed. The default implementation
+    /// falls back to [`write_string`](Self::write_string), which is only
+    /// atomic for stores that replace contents in a single step.
+    async fn write_atomic(&self, path: &Path, content: &str) -> std::io::Result<()> {
+        self.write_string(path, content).await
+    }
+
+    /// Copy a file, returning the number of bytes copied.
+    ///
+    /// The default implementation reads and rewrites the file as text.
+    async fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        let content = self.read_to_string(from).await?;
+        self.write_string(to, &content).await?;
+        Ok(content.len() as u64)
+    }
+
+    /// Move a file, replacing `to` if it exists.
+    ///
+    /// The default implementation copies then removes the source.
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        self.copy(from, to).await?;
+        self.remove_file(from).await
+    }
+
+    /// Size, modification time and kind of a path.
+    ///
+    /// The default implementation reports files by reading them and
+    /// treats any other existing path as a directory; it never knows the
+    /// modification time.
+    async fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+        match self.read_to_string(path).await {
+            Ok(content) => Ok(FileMetadata {
+                len: content.len() as u64,
+                modified: None,
+                is_dir: false,
+            }),
+            Err(_) if self.exists(path).await => Ok(FileMetadata {
+                len: 0,
+                modified: None,
+                is_dir: true,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Recursively list `root`.
+    ///
+    /// The default implementation is [`walk::walk_portable`], built on
+    /// [`list_dir`](Self::list_dir) and [`metadata`](Self::metadata).
+    async fn walk(&self, root: &Path, options: &WalkOptions) -> std::io::Result<WalkOutput> {
+        walk::walk_portable(self, root, options).await
+    }
+
+    /// Files under `root` whose path relative to `root` matches `pattern`
+    /// (e.g. `**/*.md`), in walk order.
+    async fn glob(&self, root: &Path, pattern: &str) -> std::io::Result<Vec<PathBuf>> {
+        let options = WalkOptions {
+            max_depth: walk::glob_depth(pattern),
+            ..WalkOptions::default()
+        };
+        let output = self.walk(root, &options).await?;
+        Ok(output
+            .entries
+            .into_iter()
+            .filter(|e| walk::glob_match(pattern, &walk::relative_str(root, &e.path)))
+            .map(|e| e.path)
+            .collect())
+    }
+}
+
+/// Temporary sibling path used by [`NativeFileSystem::write_atomic`].
+///
+/// Lives in the same directory as `path` so the final rename never
+/// crosses filesystems.
+#[cfg(feature = "native")]
+fn temp_sibling(path: &Path) -> PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let unique = format!(
+        ".{name}.tmp-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    path.with_file_name(unique)
 }
 
 /// Native filesystem implementation using [`tokio::fs`].
@@ -54,6 +172,10 @@ impl FileSystem for NativeFileSystem {
         tokio::fs::read_to_string(path).await
     }
 
+    async fn read_bytes(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        tokio::fs::read(path).await
+    }
+
     async fn write_string(&self, path: &Path, content: &str) -> std::io::Result<()> {
         if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent).await?;
@@ -101,6 +223,57 @@ impl FileSystem for NativeFileSystem {
     fn home_dir(&self) -> Option<PathBuf> {
         dirs::home_dir()
     }
+
+    async fn write_atomic(&self, path: &Path, content: &str) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = temp_sibling(path);
+        let result = async {
+            let mut file = tokio::fs::File::create(&tmp).await?;
+            file.write_all(content.as_bytes()).await?;
+            file.sync_all().await?;
+            drop(file);
+            tokio::fs::rename(&tmp, path).await
+        }
+        .await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        result
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(from, to).await
+    }
+
+    async fn walk(&self, root: &Path, options: &WalkOptions) -> std::io::Result<WalkOutput> {
+        let (root, options) = (root.to_path_buf(), options.clone());
+        tokio::task::spawn_blocking(move || walk::walk_native(&root, &options))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    async fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+        let meta = tokio::fs::metadata(path).await?;
+        Ok(FileMetadata {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+            is_dir: meta.is_dir(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +302,18 @@ mod tests {
         fs.remove_file(&path).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_read_bytes_non_utf8() {
+        let fs = NativeFileSystem;
+        let path = temp_test_path("bytes");
+
+        tokio::fs::write(&path, [0xff, 0x00, b'a']).await.unwrap();
+        assert_eq!(fs.read_bytes(&path).await.unwrap(), [0xff, 0x00, b'a']);
+        assert!(fs.read_to_string(&path).await.is_err());
+
+        fs.remove_file(&path).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_write_creates_parent_dirs() {
         let fs = NativeFileSystem;
@@ -228,6 +413,113 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_write_atomic_never_exposes_partial_content() {
+        let fs = std::sync::Arc::new(NativeFileSystem);
+        let dir = temp_test_path("atomic");
+        let path = dir.join("state.json");
+        let a = "a".repeat(256 * 1024);
+        let b = "b".repeat(256 * 1024);
+        fs.write_atomic(&path, &a).await.unwrap();
+
+        let writer = {
+            let (fs, path, a, b) = (fs.clone(), path.clone(), a.clone(), b.clone());
+            tokio::spawn(async move {
+                for i in 0..40 {
+                    let content = if i % 2 == 0 { &b } else { &a };
+                    fs.write_atomic(&path, content).await.unwrap();
+                }
+            })
+        };
+        while !writer.is_finished() {
+            let seen = fs.read_to_string(&path).await.unwrap();
+            assert!(seen == a || seen == b, "observed partial write");
+        }
+        writer.await.unwrap();
+
+        // Only the target remains; temp files were renamed away.
+        assert_eq!(fs.list_dir(&dir).await.unwrap(), vec![path.clone()]);
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_creates_parent_dirs() {
+        let fs = NativeFileSystem;
+        let dir = temp_test_path("atomic_parent");
+        let path = dir.join("nested").join("file.txt");
+        fs.write_atomic(&path, "v1").await.unwrap();
+        fs.write_atomic(&path, "v2").await.unwrap();
+        assert_eq!(fs.read_to_string(&path).await.unwrap(), "v2");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_append_concurrent_writers() {
+        let fs = std::sync::Arc::new(NativeFileSystem);
+        let path = temp_test_path("append_concurrent");
+
+        let tasks: Vec<_> = (0..16)
+            .map(|task| {
+                let (fs, path) = (fs.clone(), path.clone());
+                tokio::spawn(async move {
+                    for i in 0..25 {
+                        fs.append_string(&path, &format!("task-{task:02} line-{i:02}\n"))
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+
+        let contents = fs.read_to_string(&path).await.unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 16 * 25);
+        assert!(lines.iter().all(|l| l.len() == "task-00 line-00".len()));
+        fs.remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_copy_and_rename() {
+        let fs = NativeFileSystem;
+        let dir = temp_test_path("copy_rename");
+        let src = dir.join("src.txt");
+        fs.write_string(&src, "payload").await.unwrap();
+
+        let copied = dir.join("sub").join("copy.txt");
+        assert_eq!(fs.copy(&src, &copied).await.unwrap(), 7);
+        assert_eq!(fs.read_to_string(&copied).await.unwrap(), "payload");
+
+        let moved = dir.join("moved").join("dst.txt");
+        fs.rename(&src, &moved).await.unwrap();
+        assert!(!fs.exists(&src).await);
+        assert_eq!(fs.read_to_string(&moved).await.unwrap(), "payload");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_metadata() {
+        let fs = NativeFileSystem;
+        let dir = temp_test_path("metadata");
+        let file = dir.join("f.txt");
+        fs.write_string(&file, "12345").await.unwrap();
+
+        let meta = fs.metadata(&file).await.unwrap();
+        assert_eq!(meta.len, 5);
+        assert!(!meta.is_dir);
+        assert!(meta.modified.is_some());
+
+        assert!(fs.metadata(&dir).await.unwrap().is_dir);
+        assert_eq!(
+            fs.metadata(&dir.join("missing")).await.unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
     #[test]
     fn test_home_dir() {
         let fs = NativeFileSystem;
@@ -0,0 +1,588 @@
+//! Recursive directory walking and glob matching.
+//!
+//! [`FileSystem::walk`](super::FileSystem::walk) lists a tree with a depth
+//! limit, gitignore-style ignore patterns and a result cap;
+//! [`FileSystem::glob`](super::FileSystem::glob) filters a walk with a glob
+//! pattern. [`walk_portable`] is the pure-Rust implementation used by
+//! filesystems that only provide [`list_dir`](super::FileSystem::list_dir)
+//! and [`metadata`](super::FileSystem::metadata); the native filesystem
+//! walks with `walkdir` and additionally enforces symlink containment.
+//!
+//! Glob syntax: `*` and `?` match within a path component, `**` matches
+//! any number of components, and `[abc]` / `[a-z]` / `[!abc]` match one
+//! character from a set.
+
+use std::path::{Path, PathBuf};
+
+use super::FileSystem;
+
+/// Options for [`FileSystem::walk`](super::FileSystem::walk).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Maximum depth below the root (1 = direct children). `None` walks
+    /// the whole tree.
+    pub max_depth: Option<usize>,
+    /// Descend into symlinked directories. Symlinks whose target lies
+    /// outside the root are skipped either way.
+    pub follow_symlinks: bool,
+    /// Gitignore-style patterns; matching entries (and everything below
+    /// ignored directories) are left out.
+    pub ignore: Vec<String>,
+    /// Stop after this many entries.
+    pub max_results: Option<usize>,
+    /// Include directories in the results, not just files.
+    pub include_dirs: bool,
+}
+
+/// One entry produced by a walk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    /// Full path (the root joined with the relative path).
+    pub path: PathBuf,
+    /// Depth below the root (1 = direct child).
+    pub depth: usize,
+    /// Whether the entry is a directory (after following symlinks).
+    pub is_dir: bool,
+    /// Whether the entry itself is a symlink.
+    pub is_symlink: bool,
+}
+
+/// Result of a walk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalkOutput {
+    /// Entries in depth-first order, siblings sorted by name. Followed
+    /// symlinked directories are listed after the rest of the tree.
+    pub entries: Vec<WalkEntry>,
+    /// `true` when `max_results` cut the walk short.
+    pub truncated: bool,
+}
+
+/// Compiled gitignore-style ignore rules.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: String,
+    negate: bool,
+    dir_only: bool,
+    /// Match against the whole relative path rather than the file name.
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    /// Compile patterns. Blank lines and `#` comments are skipped.
+    ///
+    /// A leading `!` re-includes a path, a trailing `/` matches only
+    /// directories, and a pattern containing `/` is matched against the
+    /// path relative to the root instead of the file name.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let rules = patterns
+            .iter()
+            .filter_map(|p| {
+                let mut p = p.as_ref().trim();
+                if p.is_empty() || p.starts_with('#') {
+                    return None;
+                }
+                let negate = p.starts_with('!');
+                if negate {
+                    p = &p[1..];
+                }
+                let dir_only = p.ends_with('/');
+                let p = p.trim_end_matches('/');
+                let anchored = p.contains('/');
+                Some(IgnoreRule {
+                    pattern: p.trim_start_matches('/').to_owned(),
+                    negate,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Whether `relative` (using `/` separators) is ignored.
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.anchored { relative } else { name };
+            if glob_match(&rule.pattern, subject) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+/// Match a `/`-separated path against a glob pattern.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => {
+            match_segments(&pattern[1..], path)
+                || (!path.is_empty() && match_segments(pattern, &path[1..]))
+        }
+        Some(seg) => {
+            !path.is_empty()
+                && match_component(seg.as_bytes(), path[0].as_bytes())
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+/// Wildcard match within one component, with backtracking over `*`.
+fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    star = Some((p, n));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((matched, len)) = match_class(&pattern[p..], name[n])
+                        && matched
+                    {
+                        p += len;
+                        n += 1;
+                        continue;
+                    }
+                }
+                c if c == name[n] => {
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        match star {
+            Some((sp, sn)) => {
+                p = sp + 1;
+                n = sn + 1;
+                star = Some((sp, sn + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Match `c` against a `[...]` class at the start of `pattern`.
+///
+/// Returns whether it matched and the class length, or `None` if the
+/// class is unterminated.
+fn match_class(pattern: &[u8], c: u8) -> Option<(bool, usize)> {
+    let end = pattern.iter().skip(2).position(|&b| b == b']')? + 2;
+    let mut body = &pattern[1..end];
+    let negate = matches!(body.first(), Some(b'!' | b'^'));
+    if negate {
+        body = &body[1..];
+    }
+    let mut matched = false;
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == b'-' {
+            matched |= (body[i]..=body[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= body[i] == c;
+            i += 1;
+        }
+    }
+    Some((matched != negate, end + 1))
+}
+
+/// Path of `path` relative to `root`, with `/` separators.
+pub(crate) fn relative_str(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Depth a glob needs to reach: its component count, or unlimited
+/// when it contains `**`.
+pub(crate) fn glob_depth(pattern: &str) -> Option<usize> {
+    let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.contains(&"**") {
+        None
+    } else {
+        Some(segments.len())
+    }
+}
+
+/// Walk using only [`FileSystem::list_dir`] and [`FileSystem::metadata`].
+///
+/// Used by filesystems without native traversal (such as the in-memory
+/// browser store). Symlinks are not visible through those methods, so
+/// `follow_symlinks` has no effect and every entry is reported as a
+/// regular file or directory.
+pub async fn walk_portable<F: FileSystem + ?Sized>(
+    fs: &F,
+    root: &Path,
+    options: &WalkOptions,
+) -> std::io::Result<WalkOutput> {
+    let rules = IgnoreRules::new(&options.ignore);
+    let mut out = WalkOutput::default();
+    if options.max_depth == Some(0) {
+        return Ok(out);
+    }
+    // Depth-first preorder with a stack of sorted sibling lists.
+    let mut stack = vec![(sorted_children(fs, root).await?.into_iter(), 1usize)];
+    while let Some((siblings, depth)) = stack.last_mut() {
+        let depth = *depth;
+        let Some(child) = siblings.next() else {
+            stack.pop();
+            continue;
+        };
+        let is_dir = fs.metadata(&child).await.map(|m| m.is_dir).unwrap_or(false);
+        if rules.is_ignored(&relative_str(root, &child), is_dir) {
+            continue;
+        }
+        if !is_dir || options.include_dirs {
+            if options
+                .max_results
+                .is_some_and(|max| out.entries.len() >= max)
+            {
+                out.truncated = true;
+                return Ok(out);
+            }
+            out.entries.push(WalkEntry {
+                path: child.clone(),
+                depth,
+                is_dir,
+                is_symlink: false,
+            });
+        }
+        if is_dir && options.max_depth.is_none_or(|max| depth < max) {
+            stack.push((sorted_children(fs, &child).await?.into_iter(), depth + 1));
+        }
+    }
+    Ok(out)
+}
+
+async fn sorted_children<F: FileSystem + ?Sized>(
+    fs: &F,
+    dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    let mut children = fs.list_dir(dir).await?;
+    children.sort();
+    Ok(children)
+}
+
+/// Native walk backed by `walkdir`, run on the blocking pool.
+///
+/// Any symlink whose resolved target is outside the (canonicalized) root
+/// is skipped, so callers confined to a workspace never see paths that
+/// escape it.
+#[cfg(feature = "native")]
+pub(crate) fn walk_native(root: &Path, options: &WalkOptions) -> std::io::Result<WalkOutput> {
+    let canonical_root = std::fs::canonicalize(root)?;
+    let rules = IgnoreRules::new(&options.ignore);
+    let mut walker = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(false)
+        .sort_by_file_name();
+    if let Some(max) = options.max_depth {
+        walker = walker.max_depth(max);
+    }
+
+    let mut out = WalkOutput::default();
+    // Symlinked directories we descend into are walked separately so each
+    // one's target can be containment-checked before entering it.
+    let mut pending = vec![(walker, 0usize)];
+    let mut visited = std::collections::HashSet::from([canonical_root.clone()]);
+    while let Some((walker, base_depth)) = pending.pop() {
+        let mut it = walker.into_iter();
+        while let Some(entry) = it.next() {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    tracing::debug!(error = %e, "skipping unreadable entry during walk");
+                    continue;
+                }
+            };
+            let depth = base_depth + entry.depth();
+            let path = entry.path().to_path_buf();
+            let is_symlink = entry.path_is_symlink();
+            let mut is_dir = entry.file_type().is_dir();
+
+            if is_symlink {
+                let Ok(target) = std::fs::canonicalize(entry.path()) else {
+                    continue; // dangling link
+                };
+                if !target.starts_with(&canonical_root) {
+                    tracing::debug!(path = %path.display(), "skipping symlink outside walk root");
+                    continue;
+                }
+                is_dir = target.is_dir();
+            }
+
+            if rules.is_ignored(&relative_str(root, &path), is_dir) {
+                if entry.file_type().is_dir() {
+                    it.skip_current_dir();
+                }
+                continue;
+            }
+
+            if !is_dir || options.include_dirs {
+                if options
+                    .max_results
+                    .is_some_and(|max| out.entries.len() >= max)
+                {
+                    out.truncated = true;
+                    return Ok(out);
+                }
+                out.entries.push(WalkEntry {
+                    path: path.clone(),
+                    depth,
+                    is_dir,
+                    is_symlink,
+                });
+            }
+
+            let room = options.max_depth.is_none_or(|max| depth < max);
+            if is_symlink
+                && is_dir
+                && options.follow_symlinks
+                && room
+                && visited.insert(std::fs::canonicalize(entry.path())?)
+            {
+                let mut sub = walkdir::WalkDir::new(entry.path())
+                    .min_depth(1)
+                    .follow_links(false)
+                    .sort_by_file_name();
+                if let Some(max) = options.max_depth {
+                    sub = sub.max_depth(max - depth);
+                }
+                pending.push((sub, depth));
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_wildcards() {
+        assert!(glob_match("*.md", "README.md"));
+        assert!(!glob_match("*.md", "README.txt"));
+        assert!(glob_match("file?.rs", "file1.rs"));
+        assert!(!glob_match("file?.rs", "file10.rs"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(glob_match("[abc].txt", "b.txt"));
+        assert!(!glob_match("[abc].txt", "d.txt"));
+        assert!(glob_match("v[0-9]", "v7"));
+        assert!(glob_match("[!.]*", "visible"));
+        assert!(!glob_match("[!.]*", ".hidden"));
+    }
+
+    #[test]
+    fn double_star_spans_directories() {
+        assert!(glob_match("**/*.md", "a.md"));
+        assert!(glob_match("**/*.md", "docs/guide/a.md"));
+        assert!(glob_match("skills/**/SKILL.md", "skills/x/y/SKILL.md"));
+        assert!(glob_match("skills/**/SKILL.md", "skills/SKILL.md"));
+        assert!(!glob_match("skills/*/SKILL.md", "skills/x/y/SKILL.md"));
+        assert!(!glob_match("*.md", "docs/a.md"));
+    }
+
+    #[test]
+    fn ignore_rules_follow_gitignore_semantics() {
+        let rules = IgnoreRules::new(&[
+            "# build output",
+            "target/",
+            "*.log",
+            "!keep.log",
+            "/docs/private",
+        ]);
+        assert!(rules.is_ignored("target", true));
+        assert!(rules.is_ignored("crates/x/target", true));
+        assert!(!rules.is_ignored("target", false)); // dir-only
+        assert!(rules.is_ignored("a/b/debug.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+        assert!(rules.is_ignored("docs/private", true));
+        assert!(!rules.is_ignored("other/docs/private", true));
+    }
+
+    #[cfg(all(feature = "native", unix))]
+    mod native {
+        use super::*;
+        use crate::fs::NativeFileSystem;
+
+        /// Build a tree plus an `outside` sibling that symlinks point into.
+        fn temp_tree(name: &str) -> (PathBuf, PathBuf) {
+            let base =
+                std::env::temp_dir().join(format!("clawft_walk_{name}_{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&base);
+            let root = base.join("root");
+            let outside = base.join("outside");
+            for dir in ["docs", "target", "deep/1/2"] {
+                std::fs::create_dir_all(root.join(dir)).unwrap();
+            }
+            std::fs::create_dir_all(&outside).unwrap();
+            for file in [
+                "a.md",
+                "docs/guide.md",
+                "docs/notes.txt",
+                "target/out.md",
+                "deep/1/2/3.md",
+            ] {
+                std::fs::write(root.join(file), file).unwrap();
+            }
+            std::fs::write(outside.join("secret.md"), "secret").unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("link_out")).unwrap();
+            std::os::unix::fs::symlink(outside.join("secret.md"), root.join("secret.md")).unwrap();
+            std::os::unix::fs::symlink(root.join("docs"), root.join("link_in")).unwrap();
+            (base, root)
+        }
+
+        fn rel(root: &Path, out: &WalkOutput) -> Vec<String> {
+            out.entries
+                .iter()
+                .map(|e| relative_str(root, &e.path))
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn walk_skips_symlinks_escaping_root() {
+            let (base, root) = temp_tree("escape");
+            let fs = NativeFileSystem;
+
+            let options = WalkOptions {
+                follow_symlinks: true,
+                ..WalkOptions::default()
+            };
+            let out = fs.walk(&root, &options).await.unwrap();
+            let paths = rel(&root, &out);
+            assert!(
+                !paths
+                    .iter()
+                    .any(|p| p.contains("secret") || p.contains("link_out"))
+            );
+            // Links that stay inside the root are followed.
+            assert!(paths.contains(&"link_in/guide.md".to_owned()));
+            assert!(paths.contains(&"deep/1/2/3.md".to_owned()));
+
+            let no_follow = fs.walk(&root, &WalkOptions::default()).await.unwrap();
+            assert!(
+                !rel(&root, &no_follow)
+                    .iter()
+                    .any(|p| p.starts_with("link_in/"))
+            );
+
+            let _ = std::fs::remove_dir_all(&base);
+        }
+
+        #[tokio::test]
+        async fn walk_applies_cap_depth_and_ignores() {
+            let (base, root) = temp_tree("cap");
+            let fs = NativeFileSystem;
+
+            let capped = fs
+                .walk(
+                    &root,
+                    &WalkOptions {
+                        max_results: Some(2),
+                        ..WalkOptions::default()
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(capped.entries.len(), 2);
+            assert!(capped.truncated);
+
+            let shallow = fs
+                .walk(
+                    &root,
+                    &WalkOptions {
+                        max_depth: Some(1),
+                        include_dirs: true,
+                        ignore: vec!["target/".into(), "link_*".into()],
+                        ..WalkOptions::default()
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(rel(&root, &shallow), ["a.md", "deep", "docs"]);
+            assert!(!shallow.truncated);
+
+            let _ = std::fs::remove_dir_all(&base);
+        }
+
+        #[tokio::test]
+        async fn glob_matches_relative_paths() {
+            let (base, root) = temp_tree("glob");
+            let fs = NativeFileSystem;
+
+            let md = fs.glob(&root, "**/*.md").await.unwrap();
+            let md: Vec<String> = md.iter().map(|p| relative_str(&root, p)).collect();
+            assert_eq!(
+                md,
+                ["a.md", "deep/1/2/3.md", "docs/guide.md", "target/out.md"]
+            );
+
+            let top = fs.glob(&root, "*.md").await.unwrap();
+            assert_eq!(top, vec![root.join("a.md")]);
+
+            let _ = std::fs::remove_dir_all(&base);
+        }
+
+        #[tokio::test]
+        async fn portable_walk_matches_native_without_symlinks() {
+            let (base, root) = temp_tree("portable");
+            for link in ["link_out", "secret.md", "link_in"] {
+                std::fs::remove_file(root.join(link)).unwrap();
+            }
+            let fs = NativeFileSystem;
+            let options = WalkOptions {
+                include_dirs: true,
+                ignore: vec!["*.txt".into()],
+                ..WalkOptions::default()
+            };
+            let native = fs.walk(&root, &options).await.unwrap();
+            let portable = walk_portable(&fs, &root, &options).await.unwrap();
+            assert_eq!(rel(&root, &native), rel(&root, &portable));
+
+            let _ = std::fs::remove_dir_all(&base);
+        }
+    }
+
+    #[test]
+    fn glob_depth_limits() {
+        assert_eq!(glob_depth("*.md"), Some(1));
+        assert_eq!(glob_depth("a/*/b.md"), Some(3));
+        assert_eq!(glob_depth("**/*.md"), None);
+    }
+}
@@ -2,10 +2,34 @@
 //!
 //! Provides a platform-agnostic [`HttpClient`] trait and a native implementation
 //! backed by [`reqwest`]. A WASM implementation would use the browser's fetch API.
+//!
+//! Large bodies can be consumed incrementally with [`HttpClient::get_stream`],
+//! and [`NativeHttpClient::download`] writes a response straight to disk with
+//! a size cap and range-based resume. [`RetryingHttpClient`] adds retries
+//! with backoff on top of any client, and [`CachingHttpClient`] caches
+//! `GET` responses according to `Cache-Control` and `ETag`.
+//!
+//! Redirects are followed automatically unless a request sets
+//! [`NO_REDIRECT_HEADER`], in which case the `3xx` response is returned
+//! as-is so the caller can vet the `Location` before following it.
 
 use async_trait::async_trait;
 use std::collections::HashMap;
 
+pub mod cache;
+pub mod retry;
+
+#[cfg(feature = "native")]
+pub use cache::CachingHttpClient;
+pub use cache::{BYPASS_HEADER, CACHE_STATUS_HEADER, CREDENTIAL_HEADERS, CachePolicy};
+#[cfg(feature = "native")]
+pub use retry::RetryingHttpClient;
+pub use retry::{ATTEMPTS_HEADER, IDEMPOTENCY_KEY_HEADER, RetryPolicy, is_idempotent};
+
+/// Request header that makes [`NativeHttpClient`] return a redirect
+/// response instead of following it. It is removed before sending.
+pub const NO_REDIRECT_HEADER: &str = "x-clawft-no-redirect";
+
 /// HTTP response from a request.
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
@@ -34,6 +58,32 @@ impl HttpResponse {
     }
 }
 
+/// Status and headers of a response whose body was streamed to a
+/// [`ChunkSink`] by [`HttpClient::get_stream`].
+#[derive(Debug, Clone)]
+pub struct StreamedResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers as key-value pairs.
+    pub headers: HashMap<String, String>,
+    /// Total body bytes handed to the sink.
+    pub bytes_received: u64,
+}
+
+impl StreamedResponse {
+    /// Check if status is success (2xx).
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Callback receiving response body chunks in order.
+///
+/// Returning an error aborts the transfer; the error is passed through
+/// to the caller of [`HttpClient::get_stream`].
+pub type ChunkSink<'a> =
+    dyn FnMut(&[u8]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + 'a;
+
 /// Platform-agnostic HTTP client.
 ///
 /// Implementors provide transport-level HTTP operations. The native
@@ -68,31 +118,375 @@ pub trait HttpClient: Send + Sync {
     ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
         self.request("POST", url, headers, Some(body)).await
     }
+
+    /// Send an HTTP GET request and deliver the body to `on_chunk` as it
+    /// arrives instead of buffering it.
+    ///
+    /// The default implementation buffers the whole body with
+    /// [`get`](Self::get) and delivers it as a single chunk; clients
+    /// that can stream should override it.
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<StreamedResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.get(url, headers).await?;
+        if !response.body.is_empty() {
+            on_chunk(&response.body)?;
+        }
+        Ok(StreamedResponse {
+            status: response.status,
+            headers: response.headers,
+            bytes_received: response.body.len() as u64,
+        })
+    }
+}
+
+/// Progress of a [`NativeHttpClient::download`].
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Bytes on disk so far, including any resumed prefix.
+    pub downloaded: u64,
+    /// Expected final size, when the server reported a length.
+    pub total: Option<u64>,
+}
+
+/// Result of a completed [`NativeHttpClient::download`].
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadOutcome {
+    /// Final size of the file on disk.
+    pub bytes: u64,
+    /// Whether an existing partial file was continued with a range request.
+    pub resumed: bool,
+}
+
+/// Errors from [`NativeHttpClient::download`].
+#[cfg(feature = "native")]
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    /// The body is larger than the caller's `max_bytes`.
+    #[error("download exceeds size limit of {limit} bytes")]
+    TooLarge {
+        /// The limit that was exceeded.
+        limit: u64,
+    },
+    /// The server answered with a non-success status.
+    #[error("server returned HTTP {0}")]
+    Status(u16),
+    /// Connection or protocol failure, including a body cut off mid-stream.
+    #[error("transfer failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// Writing the destination file failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Settings for building a [`NativeHttpClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    /// Time allowed to establish a connection.
+    pub connect_timeout: Option<std::time::Duration>,
+    /// Time allowed for a whole request, including the body.
+    pub request_timeout: Option<std::time::Duration>,
+    /// HTTP(S) or SOCKS5 proxy URL for all requests. When `None`,
+    /// `HTTPS_PROXY` (or `https_proxy`) is used if set.
+    pub proxy: Option<String>,
+    /// PEM file with additional root certificates to trust.
+    pub ca_cert_path: Option<std::path::PathBuf>,
+    /// `User-Agent` header sent with every request.
+    pub user_agent: Option<String>,
+    /// Retry behavior used by [`RetryingHttpClient::from_config`].
+    pub retry: RetryPolicy,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Some(std::time::Duration::from_secs(10)),
+            request_timeout: Some(std::time::Duration::from_secs(60)),
+            proxy: None,
+            ca_cert_path: None,
+            user_agent: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// The proxy to use: the configured one, else `HTTPS_PROXY`.
+    pub fn resolved_proxy(&self) -> Option<String> {
+        self.proxy.clone().filter(|p| !p.is_empty()).or_else(|| {
+            ["HTTPS_PROXY", "https_proxy"]
+                .iter()
+                .filter_map(|key| std::env::var(key).ok())
+                .find(|v| !v.is_empty())
+        })
+    }
+}
+
+impl From<&clawft_types::config::HttpConfig> for HttpClientConfig {
+    fn from(config: &clawft_types::config::HttpConfig) -> Self {
+        let secs = |s: u64| (s > 0).then(|| std::time::Duration::from_secs(s));
+        Self {
+            connect_timeout: secs(config.connect_timeout_secs),
+            request_timeout: secs(config.request_timeout_secs),
+            proxy: config.proxy.clone(),
+            ca_cert_path: config.ca_cert_path.as_ref().map(Into::into),
+            user_agent: config.user_agent.clone(),
+            retry: RetryPolicy {
+                max_attempts: config.retry.max_attempts.max(1),
+                initial_backoff: std::time::Duration::from_millis(config.retry.initial_backoff_ms),
+                max_backoff: std::time::Duration::from_millis(config.retry.max_backoff_ms),
+                retry_statuses: config.retry.retry_statuses.clone(),
+                retry_non_idempotent: config.retry.retry_non_idempotent,
+                ..RetryPolicy::default()
+            },
+        }
+    }
 }
 
 /// Native HTTP client using [`reqwest`].
 #[cfg(feature = "native")]
 pub struct NativeHttpClient {
     client: reqwest::Client,
+    /// Same settings as `client`, but never follows redirects.
+    no_redirect: reqwest::Client,
+    proxy: Option<String>,
 }
 
 #[cfg(feature = "native")]
 impl NativeHttpClient {
     /// Create a new native HTTP client with sensible defaults.
     ///
-    /// Uses a 60-second timeout and 30-second idle connection timeout
-    /// with connection pooling enabled.
+    /// Uses a 60-second request timeout, 10-second connect timeout and
+    /// 30-second idle connection timeout with connection pooling enabled.
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(60))
-                .pool_idle_timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("failed to build reqwest client"),
+        Self::with_config(&HttpClientConfig::default()).expect("failed to build reqwest client")
+    }
+
+    /// Create a client from explicit settings.
+    ///
+    /// Fails when the proxy URL is invalid or the CA file cannot be read
+    /// or parsed.
+    pub fn with_config(
+        config: &HttpClientConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let proxy = config.resolved_proxy();
+        Ok(Self {
+            client: Self::builder(config, proxy.as_deref())?.build()?,
+            no_redirect: Self::builder(config, proxy.as_deref())?
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?,
+            proxy,
+        })
+    }
+
+    fn builder(
+        config: &HttpClientConfig,
+        proxy: Option<&str>,
+    ) -> Result<reqwest::ClientBuilder, Box<dyn std::error::Error + Send + Sync>> {
+        let mut builder =
+            reqwest::Client::builder().pool_idle_timeout(std::time::Duration::from_secs(30));
+        if let Some(timeout) = config.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(url) = proxy {
+            builder = builder.proxy(
+                reqwest::Proxy::all(url).map_err(|e| format!("invalid proxy '{url}': {e}"))?,
+            );
+        }
+        if let Some(ref path) = config.ca_cert_path {
+            let pem = std::fs::read(path)
+                .map_err(|e| format!("cannot read CA file '{}': {e}", path.display()))?;
+            for cert in reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("invalid CA file '{}': {e}", path.display()))?
+            {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let Some(ref agent) = config.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        Ok(builder)
+    }
+
+    /// Proxy URL the client routes through, if any.
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+}
+
+#[cfg(feature = "native")]
+impl NativeHttpClient {
+    /// Overall time allowed for a single [`download`](Self::download);
+    /// replaces the client's 60-second default, which is meant for API calls.
+    const DOWNLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+    /// Download `url` to `dest`, streaming chunks to disk.
+    ///
+    /// When `dest` already holds a partial file, a `Range` request asks for
+    /// the remainder; the file is appended to if the server answers `206`
+    /// starting at the current file length, and rewritten from scratch if it
+    /// ignores the range. A `206` whose `Content-Range` starts anywhere else
+    /// truncates the file and restarts the transfer without a range. A `416`
+    /// means the partial file is already complete only when its
+    /// `Content-Range: bytes */TOTAL` equals the file length; otherwise the
+    /// file is a leftover of something else and is restarted the same way.
+    /// A transfer cut off mid-stream leaves the partial file in place so a
+    /// later call resumes it.
+    ///
+    /// `max_bytes` caps the final file size. Oversized responses are
+    /// rejected from `Content-Length` before anything is written, and
+    /// otherwise as soon as the cap is crossed, in which case the partial
+    /// file is deleted.
+    pub async fn download(
+        &self,
+        url: &str,
+        dest: &std::path::Path,
+        max_bytes: Option<u64>,
+        progress: Option<&(dyn Fn(DownloadProgress) + Send + Sync)>,
+    ) -> Result<DownloadOutcome, DownloadError> {
+        use reqwest::StatusCode;
+        use tokio::io::AsyncWriteExt;
+
+        let mut existing = tokio::fs::metadata(dest)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        let mut response = loop {
+            let mut request = self.client.get(url).timeout(Self::DOWNLOAD_TIMEOUT);
+            if existing > 0 {
+                request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+            }
+            let response = request.send().await?;
+            if existing > 0 && response.status() == StatusCode::PARTIAL_CONTENT {
+                let start = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(content_range_start);
+                if start != Some(existing) {
+                    tracing::debug!(
+                        url,
+                        ?start,
+                        expected = existing,
+                        "server resumed at the wrong offset, restarting download"
+                    );
+                    tokio::fs::File::create(dest).await?;
+                    existing = 0;
+                    continue;
+                }
+            }
+            if existing > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                let total = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(content_range_total);
+                if total == Some(existing) {
+                    // Nothing left past our offset: the partial file is complete.
+                    return Ok(DownloadOutcome {
+                        bytes: existing,
+                        resumed: true,
+                    });
+                }
+                tracing::debug!(
+                    url,
+                    ?total,
+                    partial = existing,
+                    "partial file does not match the resource, restarting download"
+                );
+                tokio::fs::File::create(dest).await?;
+                existing = 0;
+                continue;
+            }
+            break response;
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DownloadError::Status(status.as_u16()));
         }
+
+        let resumed = status == StatusCode::PARTIAL_CONTENT && existing > 0;
+        let mut downloaded = if resumed { existing } else { 0 };
+        let total = response.content_length().map(|len| len + downloaded);
+        if let (Some(limit), Some(total)) = (max_bytes, total)
+            && total > limit
+        {
+            return Err(DownloadError::TooLarge { limit });
+        }
+
+        let mut file = if resumed {
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(dest)
+                .await?
+        } else {
+            tokio::fs::File::create(dest).await?
+        };
+        let report = |downloaded| {
+            if let Some(progress) = progress {
+                progress(DownloadProgress { downloaded, total });
+            }
+        };
+        report(downloaded);
+
+        while let Some(chunk) = response.chunk().await? {
+            downloaded += chunk.len() as u64;
+            if let Some(limit) = max_bytes
+                && downloaded > limit
+            {
+                drop(file);
+                let _ = tokio::fs::remove_file(dest).await;
+                return Err(DownloadError::TooLarge { limit });
+            }
+            file.write_all(&chunk).await?;
+            report(downloaded);
+        }
+        file.flush().await?;
+
+        Ok(DownloadOutcome {
+            bytes: downloaded,
+            resumed,
+        })
     }
 }
 
+/// Start offset of a `Content-Range: bytes START-END/TOTAL` header.
+#[cfg(feature = "native")]
+fn content_range_start(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Total length of a `Content-Range: bytes START-END/TOTAL` or
+/// `bytes */TOTAL` header, when known.
+#[cfg(feature = "native")]
+fn content_range_total(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .strip_prefix("bytes ")?
+        .rsplit_once('/')?
+        .1
+        .trim()
+        .parse()
+        .ok()
+}
+
 #[cfg(feature = "native")]
 impl Default for NativeHttpClient {
     fn default() -> Self {
@@ -111,10 +505,20 @@ impl HttpClient for NativeHttpClient {
         body: Option<&[u8]>,
     ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
         let reqwest_method = method.parse::<reqwest::Method>()?;
-        let mut builder = self.client.request(reqwest_method, url);
+        let follow = !headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case(NO_REDIRECT_HEADER));
+        let client = if follow {
+            &self.client
+        } else {
+            &self.no_redirect
+        };
+        let mut builder = client.request(reqwest_method, url);
 
         for (key, value) in headers {
-            builder = builder.header(key.as_str(), value.as_str());
+            if !key.eq_ignore_ascii_case(NO_REDIRECT_HEADER) {
+                builder = builder.header(key.as_str(), value.as_str());
+            }
         }
 
         if let Some(body_bytes) = body {
@@ -138,11 +542,56 @@ impl HttpClient for NativeHttpClient {
             body: resp_body,
         })
     }
+
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<StreamedResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let follow = !headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case(NO_REDIRECT_HEADER));
+        let client = if follow {
+            &self.client
+        } else {
+            &self.no_redirect
+        };
+        let mut builder = client.get(url);
+        for (key, value) in headers {
+            if !key.eq_ignore_ascii_case(NO_REDIRECT_HEADER) {
+                builder = builder.header(key.as_str(), value.as_str());
+            }
+        }
+        let mut response = builder.send().await?;
+
+        let status = response.status().as_u16();
+        let mut resp_headers = HashMap::new();
+        for (key, value) in response.headers() {
+            if let Ok(v) = value.to_str() {
+                resp_headers.insert(key.as_str().to_string(), v.to_string());
+            }
+        }
+
+        let mut bytes_received = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            bytes_received += chunk.len() as u64;
+            on_chunk(&chunk)?;
+        }
+
+        Ok(StreamedResponse {
+            status,
+            headers: resp_headers,
+            bytes_received,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn test_http_response_text() {
@@ -217,4 +666,514 @@ mod tests {
     fn test_native_http_client_default() {
         let _client = NativeHttpClient::default();
     }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-http-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("download.bin")
+    }
+
+    /// Serve one connection that promises `declared` bytes but sends only
+    /// `body` before closing, simulating a transfer cut off mid-stream.
+    ///
+    /// The whole request head is read and the close is delayed on purpose:
+    /// closing a socket with unread request bytes makes the kernel send a
+    /// reset, and the client can then lose the partial body and see a
+    /// connection error before any data, which made the download tests
+    /// flaky.
+    async fn truncating_server(declared: usize, body: &'static [u8]) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {declared}\r\n\r\n");
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(body).await.unwrap();
+            socket.flush().await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            socket.shutdown().await.unwrap();
+        });
+        format!("http://{addr}/file")
+    }
+
+    #[tokio::test]
+    async fn request_timeout_applies_to_slow_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let client = NativeHttpClient::with_config(&HttpClientConfig {
+            request_timeout: Some(std::time::Duration::from_millis(200)),
+            ..HttpClientConfig::default()
+        })
+        .unwrap();
+        let started = std::time::Instant::now();
+        let err = client
+            .get(&server.uri(), &HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        let err = err.downcast::<reqwest::Error>().unwrap();
+        assert!(err.is_timeout(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn proxy_routes_requests() {
+        // The proxy is a mock server; the upstream host does not resolve,
+        // so a response proves the request went through the proxy.
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/resource"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("via proxy"))
+            .mount(&proxy)
+            .await;
+
+        let client = NativeHttpClient::with_config(&HttpClientConfig {
+            proxy: Some(proxy.uri()),
+            ..HttpClientConfig::default()
+        })
+        .unwrap();
+        assert_eq!(client.proxy(), Some(proxy.uri().as_str()));
+        let resp = client
+            .get("http://upstream.invalid/resource", &HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(resp.text().unwrap(), "via proxy");
+    }
+
+    #[tokio::test]
+    async fn user_agent_is_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", "weft-test/1.0"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let client = NativeHttpClient::with_config(&HttpClientConfig {
+            user_agent: Some("weft-test/1.0".into()),
+            ..HttpClientConfig::default()
+        })
+        .unwrap();
+        let resp = client.get(&server.uri(), &HashMap::new()).await.unwrap();
+        assert_eq!(resp.status, 204);
+    }
+
+    #[tokio::test]
+    async fn no_redirect_header_returns_the_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("moved"))
+            .mount(&server)
+            .await;
+
+        let client = NativeHttpClient::new();
+        let url = format!("{}/old", server.uri());
+        let followed = client.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(followed.text().unwrap(), "moved");
+
+        let headers = HashMap::from([(NO_REDIRECT_HEADER.to_string(), "1".to_string())]);
+        let resp = client.get(&url, &headers).await.unwrap();
+        assert_eq!(resp.status, 302);
+        assert_eq!(resp.headers["location"], "/new");
+        let sent = &server.received_requests().await.unwrap()[2];
+        assert!(!sent.headers.contains_key(NO_REDIRECT_HEADER));
+
+        let mut sink = |_: &[u8]| Ok(());
+        let streamed = client.get_stream(&url, &headers, &mut sink).await.unwrap();
+        assert_eq!(streamed.status, 302);
+        assert_eq!(streamed.headers["location"], "/new");
+    }
+
+    #[test]
+    fn with_config_rejects_bad_inputs() {
+        let bad_ca = HttpClientConfig {
+            ca_cert_path: Some("/nonexistent/clawft/ca.pem".into()),
+            ..HttpClientConfig::default()
+        };
+        let err = NativeHttpClient::with_config(&bad_ca).err().unwrap();
+        assert!(err.to_string().contains("cannot read CA file"));
+
+        let bad_proxy = HttpClientConfig {
+            proxy: Some("::not a url::".into()),
+            ..HttpClientConfig::default()
+        };
+        let err = NativeHttpClient::with_config(&bad_proxy).err().unwrap();
+        assert!(err.to_string().contains("invalid proxy"));
+    }
+
+    #[test]
+    fn config_from_types_section() {
+        let section = clawft_types::config::HttpConfig {
+            connect_timeout_secs: 3,
+            request_timeout_secs: 0,
+            proxy: Some("http://proxy:3128".into()),
+            ca_cert_path: Some("/etc/ssl/corp.pem".into()),
+            user_agent: None,
+            retry: clawft_types::config::HttpRetryConfig {
+                max_attempts: 5,
+                initial_backoff_ms: 100,
+                max_backoff_ms: 2_000,
+                retry_statuses: vec![503],
+                retry_non_idempotent: true,
+            },
+        };
+        let config = HttpClientConfig::from(&section);
+        assert_eq!(
+            config.connect_timeout,
+            Some(std::time::Duration::from_secs(3))
+        );
+        // Zero disables the timeout.
+        assert_eq!(config.request_timeout, None);
+        assert_eq!(
+            config.resolved_proxy().as_deref(),
+            Some("http://proxy:3128")
+        );
+        assert_eq!(
+            config.ca_cert_path.as_deref(),
+            Some(std::path::Path::new("/etc/ssl/corp.pem"))
+        );
+        assert_eq!(config.retry.max_attempts, 5);
+        assert_eq!(
+            config.retry.initial_backoff,
+            std::time::Duration::from_millis(100)
+        );
+        assert_eq!(config.retry.max_backoff, std::time::Duration::from_secs(2));
+        assert_eq!(config.retry.retry_statuses, vec![503]);
+        assert!(config.retry.retry_non_idempotent);
+        assert!(config.retry.jitter);
+    }
+
+    #[test]
+    fn with_config_accepts_socks5_proxy() {
+        let config = HttpClientConfig {
+            proxy: Some("socks5://127.0.0.1:1080".into()),
+            ..HttpClientConfig::default()
+        };
+        let client = NativeHttpClient::with_config(&config).unwrap();
+        assert_eq!(client.proxy(), Some("socks5://127.0.0.1:1080"));
+    }
+
+    #[tokio::test]
+    async fn get_stream_delivers_whole_body() {
+        let server = MockServer::start().await;
+        let body = vec![7u8; 64 * 1024];
+        Mock::given(method("GET"))
+            .and(path("/big"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+            .mount(&server)
+            .await;
+
+        let client = NativeHttpClient::new();
+        let mut received = Vec::new();
+        let head = client
+            .get_stream(
+                &format!("{}/big", server.uri()),
+                &HashMap::new(),
+                &mut |chunk: &[u8]| {
+                    received.extend_from_slice(chunk);
+                    Ok(())
+                },
+            )
+            .await
+            .unwrap();
+        assert!(head.is_success());
+        assert_eq!(head.bytes_received, body.len() as u64);
+        assert_eq!(received, body);
+    }
+
+    #[tokio::test]
+    async fn get_stream_sink_error_aborts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![1u8; 1024]))
+            .mount(&server)
+            .await;
+
+        let client = NativeHttpClient::new();
+        let err = client
+            .get_stream(&server.uri(), &HashMap::new(), &mut |_: &[u8]| {
+                Err("stop".into())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "stop");
+    }
+
+    #[tokio::test]
+    async fn get_stream_mid_stream_error() {
+        let url = truncating_server(1000, b"partial").await;
+        let client = NativeHttpClient::new();
+        let mut received = Vec::new();
+        let result = client
+            .get_stream(&url, &HashMap::new(), &mut |chunk: &[u8]| {
+                received.extend_from_slice(chunk);
+                Ok(())
+            })
+            .await;
+        assert!(result.is_err());
+        assert!(received.len() < 1000);
+    }
+
+    #[tokio::test]
+    async fn download_writes_file_and_reports_progress() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"hello world".to_vec()))
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("ok");
+        let _ = std::fs::remove_file(&dest);
+        let last = std::sync::Mutex::new(None);
+        let on_progress = |p: DownloadProgress| *last.lock().unwrap() = Some(p);
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, Some(1024), Some(&on_progress))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            outcome,
+            DownloadOutcome {
+                bytes: 11,
+                resumed: false
+            }
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+        assert_eq!(
+            *last.lock().unwrap(),
+            Some(DownloadProgress {
+                downloaded: 11,
+                total: Some(11)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn download_rejects_body_over_cap() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 100]))
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("cap");
+        let _ = std::fs::remove_file(&dest);
+        let err = NativeHttpClient::new()
+            .download(&server.uri(), &dest, Some(10), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DownloadError::TooLarge { limit: 10 }));
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn download_resumes_with_range_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", "bytes=6-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 6-10/11")
+                    .set_body_bytes(b"world".to_vec()),
+            )
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("resume");
+        std::fs::write(&dest, b"hello ").unwrap();
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            DownloadOutcome {
+                bytes: 11,
+                resumed: true
+            }
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+    }
+
+    #[tokio::test]
+    async fn download_restarts_when_range_ignored() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"fresh".to_vec()))
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("restart");
+        std::fs::write(&dest, b"stale-partial").unwrap();
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap();
+        assert!(!outcome.resumed);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"fresh");
+    }
+
+    #[tokio::test]
+    async fn download_restarts_when_range_start_mismatches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", "bytes=6-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 0-10/11")
+                    .set_body_bytes(b"hello world".to_vec()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"hello world".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("range-mismatch");
+        std::fs::write(&dest, b"hello ").unwrap();
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            DownloadOutcome {
+                bytes: 11,
+                resumed: false
+            }
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+        let received = server.received_requests().await.unwrap();
+        assert!(!received[1].headers.contains_key("range"));
+    }
+
+    #[test]
+    fn parses_content_range_start() {
+        assert_eq!(content_range_start("bytes 6-10/11"), Some(6));
+        assert_eq!(content_range_start(" bytes 0-99/*"), Some(0));
+        assert_eq!(content_range_start("bytes */11"), None);
+        assert_eq!(content_range_start("items 1-2/3"), None);
+    }
+
+    #[test]
+    fn parses_content_range_total() {
+        assert_eq!(content_range_total("bytes */11"), Some(11));
+        assert_eq!(content_range_total("bytes 6-10/11"), Some(11));
+        assert_eq!(content_range_total("bytes 0-99/*"), None);
+        assert_eq!(content_range_total("items */3"), None);
+    }
+
+    #[tokio::test]
+    async fn download_accepts_complete_partial_file_on_416() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", "bytes=11-"))
+            .respond_with(ResponseTemplate::new(416).insert_header("content-range", "bytes */11"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("complete-416");
+        std::fs::write(&dest, b"hello world").unwrap();
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            DownloadOutcome {
+                bytes: 11,
+                resumed: true
+            }
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+    }
+
+    #[tokio::test]
+    async fn download_restarts_when_416_total_mismatches() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", "bytes=13-"))
+            .respond_with(ResponseTemplate::new(416).insert_header("content-range", "bytes */5"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"fresh".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // A longer leftover from some other resource.
+        let dest = temp_path("stale-416");
+        std::fs::write(&dest, b"stale-partial").unwrap();
+        let outcome = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome,
+            DownloadOutcome {
+                bytes: 5,
+                resumed: false
+            }
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"fresh");
+        let received = server.received_requests().await.unwrap();
+        assert!(!received[1].headers.contains_key("range"));
+    }
+
+    #[tokio::test]
+    async fn download_mid_stream_error_keeps_partial_file() {
+        let url = truncating_server(100, b"0123456789").await;
+        let dest = temp_path("truncated");
+        let _ = std::fs::remove_file(&dest);
+        let err = NativeHttpClient::new()
+            .download(&url, &dest, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DownloadError::Transport(_)), "{err:?}");
+        // The partial file stays so the next call can resume it.
+        assert_eq!(std::fs::read(&dest).unwrap(), b"0123456789");
+    }
+
+    #[tokio::test]
+    async fn download_non_success_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let dest = temp_path("missing");
+        let _ = std::fs::remove_file(&dest);
+        let err = NativeHttpClient::new()
+            .download(&server.uri(), &dest, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DownloadError::Status(404)));
+        assert!(!dest.exists());
+    }
 }
@@ -0,0 +1,788 @@
+//! Response-caching decorator for [`HttpClient`].
+//!
+//! [`CachingHttpClient`] keeps successful `GET` responses in a bounded
+//! in-memory LRU cache, optionally mirrored to a directory on disk. Entries
+//! are fresh for the `Cache-Control: max-age` the server sent; stale entries
+//! carrying an `ETag` are revalidated with `If-None-Match`, and a
+//! `304 Not Modified` answer is served from the cache.
+//!
+//! Requests are never cached when they use another method, carry a
+//! credential header ([`CREDENTIAL_HEADERS`]), or set [`BYPASS_HEADER`]:
+//! entries are keyed by method and URL only, so a credentialed response
+//! must not answer another caller. Requests setting
+//! [`NO_REDIRECT_HEADER`](super::NO_REDIRECT_HEADER) are cached under their
+//! own key, so a response reached by following redirects never answers a
+//! caller that vets each redirect itself. Responses marked `no-store` or
+//! `private` are never stored. Whether a response came from the cache is
+//! reported in [`CACHE_STATUS_HEADER`].
+//!
+//! The disk tier is bounded by [`CachePolicy::max_disk_bytes`]; the oldest
+//! written entries are removed first when it grows past the limit.
+
+use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+#[cfg(feature = "native")]
+use std::time::SystemTime;
+
+#[cfg(feature = "native")]
+use super::{ChunkSink, HttpClient, HttpResponse, NO_REDIRECT_HEADER, StreamedResponse};
+
+/// Request header that makes [`CachingHttpClient`] skip the cache for one
+/// request, neither reading nor storing. It is removed before sending.
+pub const BYPASS_HEADER: &str = "x-clawft-cache-bypass";
+
+/// Request headers carrying credentials. A request with any of them is
+/// never read from or stored in the cache.
+pub const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "cookie",
+];
+
+/// Response header set by [`CachingHttpClient`]: `hit`, `revalidated`,
+/// `miss` or `bypass`.
+pub const CACHE_STATUS_HEADER: &str = "x-clawft-cache";
+
+/// Size limits and storage for [`CachingHttpClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Largest body that will be cached, in bytes.
+    pub max_entry_bytes: usize,
+    /// Total body bytes kept in memory before least-recently-used entries
+    /// are evicted.
+    pub max_total_bytes: usize,
+    /// Directory mirroring the cache on disk, so entries survive restarts.
+    pub disk_dir: Option<PathBuf>,
+    /// Total bytes kept in `disk_dir` before the oldest entries are removed.
+    pub max_disk_bytes: u64,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            max_entry_bytes: 2 * 1024 * 1024,
+            max_total_bytes: 32 * 1024 * 1024,
+            disk_dir: None,
+            max_disk_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// The caching-relevant parts of a `Cache-Control` header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// `max-age` in seconds.
+    pub max_age: Option<u64>,
+    /// `no-store` or `private`: never keep the response.
+    pub no_store: bool,
+    /// `no-cache`: keep it, but revalidate before every use.
+    pub no_cache: bool,
+}
+
+impl CacheControl {
+    /// Parse the `Cache-Control` header from `headers`, if present.
+    pub fn from_headers(headers: &HashMap<String, String>) -> Self {
+        let mut cc = Self::default();
+        let Some(value) = header(headers, "cache-control") else {
+            return cc;
+        };
+        for directive in value.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", secs)) => cc.max_age = secs.trim_matches('"').parse().ok(),
+                _ if directive == "no-store" || directive == "private" => cc.no_store = true,
+                _ if directive == "no-cache" => cc.no_cache = true,
+                _ => {}
+            }
+        }
+        cc
+    }
+
+    /// How long a response stays fresh (zero means revalidate every time).
+    pub fn freshness(&self) -> Duration {
+        if self.no_cache {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(self.max_age.unwrap_or(0))
+        }
+    }
+}
+
+/// Case-insensitive header lookup.
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// A stored response plus what is needed to judge and refresh it.
+#[cfg(feature = "native")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    status: u16,
+    headers: HashMap<String, String>,
+    #[serde(skip)]
+    body: Vec<u8>,
+    etag: Option<String>,
+    stored_at: SystemTime,
+    fresh_for: Duration,
+}
+
+#[cfg(feature = "native")]
+impl CacheEntry {
+    /// Build an entry for `resp`, or `None` if it must not be cached.
+    fn from_response(resp: &HttpResponse, policy: &CachePolicy) -> Option<Self> {
+        if resp.status != 200 || resp.body.len() > policy.max_entry_bytes {
+            return None;
+        }
+        let cc = CacheControl::from_headers(&resp.headers);
+        let etag = header(&resp.headers, "etag").map(str::to_owned);
+        // Without a lifetime or a validator the entry could never be used.
+        if cc.no_store || (cc.max_age.is_none() && etag.is_none()) {
+            return None;
+        }
+        Some(Self {
+            status: resp.status,
+            headers: resp.headers.clone(),
+            body: resp.body.clone(),
+            etag,
+            stored_at: SystemTime::now(),
+            fresh_for: cc.freshness(),
+        })
+    }
+
+    fn is_fresh(&self, now: SystemTime) -> bool {
+        now.duration_since(self.stored_at)
+            .is_ok_and(|age| age < self.fresh_for)
+    }
+
+    fn response(&self, status: &str) -> HttpResponse {
+        let mut headers = self.headers.clone();
+        headers.insert(CACHE_STATUS_HEADER.to_owned(), status.to_owned());
+        HttpResponse {
+            status: self.status,
+            headers,
+            body: self.body.clone(),
+        }
+    }
+}
+
+/// Bounded LRU map of cache entries.
+#[cfg(feature = "native")]
+#[derive(Default)]
+struct MemoryCache {
+    entries: HashMap<String, (CacheEntry, u64)>,
+    total_bytes: usize,
+    clock: u64,
+}
+
+#[cfg(feature = "native")]
+impl MemoryCache {
+    fn get(&mut self, key: &str) -> Option<CacheEntry> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(entry, used)| {
+            *used = clock;
+            entry.clone()
+        })
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry, max_total: usize) {
+        self.remove(&key);
+        self.clock += 1;
+        self.total_bytes += entry.body.len();
+        self.entries.insert(key, (entry, self.clock));
+        while self.total_bytes > max_total {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((entry, _)) = self.entries.remove(key) {
+            self.total_bytes -= entry.body.len();
+        }
+    }
+}
+
+/// Cache key for a request. Requests that must not follow redirects get
+/// their own key, since the response they see can differ for the same URL.
+#[cfg(feature = "native")]
+fn cache_key(method: &str, url: &str, no_redirect: bool) -> String {
+    let method = method.to_ascii_uppercase();
+    if no_redirect {
+        format!("{method} {url} no-redirect")
+    } else {
+        format!("{method} {url}")
+    }
+}
+
+/// Whether `headers` carry any of [`CREDENTIAL_HEADERS`].
+#[cfg(feature = "native")]
+fn has_credentials(headers: &HashMap<String, String>) -> bool {
+    CREDENTIAL_HEADERS
+        .iter()
+        .any(|name| header(headers, name).is_some())
+}
+
+/// Remove cache files (`*.json` / `*.body`) from `dir`, oldest first, until
+/// at most `max_bytes` remain. An entry's two files are removed together.
+#[cfg(feature = "native")]
+fn evict_disk(dir: &Path, max_bytes: u64) -> std::io::Result<()> {
+    let mut entries: HashMap<String, (u64, SystemTime)> = HashMap::new();
+    for file in std::fs::read_dir(dir)? {
+        let file = file?;
+        let path = file.path();
+        let (Some(stem), Some("json" | "body")) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|e| e.to_str()),
+        ) else {
+            continue;
+        };
+        let meta = file.metadata()?;
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let slot = entries
+            .entry(stem.to_owned())
+            .or_insert((0, SystemTime::UNIX_EPOCH));
+        slot.0 += meta.len();
+        slot.1 = slot.1.max(modified);
+    }
+    let mut total: u64 = entries.values().map(|(len, _)| len).sum();
+    let mut by_age: Vec<_> = entries.into_iter().collect();
+    by_age.sort_by_key(|(_, (_, modified))| *modified);
+    for (stem, (len, _)) in by_age {
+        if total <= max_bytes {
+            break;
+        }
+        remove_disk_entry(dir, &stem);
+        total -= len;
+    }
+    Ok(())
+}
+
+/// Remove both files of one disk entry, ignoring ones already gone.
+#[cfg(feature = "native")]
+fn remove_disk_entry(dir: &Path, stem: &str) {
+    for ext in ["json", "body"] {
+        let _ = std::fs::remove_file(dir.join(format!("{stem}.{ext}")));
+    }
+}
+
+/// Stable file stem for a cache key (64-bit FNV-1a, hex).
+#[cfg(feature = "native")]
+fn disk_stem(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// [`HttpClient`] decorator that caches `GET` responses.
+#[cfg(feature = "native")]
+pub struct CachingHttpClient<C> {
+    inner: C,
+    policy: CachePolicy,
+    memory: std::sync::Mutex<MemoryCache>,
+}
+
+#[cfg(feature = "native")]
+impl<C: HttpClient> CachingHttpClient<C> {
+    /// Wrap `inner` with `policy`.
+    pub fn new(inner: C, policy: CachePolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            memory: std::sync::Mutex::default(),
+        }
+    }
+
+    /// The cache policy in effect.
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Drop every entry, in memory and on disk.
+    pub fn clear(&self) {
+        *self.memory.lock().expect("http cache lock poisoned") = MemoryCache::default();
+        if let Some(dir) = self.policy.disk_dir.as_ref()
+            && let Err(e) = evict_disk(dir, 0)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::debug!(dir = %dir.display(), error = %e, "failed to clear HTTP cache");
+        }
+    }
+
+    fn remember(&self, key: &str, entry: CacheEntry) {
+        self.memory
+            .lock()
+            .expect("http cache lock poisoned")
+            .insert(key.to_owned(), entry, self.policy.max_total_bytes);
+    }
+
+    async fn lookup(&self, key: &str) -> Option<CacheEntry> {
+        if let Some(entry) = self
+            .memory
+            .lock()
+            .expect("http cache lock poisoned")
+            .get(key)
+        {
+            return Some(entry);
+        }
+        let entry = self.read_disk(key).await?;
+        self.remember(key, entry.clone());
+        Some(entry)
+    }
+
+    async fn store(&self, key: &str, entry: CacheEntry) {
+        self.write_disk(key, &entry).await;
+        self.remember(key, entry);
+    }
+
+    async fn read_disk(&self, key: &str) -> Option<CacheEntry> {
+        let dir = self.policy.disk_dir.as_ref()?;
+        let stem = disk_stem(key);
+        let meta = tokio::fs::read(dir.join(format!("{stem}.json")))
+            .await
+            .ok()?;
+        let (stored_key, mut entry): (String, CacheEntry) = serde_json::from_slice(&meta).ok()?;
+        if stored_key != key {
+            return None;
+        }
+        entry.body = tokio::fs::read(dir.join(format!("{stem}.body")))
+            .await
+            .ok()?;
+        Some(entry)
+    }
+
+    /// Best effort: a cache that cannot be written is just a smaller cache.
+    async fn write_disk(&self, key: &str, entry: &CacheEntry) {
+        let Some(dir) = self.policy.disk_dir.as_ref() else {
+            return;
+        };
+        let stem = disk_stem(key);
+        let result = async {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(dir.join(format!("{stem}.body")), &entry.body).await?;
+            let meta = serde_json::to_vec(&(key, entry)).map_err(std::io::Error::other)?;
+            tokio::fs::write(dir.join(format!("{stem}.json")), meta).await?;
+            let (dir, max) = (dir.clone(), self.policy.max_disk_bytes);
+            tokio::task::spawn_blocking(move || evict_disk(&dir, max))
+                .await
+                .map_err(std::io::Error::other)?
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::debug!(dir = %dir.display(), error = %e, "failed to write HTTP cache entry");
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait::async_trait]
+impl<C: HttpClient> HttpClient for CachingHttpClient<C> {
+    async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let bypass = header(headers, BYPASS_HEADER).is_some();
+        let cacheable = method.eq_ignore_ascii_case("GET") && !bypass && !has_credentials(headers);
+        if !cacheable {
+            let mut resp = if bypass {
+                let mut headers = headers.clone();
+                headers.retain(|k, _| !k.eq_ignore_ascii_case(BYPASS_HEADER));
+                self.inner.request(method, url, &headers, body).await?
+            } else {
+                self.inner.request(method, url, headers, body).await?
+            };
+            if bypass {
+                resp.headers
+                    .insert(CACHE_STATUS_HEADER.to_owned(), "bypass".to_owned());
+            }
+            return Ok(resp);
+        }
+
+        let key = cache_key(method, url, header(headers, NO_REDIRECT_HEADER).is_some());
+        let cached = self.lookup(&key).await;
+        if let Some(ref entry) = cached
+            && entry.is_fresh(SystemTime::now())
+        {
+            return Ok(entry.response("hit"));
+        }
+
+        let mut send_headers = headers.clone();
+        if let Some(etag) = cached.as_ref().and_then(|e| e.etag.as_ref()) {
+            send_headers.insert("If-None-Match".to_owned(), etag.clone());
+        }
+        let resp = self.inner.request(method, url, &send_headers, body).await?;
+
+        if resp.status == 304
+            && let Some(mut entry) = cached
+        {
+            // Headers on a 304 update the stored ones (RFC 9111 §4.3.4).
+            for (name, value) in &resp.headers {
+                entry.headers.retain(|k, _| !k.eq_ignore_ascii_case(name));
+                entry.headers.insert(name.clone(), value.clone());
+            }
+            let cc = CacheControl::from_headers(&entry.headers);
+            entry.fresh_for = cc.freshness();
+            entry.stored_at = SystemTime::now();
+            if let Some(etag) = header(&entry.headers, "etag") {
+                entry.etag = Some(etag.to_owned());
+            }
+            let response = entry.response("revalidated");
+            self.store(&key, entry).await;
+            return Ok(response);
+        }
+
+        match CacheEntry::from_response(&resp, &self.policy) {
+            Some(entry) => self.store(&key, entry).await,
+            None => self
+                .memory
+                .lock()
+                .expect("http cache lock poisoned")
+                .remove(&key),
+        }
+        let mut resp = resp;
+        resp.headers
+            .insert(CACHE_STATUS_HEADER.to_owned(), "miss".to_owned());
+        Ok(resp)
+    }
+
+    /// Streams are passed straight through and never cached.
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<StreamedResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.get_stream(url, headers, on_chunk).await
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::http::NativeHttpClient;
+    use wiremock::matchers::{header as match_header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn cache_control_parsing() {
+        let cc = CacheControl::from_headers(&headers(&[(
+            "Cache-Control",
+            "public, max-age=60, no-cache",
+        )]));
+        assert_eq!(cc.max_age, Some(60));
+        assert!(cc.no_cache);
+        assert!(!cc.no_store);
+        assert_eq!(cc.freshness(), Duration::ZERO);
+
+        let cc = CacheControl::from_headers(&headers(&[("cache-control", "private")]));
+        assert!(cc.no_store);
+        assert_eq!(
+            CacheControl::from_headers(&HashMap::new()),
+            CacheControl::default()
+        );
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let entry = |n: usize| CacheEntry {
+            status: 200,
+            headers: HashMap::new(),
+            body: vec![0; n],
+            etag: None,
+            stored_at: SystemTime::now(),
+            fresh_for: Duration::from_secs(60),
+        };
+        let mut cache = MemoryCache::default();
+        cache.insert("a".into(), entry(4), 10);
+        cache.insert("b".into(), entry(4), 10);
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), entry(4), 10);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert_eq!(cache.total_bytes, 8);
+    }
+
+    #[test]
+    fn uncacheable_responses() {
+        let policy = CachePolicy {
+            max_entry_bytes: 8,
+            ..CachePolicy::default()
+        };
+        let resp = |status, hdrs: &[(&str, &str)], body: &[u8]| HttpResponse {
+            status,
+            headers: headers(hdrs),
+            body: body.to_vec(),
+        };
+        let max_age = [("cache-control", "max-age=60")];
+        assert!(CacheEntry::from_response(&resp(200, &max_age, b"ok"), &policy).is_some());
+        assert!(
+            CacheEntry::from_response(&resp(200, &[("etag", "\"v1\"")], b"ok"), &policy).is_some()
+        );
+        assert!(CacheEntry::from_response(&resp(404, &max_age, b"ok"), &policy).is_none());
+        assert!(CacheEntry::from_response(&resp(200, &max_age, b"too large!"), &policy).is_none());
+        assert!(CacheEntry::from_response(&resp(200, &[], b"ok"), &policy).is_none());
+        let no_store = [("cache-control", "no-store, max-age=60")];
+        assert!(CacheEntry::from_response(&resp(200, &no_store, b"ok"), &policy).is_none());
+    }
+
+    fn client(policy: CachePolicy) -> CachingHttpClient<NativeHttpClient> {
+        CachingHttpClient::new(NativeHttpClient::new(), policy)
+    }
+
+    fn status(resp: &HttpResponse) -> &str {
+        resp.headers[CACHE_STATUS_HEADER].as_str()
+    }
+
+    #[tokio::test]
+    async fn fresh_responses_are_served_from_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_string("[]"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = client(CachePolicy::default());
+        let url = format!("{}/models", server.uri());
+        let first = http.get(&url, &HashMap::new()).await.unwrap();
+        let second = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(status(&first), "miss");
+        assert_eq!(status(&second), "hit");
+        assert_eq!(second.text().unwrap(), "[]");
+    }
+
+    #[tokio::test]
+    async fn stale_entries_revalidate_with_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .and(match_header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("etag", "\"v1\""))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .insert_header("cache-control", "no-cache")
+                    .set_body_string("body v1"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = client(CachePolicy::default());
+        let url = format!("{}/page", server.uri());
+        let first = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(status(&first), "miss");
+        let second = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(second.status, 200);
+        assert_eq!(status(&second), "revalidated");
+        assert_eq!(second.text().unwrap(), "body v1");
+    }
+
+    #[tokio::test]
+    async fn credentialed_bypassed_and_post_requests_are_not_cached() {
+        let server = MockServer::start().await;
+        Mock::given(path("/data"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_string("secret"),
+            )
+            .expect(12)
+            .mount(&server)
+            .await;
+
+        let http = client(CachePolicy::default());
+        let url = format!("{}/data", server.uri());
+        let credentials = [
+            headers(&[("Authorization", "Bearer t")]),
+            headers(&[("X-Api-Key", "k")]),
+            headers(&[("api-key", "k")]),
+            headers(&[("Cookie", "session=1")]),
+        ];
+        let bypass = headers(&[(BYPASS_HEADER, "1")]);
+        for _ in 0..2 {
+            for creds in &credentials {
+                let resp = http.get(&url, creds).await.unwrap();
+                assert!(!resp.headers.contains_key(CACHE_STATUS_HEADER));
+            }
+            let resp = http.get(&url, &bypass).await.unwrap();
+            assert_eq!(status(&resp), "bypass");
+            http.post(&url, &HashMap::new(), b"{}").await.unwrap();
+        }
+        // Nothing was stored along the way, so a plain GET still misses.
+        assert!(http.memory.lock().unwrap().entries.is_empty());
+
+        let received = server.received_requests().await.unwrap();
+        assert!(
+            received
+                .iter()
+                .all(|r| !r.headers.contains_key(BYPASS_HEADER))
+        );
+    }
+
+    #[tokio::test]
+    async fn followed_redirect_is_not_served_to_no_redirect_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/start"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/internal"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/internal"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_string("internal"),
+            )
+            .mount(&server)
+            .await;
+
+        let http = client(CachePolicy::default());
+        let url = format!("{}/start", server.uri());
+        let followed = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(followed.status, 200);
+        assert_eq!(status(&followed), "miss");
+        let again = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(status(&again), "hit");
+
+        let no_redirect = headers(&[(NO_REDIRECT_HEADER, "1")]);
+        let resp = http.get(&url, &no_redirect).await.unwrap();
+        assert_eq!(resp.status, 302);
+        assert_eq!(resp.headers["location"], "/internal");
+        assert_eq!(status(&resp), "miss");
+    }
+
+    #[tokio::test]
+    async fn no_redirect_requests_are_cached_under_their_own_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_string("page"),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let http = client(CachePolicy::default());
+        let url = format!("{}/page", server.uri());
+        let no_redirect = headers(&[(NO_REDIRECT_HEADER, "1")]);
+        let first = http.get(&url, &no_redirect).await.unwrap();
+        let second = http.get(&url, &no_redirect).await.unwrap();
+        assert_eq!(status(&first), "miss");
+        assert_eq!(status(&second), "hit");
+        // A plain GET does not share the no-redirect entry.
+        let plain = http.get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(status(&plain), "miss");
+    }
+
+    #[tokio::test]
+    async fn disk_cache_survives_a_new_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_bytes(vec![0u8, 159, 146, 150]),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = std::env::temp_dir().join(format!("clawft-http-cache-{}", std::process::id()));
+        let policy = CachePolicy {
+            disk_dir: Some(dir.clone()),
+            ..CachePolicy::default()
+        };
+        let url = format!("{}/bin", server.uri());
+        client(policy.clone())
+            .get(&url, &HashMap::new())
+            .await
+            .unwrap();
+        let resp = client(policy).get(&url, &HashMap::new()).await.unwrap();
+        assert_eq!(status(&resp), "hit");
+        assert_eq!(resp.body, vec![0u8, 159, 146, 150]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn disk_cache_is_bounded_and_cleared() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=300")
+                    .set_body_bytes(vec![7u8; 1024]),
+            )
+            .mount(&server)
+            .await;
+
+        let dir =
+            std::env::temp_dir().join(format!("clawft-http-cache-bounded-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let policy = CachePolicy {
+            disk_dir: Some(dir.clone()),
+            max_disk_bytes: 4096,
+            ..CachePolicy::default()
+        };
+        let http = client(policy);
+        for i in 0..8 {
+            let url = format!("{}/item/{i}", server.uri());
+            http.get(&url, &HashMap::new()).await.unwrap();
+        }
+        let on_disk: u64 = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|f| f.unwrap().metadata().unwrap().len())
+            .sum();
+        assert!(on_disk <= 4096, "disk cache holds {on_disk} bytes");
+
+        http.clear();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(http.memory.lock().unwrap().entries.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
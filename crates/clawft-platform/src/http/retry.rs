@@ -0,0 +1,505 @@
+//! Retrying decorator for [`HttpClient`].
+//!
+//! [`RetryingHttpClient`] wraps any client and re-sends requests that fail
+//! transiently: connection-level errors and configurable status codes
+//! (by default 429, 502, 503 and 504). Delays grow exponentially with
+//! full jitter and honor a server-sent `Retry-After` header. The number
+//! of attempts a response took is exposed via [`HttpResponse::attempts`].
+//!
+//! Only idempotent requests are retried by default (see [`is_idempotent`]):
+//! a `POST` that timed out may already have been processed, and sending it
+//! again would repeat its side effect. Set
+//! [`RetryPolicy::retry_non_idempotent`] to retry every method.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::HttpResponse;
+#[cfg(feature = "native")]
+use super::{ChunkSink, HttpClient, StreamedResponse};
+
+/// Response header carrying the number of attempts a request took.
+pub const ATTEMPTS_HEADER: &str = "x-clawft-attempts";
+
+/// Request header marking a `PUT` as safe to replay.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// When and how often [`RetryingHttpClient`] retries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first (1 disables retries).
+    pub max_attempts: u32,
+    /// Backoff ceiling before the first retry; doubles on each retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay, including `Retry-After`.
+    pub max_backoff: Duration,
+    /// Status codes that trigger a retry.
+    pub retry_statuses: Vec<u16>,
+    /// Retry connection failures and timeouts.
+    pub retry_transport_errors: bool,
+    /// Randomize delays between zero and the backoff ceiling.
+    pub jitter: bool,
+    /// Also retry methods that are not idempotent (`POST`, `PATCH`,
+    /// `DELETE`, `PUT` without an idempotency key).
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_transport_errors: true,
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff ceiling after `attempt` failed attempts (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Delay before the next attempt, given the `Retry-After` hint if any.
+    ///
+    /// A server hint wins over computed backoff but is still capped at
+    /// [`max_backoff`](Self::max_backoff). `unit` is a value in `[0, 1)`
+    /// used for jitter.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>, unit: f64) -> Duration {
+        if let Some(hint) = retry_after {
+            return hint.min(self.max_backoff);
+        }
+        let ceiling = self.backoff(attempt);
+        if self.jitter {
+            ceiling.mul_f64(unit.clamp(0.0, 1.0))
+        } else {
+            ceiling
+        }
+    }
+}
+
+/// Parse a `Retry-After` header (delta-seconds or HTTP-date).
+pub fn retry_after(headers: &HashMap<String, String>) -> Option<Duration> {
+    let value = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("retry-after"))
+        .map(|(_, v)| v.trim())?;
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    #[cfg(feature = "native")]
+    if let Ok(at) = chrono::DateTime::parse_from_rfc2822(value) {
+        let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+        return Some(wait.to_std().unwrap_or(Duration::ZERO));
+    }
+    None
+}
+
+/// Whether a request can be sent again without repeating a side effect:
+/// `GET`, `HEAD` and `OPTIONS`, or a `PUT` carrying an
+/// [`Idempotency-Key`](IDEMPOTENCY_KEY_HEADER).
+pub fn is_idempotent(method: &str, headers: &HashMap<String, String>) -> bool {
+    match method.to_ascii_uppercase().as_str() {
+        "GET" | "HEAD" | "OPTIONS" => true,
+        "PUT" => headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case(IDEMPOTENCY_KEY_HEADER)),
+        _ => false,
+    }
+}
+
+impl HttpResponse {
+    /// Attempts a request took, as recorded by [`RetryingHttpClient`]
+    /// (1 for clients that do not retry).
+    pub fn attempts(&self) -> u32 {
+        self.headers
+            .get(ATTEMPTS_HEADER)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1)
+    }
+}
+
+/// Whether `err` is a connection-level failure worth retrying.
+#[cfg(feature = "native")]
+fn is_transient(err: &(dyn std::error::Error + 'static)) -> bool {
+    use std::io::ErrorKind;
+
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(e) = e.downcast_ref::<reqwest::Error>()
+            && (e.is_connect() || e.is_timeout())
+        {
+            return true;
+        }
+        if let Some(e) = e.downcast_ref::<std::io::Error>()
+            && matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+            )
+        {
+            return true;
+        }
+        current = e.source();
+    }
+    false
+}
+
+/// [`HttpClient`] decorator that retries transient failures.
+#[cfg(feature = "native")]
+pub struct RetryingHttpClient<C> {
+    inner: C,
+    policy: RetryPolicy,
+}
+
+#[cfg(feature = "native")]
+impl<C: HttpClient> RetryingHttpClient<C> {
+    /// Wrap `inner` with `policy`.
+    pub fn new(inner: C, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The retry policy in effect.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// The wrapped client.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "native")]
+impl RetryingHttpClient<super::NativeHttpClient> {
+    /// Build a native client from `config`, retrying per `config.retry`.
+    pub fn from_config(
+        config: &super::HttpClientConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self::new(
+            super::NativeHttpClient::with_config(config)?,
+            config.retry.clone(),
+        ))
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait::async_trait]
+impl<C: HttpClient> HttpClient for RetryingHttpClient<C> {
+    async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let retryable = self.policy.retry_non_idempotent || is_idempotent(method, headers);
+        let mut attempt = 1;
+        loop {
+            let result = self.inner.request(method, url, headers, body).await;
+            let retry_in = match result {
+                _ if !retryable => None,
+                Ok(ref resp) if self.policy.retry_statuses.contains(&resp.status) => Some(
+                    self.policy
+                        .delay(attempt, retry_after(&resp.headers), rand::random()),
+                ),
+                Err(ref e) if self.policy.retry_transport_errors && is_transient(e.as_ref()) => {
+                    Some(self.policy.delay(attempt, None, rand::random()))
+                }
+                _ => None,
+            };
+            match retry_in {
+                Some(delay) if attempt < self.policy.max_attempts => {
+                    tracing::debug!(
+                        method,
+                        url,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        "retrying HTTP request"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => {
+                    return result.map(|mut resp| {
+                        resp.headers
+                            .insert(ATTEMPTS_HEADER.to_owned(), attempt.to_string());
+                        resp
+                    });
+                }
+            }
+        }
+    }
+
+    /// Streams are passed straight through: once chunks have been
+    /// delivered a transfer cannot be replayed transparently.
+    async fn get_stream(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        on_chunk: &mut ChunkSink<'_>,
+    ) -> Result<StreamedResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.inner.get_stream(url, headers, on_chunk).await
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Replays a fixed script of outcomes, then answers 200.
+    struct ScriptedClient {
+        script: Mutex<VecDeque<Result<HttpResponse, std::io::ErrorKind>>>,
+        calls: Mutex<u32>,
+    }
+
+    impl ScriptedClient {
+        fn new(script: Vec<Result<HttpResponse, std::io::ErrorKind>>) -> Self {
+            Self {
+                script: Mutex::new(script.into()),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn fail_then_succeed(failures: usize, status: u16) -> Self {
+            Self::new((0..failures).map(|_| Ok(response(status, &[]))).collect())
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpClient for ScriptedClient {
+        async fn request(
+            &self,
+            _method: &str,
+            _url: &str,
+            _headers: &HashMap<String, String>,
+            _body: Option<&[u8]>,
+        ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+            *self.calls.lock().unwrap() += 1;
+            match self.script.lock().unwrap().pop_front() {
+                Some(Ok(resp)) => Ok(resp),
+                Some(Err(kind)) => Err(Box::new(std::io::Error::from(kind))),
+                None => Ok(response(200, &[])),
+            }
+        }
+    }
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: vec![],
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let client =
+            RetryingHttpClient::new(ScriptedClient::fail_then_succeed(2, 503), fast_policy(3));
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.attempts(), 3);
+        assert_eq!(client.inner().calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let client =
+            RetryingHttpClient::new(ScriptedClient::fail_then_succeed(5, 429), fast_policy(3));
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.status, 429);
+        assert_eq!(resp.attempts(), 3);
+        assert_eq!(client.inner().calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn non_retryable_status_returns_immediately() {
+        let client =
+            RetryingHttpClient::new(ScriptedClient::fail_then_succeed(1, 404), fast_policy(3));
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.status, 404);
+        assert_eq!(resp.attempts(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_connection_errors_only() {
+        let refused = ScriptedClient::new(vec![Err(std::io::ErrorKind::ConnectionRefused)]);
+        let client = RetryingHttpClient::new(refused, fast_policy(3));
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.attempts(), 2);
+
+        let denied = ScriptedClient::new(vec![Err(std::io::ErrorKind::PermissionDenied)]);
+        let client = RetryingHttpClient::new(denied, fast_policy(3));
+        assert!(client.get("http://x", &HashMap::new()).await.is_err());
+        assert_eq!(client.inner().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn single_attempt_policy_disables_retries() {
+        let client =
+            RetryingHttpClient::new(ScriptedClient::fail_then_succeed(1, 503), fast_policy(1));
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.status, 503);
+        assert_eq!(client.inner().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn honors_retry_after_header() {
+        let script = vec![Ok(response(429, &[("Retry-After", "0")]))];
+        let policy = RetryPolicy {
+            // Computed backoff would stall the test; the header overrides it.
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(60),
+            ..fast_policy(2)
+        };
+        let client = RetryingHttpClient::new(ScriptedClient::new(script), policy);
+        let started = std::time::Instant::now();
+        let resp = client.get("http://x", &HashMap::new()).await.unwrap();
+        assert_eq!(resp.attempts(), 2);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn post_timeout_is_not_retried() {
+        let timed_out = ScriptedClient::new(vec![Err(std::io::ErrorKind::TimedOut)]);
+        let client = RetryingHttpClient::new(timed_out, fast_policy(3));
+        assert!(
+            client
+                .post("http://x", &HashMap::new(), b"{}")
+                .await
+                .is_err()
+        );
+        assert_eq!(client.inner().calls(), 1);
+
+        let unavailable = ScriptedClient::fail_then_succeed(1, 503);
+        let client = RetryingHttpClient::new(unavailable, fast_policy(3));
+        let resp = client
+            .post("http://x", &HashMap::new(), b"{}")
+            .await
+            .unwrap();
+        assert_eq!(resp.status, 503);
+        assert_eq!(client.inner().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_retries_are_opt_in() {
+        let timed_out = ScriptedClient::new(vec![Err(std::io::ErrorKind::TimedOut)]);
+        let policy = RetryPolicy {
+            retry_non_idempotent: true,
+            ..fast_policy(3)
+        };
+        let client = RetryingHttpClient::new(timed_out, policy);
+        let resp = client
+            .post("http://x", &HashMap::new(), b"{}")
+            .await
+            .unwrap();
+        assert_eq!(resp.attempts(), 2);
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        let none = HashMap::new();
+        let keyed = HashMap::from([("Idempotency-Key".to_owned(), "k1".to_owned())]);
+        for method in ["GET", "head", "OPTIONS"] {
+            assert!(is_idempotent(method, &none), "{method}");
+        }
+        assert!(is_idempotent("PUT", &keyed));
+        assert!(!is_idempotent("PUT", &none));
+        for method in ["POST", "PATCH", "DELETE"] {
+            assert!(!is_idempotent(method, &none), "{method}");
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        let delays: Vec<_> = (1..=5).map(|a| policy.delay(a, None, 0.5)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 500, 500]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn jitter_scales_within_ceiling() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay(2, None, 0.0), Duration::ZERO);
+        assert_eq!(policy.delay(2, None, 0.5), Duration::from_millis(100));
+        assert!(policy.delay(2, None, 0.999) < Duration::from_millis(200));
+    }
+
+    #[test]
+    fn retry_after_is_capped() {
+        let policy = RetryPolicy::default();
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(3600)), 0.5),
+            policy.max_backoff
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_forms() {
+        let headers = |v: &str| HashMap::from([("retry-after".to_owned(), v.to_owned())]);
+        assert_eq!(retry_after(&headers("7")), Some(Duration::from_secs(7)));
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers("soon")), None);
+        assert_eq!(retry_after(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn from_config_uses_retry_policy() {
+        let config = super::super::HttpClientConfig {
+            retry: fast_policy(5),
+            ..Default::default()
+        };
+        let client = RetryingHttpClient::from_config(&config).unwrap();
+        assert_eq!(client.policy().max_attempts, 5);
+    }
+
+    #[test]
+    fn attempts_defaults_to_one() {
+        assert_eq!(response(200, &[]).attempts(), 1);
+    }
+}
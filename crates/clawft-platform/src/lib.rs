@@ -49,11 +49,17 @@
 //!
 //! Source: <https://github.com/weave-logic-ai/weftos>
 
+#[cfg(feature = "native")]
+pub mod archive;
 pub mod config_loader;
 pub mod env;
 pub mod fs;
 pub mod http;
 pub mod process;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "native")]
+pub mod watch;
 
 #[cfg(feature = "browser")]
 pub mod browser;
@@ -89,34 +95,70 @@ pub trait Platform: Send + Sync {
     /// Returns `None` in environments where process spawning is unavailable
     /// (e.g., WASM).
     fn process(&self) -> Option<&dyn process::ProcessSpawner>;
+
+    /// File change notifications, when the platform supports them.
+    #[cfg(feature = "native")]
+    fn watcher(&self) -> Option<&dyn watch::FileWatcher> {
+        None
+    }
 }
 
 /// Native platform implementation using std, tokio, and reqwest.
 ///
 /// This is the standard platform for server-side and CLI usage. It provides:
-/// - HTTP via [`reqwest`] with connection pooling and TLS.
+/// - HTTP via [`reqwest`] with connection pooling and TLS, behind an
+///   in-memory [`http::CachingHttpClient`] for cacheable `GET` responses.
 /// - Filesystem via [`tokio::fs`].
-/// - Environment via [`std::env`].
+/// - Environment via [`std::env`], layered under values from
+///   `~/.clawft/.env` (see [`config_loader::load_dotenv`]).
 /// - Process spawning via [`tokio::process`].
 #[cfg(feature = "native")]
 pub struct NativePlatform {
-    http: http::NativeHttpClient,
+    http: http::CachingHttpClient<http::NativeHttpClient>,
     fs: fs::NativeFileSystem,
-    env: env::NativeEnvironment,
+    env: env::LayeredEnvironment<env::NativeEnvironment>,
     process: process::NativeProcessSpawner,
+    watcher: watch::NativeFileWatcher,
 }
 
 #[cfg(feature = "native")]
 impl NativePlatform {
     /// Create a new native platform with default configuration.
+    ///
+    /// Variables from `~/.clawft/.env` are loaded into the platform
+    /// environment (never into the process environment); a malformed file
+    /// is logged and skipped.
     pub fn new() -> Self {
+        let env = env::LayeredEnvironment::new(env::NativeEnvironment);
+        if let Some(path) = config_loader::dotenv_path(dirs::home_dir())
+            && let Err(e) = config_loader::load_dotenv(&path, &env)
+        {
+            tracing::warn!(path = %path.display(), error = %e, "ignoring unreadable .env file");
+        }
         Self {
-            http: http::NativeHttpClient::new(),
+            http: http::CachingHttpClient::new(
+                http::NativeHttpClient::new(),
+                http::CachePolicy::default(),
+            ),
             fs: fs::NativeFileSystem,
-            env: env::NativeEnvironment,
+            env,
             process: process::NativeProcessSpawner,
+            watcher: watch::NativeFileWatcher::new(),
         }
     }
+
+    /// Create a native platform whose HTTP client uses `config`.
+    pub fn with_http_config(
+        config: &http::HttpClientConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            http: http::CachingHttpClient::new(
+                http::NativeHttpClient::with_config(config)?,
+                http::CachePolicy::default(),
+            ),
+            ..Self::new()
+        })
+    }
 }
 
 #[cfg(feature = "native")]
@@ -144,6 +186,10 @@ impl Platform for NativePlatform {
     fn process(&self) -> Option<&dyn process::ProcessSpawner> {
         Some(&self.process)
     }
+
+    fn watcher(&self) -> Option<&dyn watch::FileWatcher> {
+        Some(&self.watcher)
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +204,7 @@ mod tests {
         let _fs = platform.fs();
         let _env = platform.env();
         assert!(platform.process().is_some());
+        assert!(platform.watcher().is_some());
     }
 
     #[test]
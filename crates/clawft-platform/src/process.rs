@@ -4,10 +4,20 @@
 //! commands. The native implementation uses [`tokio::process`]. This capability
 //! is unavailable in WASM environments, which is why [`super::Platform::process`]
 //! returns `Option`.
+//!
+//! Besides run-and-collect via [`ProcessSpawner::run`], long-running commands
+//! can be driven through [`ProcessSpawner::spawn_streaming`], which returns a
+//! [`ProcessHandle`] for reading output line by line, feeding stdin, and
+//! killing the process. Native children are placed in their own process
+//! group so a kill (or timeout) takes down everything they started, not just
+//! the direct child.
 
 use async_trait::async_trait;
 use std::path::Path;
 
+/// Boxed error type used throughout this module.
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Result of running an external process.
 #[derive(Debug, Clone)]
 pub struct ProcessOutput {
@@ -19,6 +29,77 @@ pub struct ProcessOutput {
     pub stderr: String,
 }
 
+/// A single line of output from a streaming process.
+///
+/// The trailing newline (and any `\r` before it) is stripped; invalid UTF-8
+/// is replaced lossily.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputLine {
+    /// A line written to standard output.
+    Stdout(String),
+    /// A line written to standard error.
+    Stderr(String),
+}
+
+/// Handle to a process started with [`ProcessSpawner::spawn_streaming`].
+///
+/// Output should be drained with [`next_line`](ProcessHandle::next_line)
+/// until it returns `None` before calling [`wait`](ProcessHandle::wait);
+/// otherwise a chatty process can block on a full pipe until its timeout.
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+pub trait ProcessHandle: Send {
+    /// OS process id of the direct child, if still known.
+    fn id(&self) -> Option<u32>;
+
+    /// Next line of stdout or stderr, in arrival order.
+    ///
+    /// Returns `None` once both streams are closed, which happens when the
+    /// process (and anything it started that inherited the pipes) exits or
+    /// is killed.
+    async fn next_line(&mut self) -> Option<OutputLine>;
+
+    /// Write bytes to the process's standard input.
+    async fn write_stdin(&mut self, data: &[u8]) -> Result<(), BoxError>;
+
+    /// Close standard input so the process sees end-of-file.
+    fn close_stdin(&mut self);
+
+    /// Kill the process and every process in its group, then reap it.
+    async fn kill(&mut self) -> Result<(), BoxError>;
+
+    /// Wait for the process to exit and return its exit code.
+    ///
+    /// Returns an error if the process was killed by the spawn timeout.
+    async fn wait(&mut self) -> Result<i32, BoxError>;
+
+    /// Whether the spawn timeout fired and killed the process.
+    fn timed_out(&self) -> bool;
+}
+
+/// Drain a streaming process's output and wait for it to exit.
+///
+/// Lines are re-joined with `\n`, so the result matches what
+/// [`ProcessSpawner::run`] would capture for newline-terminated output.
+pub async fn collect_output(process: &mut dyn ProcessHandle) -> Result<ProcessOutput, BoxError> {
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    while let Some(line) = process.next_line().await {
+        let (buf, line) = match line {
+            OutputLine::Stdout(line) => (&mut stdout, line),
+            OutputLine::Stderr(line) => (&mut stderr, line),
+        };
+        buf.push_str(&line);
+        buf.push('\n');
+    }
+    let exit_code = process.wait().await?;
+    Ok(ProcessOutput {
+        exit_code,
+        stdout,
+        stderr,
+    })
+}
+
 /// Platform-agnostic process spawner.
 ///
 /// Implementations run external commands and capture their output.
@@ -42,6 +123,25 @@ pub trait ProcessSpawner: Send + Sync {
         working_dir: Option<&Path>,
         timeout_secs: Option<u64>,
     ) -> Result<ProcessOutput, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Start a command and return a handle for streaming its output.
+    ///
+    /// Stdout, stderr and stdin are all piped. If `timeout_secs` is set, the
+    /// process and everything it spawned are killed once it elapses, whether
+    /// or not the handle is still being polled. Dropping the handle also
+    /// kills the process group.
+    ///
+    /// The default implementation reports that streaming is unsupported.
+    async fn spawn_streaming(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        timeout_secs: Option<u64>,
+    ) -> Result<Box<dyn ProcessHandle>, BoxError> {
+        let _ = (args, working_dir, timeout_secs);
+        Err(format!("cannot stream '{command}': streaming processes are not supported").into())
+    }
 }
 
 /// Native process spawner using [`tokio::process`].
@@ -66,14 +166,20 @@ impl ProcessSpawner for NativeProcessSpawner {
         if let Some(dir) = working_dir {
             cmd.current_dir(dir);
         }
+        isolate_group(&mut cmd);
+        cmd.kill_on_drop(true);
 
         let child = cmd.spawn()?;
+        let pid = child.id();
 
         let output = if let Some(secs) = timeout_secs {
             let timeout = std::time::Duration::from_secs(secs);
             match tokio::time::timeout(timeout, child.wait_with_output()).await {
                 Ok(result) => result?,
                 Err(_) => {
+                    if let Some(pid) = pid {
+                        let _ = kill_group(pid);
+                    }
                     return Err(format!("process '{}' timed out after {}s", command, secs).into());
                 }
             }
@@ -91,6 +197,227 @@ impl ProcessSpawner for NativeProcessSpawner {
             stderr,
         })
     }
+
+    async fn spawn_streaming(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        timeout_secs: Option<u64>,
+    ) -> Result<Box<dyn ProcessHandle>, BoxError> {
+        use std::process::Stdio;
+
+        let mut cmd = tokio::process::Command::new(command);
+        cmd.args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = working_dir {
+            cmd.current_dir(dir);
+        }
+        isolate_group(&mut cmd);
+
+        let mut child = cmd.spawn()?;
+        let pid = child
+            .id()
+            .ok_or_else(|| format!("process '{command}' exited before it could be tracked"))?;
+
+        let (tx, lines) = tokio::sync::mpsc::channel(LINE_BUFFER);
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, tx.clone(), OutputLine::Stdout);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, tx, OutputLine::Stderr);
+        }
+
+        let timed_out = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let watchdog = timeout_secs.map(|secs| {
+            let timed_out = timed_out.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+                timed_out.store(true, std::sync::atomic::Ordering::SeqCst);
+                let _ = kill_group(pid);
+            })
+        });
+
+        Ok(Box::new(NativeProcessHandle {
+            stdin: child.stdin.take(),
+            child,
+            pid,
+            lines,
+            watchdog,
+            timed_out,
+            command: command.to_string(),
+            timeout_secs,
+        }))
+    }
+}
+
+/// Lines buffered per process before the reader tasks apply backpressure.
+#[cfg(feature = "native")]
+const LINE_BUFFER: usize = 256;
+
+/// Put the child in a new process group so the whole tree can be signalled.
+#[cfg(feature = "native")]
+fn isolate_group(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(windows)]
+    {
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+    #[cfg(not(any(unix, windows)))]
+    let _ = cmd;
+}
+
+/// Kill every process in the group led by `pid`.
+///
+/// A group that no longer exists is not an error.
+#[cfg(all(feature = "native", unix))]
+fn kill_group(pid: u32) -> std::io::Result<()> {
+    use nix::sys::signal::{Signal, killpg};
+    use nix::unistd::Pid;
+
+    match killpg(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+        Ok(()) | Err(nix::errno::Errno::ESRCH) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Kill the process tree rooted at `pid`.
+#[cfg(all(feature = "native", windows))]
+fn kill_group(pid: u32) -> std::io::Result<()> {
+    std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|_| ())
+}
+
+#[cfg(all(feature = "native", not(any(unix, windows))))]
+fn kill_group(_pid: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Forward lines from `reader` into `tx` until EOF or the receiver is gone.
+#[cfg(feature = "native")]
+fn forward_lines<R>(
+    reader: R,
+    tx: tokio::sync::mpsc::Sender<OutputLine>,
+    wrap: fn(String) -> OutputLine,
+) where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    tokio::spawn(async move {
+        let mut reader = tokio::io::BufReader::new(reader);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if buf.last() == Some(&b'\n') {
+                        buf.pop();
+                        if buf.last() == Some(&b'\r') {
+                            buf.pop();
+                        }
+                    }
+                    let line = String::from_utf8_lossy(&buf).into_owned();
+                    if tx.send(wrap(line)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Native [`ProcessHandle`] backed by a [`tokio::process::Child`].
+///
+/// Dropping the handle kills the child's whole process group.
+#[cfg(feature = "native")]
+pub struct NativeProcessHandle {
+    child: tokio::process::Child,
+    pid: u32,
+    stdin: Option<tokio::process::ChildStdin>,
+    lines: tokio::sync::mpsc::Receiver<OutputLine>,
+    watchdog: Option<tokio::task::JoinHandle<()>>,
+    timed_out: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    command: String,
+    timeout_secs: Option<u64>,
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl ProcessHandle for NativeProcessHandle {
+    fn id(&self) -> Option<u32> {
+        Some(self.pid)
+    }
+
+    async fn next_line(&mut self) -> Option<OutputLine> {
+        self.lines.recv().await
+    }
+
+    async fn write_stdin(&mut self, data: &[u8]) -> Result<(), BoxError> {
+        use tokio::io::AsyncWriteExt;
+
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| format!("stdin of '{}' is closed", self.command))?;
+        stdin.write_all(data).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    async fn kill(&mut self) -> Result<(), BoxError> {
+        kill_group(self.pid)?;
+        // Covers platforms without group support and a child that left
+        // its group.
+        let _ = self.child.start_kill();
+        self.child.wait().await?;
+        Ok(())
+    }
+
+    async fn wait(&mut self) -> Result<i32, BoxError> {
+        let status = self.child.wait().await?;
+        if let Some(watchdog) = self.watchdog.take() {
+            watchdog.abort();
+        }
+        if self.timed_out() {
+            return Err(format!(
+                "process '{}' timed out after {}s",
+                self.command,
+                self.timeout_secs.unwrap_or_default()
+            )
+            .into());
+        }
+        Ok(status.code().unwrap_or(-1))
+    }
+
+    fn timed_out(&self) -> bool {
+        self.timed_out.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(feature = "native")]
+impl Drop for NativeProcessHandle {
+    fn drop(&mut self) {
+        if let Some(watchdog) = self.watchdog.take() {
+            watchdog.abort();
+        }
+        // Reap anything the child left running in its group.
+        let _ = kill_group(self.pid);
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +483,183 @@ mod tests {
         assert_eq!(output.exit_code, 42);
     }
 
+    /// Whether `pid` is a live (non-zombie) process.
+    #[cfg(target_os = "linux")]
+    fn alive(pid: u32) -> bool {
+        match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+            // The state field follows the parenthesised command name.
+            Ok(stat) => stat
+                .rsplit_once(')')
+                .map(|(_, rest)| !rest.trim_start().starts_with('Z'))
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    async fn wait_dead(pid: u32) -> bool {
+        for _ in 0..50 {
+            if !alive(pid) {
+                return true;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_streaming_lines_arrive_before_exit() {
+        let spawner = NativeProcessSpawner;
+        let mut process = spawner
+            .spawn_streaming(
+                "sh",
+                &["-c", "echo first; echo oops >&2; sleep 30; echo second"],
+                None,
+                Some(60),
+            )
+            .await
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            let line = tokio::time::timeout(std::time::Duration::from_secs(5), process.next_line())
+                .await
+                .expect("line should arrive while the process is still running")
+                .unwrap();
+            seen.push(line);
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        assert!(seen.contains(&OutputLine::Stdout("first".into())));
+        assert!(seen.contains(&OutputLine::Stderr("oops".into())));
+
+        process.kill().await.unwrap();
+        assert!(process.next_line().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_stdin_round_trip() {
+        let spawner = NativeProcessSpawner;
+        let mut process = spawner
+            .spawn_streaming("cat", &[], None, Some(10))
+            .await
+            .unwrap();
+
+        process.write_stdin(b"hello\r\n").await.unwrap();
+        assert_eq!(
+            process.next_line().await,
+            Some(OutputLine::Stdout("hello".into()))
+        );
+        process.close_stdin();
+        assert!(process.write_stdin(b"late").await.is_err());
+
+        let output = collect_output(process.as_mut()).await.unwrap();
+        assert_eq!(output.exit_code, 0);
+        assert!(!process.timed_out());
+    }
+
+    #[tokio::test]
+    async fn test_collect_output_matches_run() {
+        let spawner = NativeProcessSpawner;
+        let script = "echo one; echo two; echo err >&2; exit 3";
+        let mut process = spawner
+            .spawn_streaming("sh", &["-c", script], None, Some(10))
+            .await
+            .unwrap();
+        let streamed = collect_output(process.as_mut()).await.unwrap();
+        let ran = spawner
+            .run("sh", &["-c", script], None, Some(10))
+            .await
+            .unwrap();
+
+        assert_eq!(streamed.exit_code, 3);
+        assert_eq!(streamed.exit_code, ran.exit_code);
+        assert_eq!(streamed.stdout, ran.stdout);
+        assert_eq!(streamed.stderr, ran.stderr);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kill_takes_down_grandchildren() {
+        let spawner = NativeProcessSpawner;
+        let mut process = spawner
+            .spawn_streaming("sh", &["-c", "sleep 60 & echo $!; wait"], None, None)
+            .await
+            .unwrap();
+        let child = process.id().unwrap();
+        let grandchild: u32 = match process.next_line().await {
+            Some(OutputLine::Stdout(pid)) => pid.trim().parse().unwrap(),
+            other => panic!("expected grandchild pid, got {other:?}"),
+        };
+        assert!(alive(grandchild));
+
+        process.kill().await.unwrap();
+
+        assert!(wait_dead(child).await, "child {child} survived kill");
+        assert!(
+            wait_dead(grandchild).await,
+            "grandchild {grandchild} survived kill"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_streaming_timeout_cleans_up_group() {
+        let spawner = NativeProcessSpawner;
+        let mut process = spawner
+            .spawn_streaming("sh", &["-c", "sleep 60 & echo $!; wait"], None, Some(1))
+            .await
+            .unwrap();
+        let grandchild: u32 = match process.next_line().await {
+            Some(OutputLine::Stdout(pid)) => pid.trim().parse().unwrap(),
+            other => panic!("expected grandchild pid, got {other:?}"),
+        };
+
+        // Output ends once the watchdog kills the group.
+        assert!(process.next_line().await.is_none());
+        let err = process.wait().await.unwrap_err().to_string();
+        assert!(err.contains("timed out"), "unexpected error: {err}");
+        assert!(process.timed_out());
+        assert!(wait_dead(grandchild).await);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_dropping_handle_kills_group() {
+        let spawner = NativeProcessSpawner;
+        let mut process = spawner
+            .spawn_streaming("sh", &["-c", "sleep 60 & echo $!; wait"], None, None)
+            .await
+            .unwrap();
+        let grandchild: u32 = match process.next_line().await {
+            Some(OutputLine::Stdout(pid)) => pid.trim().parse().unwrap(),
+            other => panic!("expected grandchild pid, got {other:?}"),
+        };
+        drop(process);
+        assert!(wait_dead(grandchild).await);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_run_timeout_kills_grandchildren() {
+        let dir = std::env::temp_dir().join(format!("clawft-run-timeout-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pid_file = dir.join("pid");
+        let script = format!("sleep 60 & echo $! > {}; wait", pid_file.display());
+
+        let spawner = NativeProcessSpawner;
+        let result = spawner.run("sh", &["-c", &script], None, Some(1)).await;
+        assert!(result.is_err());
+
+        let grandchild: u32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        assert!(wait_dead(grandchild).await);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[tokio::test]
     async fn test_run_timeout() {
         let spawner = NativeProcessSpawner;
@@ -0,0 +1,223 @@
+//! Test doubles for the platform traits.
+//!
+//! Enabled by the `test-util` feature, for use from other crates'
+//! `[dev-dependencies]`:
+//!
+//! ```toml
+//! clawft-platform = { workspace = true, features = ["test-util"] }
+//! ```
+//!
+//! [`MockPlatform`] bundles one double per capability, each reachable as a
+//! public field so tests can script it and inspect what happened:
+//!
+//! - [`MockHttpClient`]: URL pattern -> queue of canned responses.
+//! - [`MemoryFileSystem`]: an in-memory tree that can be seeded up front.
+//! - [`MemoryEnvironment`]: variables in a map, never the process env.
+//! - [`FakeProcessSpawner`]: canned output per command line.
+//!
+//! [`plugin`] adds an in-memory `KeyValueStore` and `ToolContext` for
+//! plugin tool tests.
+
+pub mod fs;
+pub mod http;
+pub mod plugin;
+pub mod process;
+
+pub use fs::MemoryFileSystem;
+pub use http::{MockHttpClient, MockResponse, RecordedRequest};
+pub use plugin::{MockKvStore, MockToolContext};
+pub use process::{FakeProcessSpawner, RecordedCall};
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+
+use crate::Platform;
+use crate::env::Environment;
+
+/// An [`Environment`] backed only by an in-memory map.
+#[derive(Debug, Default)]
+pub struct MemoryEnvironment {
+    vars: RwLock<HashMap<String, String>>,
+}
+
+impl MemoryEnvironment {
+    /// Create an empty environment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an environment holding `vars`.
+    pub fn with_vars<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            vars: RwLock::new(
+                vars.into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Environment for MemoryEnvironment {
+    fn get_var(&self, name: &str) -> Option<String> {
+        self.vars
+            .read()
+            .expect("env lock poisoned")
+            .get(name)
+            .cloned()
+    }
+
+    fn set_var(&self, name: &str, value: &str) {
+        self.vars
+            .write()
+            .expect("env lock poisoned")
+            .insert(name.to_string(), value.to_string());
+    }
+
+    fn remove_var(&self, name: &str) {
+        self.vars.write().expect("env lock poisoned").remove(name);
+    }
+}
+
+/// A [`Platform`] made entirely of test doubles.
+///
+/// ```
+/// # use clawft_platform::Platform;
+/// # use clawft_platform::env::Environment;
+/// # use clawft_platform::testing::{MockPlatform, MockResponse};
+/// # tokio_test_block(async {
+/// let platform = MockPlatform::new();
+/// platform.http.respond("GET https://api.example.com/*", MockResponse::text(200, "ok"));
+/// platform.fs.seed("/work/notes.md", "# notes");
+/// platform.env.set_var("API_KEY", "test");
+///
+/// let resp = platform.http().get("https://api.example.com/v1", &Default::default()).await.unwrap();
+/// assert_eq!(resp.text().unwrap(), "ok");
+/// assert_eq!(platform.http.requests().len(), 1);
+/// # });
+/// # fn tokio_test_block(f: impl std::future::Future<Output = ()>) {
+/// #     tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(f)
+/// # }
+/// ```
+pub struct MockPlatform {
+    /// Scripted HTTP client.
+    pub http: MockHttpClient,
+    /// In-memory filesystem.
+    pub fs: MemoryFileSystem,
+    /// In-memory environment.
+    pub env: MemoryEnvironment,
+    /// Fake process spawner.
+    pub process: FakeProcessSpawner,
+    process_enabled: bool,
+}
+
+impl MockPlatform {
+    /// Create a platform with empty doubles and process spawning enabled.
+    pub fn new() -> Self {
+        Self {
+            http: MockHttpClient::new(),
+            fs: MemoryFileSystem::new(),
+            env: MemoryEnvironment::new(),
+            process: FakeProcessSpawner::new(),
+            process_enabled: true,
+        }
+    }
+
+    /// Report process spawning as unavailable, like a WASM platform.
+    pub fn without_process(mut self) -> Self {
+        self.process_enabled = false;
+        self
+    }
+}
+
+impl Default for MockPlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Platform for MockPlatform {
+    fn http(&self) -> &dyn crate::http::HttpClient {
+        &self.http
+    }
+
+    fn fs(&self) -> &dyn crate::fs::FileSystem {
+        &self.fs
+    }
+
+    fn env(&self) -> &dyn Environment {
+        &self.env
+    }
+
+    fn process(&self) -> Option<&dyn crate::process::ProcessSpawner> {
+        self.process_enabled.then_some(&self.process as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessOutput;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn mock_platform_wires_all_doubles() {
+        let platform = MockPlatform::new();
+        platform
+            .http
+            .respond("https://example.com/*", MockResponse::text(200, "hi"));
+        platform.fs.seed("/ws/a.txt", "alpha");
+        platform.env.set_var("TOKEN", "t");
+        platform.process.respond(
+            "echo hi",
+            ProcessOutput {
+                exit_code: 0,
+                stdout: "hi\n".into(),
+                stderr: String::new(),
+            },
+        );
+
+        let resp = platform
+            .http()
+            .get("https://example.com/x", &HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(resp.text().unwrap(), "hi");
+        assert_eq!(
+            platform
+                .fs()
+                .read_to_string(Path::new("/ws/a.txt"))
+                .await
+                .unwrap(),
+            "alpha"
+        );
+        assert_eq!(platform.env().get_var("TOKEN").as_deref(), Some("t"));
+        let out = platform
+            .process()
+            .unwrap()
+            .run("echo", &["hi"], None, None)
+            .await
+            .unwrap();
+        assert_eq!(out.stdout, "hi\n");
+
+        assert!(MockPlatform::new().without_process().process().is_none());
+    }
+
+    #[test]
+    fn memory_environment_is_isolated() {
+        let env = MemoryEnvironment::with_vars([("A", "1")]);
+        assert_eq!(env.get_var("A").as_deref(), Some("1"));
+        env.remove_var("A");
+        assert!(env.get_var("A").is_none());
+        // PATH exists in the real environment but not here.
+        assert!(env.get_var("PATH").is_none());
+    }
+}
@@ -0,0 +1,276 @@
+//! In-memory [`FileSystem`] double.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::fs::{FileMetadata, FileSystem};
+
+#[derive(Default)]
+struct Tree {
+    files: BTreeMap<PathBuf, String>,
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl Tree {
+    fn add_parents(&mut self, path: &Path) {
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            self.dirs.insert(ancestor.to_path_buf());
+        }
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.contains(path)
+    }
+}
+
+/// A [`FileSystem`] held entirely in memory.
+///
+/// Paths are used exactly as given, with no normalisation, so tests should
+/// stick to absolute paths. Writing a file creates its parent directories,
+/// and [`seed`](Self::seed) does the same for setting up a tree before the
+/// code under test runs. [`walk`](FileSystem::walk) and
+/// [`glob`](FileSystem::glob) use the portable default implementations.
+pub struct MemoryFileSystem {
+    tree: Mutex<Tree>,
+    home: Option<PathBuf>,
+}
+
+impl MemoryFileSystem {
+    /// Create an empty filesystem whose home directory is `/home/test`.
+    pub fn new() -> Self {
+        Self {
+            tree: Mutex::default(),
+            home: Some(PathBuf::from("/home/test")),
+        }
+    }
+
+    /// Create a filesystem pre-populated with `files`.
+    pub fn with_files<I, P, C>(files: I) -> Self
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: AsRef<Path>,
+        C: Into<String>,
+    {
+        let fs = Self::new();
+        for (path, content) in files {
+            fs.seed(path, content);
+        }
+        fs
+    }
+
+    /// Use `home` as the home directory (`None` for none at all).
+    pub fn with_home(mut self, home: Option<PathBuf>) -> Self {
+        self.home = home;
+        self
+    }
+
+    /// Create or overwrite a file, along with its parent directories.
+    pub fn seed(&self, path: impl AsRef<Path>, content: impl Into<String>) -> &Self {
+        let path = path.as_ref();
+        let mut tree = self.lock();
+        tree.add_parents(path);
+        tree.files.insert(path.to_path_buf(), content.into());
+        self
+    }
+
+    /// Create a directory, along with its parents.
+    pub fn seed_dir(&self, path: impl AsRef<Path>) -> &Self {
+        let path = path.as_ref();
+        let mut tree = self.lock();
+        tree.add_parents(path);
+        tree.dirs.insert(path.to_path_buf());
+        self
+    }
+
+    /// Contents of a file, if it exists.
+    pub fn read(&self, path: impl AsRef<Path>) -> Option<String> {
+        self.lock().files.get(path.as_ref()).cloned()
+    }
+
+    /// Every file path, sorted.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.lock().files.keys().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Tree> {
+        self.tree.lock().expect("memory fs lock poisoned")
+    }
+}
+
+impl Default for MemoryFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn not_found(path: &Path) -> Error {
+    Error::new(
+        ErrorKind::NotFound,
+        format!("{}: not found", path.display()),
+    )
+}
+
+fn is_a_directory(path: &Path) -> Error {
+    Error::other(format!("{}: is a directory", path.display()))
+}
+
+#[async_trait]
+impl FileSystem for MemoryFileSystem {
+    async fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+        let tree = self.lock();
+        if tree.is_dir(path) {
+            return Err(is_a_directory(path));
+        }
+        tree.files.get(path).cloned().ok_or_else(|| not_found(path))
+    }
+
+    async fn write_string(&self, path: &Path, content: &str) -> std::io::Result<()> {
+        let mut tree = self.lock();
+        if tree.is_dir(path) {
+            return Err(is_a_directory(path));
+        }
+        tree.add_parents(path);
+        tree.files.insert(path.to_path_buf(), content.to_string());
+        Ok(())
+    }
+
+    async fn append_string(&self, path: &Path, content: &str) -> std::io::Result<()> {
+        let mut tree = self.lock();
+        if tree.is_dir(path) {
+            return Err(is_a_directory(path));
+        }
+        tree.add_parents(path);
+        tree.files
+            .entry(path.to_path_buf())
+            .or_default()
+            .push_str(content);
+        Ok(())
+    }
+
+    async fn exists(&self, path: &Path) -> bool {
+        let tree = self.lock();
+        tree.files.contains_key(path) || tree.is_dir(path)
+    }
+
+    async fn list_dir(&self, path: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let tree = self.lock();
+        if !tree.is_dir(path) {
+            return Err(if tree.files.contains_key(path) {
+                Error::other(format!("{}: not a directory", path.display()))
+            } else {
+                not_found(path)
+            });
+        }
+        let children = tree
+            .files
+            .keys()
+            .chain(tree.dirs.iter())
+            .filter(|p| p.parent() == Some(path))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        Ok(children.into_iter().collect())
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        let mut tree = self.lock();
+        if tree.files.contains_key(path) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{}: is a file", path.display()),
+            ));
+        }
+        tree.add_parents(path);
+        tree.dirs.insert(path.to_path_buf());
+        Ok(())
+    }
+
+    async fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        let mut tree = self.lock();
+        if tree.is_dir(path) {
+            return Err(is_a_directory(path));
+        }
+        tree.files
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        self.home.clone()
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        let mut tree = self.lock();
+        if tree.is_dir(to) {
+            return Err(is_a_directory(to));
+        }
+        let content = tree.files.remove(from).ok_or_else(|| not_found(from))?;
+        tree.add_parents(to);
+        tree.files.insert(to.to_path_buf(), content);
+        Ok(())
+    }
+
+    async fn metadata(&self, path: &Path) -> std::io::Result<FileMetadata> {
+        let tree = self.lock();
+        if tree.is_dir(path) {
+            return Ok(FileMetadata {
+                len: 0,
+                modified: None,
+                is_dir: true,
+            });
+        }
+        let content = tree.files.get(path).ok_or_else(|| not_found(path))?;
+        Ok(FileMetadata {
+            len: content.len() as u64,
+            modified: None,
+            is_dir: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn seeded_tree_is_listable() {
+        let fs = MemoryFileSystem::with_files([("/ws/a.txt", "a"), ("/ws/sub/b.txt", "b")]);
+        assert_eq!(
+            fs.list_dir(Path::new("/ws")).await.unwrap(),
+            vec![PathBuf::from("/ws/a.txt"), PathBuf::from("/ws/sub")]
+        );
+        assert!(fs.exists(Path::new("/ws/sub")).await);
+        assert!(fs.metadata(Path::new("/ws/sub")).await.unwrap().is_dir);
+        assert_eq!(fs.metadata(Path::new("/ws/a.txt")).await.unwrap().len, 1);
+
+        let md = fs.glob(Path::new("/ws"), "**/*.txt").await.unwrap();
+        assert_eq!(md.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn writes_and_errors_behave_like_a_real_fs() {
+        let fs = MemoryFileSystem::new();
+        let p = Path::new("/new/dir/file.txt");
+        fs.write_string(p, "one").await.unwrap();
+        fs.append_string(p, " two").await.unwrap();
+        assert_eq!(fs.read(p).as_deref(), Some("one two"));
+        assert!(fs.exists(Path::new("/new/dir")).await);
+
+        fs.rename(p, Path::new("/moved.txt")).await.unwrap();
+        assert_eq!(fs.files(), vec![PathBuf::from("/moved.txt")]);
+
+        let err = fs.remove_file(p).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        let err = fs.read_to_string(Path::new("/nope")).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(fs.read_to_string(Path::new("/new")).await.is_err());
+        assert_eq!(fs.home_dir(), Some(PathBuf::from("/home/test")));
+    }
+}
@@ -0,0 +1,279 @@
+//! Scriptable [`HttpClient`] double.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::http::{HttpClient, HttpResponse};
+
+/// A canned reply: a response or a transport error.
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// Reply with this response.
+    Response(HttpResponse),
+    /// Fail the request with this error message.
+    Error(String),
+}
+
+impl MockResponse {
+    /// A response with a text body.
+    pub fn text(status: u16, body: &str) -> Self {
+        Self::Response(HttpResponse {
+            status,
+            headers: HashMap::new(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    /// A response with a JSON body and `content-type: application/json`.
+    pub fn json(status: u16, body: &serde_json::Value) -> Self {
+        Self::Response(HttpResponse {
+            status,
+            headers: HashMap::from([("content-type".to_string(), "application/json".to_string())]),
+            body: body.to_string().into_bytes(),
+        })
+    }
+
+    /// A response with a raw byte body.
+    pub fn bytes(status: u16, body: Vec<u8>) -> Self {
+        Self::Response(HttpResponse {
+            status,
+            headers: HashMap::new(),
+            body,
+        })
+    }
+
+    /// A transport-level failure (connection refused, timeout, ...).
+    pub fn error(message: &str) -> Self {
+        Self::Error(message.to_string())
+    }
+
+    /// Add a response header. No effect on [`MockResponse::Error`].
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        if let Self::Response(ref mut resp) = self {
+            resp.headers.insert(name.to_string(), value.to_string());
+        }
+        self
+    }
+}
+
+/// A request seen by [`MockHttpClient`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// HTTP method, upper-case.
+    pub method: String,
+    /// Full request URL.
+    pub url: String,
+    /// Request headers.
+    pub headers: HashMap<String, String>,
+    /// Request body, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+impl RecordedRequest {
+    /// The body as UTF-8 text, or an empty string.
+    pub fn body_text(&self) -> String {
+        self.body
+            .as_deref()
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+            .unwrap_or_default()
+    }
+}
+
+struct Route {
+    method: Option<String>,
+    pattern: String,
+    responses: VecDeque<MockResponse>,
+}
+
+/// An [`HttpClient`] that replies from per-URL queues of canned responses.
+///
+/// Patterns are matched against the full URL; `*` matches any run of
+/// characters (including `/`) and everything else is literal. A pattern
+/// may be prefixed with a method (`"POST https://api/*"`) to match only
+/// that method. Routes are tried in registration order.
+///
+/// Each route replays its responses in order. The last response is kept
+/// and repeated, so a single canned reply serves any number of requests.
+/// Requests that match no route fail with an error naming the URL.
+#[derive(Default)]
+pub struct MockHttpClient {
+    routes: Mutex<Vec<Route>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+impl MockHttpClient {
+    /// Create a client with no routes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `response` for requests matching `pattern`.
+    ///
+    /// Calling this again with the same pattern appends to that route's
+    /// queue rather than adding a new route.
+    pub fn respond(&self, pattern: &str, response: MockResponse) -> &Self {
+        let (method, pattern) = split_method(pattern);
+        let mut routes = self.routes.lock().expect("mock http lock poisoned");
+        match routes
+            .iter_mut()
+            .find(|r| r.method == method && r.pattern == pattern)
+        {
+            Some(route) => route.responses.push_back(response),
+            None => routes.push(Route {
+                method,
+                pattern,
+                responses: VecDeque::from([response]),
+            }),
+        }
+        self
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .expect("mock http lock poisoned")
+            .clone()
+    }
+
+    /// Forget all routes and recorded requests.
+    pub fn reset(&self) {
+        self.routes.lock().expect("mock http lock poisoned").clear();
+        self.requests
+            .lock()
+            .expect("mock http lock poisoned")
+            .clear();
+    }
+}
+
+fn split_method(pattern: &str) -> (Option<String>, String) {
+    match pattern.split_once(' ') {
+        Some((method, rest))
+            if !method.is_empty() && method.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            (Some(method.to_ascii_uppercase()), rest.trim().to_string())
+        }
+        _ => (None, pattern.to_string()),
+    }
+}
+
+/// `*`-only wildcard match over the whole string.
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` at all: exact match.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[async_trait]
+impl HttpClient for MockHttpClient {
+    async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let method = method.to_ascii_uppercase();
+        self.requests
+            .lock()
+            .expect("mock http lock poisoned")
+            .push(RecordedRequest {
+                method: method.clone(),
+                url: url.to_string(),
+                headers: headers.clone(),
+                body: body.map(<[u8]>::to_vec),
+            });
+
+        let reply = {
+            let mut routes = self.routes.lock().expect("mock http lock poisoned");
+            routes
+                .iter_mut()
+                .find(|r| {
+                    r.method.as_deref().is_none_or(|m| m == method)
+                        && wildcard_match(&r.pattern, url)
+                })
+                .map(|route| {
+                    if route.responses.len() > 1 {
+                        route.responses.pop_front().expect("queue is non-empty")
+                    } else {
+                        route.responses[0].clone()
+                    }
+                })
+        };
+
+        match reply {
+            Some(MockResponse::Response(resp)) => Ok(resp),
+            Some(MockResponse::Error(message)) => Err(message.into()),
+            None => Err(format!("no mock response for {method} {url}").into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(wildcard_match("https://a/b", "https://a/b"));
+        assert!(!wildcard_match("https://a/b", "https://a/bc"));
+        assert!(wildcard_match("https://a/*", "https://a/b/c?q=1"));
+        assert!(wildcard_match("*://a/*/c", "https://a/x/y/c"));
+        assert!(!wildcard_match("https://a/*/c", "https://a/x/y/d"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[tokio::test]
+    async fn queues_replay_in_order_and_repeat_last() {
+        let http = MockHttpClient::new();
+        http.respond("https://api/*", MockResponse::text(503, "busy"))
+            .respond("https://api/*", MockResponse::text(200, "ok"));
+
+        let h = HashMap::new();
+        assert_eq!(http.get("https://api/x", &h).await.unwrap().status, 503);
+        assert_eq!(http.get("https://api/x", &h).await.unwrap().status, 200);
+        assert_eq!(http.get("https://api/y", &h).await.unwrap().status, 200);
+        assert_eq!(http.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn method_prefix_and_unmatched_requests() {
+        let http = MockHttpClient::new();
+        http.respond(
+            "POST https://api/items",
+            MockResponse::json(201, &serde_json::json!({"id": 1})),
+        );
+        http.respond("https://down/*", MockResponse::error("connection refused"));
+
+        let h = HashMap::from([("x-test".to_string(), "1".to_string())]);
+        let resp = http.post("https://api/items", &h, b"{}").await.unwrap();
+        assert_eq!(resp.status, 201);
+        assert_eq!(resp.headers["content-type"], "application/json");
+
+        let err = http.get("https://api/items", &h).await.unwrap_err();
+        assert!(err.to_string().contains("no mock response for GET"));
+        let err = http.get("https://down/x", &h).await.unwrap_err();
+        assert_eq!(err.to_string(), "connection refused");
+
+        let recorded = http.requests();
+        assert_eq!(recorded[0].method, "POST");
+        assert_eq!(recorded[0].body_text(), "{}");
+        assert_eq!(recorded[0].headers["x-test"], "1");
+    }
+}
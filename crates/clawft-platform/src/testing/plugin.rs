@@ -0,0 +1,118 @@
+//! In-memory plugin host doubles: [`MockKvStore`] and [`MockToolContext`].
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use clawft_plugin::{KeyValueStore, PluginError, ToolContext};
+
+/// A working [`KeyValueStore`] held in memory.
+#[derive(Debug, Default)]
+pub struct MockKvStore {
+    entries: Mutex<BTreeMap<String, String>>,
+}
+
+impl MockKvStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of every entry, sorted by key.
+    pub fn entries(&self) -> BTreeMap<String, String> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.entries.lock().expect("kv lock poisoned")
+    }
+}
+
+#[async_trait]
+impl KeyValueStore for MockKvStore {
+    async fn get(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.lock().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        self.lock().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, PluginError> {
+        Ok(self.lock().remove(key).is_some())
+    }
+
+    async fn list_keys(&self, prefix: Option<&str>) -> Result<Vec<String>, PluginError> {
+        Ok(self
+            .lock()
+            .keys()
+            .filter(|k| prefix.is_none_or(|p| k.starts_with(p)))
+            .cloned()
+            .collect())
+    }
+}
+
+/// A [`ToolContext`] backed by a [`MockKvStore`].
+#[derive(Debug)]
+pub struct MockToolContext {
+    /// The context's key-value store, for seeding and inspection.
+    pub kv: MockKvStore,
+    /// Reported plugin id.
+    pub plugin_id: String,
+    /// Reported agent id.
+    pub agent_id: String,
+}
+
+impl MockToolContext {
+    /// Create a context for `plugin_id`, invoked by `test-agent`.
+    pub fn new(plugin_id: &str) -> Self {
+        Self {
+            kv: MockKvStore::new(),
+            plugin_id: plugin_id.to_string(),
+            agent_id: "test-agent".to_string(),
+        }
+    }
+}
+
+impl Default for MockToolContext {
+    fn default() -> Self {
+        Self::new("test-plugin")
+    }
+}
+
+impl ToolContext for MockToolContext {
+    fn key_value_store(&self) -> &dyn KeyValueStore {
+        &self.kv
+    }
+
+    fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn kv_store_round_trips() {
+        let ctx = MockToolContext::new("clawft-plugin-git");
+        let kv = ctx.key_value_store();
+        kv.set("a/1", "x").await.unwrap();
+        kv.set("a/2", "y").await.unwrap();
+        kv.set("b/1", "z").await.unwrap();
+
+        assert_eq!(kv.get("a/1").await.unwrap().as_deref(), Some("x"));
+        assert_eq!(kv.list_keys(Some("a/")).await.unwrap(), vec!["a/1", "a/2"]);
+        assert!(kv.delete("a/1").await.unwrap());
+        assert!(!kv.delete("a/1").await.unwrap());
+        assert_eq!(ctx.kv.entries().len(), 2);
+        assert_eq!(ctx.plugin_id(), "clawft-plugin-git");
+        assert_eq!(ctx.agent_id(), "test-agent");
+    }
+}
@@ -0,0 +1,253 @@
+//! Fake [`ProcessSpawner`] with canned output per command.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::process::{OutputLine, ProcessHandle, ProcessOutput, ProcessSpawner};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A command seen by [`FakeProcessSpawner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCall {
+    /// Program name.
+    pub command: String,
+    /// Arguments, in order.
+    pub args: Vec<String>,
+    /// Working directory, if one was given.
+    pub working_dir: Option<PathBuf>,
+    /// Timeout in seconds, if one was given.
+    pub timeout_secs: Option<u64>,
+}
+
+impl RecordedCall {
+    /// The command and its arguments joined with single spaces.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.command.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A [`ProcessSpawner`] that never starts a real process.
+///
+/// Outputs are registered per command line. A call is answered by the
+/// entry for its full command line (`"git status --short"`) if there is
+/// one, otherwise by the entry for the program alone (`"git"`). Unknown
+/// commands fail the same way a missing executable would.
+///
+/// [`spawn_streaming`](ProcessSpawner::spawn_streaming) replays the
+/// canned stdout lines followed by the stderr lines.
+#[derive(Default)]
+pub struct FakeProcessSpawner {
+    outputs: Mutex<HashMap<String, ProcessOutput>>,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl FakeProcessSpawner {
+    /// Create a spawner that knows no commands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `command_line` with `output`.
+    pub fn respond(&self, command_line: &str, output: ProcessOutput) -> &Self {
+        self.outputs
+            .lock()
+            .expect("fake spawner lock poisoned")
+            .insert(command_line.trim().to_string(), output);
+        self
+    }
+
+    /// Answer `command_line` with exit code 0 and `stdout`.
+    pub fn succeed(&self, command_line: &str, stdout: &str) -> &Self {
+        self.respond(
+            command_line,
+            ProcessOutput {
+                exit_code: 0,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            },
+        )
+    }
+
+    /// Every call made so far, in order.
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls
+            .lock()
+            .expect("fake spawner lock poisoned")
+            .clone()
+    }
+
+    fn answer(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ProcessOutput, BoxError> {
+        let call = RecordedCall {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            working_dir: working_dir.map(Path::to_path_buf),
+            timeout_secs,
+        };
+        let line = call.command_line();
+        self.calls
+            .lock()
+            .expect("fake spawner lock poisoned")
+            .push(call);
+
+        let outputs = self.outputs.lock().expect("fake spawner lock poisoned");
+        outputs
+            .get(&line)
+            .or_else(|| outputs.get(command))
+            .cloned()
+            .ok_or_else(|| {
+                format!("failed to spawn '{command}': no fake output for '{line}'").into()
+            })
+    }
+}
+
+#[async_trait]
+impl ProcessSpawner for FakeProcessSpawner {
+    async fn run(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        timeout_secs: Option<u64>,
+    ) -> Result<ProcessOutput, BoxError> {
+        self.answer(command, args, working_dir, timeout_secs)
+    }
+
+    async fn spawn_streaming(
+        &self,
+        command: &str,
+        args: &[&str],
+        working_dir: Option<&Path>,
+        timeout_secs: Option<u64>,
+    ) -> Result<Box<dyn ProcessHandle>, BoxError> {
+        let output = self.answer(command, args, working_dir, timeout_secs)?;
+        let lines = output
+            .stdout
+            .lines()
+            .map(|l| OutputLine::Stdout(l.to_string()))
+            .chain(
+                output
+                    .stderr
+                    .lines()
+                    .map(|l| OutputLine::Stderr(l.to_string())),
+            )
+            .collect();
+        Ok(Box::new(FakeProcessHandle {
+            lines,
+            exit_code: output.exit_code,
+            stdin: Vec::new(),
+        }))
+    }
+}
+
+/// Handle returned by [`FakeProcessSpawner::spawn_streaming`].
+struct FakeProcessHandle {
+    lines: VecDeque<OutputLine>,
+    exit_code: i32,
+    stdin: Vec<u8>,
+}
+
+#[async_trait]
+impl ProcessHandle for FakeProcessHandle {
+    fn id(&self) -> Option<u32> {
+        None
+    }
+
+    async fn next_line(&mut self) -> Option<OutputLine> {
+        self.lines.pop_front()
+    }
+
+    async fn write_stdin(&mut self, data: &[u8]) -> Result<(), BoxError> {
+        self.stdin.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn close_stdin(&mut self) {}
+
+    async fn kill(&mut self) -> Result<(), BoxError> {
+        self.lines.clear();
+        self.exit_code = -1;
+        Ok(())
+    }
+
+    async fn wait(&mut self) -> Result<i32, BoxError> {
+        Ok(self.exit_code)
+    }
+
+    fn timed_out(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::collect_output;
+
+    #[tokio::test]
+    async fn full_command_line_wins_over_program() {
+        let spawner = FakeProcessSpawner::new();
+        spawner
+            .succeed("git", "generic\n")
+            .succeed("git status --short", " M a.rs\n");
+
+        let out = spawner
+            .run("git", &["status", "--short"], None, None)
+            .await
+            .unwrap();
+        assert_eq!(out.stdout, " M a.rs\n");
+        let out = spawner
+            .run("git", &["log"], Some(Path::new("/repo")), Some(5))
+            .await
+            .unwrap();
+        assert_eq!(out.stdout, "generic\n");
+
+        let err = spawner
+            .run("cargo", &["build"], None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cargo build"));
+
+        let calls = spawner.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].working_dir.as_deref(), Some(Path::new("/repo")));
+        assert_eq!(calls[1].timeout_secs, Some(5));
+    }
+
+    #[tokio::test]
+    async fn streaming_replays_stdout_then_stderr() {
+        let spawner = FakeProcessSpawner::new();
+        spawner.respond(
+            "sh -c make",
+            ProcessOutput {
+                exit_code: 2,
+                stdout: "a\nb\n".into(),
+                stderr: "oops\n".into(),
+            },
+        );
+        let mut handle = spawner
+            .spawn_streaming("sh", &["-c", "make"], None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            handle.next_line().await,
+            Some(OutputLine::Stdout("a".into()))
+        );
+        let rest = collect_output(handle.as_mut()).await.unwrap();
+        assert_eq!(rest.stdout, "b\n");
+        assert_eq!(rest.stderr, "oops\n");
+        assert_eq!(rest.exit_code, 2);
+    }
+}
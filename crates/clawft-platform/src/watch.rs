@@ -0,0 +1,472 @@
+//! File change notifications.
+//!
+//! [`FileWatcher`] watches paths and delivers debounced batches of
+//! [`FileEvent`]s through a [`WatchHandle`]. Events for the same path
+//! within one debounce window are coalesced ([`coalesce`]), so an editor
+//! saving a file several times in quick succession produces a single
+//! `Modified` event.
+//!
+//! [`NativeFileWatcher`] uses the OS notification API via [`notify`] and
+//! falls back to polling when that is unavailable (or when constructed
+//! with [`NativeFileWatcher::polling`]). Browser/WASM platforms have no
+//! watcher; [`Platform::watcher`](crate::Platform::watcher) returns `None`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::event::{MetadataKind, ModifyKind, RenameMode};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+/// What happened to a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileEventKind {
+    /// The path was created (or moved into place).
+    Created,
+    /// The contents of the path changed.
+    Modified,
+    /// The path was removed (or moved away).
+    Removed,
+}
+
+/// A change to one path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileEvent {
+    /// The path that changed.
+    pub path: PathBuf,
+    /// The kind of change.
+    pub kind: FileEventKind,
+}
+
+impl FileEvent {
+    /// Create an event.
+    pub fn new(path: impl Into<PathBuf>, kind: FileEventKind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+/// Options for [`FileWatcher::watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// Watch directories recursively.
+    pub recursive: bool,
+    /// Quiet period after the last event before a batch is delivered.
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            debounce: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Receiving end of a watch. Dropping it (or calling [`stop`](Self::stop))
+/// stops watching.
+pub struct WatchHandle {
+    events: mpsc::Receiver<Vec<FileEvent>>,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+impl WatchHandle {
+    /// Next batch of coalesced events, or `None` once the watch has stopped.
+    pub async fn recv(&mut self) -> Option<Vec<FileEvent>> {
+        self.events.recv().await
+    }
+
+    /// Stop watching.
+    pub fn stop(mut self) {
+        if let Some(tx) = self.stop.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        if let Some(tx) = self.stop.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Platform file-watching capability.
+pub trait FileWatcher: Send + Sync {
+    /// Start watching `paths`.
+    ///
+    /// Must be called from within a tokio runtime; debouncing runs in a
+    /// background task that lives as long as the returned handle.
+    fn watch(&self, paths: &[PathBuf], options: &WatchOptions) -> std::io::Result<WatchHandle>;
+}
+
+/// Merge events so each path appears at most once, describing its net
+/// change over the batch. Results are sorted by path.
+///
+/// A path created and then removed within the batch disappears entirely;
+/// one removed and then recreated is reported as `Modified`.
+pub fn coalesce(events: impl IntoIterator<Item = FileEvent>) -> Vec<FileEvent> {
+    use FileEventKind::*;
+
+    // `None` marks a path whose changes cancelled out.
+    let mut net: BTreeMap<PathBuf, Option<FileEventKind>> = BTreeMap::new();
+    for event in events {
+        let merged = match (net.get(&event.path).copied(), event.kind) {
+            (None, kind) => Some(kind),
+            (Some(Some(Created)), Removed) => None,
+            (Some(Some(Created)), _) => Some(Created),
+            (Some(Some(Modified)), Removed) => Some(Removed),
+            (Some(Some(Modified)), _) => Some(Modified),
+            (Some(Some(Removed)), Removed) => Some(Removed),
+            (Some(Some(Removed)), _) => Some(Modified),
+            (Some(None), Removed) => None,
+            (Some(None), _) => Some(Created),
+        };
+        net.insert(event.path, merged);
+    }
+    net.into_iter()
+        .filter_map(|(path, kind)| kind.map(|kind| FileEvent { path, kind }))
+        .collect()
+}
+
+/// Longest a batch is held back while events keep arriving, as a
+/// multiple of the debounce window.
+const MAX_DELAY_FACTOR: u32 = 10;
+
+/// Spawn the task that turns raw events into debounced, coalesced batches.
+///
+/// `guard` is kept alive for as long as the task runs (it holds the
+/// underlying OS watcher).
+pub(crate) fn spawn_debouncer<G: Send + 'static>(
+    mut raw: mpsc::UnboundedReceiver<FileEvent>,
+    debounce: Duration,
+    guard: G,
+) -> WatchHandle {
+    let (out_tx, out_rx) = mpsc::channel(16);
+    let (stop_tx, mut stop_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let _guard = guard;
+        let max_delay = debounce * MAX_DELAY_FACTOR;
+        let mut pending = Vec::new();
+        let mut first_at = tokio::time::Instant::now();
+        loop {
+            let wait = debounce.min(max_delay.saturating_sub(first_at.elapsed()));
+            tokio::select! {
+                _ = &mut stop_rx => break,
+                event = raw.recv() => match event {
+                    Some(event) => {
+                        if pending.is_empty() {
+                            first_at = tokio::time::Instant::now();
+                        }
+                        pending.push(event);
+                    }
+                    None => break,
+                },
+                _ = tokio::time::sleep(wait), if !pending.is_empty() => {
+                    let batch = coalesce(std::mem::take(&mut pending));
+                    if !batch.is_empty() && out_tx.send(batch).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        debug!("file watcher stopped");
+    });
+    WatchHandle {
+        events: out_rx,
+        stop: Some(stop_tx),
+    }
+}
+
+/// Translate a [`notify`] event into ours.
+fn convert(event: notify::Event) -> Vec<FileEvent> {
+    use FileEventKind::*;
+
+    let kind = match event.kind {
+        EventKind::Create(_) => Created,
+        EventKind::Remove(_) => Removed,
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => Removed,
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => Created,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            return vec![
+                FileEvent::new(event.paths[0].clone(), Removed),
+                FileEvent::new(event.paths[1].clone(), Created),
+            ];
+        }
+        // Polling reports content changes as a new write time.
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::WriteTime)) => Modified,
+        EventKind::Modify(ModifyKind::Metadata(_)) => return Vec::new(),
+        EventKind::Modify(_) => Modified,
+        _ => return Vec::new(),
+    };
+    event
+        .paths
+        .into_iter()
+        .map(|path| FileEvent { path, kind })
+        .collect()
+}
+
+/// Native watcher backed by [`notify`], with a polling fallback.
+#[derive(Debug, Clone)]
+pub struct NativeFileWatcher {
+    poll_interval: Duration,
+    force_polling: bool,
+}
+
+impl NativeFileWatcher {
+    /// Use OS notifications, polling every 2 seconds if they are unavailable.
+    pub fn new() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(2),
+            force_polling: false,
+        }
+    }
+
+    /// Always poll, checking modification times and contents every
+    /// `interval`.
+    ///
+    /// Useful on network filesystems where OS notifications are unreliable.
+    pub fn polling(interval: Duration) -> Self {
+        Self {
+            poll_interval: interval,
+            force_polling: true,
+        }
+    }
+}
+
+impl Default for NativeFileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileWatcher for NativeFileWatcher {
+    fn watch(&self, paths: &[PathBuf], options: &WatchOptions) -> std::io::Result<WatchHandle> {
+        let (raw_tx, raw_rx) = mpsc::unbounded_channel();
+        let handler = move |res: notify::Result<notify::Event>| match res {
+            Ok(event) => {
+                for e in convert(event) {
+                    let _ = raw_tx.send(e);
+                }
+            }
+            Err(e) => debug!(error = %e, "file watcher error"),
+        };
+
+        let poll = |handler| -> std::io::Result<Box<dyn Watcher + Send>> {
+            // Modification times are compared at one-second resolution,
+            // so also hash contents to catch rapid successive writes.
+            let config = notify::Config::default()
+                .with_poll_interval(self.poll_interval)
+                .with_compare_contents(true);
+            Ok(Box::new(
+                notify::PollWatcher::new(handler, config).map_err(std::io::Error::other)?,
+            ))
+        };
+        let mut watcher: Box<dyn Watcher + Send> = if self.force_polling {
+            poll(handler)?
+        } else {
+            match notify::RecommendedWatcher::new(handler.clone(), notify::Config::default()) {
+                Ok(w) => Box::new(w),
+                Err(e) => {
+                    warn!(error = %e, "OS file notifications unavailable, polling instead");
+                    poll(handler)?
+                }
+            }
+        };
+
+        let mode = if options.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        for path in paths {
+            watcher
+                .watch(path, mode)
+                .map_err(|e| std::io::Error::other(format!("watch {}: {e}", path.display())))?;
+        }
+        Ok(spawn_debouncer(raw_rx, options.debounce, watcher))
+    }
+}
+
+/// Whether `event` concerns `file` (compared by file name within the
+/// same directory, so it survives editors that save via rename).
+pub fn concerns(event: &FileEvent, file: &Path) -> bool {
+    event.path == file
+        || (event.path.file_name() == file.file_name()
+            && event.path.parent().map(canonical_or_self) == file.parent().map(canonical_or_self))
+}
+
+fn canonical_or_self(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FileEventKind::*;
+
+    fn ev(path: &str, kind: FileEventKind) -> FileEvent {
+        FileEvent::new(path, kind)
+    }
+
+    #[test]
+    fn coalesce_merges_per_path() {
+        let batch = coalesce([
+            ev("/a", Modified),
+            ev("/b", Created),
+            ev("/a", Modified),
+            ev("/b", Modified),
+            ev("/c", Modified),
+            ev("/c", Removed),
+        ]);
+        assert_eq!(
+            batch,
+            vec![ev("/a", Modified), ev("/b", Created), ev("/c", Removed)]
+        );
+    }
+
+    #[test]
+    fn coalesce_cancels_transient_files() {
+        assert!(
+            coalesce([
+                ev("/tmp", Created),
+                ev("/tmp", Modified),
+                ev("/tmp", Removed)
+            ])
+            .is_empty()
+        );
+        assert_eq!(
+            coalesce([ev("/x", Created), ev("/x", Removed), ev("/x", Created)]),
+            vec![ev("/x", Created)]
+        );
+    }
+
+    #[test]
+    fn coalesce_replace_is_modify() {
+        assert_eq!(
+            coalesce([ev("/cfg", Removed), ev("/cfg", Created)]),
+            vec![ev("/cfg", Modified)]
+        );
+    }
+
+    #[test]
+    fn convert_rename_both() {
+        let event = notify::Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+            .add_path("/d/.cfg.tmp".into())
+            .add_path("/d/cfg".into());
+        assert_eq!(
+            convert(event),
+            vec![ev("/d/.cfg.tmp", Removed), ev("/d/cfg", Created)]
+        );
+    }
+
+    #[tokio::test]
+    async fn debouncer_delivers_one_batch_for_rapid_events() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut handle = spawn_debouncer(rx, Duration::from_millis(100), ());
+
+        for _ in 0..20 {
+            tx.send(ev("/cfg.json", Modified)).unwrap();
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        let batch = tokio::time::timeout(Duration::from_secs(2), handle.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(batch, vec![ev("/cfg.json", Modified)]);
+
+        // Nothing else is pending.
+        let extra = tokio::time::timeout(Duration::from_millis(300), handle.recv()).await;
+        assert!(extra.is_err(), "unexpected second batch: {extra:?}");
+    }
+
+    #[tokio::test]
+    async fn debouncer_flushes_under_continuous_events() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut handle = spawn_debouncer(rx, Duration::from_millis(20), ());
+        let feeder = tokio::spawn(async move {
+            // Events every 5ms never leave a quiet period.
+            for _ in 0..200 {
+                if tx.send(ev("/log", Modified)).is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+        let batch = tokio::time::timeout(Duration::from_secs(1), handle.recv()).await;
+        assert!(batch.is_ok(), "batch held back past the max delay");
+        feeder.abort();
+    }
+
+    #[tokio::test]
+    async fn debouncer_drops_empty_batches() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut handle = spawn_debouncer(rx, Duration::from_millis(30), ());
+        tx.send(ev("/swap", Created)).unwrap();
+        tx.send(ev("/swap", Removed)).unwrap();
+        let got = tokio::time::timeout(Duration::from_millis(200), handle.recv()).await;
+        assert!(got.is_err());
+    }
+
+    #[tokio::test]
+    async fn stop_ends_the_stream() {
+        let (_tx, rx) = mpsc::unbounded_channel::<FileEvent>();
+        let handle = spawn_debouncer(rx, Duration::from_millis(10), ());
+        handle.stop();
+    }
+
+    async fn rapid_writes_coalesce(watcher: NativeFileWatcher, name: &str) {
+        let dir = std::env::temp_dir().join(format!("clawft_watch_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.json");
+        std::fs::write(&file, "v0").unwrap();
+
+        let mut handle = watcher
+            .watch(
+                std::slice::from_ref(&dir),
+                &WatchOptions {
+                    recursive: false,
+                    debounce: Duration::from_millis(400),
+                },
+            )
+            .unwrap();
+        // Give the watcher (or first poll) time to take its baseline.
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        for i in 1..=10 {
+            std::fs::write(&file, format!("v{i}")).unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let batch = tokio::time::timeout(Duration::from_secs(5), handle.recv())
+            .await
+            .expect("no events delivered")
+            .unwrap();
+        let for_file: Vec<_> = batch.iter().filter(|e| concerns(e, &file)).collect();
+        assert_eq!(for_file.len(), 1, "{batch:?}");
+        assert_eq!(for_file[0].kind, Modified);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn polling_watcher_coalesces_rapid_writes() {
+        rapid_writes_coalesce(
+            NativeFileWatcher::polling(Duration::from_millis(50)),
+            "poll",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn os_watcher_coalesces_rapid_writes() {
+        rapid_writes_coalesce(NativeFileWatcher::new(), "os").await;
+    }
+}
@@ -120,7 +120,7 @@ impl Tool for CargoTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         // Parse and validate flags from parameters
         let mut flags = CargoFlags::from_params(&params)
@@ -137,9 +137,17 @@ impl Tool for CargoTool {
             config.working_dir = Some(dir.to_string());
         }
 
-        let result = execute_cargo(self.subcommand, &flags, &config)
-            .await
-            .map_err(PluginError::ExecutionFailed)?;
+        // Dropping the run on cancellation kills the cargo process.
+        let run = execute_cargo(self.subcommand, &flags, &config, ctx.progress());
+        let result = match ctx.cancellation() {
+            Some(cancel) => tokio::select! {
+                biased;
+                _ = cancel.cancelled() => Err("cancelled".to_string()),
+                result = run => result,
+            },
+            None => run.await,
+        }
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -173,6 +181,23 @@ mod tests {
 
     struct MockToolContext;
 
+    struct CancelledToolContext(clawft_plugin::CancellationToken);
+
+    impl ToolContext for CancelledToolContext {
+        fn key_value_store(&self) -> &dyn KeyValueStore {
+            &MockKvStore
+        }
+        fn plugin_id(&self) -> &str {
+            "clawft-plugin-cargo"
+        }
+        fn agent_id(&self) -> &str {
+            "test-agent"
+        }
+        fn cancellation(&self) -> Option<&clawft_plugin::CancellationToken> {
+            Some(&self.0)
+        }
+    }
+
     impl ToolContext for MockToolContext {
         fn key_value_store(&self) -> &dyn KeyValueStore {
             &MockKvStore
@@ -232,6 +257,18 @@ mod tests {
         assert!(result["success"].as_bool().unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn cancelled_turn_stops_the_command() {
+        let tool = CargoTool::new(CargoSubcommand::Check, CargoConfig::default());
+        let cancel = clawft_plugin::CancellationToken::new();
+        cancel.cancel();
+        let ctx = CancelledToolContext(cancel);
+
+        let params = serde_json::json!({ "working_dir": env!("CARGO_MANIFEST_DIR") });
+        let err = tool.execute(params, &ctx).await.unwrap_err();
+        assert!(err.to_string().contains("cancelled"), "{err}");
+    }
+
     #[tokio::test]
     async fn rejects_invalid_package_name() {
         let tool = CargoTool::new(CargoSubcommand::Build, CargoConfig::default());
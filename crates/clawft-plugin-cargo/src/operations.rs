@@ -5,9 +5,12 @@
 //! arguments (package names, feature flags) are validated before use.
 
 use std::path::Path;
+use std::process::Output;
 use std::time::Duration;
 
-use tokio::process::Command;
+use clawft_plugin::{ProgressReporter, ToolProgress};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
 use tracing::{debug, warn};
 
 use crate::types::{CargoConfig, CargoFlags, CargoResult, CargoSubcommand};
@@ -22,10 +25,13 @@ const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
 ///
 /// The command is built entirely programmatically -- no shell interpolation.
 /// Arguments are validated by [`CargoFlags`] before reaching this function.
+/// The bytes of output read so far are reported to `progress` as cargo
+/// writes them.
 pub async fn execute_cargo(
     subcommand: CargoSubcommand,
     flags: &CargoFlags,
     config: &CargoConfig,
+    progress: Option<&dyn ProgressReporter>,
 ) -> Result<CargoResult, String> {
     let mut cmd = Command::new(&config.cargo_binary);
     cmd.arg(subcommand.as_str());
@@ -44,9 +50,10 @@ pub async fn execute_cargo(
         cmd.current_dir(path);
     }
 
-    // Capture stdout and stderr
+    // Capture stdout and stderr; kill cargo if the caller stops waiting
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
+    cmd.kill_on_drop(true);
 
     // Build command string for logging/result
     let command_str = format_command(&config.cargo_binary, subcommand, flags);
@@ -57,7 +64,7 @@ pub async fn execute_cargo(
         .spawn()
         .map_err(|e| format!("failed to spawn cargo: {e}"))?;
 
-    let output = tokio::time::timeout(DEFAULT_TIMEOUT, child.wait_with_output())
+    let output = tokio::time::timeout(DEFAULT_TIMEOUT, collect_output(child, progress))
         .await
         .map_err(|_| format!("cargo command timed out after {}s", DEFAULT_TIMEOUT.as_secs()))?
         .map_err(|e| format!("cargo process error: {e}"))?;
@@ -83,6 +90,57 @@ pub async fn execute_cargo(
     })
 }
 
+/// Wait for `child` to exit, collecting its output and reporting the
+/// bytes read so far to `progress`.
+async fn collect_output(
+    mut child: Child,
+    progress: Option<&dyn ProgressReporter>,
+) -> std::io::Result<Output> {
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let (mut stdout_buf, mut stderr_buf) = ([0u8; 8192], [0u8; 8192]);
+
+    while stdout_pipe.is_some() || stderr_pipe.is_some() {
+        let (read, from_stdout) = tokio::select! {
+            n = read_pipe(&mut stdout_pipe, &mut stdout_buf) => (n?, true),
+            n = read_pipe(&mut stderr_pipe, &mut stderr_buf) => (n?, false),
+        };
+        match (read, from_stdout) {
+            (0, true) => stdout_pipe = None,
+            (0, false) => stderr_pipe = None,
+            (n, true) => stdout.extend_from_slice(&stdout_buf[..n]),
+            (n, false) => stderr.extend_from_slice(&stderr_buf[..n]),
+        }
+        if let Some(progress) = progress
+            && read > 0
+        {
+            let bytes = stdout.len() + stderr.len();
+            progress.report(
+                ToolProgress::new(bytes as f64).with_message(format!("{bytes} bytes of output")),
+            );
+        }
+    }
+
+    let status = child.wait().await?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Read from `pipe`, or wait forever once it is closed.
+async fn read_pipe<R: AsyncRead + Unpin>(
+    pipe: &mut Option<R>,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    match pipe {
+        Some(pipe) => pipe.read(buf).await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Truncate output to `MAX_OUTPUT_BYTES` and convert to string.
 fn truncate_output(bytes: &[u8]) -> String {
     let truncated = if bytes.len() > MAX_OUTPUT_BYTES {
@@ -137,4 +195,34 @@ mod tests {
         let result = truncate_output(&data);
         assert_eq!(result.len(), MAX_OUTPUT_BYTES);
     }
+
+    struct Recorder(std::sync::Mutex<Vec<ToolProgress>>);
+
+    impl ProgressReporter for Recorder {
+        fn report(&self, progress: ToolProgress) {
+            self.0.lock().unwrap().push(progress);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn collect_output_reports_bytes_read() {
+        let child = Command::new("sh")
+            .args(["-c", "printf abc; sleep 0.1; printf de >&2"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let recorder = Recorder(std::sync::Mutex::new(Vec::new()));
+
+        let output = collect_output(child, Some(&recorder)).await.unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"abc");
+        assert_eq!(output.stderr, b"de");
+        let reports = recorder.0.into_inner().unwrap();
+        let bytes: Vec<f64> = reports.iter().map(|p| p.progress).collect();
+        assert_eq!(bytes, [3.0, 5.0]);
+        assert_eq!(reports[1].message.as_deref(), Some("5 bytes of output"));
+    }
 }
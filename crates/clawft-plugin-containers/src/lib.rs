@@ -109,7 +109,7 @@ impl Tool for ContainerBuildTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let context_path = params
             .get("context_path")
@@ -140,10 +140,16 @@ impl Tool for ContainerBuildTool {
 
         builder.push(context_path);
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -216,7 +222,7 @@ impl Tool for ContainerRunTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let image = params
             .get("image")
@@ -276,10 +282,16 @@ impl Tool for ContainerRunTool {
 
         builder.push(image);
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -346,7 +358,7 @@ impl Tool for ContainerStopTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let container = params
             .get("container")
@@ -369,10 +381,16 @@ impl Tool for ContainerStopTool {
 
         builder.push(container);
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -431,7 +449,7 @@ impl Tool for ContainerLogsTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let container = params
             .get("container")
@@ -461,10 +479,16 @@ impl Tool for ContainerLogsTool {
 
         builder.push(container);
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -518,7 +542,7 @@ impl Tool for ContainerListTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let mut builder = ArgBuilder::new();
         builder.push("ps");
@@ -534,10 +558,16 @@ impl Tool for ContainerListTool {
             builder.push("json");
         }
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -595,7 +625,7 @@ impl Tool for ContainerExecTool {
     async fn execute(
         &self,
         params: serde_json::Value,
-        _ctx: &dyn ToolContext,
+        ctx: &dyn ToolContext,
     ) -> Result<serde_json::Value, PluginError> {
         let container = params
             .get("container")
@@ -635,10 +665,16 @@ impl Tool for ContainerExecTool {
             }
         }
 
-        let result =
-            execute_container(self.config.runtime, &builder.build(), &self.config, &self.limiter)
-                .await
-                .map_err(PluginError::ExecutionFailed)?;
+        let result = execute_container(
+            self.config.runtime,
+            &builder.build(),
+            &self.config,
+            &self.limiter,
+            ctx.cancellation(),
+            ctx.progress(),
+        )
+        .await
+        .map_err(PluginError::ExecutionFailed)?;
 
         serde_json::to_value(&result).map_err(PluginError::from)
     }
@@ -5,11 +5,14 @@
 //! arguments (container names, image names, env vars) are validated
 //! before use.
 
+use std::process::Output;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::process::Command;
+use clawft_plugin::{CancellationToken, ProgressReporter, ToolProgress};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
 use tracing::{debug, warn};
 
 use crate::types::{ContainerConfig, ContainerResult, ContainerRuntime};
@@ -69,11 +72,16 @@ impl ConcurrencyLimiter {
 /// Execute a container command with validated arguments.
 ///
 /// The command is built entirely programmatically -- no shell interpolation.
+/// When `cancel` fires first, the runtime CLI process is killed and an
+/// error is returned; a container it already started keeps running. The
+/// bytes of output read so far are reported to `progress`.
 pub async fn execute_container(
     runtime: ContainerRuntime,
     args: &[String],
     config: &ContainerConfig,
     limiter: &ConcurrencyLimiter,
+    cancel: Option<&CancellationToken>,
+    progress: Option<&dyn ProgressReporter>,
 ) -> Result<ContainerResult, String> {
     if !limiter.try_acquire() {
         return Err(format!(
@@ -83,7 +91,15 @@ pub async fn execute_container(
         ));
     }
 
-    let result = execute_container_inner(runtime, args).await;
+    let run = execute_container_inner(runtime, args, progress);
+    let result = match cancel {
+        Some(cancel) => tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err("cancelled".to_string()),
+            result = run => result,
+        },
+        None => run.await,
+    };
 
     limiter.release();
     result
@@ -92,6 +108,7 @@ pub async fn execute_container(
 async fn execute_container_inner(
     runtime: ContainerRuntime,
     args: &[String],
+    progress: Option<&dyn ProgressReporter>,
 ) -> Result<ContainerResult, String> {
     let binary = runtime.binary();
     let mut cmd = Command::new(binary);
@@ -102,6 +119,7 @@ async fn execute_container_inner(
 
     cmd.stdout(std::process::Stdio::piped());
     cmd.stderr(std::process::Stdio::piped());
+    cmd.kill_on_drop(true);
 
     let command_str = format_command(binary, args);
     debug!(command = %command_str, "executing container command");
@@ -110,7 +128,7 @@ async fn execute_container_inner(
         .spawn()
         .map_err(|e| format!("failed to spawn {binary}: {e}"))?;
 
-    let output = tokio::time::timeout(DEFAULT_TIMEOUT, child.wait_with_output())
+    let output = tokio::time::timeout(DEFAULT_TIMEOUT, collect_output(child, progress))
         .await
         .map_err(|_| {
             format!(
@@ -141,6 +159,57 @@ async fn execute_container_inner(
     })
 }
 
+/// Wait for `child` to exit, collecting its output and reporting the
+/// bytes read so far to `progress`.
+async fn collect_output(
+    mut child: Child,
+    progress: Option<&dyn ProgressReporter>,
+) -> std::io::Result<Output> {
+    let mut stdout_pipe = child.stdout.take();
+    let mut stderr_pipe = child.stderr.take();
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let (mut stdout_buf, mut stderr_buf) = ([0u8; 8192], [0u8; 8192]);
+
+    while stdout_pipe.is_some() || stderr_pipe.is_some() {
+        let (read, from_stdout) = tokio::select! {
+            n = read_pipe(&mut stdout_pipe, &mut stdout_buf) => (n?, true),
+            n = read_pipe(&mut stderr_pipe, &mut stderr_buf) => (n?, false),
+        };
+        match (read, from_stdout) {
+            (0, true) => stdout_pipe = None,
+            (0, false) => stderr_pipe = None,
+            (n, true) => stdout.extend_from_slice(&stdout_buf[..n]),
+            (n, false) => stderr.extend_from_slice(&stderr_buf[..n]),
+        }
+        if let Some(progress) = progress
+            && read > 0
+        {
+            let bytes = stdout.len() + stderr.len();
+            progress.report(
+                ToolProgress::new(bytes as f64).with_message(format!("{bytes} bytes of output")),
+            );
+        }
+    }
+
+    let status = child.wait().await?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Read from `pipe`, or wait forever once it is closed.
+async fn read_pipe<R: AsyncRead + Unpin>(
+    pipe: &mut Option<R>,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    match pipe {
+        Some(pipe) => pipe.read(buf).await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Truncate output to `MAX_OUTPUT_BYTES` and convert to string.
 fn truncate_output(bytes: &[u8]) -> String {
     let truncated = if bytes.len() > MAX_OUTPUT_BYTES {
@@ -200,4 +269,29 @@ mod tests {
         let limiter = ConcurrencyLimiter::new(0);
         assert!(!limiter.try_acquire());
     }
+
+    struct Recorder(std::sync::Mutex<Vec<f64>>);
+
+    impl ProgressReporter for Recorder {
+        fn report(&self, progress: ToolProgress) {
+            self.0.lock().unwrap().push(progress.progress);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn collect_output_reports_bytes_read() {
+        let child = Command::new("sh")
+            .args(["-c", "printf 'step 1\\n'; sleep 0.1; printf 'step 2\\n'"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let recorder = Recorder(std::sync::Mutex::new(Vec::new()));
+
+        let output = collect_output(child, Some(&recorder)).await.unwrap();
+
+        assert_eq!(output.stdout, b"step 1\nstep 2\n");
+        assert_eq!(recorder.0.into_inner().unwrap(), [7.0, 14.0]);
+    }
 }
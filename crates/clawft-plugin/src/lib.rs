@@ -76,5 +76,6 @@ pub use sandbox::{
 };
 pub use traits::{
     CancellationToken, ChannelAdapter, ChannelAdapterHost, KeyValueStore, MemoryBackend,
-    PipelineStage, PipelineStageType, Skill, Tool, ToolContext, VoiceHandler,
+    PipelineStage, PipelineStageType, ProgressReporter, Skill, Tool, ToolContext, ToolProgress,
+    VoiceHandler,
 };
@@ -9,6 +9,7 @@
 //! - [`VoiceHandler`] -- placeholder for voice forward-compat
 //! - [`KeyValueStore`] -- key-value storage for plugins
 //! - [`ToolContext`] -- execution context for tools
+//! - [`ProgressReporter`] -- progress of long-running tool calls
 //! - [`ChannelAdapterHost`] -- host services for channel adapters
 //!
 //! All traits are `Send + Sync`. Async methods use `#[async_trait]`.
@@ -254,7 +255,11 @@ pub trait Skill: Send + Sync {
 /// Supports key-value storage with optional namespace isolation,
 /// TTL, tags, and semantic search. Implementations may use
 /// in-memory stores, SQLite, HNSW indices, or external services.
-#[async_trait]
+///
+/// With the `browser` feature the returned futures are not `Send`, so
+/// backends built on single-threaded WASM APIs can implement it.
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
 pub trait MemoryBackend: Send + Sync {
     /// Store a value with optional metadata.
     async fn store(
@@ -289,6 +294,53 @@ pub trait MemoryBackend: Send + Sync {
         key: &str,
         namespace: Option<&str>,
     ) -> Result<bool, PluginError>;
+
+    /// Whether [`search`](Self::search) orders results by relevance.
+    ///
+    /// Callers that need ranked results rank the entries themselves when
+    /// this is `false`. The default is `false`.
+    fn ranks_search(&self) -> bool {
+        false
+    }
+
+    /// List the `(key, value)` pairs of a namespace, oldest first.
+    ///
+    /// The default implementation reports listing as unsupported.
+    async fn list(&self, namespace: Option<&str>) -> Result<Vec<(String, String)>, PluginError> {
+        let _ = namespace;
+        Err(PluginError::NotImplemented("listing memory entries".into()))
+    }
+
+    /// Replace the `(key, value)` pairs of a namespace with `entries`, in
+    /// order, if its [`list`](Self::list)ing is still `expected`.
+    ///
+    /// Returns `false`, changing nothing, when another writer changed the
+    /// namespace first. The default implementation compares the listing,
+    /// then deletes and re-stores only the entries after the longest
+    /// prefix the two have in common. It is not atomic; backends that can
+    /// check and write in one step should.
+    async fn replace_if_unchanged(
+        &self,
+        namespace: Option<&str>,
+        expected: &[(String, String)],
+        entries: &[(String, String)],
+    ) -> Result<bool, PluginError> {
+        if self.list(namespace).await? != expected {
+            return Ok(false);
+        }
+        let common = expected
+            .iter()
+            .zip(entries)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for (key, _) in &expected[common..] {
+            self.delete(key, namespace).await?;
+        }
+        for (key, value) in &entries[common..] {
+            self.store(key, value, namespace, None, None).await?;
+        }
+        Ok(true)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -363,6 +415,66 @@ pub trait ToolContext: Send + Sync {
 
     /// The ID of the agent invoking this tool.
     fn agent_id(&self) -> &str;
+
+    /// Cancelled when the agent turn that invoked this tool is aborted.
+    ///
+    /// Long-running tools should watch it and stop their work (e.g. kill
+    /// a subprocess). `None` when the host does not support cancellation.
+    fn cancellation(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    /// Where to report progress while the tool runs.
+    ///
+    /// Long-running tools should report steps completed or output read,
+    /// so the caller can tell the call is alive. `None` when nobody is
+    /// listening.
+    fn progress(&self) -> Option<&dyn ProgressReporter> {
+        None
+    }
+}
+
+/// How far a running tool call has got.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToolProgress {
+    /// Work done so far (steps, bytes of output, ...). Increases with
+    /// every report.
+    pub progress: f64,
+    /// Total amount of work, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    /// What the tool is doing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ToolProgress {
+    /// Progress `progress` out of an unknown total.
+    pub fn new(progress: f64) -> Self {
+        Self {
+            progress,
+            total: None,
+            message: None,
+        }
+    }
+
+    /// Set the total amount of work.
+    pub fn with_total(mut self, total: f64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Set the message.
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Receives progress from running tools (see [`ToolContext::progress`]).
+pub trait ProgressReporter: Send + Sync {
+    /// Report that the tool has got as far as `progress`.
+    fn report(&self, progress: ToolProgress);
 }
 
 // ---------------------------------------------------------------------------
@@ -389,6 +501,7 @@ mod tests {
         // Supporting traits
         assert_send_sync::<dyn KeyValueStore>();
         assert_send_sync::<dyn ToolContext>();
+        assert_send_sync::<dyn ProgressReporter>();
         assert_send_sync::<dyn ChannelAdapterHost>();
     }
 
@@ -704,6 +817,8 @@ mod tests {
         assert_eq!(results[0].0, "key");
         let deleted = backend.delete("key", None).await.unwrap();
         assert!(deleted);
+        let listed = backend.list(None).await;
+        assert!(matches!(listed, Err(PluginError::NotImplemented(_))));
     }
 
     #[tokio::test]
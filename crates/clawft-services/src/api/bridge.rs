@@ -186,8 +186,8 @@ impl AgentAccess for AgentBridge {
 /// Wraps an `Arc<MessageBus>` and implements [`BusAccess`].
 ///
 /// Publishes inbound messages to the bus so they are picked up by the
-/// agent loop. Uses `publish_inbound` (sync try_send) which is safe
-/// to call from sync context.
+/// agent loop. Each message is published from a spawned task, so a full
+/// bus makes the message wait instead of dropping it.
 pub struct BusBridge {
     bus: Arc<MessageBus>,
 }
@@ -211,12 +211,16 @@ impl BusAccess for BusBridge {
             content: content.to_string(),
             timestamp: chrono::Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
 
-        if let Err(e) = self.bus.publish_inbound(msg) {
-            warn!(error = %e, "failed to publish inbound message from API");
-        }
+        let bus = self.bus.clone();
+        tokio::spawn(async move {
+            if let Err(e) = bus.publish(msg).await {
+                warn!(error = %e, "failed to publish inbound message from API");
+            }
+        });
     }
 }
 
@@ -1,19 +1,24 @@
 //! Channel status API routes.
 //!
-//! Provides endpoints for listing channel connection statuses.
+//! Provides endpoints for listing channel connection statuses and
+//! per-channel traffic metrics, plus the Prometheus `/metrics` handler
+//! mounted at the router root (which also reports MCP server health).
 
 use axum::{
     extract::State,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
 
-use super::{ApiState, ChannelStatusInfo};
+use super::{ApiState, ChannelMetricsInfo, ChannelStatusInfo};
 
 /// Build channel status API routes.
 pub fn channel_routes() -> Router<ApiState> {
     Router::new()
         .route("/channels", get(list_channels))
+        .route("/channels/metrics", get(channel_metrics))
 }
 
 // ── Handlers ───────────────────────────────────────────────────
@@ -21,3 +26,21 @@ pub fn channel_routes() -> Router<ApiState> {
 async fn list_channels(State(state): State<ApiState>) -> Json<Vec<ChannelStatusInfo>> {
     Json(state.channels.list_channels())
 }
+
+async fn channel_metrics(State(state): State<ApiState>) -> Json<Vec<ChannelMetricsInfo>> {
+    Json(state.channels.channel_metrics())
+}
+
+/// Prometheus scrape endpoint, covering channels and MCP servers; 404
+/// unless enabled in the gateway config.
+pub(crate) async fn prometheus_metrics(State(state): State<ApiState>) -> Response {
+    match state.channels.prometheus_metrics() {
+        Some(mut body) => {
+            body.push_str(&crate::mcp::health::render_prometheus(
+                &state.mcp.snapshot(),
+            ));
+            ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -1,15 +1,21 @@
 //! Delegation monitoring API routes.
 //!
 //! Provides endpoints for viewing active delegations, managing delegation
-//! rules, and browsing delegation history.
+//! rules, and browsing delegation history, plus the task endpoints peer
+//! gateways delegate work through (see [`super::remote_tasks`]).
 
 use axum::{
-    extract::{Path, Query, State},
-    routing::{delete, get, patch},
     Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
 };
+use clawft_types::delegation::RemoteTaskRequest;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
+use super::remote_tasks::SubmitError;
 use super::ApiState;
 
 /// Build delegation API routes.
@@ -20,6 +26,8 @@ pub fn delegation_routes() -> Router<ApiState> {
         .route("/delegation/rules", patch(upsert_delegation_rule))
         .route("/delegation/rules/{name}", delete(delete_delegation_rule))
         .route("/delegation/history", get(delegation_history))
+        .route("/delegation/tasks", post(submit_remote_task))
+        .route("/delegation/tasks/{id}", get(remote_task_status))
 }
 
 // ── Types ──────────────────────────────────────────────────────
@@ -86,6 +94,60 @@ pub struct PaginatedHistory {
 
 // ── Handlers ───────────────────────────────────────────────────
 
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// The response refusing the request, unless task serving is on and it
+/// carries the delegation token. Answers 404 while serving is off so the
+/// endpoint does not advertise itself.
+fn peer_rejection(state: &ApiState, headers: &HeaderMap) -> Option<Response> {
+    if !state.remote_tasks.enabled() {
+        return Some(error(
+            StatusCode::NOT_FOUND,
+            "delegated tasks are not served here",
+        ));
+    }
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !state.remote_tasks.authorize(token) {
+        return Some(error(StatusCode::UNAUTHORIZED, "invalid delegation token"));
+    }
+    None
+}
+
+async fn submit_remote_task(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(request): Json<RemoteTaskRequest>,
+) -> Response {
+    if let Some(response) = peer_rejection(&state, &headers) {
+        return response;
+    }
+    match state.remote_tasks.submit(request) {
+        Ok(status) => (StatusCode::ACCEPTED, Json(status)).into_response(),
+        Err(e @ SubmitError::Rejected(_)) => error(StatusCode::BAD_REQUEST, e.to_string()),
+        Err(e @ SubmitError::Busy(_)) => error(StatusCode::TOO_MANY_REQUESTS, e.to_string()),
+    }
+}
+
+async fn remote_task_status(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Some(response) = peer_rejection(&state, &headers) {
+        return response;
+    }
+    match state.remote_tasks.status(&id) {
+        Some(status) => Json(status).into_response(),
+        None => error(StatusCode::NOT_FOUND, format!("no delegated task '{id}'")),
+    }
+}
+
 async fn list_active_delegations(
     State(_state): State<ApiState>,
 ) -> Json<Vec<ActiveDelegation>> {
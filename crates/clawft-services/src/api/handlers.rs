@@ -41,6 +41,8 @@ pub fn api_routes() -> Router<ApiState> {
         .merge(super::cron_api::cron_routes())
         // Channels
         .merge(super::channels_api::channel_routes())
+        // MCP servers
+        .merge(super::mcp_api::mcp_routes())
         // Chat (session messages, create, export)
         .merge(super::chat::chat_routes())
         // Voice
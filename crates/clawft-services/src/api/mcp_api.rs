@@ -0,0 +1,20 @@
+//! MCP server health API route.
+//!
+//! Serves each connected MCP server's [`HealthSnapshot`]: up or down,
+//! circuit state, call counters, latency percentiles and recent errors.
+
+use axum::{Json, Router, extract::State, routing::get};
+
+use super::ApiState;
+use crate::mcp::health::HealthSnapshot;
+
+/// Build MCP API routes.
+pub fn mcp_routes() -> Router<ApiState> {
+    Router::new().route("/mcp/health", get(mcp_health))
+}
+
+// ── Handlers ───────────────────────────────────────────────────
+
+async fn mcp_health(State(state): State<ApiState>) -> Json<Vec<HealthSnapshot>> {
+    Json(state.mcp.snapshot())
+}
@@ -12,8 +12,10 @@ pub mod config_api;
 pub mod cron_api;
 pub mod delegation;
 pub mod handlers;
+pub mod mcp_api;
 pub mod memory_api;
 pub mod monitoring;
+pub mod remote_tasks;
 pub mod skills;
 pub mod voice_api;
 pub mod ws;
@@ -47,8 +49,12 @@ pub struct ApiState {
     pub channels: Arc<dyn ChannelAccess>,
     /// Voice configuration access.
     pub voice: Arc<dyn VoiceAccess>,
+    /// Health of the connected MCP servers.
+    pub mcp: Arc<crate::mcp::health::McpHealthRegistry>,
     /// Topic-based broadcaster for real-time WebSocket events.
     pub broadcaster: Arc<broadcaster::TopicBroadcaster>,
+    /// Tasks delegated to this gateway by peers.
+    pub remote_tasks: Arc<remote_tasks::RemoteTasks>,
 }
 
 /// Trait for tool registry access (decouples API from Platform generics).
@@ -177,6 +183,33 @@ pub trait ConfigAccess: Send + Sync {
 pub trait ChannelAccess: Send + Sync {
     /// List all channel statuses.
     fn list_channels(&self) -> Vec<ChannelStatusInfo>;
+
+    /// Per-channel traffic counters. Empty when metrics are unavailable.
+    fn channel_metrics(&self) -> Vec<ChannelMetricsInfo> {
+        Vec::new()
+    }
+
+    /// Metrics in the Prometheus text exposition format, or `None` when
+    /// the Prometheus endpoint is disabled.
+    fn prometheus_metrics(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Traffic counters for a channel.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ChannelMetricsInfo {
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub inbound_received: u64,
+    pub outbound_sent: u64,
+    pub send_failures: u64,
+    pub reconnects: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_inbound: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_outbound: Option<String>,
 }
 
 /// Status info for a channel.
@@ -313,7 +346,11 @@ pub fn build_router(state: ApiState, cors_origins: &[String], static_dir: Option
         //           state.clone(), auth::auth_middleware)))
         // This is intentionally disabled for now to keep the dev workflow
         // simple (no token required). Enable once the UI has a login flow.
-        .route("/ws", axum::routing::get(ws::ws_handler));
+        .route("/ws", axum::routing::get(ws::ws_handler))
+        .route(
+            "/metrics",
+            axum::routing::get(channels_api::prometheus_metrics),
+        );
 
     // Serve built UI as SPA fallback when a static directory is provided.
     if let Some(dir) = static_dir {
@@ -0,0 +1,448 @@
+//! Tasks handed over by peer gateways.
+//!
+//! A peer posts a [`RemoteTaskRequest`] to `/api/delegation/tasks` with the
+//! shared `delegation.serve` token. [`RemoteTasks`] puts the prompt on the
+//! bus as a message on the [`CHANNEL`] channel, one chat per task, so this
+//! gateway's routing rules and permissions decide which agent runs it and
+//! with what tools. An agent the peer names is only honoured when it is
+//! listed in `delegation.serve.agents`, and the peer's limits can only
+//! tighten this gateway's own.
+//!
+//! The agent loop reports the finished turn to [`RemoteTasks`] as a
+//! [`TurnObserver`]; the usage tracker supplies the task's LLM usage and
+//! cost, which the peer adds to its own accounting.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use clawft_core::agent::trace::TurnOutcome;
+use clawft_core::agent::turns::{TurnObserver, TurnReport};
+use clawft_core::bus::MessageBus;
+use clawft_llm::UsageTracker;
+use clawft_types::delegation::{
+    DelegationServeConfig, RemoteTaskRequest, RemoteTaskState, RemoteTaskStatus, RemoteUsage,
+};
+use clawft_types::event::{InboundMessage, TurnLimits};
+use clawft_types::secret::SecretString;
+use tracing::{info, warn};
+
+/// Channel delegated tasks arrive on. Routing rules and
+/// `routing.permissions.channels` entries for it apply to them.
+pub const CHANNEL: &str = "delegation";
+
+/// Sender id of delegated tasks.
+const SENDER: &str = "peer";
+
+/// How long a task may wait in the inbound queue before its turn starts.
+const QUEUE_GRACE_SECS: i64 = 60;
+
+/// How long finished tasks can still be looked up.
+const RETENTION_SECS: i64 = 3600;
+
+/// Why a task was not accepted.
+#[derive(Debug, thiserror::Error)]
+pub enum SubmitError {
+    /// The request itself is not acceptable.
+    #[error("{0}")]
+    Rejected(String),
+    /// The gateway is at capacity; the peer may retry later.
+    #[error("{0}")]
+    Busy(String),
+}
+
+struct Task {
+    status: RemoteTaskStatus,
+    /// After this the task is reported as failed if its turn has not
+    /// ended.
+    deadline: DateTime<Utc>,
+}
+
+/// Delegated tasks this gateway has accepted.
+pub struct RemoteTasks {
+    token: Option<SecretString>,
+    agents: Vec<String>,
+    max_concurrent: usize,
+    max_task_secs: u64,
+    bus: Arc<MessageBus>,
+    usage: Option<Arc<UsageTracker>>,
+    tasks: Mutex<HashMap<String, Task>>,
+}
+
+impl RemoteTasks {
+    /// Run tasks under `config`, publishing them on `bus`. Serving stays
+    /// off unless `config.enabled` is set and a `token` was resolved.
+    pub fn new(
+        config: &DelegationServeConfig,
+        token: Option<SecretString>,
+        bus: Arc<MessageBus>,
+    ) -> Self {
+        Self {
+            token: token.filter(|_| config.enabled),
+            agents: config.agents.clone(),
+            max_concurrent: config.max_concurrent.max(1) as usize,
+            max_task_secs: config.max_task_secs.max(1),
+            bus,
+            usage: None,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Report the usage `usage` records for each task's session.
+    pub fn with_usage(mut self, usage: Arc<UsageTracker>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Whether this gateway accepts delegated tasks.
+    pub fn enabled(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Whether `token` is the shared delegation token.
+    pub fn authorize(&self, token: &str) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(|t| constant_time_eq(token.as_bytes(), t.expose().as_bytes()))
+    }
+
+    /// Accept `request` and queue it for the agent loop.
+    pub fn submit(&self, request: RemoteTaskRequest) -> Result<RemoteTaskStatus, SubmitError> {
+        if request.prompt.trim().is_empty() {
+            return Err(SubmitError::Rejected("prompt must not be empty".into()));
+        }
+        if let Some(agent) = &request.agent
+            && !self.agents.contains(agent)
+        {
+            return Err(SubmitError::Rejected(format!(
+                "agent '{agent}' does not take delegated tasks"
+            )));
+        }
+        if request.max_iterations == Some(0)
+            || request.max_tokens == Some(0)
+            || request.timeout_secs == Some(0)
+        {
+            return Err(SubmitError::Rejected(
+                "max_iterations, max_tokens and timeout_secs must be positive".into(),
+            ));
+        }
+
+        let now = Utc::now();
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.retain(|_, task| {
+            task.status
+                .finished_at
+                .is_none_or(|at| now - at < Duration::seconds(RETENTION_SECS))
+        });
+        let running = tasks
+            .values()
+            .filter(|task| task.status.state == RemoteTaskState::Running && now < task.deadline)
+            .count();
+        if running >= self.max_concurrent {
+            return Err(SubmitError::Busy(format!(
+                "already running {running} delegated task(s); try again later"
+            )));
+        }
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let max_secs = request
+            .timeout_secs
+            .map_or(self.max_task_secs, |secs| secs.min(self.max_task_secs));
+        let limits = TurnLimits {
+            max_iterations: request.max_iterations,
+            max_tokens: request.max_tokens,
+            max_secs: Some(max_secs),
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            InboundMessage::LIMITS_KEY.into(),
+            serde_json::to_value(limits).unwrap_or_default(),
+        );
+        if let Some(agent) = &request.agent {
+            metadata.insert("agent".into(), agent.clone().into());
+        }
+        self.bus
+            .publish_inbound(InboundMessage {
+                channel: CHANNEL.into(),
+                sender_id: SENDER.into(),
+                chat_id: id.clone(),
+                content: request.prompt,
+                timestamp: now,
+                media: vec![],
+                attachments: vec![],
+                metadata,
+            })
+            .map_err(|e| SubmitError::Busy(e.to_string()))?;
+
+        info!(task = %id, agent = ?request.agent, max_secs, "accepted delegated task");
+        let status = RemoteTaskStatus {
+            id: id.clone(),
+            state: RemoteTaskState::Running,
+            answer: None,
+            error: None,
+            usage: Vec::new(),
+            submitted_at: now,
+            finished_at: None,
+        };
+        let deadline = now + Duration::seconds(max_secs as i64 + QUEUE_GRACE_SECS);
+        tasks.insert(
+            id,
+            Task {
+                status: status.clone(),
+                deadline,
+            },
+        );
+        Ok(status)
+    }
+
+    /// The task `id`, with its usage so far.
+    pub fn status(&self, id: &str) -> Option<RemoteTaskStatus> {
+        let mut status = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            let task = tasks.get_mut(id)?;
+            let now = Utc::now();
+            if task.status.state == RemoteTaskState::Running && now >= task.deadline {
+                let waited = (now - task.status.submitted_at).num_seconds();
+                warn!(task = id, "delegated task never finished");
+                task.status.state = RemoteTaskState::Failed;
+                task.status.error = Some(format!("task did not finish within {waited}s"));
+                task.status.finished_at = Some(now);
+            }
+            task.status.clone()
+        };
+        status.usage = self.usage_of(id);
+        Some(status)
+    }
+
+    /// LLM usage recorded for task `id`'s session.
+    fn usage_of(&self, id: &str) -> Vec<RemoteUsage> {
+        let Some(usage) = &self.usage else {
+            return Vec::new();
+        };
+        let session = format!("{CHANNEL}:{id}");
+        usage
+            .snapshot()
+            .into_iter()
+            .filter(|(key, _)| key.session == session)
+            .map(|(key, totals)| RemoteUsage {
+                provider: key.provider,
+                model: key.model,
+                requests: totals.requests,
+                input_tokens: totals.input_tokens,
+                output_tokens: totals.output_tokens,
+                cost_usd: totals.cost_usd,
+            })
+            .collect()
+    }
+}
+
+impl TurnObserver for RemoteTasks {
+    fn on_turn_end(&self, msg: &InboundMessage, report: &TurnReport) {
+        if msg.channel != CHANNEL {
+            return;
+        }
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(task) = tasks.get_mut(&msg.chat_id) else {
+            return;
+        };
+        let status = &mut task.status;
+        match report.outcome {
+            TurnOutcome::Completed => {
+                status.state = RemoteTaskState::Completed;
+                status.answer = Some(report.output.clone());
+            }
+            TurnOutcome::Cancelled => status.state = RemoteTaskState::Cancelled,
+            TurnOutcome::Error => {
+                status.state = RemoteTaskState::Failed;
+                status.error = report.error.clone();
+            }
+        }
+        status.finished_at = Some(report.finished_at);
+        info!(task = %msg.chat_id, state = ?status.state, "delegated task finished");
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_llm::PriceTable;
+    use clawft_types::provider::Usage;
+
+    fn serve_config() -> DelegationServeConfig {
+        DelegationServeConfig {
+            enabled: true,
+            agents: vec!["tester".into()],
+            max_concurrent: 1,
+            max_task_secs: 120,
+            ..Default::default()
+        }
+    }
+
+    fn tasks(bus: Arc<MessageBus>) -> RemoteTasks {
+        RemoteTasks::new(&serve_config(), Some(SecretString::new("s3cret")), bus)
+    }
+
+    fn request(prompt: &str) -> RemoteTaskRequest {
+        RemoteTaskRequest {
+            prompt: prompt.into(),
+            agent: None,
+            max_iterations: None,
+            max_tokens: None,
+            timeout_secs: None,
+        }
+    }
+
+    fn report(outcome: TurnOutcome, output: &str, error: Option<&str>) -> TurnReport {
+        TurnReport {
+            started_at: Utc::now(),
+            finished_at: Utc::now(),
+            outcome,
+            error: error.map(Into::into),
+            output: output.into(),
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn serving_needs_enabled_and_a_token() {
+        let bus = Arc::new(MessageBus::new());
+        let served = tasks(bus.clone());
+        assert!(served.enabled());
+        assert!(served.authorize("s3cret"));
+        assert!(!served.authorize("s3cre"));
+        assert!(!served.authorize(""));
+
+        let disabled = RemoteTasks::new(
+            &DelegationServeConfig::default(),
+            Some(SecretString::new("s3cret")),
+            bus.clone(),
+        );
+        assert!(!disabled.enabled());
+        assert!(!disabled.authorize("s3cret"));
+        assert!(!RemoteTasks::new(&serve_config(), None, bus).enabled());
+    }
+
+    #[tokio::test]
+    async fn submit_queues_a_delegation_message_with_capped_limits() {
+        let bus = Arc::new(MessageBus::new());
+        let served = tasks(bus.clone());
+        let status = served
+            .submit(RemoteTaskRequest {
+                agent: Some("tester".into()),
+                max_iterations: Some(4),
+                timeout_secs: Some(3600),
+                ..request("run the test suite")
+            })
+            .unwrap();
+        assert_eq!(status.state, RemoteTaskState::Running);
+
+        let msg = bus.consume_inbound().await.unwrap();
+        assert_eq!(msg.channel, CHANNEL);
+        assert_eq!(msg.sender_id, SENDER);
+        assert_eq!(msg.chat_id, status.id);
+        assert_eq!(msg.content, "run the test suite");
+        assert_eq!(msg.metadata["agent"], "tester");
+        assert_eq!(
+            msg.turn_limits(),
+            TurnLimits {
+                max_iterations: Some(4),
+                max_tokens: None,
+                max_secs: Some(120),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn submit_refuses_unlisted_agents_bad_limits_and_overload() {
+        let bus = Arc::new(MessageBus::new());
+        let served = tasks(bus.clone());
+        let err = served
+            .submit(RemoteTaskRequest {
+                agent: Some("admin".into()),
+                ..request("rm -rf")
+            })
+            .unwrap_err();
+        assert!(matches!(err, SubmitError::Rejected(_)), "{err}");
+        assert!(matches!(
+            served.submit(request("  ")),
+            Err(SubmitError::Rejected(_))
+        ));
+        assert!(matches!(
+            served.submit(RemoteTaskRequest {
+                max_tokens: Some(0),
+                ..request("x")
+            }),
+            Err(SubmitError::Rejected(_))
+        ));
+
+        let first = served.submit(request("one")).unwrap();
+        assert!(matches!(
+            served.submit(request("two")),
+            Err(SubmitError::Busy(_))
+        ));
+        let msg = bus.consume_inbound().await.unwrap();
+        served.on_turn_end(&msg, &report(TurnOutcome::Completed, "done", None));
+        assert_eq!(
+            served.status(&first.id).unwrap().state,
+            RemoteTaskState::Completed
+        );
+        assert!(served.submit(request("two")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn turn_reports_finish_tasks_with_their_usage() {
+        let bus = Arc::new(MessageBus::new());
+        let usage = Arc::new(UsageTracker::new(PriceTable::default()));
+        let served = RemoteTasks::new(
+            &DelegationServeConfig {
+                max_concurrent: 4,
+                ..serve_config()
+            },
+            Some(SecretString::new("s3cret")),
+            bus.clone(),
+        )
+        .with_usage(usage.clone());
+
+        let ok = served.submit(request("summarize")).unwrap();
+        let msg = bus.consume_inbound().await.unwrap();
+        let tokens = Usage {
+            input_tokens: 1000,
+            output_tokens: 200,
+            ..Default::default()
+        };
+        usage.record("anthropic", "claude-sonnet-4", &msg.session_key(), &tokens);
+        usage.record("anthropic", "claude-sonnet-4", "telegram:42", &tokens);
+        served.on_turn_end(&msg, &report(TurnOutcome::Completed, "all green", None));
+
+        let status = served.status(&ok.id).unwrap();
+        assert_eq!(status.state, RemoteTaskState::Completed);
+        assert_eq!(status.answer.as_deref(), Some("all green"));
+        assert!(status.finished_at.is_some());
+        assert_eq!(status.usage.len(), 1);
+        assert_eq!(status.usage[0].provider, "anthropic");
+        assert_eq!(status.usage[0].requests, 1);
+        assert_eq!(status.usage[0].input_tokens, 1000);
+        assert!(status.usage[0].cost_usd > 0.0);
+
+        let failed = served.submit(request("deploy")).unwrap();
+        let msg = bus.consume_inbound().await.unwrap();
+        served.on_turn_end(
+            &msg,
+            &report(TurnOutcome::Error, "", Some("turn token budget exceeded")),
+        );
+        let status = served.status(&failed.id).unwrap();
+        assert_eq!(status.state, RemoteTaskState::Failed);
+        assert_eq!(status.error.as_deref(), Some("turn token budget exceeded"));
+        assert!(status.usage.is_empty());
+
+        assert!(served.status("unknown").is_none());
+    }
+}
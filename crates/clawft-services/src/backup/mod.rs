@@ -0,0 +1,512 @@
+//! Scheduled backups of the clawft data directory.
+//!
+//! [`BackupService`] archives the paths in [`BACKUP_PATHS`] (config,
+//! workspace memory and sessions, skills) under `~/.clawft` into a
+//! timestamped `.tar.gz`, then deletes all but the newest `keep` archives.
+//! Files matching [`SECRET_PATTERNS`] are never archived, and neither is
+//! anything matching the configured exclude list. With the `backup-s3`
+//! feature and an [`S3Uploader`](s3::S3Uploader), each archive is also
+//! uploaded to an S3-compatible bucket.
+//!
+//! [`RestorePlan`] unpacks a backup into a staging directory and compares
+//! it with the data directory, so callers can show which files would be
+//! created or overwritten before [`RestorePlan::apply`] copies them into
+//! place.
+
+#[cfg(feature = "backup-s3")]
+pub mod s3;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use clawft_platform::archive::{self, ExtractLimits};
+use clawft_platform::fs::walk::IgnoreRules;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::error::{Result, ServiceError};
+use clawft_types::config::BackupConfig;
+
+/// Paths backed up, relative to the data directory (`~/.clawft`).
+pub const BACKUP_PATHS: &[&str] = &[
+    "config.json",
+    "workspace/memory",
+    "workspace/sessions",
+    "skills",
+];
+
+/// Gitignore-style patterns of secrets files, never backed up.
+pub const SECRET_PATTERNS: &[&str] = &[
+    ".env",
+    ".env.*",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "id_rsa*",
+    "id_ed25519*",
+    "credentials*",
+    "*secret*",
+    "keys/",
+    "tokens/",
+];
+
+/// File name prefix of backup archives.
+const ARCHIVE_PREFIX: &str = "clawft-backup-";
+
+/// File name extension of backup archives.
+const ARCHIVE_EXT: &str = ".tar.gz";
+
+/// Takes, prunes and (optionally) uploads backups.
+#[derive(Clone)]
+pub struct BackupService {
+    home: PathBuf,
+    dir: PathBuf,
+    keep: usize,
+    interval: Duration,
+    ignore: IgnoreRules,
+    #[cfg(feature = "backup-s3")]
+    upload: Option<s3::S3Uploader>,
+}
+
+impl BackupService {
+    /// Back up the data directory `home` as `config` describes. Archives go
+    /// to `config.dir`, or `home/backups` when unset.
+    pub fn new(config: &BackupConfig, home: PathBuf) -> Self {
+        let dir = config
+            .dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("backups"));
+        let patterns: Vec<&str> = SECRET_PATTERNS
+            .iter()
+            .copied()
+            .chain(config.exclude.iter().map(String::as_str))
+            .collect();
+        Self {
+            home,
+            dir,
+            keep: config.keep.max(1),
+            interval: Duration::from_secs(config.interval_hours.max(1) * 3600),
+            ignore: IgnoreRules::new(&patterns),
+            #[cfg(feature = "backup-s3")]
+            upload: None,
+        }
+    }
+
+    /// Upload every backup through `upload` as well.
+    #[cfg(feature = "backup-s3")]
+    pub fn with_upload(mut self, upload: s3::S3Uploader) -> Self {
+        self.upload = Some(upload);
+        self
+    }
+
+    /// Directory the archives are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Take a backup now, prune old ones, and upload it when an uploader
+    /// is set. Returns the archive path.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there is nothing to back up or the archive cannot be
+    /// written. A failed upload is an error too, but the archive is kept.
+    pub async fn backup(&self) -> Result<PathBuf> {
+        let service = self.clone();
+        let path = tokio::task::spawn_blocking(move || service.snapshot())
+            .await
+            .map_err(|e| ServiceError::Backup(format!("backup task failed: {e}")))??;
+        #[cfg(feature = "backup-s3")]
+        if let Some(upload) = &self.upload {
+            let key = upload.put(&path).await.map_err(|e| {
+                ServiceError::Backup(format!("saved {}, but upload failed: {e}", path.display()))
+            })?;
+            info!(key = %key, "backup uploaded");
+        }
+        Ok(path)
+    }
+
+    /// Take a backup every interval until `cancel` fires. The first one is
+    /// taken one interval after starting.
+    pub async fn start(&self, cancel: CancellationToken) -> Result<()> {
+        info!(
+            interval_hours = self.interval.as_secs() / 3600,
+            keep = self.keep,
+            dir = %self.dir.display(),
+            "backup service started"
+        );
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    info!("backup service shutting down");
+                    return Ok(());
+                }
+                _ = tokio::time::sleep(self.interval) => {}
+            }
+            match self.backup().await {
+                Ok(path) => info!(path = %path.display(), "backup taken"),
+                Err(e) => warn!(error = %e, "backup failed"),
+            }
+        }
+    }
+
+    /// Write a new archive and prune old ones (blocking).
+    fn snapshot(&self) -> Result<PathBuf> {
+        let paths: Vec<PathBuf> = BACKUP_PATHS
+            .iter()
+            .map(|rel| self.home.join(rel))
+            .filter(|path| path.exists())
+            .collect();
+        if paths.is_empty() {
+            return Err(ServiceError::Backup(format!(
+                "nothing to back up in {}",
+                self.home.display()
+            )));
+        }
+        std::fs::create_dir_all(&self.dir)?;
+
+        let name = format!(
+            "{ARCHIVE_PREFIX}{}{ARCHIVE_EXT}",
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        );
+        // Written under a hidden name first, so a half-written archive is
+        // never listed or pruned as a backup.
+        let partial = self.dir.join(format!(".{name}"));
+        let path = self.dir.join(&name);
+        let files = match archive::create_filtered(&partial, &self.home, &paths, &self.ignore) {
+            Ok(files) => files,
+            Err(e) => {
+                let _ = std::fs::remove_file(&partial);
+                return Err(ServiceError::Backup(e.to_string()));
+            }
+        };
+        std::fs::rename(&partial, &path)?;
+        info!(path = %path.display(), files, "backup written");
+
+        for old in prune(&self.dir, self.keep)? {
+            info!(path = %old.display(), "old backup deleted");
+        }
+        Ok(path)
+    }
+}
+
+/// Backup archives in `dir`, oldest first.
+pub fn list_backups(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_backup = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(ARCHIVE_PREFIX) && n.ends_with(ARCHIVE_EXT));
+        if is_backup && path.is_file() {
+            backups.push(path);
+        }
+    }
+    // Timestamps in the names sort chronologically.
+    backups.sort();
+    Ok(backups)
+}
+
+/// Delete all but the newest `keep` backups in `dir`. Returns the deleted
+/// paths.
+pub fn prune(dir: &Path, keep: usize) -> Result<Vec<PathBuf>> {
+    let backups = list_backups(dir)?;
+    let excess = backups.len().saturating_sub(keep);
+    let mut deleted = Vec::with_capacity(excess);
+    for path in backups.into_iter().take(excess) {
+        std::fs::remove_file(&path)?;
+        deleted.push(path);
+    }
+    Ok(deleted)
+}
+
+/// What restoring a file does to the data directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreChange {
+    /// The file does not exist yet.
+    Create,
+    /// The file exists with different contents.
+    Overwrite,
+    /// The file exists with the same contents; it is left alone.
+    Unchanged,
+}
+
+/// One file in a [`RestorePlan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreFile {
+    /// Path relative to the data directory, with `/` separators.
+    pub path: String,
+    /// What restoring it does.
+    pub change: RestoreChange,
+    /// Size of the backed-up file.
+    pub size: u64,
+    /// Size of the file it replaces, if there is one.
+    pub current_size: Option<u64>,
+}
+
+/// A backup unpacked into a staging directory, ready to restore.
+///
+/// The staging directory is removed when the plan is dropped.
+#[derive(Debug)]
+pub struct RestorePlan {
+    home: PathBuf,
+    staging: PathBuf,
+    /// Files in the backup, sorted by path.
+    pub files: Vec<RestoreFile>,
+    /// Files in the archive outside [`BACKUP_PATHS`], which are not
+    /// restored.
+    pub skipped: Vec<String>,
+}
+
+impl RestorePlan {
+    /// Unpack `archive` and compare it with the data directory `home`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the archive cannot be read or is unsafe to unpack (see
+    /// [`archive::extract`]).
+    pub fn prepare(archive: &Path, home: &Path) -> Result<Self> {
+        let staging = std::env::temp_dir().join(format!("clawft-restore-{}", uuid::Uuid::new_v4()));
+        let mut plan = Self {
+            home: home.to_path_buf(),
+            staging,
+            files: Vec::new(),
+            skipped: Vec::new(),
+        };
+        archive::extract(archive, &plan.staging, &ExtractLimits::default())
+            .map_err(|e| ServiceError::Backup(format!("{}: {e}", archive.display())))?;
+
+        for item in walk_files(&plan.staging)? {
+            let rel = item
+                .strip_prefix(&plan.staging)
+                .expect("walked below staging")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let restorable = BACKUP_PATHS
+                .iter()
+                .any(|root| rel == *root || rel.starts_with(&format!("{root}/")));
+            if !restorable {
+                plan.skipped.push(rel);
+                continue;
+            }
+            let size = std::fs::metadata(&item)?.len();
+            let target = home.join(&rel);
+            let (change, current_size) = match std::fs::metadata(&target) {
+                Ok(meta) if std::fs::read(&target)? == std::fs::read(&item)? => {
+                    (RestoreChange::Unchanged, Some(meta.len()))
+                }
+                Ok(meta) => (RestoreChange::Overwrite, Some(meta.len())),
+                Err(_) => (RestoreChange::Create, None),
+            };
+            plan.files.push(RestoreFile {
+                path: rel,
+                change,
+                size,
+                current_size,
+            });
+        }
+        plan.files.sort_by(|a, b| a.path.cmp(&b.path));
+        plan.skipped.sort();
+        Ok(plan)
+    }
+
+    /// Files restoring would overwrite.
+    pub fn overwrites(&self) -> impl Iterator<Item = &RestoreFile> {
+        self.files
+            .iter()
+            .filter(|f| f.change == RestoreChange::Overwrite)
+    }
+
+    /// Copy the created and overwritten files into the data directory.
+    /// Returns how many were written.
+    pub fn apply(self) -> Result<usize> {
+        let mut written = 0;
+        for file in &self.files {
+            if file.change == RestoreChange::Unchanged {
+                continue;
+            }
+            let target = self.home.join(&file.path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(self.staging.join(&file.path), &target)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+impl Drop for RestorePlan {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.staging);
+    }
+}
+
+/// Regular files below `dir`, recursively.
+fn walk_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// A data directory with memory, a session, config and a skill, plus
+    /// secrets that must not be backed up.
+    fn data_dir() -> PathBuf {
+        let home = temp_dir();
+        write(&home.join("config.json"), "{}");
+        write(&home.join("workspace/memory/MEMORY.md"), "likes tea");
+        write(&home.join("workspace/sessions/slack_C1.jsonl"), "{}\n");
+        write(&home.join("workspace/sessions/debug.log"), "noise");
+        write(&home.join("skills/notes/SKILL.md"), "# notes");
+        write(&home.join("skills/notes/.env"), "TOKEN=secret");
+        write(&home.join(".env"), "OPENAI_API_KEY=sk");
+        home
+    }
+
+    fn service(home: &Path, keep: usize) -> BackupService {
+        let config = BackupConfig {
+            enabled: true,
+            keep,
+            exclude: vec!["*.log".into()],
+            ..BackupConfig::default()
+        };
+        BackupService::new(&config, home.to_path_buf())
+    }
+
+    #[tokio::test]
+    async fn backups_skip_secrets_and_excluded_files() {
+        let home = data_dir();
+        let path = service(&home, 3).backup().await.unwrap();
+        assert!(path.starts_with(home.join("backups")));
+
+        let out = temp_dir();
+        archive::extract(&path, &out, &ExtractLimits::default()).unwrap();
+        assert!(out.join("config.json").exists());
+        assert!(out.join("workspace/memory/MEMORY.md").exists());
+        assert!(out.join("workspace/sessions/slack_C1.jsonl").exists());
+        assert!(out.join("skills/notes/SKILL.md").exists());
+        assert!(!out.join("skills/notes/.env").exists());
+        assert!(!out.join("workspace/sessions/debug.log").exists());
+        assert!(!out.join(".env").exists());
+        let _ = std::fs::remove_dir_all(&home);
+        let _ = std::fs::remove_dir_all(&out);
+    }
+
+    #[tokio::test]
+    async fn keeps_only_the_newest_backups() {
+        let home = data_dir();
+        let service = service(&home, 2);
+        let mut taken = Vec::new();
+        for _ in 0..4 {
+            taken.push(service.backup().await.unwrap());
+            // Names carry millisecond timestamps.
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(list_backups(service.dir()).unwrap(), taken[2..]);
+        // Unrelated files in the directory are left alone.
+        write(&service.dir().join("notes.txt"), "keep me");
+        assert!(prune(service.dir(), 1).unwrap() == [taken[2].clone()]);
+        assert!(service.dir().join("notes.txt").exists());
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[tokio::test]
+    async fn restore_plan_shows_what_would_change() {
+        let home = data_dir();
+        let path = service(&home, 3).backup().await.unwrap();
+
+        write(&home.join("workspace/memory/MEMORY.md"), "likes coffee");
+        std::fs::remove_file(home.join("workspace/sessions/slack_C1.jsonl")).unwrap();
+
+        let plan = RestorePlan::prepare(&path, &home).unwrap();
+        let change = |p: &str| {
+            plan.files
+                .iter()
+                .find(|f| f.path == p)
+                .map(|f| f.change)
+                .unwrap()
+        };
+        assert_eq!(
+            change("workspace/memory/MEMORY.md"),
+            RestoreChange::Overwrite
+        );
+        assert_eq!(
+            change("workspace/sessions/slack_C1.jsonl"),
+            RestoreChange::Create
+        );
+        assert_eq!(change("config.json"), RestoreChange::Unchanged);
+        let overwrites: Vec<_> = plan.overwrites().map(|f| f.path.as_str()).collect();
+        assert_eq!(overwrites, ["workspace/memory/MEMORY.md"]);
+
+        // Preparing the plan changed nothing.
+        assert_eq!(
+            std::fs::read_to_string(home.join("workspace/memory/MEMORY.md")).unwrap(),
+            "likes coffee"
+        );
+        assert!(!home.join("workspace/sessions/slack_C1.jsonl").exists());
+
+        assert_eq!(plan.apply().unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(home.join("workspace/memory/MEMORY.md")).unwrap(),
+            "likes tea"
+        );
+        assert!(home.join("workspace/sessions/slack_C1.jsonl").exists());
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn restore_ignores_paths_outside_the_backup_set() {
+        let work = temp_dir();
+        let src = work.join("src");
+        write(&src.join("skills/a/SKILL.md"), "# a");
+        write(&src.join("keys/signing.key"), "secret");
+        let path = work.join("crafted.tar.gz");
+        archive::create(&path, &[src.join("skills"), src.join("keys")]).unwrap();
+
+        let home = work.join("home");
+        let plan = RestorePlan::prepare(&path, &home).unwrap();
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.skipped, ["keys/signing.key"]);
+        assert_eq!(plan.apply().unwrap(), 1);
+        assert!(!home.join("keys").exists());
+        let _ = std::fs::remove_dir_all(&work);
+    }
+}
@@ -0,0 +1,202 @@
+//! Per-job execution history.
+//!
+//! Every run of a job is appended as one JSON line to
+//! `<dir>/<job_id>.jsonl`, where `dir` is normally
+//! `~/.clawft/state/cron-history/` (see [`history_dir`]). Only the most
+//! recent runs are kept: once a file holds more than its retention, the
+//! oldest lines are dropped. Outputs longer than [`MAX_OUTPUT_CHARS`] are
+//! cut.
+//!
+//! The CLI reads the files directly with [`load_runs_sync`].
+
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use super::scheduler::CronRun;
+use crate::error::Result;
+
+/// Name of the history directory inside the state directory.
+pub const HISTORY_DIR: &str = "cron-history";
+
+/// Runs kept per job by default.
+pub const DEFAULT_RETENTION: usize = 50;
+
+/// Longest output kept for a run, in characters.
+pub const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// Path of the history directory under the user's home directory.
+pub fn history_dir(home: &Path) -> PathBuf {
+    home.join(".clawft").join("state").join(HISTORY_DIR)
+}
+
+/// Bounded run history of all jobs, one file per job.
+pub struct CronHistory {
+    dir: PathBuf,
+    retention: usize,
+}
+
+impl CronHistory {
+    /// History kept in `dir`, holding at most `retention` runs per job.
+    pub fn new(dir: PathBuf, retention: usize) -> Self {
+        Self {
+            dir,
+            retention: retention.max(1),
+        }
+    }
+
+    /// Append `run` to the history of `job_id`, dropping the oldest runs
+    /// beyond the retention.
+    pub async fn record(&self, job_id: &str, run: &CronRun) -> Result<()> {
+        let mut runs = self.load(job_id).await?;
+        let mut run = run.clone();
+        truncate_output(&mut run.output);
+        runs.push(run);
+        let excess = runs.len().saturating_sub(self.retention);
+        runs.drain(..excess);
+
+        let mut content = String::new();
+        for run in &runs {
+            content.push_str(&serde_json::to_string(run)?);
+            content.push('\n');
+        }
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = run_file(&self.dir, job_id);
+        let tmp = path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp, content).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    /// The recorded runs of `job_id`, oldest first.
+    pub async fn load(&self, job_id: &str) -> Result<Vec<CronRun>> {
+        match tokio::fs::read_to_string(run_file(&self.dir, job_id)).await {
+            Ok(content) => Ok(parse_runs(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete the history of `job_id`.
+    pub async fn remove(&self, job_id: &str) -> Result<()> {
+        match tokio::fs::remove_file(run_file(&self.dir, job_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Synchronously read the recorded runs of `job_id` from `dir`, oldest
+/// first (used by the CLI).
+pub fn load_runs_sync(dir: &Path, job_id: &str) -> std::io::Result<Vec<CronRun>> {
+    match std::fs::read_to_string(run_file(dir, job_id)) {
+        Ok(content) => Ok(parse_runs(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The history file of `job_id`. Path separators in the ID are replaced
+/// so that it cannot name a file outside `dir`.
+fn run_file(dir: &Path, job_id: &str) -> PathBuf {
+    let name: String = job_id
+        .chars()
+        .map(|c| if c == '/' || c == '\\' { '_' } else { c })
+        .collect();
+    dir.join(format!("{name}.jsonl"))
+}
+
+fn parse_runs(content: &str) -> Vec<CronRun> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                warn!(error = %e, "skipping invalid cron history line");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Cut `output` to [`MAX_OUTPUT_CHARS`] characters, marking the cut.
+fn truncate_output(output: &mut String) {
+    if let Some((at, _)) = output.char_indices().nth(MAX_OUTPUT_CHARS) {
+        output.truncate(at);
+        output.push_str("...");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+    use crate::cron_service::scheduler::JobStatus;
+
+    fn run(minute: i64, output: &str) -> CronRun {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minute);
+        CronRun {
+            started_at: start,
+            finished_at: start + Duration::seconds(5),
+            status: JobStatus::Ok,
+            output: output.into(),
+            error: None,
+            input_tokens: 10,
+            output_tokens: 5,
+        }
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("clawft-cron-history-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn retention_drops_oldest_runs() {
+        let dir = temp_dir();
+        let history = CronHistory::new(dir.clone(), 3);
+        for minute in 0..5 {
+            history
+                .record("job-1", &run(minute, &format!("run {minute}")))
+                .await
+                .unwrap();
+        }
+        history.record("job-2", &run(0, "other")).await.unwrap();
+
+        let runs = history.load("job-1").await.unwrap();
+        let outputs: Vec<&str> = runs.iter().map(|r| r.output.as_str()).collect();
+        assert_eq!(outputs, ["run 2", "run 3", "run 4"]);
+        assert_eq!(load_runs_sync(&dir, "job-2").unwrap().len(), 1);
+
+        history.remove("job-1").await.unwrap();
+        assert!(history.load("job-1").await.unwrap().is_empty());
+        history.remove("job-1").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn long_output_is_truncated() {
+        let dir = temp_dir();
+        let history = CronHistory::new(dir.clone(), DEFAULT_RETENTION);
+        let long = "é".repeat(MAX_OUTPUT_CHARS + 10);
+        history.record("job-1", &run(0, &long)).await.unwrap();
+
+        let stored = &history.load("job-1").await.unwrap()[0].output;
+        assert_eq!(stored.chars().count(), MAX_OUTPUT_CHARS + 3);
+        assert!(stored.ends_with("..."));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn job_ids_cannot_escape_the_directory() {
+        let dir = Path::new("/state/cron-history");
+        assert_eq!(
+            run_file(dir, "../../etc/passwd"),
+            dir.join(".._.._etc_passwd.jsonl")
+        );
+        assert!(load_runs_sync(&temp_dir(), "missing").unwrap().is_empty());
+    }
+}
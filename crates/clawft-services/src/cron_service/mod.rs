@@ -7,7 +7,31 @@
 //! Uses the canonical [`CronJob`] type from [`clawft_types::cron`].
 //! The CLI cron commands use the same JSONL storage format via the
 //! synchronous helpers in [`storage`].
+//!
+//! A job that fires while the message queue is full is shed with a
+//! warning and counts as run; it fires again at its next scheduled time.
+//!
+//! When the gateway reports how a job's agent turn went
+//! ([`CronService::record_outcome`]), the run is added to the job's
+//! [`history`] and consecutive failures are counted; past the job's
+//! [`FailureAlert`](clawft_types::cron::FailureAlert) threshold an alert
+//! is returned for the caller to send.
+//!
+//! Besides cron expressions, jobs can fire every fixed interval or once
+//! (see [`CronService::create_job`]). Once such a job has no run left it
+//! is disabled.
+//!
+//! Each run's time is persisted. When the service starts, every job is
+//! rescheduled from the current time and runs missed since its last run
+//! are fired according to its [`CatchUp`](clawft_types::cron::CatchUp)
+//! policy.
+//!
+//! Every fired job carries
+//! [`ProactiveDelivery`](clawft_types::delivery::ProactiveDelivery) rules
+//! built from its payload (see [`CronPayload::delivery`]), so its reply can
+//! go to a channel, wait out quiet hours, or be suppressed with `NO_NOTIFY`.
 
+pub mod history;
 pub mod scheduler;
 pub mod storage;
 
@@ -15,15 +39,19 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tokio::sync::{RwLock, mpsc};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 use crate::error::{Result, ServiceError};
-use clawft_types::cron::{CronJobState, CronPayload, CronSchedule, ScheduleKind};
-use clawft_types::event::InboundMessage;
-use scheduler::{CronJob, CronScheduler, compute_next_run};
+use clawft_types::cron::{
+    CronJobState, CronPayload, CronRun, CronSchedule, JobStatus, ScheduleKind,
+};
+use clawft_types::delivery::QuietHours;
+use clawft_types::event::{InboundMessage, OutboundMessage};
+use history::CronHistory;
+use scheduler::{CronJob, CronScheduler, compute_next_run, next_run};
 use storage::CronStorage;
 
 /// Cron scheduling service.
@@ -34,6 +62,8 @@ pub struct CronService {
     scheduler: Arc<RwLock<CronScheduler>>,
     storage: CronStorage,
     message_tx: mpsc::Sender<InboundMessage>,
+    default_quiet_hours: Option<QuietHours>,
+    history: Option<CronHistory>,
 }
 
 impl CronService {
@@ -59,9 +89,23 @@ impl CronService {
             scheduler: Arc::new(RwLock::new(scheduler)),
             storage,
             message_tx,
+            default_quiet_hours: None,
+            history: None,
         })
     }
 
+    /// Quiet hours for jobs that do not set their own.
+    pub fn with_quiet_hours(mut self, quiet_hours: Option<QuietHours>) -> Self {
+        self.default_quiet_hours = quiet_hours;
+        self
+    }
+
+    /// Keep each job's run history in `history`.
+    pub fn with_history(mut self, history: CronHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
     /// Add a new cron job.
     ///
     /// Returns the generated job ID.
@@ -82,6 +126,7 @@ impl CronService {
                 every_ms: None,
                 expr: Some(schedule),
                 tz: Some("UTC".into()),
+                ..Default::default()
             },
             payload: CronPayload {
                 message: prompt,
@@ -94,6 +139,7 @@ impl CronService {
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         };
 
         // Add to in-memory scheduler (validates the cron expression).
@@ -106,10 +152,39 @@ impl CronService {
         Ok(id)
     }
 
+    /// Add a job with any kind of schedule, e.g. a one-shot reminder.
+    ///
+    /// The job gets a fresh ID and its first run is scheduled from now.
+    /// Fails with [`ServiceError::InvalidSchedule`] if it would never run.
+    /// Returns the job ID.
+    pub async fn create_job(&self, mut job: CronJob) -> Result<String> {
+        let now = Utc::now();
+        job.id = format!("job-{}", uuid::Uuid::new_v4());
+        job.created_at = now;
+        job.updated_at = now;
+        job.state.next_run_at = next_run(&job, &now)?;
+        if job.state.next_run_at.is_none() {
+            return Err(ServiceError::InvalidSchedule(format!(
+                "job '{}' has no run after {}",
+                job.name,
+                now.to_rfc3339()
+            )));
+        }
+
+        self.scheduler.write().await.add_job(job.clone())?;
+        self.storage.append_create(&job).await?;
+
+        info!(job_id = %job.id, kind = ?job.schedule.kind, "added cron job");
+        Ok(job.id)
+    }
+
     /// Remove a job by ID.
     pub async fn remove_job(&self, job_id: &str) -> Result<()> {
         self.scheduler.write().await.remove_job(job_id)?;
         self.storage.append_delete(job_id).await?;
+        if let Some(history) = &self.history {
+            history.remove(job_id).await?;
+        }
         info!(job_id, "removed cron job");
         Ok(())
     }
@@ -145,19 +220,78 @@ impl CronService {
         self.fire_job(job)?;
         drop(sched);
 
-        // Update last_run.
-        let now = Utc::now();
-        self.scheduler.write().await.update_job_run(job_id, now)?;
+        self.record_run(job_id, Utc::now()).await
+    }
 
-        Ok(())
+    /// Record how a run of `job_id` went.
+    ///
+    /// The run is added to the job's history, and its status and failure
+    /// count are persisted. Returns the alert to send when the job has
+    /// now failed often enough in a row (see
+    /// [`FailureAlert::is_due`](clawft_types::cron::FailureAlert::is_due)).
+    pub async fn record_outcome(
+        &self,
+        job_id: &str,
+        run: CronRun,
+    ) -> Result<Option<OutboundMessage>> {
+        let mut sched = self.scheduler.write().await;
+        let job = sched
+            .get_job_mut(job_id)
+            .ok_or_else(|| ServiceError::JobNotFound(job_id.to_string()))?;
+        let failed = run.status == JobStatus::Error;
+        if failed {
+            job.state.consecutive_failures = job.state.consecutive_failures.saturating_add(1);
+            job.state.last_error = run.error.clone();
+        } else {
+            job.state.consecutive_failures = 0;
+            job.state.last_error = None;
+        }
+        job.state.last_status = Some(run.status);
+        let failures = job.state.consecutive_failures;
+        let alert = match &job.payload.on_failure {
+            Some(alert) if failed && alert.is_due(failures) => Some(OutboundMessage {
+                channel: alert.channel.clone(),
+                chat_id: alert.to.clone(),
+                content: format!(
+                    "Scheduled job '{}' has failed {failures} times in a row. Last error: {}",
+                    job.name,
+                    run.error.as_deref().unwrap_or("unknown")
+                ),
+                reply_to: None,
+                media: vec![],
+                attachments: vec![],
+                metadata: Default::default(),
+            }),
+            _ => None,
+        };
+        drop(sched);
+
+        if let Some(history) = &self.history {
+            history.record(job_id, &run).await?;
+        }
+        self.storage
+            .append_update(job_id, "last_status", &serde_json::json!(run.status))
+            .await?;
+        self.storage
+            .append_update(job_id, "last_error", &serde_json::json!(run.error))
+            .await?;
+        self.storage
+            .append_update(job_id, "consecutive_failures", &serde_json::json!(failures))
+            .await?;
+
+        if alert.is_some() {
+            warn!(job_id, failures, "cron job keeps failing, sending alert");
+        }
+        Ok(alert)
     }
 
     /// Start the background scheduler loop.
     ///
-    /// Checks for due jobs every 60 seconds. Exits when the
-    /// cancellation token is triggered.
+    /// Catches up on missed runs, then checks for due jobs every 60
+    /// seconds. Exits when the cancellation token is triggered.
     pub async fn start(&self, cancel: CancellationToken) -> Result<()> {
         info!("cron service started");
+        self.catch_up(Utc::now()).await;
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
 
         loop {
@@ -185,18 +319,70 @@ impl CronService {
                 continue;
             }
 
-            let now = Utc::now();
-            if let Err(e) = self.scheduler.write().await.update_job_run(&job.id, now) {
+            if let Err(e) = self.record_run(&job.id, Utc::now()).await {
                 error!(job_id = %job.id, error = %e, "failed to update job run time");
             }
         }
     }
 
+    /// Reschedule all jobs from `now` and fire the runs they missed while
+    /// the service was down. Returns the number of runs fired.
+    async fn catch_up(&self, now: DateTime<Utc>) -> usize {
+        let owed = match self.scheduler.write().await.catch_up(now) {
+            Ok(owed) => owed,
+            Err(e) => {
+                error!(error = %e, "failed to compute missed cron runs");
+                return 0;
+            }
+        };
+
+        let mut fired = 0;
+        for (job, runs) in &owed {
+            info!(job_id = %job.id, runs, "catching up on missed cron runs");
+            for _ in 0..*runs {
+                if let Err(e) = self.fire_job(job) {
+                    error!(job_id = %job.id, error = %e, "failed to fire cron job");
+                    break;
+                }
+                fired += 1;
+            }
+            if let Err(e) = self.record_run(&job.id, now).await {
+                error!(job_id = %job.id, error = %e, "failed to update job run time");
+            }
+        }
+        self.retire_finished_jobs().await;
+        fired
+    }
+
+    /// Record a run of `job_id` at `now` and persist its run time.
+    async fn record_run(&self, job_id: &str, now: DateTime<Utc>) -> Result<()> {
+        self.scheduler.write().await.update_job_run(job_id, now)?;
+        self.storage
+            .append_update(job_id, "last_run_at", &serde_json::json!(now.to_rfc3339()))
+            .await?;
+        self.retire_finished_jobs().await;
+        Ok(())
+    }
+
+    /// Disable the one-shot and interval jobs that have no runs left.
+    async fn retire_finished_jobs(&self) {
+        let finished = self.scheduler.read().await.finished_jobs();
+        for job_id in finished {
+            match self.enable_job(&job_id, false).await {
+                Ok(()) => info!(job_id, "cron job has no runs left, disabled"),
+                Err(e) => error!(job_id, error = %e, "failed to disable finished cron job"),
+            }
+        }
+    }
+
     /// Post a job's prompt as an InboundMessage.
     fn fire_job(&self, job: &CronJob) -> Result<()> {
         let mut metadata = HashMap::new();
         metadata.insert("job_id".to_string(), serde_json::json!(job.id));
         metadata.insert("job_name".to_string(), serde_json::json!(job.name));
+        job.payload
+            .delivery(self.default_quiet_hours.as_ref())
+            .insert_into(&mut metadata);
 
         let msg = InboundMessage {
             channel: "cron".to_string(),
@@ -205,14 +391,18 @@ impl CronService {
             content: job.payload.message.clone(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata,
         };
 
-        self.message_tx
-            .try_send(msg)
-            .map_err(|_| ServiceError::ChannelClosed)?;
-
-        Ok(())
+        match self.message_tx.try_send(msg) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                warn!(job_id = %job.id, "message queue full, cron job shed");
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => Err(ServiceError::ChannelClosed),
+        }
     }
 }
 
@@ -220,6 +410,25 @@ impl CronService {
 mod tests {
     use super::*;
 
+    fn blank_job() -> CronJob {
+        let now = Utc::now();
+        CronJob {
+            id: String::new(),
+            name: String::new(),
+            enabled: true,
+            schedule: CronSchedule::default(),
+            payload: CronPayload {
+                message: "test prompt".into(),
+                ..Default::default()
+            },
+            state: CronJobState::default(),
+            created_at: now,
+            updated_at: now,
+            delete_after_run: false,
+            created_by: None,
+        }
+    }
+
     async fn setup() -> (CronService, mpsc::Receiver<InboundMessage>) {
         let dir = std::env::temp_dir().join(format!("clawft-cron-test-{}", uuid::Uuid::new_v4()));
         let path = dir.join("cron.jsonl");
@@ -301,6 +510,49 @@ mod tests {
         assert_eq!(msg.metadata["job_name"], "fire");
     }
 
+    #[tokio::test]
+    async fn fired_job_carries_delivery_rules() {
+        use clawft_types::delivery::ProactiveDelivery;
+
+        let (svc, mut rx) = setup().await;
+        let night = QuietHours::parse("22:00-07:00", "UTC").unwrap();
+        let svc = svc.with_quiet_hours(Some(night.clone()));
+        let id = svc
+            .add_job(
+                "morning".into(),
+                "0 0 9 * * * *".into(),
+                "check mail".into(),
+            )
+            .await
+            .unwrap();
+
+        svc.run_job_now(&id).await.unwrap();
+
+        let msg = rx.try_recv().unwrap();
+        let delivery = ProactiveDelivery::from_metadata(&msg.metadata).unwrap();
+        assert!(delivery.target.is_none());
+        assert_eq!(delivery.quiet_hours, Some(night));
+    }
+
+    #[tokio::test]
+    async fn full_channel_sheds_job_without_error() {
+        let dir = std::env::temp_dir().join(format!("clawft-cron-test-{}", uuid::Uuid::new_v4()));
+        let (tx, mut rx) = mpsc::channel(1);
+        let svc = CronService::new(dir.join("cron.jsonl"), tx).await.unwrap();
+        let id = svc
+            .add_job("fire".into(), "0 0 * * * * *".into(), "hello".into())
+            .await
+            .unwrap();
+
+        svc.run_job_now(&id).await.unwrap();
+        // The queue is full; the second run is shed but still recorded.
+        svc.run_job_now(&id).await.unwrap();
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+        let jobs = svc.list_jobs().await.unwrap();
+        assert!(jobs[0].state.last_run_at.is_some());
+    }
+
     #[tokio::test]
     async fn run_nonexistent_job_fails() {
         let (svc, _rx) = setup().await;
@@ -326,4 +578,230 @@ mod tests {
         let jobs = svc.list_jobs().await.unwrap();
         assert!(jobs[0].state.next_run_at.is_some());
     }
+
+    #[tokio::test]
+    async fn catch_up_fires_missed_runs_and_persists_last_run() {
+        use chrono::TimeZone;
+        use clawft_types::cron::CatchUp;
+
+        let dir = std::env::temp_dir().join(format!("clawft-cron-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("cron.jsonl");
+        let created = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let job = |id: &str, catch_up| CronJob {
+            id: id.into(),
+            name: id.into(),
+            enabled: true,
+            schedule: CronSchedule {
+                kind: ScheduleKind::Cron,
+                expr: Some("0 0 * * * * *".into()),
+                catch_up,
+                ..Default::default()
+            },
+            payload: CronPayload {
+                message: id.into(),
+                ..Default::default()
+            },
+            state: CronJobState::default(),
+            created_at: created,
+            updated_at: created,
+            delete_after_run: false,
+            created_by: None,
+        };
+        let storage = CronStorage::new(path.clone());
+        for (id, policy) in [
+            ("skip", CatchUp::Skip),
+            ("once", CatchUp::RunOnce),
+            ("all", CatchUp::RunAll),
+        ] {
+            storage.append_create(&job(id, policy)).await.unwrap();
+        }
+
+        let (tx, mut rx) = mpsc::channel(1024);
+        let svc = CronService::new(path.clone(), tx).await.unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 3, 30, 0).unwrap();
+        assert_eq!(svc.catch_up(now).await, 4);
+
+        let mut fired = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            fired.push(msg.content);
+        }
+        fired.sort();
+        assert_eq!(fired, ["all", "all", "all", "once"]);
+
+        // A restart right after has nothing left to catch up on.
+        let (tx, _rx) = mpsc::channel(1024);
+        let svc = CronService::new(path, tx).await.unwrap();
+        let jobs = svc.list_jobs().await.unwrap();
+        let all = jobs.iter().find(|j| j.id == "all").unwrap();
+        assert_eq!(all.state.last_run_at, Some(now));
+        assert_eq!(svc.catch_up(now).await, 0);
+    }
+
+    #[tokio::test]
+    async fn create_job_schedules_once_and_interval_jobs() {
+        use chrono::Duration;
+
+        let (svc, _rx) = setup().await;
+        let at = Utc::now() + Duration::minutes(45);
+        let once = CronJob {
+            name: "reminder".into(),
+            schedule: CronSchedule::once(at),
+            created_by: Some("default".into()),
+            ..blank_job()
+        };
+        let id = svc.create_job(once).await.unwrap();
+        let jobs = svc.list_jobs().await.unwrap();
+        let job = jobs.iter().find(|j| j.id == id).unwrap();
+        assert_eq!(
+            job.state.next_run_at.map(|t| t.timestamp_millis()),
+            Some(at.timestamp_millis())
+        );
+        assert_eq!(job.created_by.as_deref(), Some("default"));
+
+        let past = CronJob {
+            name: "too late".into(),
+            schedule: CronSchedule::once(Utc::now() - Duration::minutes(1)),
+            ..blank_job()
+        };
+        assert!(matches!(
+            svc.create_job(past).await,
+            Err(ServiceError::InvalidSchedule(_))
+        ));
+
+        let every = CronJob {
+            name: "poll".into(),
+            schedule: CronSchedule::interval(Duration::minutes(10), None),
+            ..blank_job()
+        };
+        let id = svc.create_job(every).await.unwrap();
+        svc.run_job_now(&id).await.unwrap();
+        let jobs = svc.list_jobs().await.unwrap();
+        assert!(jobs.iter().find(|j| j.id == id).unwrap().enabled);
+    }
+
+    #[tokio::test]
+    async fn finished_once_job_is_disabled_after_running() {
+        use chrono::TimeZone;
+
+        let dir = std::env::temp_dir().join(format!("clawft-cron-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("cron.jsonl");
+        let created = Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+        let job = CronJob {
+            id: "reminder".into(),
+            name: "reminder".into(),
+            schedule: CronSchedule::once(Utc.with_ymd_and_hms(2026, 1, 1, 9, 45, 0).unwrap()),
+            created_at: created,
+            updated_at: created,
+            ..blank_job()
+        };
+        CronStorage::new(path.clone())
+            .append_create(&job)
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let svc = CronService::new(path.clone(), tx).await.unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(svc.catch_up(now).await, 1);
+        assert_eq!(rx.try_recv().unwrap().content, "test prompt");
+
+        let (tx, _rx) = mpsc::channel(16);
+        let svc = CronService::new(path, tx).await.unwrap();
+        let jobs = svc.list_jobs().await.unwrap();
+        assert!(!jobs[0].enabled);
+        assert_eq!(svc.catch_up(now).await, 0);
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_alert_with_backoff_and_keep_history() {
+        use clawft_types::cron::FailureAlert;
+
+        let dir = std::env::temp_dir().join(format!("clawft-cron-test-{}", uuid::Uuid::new_v4()));
+        let (tx, _rx) = mpsc::channel(16);
+        let svc = CronService::new(dir.join("cron.jsonl"), tx)
+            .await
+            .unwrap()
+            .with_history(CronHistory::new(dir.join("history"), 4));
+        let id = svc
+            .add_job("digest".into(), "0 0 * * * * *".into(), "p".into())
+            .await
+            .unwrap();
+        svc.scheduler
+            .write()
+            .await
+            .get_job_mut(&id)
+            .unwrap()
+            .payload
+            .on_failure = Some(FailureAlert {
+            channel: "telegram".into(),
+            to: "42".into(),
+            after: 2,
+        });
+
+        let run = |status| CronRun {
+            started_at: Utc::now(),
+            finished_at: Utc::now(),
+            status,
+            output: String::new(),
+            error: (status == JobStatus::Error).then(|| "provider unavailable".to_string()),
+            input_tokens: 0,
+            output_tokens: 0,
+        };
+        let mut alerted = Vec::new();
+        for n in 1..=8 {
+            if let Some(alert) = svc
+                .record_outcome(&id, run(JobStatus::Error))
+                .await
+                .unwrap()
+            {
+                assert_eq!(alert.channel, "telegram");
+                assert_eq!(alert.chat_id, "42");
+                assert!(alert.content.contains("'digest' has failed"));
+                assert!(alert.content.contains("provider unavailable"));
+                alerted.push(n);
+            }
+        }
+        assert_eq!(alerted, [2, 4, 8]);
+
+        // A success resets the streak.
+        assert!(
+            svc.record_outcome(&id, run(JobStatus::Ok))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            svc.record_outcome(&id, run(JobStatus::Error))
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            svc.record_outcome(&id, run(JobStatus::Error))
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // The failure count survives a restart; history keeps the last 4 runs.
+        let (tx, _rx) = mpsc::channel(16);
+        let reloaded = CronService::new(dir.join("cron.jsonl"), tx).await.unwrap();
+        let job = reloaded.list_jobs().await.unwrap().remove(0);
+        assert_eq!(job.state.consecutive_failures, 2);
+        assert_eq!(
+            job.state.last_error.as_deref(),
+            Some("provider unavailable")
+        );
+        let runs = history::load_runs_sync(&dir.join("history"), &id).unwrap();
+        assert_eq!(runs.len(), 4);
+        assert_eq!(runs[1].status, JobStatus::Ok);
+
+        svc.remove_job(&id).await.unwrap();
+        assert!(
+            history::load_runs_sync(&dir.join("history"), &id)
+                .unwrap()
+                .is_empty()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -3,22 +3,37 @@
 //! Maintains a map of [`CronJob`] entries and determines which are due
 //! to fire based on their `next_run_at` timestamp.
 //!
+//! Cron expressions are evaluated in the schedule's IANA timezone, so
+//! `0 0 9 * * * *` in `America/New_York` fires at 9am local time on both
+//! sides of a DST change. Each run time may be pushed back by up to
+//! `jitter_secs`; the delay is derived from the job ID and fire time, so
+//! it is the same after a restart.
+//!
+//! Interval schedules stop at their `until` time and one-shot schedules
+//! after their single run; such jobs are then reported by
+//! [`CronScheduler::finished_jobs`] so the service can disable them.
+//!
 //! Uses the canonical [`CronJob`] type from [`clawft_types::cron`].
 
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::str::FromStr;
 
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
 
 use crate::error::{Result, ServiceError};
 
 // Re-export the canonical CronJob from clawft-types.
 pub use clawft_types::cron::{
-    CronJob, CronJobState, CronPayload, CronSchedule, CronStore, JobStatus, PayloadKind,
-    ScheduleKind,
+    CatchUp, CronJob, CronJobState, CronPayload, CronRun, CronSchedule, CronStore, FailureAlert,
+    JobStatus, PayloadKind, ScheduleKind,
 };
 
+/// Most runs fired for one job under [`CatchUp::RunAll`].
+pub const MAX_CATCH_UP_RUNS: usize = 100;
+
 /// In-memory scheduler holding all jobs.
 pub struct CronScheduler {
     jobs: HashMap<String, CronJob>,
@@ -34,8 +49,8 @@ impl CronScheduler {
 
     /// Add a job to the scheduler.
     ///
-    /// Validates the cron expression (if the schedule kind is `Cron`)
-    /// and rejects duplicate names.
+    /// Validates the cron expression (if the schedule kind is `Cron`) and
+    /// the timezone, and rejects duplicate names.
     pub fn add_job(&mut self, job: CronJob) -> Result<()> {
         // Validate the cron expression if present.
         if job.schedule.kind == ScheduleKind::Cron
@@ -44,6 +59,7 @@ impl CronScheduler {
             Schedule::from_str(expr)
                 .map_err(|e| ServiceError::InvalidCronExpression(e.to_string()))?;
         }
+        parse_timezone(job.schedule.tz.as_deref())?;
 
         // Check for duplicate names.
         if self
@@ -68,7 +84,11 @@ impl CronScheduler {
 
     /// Return all enabled jobs whose `next_run_at` is at or before now.
     pub fn get_due_jobs(&self) -> Vec<CronJob> {
-        let now = Utc::now();
+        self.due_jobs(Utc::now())
+    }
+
+    /// Return all enabled jobs whose `next_run_at` is at or before `now`.
+    pub fn due_jobs(&self, now: DateTime<Utc>) -> Vec<CronJob> {
         self.jobs
             .values()
             .filter(|j| j.enabled && j.state.next_run_at.is_some_and(|nr| nr <= now))
@@ -101,18 +121,47 @@ impl CronScheduler {
         job.state.last_run_at = Some(run_time);
         job.state.last_status = Some(JobStatus::Ok);
         job.updated_at = run_time;
-
-        // Compute next run from the cron schedule expression.
-        if job.schedule.kind == ScheduleKind::Cron
-            && let Some(ref expr) = job.schedule.expr
-            && let Ok(schedule) = Schedule::from_str(expr)
-        {
-            job.state.next_run_at =
-                schedule.after(&run_time).next().map(|dt| dt.with_timezone(&Utc));
-        }
+        job.state.next_run_at = next_run(job, &run_time)?;
 
         Ok(())
     }
+
+    /// IDs of enabled one-shot and interval jobs that will not fire again.
+    ///
+    /// Cron-expression jobs are never reported.
+    pub fn finished_jobs(&self) -> Vec<String> {
+        self.jobs
+            .values()
+            .filter(|j| {
+                j.enabled && j.schedule.kind != ScheduleKind::Cron && j.state.next_run_at.is_none()
+            })
+            .map(|j| j.id.clone())
+            .collect()
+    }
+
+    /// Reschedule every enabled job from `now`, as on startup, and return
+    /// the jobs owed runs under their [`CatchUp`] policy with the number
+    /// of runs each is owed.
+    ///
+    /// Runs are counted from the job's last run, or its creation if it
+    /// never ran.
+    pub fn catch_up(&mut self, now: DateTime<Utc>) -> Result<Vec<(CronJob, usize)>> {
+        let mut owed = Vec::new();
+        for job in self.jobs.values_mut().filter(|j| j.enabled) {
+            let since = job.state.last_run_at.unwrap_or(job.created_at);
+            let missed = missed_runs(&job.schedule, &since, &now)?;
+            let runs = match job.schedule.catch_up {
+                CatchUp::RunOnce => missed.min(1),
+                CatchUp::RunAll => missed,
+                _ => 0,
+            };
+            job.state.next_run_at = next_run(job, &now)?;
+            if runs > 0 {
+                owed.push((job.clone(), runs));
+            }
+        }
+        Ok(owed)
+    }
 }
 
 impl Default for CronScheduler {
@@ -136,6 +185,85 @@ pub fn compute_next_run(
         .map(|dt| dt.with_timezone(&Utc)))
 }
 
+/// Parse a schedule timezone name; unset means UTC.
+pub fn parse_timezone(tz: Option<&str>) -> Result<Tz> {
+    match tz {
+        None => Ok(Tz::UTC),
+        Some(name) => name
+            .parse()
+            .map_err(|_| ServiceError::InvalidTimezone(name.to_string())),
+    }
+}
+
+/// The first time `schedule` fires strictly after `after`, without jitter.
+///
+/// Cron expressions are evaluated in the schedule's timezone.
+pub fn next_fire(schedule: &CronSchedule, after: &DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    match schedule.kind {
+        ScheduleKind::Cron => {
+            let Some(expr) = schedule.expr.as_deref() else {
+                return Ok(None);
+            };
+            let parsed = Schedule::from_str(expr)
+                .map_err(|e| ServiceError::InvalidCronExpression(e.to_string()))?;
+            let tz = parse_timezone(schedule.tz.as_deref())?;
+            Ok(parsed
+                .after(&after.with_timezone(&tz))
+                .next()
+                .map(|dt| dt.with_timezone(&Utc)))
+        }
+        ScheduleKind::Every => Ok(schedule
+            .every_ms
+            .filter(|ms| *ms > 0)
+            .map(|ms| *after + Duration::milliseconds(ms))
+            .filter(|next| schedule.until.is_none_or(|until| *next <= until))),
+        ScheduleKind::At => Ok(schedule
+            .at_ms
+            .and_then(ms_to_datetime)
+            .filter(|at| at > after)),
+        _ => Ok(None),
+    }
+}
+
+/// The next run time of `job` after `after`: its next fire time plus
+/// jitter.
+pub fn next_run(job: &CronJob, after: &DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    Ok(next_fire(&job.schedule, after)?
+        .map(|fire| fire + jitter(&job.id, &fire, job.schedule.jitter_secs)))
+}
+
+/// How many times `schedule` fired in `(since, now]`, at most
+/// [`MAX_CATCH_UP_RUNS`].
+pub fn missed_runs(
+    schedule: &CronSchedule,
+    since: &DateTime<Utc>,
+    now: &DateTime<Utc>,
+) -> Result<usize> {
+    let mut missed = 0;
+    let mut cursor = *since;
+    while missed < MAX_CATCH_UP_RUNS {
+        match next_fire(schedule, &cursor)? {
+            Some(fire) if fire <= *now => {
+                missed += 1;
+                cursor = fire;
+            }
+            _ => break,
+        }
+    }
+    Ok(missed)
+}
+
+/// Delay in `0..=jitter_secs` for the run of `job_id` due at `fire`.
+fn jitter(job_id: &str, fire: &DateTime<Utc>, jitter_secs: u64) -> Duration {
+    if jitter_secs == 0 {
+        return Duration::zero();
+    }
+    let mut hasher = DefaultHasher::new();
+    job_id.hash(&mut hasher);
+    fire.timestamp().hash(&mut hasher);
+    Duration::seconds((hasher.finish() % (jitter_secs + 1)) as i64)
+}
+
 /// Convert a millisecond timestamp to a `DateTime<Utc>`, if valid.
 pub fn ms_to_datetime(ms: i64) -> Option<DateTime<Utc>> {
     Utc.timestamp_millis_opt(ms).single()
@@ -157,8 +285,11 @@ mod tests {
                 kind: ScheduleKind::Cron,
                 at_ms: None,
                 every_ms: None,
+                until: None,
                 expr: Some(schedule_expr.into()),
                 tz: Some("UTC".into()),
+                jitter_secs: 0,
+                catch_up: CatchUp::Skip,
             },
             payload: CronPayload {
                 message: "test prompt".into(),
@@ -168,6 +299,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         }
     }
 
@@ -350,4 +482,193 @@ mod tests {
         let job = sched.get_job("j1").unwrap();
         assert_eq!(job.state.last_status, Some(JobStatus::Ok));
     }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn unknown_timezone_is_rejected() {
+        let mut sched = CronScheduler::new();
+        let mut job = make_job("j1", "mars", "0 0 9 * * * *");
+        job.schedule.tz = Some("Mars/Olympus_Mons".into());
+        assert!(matches!(
+            sched.add_job(job).unwrap_err(),
+            ServiceError::InvalidTimezone(_)
+        ));
+        assert!(parse_timezone(None).is_ok());
+        assert!(parse_timezone(Some("Asia/Shanghai")).is_ok());
+    }
+
+    #[test]
+    fn next_fire_keeps_local_time_across_dst() {
+        let mut job = make_job("j1", "nine-am", "0 0 9 * * * *");
+        job.schedule.tz = Some("America/New_York".into());
+
+        // Clocks go forward on 2026-03-08: 9am is 14:00 UTC before, 13:00 after.
+        let before = next_fire(&job.schedule, &utc(2026, 3, 6, 15, 0)).unwrap();
+        assert_eq!(before, Some(utc(2026, 3, 7, 14, 0)));
+        let after = next_fire(&job.schedule, &utc(2026, 3, 7, 15, 0)).unwrap();
+        assert_eq!(after, Some(utc(2026, 3, 8, 13, 0)));
+
+        // And back on 2026-11-01.
+        let fall = next_fire(&job.schedule, &utc(2026, 10, 31, 14, 0)).unwrap();
+        assert_eq!(fall, Some(utc(2026, 11, 1, 14, 0)));
+
+        // A scheduler driven across the change keeps firing at 9am local.
+        let mut sched = CronScheduler::new();
+        sched.add_job(job).unwrap();
+        sched.update_job_run("j1", utc(2026, 3, 7, 14, 0)).unwrap();
+        let next = sched.get_job("j1").unwrap().state.next_run_at.unwrap();
+        assert_eq!(next, utc(2026, 3, 8, 13, 0));
+        assert!(sched.due_jobs(utc(2026, 3, 8, 12, 59)).is_empty());
+        assert_eq!(sched.due_jobs(utc(2026, 3, 8, 13, 0)).len(), 1);
+    }
+
+    #[test]
+    fn jitter_delays_within_bound_and_is_stable() {
+        let mut job = make_job("j1", "jittery", "0 0 * * * * *");
+        job.schedule.jitter_secs = 300;
+        let fire = utc(2026, 1, 1, 1, 0);
+
+        let first = next_run(&job, &utc(2026, 1, 1, 0, 30)).unwrap().unwrap();
+        assert!(first >= fire && first <= fire + Duration::seconds(300));
+        assert_eq!(
+            next_run(&job, &utc(2026, 1, 1, 0, 30)).unwrap(),
+            Some(first)
+        );
+
+        job.schedule.jitter_secs = 0;
+        assert_eq!(next_run(&job, &utc(2026, 1, 1, 0, 30)).unwrap(), Some(fire));
+    }
+
+    #[test]
+    fn missed_runs_counts_fires_since_last_run() {
+        let job = make_job("j1", "hourly", "0 0 * * * * *");
+        let since = utc(2026, 1, 1, 0, 0);
+        assert_eq!(missed_runs(&job.schedule, &since, &since).unwrap(), 0);
+        assert_eq!(
+            missed_runs(&job.schedule, &since, &utc(2026, 1, 1, 3, 30)).unwrap(),
+            3
+        );
+        assert_eq!(
+            missed_runs(&job.schedule, &since, &utc(2027, 1, 1, 0, 0)).unwrap(),
+            MAX_CATCH_UP_RUNS
+        );
+    }
+
+    #[test]
+    fn catch_up_policy_matrix() {
+        let last_run = utc(2026, 1, 1, 0, 0);
+        let now = utc(2026, 1, 1, 3, 30);
+        let cases = [
+            (CatchUp::Skip, Some(last_run), 0),
+            (CatchUp::RunOnce, Some(last_run), 1),
+            (CatchUp::RunAll, Some(last_run), 3),
+            // Nothing was missed: no policy fires.
+            (CatchUp::Skip, Some(utc(2026, 1, 1, 3, 0)), 0),
+            (CatchUp::RunOnce, Some(utc(2026, 1, 1, 3, 0)), 0),
+            (CatchUp::RunAll, Some(utc(2026, 1, 1, 3, 0)), 0),
+            // Never ran: counted from creation.
+            (CatchUp::RunAll, None, 2),
+        ];
+
+        for (policy, last_run_at, expected) in cases {
+            let mut sched = CronScheduler::new();
+            let mut job = make_job("j1", "hourly", "0 0 * * * * *");
+            job.created_at = utc(2026, 1, 1, 1, 30);
+            job.schedule.catch_up = policy;
+            job.state.last_run_at = last_run_at;
+            sched.add_job(job).unwrap();
+
+            let owed = sched.catch_up(now).unwrap();
+            let runs = owed.first().map_or(0, |(_, runs)| *runs);
+            assert_eq!(runs, expected, "{policy:?} since {last_run_at:?}");
+            assert_eq!(
+                sched.get_job("j1").unwrap().state.next_run_at,
+                Some(utc(2026, 1, 1, 4, 0))
+            );
+        }
+    }
+
+    #[test]
+    fn catch_up_ignores_disabled_jobs() {
+        let mut sched = CronScheduler::new();
+        let mut job = make_job("j1", "off", "0 0 * * * * *");
+        job.enabled = false;
+        job.schedule.catch_up = CatchUp::RunAll;
+        job.state.last_run_at = Some(utc(2026, 1, 1, 0, 0));
+        sched.add_job(job).unwrap();
+
+        assert!(sched.catch_up(utc(2026, 1, 2, 0, 0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn once_job_runs_once_then_finishes() {
+        let at = utc(2026, 1, 1, 9, 45);
+        let mut sched = CronScheduler::new();
+        let mut job = make_job("j1", "reminder", "unused");
+        job.schedule = CronSchedule::once(at);
+        job.state.next_run_at = next_run(&job, &utc(2026, 1, 1, 9, 0)).unwrap();
+        assert_eq!(job.state.next_run_at, Some(at));
+        sched.add_job(job).unwrap();
+
+        assert_eq!(sched.due_jobs(at).len(), 1);
+        assert!(sched.finished_jobs().is_empty());
+        sched.update_job_run("j1", at).unwrap();
+        assert!(sched.get_job("j1").unwrap().state.next_run_at.is_none());
+        assert_eq!(sched.finished_jobs(), ["j1"]);
+    }
+
+    #[test]
+    fn missed_once_job_still_runs_on_startup() {
+        let mut sched = CronScheduler::new();
+        let mut job = make_job("j1", "reminder", "unused");
+        job.created_at = utc(2026, 1, 1, 9, 0);
+        job.schedule = CronSchedule::once(utc(2026, 1, 1, 9, 45));
+        sched.add_job(job).unwrap();
+
+        let owed = sched.catch_up(utc(2026, 1, 1, 12, 0)).unwrap();
+        assert_eq!(owed.len(), 1);
+        assert_eq!(owed[0].1, 1);
+    }
+
+    #[test]
+    fn interval_job_stops_at_until() {
+        let start = utc(2026, 1, 1, 9, 0);
+        let schedule = CronSchedule::interval(Duration::minutes(10), Some(utc(2026, 1, 1, 9, 25)));
+        assert_eq!(
+            next_fire(&schedule, &start).unwrap(),
+            Some(utc(2026, 1, 1, 9, 10))
+        );
+        assert_eq!(
+            next_fire(&schedule, &utc(2026, 1, 1, 9, 10)).unwrap(),
+            Some(utc(2026, 1, 1, 9, 20))
+        );
+        assert_eq!(next_fire(&schedule, &utc(2026, 1, 1, 9, 20)).unwrap(), None);
+        assert_eq!(
+            missed_runs(&schedule, &start, &utc(2026, 1, 2, 0, 0)).unwrap(),
+            2
+        );
+
+        let unbounded = CronSchedule::interval(Duration::minutes(10), None);
+        assert_eq!(
+            next_fire(&unbounded, &utc(2030, 1, 1, 0, 0)).unwrap(),
+            Some(utc(2030, 1, 1, 0, 10))
+        );
+    }
+
+    #[test]
+    fn finished_jobs_ignores_cron_and_disabled_jobs() {
+        let mut sched = CronScheduler::new();
+        sched
+            .add_job(make_job("j1", "cron", "0 0 * * * * *"))
+            .unwrap();
+        let mut job = make_job("j2", "stopped", "unused");
+        job.schedule = CronSchedule::interval(Duration::minutes(1), None);
+        job.enabled = false;
+        sched.add_job(job).unwrap();
+
+        assert!(sched.finished_jobs().is_empty());
+    }
 }
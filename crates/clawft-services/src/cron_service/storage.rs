@@ -266,6 +266,11 @@ fn apply_field_update(job: &mut CronJob, field: &str, value: &serde_json::Value)
         "last_error" => {
             job.state.last_error = value.as_str().map(|s| s.to_string());
         }
+        "consecutive_failures" => {
+            if let Some(v) = value.as_u64() {
+                job.state.consecutive_failures = u32::try_from(v).unwrap_or(u32::MAX);
+            }
+        }
         _ => {
             warn!(field, "unknown field in storage update event");
         }
@@ -290,6 +295,7 @@ mod tests {
                 every_ms: None,
                 expr: Some("0 0 * * * * *".into()),
                 tz: Some("UTC".into()),
+                ..Default::default()
             },
             payload: CronPayload {
                 message: "test".into(),
@@ -299,6 +305,7 @@ mod tests {
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         }
     }
 
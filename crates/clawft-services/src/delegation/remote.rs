@@ -0,0 +1,114 @@
+//! Delegation to peer gateways.
+//!
+//! [`RemoteDelegator`] hands a task to another clawft gateway listed under
+//! `delegation.remotes`: it posts the task to the peer's
+//! `/api/delegation/tasks` endpoint and polls the task until it finishes or
+//! the configured timeout passes. The peer decides which of its agents runs
+//! the task and reports the LLM usage it cost.
+//!
+//! # Feature gate
+//!
+//! This module lives inside the `delegate`-gated `delegation` module.
+
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use clawft_types::delegation::{RemoteDelegateConfig, RemoteTaskRequest, RemoteTaskStatus};
+use clawft_types::secret::SecretString;
+
+use super::claude::{DelegationError, Result};
+
+/// Hands tasks to one peer gateway.
+pub struct RemoteDelegator {
+    client: reqwest::Client,
+    name: String,
+    base_url: String,
+    token: SecretString,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl RemoteDelegator {
+    /// Delegate to the peer `name` configured by `config`, authenticating
+    /// with `token`.
+    pub fn new(name: &str, config: &RemoteDelegateConfig, token: SecretString) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            name: name.to_string(),
+            base_url: config.url.trim_end_matches('/').to_string(),
+            token,
+            timeout: Duration::from_secs(config.timeout_secs.max(1)),
+            poll_interval: Duration::from_secs(config.poll_interval_secs.max(1)),
+        }
+    }
+
+    /// Override the polling interval (tests poll faster than a second).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The peer's name in `delegation.remotes`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Submit `request`; the returned status carries the task id.
+    pub async fn submit(&self, request: &RemoteTaskRequest) -> Result<RemoteTaskStatus> {
+        let response = self
+            .client
+            .post(format!("{}/api/delegation/tasks", self.base_url))
+            .bearer_auth(self.token.expose())
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| DelegationError::Http(e.to_string()))?;
+        parse(response).await
+    }
+
+    /// The current status of task `id`.
+    pub async fn status(&self, id: &str) -> Result<RemoteTaskStatus> {
+        let response = self
+            .client
+            .get(format!("{}/api/delegation/tasks/{id}", self.base_url))
+            .bearer_auth(self.token.expose())
+            .send()
+            .await
+            .map_err(|e| DelegationError::Http(e.to_string()))?;
+        parse(response).await
+    }
+
+    /// Submit `request` and wait for the task to finish.
+    ///
+    /// A task still running after the configured timeout is left to the
+    /// peer and reported as [`DelegationError::Timeout`].
+    pub async fn run(&self, request: &RemoteTaskRequest) -> Result<RemoteTaskStatus> {
+        let started = Instant::now();
+        let mut status = self.submit(request).await?;
+        debug!(peer = %self.name, task = %status.id, "delegated task to peer");
+        while !status.state.is_finished() {
+            if started.elapsed() >= self.timeout {
+                return Err(DelegationError::Timeout {
+                    elapsed: started.elapsed(),
+                });
+            }
+            tokio::time::sleep(self.poll_interval).await;
+            status = self.status(&status.id).await?;
+        }
+        Ok(status)
+    }
+}
+
+async fn parse<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let status = response.status().as_u16();
+    if !(200..300).contains(&status) {
+        let body = response.text().await.unwrap_or_default();
+        return Err(DelegationError::Api { status, body });
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| DelegationError::InvalidResponse(e.to_string()))
+}
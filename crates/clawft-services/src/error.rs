@@ -9,6 +9,14 @@ pub enum ServiceError {
     #[error("invalid cron expression: {0}")]
     InvalidCronExpression(String),
 
+    /// A schedule timezone is not a known IANA zone name.
+    #[error("invalid timezone: {0}")]
+    InvalidTimezone(String),
+
+    /// A schedule would never fire (e.g. a one-shot time in the past).
+    #[error("invalid schedule: {0}")]
+    InvalidSchedule(String),
+
     /// The requested job was not found.
     #[error("job not found: {0}")]
     JobNotFound(String),
@@ -25,6 +33,19 @@ pub enum ServiceError {
     #[error("mcp protocol error: {0}")]
     McpProtocol(String),
 
+    /// The MCP server's connection was lost and is being re-established;
+    /// the request may succeed if retried.
+    #[error("mcp server unavailable: {0}")]
+    McpUnavailable(String),
+
+    /// An MCP call took longer than the server's call timeout (seconds).
+    #[error("mcp call timed out after {0}s")]
+    McpTimeout(u64),
+
+    /// A backup could not be taken, uploaded or restored.
+    #[error("backup error: {0}")]
+    Backup(String),
+
     /// Underlying I/O error.
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
@@ -50,6 +71,9 @@ mod tests {
         let err = ServiceError::InvalidCronExpression("bad".into());
         assert_eq!(err.to_string(), "invalid cron expression: bad");
 
+        let err = ServiceError::InvalidTimezone("Mars/Olympus".into());
+        assert_eq!(err.to_string(), "invalid timezone: Mars/Olympus");
+
         let err = ServiceError::JobNotFound("abc".into());
         assert_eq!(err.to_string(), "job not found: abc");
 
@@ -62,6 +86,9 @@ mod tests {
         let err = ServiceError::McpProtocol("method not found".into());
         assert_eq!(err.to_string(), "mcp protocol error: method not found");
 
+        let err = ServiceError::McpUnavailable("reconnecting".into());
+        assert_eq!(err.to_string(), "mcp server unavailable: reconnecting");
+
         let err = ServiceError::ChannelClosed;
         assert_eq!(err.to_string(), "channel closed");
     }
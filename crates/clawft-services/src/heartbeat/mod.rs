@@ -8,19 +8,44 @@
 //!   a fixed interval.
 //! - [`HeartbeatMode::CheckIn`] -- proactive check-in mode: per-channel
 //!   prompts triggered on a configurable schedule (e.g. cron).
+//!
+//! A heartbeat that finds the message queue full is skipped with a
+//! warning rather than waiting; the next tick sends it again.
+//!
+//! With [`HeartbeatService::with_delivery`], each heartbeat carries
+//! [`ProactiveDelivery`] rules: where the agent's reply goes, when it is
+//! held back, and that a `NO_NOTIFY` reply is dropped.
+//!
+//! With [`HeartbeatService::with_probe`], each heartbeat carries a
+//! [`StatusSnapshot`] of the gateway, and a beat is deferred while a turn
+//! is still running in the heartbeat's session or its delivery target.
+//! With [`HeartbeatService::with_backoff`], the interval doubles for each
+//! `NO_NOTIFY` reply past a threshold, up to a cap; replies are fed back
+//! through [`HeartbeatService::replies`].
+
+pub mod status;
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
+use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
 use crate::error::{Result, ServiceError};
+use clawft_types::delivery::{self, ProactiveDelivery};
 use clawft_types::event::InboundMessage;
 
+pub use status::{ChannelHealth, HeartbeatProbe, PendingJob, StatusSnapshot, UsageDay};
+
+/// How long a beat waits for a busy session before trying again.
+const BUSY_RETRY: Duration = Duration::from_secs(60);
+
 /// A target channel for proactive check-in heartbeats.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckInTarget {
@@ -48,11 +73,56 @@ pub enum HeartbeatMode {
     },
 }
 
+/// Stretches the heartbeat interval while the agent has nothing to say.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// Consecutive `NO_NOTIFY` replies before the interval starts doubling.
+    pub after: u32,
+    /// Longest interval the backoff reaches.
+    pub max_interval: Duration,
+}
+
+/// Tracks how the agent has been answering heartbeats.
+#[derive(Debug, Default)]
+pub struct HeartbeatReplies {
+    quiet_streak: AtomicU32,
+}
+
+impl HeartbeatReplies {
+    /// Record the agent's reply to a heartbeat: a `NO_NOTIFY` reply extends
+    /// the quiet streak, anything else ends it.
+    pub fn record(&self, reply: &str) {
+        if delivery::is_suppressed(reply) {
+            self.quiet_streak.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.quiet_streak.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Consecutive `NO_NOTIFY` replies so far.
+    pub fn quiet_streak(&self) -> u32 {
+        self.quiet_streak.load(Ordering::Relaxed)
+    }
+}
+
+/// What the loop does when it wakes up.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// Send a heartbeat now.
+    Beat,
+    /// Sleep this long, then look again.
+    Wait(Duration),
+}
+
 /// A service that emits heartbeat messages at a regular interval.
 pub struct HeartbeatService {
     interval: Duration,
     mode: HeartbeatMode,
     message_tx: mpsc::Sender<InboundMessage>,
+    delivery: Option<ProactiveDelivery>,
+    probe: Option<Arc<dyn HeartbeatProbe>>,
+    backoff: Option<Backoff>,
+    replies: Arc<HeartbeatReplies>,
 }
 
 impl HeartbeatService {
@@ -65,11 +135,11 @@ impl HeartbeatService {
         prompt: String,
         message_tx: mpsc::Sender<InboundMessage>,
     ) -> Self {
-        Self {
-            interval: Duration::from_secs(interval_minutes * 60),
-            mode: HeartbeatMode::Simple { prompt },
+        Self::with_mode(
+            Duration::from_secs(interval_minutes * 60),
+            HeartbeatMode::Simple { prompt },
             message_tx,
-        }
+        )
     }
 
     /// Create a new heartbeat service in `CheckIn` mode.
@@ -80,12 +150,114 @@ impl HeartbeatService {
         interval_minutes: u64,
         targets: Vec<CheckInTarget>,
         message_tx: mpsc::Sender<InboundMessage>,
+    ) -> Self {
+        Self::with_mode(
+            Duration::from_secs(interval_minutes * 60),
+            HeartbeatMode::CheckIn { targets },
+            message_tx,
+        )
+    }
+
+    fn with_mode(
+        interval: Duration,
+        mode: HeartbeatMode,
+        message_tx: mpsc::Sender<InboundMessage>,
     ) -> Self {
         Self {
-            interval: Duration::from_secs(interval_minutes * 60),
-            mode: HeartbeatMode::CheckIn { targets },
+            interval,
+            mode,
             message_tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::new(HeartbeatReplies::default()),
+        }
+    }
+
+    /// Attach delivery rules for the replies to the heartbeat prompts.
+    pub fn with_delivery(mut self, delivery: ProactiveDelivery) -> Self {
+        self.delivery = Some(delivery);
+        self
+    }
+
+    /// Attach the probe that supplies status snapshots and reports busy
+    /// sessions.
+    pub fn with_probe(mut self, probe: Arc<dyn HeartbeatProbe>) -> Self {
+        self.probe = Some(probe);
+        self
+    }
+
+    /// Double the interval for each `NO_NOTIFY` reply once `after` of them
+    /// arrived in a row, up to `max_interval_minutes`. `after == 0` turns
+    /// the backoff off.
+    pub fn with_backoff(mut self, after: u32, max_interval_minutes: u64) -> Self {
+        self.backoff = (after > 0).then(|| Backoff {
+            after,
+            max_interval: Duration::from_secs(max_interval_minutes * 60),
+        });
+        self
+    }
+
+    /// Where the agent's replies to heartbeats are recorded.
+    pub fn replies(&self) -> Arc<HeartbeatReplies> {
+        Arc::clone(&self.replies)
+    }
+
+    /// Metadata for one heartbeat message, starting from `metadata`.
+    fn with_delivery_metadata(
+        &self,
+        mut metadata: HashMap<String, serde_json::Value>,
+    ) -> HashMap<String, serde_json::Value> {
+        if let Some(delivery) = &self.delivery {
+            delivery.insert_into(&mut metadata);
         }
+        metadata
+    }
+
+    /// The interval before the next beat, stretched by the backoff.
+    fn current_interval(&self) -> Duration {
+        let Some(backoff) = self.backoff else {
+            return self.interval;
+        };
+        let streak = self.replies.quiet_streak();
+        if streak < backoff.after {
+            return self.interval;
+        }
+        let doublings = (streak - backoff.after + 1).min(16);
+        self.interval
+            .saturating_mul(1 << doublings)
+            .min(backoff.max_interval.max(self.interval))
+    }
+
+    /// Sessions a beat must not interrupt: the heartbeat's own and the one
+    /// its replies are delivered to.
+    fn session_keys(&self) -> Vec<String> {
+        let mut keys = match &self.mode {
+            HeartbeatMode::Simple { .. } => vec!["heartbeat:heartbeat".to_string()],
+            HeartbeatMode::CheckIn { targets } => targets
+                .iter()
+                .map(|t| format!("heartbeat:heartbeat:{}", t.channel))
+                .collect(),
+        };
+        if let Some(target) = self.delivery.as_ref().and_then(|d| d.target.as_ref()) {
+            keys.push(format!("{}:{}", target.channel, target.chat_id));
+        }
+        keys
+    }
+
+    /// Decide what to do `elapsed` after the last beat.
+    fn next_step(&self, elapsed: Duration) -> Step {
+        let due = self.current_interval();
+        if elapsed < due {
+            return Step::Wait(due - elapsed);
+        }
+        if let Some(probe) = &self.probe
+            && let Some(session) = self.session_keys().into_iter().find(|k| probe.is_busy(k))
+        {
+            info!(session = %session, "turn in progress, deferring heartbeat");
+            return Step::Wait(BUSY_RETRY.min(self.interval));
+        }
+        Step::Beat
     }
 
     /// Start the heartbeat loop.
@@ -101,11 +273,10 @@ impl HeartbeatService {
             },
             "heartbeat service started"
         );
-        let mut interval = tokio::time::interval(self.interval);
 
-        // The first tick fires immediately; skip it so the first heartbeat
-        // happens after one full interval.
-        interval.tick().await;
+        // The first heartbeat happens after one full interval.
+        let mut last_beat = Instant::now();
+        let mut wait = self.interval;
 
         loop {
             tokio::select! {
@@ -113,30 +284,56 @@ impl HeartbeatService {
                     info!("heartbeat service shutting down");
                     return Ok(());
                 }
-                _ = interval.tick() => {
-                    self.emit_heartbeat()?;
-                }
+                _ = tokio::time::sleep(wait) => {}
             }
+            wait = match self.next_step(last_beat.elapsed()) {
+                Step::Beat => {
+                    let status = match &self.probe {
+                        Some(probe) => Some(probe.snapshot().await),
+                        None => None,
+                    };
+                    self.emit_heartbeat(status.as_ref())?;
+                    last_beat = Instant::now();
+                    self.interval
+                }
+                Step::Wait(wait) => {
+                    debug!(wait_secs = wait.as_secs(), "heartbeat not due yet");
+                    wait
+                }
+            };
         }
     }
 
-    /// Emit one round of heartbeat messages based on the current mode.
-    fn emit_heartbeat(&self) -> Result<()> {
+    /// Emit one round of heartbeat messages based on the current mode,
+    /// each carrying `status` when given.
+    fn emit_heartbeat(&self, status: Option<&StatusSnapshot>) -> Result<()> {
+        let content = |prompt: &str| match status {
+            Some(status) => format!("{prompt}\n\n{}", status.render()),
+            None => prompt.to_string(),
+        };
+        let with_status = |mut metadata: HashMap<String, serde_json::Value>| {
+            if let Some(status) = status
+                && let Ok(value) = serde_json::to_value(status)
+            {
+                metadata.insert(status::STATUS_KEY.to_string(), value);
+            }
+            self.with_delivery_metadata(metadata)
+        };
+
         match &self.mode {
             HeartbeatMode::Simple { prompt } => {
                 let msg = InboundMessage {
                     channel: "heartbeat".to_string(),
                     sender_id: "system".to_string(),
                     chat_id: "heartbeat".to_string(),
-                    content: prompt.clone(),
+                    content: content(prompt),
                     timestamp: Utc::now(),
                     media: vec![],
-                    metadata: HashMap::new(),
+                    attachments: vec![],
+                    metadata: with_status(HashMap::new()),
                 };
 
-                self.message_tx
-                    .try_send(msg)
-                    .map_err(|_| ServiceError::ChannelClosed)?;
+                self.send(msg)?;
             }
             HeartbeatMode::CheckIn { targets } => {
                 for target in targets {
@@ -154,25 +351,40 @@ impl HeartbeatService {
                         channel: "heartbeat".to_string(),
                         sender_id: "system".to_string(),
                         chat_id: format!("heartbeat:{}", target.channel),
-                        content: target.prompt.clone(),
+                        content: content(&target.prompt),
                         timestamp: Utc::now(),
                         media: vec![],
-                        metadata,
+                        attachments: vec![],
+                        metadata: with_status(metadata),
                     };
 
-                    if let Err(e) = self.message_tx.try_send(msg) {
+                    if let Err(e) = self.send(msg) {
                         warn!(
                             target_channel = %target.channel,
                             "failed to send check-in heartbeat"
                         );
-                        debug!(error = %e, "channel send error");
-                        return Err(ServiceError::ChannelClosed);
+                        return Err(e);
                     }
                 }
             }
         }
         Ok(())
     }
+
+    /// Queue one heartbeat message, shedding it if the queue is full.
+    fn send(&self, msg: InboundMessage) -> Result<()> {
+        match self.message_tx.try_send(msg) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(msg)) => {
+                warn!(
+                    chat_id = %msg.chat_id,
+                    "message queue full, heartbeat shed"
+                );
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => Err(ServiceError::ChannelClosed),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -191,6 +403,10 @@ mod tests {
                 prompt: "heartbeat check".into(),
             },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         let cancel = CancellationToken::new();
@@ -222,6 +438,10 @@ mod tests {
                 prompt: "test".into(),
             },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         let cancel = CancellationToken::new();
@@ -246,6 +466,10 @@ mod tests {
                 prompt: "test".into(),
             },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         // Drop the receiver so the channel is closed.
@@ -261,6 +485,28 @@ mod tests {
         assert!(matches!(result.unwrap_err(), ServiceError::ChannelClosed));
     }
 
+    #[tokio::test]
+    async fn full_channel_sheds_heartbeat_and_keeps_running() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let svc = HeartbeatService {
+            interval: Duration::from_millis(10),
+            mode: HeartbeatMode::Simple {
+                prompt: "test".into(),
+            },
+            message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
+        };
+
+        svc.emit_heartbeat(None).unwrap();
+        // The queue is full; the second heartbeat is shed, not an error.
+        svc.emit_heartbeat(None).unwrap();
+        assert_eq!(rx.recv().await.unwrap().content, "test");
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn new_sets_interval_from_minutes() {
         let (tx, _rx) = mpsc::channel(1024);
@@ -305,6 +551,10 @@ mod tests {
             interval: Duration::from_millis(50),
             mode: HeartbeatMode::CheckIn { targets },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         let cancel = CancellationToken::new();
@@ -358,6 +608,10 @@ mod tests {
             interval: Duration::from_millis(50),
             mode: HeartbeatMode::CheckIn { targets: vec![] },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         let cancel = CancellationToken::new();
@@ -386,6 +640,10 @@ mod tests {
             interval: Duration::from_millis(10),
             mode: HeartbeatMode::CheckIn { targets },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
         // Drop receiver to close the channel.
@@ -422,9 +680,13 @@ mod tests {
                 prompt: "simple check".into(),
             },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
-        svc.emit_heartbeat().unwrap();
+        svc.emit_heartbeat(None).unwrap();
 
         let msg = rx.try_recv().unwrap();
         assert_eq!(msg.channel, "heartbeat");
@@ -449,9 +711,13 @@ mod tests {
             interval: Duration::from_secs(60),
             mode: HeartbeatMode::CheckIn { targets },
             message_tx: tx,
+            delivery: None,
+            probe: None,
+            backoff: None,
+            replies: Arc::default(),
         };
 
-        svc.emit_heartbeat().unwrap();
+        svc.emit_heartbeat(None).unwrap();
 
         let msg1 = rx.try_recv().unwrap();
         assert_eq!(msg1.chat_id, "heartbeat:email");
@@ -469,4 +735,165 @@ mod tests {
         assert_eq!(msg2.chat_id, "heartbeat:discord");
         assert_eq!(msg2.content, "Discord check");
     }
+
+    #[test]
+    fn emit_heartbeat_carries_delivery_rules() {
+        use clawft_types::delivery::{DeliveryTarget, QuietHours};
+
+        let (tx, mut rx) = mpsc::channel(1024);
+        let delivery = ProactiveDelivery {
+            target: Some(DeliveryTarget {
+                channel: "telegram".into(),
+                chat_id: "42".into(),
+            }),
+            quiet_hours: Some(QuietHours::parse("22:00-07:00", "UTC").unwrap()),
+        };
+        let svc =
+            HeartbeatService::new(60, "check calendar".into(), tx).with_delivery(delivery.clone());
+
+        svc.emit_heartbeat(None).unwrap();
+
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(
+            ProactiveDelivery::from_metadata(&msg.metadata),
+            Some(delivery)
+        );
+    }
+
+    // -- Status snapshots, busy sessions and backoff --
+
+    struct MockProbe {
+        busy: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl MockProbe {
+        fn new(busy: &[&str]) -> Arc<Self> {
+            Arc::new(Self {
+                busy: std::sync::Mutex::new(busy.iter().map(|s| s.to_string()).collect()),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HeartbeatProbe for MockProbe {
+        async fn snapshot(&self) -> StatusSnapshot {
+            StatusSnapshot {
+                held_replies: 4,
+                ..StatusSnapshot::new(Utc::now())
+            }
+        }
+
+        fn is_busy(&self, session_key: &str) -> bool {
+            self.busy.lock().unwrap().iter().any(|k| k == session_key)
+        }
+    }
+
+    #[test]
+    fn emit_heartbeat_carries_status() {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let svc = HeartbeatService::new(60, "anything new?".into(), tx);
+        let status = StatusSnapshot {
+            held_replies: 1,
+            ..StatusSnapshot::new(Utc::now())
+        };
+
+        svc.emit_heartbeat(Some(&status)).unwrap();
+
+        let msg = rx.try_recv().unwrap();
+        assert!(msg.content.starts_with("anything new?\n\nStatus at "));
+        assert!(msg.content.contains("Replies held for quiet hours: 1"));
+        let carried: StatusSnapshot =
+            serde_json::from_value(msg.metadata[status::STATUS_KEY].clone()).unwrap();
+        assert_eq!(carried, status);
+    }
+
+    #[test]
+    fn busy_session_defers_the_beat() {
+        use clawft_types::delivery::DeliveryTarget;
+
+        let (tx, _rx) = mpsc::channel(1024);
+        let probe = MockProbe::new(&["telegram:42"]);
+        let svc = HeartbeatService::new(30, "check".into(), tx)
+            .with_delivery(ProactiveDelivery {
+                target: Some(DeliveryTarget {
+                    channel: "telegram".into(),
+                    chat_id: "42".into(),
+                }),
+                quiet_hours: None,
+            })
+            .with_probe(probe.clone());
+
+        let interval = Duration::from_secs(30 * 60);
+        assert_eq!(
+            svc.next_step(Duration::from_secs(60)),
+            Step::Wait(interval - Duration::from_secs(60))
+        );
+        assert_eq!(svc.next_step(interval), Step::Wait(BUSY_RETRY));
+
+        *probe.busy.lock().unwrap() = vec!["heartbeat:heartbeat".into()];
+        assert_eq!(svc.next_step(interval), Step::Wait(BUSY_RETRY));
+
+        probe.busy.lock().unwrap().clear();
+        assert_eq!(svc.next_step(interval), Step::Beat);
+    }
+
+    #[tokio::test]
+    async fn busy_session_sends_nothing_until_idle() {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let probe = MockProbe::new(&["heartbeat:heartbeat"]);
+        let mut svc = HeartbeatService::new(1, "check".into(), tx).with_probe(probe.clone());
+        svc.interval = Duration::from_millis(20);
+
+        let cancel = CancellationToken::new();
+        let handle = tokio::spawn({
+            let cancel = cancel.clone();
+            async move { svc.start(cancel).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx.try_recv().is_err(), "beat sent into a busy session");
+
+        probe.busy.lock().unwrap().clear();
+        let msg = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(msg.content.contains("Replies held for quiet hours: 4"));
+
+        cancel.cancel();
+        handle.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn quiet_replies_back_off_the_interval() {
+        let (tx, _rx) = mpsc::channel(1024);
+        let svc = HeartbeatService::new(30, "check".into(), tx).with_backoff(2, 180);
+        let replies = svc.replies();
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+
+        replies.record("All quiet. NO_NOTIFY");
+        assert_eq!(svc.current_interval(), minutes(30));
+        replies.record("NO_NOTIFY");
+        assert_eq!(svc.current_interval(), minutes(60));
+        replies.record("NO_NOTIFY.");
+        assert_eq!(svc.current_interval(), minutes(120));
+        replies.record("NO_NOTIFY");
+        assert_eq!(svc.current_interval(), minutes(180));
+        assert_eq!(svc.next_step(minutes(120)), Step::Wait(minutes(60)));
+
+        replies.record("Your flight was moved to 9am.");
+        assert_eq!(replies.quiet_streak(), 0);
+        assert_eq!(svc.current_interval(), minutes(30));
+        assert_eq!(svc.next_step(minutes(30)), Step::Beat);
+    }
+
+    #[test]
+    fn backoff_disabled_keeps_the_interval() {
+        let (tx, _rx) = mpsc::channel(1024);
+        let svc = HeartbeatService::new(30, "check".into(), tx).with_backoff(0, 180);
+        for _ in 0..10 {
+            svc.replies().record("NO_NOTIFY");
+        }
+        assert_eq!(svc.current_interval(), Duration::from_secs(30 * 60));
+    }
 }
@@ -0,0 +1,189 @@
+//! Status snapshots sent with each heartbeat.
+//!
+//! Before a beat the service asks its [`HeartbeatProbe`] for a
+//! [`StatusSnapshot`] of the gateway and appends it to the prompt, so the
+//! agent has something concrete to report on instead of a bare nudge. The
+//! snapshot also travels in the message metadata under [`STATUS_KEY`].
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the [`StatusSnapshot`] a heartbeat message carries.
+pub const STATUS_KEY: &str = "heartbeat_status";
+
+/// How many scheduled jobs a snapshot lists.
+pub const MAX_PENDING_JOBS: usize = 5;
+
+/// What the heartbeat looks at before each beat.
+#[async_trait]
+pub trait HeartbeatProbe: Send + Sync {
+    /// The current status, sent with the prompt.
+    async fn snapshot(&self) -> StatusSnapshot;
+
+    /// Whether a turn is running in session `session_key`.
+    fn is_busy(&self, session_key: &str) -> bool;
+}
+
+/// The gateway's status at one beat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    /// When the snapshot was taken.
+    pub taken_at: DateTime<Utc>,
+    /// Enabled scheduled jobs, soonest first, at most [`MAX_PENDING_JOBS`].
+    #[serde(default)]
+    pub pending_jobs: Vec<PendingJob>,
+    /// Every channel the gateway runs.
+    #[serde(default)]
+    pub channels: Vec<ChannelHealth>,
+    /// LLM usage over the previous day, when a usage ledger is kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_yesterday: Option<UsageDay>,
+    /// Replies to scheduled prompts held back for quiet hours.
+    #[serde(default)]
+    pub held_replies: usize,
+}
+
+/// A scheduled job that has yet to run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingJob {
+    /// Job name.
+    pub name: String,
+    /// When it runs next.
+    pub next_run: DateTime<Utc>,
+}
+
+/// How one channel is doing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelHealth {
+    /// Channel name.
+    pub channel: String,
+    /// Status reported by the channel (e.g. `"running"`).
+    pub status: String,
+    /// Sends that failed since the gateway started.
+    pub send_failures: u64,
+}
+
+/// LLM usage totals for one day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageDay {
+    /// Completed LLM calls.
+    pub requests: u64,
+    /// Prompt tokens.
+    pub input_tokens: u64,
+    /// Completion tokens.
+    pub output_tokens: u64,
+    /// Cost in US dollars.
+    pub cost_usd: f64,
+}
+
+impl StatusSnapshot {
+    /// An empty snapshot taken at `taken_at`.
+    pub fn new(taken_at: DateTime<Utc>) -> Self {
+        Self {
+            taken_at,
+            pending_jobs: Vec::new(),
+            channels: Vec::new(),
+            usage_yesterday: None,
+            held_replies: 0,
+        }
+    }
+
+    /// The snapshot as a text block for the agent.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Status at {}:\n",
+            self.taken_at.format("%Y-%m-%d %H:%M UTC")
+        );
+
+        out.push_str("- Scheduled jobs: ");
+        if self.pending_jobs.is_empty() {
+            out.push_str("none");
+        } else {
+            let jobs: Vec<String> = self
+                .pending_jobs
+                .iter()
+                .map(|j| format!("\"{}\" at {}", j.name, j.next_run.format("%Y-%m-%d %H:%M")))
+                .collect();
+            out.push_str(&jobs.join("; "));
+        }
+        out.push('\n');
+
+        out.push_str("- Channels: ");
+        if self.channels.is_empty() {
+            out.push_str("none");
+        } else {
+            let channels: Vec<String> = self
+                .channels
+                .iter()
+                .map(|c| match c.send_failures {
+                    0 => format!("{} {}", c.channel, c.status),
+                    n => format!("{} {} ({n} failed sends)", c.channel, c.status),
+                })
+                .collect();
+            out.push_str(&channels.join("; "));
+        }
+        out.push('\n');
+
+        if let Some(usage) = &self.usage_yesterday {
+            out.push_str(&format!(
+                "- Usage yesterday: {} requests, {} input / {} output tokens, ${:.2}\n",
+                usage.requests, usage.input_tokens, usage.output_tokens, usage.cost_usd
+            ));
+        }
+        out.push_str(&format!(
+            "- Replies held for quiet hours: {}\n",
+            self.held_replies
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn renders_every_section() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap();
+        let snapshot = StatusSnapshot {
+            pending_jobs: vec![PendingJob {
+                name: "digest".into(),
+                next_run: Utc.with_ymd_and_hms(2026, 10, 18, 18, 0, 0).unwrap(),
+            }],
+            channels: vec![
+                ChannelHealth {
+                    channel: "telegram".into(),
+                    status: "running".into(),
+                    send_failures: 0,
+                },
+                ChannelHealth {
+                    channel: "slack".into(),
+                    status: "error".into(),
+                    send_failures: 3,
+                },
+            ],
+            usage_yesterday: Some(UsageDay {
+                requests: 42,
+                input_tokens: 180_000,
+                output_tokens: 9_000,
+                cost_usd: 1.234,
+            }),
+            held_replies: 2,
+            ..StatusSnapshot::new(at)
+        };
+        assert_eq!(
+            snapshot.render(),
+            "Status at 2026-10-18 09:00 UTC:\n\
+             - Scheduled jobs: \"digest\" at 2026-10-18 18:00\n\
+             - Channels: telegram running; slack error (3 failed sends)\n\
+             - Usage yesterday: 42 requests, 180000 input / 9000 output tokens, $1.23\n\
+             - Replies held for quiet hours: 2\n"
+        );
+
+        let empty = StatusSnapshot::new(at).render();
+        assert!(empty.contains("Scheduled jobs: none"), "{empty}");
+        assert!(!empty.contains("Usage yesterday"), "{empty}");
+    }
+}
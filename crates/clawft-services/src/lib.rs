@@ -1,9 +1,11 @@
 //! Services for the clawft framework.
 //!
-//! Provides cron scheduling, heartbeat monitoring, and MCP client
-//! functionality. Each service generates [`InboundMessage`](clawft_types::event::InboundMessage)
+//! Provides cron scheduling, heartbeat monitoring, workspace backups, and
+//! MCP client functionality. Each service generates [`InboundMessage`](clawft_types::event::InboundMessage)
 //! events that feed into the main message bus.
 
+#[cfg(feature = "backup")]
+pub mod backup;
 pub mod clawhub;
 pub mod cron_service;
 #[cfg(feature = "delegate")]
@@ -0,0 +1,645 @@
+//! Per-server limits and health for MCP tool calls.
+//!
+//! One hung server should not stall every turn that touches its tools.
+//! Each server's calls go through a [`ServerHealth`], which:
+//!
+//! - fails a call that takes longer than the server's call timeout,
+//!   counting any wait for a free call slot;
+//! - lets at most `maxConcurrentCalls` calls run at once;
+//! - opens a circuit breaker after `circuit.failureThreshold` consecutive
+//!   failures, failing calls at once until `circuit.cooldownSecs` pass.
+//!   The next call then probes the server: success closes the circuit,
+//!   failure reopens it.
+//!
+//! Timeouts and lost connections are failures. An error answer from the
+//! server (a JSON-RPC error or a tool result with `isError`) shows the
+//! server is responding, so it resets the failure count.
+//!
+//! [`McpHealthRegistry`] collects the servers' [`HealthSnapshot`]s, which
+//! the gateway serves at `/api/mcp/health` and, in Prometheus form, at
+//! `/metrics`.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use clawft_types::config::MCPServerConfig;
+
+use super::supervisor::SupervisedSession;
+use super::transport::Termination;
+use crate::error::{Result, ServiceError};
+
+/// Latencies kept for the percentiles.
+const LATENCY_WINDOW: usize = 128;
+
+/// Errors kept for [`HealthSnapshot::recent_errors`].
+const RECENT_ERRORS: usize = 5;
+
+/// A server's call limits, from its [`MCPServerConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallLimits {
+    /// Longest a call may take, slot wait included. `None` = no limit.
+    pub timeout: Option<Duration>,
+    /// Most calls in flight at once. 0 = no limit.
+    pub max_concurrent: usize,
+    /// Consecutive failures that open the circuit. 0 = never.
+    pub failure_threshold: u32,
+    /// How long an open circuit fails calls before a probe.
+    pub cooldown: Duration,
+}
+
+impl CallLimits {
+    /// The limits `config` sets.
+    pub fn from_config(config: &MCPServerConfig) -> Self {
+        Self {
+            timeout: (config.call_timeout_secs > 0)
+                .then(|| Duration::from_secs(config.call_timeout_secs)),
+            max_concurrent: config.max_concurrent_calls,
+            failure_threshold: config.circuit.failure_threshold,
+            cooldown: Duration::from_secs(config.circuit.cooldown_secs),
+        }
+    }
+}
+
+/// State of a server's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go to the server.
+    Closed,
+    /// Calls fail at once until the cooldown passes.
+    Open,
+    /// The cooldown has passed; the next call probes the server.
+    HalfOpen,
+}
+
+/// Call latency percentiles, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+/// A failed call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// Health of one MCP server at a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthSnapshot {
+    pub server: String,
+    /// `"up"`, or `"down"` while the server is disconnected or its
+    /// circuit is open.
+    pub status: String,
+    pub connected: bool,
+    pub circuit: CircuitState,
+    pub in_flight: usize,
+    /// Calls that reached the server.
+    pub calls: u64,
+    /// Calls that timed out or lost the connection.
+    pub failures: u64,
+    /// Of `failures`, the calls that timed out.
+    pub timeouts: u64,
+    /// Calls failed at once by the open circuit.
+    pub rejected: u64,
+    pub consecutive_failures: u32,
+    /// Over the last 128 answered calls; `None` before the first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<LatencyPercentiles>,
+    /// The last few failures, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_errors: Vec<RecentError>,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// When the current half-open probe started. A probe older than the
+    /// cooldown is treated as abandoned.
+    probe_started: Option<Instant>,
+    calls: u64,
+    failures: u64,
+    timeouts: u64,
+    rejected: u64,
+    latencies: VecDeque<Duration>,
+    recent_errors: VecDeque<RecentError>,
+}
+
+/// How a call affects its server's health.
+enum Outcome {
+    /// The server answered.
+    Answered(Duration),
+    Failed {
+        message: String,
+        timeout: bool,
+    },
+}
+
+/// Limits and health of one MCP server's tool calls.
+pub struct ServerHealth {
+    server: String,
+    limits: CallLimits,
+    slots: Option<Semaphore>,
+    in_flight: AtomicUsize,
+    stats: Mutex<Stats>,
+}
+
+impl ServerHealth {
+    /// Track server `server` under `limits`.
+    pub fn new(server: impl Into<String>, limits: CallLimits) -> Self {
+        Self {
+            server: server.into(),
+            limits,
+            slots: (limits.max_concurrent > 0).then(|| Semaphore::new(limits.max_concurrent)),
+            in_flight: AtomicUsize::new(0),
+            stats: Mutex::new(Stats::default()),
+        }
+    }
+
+    /// Server name.
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Run `call` within the server's limits.
+    ///
+    /// Fails at once with [`ServiceError::McpUnavailable`] while the
+    /// circuit is open, and with [`ServiceError::McpTimeout`] when the
+    /// call (with its wait for a slot) outlasts the timeout.
+    pub async fn call<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        self.admit()?;
+        let started = Instant::now();
+        let deadline = self.limits.timeout.map(|t| started + t);
+
+        let _permit = match &self.slots {
+            Some(slots) => match within(deadline, slots.acquire()).await {
+                Some(permit) => Some(permit.expect("semaphore is never closed")),
+                None => return Err(self.timed_out()),
+            },
+            None => None,
+        };
+
+        let result = {
+            let _running = InFlight::enter(&self.in_flight);
+            within(deadline, call).await
+        };
+
+        match result {
+            None => Err(self.timed_out()),
+            Some(result) => {
+                match &result {
+                    Err(
+                        e @ (ServiceError::McpTransport(_)
+                        | ServiceError::McpUnavailable(_)
+                        | ServiceError::McpTimeout(_)
+                        | ServiceError::Io(_)
+                        | ServiceError::ChannelClosed),
+                    ) => self.record(Outcome::Failed {
+                        message: e.to_string(),
+                        timeout: matches!(e, ServiceError::McpTimeout(_)),
+                    }),
+                    _ => self.record(Outcome::Answered(started.elapsed())),
+                }
+                result
+            }
+        }
+    }
+
+    /// Current health. `connected` is whether the server's session is up.
+    pub fn snapshot(&self, connected: bool) -> HealthSnapshot {
+        let stats = self.lock();
+        let circuit = circuit_state(&stats, Instant::now());
+        let status = if connected && circuit != CircuitState::Open {
+            "up"
+        } else {
+            "down"
+        };
+        HealthSnapshot {
+            server: self.server.clone(),
+            status: status.into(),
+            connected,
+            circuit,
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            calls: stats.calls,
+            failures: stats.failures,
+            timeouts: stats.timeouts,
+            rejected: stats.rejected,
+            consecutive_failures: stats.consecutive_failures,
+            latency_ms: percentiles(&stats.latencies),
+            recent_errors: stats.recent_errors.iter().cloned().collect(),
+        }
+    }
+
+    /// Whether the circuit lets a call through now.
+    fn admit(&self) -> Result<()> {
+        let now = Instant::now();
+        let mut stats = self.lock();
+        match circuit_state(&stats, now) {
+            CircuitState::Closed => Ok(()),
+            CircuitState::HalfOpen
+                if stats
+                    .probe_started
+                    .is_none_or(|started| now >= started + self.limits.cooldown) =>
+            {
+                stats.probe_started = Some(now);
+                Ok(())
+            }
+            _ => {
+                stats.rejected += 1;
+                let retry_in = stats
+                    .open_until
+                    .map(|until| until.saturating_duration_since(now))
+                    .filter(|d| !d.is_zero())
+                    .unwrap_or(self.limits.cooldown);
+                Err(ServiceError::McpUnavailable(format!(
+                    "MCP server '{}' is failing ({} consecutive failures); \
+                     calls are paused, retry in {}s",
+                    self.server,
+                    stats.consecutive_failures,
+                    retry_in.as_secs_f64().ceil().max(1.0) as u64
+                )))
+            }
+        }
+    }
+
+    fn timed_out(&self) -> ServiceError {
+        let secs = self.limits.timeout.map_or(0, |t| t.as_secs());
+        let err = ServiceError::McpTimeout(secs);
+        self.record(Outcome::Failed {
+            message: format!("MCP server '{}': {err}", self.server),
+            timeout: true,
+        });
+        err
+    }
+
+    fn record(&self, outcome: Outcome) {
+        let now = Instant::now();
+        let mut stats = self.lock();
+        stats.calls += 1;
+        match outcome {
+            Outcome::Answered(latency) => {
+                if stats.open_until.is_some() {
+                    info!(server = %self.server, "MCP server answering again, circuit closed");
+                }
+                stats.consecutive_failures = 0;
+                stats.open_until = None;
+                stats.probe_started = None;
+                if stats.latencies.len() == LATENCY_WINDOW {
+                    stats.latencies.pop_front();
+                }
+                stats.latencies.push_back(latency);
+            }
+            Outcome::Failed { message, timeout } => {
+                stats.failures += 1;
+                if timeout {
+                    stats.timeouts += 1;
+                }
+                stats.consecutive_failures += 1;
+                if stats.recent_errors.len() == RECENT_ERRORS {
+                    stats.recent_errors.pop_front();
+                }
+                stats.recent_errors.push_back(RecentError {
+                    at: Utc::now(),
+                    message,
+                });
+                let threshold = self.limits.failure_threshold;
+                let probe_failed = stats.probe_started.take().is_some();
+                if threshold > 0 && (probe_failed || stats.consecutive_failures >= threshold) {
+                    if stats.open_until.is_none() || probe_failed {
+                        warn!(
+                            server = %self.server,
+                            failures = stats.consecutive_failures,
+                            cooldown_secs = self.limits.cooldown.as_secs(),
+                            "MCP server failing, circuit opened"
+                        );
+                    }
+                    stats.open_until = Some(now + self.limits.cooldown);
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Stats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Counts a running call until dropped, so cancelled calls are counted
+/// out too.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn enter(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::Relaxed);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Await `future` until `deadline`, if any; `None` when it passes first.
+async fn within<F: Future>(deadline: Option<Instant>, future: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+fn circuit_state(stats: &Stats, now: Instant) -> CircuitState {
+    match stats.open_until {
+        None => CircuitState::Closed,
+        Some(until) if now < until => CircuitState::Open,
+        Some(_) => CircuitState::HalfOpen,
+    }
+}
+
+fn percentiles(latencies: &VecDeque<Duration>) -> Option<LatencyPercentiles> {
+    if latencies.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<u64> = latencies.iter().map(|d| d.as_millis() as u64).collect();
+    sorted.sort_unstable();
+    let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+    Some(LatencyPercentiles {
+        p50: at(0.50),
+        p95: at(0.95),
+        p99: at(0.99),
+    })
+}
+
+/// The MCP servers a process connected to, with their health.
+#[derive(Default)]
+pub struct McpHealthRegistry {
+    servers: Mutex<Vec<(Arc<ServerHealth>, Arc<SupervisedSession>)>>,
+}
+
+impl McpHealthRegistry {
+    /// Track `session`'s server through `health`.
+    pub fn register(&self, health: Arc<ServerHealth>, session: Arc<SupervisedSession>) {
+        self.lock().push((health, session));
+    }
+
+    /// The session of server `server`.
+    pub fn session(&self, server: &str) -> Option<Arc<SupervisedSession>> {
+        self.lock()
+            .iter()
+            .find(|(health, _)| health.server() == server)
+            .map(|(_, session)| session.clone())
+    }
+
+    /// Health of every server, sorted by name.
+    pub fn snapshot(&self) -> Vec<HealthSnapshot> {
+        let mut snapshots: Vec<_> = self
+            .lock()
+            .iter()
+            .map(|(health, session)| health.snapshot(session.is_connected()))
+            .collect();
+        snapshots.sort_by(|a, b| a.server.cmp(&b.server));
+        snapshots
+    }
+
+    /// End every server's process at once, giving each `grace` to exit
+    /// before it is killed. Returns how each server ended, sorted by name.
+    pub async fn shutdown(&self, grace: Duration) -> Vec<(String, Termination)> {
+        let sessions: Vec<_> = self
+            .lock()
+            .iter()
+            .map(|(_, session)| session.clone())
+            .collect();
+        let mut tasks = tokio::task::JoinSet::new();
+        for session in sessions {
+            tasks.spawn(async move {
+                let ended = session.shutdown(grace).await;
+                (session.server().to_string(), ended)
+            });
+        }
+        let mut ended = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            if let Ok(server) = result {
+                ended.push(server);
+            }
+        }
+        ended.sort_by(|a, b| a.0.cmp(&b.0));
+        ended
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Arc<ServerHealth>, Arc<SupervisedSession>)>> {
+        self.servers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Render `snapshots` in the Prometheus text exposition format.
+pub fn render_prometheus(snapshots: &[HealthSnapshot]) -> String {
+    type Getter = fn(&HealthSnapshot) -> Option<f64>;
+    let families: [(&str, &str, &str, Getter); 9] = [
+        (
+            "clawft_mcp_server_up",
+            "gauge",
+            "1 when the server is connected and its circuit is not open.",
+            |s| Some(if s.status == "up" { 1.0 } else { 0.0 }),
+        ),
+        (
+            "clawft_mcp_circuit_open",
+            "gauge",
+            "1 while the server's circuit breaker is open.",
+            |s| {
+                Some(if s.circuit == CircuitState::Open {
+                    1.0
+                } else {
+                    0.0
+                })
+            },
+        ),
+        (
+            "clawft_mcp_calls_in_flight",
+            "gauge",
+            "Tool calls currently running on the server.",
+            |s| Some(s.in_flight as f64),
+        ),
+        (
+            "clawft_mcp_calls_total",
+            "counter",
+            "Tool calls that reached the server.",
+            |s| Some(s.calls as f64),
+        ),
+        (
+            "clawft_mcp_call_failures_total",
+            "counter",
+            "Tool calls that timed out or lost the connection.",
+            |s| Some(s.failures as f64),
+        ),
+        (
+            "clawft_mcp_call_timeouts_total",
+            "counter",
+            "Tool calls that timed out.",
+            |s| Some(s.timeouts as f64),
+        ),
+        (
+            "clawft_mcp_calls_rejected_total",
+            "counter",
+            "Tool calls failed at once by the open circuit.",
+            |s| Some(s.rejected as f64),
+        ),
+        (
+            "clawft_mcp_call_latency_p50_seconds",
+            "gauge",
+            "Median latency of recent answered calls.",
+            |s| s.latency_ms.map(|l| l.p50 as f64 / 1000.0),
+        ),
+        (
+            "clawft_mcp_call_latency_p99_seconds",
+            "gauge",
+            "99th percentile latency of recent answered calls.",
+            |s| s.latency_ms.map(|l| l.p99 as f64 / 1000.0),
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, kind, help, get) in families {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for s in snapshots {
+            if let Some(v) = get(s) {
+                let label = s.server.replace('\\', "\\\\").replace('"', "\\\"");
+                let _ = writeln!(out, "{name}{{server=\"{label}\"}} {v}");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(timeout_ms: u64, max_concurrent: usize, threshold: u32) -> CallLimits {
+        CallLimits {
+            timeout: Some(Duration::from_millis(timeout_ms)),
+            max_concurrent,
+            failure_threshold: threshold,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+
+    async fn slow(ms: u64) -> Result<&'static str> {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        Ok("done")
+    }
+
+    #[tokio::test]
+    async fn slow_calls_time_out() {
+        let health = ServerHealth::new("slow", limits(20, 0, 0));
+        let err = health.call(slow(1_000)).await.unwrap_err();
+        assert!(matches!(err, ServiceError::McpTimeout(_)));
+        assert_eq!(health.call(slow(1)).await.unwrap(), "done");
+
+        let snap = health.snapshot(true);
+        assert_eq!((snap.calls, snap.failures, snap.timeouts), (2, 1, 1));
+        assert_eq!(snap.consecutive_failures, 0);
+        assert_eq!(snap.recent_errors.len(), 1);
+        assert!(snap.latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_slot_counts_toward_the_timeout() {
+        let health = Arc::new(ServerHealth::new("busy", limits(100, 1, 0)));
+        let holder = health.clone();
+        let first = tokio::spawn(async move { holder.call(slow(90)).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(health.snapshot(true).in_flight, 1);
+
+        // 40ms alone is well within the limit, but not after ~80ms waiting.
+        let err = health.call(slow(40)).await.unwrap_err();
+        assert!(matches!(err, ServiceError::McpTimeout(_)));
+        assert!(first.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn open_circuit_fails_fast_until_a_probe_succeeds() {
+        let mut limits = limits(20, 0, 2);
+        limits.cooldown = Duration::from_millis(100);
+        let health = ServerHealth::new("hung", limits);
+        for _ in 0..2 {
+            assert!(health.call(slow(1_000)).await.is_err());
+        }
+        assert_eq!(health.snapshot(true).circuit, CircuitState::Open);
+        assert_eq!(health.snapshot(true).status, "down");
+
+        let started = Instant::now();
+        let err = health.call(slow(1)).await.unwrap_err();
+        assert!(matches!(err, ServiceError::McpUnavailable(_)));
+        assert!(started.elapsed() < Duration::from_millis(10));
+        assert_eq!(health.snapshot(true).rejected, 1);
+
+        // After the cooldown one probe goes through; its failure reopens.
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(health.snapshot(true).circuit, CircuitState::HalfOpen);
+        assert!(matches!(
+            health.call(slow(1_000)).await,
+            Err(ServiceError::McpTimeout(_))
+        ));
+        assert_eq!(health.snapshot(true).circuit, CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(health.call(slow(1)).await.unwrap(), "done");
+        let snap = health.snapshot(true);
+        assert_eq!(snap.circuit, CircuitState::Closed);
+        assert_eq!(snap.status, "up");
+    }
+
+    #[tokio::test]
+    async fn server_errors_do_not_trip_the_circuit() {
+        let health = ServerHealth::new("picky", limits(1_000, 0, 1));
+        for _ in 0..3 {
+            let err = health
+                .call(async { Err::<(), _>(ServiceError::McpProtocol("invalid params".into())) })
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ServiceError::McpProtocol(_)));
+        }
+        let snap = health.snapshot(true);
+        assert_eq!(snap.circuit, CircuitState::Closed);
+        assert_eq!(snap.failures, 0);
+
+        let _ = health
+            .call(async { Err::<(), _>(ServiceError::McpTransport("broken pipe".into())) })
+            .await;
+        assert_eq!(health.snapshot(true).circuit, CircuitState::Open);
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let latencies: VecDeque<_> = (1..=100).map(Duration::from_millis).collect();
+        let p = percentiles(&latencies).unwrap();
+        assert_eq!((p.p50, p.p95, p.p99), (51, 95, 99));
+        assert_eq!(percentiles(&VecDeque::new()), None);
+    }
+
+    #[test]
+    fn prometheus_output_labels_each_server() {
+        let health = ServerHealth::new("docs", limits(1_000, 0, 0));
+        let text = render_prometheus(&[health.snapshot(false)]);
+        assert!(text.contains("# TYPE clawft_mcp_server_up gauge"));
+        assert!(text.contains("clawft_mcp_server_up{server=\"docs\"} 0"));
+        assert!(text.contains("clawft_mcp_calls_total{server=\"docs\"} 0"));
+        assert!(!text.contains("latency_p50_seconds{"));
+    }
+}
@@ -7,10 +7,17 @@ pub mod bridge;
 pub mod client;
 pub mod composite;
 pub mod discovery;
+pub mod health;
 pub mod ide;
 pub mod middleware;
+pub mod progress;
+pub mod prompts;
 pub mod provider;
+pub mod resources;
+pub mod sampling;
 pub mod server;
+pub mod subscriptions;
+pub mod supervisor;
 pub mod transport;
 pub mod types;
 
@@ -25,11 +32,13 @@ pub use provider::{
 /// strings used in both client and server code.
 pub const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
 
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Result, ServiceError};
+use sampling::{SamplingHandler, SamplingRequests};
 use transport::McpTransport;
 use types::JsonRpcRequest;
 
@@ -205,6 +214,21 @@ pub struct McpSession {
 impl McpSession {
     /// Connect to an MCP server by performing the initialize handshake.
     pub async fn connect(transport: Box<dyn McpTransport>) -> Result<Self> {
+        Self::connect_with_sampling(transport, None).await
+    }
+
+    /// Connect to an MCP server, advertising the `sampling` capability and
+    /// answering its `sampling/createMessage` requests with `sampling` when
+    /// one is given.
+    pub async fn connect_with_sampling(
+        transport: Box<dyn McpTransport>,
+        sampling: Option<Arc<dyn SamplingHandler>>,
+    ) -> Result<Self> {
+        let mut capabilities = serde_json::json!({ "tools": {} });
+        if let Some(handler) = sampling {
+            transport.set_request_handler(Arc::new(SamplingRequests(handler)));
+            capabilities["sampling"] = serde_json::json!({});
+        }
         let client = McpClient::new(transport);
 
         // Step 1: Send initialize request.
@@ -213,7 +237,7 @@ impl McpSession {
                 "initialize",
                 serde_json::json!({
                     "protocolVersion": MCP_PROTOCOL_VERSION,
-                    "capabilities": { "tools": {} },
+                    "capabilities": capabilities,
                     "clientInfo": {
                         "name": "clawft",
                         "version": env!("CARGO_PKG_VERSION")
@@ -265,6 +289,30 @@ impl McpSession {
         self.client.call_tool(name, params).await
     }
 
+    /// Subscribe to the server's notifications, or `None` when the
+    /// transport cannot receive them.
+    pub fn subscribe_notifications(
+        &self,
+    ) -> Option<tokio::sync::broadcast::Receiver<types::JsonRpcNotification>> {
+        self.client.transport().subscribe()
+    }
+
+    /// Whether the connection to the server is known to be lost.
+    pub fn is_closed(&self) -> bool {
+        self.client.transport().is_closed()
+    }
+
+    /// Wait until the connection to the server is lost.
+    pub async fn closed(&self) {
+        self.client.transport().closed().await
+    }
+
+    /// Stop the server process, killing it if it does not exit within
+    /// `grace` (see [`McpTransport::terminate`]).
+    pub async fn terminate(&self, grace: std::time::Duration) -> transport::Termination {
+        self.client.transport().terminate(grace).await
+    }
+
     /// Access the underlying client.
     pub fn client(&self) -> &McpClient {
         &self.client
@@ -527,6 +575,53 @@ mod tests {
         assert_eq!(session.protocol_version, "2025-06-18");
     }
 
+    struct FixedReply;
+
+    #[async_trait::async_trait]
+    impl SamplingHandler for FixedReply {
+        async fn create_message(
+            &self,
+            _request: sampling::CreateMessageRequest,
+        ) -> std::result::Result<sampling::CreateMessageResult, sampling::SamplingError> {
+            Ok(sampling::CreateMessageResult::text(
+                "ok",
+                "test-model",
+                None,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn session_advertises_sampling_only_with_a_handler() {
+        let transport = MockTransport::new(vec![make_init_response(1)]);
+        McpSession::connect(Box::new(transport.clone()))
+            .await
+            .unwrap();
+        let init = &transport.requests().await[0];
+        assert!(init.params["capabilities"].get("sampling").is_none());
+        let reply = transport
+            .server_request(sampling::CREATE_MESSAGE, serde_json::json!({}))
+            .await;
+        assert_eq!(reply.error.unwrap().code, -32601);
+
+        let transport = MockTransport::new(vec![make_init_response(1)]);
+        McpSession::connect_with_sampling(Box::new(transport.clone()), Some(Arc::new(FixedReply)))
+            .await
+            .unwrap();
+        let init = &transport.requests().await[0];
+        assert_eq!(
+            init.params["capabilities"]["sampling"],
+            serde_json::json!({})
+        );
+        let reply = transport
+            .server_request(
+                sampling::CREATE_MESSAGE,
+                serde_json::json!({"messages": [], "maxTokens": 5}),
+            )
+            .await;
+        assert_eq!(reply.result.unwrap()["content"]["text"], "ok");
+    }
+
     #[tokio::test]
     async fn session_connect_error_propagates() {
         let response = make_error_response(1, -32600, "bad init");
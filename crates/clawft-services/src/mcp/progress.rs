@@ -0,0 +1,155 @@
+//! MCP progress notifications for long-running tool calls.
+//!
+//! A client that wants progress puts a `progressToken` in the `_meta` of
+//! its `tools/call` params. While the call runs, the server shell makes a
+//! [`ProgressSender`] for that token [`current`] to the tool provider,
+//! which passes progress on as `notifications/progress` messages; the
+//! transport delivers them ahead of the result. Like the other providers,
+//! the bridge to the tools themselves lives at the integration layer so
+//! this crate does not depend on `clawft-core`.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// Method of a progress notification.
+pub const PROGRESS: &str = "notifications/progress";
+
+/// How far a tool call has got.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Work done so far; must increase with every notification.
+    pub progress: f64,
+    /// Total amount of work, when known.
+    pub total: Option<f64>,
+    /// What the tool is doing.
+    pub message: Option<String>,
+}
+
+/// The `progressToken` of a `tools/call` request, if it asked for progress.
+pub fn token(params: &Value) -> Option<Value> {
+    params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"))
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
+
+/// Sends the progress of one tool call to the client.
+#[derive(Debug, Clone)]
+pub struct ProgressSender {
+    token: Value,
+    notifications: mpsc::UnboundedSender<Value>,
+    last: Arc<Mutex<Option<f64>>>,
+}
+
+impl ProgressSender {
+    /// Progress for the request with `token`, sent as notifications on
+    /// `notifications`.
+    pub fn new(token: Value, notifications: mpsc::UnboundedSender<Value>) -> Self {
+        Self {
+            token,
+            notifications,
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Send `progress` to the client. Reports that do not advance past the
+    /// last one are dropped, as the protocol requires progress to increase.
+    pub fn report(&self, progress: Progress) {
+        {
+            let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_some_and(|last| progress.progress <= last) {
+                return;
+            }
+            *last = Some(progress.progress);
+        }
+
+        let mut params = serde_json::json!({
+            "progressToken": self.token,
+            "progress": progress.progress,
+        });
+        if let Some(total) = progress.total {
+            params["total"] = total.into();
+        }
+        if let Some(message) = progress.message {
+            params["message"] = message.into();
+        }
+        // The request may have been answered already; nobody is listening.
+        let _ = self.notifications.send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": PROGRESS,
+            "params": params
+        }));
+    }
+}
+
+tokio::task_local! {
+    static SENDER: ProgressSender;
+}
+
+/// Await `fut` with `sender` as the [`current`] progress sender.
+pub async fn scope<F: Future>(sender: ProgressSender, fut: F) -> F::Output {
+    SENDER.scope(sender, fut).await
+}
+
+/// Where the tool call being run sends its progress, when the client asked
+/// for it.
+pub fn current() -> Option<ProgressSender> {
+    SENDER.try_with(Clone::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_comes_from_meta() {
+        let params = serde_json::json!({ "name": "t", "_meta": { "progressToken": "abc" } });
+        assert_eq!(token(&params), Some(serde_json::json!("abc")));
+        let params = serde_json::json!({ "name": "t", "_meta": { "progressToken": 7 } });
+        assert_eq!(token(&params), Some(serde_json::json!(7)));
+        assert_eq!(token(&serde_json::json!({ "name": "t" })), None);
+        let params = serde_json::json!({ "_meta": { "progressToken": null } });
+        assert_eq!(token(&params), None);
+    }
+
+    #[tokio::test]
+    async fn reports_only_increasing_progress() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sender = ProgressSender::new(serde_json::json!(1), tx);
+        let step = |progress: f64| Progress {
+            progress,
+            total: Some(3.0),
+            message: None,
+        };
+
+        scope(sender, async {
+            let sender = current().unwrap();
+            sender.report(step(1.0));
+            sender.report(step(1.0));
+            sender.report(Progress {
+                message: Some("linking".into()),
+                ..step(2.0)
+            });
+        })
+        .await;
+        assert!(current().is_none());
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(
+            first,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": { "progressToken": 1, "progress": 1.0, "total": 3.0 }
+            })
+        );
+        let second = rx.recv().await.unwrap();
+        assert_eq!(second["params"]["progress"], 2.0);
+        assert_eq!(second["params"]["message"], "linking");
+        assert!(rx.try_recv().is_err());
+    }
+}
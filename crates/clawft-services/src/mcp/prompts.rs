@@ -0,0 +1,140 @@
+//! Prompt providers for the MCP server.
+//!
+//! MCP prompts are named, parameterized prompts a client offers its user
+//! (`prompts/list`, `prompts/get`). [`PromptProvider`] is the source the
+//! server asks; like [`BuiltinToolProvider`], the implementation backed by
+//! the agent's prompt templates and skills lives at the integration layer
+//! so this crate does not depend on `clawft-core`.
+//!
+//! [`BuiltinToolProvider`]: super::provider::BuiltinToolProvider
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::provider::ContentBlock;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// An argument a prompt accepts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptArgument {
+    /// Argument name.
+    pub name: String,
+    /// Human-readable description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the argument must be given.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A prompt as listed by `prompts/list`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Prompt {
+    /// Prompt name, unique within the server.
+    pub name: String,
+    /// Human-readable description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Arguments the prompt accepts.
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// Who a [`PromptMessage`] is from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptRole {
+    /// The user.
+    User,
+    /// The assistant.
+    Assistant,
+}
+
+/// One message of a rendered prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptMessage {
+    /// Who the message is from.
+    pub role: PromptRole,
+    /// The message content.
+    pub content: ContentBlock,
+}
+
+/// The result of `prompts/get`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GetPromptResult {
+    /// Description of the rendered prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The rendered messages.
+    pub messages: Vec<PromptMessage>,
+}
+
+impl GetPromptResult {
+    /// A single user message with `text`.
+    pub fn user_text(description: Option<String>, text: impl Into<String>) -> Self {
+        Self {
+            description,
+            messages: vec![PromptMessage {
+                role: PromptRole::User,
+                content: ContentBlock::Text { text: text.into() },
+            }],
+        }
+    }
+}
+
+/// Errors getting a prompt.
+#[derive(Debug, thiserror::Error)]
+pub enum PromptError {
+    /// No prompt has this name.
+    #[error("unknown prompt: {0}")]
+    NotFound(String),
+
+    /// A required argument was not given.
+    #[error("prompt '{prompt}' requires argument '{argument}'")]
+    MissingArgument { prompt: String, argument: String },
+}
+
+// ---------------------------------------------------------------------------
+// PromptProvider trait
+// ---------------------------------------------------------------------------
+
+/// A source of prompts.
+#[async_trait]
+pub trait PromptProvider: Send + Sync {
+    /// List the prompts available.
+    async fn list_prompts(&self) -> Vec<Prompt>;
+
+    /// Render prompt `name` with `arguments`.
+    async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: &HashMap<String, String>,
+    ) -> Result<GetPromptResult, PromptError>;
+}
+
+/// Check that `arguments` has every argument `prompt` requires.
+///
+/// # Errors
+///
+/// Returns [`PromptError::MissingArgument`] naming the first one missing.
+pub fn check_required(
+    prompt: &Prompt,
+    arguments: &HashMap<String, String>,
+) -> Result<(), PromptError> {
+    match prompt
+        .arguments
+        .iter()
+        .find(|a| a.required && !arguments.contains_key(&a.name))
+    {
+        Some(missing) => Err(PromptError::MissingArgument {
+            prompt: prompt.name.clone(),
+            argument: missing.name.clone(),
+        }),
+        None => Ok(()),
+    }
+}
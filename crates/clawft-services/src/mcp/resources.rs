@@ -0,0 +1,458 @@
+//! Resource providers for the MCP server.
+//!
+//! MCP resources are read-only documents a client can list and read
+//! (`resources/list`, `resources/read`). Each [`ResourceProvider`] owns a
+//! URI scheme; the server routes `resources/read` to the provider whose
+//! scheme matches, the same way [`CompositeToolProvider`] routes tool calls
+//! by namespace.
+//!
+//! [`DirResourceProvider`] serves the files under a directory: the
+//! workspace, the memory directory, the session transcripts. Reads are
+//! contained to the directory (no `..`, no symlinks out of it) and capped
+//! in size.
+//!
+//! [`CompositeToolProvider`]: super::composite::CompositeToolProvider
+
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Default cap on the size of a resource that can be read (1 MiB).
+pub const DEFAULT_MAX_RESOURCE_BYTES: u64 = 1024 * 1024;
+
+/// Default cap on the number of files a directory provider lists.
+pub const DEFAULT_MAX_RESOURCES: usize = 1000;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A resource as listed by `resources/list`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Resource {
+    /// The resource's URI, e.g. `memory://MEMORY.md`.
+    pub uri: String,
+    /// Display name.
+    pub name: String,
+    /// Human-readable description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME type of the contents.
+    #[serde(default, rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Size in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// A family of resources as listed by `resources/templates/list`, e.g.
+/// `clawft://sessions/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceTemplate {
+    /// RFC 6570 URI template.
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    /// Display name.
+    pub name: String,
+    /// Human-readable description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME type of the contents.
+    #[serde(default, rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// The text contents of a resource, as returned by `resources/read`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceContents {
+    /// The resource's URI.
+    pub uri: String,
+    /// MIME type of the contents.
+    #[serde(default, rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// The contents.
+    pub text: String,
+}
+
+/// Errors reading a resource.
+#[derive(Debug, thiserror::Error)]
+pub enum ResourceError {
+    /// No resource has this URI (or it lies outside the provider's root).
+    #[error("resource not found: {0}")]
+    NotFound(String),
+
+    /// The resource is larger than the provider serves.
+    #[error("resource {uri} is {size} bytes, over the {limit}-byte limit")]
+    TooLarge { uri: String, size: u64, limit: u64 },
+
+    /// The resource is not UTF-8 text.
+    #[error("resource {0} is not text")]
+    NotText(String),
+
+    /// The resource could not be read.
+    #[error("failed to read {uri}: {reason}")]
+    Io { uri: String, reason: String },
+}
+
+// ---------------------------------------------------------------------------
+// ResourceProvider trait
+// ---------------------------------------------------------------------------
+
+/// A source of read-only resources with URIs of one scheme.
+#[async_trait]
+pub trait ResourceProvider: Send + Sync {
+    /// URI scheme of this provider's resources (e.g. `"memory"` for
+    /// `memory://MEMORY.md`).
+    fn scheme(&self) -> &str;
+
+    /// List the resources available from this provider.
+    async fn list_resources(&self) -> Vec<Resource>;
+
+    /// Templates of the URIs this provider reads. The default is none.
+    fn templates(&self) -> Vec<ResourceTemplate> {
+        Vec::new()
+    }
+
+    /// Read the resource at `uri`, whose scheme is [`scheme`](Self::scheme).
+    async fn read_resource(&self, uri: &str) -> Result<ResourceContents, ResourceError>;
+}
+
+// ---------------------------------------------------------------------------
+// DirResourceProvider
+// ---------------------------------------------------------------------------
+
+/// A [`ResourceProvider`] serving the files under a directory as
+/// `<scheme>://<relative path>`.
+///
+/// Hidden files and directories (names starting with `.`) are skipped and
+/// symlinks are not followed when listing. A read resolves the path and
+/// refuses anything that ends up outside the directory, and anything over
+/// the size limit.
+#[derive(Debug, Clone)]
+pub struct DirResourceProvider {
+    scheme: String,
+    root: PathBuf,
+    description: String,
+    max_bytes: u64,
+    max_resources: usize,
+}
+
+impl DirResourceProvider {
+    /// Serve the files under `root` with URI scheme `scheme`. `description`
+    /// is attached to every listed resource.
+    pub fn new(
+        scheme: impl Into<String>,
+        root: impl Into<PathBuf>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            scheme: scheme.into(),
+            root: root.into(),
+            description: description.into(),
+            max_bytes: DEFAULT_MAX_RESOURCE_BYTES,
+            max_resources: DEFAULT_MAX_RESOURCES,
+        }
+    }
+
+    /// Set the largest file, in bytes, that can be read.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Set the most files listed.
+    pub fn with_max_resources(mut self, max_resources: usize) -> Self {
+        self.max_resources = max_resources;
+        self
+    }
+
+    fn uri_for(&self, relative: &Path) -> String {
+        let path = relative
+            .components()
+            .map(|c| encode_segment(&c.as_os_str().to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{}://{path}", self.scheme)
+    }
+
+    /// The file `uri` names, if it is one of this provider's and lies
+    /// under the root.
+    fn resolve(&self, uri: &str) -> Result<PathBuf, ResourceError> {
+        let not_found = || ResourceError::NotFound(uri.to_string());
+        let relative = uri
+            .strip_prefix(self.scheme.as_str())
+            .and_then(|rest| rest.strip_prefix("://"))
+            .and_then(decode_path)
+            .ok_or_else(not_found)?;
+        let relative = Path::new(&relative);
+        if relative.as_os_str().is_empty()
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(not_found());
+        }
+
+        // Resolve symlinks on both sides before checking containment.
+        let root = self.root.canonicalize().map_err(|_| not_found())?;
+        let path = root
+            .join(relative)
+            .canonicalize()
+            .map_err(|_| not_found())?;
+        if !path.starts_with(&root) || !path.is_file() {
+            return Err(not_found());
+        }
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl ResourceProvider for DirResourceProvider {
+    fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    async fn list_resources(&self) -> Vec<Resource> {
+        let root = self.root.clone();
+        let limit = self.max_resources;
+        let files = tokio::task::spawn_blocking(move || list_files(&root, limit))
+            .await
+            .unwrap_or_default();
+
+        files
+            .into_iter()
+            .map(|(relative, size)| Resource {
+                uri: self.uri_for(&relative),
+                name: relative.to_string_lossy().replace('\\', "/"),
+                description: Some(self.description.clone()),
+                mime_type: Some(mime_type(&relative).to_string()),
+                size: Some(size),
+            })
+            .collect()
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<ResourceContents, ResourceError> {
+        let path = self.resolve(uri)?;
+        let io = |e: std::io::Error| ResourceError::Io {
+            uri: uri.to_string(),
+            reason: e.to_string(),
+        };
+
+        let size = tokio::fs::metadata(&path).await.map_err(io)?.len();
+        if size > self.max_bytes {
+            return Err(ResourceError::TooLarge {
+                uri: uri.to_string(),
+                size,
+                limit: self.max_bytes,
+            });
+        }
+        let bytes = tokio::fs::read(&path).await.map_err(io)?;
+        let text = String::from_utf8(bytes).map_err(|_| ResourceError::NotText(uri.to_string()))?;
+
+        Ok(ResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type(&path).to_string()),
+            text,
+        })
+    }
+}
+
+/// Regular files under `root` (relative paths and sizes), sorted, at most
+/// `limit` of them.
+fn list_files(root: &Path, limit: usize) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(root.join(&dir)) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries.into_iter().rev() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            // `DirEntry::file_type` does not follow symlinks.
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let relative = dir.join(entry.file_name());
+            if file_type.is_dir() {
+                dirs.push(relative);
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                files.push((relative, size));
+            }
+        }
+    }
+    files.sort();
+    files.truncate(limit);
+    files
+}
+
+/// MIME type by file extension, defaulting to plain text.
+fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "md" => "text/markdown",
+        "json" => "application/json",
+        "jsonl" => "application/jsonl",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "html" => "text/html",
+        "csv" => "text/csv",
+        _ => "text/plain",
+    }
+}
+
+/// Percent-encode a path segment, keeping unreserved characters.
+pub fn encode_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// Decode a percent-encoded path. `None` for malformed escapes or
+/// non-UTF-8 results.
+pub fn decode_path(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-{prefix}-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn workspace() -> PathBuf {
+        let dir = temp_dir("mcp-resources");
+        std::fs::create_dir_all(dir.join("memory")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join("memory/MEMORY.md"), "# Memory\n").unwrap();
+        std::fs::write(dir.join("notes plan.txt"), "plan").unwrap();
+        std::fs::write(dir.join(".git/config"), "secret").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn lists_visible_files_with_uris() {
+        let dir = workspace();
+        let provider = DirResourceProvider::new("workspace", &dir, "Workspace file");
+        let resources = provider.list_resources().await;
+
+        let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            [
+                "workspace://memory/MEMORY.md",
+                "workspace://notes%20plan.txt"
+            ]
+        );
+        assert_eq!(resources[0].name, "memory/MEMORY.md");
+        assert_eq!(resources[0].mime_type.as_deref(), Some("text/markdown"));
+        assert_eq!(resources[0].size, Some(9));
+
+        let capped = provider.with_max_resources(1).list_resources().await;
+        assert_eq!(capped.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reads_listed_files() {
+        let dir = workspace();
+        let provider = DirResourceProvider::new("workspace", &dir, "Workspace file");
+        let contents = provider
+            .read_resource("workspace://notes%20plan.txt")
+            .await
+            .unwrap();
+        assert_eq!(contents.text, "plan");
+        assert_eq!(contents.mime_type.as_deref(), Some("text/plain"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reads_stay_inside_the_root() {
+        let dir = workspace();
+        let provider = DirResourceProvider::new("memory", dir.join("memory"), "Memory file");
+        for uri in [
+            "memory://../notes%20plan.txt",
+            "memory://%2E%2E/notes%20plan.txt",
+            "memory:///etc/passwd",
+            "memory://",
+            "memory://missing.md",
+            "workspace://MEMORY.md",
+        ] {
+            assert!(
+                matches!(
+                    provider.read_resource(uri).await,
+                    Err(ResourceError::NotFound(_))
+                ),
+                "{uri}"
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("notes plan.txt"), dir.join("memory/link.txt"))
+                .unwrap();
+            assert!(matches!(
+                provider.read_resource("memory://link.txt").await,
+                Err(ResourceError::NotFound(_))
+            ));
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reads_are_size_capped() {
+        let dir = workspace();
+        let provider =
+            DirResourceProvider::new("workspace", &dir, "Workspace file").with_max_bytes(4);
+        assert!(
+            provider
+                .read_resource("workspace://notes%20plan.txt")
+                .await
+                .is_ok()
+        );
+        let err = provider
+            .read_resource("workspace://memory/MEMORY.md")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ResourceError::TooLarge {
+                size: 9,
+                limit: 4,
+                ..
+            }
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
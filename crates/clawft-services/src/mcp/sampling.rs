@@ -0,0 +1,319 @@
+//! MCP sampling: servers asking the client to run a model call.
+//!
+//! A server that needs a completion (to summarize a document, say) sends
+//! `sampling/createMessage` with the conversation and its model
+//! preferences. The client only advertises the `sampling` capability when
+//! it has a [`SamplingHandler`]; the handler decides whether the call may
+//! run, picks the model, and returns the assistant's reply. Like
+//! [`PromptProvider`], the implementation backed by the LLM providers lives
+//! at the integration layer so this crate does not depend on `clawft-core`.
+//!
+//! [`PromptProvider`]: super::prompts::PromptProvider
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::prompts::PromptRole;
+use super::transport::RequestHandler;
+use super::types::JsonRpcError;
+
+/// Method a server calls to request a completion.
+pub const CREATE_MESSAGE: &str = "sampling/createMessage";
+
+/// JSON-RPC error code for a request the user (or policy) refused, as used
+/// by the MCP specification.
+pub const REJECTED: i32 = -1;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Parameters of `sampling/createMessage`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageRequest {
+    /// The conversation to complete.
+    pub messages: Vec<SamplingMessage>,
+    /// Which model the server would like.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+    /// System prompt to use, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Sampling temperature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Most tokens the server wants generated.
+    pub max_tokens: u32,
+    /// Sequences that end generation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+}
+
+/// One message of a sampling conversation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SamplingMessage {
+    /// Who the message is from.
+    pub role: PromptRole,
+    /// The message content.
+    pub content: SamplingContent,
+}
+
+/// Content of a [`SamplingMessage`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SamplingContent {
+    /// Plain text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// A base64-encoded image.
+    Image {
+        /// Base64 image data.
+        data: String,
+        /// MIME type of the image.
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// Base64-encoded audio.
+    Audio {
+        /// Base64 audio data.
+        data: String,
+        /// MIME type of the audio.
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+}
+
+impl SamplingContent {
+    /// The text, for text content.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// A server's model preferences. Hints are advisory and in order of
+/// preference; priorities range from 0 to 1.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    /// Model names (or name fragments) to prefer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<ModelHint>,
+    /// How much cost matters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_priority: Option<f64>,
+    /// How much latency matters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_priority: Option<f64>,
+    /// How much capability matters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intelligence_priority: Option<f64>,
+}
+
+/// A model name hint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelHint {
+    /// Full or partial model name, such as `"sonnet"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Result of `sampling/createMessage`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    /// Always [`PromptRole::Assistant`].
+    pub role: PromptRole,
+    /// The generated content.
+    pub content: SamplingContent,
+    /// The model that produced it.
+    pub model: String,
+    /// Why generation stopped: `"endTurn"`, `"stopSequence"`,
+    /// `"maxTokens"`, or a provider-specific reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+impl CreateMessageResult {
+    /// An assistant text reply.
+    pub fn text(
+        text: impl Into<String>,
+        model: impl Into<String>,
+        stop_reason: Option<String>,
+    ) -> Self {
+        Self {
+            role: PromptRole::Assistant,
+            content: SamplingContent::Text { text: text.into() },
+            model: model.into(),
+            stop_reason,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handler
+// ---------------------------------------------------------------------------
+
+/// Why a sampling request was not completed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SamplingError {
+    /// Refused by the approval policy or the user.
+    #[error("sampling request rejected: {0}")]
+    Rejected(String),
+    /// The server used up its token budget.
+    #[error("sampling budget exhausted: {0}")]
+    BudgetExhausted(String),
+    /// The request cannot be served as given.
+    #[error("invalid sampling request: {0}")]
+    Invalid(String),
+    /// The model call failed.
+    #[error("sampling failed: {0}")]
+    Failed(String),
+}
+
+impl From<SamplingError> for JsonRpcError {
+    fn from(err: SamplingError) -> Self {
+        let code = match err {
+            SamplingError::Rejected(_) => REJECTED,
+            SamplingError::BudgetExhausted(_) => -32000,
+            SamplingError::Invalid(_) => -32602,
+            SamplingError::Failed(_) => -32603,
+        };
+        Self {
+            code,
+            message: err.to_string(),
+            data: None,
+        }
+    }
+}
+
+/// Runs model calls requested by one MCP server.
+#[async_trait]
+pub trait SamplingHandler: Send + Sync {
+    /// Complete `request`, or refuse it.
+    async fn create_message(
+        &self,
+        request: CreateMessageRequest,
+    ) -> Result<CreateMessageResult, SamplingError>;
+}
+
+/// Routes `sampling/createMessage` requests to a [`SamplingHandler`].
+pub(crate) struct SamplingRequests(pub(crate) Arc<dyn SamplingHandler>);
+
+#[async_trait]
+impl RequestHandler for SamplingRequests {
+    async fn handle(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, JsonRpcError> {
+        if method != CREATE_MESSAGE {
+            return Err(JsonRpcError {
+                code: -32601,
+                message: format!("method not found: {method}"),
+                data: None,
+            });
+        }
+        let request: CreateMessageRequest =
+            serde_json::from_value(params).map_err(|e| SamplingError::Invalid(e.to_string()))?;
+        let result = self.0.create_message(request).await?;
+        serde_json::to_value(result).map_err(|e| SamplingError::Failed(e.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Replies with the last message's text, upper-cased.
+    struct Shout;
+
+    #[async_trait]
+    impl SamplingHandler for Shout {
+        async fn create_message(
+            &self,
+            request: CreateMessageRequest,
+        ) -> Result<CreateMessageResult, SamplingError> {
+            let text = request
+                .messages
+                .last()
+                .and_then(|m| m.content.as_text())
+                .ok_or_else(|| SamplingError::Invalid("no text".into()))?;
+            Ok(CreateMessageResult::text(
+                text.to_uppercase(),
+                "shout-1",
+                Some("endTurn".into()),
+            ))
+        }
+    }
+
+    #[test]
+    fn request_parses_the_spec_shape() {
+        let request: CreateMessageRequest = serde_json::from_value(json!({
+            "messages": [{"role": "user", "content": {"type": "text", "text": "hi"}}],
+            "modelPreferences": {"hints": [{"name": "sonnet"}], "intelligencePriority": 0.8},
+            "systemPrompt": "Be brief.",
+            "includeContext": "none",
+            "maxTokens": 100
+        }))
+        .unwrap();
+        assert_eq!(request.messages[0].role, PromptRole::User);
+        assert_eq!(request.messages[0].content.as_text(), Some("hi"));
+        let prefs = request.model_preferences.unwrap();
+        assert_eq!(prefs.hints[0].name.as_deref(), Some("sonnet"));
+        assert_eq!(prefs.intelligence_priority, Some(0.8));
+        assert_eq!(request.system_prompt.as_deref(), Some("Be brief."));
+        assert_eq!(request.max_tokens, 100);
+    }
+
+    #[test]
+    fn result_serializes_to_the_spec_shape() {
+        let result = CreateMessageResult::text("hello", "gpt-4o", Some("endTurn".into()));
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": "hello"},
+                "model": "gpt-4o",
+                "stopReason": "endTurn"
+            })
+        );
+    }
+
+    #[test]
+    fn rejection_uses_the_spec_error_code() {
+        let err: JsonRpcError = SamplingError::Rejected("denied".into()).into();
+        assert_eq!(err.code, REJECTED);
+        assert!(err.message.contains("denied"));
+    }
+
+    #[tokio::test]
+    async fn requests_are_routed_to_the_handler() {
+        let handler = SamplingRequests(Arc::new(Shout));
+        let result = handler
+            .handle(
+                CREATE_MESSAGE,
+                json!({
+                    "messages": [{"role": "user", "content": {"type": "text", "text": "hi"}}],
+                    "maxTokens": 10
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["content"]["text"], "HI");
+        assert_eq!(result["model"], "shout-1");
+
+        let err = handler.handle(CREATE_MESSAGE, json!({})).await.unwrap_err();
+        assert_eq!(err.code, -32602);
+        let err = handler.handle("roots/list", json!({})).await.unwrap_err();
+        assert_eq!(err.code, -32601);
+    }
+}
@@ -3,14 +3,33 @@
 //!
 //! [`McpServerShell`] is generic over `AsyncBufRead + AsyncWrite` so it
 //! can be driven by stdio, TCP, or in-memory buffers for testing.
+//!
+//! Besides tools, the shell serves resources ([`ResourceProvider`]) and
+//! prompts ([`PromptProvider`]) when they are configured, and forwards
+//! [`ListChanged`] events to the client as `list_changed` notifications.
+//! A `tools/call` with a `progressToken` gets `notifications/progress`
+//! messages while the tool runs (see [`progress`](super::progress)), and
+//! a client subscribed to a resource gets `notifications/resources/updated`
+//! when it changes (see [`subscriptions`](super::subscriptions)).
+
+use std::collections::HashMap;
 
+use serde::Serialize;
 use serde_json::Value;
 use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
 
 use super::ToolDefinition;
 use super::composite::CompositeToolProvider;
 use super::middleware::{Middleware, ToolCallRequest};
+use super::progress::{self, ProgressSender};
+use super::prompts::PromptProvider;
 use super::provider::CallToolResult;
+use super::resources::{ResourceError, ResourceProvider};
+use super::subscriptions::{self, Subscriptions};
+
+#[cfg(feature = "mcp-http")]
+pub mod http;
 
 // ── Constants ───────────────────────────────────────────────────────────
 
@@ -23,6 +42,46 @@ const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
 const METHOD_NOT_FOUND: i32 = -32601;
 const NOT_INITIALIZED: i32 = -32002;
 const INVALID_REQUEST: i32 = -32600;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+/// MCP's code for `resources/read` of an unknown URI.
+const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// Most resources or prompts returned by one list request; the rest are
+/// fetched with the returned `nextCursor`.
+const PAGE_SIZE: usize = 100;
+
+// ── ListChanged ────────────────────────────────────────────────────────
+
+/// A list the server offers has changed, and clients should fetch it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListChanged {
+    /// The tool list (`tools/list`).
+    Tools,
+    /// The resource list (`resources/list`).
+    Resources,
+    /// The prompt list (`prompts/list`).
+    Prompts,
+}
+
+impl ListChanged {
+    /// The notification method sent to the client.
+    pub fn method(self) -> &'static str {
+        match self {
+            Self::Tools => "notifications/tools/list_changed",
+            Self::Resources => "notifications/resources/list_changed",
+            Self::Prompts => "notifications/prompts/list_changed",
+        }
+    }
+
+    /// The JSON-RPC notification sent to the client.
+    pub fn notification(self) -> Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": self.method()
+        })
+    }
+}
 
 // ── McpServerShell ─────────────────────────────────────────────────────
 
@@ -30,13 +89,21 @@ const INVALID_REQUEST: i32 = -32600;
 /// writes responses to a writer.
 ///
 /// Handles the `initialize` handshake, `tools/list`, `tools/call`, and
-/// `notifications/initialized` methods. Unknown methods receive a
-/// `-32601 Method not found` error. Requests sent before `initialize`
-/// receive a `-32002 Server not initialized` error.
+/// `notifications/initialized` methods, plus `resources/list`,
+/// `resources/read` and `resources/templates/list` when a resource
+/// provider is added (and `resources/subscribe` and `resources/unsubscribe`
+/// when resource updates are set), and `prompts/list` and `prompts/get`
+/// when a prompt provider is set. Unknown methods receive a `-32601 Method not found`
+/// error. Requests sent before `initialize` receive a
+/// `-32002 Server not initialized` error.
 pub struct McpServerShell {
     provider: CompositeToolProvider,
     middlewares: Vec<Box<dyn Middleware>>,
-    initialized: bool,
+    resources: Vec<Box<dyn ResourceProvider>>,
+    prompts: Option<Box<dyn PromptProvider>>,
+    list_changed: Option<mpsc::Receiver<ListChanged>>,
+    resource_updates: Option<mpsc::Receiver<String>>,
+    subscribable: bool,
 }
 
 impl McpServerShell {
@@ -45,7 +112,11 @@ impl McpServerShell {
         Self {
             provider,
             middlewares: Vec::new(),
-            initialized: false,
+            resources: Vec::new(),
+            prompts: None,
+            list_changed: None,
+            resource_updates: None,
+            subscribable: false,
         }
     }
 
@@ -54,6 +125,77 @@ impl McpServerShell {
         self.middlewares.push(middleware);
     }
 
+    /// Add a resource provider. `resources/read` is routed by URI scheme,
+    /// so each provider's scheme should be distinct.
+    pub fn add_resource_provider(&mut self, provider: Box<dyn ResourceProvider>) {
+        self.resources.push(provider);
+    }
+
+    /// Set the prompt provider.
+    pub fn set_prompt_provider(&mut self, provider: Box<dyn PromptProvider>) {
+        self.prompts = Some(provider);
+    }
+
+    /// Forward the changes received on `changes` to the client as
+    /// `list_changed` notifications. Changes to a list the server does
+    /// not offer, or that arrive before `initialize`, are dropped.
+    pub fn set_list_changed(&mut self, changes: mpsc::Receiver<ListChanged>) {
+        self.list_changed = Some(changes);
+    }
+
+    /// Take the channel set with [`set_list_changed`](Self::set_list_changed),
+    /// for a transport that delivers notifications itself.
+    pub fn take_list_changed(&mut self) -> Option<mpsc::Receiver<ListChanged>> {
+        self.list_changed.take()
+    }
+
+    /// Offer resource subscriptions, sending the URIs received on `updates`
+    /// to the clients subscribed to them as `notifications/resources/updated`.
+    /// Only takes effect when a resource provider is added.
+    pub fn set_resource_updates(&mut self, updates: mpsc::Receiver<String>) {
+        self.resource_updates = Some(updates);
+        self.subscribable = true;
+    }
+
+    /// Take the channel set with
+    /// [`set_resource_updates`](Self::set_resource_updates), for a transport
+    /// that delivers notifications itself. Subscriptions stay offered.
+    pub fn take_resource_updates(&mut self) -> Option<mpsc::Receiver<String>> {
+        self.resource_updates.take()
+    }
+
+    /// Whether clients may subscribe to resources.
+    fn subscribable(&self) -> bool {
+        self.subscribable && !self.resources.is_empty()
+    }
+
+    /// The `capabilities` object of the `initialize` response.
+    fn capabilities(&self) -> Value {
+        let mut capabilities = serde_json::json!({
+            "tools": { "listChanged": true }
+        });
+        if !self.resources.is_empty() {
+            capabilities["resources"] = serde_json::json!({
+                "subscribe": self.subscribable,
+                "listChanged": false
+            });
+        }
+        if self.prompts.is_some() {
+            capabilities["prompts"] = serde_json::json!({ "listChanged": true });
+        }
+        capabilities
+    }
+
+    /// Whether the client was told it may receive `change`.
+    pub fn advertises(&self, change: ListChanged) -> bool {
+        match change {
+            ListChanged::Tools => true,
+            // Resource lists are not advertised as changing.
+            ListChanged::Resources => false,
+            ListChanged::Prompts => self.prompts.is_some(),
+        }
+    }
+
     /// Run the server loop, reading lines from `reader` and writing
     /// responses to `writer` until EOF.
     pub async fn run<R, W>(&mut self, reader: R, mut writer: W) -> std::io::Result<()>
@@ -62,8 +204,31 @@ impl McpServerShell {
         W: AsyncWrite + Unpin,
     {
         let mut lines = reader.lines();
-
-        while let Some(line) = lines.next_line().await? {
+        let mut list_changed = self.list_changed.take();
+        let mut resource_updates = self.resource_updates.take();
+        // Dropped, with the client's subscriptions, when the client goes.
+        let subscriptions = Subscriptions::default();
+        let mut initialized = false;
+
+        loop {
+            let line = tokio::select! {
+                line = lines.next_line() => match line? {
+                    Some(line) => line,
+                    None => break,
+                },
+                Some(change) = next_change(&mut list_changed) => {
+                    if initialized && self.advertises(change) {
+                        write_response(&mut writer, &change.notification()).await?;
+                    }
+                    continue;
+                }
+                Some(uri) = next_change(&mut resource_updates) => {
+                    if initialized && subscriptions.contains(&uri) {
+                        write_response(&mut writer, &subscriptions::notification(&uri)).await?;
+                    }
+                    continue;
+                }
+            };
             let line = line.trim().to_string();
             if line.is_empty() {
                 continue;
@@ -79,154 +244,372 @@ impl McpServerShell {
                 }
             };
 
-            let method = msg.get("method").and_then(|v| v.as_str()).unwrap_or("");
-            let id = msg.get("id").cloned();
-            let params = msg
-                .get("params")
-                .cloned()
-                .unwrap_or_else(|| Value::Object(Default::default()));
-
-            // Notifications have no id -- never send a response.
-            let is_notification = id.is_none();
-
-            match method {
-                "initialize" => {
-                    self.initialized = true;
-                    let result = serde_json::json!({
-                        "protocolVersion": PROTOCOL_VERSION,
-                        "capabilities": {
-                            "tools": { "listChanged": true }
-                        },
-                        "serverInfo": {
-                            "name": SERVER_NAME,
-                            "version": SERVER_VERSION
+            if msg.get("method").and_then(|v| v.as_str()) == Some("initialize") {
+                initialized = true;
+            }
+
+            // Progress notifications go out while the request is handled.
+            let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+            let reply = {
+                let handling =
+                    self.handle_with(&msg, initialized, Some(&subscriptions), Some(progress_tx));
+                tokio::pin!(handling);
+                loop {
+                    tokio::select! {
+                        reply = &mut handling => break reply,
+                        Some(note) = progress_rx.recv() => {
+                            write_response(&mut writer, &note).await?;
                         }
-                    });
-                    if let Some(id) = id {
-                        let resp = make_success_response(id, result);
-                        write_response(&mut writer, &resp).await?;
                     }
                 }
+            };
+            while let Ok(note) = progress_rx.try_recv() {
+                write_response(&mut writer, &note).await?;
+            }
+            if let Some(resp) = reply {
+                write_response(&mut writer, &resp).await?;
+            }
+        }
 
-                "notifications/initialized" => {
-                    // Notification acknowledgement -- no response.
-                }
+        Ok(())
+    }
 
-                _ if !self.initialized => {
-                    if !is_notification {
-                        let resp = make_error_response(
-                            id.unwrap_or(Value::Null),
-                            NOT_INITIALIZED,
-                            "Server not initialized",
-                        );
-                        write_response(&mut writer, &resp).await?;
-                    }
+    /// Handle one JSON-RPC message, returning the response to send, if
+    /// any. Notifications (no `id`) never get one. Apart from
+    /// `initialize`, requests are refused unless `initialized`.
+    ///
+    /// Transports track the session: stdio has one per process, the HTTP
+    /// transport one per `Mcp-Session-Id`.
+    pub async fn handle(&self, msg: &Value, initialized: bool) -> Option<Value> {
+        self.handle_with(msg, initialized, None, None).await
+    }
+
+    /// Like [`handle`](Self::handle), for a message from the connection
+    /// whose resource subscriptions are `subscriptions`. Without them,
+    /// `resources/subscribe` is not available. The progress of a
+    /// `tools/call` that asked for it is sent to `progress` as
+    /// `notifications/progress` messages while the tool runs.
+    pub async fn handle_with(
+        &self,
+        msg: &Value,
+        initialized: bool,
+        subscriptions: Option<&Subscriptions>,
+        progress: Option<mpsc::UnboundedSender<Value>>,
+    ) -> Option<Value> {
+        let method = msg.get("method").and_then(|v| v.as_str()).unwrap_or("");
+        let id = msg.get("id").cloned();
+        let params = msg
+            .get("params")
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Default::default()));
+
+        let result = match method {
+            "initialize" => Ok(serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": self.capabilities(),
+                "serverInfo": {
+                    "name": SERVER_NAME,
+                    "version": SERVER_VERSION
                 }
+            })),
 
-                "tools/list" => {
-                    let mut tools = self.provider.list_tools_all();
+            // Notification acknowledgement -- no response.
+            "notifications/initialized" => return None,
 
-                    // Apply middleware filter_tools in order.
-                    for mw in &self.middlewares {
-                        tools = mw.filter_tools(tools).await;
-                    }
+            _ if !initialized => Err(ErrorReply::new(NOT_INITIALIZED, "Server not initialized")),
 
-                    let tools_json = serialize_tools(&tools);
-                    let result = serde_json::json!({ "tools": tools_json });
+            "tools/list" => {
+                let mut tools = self.provider.list_tools_all();
 
-                    if let Some(id) = id {
-                        let resp = make_success_response(id, result);
-                        write_response(&mut writer, &resp).await?;
-                    }
+                // Apply middleware filter_tools in order.
+                for mw in &self.middlewares {
+                    tools = mw.filter_tools(tools).await;
                 }
 
-                "tools/call" => {
-                    let name = params
-                        .get("name")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    let args = params
-                        .get("arguments")
-                        .cloned()
-                        .unwrap_or_else(|| Value::Object(Default::default()));
-
-                    let mut request = ToolCallRequest {
-                        name: name.clone(),
-                        args,
-                    };
+                let tools_json = serialize_tools(&tools);
+                Ok(serde_json::json!({ "tools": tools_json }))
+            }
 
-                    // Apply middleware before_call hooks.
-                    let mut mw_error = None;
-                    for mw in &self.middlewares {
-                        match mw.before_call(request).await {
-                            Ok(r) => request = r,
-                            Err(e) => {
-                                mw_error = Some(e);
-                                // Reconstruct a minimal request for the error path.
-                                request = ToolCallRequest {
-                                    name,
-                                    args: Value::Object(Default::default()),
-                                };
-                                break;
-                            }
-                        }
-                    }
+            "tools/call" => Ok(self.call_tool(&params, progress).await),
 
-                    let call_result = if let Some(err) = mw_error {
-                        Err(err)
-                    } else {
-                        self.provider
-                            .call_tool(&request.name, request.args.clone())
-                            .await
+            "resources/list" if !self.resources.is_empty() => {
+                let mut resources = Vec::new();
+                for provider in &self.resources {
+                    resources.extend(provider.list_resources().await);
+                }
+                paginate(resources, &params, "resources")
+            }
+
+            "resources/templates/list" if !self.resources.is_empty() => {
+                let templates: Vec<_> = self.resources.iter().flat_map(|p| p.templates()).collect();
+                paginate(templates, &params, "resourceTemplates")
+            }
+
+            "resources/read" if !self.resources.is_empty() => self.read_resource(&params).await,
+
+            "resources/subscribe" | "resources/unsubscribe" if self.subscribable() => {
+                self.subscribe(&params, subscriptions, method == "resources/subscribe")
+            }
+
+            "prompts/list" if self.prompts.is_some() => {
+                let prompts = match &self.prompts {
+                    Some(provider) => provider.list_prompts().await,
+                    None => Vec::new(),
+                };
+                paginate(prompts, &params, "prompts")
+            }
+
+            "prompts/get" if self.prompts.is_some() => self.get_prompt(&params).await,
+
+            // Unknown method.
+            _ => Err(ErrorReply::new(
+                METHOD_NOT_FOUND,
+                &format!("Method not found: {method}"),
+            )),
+        };
+
+        // Notifications have no id -- never send a response.
+        let id = id?;
+        Some(match result {
+            Ok(result) => make_success_response(id, result),
+            Err(reply) => reply.with_id(id),
+        })
+    }
+
+    /// Handle `tools/call` through the middleware pipeline. Failures are
+    /// reported in the result (`isError`), not as JSON-RPC errors.
+    async fn call_tool(
+        &self,
+        params: &Value,
+        progress: Option<mpsc::UnboundedSender<Value>>,
+    ) -> Value {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| Value::Object(Default::default()));
+
+        let mut request = ToolCallRequest {
+            name: name.clone(),
+            args,
+        };
+
+        // Apply middleware before_call hooks.
+        let mut mw_error = None;
+        for mw in &self.middlewares {
+            match mw.before_call(request).await {
+                Ok(r) => request = r,
+                Err(e) => {
+                    mw_error = Some(e);
+                    // Reconstruct a minimal request for the error path.
+                    request = ToolCallRequest {
+                        name,
+                        args: Value::Object(Default::default()),
                     };
+                    break;
+                }
+            }
+        }
 
-                    let result_value = match call_result {
-                        Ok(mut result) => {
-                            // Apply middleware after_call hooks.
-                            for mw in &self.middlewares {
-                                match mw.after_call(&request, result).await {
-                                    Ok(r) => result = r,
-                                    Err(e) => {
-                                        result = CallToolResult::error(e.to_string());
-                                        break;
-                                    }
-                                }
-                            }
-                            serde_json::to_value(&result).unwrap_or(Value::Null)
-                        }
+        let call_result = if let Some(err) = mw_error {
+            Err(err)
+        } else {
+            let call = self.provider.call_tool(&request.name, request.args.clone());
+            match (progress::token(params), progress) {
+                (Some(token), Some(notifications)) => {
+                    progress::scope(ProgressSender::new(token, notifications), call).await
+                }
+                _ => call.await,
+            }
+        };
+
+        match call_result {
+            Ok(mut result) => {
+                // Apply middleware after_call hooks.
+                for mw in &self.middlewares {
+                    match mw.after_call(&request, result).await {
+                        Ok(r) => result = r,
                         Err(e) => {
-                            let err_result = CallToolResult::error(e.to_string());
-                            serde_json::to_value(&err_result).unwrap_or(Value::Null)
+                            result = CallToolResult::error(e.to_string());
+                            break;
                         }
-                    };
-
-                    if let Some(id) = id {
-                        let resp = make_success_response(id, result_value);
-                        write_response(&mut writer, &resp).await?;
                     }
                 }
+                serde_json::to_value(&result).unwrap_or(Value::Null)
+            }
+            Err(e) => {
+                let err_result = CallToolResult::error(e.to_string());
+                serde_json::to_value(&err_result).unwrap_or(Value::Null)
+            }
+        }
+    }
 
-                _ => {
-                    // Unknown method.
-                    if !is_notification {
-                        let resp = make_error_response(
-                            id.unwrap_or(Value::Null),
-                            METHOD_NOT_FOUND,
-                            &format!("Method not found: {method}"),
-                        );
-                        write_response(&mut writer, &resp).await?;
-                    }
-                }
+    /// Handle `resources/read`: route the URI to the provider of its scheme.
+    async fn read_resource(&self, params: &Value) -> Result<Value, ErrorReply> {
+        let uri = uri_param(params)?;
+        let provider = self
+            .resource_provider(uri)
+            .ok_or_else(|| ErrorReply::resource_not_found(uri))?;
+
+        match provider.read_resource(uri).await {
+            Ok(contents) => Ok(serde_json::json!({ "contents": [contents] })),
+            Err(ResourceError::NotFound(_)) => Err(ErrorReply::resource_not_found(uri)),
+            Err(e @ (ResourceError::TooLarge { .. } | ResourceError::NotText(_))) => {
+                Err(ErrorReply::new(INVALID_PARAMS, &e.to_string()))
+            }
+            Err(e @ ResourceError::Io { .. }) => {
+                Err(ErrorReply::new(INTERNAL_ERROR, &e.to_string()))
             }
         }
+    }
 
-        Ok(())
+    /// Handle `resources/subscribe` (or, unless `subscribe`,
+    /// `resources/unsubscribe`). Any URI of a provided scheme can be
+    /// subscribed to, including one that does not exist yet.
+    fn subscribe(
+        &self,
+        params: &Value,
+        subscriptions: Option<&Subscriptions>,
+        subscribe: bool,
+    ) -> Result<Value, ErrorReply> {
+        let uri = uri_param(params)?;
+        let Some(subscriptions) = subscriptions else {
+            return Err(ErrorReply::new(
+                INVALID_REQUEST,
+                "No connection to subscribe",
+            ));
+        };
+        if subscribe {
+            if self.resource_provider(uri).is_none() {
+                return Err(ErrorReply::resource_not_found(uri));
+            }
+            subscriptions.subscribe(uri);
+        } else {
+            subscriptions.unsubscribe(uri);
+        }
+        Ok(serde_json::json!({}))
+    }
+
+    /// The provider serving `uri`, by its scheme.
+    fn resource_provider(&self, uri: &str) -> Option<&dyn ResourceProvider> {
+        let scheme = uri.split_once("://").map(|(scheme, _)| scheme)?;
+        self.resources
+            .iter()
+            .find(|p| p.scheme() == scheme)
+            .map(|p| p.as_ref())
+    }
+
+    /// Handle `prompts/get`.
+    async fn get_prompt(&self, params: &Value) -> Result<Value, ErrorReply> {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ErrorReply::new(INVALID_PARAMS, "Missing required parameter: name"))?;
+        let arguments: HashMap<String, String> = match params.get("arguments") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(args) => serde_json::from_value(args.clone())
+                .map_err(|_| ErrorReply::new(INVALID_PARAMS, "Prompt arguments must be strings"))?,
+        };
+        let Some(prompts) = &self.prompts else {
+            return Err(ErrorReply::new(
+                METHOD_NOT_FOUND,
+                "Method not found: prompts/get",
+            ));
+        };
+
+        // Both an unknown name and a missing argument are invalid params.
+        prompts
+            .get_prompt(name, &arguments)
+            .await
+            .map(|result| serde_json::to_value(&result).unwrap_or(Value::Null))
+            .map_err(|e| ErrorReply::new(INVALID_PARAMS, &e.to_string()))
+    }
+}
+
+/// The next change, or never when there is no change channel (or it
+/// closed).
+async fn next_change<T>(changes: &mut Option<mpsc::Receiver<T>>) -> Option<T> {
+    match changes {
+        Some(rx) => match rx.recv().await {
+            Some(change) => Some(change),
+            None => {
+                *changes = None;
+                std::future::pending().await
+            }
+        },
+        None => std::future::pending().await,
     }
 }
 
 // ── Helpers ─────────────────────────────────────────────────────────────
 
+/// The `uri` parameter of a resource request.
+fn uri_param(params: &Value) -> Result<&str, ErrorReply> {
+    params
+        .get("uri")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ErrorReply::new(INVALID_PARAMS, "Missing required parameter: uri"))
+}
+
+/// A JSON-RPC error, before the request id is attached.
+struct ErrorReply {
+    code: i32,
+    message: String,
+    data: Option<Value>,
+}
+
+impl ErrorReply {
+    fn new(code: i32, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    fn resource_not_found(uri: &str) -> Self {
+        Self {
+            code: RESOURCE_NOT_FOUND,
+            message: "Resource not found".to_string(),
+            data: Some(serde_json::json!({ "uri": uri })),
+        }
+    }
+
+    fn with_id(self, id: Value) -> Value {
+        let mut resp = make_error_response(id, self.code, &self.message);
+        if let Some(data) = self.data {
+            resp["error"]["data"] = data;
+        }
+        resp
+    }
+}
+
+/// One page of `items` as `{ "<key>": [...], "nextCursor": ... }`, starting
+/// at the request's `cursor`. Cursors are opaque to clients; here they are
+/// the offset of the next page.
+fn paginate<T: Serialize>(items: Vec<T>, params: &Value, key: &str) -> Result<Value, ErrorReply> {
+    let start = match params.get("cursor") {
+        None | Some(Value::Null) => 0,
+        Some(cursor) => cursor
+            .as_str()
+            .and_then(|c| c.parse::<usize>().ok())
+            .filter(|&start| start <= items.len())
+            .ok_or_else(|| ErrorReply::new(INVALID_PARAMS, "Invalid cursor"))?,
+    };
+    let end = (start + PAGE_SIZE).min(items.len());
+    let mut result = serde_json::json!({
+        key: serde_json::to_value(&items[start..end]).unwrap_or_else(|_| Value::Array(vec![]))
+    });
+    if end < items.len() {
+        result["nextCursor"] = Value::String(end.to_string());
+    }
+    Ok(result)
+}
+
 fn make_success_response(id: Value, result: Value) -> Value {
     serde_json::json!({
         "jsonrpc": "2.0",
@@ -726,4 +1109,477 @@ mod tests {
         // 4: Unknown method error.
         assert_eq!(responses[3]["error"]["code"], METHOD_NOT_FOUND);
     }
+
+    // ── Resources and prompts ───────────────────────────────────────────
+
+    use super::super::prompts::{
+        GetPromptResult, Prompt, PromptArgument, PromptError, check_required,
+    };
+    use super::super::resources::DirResourceProvider;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// Prompt provider with one `greet` prompt taking a required `who`.
+    struct GreetPrompts;
+
+    #[async_trait]
+    impl PromptProvider for GreetPrompts {
+        async fn list_prompts(&self) -> Vec<Prompt> {
+            vec![Prompt {
+                name: "greet".into(),
+                description: Some("Greets someone".into()),
+                arguments: vec![PromptArgument {
+                    name: "who".into(),
+                    description: None,
+                    required: true,
+                }],
+            }]
+        }
+
+        async fn get_prompt(
+            &self,
+            name: &str,
+            arguments: &HashMap<String, String>,
+        ) -> Result<GetPromptResult, PromptError> {
+            let prompt = self
+                .list_prompts()
+                .await
+                .into_iter()
+                .find(|p| p.name == name)
+                .ok_or_else(|| PromptError::NotFound(name.to_string()))?;
+            check_required(&prompt, arguments)?;
+            Ok(GetPromptResult::user_text(
+                prompt.description,
+                format!("Say hello to {}.", arguments["who"]),
+            ))
+        }
+    }
+
+    fn temp_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("clawft-mcp-server-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn make_full_server(dir: &std::path::Path) -> McpServerShell {
+        let mut server = make_server();
+        server.add_resource_provider(Box::new(
+            DirResourceProvider::new("memory", dir, "Memory file").with_max_bytes(64),
+        ));
+        server.set_prompt_provider(Box::new(GreetPrompts));
+        server
+    }
+
+    async fn run_requests(server: &mut McpServerShell, requests: &[(&str, Value)]) -> Vec<Value> {
+        let mut input = init_line(0);
+        for (i, (method, params)) in requests.iter().enumerate() {
+            input.push_str(&request_line(i as u64 + 1, method, params.clone()));
+        }
+        let mut output = Vec::new();
+        server
+            .run(Cursor::new(input.into_bytes()), &mut output)
+            .await
+            .unwrap();
+        parse_responses(&output)
+    }
+
+    #[tokio::test]
+    async fn initialize_advertises_configured_capabilities() {
+        let responses = run_requests(&mut make_server(), &[]).await;
+        let caps = &responses[0]["result"]["capabilities"];
+        assert!(caps.get("resources").is_none());
+        assert!(caps.get("prompts").is_none());
+
+        let dir = temp_dir();
+        let responses = run_requests(&mut make_full_server(&dir), &[]).await;
+        let caps = &responses[0]["result"]["capabilities"];
+        assert_eq!(caps["tools"]["listChanged"], true);
+        assert_eq!(caps["resources"]["listChanged"], false);
+        assert_eq!(caps["resources"]["subscribe"], false);
+        assert_eq!(caps["prompts"]["listChanged"], true);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn resources_list_and_read() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("MEMORY.md"), "# Facts\n").unwrap();
+        let responses = run_requests(
+            &mut make_full_server(&dir),
+            &[
+                ("resources/list", json!({})),
+                ("resources/read", json!({ "uri": "memory://MEMORY.md" })),
+                ("resources/templates/list", json!({})),
+            ],
+        )
+        .await;
+
+        let list = &responses[1]["result"];
+        assert!(list.get("nextCursor").is_none());
+        assert_eq!(
+            list["resources"],
+            json!([{
+                "uri": "memory://MEMORY.md",
+                "name": "MEMORY.md",
+                "description": "Memory file",
+                "mimeType": "text/markdown",
+                "size": 8
+            }])
+        );
+        assert_eq!(
+            responses[2]["result"],
+            json!({ "contents": [{
+                "uri": "memory://MEMORY.md",
+                "mimeType": "text/markdown",
+                "text": "# Facts\n"
+            }] })
+        );
+        assert_eq!(responses[3]["result"], json!({ "resourceTemplates": [] }));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn resources_read_errors() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("big.md"), "x".repeat(65)).unwrap();
+        let responses = run_requests(
+            &mut make_full_server(&dir),
+            &[
+                ("resources/read", json!({ "uri": "memory://missing.md" })),
+                ("resources/read", json!({ "uri": "file:///etc/passwd" })),
+                ("resources/read", json!({})),
+                ("resources/read", json!({ "uri": "memory://big.md" })),
+            ],
+        )
+        .await;
+
+        assert_eq!(responses[1]["error"]["code"], RESOURCE_NOT_FOUND);
+        assert_eq!(responses[1]["error"]["data"]["uri"], "memory://missing.md");
+        assert_eq!(responses[2]["error"]["code"], RESOURCE_NOT_FOUND);
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[4]["error"]["code"], INVALID_PARAMS);
+        assert!(
+            responses[4]["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("limit")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn resources_list_paginates() {
+        let dir = temp_dir();
+        for i in 0..PAGE_SIZE + 1 {
+            std::fs::write(dir.join(format!("note-{i:03}.md")), "n").unwrap();
+        }
+        let responses = run_requests(
+            &mut make_full_server(&dir),
+            &[
+                ("resources/list", json!({})),
+                ("resources/list", json!({ "cursor": PAGE_SIZE.to_string() })),
+                ("resources/list", json!({ "cursor": "bogus" })),
+            ],
+        )
+        .await;
+
+        let first = &responses[1]["result"];
+        assert_eq!(first["resources"].as_array().unwrap().len(), PAGE_SIZE);
+        assert_eq!(first["nextCursor"], PAGE_SIZE.to_string());
+        let second = &responses[2]["result"];
+        assert_eq!(second["resources"].as_array().unwrap().len(), 1);
+        assert!(second.get("nextCursor").is_none());
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn prompts_list_and_get() {
+        let dir = temp_dir();
+        let responses = run_requests(
+            &mut make_full_server(&dir),
+            &[
+                ("prompts/list", json!({})),
+                (
+                    "prompts/get",
+                    json!({ "name": "greet", "arguments": { "who": "Ada" } }),
+                ),
+                ("prompts/get", json!({ "name": "greet" })),
+                ("prompts/get", json!({ "name": "nope" })),
+                (
+                    "prompts/get",
+                    json!({ "name": "greet", "arguments": { "who": 1 } }),
+                ),
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            responses[1]["result"],
+            json!({ "prompts": [{
+                "name": "greet",
+                "description": "Greets someone",
+                "arguments": [{ "name": "who", "required": true }]
+            }] })
+        );
+        assert_eq!(
+            responses[2]["result"],
+            json!({
+                "description": "Greets someone",
+                "messages": [{
+                    "role": "user",
+                    "content": { "type": "text", "text": "Say hello to Ada." }
+                }]
+            })
+        );
+        for resp in &responses[3..] {
+            assert_eq!(resp["error"]["code"], INVALID_PARAMS, "{resp}");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn resources_and_prompts_need_providers() {
+        let responses = run_requests(
+            &mut make_server(),
+            &[
+                ("resources/list", json!({})),
+                ("resources/read", json!({ "uri": "memory://MEMORY.md" })),
+                ("prompts/list", json!({})),
+                ("prompts/get", json!({ "name": "greet" })),
+            ],
+        )
+        .await;
+        for resp in &responses[1..] {
+            assert_eq!(resp["error"]["code"], METHOD_NOT_FOUND, "{resp}");
+        }
+    }
+
+    #[tokio::test]
+    async fn list_changes_are_notified_after_initialize() {
+        let dir = temp_dir();
+        let mut server = make_full_server(&dir);
+        let (tx, rx) = mpsc::channel(4);
+        server.set_list_changed(rx);
+
+        let (mut client, server_io) = tokio::io::duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let task = tokio::spawn(async move {
+            server
+                .run(BufReader::new(server_read), server_write)
+                .await
+                .unwrap();
+        });
+
+        client.write_all(init_line(1).as_bytes()).await.unwrap();
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_read).lines();
+        let init: Value = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(init["id"], 1);
+
+        // Resource list changes are not advertised, so not sent.
+        tx.send(ListChanged::Resources).await.unwrap();
+        tx.send(ListChanged::Tools).await.unwrap();
+        tx.send(ListChanged::Prompts).await.unwrap();
+        for method in [
+            "notifications/tools/list_changed",
+            "notifications/prompts/list_changed",
+        ] {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let notification: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(notification, json!({ "jsonrpc": "2.0", "method": method }));
+        }
+
+        client_write.shutdown().await.unwrap();
+        drop(client_write);
+        task.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn subscribed_resources_send_updates() {
+        let dir = temp_dir();
+        let mut server = make_full_server(&dir);
+        let (tx, rx) = mpsc::channel(4);
+        server.set_resource_updates(rx);
+
+        let (client, server_io) = tokio::io::duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        let task = tokio::spawn(async move {
+            server
+                .run(BufReader::new(server_read), server_write)
+                .await
+                .unwrap();
+        });
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_read).lines();
+        let mut next = async || -> Value {
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+        };
+
+        client_write
+            .write_all(init_line(1).as_bytes())
+            .await
+            .unwrap();
+        let init = next().await;
+        assert_eq!(
+            init["result"]["capabilities"]["resources"]["subscribe"],
+            true
+        );
+
+        let subscribe = json!({ "uri": "memory://MEMORY.md" });
+        client_write
+            .write_all(request_line(2, "resources/subscribe", subscribe.clone()).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(next().await["result"], json!({}));
+
+        // Only the subscribed resource is notified.
+        tx.send("memory://other.md".into()).await.unwrap();
+        tx.send("memory://MEMORY.md".into()).await.unwrap();
+        assert_eq!(
+            next().await,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": "memory://MEMORY.md" }
+            })
+        );
+
+        client_write
+            .write_all(request_line(3, "resources/unsubscribe", subscribe).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(next().await["id"], 3);
+        tx.send("memory://MEMORY.md".into()).await.unwrap();
+        client_write
+            .write_all(
+                request_line(
+                    4,
+                    "resources/subscribe",
+                    json!({ "uri": "file:///etc/passwd" }),
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let refused = next().await;
+        assert_eq!(refused["id"], 4);
+        assert_eq!(refused["error"]["code"], RESOURCE_NOT_FOUND);
+
+        client_write.shutdown().await.unwrap();
+        drop(client_write);
+        task.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn subscriptions_need_resource_updates() {
+        let dir = temp_dir();
+        let responses = run_requests(
+            &mut make_full_server(&dir),
+            &[(
+                "resources/subscribe",
+                json!({ "uri": "memory://MEMORY.md" }),
+            )],
+        )
+        .await;
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // ── Progress ────────────────────────────────────────────────────────
+
+    /// Runs in three slow steps, reporting each.
+    struct SlowProvider;
+
+    #[async_trait]
+    impl ToolProvider for SlowProvider {
+        fn namespace(&self) -> &str {
+            "slow"
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![ToolDefinition {
+                name: "build".to_string(),
+                description: "Takes a while".to_string(),
+                input_schema: json!({ "type": "object" }),
+            }]
+        }
+
+        async fn call_tool(&self, _name: &str, _args: Value) -> Result<CallToolResult, ToolError> {
+            let progress = progress::current();
+            for step in 1..=3 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                if let Some(progress) = &progress {
+                    progress.report(progress::Progress {
+                        progress: f64::from(step),
+                        total: Some(3.0),
+                        message: Some(format!("step {step}")),
+                    });
+                }
+            }
+            Ok(CallToolResult::text("built"))
+        }
+    }
+
+    fn slow_server() -> McpServerShell {
+        let mut provider = CompositeToolProvider::new();
+        provider.register(Box::new(SlowProvider));
+        McpServerShell::new(provider)
+    }
+
+    #[tokio::test]
+    async fn tools_call_sends_progress_before_the_result() {
+        let mut server = slow_server();
+        let mut input = init_line(1);
+        input.push_str(&request_line(
+            2,
+            "tools/call",
+            json!({
+                "name": "slow__build",
+                "arguments": {},
+                "_meta": { "progressToken": "build-1" }
+            }),
+        ));
+
+        let mut output = Vec::new();
+        server
+            .run(Cursor::new(input.into_bytes()), &mut output)
+            .await
+            .unwrap();
+
+        let responses = parse_responses(&output);
+        assert_eq!(responses.len(), 5, "{responses:#?}");
+        for (i, note) in responses[1..4].iter().enumerate() {
+            assert_eq!(note["method"], "notifications/progress");
+            assert!(note.get("id").is_none());
+            assert_eq!(note["params"]["progressToken"], "build-1");
+            assert_eq!(note["params"]["progress"], (i + 1) as f64);
+            assert_eq!(note["params"]["total"], 3.0);
+            assert_eq!(note["params"]["message"], format!("step {}", i + 1));
+        }
+        assert_eq!(responses[4]["id"], 2);
+        assert_eq!(responses[4]["result"]["content"][0]["text"], "built");
+    }
+
+    #[tokio::test]
+    async fn tools_call_without_token_sends_no_progress() {
+        let mut server = slow_server();
+        let mut input = init_line(1);
+        input.push_str(&request_line(
+            2,
+            "tools/call",
+            json!({ "name": "slow__build", "arguments": {} }),
+        ));
+
+        let mut output = Vec::new();
+        server
+            .run(Cursor::new(input.into_bytes()), &mut output)
+            .await
+            .unwrap();
+
+        let responses = parse_responses(&output);
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1]["result"]["content"][0]["text"], "built");
+    }
 }
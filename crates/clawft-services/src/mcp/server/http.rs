@@ -0,0 +1,892 @@
+//! Streamable HTTP transport for [`McpServerShell`].
+//!
+//! Serves the MCP Streamable HTTP transport on one endpoint ([`MCP_PATH`]),
+//! so remote clients, and several clients at once, can connect:
+//!
+//! - `POST` carries one JSON-RPC message. A request is answered with a
+//!   single-event `text/event-stream` when the client accepts one, else
+//!   with `application/json`; notifications and responses get
+//!   `202 Accepted`. A `tools/call` with a `progressToken` streams its
+//!   `notifications/progress` ahead of the result when the client accepts
+//!   an event stream.
+//! - `initialize` starts a session. Its response carries an
+//!   `Mcp-Session-Id` header, which the client sends with every later
+//!   request. A missing id is `400`; an unknown (or ended) one is `404`,
+//!   telling the client to initialize again.
+//! - `GET` opens an event stream of server notifications: `list_changed`,
+//!   and `resources/updated` for the session's resource subscriptions.
+//! - `DELETE` ends the session, dropping its subscriptions.
+//!
+//! Requests whose `Origin` is neither a localhost origin nor configured are
+//! refused with `403`, which guards loopback servers against DNS
+//! rebinding. When a token is configured, every request must carry it as
+//! `Authorization: Bearer <token>`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info};
+
+use super::{INVALID_REQUEST, ListChanged, McpServerShell, PROTOCOL_VERSION, make_error_response};
+use crate::mcp::progress;
+use crate::mcp::subscriptions::{self, Subscriptions};
+
+/// Path of the MCP endpoint.
+pub const MCP_PATH: &str = "/mcp";
+
+/// Header carrying the session id.
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Header carrying the negotiated protocol version.
+pub const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// Protocol versions accepted in [`PROTOCOL_VERSION_HEADER`].
+const SUPPORTED_VERSIONS: &[&str] = &[PROTOCOL_VERSION, "2025-03-26"];
+
+/// Server notifications buffered per `GET` stream before the oldest are
+/// dropped.
+const NOTIFICATION_CAPACITY: usize = 16;
+
+/// Access control for the HTTP transport.
+#[derive(Debug, Clone, Default)]
+pub struct HttpOptions {
+    /// Bearer token every request must carry; `None` disables the check.
+    pub auth_token: Option<String>,
+    /// Origins allowed besides localhost ones.
+    pub allowed_origins: Vec<String>,
+}
+
+struct HttpState {
+    shell: McpServerShell,
+    options: HttpOptions,
+    /// Live sessions and their resource subscriptions.
+    sessions: Mutex<HashMap<String, Arc<Subscriptions>>>,
+    notifications: broadcast::Sender<ListChanged>,
+    resource_updates: broadcast::Sender<String>,
+}
+
+impl HttpState {
+    fn session(&self, id: &str) -> Option<Arc<Subscriptions>> {
+        self.sessions
+            .lock()
+            .expect("MCP session lock poisoned")
+            .get(id)
+            .cloned()
+    }
+}
+
+/// Build the router serving `shell` at [`MCP_PATH`].
+///
+/// List changes set on the shell with
+/// [`McpServerShell::set_list_changed`] are sent to every open `GET`
+/// stream, resource updates set with
+/// [`McpServerShell::set_resource_updates`] to the streams of the sessions
+/// subscribed to them. Must be called within a Tokio runtime.
+pub fn router(mut shell: McpServerShell, options: HttpOptions) -> Router {
+    let notifications = fan_out(shell.take_list_changed());
+    let resource_updates = fan_out(shell.take_resource_updates());
+
+    let state = Arc::new(HttpState {
+        shell,
+        options,
+        sessions: Mutex::new(HashMap::new()),
+        notifications,
+        resource_updates,
+    });
+    Router::new()
+        .route(
+            MCP_PATH,
+            post(handle_post).get(handle_get).delete(handle_delete),
+        )
+        .with_state(state)
+}
+
+/// Broadcast what arrives on `changes` to every open `GET` stream.
+fn fan_out<T: Clone + Send + 'static>(changes: Option<mpsc::Receiver<T>>) -> broadcast::Sender<T> {
+    let (sender, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+    if let Some(mut changes) = changes {
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            while let Some(change) = changes.recv().await {
+                // No open streams is not an error.
+                let _ = sender.send(change);
+            }
+        });
+    }
+    sender
+}
+
+/// Serve `shell` on `listener` until the server fails.
+pub async fn serve(
+    listener: TcpListener,
+    shell: McpServerShell,
+    options: HttpOptions,
+) -> std::io::Result<()> {
+    if let Ok(addr) = listener.local_addr() {
+        info!(%addr, path = MCP_PATH, "MCP server listening for HTTP clients");
+    }
+    axum::serve(listener, router(shell, options)).await
+}
+
+// ── Handlers ───────────────────────────────────────────────────────────
+
+async fn handle_post(
+    State(state): State<Arc<HttpState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(resp) = check_access(&state.options, &headers) {
+        return resp;
+    }
+
+    let msg: Value = match serde_json::from_slice(&body) {
+        Ok(msg @ Value::Object(_)) => msg,
+        Ok(_) => {
+            return rpc_error(
+                StatusCode::BAD_REQUEST,
+                "Expected a single JSON-RPC message",
+            );
+        }
+        Err(_) => return rpc_error(StatusCode::BAD_REQUEST, "Parse error"),
+    };
+
+    let is_initialize =
+        msg.get("method").and_then(|v| v.as_str()) == Some("initialize") && msg.get("id").is_some();
+    let (new_session, subscriptions) = if is_initialize {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let subscriptions = Arc::new(Subscriptions::default());
+        state
+            .sessions
+            .lock()
+            .expect("MCP session lock poisoned")
+            .insert(id.clone(), subscriptions.clone());
+        debug!(session = %id, "MCP HTTP session started");
+        (Some(id), subscriptions)
+    } else {
+        match require_session(&state, &headers) {
+            Ok((_, subscriptions)) => (None, subscriptions),
+            Err(resp) => return resp,
+        }
+    };
+
+    let wants_progress = msg.get("method").and_then(|v| v.as_str()) == Some("tools/call")
+        && msg.get("id").is_some()
+        && msg.get("params").and_then(progress::token).is_some();
+    if wants_progress && accepts(&headers, "text/event-stream") {
+        return progress_stream(state, msg, subscriptions);
+    }
+
+    // A session exists only once `initialize` was answered.
+    let reply = state
+        .shell
+        .handle_with(&msg, true, Some(&subscriptions), None)
+        .await;
+    let Some(reply) = reply else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let mut response = if accepts(&headers, "text/event-stream") {
+        let event = Event::default().data(reply.to_string());
+        Sse::new(futures_util::stream::once(async move {
+            Ok::<_, Infallible>(event)
+        }))
+        .into_response()
+    } else {
+        axum::Json(reply).into_response()
+    };
+    if let Some(id) = new_session
+        && let Ok(value) = HeaderValue::from_str(&id)
+    {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+/// Answer `msg` with an event stream of its progress notifications,
+/// ending with the response.
+fn progress_stream(
+    state: Arc<HttpState>,
+    msg: Value,
+    subscriptions: Arc<Subscriptions>,
+) -> Response {
+    let (events, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let reply = state
+            .shell
+            .handle_with(&msg, true, Some(&subscriptions), Some(events.clone()))
+            .await;
+        if let Some(reply) = reply {
+            let _ = events.send(reply);
+        }
+    });
+
+    // Notifications have no id; the stream ends after the response.
+    let stream = futures_util::stream::unfold(Some(receiver), |receiver| async move {
+        let mut receiver = receiver?;
+        let msg = receiver.recv().await?;
+        let done = msg.get("id").is_some();
+        let event = Event::default().data(msg.to_string());
+        Some((Ok::<_, Infallible>(event), (!done).then_some(receiver)))
+    });
+    Sse::new(stream).into_response()
+}
+
+async fn handle_get(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if let Err(resp) = check_access(&state.options, &headers) {
+        return resp;
+    }
+    if !accepts(&headers, "text/event-stream") {
+        return rpc_error(
+            StatusCode::NOT_ACCEPTABLE,
+            "GET requires Accept: text/event-stream",
+        );
+    }
+    let subscriptions = match require_session(&state, &headers) {
+        Ok((_, subscriptions)) => subscriptions,
+        Err(resp) => return resp,
+    };
+
+    let listener = Listener {
+        changes: state.notifications.subscribe(),
+        updates: state.resource_updates.subscribe(),
+        subscriptions,
+        state,
+    };
+    let stream = futures_util::stream::unfold(listener, |mut listener| async move {
+        let notification = listener.next().await?;
+        let event = Event::default().data(notification.to_string());
+        Some((Ok::<_, Infallible>(event), listener))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// What one `GET` stream listens to.
+struct Listener {
+    changes: broadcast::Receiver<ListChanged>,
+    updates: broadcast::Receiver<String>,
+    subscriptions: Arc<Subscriptions>,
+    state: Arc<HttpState>,
+}
+
+impl Listener {
+    /// The next notification for the session, or `None` once the server
+    /// has shut down.
+    async fn next(&mut self) -> Option<Value> {
+        use broadcast::error::RecvError;
+
+        loop {
+            tokio::select! {
+                change = self.changes.recv() => match change {
+                    Ok(change) if self.state.shell.advertises(change) => {
+                        return Some(change.notification());
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                },
+                uri = self.updates.recv() => match uri {
+                    Ok(uri) if self.subscriptions.contains(&uri) => {
+                        return Some(subscriptions::notification(&uri));
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                },
+            }
+        }
+    }
+}
+
+async fn handle_delete(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if let Err(resp) = check_access(&state.options, &headers) {
+        return resp;
+    }
+    let (id, subscriptions) = match require_session(&state, &headers) {
+        Ok(session) => session,
+        Err(resp) => return resp,
+    };
+    state
+        .sessions
+        .lock()
+        .expect("MCP session lock poisoned")
+        .remove(&id);
+    // A `GET` stream still open for the session hears no more updates.
+    subscriptions.clear();
+    debug!(session = %id, "MCP HTTP session ended");
+    StatusCode::OK.into_response()
+}
+
+// ── Checks ─────────────────────────────────────────────────────────────
+
+/// Origin, bearer token and protocol version checks shared by all methods.
+#[allow(clippy::result_large_err)] // the error is the response to send
+fn check_access(options: &HttpOptions, headers: &HeaderMap) -> Result<(), Response> {
+    if let Some(origin) = header_str(headers, header::ORIGIN.as_str())
+        && !origin_allowed(origin, &options.allowed_origins)
+    {
+        debug!(origin, "refused MCP HTTP request from disallowed origin");
+        return Err(rpc_error(StatusCode::FORBIDDEN, "Origin not allowed"));
+    }
+
+    if let Some(token) = &options.auth_token {
+        let given = header_str(headers, header::AUTHORIZATION.as_str())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or("");
+        if !constant_time_eq(given.as_bytes(), token.as_bytes()) {
+            let mut resp = rpc_error(StatusCode::UNAUTHORIZED, "Unauthorized");
+            resp.headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            return Err(resp);
+        }
+    }
+
+    if let Some(version) = header_str(headers, PROTOCOL_VERSION_HEADER)
+        && !SUPPORTED_VERSIONS.contains(&version)
+    {
+        return Err(rpc_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Unsupported protocol version: {version}"),
+        ));
+    }
+    Ok(())
+}
+
+/// The request's session id, which must be a live session, and the
+/// session's subscriptions.
+#[allow(clippy::result_large_err)] // the error is the response to send
+fn require_session(
+    state: &HttpState,
+    headers: &HeaderMap,
+) -> Result<(String, Arc<Subscriptions>), Response> {
+    let Some(id) = header_str(headers, SESSION_HEADER) else {
+        return Err(rpc_error(
+            StatusCode::BAD_REQUEST,
+            "Missing Mcp-Session-Id header",
+        ));
+    };
+    match state.session(id) {
+        Some(subscriptions) => Ok((id.to_string(), subscriptions)),
+        None => Err(rpc_error(StatusCode::NOT_FOUND, "Session not found")),
+    }
+}
+
+/// Whether `origin` is a localhost origin or one of `allowed`.
+fn origin_allowed(origin: &str, allowed: &[String]) -> bool {
+    if allowed.iter().any(|a| a.eq_ignore_ascii_case(origin)) {
+        return true;
+    }
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let authority = authority.split('/').next().unwrap_or("");
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => authority.split(':').next().unwrap_or(""),
+    };
+    matches!(
+        host.to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "::1"
+    )
+}
+
+fn accepts(headers: &HeaderMap, mime: &str) -> bool {
+    header_str(headers, header::ACCEPT.as_str()).is_some_and(|accept| {
+        accept
+            .split(',')
+            .any(|part| part.split(';').next().unwrap_or("").trim() == mime)
+    })
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// A transport-level failure as an HTTP status with a JSON-RPC error body.
+fn rpc_error(status: StatusCode, message: &str) -> Response {
+    let body = make_error_response(Value::Null, INVALID_REQUEST, message);
+    (status, axum::Json(body)).into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::ToolDefinition;
+    use crate::mcp::composite::CompositeToolProvider;
+    use crate::mcp::provider::{CallToolResult, ToolError, ToolProvider};
+    use crate::mcp::resources::{Resource, ResourceContents, ResourceError, ResourceProvider};
+    use crate::mcp::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl ToolProvider for EchoProvider {
+        fn namespace(&self) -> &str {
+            "echo"
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![ToolDefinition {
+                name: "say".to_string(),
+                description: "Echoes text".to_string(),
+                input_schema: json!({ "type": "object" }),
+            }]
+        }
+
+        async fn call_tool(&self, _name: &str, args: Value) -> Result<CallToolResult, ToolError> {
+            if let Some(progress) = progress::current() {
+                for step in [1.0, 2.0] {
+                    progress.report(progress::Progress {
+                        progress: step,
+                        total: Some(2.0),
+                        message: None,
+                    });
+                }
+            }
+            Ok(CallToolResult::text(args["text"].as_str().unwrap_or("")))
+        }
+    }
+
+    fn shell() -> McpServerShell {
+        let mut provider = CompositeToolProvider::new();
+        provider.register(Box::new(EchoProvider));
+        McpServerShell::new(provider)
+    }
+
+    /// Start a server on an ephemeral loopback port; returns the endpoint.
+    async fn start(shell: McpServerShell, options: HttpOptions) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, shell, options));
+        format!("http://{addr}{MCP_PATH}")
+    }
+
+    fn init_request() -> JsonRpcRequest {
+        JsonRpcRequest::new(
+            1,
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0.1" }
+            }),
+        )
+    }
+
+    /// Initialize and return the session id.
+    async fn initialize(client: &reqwest::Client, url: &str) -> String {
+        let resp = client.post(url).json(&init_request()).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let session = resp.headers()[SESSION_HEADER].to_str().unwrap().to_string();
+        let body: JsonRpcResponse = resp.json().await.unwrap();
+        assert_eq!(body.id, 1);
+        assert_eq!(body.result.unwrap()["protocolVersion"], PROTOCOL_VERSION);
+        session
+    }
+
+    #[tokio::test]
+    async fn session_lifecycle() {
+        let url = start(shell(), HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+
+        // The initialized notification is accepted without a body.
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&JsonRpcNotification::new(
+                "notifications/initialized",
+                json!({}),
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        assert!(resp.bytes().await.unwrap().is_empty());
+
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .header(PROTOCOL_VERSION_HEADER, PROTOCOL_VERSION)
+            .json(&JsonRpcRequest::new(
+                2,
+                "tools/call",
+                json!({ "name": "echo__say", "arguments": { "text": "over http" } }),
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: JsonRpcResponse = resp.json().await.unwrap();
+        assert_eq!(body.id, 2);
+        assert_eq!(body.result.unwrap()["content"][0]["text"], "over http");
+
+        // Ending the session makes its id unknown.
+        let resp = client
+            .delete(&url)
+            .header(SESSION_HEADER, &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&JsonRpcRequest::new(3, "tools/list", json!({})))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn requests_need_a_session() {
+        let url = start(shell(), HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let list = JsonRpcRequest::new(2, "tools/list", json!({}));
+
+        let resp = client.post(&url).json(&list).send().await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, "bogus")
+            .json(&list)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // Sessions are independent.
+        let a = initialize(&client, &url).await;
+        let b = initialize(&client, &url).await;
+        assert_ne!(a, b);
+        for session in [a, b] {
+            let resp = client
+                .post(&url)
+                .header(SESSION_HEADER, session)
+                .json(&list)
+                .send()
+                .await
+                .unwrap();
+            let body: JsonRpcResponse = resp.json().await.unwrap();
+            assert_eq!(body.result.unwrap()["tools"][0]["name"], "echo__say");
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_stream_as_sse_when_accepted() {
+        let url = start(shell(), HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .header(header::ACCEPT, "application/json, text/event-stream")
+            .json(&JsonRpcRequest::new(7, "tools/list", json!({})))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(
+            resp.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("text/event-stream")
+        );
+        let text = resp.text().await.unwrap();
+        let data = text
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .expect("an SSE data line");
+        let body: JsonRpcResponse = serde_json::from_str(data).unwrap();
+        assert_eq!(body.id, 7);
+        assert!(body.result.unwrap()["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn progress_streams_ahead_of_the_result() {
+        let url = start(shell(), HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .header(header::ACCEPT, "application/json, text/event-stream")
+            .json(&JsonRpcRequest::new(
+                8,
+                "tools/call",
+                json!({
+                    "name": "echo__say",
+                    "arguments": { "text": "done" },
+                    "_meta": { "progressToken": 42 }
+                }),
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let text = resp.text().await.unwrap();
+        let messages: Vec<Value> = text
+            .lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(messages.len(), 3, "{text}");
+        assert_eq!(messages[0]["method"], "notifications/progress");
+        assert_eq!(messages[0]["params"]["progressToken"], 42);
+        assert_eq!(messages[0]["params"]["progress"], 1.0);
+        assert_eq!(messages[1]["params"]["progress"], 2.0);
+        assert_eq!(messages[2]["id"], 8);
+        assert_eq!(messages[2]["result"]["content"][0]["text"], "done");
+
+        // Without an event stream the client gets the result alone.
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&JsonRpcRequest::new(
+                9,
+                "tools/call",
+                json!({
+                    "name": "echo__say",
+                    "arguments": { "text": "plain" },
+                    "_meta": { "progressToken": 43 }
+                }),
+            ))
+            .send()
+            .await
+            .unwrap();
+        let body: JsonRpcResponse = resp.json().await.unwrap();
+        assert_eq!(body.result.unwrap()["content"][0]["text"], "plain");
+    }
+
+    #[tokio::test]
+    async fn malformed_bodies_are_rejected() {
+        let url = start(shell(), HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        for body in ["not json", "[]"] {
+            let resp = client
+                .post(&url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{body}");
+            let err: Value = resp.json().await.unwrap();
+            assert_eq!(err["error"]["code"], INVALID_REQUEST);
+        }
+
+        let resp = client
+            .post(&url)
+            .header(PROTOCOL_VERSION_HEADER, "1999-01-01")
+            .json(&init_request())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn origins_are_checked() {
+        let options = HttpOptions {
+            allowed_origins: vec!["https://app.example.com".into()],
+            ..Default::default()
+        };
+        let url = start(shell(), options).await;
+        let client = reqwest::Client::new();
+        for (origin, status) in [
+            ("http://localhost:5173", StatusCode::OK),
+            ("http://127.0.0.1", StatusCode::OK),
+            ("http://[::1]:8080", StatusCode::OK),
+            ("https://app.example.com", StatusCode::OK),
+            ("https://evil.example.com", StatusCode::FORBIDDEN),
+            ("http://localhost.evil.example.com", StatusCode::FORBIDDEN),
+            ("null", StatusCode::FORBIDDEN),
+        ] {
+            let resp = client
+                .post(&url)
+                .header(header::ORIGIN, origin)
+                .json(&init_request())
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), status, "{origin}");
+        }
+    }
+
+    #[tokio::test]
+    async fn bearer_token_is_required_when_configured() {
+        let options = HttpOptions {
+            auth_token: Some("s3cret".into()),
+            ..Default::default()
+        };
+        let url = start(shell(), options).await;
+        let client = reqwest::Client::new();
+
+        let resp = client
+            .post(&url)
+            .json(&init_request())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let resp = client
+            .post(&url)
+            .bearer_auth("wrong")
+            .json(&init_request())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = client
+            .post(&url)
+            .bearer_auth("s3cret")
+            .json(&init_request())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_streams_list_changed_notifications() {
+        let mut shell = shell();
+        let (tx, rx) = mpsc::channel(4);
+        shell.set_list_changed(rx);
+        let url = start(shell, HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+
+        let resp = client
+            .get(&url)
+            .header(SESSION_HEADER, &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+
+        let mut resp = client
+            .get(&url)
+            .header(SESSION_HEADER, &session)
+            .header(header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Prompts are not offered by this server, so only tools is sent.
+        tx.send(ListChanged::Prompts).await.unwrap();
+        tx.send(ListChanged::Tools).await.unwrap();
+        let mut text = String::new();
+        while !text.contains("\n\n") {
+            let chunk = resp.chunk().await.unwrap().expect("stream open");
+            text.push_str(&String::from_utf8_lossy(&chunk));
+        }
+        let data = text
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .expect("an SSE data line");
+        let notification: JsonRpcNotification = serde_json::from_str(data).unwrap();
+        assert_eq!(notification.method, "notifications/tools/list_changed");
+    }
+
+    /// Serves no resources, but owns the `note` scheme.
+    struct NoteResources;
+
+    #[async_trait]
+    impl ResourceProvider for NoteResources {
+        fn scheme(&self) -> &str {
+            "note"
+        }
+
+        async fn list_resources(&self) -> Vec<Resource> {
+            Vec::new()
+        }
+
+        async fn read_resource(&self, uri: &str) -> Result<ResourceContents, ResourceError> {
+            Err(ResourceError::NotFound(uri.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn get_streams_updates_of_subscribed_resources() {
+        let mut shell = shell();
+        shell.add_resource_provider(Box::new(NoteResources));
+        let (tx, rx) = mpsc::channel(4);
+        shell.set_resource_updates(rx);
+        let url = start(shell, HttpOptions::default()).await;
+        let client = reqwest::Client::new();
+        let session = initialize(&client, &url).await;
+
+        let resp = client
+            .post(&url)
+            .header(SESSION_HEADER, &session)
+            .json(&JsonRpcRequest::new(
+                2,
+                "resources/subscribe",
+                json!({ "uri": "note://todo" }),
+            ))
+            .send()
+            .await
+            .unwrap();
+        let body: JsonRpcResponse = resp.json().await.unwrap();
+        assert_eq!(body.result, Some(json!({})));
+
+        let mut resp = client
+            .get(&url)
+            .header(SESSION_HEADER, &session)
+            .header(header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        tx.send("note://done".into()).await.unwrap();
+        tx.send("note://todo".into()).await.unwrap();
+        let mut text = String::new();
+        while !text.contains("\n\n") {
+            let chunk = resp.chunk().await.unwrap().expect("stream open");
+            text.push_str(&String::from_utf8_lossy(&chunk));
+        }
+        let data = text
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .expect("an SSE data line");
+        let notification: JsonRpcNotification = serde_json::from_str(data).unwrap();
+        assert_eq!(notification.method, "notifications/resources/updated");
+        assert_eq!(notification.params, json!({ "uri": "note://todo" }));
+    }
+
+    #[test]
+    fn origin_matching() {
+        assert!(origin_allowed("http://LOCALHOST:3000", &[]));
+        assert!(origin_allowed("https://127.0.0.1/path", &[]));
+        assert!(!origin_allowed("localhost", &[]));
+        assert!(!origin_allowed("http://127.0.0.1.nip.io", &[]));
+    }
+}
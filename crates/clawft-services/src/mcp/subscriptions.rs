@@ -0,0 +1,94 @@
+//! Resource subscriptions.
+//!
+//! A client that subscribes to a resource (`resources/subscribe`) is sent
+//! `notifications/resources/updated` each time the resource changes, until
+//! it unsubscribes or disconnects. Subscriptions belong to a connection:
+//! the stdio loop keeps one [`Subscriptions`] for its client, the HTTP
+//! transport one per session. Changes reach the shell as URIs on the
+//! channel set with [`McpServerShell::set_resource_updates`]; what watches
+//! the stores lives at the integration layer.
+//!
+//! [`McpServerShell::set_resource_updates`]: super::server::McpServerShell::set_resource_updates
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+/// Method of a resource update notification.
+pub const UPDATED: &str = "notifications/resources/updated";
+
+/// The notification telling a subscriber that `uri` changed.
+pub fn notification(uri: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": UPDATED,
+        "params": { "uri": uri }
+    })
+}
+
+/// The resource URIs one connection is subscribed to.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    uris: Mutex<HashSet<String>>,
+}
+
+impl Subscriptions {
+    /// Subscribe to `uri`. Subscribing twice is the same as once.
+    pub fn subscribe(&self, uri: &str) {
+        self.lock().insert(uri.to_string());
+    }
+
+    /// Unsubscribe from `uri`. Returns `false` if it was not subscribed.
+    pub fn unsubscribe(&self, uri: &str) -> bool {
+        self.lock().remove(uri)
+    }
+
+    /// Whether the connection is subscribed to `uri`.
+    pub fn contains(&self, uri: &str) -> bool {
+        self.lock().contains(uri)
+    }
+
+    /// Drop every subscription, as when the connection ends.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.uris.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribe_and_unsubscribe() {
+        let subscriptions = Subscriptions::default();
+        subscriptions.subscribe("clawft://memory/default");
+        subscriptions.subscribe("clawft://memory/default");
+        assert!(subscriptions.contains("clawft://memory/default"));
+        assert!(!subscriptions.contains("clawft://sessions/cli%3Adefault"));
+
+        assert!(subscriptions.unsubscribe("clawft://memory/default"));
+        assert!(!subscriptions.unsubscribe("clawft://memory/default"));
+        assert!(!subscriptions.contains("clawft://memory/default"));
+
+        subscriptions.subscribe("clawft://sessions/cli%3Adefault");
+        subscriptions.clear();
+        assert!(!subscriptions.contains("clawft://sessions/cli%3Adefault"));
+    }
+
+    #[test]
+    fn notification_names_the_uri() {
+        assert_eq!(
+            notification("clawft://memory/default"),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/resources/updated",
+                "params": { "uri": "clawft://memory/default" }
+            })
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! Supervised MCP sessions that survive server restarts.
+//!
+//! An [`McpSession`] is bound to one transport: once a stdio server
+//! crashes or an HTTP server goes away, every request on it fails.
+//! [`SupervisedSession`] watches the transport and, when it closes, opens
+//! a new one through an [`McpConnector`] (restarting the server process
+//! for stdio), redoes the initialize handshake, and swaps the new session
+//! in. Attempts back off exponentially per [`ReconnectPolicy`] and go on
+//! until one succeeds.
+//!
+//! While the connection is down, calls fail with
+//! [`ServiceError::McpUnavailable`], which callers may retry. Subscribers
+//! get the server's notifications and a [`SessionEvent`] for each
+//! disconnect and reconnect, so state derived from the server (such as its
+//! tool list) can be fetched again.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::sampling::SamplingHandler;
+use super::transport::{McpTransport, Termination};
+use super::types::JsonRpcNotification;
+use super::{McpSession, ToolDefinition};
+use crate::error::{Result, ServiceError};
+
+/// Events buffered per subscriber before the oldest are dropped.
+const EVENT_CAPACITY: usize = 16;
+
+/// Opens transports to one MCP server.
+#[async_trait]
+pub trait McpConnector: Send + Sync {
+    /// Open a new transport, spawning the server process if it has one.
+    async fn connect(&self) -> Result<Box<dyn McpTransport>>;
+}
+
+/// Delays between reconnection attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first attempt.
+    pub initial_delay: Duration,
+    /// Longest delay; each failed attempt doubles the delay up to this.
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A change seen by a [`SupervisedSession`] subscriber.
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// The server sent a notification.
+    Notification(JsonRpcNotification),
+    /// The connection was lost; calls fail until it is re-established.
+    Disconnected,
+    /// A new connection completed the initialize handshake. Server state
+    /// may have changed while disconnected.
+    Reconnected,
+}
+
+struct Shared {
+    server: String,
+    current: RwLock<Option<Arc<McpSession>>>,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+/// An MCP session that reconnects when its transport is lost.
+pub struct SupervisedSession {
+    shared: Arc<Shared>,
+    supervisor: tokio::task::JoinHandle<()>,
+}
+
+impl SupervisedSession {
+    /// Connect to server `server` through `connector` and supervise the
+    /// session. Must be called within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the first connection or handshake fails; reconnection
+    /// only starts once a session was established.
+    pub async fn connect(
+        server: impl Into<String>,
+        connector: Box<dyn McpConnector>,
+        policy: ReconnectPolicy,
+    ) -> Result<Self> {
+        Self::connect_with_sampling(server, connector, policy, None).await
+    }
+
+    /// Like [`connect`](Self::connect), answering the server's sampling
+    /// requests with `sampling` on every connection when one is given.
+    pub async fn connect_with_sampling(
+        server: impl Into<String>,
+        connector: Box<dyn McpConnector>,
+        policy: ReconnectPolicy,
+        sampling: Option<Arc<dyn SamplingHandler>>,
+    ) -> Result<Self> {
+        let transport = connector.connect().await?;
+        let session =
+            Arc::new(McpSession::connect_with_sampling(transport, sampling.clone()).await?);
+        let notifications = session.subscribe_notifications();
+        let shared = Arc::new(Shared {
+            server: server.into(),
+            current: RwLock::new(Some(session.clone())),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        });
+        let supervisor = tokio::spawn(supervise(
+            shared.clone(),
+            connector,
+            policy,
+            sampling,
+            session,
+            notifications,
+        ));
+        Ok(Self { shared, supervisor })
+    }
+
+    /// Name of the server.
+    pub fn server(&self) -> &str {
+        &self.shared.server
+    }
+
+    /// The current session, or `None` while reconnecting.
+    pub fn session(&self) -> Option<Arc<McpSession>> {
+        self.shared
+            .current
+            .read()
+            .expect("MCP session lock poisoned")
+            .clone()
+    }
+
+    /// Whether a session is established.
+    pub fn is_connected(&self) -> bool {
+        self.session().is_some_and(|session| !session.is_closed())
+    }
+
+    /// Subscribe to the server's notifications and to connection changes.
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.shared.events.subscribe()
+    }
+
+    /// List tools available on the server.
+    pub async fn list_tools(&self) -> Result<Vec<ToolDefinition>> {
+        let session = self.live()?;
+        let result = session.list_tools().await;
+        self.check(&session, result)
+    }
+
+    /// Call a tool on the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ServiceError::McpUnavailable`] while reconnecting, or when
+    /// the connection is lost during the call.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let session = self.live()?;
+        let result = session.call_tool(name, params).await;
+        self.check(&session, result)
+    }
+
+    /// Stop supervising and end the server process, killing it if it does
+    /// not exit within `grace`. The session is not reconnected afterwards.
+    pub async fn shutdown(&self, grace: Duration) -> Termination {
+        self.supervisor.abort();
+        let session = self
+            .shared
+            .current
+            .write()
+            .expect("MCP session lock poisoned")
+            .take();
+        match session {
+            Some(session) => session.terminate(grace).await,
+            None => Termination::Exited,
+        }
+    }
+
+    /// The current session, if its connection is up.
+    fn live(&self) -> Result<Arc<McpSession>> {
+        match self.session() {
+            Some(session) if !session.is_closed() => Ok(session),
+            _ => Err(ServiceError::McpUnavailable(format!(
+                "MCP server '{}' is reconnecting",
+                self.shared.server
+            ))),
+        }
+    }
+
+    /// Report a transport failure on a lost connection as unavailable.
+    fn check<T>(&self, session: &McpSession, result: Result<T>) -> Result<T> {
+        match result {
+            Err(ServiceError::McpTransport(e)) if session.is_closed() => {
+                Err(ServiceError::McpUnavailable(format!(
+                    "MCP server '{}' disconnected: {e}",
+                    self.shared.server
+                )))
+            }
+            other => other,
+        }
+    }
+}
+
+impl Drop for SupervisedSession {
+    fn drop(&mut self) {
+        // Dropping the task drops its session, ending the server process.
+        self.supervisor.abort();
+    }
+}
+
+impl std::fmt::Debug for SupervisedSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SupervisedSession")
+            .field("server", &self.shared.server)
+            .field("connected", &self.is_connected())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Forward `session`'s notifications until its transport closes, then
+/// reconnect; forever.
+async fn supervise(
+    shared: Arc<Shared>,
+    connector: Box<dyn McpConnector>,
+    policy: ReconnectPolicy,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+    mut session: Arc<McpSession>,
+    mut notifications: Option<broadcast::Receiver<JsonRpcNotification>>,
+) {
+    loop {
+        forward_until_closed(&session, notifications, &shared.events).await;
+
+        *shared.current.write().expect("MCP session lock poisoned") = None;
+        drop(session);
+        warn!(server = %shared.server, "MCP server connection lost, reconnecting");
+        let _ = shared.events.send(SessionEvent::Disconnected);
+
+        session = reconnect(&shared.server, &*connector, policy, sampling.clone()).await;
+        notifications = session.subscribe_notifications();
+        *shared.current.write().expect("MCP session lock poisoned") = Some(session.clone());
+        info!(server = %shared.server, "MCP server reconnected");
+        let _ = shared.events.send(SessionEvent::Reconnected);
+    }
+}
+
+async fn forward_until_closed(
+    session: &McpSession,
+    notifications: Option<broadcast::Receiver<JsonRpcNotification>>,
+    events: &broadcast::Sender<SessionEvent>,
+) {
+    use broadcast::error::RecvError;
+
+    let Some(mut notifications) = notifications else {
+        session.closed().await;
+        return;
+    };
+    loop {
+        tokio::select! {
+            () = session.closed() => return,
+            received = notifications.recv() => match received {
+                Ok(notification) => {
+                    let _ = events.send(SessionEvent::Notification(notification));
+                }
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => {
+                    session.closed().await;
+                    return;
+                }
+            },
+        }
+    }
+}
+
+/// Connect and handshake, retrying with backoff until it succeeds.
+async fn reconnect(
+    server: &str,
+    connector: &dyn McpConnector,
+    policy: ReconnectPolicy,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+) -> Arc<McpSession> {
+    let mut delay = policy.initial_delay;
+    let mut attempt = 1u32;
+    loop {
+        tokio::time::sleep(delay).await;
+        let connected = match connector.connect().await {
+            Ok(transport) => McpSession::connect_with_sampling(transport, sampling.clone()).await,
+            Err(e) => Err(e),
+        };
+        match connected {
+            Ok(session) => return Arc::new(session),
+            Err(e) => warn!(
+                server,
+                attempt,
+                error = %e,
+                retry_in_ms = delay.saturating_mul(2).min(policy.max_delay).as_millis() as u64,
+                "MCP server reconnection failed"
+            ),
+        }
+        delay = delay.saturating_mul(2).min(policy.max_delay);
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::transport::MockTransport;
+    use crate::mcp::types::{JsonRpcRequest, JsonRpcResponse};
+    use std::collections::VecDeque;
+    use tokio::sync::Mutex;
+
+    fn response(id: u64, result: serde_json::Value) -> JsonRpcResponse {
+        JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// A server that completes the handshake, then answers `then`.
+    fn server(then: Vec<JsonRpcResponse>) -> MockTransport {
+        let mut responses = vec![response(
+            1,
+            serde_json::json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "mock", "version": "1" }
+            }),
+        )];
+        responses.extend(then);
+        MockTransport::new(responses)
+    }
+
+    fn tools(id: u64, names: &[&str]) -> JsonRpcResponse {
+        let tools: Vec<_> = names
+            .iter()
+            .map(|name| serde_json::json!({ "name": name, "description": "", "inputSchema": {} }))
+            .collect();
+        response(id, serde_json::json!({ "tools": tools }))
+    }
+
+    /// Hands out scripted transports in order; `None` is a failed attempt.
+    /// Once the script runs out, every attempt fails.
+    #[derive(Clone)]
+    struct ScriptedConnector {
+        script: Arc<Mutex<VecDeque<Option<MockTransport>>>>,
+    }
+
+    impl ScriptedConnector {
+        fn new(script: Vec<Option<MockTransport>>) -> Self {
+            Self {
+                script: Arc::new(Mutex::new(script.into())),
+            }
+        }
+
+        async fn remaining(&self) -> usize {
+            self.script.lock().await.len()
+        }
+    }
+
+    #[async_trait]
+    impl McpConnector for ScriptedConnector {
+        async fn connect(&self) -> Result<Box<dyn McpTransport>> {
+            match self.script.lock().await.pop_front().flatten() {
+                Some(transport) => Ok(Box::new(transport)),
+                None => Err(ServiceError::McpTransport("server is down".into())),
+            }
+        }
+    }
+
+    /// Loses the connection while answering a request.
+    struct DyingTransport(MockTransport);
+
+    #[async_trait]
+    impl McpTransport for DyingTransport {
+        async fn send_request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+            if request.method == "tools/call" {
+                self.0.close();
+            }
+            self.0.send_request(request).await
+        }
+
+        async fn send_notification(&self, method: &str, params: serde_json::Value) -> Result<()> {
+            self.0.send_notification(method, params).await
+        }
+
+        fn is_closed(&self) -> bool {
+            self.0.is_closed()
+        }
+
+        async fn closed(&self) {
+            self.0.closed().await
+        }
+    }
+
+    struct DyingConnector(MockTransport);
+
+    #[async_trait]
+    impl McpConnector for DyingConnector {
+        async fn connect(&self) -> Result<Box<dyn McpTransport>> {
+            Ok(Box::new(DyingTransport(self.0.clone())))
+        }
+    }
+
+    fn fast() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+        }
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<SessionEvent>) -> SessionEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event before timeout")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_the_server_dies() {
+        let first = server(vec![tools(2, &["a"])]);
+        let second = server(vec![tools(2, &["a", "b"])]);
+        // Two failed attempts while the server restarts.
+        let connector =
+            ScriptedConnector::new(vec![Some(first.clone()), None, None, Some(second.clone())]);
+        let session = SupervisedSession::connect("docs", Box::new(connector.clone()), fast())
+            .await
+            .unwrap();
+        let mut events = session.subscribe();
+        assert_eq!(session.list_tools().await.unwrap().len(), 1);
+
+        first.close();
+        assert!(matches!(
+            next_event(&mut events).await,
+            SessionEvent::Disconnected
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            SessionEvent::Reconnected
+        ));
+        assert_eq!(connector.remaining().await, 0);
+        assert!(session.is_connected());
+
+        // The new session did its own handshake.
+        let requests = second.requests().await;
+        assert_eq!(requests[0].method, "initialize");
+        assert_eq!(
+            second.notifications().await[0].method,
+            "notifications/initialized"
+        );
+        let names: Vec<_> = session
+            .list_tools()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn shutdown_does_not_reconnect() {
+        let first = server(vec![]);
+        let connector = ScriptedConnector::new(vec![Some(first), Some(server(vec![]))]);
+        let session = SupervisedSession::connect("docs", Box::new(connector.clone()), fast())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            session.shutdown(Duration::from_secs(1)).await,
+            Termination::Exited
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!session.is_connected());
+        assert_eq!(connector.remaining().await, 1);
+        let err = session.list_tools().await.unwrap_err();
+        assert!(matches!(err, ServiceError::McpUnavailable(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn calls_fail_as_unavailable_while_reconnecting() {
+        let first = server(vec![]);
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(60),
+        };
+        let session = SupervisedSession::connect("docs", Box::new(DyingConnector(first)), policy)
+            .await
+            .unwrap();
+        let mut events = session.subscribe();
+
+        // The server dies during the call.
+        let err = session
+            .call_tool("search", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::McpUnavailable(_)), "{err}");
+        assert!(err.to_string().contains("'docs' disconnected"), "{err}");
+
+        assert!(matches!(
+            next_event(&mut events).await,
+            SessionEvent::Disconnected
+        ));
+        assert!(!session.is_connected());
+        let err = session.list_tools().await.unwrap_err();
+        assert!(matches!(err, ServiceError::McpUnavailable(_)), "{err}");
+        assert!(err.to_string().contains("'docs' is reconnecting"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn errors_on_a_live_connection_pass_through() {
+        let first = server(vec![]);
+        let connector = ScriptedConnector::new(vec![Some(first)]);
+        let session = SupervisedSession::connect("docs", Box::new(connector), fast())
+            .await
+            .unwrap();
+        // Out of scripted responses, but the transport is not closed.
+        let err = session
+            .call_tool("search", serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::McpTransport(_)), "{err}");
+        assert!(session.is_connected());
+    }
+
+    #[tokio::test]
+    async fn forwards_notifications_across_reconnects() {
+        let first = server(vec![]);
+        let second = server(vec![]);
+        let connector = ScriptedConnector::new(vec![Some(first.clone()), Some(second.clone())]);
+        let session = SupervisedSession::connect("docs", Box::new(connector), fast())
+            .await
+            .unwrap();
+        let mut events = session.subscribe();
+
+        first.notify("notifications/tools/list_changed", serde_json::json!({}));
+        match next_event(&mut events).await {
+            SessionEvent::Notification(n) => {
+                assert_eq!(n.method, "notifications/tools/list_changed")
+            }
+            other => panic!("expected a notification, got {other:?}"),
+        }
+
+        first.close();
+        assert!(matches!(
+            next_event(&mut events).await,
+            SessionEvent::Disconnected
+        ));
+        assert!(matches!(
+            next_event(&mut events).await,
+            SessionEvent::Reconnected
+        ));
+        second.notify("notifications/message", serde_json::json!({}));
+        match next_event(&mut events).await {
+            SessionEvent::Notification(n) => assert_eq!(n.method, "notifications/message"),
+            other => panic!("expected a notification, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn first_connection_failure_is_an_error() {
+        let connector = ScriptedConnector::new(vec![None]);
+        let err = SupervisedSession::connect("docs", Box::new(connector), fast())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("server is down"), "{err}");
+    }
+}
@@ -4,6 +4,10 @@
 //! - [`StdioTransport`]: communicates with a child process over stdin/stdout
 //!   using request-ID multiplexing for concurrent requests
 //! - [`HttpTransport`]: communicates over HTTP POST
+//!
+//! Servers may also send requests to the client (such as
+//! `sampling/createMessage`); transports that can receive them pass them to
+//! the [`RequestHandler`] set with [`McpTransport::set_request_handler`].
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -11,10 +15,13 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::{Mutex, oneshot};
+use tokio::sync::{Mutex, broadcast, oneshot, watch};
 use tracing::{debug, warn};
 
-use super::types::{JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use super::types::{
+    JsonRpcError, JsonRpcIncomingRequest, JsonRpcNotification, JsonRpcReply, JsonRpcRequest,
+    JsonRpcResponse,
+};
 use crate::error::{Result, ServiceError};
 
 /// Transport layer for MCP JSON-RPC communication.
@@ -25,8 +32,95 @@ pub trait McpTransport: Send + Sync {
 
     /// Send a JSON-RPC notification (no `id`, no response expected).
     async fn send_notification(&self, method: &str, params: serde_json::Value) -> Result<()>;
+
+    /// Subscribe to notifications sent by the server, such as
+    /// `notifications/tools/list_changed`.
+    ///
+    /// Returns `None` for transports that cannot receive them.
+    fn subscribe(&self) -> Option<broadcast::Receiver<JsonRpcNotification>> {
+        None
+    }
+
+    /// Whether the connection is known to be lost (the server process
+    /// exited, or the server could not be reached). A closed transport
+    /// does not recover; a new one must be opened.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Wait until the connection is lost. Never completes for transports
+    /// that cannot tell.
+    async fn closed(&self) {
+        std::future::pending::<()>().await
+    }
+
+    /// Set the handler for requests sent by the server. Without one, such
+    /// requests are answered with "method not found".
+    ///
+    /// Ignored by transports that cannot receive them.
+    fn set_request_handler(&self, _handler: Arc<dyn RequestHandler>) {}
+
+    /// Stop the server process: ask it to exit, and kill it if it is still
+    /// running after `grace`.
+    ///
+    /// Transports without a process have nothing to stop and report
+    /// [`Termination::Exited`].
+    async fn terminate(&self, _grace: std::time::Duration) -> Termination {
+        Termination::Exited
+    }
+}
+
+/// How a server process ended on [`McpTransport::terminate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The process exited on its own or when asked to (or there was none).
+    Exited,
+    /// The process outlived the grace period and was killed.
+    Killed,
+}
+
+/// Answers requests the server sends to the client.
+#[async_trait]
+pub trait RequestHandler: Send + Sync {
+    /// Handle request `method`, returning its result or a JSON-RPC error.
+    async fn handle(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> std::result::Result<serde_json::Value, JsonRpcError>;
+}
+
+/// The slot holding a transport's [`RequestHandler`].
+type HandlerSlot = Arc<std::sync::RwLock<Option<Arc<dyn RequestHandler>>>>;
+
+/// Answer a server request with `handler`. `ping` is always answered.
+async fn answer(
+    handler: Option<Arc<dyn RequestHandler>>,
+    request: JsonRpcIncomingRequest,
+) -> JsonRpcReply {
+    let outcome = match handler {
+        _ if request.method == "ping" => Ok(serde_json::json!({})),
+        Some(handler) => handler.handle(&request.method, request.params).await,
+        None => Err(JsonRpcError {
+            code: -32601,
+            message: format!("method not found: {}", request.method),
+            data: None,
+        }),
+    };
+    JsonRpcReply::new(request.id, outcome)
+}
+
+/// Wait until `closed` is set.
+async fn wait_closed(closed: &watch::Sender<bool>) {
+    let mut rx = closed.subscribe();
+    // The sender is borrowed, so the channel cannot close.
+    let _ = rx.wait_for(|closed| *closed).await;
 }
 
+/// Server notifications buffered per subscriber before the oldest are
+/// dropped.
+const NOTIFICATION_CAPACITY: usize = 16;
+
 /// Pending response registry: maps request IDs to oneshot senders.
 type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
 
@@ -35,19 +129,25 @@ type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
 /// Uses a background reader task and request-ID multiplexing to support
 /// concurrent requests. Each `send_request` call registers a oneshot
 /// channel keyed by the request ID, writes to stdin, and waits for the
-/// background reader to deliver the matching response.
+/// background reader to deliver the matching response. Requests from the
+/// server are answered on their own task, so a slow handler does not hold
+/// up responses.
 pub struct StdioTransport {
-    #[allow(dead_code)]
     child: Arc<Mutex<Child>>,
     stdin: Arc<Mutex<tokio::process::ChildStdin>>,
     pending: PendingMap,
+    notifications: broadcast::Sender<JsonRpcNotification>,
+    closed: Arc<watch::Sender<bool>>,
+    handler: HandlerSlot,
+    request_timeout: std::time::Duration,
     #[allow(dead_code)]
     reader_handle: Arc<tokio::task::JoinHandle<()>>,
 }
 
 impl StdioTransport {
     /// Spawn a child process and set up JSON-RPC communication with
-    /// request-ID multiplexing.
+    /// request-ID multiplexing. The process is killed when the transport
+    /// is dropped.
     pub async fn new(
         command: &str,
         args: &[String],
@@ -58,7 +158,8 @@ impl StdioTransport {
             .envs(env)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null());
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
 
         let mut child = cmd.spawn()?;
 
@@ -73,9 +174,20 @@ impl StdioTransport {
 
         let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
 
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        let closed = Arc::new(watch::Sender::new(false));
+        let stdin = Arc::new(Mutex::new(stdin));
+        let handler: HandlerSlot = Arc::default();
+
         // Spawn background reader task that reads lines from stdout and
-        // dispatches responses to the matching pending oneshot sender.
+        // dispatches responses to the matching pending oneshot sender,
+        // notifications to the subscribers, and server requests to the
+        // handler.
         let reader_pending = Arc::clone(&pending);
+        let reader_notifications = notifications.clone();
+        let reader_closed = Arc::clone(&closed);
+        let reader_stdin = Arc::clone(&stdin);
+        let reader_handler = Arc::clone(&handler);
         let reader_handle = tokio::spawn(async move {
             let mut reader = BufReader::new(stdout);
             let mut line = String::new();
@@ -91,19 +203,60 @@ impl StdioTransport {
                         if trimmed.is_empty() {
                             continue;
                         }
-                        match serde_json::from_str::<JsonRpcResponse>(trimmed) {
-                            Ok(response) => {
-                                let id = response.id;
-                                let mut map = reader_pending.lock().await;
-                                if let Some(tx) = map.remove(&id) {
-                                    let _ = tx.send(response);
-                                } else {
-                                    warn!(id, "stdio reader: received response with no pending request");
+                        let message = match serde_json::from_str::<serde_json::Value>(trimmed) {
+                            Ok(message) => message,
+                            Err(e) => {
+                                debug!(error = %e, "stdio reader: ignoring malformed line");
+                                continue;
+                            }
+                        };
+                        if message.get("method").is_none() {
+                            match serde_json::from_value::<JsonRpcResponse>(message) {
+                                Ok(response) => {
+                                    let id = response.id;
+                                    let mut map = reader_pending.lock().await;
+                                    if let Some(tx) = map.remove(&id) {
+                                        let _ = tx.send(response);
+                                    } else {
+                                        warn!(
+                                            id,
+                                            "stdio reader: received response with no pending request"
+                                        );
+                                    }
+                                }
+                                Err(e) => {
+                                    debug!(error = %e, "stdio reader: ignoring malformed line");
                                 }
                             }
-                            Err(e) => {
-                                // Could be a notification or malformed line; skip
-                                debug!(error = %e, "stdio reader: ignoring non-response line");
+                        } else if message.get("id").is_some() {
+                            match serde_json::from_value::<JsonRpcIncomingRequest>(message) {
+                                Ok(request) => {
+                                    debug!(method = %request.method, "stdio reader: server request");
+                                    let handler = reader_handler
+                                        .read()
+                                        .expect("MCP request handler lock poisoned")
+                                        .clone();
+                                    let stdin = Arc::clone(&reader_stdin);
+                                    tokio::spawn(async move {
+                                        let reply = answer(handler, request).await;
+                                        if let Err(e) = write_line(&stdin, &reply).await {
+                                            warn!(error = %e, "stdio reader: failed to reply to server request");
+                                        }
+                                    });
+                                }
+                                Err(e) => {
+                                    debug!(error = %e, "stdio reader: ignoring malformed line");
+                                }
+                            }
+                        } else {
+                            match serde_json::from_value::<JsonRpcNotification>(message) {
+                                Ok(notification) => {
+                                    debug!(method = %notification.method, "stdio reader: server notification");
+                                    let _ = reader_notifications.send(notification);
+                                }
+                                Err(e) => {
+                                    debug!(error = %e, "stdio reader: ignoring malformed line");
+                                }
                             }
                         }
                     }
@@ -115,17 +268,42 @@ impl StdioTransport {
             }
 
             // Signal all pending requests that the reader has stopped.
+            reader_closed.send_replace(true);
             let mut map = reader_pending.lock().await;
             map.clear();
         });
 
         Ok(Self {
             child: Arc::new(Mutex::new(child)),
-            stdin: Arc::new(Mutex::new(stdin)),
+            stdin,
             pending,
+            notifications,
+            closed,
+            handler,
+            request_timeout: REQUEST_TIMEOUT,
             reader_handle: Arc::new(reader_handle),
         })
     }
+
+    /// Wait up to `timeout` for each response instead of the default 30
+    /// seconds.
+    pub fn with_request_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+}
+
+/// Write `message` to the child's stdin as one line.
+async fn write_line(
+    stdin: &Mutex<tokio::process::ChildStdin>,
+    message: &impl serde::Serialize,
+) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
 }
 
 /// Default timeout for waiting on a response from the child process.
@@ -147,20 +325,24 @@ impl McpTransport for StdioTransport {
             map.insert(id, tx);
         }
 
-        // Write to stdin.
+        // Write to stdin. The child is gone if that fails.
         {
             let mut stdin = self.stdin.lock().await;
-            stdin.write_all(line.as_bytes()).await.map_err(|e| {
-                ServiceError::McpTransport(format!("failed to write to stdin: {e}"))
-            })?;
-            stdin
-                .flush()
-                .await
-                .map_err(|e| ServiceError::McpTransport(format!("failed to flush stdin: {e}")))?;
+            let written = match stdin.write_all(line.as_bytes()).await {
+                Ok(()) => stdin.flush().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = written {
+                self.pending.lock().await.remove(&id);
+                self.closed.send_replace(true);
+                return Err(ServiceError::McpTransport(format!(
+                    "failed to write to stdin: {e}"
+                )));
+            }
         }
 
         // Wait for the background reader to deliver the response, with timeout.
-        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+        match tokio::time::timeout(self.request_timeout, rx).await {
             Ok(Ok(response)) => Ok(response),
             Ok(Err(_)) => {
                 // Oneshot sender was dropped (reader task exited).
@@ -174,7 +356,7 @@ impl McpTransport for StdioTransport {
                 map.remove(&id);
                 Err(ServiceError::McpTransport(format!(
                     "request {id} timed out after {}s",
-                    REQUEST_TIMEOUT.as_secs()
+                    self.request_timeout.as_secs()
                 )))
             }
         }
@@ -198,15 +380,62 @@ impl McpTransport for StdioTransport {
         // Notifications do not expect a response -- do NOT read from stdout.
         Ok(())
     }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<JsonRpcNotification>> {
+        Some(self.notifications.subscribe())
+    }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    async fn closed(&self) {
+        wait_closed(&self.closed).await
+    }
+
+    fn set_request_handler(&self, handler: Arc<dyn RequestHandler>) {
+        *self
+            .handler
+            .write()
+            .expect("MCP request handler lock poisoned") = Some(handler);
+    }
+
+    /// Send SIGTERM, then SIGKILL once `grace` runs out (other platforms
+    /// kill straight away).
+    async fn terminate(&self, grace: std::time::Duration) -> Termination {
+        let mut child = self.child.lock().await;
+        if let Ok(Some(_)) = child.try_wait() {
+            return Termination::Exited;
+        }
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            use nix::sys::signal::{Signal, kill};
+            use nix::unistd::Pid;
+
+            if kill(Pid::from_raw(pid as i32), Signal::SIGTERM).is_ok()
+                && tokio::time::timeout(grace, child.wait()).await.is_ok()
+            {
+                return Termination::Exited;
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = grace;
+        if let Err(e) = child.kill().await {
+            warn!(error = %e, "failed to kill MCP server process");
+        }
+        Termination::Killed
+    }
 }
 
 /// Transport that communicates via HTTP POST.
 ///
 /// Sends JSON-RPC requests as the body of POST requests to the
-/// configured endpoint URL.
+/// configured endpoint URL. The transport counts as closed once a request
+/// cannot reach the server.
 pub struct HttpTransport {
     client: reqwest::Client,
     endpoint: String,
+    closed: watch::Sender<bool>,
 }
 
 impl HttpTransport {
@@ -215,6 +444,7 @@ impl HttpTransport {
         Self {
             client: reqwest::Client::new(),
             endpoint,
+            closed: watch::Sender::new(false),
         }
     }
 }
@@ -235,7 +465,12 @@ impl McpTransport for HttpTransport {
             .json(&request)
             .send()
             .await
-            .map_err(|e| ServiceError::McpTransport(format!("HTTP request failed: {e}")))?;
+            .map_err(|e| {
+                if e.is_connect() {
+                    self.closed.send_replace(true);
+                }
+                ServiceError::McpTransport(format!("HTTP request failed: {e}"))
+            })?;
 
         let status = resp.status();
         if !status.is_success() {
@@ -266,7 +501,12 @@ impl McpTransport for HttpTransport {
             .json(&notif)
             .send()
             .await
-            .map_err(|e| ServiceError::McpTransport(format!("HTTP notification failed: {e}")))?;
+            .map_err(|e| {
+                if e.is_connect() {
+                    self.closed.send_replace(true);
+                }
+                ServiceError::McpTransport(format!("HTTP notification failed: {e}"))
+            })?;
 
         // Log non-success status but don't fail -- notifications are fire-and-forget.
         let status = resp.status();
@@ -280,20 +520,35 @@ impl McpTransport for HttpTransport {
 
         Ok(())
     }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    async fn closed(&self) {
+        wait_closed(&self.closed).await
+    }
 }
 
 /// A mock transport for testing.
 ///
 /// Allows pre-programming responses that will be returned in order.
-/// Also records all sent notifications for verification.
+/// Also records all sent notifications for verification, and can be
+/// [closed](MockTransport::close) to simulate a dead server.
 ///
 /// Available in tests and when the `test-utils` feature is enabled,
-/// allowing downstream crates to use it in their own test suites.
+/// allowing downstream crates to use it in their own test suites. Clones
+/// share their state, so a test can keep one after handing another to a
+/// session.
 #[cfg(any(test, feature = "test-utils"))]
+#[derive(Clone)]
 pub struct MockTransport {
     responses: Arc<Mutex<Vec<JsonRpcResponse>>>,
     requests: Arc<Mutex<Vec<JsonRpcRequest>>>,
     notifications: Arc<Mutex<Vec<JsonRpcNotification>>>,
+    server_notifications: broadcast::Sender<JsonRpcNotification>,
+    closed: Arc<watch::Sender<bool>>,
+    handler: HandlerSlot,
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -304,9 +559,41 @@ impl MockTransport {
             responses: Arc::new(Mutex::new(responses)),
             requests: Arc::new(Mutex::new(Vec::new())),
             notifications: Arc::new(Mutex::new(Vec::new())),
+            server_notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            closed: Arc::new(watch::Sender::new(false)),
+            handler: Arc::default(),
         }
     }
 
+    /// Send a request as if the server had sent it, returning the client's
+    /// reply.
+    pub async fn server_request(&self, method: &str, params: serde_json::Value) -> JsonRpcReply {
+        let handler = self
+            .handler
+            .read()
+            .expect("MCP request handler lock poisoned")
+            .clone();
+        let request = JsonRpcIncomingRequest {
+            jsonrpc: "2.0".into(),
+            id: serde_json::json!(1),
+            method: method.into(),
+            params,
+        };
+        answer(handler, request).await
+    }
+
+    /// Lose the connection: later requests fail with a transport error.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Deliver a notification as if the server had sent it.
+    pub fn notify(&self, method: &str, params: serde_json::Value) {
+        let _ = self
+            .server_notifications
+            .send(JsonRpcNotification::new(method, params));
+    }
+
     /// Get all requests that were sent through this transport.
     pub async fn requests(&self) -> Vec<JsonRpcRequest> {
         self.requests.lock().await.clone()
@@ -323,6 +610,9 @@ impl MockTransport {
 impl McpTransport for MockTransport {
     async fn send_request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
         self.requests.lock().await.push(request);
+        if self.is_closed() {
+            return Err(ServiceError::McpTransport("connection closed".into()));
+        }
         let mut responses = self.responses.lock().await;
         if responses.is_empty() {
             Err(ServiceError::McpTransport("no more mock responses".into()))
@@ -336,6 +626,25 @@ impl McpTransport for MockTransport {
         self.notifications.lock().await.push(notif);
         Ok(())
     }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<JsonRpcNotification>> {
+        Some(self.server_notifications.subscribe())
+    }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    async fn closed(&self) {
+        wait_closed(&self.closed).await
+    }
+
+    fn set_request_handler(&self, handler: Arc<dyn RequestHandler>) {
+        *self
+            .handler
+            .write()
+            .expect("MCP request handler lock poisoned") = Some(handler);
+    }
 }
 
 #[cfg(test)]
@@ -412,6 +721,163 @@ mod tests {
         assert_eq!(notifs[1].params["token"], "abc");
     }
 
+    #[tokio::test]
+    async fn mock_transport_delivers_server_notifications() {
+        let transport = MockTransport::new(vec![]);
+        let mut rx = transport.subscribe().unwrap();
+        transport.notify("notifications/tools/list_changed", serde_json::json!({}));
+        let notif = rx.recv().await.unwrap();
+        assert_eq!(notif.method, "notifications/tools/list_changed");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_transport_delivers_server_notifications() {
+        let script = r#"read _; echo '{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}'; sleep 5"#;
+        let transport = StdioTransport::new("sh", &["-c".into(), script.into()], &HashMap::new())
+            .await
+            .unwrap();
+        let mut rx = transport.subscribe().unwrap();
+        transport
+            .send_notification("notifications/initialized", serde_json::json!({}))
+            .await
+            .unwrap();
+        let notif = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("notification before timeout")
+            .unwrap();
+        assert_eq!(notif.method, "notifications/tools/list_changed");
+        assert_eq!(notif.params, serde_json::json!({}));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_transport_terminates_the_child() {
+        let transport = StdioTransport::new("sh", &["-c".into(), "read _".into()], &HashMap::new())
+            .await
+            .unwrap();
+        let ended = transport.terminate(std::time::Duration::from_secs(5)).await;
+        assert_eq!(ended, Termination::Exited);
+        tokio::time::timeout(std::time::Duration::from_secs(5), transport.closed())
+            .await
+            .expect("transport closes once the child is gone");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_transport_kills_a_child_ignoring_sigterm() {
+        let script = r#"trap '' TERM; echo ready; read _"#;
+        let transport = StdioTransport::new("sh", &["-c".into(), script.into()], &HashMap::new())
+            .await
+            .unwrap();
+        // Let the trap be installed before signalling.
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let ended = transport
+            .terminate(std::time::Duration::from_millis(200))
+            .await;
+        assert_eq!(ended, Termination::Killed);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_transport_closes_when_the_child_exits() {
+        let transport = StdioTransport::new("sh", &["-c".into(), "read _".into()], &HashMap::new())
+            .await
+            .unwrap();
+        assert!(!transport.is_closed());
+        let req = JsonRpcRequest::new(1, "tools/list", serde_json::json!({}));
+        assert!(transport.send_request(req).await.is_err());
+        tokio::time::timeout(std::time::Duration::from_secs(5), transport.closed())
+            .await
+            .expect("closed before timeout");
+        assert!(transport.is_closed());
+    }
+
+    #[tokio::test]
+    async fn http_transport_closes_when_unreachable() {
+        // Nothing listens on a port just released.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let transport = HttpTransport::new(format!("http://{addr}/mcp"));
+        let req = JsonRpcRequest::new(1, "tools/list", serde_json::json!({}));
+        assert!(transport.send_request(req).await.is_err());
+        assert!(transport.is_closed());
+    }
+
+    #[tokio::test]
+    async fn mock_transport_close_fails_requests() {
+        let transport = MockTransport::new(vec![]);
+        transport.close();
+        transport.closed().await;
+        let req = JsonRpcRequest::new(1, "tools/list", serde_json::json!({}));
+        let err = transport.send_request(req).await.unwrap_err();
+        assert!(err.to_string().contains("connection closed"));
+    }
+
+    /// Echoes a request's params back as its result.
+    struct Echo;
+
+    #[async_trait]
+    impl RequestHandler for Echo {
+        async fn handle(
+            &self,
+            method: &str,
+            params: serde_json::Value,
+        ) -> std::result::Result<serde_json::Value, JsonRpcError> {
+            Ok(serde_json::json!({"method": method, "params": params}))
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_transport_answers_server_requests_with_the_handler() {
+        let transport = MockTransport::new(vec![]);
+        let reply = transport
+            .server_request("test/echo", serde_json::json!({}))
+            .await;
+        assert_eq!(reply.error.unwrap().code, -32601);
+
+        transport.set_request_handler(Arc::new(Echo));
+        let reply = transport
+            .server_request("test/echo", serde_json::json!({"x": 1}))
+            .await;
+        assert_eq!(reply.result.unwrap()["params"]["x"], 1);
+    }
+
+    #[tokio::test]
+    async fn ping_is_answered_without_a_handler() {
+        let transport = MockTransport::new(vec![]);
+        let reply = transport
+            .server_request("ping", serde_json::json!({}))
+            .await;
+        assert_eq!(reply.result, Some(serde_json::json!({})));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stdio_transport_replies_to_server_requests() {
+        // The server sends a request, then reports the reply it got back
+        // as a notification.
+        let script = r#"read _; echo '{"jsonrpc":"2.0","id":"s-1","method":"test/echo","params":{"x":1}}'; read reply; echo "{\"jsonrpc\":\"2.0\",\"method\":\"test/reply\",\"params\":$reply}"; sleep 5"#;
+        let transport = StdioTransport::new("sh", &["-c".into(), script.into()], &HashMap::new())
+            .await
+            .unwrap();
+        transport.set_request_handler(Arc::new(Echo));
+        let mut rx = transport.subscribe().unwrap();
+        transport
+            .send_notification("notifications/initialized", serde_json::json!({}))
+            .await
+            .unwrap();
+        let notif = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("reply before timeout")
+            .unwrap();
+        assert_eq!(notif.method, "test/reply");
+        assert_eq!(notif.params["id"], "s-1");
+        assert_eq!(notif.params["result"]["method"], "test/echo");
+        assert_eq!(notif.params["result"]["params"]["x"], 1);
+    }
+
     #[tokio::test]
     async fn notification_has_no_id_field() {
         let notif = JsonRpcNotification::new("test/notify", serde_json::json!({}));
@@ -82,6 +82,56 @@ impl JsonRpcNotification {
     }
 }
 
+/// A JSON-RPC 2.0 request sent by the server to the client, such as
+/// `sampling/createMessage`. Servers choose their own ids, which may be
+/// numbers or strings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcIncomingRequest {
+    /// Protocol version, always `"2.0"`.
+    pub jsonrpc: String,
+    /// Request identifier, echoed back in the reply.
+    pub id: serde_json::Value,
+    /// Method name.
+    pub method: String,
+    /// Method parameters.
+    #[serde(default = "default_params")]
+    pub params: serde_json::Value,
+}
+
+/// The client's reply to a [`JsonRpcIncomingRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcReply {
+    /// Protocol version, always `"2.0"`.
+    pub jsonrpc: String,
+    /// Identifier of the request being answered.
+    pub id: serde_json::Value,
+    /// Successful result (mutually exclusive with `error`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// Error result (mutually exclusive with `result`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcReply {
+    /// Build the reply to request `id` from a handler's outcome.
+    pub fn new(
+        id: serde_json::Value,
+        outcome: std::result::Result<serde_json::Value, JsonRpcError>,
+    ) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +254,35 @@ mod tests {
         assert_eq!(restored.method, "notifications/initialized");
         assert_eq!(restored.params["ready"], true);
     }
+
+    #[test]
+    fn incoming_request_accepts_string_ids() {
+        let json = r#"{"jsonrpc":"2.0","id":"s-1","method":"sampling/createMessage"}"#;
+        let req: JsonRpcIncomingRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.id, "s-1");
+        assert!(req.params.is_object());
+    }
+
+    #[test]
+    fn reply_carries_result_or_error() {
+        let ok = JsonRpcReply::new(serde_json::json!(7), Ok(serde_json::json!({"a": 1})));
+        let json = serde_json::to_value(&ok).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": {"a": 1}})
+        );
+
+        let err = JsonRpcReply::new(
+            serde_json::json!("x"),
+            Err(JsonRpcError {
+                code: -32601,
+                message: "method not found".into(),
+                data: None,
+            }),
+        );
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["id"], "x");
+        assert_eq!(json["error"]["code"], -32601);
+        assert!(json.get("result").is_none());
+    }
 }
@@ -0,0 +1,482 @@
+//! Desktop clipboard and notification tools.
+//!
+//! `clipboard` reads and sets the system clipboard text, and `notify` shows
+//! a desktop notification: through the freedesktop notification service on
+//! Linux and the BSDs, `osascript` on macOS and PowerShell on Windows.
+//!
+//! Both act outside the workspace, so neither is registered unless opted
+//! in through `tools.desktop` (see [`register`]).
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use clawft_core::tools::registry::{Tool, ToolError, ToolRegistry};
+use clawft_types::config::DesktopToolConfig;
+use serde_json::json;
+
+/// Longest notification title, in characters.
+const TITLE_MAX_CHARS: usize = 256;
+
+/// Longest notification body, in characters.
+const BODY_MAX_CHARS: usize = 4096;
+
+/// Register the desktop tools `config` opts in to.
+pub fn register(registry: &mut ToolRegistry, config: &DesktopToolConfig) {
+    if config.clipboard {
+        registry.register(Arc::new(ClipboardTool::new(config.max_clipboard_bytes)));
+    }
+    if config.notifications {
+        registry.register(Arc::new(NotifyTool::new()));
+    }
+}
+
+fn str_arg<'a>(args: &'a serde_json::Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(|v| v.as_str())
+}
+
+async fn blocking<T: Send + 'static>(
+    what: &str,
+    f: impl FnOnce() -> Result<T, ToolError> + Send + 'static,
+) -> Result<T, ToolError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("{what} task failed: {e}")))?
+}
+
+/// Cut `text` to at most `max_bytes`, on a character boundary.
+fn truncate_to(text: &mut String, max_bytes: usize) -> bool {
+    if text.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    true
+}
+
+// ---------------------------------------------------------------------------
+// ClipboardTool
+// ---------------------------------------------------------------------------
+
+/// Read or set the system clipboard text.
+pub struct ClipboardTool {
+    max_bytes: usize,
+    // Kept open once used: on X11 and Wayland the text we set is served by
+    // our own handle, so dropping it would clear the clipboard.
+    clipboard: Arc<Mutex<Option<arboard::Clipboard>>>,
+}
+
+impl ClipboardTool {
+    /// Create a new `ClipboardTool` that sets at most `max_bytes` of text
+    /// and truncates what it reads to the same size.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            clipboard: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn with_clipboard<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error> + Send + 'static,
+    ) -> Result<T, ToolError> {
+        let clipboard = self.clipboard.clone();
+        blocking("clipboard", move || {
+            let mut guard = clipboard
+                .lock()
+                .map_err(|_| ToolError::ExecutionFailed("clipboard lock poisoned".into()))?;
+            if guard.is_none() {
+                *guard = Some(arboard::Clipboard::new().map_err(|e| {
+                    ToolError::ExecutionFailed(format!("cannot open the clipboard: {e}"))
+                })?);
+            }
+            let clipboard = guard.as_mut().expect("clipboard opened above");
+            f(clipboard).map_err(|e| ToolError::ExecutionFailed(format!("clipboard: {e}")))
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl Tool for ClipboardTool {
+    fn name(&self) -> &str {
+        "clipboard"
+    }
+
+    fn description(&self) -> &str {
+        "Read ('get') or replace ('set') the text on the user's system clipboard."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["get", "set"],
+                    "description": "Read the clipboard text, or replace it"
+                },
+                "text": {
+                    "type": "string",
+                    "description": "Text to put on the clipboard (set)"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let operation = str_arg(&args, "operation")
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: operation".into()))?;
+        match operation {
+            "get" => {
+                let mut text = self.with_clipboard(|c| c.get_text()).await?;
+                let bytes = text.len();
+                let truncated = truncate_to(&mut text, self.max_bytes);
+                Ok(json!({ "text": text, "bytes": bytes, "truncated": truncated }))
+            }
+            "set" => {
+                let text = str_arg(&args, "text")
+                    .ok_or_else(|| ToolError::InvalidArgs("missing required field: text".into()))?
+                    .to_string();
+                if text.len() > self.max_bytes {
+                    return Err(ToolError::InvalidArgs(format!(
+                        "text is {} bytes; the clipboard limit is {} bytes",
+                        text.len(),
+                        self.max_bytes
+                    )));
+                }
+                let bytes = text.len();
+                self.with_clipboard(move |c| c.set_text(text)).await?;
+                Ok(json!({ "set": true, "bytes": bytes }))
+            }
+            other => Err(ToolError::InvalidArgs(format!(
+                "unknown operation '{other}'; use get or set"
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NotifyTool
+// ---------------------------------------------------------------------------
+
+/// How insistent a notification is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Self::Low),
+            "normal" => Some(Self::Normal),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// A validated notification.
+#[derive(Debug)]
+struct Notification {
+    title: String,
+    body: String,
+    urgency: Urgency,
+    agent: Option<String>,
+}
+
+impl Notification {
+    fn from_args(args: &serde_json::Value, agent: Option<String>) -> Result<Self, ToolError> {
+        let title = str_arg(args, "title")
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: title".into()))?;
+        if title.chars().count() > TITLE_MAX_CHARS {
+            return Err(ToolError::InvalidArgs(format!(
+                "title is longer than {TITLE_MAX_CHARS} characters"
+            )));
+        }
+        let body = str_arg(args, "body").unwrap_or_default().trim();
+        if body.chars().count() > BODY_MAX_CHARS {
+            return Err(ToolError::InvalidArgs(format!(
+                "body is longer than {BODY_MAX_CHARS} characters"
+            )));
+        }
+        let urgency = match str_arg(args, "urgency") {
+            None => Urgency::Normal,
+            Some(name) => Urgency::parse(name).ok_or_else(|| {
+                ToolError::InvalidArgs(format!(
+                    "unknown urgency '{name}'; use low, normal or critical"
+                ))
+            })?,
+        };
+        Ok(Self {
+            title: title.to_string(),
+            body: body.to_string(),
+            urgency,
+            agent,
+        })
+    }
+
+    /// The title as shown: prefixed with the agent that sent it, so
+    /// notifications from several agents can be told apart.
+    fn display_title(&self) -> String {
+        match &self.agent {
+            Some(agent) => format!("{agent}: {}", self.title),
+            None => self.title.clone(),
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(n: &Notification) -> Result<(), String> {
+    let urgency = match n.urgency {
+        Urgency::Low => notify_rust::Urgency::Low,
+        Urgency::Normal => notify_rust::Urgency::Normal,
+        Urgency::Critical => notify_rust::Urgency::Critical,
+    };
+    notify_rust::Notification::new()
+        .appname("clawft")
+        .summary(&n.display_title())
+        .body(&n.body)
+        .urgency(urgency)
+        .show()
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn show(n: &Notification) -> Result<(), String> {
+    // macOS has no urgency levels; critical notifications play a sound.
+    let sound = if n.urgency == Urgency::Critical {
+        " sound name \"Glass\""
+    } else {
+        ""
+    };
+    // The text is passed as script arguments, never spliced into the script.
+    let script = format!(
+        "on run argv\ndisplay notification (item 2 of argv) with title (item 1 of argv){sound}\nend run"
+    );
+    run(
+        "osascript",
+        &["-e", &script, &n.display_title(), &n.body],
+        &[],
+    )
+}
+
+#[cfg(windows)]
+fn show(n: &Notification) -> Result<(), String> {
+    // Toasts have no urgency levels. The text is passed in environment
+    // variables, never spliced into the script.
+    const SCRIPT: &str = "\
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:CLAWFT_NOTIFY_TITLE)) > $null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:CLAWFT_NOTIFY_BODY)) > $null
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('clawft').Show([Windows.UI.Notifications.ToastNotification]::new($xml))";
+    run(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", SCRIPT],
+        &[
+            ("CLAWFT_NOTIFY_TITLE", &n.display_title()),
+            ("CLAWFT_NOTIFY_BODY", &n.body),
+        ],
+    )
+}
+
+#[cfg(not(any(unix, windows)))]
+fn show(_n: &Notification) -> Result<(), String> {
+    Err("desktop notifications are not supported on this platform".into())
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn run(program: &str, args: &[&str], envs: &[(&str, &str)]) -> Result<(), String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("cannot run {program}: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Show a desktop notification.
+///
+/// The notification title is prefixed with the id of the agent that sent
+/// it, taken from [`clawft_core::runtime::agent_id`].
+pub struct NotifyTool;
+
+impl NotifyTool {
+    /// Create a new `NotifyTool`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NotifyTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for NotifyTool {
+    fn name(&self) -> &str {
+        "notify"
+    }
+
+    fn description(&self) -> &str {
+        "Show a desktop notification to the user, e.g. when a long task finishes or needs \
+         their attention."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Notification title"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Notification text"
+                },
+                "urgency": {
+                    "type": "string",
+                    "enum": ["low", "normal", "critical"],
+                    "description": "How insistent the notification is (default normal)"
+                }
+            },
+            "required": ["title"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let notification = Notification::from_args(&args, clawft_core::runtime::agent_id())?;
+        let result = json!({
+            "shown": true,
+            "title": notification.display_title(),
+            "urgency": notification.urgency.as_str(),
+            "agent": notification.agent,
+        });
+        blocking("notification", move || {
+            show(&notification)
+                .map_err(|e| ToolError::ExecutionFailed(format!("cannot show notification: {e}")))
+        })
+        .await?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_only_opted_in_tools() {
+        let mut config = DesktopToolConfig::default();
+        let mut registry = ToolRegistry::new();
+        register(&mut registry, &config);
+        assert!(registry.is_empty());
+
+        config.notifications = true;
+        let mut registry = ToolRegistry::new();
+        register(&mut registry, &config);
+        assert_eq!(registry.list(), ["notify"]);
+
+        config.clipboard = true;
+        let mut registry = ToolRegistry::new();
+        register(&mut registry, &config);
+        assert!(registry.has("clipboard"));
+        assert!(registry.has("notify"));
+    }
+
+    #[tokio::test]
+    async fn clipboard_rejects_bad_arguments_before_opening_the_clipboard() {
+        let tool = ClipboardTool::new(8);
+        for args in [
+            json!({}),
+            json!({"operation": "clear"}),
+            json!({"operation": "set"}),
+            json!({"operation": "set", "text": "more than eight bytes"}),
+        ] {
+            let err = tool.execute(args.clone()).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{args}: {err:?}");
+        }
+        assert!(tool.clipboard.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        let mut text = "héllo".to_string();
+        assert!(truncate_to(&mut text, 2));
+        assert_eq!(text, "h");
+
+        let mut text = "hello".to_string();
+        assert!(!truncate_to(&mut text, 5));
+        assert_eq!(text, "hello");
+    }
+
+    #[test]
+    fn notification_validates_arguments() {
+        for args in [
+            json!({}),
+            json!({"title": "  "}),
+            json!({"title": "x".repeat(TITLE_MAX_CHARS + 1)}),
+            json!({"title": "done", "body": "x".repeat(BODY_MAX_CHARS + 1)}),
+            json!({"title": "done", "urgency": "urgent"}),
+        ] {
+            let err = Notification::from_args(&args, None).unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{args}: {err:?}");
+        }
+
+        let n = Notification::from_args(&json!({"title": " build done "}), None).unwrap();
+        assert_eq!(n.urgency, Urgency::Normal);
+        assert_eq!(n.body, "");
+        assert_eq!(n.display_title(), "build done");
+    }
+
+    #[test]
+    fn notification_title_names_the_agent() {
+        let args = json!({"title": "tests passed", "body": "42 ok", "urgency": "low"});
+        let n = Notification::from_args(&args, Some("researcher".into())).unwrap();
+        assert_eq!(n.display_title(), "researcher: tests passed");
+        assert_eq!(n.urgency, Urgency::Low);
+    }
+
+    #[tokio::test]
+    async fn notify_rejects_bad_arguments() {
+        let err = NotifyTool::new()
+            .execute(json!({"title": "done", "urgency": "loud"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+    }
+}
@@ -0,0 +1,770 @@
+//! File download tool.
+//!
+//! Provides a `download_file` tool that streams a URL into the workspace
+//! with the platform HTTP client's
+//! [`get_stream`](clawft_platform::http::HttpClient::get_stream).
+//!
+//! The body is written to `<dest>.part` and only moved into place once the
+//! size limit and any expected checksum have been checked; a file that
+//! fails either check is deleted. A transfer that breaks off leaves the
+//! `.part` file behind, and the next call continues it with a `Range`
+//! request when the server supports one. Redirects are followed here so
+//! every hop is checked against the [`UrlPolicy`].
+//!
+//! Downloads can be large, so no file work runs on the async runtime:
+//! chunks are written on a blocking thread ([`ChunkWriter`]) and the rest
+//! goes through `tokio::fs`.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc;
+
+use async_trait::async_trait;
+use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_platform::Platform;
+use clawft_platform::http::NO_REDIRECT_HEADER;
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha512};
+use tokio::fs;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::file_tools::validate_parent_path;
+use crate::redirects::{check_url, header, next_hop};
+use crate::url_safety::UrlPolicy;
+
+/// Default maximum download size in bytes (1 GB).
+const DEFAULT_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Checksum algorithms the tool can verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    fn hex_len(self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+/// Hex digest of the file at `path`.
+fn file_digest(path: &Path, algorithm: Algorithm) -> std::io::Result<String> {
+    fn hash<D: Digest>(mut file: File) -> std::io::Result<String> {
+        let mut hasher = D::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    }
+
+    let file = File::open(path)?;
+    match algorithm {
+        Algorithm::Sha256 => hash::<Sha256>(file),
+        Algorithm::Sha512 => hash::<Sha512>(file),
+    }
+}
+
+/// The expected checksum from `sha256` or `sha512`, if either is given.
+fn expected_digest(args: &serde_json::Value) -> Result<Option<(Algorithm, String)>, ToolError> {
+    let mut given = [Algorithm::Sha256, Algorithm::Sha512]
+        .into_iter()
+        .filter_map(|alg| {
+            args.get(alg.name())
+                .and_then(|v| v.as_str())
+                .map(|hex| (alg, hex.trim().to_ascii_lowercase()))
+        });
+    let Some((algorithm, hex)) = given.next() else {
+        return Ok(None);
+    };
+    if given.next().is_some() {
+        return Err(ToolError::InvalidArgs(
+            "pass only one of sha256 or sha512".into(),
+        ));
+    }
+    if hex.len() != algorithm.hex_len() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ToolError::InvalidArgs(format!(
+            "{} must be {} hex characters",
+            algorithm.name(),
+            algorithm.hex_len()
+        )));
+    }
+    Ok(Some((algorithm, hex)))
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn io_error(what: &str, e: std::io::Error) -> ToolError {
+    ToolError::ExecutionFailed(format!("{what}: {e}"))
+}
+
+/// Start offset of a `Content-Range: bytes START-END/TOTAL` header.
+fn range_start(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Total length of a `Content-Range: bytes START-END/TOTAL` or
+/// `bytes */TOTAL` header, when known.
+fn range_total(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .strip_prefix("bytes ")?
+        .rsplit_once('/')?
+        .1
+        .trim()
+        .parse()
+        .ok()
+}
+
+type SinkResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Writes streamed chunks to a file on a blocking thread.
+///
+/// The HTTP chunk sink is synchronous and runs on the async runtime, so it
+/// only queues each chunk. The queue is unbounded: the size limit caps how
+/// much can be queued, and disks outpace the network in practice.
+struct ChunkWriter {
+    chunks: mpsc::Sender<Vec<u8>>,
+    task: JoinHandle<std::io::Result<()>>,
+}
+
+impl ChunkWriter {
+    /// Create (or truncate) `path` and start the writer thread.
+    async fn create(path: &Path) -> std::io::Result<Self> {
+        let file = fs::File::create(path).await?.into_std().await;
+        let (chunks, queued) = mpsc::channel::<Vec<u8>>();
+        let task = tokio::task::spawn_blocking(move || {
+            let mut writer = BufWriter::new(file);
+            for chunk in queued {
+                writer.write_all(&chunk)?;
+            }
+            writer.flush()
+        });
+        Ok(Self { chunks, task })
+    }
+
+    /// Queue `chunk`. Fails once the writer thread has stopped on an error,
+    /// which [`finish`](Self::finish) then reports.
+    fn write(&self, chunk: &[u8]) -> SinkResult {
+        self.chunks
+            .send(chunk.to_vec())
+            .map_err(|_| "cannot write temp file".into())
+    }
+
+    /// Wait for every queued chunk to be written and the file flushed.
+    async fn finish(self) -> std::io::Result<()> {
+        drop(self.chunks);
+        self.task.await.map_err(std::io::Error::other)?
+    }
+}
+
+/// What one transfer left on disk.
+struct Transfer {
+    /// Final URL, after redirects.
+    url: Url,
+    /// Each redirect target, in order.
+    redirects: Vec<String>,
+    /// `Content-Type` of the final response.
+    content_type: String,
+    /// Whether an existing `.part` file was continued.
+    resumed: bool,
+}
+
+/// Download file tool.
+///
+/// Streams a URL to a workspace file with a size limit, optional
+/// checksum verification and resume support.
+pub struct DownloadFileTool<P: Platform> {
+    platform: Arc<P>,
+    workspace: PathBuf,
+    url_policy: UrlPolicy,
+    max_bytes: u64,
+}
+
+impl<P: Platform> DownloadFileTool<P> {
+    /// Create a new `DownloadFileTool` with a 1 GB size limit.
+    pub fn new(platform: Arc<P>, workspace: PathBuf, url_policy: UrlPolicy) -> Self {
+        Self {
+            platform,
+            workspace,
+            url_policy,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Set the largest file the tool will download.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Fetch `url` into `part`, following redirects and continuing an
+    /// existing partial file from `offset`. A partial file the server says
+    /// does not match the resource is deleted and fetched again in full.
+    async fn transfer(
+        &self,
+        mut url: Url,
+        part: &Path,
+        mut offset: u64,
+        max_bytes: u64,
+    ) -> Result<Transfer, ToolError> {
+        let incoming = sibling(part, ".incoming");
+        let mut redirects = Vec::new();
+        loop {
+            let mut headers = HashMap::from([(NO_REDIRECT_HEADER.to_string(), "1".to_string())]);
+            if offset > 0 {
+                headers.insert("Range".into(), format!("bytes={offset}-"));
+            }
+
+            let writer = ChunkWriter::create(&incoming)
+                .await
+                .map_err(|e| io_error("cannot create temp file", e))?;
+            let mut received = 0u64;
+            let mut too_large = false;
+            let mut sink = |chunk: &[u8]| -> SinkResult {
+                received += chunk.len() as u64;
+                if received > max_bytes {
+                    too_large = true;
+                    return Err("download exceeds size limit".into());
+                }
+                writer.write(chunk)
+            };
+            let streamed = self
+                .platform
+                .http()
+                .get_stream(url.as_str(), &headers, &mut sink)
+                .await;
+            let flushed = writer.finish().await;
+
+            let response = match streamed {
+                Ok(response) => response,
+                Err(e) => {
+                    // A fresh transfer cut off mid-stream is kept for a
+                    // later resume; a continuation of unknown status is not.
+                    if !too_large && offset == 0 && received > 0 && flushed.is_ok() {
+                        let _ = fs::rename(&incoming, part).await;
+                    } else {
+                        let _ = fs::remove_file(&incoming).await;
+                    }
+                    if too_large {
+                        let _ = fs::remove_file(part).await;
+                        return Err(ToolError::ExecutionFailed(format!(
+                            "download exceeds the {max_bytes} byte limit"
+                        )));
+                    }
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "download failed: {e} (partial data is kept; call again to resume)"
+                    )));
+                }
+            };
+            flushed.map_err(|e| io_error("cannot write temp file", e))?;
+
+            if let Some(next) = next_hop(
+                "download_file",
+                &self.url_policy,
+                &url,
+                response.status,
+                &response.headers,
+                redirects.len(),
+            )? {
+                debug!(from = %url, to = %next, status = response.status, "following redirect");
+                let _ = fs::remove_file(&incoming).await;
+                redirects.push(next.to_string());
+                url = next;
+                continue;
+            }
+
+            let content_type = header(&response.headers, "content-type")
+                .unwrap_or_default()
+                .to_string();
+            let resumed = match response.status {
+                416 if offset > 0 => {
+                    let _ = fs::remove_file(&incoming).await;
+                    let total = header(&response.headers, "content-range").and_then(range_total);
+                    if total != Some(offset) {
+                        // A leftover from another resource, not a finished
+                        // copy of this one.
+                        debug!(%url, ?total, partial = offset, "discarding mismatched partial file");
+                        let _ = fs::remove_file(part).await;
+                        offset = 0;
+                        continue;
+                    }
+                    // Nothing past our offset: the partial file is complete.
+                    true
+                }
+                206 if offset > 0 => {
+                    let start = header(&response.headers, "content-range").and_then(range_start);
+                    if start != Some(offset) {
+                        let _ = fs::remove_file(&incoming).await;
+                        let _ = fs::remove_file(part).await;
+                        return Err(ToolError::ExecutionFailed(format!(
+                            "server resumed at {start:?} instead of byte {offset}; \
+                             the partial file was discarded, call again to restart"
+                        )));
+                    }
+                    let appended = async {
+                        let mut src = fs::File::open(&incoming).await?;
+                        let mut dst = fs::OpenOptions::new().append(true).open(part).await?;
+                        tokio::io::copy(&mut src, &mut dst).await
+                    }
+                    .await;
+                    let _ = fs::remove_file(&incoming).await;
+                    appended.map_err(|e| io_error("cannot append to partial file", e))?;
+                    true
+                }
+                200..=299 => {
+                    fs::rename(&incoming, part)
+                        .await
+                        .map_err(|e| io_error("cannot move temp file", e))?;
+                    false
+                }
+                status => {
+                    let _ = fs::remove_file(&incoming).await;
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "server returned HTTP {status} for {url}"
+                    )));
+                }
+            };
+            return Ok(Transfer {
+                url,
+                redirects,
+                content_type,
+                resumed,
+            });
+        }
+    }
+}
+
+#[async_trait]
+impl<P: Platform + 'static> Tool for DownloadFileTool<P> {
+    fn name(&self) -> &str {
+        "download_file"
+    }
+
+    fn description(&self) -> &str {
+        "Download a URL to a file in the workspace. Optionally verifies a sha256 or sha512 \
+         checksum before the file is kept, and resumes interrupted downloads. Returns the \
+         size, content type and digest of the file."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "URL to download (http:// or https://)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Destination file path relative to the workspace"
+                },
+                "sha256": {
+                    "type": "string",
+                    "description": "Expected SHA-256 digest (hex). The file is deleted if it does not match."
+                },
+                "sha512": {
+                    "type": "string",
+                    "description": "Expected SHA-512 digest (hex). The file is deleted if it does not match."
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": format!("Largest file to accept (default and max {})", self.max_bytes)
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Replace an existing file at path (default false)"
+                },
+                "resume": {
+                    "type": "boolean",
+                    "description": "Continue a previously interrupted download of this path (default true)"
+                }
+            },
+            "required": ["url", "path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: url".into()))?;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(ToolError::InvalidArgs(
+                "url must start with http:// or https://".into(),
+            ));
+        }
+        check_url("download_file", url, &self.url_policy)?;
+        let parsed = Url::parse(url)
+            .map_err(|e| ToolError::InvalidArgs(format!("invalid url '{url}': {e}")))?;
+
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: path".into()))?;
+        let dest = validate_parent_path(path, &self.workspace)?;
+        let overwrite = args
+            .get("overwrite")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        match fs::metadata(&dest).await {
+            Ok(meta) if meta.is_dir() => {
+                return Err(ToolError::InvalidPath(format!("{path} is a directory")));
+            }
+            Ok(_) if !overwrite => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "{path} already exists; pass overwrite: true to replace it"
+                )));
+            }
+            _ => {}
+        }
+        let expected = expected_digest(&args)?;
+        let max_bytes = args
+            .get("max_bytes")
+            .and_then(|v| v.as_u64())
+            .map_or(self.max_bytes, |n| n.min(self.max_bytes));
+        let resume = args.get("resume").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("cannot create directory", e))?;
+        }
+        let part = sibling(&dest, ".part");
+        let mut offset = fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+        if offset > 0 && (!resume || offset > max_bytes) {
+            let _ = fs::remove_file(&part).await;
+            offset = 0;
+        }
+
+        debug!(url, path, offset, "downloading file");
+        let transfer = self.transfer(parsed, &part, offset, max_bytes).await?;
+
+        let bytes = fs::metadata(&part)
+            .await
+            .map_err(|e| io_error("cannot stat partial file", e))?
+            .len();
+        if bytes > max_bytes {
+            let _ = fs::remove_file(&part).await;
+            return Err(ToolError::ExecutionFailed(format!(
+                "download exceeds the {max_bytes} byte limit"
+            )));
+        }
+
+        let algorithm = expected.as_ref().map_or(Algorithm::Sha256, |(alg, _)| *alg);
+        let hash_path = part.clone();
+        let digest = tokio::task::spawn_blocking(move || file_digest(&hash_path, algorithm))
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("hash task failed: {e}")))?
+            .map_err(|e| io_error("cannot read downloaded file", e))?;
+        if let Some((_, want)) = &expected
+            && *want != digest
+        {
+            warn!(url, path, expected = %want, actual = %digest, "checksum mismatch");
+            let _ = fs::remove_file(&part).await;
+            return Err(ToolError::ExecutionFailed(format!(
+                "{} mismatch: expected {want}, got {digest}; the download was deleted",
+                algorithm.name()
+            )));
+        }
+
+        fs::rename(&part, &dest)
+            .await
+            .map_err(|e| io_error("cannot move file into place", e))?;
+
+        let mut result = json!({
+            "path": path,
+            "url": transfer.url.as_str(),
+            "bytes": bytes,
+            "content_type": transfer.content_type,
+            "resumed": transfer.resumed,
+            "verified": expected.is_some(),
+        });
+        result[algorithm.name()] = json!(digest);
+        if !transfer.redirects.is_empty() {
+            result["redirects"] = json!(transfer.redirects);
+        }
+        Ok(result)
+    }
+
+    /// Concurrent downloads to the same path would share a `.part` file.
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::testing::{MockPlatform, MockResponse};
+    use std::fs;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_workspace() -> PathBuf {
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let ws = std::env::temp_dir().join(format!("clawft_download_test_{pid}_{id}"));
+        fs::create_dir_all(&ws).unwrap();
+        ws
+    }
+
+    fn tool(platform: &Arc<MockPlatform>, ws: &Path) -> DownloadFileTool<MockPlatform> {
+        let policy = UrlPolicy {
+            allowed_domains: ["files.example.com".to_string()].into(),
+            ..UrlPolicy::default()
+        };
+        DownloadFileTool::new(platform.clone(), ws.to_path_buf(), policy)
+    }
+
+    fn sha256_hex(data: &[u8]) -> String {
+        Sha256::digest(data)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_checksum() {
+        let ws = temp_workspace();
+        let platform = Arc::new(MockPlatform::new());
+        let body = b"release tarball contents".to_vec();
+        platform.http.respond(
+            "GET https://files.example.com/v1.tar.gz",
+            MockResponse::bytes(200, body.clone()).with_header("Content-Type", "application/gzip"),
+        );
+
+        let result = tool(&platform, &ws)
+            .execute(json!({
+                "url": "https://files.example.com/v1.tar.gz",
+                "path": "dist/v1.tar.gz",
+                "sha256": sha256_hex(&body).to_uppercase()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["bytes"], body.len());
+        assert_eq!(result["content_type"], "application/gzip");
+        assert_eq!(result["sha256"], sha256_hex(&body));
+        assert_eq!(result["verified"], true);
+        assert_eq!(fs::read(ws.join("dist/v1.tar.gz")).unwrap(), body);
+        assert!(!ws.join("dist/v1.tar.gz.part").exists());
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch_deletes_the_download() {
+        let ws = temp_workspace();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/tampered.bin",
+            MockResponse::bytes(200, b"not what you expected".to_vec()),
+        );
+
+        let err = tool(&platform, &ws)
+            .execute(json!({
+                "url": "https://files.example.com/tampered.bin",
+                "path": "tampered.bin",
+                "sha256": sha256_hex(b"the real thing")
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sha256 mismatch"), "{err}");
+        assert!(!ws.join("tampered.bin").exists());
+        assert!(!ws.join("tampered.bin.part").exists());
+        assert!(!ws.join("tampered.bin.part.incoming").exists());
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn size_cap_aborts_and_cleans_up() {
+        let ws = temp_workspace();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/big.iso",
+            MockResponse::bytes(200, vec![7u8; 4096]),
+        );
+
+        let err = tool(&platform, &ws)
+            .execute(json!({
+                "url": "https://files.example.com/big.iso",
+                "path": "big.iso",
+                "max_bytes": 1024
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1024 byte limit"), "{err}");
+        let leftovers: Vec<_> = fs::read_dir(&ws).unwrap().collect();
+        assert!(leftovers.is_empty());
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn resumes_partial_file_with_range_request() {
+        let ws = temp_workspace();
+        fs::write(ws.join("data.csv.part"), b"hello ").unwrap();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/data.csv",
+            MockResponse::bytes(206, b"world".to_vec())
+                .with_header("Content-Range", "bytes 6-10/11"),
+        );
+
+        let result = tool(&platform, &ws)
+            .execute(json!({"url": "https://files.example.com/data.csv", "path": "data.csv"}))
+            .await
+            .unwrap();
+        assert_eq!(result["resumed"], true);
+        assert_eq!(result["sha256"], sha256_hex(b"hello world"));
+        assert_eq!(fs::read(ws.join("data.csv")).unwrap(), b"hello world");
+        let sent = &platform.http.requests()[0];
+        assert_eq!(sent.headers["Range"], "bytes=6-");
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn restarts_when_range_is_ignored() {
+        let ws = temp_workspace();
+        fs::write(ws.join("data.csv.part"), b"stale").unwrap();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/data.csv",
+            MockResponse::bytes(200, b"fresh copy".to_vec()),
+        );
+
+        let result = tool(&platform, &ws)
+            .execute(json!({"url": "https://files.example.com/data.csv", "path": "data.csv"}))
+            .await
+            .unwrap();
+        assert_eq!(result["resumed"], false);
+        assert_eq!(fs::read(ws.join("data.csv")).unwrap(), b"fresh copy");
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn complete_partial_file_is_kept_on_416() {
+        let ws = temp_workspace();
+        fs::write(ws.join("data.csv.part"), b"hello world").unwrap();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/data.csv",
+            MockResponse::bytes(416, Vec::new()).with_header("Content-Range", "bytes */11"),
+        );
+
+        let result = tool(&platform, &ws)
+            .execute(json!({"url": "https://files.example.com/data.csv", "path": "data.csv"}))
+            .await
+            .unwrap();
+        assert_eq!(result["resumed"], true);
+        assert_eq!(fs::read(ws.join("data.csv")).unwrap(), b"hello world");
+        assert_eq!(platform.http.requests().len(), 1);
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn mismatched_partial_file_is_restarted_on_416() {
+        let ws = temp_workspace();
+        fs::write(ws.join("data.csv.part"), b"leftover from another file").unwrap();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/data.csv",
+            MockResponse::bytes(416, Vec::new()).with_header("Content-Range", "bytes */10"),
+        );
+        platform.http.respond(
+            "https://files.example.com/data.csv",
+            MockResponse::bytes(200, b"fresh copy".to_vec()),
+        );
+
+        let result = tool(&platform, &ws)
+            .execute(json!({"url": "https://files.example.com/data.csv", "path": "data.csv"}))
+            .await
+            .unwrap();
+        assert_eq!(result["resumed"], false);
+        assert_eq!(fs::read(ws.join("data.csv")).unwrap(), b"fresh copy");
+        let requests = platform.http.requests();
+        assert_eq!(requests[0].headers["Range"], "bytes=26-");
+        assert!(!requests[1].headers.contains_key("Range"));
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn rejects_blocked_redirects_and_escaping_paths() {
+        let ws = temp_workspace();
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://files.example.com/latest",
+            MockResponse::text(302, "").with_header("Location", "http://169.254.169.254/"),
+        );
+        let tool = tool(&platform, &ws);
+
+        let err = tool
+            .execute(json!({"url": "https://files.example.com/latest", "path": "latest"}))
+            .await
+            .unwrap_err();
+        match err {
+            ToolError::PermissionDenied { reason, .. } => {
+                assert!(reason.contains("redirect from"), "{reason}")
+            }
+            other => panic!("expected PermissionDenied, got {other:?}"),
+        }
+        assert_eq!(platform.http.requests().len(), 1);
+
+        let err = tool
+            .execute(json!({"url": "https://files.example.com/x", "path": "../outside"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidPath(_)));
+
+        fs::write(ws.join("kept.txt"), b"mine").unwrap();
+        let err = tool
+            .execute(json!({"url": "https://files.example.com/x", "path": "kept.txt"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        let _ = fs::remove_dir_all(&ws);
+    }
+}
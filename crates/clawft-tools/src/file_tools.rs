@@ -10,9 +10,12 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use clawft_core::tools::registry::{Tool, ToolError};
 use clawft_platform::Platform;
+use clawft_platform::fs::walk::{WalkOptions, glob_match};
 use serde_json::json;
 use tracing::debug;
 
+use crate::text_edit::{self, Edit};
+
 /// Resolve a path to its canonical form.
 ///
 /// On native targets this follows symlinks via `std::fs::canonicalize`.
@@ -65,7 +68,7 @@ fn path_exists(_path: &Path) -> bool {
 ///
 /// Returns the canonical path on success, or a [`ToolError`] if the path
 /// escapes the workspace or does not exist.
-fn validate_path(path: &str, workspace: &Path) -> Result<PathBuf, ToolError> {
+pub(crate) fn validate_path(path: &str, workspace: &Path) -> Result<PathBuf, ToolError> {
     let resolved = workspace.join(path);
     let canonical = resolve_sandbox_path(&resolved)
         .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
@@ -84,7 +87,7 @@ fn validate_path(path: &str, workspace: &Path) -> Result<PathBuf, ToolError> {
 
 /// Validate that a parent directory is within workspace, for paths that
 /// do not yet exist (write operations creating new files).
-fn validate_parent_path(path: &str, workspace: &Path) -> Result<PathBuf, ToolError> {
+pub(crate) fn validate_parent_path(path: &str, workspace: &Path) -> Result<PathBuf, ToolError> {
     let resolved = workspace.join(path);
 
     // Find the deepest existing ancestor and canonicalize from there.
@@ -121,14 +124,123 @@ fn required_str(args: &serde_json::Value, field: &str) -> Result<String, ToolErr
         .ok_or_else(|| ToolError::InvalidArgs(format!("missing required field: {}", field)))
 }
 
+/// Extract an optional non-negative integer field from a JSON arguments object.
+fn optional_usize(args: &serde_json::Value, field: &str) -> Result<Option<usize>, ToolError> {
+    match args.get(field) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => v.as_u64().map(|n| Some(n as usize)).ok_or_else(|| {
+            ToolError::InvalidArgs(format!("{field} must be a non-negative integer"))
+        }),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ReadFileTool
 // ---------------------------------------------------------------------------
 
+/// Default `max_bytes` for `read_file`, leaving room for the rest of the
+/// result under the agent loop's 64 KB tool-result cap.
+const READ_MAX_BYTES: usize = 48 * 1024;
+
+/// Leading bytes inspected when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Leading bytes shown, in hex, for a binary file.
+const HEX_PREVIEW_BYTES: usize = 64;
+
+/// Whether `bytes` look like binary data rather than text.
+///
+/// A NUL byte in the leading [`BINARY_SNIFF_BYTES`] marks the file as
+/// binary, as does more than 10% of that sample failing to decode as UTF-8.
+fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sample.contains(&0) {
+        return true;
+    }
+    let text = String::from_utf8_lossy(sample);
+    let total = text.chars().count();
+    let invalid = text
+        .chars()
+        .filter(|&c| c == char::REPLACEMENT_CHARACTER)
+        .count();
+    invalid * 10 > total
+}
+
+/// Space-separated hex of the first [`HEX_PREVIEW_BYTES`] of `bytes`.
+fn hex_preview(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take(HEX_PREVIEW_BYTES)
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The 1-based, inclusive line span `read_file` returns out of `total`
+/// lines. The span is empty (`start > end`) when it lies past the end.
+fn select_lines(args: &serde_json::Value, total: usize) -> Result<(usize, usize), ToolError> {
+    let start = optional_usize(args, "start_line")?;
+    let end = optional_usize(args, "end_line")?;
+    let head = optional_usize(args, "head")?;
+    let tail = optional_usize(args, "tail")?;
+
+    let ranged = start.is_some() || end.is_some();
+    match (head, tail) {
+        (Some(_), Some(_)) => Err(ToolError::InvalidArgs(
+            "head and tail cannot be combined".into(),
+        )),
+        (Some(_), None) | (None, Some(_)) if ranged => Err(ToolError::InvalidArgs(
+            "head/tail cannot be combined with start_line/end_line".into(),
+        )),
+        (Some(n), None) => Ok((1, n.min(total))),
+        (None, Some(n)) => Ok((total - n.min(total) + 1, total)),
+        (None, None) => {
+            if let (Some(s), Some(e)) = (start, end)
+                && s > e
+            {
+                return Err(ToolError::InvalidArgs(format!(
+                    "start_line ({s}) is after end_line ({e})"
+                )));
+            }
+            Ok((start.unwrap_or(1).max(1), end.unwrap_or(total).min(total)))
+        }
+    }
+}
+
+/// Cut the middle out of `text` so it fits in about `max_bytes`, keeping
+/// whole lines at both ends and marking what was left out.
+///
+/// Returns the text unchanged when it already fits.
+fn truncate_middle(text: &str, max_bytes: usize) -> (String, bool) {
+    if text.len() <= max_bytes {
+        return (text.to_string(), false);
+    }
+    let mut head_end = text.floor_char_boundary(max_bytes / 2);
+    if let Some(nl) = text[..head_end].rfind('\n') {
+        head_end = nl + 1;
+    }
+    let mut tail_start = text.ceil_char_boundary(text.len() - (max_bytes - head_end));
+    if let Some(nl) = text[tail_start..].find('\n') {
+        tail_start += nl + 1;
+    }
+    let omitted = &text[head_end..tail_start];
+    let marker = format!(
+        "[... {} lines ({} bytes) omitted; read them with start_line/end_line ...]\n",
+        omitted.matches('\n').count(),
+        omitted.len()
+    );
+    (
+        format!("{}{marker}{}", &text[..head_end], &text[tail_start..]),
+        true,
+    )
+}
+
 /// Read the contents of a file within the workspace.
 ///
-/// Returns the file content as a string value. Rejects paths that escape
-/// the configured workspace directory.
+/// Returns a line range of the file (the whole file by default) along
+/// with its total line count, cutting the middle out of text longer than
+/// `max_bytes`. Binary files are reported by size and a hex preview
+/// instead. Rejects paths that escape the configured workspace directory.
 pub struct ReadFileTool<P: Platform> {
     platform: Arc<P>,
     workspace: PathBuf,
@@ -152,7 +264,9 @@ impl<P: Platform + 'static> Tool for ReadFileTool<P> {
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file at the given path."
+        "Read the contents of a file at the given path. Reports the file's \
+         total line count; use start_line/end_line, head or tail to read \
+         part of a large file. Binary files return their size and a hex preview."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -162,6 +276,26 @@ impl<P: Platform + 'static> Tool for ReadFileTool<P> {
                 "path": {
                     "type": "string",
                     "description": "The file path to read (relative to workspace)"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line to read (1-based, inclusive)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line to read (1-based, inclusive)"
+                },
+                "head": {
+                    "type": "integer",
+                    "description": "Read only the first N lines"
+                },
+                "tail": {
+                    "type": "integer",
+                    "description": "Read only the last N lines"
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": "Most bytes of content to return; the middle of longer text is replaced by a marker (default: 49152)"
                 }
             },
             "required": ["path"]
@@ -171,17 +305,46 @@ impl<P: Platform + 'static> Tool for ReadFileTool<P> {
     async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
         let path_str = required_str(&args, "path")?;
         let canonical = validate_path(&path_str, &self.workspace)?;
+        let max_bytes = optional_usize(&args, "max_bytes")?
+            .unwrap_or(READ_MAX_BYTES)
+            .max(1);
 
         debug!(path = %canonical.display(), "reading file");
 
-        let content = self
+        let bytes = self
             .platform
             .fs()
-            .read_to_string(&canonical)
+            .read_bytes(&canonical)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("read failed: {}", e)))?;
 
-        Ok(json!({ "content": content }))
+        if looks_binary(&bytes) {
+            return Ok(json!({
+                "binary": true,
+                "size": bytes.len(),
+                "hex_preview": hex_preview(&bytes),
+                "message": format!("{path_str} is a binary file ({} bytes); not shown as text", bytes.len()),
+            }));
+        }
+
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let total = lines.len();
+        let (start, end) = select_lines(&args, total)?;
+        let selected = if start <= end {
+            lines[start - 1..end].concat()
+        } else {
+            String::new()
+        };
+        let (content, truncated) = truncate_middle(&selected, max_bytes);
+
+        Ok(json!({
+            "content": content,
+            "total_lines": total,
+            "start_line": start,
+            "end_line": end,
+            "truncated": truncated,
+        }))
     }
 }
 
@@ -189,10 +352,78 @@ impl<P: Platform + 'static> Tool for ReadFileTool<P> {
 // WriteFileTool
 // ---------------------------------------------------------------------------
 
+/// Most diff lines `write_file` returns when it replaces a file.
+const WRITE_DIFF_PREVIEW_LINES: usize = 40;
+
+/// How `write_file` treats an existing file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteMode {
+    /// Replace the file; an existing file with different content needs
+    /// `overwrite: true`.
+    Overwrite,
+    /// Add to the end of the file, creating it if missing.
+    Append,
+    /// Create the file; fail if it exists.
+    CreateNew,
+}
+
+impl WriteMode {
+    fn parse(args: &serde_json::Value) -> Result<Self, ToolError> {
+        match args.get("mode").and_then(|v| v.as_str()) {
+            None | Some("overwrite") => Ok(Self::Overwrite),
+            Some("append") => Ok(Self::Append),
+            Some("create_new") => Ok(Self::CreateNew),
+            Some(other) => Err(ToolError::InvalidArgs(format!(
+                "unknown mode '{other}'; use overwrite, append or create_new"
+            ))),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Overwrite => "overwrite",
+            Self::Append => "append",
+            Self::CreateNew => "create_new",
+        }
+    }
+}
+
+/// Summary of what replacing `old` with `new` changed: line counts and the
+/// start of a unified diff. `None` when the old file was empty or not
+/// text.
+fn overwrite_diff(path: &str, old: &[u8], new: &str) -> Option<serde_json::Value> {
+    let old = std::str::from_utf8(old).ok()?;
+    let replacement = text_edit::rewrite(old, new)?;
+    let diff = text_edit::unified_diff(path, old, &[replacement]);
+    let body = || diff.lines().skip(2);
+    let removed = body().filter(|l| l.starts_with('-')).count();
+    let added = body().filter(|l| l.starts_with('+')).count();
+    let total = diff.lines().count();
+    let mut preview: String = diff
+        .split_inclusive('\n')
+        .take(WRITE_DIFF_PREVIEW_LINES)
+        .collect();
+    if total > WRITE_DIFF_PREVIEW_LINES {
+        preview.push_str(&format!(
+            "... ({} more diff lines)\n",
+            total - WRITE_DIFF_PREVIEW_LINES
+        ));
+    }
+    Some(json!({
+        "lines_removed": removed,
+        "lines_added": added,
+        "preview": preview,
+    }))
+}
+
 /// Write content to a file within the workspace.
 ///
-/// Creates parent directories if they do not exist. Overwrites the file
-/// if it already exists. Rejects paths that escape the workspace.
+/// `mode` picks what happens to an existing file: `overwrite` (the
+/// default) replaces it atomically but only with `overwrite: true` when
+/// its content differs, `append` adds to it, and `create_new` refuses it.
+/// Replacing a file reports its previous size and a short diff, so the
+/// transcript shows what was lost. Parent directories are created unless
+/// `create_dirs` is false. Rejects paths that escape the workspace.
 pub struct WriteFileTool<P: Platform> {
     platform: Arc<P>,
     workspace: PathBuf,
@@ -216,7 +447,9 @@ impl<P: Platform + 'static> Tool for WriteFileTool<P> {
     }
 
     fn description(&self) -> &str {
-        "Write content to a file at the given path. Creates parent directories if needed."
+        "Write content to a file at the given path. Replacing an existing file with different \
+         content requires overwrite: true; use mode 'append' to add to a file or 'create_new' \
+         to fail if it exists. Creates parent directories unless create_dirs is false."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -230,6 +463,19 @@ impl<P: Platform + 'static> Tool for WriteFileTool<P> {
                 "content": {
                     "type": "string",
                     "description": "The content to write"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["overwrite", "append", "create_new"],
+                    "description": "overwrite (default) replaces the file, append adds to its end (creating it if missing), create_new fails if the file exists"
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Confirm replacing an existing file whose content differs (default: false)"
+                },
+                "create_dirs": {
+                    "type": "boolean",
+                    "description": "Create missing parent directories (default: true)"
                 }
             },
             "required": ["path", "content"]
@@ -239,19 +485,98 @@ impl<P: Platform + 'static> Tool for WriteFileTool<P> {
     async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
         let path_str = required_str(&args, "path")?;
         let content = required_str(&args, "content")?;
+        let mode = WriteMode::parse(&args)?;
+        let flag =
+            |name: &str, default: bool| args.get(name).and_then(|v| v.as_bool()).unwrap_or(default);
+        let confirmed = flag("overwrite", false);
+        let create_dirs = flag("create_dirs", true);
         let target = validate_parent_path(&path_str, &self.workspace)?;
+        let fs = self.platform.fs();
 
-        debug!(path = %target.display(), bytes = content.len(), "writing file");
+        debug!(path = %target.display(), bytes = content.len(), mode = mode.as_str(), "writing file");
 
-        self.platform
-            .fs()
-            .write_string(&target, &content)
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("write failed: {}", e)))?;
+        if !create_dirs
+            && let Some(parent) = target.parent()
+            && !fs.exists(parent).await
+        {
+            return Err(ToolError::ExecutionFailed(format!(
+                "parent directory of {path_str} does not exist; set create_dirs: true to create it"
+            )));
+        }
 
-        Ok(json!({
-            "message": format!("Successfully wrote {} bytes to {}", content.len(), path_str)
-        }))
+        let previous = if fs.exists(&target).await {
+            Some(
+                fs.read_bytes(&target)
+                    .await
+                    .map_err(|e| ToolError::ExecutionFailed(format!("read failed: {}", e)))?,
+            )
+        } else {
+            None
+        };
+        let write_failed =
+            |e: std::io::Error| ToolError::ExecutionFailed(format!("write failed: {}", e));
+
+        let mut result = json!({ "path": path_str, "mode": mode.as_str(), "bytes": content.len() });
+        match (mode, &previous) {
+            (WriteMode::CreateNew, Some(old)) => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "{path_str} already exists ({} bytes); mode create_new only writes new files",
+                    old.len()
+                )));
+            }
+            (WriteMode::Overwrite, Some(old)) if old == content.as_bytes() => {
+                result["message"] = json!(format!(
+                    "{path_str} already has this content; nothing written"
+                ));
+                result["unchanged"] = json!(true);
+                return Ok(result);
+            }
+            (WriteMode::Overwrite, Some(old)) if !confirmed => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "{path_str} already exists ({} bytes) with different content; set \
+                     overwrite: true to replace it, or use mode append",
+                    old.len()
+                )));
+            }
+            (WriteMode::Append, _) => {
+                fs.append_string(&target, &content)
+                    .await
+                    .map_err(write_failed)?;
+                result["message"] = json!(format!(
+                    "Successfully appended {} bytes to {}",
+                    content.len(),
+                    path_str
+                ));
+            }
+            (WriteMode::Overwrite | WriteMode::CreateNew, _) => {
+                fs.write_atomic(&target, &content)
+                    .await
+                    .map_err(write_failed)?;
+                result["message"] = json!(format!(
+                    "Successfully wrote {} bytes to {}",
+                    content.len(),
+                    path_str
+                ));
+            }
+        }
+
+        match &previous {
+            None => result["created"] = json!(true),
+            Some(old) => {
+                result["previous_bytes"] = json!(old.len());
+                if mode == WriteMode::Overwrite
+                    && let Some(diff) = overwrite_diff(&path_str, old, &content)
+                {
+                    result["diff"] = diff;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Concurrent writes to the same path would race.
+    fn parallel_safe(&self) -> bool {
+        false
     }
 }
 
@@ -259,10 +584,48 @@ impl<P: Platform + 'static> Tool for WriteFileTool<P> {
 // EditFileTool
 // ---------------------------------------------------------------------------
 
-/// Edit a file by replacing the first occurrence of `old_text` with `new_text`.
+/// Parse `edit_file` arguments into edits: either an `edits` array or a
+/// single top-level `old_text`/`new_text` pair.
+fn parse_edits(args: &serde_json::Value) -> Result<Vec<Edit>, ToolError> {
+    let parse_one = |value: &serde_json::Value| -> Result<Edit, ToolError> {
+        Ok(Edit {
+            old_text: required_str(value, "old_text")?,
+            new_text: required_str(value, "new_text")?,
+            occurrence: optional_usize(value, "occurrence")?,
+        })
+    };
+    let Some(edits) = args.get("edits") else {
+        return Ok(vec![parse_one(args)?]);
+    };
+    if args.get("old_text").is_some() {
+        return Err(ToolError::InvalidArgs(
+            "use either edits or old_text/new_text, not both".into(),
+        ));
+    }
+    let edits = edits
+        .as_array()
+        .filter(|edits| !edits.is_empty())
+        .ok_or_else(|| ToolError::InvalidArgs("edits must be a non-empty array".into()))?;
+    edits
+        .iter()
+        .enumerate()
+        .map(|(i, edit)| {
+            parse_one(edit).map_err(|e| match e {
+                ToolError::InvalidArgs(msg) => ToolError::InvalidArgs(format!("edits[{i}]: {msg}")),
+                other => other,
+            })
+        })
+        .collect()
+}
+
+/// Edit a file by search/replace.
 ///
-/// The file must exist and must contain exactly one occurrence of `old_text`.
-/// Rejects paths that escape the workspace.
+/// Takes one `old_text`/`new_text` pair or an `edits` array. Each
+/// `old_text` must match exactly once in the original file unless an
+/// `occurrence` picks one match. All edits are located before any is
+/// applied, so a call changes all of them or nothing; `dry_run` returns
+/// a unified diff instead of writing. Rejects paths that escape the
+/// workspace.
 pub struct EditFileTool<P: Platform> {
     platform: Arc<P>,
     workspace: PathBuf,
@@ -286,10 +649,16 @@ impl<P: Platform + 'static> Tool for EditFileTool<P> {
     }
 
     fn description(&self) -> &str {
-        "Edit a file by replacing old_text with new_text. The old_text must exist exactly once in the file."
+        "Edit a file by replacing old_text with new_text, or apply several such edits at once \
+         with `edits`. Each old_text must exist exactly once in the file unless `occurrence` \
+         picks a match. Edits apply all-or-nothing; set dry_run to preview a unified diff."
     }
 
     fn parameters(&self) -> serde_json::Value {
+        let occurrence = json!({
+            "type": "integer",
+            "description": "Which match of old_text to replace (1-based), when it appears more than once"
+        });
         json!({
             "type": "object",
             "properties": {
@@ -304,19 +673,40 @@ impl<P: Platform + 'static> Tool for EditFileTool<P> {
                 "new_text": {
                     "type": "string",
                     "description": "The text to replace with"
+                },
+                "occurrence": occurrence,
+                "edits": {
+                    "type": "array",
+                    "description": "Several edits, used instead of old_text/new_text. Every old_text refers to the file before any edit.",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "old_text": { "type": "string" },
+                            "new_text": { "type": "string" },
+                            "occurrence": occurrence
+                        },
+                        "required": ["old_text", "new_text"]
+                    }
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Return a unified diff of the change without writing the file (default: false)"
                 }
             },
-            "required": ["path", "old_text", "new_text"]
+            "required": ["path"]
         })
     }
 
     async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
         let path_str = required_str(&args, "path")?;
-        let old_text = required_str(&args, "old_text")?;
-        let new_text = required_str(&args, "new_text")?;
+        let edits = parse_edits(&args)?;
+        let dry_run = args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
         let canonical = validate_path(&path_str, &self.workspace)?;
 
-        debug!(path = %canonical.display(), "editing file");
+        debug!(path = %canonical.display(), edits = edits.len(), dry_run, "editing file");
 
         let content = self
             .platform
@@ -325,41 +715,113 @@ impl<P: Platform + 'static> Tool for EditFileTool<P> {
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("read failed: {}", e)))?;
 
-        let count = content.matches(&old_text).count();
-        if count == 0 {
-            return Err(ToolError::InvalidArgs(
-                "old_text not found in file".to_string(),
-            ));
-        }
-        if count > 1 {
-            return Err(ToolError::InvalidArgs(format!(
-                "old_text appears {} times; provide more context to make it unique",
-                count
-            )));
-        }
+        let single = args.get("edits").is_none();
+        let replacements = text_edit::locate(&content, &edits, |i| {
+            if single {
+                "old_text".to_string()
+            } else {
+                format!("edits[{i}].old_text")
+            }
+        })?;
 
-        let new_content = content.replacen(&old_text, &new_text, 1);
+        if dry_run {
+            return Ok(json!({
+                "dry_run": true,
+                "diff": text_edit::unified_diff(&path_str, &content, &replacements),
+                "message": format!("Dry run: {} edit(s) to {} not written", edits.len(), path_str)
+            }));
+        }
 
+        let new_content = text_edit::apply(&content, &replacements);
         self.platform
             .fs()
-            .write_string(&canonical, &new_content)
+            .write_atomic(&canonical, &new_content)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("write failed: {}", e)))?;
 
         Ok(json!({
-            "message": format!("Successfully edited {}", path_str)
+            "message": format!("Successfully edited {}", path_str),
+            "edits": edits.len()
         }))
     }
+
+    /// Concurrent edits to the same file would race.
+    fn parallel_safe(&self) -> bool {
+        false
+    }
 }
 
 // ---------------------------------------------------------------------------
 // ListDirectoryTool
 // ---------------------------------------------------------------------------
 
+/// Default `max_depth` for a recursive `list_directory`.
+const LIST_DEFAULT_DEPTH: usize = 3;
+
+/// Default `max_entries` for `list_directory`.
+const LIST_DEFAULT_ENTRIES: usize = 500;
+
+/// Path of `path` relative to `root`, with `/` separators.
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether `relative` passes a `list_directory` glob. A pattern without
+/// `/` is matched against the entry name, anything else against the whole
+/// relative path.
+fn list_glob_matches(pattern: &str, relative: &str) -> bool {
+    if pattern.contains('/') {
+        glob_match(pattern, relative)
+    } else {
+        glob_match(pattern, relative.rsplit('/').next().unwrap_or(relative))
+    }
+}
+
+/// Indented tree of `/`-separated relative paths, given in walk order.
+///
+/// Directories get a trailing `/`. Parents of listed entries that were
+/// not themselves listed (e.g. filtered out by a glob) are still shown
+/// so every entry appears under its directory.
+fn tree_summary(entries: &[(String, bool)], truncated: bool) -> String {
+    let mut out = String::new();
+    let mut open: Vec<&str> = Vec::new();
+    for (relative, is_dir) in entries {
+        let parts: Vec<&str> = relative.split('/').collect();
+        let (name, parents) = parts.split_last().expect("split yields one part");
+        let shared = open.iter().zip(parents).take_while(|(a, b)| a == b).count();
+        open.truncate(shared);
+        for parent in &parents[shared..] {
+            out.push_str(&format!("{}{parent}/\n", "  ".repeat(open.len())));
+            open.push(parent);
+        }
+        let suffix = if *is_dir { "/" } else { "" };
+        out.push_str(&format!("{}{name}{suffix}\n", "  ".repeat(open.len())));
+        if *is_dir {
+            open.push(name);
+        }
+    }
+    if truncated {
+        out.push_str(&format!("... (stopped at {} entries)\n", entries.len()));
+    }
+    out
+}
+
 /// List the contents of a directory within the workspace.
 ///
-/// Returns a JSON array of entries with `name`, `is_dir`, and `size` fields.
-/// Rejects paths that escape the workspace.
+/// Lists direct children by default, or the tree below the directory with
+/// `recursive` (to `max_depth`). Each entry carries its relative path,
+/// type, size and modification time; a tree-formatted `summary` covers
+/// the same entries in fewer tokens. Hidden entries are skipped unless
+/// `include_hidden` is set, and at most `max_entries` are returned.
+///
+/// Rejects paths that escape the workspace. During recursion, symlinks
+/// that resolve outside the listed directory are left out and symlinked
+/// directories are not descended into.
 pub struct ListDirectoryTool<P: Platform> {
     platform: Arc<P>,
     workspace: PathBuf,
@@ -383,7 +845,8 @@ impl<P: Platform + 'static> Tool for ListDirectoryTool<P> {
     }
 
     fn description(&self) -> &str {
-        "List the contents of a directory with metadata (name, is_dir, size)."
+        "List the contents of a directory with metadata (path, type, size, mtime). \
+         Set recursive to list the tree below it; the summary field is a compact tree view."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -393,6 +856,26 @@ impl<P: Platform + 'static> Tool for ListDirectoryTool<P> {
                 "path": {
                     "type": "string",
                     "description": "The directory path to list (relative to workspace)"
+                },
+                "recursive": {
+                    "type": "boolean",
+                    "description": "List subdirectories too (default: false)"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "Levels to descend when recursive; 1 = direct children (default: 3)"
+                },
+                "glob": {
+                    "type": "string",
+                    "description": "Only list entries matching this pattern, e.g. \"*.rs\" (name) or \"src/**/*.rs\" (relative path)"
+                },
+                "include_hidden": {
+                    "type": "boolean",
+                    "description": "Include entries whose name starts with '.' (default: false)"
+                },
+                "max_entries": {
+                    "type": "integer",
+                    "description": "Most entries to return (default: 500)"
                 }
             },
             "required": ["path"]
@@ -401,50 +884,107 @@ impl<P: Platform + 'static> Tool for ListDirectoryTool<P> {
 
     async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
         let path_str = required_str(&args, "path")?;
+        let recursive = args
+            .get("recursive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let include_hidden = args
+            .get("include_hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let glob = args.get("glob").and_then(|v| v.as_str());
+        let max_depth = if recursive {
+            optional_usize(&args, "max_depth")?
+                .unwrap_or(LIST_DEFAULT_DEPTH)
+                .max(1)
+        } else {
+            1
+        };
+        let max_entries = optional_usize(&args, "max_entries")?
+            .unwrap_or(LIST_DEFAULT_ENTRIES)
+            .max(1);
         let canonical = validate_path(&path_str, &self.workspace)?;
 
-        debug!(path = %canonical.display(), "listing directory");
+        debug!(path = %canonical.display(), recursive, max_depth, "listing directory");
 
-        let entries = self
-            .platform
-            .fs()
-            .list_dir(&canonical)
+        let fs = self.platform.fs();
+        let meta = fs
+            .metadata(&canonical)
             .await
             .map_err(|e| ToolError::ExecutionFailed(format!("list_dir failed: {}", e)))?;
+        if !meta.is_dir {
+            return Err(ToolError::ExecutionFailed(format!(
+                "list_dir failed: {path_str} is not a directory"
+            )));
+        }
 
-        let mut result = Vec::new();
-        for entry_path in &entries {
-            let name = entry_path
+        let options = WalkOptions {
+            max_depth: Some(max_depth),
+            follow_symlinks: false,
+            ignore: if include_hidden {
+                Vec::new()
+            } else {
+                vec![".*".to_string()]
+            },
+            // One past the cap, so a full listing can tell it was cut short.
+            max_results: glob.is_none().then_some(max_entries + 1),
+            include_dirs: true,
+        };
+        let walked = fs
+            .walk(&canonical, &options)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("list_dir failed: {}", e)))?;
+
+        let mut entries = Vec::new();
+        let mut listed = Vec::new();
+        let mut truncated = false;
+        for entry in &walked.entries {
+            let relative = relative_path(&canonical, &entry.path);
+            if glob.is_some_and(|pattern| !list_glob_matches(pattern, &relative)) {
+                continue;
+            }
+            if entries.len() >= max_entries {
+                truncated = true;
+                break;
+            }
+            let name = entry
+                .path
                 .file_name()
                 .map(|n| n.to_string_lossy().into_owned())
                 .unwrap_or_default();
-
-            #[cfg(feature = "native")]
-            let (is_dir, size) = {
-                let metadata = tokio::fs::metadata(&entry_path).await;
-                match metadata {
-                    Ok(m) => (m.is_dir(), m.len()),
-                    Err(_) => (false, 0),
-                }
+            let (size, mtime) = match fs.metadata(&entry.path).await {
+                Ok(m) => (
+                    m.len,
+                    m.modified
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs()),
+                ),
+                Err(_) => (0, None),
             };
-            #[cfg(not(feature = "native"))]
-            let (is_dir, size) = (false, 0u64);
-
-            result.push(json!({
+            let kind = if entry.is_symlink {
+                "symlink"
+            } else if entry.is_dir {
+                "dir"
+            } else {
+                "file"
+            };
+            entries.push(json!({
                 "name": name,
-                "is_dir": is_dir,
+                "path": relative,
+                "type": kind,
+                "is_dir": entry.is_dir,
                 "size": size,
+                "mtime": mtime,
             }));
+            listed.push((relative, entry.is_dir));
         }
 
-        // Sort by name for deterministic output.
-        result.sort_by(|a, b| {
-            let na = a["name"].as_str().unwrap_or("");
-            let nb = b["name"].as_str().unwrap_or("");
-            na.cmp(nb)
-        });
-
-        Ok(json!({ "entries": result }))
+        Ok(json!({
+            "entries": entries,
+            "total": entries.len(),
+            "truncated": truncated,
+            "summary": tree_summary(&listed, truncated),
+        }))
     }
 }
 
@@ -582,6 +1122,136 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    /// Write a text file of `n` lines, `line 1` through `line n`.
+    async fn write_numbered_lines(ws: &Path, name: &str, n: usize) {
+        let text: String = (1..=n).map(|i| format!("line {i}\n")).collect();
+        tokio::fs::write(ws.join(name), text).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_file_line_ranges() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ReadFileTool::new(platform, ws.clone());
+        write_numbered_lines(&ws, "big.log", 20_000).await;
+
+        let result = tool
+            .execute(json!({"path": "big.log", "start_line": 10, "end_line": 12}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "line 10\nline 11\nline 12\n");
+        assert_eq!(result["total_lines"], 20_000);
+        assert_eq!(result["truncated"], false);
+
+        // end_line is clamped to the file.
+        let result = tool
+            .execute(json!({"path": "big.log", "start_line": 19_999, "end_line": 50_000}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "line 19999\nline 20000\n");
+        assert_eq!(result["end_line"], 20_000);
+
+        let result = tool
+            .execute(json!({"path": "big.log", "head": 2}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "line 1\nline 2\n");
+
+        let result = tool
+            .execute(json!({"path": "big.log", "tail": 1}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "line 20000\n");
+        assert_eq!(result["start_line"], 20_000);
+
+        // A range past the end is empty but still reports the line count.
+        let result = tool
+            .execute(json!({"path": "big.log", "start_line": 30_000}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "");
+        assert_eq!(result["total_lines"], 20_000);
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_file_truncates_middle_of_large_file() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ReadFileTool::new(platform, ws.clone());
+        write_numbered_lines(&ws, "big.log", 20_000).await;
+
+        let result = tool.execute(json!({"path": "big.log"})).await.unwrap();
+        let content = result["content"].as_str().unwrap();
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["total_lines"], 20_000);
+        assert!(content.len() < READ_MAX_BYTES + 100);
+        assert!(content.starts_with("line 1\nline 2\n"));
+        assert!(content.ends_with("line 19999\nline 20000\n"));
+        assert!(content.contains("lines ("), "no marker: {content}");
+
+        let result = tool
+            .execute(json!({"path": "big.log", "max_bytes": 40}))
+            .await
+            .unwrap();
+        let content = result["content"].as_str().unwrap();
+        assert!(content.starts_with("line 1\nline 2\n["), "{content}");
+        assert!(content.contains("19996 lines"), "{content}");
+        assert!(
+            content.ends_with("]\nline 19999\nline 20000\n"),
+            "{content}"
+        );
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_file_binary_fixture() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ReadFileTool::new(platform, ws.clone());
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+        elf.extend(std::iter::repeat_n(0u8, 120));
+        tokio::fs::write(ws.join("app.bin"), &elf).await.unwrap();
+
+        let result = tool.execute(json!({"path": "app.bin"})).await.unwrap();
+        assert_eq!(result["binary"], true);
+        assert_eq!(result["size"], 128);
+        let preview = result["hex_preview"].as_str().unwrap();
+        assert!(preview.starts_with("7f 45 4c 46 02 01 01 00"));
+        assert_eq!(preview.split(' ').count(), HEX_PREVIEW_BYTES);
+        assert!(result.get("content").is_none());
+
+        cleanup(&ws).await;
+    }
+
+    #[test]
+    fn test_looks_binary_invalid_utf8_ratio() {
+        // Mostly invalid UTF-8 without any NUL bytes.
+        let noise: Vec<u8> = (0..200u8).map(|i| 0x80 | i).collect();
+        assert!(looks_binary(&noise));
+        // Text with a stray Latin-1 byte is still text.
+        assert!(!looks_binary(b"caf\xe9 au lait, served hot\n"));
+        assert!(!looks_binary(b""));
+    }
+
+    #[tokio::test]
+    async fn test_read_file_rejects_conflicting_ranges() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ReadFileTool::new(platform, ws.clone());
+        write_numbered_lines(&ws, "small.txt", 5).await;
+
+        for args in [
+            json!({"path": "small.txt", "head": 1, "tail": 1}),
+            json!({"path": "small.txt", "head": 1, "start_line": 2}),
+            json!({"path": "small.txt", "start_line": 4, "end_line": 2}),
+            json!({"path": "small.txt", "start_line": -1}),
+        ] {
+            let err = tool.execute(args.clone()).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{args}: {err:?}");
+        }
+
+        cleanup(&ws).await;
+    }
+
     // -- WriteFileTool tests -----------------------------------------------
 
     #[tokio::test]
@@ -653,6 +1323,194 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    #[tokio::test]
+    async fn test_write_file_overwrite_requires_confirmation() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+        let path = ws.join("notes.txt");
+        platform
+            .fs()
+            .write_string(&path, "one\ntwo\nthree\n")
+            .await
+            .unwrap();
+
+        let err = tool
+            .execute(json!({"path": "notes.txt", "content": "replaced\n"}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ToolError::InvalidArgs(msg) if msg.contains("14 bytes") && msg.contains("overwrite: true")),
+            "{err:?}"
+        );
+        assert_eq!(
+            platform.fs().read_to_string(&path).await.unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        // Same content: nothing to confirm, nothing written.
+        let same = tool
+            .execute(json!({"path": "notes.txt", "content": "one\ntwo\nthree\n"}))
+            .await
+            .unwrap();
+        assert_eq!(same["unchanged"], true);
+
+        let result = tool
+            .execute(json!({"path": "notes.txt", "content": "one\n2\nthree\n", "overwrite": true}))
+            .await
+            .unwrap();
+        assert_eq!(result["previous_bytes"], 14);
+        assert_eq!(result["diff"]["lines_removed"], 1);
+        assert_eq!(result["diff"]["lines_added"], 1);
+        assert!(
+            result["diff"]["preview"]
+                .as_str()
+                .unwrap()
+                .contains("-two\n+2\n")
+        );
+        assert_eq!(
+            platform.fs().read_to_string(&path).await.unwrap(),
+            "one\n2\nthree\n"
+        );
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_overwrite_diff_is_capped() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+        let old: String = (0..200).map(|i| format!("line {i}\n")).collect();
+        platform
+            .fs()
+            .write_string(&ws.join("big.txt"), &old)
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(json!({"path": "big.txt", "content": "", "overwrite": true}))
+            .await
+            .unwrap();
+        assert_eq!(result["diff"]["lines_removed"], 200);
+        let preview = result["diff"]["preview"].as_str().unwrap();
+        assert_eq!(preview.lines().count(), WRITE_DIFF_PREVIEW_LINES + 1);
+        assert!(preview.ends_with("more diff lines)\n"));
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_append_mode() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+
+        // Appending to a missing file creates it, parents included.
+        let created = tool
+            .execute(json!({"path": "logs/run.log", "content": "start\n", "mode": "append"}))
+            .await
+            .unwrap();
+        assert_eq!(created["created"], true);
+
+        let appended = tool
+            .execute(json!({"path": "logs/run.log", "content": "done\n", "mode": "append"}))
+            .await
+            .unwrap();
+        assert_eq!(appended["previous_bytes"], 6);
+        assert!(appended.get("diff").is_none());
+        assert_eq!(
+            platform
+                .fs()
+                .read_to_string(&ws.join("logs/run.log"))
+                .await
+                .unwrap(),
+            "start\ndone\n"
+        );
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_create_new_mode() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+        let args = json!({"path": "fresh.txt", "content": "v1", "mode": "create_new"});
+
+        let created = tool.execute(args.clone()).await.unwrap();
+        assert_eq!(created["created"], true);
+
+        // Refused even with overwrite: true, and even for identical content.
+        for args in [
+            args.clone(),
+            json!({"path": "fresh.txt", "content": "v2", "mode": "create_new", "overwrite": true}),
+        ] {
+            let err = tool.execute(args).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{err:?}");
+        }
+        assert_eq!(
+            platform
+                .fs()
+                .read_to_string(&ws.join("fresh.txt"))
+                .await
+                .unwrap(),
+            "v1"
+        );
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_create_dirs_false() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+
+        let err = tool
+            .execute(json!({"path": "missing/dir/file.txt", "content": "x", "create_dirs": false}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(_)));
+        assert!(!ws.join("missing").exists());
+
+        tool.execute(json!({"path": "top.txt", "content": "x", "create_dirs": false}))
+            .await
+            .unwrap();
+        assert!(ws.join("top.txt").exists());
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_rejects_unknown_mode() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform, ws.clone());
+
+        let err = tool
+            .execute(json!({"path": "a.txt", "content": "x", "mode": "truncate"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+        assert!(!ws.join("a.txt").exists());
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_write_file_traversal_rejected_in_every_mode() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = WriteFileTool::new(platform, ws.clone());
+
+        for mode in ["overwrite", "append", "create_new"] {
+            let err = tool
+                .execute(json!({"path": "../../escape.txt", "content": "bad", "mode": mode, "overwrite": true}))
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, ToolError::InvalidPath(_)),
+                "{mode}: expected InvalidPath, got: {err:?}"
+            );
+        }
+
+        cleanup(&ws).await;
+    }
+
     // -- EditFileTool tests ------------------------------------------------
 
     #[tokio::test]
@@ -755,6 +1613,116 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    #[tokio::test]
+    async fn test_edit_file_multiple_edits() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = EditFileTool::new(platform.clone(), ws.clone());
+        let path = ws.join("lib.rs");
+        tokio::fs::write(&path, "fn a() {}\nfn b() {}\nfn a() {}\n")
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(json!({
+                "path": "lib.rs",
+                "edits": [
+                    {"old_text": "fn b()", "new_text": "fn beta()"},
+                    {"old_text": "fn a()", "new_text": "fn alpha()", "occurrence": 2}
+                ]
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["edits"], 2);
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content, "fn a() {}\nfn beta() {}\nfn alpha() {}\n");
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_edit_file_failing_edit_changes_nothing() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = EditFileTool::new(platform.clone(), ws.clone());
+        let path = ws.join("notes.txt");
+        tokio::fs::write(&path, "one\ntwo\none\n").await.unwrap();
+
+        // The first edit is fine; the second is ambiguous.
+        let err = tool
+            .execute(json!({
+                "path": "notes.txt",
+                "edits": [
+                    {"old_text": "two", "new_text": "2"},
+                    {"old_text": "one", "new_text": "1"}
+                ]
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments: edits[1].old_text appears 2 times (lines 1, 3); \
+             set occurrence or provide more context to make it unique"
+        );
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content, "one\ntwo\none\n");
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_edit_file_overlapping_edits_rejected() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = EditFileTool::new(platform.clone(), ws.clone());
+        let path = ws.join("overlap.txt");
+        tokio::fs::write(&path, "let total = price * qty;\n")
+            .await
+            .unwrap();
+
+        let err = tool
+            .execute(json!({
+                "path": "overlap.txt",
+                "edits": [
+                    {"old_text": "price * qty", "new_text": "subtotal"},
+                    {"old_text": "total = price", "new_text": "sum = cost"}
+                ]
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("overlap"), "{err}");
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content, "let total = price * qty;\n");
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_edit_file_dry_run_returns_diff() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = EditFileTool::new(platform.clone(), ws.clone());
+        let path = ws.join("config.toml");
+        let original = "[server]\nhost = \"localhost\"\nport = 8080\n";
+        tokio::fs::write(&path, original).await.unwrap();
+
+        let result = tool
+            .execute(json!({
+                "path": "config.toml",
+                "old_text": "port = 8080",
+                "new_text": "port = 9090",
+                "dry_run": true
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(
+            result["diff"],
+            "--- a/config.toml\n+++ b/config.toml\n@@ -1,3 +1,3 @@\n \
+             [server]\n host = \"localhost\"\n-port = 8080\n+port = 9090\n"
+        );
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(content, original);
+
+        cleanup(&ws).await;
+    }
+
     // -- ListDirectoryTool tests -------------------------------------------
 
     #[tokio::test]
@@ -833,6 +1801,174 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    /// Create `files` (relative paths) under `ws`, with their parents.
+    async fn write_tree(ws: &Path, files: &[&str]) {
+        for file in files {
+            let path = ws.join(file);
+            tokio::fs::create_dir_all(path.parent().unwrap())
+                .await
+                .unwrap();
+            tokio::fs::write(&path, "x").await.unwrap();
+        }
+    }
+
+    fn entry_paths(result: &serde_json::Value) -> Vec<&str> {
+        result["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_recursive_depth_limit() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ListDirectoryTool::new(platform, ws.clone());
+        write_tree(
+            &ws,
+            &[
+                "Cargo.toml",
+                "src/main.rs",
+                "src/cli/args.rs",
+                "src/cli/deep/x.rs",
+            ],
+        )
+        .await;
+
+        let result = tool
+            .execute(json!({"path": ".", "recursive": true, "max_depth": 2}))
+            .await
+            .unwrap();
+        assert_eq!(
+            entry_paths(&result),
+            ["Cargo.toml", "src", "src/cli", "src/main.rs"]
+        );
+        assert_eq!(result["summary"], "Cargo.toml\nsrc/\n  cli/\n  main.rs\n");
+        assert_eq!(result["truncated"], false);
+
+        let main = &result["entries"][3];
+        assert_eq!(main["type"], "file");
+        assert_eq!(main["size"], 1);
+        assert!(main["mtime"].as_u64().unwrap() > 0);
+        assert_eq!(result["entries"][1]["type"], "dir");
+
+        // Without `recursive`, only direct children are listed.
+        let result = tool
+            .execute(json!({"path": ".", "max_depth": 5}))
+            .await
+            .unwrap();
+        assert_eq!(entry_paths(&result), ["Cargo.toml", "src"]);
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_glob_and_hidden() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ListDirectoryTool::new(platform, ws.clone());
+        write_tree(
+            &ws,
+            &[
+                ".env",
+                ".git/HEAD",
+                "README.md",
+                "src/lib.rs",
+                "src/util.rs",
+            ],
+        )
+        .await;
+
+        let result = tool
+            .execute(json!({"path": ".", "recursive": true, "glob": "*.rs"}))
+            .await
+            .unwrap();
+        assert_eq!(entry_paths(&result), ["src/lib.rs", "src/util.rs"]);
+        // Parents of matches still appear in the tree.
+        assert_eq!(result["summary"], "src/\n  lib.rs\n  util.rs\n");
+
+        let result = tool
+            .execute(json!({"path": ".", "include_hidden": true}))
+            .await
+            .unwrap();
+        assert_eq!(entry_paths(&result), [".env", ".git", "README.md", "src"]);
+
+        cleanup(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_entry_cap() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ListDirectoryTool::new(platform, ws.clone());
+        let files: Vec<String> = (0..10).map(|i| format!("d/f{i}.txt")).collect();
+        let files: Vec<&str> = files.iter().map(String::as_str).collect();
+        write_tree(&ws, &files).await;
+
+        let result = tool
+            .execute(json!({"path": ".", "recursive": true, "max_entries": 4}))
+            .await
+            .unwrap();
+        assert_eq!(result["total"], 4);
+        assert_eq!(result["truncated"], true);
+        assert_eq!(
+            entry_paths(&result),
+            ["d", "d/f0.txt", "d/f1.txt", "d/f2.txt"]
+        );
+        assert!(
+            result["summary"]
+                .as_str()
+                .unwrap()
+                .ends_with("... (stopped at 4 entries)\n")
+        );
+
+        // Exactly at the cap is not truncated.
+        let result = tool
+            .execute(json!({"path": "d", "max_entries": 10}))
+            .await
+            .unwrap();
+        assert_eq!(result["total"], 10);
+        assert_eq!(result["truncated"], false);
+
+        cleanup(&ws).await;
+    }
+
+    /// SEC-05: Recursive listing never follows a symlink out of the
+    /// workspace, and does not descend into symlinked directories.
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_directory_recursive_symlink_containment() {
+        let (platform, ws) = setup_workspace().await;
+        let tool = ListDirectoryTool::new(platform, ws.clone());
+        write_tree(&ws, &["inside/a.txt"]).await;
+
+        let outside = ws.parent().unwrap().join(format!(
+            "outside_list_{}",
+            ws.file_name().unwrap().to_string_lossy()
+        ));
+        write_tree(&outside, &["secret.txt"]).await;
+        tokio::fs::symlink(&outside, ws.join("inside/escape"))
+            .await
+            .unwrap();
+        tokio::fs::symlink(outside.join("secret.txt"), ws.join("leak.txt"))
+            .await
+            .unwrap();
+        tokio::fs::symlink(ws.join("inside"), ws.join("alias"))
+            .await
+            .unwrap();
+
+        let result = tool
+            .execute(json!({"path": ".", "recursive": true, "max_depth": 5}))
+            .await
+            .unwrap();
+        assert_eq!(entry_paths(&result), ["alias", "inside", "inside/a.txt"]);
+        assert_eq!(result["entries"][0]["type"], "symlink");
+        assert_eq!(result["entries"][0]["is_dir"], true);
+        assert!(!result["summary"].as_str().unwrap().contains("secret"));
+
+        let _ = tokio::fs::remove_dir_all(&outside).await;
+        cleanup(&ws).await;
+    }
+
     // -- SEC-05: Symlink traversal tests ----------------------------------
 
     /// SEC-05: Verify that a symlink pointing outside the workspace is
@@ -885,6 +2021,25 @@ mod tests {
             "ReadFileTool should reject symlink outside workspace: {err:?}"
         );
 
+        // WriteFileTool must not write through it in any mode
+        let tool = WriteFileTool::new(platform.clone(), ws.clone());
+        for mode in ["overwrite", "append", "create_new"] {
+            let err = tool
+                .execute(
+                    json!({"path": "escape_link", "content": "x", "mode": mode, "overwrite": true}),
+                )
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, ToolError::InvalidPath(_)),
+                "WriteFileTool ({mode}) should reject symlink outside workspace: {err:?}"
+            );
+        }
+        assert_eq!(
+            tokio::fs::read_to_string(&outside_file).await.unwrap(),
+            "secret data"
+        );
+
         // Cleanup
         let _ = tokio::fs::remove_dir_all(&outside_dir).await;
         cleanup(&ws).await;
@@ -0,0 +1,953 @@
+//! Main-content extraction from HTML pages.
+//!
+//! Backs `web_fetch`'s `markdown` and `text` modes. The page is parsed
+//! into a small element tree (forgiving of unclosed tags), boilerplate
+//! such as navigation, footers, forms and scripts is dropped, and the
+//! element holding the article body is picked the way readability tools
+//! do: `<article>` / `<main>` when present, otherwise the container whose
+//! paragraphs score highest once link-heavy blocks are discounted. That
+//! element is then rendered as Markdown or plain text.
+//!
+//! The same tree backs [`inline_text`] and [`select_by_class`], which
+//! `web_search` uses to read snippets and result pages.
+
+use std::collections::HashMap;
+
+use url::Url;
+
+/// Elements whose contents are never part of the readable text.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object",
+    "embed", "nav", "header", "footer", "aside", "form", "button", "select", "input", "textarea",
+    "dialog", "menu",
+];
+
+/// Class / id words marking boilerplate containers.
+const BOILERPLATE_WORDS: &[&str] = &[
+    "nav",
+    "navbar",
+    "navigation",
+    "menu",
+    "footer",
+    "sidebar",
+    "comment",
+    "comments",
+    "cookie",
+    "cookies",
+    "consent",
+    "banner",
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "share",
+    "sharing",
+    "social",
+    "related",
+    "breadcrumb",
+    "breadcrumbs",
+    "popup",
+    "modal",
+    "subscribe",
+    "newsletter",
+    "promo",
+    "sponsored",
+];
+
+/// Elements with no content or closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is raw text, not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "title", "textarea"];
+
+/// Minimum text length for a `<article>` / `<main>` to be trusted as the
+/// main content without scoring.
+const SEMANTIC_MIN_CHARS: usize = 200;
+
+/// What [`extract`] found on a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Extracted {
+    /// Text of the `<title>` element, if any.
+    pub title: Option<String>,
+    /// The main content, rendered.
+    pub content: String,
+}
+
+/// Output format for [`extract`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Markdown,
+    Text,
+}
+
+/// Extract the main content of `html`, resolving links against `base`.
+pub(crate) fn extract(html: &str, base: Option<&Url>, format: Format) -> Extracted {
+    let mut root = parse(html);
+    let title = find(&root, &|el| el.name == "title")
+        .map(|el| collapse_whitespace(&el.text()))
+        .filter(|t| !t.is_empty());
+    strip_boilerplate(&mut root);
+    let main = main_content(&root);
+    let mut renderer = Renderer::new(base, format);
+    renderer.element(main);
+    Extracted {
+        title,
+        content: renderer.finish(),
+    }
+}
+
+/// Text of an HTML fragment with tags removed and whitespace collapsed.
+pub(crate) fn inline_text(html: &str) -> String {
+    collapse_whitespace(&parse(html).text())
+}
+
+/// An element picked out of a page by [`select_by_class`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Selected {
+    /// Which of the requested classes the element carries.
+    pub class: String,
+    /// The element's text, whitespace collapsed.
+    pub text: String,
+    /// The element's `href`, if any.
+    pub href: Option<String>,
+}
+
+/// Every element carrying one of `classes`, in document order.
+pub(crate) fn select_by_class(html: &str, classes: &[&str]) -> Vec<Selected> {
+    fn walk(el: &Element, classes: &[&str], out: &mut Vec<Selected>) {
+        let matched = el
+            .attr("class")
+            .and_then(|value| value.split_whitespace().find(|c| classes.contains(c)));
+        if let Some(class) = matched {
+            out.push(Selected {
+                class: class.to_string(),
+                text: collapse_whitespace(&el.text()),
+                href: el.attr("href").map(str::to_string),
+            });
+        }
+        for child in el.elements() {
+            walk(child, classes, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(&parse(html), classes, &mut out);
+    out
+}
+
+// ── element tree ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str, attrs: Vec<(String, String)>) -> Self {
+        Self {
+            name: name.to_string(),
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(el) => Some(el),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// All text below this element, unformatted.
+    fn text(&self) -> String {
+        let mut out = String::new();
+        self.collect_text(&mut out);
+        out
+    }
+
+    fn collect_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(el) => el.collect_text(out),
+            }
+        }
+    }
+
+    /// Non-whitespace characters of text below this element.
+    fn text_len(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| match child {
+                Node::Text(text) => text.chars().filter(|c| !c.is_whitespace()).count(),
+                Node::Element(el) => el.text_len(),
+            })
+            .sum()
+    }
+
+    /// Non-whitespace characters of text inside links below this element.
+    fn link_text_len(&self) -> usize {
+        self.elements()
+            .map(|el| {
+                if el.name == "a" {
+                    el.text_len()
+                } else {
+                    el.link_text_len()
+                }
+            })
+            .sum()
+    }
+}
+
+fn find<'a>(el: &'a Element, pred: &dyn Fn(&Element) -> bool) -> Option<&'a Element> {
+    if pred(el) {
+        return Some(el);
+    }
+    el.elements().find_map(|child| find(child, pred))
+}
+
+/// Parse `html` into a tree under a synthetic root element.
+///
+/// Unknown or mismatched closing tags are ignored, and a closing tag
+/// closes any elements left open inside it, so real-world markup
+/// parses into something usable.
+fn parse(html: &str) -> Element {
+    let mut stack = vec![Element::new("#root", Vec::new())];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            push_text(&mut stack, rest);
+            break;
+        };
+        push_text(&mut stack, &rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').unwrap_or(after.len());
+            let name = after[..end].trim().to_ascii_lowercase();
+            close(&mut stack, &name);
+            rest = after.get(end + 1..).unwrap_or("");
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (name, attrs, self_closing, after) = parse_tag(&rest[1..]);
+            rest = after;
+            open(&mut stack, &name, attrs);
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let (text, after) = raw_text(rest, &name);
+                push_text(&mut stack, text);
+                close(&mut stack, &name);
+                rest = after;
+            } else if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+                close(&mut stack, &name);
+            }
+        } else {
+            push_text(&mut stack, "<");
+            rest = &rest[1..];
+        }
+    }
+
+    while stack.len() > 1 {
+        pop(&mut stack);
+    }
+    stack.pop().unwrap_or_default()
+}
+
+fn push_text(stack: &mut [Element], text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(top) = stack.last_mut() {
+        top.children.push(Node::Text(decode_entities(text)));
+    }
+}
+
+fn open(stack: &mut Vec<Element>, name: &str, attrs: Vec<(String, String)>) {
+    // Tags that implicitly end an open sibling of the same kind.
+    let closes: &[&str] = match name {
+        "p" | "div" | "ul" | "ol" | "table" | "pre" | "blockquote" | "h1" | "h2" | "h3" | "h4"
+        | "h5" | "h6" | "section" | "article" => &["p"],
+        "li" => &["li"],
+        "dt" | "dd" => &["dt", "dd"],
+        "tr" => &["tr", "td", "th"],
+        "td" | "th" => &["td", "th"],
+        "option" => &["option"],
+        _ => &[],
+    };
+    if stack
+        .last()
+        .is_some_and(|top| closes.contains(&top.name.as_str()))
+    {
+        pop(stack);
+    }
+    stack.push(Element::new(name, attrs));
+}
+
+/// Close the innermost open `name`, and everything opened inside it.
+fn close(stack: &mut Vec<Element>, name: &str) {
+    let Some(depth) = stack.iter().rposition(|el| el.name == name) else {
+        return;
+    };
+    if depth == 0 {
+        return;
+    }
+    while stack.len() > depth {
+        pop(stack);
+    }
+}
+
+fn pop(stack: &mut Vec<Element>) {
+    if let Some(el) = stack.pop()
+        && let Some(parent) = stack.last_mut()
+    {
+        parent.children.push(Node::Element(el));
+    }
+}
+
+/// Parse a start tag after its `<`: name, attributes, whether it ends
+/// in `/>`, and the input after the `>`.
+fn parse_tag(s: &str) -> (String, Vec<(String, String)>, bool, &str) {
+    let name_end = s
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(s.len());
+    let name = s[..name_end].to_ascii_lowercase();
+    let mut rest = &s[name_end..];
+    let mut attrs = Vec::new();
+    let mut self_closing = false;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            rest = after;
+            break;
+        }
+        if let Some(after) = rest.strip_prefix("/>") {
+            self_closing = true;
+            rest = after;
+            break;
+        }
+        if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            continue;
+        }
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len())
+            .max(1);
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, next) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(q).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = next;
+        }
+        attrs.push((key, value));
+    }
+    (name, attrs, self_closing, rest)
+}
+
+/// Split raw-text content at its closing tag.
+fn raw_text<'a>(s: &'a str, name: &str) -> (&'a str, &'a str) {
+    let closing = format!("</{name}");
+    let end = s
+        .char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| {
+            s.get(i..i + closing.len())
+                .is_some_and(|t| t.eq_ignore_ascii_case(&closing))
+        })
+        .unwrap_or(s.len());
+    (&s[..end], &s[end..])
+}
+
+/// Decode character references (`&amp;`, `&#39;`, `&#x2014;`, ...).
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|c| (c, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "mdash" => '—',
+        "ndash" => '–',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "middot" => '·',
+        "bull" => '•',
+        _ => return None,
+    })
+}
+
+// ── content selection ─────────────────────────────────────────────────
+
+fn is_boilerplate(el: &Element) -> bool {
+    if DROPPED_ELEMENTS.contains(&el.name.as_str()) {
+        return true;
+    }
+    if matches!(el.attr("aria-hidden"), Some("true")) || el.attr("hidden").is_some() {
+        return true;
+    }
+    if matches!(
+        el.attr("role"),
+        Some("navigation" | "banner" | "contentinfo" | "complementary" | "dialog")
+    ) {
+        return true;
+    }
+    if matches!(el.name.as_str(), "body" | "article" | "main") {
+        return false;
+    }
+    [el.attr("class"), el.attr("id")]
+        .into_iter()
+        .flatten()
+        .flat_map(|value| value.split(|c: char| c.is_whitespace() || c == '-' || c == '_'))
+        .any(|word| BOILERPLATE_WORDS.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+/// Remove boilerplate elements (keeping `<title>` out of the way too).
+fn strip_boilerplate(el: &mut Element) {
+    el.children.retain(|child| match child {
+        Node::Element(child) => child.name != "title" && !is_boilerplate(child),
+        Node::Text(_) => true,
+    });
+    for child in &mut el.children {
+        if let Node::Element(child) = child {
+            strip_boilerplate(child);
+        }
+    }
+}
+
+/// The element holding the page's main content.
+fn main_content(root: &Element) -> &Element {
+    let semantic = find(root, &|el| {
+        (el.name == "article" || el.name == "main" || el.attr("role") == Some("main"))
+            && el.text_len() >= SEMANTIC_MIN_CHARS
+    });
+    if let Some(el) = semantic {
+        return el;
+    }
+
+    // Readability-style scoring: every paragraph credits its parent in
+    // full and its grandparent by half.
+    let mut scores: HashMap<Vec<usize>, f64> = HashMap::new();
+    score_paragraphs(root, &mut Vec::new(), &mut scores);
+    let best = scores
+        .into_iter()
+        .filter_map(|(path, score)| {
+            let el = at_path(root, &path)?;
+            let text = el.text_len().max(1) as f64;
+            let link_density = el.link_text_len() as f64 / text;
+            Some((path, score * (1.0 - link_density)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.len().cmp(&a.0.len())));
+
+    best.and_then(|(path, _)| at_path(root, &path))
+        .or_else(|| find(root, &|el| el.name == "body"))
+        .unwrap_or(root)
+}
+
+fn score_paragraphs(el: &Element, path: &mut Vec<usize>, scores: &mut HashMap<Vec<usize>, f64>) {
+    for (i, child) in el.children.iter().enumerate() {
+        let Node::Element(child) = child else {
+            continue;
+        };
+        path.push(i);
+        if matches!(child.name.as_str(), "p" | "pre" | "blockquote" | "td") {
+            let text = child.text();
+            let len = text.trim().chars().count();
+            if len >= 25 {
+                let score = 1.0 + text.matches(',').count() as f64 + (len as f64 / 100.0).min(3.0);
+                let parent = path[..path.len() - 1].to_vec();
+                *scores.entry(parent.clone()).or_default() += score;
+                if let Some((_, grandparent)) = parent.split_last() {
+                    *scores.entry(grandparent.to_vec()).or_default() += score / 2.0;
+                }
+            }
+        }
+        score_paragraphs(child, path, scores);
+        path.pop();
+    }
+}
+
+fn at_path<'a>(root: &'a Element, path: &[usize]) -> Option<&'a Element> {
+    path.iter()
+        .try_fold(root, |el, &i| match el.children.get(i)? {
+            Node::Element(child) => Some(child),
+            Node::Text(_) => None,
+        })
+}
+
+// ── rendering ─────────────────────────────────────────────────────────
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Renders an element tree as Markdown or plain text.
+struct Renderer<'a> {
+    out: String,
+    base: Option<&'a Url>,
+    format: Format,
+    /// Open lists: whether ordered, and the next item number.
+    lists: Vec<(bool, usize)>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(base: Option<&'a Url>, format: Format) -> Self {
+        Self {
+            out: String::new(),
+            base,
+            format,
+            lists: Vec::new(),
+        }
+    }
+
+    fn markdown(&self) -> bool {
+        self.format == Format::Markdown
+    }
+
+    /// Render `el`'s children inline, into a string of their own.
+    fn inline(&self, el: &Element) -> String {
+        let mut sub = Renderer::new(self.base, self.format);
+        sub.children(el);
+        collapse_whitespace(&sub.out)
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        let mut blank_run = 0;
+        for line in self.out.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                blank_run += 1;
+                if blank_run > 1 || out.is_empty() {
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.trim_end().to_string()
+    }
+
+    fn block_break(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push_str(if self.out.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let at_line_start = self.out.is_empty() || self.out.ends_with('\n');
+        let collapsed = collapse_whitespace(text);
+        if collapsed.is_empty() {
+            if !at_line_start && !self.out.ends_with(' ') && !text.is_empty() {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) && !at_line_start && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+        self.out.push_str(&collapsed);
+        if text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn children(&mut self, el: &Element) {
+        for child in &el.children {
+            match child {
+                Node::Text(text) => self.text(text),
+                Node::Element(child) => self.element(child),
+            }
+        }
+    }
+
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(String::from),
+            None => Some(href.to_string()),
+        }
+    }
+
+    fn wrap_inline(&mut self, el: &Element, marker: &str) {
+        let inner = self.inline(el);
+        if inner.is_empty() {
+            return;
+        }
+        if self.markdown() {
+            self.text(&format!("{marker}{inner}{marker}"));
+        } else {
+            self.text(&inner);
+        }
+    }
+
+    fn element(&mut self, el: &Element) {
+        match el.name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let inner = self.inline(el);
+                if inner.is_empty() {
+                    return;
+                }
+                self.block_break();
+                if self.markdown() {
+                    let level = el.name[1..].parse().unwrap_or(1);
+                    self.out.push_str(&"#".repeat(level));
+                    self.out.push(' ');
+                }
+                self.out.push_str(&inner);
+                self.block_break();
+            }
+            "p" | "div" | "section" | "article" | "main" | "figure" | "figcaption" | "dl"
+            | "address" | "details" | "summary" => {
+                self.block_break();
+                self.children(el);
+                self.block_break();
+            }
+            "br" => self.line_break(),
+            "hr" => {
+                self.block_break();
+                if self.markdown() {
+                    self.out.push_str("---");
+                }
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                } else {
+                    self.line_break();
+                }
+                self.lists.push((el.name == "ol", 1));
+                self.children(el);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_break();
+                } else {
+                    self.line_break();
+                }
+            }
+            "li" => {
+                self.line_break();
+                let depth = self.lists.len().saturating_sub(1);
+                let bullet = match self.lists.last_mut() {
+                    Some((true, n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&bullet);
+                self.children(el);
+                self.line_break();
+            }
+            "dt" | "dd" => {
+                self.line_break();
+                self.children(el);
+                self.line_break();
+            }
+            "blockquote" => {
+                let mut sub = Renderer::new(self.base, self.format);
+                sub.children(el);
+                let inner = sub.finish();
+                if inner.is_empty() {
+                    return;
+                }
+                self.block_break();
+                let prefix = if self.markdown() { "> " } else { "" };
+                for line in inner.lines() {
+                    self.out.push_str(prefix);
+                    self.out.push_str(line);
+                    self.out.push('\n');
+                }
+                self.block_break();
+            }
+            "pre" => {
+                let code = el.text();
+                let code = code.trim_matches('\n');
+                if code.trim().is_empty() {
+                    return;
+                }
+                self.block_break();
+                if self.markdown() {
+                    self.out.push_str("```\n");
+                    self.out.push_str(code);
+                    self.out.push_str("\n```");
+                } else {
+                    self.out.push_str(code);
+                }
+                self.block_break();
+            }
+            "code" | "kbd" | "samp" => self.wrap_inline(el, "`"),
+            "strong" | "b" => self.wrap_inline(el, "**"),
+            "em" | "i" => self.wrap_inline(el, "_"),
+            "a" => {
+                let inner = self.inline(el);
+                if inner.is_empty() {
+                    return;
+                }
+                match el.attr("href").and_then(|href| self.resolve(href)) {
+                    Some(href) if self.markdown() => self.text(&format!("[{inner}]({href})")),
+                    _ => self.text(&inner),
+                }
+            }
+            "img" => {
+                let alt = collapse_whitespace(el.attr("alt").unwrap_or_default());
+                if alt.is_empty() {
+                    return;
+                }
+                match el.attr("src").and_then(|src| self.resolve(src)) {
+                    Some(src) if self.markdown() => self.text(&format!("![{alt}]({src})")),
+                    _ => self.text(&alt),
+                }
+            }
+            "table" => self.table(el),
+            _ => self.children(el),
+        }
+    }
+
+    fn table(&mut self, table: &Element) {
+        let mut rows = Vec::new();
+        collect_rows(table, &mut rows);
+        let rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|row| {
+                row.elements()
+                    .filter(|cell| cell.name == "td" || cell.name == "th")
+                    .map(|cell| self.inline(cell).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|cells| !cells.is_empty())
+            .collect();
+        if rows.is_empty() {
+            return;
+        }
+        self.block_break();
+        for (i, cells) in rows.iter().enumerate() {
+            if self.markdown() {
+                self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                if i == 0 {
+                    self.out
+                        .push_str(&format!("|{}\n", " --- |".repeat(cells.len())));
+                }
+            } else {
+                self.out.push_str(&cells.join("\t"));
+                self.out.push('\n');
+            }
+        }
+        self.block_break();
+    }
+}
+
+fn collect_rows<'a>(el: &'a Element, rows: &mut Vec<&'a Element>) {
+    for child in el.elements() {
+        match child.name.as_str() {
+            "tr" => rows.push(child),
+            "table" => {}
+            _ => collect_rows(child, rows),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE_PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Rust &amp; You</title>
+<style>body { color: red }</style>
+<script>var tracking = "<p>not text</p>";</script></head>
+<body>
+<nav><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+<div class="cookie-banner">We use cookies, lots of them.</div>
+<div id="content">
+  <h1>Ownership explained</h1>
+  <p>Rust's ownership model ensures memory safety, without a garbage collector, at compile time.</p>
+  <p>Each value has an <em>owner</em>; when the owner goes out of scope, the value is
+     <strong>dropped</strong>. See <a href="/docs/ownership">the book</a>.</p>
+  <ul><li>Move semantics<li>Borrowing <ul><li>shared</li><li>mutable</li></ul></ul>
+  <pre><code>let s = String::from("hi");
+let t = s;</code></pre>
+  <table><tr><th>Kind</th><th>Cost</th></tr><tr><td>move</td><td>free</td></tr></table>
+</div>
+<aside>Related posts, sponsored content, and more, and more, and more.</aside>
+<footer>&copy; 2026 Example</footer>
+</body></html>"#;
+
+    #[test]
+    fn markdown_keeps_main_content_only() {
+        let base = Url::parse("https://example.com/post/1").unwrap();
+        let out = extract(ARTICLE_PAGE, Some(&base), Format::Markdown);
+        assert_eq!(out.title.as_deref(), Some("Rust & You"));
+        let md = out.content;
+        assert!(md.starts_with("# Ownership explained"), "{md}");
+        assert!(md.contains("_owner_"));
+        assert!(md.contains("**dropped**"));
+        assert!(md.contains("[the book](https://example.com/docs/ownership)"));
+        assert!(
+            md.contains("- Move semantics\n- Borrowing\n  - shared\n  - mutable"),
+            "{md}"
+        );
+        assert!(md.contains("```\nlet s = String::from(\"hi\");\nlet t = s;\n```"));
+        assert!(md.contains("| Kind | Cost |\n| --- | --- |\n| move | free |"));
+        for boilerplate in [
+            "Home",
+            "cookies",
+            "Related posts",
+            "2026",
+            "tracking",
+            "color",
+        ] {
+            assert!(!md.contains(boilerplate), "{boilerplate} leaked: {md}");
+        }
+    }
+
+    #[test]
+    fn text_has_no_markup() {
+        let out = extract(ARTICLE_PAGE, None, Format::Text);
+        let text = out.content;
+        assert!(text.starts_with("Ownership explained\n\nRust's ownership model"));
+        assert!(text.contains("Each value has an owner; when the owner goes out of scope"));
+        assert!(!text.contains("**"));
+        assert!(!text.contains("]("));
+        assert!(!text.contains("```"));
+        assert!(text.contains("Kind\tCost"));
+    }
+
+    #[test]
+    fn article_element_wins_over_scoring() {
+        let body = "Long enough article text, with commas, to be trusted. ".repeat(6);
+        let html = format!(
+            "<body><div><p>{body}</p></div><article><p>{body}</p><p>tail</p></article></body>"
+        );
+        let out = extract(&html, None, Format::Text);
+        assert!(out.content.ends_with("tail"), "{}", out.content);
+    }
+
+    #[test]
+    fn scoring_prefers_paragraph_dense_container_over_link_lists() {
+        let links = "<a href='/x'>A link with a fairly long title, here</a> ".repeat(20);
+        let para =
+            "<p>Plain paragraph text that reads like an article, with a clause, and another.</p>";
+        let html = format!(
+            "<body><div class='list'><p>{links}</p></div><div class='story'>{para}{para}{para}</div></body>"
+        );
+        let out = extract(&html, None, Format::Text);
+        assert!(
+            out.content.starts_with("Plain paragraph text"),
+            "{}",
+            out.content
+        );
+        assert!(!out.content.contains("A link with"));
+    }
+
+    #[test]
+    fn entities_and_sloppy_markup_are_handled() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#39;c&#x27; &nosuch; & d"),
+            "a <b> 'c' &nosuch; & d"
+        );
+        let out = extract("<p>one<p>two <b>bold</p>three", None, Format::Markdown);
+        assert_eq!(out.content, "one\n\ntwo **bold**\n\nthree");
+    }
+}
@@ -0,0 +1,740 @@
+//! General-purpose HTTP request tool.
+//!
+//! Provides an `http_request` tool for REST endpoints that take an API
+//! key header or no authentication at all. Unlike `web_fetch`, the
+//! response body is returned exactly as received; unlike the OAuth2
+//! plugin's `rest_request`, no token store is involved. Credentials come
+//! from the named secrets in `tools.secrets` ([`Secrets`]), referenced
+//! from header values as `{{secret:NAME}}`, so the raw values never pass
+//! through the model. A secret must be granted to `http_request`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use clawft_core::agent::secrets::Secrets;
+use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_platform::Platform;
+use serde_json::json;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::redirects::{self, check_url, header};
+use crate::url_safety::UrlPolicy;
+use crate::web_search::provider::encode_query;
+
+/// Default request timeout in seconds.
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Upper bound for the `timeout_secs` argument.
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+/// Default maximum response body size in bytes (1 MB).
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Methods the tool accepts.
+const METHODS: &[&str] = &["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
+/// Opening of a secret reference in a header value.
+const SECRET_OPEN: &str = "{{secret:";
+
+/// General-purpose HTTP request tool.
+///
+/// Sends any method with a JSON, form or raw body. Every URL, including
+/// each redirect target, is checked against [`UrlPolicy`], and headers
+/// carrying a secret are not sent to a redirect target on another origin.
+/// Response bodies are capped at a configurable size, and binary bodies
+/// are reported rather than returned.
+pub struct HttpRequestTool<P: Platform> {
+    platform: Arc<P>,
+    url_policy: UrlPolicy,
+    secrets: Arc<Secrets>,
+    timeout_secs: u64,
+    max_response_bytes: usize,
+}
+
+impl<P: Platform> HttpRequestTool<P> {
+    /// Create a new `HttpRequestTool` with no secrets, a 30 s default
+    /// timeout and a 1 MB body size limit.
+    pub fn new(platform: Arc<P>, url_policy: UrlPolicy) -> Self {
+        Self {
+            platform,
+            url_policy,
+            secrets: Arc::new(Secrets::default()),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+
+    /// Make `secrets` available to headers as `{{secret:NAME}}`. Only
+    /// those granted to `http_request` can be used.
+    pub fn with_secrets(mut self, secrets: Arc<Secrets>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Set the timeout used when the call does not pass `timeout_secs`.
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = secs;
+        self
+    }
+
+    /// Set the response body size limit.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = max_bytes;
+        self
+    }
+}
+
+/// A request body and the content type it implies, if any.
+struct RequestBody {
+    bytes: Vec<u8>,
+    content_type: Option<&'static str>,
+}
+
+/// Build the request body from whichever of `json`, `form` or `body` is
+/// set.
+fn request_body(args: &serde_json::Value) -> Result<Option<RequestBody>, ToolError> {
+    let given: Vec<&str> = ["json", "form", "body"]
+        .into_iter()
+        .filter(|key| args.get(*key).is_some_and(|v| !v.is_null()))
+        .collect();
+    if given.len() > 1 {
+        return Err(ToolError::InvalidArgs(format!(
+            "pass only one of json, form or body (got {})",
+            given.join(", ")
+        )));
+    }
+    match given.first().copied() {
+        None => Ok(None),
+        Some("json") => {
+            let bytes = serde_json::to_vec(&args["json"])
+                .map_err(|e| ToolError::InvalidArgs(format!("invalid json body: {e}")))?;
+            Ok(Some(RequestBody {
+                bytes,
+                content_type: Some("application/json"),
+            }))
+        }
+        Some("form") => {
+            let fields = args["form"]
+                .as_object()
+                .ok_or_else(|| ToolError::InvalidArgs("form must be an object".into()))?;
+            let encoded = fields
+                .iter()
+                .map(|(k, v)| {
+                    let value = match v {
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    format!("{}={}", encode_query(k), encode_query(&value))
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            Ok(Some(RequestBody {
+                bytes: encoded.into_bytes(),
+                content_type: Some("application/x-www-form-urlencoded"),
+            }))
+        }
+        Some(_) => {
+            let raw = args["body"]
+                .as_str()
+                .ok_or_else(|| ToolError::InvalidArgs("body must be a string".into()))?;
+            Ok(Some(RequestBody {
+                bytes: raw.as_bytes().to_vec(),
+                content_type: None,
+            }))
+        }
+    }
+}
+
+/// Whether a body should be reported rather than returned: media types,
+/// or anything that is not UTF-8 text (a character cut off by the size
+/// limit is fine).
+fn is_binary(content_type: &str, shown: &[u8]) -> bool {
+    let mime = content_type.trim_start().to_ascii_lowercase();
+    if ["image/", "audio/", "video/", "font/"]
+        .iter()
+        .any(|prefix| mime.starts_with(prefix))
+    {
+        return true;
+    }
+    let valid = match std::str::from_utf8(shown) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    !valid || shown.contains(&0)
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl<P: Platform + 'static> Tool for HttpRequestTool<P> {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Send an HTTP request to a REST endpoint and return the status, headers and raw body. \
+         Supports any method and JSON, form or raw bodies. Authenticate with API-key headers \
+         that reference configured secrets as {{secret:NAME}}. For OAuth2-protected APIs use \
+         rest_request; to read a web page as text use web_fetch."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "enum": METHODS,
+                    "description": "HTTP method. Defaults to GET."
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Request URL (http:// or https://)"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Request headers. Values may reference configured secrets as {{secret:NAME}}.",
+                    "additionalProperties": { "type": "string" }
+                },
+                "json": {
+                    "description": "JSON request body; sets Content-Type: application/json"
+                },
+                "form": {
+                    "type": "object",
+                    "description": "Form fields, sent URL-encoded"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Raw request body, sent as-is"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": format!(
+                        "Request timeout in seconds (default {}, max {MAX_TIMEOUT_SECS})",
+                        self.timeout_secs
+                    )
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: url".into()))?;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(ToolError::InvalidArgs(
+                "url must start with http:// or https://".into(),
+            ));
+        }
+        check_url("http_request", url, &self.url_policy)?;
+        let parsed = Url::parse(url)
+            .map_err(|e| ToolError::InvalidArgs(format!("invalid url '{url}': {e}")))?;
+
+        let method = args
+            .get("method")
+            .and_then(|v| v.as_str())
+            .unwrap_or("GET")
+            .to_uppercase();
+        if !METHODS.contains(&method.as_str()) {
+            return Err(ToolError::InvalidArgs(format!(
+                "unsupported method '{method}', expected one of {}",
+                METHODS.join(", ")
+            )));
+        }
+
+        // Headers carrying a secret, which must not follow a redirect to
+        // another origin. Values filled in by `secret_use` arrive resolved.
+        let mut secret_headers = Vec::new();
+        let mut headers = HashMap::new();
+        if let Some(obj) = args.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in obj {
+                let text = value.as_str().ok_or_else(|| {
+                    ToolError::InvalidArgs(format!("header '{name}' must be a string"))
+                })?;
+                let mut value = value.clone();
+                if text.contains(SECRET_OPEN) {
+                    self.secrets.interpolate("http_request", &mut value)?;
+                    secret_headers.push(name.clone());
+                } else if self.secrets.contains_secret(text) {
+                    secret_headers.push(name.clone());
+                }
+                headers.insert(name.clone(), value.as_str().unwrap_or_default().to_string());
+            }
+        }
+
+        let body = match request_body(&args)? {
+            Some(RequestBody {
+                bytes,
+                content_type,
+            }) => {
+                if let Some(content_type) = content_type
+                    && header(&headers, "content-type").is_none()
+                {
+                    headers.insert("Content-Type".into(), content_type.into());
+                }
+                Some(bytes)
+            }
+            None => None,
+        };
+
+        let timeout_secs = args
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(self.timeout_secs)
+            .clamp(1, MAX_TIMEOUT_SECS);
+
+        debug!(url, method = %method, timeout_secs, "sending http request");
+
+        let send = redirects::send(
+            self.platform.http(),
+            &self.url_policy,
+            "http_request",
+            method,
+            parsed,
+            headers,
+            body,
+            &secret_headers,
+        );
+        #[cfg(feature = "native")]
+        let followed = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), send)
+            .await
+            .map_err(|_| ToolError::Timeout(timeout_secs))??;
+        #[cfg(not(feature = "native"))]
+        let followed = send.await?;
+
+        let redirects::Followed {
+            response,
+            url: final_url,
+            redirects,
+        } = followed;
+        let total_bytes = response.body.len();
+        let content_type = header(&response.headers, "content-type")
+            .unwrap_or_default()
+            .to_string();
+        let response_headers: serde_json::Map<String, serde_json::Value> = response
+            .headers
+            .iter()
+            .map(|(k, v)| (k.to_ascii_lowercase(), json!(v)))
+            .collect();
+
+        let mut result = json!({
+            "status": response.status,
+            "url": final_url.as_str(),
+            "headers": response_headers,
+            "content_type": content_type,
+            "bytes": total_bytes,
+        });
+        if !redirects.is_empty() {
+            result["redirects"] = json!(redirects);
+        }
+
+        let limit = self.max_response_bytes;
+        let shown = &response.body[..total_bytes.min(limit)];
+        if is_binary(&content_type, shown) {
+            result["binary"] = json!(true);
+            result["message"] = json!(format!(
+                "binary response body ({total_bytes} bytes) not returned"
+            ));
+            self.secrets.scrub(&mut result);
+            return Ok(result);
+        }
+
+        let mut body = String::from_utf8_lossy(shown).into_owned();
+        if total_bytes > limit {
+            warn!(
+                url,
+                total_bytes, limit, "response body exceeds size limit, truncating"
+            );
+            body.push_str(&format!(
+                "\n\n[... truncated at {limit} bytes, total response was {total_bytes} bytes]"
+            ));
+            result["truncated"] = json!(true);
+            result["limit_bytes"] = json!(limit);
+            result["warning"] = json!(format!(
+                "Response body ({total_bytes} bytes) exceeded the {limit} byte limit and was truncated"
+            ));
+        }
+        result["body"] = json!(body);
+        self.secrets.scrub(&mut result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::http::NO_REDIRECT_HEADER;
+    use clawft_platform::testing::{MockPlatform, MockResponse};
+    use clawft_types::config::SecretRef;
+
+    fn example_policy() -> UrlPolicy {
+        UrlPolicy {
+            allowed_domains: ["api.example.com", "cdn.example.com"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            ..UrlPolicy::default()
+        }
+    }
+
+    fn tool(platform: Arc<MockPlatform>) -> HttpRequestTool<MockPlatform> {
+        let config: HashMap<String, SecretRef> = serde_json::from_value(json!({
+            "weather": { "value": "w-secret-123", "tools": ["http_request"] },
+            "github": { "value": "ghp_abc123", "tools": ["write_file"] }
+        }))
+        .unwrap();
+        HttpRequestTool::new(platform, example_policy())
+            .with_secrets(Arc::new(Secrets::from_config(&config, |_| None)))
+    }
+
+    #[test]
+    fn description_points_to_other_tools() {
+        let tool = tool(Arc::new(MockPlatform::new()));
+        assert_eq!(tool.name(), "http_request");
+        assert!(tool.description().contains("rest_request"));
+        assert!(tool.description().contains("web_fetch"));
+        let params = tool.parameters();
+        assert_eq!(params["required"], json!(["url"]));
+        assert!(params["properties"]["json"].is_object());
+    }
+
+    #[tokio::test]
+    async fn get_returns_status_headers_and_body() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "GET https://api.example.com/v1/items",
+            MockResponse::json(200, &json!({"items": [1, 2]})).with_header("X-Request-Id", "r-1"),
+        );
+
+        let result = tool(platform.clone())
+            .execute(json!({"url": "https://api.example.com/v1/items"}))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["content_type"], "application/json");
+        assert_eq!(result["headers"]["x-request-id"], "r-1");
+        assert_eq!(result["body"], r#"{"items":[1,2]}"#);
+        assert!(result.get("binary").is_none());
+        assert!(
+            platform.http.requests()[0]
+                .headers
+                .contains_key(NO_REDIRECT_HEADER)
+        );
+    }
+
+    #[tokio::test]
+    async fn error_statuses_are_results_not_errors() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "DELETE https://api.example.com/v1/items/9",
+            MockResponse::json(404, &json!({"error": "no such item"})),
+        );
+
+        let result = tool(platform)
+            .execute(json!({"url": "https://api.example.com/v1/items/9", "method": "delete"}))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 404);
+        assert!(result["body"].as_str().unwrap().contains("no such item"));
+    }
+
+    #[tokio::test]
+    async fn json_form_and_raw_bodies() {
+        let platform = Arc::new(MockPlatform::new());
+        for _ in 0..3 {
+            platform.http.respond(
+                "https://api.example.com/v1/echo",
+                MockResponse::text(201, "ok"),
+            );
+        }
+        let tool = tool(platform.clone());
+
+        tool.execute(json!({
+            "url": "https://api.example.com/v1/echo",
+            "method": "POST",
+            "json": {"name": "widget", "count": 2},
+        }))
+        .await
+        .unwrap();
+        tool.execute(json!({
+            "url": "https://api.example.com/v1/echo",
+            "method": "PUT",
+            "form": {"q": "a b&c", "n": 3},
+        }))
+        .await
+        .unwrap();
+        tool.execute(json!({
+            "url": "https://api.example.com/v1/echo",
+            "method": "PATCH",
+            "headers": {"Content-Type": "text/csv"},
+            "body": "a,b\n1,2\n",
+        }))
+        .await
+        .unwrap();
+
+        let requests = platform.http.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].headers["Content-Type"], "application/json");
+        let sent: serde_json::Value = serde_json::from_str(&requests[0].body_text()).unwrap();
+        assert_eq!(sent, json!({"name": "widget", "count": 2}));
+
+        assert_eq!(
+            requests[1].headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+        let form = requests[1].body_text();
+        assert!(form.contains("q=a%20b%26c"), "{form}");
+        assert!(form.contains("n=3"), "{form}");
+
+        assert_eq!(requests[2].headers["Content-Type"], "text/csv");
+        assert_eq!(requests[2].body_text(), "a,b\n1,2\n");
+    }
+
+    #[tokio::test]
+    async fn only_one_body_kind_is_allowed() {
+        let err = tool(Arc::new(MockPlatform::new()))
+            .execute(json!({
+                "url": "https://api.example.com/x",
+                "json": {},
+                "body": "raw",
+            }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+        assert!(err.to_string().contains("json, body"));
+    }
+
+    #[tokio::test]
+    async fn secrets_are_interpolated_and_redacted() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://api.example.com/v1/forecast",
+            MockResponse::text(200, "key w-secret-123 accepted")
+                .with_header("content-type", "text/plain")
+                .with_header("x-echo-key", "w-secret-123"),
+        );
+
+        let result = tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/v1/forecast",
+                "headers": {"X-Api-Key": "{{secret:weather}}", "Authorization": "Token {{secret:weather}}"},
+            }))
+            .await
+            .unwrap();
+
+        let sent = &platform.http.requests()[0].headers;
+        assert_eq!(sent["X-Api-Key"], "w-secret-123");
+        assert_eq!(sent["Authorization"], "Token w-secret-123");
+        assert_eq!(result["body"], "key [REDACTED] accepted");
+        assert_eq!(result["headers"]["x-echo-key"], "[REDACTED]");
+        assert!(!result.to_string().contains("w-secret-123"));
+    }
+
+    #[tokio::test]
+    async fn unknown_secret_is_rejected() {
+        let err = tool(Arc::new(MockPlatform::new()))
+            .execute(json!({
+                "url": "https://api.example.com/x",
+                "headers": {"X-Api-Key": "{{secret:gitlab}}"},
+            }))
+            .await
+            .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("unknown secret 'gitlab'"), "{msg}");
+        assert!(msg.contains("configured secrets: github, weather"), "{msg}");
+    }
+
+    #[tokio::test]
+    async fn secrets_not_granted_to_http_request_are_refused() {
+        let platform = Arc::new(MockPlatform::new());
+        let err = tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/x",
+                "headers": {"Authorization": "Bearer {{secret:github}}"},
+            }))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ToolError::PermissionDenied { reason, .. } if reason.contains("not granted")),
+            "{err:?}"
+        );
+        assert!(platform.http.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn binary_and_oversized_responses() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://cdn.example.com/logo.png",
+            MockResponse::bytes(200, vec![0x89, b'P', b'N', b'G', 0, 0, 0xff])
+                .with_header("content-type", "image/png"),
+        );
+        platform.http.respond(
+            "https://api.example.com/big",
+            MockResponse::bytes(200, vec![b'x'; 300]),
+        );
+        let tool = tool(platform).with_max_bytes(100);
+
+        let result = tool
+            .execute(json!({"url": "https://cdn.example.com/logo.png"}))
+            .await
+            .unwrap();
+        assert_eq!(result["binary"], true);
+        assert_eq!(result["bytes"], 7);
+        assert!(result.get("body").is_none());
+
+        let result = tool
+            .execute(json!({"url": "https://api.example.com/big"}))
+            .await
+            .unwrap();
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["limit_bytes"], 100);
+        assert!(
+            result["body"]
+                .as_str()
+                .unwrap()
+                .ends_with("[... truncated at 100 bytes, total response was 300 bytes]")
+        );
+    }
+
+    #[tokio::test]
+    async fn private_and_link_local_targets_are_blocked() {
+        let platform = Arc::new(MockPlatform::new());
+        let tool = HttpRequestTool::new(platform.clone(), UrlPolicy::default());
+
+        for url in [
+            "http://localhost:8080/admin",
+            "http://127.0.0.1/",
+            "http://[::1]:9000/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://169.254.10.1/",
+            "http://10.0.0.5/internal",
+            "http://[fe80::1]/",
+        ] {
+            let err = tool.execute(json!({"url": url})).await.unwrap_err();
+            assert!(
+                matches!(err, ToolError::PermissionDenied { .. }),
+                "{url}: {err:?}"
+            );
+        }
+        assert!(platform.http.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn redirect_to_link_local_is_blocked() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "POST https://api.example.com/v1/jobs",
+            MockResponse::text(307, "").with_header("location", "http://169.254.169.254/latest/"),
+        );
+
+        let err = tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/v1/jobs",
+                "method": "POST",
+                "json": {"run": true},
+            }))
+            .await
+            .unwrap_err();
+        match err {
+            ToolError::PermissionDenied { tool, reason } => {
+                assert_eq!(tool, "http_request");
+                assert!(reason.contains("redirect from"), "{reason}");
+            }
+            other => panic!("expected PermissionDenied, got {other:?}"),
+        }
+        assert_eq!(platform.http.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn temporary_redirect_keeps_method_and_body() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "POST https://api.example.com/v1/jobs",
+            MockResponse::text(308, "").with_header("location", "https://api.example.com/v2/jobs"),
+        );
+        platform.http.respond(
+            "POST https://api.example.com/v2/jobs",
+            MockResponse::text(202, "queued"),
+        );
+
+        let result = tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/v1/jobs",
+                "method": "POST",
+                "json": {"run": true},
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 202);
+        assert_eq!(result["url"], "https://api.example.com/v2/jobs");
+        let second = &platform.http.requests()[1];
+        assert_eq!(second.body_text(), r#"{"run":true}"#);
+    }
+
+    #[tokio::test]
+    async fn secret_headers_are_dropped_on_cross_origin_redirect() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "GET https://api.example.com/v1/forecast",
+            MockResponse::text(302, "").with_header("location", "/v2/forecast"),
+        );
+        platform.http.respond(
+            "GET https://api.example.com/v2/forecast",
+            MockResponse::text(302, "")
+                .with_header("location", "https://cdn.example.com/forecast.json"),
+        );
+        platform.http.respond(
+            "GET https://cdn.example.com/forecast.json",
+            MockResponse::text(200, "sunny"),
+        );
+
+        let result = tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/v1/forecast",
+                "headers": {"X-Api-Key": "{{secret:weather}}", "Accept": "application/json"},
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["body"], "sunny");
+
+        let requests = platform.http.requests();
+        assert_eq!(requests.len(), 3);
+        // Same origin: the key is still sent.
+        assert_eq!(requests[1].headers["X-Api-Key"], "w-secret-123");
+        // Other origin: the key is gone, plain headers are kept.
+        assert!(!requests[2].headers.contains_key("X-Api-Key"));
+        assert_eq!(requests[2].headers["Accept"], "application/json");
+    }
+
+    #[tokio::test]
+    async fn values_filled_in_by_secret_use_are_dropped_on_cross_origin_redirect() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "GET https://api.example.com/v1/forecast",
+            MockResponse::text(302, "")
+                .with_header("location", "https://cdn.example.com/forecast.json"),
+        );
+        platform.http.respond(
+            "GET https://cdn.example.com/forecast.json",
+            MockResponse::text(200, "sunny"),
+        );
+
+        // `secret_use` resolves references before the tool runs.
+        tool(platform.clone())
+            .execute(json!({
+                "url": "https://api.example.com/v1/forecast",
+                "headers": {"X-Api-Key": "w-secret-123"},
+            }))
+            .await
+            .unwrap();
+
+        let requests = platform.http.requests();
+        assert_eq!(requests[0].headers["X-Api-Key"], "w-secret-123");
+        assert!(!requests[1].headers.contains_key("X-Api-Key"));
+    }
+}
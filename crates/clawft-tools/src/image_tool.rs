@@ -0,0 +1,706 @@
+//! Image tool for attachment workflows (feature `image`).
+//!
+//! `image` inspects, resizes, converts and crops PNG, JPEG and WebP files
+//! in the workspace, so agents can fit an attachment to a vision model's
+//! input limits before sending it on.
+//!
+//! Inputs are checked against a file size limit and, before decoding,
+//! against a pixel-count limit read from the image header, so a small
+//! file that expands to a huge bitmap is refused without allocating it.
+//! Outputs are encoded in memory, written to a temporary sibling and
+//! renamed into place. Decoding and encoding run on the blocking thread
+//! pool.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use clawft_core::tools::registry::{Tool, ToolError};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{ColorType, DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use serde_json::json;
+use tracing::debug;
+
+use crate::file_tools::{validate_parent_path, validate_path};
+
+/// Formats the tool reads and writes.
+const FORMATS: &[ImageFormat] = &[ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP];
+
+/// Default JPEG quality for `convert` and JPEG outputs.
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// Size limits applied to every input image.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageLimits {
+    /// Largest image file that may be opened, in bytes.
+    pub max_file_bytes: u64,
+    /// Largest image that may be decoded, as width times height.
+    pub max_pixels: u64,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 50 * 1024 * 1024,
+            max_pixels: 50_000_000,
+        }
+    }
+}
+
+fn image_error(e: image::ImageError) -> ToolError {
+    ToolError::ExecutionFailed(format!("image error: {e}"))
+}
+
+fn format_name(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpeg",
+        ImageFormat::WebP => "webp",
+        _ => "unknown",
+    }
+}
+
+/// Parse a format name (`png`, `jpeg`/`jpg`, `webp`).
+fn parse_format(name: &str) -> Result<ImageFormat, ToolError> {
+    ImageFormat::from_extension(name.to_ascii_lowercase())
+        .filter(|f| FORMATS.contains(f))
+        .ok_or_else(|| {
+            ToolError::InvalidArgs(format!(
+                "unsupported image format '{name}'; use png, jpeg or webp"
+            ))
+        })
+}
+
+/// An input image read into memory and checked against the limits.
+struct Source {
+    bytes: Vec<u8>,
+    format: ImageFormat,
+    color: ColorType,
+    width: u32,
+    height: u32,
+}
+
+impl Source {
+    fn reader(&self) -> ImageReader<Cursor<&[u8]>> {
+        ImageReader::with_format(Cursor::new(&self.bytes[..]), self.format)
+    }
+
+    /// Decode the image, letting the decoder allocate only what the
+    /// header promised.
+    fn decode(&self) -> Result<DynamicImage, ToolError> {
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(self.width);
+        limits.max_image_height = Some(self.height);
+        let mut reader = self.reader();
+        reader.limits(limits);
+        reader.decode().map_err(image_error)
+    }
+}
+
+/// Read `path` and check its size, format and pixel count without
+/// decoding the pixels.
+fn load(path: &Path, shown: &str, limits: ImageLimits) -> Result<Source, ToolError> {
+    let len = std::fs::metadata(path)
+        .map_err(|e| ToolError::ExecutionFailed(format!("cannot stat {shown}: {e}")))?
+        .len();
+    if len > limits.max_file_bytes {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{shown} is {len} bytes, over the {} byte limit",
+            limits.max_file_bytes
+        )));
+    }
+    let bytes = std::fs::read(path)
+        .map_err(|e| ToolError::ExecutionFailed(format!("cannot read {shown}: {e}")))?;
+
+    let format = image::guess_format(&bytes)
+        .ok()
+        .filter(|f| FORMATS.contains(f))
+        .ok_or_else(|| {
+            ToolError::ExecutionFailed(format!("{shown} is not a PNG, JPEG or WebP image"))
+        })?;
+    let decoder = ImageReader::with_format(Cursor::new(&bytes[..]), format)
+        .into_decoder()
+        .map_err(image_error)?;
+    let (width, height) = decoder.dimensions();
+    let color = decoder.color_type();
+    drop(decoder);
+    let pixels = u64::from(width) * u64::from(height);
+    if pixels > limits.max_pixels {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{shown} is {width}x{height} ({pixels} pixels), over the {} pixel limit",
+            limits.max_pixels
+        )));
+    }
+    Ok(Source {
+        bytes,
+        format,
+        color,
+        width,
+        height,
+    })
+}
+
+/// Encode `img` as `format`. JPEG drops the alpha channel; WebP is
+/// written lossless.
+fn encode(img: &DynamicImage, format: ImageFormat, quality: u8) -> Result<Vec<u8>, ToolError> {
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            let rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+            rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality))
+                .map_err(image_error)?;
+        }
+        ImageFormat::WebP => {
+            let converted = if img.color().has_alpha() {
+                DynamicImage::ImageRgba8(img.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(img.to_rgb8())
+            };
+            converted
+                .write_to(&mut Cursor::new(&mut out), format)
+                .map_err(image_error)?;
+        }
+        _ => img
+            .write_to(&mut Cursor::new(&mut out), format)
+            .map_err(image_error)?,
+    }
+    Ok(out)
+}
+
+/// Write `bytes` to a temporary sibling of `path`, then rename it over
+/// `path` so readers never see a partial image.
+fn write_atomic(path: &Path, shown: &str, bytes: &[u8]) -> Result<(), ToolError> {
+    let io_error =
+        |e: std::io::Error| ToolError::ExecutionFailed(format!("cannot write {shown}: {e}"));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    if let Err(e) = std::fs::write(&tmp, bytes).and_then(|()| std::fs::rename(&tmp, path)) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(io_error(e));
+    }
+    Ok(())
+}
+
+/// Default output path: the input's stem plus `suffix`, with the
+/// extension of `format`.
+fn default_output(input: &str, suffix: &str, format: ImageFormat) -> String {
+    let path = Path::new(input);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    let ext = format.extensions_str().first().copied().unwrap_or("img");
+    let name = format!("{stem}{suffix}.{ext}");
+    match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(parent) => parent.join(name).to_string_lossy().into_owned(),
+        None => name,
+    }
+}
+
+fn u32_arg(args: &serde_json::Value, name: &str) -> Result<Option<u32>, ToolError> {
+    match args.get(name).filter(|v| !v.is_null()) {
+        None => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                ToolError::InvalidArgs(format!("{name} must be a non-negative integer"))
+            }),
+    }
+}
+
+fn required_u32(args: &serde_json::Value, name: &str) -> Result<u32, ToolError> {
+    u32_arg(args, name)?
+        .ok_or_else(|| ToolError::InvalidArgs(format!("missing required field: {name}")))
+}
+
+/// Dimensions that fit `width` x `height` inside the requested bounds,
+/// keeping the aspect ratio. Images are never enlarged.
+fn fit_within(
+    width: u32,
+    height: u32,
+    max_width: Option<u32>,
+    max_height: Option<u32>,
+) -> (u32, u32) {
+    let scale_w = max_width.map_or(1.0, |w| f64::from(w) / f64::from(width));
+    let scale_h = max_height.map_or(1.0, |h| f64::from(h) / f64::from(height));
+    let scale = scale_w.min(scale_h).min(1.0);
+    if scale >= 1.0 {
+        return (width, height);
+    }
+    let scaled = |n: u32| ((f64::from(n) * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, ToolError> + Send + 'static,
+) -> Result<T, ToolError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("image task failed: {e}")))?
+}
+
+// ---------------------------------------------------------------------------
+// ImageTool
+// ---------------------------------------------------------------------------
+
+/// Inspect, resize, convert and crop workspace images.
+pub struct ImageTool {
+    workspace: PathBuf,
+    limits: ImageLimits,
+}
+
+impl ImageTool {
+    /// Create a new `ImageTool` confined to `workspace`.
+    pub fn new(workspace: PathBuf, limits: ImageLimits) -> Self {
+        Self { workspace, limits }
+    }
+}
+
+#[async_trait]
+impl Tool for ImageTool {
+    fn name(&self) -> &str {
+        "image"
+    }
+
+    fn description(&self) -> &str {
+        "Work with PNG, JPEG and WebP images in the workspace. 'info' reports format and \
+         dimensions; 'resize' scales an image down to fit max_width/max_height keeping its \
+         aspect ratio; 'convert' changes the format; 'crop' cuts out a rectangle. Operations \
+         that write return the output path and its dimensions."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["info", "resize", "convert", "crop"],
+                    "description": "What to do with the image"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Input image path relative to the workspace"
+                },
+                "output": {
+                    "type": "string",
+                    "description": "Output path relative to the workspace. Defaults to a name derived from the input (e.g. photo_1024x768.jpg)."
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["png", "jpeg", "webp"],
+                    "description": "Output format. Required for convert; otherwise taken from the output extension or the input."
+                },
+                "quality": {
+                    "type": "integer",
+                    "description": format!("JPEG quality 1-100 (default {DEFAULT_JPEG_QUALITY})")
+                },
+                "max_width": { "type": "integer", "description": "resize: maximum width in pixels" },
+                "max_height": { "type": "integer", "description": "resize: maximum height in pixels" },
+                "x": { "type": "integer", "description": "crop: left edge" },
+                "y": { "type": "integer", "description": "crop: top edge" },
+                "width": { "type": "integer", "description": "crop: width" },
+                "height": { "type": "integer", "description": "crop: height" }
+            },
+            "required": ["operation", "path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: operation".into()))?
+            .to_string();
+        let input = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: path".into()))?
+            .to_string();
+        let path = validate_path(&input, &self.workspace)?;
+        let limits = self.limits;
+
+        if operation == "info" {
+            return blocking(move || {
+                let source = load(&path, &input, limits)?;
+                Ok(json!({
+                    "path": input,
+                    "format": format_name(source.format),
+                    "mime_type": source.format.to_mime_type(),
+                    "width": source.width,
+                    "height": source.height,
+                    "color": format!("{:?}", source.color),
+                    "has_alpha": source.color.has_alpha(),
+                    "bytes": source.bytes.len(),
+                }))
+            })
+            .await;
+        }
+
+        let quality = match u32_arg(&args, "quality")? {
+            None => DEFAULT_JPEG_QUALITY,
+            Some(q @ 1..=100) => q as u8,
+            Some(q) => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "quality must be between 1 and 100, got {q}"
+                )));
+            }
+        };
+        let format = args
+            .get("format")
+            .and_then(|v| v.as_str())
+            .map(parse_format)
+            .transpose()?;
+        let output = args
+            .get("output")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        // The transform to apply, checked before anything is decoded.
+        enum Op {
+            Resize(Option<u32>, Option<u32>),
+            Convert,
+            Crop(u32, u32, u32, u32),
+        }
+        let op = match operation.as_str() {
+            "resize" => {
+                let max_width = u32_arg(&args, "max_width")?;
+                let max_height = u32_arg(&args, "max_height")?;
+                if max_width.is_none() && max_height.is_none() {
+                    return Err(ToolError::InvalidArgs(
+                        "resize needs max_width, max_height or both".into(),
+                    ));
+                }
+                if max_width == Some(0) || max_height == Some(0) {
+                    return Err(ToolError::InvalidArgs(
+                        "max_width and max_height must be positive".into(),
+                    ));
+                }
+                Op::Resize(max_width, max_height)
+            }
+            "convert" if format.is_none() => {
+                return Err(ToolError::InvalidArgs(
+                    "convert needs a target format".into(),
+                ));
+            }
+            "convert" => Op::Convert,
+            "crop" => {
+                let width = required_u32(&args, "width")?;
+                let height = required_u32(&args, "height")?;
+                if width == 0 || height == 0 {
+                    return Err(ToolError::InvalidArgs(
+                        "crop width and height must be positive".into(),
+                    ));
+                }
+                Op::Crop(
+                    u32_arg(&args, "x")?.unwrap_or(0),
+                    u32_arg(&args, "y")?.unwrap_or(0),
+                    width,
+                    height,
+                )
+            }
+            other => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "unknown operation '{other}'; use info, resize, convert or crop"
+                )));
+            }
+        };
+
+        let source = {
+            let path = path.clone();
+            let input = input.clone();
+            blocking(move || load(&path, &input, limits)).await?
+        };
+
+        // Output format: explicit, else the output extension, else the input's.
+        let out_format = match (format, &output) {
+            (Some(f), _) => f,
+            (None, Some(out)) => match Path::new(out).extension().and_then(|e| e.to_str()) {
+                Some(ext) => parse_format(ext)?,
+                None => source.format,
+            },
+            (None, None) => source.format,
+        };
+        let output = match output {
+            Some(out) => out,
+            None => {
+                let suffix = match op {
+                    Op::Resize(w, h) => {
+                        let (w, h) = fit_within(source.width, source.height, w, h);
+                        format!("_{w}x{h}")
+                    }
+                    Op::Convert => String::new(),
+                    Op::Crop(..) => "_crop".to_string(),
+                };
+                default_output(&input, &suffix, out_format)
+            }
+        };
+        let out_path = validate_parent_path(&output, &self.workspace)?;
+        if let Op::Crop(x, y, width, height) = op
+            && (u64::from(x) + u64::from(width) > u64::from(source.width)
+                || u64::from(y) + u64::from(height) > u64::from(source.height))
+        {
+            return Err(ToolError::InvalidArgs(format!(
+                "crop {width}x{height} at ({x}, {y}) falls outside the {}x{} image",
+                source.width, source.height
+            )));
+        }
+
+        debug!(operation = %operation, input = %input, output = %output, "processing image");
+        blocking(move || {
+            let img = source.decode()?;
+            let img = match op {
+                Op::Resize(w, h) => {
+                    let (w, h) = fit_within(img.width(), img.height(), w, h);
+                    if (w, h) == (img.width(), img.height()) {
+                        img
+                    } else {
+                        img.resize_exact(w, h, FilterType::Lanczos3)
+                    }
+                }
+                Op::Convert => img,
+                Op::Crop(x, y, width, height) => img.crop_imm(x, y, width, height),
+            };
+            let bytes = encode(&img, out_format, quality)?;
+            write_atomic(&out_path, &output, &bytes)?;
+            Ok(json!({
+                "path": output,
+                "format": format_name(out_format),
+                "width": img.width(),
+                "height": img.height(),
+                "bytes": bytes.len(),
+                "source": {
+                    "path": input,
+                    "format": format_name(source.format),
+                    "width": source.width,
+                    "height": source.height,
+                },
+            }))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_workspace() -> PathBuf {
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let ws = std::env::temp_dir().join(format!("clawft_image_tool_test_{pid}_{id}"));
+        std::fs::create_dir_all(&ws).unwrap();
+        ws
+    }
+
+    fn tool(ws: &Path) -> ImageTool {
+        ImageTool::new(ws.to_path_buf(), ImageLimits::default())
+    }
+
+    /// Write a gradient fixture of the given size and format.
+    fn fixture(ws: &Path, name: &str, width: u32, height: u32, format: ImageFormat) {
+        let img = RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(x * 255 / width) as u8, (y * 255 / height) as u8, 128])
+        });
+        let bytes = encode(&DynamicImage::ImageRgb8(img), format, 90).unwrap();
+        std::fs::write(ws.join(name), bytes).unwrap();
+    }
+
+    #[tokio::test]
+    async fn info_reports_format_and_dimensions() {
+        let ws = temp_workspace();
+        fixture(&ws, "a.png", 40, 30, ImageFormat::Png);
+        fixture(&ws, "b.jpg", 64, 48, ImageFormat::Jpeg);
+        fixture(&ws, "c.webp", 20, 10, ImageFormat::WebP);
+
+        for (path, format, width, height) in [
+            ("a.png", "png", 40, 30),
+            ("b.jpg", "jpeg", 64, 48),
+            ("c.webp", "webp", 20, 10),
+        ] {
+            let info = tool(&ws)
+                .execute(json!({"operation": "info", "path": path}))
+                .await
+                .unwrap();
+            assert_eq!(info["format"], format, "{path}");
+            assert_eq!(info["width"], width, "{path}");
+            assert_eq!(info["height"], height, "{path}");
+            assert_eq!(info["has_alpha"], false, "{path}");
+        }
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn resize_fits_within_bounds_and_keeps_aspect() {
+        let ws = temp_workspace();
+        fixture(&ws, "photo.jpg", 400, 200, ImageFormat::Jpeg);
+
+        let result = tool(&ws)
+            .execute(json!({"operation": "resize", "path": "photo.jpg", "max_width": 100}))
+            .await
+            .unwrap();
+        assert_eq!(result["path"], "photo_100x50.jpg");
+        assert_eq!(result["width"], 100);
+        assert_eq!(result["height"], 50);
+        let (w, h) = image::image_dimensions(ws.join("photo_100x50.jpg")).unwrap();
+        assert_eq!((w, h), (100, 50));
+
+        // Already small enough: written unchanged in size, never enlarged.
+        let same = tool(&ws)
+            .execute(json!({
+                "operation": "resize",
+                "path": "photo.jpg",
+                "max_width": 1000,
+                "max_height": 1000,
+                "output": "out/copy.png"
+            }))
+            .await
+            .unwrap();
+        assert_eq!(same["format"], "png");
+        assert_eq!(
+            (same["width"].as_u64(), same["height"].as_u64()),
+            (Some(400), Some(200))
+        );
+        assert!(ws.join("out/copy.png").exists());
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn convert_and_crop_write_new_files() {
+        let ws = temp_workspace();
+        let img = RgbaImage::from_fn(30, 20, |x, _| Rgba([x as u8 * 8, 0, 0, 200]));
+        img.save_with_format(ws.join("logo.png"), ImageFormat::Png)
+            .unwrap();
+
+        let webp = tool(&ws)
+            .execute(json!({"operation": "convert", "path": "logo.png", "format": "webp"}))
+            .await
+            .unwrap();
+        assert_eq!(webp["path"], "logo.webp");
+        let info = tool(&ws)
+            .execute(json!({"operation": "info", "path": "logo.webp"}))
+            .await
+            .unwrap();
+        assert_eq!(info["format"], "webp");
+        assert_eq!(info["has_alpha"], true);
+
+        let jpeg = tool(&ws)
+            .execute(
+                json!({"operation": "convert", "path": "logo.png", "format": "jpg", "quality": 70}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(jpeg["path"], "logo.jpg");
+        assert_eq!(
+            image::guess_format(&std::fs::read(ws.join("logo.jpg")).unwrap()).unwrap(),
+            ImageFormat::Jpeg
+        );
+
+        let crop = tool(&ws)
+            .execute(json!({"operation": "crop", "path": "logo.png", "x": 10, "y": 5, "width": 15, "height": 10}))
+            .await
+            .unwrap();
+        assert_eq!(crop["path"], "logo_crop.png");
+        assert_eq!(
+            (crop["width"].as_u64(), crop["height"].as_u64()),
+            (Some(15), Some(10))
+        );
+        let cropped = image::open(ws.join("logo_crop.png")).unwrap().to_rgba8();
+        assert_eq!(cropped.get_pixel(0, 0), &Rgba([80, 0, 0, 200]));
+
+        let err = tool(&ws)
+            .execute(json!({"operation": "crop", "path": "logo.png", "x": 20, "width": 15, "height": 10}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn oversized_images_are_rejected_before_decoding() {
+        let ws = temp_workspace();
+        fixture(&ws, "big.png", 300, 300, ImageFormat::Png);
+
+        let by_pixels = ImageTool::new(
+            ws.clone(),
+            ImageLimits {
+                max_pixels: 10_000,
+                ..ImageLimits::default()
+            },
+        );
+        let err = by_pixels
+            .execute(json!({"operation": "resize", "path": "big.png", "max_width": 50}))
+            .await
+            .unwrap_err();
+        let ToolError::ExecutionFailed(msg) = err else {
+            panic!("expected ExecutionFailed, got {err:?}");
+        };
+        assert!(msg.contains("300x300"), "{msg}");
+        assert!(msg.contains("pixel limit"), "{msg}");
+        assert!(!ws.join("big_50x50.png").exists());
+
+        let by_bytes = ImageTool::new(
+            ws.clone(),
+            ImageLimits {
+                max_file_bytes: 100,
+                ..ImageLimits::default()
+            },
+        );
+        let err = by_bytes
+            .execute(json!({"operation": "info", "path": "big.png"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(ref m) if m.contains("byte limit")));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_outside_workspace_and_non_images() {
+        let ws = temp_workspace();
+        std::fs::write(ws.join("notes.txt"), "not an image").unwrap();
+
+        let err = tool(&ws)
+            .execute(json!({"operation": "info", "path": "../escape.png"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::InvalidPath(_) | ToolError::FileNotFound(_)
+        ));
+
+        let err = tool(&ws)
+            .execute(json!({"operation": "info", "path": "notes.txt"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(_)));
+
+        fixture(&ws, "a.png", 4, 4, ImageFormat::Png);
+        let err = tool(&ws)
+            .execute(json!({"operation": "resize", "path": "a.png", "max_width": 2, "output": "../out.png"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidPath(_)));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[test]
+    fn fit_within_never_enlarges() {
+        assert_eq!(fit_within(400, 200, Some(100), None), (100, 50));
+        assert_eq!(fit_within(400, 200, Some(100), Some(10)), (20, 10));
+        assert_eq!(fit_within(400, 200, Some(800), None), (400, 200));
+        assert_eq!(fit_within(1000, 1, Some(10), None), (10, 1));
+    }
+}
@@ -7,7 +7,17 @@
 //!
 //! - **File tools** ([`file_tools`]): `read_file`, `write_file`, `edit_file`, `list_directory`
 //! - **Shell tool** ([`shell_tool`]): `exec_shell`
-//! - **Memory tools** ([`memory_tool`]): `memory_read`, `memory_write`
+//! - **Job tools** ([`shell_jobs`]): `job_status`, `job_output`, `job_kill`
+//! - **Memory tools** ([`memory_tool`]): `memory_read`, `memory_search`, `memory_write`
+//! - **Task list** ([`task_tool`]): `task_list`
+//! - **Web tools** ([`web_search`], [`web_fetch`], [`http_request`]): `web_search`,
+//!   `web_fetch`, `http_request`
+//! - **Download tool** (`download_file`, native only): `download_file`
+//! - **SQLite tools** (`sqlite_tool`, feature `sqlite`): `sqlite_query`, `sqlite_execute`
+//! - **Image tool** (`image_tool`, feature `image`): `image`
+//! - **Desktop tools** (`desktop_tool`, feature `desktop`): `clipboard`, `notify`
+//! - **Schedule tool** (`schedule_tool`, feature `cron`): `schedule_task`,
+//!   registered by the gateway alongside its scheduler
 //!
 //! All file and directory operations enforce workspace path containment
 //! to prevent directory traversal attacks.
@@ -16,14 +26,33 @@
 pub mod render_ui;
 #[cfg(feature = "delegate")]
 pub mod delegate_tool;
+#[cfg(feature = "desktop")]
+pub mod desktop_tool;
+#[cfg(feature = "native")]
+pub mod download_file;
 pub mod file_tools;
+mod html_extract;
+pub mod http_request;
+#[cfg(feature = "image")]
+pub mod image_tool;
 pub mod memory_tool;
 pub mod message_tool;
+mod redirects;
+#[cfg(feature = "cron")]
+pub mod schedule_tool;
 pub mod security_policy;
 #[cfg(feature = "native-exec")]
+pub mod shell_jobs;
+#[cfg(feature = "native-exec")]
+pub mod shell_session;
+#[cfg(feature = "native-exec")]
 pub mod shell_tool;
 #[cfg(feature = "native-exec")]
 pub mod spawn_tool;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_tool;
+pub mod task_tool;
+mod text_edit;
 pub mod url_safety;
 #[cfg(feature = "voice")]
 pub mod audio_synthesize;
@@ -39,8 +68,13 @@ pub mod web_search;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use clawft_core::agent::memory::MemoryBackend;
+use clawft_core::agent::secrets::Secrets;
 use clawft_core::tools::registry::ToolRegistry;
 use clawft_platform::Platform;
+use clawft_types::config::{
+    DesktopToolConfig, ExecToolConfig, HttpToolConfig, MemoryConfig, SqliteToolConfig,
+};
 
 use crate::security_policy::CommandPolicy;
 use crate::url_safety::UrlPolicy;
@@ -51,8 +85,8 @@ use crate::web_search::WebSearchConfig;
 /// Creates instances of every tool in this crate and registers them
 /// with `registry`. File tools are sandboxed to `workspace_dir`.
 /// Shell and spawn tools are gated by `command_policy` and require the
-/// `native-exec` feature. Web fetch tool is gated by `url_policy` for
-/// SSRF protection.
+/// `native-exec` feature. Web fetch, HTTP request and download tools are
+/// gated by `url_policy` for SSRF protection.
 ///
 /// # Arguments
 ///
@@ -62,6 +96,18 @@ use crate::web_search::WebSearchConfig;
 /// * `command_policy` - Security policy for shell/spawn command execution.
 /// * `url_policy` - Security policy for URL fetching (SSRF protection).
 /// * `web_search_config` - Configuration for the web search tool (API key / endpoint).
+/// * `memory` - Memory backend for the memory tools (`agents.memory.backend`).
+/// * `memory_config` - Memory hygiene settings for `memory_write`.
+/// * `exec_config` - Background job limit and persistent session settings
+///   for `exec_shell`.
+/// * `http_config` - Timeout and size limit for `http_request`.
+/// * `secrets` - Named secrets (`tools.secrets`) that `http_request`
+///   headers can reference, subject to each secret's tool grants.
+/// * `sqlite_config` - Limits for the SQLite tools, and whether
+///   `sqlite_execute` is registered (feature `sqlite`).
+/// * `desktop_config` - Opt-in for the `clipboard` and `notify` tools
+///   (feature `desktop`).
+#[allow(clippy::too_many_arguments)]
 pub fn register_all<P: Platform + 'static>(
     registry: &mut ToolRegistry,
     platform: Arc<P>,
@@ -69,10 +115,23 @@ pub fn register_all<P: Platform + 'static>(
     command_policy: CommandPolicy,
     url_policy: UrlPolicy,
     web_search_config: WebSearchConfig,
+    memory: Arc<dyn MemoryBackend>,
+    memory_config: &MemoryConfig,
+    exec_config: &ExecToolConfig,
+    http_config: &HttpToolConfig,
+    secrets: Arc<Secrets>,
+    sqlite_config: &SqliteToolConfig,
+    desktop_config: &DesktopToolConfig,
 ) {
     // Suppress unused warning when native-exec is disabled.
     #[cfg(not(feature = "native-exec"))]
-    let _ = &command_policy;
+    let _ = (&command_policy, exec_config);
+    #[cfg(not(feature = "sqlite"))]
+    let _ = sqlite_config;
+    #[cfg(not(feature = "desktop"))]
+    if desktop_config.clipboard || desktop_config.notifications {
+        tracing::warn!("tools.desktop is enabled, but this build lacks desktop tool support");
+    }
 
     registry.register(Arc::new(file_tools::ReadFileTool::new(
         platform.clone(),
@@ -92,14 +151,32 @@ pub fn register_all<P: Platform + 'static>(
     )));
 
     #[cfg(feature = "native-exec")]
-    registry.register(Arc::new(shell_tool::ShellExecTool::new(
-        workspace_dir.clone(),
-        command_policy.clone(),
-    )));
+    {
+        let jobs = shell_jobs::ShellJobs::new(exec_config.max_background_jobs);
+        let mut shell =
+            shell_tool::ShellExecTool::new(workspace_dir.clone(), command_policy.clone())
+                .with_jobs(jobs.clone());
+        if exec_config.persistent_session {
+            shell = shell.with_sessions(shell_session::ShellSessions::new(
+                workspace_dir.clone(),
+                std::time::Duration::from_secs(exec_config.session_idle_secs),
+            ));
+        }
+        registry.register(Arc::new(shell));
+        registry.register(Arc::new(shell_jobs::JobStatusTool::new(jobs.clone())));
+        registry.register(Arc::new(shell_jobs::JobOutputTool::new(jobs.clone())));
+        registry.register(Arc::new(shell_jobs::JobKillTool::new(jobs)));
+    }
 
-    registry.register(Arc::new(memory_tool::MemoryReadTool::new(platform.clone())));
+    registry.register(Arc::new(memory_tool::MemoryReadTool::new(memory.clone())));
+    registry.register(Arc::new(memory_tool::MemorySearchTool::new(memory.clone())));
     registry.register(Arc::new(memory_tool::MemoryWriteTool::new(
+        memory,
+        memory_config.duplicate_threshold,
+    )));
+    registry.register(Arc::new(task_tool::TaskListTool::new(
         platform.clone(),
+        &workspace_dir,
     )));
     registry.register(Arc::new(web_search::WebSearchTool::new(
         platform.clone(),
@@ -107,8 +184,48 @@ pub fn register_all<P: Platform + 'static>(
     )));
     registry.register(Arc::new(web_fetch::WebFetchTool::new(
         platform.clone(),
-        url_policy,
+        url_policy.clone(),
+    )));
+    #[cfg(feature = "native")]
+    registry.register(Arc::new(download_file::DownloadFileTool::new(
+        platform.clone(),
+        workspace_dir.clone(),
+        url_policy.clone(),
     )));
+    registry.register(Arc::new(
+        http_request::HttpRequestTool::new(platform.clone(), url_policy)
+            .with_secrets(secrets)
+            .with_timeout(http_config.timeout_secs)
+            .with_max_bytes(http_config.max_response_bytes),
+    ));
+
+    #[cfg(feature = "sqlite")]
+    {
+        let limits = sqlite_tool::SqliteLimits {
+            max_db_bytes: sqlite_config.max_db_bytes,
+            max_rows: sqlite_config.max_rows,
+            max_result_bytes: sqlite_config.max_result_bytes,
+        };
+        registry.register(Arc::new(sqlite_tool::SqliteQueryTool::new(
+            workspace_dir.clone(),
+            limits,
+        )));
+        if sqlite_config.allow_writes {
+            registry.register(Arc::new(sqlite_tool::SqliteExecuteTool::new(
+                workspace_dir.clone(),
+                limits,
+            )));
+        }
+    }
+
+    #[cfg(feature = "image")]
+    registry.register(Arc::new(image_tool::ImageTool::new(
+        workspace_dir.clone(),
+        image_tool::ImageLimits::default(),
+    )));
+
+    #[cfg(feature = "desktop")]
+    desktop_tool::register(registry, desktop_config);
 
     #[cfg(feature = "native-exec")]
     registry.register(Arc::new(spawn_tool::SpawnTool::new(
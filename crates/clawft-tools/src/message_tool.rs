@@ -91,6 +91,7 @@ impl Tool for MessageTool {
             content: content.to_owned(),
             reply_to: None,
             media: vec![],
+            attachments: vec![],
             metadata: Default::default(),
         };
 
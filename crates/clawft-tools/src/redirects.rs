@@ -0,0 +1,155 @@
+//! Redirect following with per-hop URL policy checks.
+//!
+//! The platform HTTP client is asked not to follow redirects (via
+//! [`NO_REDIRECT_HEADER`]) so that every `Location` can be validated
+//! against the [`UrlPolicy`] before it is requested. Shared by
+//! `web_fetch`, `http_request` and `download_file`.
+
+use std::collections::HashMap;
+
+use clawft_core::tools::registry::ToolError;
+use clawft_platform::http::{HttpClient, HttpResponse, NO_REDIRECT_HEADER};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::url_safety::{UrlPolicy, validate_url};
+
+/// Maximum number of redirects followed for one request.
+pub(crate) const MAX_REDIRECTS: usize = 5;
+
+/// Headers carrying credentials, dropped when a redirect changes origin.
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// Headers describing the request body, dropped with it.
+const BODY_HEADERS: &[&str] = &["content-type", "content-length"];
+
+/// The outcome of [`send`].
+pub(crate) struct Followed {
+    /// The final, non-redirect response.
+    pub response: HttpResponse,
+    /// URL the final response came from.
+    pub url: Url,
+    /// Each redirect target, in order.
+    pub redirects: Vec<String>,
+}
+
+/// Validate `url` against `policy`, as a `PermissionDenied` for `tool`.
+pub(crate) fn check_url(tool: &str, url: &str, policy: &UrlPolicy) -> Result<(), ToolError> {
+    validate_url(url, policy).map_err(|e| {
+        warn!(url, error = %e, "URL rejected by safety policy");
+        ToolError::PermissionDenied {
+            tool: tool.into(),
+            reason: e.to_string(),
+        }
+    })
+}
+
+/// Send a request, following up to [`MAX_REDIRECTS`] redirects and
+/// re-validating each target against `policy`.
+///
+/// `303` responses, and `301`/`302` responses to a `POST`, are followed
+/// with a body-less `GET`; `307`/`308` keep the method and body.
+/// Credential headers, and the `secret_headers` the caller filled in from
+/// secrets, are dropped once a redirect leaves the origin.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send(
+    http: &dyn HttpClient,
+    policy: &UrlPolicy,
+    tool: &str,
+    mut method: String,
+    mut url: Url,
+    mut headers: HashMap<String, String>,
+    mut body: Option<Vec<u8>>,
+    secret_headers: &[String],
+) -> Result<Followed, ToolError> {
+    let mut redirects = Vec::new();
+    loop {
+        let mut send = headers.clone();
+        send.insert(NO_REDIRECT_HEADER.to_string(), "1".to_string());
+        let response = http
+            .request(&method, url.as_str(), &send, body.as_deref())
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("request failed: {e}")))?;
+
+        let Some(next) = next_hop(
+            tool,
+            policy,
+            &url,
+            response.status,
+            &response.headers,
+            redirects.len(),
+        )?
+        else {
+            return Ok(Followed {
+                response,
+                url,
+                redirects,
+            });
+        };
+
+        debug!(from = %url, to = %next, status = response.status, "following redirect");
+        if response.status == 303 || (matches!(response.status, 301 | 302) && method == "POST") {
+            method = "GET".into();
+            body = None;
+            headers.retain(|k, _| !BODY_HEADERS.iter().any(|h| k.eq_ignore_ascii_case(h)));
+        }
+        if next.origin() != url.origin() {
+            headers.retain(|k, _| {
+                !CREDENTIAL_HEADERS.iter().any(|c| k.eq_ignore_ascii_case(c))
+                    && !secret_headers.iter().any(|s| k.eq_ignore_ascii_case(s))
+            });
+        }
+        redirects.push(next.to_string());
+        url = next;
+    }
+}
+
+/// The target of a redirect response from `url`, validated against
+/// `policy`, or `None` when the response is not a redirect. `followed` is
+/// the number of redirects already followed for this request.
+pub(crate) fn next_hop(
+    tool: &str,
+    policy: &UrlPolicy,
+    url: &Url,
+    status: u16,
+    headers: &HashMap<String, String>,
+    followed: usize,
+) -> Result<Option<Url>, ToolError> {
+    let (true, Some(location)) = (is_redirect(status), header(headers, "location")) else {
+        return Ok(None);
+    };
+    if followed == MAX_REDIRECTS {
+        return Err(ToolError::ExecutionFailed(format!(
+            "too many redirects (more than {MAX_REDIRECTS}), last hop was {url}"
+        )));
+    }
+    let next = url.join(location).map_err(|e| {
+        ToolError::ExecutionFailed(format!("invalid redirect location '{location}': {e}"))
+    })?;
+    if !matches!(next.scheme(), "http" | "https") {
+        return Err(ToolError::PermissionDenied {
+            tool: tool.into(),
+            reason: format!("redirect to non-HTTP URL {next}"),
+        });
+    }
+    check_url(tool, next.as_str(), policy).map_err(|e| match e {
+        ToolError::PermissionDenied { tool, reason } => ToolError::PermissionDenied {
+            tool,
+            reason: format!("redirect from {url} blocked: {reason}"),
+        },
+        other => other,
+    })?;
+    Ok(Some(next))
+}
+
+fn is_redirect(status: u16) -> bool {
+    matches!(status, 301 | 302 | 303 | 307 | 308)
+}
+
+/// Case-insensitive header lookup.
+pub(crate) fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
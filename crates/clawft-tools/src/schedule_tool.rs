@@ -0,0 +1,346 @@
+//! Schedule task tool.
+//!
+//! Provides a `schedule_task` tool that lets the agent schedule a one-shot
+//! job for itself ("remind me in 45 minutes"). The job is added to the
+//! gateway's [`CronService`]; when it fires, its prompt runs as a normal
+//! agent turn and the reply goes to the conversation that scheduled it.
+//!
+//! Each agent may have at most `max_pending` of its jobs waiting to run.
+//! Jobs record the agent that created them (`created_by`), and every job
+//! created is logged.
+//!
+//! # Feature gate
+//!
+//! This module is gated behind `feature = "cron"`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_services::cron_service::CronService;
+use clawft_services::error::ServiceError;
+use clawft_types::cron::{CronJob, CronJobState, CronPayload, CronSchedule, parse_interval};
+use serde_json::{Value, json};
+use tracing::info;
+
+/// Agent ID used when the caller did not set one.
+const DEFAULT_AGENT: &str = "default";
+
+/// Tool that schedules a one-shot agent turn.
+pub struct ScheduleTaskTool {
+    cron: Arc<CronService>,
+    max_pending: usize,
+}
+
+impl ScheduleTaskTool {
+    /// Create a tool adding jobs to `cron`, allowing each agent at most
+    /// `max_pending` jobs waiting to run.
+    pub fn new(cron: Arc<CronService>, max_pending: usize) -> Self {
+        Self { cron, max_pending }
+    }
+
+    /// Jobs created by `agent` that have not run yet.
+    async fn pending_for(&self, agent: &str) -> Result<usize, ToolError> {
+        let jobs = self.cron.list_jobs().await.map_err(exec_err)?;
+        Ok(jobs
+            .iter()
+            .filter(|j| j.enabled && j.created_by.as_deref() == Some(agent))
+            .count())
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleTaskTool {
+    fn name(&self) -> &str {
+        "schedule_task"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a prompt to run once at a later time, e.g. a reminder. When it \
+         runs, the reply is sent to this conversation. Give either 'at' or 'in'."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "prompt": {
+                    "type": "string",
+                    "description": "What to do when the task runs, e.g. 'Remind the user to call Sam.'"
+                },
+                "at": {
+                    "type": "string",
+                    "description": "When to run, as an RFC 3339 timestamp (e.g. '2026-03-01T09:45:00Z')"
+                },
+                "in": {
+                    "type": "string",
+                    "description": "When to run, as a delay from now (e.g. '45m', '2h', '1d')"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Short label for the task (optional)"
+                },
+                "channel": {
+                    "type": "string",
+                    "description": "Channel to send the reply on (defaults to this conversation's)"
+                },
+                "chat_id": {
+                    "type": "string",
+                    "description": "Chat to send the reply to (defaults to this conversation)"
+                }
+            },
+            "required": ["prompt"]
+        })
+    }
+
+    fn parallel_safe(&self) -> bool {
+        // The pending-job limit is checked before adding; calls must not
+        // interleave.
+        false
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value, ToolError> {
+        let prompt = str_arg(&args, "prompt")
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: prompt".into()))?;
+        let at = run_time(str_arg(&args, "at"), str_arg(&args, "in"), Utc::now())?;
+        let (channel, chat_id) = reply_target(
+            str_arg(&args, "channel"),
+            str_arg(&args, "chat_id"),
+            clawft_core::runtime::session_key().as_deref(),
+        )?;
+
+        let agent = clawft_core::runtime::agent_id().unwrap_or_else(|| DEFAULT_AGENT.into());
+        let pending = self.pending_for(&agent).await?;
+        if pending >= self.max_pending {
+            return Err(ToolError::ExecutionFailed(format!(
+                "agent '{agent}' already has {pending} scheduled tasks waiting \
+                 (limit {}); wait for one to run first",
+                self.max_pending
+            )));
+        }
+
+        let name = match str_arg(&args, "name") {
+            Some(name) => format!("{agent}: {name} ({})", at.format("%Y-%m-%d %H:%M:%S")),
+            None => format!("{agent}: task at {}", at.to_rfc3339()),
+        };
+        let job = CronJob {
+            id: String::new(),
+            name: name.clone(),
+            enabled: true,
+            schedule: CronSchedule::once(at),
+            payload: CronPayload {
+                message: prompt.to_owned(),
+                deliver: channel.is_some(),
+                channel: channel.clone(),
+                to: chat_id.clone(),
+                ..Default::default()
+            },
+            state: CronJobState::default(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            delete_after_run: false,
+            created_by: Some(agent.clone()),
+        };
+        let job_id = self.cron.create_job(job).await.map_err(|e| match e {
+            ServiceError::InvalidSchedule(_) | ServiceError::DuplicateJobName(_) => {
+                ToolError::InvalidArgs(e.to_string())
+            }
+            e => exec_err(e),
+        })?;
+
+        info!(
+            agent = %agent,
+            job_id = %job_id,
+            at = %at.to_rfc3339(),
+            channel = channel.as_deref().unwrap_or("-"),
+            chat_id = chat_id.as_deref().unwrap_or("-"),
+            "schedule_task created one-shot job"
+        );
+
+        Ok(json!({
+            "status": "scheduled",
+            "job_id": job_id,
+            "name": name,
+            "at": at.to_rfc3339(),
+            "pending": pending + 1,
+            "limit": self.max_pending,
+        }))
+    }
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn exec_err(e: ServiceError) -> ToolError {
+    ToolError::ExecutionFailed(e.to_string())
+}
+
+/// The run time from exactly one of `at` (RFC 3339) and `delay` (an
+/// interval such as `"45m"`), which must be after `now`.
+fn run_time(
+    at: Option<&str>,
+    delay: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, ToolError> {
+    let time = match (at, delay) {
+        (Some(at), None) => DateTime::parse_from_rfc3339(at)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|e| ToolError::InvalidArgs(format!("invalid 'at' {at:?}: {e}")))?,
+        (None, Some(delay)) => {
+            let delay = parse_interval(delay).ok_or_else(|| {
+                ToolError::InvalidArgs(format!(
+                    "invalid 'in' {delay:?}: expected e.g. '45m', '2h' or '1d'"
+                ))
+            })?;
+            now + delay
+        }
+        _ => {
+            return Err(ToolError::InvalidArgs(
+                "give exactly one of 'at' and 'in'".into(),
+            ));
+        }
+    };
+    if time <= now {
+        return Err(ToolError::InvalidArgs(format!(
+            "{} is in the past",
+            time.to_rfc3339()
+        )));
+    }
+    Ok(time)
+}
+
+/// Where the reply goes: the given channel and chat, or else the calling
+/// session's (`"<channel>:<chat_id>"`). Turns that are themselves
+/// scheduled (channel `cron`) have no conversation to reply to.
+fn reply_target(
+    channel: Option<&str>,
+    chat_id: Option<&str>,
+    session_key: Option<&str>,
+) -> Result<(Option<String>, Option<String>), ToolError> {
+    match (channel, chat_id) {
+        (Some(channel), Some(chat_id)) => Ok((Some(channel.into()), Some(chat_id.into()))),
+        (None, None) => Ok(session_key
+            .and_then(|key| key.split_once(':'))
+            .filter(|(channel, _)| *channel != "cron")
+            .map_or((None, None), |(channel, chat_id)| {
+                (Some(channel.into()), Some(chat_id.into()))
+            })),
+        _ => Err(ToolError::InvalidArgs(
+            "give both 'channel' and 'chat_id', or neither".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use clawft_types::event::InboundMessage;
+    use tokio::sync::mpsc;
+
+    async fn make_tool(max_pending: usize) -> (ScheduleTaskTool, mpsc::Receiver<InboundMessage>) {
+        let dir = std::env::temp_dir().join(format!(
+            "clawft-schedule-tool-{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let (tx, rx) = mpsc::channel(16);
+        let cron = CronService::new(dir.join("cron.jsonl"), tx).await.unwrap();
+        (ScheduleTaskTool::new(Arc::new(cron), max_pending), rx)
+    }
+
+    #[test]
+    fn run_time_accepts_at_or_delay() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        assert_eq!(
+            run_time(None, Some("45m"), now).unwrap(),
+            now + Duration::minutes(45)
+        );
+        assert_eq!(
+            run_time(Some("2026-03-01T11:00:00+01:00"), None, now).unwrap(),
+            now + Duration::hours(1)
+        );
+        for (at, delay) in [
+            (None, None),
+            (Some("2026-03-01T10:00:00Z"), Some("5m")),
+            (Some("tomorrow"), None),
+            (None, Some("soon")),
+            // Exactly now, and earlier, are in the past.
+            (Some("2026-03-01T10:00:00+01:00"), None),
+            (Some("2026-03-01T08:00:00Z"), None),
+        ] {
+            assert!(
+                matches!(run_time(at, delay, now), Err(ToolError::InvalidArgs(_))),
+                "{at:?} {delay:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reply_goes_to_calling_conversation_by_default() {
+        assert_eq!(
+            reply_target(None, None, Some("telegram:42")).unwrap(),
+            (Some("telegram".into()), Some("42".into()))
+        );
+        assert_eq!(
+            reply_target(Some("slack"), Some("C1"), Some("telegram:42")).unwrap(),
+            (Some("slack".into()), Some("C1".into()))
+        );
+        assert_eq!(
+            reply_target(None, None, Some("cron:job-1")).unwrap(),
+            (None, None)
+        );
+        assert_eq!(reply_target(None, None, None).unwrap(), (None, None));
+        assert!(reply_target(Some("slack"), None, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn schedules_once_job_for_the_calling_agent() {
+        let (tool, _rx) = make_tool(5).await;
+        let run = clawft_core::runtime::with_session_key(
+            "telegram:42",
+            tool.execute(json!({ "prompt": "Remind me to stretch", "in": "45m" })),
+        );
+        let result = clawft_core::runtime::with_agent_id("coach", run)
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "scheduled");
+        assert_eq!(result["pending"], 1);
+
+        let jobs = tool.cron.list_jobs().await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert_eq!(job.id, result["job_id"].as_str().unwrap());
+        assert_eq!(job.created_by.as_deref(), Some("coach"));
+        assert_eq!(job.payload.message, "Remind me to stretch");
+        assert!(job.payload.deliver);
+        assert_eq!(job.payload.channel.as_deref(), Some("telegram"));
+        assert_eq!(job.payload.to.as_deref(), Some("42"));
+        assert!(job.state.next_run_at.unwrap() > Utc::now() + Duration::minutes(44));
+    }
+
+    #[tokio::test]
+    async fn pending_tasks_are_capped_per_agent() {
+        let (tool, _rx) = make_tool(2).await;
+        let schedule = |agent: &'static str, delay: &'static str| {
+            let tool = &tool;
+            async move {
+                clawft_core::runtime::with_agent_id(
+                    agent,
+                    tool.execute(json!({ "prompt": "ping", "in": delay })),
+                )
+                .await
+            }
+        };
+        schedule("a", "1h").await.unwrap();
+        schedule("a", "2h").await.unwrap();
+        let err = schedule("a", "3h").await.unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(ref m) if m.contains("limit 2")));
+        // Other agents have their own allowance.
+        schedule("b", "3h").await.unwrap();
+    }
+}
@@ -0,0 +1,563 @@
+//! Background shell jobs.
+//!
+//! `exec_shell` with `background: true` hands the command to a
+//! [`ShellJobs`] table and returns a job id at once. The job's output is
+//! captured as it arrives (head and tail kept, like foreground commands),
+//! and the companion tools `job_status`, `job_output` and `job_kill` let
+//! the agent check on it, read what it printed, or stop it.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use async_trait::async_trait;
+use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_platform::process::{NativeProcessSpawner, ProcessSpawner};
+use serde_json::json;
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::shell_tool::{OutputCapture, merge_json};
+
+/// Default and maximum run time of a background job, in seconds.
+const MAX_JOB_TIMEOUT_SECS: u64 = 3600;
+
+/// Finished jobs kept for `job_status` / `job_output` before the oldest
+/// are forgotten.
+const MAX_FINISHED_JOBS: usize = 32;
+
+/// Where a job is in its life.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JobState {
+    Running,
+    Exited(i32),
+    TimedOut,
+    Killed,
+    Failed(String),
+}
+
+impl JobState {
+    fn label(&self) -> &'static str {
+        match self {
+            JobState::Running => "running",
+            JobState::Exited(_) => "exited",
+            JobState::TimedOut => "timed_out",
+            JobState::Killed => "killed",
+            JobState::Failed(_) => "failed",
+        }
+    }
+}
+
+/// What the reader task records about a job.
+#[derive(Debug)]
+struct JobProgress {
+    state: JobState,
+    output: OutputCapture,
+    finished: Option<Instant>,
+}
+
+/// One background job.
+struct Job {
+    command: String,
+    started: Instant,
+    progress: Arc<Mutex<JobProgress>>,
+    kill: Arc<Notify>,
+}
+
+impl Job {
+    /// Status fields shared by `job_status` and `job_output`.
+    fn status_json(&self, id: u64) -> serde_json::Value {
+        let progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        let end = progress.finished.unwrap_or_else(Instant::now);
+        let mut status = json!({
+            "job_id": id,
+            "command": self.command,
+            "status": progress.state.label(),
+            "elapsed_ms": end.duration_since(self.started).as_millis() as u64,
+            "stdout_bytes": progress.output.stdout.total_bytes(),
+            "stderr_bytes": progress.output.stderr.total_bytes(),
+            "stdout_lines": progress.output.stdout.total_lines(),
+            "stderr_lines": progress.output.stderr.total_lines(),
+        });
+        match &progress.state {
+            JobState::Exited(code) => status["exit_code"] = json!(code),
+            JobState::Failed(error) => status["error"] = json!(error),
+            _ => {}
+        }
+        status
+    }
+
+    fn is_running(&self) -> bool {
+        let progress = self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        progress.state == JobState::Running
+    }
+}
+
+#[derive(Default)]
+struct JobTable {
+    next_id: u64,
+    jobs: BTreeMap<u64, Job>,
+}
+
+impl JobTable {
+    /// Forget the oldest finished jobs beyond [`MAX_FINISHED_JOBS`].
+    fn prune(&mut self) {
+        let finished: Vec<u64> = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| !job.is_running())
+            .map(|(id, _)| *id)
+            .collect();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for id in finished.into_iter().take(excess) {
+            self.jobs.remove(&id);
+        }
+    }
+}
+
+/// Table of background jobs, shared by `exec_shell` and the job tools.
+///
+/// Cloning is cheap; clones see the same jobs.
+#[derive(Clone)]
+pub struct ShellJobs {
+    table: Arc<Mutex<JobTable>>,
+    max_running: usize,
+}
+
+impl ShellJobs {
+    /// Create an empty table allowing `max_running` jobs at once.
+    pub fn new(max_running: usize) -> Self {
+        Self {
+            table: Arc::new(Mutex::new(JobTable::default())),
+            max_running,
+        }
+    }
+
+    fn table(&self) -> std::sync::MutexGuard<'_, JobTable> {
+        self.table.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start `command` in `workspace` as a job and return its id.
+    ///
+    /// The command must already have passed the command policy.
+    /// `timeout_secs` defaults to, and is clamped to, one hour.
+    pub async fn start(
+        &self,
+        command: &str,
+        workspace: &Path,
+        timeout_secs: Option<u64>,
+    ) -> Result<serde_json::Value, ToolError> {
+        let timeout_secs = timeout_secs
+            .unwrap_or(MAX_JOB_TIMEOUT_SECS)
+            .min(MAX_JOB_TIMEOUT_SECS);
+        {
+            let table = self.table();
+            let running = table.jobs.values().filter(|job| job.is_running()).count();
+            if running >= self.max_running {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "too many background jobs running ({running}/{}); wait for one or kill it with job_kill",
+                    self.max_running
+                )));
+            }
+        }
+
+        let mut process = NativeProcessSpawner
+            .spawn_streaming("sh", &["-c", command], Some(workspace), Some(timeout_secs))
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to spawn process: {}", e)))?;
+        process.close_stdin();
+
+        let progress = Arc::new(Mutex::new(JobProgress {
+            state: JobState::Running,
+            output: OutputCapture::default(),
+            finished: None,
+        }));
+        let kill = Arc::new(Notify::new());
+
+        let id = {
+            let mut table = self.table();
+            table.next_id += 1;
+            let id = table.next_id;
+            table.jobs.insert(
+                id,
+                Job {
+                    command: command.to_string(),
+                    started: Instant::now(),
+                    progress: progress.clone(),
+                    kill: kill.clone(),
+                },
+            );
+            table.prune();
+            id
+        };
+
+        tokio::spawn(async move {
+            let killed = loop {
+                tokio::select! {
+                    line = process.next_line() => match line {
+                        Some(line) => progress
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .output
+                            .push(&line),
+                        None => break false,
+                    },
+                    _ = kill.notified() => break true,
+                }
+            };
+            let state = if killed {
+                let _ = process.kill().await;
+                JobState::Killed
+            } else if process.timed_out() {
+                JobState::TimedOut
+            } else {
+                match process.wait().await {
+                    Ok(code) => JobState::Exited(code),
+                    Err(_) if process.timed_out() => JobState::TimedOut,
+                    Err(e) => JobState::Failed(e.to_string()),
+                }
+            };
+            debug!(
+                job_id = id,
+                state = state.label(),
+                "background job finished"
+            );
+            let mut progress = progress.lock().unwrap_or_else(|e| e.into_inner());
+            progress.state = state;
+            progress.finished = Some(Instant::now());
+        });
+
+        debug!(job_id = id, command, timeout_secs, "background job started");
+        Ok(json!({
+            "job_id": id,
+            "status": "running",
+            "timeout_secs": timeout_secs,
+        }))
+    }
+
+    /// Status of job `id`, or of every known job when `id` is `None`.
+    pub fn status(&self, id: Option<u64>) -> Result<serde_json::Value, ToolError> {
+        let table = self.table();
+        match id {
+            Some(id) => Ok(job(&table, id)?.status_json(id)),
+            None => Ok(json!({
+                "jobs": table
+                    .jobs
+                    .iter()
+                    .map(|(id, job)| job.status_json(*id))
+                    .collect::<Vec<_>>(),
+            })),
+        }
+    }
+
+    /// Status of job `id` plus the output it has printed so far.
+    pub fn output(&self, id: u64) -> Result<serde_json::Value, ToolError> {
+        let table = self.table();
+        let job = job(&table, id)?;
+        let output = job
+            .progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .output
+            .to_json();
+        Ok(merge_json(job.status_json(id), output))
+    }
+
+    /// Kill job `id` and everything it started.
+    ///
+    /// Returns once the job has stopped. Killing a finished job is not an
+    /// error; its status is returned unchanged.
+    pub async fn kill(&self, id: u64) -> Result<serde_json::Value, ToolError> {
+        let (kill, progress) = {
+            let table = self.table();
+            let job = job(&table, id)?;
+            if !job.is_running() {
+                return Ok(job.status_json(id));
+            }
+            (job.kill.clone(), job.progress.clone())
+        };
+        kill.notify_one();
+        while progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .finished
+            .is_none()
+        {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        self.status(Some(id))
+    }
+}
+
+fn job(table: &JobTable, id: u64) -> Result<&Job, ToolError> {
+    table
+        .jobs
+        .get(&id)
+        .ok_or_else(|| ToolError::InvalidArgs(format!("no background job with id {id}")))
+}
+
+fn job_id_arg(args: &serde_json::Value) -> Result<Option<u64>, ToolError> {
+    match args.get("job_id") {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| ToolError::InvalidArgs("job_id must be a positive integer".into())),
+    }
+}
+
+fn required_job_id(args: &serde_json::Value) -> Result<u64, ToolError> {
+    job_id_arg(args)?.ok_or_else(|| ToolError::InvalidArgs("missing required field: job_id".into()))
+}
+
+fn job_id_schema(required: bool) -> serde_json::Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": {
+            "job_id": {
+                "type": "integer",
+                "description": "Id returned by exec_shell with background: true"
+            }
+        }
+    });
+    if required {
+        schema["required"] = json!(["job_id"]);
+    }
+    schema
+}
+
+/// `job_status`: where one or all background jobs are.
+pub struct JobStatusTool {
+    jobs: ShellJobs,
+}
+
+impl JobStatusTool {
+    /// Create a `job_status` tool over `jobs`.
+    pub fn new(jobs: ShellJobs) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for JobStatusTool {
+    fn name(&self) -> &str {
+        "job_status"
+    }
+
+    fn description(&self) -> &str {
+        "Show the status of a background shell job (or all jobs when job_id is omitted): \
+         running or finished, exit code, elapsed time and output size so far."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        job_id_schema(false)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        self.jobs.status(job_id_arg(&args)?)
+    }
+
+    fn parallel_safe(&self) -> bool {
+        true
+    }
+}
+
+/// `job_output`: what a background job has printed so far.
+pub struct JobOutputTool {
+    jobs: ShellJobs,
+}
+
+impl JobOutputTool {
+    /// Create a `job_output` tool over `jobs`.
+    pub fn new(jobs: ShellJobs) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for JobOutputTool {
+    fn name(&self) -> &str {
+        "job_output"
+    }
+
+    fn description(&self) -> &str {
+        "Read the stdout and stderr a background shell job has printed so far, \
+         with the start and end kept when it is long."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        job_id_schema(true)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        self.jobs.output(required_job_id(&args)?)
+    }
+
+    fn parallel_safe(&self) -> bool {
+        true
+    }
+}
+
+/// `job_kill`: stop a background job.
+pub struct JobKillTool {
+    jobs: ShellJobs,
+}
+
+impl JobKillTool {
+    /// Create a `job_kill` tool over `jobs`.
+    pub fn new(jobs: ShellJobs) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for JobKillTool {
+    fn name(&self) -> &str {
+        "job_kill"
+    }
+
+    fn description(&self) -> &str {
+        "Kill a background shell job and every process it started."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        job_id_schema(true)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        self.jobs.kill(required_job_id(&args)?).await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security_policy::{CommandPolicy, PolicyMode};
+    use crate::shell_tool::ShellExecTool;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn temp_workspace(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("clawft_jobs_{name}_{}", std::process::id()))
+    }
+
+    fn denylist_policy() -> CommandPolicy {
+        let mut policy = CommandPolicy::safe_defaults();
+        policy.mode = PolicyMode::Denylist;
+        policy
+    }
+
+    async fn wait_finished(jobs: &ShellJobs, id: u64) -> serde_json::Value {
+        for _ in 0..500 {
+            let status = jobs.status(Some(id)).unwrap();
+            if status["status"] != "running" {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn background_job_runs_to_completion() {
+        let ws = temp_workspace("complete");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let jobs = ShellJobs::new(2);
+        let tool = ShellExecTool::new(ws.clone(), denylist_policy()).with_jobs(jobs.clone());
+
+        let started = tool
+            .execute(json!({"command": "echo one; echo two >&2; exit 3", "background": true}))
+            .await
+            .unwrap();
+        assert_eq!(started["status"], "running");
+        let id = started["job_id"].as_u64().unwrap();
+
+        let status = wait_finished(&jobs, id).await;
+        assert_eq!(status["status"], "exited");
+        assert_eq!(status["exit_code"], 3);
+
+        let output = JobOutputTool::new(jobs.clone())
+            .execute(json!({"job_id": id}))
+            .await
+            .unwrap();
+        assert_eq!(output["stdout"], "one\n");
+        assert_eq!(output["stderr"], "two\n");
+        assert_eq!(output["stdout_bytes"], 4);
+
+        let all = JobStatusTool::new(jobs).execute(json!({})).await.unwrap();
+        assert_eq!(all["jobs"].as_array().unwrap().len(), 1);
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn background_job_can_be_killed() {
+        let ws = temp_workspace("kill");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let jobs = ShellJobs::new(1);
+        let tool = ShellExecTool::new(ws.clone(), denylist_policy()).with_jobs(jobs.clone());
+
+        let started = tool
+            .execute(json!({"command": "echo started; sleep 60", "background": true}))
+            .await
+            .unwrap();
+        let id = started["job_id"].as_u64().unwrap();
+
+        // The limit is one running job.
+        let err = tool
+            .execute(json!({"command": "sleep 1", "background": true}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionFailed(_)));
+
+        let killed = JobKillTool::new(jobs.clone())
+            .execute(json!({"job_id": id}))
+            .await
+            .unwrap();
+        assert_eq!(killed["status"], "killed");
+        assert!(
+            jobs.status(Some(id)).unwrap()["elapsed_ms"]
+                .as_u64()
+                .unwrap()
+                < 60_000
+        );
+
+        // Killing again just reports the status.
+        let again = jobs.kill(id).await.unwrap();
+        assert_eq!(again["status"], "killed");
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn background_requires_jobs_and_policy() {
+        let ws = temp_workspace("policy");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+
+        let plain = ShellExecTool::new(ws.clone(), denylist_policy());
+        let err = plain
+            .execute(json!({"command": "echo hi", "background": true}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+
+        let jobs = ShellJobs::new(1);
+        let tool = ShellExecTool::new(ws.clone(), denylist_policy()).with_jobs(jobs.clone());
+        let err = tool
+            .execute(json!({"command": "rm -rf /", "background": true}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::PermissionDenied { .. }));
+        assert_eq!(jobs.status(None).unwrap()["jobs"], json!([]));
+
+        let err = jobs.output(99).unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+}
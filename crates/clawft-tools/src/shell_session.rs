@@ -0,0 +1,402 @@
+//! Persistent shell sessions.
+//!
+//! With `tools.exec.persistentSession` enabled, each agent session gets
+//! one long-lived `sh` that runs its foreground `exec_shell` commands, so
+//! `cd` and `export` carry over from one call to the next. Commands are
+//! fed to the shell's stdin through `command eval` (so a syntax error
+//! fails the command rather than exiting the shell), and a per-command marker
+//! printed afterwards on both streams tells where the command's output
+//! ends and carries its exit status.
+//!
+//! A shell unused for the idle timeout is closed; the next call in that
+//! session starts a fresh one in the workspace. A command that times out
+//! takes its shell down with it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use clawft_core::tools::registry::ToolError;
+use clawft_platform::process::{NativeProcessSpawner, OutputLine, ProcessHandle, ProcessSpawner};
+use serde_json::json;
+use tracing::debug;
+
+use crate::shell_tool::{OutputCapture, merge_json};
+
+/// Source of unique end-of-command markers.
+static MARKER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// One session's shell.
+struct Session {
+    process: Box<dyn ProcessHandle>,
+    last_used: Instant,
+}
+
+type SessionMap = HashMap<String, Arc<tokio::sync::Mutex<Session>>>;
+
+/// Long-lived shells keyed by agent session.
+///
+/// Cloning is cheap; clones share the same shells.
+#[derive(Clone)]
+pub struct ShellSessions {
+    workspace: PathBuf,
+    idle: Duration,
+    sessions: Arc<Mutex<SessionMap>>,
+    reaper_started: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl ShellSessions {
+    /// Create a session table whose shells start in `workspace` and close
+    /// after `idle` without use.
+    pub fn new(workspace: PathBuf, idle: Duration) -> Self {
+        Self {
+            workspace,
+            idle,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            reaper_started: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    /// Number of shells currently open.
+    pub fn len(&self) -> usize {
+        self.map().len()
+    }
+
+    /// Whether no shells are open.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn map(&self) -> std::sync::MutexGuard<'_, SessionMap> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `command` in `session_key`'s shell, starting one if needed.
+    ///
+    /// The result has the same fields as a one-off `exec_shell` run. If
+    /// the command does not finish within `timeout_secs` the shell is
+    /// killed and [`ToolError::Timeout`] returned.
+    pub async fn run(
+        &self,
+        session_key: &str,
+        command: &str,
+        timeout_secs: u64,
+    ) -> Result<serde_json::Value, ToolError> {
+        self.start_reaper();
+        let session = self.session(session_key).await?;
+        let mut session = session.lock().await;
+
+        let marker = format!(
+            "__clawft_done_{}_{}__",
+            std::process::id(),
+            MARKER_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let script = format!(
+            "command eval '{}' </dev/null\n\
+             printf '%s %d\\n' '{marker}' \"$?\"\n\
+             printf '%s\\n' '{marker}' >&2\n",
+            command.replace('\'', r"'\''")
+        );
+
+        let outcome = tokio::time::timeout(Duration::from_secs(timeout_secs), async {
+            session
+                .process
+                .write_stdin(script.as_bytes())
+                .await
+                .map_err(|e| ToolError::ExecutionFailed(format!("session shell closed: {e}")))?;
+            Ok::<_, ToolError>(read_until_marker(session.process.as_mut(), &marker).await)
+        })
+        .await;
+        session.last_used = Instant::now();
+
+        match outcome {
+            Ok(Ok((output, Some(exit_code)))) => Ok(merge_json(
+                json!({ "exit_code": exit_code }),
+                output.to_json(),
+            )),
+            Ok(Ok((output, None))) => {
+                // The shell exited (e.g. the command ran `exit`).
+                let exit_code = session.process.wait().await.unwrap_or(-1);
+                drop(session);
+                self.map().remove(session_key);
+                debug!(session_key, "session shell exited");
+                Ok(merge_json(
+                    json!({ "exit_code": exit_code, "session_ended": true }),
+                    output.to_json(),
+                ))
+            }
+            Ok(Err(e)) => {
+                drop(session);
+                self.map().remove(session_key);
+                Err(e)
+            }
+            Err(_) => {
+                let _ = session.process.kill().await;
+                drop(session);
+                self.map().remove(session_key);
+                Err(ToolError::Timeout(timeout_secs))
+            }
+        }
+    }
+
+    /// The shell for `session_key`, spawning it if there is none.
+    async fn session(
+        &self,
+        session_key: &str,
+    ) -> Result<Arc<tokio::sync::Mutex<Session>>, ToolError> {
+        if let Some(session) = self.map().get(session_key) {
+            return Ok(session.clone());
+        }
+        let process = NativeProcessSpawner
+            .spawn_streaming("sh", &[], Some(&self.workspace), None)
+            .await
+            .map_err(|e| {
+                ToolError::ExecutionFailed(format!("failed to spawn session shell: {e}"))
+            })?;
+        debug!(session_key, "session shell started");
+        let session = Arc::new(tokio::sync::Mutex::new(Session {
+            process,
+            last_used: Instant::now(),
+        }));
+        // A concurrent call may have started one first; keep that one.
+        Ok(self
+            .map()
+            .entry(session_key.to_string())
+            .or_insert(session)
+            .clone())
+    }
+
+    /// Start the task that closes idle shells, once.
+    fn start_reaper(&self) {
+        if self.reaper_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let sessions = Arc::downgrade(&self.sessions);
+        let idle = self.idle;
+        let period = (idle / 2).max(Duration::from_secs(1));
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(period).await;
+                if !reap_idle(&sessions, idle) {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Drop shells idle longer than `idle`. Returns `false` once the table
+/// itself is gone.
+fn reap_idle(sessions: &Weak<Mutex<SessionMap>>, idle: Duration) -> bool {
+    let Some(sessions) = sessions.upgrade() else {
+        return false;
+    };
+    let mut map = sessions.lock().unwrap_or_else(|e| e.into_inner());
+    map.retain(|key, session| {
+        // A shell busy with a command is in use, however long it runs.
+        let Ok(session) = session.try_lock() else {
+            return true;
+        };
+        let keep = session.last_used.elapsed() < idle;
+        if !keep {
+            debug!(session_key = %key, "closing idle session shell");
+        }
+        keep
+    });
+    true
+}
+
+/// Read lines until `marker` has been seen on both streams.
+///
+/// Returns the command's output and its exit status, or `None` for the
+/// status if the shell closed first.
+async fn read_until_marker(
+    process: &mut dyn ProcessHandle,
+    marker: &str,
+) -> (OutputCapture, Option<i32>) {
+    let mut output = OutputCapture::default();
+    let mut exit_code = None;
+    let mut stderr_done = false;
+    while exit_code.is_none() || !stderr_done {
+        let Some(line) = process.next_line().await else {
+            return (output, None);
+        };
+        let (text, is_stdout) = match &line {
+            OutputLine::Stdout(text) => (text, true),
+            OutputLine::Stderr(text) => (text, false),
+        };
+        let Some(at) = text.find(marker) else {
+            output.push(&line);
+            continue;
+        };
+        // Output without a trailing newline shares the marker's line.
+        let before = &text[..at];
+        if is_stdout {
+            if !before.is_empty() {
+                output.stdout.push(before);
+            }
+            exit_code = Some(text[at + marker.len()..].trim().parse().unwrap_or(-1));
+        } else {
+            if !before.is_empty() {
+                output.stderr.push(before);
+            }
+            stderr_done = true;
+        }
+    }
+    (output, exit_code)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security_policy::{CommandPolicy, PolicyMode};
+    use crate::shell_tool::ShellExecTool;
+    use clawft_core::tools::registry::Tool;
+
+    fn temp_workspace(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "clawft_shell_session_{name}_{}",
+            std::process::id()
+        ))
+    }
+
+    fn denylist_policy() -> CommandPolicy {
+        let mut policy = CommandPolicy::safe_defaults();
+        policy.mode = PolicyMode::Denylist;
+        policy
+    }
+
+    #[tokio::test]
+    async fn session_keeps_cwd_and_env_between_calls() {
+        let ws = temp_workspace("cwd");
+        tokio::fs::create_dir_all(ws.join("sub")).await.unwrap();
+        let sessions = ShellSessions::new(ws.clone(), Duration::from_secs(60));
+
+        let cd = sessions
+            .run("chat:a", "cd sub && export GREETING=hi", 10)
+            .await
+            .unwrap();
+        assert_eq!(cd["exit_code"], 0);
+
+        let pwd = sessions
+            .run("chat:a", "pwd; echo \"$GREETING\"", 10)
+            .await
+            .unwrap();
+        let stdout = pwd["stdout"].as_str().unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert!(lines[0].ends_with("/sub"), "{stdout}");
+        assert_eq!(lines[1], "hi");
+
+        // Another session starts fresh in the workspace.
+        let other = sessions
+            .run("chat:b", "pwd; echo \"[$GREETING]\"", 10)
+            .await
+            .unwrap();
+        let stdout = other["stdout"].as_str().unwrap();
+        assert!(!stdout.lines().next().unwrap().ends_with("/sub"));
+        assert!(stdout.contains("[]"));
+        assert_eq!(sessions.len(), 2);
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn session_reports_status_quotes_and_unterminated_output() {
+        let ws = temp_workspace("status");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let sessions = ShellSessions::new(ws.clone(), Duration::from_secs(60));
+
+        let result = sessions
+            .run("s", "printf 'it'\\''s'; printf oops >&2; (exit 4)", 10)
+            .await
+            .unwrap();
+        assert_eq!(result["exit_code"], 4);
+        assert_eq!(result["stdout"], "it's\n");
+        assert_eq!(result["stderr"], "oops\n");
+
+        // A syntax error fails the command, not the shell.
+        let broken = sessions.run("s", "echo 'unterminated", 10).await.unwrap();
+        assert_ne!(broken["exit_code"], 0);
+        let after = sessions.run("s", "echo still here", 10).await.unwrap();
+        assert_eq!(after["stdout"], "still here\n");
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn session_timeout_and_exit_close_the_shell() {
+        let ws = temp_workspace("timeout");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let sessions = ShellSessions::new(ws.clone(), Duration::from_secs(60));
+
+        let err = sessions.run("s", "sleep 30", 1).await.unwrap_err();
+        assert!(matches!(err, ToolError::Timeout(1)));
+        assert!(sessions.is_empty());
+
+        let exited = sessions.run("s", "exit 7", 10).await.unwrap();
+        assert_eq!(exited["exit_code"], 7);
+        assert_eq!(exited["session_ended"], true);
+        assert!(sessions.is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn idle_shells_are_reaped() {
+        let ws = temp_workspace("idle");
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let sessions = ShellSessions::new(ws.clone(), Duration::from_millis(50));
+
+        sessions.run("s", "true", 10).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(reap_idle(
+            &Arc::downgrade(&sessions.sessions),
+            sessions.idle
+        ));
+        assert!(sessions.is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+
+    #[tokio::test]
+    async fn exec_shell_uses_the_calling_session() {
+        let ws = temp_workspace("tool");
+        tokio::fs::create_dir_all(ws.join("deeper")).await.unwrap();
+        let sessions = ShellSessions::new(ws.clone(), Duration::from_secs(60));
+        let tool = ShellExecTool::new(ws.clone(), denylist_policy()).with_sessions(sessions);
+
+        clawft_core::runtime::with_session_key("chat:1", async {
+            tool.execute(json!({"command": "cd deeper"})).await.unwrap();
+            let pwd = tool.execute(json!({"command": "pwd"})).await.unwrap();
+            assert!(
+                pwd["stdout"]
+                    .as_str()
+                    .unwrap()
+                    .trim_end()
+                    .ends_with("/deeper")
+            );
+            assert!(pwd["duration_ms"].as_u64().is_some());
+        })
+        .await;
+
+        // Outside any session each call gets a fresh shell.
+        tool.execute(json!({"command": "cd deeper"})).await.unwrap();
+        let pwd = tool.execute(json!({"command": "pwd"})).await.unwrap();
+        assert!(
+            !pwd["stdout"]
+                .as_str()
+                .unwrap()
+                .trim_end()
+                .ends_with("/deeper")
+        );
+
+        let _ = tokio::fs::remove_dir_all(&ws).await;
+    }
+}
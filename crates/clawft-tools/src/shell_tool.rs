@@ -2,16 +2,26 @@
 //!
 //! Ported from Python `nanobot/agent/tools/shell.py`. Executes shell commands
 //! with timeout enforcement and dangerous command rejection.
+//!
+//! Output is captured line by line as it arrives, keeping the start and the
+//! end of each stream when a command prints more than fits in a tool
+//! result. Commands can also run in the background as jobs
+//! ([`crate::shell_jobs`]) or, when enabled, in a shell that lives as long
+//! as the agent session ([`crate::shell_session`]).
 
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::time::Instant;
 
 use async_trait::async_trait;
 use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_platform::process::{NativeProcessSpawner, OutputLine, ProcessSpawner};
 use serde_json::json;
 use tracing::{debug, warn};
 
 use crate::security_policy::CommandPolicy;
+use crate::shell_jobs::ShellJobs;
+use crate::shell_session::ShellSessions;
 
 /// Maximum allowed timeout in seconds.
 const MAX_TIMEOUT_SECS: u64 = 300;
@@ -19,25 +29,149 @@ const MAX_TIMEOUT_SECS: u64 = 300;
 /// Default timeout in seconds when none is specified.
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// Bytes of each output stream kept from the start of a command's output.
+const HEAD_BYTES: usize = 16 * 1024;
+
+/// Bytes of each output stream kept from the end of a command's output.
+const TAIL_BYTES: usize = 16 * 1024;
+
+/// One output stream, captured line by line.
+///
+/// The first [`HEAD_BYTES`] and the last [`TAIL_BYTES`] are kept; lines in
+/// between are counted and dropped, so memory stays bounded however much
+/// a command prints.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StreamCapture {
+    head: String,
+    head_full: bool,
+    tail: VecDeque<String>,
+    tail_bytes: usize,
+    total_bytes: u64,
+    total_lines: u64,
+    dropped_lines: u64,
+}
+
+impl StreamCapture {
+    /// Record one line (without its newline).
+    pub(crate) fn push(&mut self, line: &str) {
+        let len = line.len() + 1;
+        self.total_bytes += len as u64;
+        self.total_lines += 1;
+        if !self.head_full && self.head.len() + len <= HEAD_BYTES {
+            self.head.push_str(line);
+            self.head.push('\n');
+            return;
+        }
+        self.head_full = true;
+        let line = &line[..line.floor_char_boundary(TAIL_BYTES)];
+        self.tail_bytes += line.len() + 1;
+        self.tail.push_back(line.to_string());
+        while self.tail_bytes > TAIL_BYTES {
+            let Some(old) = self.tail.pop_front() else {
+                break;
+            };
+            self.tail_bytes -= old.len() + 1;
+            self.dropped_lines += 1;
+        }
+    }
+
+    /// Bytes seen so far, including dropped ones.
+    pub(crate) fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Lines seen so far, including dropped ones.
+    pub(crate) fn total_lines(&self) -> u64 {
+        self.total_lines
+    }
+
+    /// Whether any output was left out of [`render`](Self::render).
+    pub(crate) fn truncated(&self) -> bool {
+        self.total_bytes > (self.head.len() + self.tail_bytes) as u64
+    }
+
+    /// The kept output, with a marker where lines were left out.
+    pub(crate) fn render(&self) -> String {
+        let mut out = self.head.clone();
+        if self.truncated() {
+            let kept = (self.head.len() + self.tail_bytes) as u64;
+            out.push_str(&format!(
+                "[... {} lines ({} bytes) omitted ...]\n",
+                self.dropped_lines,
+                self.total_bytes - kept
+            ));
+        }
+        for line in &self.tail {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Both output streams of a command.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutputCapture {
+    pub stdout: StreamCapture,
+    pub stderr: StreamCapture,
+}
+
+impl OutputCapture {
+    /// Record one line from either stream.
+    pub(crate) fn push(&mut self, line: &OutputLine) {
+        match line {
+            OutputLine::Stdout(line) => self.stdout.push(line),
+            OutputLine::Stderr(line) => self.stderr.push(line),
+        }
+    }
+
+    /// `stdout`, `stderr`, their sizes and whether either was cut, as
+    /// result fields.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        json!({
+            "stdout": self.stdout.render(),
+            "stderr": self.stderr.render(),
+            "stdout_bytes": self.stdout.total_bytes(),
+            "stderr_bytes": self.stderr.total_bytes(),
+            "truncated": self.stdout.truncated() || self.stderr.truncated(),
+        })
+    }
+}
+
+/// Merge the fields of `extra` into the object `base`.
+pub(crate) fn merge_json(
+    mut base: serde_json::Value,
+    extra: serde_json::Value,
+) -> serde_json::Value {
+    if let (Some(base), serde_json::Value::Object(extra)) = (base.as_object_mut(), extra) {
+        base.extend(extra);
+    }
+    base
+}
+
 /// Execute shell commands with safety guardrails.
 ///
 /// Commands are validated against a [`CommandPolicy`] before execution.
 /// In the default allowlist mode, only pre-approved commands can run.
 /// A configurable timeout prevents runaway processes.
+///
+/// With [`with_jobs`](Self::with_jobs), `background: true` starts the
+/// command as a job and returns its id at once. With
+/// [`with_sessions`](Self::with_sessions), foreground commands run in the
+/// calling session's long-lived shell instead of a fresh `sh -c`; calls
+/// made outside an agent session still get a fresh shell.
 pub struct ShellExecTool {
     workspace: PathBuf,
     max_timeout: u64,
     policy: CommandPolicy,
+    jobs: Option<ShellJobs>,
+    sessions: Option<ShellSessions>,
 }
 
 impl ShellExecTool {
     /// Create a new `ShellExecTool` with the given workspace directory and policy.
     pub fn new(workspace: PathBuf, policy: CommandPolicy) -> Self {
-        Self {
-            workspace,
-            max_timeout: MAX_TIMEOUT_SECS,
-            policy,
-        }
+        Self::with_max_timeout(workspace, MAX_TIMEOUT_SECS, policy)
     }
 
     /// Create a new `ShellExecTool` with a custom maximum timeout.
@@ -46,8 +180,59 @@ impl ShellExecTool {
             workspace,
             max_timeout,
             policy,
+            jobs: None,
+            sessions: None,
         }
     }
+
+    /// Allow `background: true`, running jobs in `jobs`.
+    pub fn with_jobs(mut self, jobs: ShellJobs) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Run foreground commands in per-session persistent shells.
+    pub fn with_sessions(mut self, sessions: ShellSessions) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Run `command` in a fresh `sh -c`, capturing output as it arrives.
+    async fn run_once(
+        &self,
+        command: &str,
+        timeout_secs: u64,
+    ) -> Result<serde_json::Value, ToolError> {
+        // Run through the platform spawner so a timeout kills the whole
+        // process group, including anything the command backgrounded.
+        let mut process = NativeProcessSpawner
+            .spawn_streaming(
+                "sh",
+                &["-c", command],
+                Some(&self.workspace),
+                Some(timeout_secs),
+            )
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("failed to spawn process: {}", e)))?;
+        process.close_stdin();
+
+        let mut output = OutputCapture::default();
+        while let Some(line) = process.next_line().await {
+            output.push(&line);
+        }
+        if process.timed_out() {
+            return Err(ToolError::Timeout(timeout_secs));
+        }
+        let exit_code = process
+            .wait()
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("process error: {}", e)))?;
+
+        Ok(merge_json(
+            json!({ "exit_code": exit_code }),
+            output.to_json(),
+        ))
+    }
 }
 
 #[async_trait]
@@ -57,7 +242,8 @@ impl Tool for ShellExecTool {
     }
 
     fn description(&self) -> &str {
-        "Execute a shell command and return its output. Enforces timeout and rejects dangerous commands."
+        "Execute a shell command and return its output. Enforces timeout and rejects dangerous commands. \
+         Set background to start a long-running command as a job and check on it with job_status / job_output."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -70,7 +256,11 @@ impl Tool for ShellExecTool {
                 },
                 "timeout": {
                     "type": "number",
-                    "description": "Timeout in seconds (default 30, max 300)"
+                    "description": "Timeout in seconds (default 30, max 300; background jobs default and max 3600)"
+                },
+                "background": {
+                    "type": "boolean",
+                    "description": "Start the command as a background job and return its job_id immediately (default false)"
                 }
             },
             "required": ["command"]
@@ -82,12 +272,13 @@ impl Tool for ShellExecTool {
             .get("command")
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::InvalidArgs("missing required field: command".to_string()))?;
-
-        let timeout_secs = args
+        let requested_timeout = args
             .get("timeout")
-            .and_then(|v| v.as_u64().or_else(|| v.as_f64().map(|f| f as u64)))
-            .unwrap_or(DEFAULT_TIMEOUT_SECS)
-            .min(self.max_timeout);
+            .and_then(|v| v.as_u64().or_else(|| v.as_f64().map(|f| f as u64)));
+        let background = args
+            .get("background")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         // Security policy check (allowlist/denylist + dangerous patterns).
         if let Err(e) = self.policy.validate(command) {
@@ -98,66 +289,35 @@ impl Tool for ShellExecTool {
             });
         }
 
+        if background {
+            let jobs = self.jobs.as_ref().ok_or_else(|| {
+                ToolError::InvalidArgs("background jobs are not enabled".to_string())
+            })?;
+            debug!(command, "starting background job");
+            return jobs
+                .start(command, &self.workspace, requested_timeout)
+                .await;
+        }
+
+        let timeout_secs = requested_timeout
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .min(self.max_timeout);
         debug!(command, timeout_secs, "executing shell command");
 
         let start = Instant::now();
-
-        let mut child = tokio::process::Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&self.workspace)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .spawn()
-            .map_err(|e| ToolError::ExecutionFailed(format!("failed to spawn process: {}", e)))?;
-
-        // Take stdout/stderr handles before awaiting so we can read them
-        // and still kill the child on timeout.
-        let stdout_handle = child.stdout.take();
-        let stderr_handle = child.stderr.take();
-
-        let wait_result =
-            tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), child.wait()).await;
-
-        let status = match wait_result {
-            Ok(Ok(status)) => status,
-            Ok(Err(e)) => {
-                return Err(ToolError::ExecutionFailed(format!("process error: {}", e)));
-            }
-            Err(_) => {
-                // Attempt to kill the timed-out process.
-                let _ = child.kill().await;
-                return Err(ToolError::Timeout(timeout_secs));
-            }
+        let session_key = clawft_core::runtime::session_key();
+        let result = match (&self.sessions, session_key) {
+            (Some(sessions), Some(key)) => sessions.run(&key, command, timeout_secs).await?,
+            _ => self.run_once(command, timeout_secs).await?,
         };
-
         let duration_ms = start.elapsed().as_millis() as u64;
-        let exit_code = status.code().unwrap_or(-1);
-
-        let stdout = if let Some(mut handle) = stdout_handle {
-            use tokio::io::AsyncReadExt;
-            let mut buf = Vec::new();
-            let _ = handle.read_to_end(&mut buf).await;
-            String::from_utf8_lossy(&buf).into_owned()
-        } else {
-            String::new()
-        };
 
-        let stderr = if let Some(mut handle) = stderr_handle {
-            use tokio::io::AsyncReadExt;
-            let mut buf = Vec::new();
-            let _ = handle.read_to_end(&mut buf).await;
-            String::from_utf8_lossy(&buf).into_owned()
-        } else {
-            String::new()
-        };
+        Ok(merge_json(result, json!({ "duration_ms": duration_ms })))
+    }
 
-        Ok(json!({
-            "exit_code": exit_code,
-            "stdout": stdout,
-            "stderr": stderr,
-            "duration_ms": duration_ms,
-        }))
+    /// Commands may depend on each other's side effects; never interleave them.
+    fn parallel_safe(&self) -> bool {
+        false
     }
 }
 
@@ -272,6 +432,28 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    #[tokio::test]
+    async fn test_timeout_kills_background_jobs() {
+        let ws = temp_workspace();
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let mut policy = CommandPolicy::safe_defaults();
+        policy.mode = crate::security_policy::PolicyMode::Denylist;
+        let tool = ShellExecTool::with_max_timeout(ws.clone(), 5, policy);
+
+        // The backgrounded sleep holds stdout open after `sh` exits; the
+        // timeout must take it down rather than waiting out the 60s.
+        let start = Instant::now();
+        let err = tool
+            .execute(json!({"command": "sleep 60 & echo started", "timeout": 1}))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, ToolError::Timeout(1)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+        cleanup(&ws).await;
+    }
+
     #[tokio::test]
     async fn test_dangerous_rm_rf() {
         let (tool, ws) = setup().await;
@@ -379,6 +561,47 @@ mod tests {
         cleanup(&ws).await;
     }
 
+    #[test]
+    fn test_capture_keeps_head_and_tail() {
+        let mut capture = StreamCapture::default();
+        let line = "x".repeat(99);
+        for _ in 0..1000 {
+            capture.push(&line);
+        }
+        capture.push("last line");
+
+        assert!(capture.truncated());
+        assert_eq!(capture.total_lines(), 1001);
+        assert_eq!(capture.total_bytes(), 1000 * 100 + 10);
+        let rendered = capture.render();
+        assert!(rendered.len() <= HEAD_BYTES + TAIL_BYTES + 64);
+        assert!(rendered.starts_with(&line));
+        assert!(rendered.ends_with("last line\n"));
+        assert!(rendered.contains(" lines ("));
+    }
+
+    #[tokio::test]
+    async fn test_large_output_is_truncated_with_sizes() {
+        let ws = temp_workspace();
+        tokio::fs::create_dir_all(&ws).await.unwrap();
+        let mut policy = CommandPolicy::safe_defaults();
+        policy.mode = crate::security_policy::PolicyMode::Denylist;
+        let tool = ShellExecTool::new(ws.clone(), policy);
+
+        let result = tool
+            .execute(json!({"command": "seq 1 20000"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["stdout_bytes"], 108_894);
+        let stdout = result["stdout"].as_str().unwrap();
+        assert!(stdout.starts_with("1\n2\n"));
+        assert!(stdout.ends_with("19999\n20000\n"));
+
+        cleanup(&ws).await;
+    }
+
     #[tokio::test]
     async fn test_default_timeout_used() {
         let (tool, ws) = setup().await;
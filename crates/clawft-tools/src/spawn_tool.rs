@@ -10,6 +10,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use async_trait::async_trait;
 use clawft_core::tools::registry::{Tool, ToolError};
 use clawft_platform::Platform;
+use clawft_platform::process::collect_output;
 use serde_json::json;
 use tracing::{debug, warn};
 
@@ -142,17 +143,26 @@ impl<P: Platform + 'static> Tool for SpawnTool<P> {
 
         ACTIVE_SPAWNS.fetch_add(1, Ordering::Relaxed);
 
-        // Convert Vec<String> to &[&str] for the ProcessSpawner::run signature.
+        // Convert Vec<String> to &[&str] for the ProcessSpawner signature.
         let arg_refs: Vec<&str> = cmd_args.iter().map(|s| s.as_str()).collect();
 
-        let result = spawner
-            .run(
+        // Stream rather than `run` so a timeout kills the whole process
+        // group, not just the direct child.
+        let result = match spawner
+            .spawn_streaming(
                 command,
                 &arg_refs,
                 Some(&self.workspace),
                 Some(timeout_secs),
             )
-            .await;
+            .await
+        {
+            Ok(mut process) => {
+                process.close_stdin();
+                collect_output(process.as_mut()).await
+            }
+            Err(e) => Err(e),
+        };
 
         ACTIVE_SPAWNS.fetch_sub(1, Ordering::Relaxed);
 
@@ -270,6 +280,53 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn spawn_passes_args_and_workspace_to_spawner() {
+        use clawft_platform::process::ProcessOutput;
+        use clawft_platform::testing::MockPlatform;
+
+        let platform = Arc::new(MockPlatform::new());
+        platform.process.respond(
+            "echo a b",
+            ProcessOutput {
+                exit_code: 3,
+                stdout: "a b\n".into(),
+                stderr: "warn\n".into(),
+            },
+        );
+        let tool = SpawnTool::new(
+            platform.clone(),
+            PathBuf::from("/work"),
+            CommandPolicy::safe_defaults(),
+        );
+
+        let val = tool
+            .execute(json!({"command": "echo", "args": ["a", "b"], "timeout": 5}))
+            .await
+            .unwrap();
+        assert_eq!(val["exit_code"], 3);
+        assert_eq!(val["stdout"], "a b\n");
+        assert_eq!(val["stderr"], "warn\n");
+
+        let calls = platform.process.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].working_dir, Some(PathBuf::from("/work")));
+        assert_eq!(calls[0].timeout_secs, Some(5));
+    }
+
+    #[tokio::test]
+    async fn spawn_without_process_support_fails() {
+        use clawft_platform::testing::MockPlatform;
+
+        let tool = SpawnTool::new(
+            Arc::new(MockPlatform::new().without_process()),
+            PathBuf::from("/work"),
+            CommandPolicy::safe_defaults(),
+        );
+        let err = tool.execute(json!({"command": "echo"})).await.unwrap_err();
+        assert!(err.to_string().contains("not supported"));
+    }
+
     #[tokio::test]
     async fn concurrency_limit_enforced() {
         // Set active spawns to the maximum.
@@ -0,0 +1,928 @@
+//! SQLite tools for local data analysis (feature `sqlite`).
+//!
+//! `sqlite_query` runs read-only SQL against a database file in the
+//! workspace, or against an in-memory database loaded from CSV files.
+//! Only `SELECT`, `WITH` and `EXPLAIN` statements are accepted, each
+//! statement must be one SQLite itself reports as read-only, and the
+//! connection is opened read-only (`PRAGMA query_only` for in-memory
+//! databases) so nothing slips through.
+//!
+//! `sqlite_execute` may modify workspace databases and is only registered
+//! when `tools.sqlite.allowWrites` is set.
+//!
+//! Database calls run on the blocking thread pool.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use clawft_core::tools::registry::{Tool, ToolError};
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{Connection, ErrorCode, OpenFlags, params_from_iter};
+use serde_json::json;
+use tracing::debug;
+
+use crate::file_tools::{validate_parent_path, validate_path};
+
+/// Statements `sqlite_query` accepts, by leading keyword.
+const QUERY_KEYWORDS: &[&str] = &["SELECT", "WITH", "EXPLAIN"];
+
+/// Statements `sqlite_execute` accepts, by leading keyword. `ATTACH`,
+/// `VACUUM INTO` and `PRAGMA` are left out: they can reach files outside
+/// the workspace or change connection settings.
+const EXECUTE_KEYWORDS: &[&str] = &[
+    "INSERT", "UPDATE", "DELETE", "REPLACE", "CREATE", "DROP", "ALTER", "WITH",
+];
+
+/// Size and result limits shared by both tools.
+#[derive(Debug, Clone, Copy)]
+pub struct SqliteLimits {
+    /// Largest database or CSV file that may be opened, in bytes. Writes
+    /// that would grow a database past it fail.
+    pub max_db_bytes: u64,
+    /// Maximum rows returned by one query.
+    pub max_rows: usize,
+    /// Maximum size of the returned rows, serialized as JSON.
+    pub max_result_bytes: usize,
+}
+
+impl Default for SqliteLimits {
+    fn default() -> Self {
+        Self {
+            max_db_bytes: 100 * 1024 * 1024,
+            max_rows: 500,
+            max_result_bytes: 32 * 1024,
+        }
+    }
+}
+
+/// The first keyword of `sql`, uppercased, skipping whitespace and
+/// comments.
+fn leading_keyword(sql: &str) -> String {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.split_once('\n').map_or("", |(_, tail)| tail);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map_or("", |(_, tail)| tail);
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Reject `sql` unless it starts with one of `allowed`.
+fn check_keyword(tool: &str, sql: &str, allowed: &[&str]) -> Result<(), ToolError> {
+    let keyword = leading_keyword(sql);
+    if allowed.contains(&keyword.as_str()) {
+        return Ok(());
+    }
+    let found = if keyword.is_empty() {
+        "no statement".to_string()
+    } else {
+        format!("a {keyword} statement")
+    };
+    Err(ToolError::PermissionDenied {
+        tool: tool.into(),
+        reason: format!(
+            "{tool} accepts {} statements only, got {found}",
+            allowed.join(", ")
+        ),
+    })
+}
+
+fn sql_error(e: rusqlite::Error) -> ToolError {
+    ToolError::ExecutionFailed(format!("sqlite error: {e}"))
+}
+
+/// Convert the `params` argument to SQL values. Booleans become 0/1, and
+/// arrays and objects are bound as JSON text.
+fn sql_params(args: &serde_json::Value) -> Result<Vec<Value>, ToolError> {
+    let Some(params) = args.get("params").filter(|v| !v.is_null()) else {
+        return Ok(Vec::new());
+    };
+    let params = params
+        .as_array()
+        .ok_or_else(|| ToolError::InvalidArgs("params must be an array".into()))?;
+    Ok(params
+        .iter()
+        .map(|p| match p {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Integer(i64::from(*b)),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Integer(i),
+                None => Value::Real(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::Text(s.clone()),
+            other => Value::Text(other.to_string()),
+        })
+        .collect())
+}
+
+fn to_json(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => json!(i),
+        ValueRef::Real(f) => json!(f),
+        ValueRef::Text(t) => json!(String::from_utf8_lossy(t)),
+        ValueRef::Blob(b) => json!(format!("<blob: {} bytes>", b.len())),
+    }
+}
+
+/// Refuse files larger than `max_bytes`.
+fn check_size(path: &Path, shown: &str, max_bytes: u64) -> Result<(), ToolError> {
+    let len = std::fs::metadata(path)
+        .map_err(|e| ToolError::ExecutionFailed(format!("cannot stat {shown}: {e}")))?
+        .len();
+    if len > max_bytes {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{shown} is {len} bytes, over the {max_bytes} byte limit"
+        )));
+    }
+    Ok(())
+}
+
+/// Run a read-only query, returning column metadata and rows capped by
+/// count and serialized size.
+fn run_query(
+    conn: &Connection,
+    sql: &str,
+    params: Vec<Value>,
+    limits: SqliteLimits,
+) -> Result<serde_json::Value, ToolError> {
+    let mut stmt = conn.prepare(sql).map_err(sql_error)?;
+    if !stmt.readonly() {
+        return Err(ToolError::PermissionDenied {
+            tool: "sqlite_query".into(),
+            reason: "statement would modify the database; sqlite_query is read-only".into(),
+        });
+    }
+    let columns: Vec<serde_json::Value> = stmt
+        .columns()
+        .iter()
+        .map(|c| json!({ "name": c.name(), "type": c.decl_type() }))
+        .collect();
+    let count = columns.len();
+
+    let mut rows = stmt.query(params_from_iter(params)).map_err(sql_error)?;
+    let mut out = Vec::new();
+    // Account for the enclosing brackets.
+    let mut bytes = 2;
+    let mut truncated_by = None;
+    while let Some(row) = rows.next().map_err(sql_error)? {
+        if out.len() == limits.max_rows {
+            truncated_by = Some(format!("{} row", limits.max_rows));
+            break;
+        }
+        let values = (0..count)
+            .map(|i| row.get_ref(i).map(to_json))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sql_error)?;
+        let row = serde_json::Value::Array(values);
+        let size = row.to_string().len() + 1;
+        if bytes + size > limits.max_result_bytes {
+            truncated_by = Some(format!("{} byte", limits.max_result_bytes));
+            break;
+        }
+        bytes += size;
+        out.push(row);
+    }
+
+    let mut result = json!({
+        "columns": columns,
+        "rows": out,
+        "row_count": out.len(),
+        "truncated": truncated_by.is_some(),
+    });
+    if let Some(limit) = truncated_by {
+        result["warning"] = json!(format!(
+            "Result stopped at the {limit} limit after {} rows; add a LIMIT or aggregate",
+            out.len()
+        ));
+    }
+    Ok(result)
+}
+
+// ---------------------------------------------------------------------------
+// CSV import
+// ---------------------------------------------------------------------------
+
+/// One `import_csv` entry.
+struct CsvImport {
+    path: String,
+    table: String,
+    delimiter: char,
+}
+
+fn parse_imports(value: &serde_json::Value) -> Result<Vec<CsvImport>, ToolError> {
+    let entries = match value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    entries
+        .into_iter()
+        .map(|entry| {
+            let field = |name: &str| {
+                entry.get(name).and_then(|v| v.as_str()).ok_or_else(|| {
+                    ToolError::InvalidArgs(format!("import_csv entries need a string '{name}'"))
+                })
+            };
+            let delimiter = match entry.get("delimiter").and_then(|v| v.as_str()) {
+                None => ',',
+                Some(d) if d.chars().count() == 1 => d.chars().next().unwrap_or(','),
+                Some(d) => {
+                    return Err(ToolError::InvalidArgs(format!(
+                        "delimiter must be a single character, got '{d}'"
+                    )));
+                }
+            };
+            Ok(CsvImport {
+                path: field("path")?.to_string(),
+                table: field("table")?.to_string(),
+                delimiter,
+            })
+        })
+        .collect()
+}
+
+/// Split CSV text into records. Quoted fields may contain the delimiter,
+/// newlines and doubled quotes. Blank lines are skipped.
+fn parse_csv(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("unterminated quoted field at line {line}"));
+    }
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Column type inferred from CSV values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CsvType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl CsvType {
+    fn sql(self) -> &'static str {
+        match self {
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Text => "TEXT",
+        }
+    }
+}
+
+/// The narrowest type every non-empty value fits. Numbers with a leading
+/// zero (`007`, zip codes) stay text.
+fn infer_type<'a>(values: impl Iterator<Item = &'a str>) -> CsvType {
+    let mut ty = CsvType::Integer;
+    for value in values.map(str::trim).filter(|v| !v.is_empty()) {
+        let digits = value.trim_start_matches(['-', '+']);
+        if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+            return CsvType::Text;
+        }
+        if ty == CsvType::Integer && value.parse::<i64>().is_ok() {
+            continue;
+        }
+        if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            ty = CsvType::Real;
+        } else {
+            return CsvType::Text;
+        }
+    }
+    ty
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Column names from the header row: blanks are named `columnN` and
+/// duplicates get a numeric suffix.
+fn column_names(header: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let base = match name.trim() {
+                "" => format!("column{}", i + 1),
+                trimmed => trimmed.to_string(),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !seen.insert(name.to_ascii_lowercase()) {
+                name = format!("{base}_{n}");
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+/// Load one CSV file into a new table, returning a summary.
+fn import_csv(
+    conn: &Connection,
+    path: &Path,
+    import: &CsvImport,
+) -> Result<serde_json::Value, ToolError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ToolError::ExecutionFailed(format!("cannot read {}: {e}", import.path)))?;
+    let records = parse_csv(&text, import.delimiter)
+        .map_err(|e| ToolError::ExecutionFailed(format!("{}: {e}", import.path)))?;
+    let Some((header, rows)) = records.split_first() else {
+        return Err(ToolError::ExecutionFailed(format!(
+            "{} has no header row",
+            import.path
+        )));
+    };
+    for (i, row) in rows.iter().enumerate() {
+        if row.len() != header.len() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "{}: record {} has {} fields, the header has {}",
+                import.path,
+                i + 2,
+                row.len(),
+                header.len()
+            )));
+        }
+    }
+
+    let names = column_names(header);
+    let types: Vec<CsvType> = (0..names.len())
+        .map(|i| infer_type(rows.iter().map(|row| row[i].as_str())))
+        .collect();
+    let definitions: Vec<String> = names
+        .iter()
+        .zip(&types)
+        .map(|(name, ty)| format!("{} {}", quote_ident(name), ty.sql()))
+        .collect();
+    let table = quote_ident(&import.table);
+
+    let tx = conn.unchecked_transaction().map_err(sql_error)?;
+    tx.execute(
+        &format!("CREATE TABLE {table} ({})", definitions.join(", ")),
+        [],
+    )
+    .map_err(sql_error)?;
+    {
+        let placeholders = vec!["?"; names.len()].join(", ");
+        let mut insert = tx
+            .prepare(&format!("INSERT INTO {table} VALUES ({placeholders})"))
+            .map_err(sql_error)?;
+        for row in rows {
+            let values = row.iter().zip(&types).map(|(raw, ty)| {
+                let raw = raw.trim();
+                match ty {
+                    _ if raw.is_empty() => Value::Null,
+                    CsvType::Integer => raw.parse().map_or(Value::Null, Value::Integer),
+                    CsvType::Real => raw.parse().map_or(Value::Null, Value::Real),
+                    CsvType::Text => Value::Text(raw.to_string()),
+                }
+            });
+            insert
+                .execute(params_from_iter(values))
+                .map_err(sql_error)?;
+        }
+    }
+    tx.commit().map_err(sql_error)?;
+
+    debug!(table = %import.table, rows = rows.len(), "imported csv");
+    Ok(json!({
+        "table": import.table,
+        "path": import.path,
+        "rows": rows.len(),
+        "columns": names
+            .iter()
+            .zip(&types)
+            .map(|(name, ty)| json!({ "name": name, "type": ty.sql() }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, ToolError> + Send + 'static,
+) -> Result<T, ToolError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ToolError::ExecutionFailed(format!("sqlite task failed: {e}")))?
+}
+
+fn required_sql(args: &serde_json::Value) -> Result<String, ToolError> {
+    args.get("sql")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| ToolError::InvalidArgs("missing required field: sql".into()))
+}
+
+// ---------------------------------------------------------------------------
+// SqliteQueryTool
+// ---------------------------------------------------------------------------
+
+/// Read-only SQL over a workspace database or imported CSV files.
+pub struct SqliteQueryTool {
+    workspace: PathBuf,
+    limits: SqliteLimits,
+}
+
+impl SqliteQueryTool {
+    /// Create a new `SqliteQueryTool` confined to `workspace`.
+    pub fn new(workspace: PathBuf, limits: SqliteLimits) -> Self {
+        Self { workspace, limits }
+    }
+}
+
+#[async_trait]
+impl Tool for SqliteQueryTool {
+    fn name(&self) -> &str {
+        "sqlite_query"
+    }
+
+    fn description(&self) -> &str {
+        "Run a read-only SQL query (SELECT, WITH or EXPLAIN) against a SQLite database in the \
+         workspace, or against an in-memory database loaded from CSV files with import_csv. \
+         Returns column names and types and the result rows."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        let import = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "CSV file path relative to the workspace" },
+                "table": { "type": "string", "description": "Name of the table to create" },
+                "delimiter": { "type": "string", "description": "Field delimiter (default ',')" }
+            },
+            "required": ["path", "table"]
+        });
+        json!({
+            "type": "object",
+            "properties": {
+                "sql": {
+                    "type": "string",
+                    "description": "A single SELECT, WITH or EXPLAIN statement"
+                },
+                "database": {
+                    "type": "string",
+                    "description": "Database file relative to the workspace. Omit to query an in-memory database."
+                },
+                "import_csv": {
+                    "description": "CSV file(s) to load into the in-memory database before the query. Column types (INTEGER, REAL, TEXT) are inferred from the values.",
+                    "oneOf": [import, { "type": "array", "items": import }]
+                },
+                "params": {
+                    "type": "array",
+                    "description": "Values bound to ? placeholders, in order"
+                },
+                "max_rows": {
+                    "type": "integer",
+                    "description": format!("Maximum rows to return (default and max {})", self.limits.max_rows)
+                }
+            },
+            "required": ["sql"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let sql = required_sql(&args)?;
+        check_keyword("sqlite_query", &sql, QUERY_KEYWORDS)?;
+        let params = sql_params(&args)?;
+
+        let mut limits = self.limits;
+        if let Some(n) = args.get("max_rows").and_then(|v| v.as_u64()) {
+            limits.max_rows = limits.max_rows.min(n as usize);
+        }
+
+        let database = match args.get("database").and_then(|v| v.as_str()) {
+            Some(db) => {
+                let path = validate_path(db, &self.workspace)?;
+                check_size(&path, db, limits.max_db_bytes)?;
+                Some(path)
+            }
+            None => None,
+        };
+        let imports = match args.get("import_csv").filter(|v| !v.is_null()) {
+            Some(_) if database.is_some() => {
+                return Err(ToolError::InvalidArgs(
+                    "import_csv loads into an in-memory database; omit database to use it".into(),
+                ));
+            }
+            Some(value) => parse_imports(value)?
+                .into_iter()
+                .map(|import| {
+                    let path = validate_path(&import.path, &self.workspace)?;
+                    check_size(&path, &import.path, limits.max_db_bytes)?;
+                    Ok((path, import))
+                })
+                .collect::<Result<Vec<_>, ToolError>>()?,
+            None => Vec::new(),
+        };
+
+        debug!(database = ?database, imports = imports.len(), "running sqlite query");
+        blocking(move || {
+            let conn = match &database {
+                Some(path) => Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                ),
+                None => Connection::open_in_memory(),
+            }
+            .map_err(sql_error)?;
+            let imported = imports
+                .iter()
+                .map(|(path, import)| import_csv(&conn, path, import))
+                .collect::<Result<Vec<_>, _>>()?;
+            conn.pragma_update(None, "query_only", true)
+                .map_err(sql_error)?;
+
+            let mut result = run_query(&conn, &sql, params, limits)?;
+            if !imported.is_empty() {
+                result["imported"] = json!(imported);
+            }
+            Ok(result)
+        })
+        .await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SqliteExecuteTool
+// ---------------------------------------------------------------------------
+
+/// Write access to workspace databases (`tools.sqlite.allowWrites`).
+pub struct SqliteExecuteTool {
+    workspace: PathBuf,
+    limits: SqliteLimits,
+}
+
+impl SqliteExecuteTool {
+    /// Create a new `SqliteExecuteTool` confined to `workspace`.
+    pub fn new(workspace: PathBuf, limits: SqliteLimits) -> Self {
+        Self { workspace, limits }
+    }
+}
+
+#[async_trait]
+impl Tool for SqliteExecuteTool {
+    fn name(&self) -> &str {
+        "sqlite_execute"
+    }
+
+    fn description(&self) -> &str {
+        "Run one SQL statement that modifies a SQLite database in the workspace (INSERT, \
+         UPDATE, DELETE, REPLACE, CREATE, DROP or ALTER). The database is created if it does \
+         not exist. Use sqlite_query for reads."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "sql": {
+                    "type": "string",
+                    "description": "A single statement to execute"
+                },
+                "database": {
+                    "type": "string",
+                    "description": "Database file relative to the workspace"
+                },
+                "params": {
+                    "type": "array",
+                    "description": "Values bound to ? placeholders, in order"
+                }
+            },
+            "required": ["sql", "database"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let sql = required_sql(&args)?;
+        check_keyword("sqlite_execute", &sql, EXECUTE_KEYWORDS)?;
+        let params = sql_params(&args)?;
+        let db = args
+            .get("database")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidArgs("missing required field: database".into()))?
+            .to_string();
+        let path = validate_parent_path(&db, &self.workspace)?;
+        let max_bytes = self.limits.max_db_bytes;
+        if path.exists() {
+            check_size(&path, &db, max_bytes)?;
+        }
+
+        debug!(database = %path.display(), "executing sqlite statement");
+        blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| ToolError::ExecutionFailed(format!("cannot create {db}: {e}")))?;
+            }
+            let conn = Connection::open(&path).map_err(sql_error)?;
+            let page_size: u64 = conn
+                .pragma_query_value(None, "page_size", |row| row.get(0))
+                .map_err(sql_error)?;
+            conn.pragma_update(None, "max_page_count", (max_bytes / page_size).max(1))
+                .map_err(sql_error)?;
+
+            let rows_affected = conn
+                .prepare(&sql)
+                .and_then(|mut stmt| stmt.execute(params_from_iter(params)))
+                .map_err(|e| match e.sqlite_error_code() {
+                    Some(ErrorCode::DiskFull) => ToolError::ExecutionFailed(format!(
+                        "{db} would grow past the {max_bytes} byte limit"
+                    )),
+                    _ => sql_error(e),
+                })?;
+            Ok(json!({
+                "database": db,
+                "rows_affected": rows_affected,
+                "last_insert_rowid": conn.last_insert_rowid(),
+            }))
+        })
+        .await
+    }
+
+    /// Concurrent writes to the same database would contend for its lock.
+    fn parallel_safe(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_workspace() -> PathBuf {
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let ws = std::env::temp_dir().join(format!("clawft_sqlite_tool_test_{pid}_{id}"));
+        std::fs::create_dir_all(&ws).unwrap();
+        ws
+    }
+
+    fn query_tool(ws: &Path) -> SqliteQueryTool {
+        SqliteQueryTool::new(ws.to_path_buf(), SqliteLimits::default())
+    }
+
+    fn execute_tool(ws: &Path) -> SqliteExecuteTool {
+        SqliteExecuteTool::new(ws.to_path_buf(), SqliteLimits::default())
+    }
+
+    async fn seed(ws: &Path) {
+        let exec = execute_tool(ws);
+        for sql in [
+            "CREATE TABLE runs (id INTEGER PRIMARY KEY, status TEXT)",
+            "INSERT INTO runs (status) VALUES ('ok'), ('failed'), ('failed')",
+        ] {
+            exec.execute(json!({"database": "data.db", "sql": sql}))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn leading_keyword_skips_comments() {
+        assert_eq!(leading_keyword("  -- note\n/* x */ select 1"), "SELECT");
+        assert_eq!(
+            leading_keyword("with t as (select 1) select * from t"),
+            "WITH"
+        );
+        assert_eq!(leading_keyword("-- only a comment"), "");
+    }
+
+    #[tokio::test]
+    async fn query_reads_workspace_database() {
+        let ws = temp_workspace();
+        seed(&ws).await;
+
+        let result = query_tool(&ws)
+            .execute(json!({
+                "database": "data.db",
+                "sql": "SELECT status, COUNT(*) AS n FROM runs WHERE status = ? GROUP BY status",
+                "params": ["failed"]
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["rows"], json!([["failed", 2]]));
+        assert_eq!(result["columns"][0]["name"], "status");
+        assert_eq!(result["columns"][0]["type"], "TEXT");
+        assert_eq!(result["truncated"], false);
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn query_rejects_writes() {
+        let ws = temp_workspace();
+        seed(&ws).await;
+        let tool = query_tool(&ws);
+
+        for sql in [
+            "DELETE FROM runs",
+            "ATTACH DATABASE '/tmp/other.db' AS other",
+            "PRAGMA journal_mode = DELETE",
+            // Passes the keyword check; SQLite reports it as a write.
+            "WITH doomed AS (SELECT id FROM runs) DELETE FROM runs WHERE id IN doomed",
+            "SELECT 1; DELETE FROM runs",
+        ] {
+            let err = tool
+                .execute(json!({"database": "data.db", "sql": sql}))
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    ToolError::PermissionDenied { .. } | ToolError::ExecutionFailed(_)
+                ),
+                "{sql}: {err:?}"
+            );
+        }
+
+        let result = tool
+            .execute(json!({"database": "data.db", "sql": "SELECT COUNT(*) FROM runs"}))
+            .await
+            .unwrap();
+        assert_eq!(result["rows"], json!([[3]]));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn query_rejects_paths_outside_workspace() {
+        let ws = temp_workspace();
+        let err = query_tool(&ws)
+            .execute(json!({"database": "../escape.db", "sql": "SELECT 1"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::InvalidPath(_) | ToolError::FileNotFound(_)
+        ));
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn csv_import_infers_types() {
+        let ws = temp_workspace();
+        std::fs::write(
+            ws.join("jobs.csv"),
+            "id,status,duration,zip,note\n\
+             1,ok,1.5,02134,\"hello, world\"\n\
+             2,failed,3,10001,\"said \"\"hi\"\"\"\n\
+             3,failed,,94105,\n",
+        )
+        .unwrap();
+
+        let result = query_tool(&ws)
+            .execute(json!({
+                "sql": "SELECT typeof(id), typeof(duration), typeof(zip), note FROM jobs ORDER BY id",
+                "import_csv": {"path": "jobs.csv", "table": "jobs"}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            result["rows"],
+            json!([
+                ["integer", "real", "text", "hello, world"],
+                ["integer", "real", "text", "said \"hi\""],
+                ["integer", "null", "text", null]
+            ])
+        );
+        let columns = &result["imported"][0]["columns"];
+        assert_eq!(columns[0], json!({"name": "id", "type": "INTEGER"}));
+        assert_eq!(columns[2], json!({"name": "duration", "type": "REAL"}));
+        assert_eq!(columns[3], json!({"name": "zip", "type": "TEXT"}));
+        assert_eq!(result["imported"][0]["rows"], 3);
+
+        let failed = query_tool(&ws)
+            .execute(json!({
+                "sql": "SELECT COUNT(*) FROM jobs WHERE status = 'failed'",
+                "import_csv": [{"path": "jobs.csv", "table": "jobs"}]
+            }))
+            .await
+            .unwrap();
+        assert_eq!(failed["rows"], json!([[2]]));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[test]
+    fn csv_parser_handles_quotes_and_crlf() {
+        let records = parse_csv("a;b\r\n\"x;y\";\"multi\nline\"\r\n\r\n", ';').unwrap();
+        assert_eq!(records, vec![vec!["a", "b"], vec!["x;y", "multi\nline"]]);
+        assert!(parse_csv("a\n\"open", ',').is_err());
+    }
+
+    #[tokio::test]
+    async fn results_are_capped_by_rows_and_bytes() {
+        let ws = temp_workspace();
+        let sql = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 1000) \
+                   SELECT x, printf('%050d', x) FROM n";
+
+        let by_rows = query_tool(&ws)
+            .execute(json!({"sql": sql, "max_rows": 10}))
+            .await
+            .unwrap();
+        assert_eq!(by_rows["row_count"], 10);
+        assert_eq!(by_rows["truncated"], true);
+        assert!(by_rows["warning"].as_str().unwrap().contains("10 row"));
+
+        let limits = SqliteLimits {
+            max_result_bytes: 1024,
+            ..SqliteLimits::default()
+        };
+        let by_bytes = SqliteQueryTool::new(ws.clone(), limits)
+            .execute(json!({"sql": sql}))
+            .await
+            .unwrap();
+        assert_eq!(by_bytes["truncated"], true);
+        assert!(by_bytes["rows"].to_string().len() <= 1024);
+        assert!(by_bytes["row_count"].as_u64().unwrap() > 0);
+
+        let exact = query_tool(&ws)
+            .execute(json!({"sql": "SELECT 1 UNION ALL SELECT 2", "max_rows": 2}))
+            .await
+            .unwrap();
+        assert_eq!(exact["truncated"], false);
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn execute_enforces_database_size_limit() {
+        let ws = temp_workspace();
+        let limits = SqliteLimits {
+            max_db_bytes: 16 * 1024,
+            ..SqliteLimits::default()
+        };
+        let tool = SqliteExecuteTool::new(ws.clone(), limits);
+        tool.execute(json!({"database": "small.db", "sql": "CREATE TABLE t (v TEXT)"}))
+            .await
+            .unwrap();
+        let err = tool
+            .execute(json!({
+                "database": "small.db",
+                "sql": "INSERT INTO t VALUES (?)",
+                "params": ["x".repeat(64 * 1024)]
+            }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("byte limit"), "{err}");
+
+        let err = tool
+            .execute(json!({"database": "small.db", "sql": "ATTACH 'x.db' AS x"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::PermissionDenied { .. }));
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+}
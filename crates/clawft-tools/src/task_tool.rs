@@ -0,0 +1,346 @@
+//! Task list tool.
+//!
+//! `task_list` lets the agent keep a structured list of what it is working
+//! on across turns: tasks with a status, optional subtasks and notes,
+//! stored in the workspace's `.clawft/tasks.json`. Open tasks are shown to
+//! the model at the start of each turn (see
+//! [`TaskList::summary`](clawft_core::agent::tasks::TaskList::summary)).
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use clawft_core::agent::tasks::{Task, TaskError, TaskStatus, TaskStore};
+use clawft_core::tools::registry::{Tool, ToolError};
+use clawft_platform::Platform;
+use serde_json::json;
+
+fn task_error(e: TaskError) -> ToolError {
+    match e {
+        TaskError::NotFound(_) | TaskError::Invalid(_) => ToolError::InvalidArgs(e.to_string()),
+        TaskError::Corrupt(_) | TaskError::Io(_) => ToolError::ExecutionFailed(e.to_string()),
+    }
+}
+
+fn task_json(task: &Task) -> serde_json::Value {
+    json!({
+        "id": task.id,
+        "title": task.title,
+        "status": task.status.as_str(),
+        "parent": task.parent,
+        "notes": task.notes,
+    })
+}
+
+fn str_arg<'a>(args: &'a serde_json::Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(|v| v.as_str())
+}
+
+fn required<'a>(args: &'a serde_json::Value, name: &str) -> Result<&'a str, ToolError> {
+    str_arg(args, name)
+        .ok_or_else(|| ToolError::InvalidArgs(format!("missing required field: {name}")))
+}
+
+fn parse_status(name: &str) -> Result<TaskStatus, ToolError> {
+    TaskStatus::parse(name).ok_or_else(|| {
+        ToolError::InvalidArgs(format!(
+            "unknown status '{name}'; use pending, in_progress, done or cancelled"
+        ))
+    })
+}
+
+/// Maintain the workspace task list.
+pub struct TaskListTool<P: Platform> {
+    store: TaskStore<P>,
+}
+
+impl<P: Platform> TaskListTool<P> {
+    /// Create a new `TaskListTool` for the task list of `workspace`.
+    pub fn new(platform: Arc<P>, workspace: &Path) -> Self {
+        Self {
+            store: TaskStore::new(platform, workspace),
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "browser"), async_trait)]
+#[cfg_attr(feature = "browser", async_trait(?Send))]
+impl<P: Platform + 'static> Tool for TaskListTool<P> {
+    fn name(&self) -> &str {
+        "task_list"
+    }
+
+    fn description(&self) -> &str {
+        "Keep a task list for long, multi-step work. 'add' creates a task (optionally a \
+         subtask of 'parent'), 'update' changes its title, status or notes, 'complete' marks \
+         it done, and 'list' shows the tasks. Open tasks are shown to you at the start of \
+         every turn, so keep the list current."
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["add", "update", "complete", "list"],
+                    "description": "What to do"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Task id (update, complete)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Task title (add, update)"
+                },
+                "status": {
+                    "type": "string",
+                    "description": "New status for update: pending, in_progress, done or cancelled. For list: a status, 'open' or 'all' (default)"
+                },
+                "parent": {
+                    "type": "string",
+                    "description": "Id of the parent task, for subtasks (add, update)"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Free-form notes; replaces existing notes (add, update, complete)"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> Result<serde_json::Value, ToolError> {
+        let operation = required(&args, "operation")?;
+        match operation {
+            "add" => {
+                let title = required(&args, "title")?.to_string();
+                let parent = str_arg(&args, "parent").map(str::to_string);
+                let notes = str_arg(&args, "notes").map(str::to_string);
+                let (id, tasks) = self
+                    .store
+                    .update(|tasks| tasks.add(&title, parent.as_deref(), notes.as_deref()))
+                    .await
+                    .map_err(task_error)?;
+                // A write from another process may have taken the id, in
+                // which case the merge renumbered ours.
+                let title = title.trim();
+                let task = tasks
+                    .get(&id)
+                    .filter(|t| t.title == title)
+                    .or_else(|| tasks.tasks.iter().rev().find(|t| t.title == title));
+                Ok(json!({ "added": task.map(task_json) }))
+            }
+            "update" | "complete" => {
+                let id = required(&args, "id")?.to_string();
+                let status = if operation == "complete" {
+                    Some(TaskStatus::Done)
+                } else {
+                    str_arg(&args, "status").map(parse_status).transpose()?
+                };
+                let title = str_arg(&args, "title").map(str::to_string);
+                let parent = str_arg(&args, "parent").map(str::to_string);
+                let notes = str_arg(&args, "notes").map(str::to_string);
+                if operation == "update"
+                    && status.is_none()
+                    && title.is_none()
+                    && parent.is_none()
+                    && notes.is_none()
+                {
+                    return Err(ToolError::InvalidArgs(
+                        "update needs at least one of title, status, parent or notes".into(),
+                    ));
+                }
+
+                let (open_subtasks, tasks) = self
+                    .store
+                    .update(|tasks| {
+                        if let Some(parent) = &parent {
+                            if parent == &id {
+                                return Err(TaskError::Invalid(
+                                    "a task cannot be its own parent".into(),
+                                ));
+                            }
+                            if tasks.get(parent).is_none() {
+                                return Err(TaskError::NotFound(parent.clone()));
+                            }
+                        }
+                        let task = tasks
+                            .get_mut(&id)
+                            .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+                        if let Some(title) = title.as_deref().map(str::trim) {
+                            if title.is_empty() {
+                                return Err(TaskError::Invalid(
+                                    "task title must not be empty".into(),
+                                ));
+                            }
+                            task.title = title.to_string();
+                        }
+                        if let Some(status) = status {
+                            task.status = status;
+                        }
+                        if let Some(parent) = &parent {
+                            task.parent = Some(parent.clone());
+                        }
+                        if let Some(notes) = &notes {
+                            task.notes = Some(notes.clone()).filter(|n| !n.trim().is_empty());
+                        }
+                        task.touch();
+                        let open_subtasks: Vec<String> = tasks
+                            .open()
+                            .filter(|t| t.parent.as_deref() == Some(id.as_str()))
+                            .map(|t| t.id.clone())
+                            .collect();
+                        Ok(open_subtasks)
+                    })
+                    .await
+                    .map_err(task_error)?;
+
+                let mut result = json!({ "updated": tasks.get(&id).map(task_json) });
+                if status.is_some_and(|s| !s.is_open()) && !open_subtasks.is_empty() {
+                    result["warning"] = json!(format!(
+                        "subtasks {} are still open",
+                        open_subtasks.join(", ")
+                    ));
+                }
+                Ok(result)
+            }
+            "list" => {
+                let tasks = self.store.load().await.map_err(task_error)?;
+                let filter = match str_arg(&args, "status") {
+                    None | Some("all") => None,
+                    Some("open") => Some(None),
+                    Some(name) => Some(Some(parse_status(name)?)),
+                };
+                let listed: Vec<serde_json::Value> = tasks
+                    .tasks
+                    .iter()
+                    .filter(|t| match filter {
+                        None => true,
+                        Some(None) => t.status.is_open(),
+                        Some(Some(status)) => t.status == status,
+                    })
+                    .map(task_json)
+                    .collect();
+                Ok(json!({
+                    "tasks": listed,
+                    "open": tasks.open().count(),
+                    "total": tasks.tasks.len(),
+                }))
+            }
+            other => Err(ToolError::InvalidArgs(format!(
+                "unknown operation '{other}'; use add, update, complete or list"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clawft_platform::NativePlatform;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    fn temp_workspace() -> PathBuf {
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let ws = std::env::temp_dir().join(format!("clawft_task_tool_test_{pid}_{id}"));
+        std::fs::create_dir_all(&ws).unwrap();
+        ws
+    }
+
+    fn tool(ws: &Path) -> TaskListTool<NativePlatform> {
+        TaskListTool::new(Arc::new(NativePlatform::new()), ws)
+    }
+
+    #[tokio::test]
+    async fn add_update_complete_and_list() {
+        let ws = temp_workspace();
+        let tool = tool(&ws);
+
+        let added = tool
+            .execute(json!({"operation": "add", "title": "ship release"}))
+            .await
+            .unwrap();
+        assert_eq!(added["added"]["id"], "1");
+        tool.execute(json!({"operation": "add", "title": "run tests", "parent": "1"}))
+            .await
+            .unwrap();
+
+        let updated = tool
+            .execute(json!({"operation": "update", "id": "1", "status": "in_progress", "notes": "tagging v2"}))
+            .await
+            .unwrap();
+        assert_eq!(updated["updated"]["status"], "in_progress");
+        assert_eq!(updated["updated"]["notes"], "tagging v2");
+
+        let done = tool
+            .execute(json!({"operation": "complete", "id": "1"}))
+            .await
+            .unwrap();
+        assert_eq!(done["updated"]["status"], "done");
+        assert!(done["warning"].as_str().unwrap().contains('2'));
+
+        let open = tool
+            .execute(json!({"operation": "list", "status": "open"}))
+            .await
+            .unwrap();
+        assert_eq!(open["open"], 1);
+        assert_eq!(open["total"], 2);
+        assert_eq!(open["tasks"][0]["title"], "run tests");
+        assert_eq!(open["tasks"][0]["parent"], "1");
+
+        assert!(ws.join(".clawft/tasks.json").exists());
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_ids_and_bad_arguments() {
+        let ws = temp_workspace();
+        let tool = tool(&ws);
+
+        for args in [
+            json!({"operation": "complete", "id": "7"}),
+            json!({"operation": "add", "title": "sub", "parent": "7"}),
+            json!({"operation": "add"}),
+            json!({"operation": "update", "id": "1"}),
+            json!({"operation": "list", "status": "someday"}),
+            json!({"operation": "archive"}),
+        ] {
+            let err = tool.execute(args.clone()).await.unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArgs(_)), "{args}: {err:?}");
+        }
+        assert!(!ws.join(".clawft/tasks.json").exists());
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+
+    #[tokio::test]
+    async fn parallel_adds_keep_every_task() {
+        let ws = temp_workspace();
+        let tool = Arc::new(tool(&ws));
+
+        let calls: Vec<_> = (0..10)
+            .map(|i| {
+                let tool = tool.clone();
+                tokio::spawn(async move {
+                    tool.execute(json!({"operation": "add", "title": format!("step {i}")}))
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        for call in calls {
+            call.await.unwrap();
+        }
+
+        let listed = tool.execute(json!({"operation": "list"})).await.unwrap();
+        assert_eq!(listed["total"], 10);
+
+        let _ = std::fs::remove_dir_all(&ws);
+    }
+}
@@ -0,0 +1,433 @@
+//! Search/replace edits over in-memory text, and unified diffs of them.
+//!
+//! Backs `edit_file`: every edit is located in the original text before
+//! any is applied, so a call either applies all of its edits or none.
+
+use std::fmt::Write as _;
+
+use clawft_core::tools::registry::ToolError;
+
+/// Lines of unchanged context around each diff hunk.
+const DIFF_CONTEXT: usize = 3;
+
+/// One requested search/replace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Edit {
+    /// Text to find. Must not be empty.
+    pub old_text: String,
+    /// Replacement text.
+    pub new_text: String,
+    /// Which match to replace (1-based). Required when `old_text`
+    /// matches more than once.
+    pub occurrence: Option<usize>,
+}
+
+/// An edit located in the original text: bytes `start..end` become `new_text`.
+#[derive(Debug, Clone)]
+pub(crate) struct Replacement {
+    start: usize,
+    end: usize,
+    new_text: String,
+}
+
+/// Byte offsets at which each line of `text` starts.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&i| i < text.len() || i == 0)
+        .collect()
+}
+
+/// 0-based index of the line containing byte `pos`.
+fn line_of(starts: &[usize], pos: usize) -> usize {
+    starts.partition_point(|&s| s <= pos).saturating_sub(1)
+}
+
+/// Locate every edit in `text`, failing on the first that cannot be placed
+/// unambiguously or that overlaps another.
+///
+/// `label` names an edit in error messages (e.g. `"edits[1]"`); edits are
+/// numbered from 0.
+pub(crate) fn locate(
+    text: &str,
+    edits: &[Edit],
+    label: impl Fn(usize) -> String,
+) -> Result<Vec<Replacement>, ToolError> {
+    let starts = line_starts(text);
+    let mut located: Vec<(usize, Replacement)> = Vec::with_capacity(edits.len());
+    for (i, edit) in edits.iter().enumerate() {
+        let name = label(i);
+        if edit.old_text.is_empty() {
+            return Err(ToolError::InvalidArgs(format!("{name} must not be empty")));
+        }
+        let matches: Vec<usize> = text
+            .match_indices(edit.old_text.as_str())
+            .map(|(pos, _)| pos)
+            .collect();
+        let start = match (matches.len(), edit.occurrence) {
+            (0, _) => {
+                return Err(ToolError::InvalidArgs(format!("{name} not found in file")));
+            }
+            (_, Some(0)) => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "{name}: occurrence is 1-based"
+                )));
+            }
+            (count, Some(n)) if n > count => {
+                return Err(ToolError::InvalidArgs(format!(
+                    "{name} appears {count} times; occurrence {n} does not exist"
+                )));
+            }
+            (_, Some(n)) => matches[n - 1],
+            (1, None) => matches[0],
+            (count, None) => {
+                let lines: Vec<String> = matches
+                    .iter()
+                    .map(|&pos| (line_of(&starts, pos) + 1).to_string())
+                    .collect();
+                return Err(ToolError::InvalidArgs(format!(
+                    "{name} appears {count} times (lines {}); set occurrence or provide more context to make it unique",
+                    lines.join(", ")
+                )));
+            }
+        };
+        located.push((
+            i,
+            Replacement {
+                start,
+                end: start + edit.old_text.len(),
+                new_text: edit.new_text.clone(),
+            },
+        ));
+    }
+
+    located.sort_by_key(|(_, r)| r.start);
+    for pair in located.windows(2) {
+        let ((a, first), (b, second)) = (&pair[0], &pair[1]);
+        if second.start < first.end {
+            let (a, b) = ((*a).min(*b), (*a).max(*b));
+            return Err(ToolError::InvalidArgs(format!(
+                "{} and {} overlap",
+                label(a),
+                label(b)
+            )));
+        }
+    }
+    Ok(located.into_iter().map(|(_, r)| r).collect())
+}
+
+/// The replacement that turns `old` into `new`: the lines between their
+/// common leading and trailing lines. `None` when the texts are equal or
+/// `old` is empty.
+///
+/// The replaced range always covers at least one line of `old`, so a pure
+/// insertion takes a neighbouring line along.
+pub(crate) fn rewrite(old: &str, new: &str) -> Option<Replacement> {
+    if old == new || old.is_empty() {
+        return None;
+    }
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let mut prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix + suffix == old_lines.len() {
+        if suffix > 0 {
+            suffix -= 1;
+        } else {
+            prefix -= 1;
+        }
+    }
+    let start: usize = old_lines[..prefix].iter().map(|l| l.len()).sum();
+    let end = old.len()
+        - old_lines[old_lines.len() - suffix..]
+            .iter()
+            .map(|l| l.len())
+            .sum::<usize>();
+    Some(Replacement {
+        start,
+        end,
+        new_text: new_lines[prefix..new_lines.len() - suffix].concat(),
+    })
+}
+
+/// Apply located replacements (sorted, non-overlapping) to `text`.
+pub(crate) fn apply(text: &str, replacements: &[Replacement]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for r in replacements {
+        out.push_str(&text[pos..r.start]);
+        out.push_str(&r.new_text);
+        pos = r.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Whole original lines `first..last`, replaced by `new_text`.
+struct Block {
+    first: usize,
+    last: usize,
+    new_text: String,
+}
+
+/// Group replacements into blocks of whole lines; replacements that share
+/// a line land in the same block.
+fn blocks(text: &str, starts: &[usize], replacements: &[Replacement]) -> Vec<Block> {
+    let line_end = |line: usize| starts.get(line + 1).copied().unwrap_or(text.len());
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut group: Vec<&Replacement> = Vec::new();
+    let flush = |group: &mut Vec<&Replacement>, blocks: &mut Vec<Block>| {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            return;
+        };
+        let first_line = line_of(starts, first.start);
+        let last_line = line_of(starts, last.end - 1);
+        let mut new_text = String::new();
+        let mut pos = starts[first_line];
+        for r in group.iter() {
+            new_text.push_str(&text[pos..r.start]);
+            new_text.push_str(&r.new_text);
+            pos = r.end;
+        }
+        new_text.push_str(&text[pos..line_end(last_line)]);
+        blocks.push(Block {
+            first: first_line,
+            last: last_line + 1,
+            new_text,
+        });
+        group.clear();
+    };
+    for r in replacements {
+        if let Some(prev) = group.last()
+            && line_of(starts, r.start) > line_of(starts, prev.end - 1)
+        {
+            flush(&mut group, &mut blocks);
+        }
+        group.push(r);
+    }
+    flush(&mut group, &mut blocks);
+    blocks
+}
+
+/// Append `line` to a diff with `prefix`, marking a missing final newline.
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Start of a hunk range in unified-diff form (`0` for an empty range).
+fn hunk_start(first: usize, len: usize) -> usize {
+    if len == 0 { first } else { first + 1 }
+}
+
+/// Unified diff (3 lines of context) of applying `replacements` to `text`,
+/// with `path` in the `---`/`+++` headers. Empty when nothing changes.
+pub(crate) fn unified_diff(path: &str, text: &str, replacements: &[Replacement]) -> String {
+    let starts = line_starts(text);
+    let old_lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let blocks: Vec<Block> = blocks(text, &starts, replacements)
+        .into_iter()
+        .filter(|b| {
+            b.new_text != text[starts[b.first]..starts.get(b.last).copied().unwrap_or(text.len())]
+        })
+        .collect();
+    if blocks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{path}\n+++ b/{path}\n");
+    // New-file line offset accumulated from the blocks before a hunk.
+    let mut delta: isize = 0;
+    let mut i = 0;
+    while i < blocks.len() {
+        // Blocks whose context would touch share a hunk.
+        let mut j = i;
+        while j + 1 < blocks.len() && blocks[j + 1].first - blocks[j].last <= 2 * DIFF_CONTEXT {
+            j += 1;
+        }
+        let old_first = blocks[i].first.saturating_sub(DIFF_CONTEXT);
+        let old_last = (blocks[j].last + DIFF_CONTEXT).min(old_lines.len());
+
+        let mut body = String::new();
+        let mut new_len = 0;
+        let mut line = old_first;
+        for block in &blocks[i..=j] {
+            for ctx in &old_lines[line..block.first] {
+                push_line(&mut body, ' ', ctx);
+                new_len += 1;
+            }
+            for old in &old_lines[block.first..block.last] {
+                push_line(&mut body, '-', old);
+            }
+            for new in block.new_text.split_inclusive('\n') {
+                push_line(&mut body, '+', new);
+                new_len += 1;
+            }
+            line = block.last;
+        }
+        for ctx in &old_lines[line..old_last] {
+            push_line(&mut body, ' ', ctx);
+            new_len += 1;
+        }
+
+        let old_len = old_last - old_first;
+        let new_first = (old_first as isize + delta) as usize;
+        let _ = writeln!(
+            out,
+            "@@ -{},{old_len} +{},{new_len} @@",
+            hunk_start(old_first, old_len),
+            hunk_start(new_first, new_len)
+        );
+        out.push_str(&body);
+        for block in &blocks[i..=j] {
+            delta += block.new_text.split_inclusive('\n').count() as isize
+                - (block.last - block.first) as isize;
+        }
+        i = j + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(old: &str, new: &str) -> Edit {
+        Edit {
+            old_text: old.into(),
+            new_text: new.into(),
+            occurrence: None,
+        }
+    }
+
+    fn label(i: usize) -> String {
+        format!("edits[{i}]")
+    }
+
+    #[test]
+    fn ambiguous_match_lists_candidate_lines() {
+        let text = "let a = 1;\nlet b = 2;\nlet a = 1;\n";
+        let err = locate(text, &[edit("let a = 1;", "let a = 3;")], label).unwrap_err();
+        assert!(
+            err.to_string().contains("appears 2 times (lines 1, 3)"),
+            "{err}"
+        );
+
+        let mut second = edit("let a = 1;", "let a = 3;");
+        second.occurrence = Some(2);
+        let replacements = locate(text, &[second], label).unwrap();
+        assert_eq!(
+            apply(text, &replacements),
+            "let a = 1;\nlet b = 2;\nlet a = 3;\n"
+        );
+    }
+
+    #[test]
+    fn overlapping_edits_are_rejected() {
+        let text = "alpha beta gamma\n";
+        let err = locate(
+            text,
+            &[edit("beta gamma", "x"), edit("alpha beta", "y")],
+            label,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments: edits[0] and edits[1] overlap"
+        );
+    }
+
+    #[test]
+    fn diff_of_separate_and_shared_line_edits() {
+        let text: String = (1..=20).map(|i| format!("line {i}\n")).collect();
+        let edits = [
+            edit("line 2\n", "line two\n"),
+            edit("line 15\nline 16\n", "merged\n"),
+            edit("line 20\n", "line 20"),
+        ];
+        let replacements = locate(&text, &edits, label).unwrap();
+        let diff = unified_diff("notes.txt", &text, &replacements);
+        let expected = "\
+--- a/notes.txt
++++ b/notes.txt
+@@ -1,5 +1,5 @@
+ line 1
+-line 2
++line two
+ line 3
+ line 4
+ line 5
+@@ -12,9 +12,8 @@
+ line 12
+ line 13
+ line 14
+-line 15
+-line 16
++merged
+ line 17
+ line 18
+ line 19
+-line 20
++line 20
+\\ No newline at end of file
+";
+        assert_eq!(diff, expected);
+    }
+
+    #[test]
+    fn diff_merges_edits_on_one_line_and_skips_no_ops() {
+        let text = "x y z\nunchanged\n";
+        let replacements = locate(text, &[edit("x", "X"), edit("z", "Z")], label).unwrap();
+        let diff = unified_diff("f", text, &replacements);
+        assert_eq!(
+            diff,
+            "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n-x y z\n+X y Z\n unchanged\n"
+        );
+
+        let same = locate(text, &[edit("y", "y")], label).unwrap();
+        assert_eq!(unified_diff("f", text, &same), "");
+    }
+
+    #[test]
+    fn rewrite_diffs_whole_texts() {
+        let old = "a\nb\nc\n";
+        let r = rewrite(old, "a\nB\nc\n").unwrap();
+        assert_eq!(apply(old, std::slice::from_ref(&r)), "a\nB\nc\n");
+        assert_eq!(
+            unified_diff("f", old, &[r]),
+            "--- a/f\n+++ b/f\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+        );
+
+        // Pure insertions and deletions still replace at least one line.
+        for new in ["a\nb\nc\nd\n", "x\na\nb\nc\n", "a\nc\n", "", "a\nb\nc"] {
+            let r = rewrite(old, new).unwrap();
+            assert!(r.end > r.start, "{new:?}");
+            assert_eq!(apply(old, &[r]), new);
+        }
+
+        assert!(rewrite(old, old).is_none());
+        assert!(rewrite("", "new").is_none());
+    }
+
+    #[test]
+    fn diff_of_deleting_every_line() {
+        let text = "only\n";
+        let replacements = locate(text, &[edit("only\n", "")], label).unwrap();
+        assert_eq!(
+            unified_diff("f", text, &replacements),
+            "--- a/f\n+++ b/f\n@@ -1,1 +0,0 @@\n-only\n"
+        );
+    }
+}
@@ -2,7 +2,15 @@
 //!
 //! Provides a `web_fetch` tool that retrieves content from a URL using
 //! the platform HTTP client.
-
+//!
+//! HTML pages are reduced to their main content (Markdown by default,
+//! plain text on request), JSON can be returned parsed, and other text
+//! types come back as-is. Binary types such as PDFs and images are not
+//! decoded; the tool reports their metadata instead. Redirects are
+//! followed here rather than by the HTTP client so every hop is checked
+//! against the [`UrlPolicy`].
+
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -10,17 +18,133 @@ use clawft_core::tools::registry::{Tool, ToolError};
 use clawft_platform::Platform;
 use serde_json::json;
 use tracing::{debug, warn};
+use url::Url;
 
-use crate::url_safety::{UrlPolicy, validate_url};
+use crate::html_extract::{self, Format};
+use crate::redirects::{self, check_url, header};
+use crate::url_safety::UrlPolicy;
 
 /// Default maximum response body size in bytes (10 MB).
 const DEFAULT_MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
 
+/// Body size limit for HTML pages (5 MB) before extraction.
+const HTML_LIMIT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Body size limit for JSON responses (10 MB).
+const JSON_LIMIT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Body size limit for other text responses (2 MB).
+const TEXT_LIMIT_BYTES: usize = 2 * 1024 * 1024;
+
+/// How the response body is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Main content of HTML pages as Markdown; other text as-is.
+    Markdown,
+    /// Main content of HTML pages as plain text; other text as-is.
+    Text,
+    /// The body exactly as received.
+    Raw,
+    /// The body parsed as JSON.
+    Json,
+}
+
+impl Mode {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "markdown" => Some(Self::Markdown),
+            "text" => Some(Self::Text),
+            "raw" => Some(Self::Raw),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Text => "text",
+            Self::Raw => "raw",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Broad class of a response body, from its content type or contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Html,
+    Json,
+    Text,
+    Unsupported,
+}
+
+impl Kind {
+    fn classify(content_type: &str, body: &[u8]) -> Self {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        if mime.is_empty() {
+            return Self::sniff(body);
+        }
+        if mime == "text/html" || mime == "application/xhtml+xml" {
+            Self::Html
+        } else if mime == "application/json" || mime.ends_with("+json") {
+            Self::Json
+        } else if mime.starts_with("text/")
+            || mime.ends_with("+xml")
+            || matches!(
+                mime.as_str(),
+                "application/xml"
+                    | "application/javascript"
+                    | "application/ecmascript"
+                    | "application/x-yaml"
+                    | "application/yaml"
+                    | "application/toml"
+                    | "application/x-ndjson"
+                    | "application/x-www-form-urlencoded"
+            )
+        {
+            Self::Text
+        } else {
+            Self::Unsupported
+        }
+    }
+
+    /// Guess the kind of a body served without a content type.
+    fn sniff(body: &[u8]) -> Self {
+        let head = &body[..body.len().min(1024)];
+        if head.contains(&0) || head.starts_with(b"%PDF") {
+            return Self::Unsupported;
+        }
+        let text = String::from_utf8_lossy(head)
+            .trim_start()
+            .to_ascii_lowercase();
+        if text.starts_with("<!doctype html") || text.starts_with("<html") {
+            Self::Html
+        } else {
+            Self::Text
+        }
+    }
+
+    fn limit_bytes(self) -> usize {
+        match self {
+            Self::Html => HTML_LIMIT_BYTES,
+            Self::Json => JSON_LIMIT_BYTES,
+            Self::Text | Self::Unsupported => TEXT_LIMIT_BYTES,
+        }
+    }
+}
+
 /// Web fetch tool.
 ///
 /// Fetches content from a given URL and returns it as text. Enforces
-/// SSRF protection via [`UrlPolicy`] and a configurable maximum response
-/// size to prevent memory exhaustion.
+/// SSRF protection via [`UrlPolicy`] on the requested URL and on every
+/// redirect, and a configurable maximum response size (further capped
+/// per content type) to prevent memory exhaustion.
 pub struct WebFetchTool<P: Platform> {
     platform: Arc<P>,
     url_policy: UrlPolicy,
@@ -55,7 +179,9 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
     }
 
     fn description(&self) -> &str {
-        "Fetch content from a URL. Returns the response body as text (HTML, JSON, etc.)."
+        "Fetch content from a URL. HTML pages are reduced to their main content as \
+         Markdown (or plain text); JSON and other text are returned as text, or JSON \
+         parsed with mode=json. Binary types such as PDFs return metadata only."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -73,6 +199,13 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
                 "headers": {
                     "type": "object",
                     "description": "Optional HTTP headers as key-value pairs"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["markdown", "text", "raw", "json"],
+                    "description": "How to return the body: 'markdown' (default) extracts the \
+                                    main content of HTML pages, 'text' does the same as plain \
+                                    text, 'raw' returns the body unchanged, 'json' parses it."
                 }
             },
             "required": ["url"]
@@ -93,13 +226,19 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
         }
 
         // SSRF protection: validate URL against policy.
-        if let Err(e) = validate_url(url, &self.url_policy) {
-            warn!(url, error = %e, "URL rejected by safety policy");
-            return Err(ToolError::PermissionDenied {
-                tool: "web_fetch".into(),
-                reason: e.to_string(),
-            });
-        }
+        check_url("web_fetch", url, &self.url_policy)?;
+
+        let parsed = Url::parse(url)
+            .map_err(|e| ToolError::InvalidArgs(format!("invalid url '{url}': {e}")))?;
+
+        let mode = match args.get("mode").and_then(|v| v.as_str()) {
+            None => Mode::Markdown,
+            Some(m) => Mode::parse(m).ok_or_else(|| {
+                ToolError::InvalidArgs(format!(
+                    "unknown mode '{m}', expected markdown, text, raw or json"
+                ))
+            })?,
+        };
 
         let method = args
             .get("method")
@@ -107,7 +246,7 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
             .unwrap_or("GET")
             .to_uppercase();
 
-        let headers: std::collections::HashMap<String, String> = args
+        let headers: HashMap<String, String> = args
             .get("headers")
             .and_then(|v| v.as_object())
             .map(|obj| {
@@ -117,54 +256,105 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
             })
             .unwrap_or_default();
 
-        debug!(url = %url, method = %method, "fetching web content");
-
-        let response = self
-            .platform
-            .http()
-            .request(&method, url, &headers, None)
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("fetch failed: {e}")))?;
+        debug!(url = %url, method = %method, mode = mode.as_str(), "fetching web content");
+
+        let redirects::Followed {
+            response,
+            url: final_url,
+            redirects,
+        } = redirects::send(
+            self.platform.http(),
+            &self.url_policy,
+            "web_fetch",
+            method,
+            parsed,
+            headers,
+            None,
+            &[],
+        )
+        .await?;
 
         let status = response.status;
         let total_bytes = response.body.len();
-        let was_truncated = total_bytes > self.max_response_bytes;
-        let body = if was_truncated {
-            let truncated = String::from_utf8_lossy(&response.body[..self.max_response_bytes]);
-            warn!(
-                url,
-                total_bytes,
-                limit = self.max_response_bytes,
-                "response body exceeds size limit, truncating"
-            );
-            format!(
-                "{truncated}\n\n[... truncated at {} bytes, total response was {} bytes]",
-                self.max_response_bytes, total_bytes
-            )
-        } else {
-            String::from_utf8_lossy(&response.body).into_owned()
-        };
-
-        let content_type = response
-            .headers
-            .get("content-type")
-            .or_else(|| response.headers.get("Content-Type"))
-            .cloned()
-            .unwrap_or_default();
+        let content_type = header(&response.headers, "content-type")
+            .unwrap_or_default()
+            .to_string();
+        let kind = Kind::classify(&content_type, &response.body);
 
         let mut result = json!({
             "status": status,
             "content_type": content_type,
-            "body": body,
-            "url": url,
+            "url": final_url.as_str(),
             "bytes": total_bytes,
+            "mode": mode.as_str(),
         });
+        if !redirects.is_empty() {
+            result["redirects"] = json!(redirects);
+        }
+
+        if kind == Kind::Unsupported {
+            let shown = if content_type.is_empty() {
+                "binary data"
+            } else {
+                content_type.as_str()
+            };
+            result["unsupported"] = json!(true);
+            result["message"] = json!(format!(
+                "unsupported content type ({shown}): web_fetch returns HTML, JSON and \
+                 text only; the {total_bytes} byte body was not decoded"
+            ));
+            return Ok(result);
+        }
+
+        let limit = kind.limit_bytes().min(self.max_response_bytes);
+        let was_truncated = total_bytes > limit;
+        if was_truncated {
+            warn!(
+                url,
+                total_bytes, limit, "response body exceeds size limit, truncating"
+            );
+        }
+        let text = String::from_utf8_lossy(&response.body[..total_bytes.min(limit)]);
+
+        let body = match (mode, kind) {
+            (Mode::Json, _) => match serde_json::from_str::<serde_json::Value>(&text) {
+                Ok(value) => {
+                    result["json"] = value;
+                    None
+                }
+                Err(e) => {
+                    result["json_error"] = json!(format!("body is not valid JSON: {e}"));
+                    Some(text.into_owned())
+                }
+            },
+            (Mode::Markdown | Mode::Text, Kind::Html) => {
+                let format = if mode == Mode::Markdown {
+                    Format::Markdown
+                } else {
+                    Format::Text
+                };
+                let extracted = html_extract::extract(&text, Some(&final_url), format);
+                if let Some(title) = extracted.title {
+                    result["title"] = json!(title);
+                }
+                Some(extracted.content)
+            }
+            _ => Some(text.into_owned()),
+        };
+        if let Some(mut body) = body {
+            if was_truncated {
+                body.push_str(&format!(
+                    "\n\n[... truncated at {limit} bytes, total response was {total_bytes} bytes]"
+                ));
+            }
+            result["body"] = json!(body);
+        }
+
         if was_truncated {
             result["truncated"] = json!(true);
-            result["limit_bytes"] = json!(self.max_response_bytes);
+            result["limit_bytes"] = json!(limit);
             result["warning"] = json!(format!(
-                "Response body ({total_bytes} bytes) exceeded the {} byte limit and was truncated",
-                self.max_response_bytes
+                "Response body ({total_bytes} bytes) exceeded the {limit} byte limit and was truncated"
             ));
         }
         Ok(result)
@@ -174,7 +364,10 @@ impl<P: Platform + 'static> Tool for WebFetchTool<P> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::redirects::MAX_REDIRECTS;
     use clawft_platform::NativePlatform;
+    use clawft_platform::http::NO_REDIRECT_HEADER;
+    use clawft_platform::testing::{MockPlatform, MockResponse};
 
     fn make_tool() -> WebFetchTool<NativePlatform> {
         WebFetchTool::new(Arc::new(NativePlatform::new()), UrlPolicy::default())
@@ -237,63 +430,13 @@ mod tests {
     }
 
     /// Test that oversized responses are truncated with warning metadata.
-    ///
-    /// Uses a mock platform that returns a large response body to verify
-    /// that the tool truncates the body and includes truncation metadata
-    /// in the result JSON.
     #[tokio::test]
     async fn oversized_response_is_truncated() {
-        use clawft_platform::http::HttpResponse;
-        use std::collections::HashMap;
-
-        // Create a mock platform that returns a large body.
-        struct MockHttp {
-            body: Vec<u8>,
-        }
-
-        #[async_trait]
-        impl clawft_platform::http::HttpClient for MockHttp {
-            async fn request(
-                &self,
-                _method: &str,
-                _url: &str,
-                _headers: &HashMap<String, String>,
-                _body: Option<&[u8]>,
-            ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
-                Ok(HttpResponse {
-                    status: 200,
-                    headers: HashMap::new(),
-                    body: self.body.clone(),
-                })
-            }
-        }
-
-        struct MockPlatform {
-            http: MockHttp,
-            native: clawft_platform::NativePlatform,
-        }
-
-        #[async_trait]
-        impl clawft_platform::Platform for MockPlatform {
-            fn http(&self) -> &dyn clawft_platform::http::HttpClient {
-                &self.http
-            }
-            fn fs(&self) -> &dyn clawft_platform::fs::FileSystem {
-                self.native.fs()
-            }
-            fn env(&self) -> &dyn clawft_platform::env::Environment {
-                self.native.env()
-            }
-            fn process(&self) -> Option<&dyn clawft_platform::process::ProcessSpawner> {
-                self.native.process()
-            }
-        }
-
-        let large_body = vec![b'A'; 2000]; // 2000 bytes
-        let platform = Arc::new(MockPlatform {
-            http: MockHttp { body: large_body },
-            native: clawft_platform::NativePlatform::new(),
-        });
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/large",
+            MockResponse::bytes(200, vec![b'A'; 2000]),
+        );
 
         // Set limit to 500 bytes
         let tool = WebFetchTool::with_max_bytes(
@@ -327,53 +470,11 @@ mod tests {
     /// Test that responses within the limit are NOT truncated.
     #[tokio::test]
     async fn response_within_limit_not_truncated() {
-        use clawft_platform::http::HttpResponse;
-        use std::collections::HashMap;
-
-        struct MockHttp;
-
-        #[async_trait]
-        impl clawft_platform::http::HttpClient for MockHttp {
-            async fn request(
-                &self,
-                _method: &str,
-                _url: &str,
-                _headers: &HashMap<String, String>,
-                _body: Option<&[u8]>,
-            ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
-                Ok(HttpResponse {
-                    status: 200,
-                    headers: HashMap::new(),
-                    body: b"small body".to_vec(),
-                })
-            }
-        }
-
-        struct MockPlatform {
-            http: MockHttp,
-            native: clawft_platform::NativePlatform,
-        }
-
-        #[async_trait]
-        impl clawft_platform::Platform for MockPlatform {
-            fn http(&self) -> &dyn clawft_platform::http::HttpClient {
-                &self.http
-            }
-            fn fs(&self) -> &dyn clawft_platform::fs::FileSystem {
-                self.native.fs()
-            }
-            fn env(&self) -> &dyn clawft_platform::env::Environment {
-                self.native.env()
-            }
-            fn process(&self) -> Option<&dyn clawft_platform::process::ProcessSpawner> {
-                self.native.process()
-            }
-        }
-
-        let platform = Arc::new(MockPlatform {
-            http: MockHttp,
-            native: clawft_platform::NativePlatform::new(),
-        });
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/*",
+            MockResponse::text(200, "small body"),
+        );
 
         let tool = WebFetchTool::with_max_bytes(platform, UrlPolicy::permissive(), 10000);
 
@@ -388,4 +489,327 @@ mod tests {
         assert_eq!(result["body"], "small body");
         assert_eq!(result["bytes"], 10);
     }
+
+    #[tokio::test]
+    async fn request_uses_method_and_headers() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "POST https://example.com/api",
+            MockResponse::text(201, "made"),
+        );
+
+        let tool = WebFetchTool::new(platform.clone(), UrlPolicy::permissive());
+        let result = tool
+            .execute(json!({
+                "url": "https://example.com/api",
+                "method": "POST",
+                "headers": {"x-token": "abc"},
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 201);
+
+        let requests = platform.http.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].headers["x-token"], "abc");
+    }
+
+    /// Policy that trusts only the example.com hosts, so tests never hit DNS.
+    fn example_policy() -> UrlPolicy {
+        UrlPolicy {
+            allowed_domains: ["example.com", "docs.example.com"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            ..UrlPolicy::default()
+        }
+    }
+
+    const ARTICLE: &str = "<html><head><title>Release notes</title></head><body>\
+        <nav><a href=\"/\">Home</a> <a href=\"/blog\">Blog</a></nav>\
+        <article><h1>Version 2</h1>\
+        <p>This release rewrites the scheduler so that long running jobs no longer \
+        starve short ones, and adds <a href=\"/docs/jobs\">job priorities</a>.</p>\
+        <p>Upgrading needs no configuration changes; existing queues keep working \
+        exactly as before and pick up the new defaults on restart.</p></article>\
+        <footer>Copyright Example Corp</footer></body></html>";
+
+    fn html_platform() -> Arc<MockPlatform> {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/notes",
+            MockResponse::text(200, ARTICLE)
+                .with_header("content-type", "text/html; charset=utf-8"),
+        );
+        platform
+    }
+
+    #[tokio::test]
+    async fn markdown_mode_extracts_main_content() {
+        let tool = WebFetchTool::new(html_platform(), example_policy());
+        let result = tool
+            .execute(json!({"url": "https://example.com/notes"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["mode"], "markdown");
+        assert_eq!(result["title"], "Release notes");
+        let body = result["body"].as_str().unwrap();
+        assert!(body.starts_with("# Version 2"), "{body}");
+        assert!(body.contains("[job priorities](https://example.com/docs/jobs)"));
+        assert!(!body.contains("Home"));
+        assert!(!body.contains("Copyright"));
+    }
+
+    #[tokio::test]
+    async fn text_mode_drops_markup() {
+        let tool = WebFetchTool::new(html_platform(), example_policy());
+        let result = tool
+            .execute(json!({"url": "https://example.com/notes", "mode": "text"}))
+            .await
+            .unwrap();
+
+        let body = result["body"].as_str().unwrap();
+        assert!(body.starts_with("Version 2"), "{body}");
+        assert!(body.contains("adds job priorities."));
+        assert!(!body.contains('#'));
+        assert!(!body.contains("]("));
+    }
+
+    #[tokio::test]
+    async fn raw_mode_returns_body_unchanged() {
+        let tool = WebFetchTool::new(html_platform(), example_policy());
+        let result = tool
+            .execute(json!({"url": "https://example.com/notes", "mode": "raw"}))
+            .await
+            .unwrap();
+
+        assert_eq!(result["body"], ARTICLE);
+        assert!(result.get("title").is_none());
+    }
+
+    #[tokio::test]
+    async fn json_mode_parses_body() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/api/items",
+            MockResponse::json(200, &json!({"items": [1, 2, 3]})),
+        );
+        platform.http.respond(
+            "https://example.com/api/broken",
+            MockResponse::text(200, "{not json").with_header("content-type", "application/json"),
+        );
+        let tool = WebFetchTool::new(platform, example_policy());
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/api/items", "mode": "json"}))
+            .await
+            .unwrap();
+        assert_eq!(result["json"]["items"], json!([1, 2, 3]));
+        assert!(result.get("body").is_none());
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/api/broken", "mode": "json"}))
+            .await
+            .unwrap();
+        assert_eq!(result["body"], "{not json");
+        assert!(
+            result["json_error"]
+                .as_str()
+                .unwrap()
+                .contains("not valid JSON")
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_mode_is_rejected() {
+        let err = make_tool()
+            .execute(json!({"url": "https://example.com", "mode": "pdf"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgs(_)));
+    }
+
+    #[tokio::test]
+    async fn binary_content_types_return_metadata_only() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/paper.pdf",
+            MockResponse::bytes(200, b"%PDF-1.7\n\x00\x01binary".to_vec())
+                .with_header("content-type", "application/pdf"),
+        );
+        platform.http.respond(
+            "https://example.com/blob",
+            MockResponse::bytes(200, b"%PDF-1.4 untyped".to_vec()),
+        );
+        let tool = WebFetchTool::new(platform, example_policy());
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/paper.pdf"}))
+            .await
+            .unwrap();
+        assert_eq!(result["unsupported"], true);
+        assert_eq!(result["content_type"], "application/pdf");
+        assert_eq!(result["bytes"], 17);
+        assert!(result.get("body").is_none());
+        assert!(
+            result["message"]
+                .as_str()
+                .unwrap()
+                .contains("unsupported content type (application/pdf)")
+        );
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/blob", "mode": "raw"}))
+            .await
+            .unwrap();
+        assert_eq!(result["unsupported"], true);
+        assert!(result.get("body").is_none());
+    }
+
+    #[tokio::test]
+    async fn html_limit_applies_before_extraction() {
+        let platform = Arc::new(MockPlatform::new());
+        let page = format!("<html><body><p>{}</p></body></html>", "word ".repeat(100));
+        platform.http.respond(
+            "https://example.com/long",
+            MockResponse::text(200, &page).with_header("content-type", "text/html"),
+        );
+        let tool = WebFetchTool::with_max_bytes(platform, example_policy(), 100);
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/long"}))
+            .await
+            .unwrap();
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["limit_bytes"], 100);
+        let body = result["body"].as_str().unwrap();
+        assert!(body.starts_with("word word"));
+        assert!(body.ends_with(&format!(
+            "[... truncated at 100 bytes, total response was {} bytes]",
+            page.len()
+        )));
+    }
+
+    #[tokio::test]
+    async fn redirects_are_followed_and_reported() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/old",
+            MockResponse::text(301, "").with_header("Location", "https://docs.example.com/new"),
+        );
+        platform.http.respond(
+            "https://docs.example.com/new",
+            MockResponse::text(302, "").with_header("location", "/final"),
+        );
+        platform.http.respond(
+            "https://docs.example.com/final",
+            MockResponse::text(200, "arrived").with_header("content-type", "text/plain"),
+        );
+        let tool = WebFetchTool::new(platform.clone(), example_policy());
+
+        let result = tool
+            .execute(json!({
+                "url": "https://example.com/old",
+                "headers": {"Authorization": "Bearer secret", "x-trace": "1"},
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["body"], "arrived");
+        assert_eq!(result["url"], "https://docs.example.com/final");
+        assert_eq!(
+            result["redirects"],
+            json!([
+                "https://docs.example.com/new",
+                "https://docs.example.com/final"
+            ])
+        );
+
+        let requests = platform.http.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(
+            requests
+                .iter()
+                .all(|r| r.headers.contains_key(NO_REDIRECT_HEADER))
+        );
+        // Credentials stay with the origin they were given for.
+        assert_eq!(requests[0].headers["Authorization"], "Bearer secret");
+        assert!(!requests[1].headers.contains_key("Authorization"));
+        assert_eq!(requests[2].headers["x-trace"], "1");
+    }
+
+    #[tokio::test]
+    async fn redirect_to_blocked_host_is_denied() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://example.com/go",
+            MockResponse::text(302, "")
+                .with_header("location", "http://169.254.169.254/latest/meta-data/"),
+        );
+        platform.http.respond(
+            "https://example.com/local",
+            MockResponse::text(307, "").with_header("location", "http://127.0.0.1:8080/admin"),
+        );
+        let tool = WebFetchTool::new(platform.clone(), example_policy());
+
+        for path in ["go", "local"] {
+            let err = tool
+                .execute(json!({"url": format!("https://example.com/{path}")}))
+                .await
+                .unwrap_err();
+            match err {
+                ToolError::PermissionDenied { reason, .. } => {
+                    assert!(
+                        reason.contains("redirect from https://example.com/"),
+                        "{reason}"
+                    );
+                }
+                other => panic!("expected PermissionDenied, got {other:?}"),
+            }
+        }
+        // The blocked targets were never requested.
+        assert_eq!(platform.http.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn redirect_loop_stops_at_limit() {
+        let platform = Arc::new(MockPlatform::new());
+        for _ in 0..=MAX_REDIRECTS {
+            platform.http.respond(
+                "https://example.com/loop",
+                MockResponse::text(302, "").with_header("location", "/loop"),
+            );
+        }
+        let tool = WebFetchTool::new(platform.clone(), example_policy());
+
+        let err = tool
+            .execute(json!({"url": "https://example.com/loop"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("too many redirects"));
+        assert_eq!(platform.http.requests().len(), MAX_REDIRECTS + 1);
+    }
+
+    #[tokio::test]
+    async fn see_other_switches_post_to_get() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "POST https://example.com/submit",
+            MockResponse::text(303, "").with_header("location", "/done"),
+        );
+        platform.http.respond(
+            "GET https://example.com/done",
+            MockResponse::text(200, "ok"),
+        );
+        let tool = WebFetchTool::new(platform.clone(), example_policy());
+
+        let result = tool
+            .execute(json!({"url": "https://example.com/submit", "method": "POST"}))
+            .await
+            .unwrap();
+        assert_eq!(result["body"], "ok");
+        assert_eq!(platform.http.requests()[1].method, "GET");
+    }
 }
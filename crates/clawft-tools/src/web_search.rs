@@ -1,35 +1,47 @@
 //! Web search tool.
 //!
-//! Provides a `web_search` tool that queries the Brave Search API or a custom
-//! search API endpoint. Falls back gracefully when no credentials are configured.
+//! Provides a `web_search` tool backed by pluggable [`SearchProvider`]s:
+//! the Brave Search API, Google Custom Search, a SearXNG instance, or
+//! DuckDuckGo's HTML results page. A custom search API endpoint can be
+//! used instead. Falls back gracefully when nothing is configured.
 //!
 //! # Configuration
 //!
 //! The tool accepts a [`WebSearchConfig`] which can provide:
 //!
-//! - An API key for the Brave Search API (constructs the endpoint automatically)
+//! - The primary provider and a fallback chain tried after it
+//! - Per-provider credentials and base URLs
 //! - A custom endpoint URL (used as-is, for self-hosted or alternative search APIs)
 //!
-//! When both are provided, the custom endpoint takes precedence.
+//! When a custom endpoint is set it takes precedence over the providers.
 
-use std::collections::HashMap;
+pub mod provider;
+
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use clawft_core::tools::registry::{Tool, ToolError};
 use clawft_platform::Platform;
 use serde_json::json;
-use tracing::debug;
+use tracing::{debug, warn};
+
+pub use clawft_types::config::SearchProviderKind;
 
-/// Brave Search API base URL.
-const BRAVE_SEARCH_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+use self::provider::{
+    BraveProvider, DuckDuckGoProvider, GoogleProvider, SearchProvider, SearxngProvider, dedup_key,
+    encode_query,
+};
 
 /// Configuration for the web search tool.
 ///
 /// Supports two modes:
 ///
-/// 1. **Brave Search API key**: Set `api_key` and the tool constructs the
-///    proper Brave Search endpoint, adding the `X-Subscription-Token` header.
+/// 1. **Providers**: `provider` names the service queried first and
+///    `fallback_chain` the ones tried after it. A provider missing its
+///    credentials (Brave needs `api_key`, Google `google_api_key` and
+///    `google_cx`, SearXNG `searxng_url`) is skipped; DuckDuckGo needs
+///    nothing.
 ///
 /// 2. **Custom endpoint**: Set `endpoint` to a URL that accepts `?q=<query>&count=<n>`
 ///    query parameters and returns JSON search results.
@@ -39,21 +51,58 @@ const BRAVE_SEARCH_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/sea
 pub struct WebSearchConfig {
     /// Brave Search API key. Used to authenticate against the Brave Search API.
     pub api_key: Option<String>,
-    /// Custom search API endpoint URL. Overrides Brave Search when set.
+    /// Custom search API endpoint URL. Overrides the providers when set.
     pub endpoint: Option<String>,
     /// Maximum number of results per query (default: 5).
     pub max_results: u32,
+    /// Provider queried first.
+    pub provider: SearchProviderKind,
+    /// Providers tried after `provider`, in order.
+    pub fallback_chain: Vec<SearchProviderKind>,
+    /// Google Custom Search API key.
+    pub google_api_key: Option<String>,
+    /// Google Programmable Search Engine ID.
+    pub google_cx: Option<String>,
+    /// Base URL of a SearXNG instance.
+    pub searxng_url: Option<String>,
+    /// Override for the DuckDuckGo HTML endpoint.
+    pub duckduckgo_url: Option<String>,
 }
 
 impl WebSearchConfig {
     /// Returns `true` if the config has enough information to perform searches.
     pub fn is_configured(&self) -> bool {
-        self.has_api_key() || self.has_endpoint()
-    }
-
-    /// Returns `true` if a non-empty API key is set.
-    fn has_api_key(&self) -> bool {
-        self.api_key.as_ref().is_some_and(|k| !k.is_empty())
+        self.has_endpoint() || !self.providers().is_empty()
+    }
+
+    /// The usable providers, primary first, without duplicates.
+    pub fn providers(&self) -> Vec<Box<dyn SearchProvider>> {
+        let mut seen = HashSet::new();
+        std::iter::once(self.provider)
+            .chain(self.fallback_chain.iter().copied())
+            .filter(|kind| seen.insert(*kind))
+            .filter_map(|kind| self.build_provider(kind))
+            .collect()
+    }
+
+    fn build_provider(&self, kind: SearchProviderKind) -> Option<Box<dyn SearchProvider>> {
+        match kind {
+            SearchProviderKind::Brave => non_empty(&self.api_key)
+                .map(|key| Box::new(BraveProvider::new(key)) as Box<dyn SearchProvider>),
+            SearchProviderKind::Google => {
+                let key = non_empty(&self.google_api_key)?;
+                let cx = non_empty(&self.google_cx)?;
+                Some(Box::new(GoogleProvider::new(key, cx)))
+            }
+            SearchProviderKind::Searxng => non_empty(&self.searxng_url)
+                .map(|url| Box::new(SearxngProvider::new(url)) as Box<dyn SearchProvider>),
+            SearchProviderKind::DuckDuckGo => {
+                Some(Box::new(match non_empty(&self.duckduckgo_url) {
+                    Some(url) => DuckDuckGoProvider::with_base_url(url),
+                    None => DuckDuckGoProvider::new(),
+                }))
+            }
+        }
     }
 
     /// Returns `true` if a non-empty custom endpoint is set.
@@ -62,20 +111,30 @@ impl WebSearchConfig {
     }
 }
 
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
 /// Web search tool.
 ///
-/// Sends a search query to the Brave Search API or a custom search endpoint
-/// and returns results. If no API key or endpoint is configured, returns a
-/// descriptive error message.
+/// Sends a search query to the configured providers (or a custom search
+/// endpoint) and returns normalized results. If nothing is configured,
+/// returns a descriptive error message.
 pub struct WebSearchTool<P: Platform> {
     platform: Arc<P>,
     config: WebSearchConfig,
+    providers: Vec<Box<dyn SearchProvider>>,
 }
 
 impl<P: Platform> WebSearchTool<P> {
     /// Create a new web search tool with the given configuration.
     pub fn new(platform: Arc<P>, config: WebSearchConfig) -> Self {
-        Self { platform, config }
+        let providers = config.providers();
+        Self {
+            platform,
+            config,
+            providers,
+        }
     }
 
     /// Create a web search tool from a raw endpoint URL (legacy API).
@@ -83,44 +142,121 @@ impl<P: Platform> WebSearchTool<P> {
     /// Provided for backward compatibility. Prefer [`new`](Self::new) with
     /// a [`WebSearchConfig`] instead.
     pub fn from_endpoint(platform: Arc<P>, endpoint: Option<String>) -> Self {
-        Self {
+        Self::new(
             platform,
-            config: WebSearchConfig {
+            WebSearchConfig {
                 endpoint,
                 max_results: 5,
-                api_key: None,
+                ..Default::default()
             },
-        }
+        )
     }
 
-    /// Build the request URL and headers for the search.
+    /// Build the request URL and headers for the search: the custom
+    /// endpoint when set, otherwise the primary provider's request.
     fn build_request(&self, query: &str, num_results: usize) -> (String, HashMap<String, String>) {
-        let mut headers = HashMap::new();
-
         if self.config.has_endpoint() {
             // Custom endpoint mode: no auth headers, use endpoint as-is.
             let endpoint = self.config.endpoint.as_ref().unwrap();
             let url = format!(
                 "{}?q={}&count={}",
                 endpoint,
-                urlencoding_minimal(query),
+                encode_query(query),
                 num_results
             );
-            (url, headers)
+            (url, HashMap::new())
         } else {
-            // Brave Search API mode: use the standard endpoint with auth header.
-            let api_key = self.config.api_key.as_ref().unwrap();
-            headers.insert("X-Subscription-Token".to_string(), api_key.clone());
-            headers.insert("Accept".to_string(), "application/json".to_string());
-            let url = format!(
-                "{}?q={}&count={}",
-                BRAVE_SEARCH_ENDPOINT,
-                urlencoding_minimal(query),
-                num_results
-            );
-            (url, headers)
+            let request = self.providers[0].request(query, num_results);
+            (request.url, request.headers)
         }
     }
+
+    /// Query the custom endpoint and pass its JSON through unchanged.
+    async fn search_endpoint(
+        &self,
+        query: &str,
+        num_results: usize,
+    ) -> Result<serde_json::Value, ToolError> {
+        let (url, headers) = self.build_request(query, num_results);
+
+        let result = self
+            .platform
+            .http()
+            .request("GET", &url, &headers, None)
+            .await
+            .map_err(|e| ToolError::ExecutionFailed(format!("search request failed: {e}")))?;
+
+        if result.status >= 400 {
+            return Err(ToolError::ExecutionFailed(format!(
+                "search API returned status {}",
+                result.status
+            )));
+        }
+
+        // Parse the response body as JSON.
+        let body = String::from_utf8_lossy(&result.body);
+        match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(parsed) => Ok(json!({
+                "query": query,
+                "results": parsed,
+            })),
+            Err(_) => Ok(json!({
+                "query": query,
+                "results": body.to_string(),
+            })),
+        }
+    }
+
+    /// Query the providers in order until enough distinct results are in.
+    ///
+    /// A provider that fails is noted and the next one is tried; results
+    /// from later providers only add URLs not seen yet.
+    async fn search_providers(
+        &self,
+        query: &str,
+        num_results: usize,
+    ) -> Result<serde_json::Value, ToolError> {
+        let mut results = Vec::new();
+        let mut seen = HashSet::new();
+        let mut answered = Vec::new();
+        let mut errors = Vec::new();
+
+        for provider in &self.providers {
+            match provider::search(provider.as_ref(), self.platform.http(), query, num_results)
+                .await
+            {
+                Ok(found) => {
+                    answered.push(provider.name());
+                    results.extend(found.into_iter().filter(|r| seen.insert(dedup_key(&r.url))));
+                }
+                Err(e) => {
+                    warn!(provider = provider.name(), error = %e, "search provider failed");
+                    errors.push(format!("{}: {e}", provider.name()));
+                }
+            }
+            if results.len() >= num_results {
+                break;
+            }
+        }
+
+        if answered.is_empty() {
+            return Err(ToolError::ExecutionFailed(format!(
+                "search failed: {}",
+                errors.join("; ")
+            )));
+        }
+        results.truncate(num_results);
+
+        let mut out = json!({
+            "query": query,
+            "providers": answered,
+            "results": results,
+        });
+        if !errors.is_empty() {
+            out["errors"] = json!(errors);
+        }
+        Ok(out)
+    }
 }
 
 #[cfg_attr(not(feature = "browser"), async_trait)]
@@ -131,7 +267,7 @@ impl<P: Platform + 'static> Tool for WebSearchTool<P> {
     }
 
     fn description(&self) -> &str {
-        "Search the web for information. Returns a list of search results with titles, URLs, and snippets."
+        "Search the web for information. Returns a list of search results with titles, URLs, snippets, and publication dates when known."
     }
 
     fn parameters(&self) -> serde_json::Value {
@@ -167,63 +303,25 @@ impl<P: Platform + 'static> Tool for WebSearchTool<P> {
         if !self.config.is_configured() {
             return Ok(json!({
                 "error": "web search not configured",
-                "message": "No search API key or endpoint is configured. Set 'tools.web.search.api_key' (Brave Search) or 'tools.web.search.endpoint' (custom) in your config.",
+                "message": "No usable search provider is configured. Set 'tools.web.search.api_key' (Brave Search), configure 'tools.web.search.google' or 'tools.web.search.searxng' and select it with 'tools.web.search.provider', or set the provider to 'duckduckgo', which needs no key.",
                 "query": query,
             }));
         }
 
-        let (url, headers) = self.build_request(query, num_results);
-
-        let result = self
-            .platform
-            .http()
-            .request("GET", &url, &headers, None)
-            .await
-            .map_err(|e| ToolError::ExecutionFailed(format!("search request failed: {e}")))?;
-
-        if result.status >= 400 {
-            return Err(ToolError::ExecutionFailed(format!(
-                "search API returned status {}",
-                result.status
-            )));
-        }
-
-        // Parse the response body as JSON.
-        let body = String::from_utf8_lossy(&result.body);
-        match serde_json::from_str::<serde_json::Value>(&body) {
-            Ok(parsed) => Ok(json!({
-                "query": query,
-                "results": parsed,
-            })),
-            Err(_) => Ok(json!({
-                "query": query,
-                "results": body.to_string(),
-            })),
-        }
-    }
-}
-
-/// Minimal URL encoding for query strings.
-fn urlencoding_minimal(s: &str) -> String {
-    let mut result = String::with_capacity(s.len());
-    for c in s.chars() {
-        match c {
-            ' ' => result.push_str("%20"),
-            '&' => result.push_str("%26"),
-            '=' => result.push_str("%3D"),
-            '+' => result.push_str("%2B"),
-            '#' => result.push_str("%23"),
-            '%' => result.push_str("%25"),
-            _ => result.push(c),
+        if self.config.has_endpoint() {
+            self.search_endpoint(query, num_results).await
+        } else {
+            self.search_providers(query, num_results).await
         }
     }
-    result
 }
 
 #[cfg(test)]
 mod tests {
+    use super::provider::BRAVE_SEARCH_ENDPOINT;
     use super::*;
     use clawft_platform::NativePlatform;
+    use clawft_platform::testing::{MockPlatform, MockResponse};
 
     fn make_tool_configured(
         api_key: Option<&str>,
@@ -235,6 +333,7 @@ mod tests {
                 api_key: api_key.map(|s| s.to_string()),
                 endpoint: endpoint.map(|s| s.to_string()),
                 max_results: 5,
+                ..Default::default()
             },
         )
     }
@@ -290,18 +389,18 @@ mod tests {
     }
 
     #[test]
-    fn urlencoding_spaces() {
-        assert_eq!(urlencoding_minimal("hello world"), "hello%20world");
+    fn encode_query_spaces() {
+        assert_eq!(encode_query("hello world"), "hello%20world");
     }
 
     #[test]
-    fn urlencoding_special_chars() {
-        assert_eq!(urlencoding_minimal("a&b=c"), "a%26b%3Dc");
+    fn encode_query_special_chars() {
+        assert_eq!(encode_query("a&b=c"), "a%26b%3Dc");
     }
 
     #[test]
-    fn urlencoding_plain() {
-        assert_eq!(urlencoding_minimal("hello"), "hello");
+    fn encode_query_plain() {
+        assert_eq!(encode_query("hello"), "hello");
     }
 
     #[test]
@@ -325,6 +424,7 @@ mod tests {
             api_key: Some("test-key".into()),
             endpoint: None,
             max_results: 5,
+            ..Default::default()
         };
         assert!(config.is_configured());
     }
@@ -335,6 +435,7 @@ mod tests {
             api_key: None,
             endpoint: Some("https://search.example.com".into()),
             max_results: 5,
+            ..Default::default()
         };
         assert!(config.is_configured());
     }
@@ -345,6 +446,7 @@ mod tests {
             api_key: Some(String::new()),
             endpoint: Some(String::new()),
             max_results: 5,
+            ..Default::default()
         };
         assert!(!config.is_configured());
     }
@@ -416,4 +518,146 @@ mod tests {
         // Just check it doesn't crash; config max_results=5 is the default
         assert_eq!(result["error"], "web search not configured");
     }
+
+    // -- provider chain --
+
+    fn searxng_results(urls: &[&str]) -> serde_json::Value {
+        let results: Vec<_> = urls
+            .iter()
+            .map(|url| json!({"url": url, "title": format!("title {url}"), "content": "c"}))
+            .collect();
+        json!({"results": results})
+    }
+
+    fn chain_tool(platform: Arc<MockPlatform>, max_results: u32) -> WebSearchTool<MockPlatform> {
+        WebSearchTool::new(
+            platform,
+            WebSearchConfig {
+                provider: SearchProviderKind::Searxng,
+                fallback_chain: vec![SearchProviderKind::Brave, SearchProviderKind::DuckDuckGo],
+                api_key: Some("brave-key".into()),
+                searxng_url: Some("https://searx.example.org".into()),
+                max_results,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn providers_skip_unconfigured_and_duplicates() {
+        let config = WebSearchConfig {
+            provider: SearchProviderKind::Google,
+            fallback_chain: vec![
+                SearchProviderKind::Searxng,
+                SearchProviderKind::DuckDuckGo,
+                SearchProviderKind::Google,
+            ],
+            google_api_key: Some("key".into()),
+            ..Default::default()
+        };
+        // Google lacks a cx and SearXNG a base URL.
+        let names: Vec<_> = config.providers().iter().map(|p| p.name()).collect();
+        assert_eq!(names, ["duckduckgo"]);
+        assert!(config.is_configured());
+    }
+
+    #[tokio::test]
+    async fn fallback_chain_merges_and_dedups_by_url() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://searx.example.org/search*",
+            MockResponse::json(
+                200,
+                &searxng_results(&["https://a.example/", "https://b.example/x"]),
+            ),
+        );
+        platform.http.respond(
+            "https://api.search.brave.com/*",
+            MockResponse::json(
+                200,
+                &json!({"web": {"results": [
+                    {"title": "B again", "url": "http://www.b.example/x/", "description": "dup"},
+                    {"title": "C", "url": "https://c.example/", "description": "new"},
+                    {"title": "D", "url": "https://d.example/", "description": "extra"}
+                ]}}),
+            ),
+        );
+        let tool = chain_tool(platform.clone(), 3);
+
+        let result = tool.execute(json!({"query": "rust"})).await.unwrap();
+        let urls: Vec<_> = result["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["url"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://a.example/",
+                "https://b.example/x",
+                "https://c.example/"
+            ]
+        );
+        assert_eq!(result["providers"], json!(["searxng", "brave"]));
+        assert!(result.get("errors").is_none());
+        // DuckDuckGo was not needed.
+        assert_eq!(platform.http.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failing_provider_falls_through() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://searx.example.org/search*",
+            MockResponse::text(403, "Forbidden"),
+        );
+        platform.http.respond(
+            "https://api.search.brave.com/*",
+            MockResponse::error("connection refused"),
+        );
+        platform.http.respond(
+            "https://html.duckduckgo.com/html/*",
+            MockResponse::text(
+                200,
+                r#"<a class="result__a" href="https://example.com/">Example</a>
+                   <a class="result__snippet">An example.</a>"#,
+            ),
+        );
+        let tool = chain_tool(platform, 5);
+
+        let result = tool.execute(json!({"query": "example"})).await.unwrap();
+        assert_eq!(result["providers"], json!(["duckduckgo"]));
+        assert_eq!(result["results"][0]["url"], "https://example.com/");
+        assert_eq!(result["results"][0]["snippet"], "An example.");
+        let errors = result["errors"].as_array().unwrap();
+        assert_eq!(errors[0], "searxng: returned status 403");
+        assert!(
+            errors[1]
+                .as_str()
+                .unwrap()
+                .starts_with("brave: request failed")
+        );
+    }
+
+    #[tokio::test]
+    async fn all_providers_failing_is_an_error() {
+        let platform = Arc::new(MockPlatform::new());
+        platform.http.respond(
+            "https://searx.example.org/search*",
+            MockResponse::text(500, "oops"),
+        );
+        let tool = WebSearchTool::new(
+            platform,
+            WebSearchConfig {
+                provider: SearchProviderKind::Searxng,
+                searxng_url: Some("https://searx.example.org".into()),
+                max_results: 5,
+                ..Default::default()
+            },
+        );
+
+        let err = tool.execute(json!({"query": "x"})).await.unwrap_err();
+        assert!(err.to_string().contains("searxng: returned status 500"));
+    }
 }
@@ -0,0 +1,591 @@
+//! Search providers behind the `web_search` tool.
+//!
+//! Each [`SearchProvider`] knows how to phrase a query for one service
+//! and how to read its response into [`SearchResult`]s. Sending the
+//! request is shared: [`search`] issues the `GET` through the platform
+//! HTTP client, so providers stay free of I/O and their parsers can be
+//! tested against captured responses.
+
+use std::collections::HashMap;
+
+use clawft_platform::http::HttpClient;
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+use crate::html_extract;
+
+/// Brave Search API base URL.
+pub const BRAVE_SEARCH_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+/// Google Custom Search JSON API base URL.
+pub const GOOGLE_SEARCH_ENDPOINT: &str = "https://www.googleapis.com/customsearch/v1";
+
+/// DuckDuckGo's script-free results page.
+pub const DUCKDUCKGO_HTML_ENDPOINT: &str = "https://html.duckduckgo.com/html/";
+
+/// Largest page the Google Custom Search API will return.
+const GOOGLE_MAX_NUM: usize = 10;
+
+/// One search result, normalized across providers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchResult {
+    /// Page title, as plain text.
+    pub title: String,
+    /// Link to the page.
+    pub url: String,
+    /// Summary or excerpt, as plain text.
+    pub snippet: String,
+    /// Publication date as reported by the provider, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+/// The `GET` request a provider wants sent for a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchRequest {
+    /// Full request URL, query string included.
+    pub url: String,
+    /// Request headers.
+    pub headers: HashMap<String, String>,
+}
+
+/// Errors from a single provider.
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    /// The request could not be sent.
+    #[error("request failed: {0}")]
+    Request(String),
+
+    /// The service answered with an error status.
+    #[error("returned status {0}")]
+    Status(u16),
+
+    /// The response did not have the expected shape.
+    #[error("unexpected response: {0}")]
+    Parse(String),
+}
+
+/// A web search service.
+pub trait SearchProvider: Send + Sync {
+    /// Short identifier reported with results (`"brave"`, `"google"`, ...).
+    fn name(&self) -> &'static str;
+
+    /// Build the request for `query`, asking for up to `max_results`.
+    fn request(&self, query: &str, max_results: usize) -> SearchRequest;
+
+    /// Read results out of a successful response body.
+    fn parse(&self, body: &[u8]) -> Result<Vec<SearchResult>, SearchError>;
+}
+
+/// Run `query` against `provider`, returning at most `max_results`.
+pub async fn search(
+    provider: &dyn SearchProvider,
+    http: &dyn HttpClient,
+    query: &str,
+    max_results: usize,
+) -> Result<Vec<SearchResult>, SearchError> {
+    let request = provider.request(query, max_results);
+    let response = http
+        .request("GET", &request.url, &request.headers, None)
+        .await
+        .map_err(|e| SearchError::Request(e.to_string()))?;
+    if response.status >= 400 {
+        return Err(SearchError::Status(response.status));
+    }
+    let mut results = provider.parse(&response.body)?;
+    results.retain(|r| !r.url.is_empty());
+    results.truncate(max_results);
+    Ok(results)
+}
+
+/// Key under which two result URLs count as the same page: scheme,
+/// `www.`, fragment and a trailing slash are ignored.
+pub fn dedup_key(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.trim_end_matches('/').to_string();
+    };
+    let host = parsed.host_str().unwrap_or("").to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let path = parsed.path().trim_end_matches('/');
+    match parsed.query() {
+        Some(query) => format!("{host}{path}?{query}"),
+        None => format!("{host}{path}"),
+    }
+}
+
+/// Percent-encode a query-string component (RFC 3986 unreserved
+/// characters are kept as-is).
+pub(crate) fn encode_query(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn with_query(base: &str, params: &[(&str, &str)]) -> String {
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{k}={}", encode_query(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let sep = if base.contains('?') { '&' } else { '?' };
+    format!("{base}{sep}{query}")
+}
+
+fn parse_json(body: &[u8]) -> Result<Value, SearchError> {
+    serde_json::from_slice(body).map_err(|e| SearchError::Parse(format!("invalid JSON: {e}")))
+}
+
+fn str_field(item: &Value, key: &str) -> String {
+    item.get(key)
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn opt_field(item: &Value, key: &str) -> Option<String> {
+    item.get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Build a result, stripping any markup providers leave in titles and
+/// snippets (Brave, for one, bolds matched terms).
+fn result(title: &str, url: String, snippet: &str, published: Option<String>) -> SearchResult {
+    SearchResult {
+        title: html_extract::inline_text(title),
+        url,
+        snippet: html_extract::inline_text(snippet),
+        published,
+    }
+}
+
+// ── Brave ─────────────────────────────────────────────────────────────
+
+/// Brave Search API.
+pub struct BraveProvider {
+    api_key: String,
+}
+
+impl BraveProvider {
+    /// Create a provider using `api_key` as the subscription token.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+        }
+    }
+}
+
+impl SearchProvider for BraveProvider {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
+    fn request(&self, query: &str, max_results: usize) -> SearchRequest {
+        let count = max_results.to_string();
+        SearchRequest {
+            url: with_query(BRAVE_SEARCH_ENDPOINT, &[("q", query), ("count", &count)]),
+            headers: HashMap::from([
+                ("X-Subscription-Token".to_string(), self.api_key.clone()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]),
+        }
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<Vec<SearchResult>, SearchError> {
+        let json = parse_json(body)?;
+        let Some(items) = json.pointer("/web/results") else {
+            // Brave omits the `web` section when nothing matched.
+            return Ok(Vec::new());
+        };
+        let items = items
+            .as_array()
+            .ok_or_else(|| SearchError::Parse("web.results is not an array".into()))?;
+        Ok(items
+            .iter()
+            .map(|item| {
+                result(
+                    &str_field(item, "title"),
+                    str_field(item, "url"),
+                    &str_field(item, "description"),
+                    opt_field(item, "page_age").or_else(|| opt_field(item, "age")),
+                )
+            })
+            .collect())
+    }
+}
+
+// ── Google Custom Search ──────────────────────────────────────────────
+
+/// Google Custom Search JSON API.
+pub struct GoogleProvider {
+    api_key: String,
+    cx: String,
+}
+
+impl GoogleProvider {
+    /// Create a provider for the search engine `cx`.
+    pub fn new(api_key: impl Into<String>, cx: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            cx: cx.into(),
+        }
+    }
+}
+
+impl SearchProvider for GoogleProvider {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn request(&self, query: &str, max_results: usize) -> SearchRequest {
+        let num = max_results.clamp(1, GOOGLE_MAX_NUM).to_string();
+        SearchRequest {
+            url: with_query(
+                GOOGLE_SEARCH_ENDPOINT,
+                &[
+                    ("key", &self.api_key),
+                    ("cx", &self.cx),
+                    ("q", query),
+                    ("num", &num),
+                ],
+            ),
+            headers: HashMap::from([("Accept".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<Vec<SearchResult>, SearchError> {
+        let json = parse_json(body)?;
+        if let Some(message) = json.pointer("/error/message").and_then(Value::as_str) {
+            return Err(SearchError::Parse(message.to_string()));
+        }
+        // `items` is absent when there are no results.
+        let Some(items) = json.get("items").and_then(Value::as_array) else {
+            return Ok(Vec::new());
+        };
+        Ok(items
+            .iter()
+            .map(|item| {
+                let published = item
+                    .pointer("/pagemap/metatags/0")
+                    .and_then(|tags| opt_field(tags, "article:published_time"));
+                result(
+                    &str_field(item, "title"),
+                    str_field(item, "link"),
+                    &str_field(item, "snippet"),
+                    published,
+                )
+            })
+            .collect())
+    }
+}
+
+// ── SearXNG ───────────────────────────────────────────────────────────
+
+/// A SearXNG instance's JSON API.
+pub struct SearxngProvider {
+    base_url: String,
+}
+
+impl SearxngProvider {
+    /// Create a provider for the instance at `base_url`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl SearchProvider for SearxngProvider {
+    fn name(&self) -> &'static str {
+        "searxng"
+    }
+
+    fn request(&self, query: &str, _max_results: usize) -> SearchRequest {
+        // SearXNG has no result-count parameter; `search` truncates.
+        SearchRequest {
+            url: with_query(
+                &format!("{}/search", self.base_url),
+                &[("q", query), ("format", "json")],
+            ),
+            headers: HashMap::from([("Accept".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<Vec<SearchResult>, SearchError> {
+        let json = parse_json(body)?;
+        let items = json
+            .get("results")
+            .and_then(Value::as_array)
+            .ok_or_else(|| SearchError::Parse("missing results array".into()))?;
+        Ok(items
+            .iter()
+            .map(|item| {
+                result(
+                    &str_field(item, "title"),
+                    str_field(item, "url"),
+                    &str_field(item, "content"),
+                    opt_field(item, "publishedDate"),
+                )
+            })
+            .collect())
+    }
+}
+
+// ── DuckDuckGo ────────────────────────────────────────────────────────
+
+/// DuckDuckGo's HTML results page, for use without any API key.
+pub struct DuckDuckGoProvider {
+    base_url: String,
+}
+
+impl DuckDuckGoProvider {
+    /// Create a provider using the public endpoint.
+    pub fn new() -> Self {
+        Self::with_base_url(DUCKDUCKGO_HTML_ENDPOINT)
+    }
+
+    /// Create a provider using another endpoint serving the same page.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Unwrap DuckDuckGo's `/l/?uddg=<target>` redirect links.
+    fn target(&self, href: &str) -> Option<String> {
+        let base = Url::parse(&self.base_url).ok()?;
+        let link = base.join(href).ok()?;
+        let is_ddg = link
+            .host_str()
+            .is_some_and(|h| h == "duckduckgo.com" || h.ends_with(".duckduckgo.com"));
+        if !is_ddg {
+            return Some(link.to_string());
+        }
+        // Other DuckDuckGo links (ads, internal pages) are not results.
+        link.query_pairs()
+            .find(|(k, _)| k == "uddg")
+            .map(|(_, v)| v.into_owned())
+    }
+}
+
+impl Default for DuckDuckGoProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchProvider for DuckDuckGoProvider {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+
+    fn request(&self, query: &str, _max_results: usize) -> SearchRequest {
+        SearchRequest {
+            url: with_query(&self.base_url, &[("q", query)]),
+            headers: HashMap::from([("Accept".to_string(), "text/html".to_string())]),
+        }
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<Vec<SearchResult>, SearchError> {
+        let html = String::from_utf8_lossy(body);
+        let mut results: Vec<SearchResult> = Vec::new();
+        let mut skipping = false;
+        for el in html_extract::select_by_class(&html, &["result__a", "result__snippet"]) {
+            if el.class == "result__a" {
+                let url = el.href.as_deref().and_then(|href| self.target(href));
+                skipping = url.is_none();
+                if let Some(url) = url {
+                    results.push(SearchResult {
+                        title: el.text,
+                        url,
+                        snippet: String::new(),
+                        published: None,
+                    });
+                }
+            } else if !skipping && let Some(last) = results.last_mut() {
+                last.snippet = el.text;
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRAVE_FIXTURE: &str = r#"{
+        "type": "search",
+        "query": {"original": "rust async"},
+        "web": {"type": "search", "results": [
+            {"title": "Asynchronous Programming in <strong>Rust</strong>",
+             "url": "https://rust-lang.github.io/async-book/",
+             "description": "Getting started with <strong>async</strong> &amp; await.",
+             "page_age": "2024-03-01T00:00:00"},
+            {"title": "Tokio", "url": "https://tokio.rs/",
+             "description": "An asynchronous runtime.", "age": "2 days ago"}
+        ]}
+    }"#;
+
+    const GOOGLE_FIXTURE: &str = r#"{
+        "kind": "customsearch#search",
+        "items": [
+            {"kind": "customsearch#result", "title": "The Rust Book",
+             "link": "https://doc.rust-lang.org/book/",
+             "snippet": "An introductory book about Rust.",
+             "pagemap": {"metatags": [{"article:published_time": "2023-11-02"}]}},
+            {"kind": "customsearch#result", "title": "Rust by Example",
+             "link": "https://doc.rust-lang.org/rust-by-example/",
+             "snippet": "A collection of runnable examples."}
+        ]
+    }"#;
+
+    const SEARXNG_FIXTURE: &str = r#"{
+        "query": "rust",
+        "number_of_results": 0,
+        "results": [
+            {"url": "https://www.rust-lang.org/", "title": "Rust Programming Language",
+             "content": "A language empowering everyone.", "engine": "bing",
+             "publishedDate": null},
+            {"url": "https://blog.rust-lang.org/2024/01/01/news.html", "title": "News",
+             "content": "Release notes.", "publishedDate": "2024-01-01T00:00:00"}
+        ]
+    }"#;
+
+    const DUCKDUCKGO_FIXTURE: &str = r#"<html><body><div class="serp__results">
+        <div class="result results_links results_links_deep result--ad">
+          <a class="result__a" href="https://duckduckgo.com/y.js?ad_provider=x&amp;u3=y">Sponsored</a>
+          <a class="result__snippet" href="https://duckduckgo.com/y.js?ad_provider=x">Buy things.</a>
+        </div>
+        <div class="result results_links results_links_deep web-result">
+          <h2 class="result__title">
+            <a rel="nofollow" class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fwww.rust%2Dlang.org%2F&amp;rut=abc">Rust Programming <b>Language</b></a>
+          </h2>
+          <a class="result__snippet" href="//duckduckgo.com/l/?uddg=x">A language empowering
+            everyone to build <b>reliable</b> &amp; efficient software.</a>
+        </div>
+        <div class="result results_links results_links_deep web-result">
+          <h2 class="result__title"><a class="result__a" href="https://crates.io/">crates.io</a></h2>
+          <a class="result__snippet">The Rust community&#39;s crate registry.</a>
+        </div>
+    </div></body></html>"#;
+
+    #[test]
+    fn brave_parses_fixture() {
+        let results = BraveProvider::new("k")
+            .parse(BRAVE_FIXTURE.as_bytes())
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Asynchronous Programming in Rust");
+        assert_eq!(results[0].url, "https://rust-lang.github.io/async-book/");
+        assert_eq!(results[0].snippet, "Getting started with async & await.");
+        assert_eq!(results[0].published.as_deref(), Some("2024-03-01T00:00:00"));
+        assert_eq!(results[1].published.as_deref(), Some("2 days ago"));
+
+        let empty = BraveProvider::new("k")
+            .parse(br#"{"type": "search"}"#)
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn brave_request_carries_token() {
+        let request = BraveProvider::new("secret").request("rust & go", 3);
+        assert_eq!(
+            request.url,
+            format!("{BRAVE_SEARCH_ENDPOINT}?q=rust%20%26%20go&count=3")
+        );
+        assert_eq!(request.headers["X-Subscription-Token"], "secret");
+    }
+
+    #[test]
+    fn google_parses_fixture() {
+        let provider = GoogleProvider::new("key", "cx1");
+        let results = provider.parse(GOOGLE_FIXTURE.as_bytes()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "The Rust Book");
+        assert_eq!(results[0].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(results[0].published.as_deref(), Some("2023-11-02"));
+        assert_eq!(results[1].snippet, "A collection of runnable examples.");
+        assert!(results[1].published.is_none());
+
+        let err = provider
+            .parse(br#"{"error": {"code": 400, "message": "API key not valid."}}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("API key not valid"));
+    }
+
+    #[test]
+    fn google_request_caps_num() {
+        let request = GoogleProvider::new("key", "cx1").request("rust", 50);
+        assert_eq!(
+            request.url,
+            format!("{GOOGLE_SEARCH_ENDPOINT}?key=key&cx=cx1&q=rust&num=10")
+        );
+    }
+
+    #[test]
+    fn searxng_parses_fixture() {
+        let provider = SearxngProvider::new("https://searx.example.org/");
+        assert_eq!(
+            provider.request("rust lang", 5).url,
+            "https://searx.example.org/search?q=rust%20lang&format=json"
+        );
+
+        let results = provider.parse(SEARXNG_FIXTURE.as_bytes()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(results[0].snippet, "A language empowering everyone.");
+        assert!(results[0].published.is_none());
+        assert_eq!(results[1].published.as_deref(), Some("2024-01-01T00:00:00"));
+
+        assert!(provider.parse(b"<html>format disabled</html>").is_err());
+    }
+
+    #[test]
+    fn duckduckgo_parses_fixture() {
+        let results = DuckDuckGoProvider::new()
+            .parse(DUCKDUCKGO_FIXTURE.as_bytes())
+            .unwrap();
+        assert_eq!(results.len(), 2, "{results:?}");
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(
+            results[0].snippet,
+            "A language empowering everyone to build reliable & efficient software."
+        );
+        assert_eq!(results[1].url, "https://crates.io/");
+        assert_eq!(results[1].snippet, "The Rust community's crate registry.");
+    }
+
+    #[test]
+    fn dedup_key_ignores_scheme_www_and_slash() {
+        assert_eq!(
+            dedup_key("https://www.rust-lang.org/"),
+            dedup_key("http://rust-lang.org")
+        );
+        assert_eq!(
+            dedup_key("https://example.com/a#section"),
+            dedup_key("https://example.com/a/")
+        );
+        assert_ne!(
+            dedup_key("https://example.com/a?page=1"),
+            dedup_key("https://example.com/a?page=2")
+        );
+    }
+
+    #[test]
+    fn encode_query_escapes_reserved() {
+        assert_eq!(encode_query("a b&c=d+e#f%"), "a%20b%26c%3Dd%2Be%23f%25");
+        assert_eq!(encode_query("café"), "caf%C3%A9");
+    }
+}
@@ -28,6 +28,8 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::delegation::DelegationConfig;
+use crate::delivery::{DeliveryTarget, ProactiveDelivery, QuietHours};
+use crate::provider::ReasoningEffort;
 use crate::routing::RoutingConfig;
 use crate::secret::SecretString;
 
@@ -59,6 +61,10 @@ pub struct Config {
     #[serde(default)]
     pub gateway: GatewayConfig,
 
+    /// Outbound HTTP client settings (timeouts, proxy, extra CA).
+    #[serde(default)]
+    pub http: HttpConfig,
+
     /// Tool configurations (web search, exec, MCP servers).
     #[serde(default)]
     pub tools: ToolsConfig,
@@ -82,14 +88,27 @@ pub struct Config {
     /// Pipeline stage selection (scorer, learner backends).
     #[serde(default)]
     pub pipeline: PipelineConfig,
+
+    /// Agent loop hooks (built-in policy and logging hooks).
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// `weft mcp-server` settings (HTTP transport).
+    #[serde(default, alias = "mcpServer")]
+    pub mcp_server: McpServeConfig,
+
+    /// Scheduled backups of memory, sessions, config and skills.
+    #[serde(default)]
+    pub backup: BackupConfig,
 }
 
 // ── Pipeline ────────────────────────────────────────────────────────────
 
-/// Pipeline stage backend selection.
+/// Pipeline stage backend selection and stage order.
 ///
-/// Allows selecting which scorer and learner implementations to use.
-/// Defaults to `"noop"` for backward compatibility.
+/// Allows selecting which scorer and learner implementations to use, and
+/// where custom stages run between the six built-in ones. Defaults to
+/// `"noop"` backends and the built-in order for backward compatibility.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineConfig {
     /// Quality scorer backend: `"noop"` (default) or `"fitness"`.
@@ -99,6 +118,23 @@ pub struct PipelineConfig {
     /// Learning backend: `"noop"` (default) or `"trajectory"`.
     #[serde(default = "default_learner")]
     pub learner: String,
+
+    /// Stage order: the six built-in stages in their fixed order, with
+    /// custom stages inserted between them. Empty keeps the built-in order.
+    #[serde(default)]
+    pub stages: Vec<PipelineStageEntry>,
+
+    /// Stage orders for individual agents, replacing `stages` for them.
+    #[serde(default, alias = "agentStages")]
+    pub agent_stages: HashMap<String, Vec<PipelineStageEntry>>,
+
+    /// What a failing custom stage does, unless its entry says otherwise.
+    #[serde(default, alias = "onStageError")]
+    pub on_stage_error: StageErrorPolicy,
+
+    /// Built-in stage that masks text matching regular expressions.
+    #[serde(default)]
+    pub redactor: RedactorStageConfig,
 }
 
 fn default_scorer() -> String {
@@ -114,10 +150,162 @@ impl Default for PipelineConfig {
         Self {
             scorer: default_scorer(),
             learner: default_learner(),
+            stages: Vec::new(),
+            agent_stages: HashMap::new(),
+            on_stage_error: StageErrorPolicy::default(),
+            redactor: RedactorStageConfig::default(),
+        }
+    }
+}
+
+/// One entry of a pipeline stage order: a stage name, or a name with its
+/// own error policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PipelineStageEntry {
+    /// A stage by name.
+    Name(String),
+    /// A stage by name, with an error policy overriding `onStageError`.
+    Detailed {
+        /// Stage name.
+        name: String,
+        /// What a failure of this stage does.
+        #[serde(default, alias = "onError")]
+        on_error: Option<StageErrorPolicy>,
+    },
+}
+
+impl PipelineStageEntry {
+    /// The stage name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) | Self::Detailed { name, .. } => name,
+        }
+    }
+
+    /// The entry's own error policy, if it has one.
+    pub fn on_error(&self) -> Option<StageErrorPolicy> {
+        match self {
+            Self::Name(_) => None,
+            Self::Detailed { on_error, .. } => *on_error,
+        }
+    }
+}
+
+/// What happens to a turn when a custom pipeline stage fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageErrorPolicy {
+    /// Fail the turn with the stage's error.
+    #[default]
+    Abort,
+    /// Log a warning and carry on as if the stage were not there.
+    Skip,
+}
+
+/// Settings for the built-in `redactor` pipeline stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactorStageConfig {
+    /// Regular expressions; matching text in message and response content
+    /// is replaced. The stage is registered when this is non-empty.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Text that replaces each match.
+    #[serde(default = "default_redaction")]
+    pub replacement: String,
+}
+
+fn default_redaction() -> String {
+    "[REDACTED]".into()
+}
+
+impl Default for RedactorStageConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            replacement: default_redaction(),
+        }
+    }
+}
+
+// ── Hooks ───────────────────────────────────────────────────────────────
+
+/// Agent loop hook settings.
+///
+/// Hooks run before and after every tool call and LLM completion, in
+/// registration order. Each may observe, modify or veto the call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// How long one hook may take on one event before it is skipped.
+    #[serde(default = "default_hook_timeout_ms", alias = "timeoutMs")]
+    pub timeout_ms: u64,
+
+    /// Built-in hook that vetoes shell commands matching a pattern.
+    #[serde(default, alias = "commandBlocker")]
+    pub command_blocker: CommandBlockerHookConfig,
+
+    /// Built-in hook that appends every LLM request to a JSONL file.
+    #[serde(default, alias = "promptLogger")]
+    pub prompt_logger: PromptLoggerHookConfig,
+}
+
+fn default_hook_timeout_ms() -> u64 {
+    5_000
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_hook_timeout_ms(),
+            command_blocker: CommandBlockerHookConfig::default(),
+            prompt_logger: PromptLoggerHookConfig::default(),
+        }
+    }
+}
+
+/// Settings for the built-in command blocker hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandBlockerHookConfig {
+    /// Register the hook.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Regular expressions; a command matching any of them is vetoed.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+
+    /// Tools whose `command` argument is checked.
+    #[serde(default = "default_blocked_command_tools")]
+    pub tools: Vec<String>,
+}
+
+fn default_blocked_command_tools() -> Vec<String> {
+    vec!["exec_shell".into()]
+}
+
+impl Default for CommandBlockerHookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            patterns: Vec::new(),
+            tools: default_blocked_command_tools(),
         }
     }
 }
 
+/// Settings for the built-in prompt logger hook.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptLoggerHookConfig {
+    /// Register the hook.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Log file (default: `~/.clawft/state/prompts.jsonl`).
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
 impl Config {
     /// Get the expanded workspace path.
     ///
@@ -158,6 +346,259 @@ pub struct AgentsConfig {
     /// Default settings applied to all agents.
     #[serde(default)]
     pub defaults: AgentDefaults,
+
+    /// Inbound dispatch policy (per-session ordering and concurrency).
+    #[serde(default)]
+    pub dispatch: DispatchConfig,
+
+    /// Token usage accounting, pricing overrides, and spend limits.
+    #[serde(default)]
+    pub usage: UsageConfig,
+
+    /// Per-turn and per-day resource limits.
+    #[serde(default)]
+    pub budget: BudgetConfig,
+
+    /// Response cache for requests that opt in to caching.
+    #[serde(default)]
+    pub cache: ResponseCacheConfig,
+
+    /// Where conversation sessions are persisted.
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+
+    /// Where long-term memory and history are stored.
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    /// Summarizing history that falls out of the context window.
+    #[serde(default)]
+    pub compaction: CompactionConfig,
+
+    /// Plan-then-execute turns with user approval.
+    #[serde(default)]
+    pub planning: PlanModeConfig,
+
+    /// Variables for the prompt templates.
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+
+    /// Activating skills automatically from message content.
+    #[serde(default)]
+    pub skills: SkillActivationConfig,
+
+    /// The `weft agent` interactive session.
+    #[serde(default)]
+    pub interactive: InteractiveConfig,
+}
+
+/// Inbound message dispatch policy.
+///
+/// Messages for the same session (`channel:chat_id`) are always processed
+/// strictly in order; up to `max_concurrent_sessions` different sessions
+/// are processed at the same time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchConfig {
+    /// Maximum number of sessions processed concurrently (minimum 1).
+    #[serde(
+        default = "default_max_concurrent_sessions",
+        alias = "maxConcurrentSessions"
+    )]
+    pub max_concurrent_sessions: usize,
+
+    /// Maximum number of messages waiting per session before new messages
+    /// for that session are shed with a busy reply.
+    #[serde(default = "default_max_session_queue", alias = "maxSessionQueue")]
+    pub max_session_queue: usize,
+
+    /// Maximum number of messages waiting across all sessions.
+    #[serde(default = "default_max_total_queue", alias = "maxTotalQueue")]
+    pub max_total_queue: usize,
+
+    /// Reply sent when a message is shed because queues are full.
+    /// Empty disables the reply (the message is dropped silently).
+    #[serde(default = "default_busy_reply", alias = "busyReply")]
+    pub busy_reply: String,
+
+    /// Capacity of the message bus queues. A full inbound queue makes
+    /// channel adapters wait; heartbeat and cron events are shed instead.
+    #[serde(default = "default_bus_capacity", alias = "busCapacity")]
+    pub bus_capacity: usize,
+
+    /// Seconds the inbound queue must stay above 80% full before the
+    /// gateway logs a backpressure warning (0 disables the warning).
+    #[serde(
+        default = "default_backpressure_warn_secs",
+        alias = "backpressureWarnSecs"
+    )]
+    pub backpressure_warn_secs: u64,
+}
+
+fn default_max_concurrent_sessions() -> usize {
+    4
+}
+fn default_max_session_queue() -> usize {
+    16
+}
+fn default_max_total_queue() -> usize {
+    256
+}
+fn default_busy_reply() -> String {
+    "I'm handling a lot of messages right now. Please try again in a moment.".into()
+}
+fn default_bus_capacity() -> usize {
+    1024
+}
+fn default_backpressure_warn_secs() -> u64 {
+    30
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_sessions: default_max_concurrent_sessions(),
+            max_session_queue: default_max_session_queue(),
+            max_total_queue: default_max_total_queue(),
+            busy_reply: default_busy_reply(),
+            bus_capacity: default_bus_capacity(),
+            backpressure_warn_secs: default_backpressure_warn_secs(),
+        }
+    }
+}
+
+/// Token usage accounting and spend limits.
+///
+/// Usage is priced from the built-in list prices
+/// ([`MODEL_PRICES`](crate::provider::MODEL_PRICES)); entries in `pricing`
+/// override them, keyed by bare model-name prefix (longest match wins).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Write usage records to the ledger in the state directory.
+    #[serde(default = "default_true")]
+    pub ledger: bool,
+
+    /// Seconds between ledger flushes.
+    #[serde(default = "default_flush_interval_secs", alias = "flushIntervalSecs")]
+    pub flush_interval_secs: u64,
+
+    /// Maximum spend per session in US dollars. Once reached, the agent
+    /// refuses further LLM calls for that session. `None` means unlimited.
+    #[serde(default, alias = "sessionBudgetUsd")]
+    pub session_budget_usd: Option<f64>,
+
+    /// Per-model price overrides.
+    #[serde(default)]
+    pub pricing: HashMap<String, PriceOverride>,
+}
+
+/// A configured model price, in US dollars per 1K tokens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceOverride {
+    /// Price of 1K prompt tokens.
+    #[serde(default, alias = "inputPer1k")]
+    pub input_per_1k: f64,
+
+    /// Price of 1K completion tokens.
+    #[serde(default, alias = "outputPer1k")]
+    pub output_per_1k: f64,
+
+    /// Price of 1K prompt-cache reads. Defaults to the input price.
+    #[serde(
+        default,
+        alias = "cacheReadPer1k",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cache_read_per_1k: Option<f64>,
+
+    /// Price of 1K prompt-cache writes. Defaults to the input price.
+    #[serde(
+        default,
+        alias = "cacheWritePer1k",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cache_write_per_1k: Option<f64>,
+}
+
+fn default_flush_interval_secs() -> u64 {
+    60
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            ledger: true,
+            flush_interval_secs: default_flush_interval_secs(),
+            session_budget_usd: None,
+            pricing: HashMap::new(),
+        }
+    }
+}
+
+/// Resource limits enforced by the agent loop.
+///
+/// Each limit is off when unset. A turn that trips one ends with a message
+/// saying which; the interactive CLI can lift them with `--no-budget`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Maximum prompt plus completion tokens across a turn's LLM calls.
+    #[serde(default, alias = "maxTokensPerTurn")]
+    pub max_tokens_per_turn: Option<u64>,
+
+    /// Maximum wall-clock seconds for one turn.
+    #[serde(default, alias = "maxTurnSecs")]
+    pub max_turn_secs: Option<u64>,
+
+    /// Maximum tool executions in one turn.
+    #[serde(default, alias = "maxToolCallsPerTurn")]
+    pub max_tool_calls_per_turn: Option<u32>,
+
+    /// Maximum spend per local calendar day in US dollars, across all
+    /// sessions. Persisted in the state directory.
+    #[serde(default, alias = "maxCostPerDayUsd")]
+    pub max_cost_per_day_usd: Option<f64>,
+}
+
+/// LLM response cache.
+///
+/// Only requests that explicitly opt in at temperature 0 are cached:
+/// memory merges and history / tool-result summaries. Conversational calls
+/// never are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// Serve opted-in requests from the cache.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Maximum number of responses kept in memory.
+    #[serde(default = "default_cache_capacity")]
+    pub capacity: usize,
+
+    /// Seconds a cached response stays valid.
+    #[serde(default = "default_cache_ttl_secs", alias = "ttlSecs")]
+    pub ttl_secs: u64,
+
+    /// Also keep responses on disk in the state directory, across restarts.
+    #[serde(default)]
+    pub persist: bool,
+}
+
+fn default_cache_capacity() -> usize {
+    512
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    86_400
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capacity: default_cache_capacity(),
+            ttl_secs: default_cache_ttl_secs(),
+            persist: false,
+        }
+    }
 }
 
 /// Default agent settings.
@@ -179,6 +620,11 @@ pub struct AgentDefaults {
     #[serde(default = "default_temperature")]
     pub temperature: f64,
 
+    /// Nucleus sampling threshold sent with every request. Unset leaves
+    /// the provider's default.
+    #[serde(default, alias = "topP", skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+
     /// Maximum tool-use iterations per turn.
     #[serde(default = "default_max_tool_iterations", alias = "maxToolIterations")]
     pub max_tool_iterations: i32,
@@ -186,6 +632,10 @@ pub struct AgentDefaults {
     /// Number of recent messages to include in context.
     #[serde(default = "default_memory_window", alias = "memoryWindow")]
     pub memory_window: i32,
+
+    /// Maximum tool calls from one LLM response executed concurrently.
+    #[serde(default = "default_max_parallel_tools", alias = "maxParallelTools")]
+    pub max_parallel_tools: u32,
 }
 
 fn default_workspace() -> String {
@@ -206,6 +656,9 @@ fn default_max_tool_iterations() -> i32 {
 fn default_memory_window() -> i32 {
     50
 }
+fn default_max_parallel_tools() -> u32 {
+    4
+}
 
 impl Default for AgentDefaults {
     fn default() -> Self {
@@ -214,12 +667,323 @@ impl Default for AgentDefaults {
             model: default_model(),
             max_tokens: default_max_tokens(),
             temperature: default_temperature(),
+            top_p: None,
             max_tool_iterations: default_max_tool_iterations(),
             memory_window: default_memory_window(),
+            max_parallel_tools: default_max_parallel_tools(),
+        }
+    }
+}
+
+/// Session persistence and retention settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// Storage backend for sessions.
+    #[serde(default)]
+    pub backend: SessionBackend,
+
+    /// Archive sessions not updated for this many days. 0 keeps them.
+    #[serde(default, alias = "maxIdleDays")]
+    pub max_idle_days: u32,
+
+    /// Archive the least recently updated sessions beyond this many.
+    /// 0 means no limit.
+    #[serde(default, alias = "maxSessions")]
+    pub max_sessions: usize,
+
+    /// How often the gateway checks the retention limits.
+    #[serde(
+        default = "default_retention_interval_minutes",
+        alias = "retentionIntervalMinutes"
+    )]
+    pub retention_interval_minutes: u64,
+
+    /// Write a structured trace of every turn under the sessions
+    /// directory, for `weft sessions inspect --trace`.
+    #[serde(default)]
+    pub trace: bool,
+}
+
+fn default_retention_interval_minutes() -> u64 {
+    60
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            backend: SessionBackend::default(),
+            max_idle_days: 0,
+            max_sessions: 0,
+            retention_interval_minutes: default_retention_interval_minutes(),
+            trace: false,
+        }
+    }
+}
+
+impl SessionsConfig {
+    /// Whether any retention limit is set.
+    pub fn retention_enabled(&self) -> bool {
+        self.max_idle_days > 0 || self.max_sessions > 0
+    }
+}
+
+/// Memory storage and hygiene settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Storage backend for long-term memory and history.
+    #[serde(default)]
+    pub backend: MemoryBackendKind,
+
+    /// Similarity (0.0-1.0) at or above which a new long-term entry counts
+    /// as a duplicate of an existing one and is not stored again. Values
+    /// above 1.0 turn duplicate suppression off.
+    #[serde(
+        default = "default_memory_duplicate_threshold",
+        alias = "duplicateThreshold"
+    )]
+    pub duplicate_threshold: f64,
+
+    /// Similarity at or above which `weft memory compact` asks the model to
+    /// merge entries into one.
+    #[serde(default = "default_memory_merge_threshold", alias = "mergeThreshold")]
+    pub merge_threshold: f64,
+
+    /// Entries not referenced for this many days are dropped by
+    /// `weft memory compact`.
+    #[serde(default = "default_memory_decay_days", alias = "decayDays")]
+    pub decay_days: u32,
+
+    /// Model that merges similar entries, in `provider/model` form. Unset
+    /// uses `agents.defaults.model`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+fn default_memory_duplicate_threshold() -> f64 {
+    0.85
+}
+fn default_memory_merge_threshold() -> f64 {
+    0.6
+}
+fn default_memory_decay_days() -> u32 {
+    30
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            backend: MemoryBackendKind::default(),
+            duplicate_threshold: default_memory_duplicate_threshold(),
+            merge_threshold: default_memory_merge_threshold(),
+            decay_days: default_memory_decay_days(),
+            model: None,
+        }
+    }
+}
+
+/// History compaction.
+///
+/// When a session outgrows `memory_window`, the messages that fall out of
+/// the window are summarized into a rolling summary that is kept on the
+/// session and sent ahead of the recent history. The stored messages are
+/// never modified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    /// Summarize evicted history instead of silently dropping it.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Model that writes the summary, in `provider/model` form. Unset uses
+    /// `agents.defaults.model`; a cheaper model is usually enough.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Minimum number of user turns between two compactions.
+    #[serde(default = "default_compaction_min_turns", alias = "minTurns")]
+    pub min_turns: usize,
+
+    /// Maximum length of the summary, in tokens.
+    #[serde(
+        default = "default_compaction_max_summary_tokens",
+        alias = "maxSummaryTokens"
+    )]
+    pub max_summary_tokens: usize,
+
+    /// Characters of each tool result passed to the summarizer. Tool
+    /// output is cut much shorter than user and assistant text.
+    #[serde(
+        default = "default_compaction_tool_result_chars",
+        alias = "toolResultChars"
+    )]
+    pub tool_result_chars: usize,
+}
+
+fn default_compaction_min_turns() -> usize {
+    5
+}
+fn default_compaction_max_summary_tokens() -> usize {
+    1024
+}
+fn default_compaction_tool_result_chars() -> usize {
+    300
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            model: None,
+            min_turns: default_compaction_min_turns(),
+            max_summary_tokens: default_compaction_max_summary_tokens(),
+            tool_result_chars: default_compaction_tool_result_chars(),
+        }
+    }
+}
+
+/// Planning mode: the agent drafts a plan, waits for approval, then runs
+/// it step by step.
+///
+/// A `/plan <task>` message always starts planning; with `enabled` every
+/// message does. Each step's result is checked against its success
+/// criteria, and a failed step leads to a revised plan that again needs
+/// approval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanModeConfig {
+    /// Plan every message, not only `/plan` commands.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Model that drafts plans and checks step results, in
+    /// `provider/model` form. Unset uses `agents.defaults.model`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Revised plans drafted after failed steps before the plan is
+    /// abandoned.
+    #[serde(default = "default_plan_max_replans", alias = "maxReplans")]
+    pub max_replans: u32,
+}
+
+fn default_plan_max_replans() -> u32 {
+    2
+}
+
+impl Default for PlanModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: None,
+            max_replans: default_plan_max_replans(),
+        }
+    }
+}
+
+/// Prompt template settings.
+///
+/// Templates are read from `<workspace>/templates/<name>.md`, falling back
+/// to built-in defaults, and may use `{{variable}}` placeholders.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplatesConfig {
+    /// User-defined variables, usable in every template next to the
+    /// built-in ones (`agent`, `date`, `workspace`, `model`).
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+}
+
+/// Automatic skill activation.
+///
+/// With `auto_activate`, each message is scored against the triggers of
+/// the registered skills and the best match at or above `threshold` is
+/// activated for the turn: its instructions are added to the prompt and
+/// its `allowed-tools` limit the turn's tools.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillActivationConfig {
+    /// Activate matching skills without an explicit `/use`.
+    #[serde(default, alias = "autoActivate")]
+    pub auto_activate: bool,
+
+    /// How messages are scored against skill triggers.
+    #[serde(default)]
+    pub scoring: SkillScoring,
+
+    /// Minimum score (0.0 to 1.0) a skill needs to be activated.
+    #[serde(default = "default_skill_threshold")]
+    pub threshold: f64,
+
+    /// Skill preferred when several score the same. An agent's first
+    /// listed skill takes precedence over this for that agent.
+    #[serde(
+        default,
+        alias = "defaultSkill",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_skill: Option<String>,
+}
+
+fn default_skill_threshold() -> f64 {
+    0.5
+}
+
+impl Default for SkillActivationConfig {
+    fn default() -> Self {
+        Self {
+            auto_activate: false,
+            scoring: SkillScoring::default(),
+            threshold: default_skill_threshold(),
+            default_skill: None,
         }
     }
 }
 
+/// How messages are scored against skill triggers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillScoring {
+    /// Keyword matches and word overlap with the example messages.
+    #[default]
+    Keyword,
+    /// Similarity to the centroid of the example embeddings. Requires the
+    /// `vector-memory` feature; keyword scoring is used without it.
+    Embedding,
+}
+
+/// The `weft agent` interactive session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InteractiveConfig {
+    /// Send slash commands the REPL does not know to the agent as
+    /// ordinary messages instead of rejecting them.
+    #[serde(default, alias = "forwardUnknownCommands")]
+    pub forward_unknown_commands: bool,
+}
+
+/// Which store persists conversation sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionBackend {
+    /// One JSONL file per session (default).
+    #[default]
+    File,
+    /// A single SQLite database (`sessions.db`), safe for several
+    /// processes writing at once. Requires the `sqlite-sessions` feature.
+    Sqlite,
+}
+
+/// Which store holds agent memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryBackendKind {
+    /// `MEMORY.md` and `HISTORY.md` in the workspace memory directory
+    /// (default).
+    #[default]
+    Markdown,
+    /// A SQLite database (`memory.db`) with full-text search. Requires the
+    /// `sqlite-memory` feature.
+    Sqlite,
+    /// Embedded entries ranked by semantic similarity (`vectors.json`).
+    /// Requires the `vector-memory` feature.
+    Vector,
+}
+
 // ── Providers ────────────────────────────────────────────────────────────
 
 /// LLM provider credentials.
@@ -244,6 +1008,101 @@ pub struct ProviderConfig {
     /// CORS proxy URL for browser-mode API calls (e.g. "https://proxy.example.com").
     #[serde(default, alias = "corsProxy")]
     pub cors_proxy: Option<String>,
+
+    /// Client-side request limits applied before calls reach the provider.
+    #[serde(default, alias = "rateLimit")]
+    pub rate_limit: RateLimitConfig,
+
+    /// Azure OpenAI only: deployment name for each model name. Models
+    /// without an entry are used as the deployment name directly.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub deployments: HashMap<String, String>,
+
+    /// Azure OpenAI only: the `api-version` query parameter.
+    #[serde(default, alias = "apiVersion", skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+    /// Overall request deadline in seconds (provider default when unset).
+    #[serde(
+        default,
+        alias = "timeoutSecs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout_secs: Option<u64>,
+
+    /// Connection timeout in seconds (provider default when unset).
+    #[serde(
+        default,
+        alias = "connectTimeoutSecs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Longest gap between streamed chunks, in seconds (provider default
+    /// when unset).
+    #[serde(
+        default,
+        alias = "readTimeoutSecs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub read_timeout_secs: Option<u64>,
+
+    /// Default reasoning effort (`minimal`, `low`, `medium`, `high`) for
+    /// reasoning models on this provider. Ignored by models that do not
+    /// accept it.
+    #[serde(
+        default,
+        alias = "reasoningEffort",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
+/// Client-side rate limits for a single provider.
+///
+/// Every limit is optional; an unset limit is not enforced. Requests that
+/// cannot be admitted within `max_queue_wait_secs` fail with a rate-limit
+/// error instead of waiting indefinitely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Maximum requests started per rolling minute.
+    #[serde(default, alias = "requestsPerMinute")]
+    pub requests_per_minute: Option<u32>,
+
+    /// Maximum tokens (prompt plus requested completion) per rolling minute.
+    #[serde(default, alias = "tokensPerMinute")]
+    pub tokens_per_minute: Option<u32>,
+
+    /// Maximum requests in flight at once.
+    #[serde(default, alias = "maxConcurrent")]
+    pub max_concurrent: Option<u32>,
+
+    /// Longest a request may wait in the queue before it is rejected.
+    #[serde(default = "default_max_queue_wait_secs", alias = "maxQueueWaitSecs")]
+    pub max_queue_wait_secs: u64,
+}
+
+fn default_max_queue_wait_secs() -> u64 {
+    30
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            max_concurrent: None,
+            max_queue_wait_secs: default_max_queue_wait_secs(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Whether no limit is configured.
+    pub fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none()
+            && self.tokens_per_minute.is_none()
+            && self.max_concurrent.is_none()
+    }
 }
 
 /// Configuration for all LLM providers.
@@ -309,11 +1168,53 @@ pub struct ProvidersConfig {
     #[serde(default)]
     pub xai: ProviderConfig,
 
+    /// Azure OpenAI. `api_base` is the resource endpoint
+    /// (`https://<resource>.openai.azure.com`).
+    #[serde(default)]
+    pub azure: ProviderConfig,
+
     /// ElevenLabs (TTS).
     #[serde(default)]
     pub elevenlabs: ProviderConfig,
 }
 
+impl ProvidersConfig {
+    /// Standard API-key environment variables for providers that have one.
+    pub const API_KEY_ENV_VARS: &'static [(&'static str, &'static str)] = &[
+        ("openai", "OPENAI_API_KEY"),
+        ("anthropic", "ANTHROPIC_API_KEY"),
+        ("groq", "GROQ_API_KEY"),
+        ("deepseek", "DEEPSEEK_API_KEY"),
+        ("openrouter", "OPENROUTER_API_KEY"),
+        ("gemini", "GOOGLE_GEMINI_API_KEY"),
+        ("xai", "XAI_API_KEY"),
+        ("azure", "AZURE_OPENAI_API_KEY"),
+    ];
+
+    /// Fill empty provider API keys from their standard environment
+    /// variables, read through `lookup`.
+    ///
+    /// Keys set explicitly in the config are left alone.
+    pub fn resolve_api_keys(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        for (name, var) in Self::API_KEY_ENV_VARS {
+            let provider = match *name {
+                "openai" => &mut self.openai,
+                "anthropic" => &mut self.anthropic,
+                "groq" => &mut self.groq,
+                "deepseek" => &mut self.deepseek,
+                "openrouter" => &mut self.openrouter,
+                "gemini" => &mut self.gemini,
+                "xai" => &mut self.xai,
+                "azure" => &mut self.azure,
+                _ => continue,
+            };
+            if let Some(key) = provider.api_key.resolve(Some(var), &lookup) {
+                provider.api_key = key;
+            }
+        }
+    }
+}
+
 // ── Gateway ──────────────────────────────────────────────────────────────
 
 /// Gateway / HTTP server configuration.
@@ -335,6 +1236,43 @@ pub struct GatewayConfig {
     #[serde(default = "default_heartbeat_prompt", alias = "heartbeatPrompt")]
     pub heartbeat_prompt: String,
 
+    /// Channel that receives the heartbeat reply (e.g. `"telegram"`).
+    /// Unset keeps the reply on the internal `heartbeat` channel.
+    #[serde(
+        default,
+        alias = "heartbeatChannel",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub heartbeat_channel: Option<String>,
+
+    /// Conversation within `heartbeat_channel` that receives the reply.
+    #[serde(
+        default,
+        alias = "heartbeatChatId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub heartbeat_chat_id: Option<String>,
+
+    /// Consecutive `NO_NOTIFY` heartbeat replies after which the interval
+    /// starts doubling (0 = never back off).
+    #[serde(
+        default = "default_heartbeat_backoff_after",
+        alias = "heartbeatBackoffAfter"
+    )]
+    pub heartbeat_backoff_after: u32,
+
+    /// Longest heartbeat interval the backoff reaches, in minutes.
+    #[serde(
+        default = "default_heartbeat_max_interval_minutes",
+        alias = "heartbeatMaxIntervalMinutes"
+    )]
+    pub heartbeat_max_interval_minutes: u64,
+
+    /// Window during which heartbeat and cron replies are held back.
+    /// Cron jobs can set their own.
+    #[serde(default, alias = "quietHours", skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+
     /// Port for the UI REST API (separate from gateway port).
     #[serde(default = "default_api_port", alias = "apiPort")]
     pub api_port: u16,
@@ -346,6 +1284,15 @@ pub struct GatewayConfig {
     /// Whether the REST/WS API is enabled.
     #[serde(default, alias = "apiEnabled")]
     pub api_enabled: bool,
+
+    /// Serve channel metrics in Prometheus text format at `/metrics` on
+    /// the API port (requires `api_enabled`).
+    #[serde(default, alias = "metricsEnabled")]
+    pub metrics_enabled: bool,
+
+    /// Structured log file read by `weft logs`.
+    #[serde(default)]
+    pub logs: LogSinkConfig,
 }
 
 fn default_gateway_host() -> String {
@@ -354,89 +1301,842 @@ fn default_gateway_host() -> String {
 fn default_gateway_port() -> u16 {
     18790
 }
-fn default_heartbeat_prompt() -> String {
-    "heartbeat".into()
+fn default_heartbeat_prompt() -> String {
+    "heartbeat".into()
+}
+fn default_heartbeat_backoff_after() -> u32 {
+    3
+}
+fn default_heartbeat_max_interval_minutes() -> u64 {
+    24 * 60
+}
+
+impl GatewayConfig {
+    /// Delivery rules for the heartbeat reply.
+    pub fn heartbeat_delivery(&self) -> ProactiveDelivery {
+        let target = match (&self.heartbeat_channel, &self.heartbeat_chat_id) {
+            (Some(channel), Some(chat_id)) => Some(DeliveryTarget {
+                channel: channel.clone(),
+                chat_id: chat_id.clone(),
+            }),
+            _ => None,
+        };
+        ProactiveDelivery {
+            target,
+            quiet_hours: self.quiet_hours.clone(),
+        }
+    }
+}
+fn default_api_port() -> u16 {
+    18789
+}
+fn default_cors_origins() -> Vec<String> {
+    vec!["http://localhost:5173".into()]
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            host: default_gateway_host(),
+            port: default_gateway_port(),
+            heartbeat_interval_minutes: 0,
+            heartbeat_prompt: default_heartbeat_prompt(),
+            heartbeat_channel: None,
+            heartbeat_chat_id: None,
+            heartbeat_backoff_after: default_heartbeat_backoff_after(),
+            heartbeat_max_interval_minutes: default_heartbeat_max_interval_minutes(),
+            quiet_hours: None,
+            api_port: default_api_port(),
+            cors_origins: default_cors_origins(),
+            api_enabled: false,
+            metrics_enabled: false,
+            logs: LogSinkConfig::default(),
+        }
+    }
+}
+
+/// The gateway's structured log file, `~/.clawft/state/logs.jsonl`.
+///
+/// Every tracing event at or above `level` is written as one JSON line,
+/// with the session, channel, agent and tool it concerns when known.
+/// Independent of `RUST_LOG`, which only affects terminal output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSinkConfig {
+    /// Write the log file.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Least severe level written: `trace`, `debug`, `info`, `warn` or
+    /// `error`.
+    #[serde(default = "default_log_sink_level")]
+    pub level: String,
+
+    /// Rotate the file once it would grow past this many bytes.
+    #[serde(default = "default_log_sink_max_file_bytes", alias = "maxFileBytes")]
+    pub max_file_bytes: u64,
+
+    /// Rotated files to keep (`logs.jsonl.1` is the newest).
+    #[serde(default = "default_log_sink_max_files", alias = "maxFiles")]
+    pub max_files: usize,
+}
+
+fn default_log_sink_level() -> String {
+    "info".into()
+}
+
+fn default_log_sink_max_file_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_sink_max_files() -> usize {
+    5
+}
+
+impl Default for LogSinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: default_log_sink_level(),
+            max_file_bytes: default_log_sink_max_file_bytes(),
+            max_files: default_log_sink_max_files(),
+        }
+    }
+}
+
+// ── MCP server ───────────────────────────────────────────────────────────
+
+/// Settings for `weft mcp-server --http`, which serves MCP over the
+/// Streamable HTTP transport.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServeConfig {
+    /// Bind address. Loopback by default; other addresses expose the
+    /// agent's tools to the network, so set `auth_token` with them.
+    #[serde(default = "default_mcp_serve_host")]
+    pub host: String,
+
+    /// Listen port.
+    #[serde(default = "default_mcp_serve_port")]
+    pub port: u16,
+
+    /// Bearer token clients must send (`Authorization: Bearer <token>`).
+    /// Empty disables authentication.
+    #[serde(default, alias = "authToken")]
+    pub auth_token: SecretString,
+
+    /// Browser origins allowed besides localhost ones (e.g.
+    /// `"https://app.example.com"`). Requests with any other `Origin`
+    /// header are refused.
+    #[serde(default, alias = "allowedOrigins")]
+    pub allowed_origins: Vec<String>,
+}
+
+fn default_mcp_serve_host() -> String {
+    "127.0.0.1".into()
+}
+fn default_mcp_serve_port() -> u16 {
+    18791
+}
+
+impl Default for McpServeConfig {
+    fn default() -> Self {
+        Self {
+            host: default_mcp_serve_host(),
+            port: default_mcp_serve_port(),
+            auth_token: SecretString::default(),
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+// ── Backups ──────────────────────────────────────────────────────────────
+
+/// Scheduled backups, taken by `weft gateway` while it runs.
+///
+/// Each backup is a timestamped `.tar.gz` of `~/.clawft/config.json`, the
+/// workspace memory and sessions, and `~/.clawft/skills`. Secrets files
+/// (`.env`, keys, certificates) are always left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Take backups.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Hours between backups.
+    #[serde(default = "default_backup_interval_hours", alias = "intervalHours")]
+    pub interval_hours: u64,
+
+    /// Backups to keep; older ones are deleted after each backup.
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+
+    /// Directory for the archives. Defaults to `~/.clawft/backups`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+
+    /// Gitignore-style patterns of paths to leave out, relative to
+    /// `~/.clawft` (e.g. `"workspace/sessions/archive/"`, `"*.log"`).
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Also upload each backup to an S3-compatible bucket (needs the
+    /// `backup-s3` feature).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s3: Option<S3BackupConfig>,
+}
+
+fn default_backup_interval_hours() -> u64 {
+    24
+}
+fn default_backup_keep() -> usize {
+    7
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_backup_interval_hours(),
+            keep: default_backup_keep(),
+            dir: None,
+            exclude: Vec::new(),
+            s3: None,
+        }
+    }
+}
+
+/// An S3-compatible bucket backups are uploaded to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3BackupConfig {
+    /// Endpoint URL, e.g. `https://s3.us-east-1.amazonaws.com` or a MinIO
+    /// / R2 endpoint. Objects are addressed path-style.
+    pub endpoint: String,
+
+    /// Bucket name.
+    pub bucket: String,
+
+    /// Signing region.
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+
+    /// Key prefix for uploaded archives (e.g. `"clawft/laptop/"`).
+    #[serde(default)]
+    pub prefix: String,
+
+    /// Access key id.
+    #[serde(default, alias = "accessKeyId")]
+    pub access_key_id: String,
+
+    /// Environment variable holding the access key id, used when
+    /// `access_key_id` is empty.
+    #[serde(default, alias = "accessKeyIdEnv")]
+    pub access_key_id_env: Option<String>,
+
+    /// Secret access key.
+    #[serde(default, alias = "secretAccessKey")]
+    pub secret_access_key: SecretString,
+
+    /// Environment variable holding the secret access key, used when
+    /// `secret_access_key` is empty.
+    #[serde(default, alias = "secretAccessKeyEnv")]
+    pub secret_access_key_env: Option<String>,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".into()
+}
+
+// ── Outbound HTTP ────────────────────────────────────────────────────────
+
+/// Outbound HTTP client configuration shared by providers and tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Seconds allowed to establish a connection.
+    #[serde(default = "default_connect_timeout_secs", alias = "connectTimeoutSecs")]
+    pub connect_timeout_secs: u64,
+
+    /// Seconds allowed for a whole request, including the body.
+    #[serde(default = "default_request_timeout_secs", alias = "requestTimeoutSecs")]
+    pub request_timeout_secs: u64,
+
+    /// Proxy URL for all requests (e.g. `http://proxy.corp:3128` or
+    /// `socks5://127.0.0.1:1080`). Falls back to `HTTPS_PROXY` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// PEM file with additional root certificates to trust.
+    #[serde(default, alias = "caCertPath", skip_serializing_if = "Option::is_none")]
+    pub ca_cert_path: Option<String>,
+
+    /// `User-Agent` header sent with every request.
+    #[serde(default, alias = "userAgent", skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// When and how often failed requests are retried.
+    #[serde(default)]
+    pub retry: HttpRetryConfig,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+fn default_request_timeout_secs() -> u64 {
+    60
+}
+
+/// Retry settings for outbound HTTP requests.
+///
+/// Connection failures, timeouts and the listed status codes are retried
+/// with exponential backoff. Only idempotent requests are retried unless
+/// `retry_non_idempotent` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRetryConfig {
+    /// Total attempts including the first (1 disables retries).
+    #[serde(default = "default_http_max_attempts", alias = "maxAttempts")]
+    pub max_attempts: u32,
+
+    /// Milliseconds to wait before the first retry; doubles on each retry.
+    #[serde(
+        default = "default_http_initial_backoff_ms",
+        alias = "initialBackoffMs"
+    )]
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound in milliseconds for any single delay.
+    #[serde(default = "default_http_max_backoff_ms", alias = "maxBackoffMs")]
+    pub max_backoff_ms: u64,
+
+    /// Status codes that trigger a retry.
+    #[serde(default = "default_http_retry_statuses", alias = "retryStatuses")]
+    pub retry_statuses: Vec<u16>,
+
+    /// Also retry `POST`, `PATCH`, `DELETE` and unkeyed `PUT` requests,
+    /// which may repeat a side effect.
+    #[serde(default, alias = "retryNonIdempotent")]
+    pub retry_non_idempotent: bool,
+}
+
+fn default_http_max_attempts() -> u32 {
+    3
+}
+fn default_http_initial_backoff_ms() -> u64 {
+    250
+}
+fn default_http_max_backoff_ms() -> u64 {
+    10_000
+}
+fn default_http_retry_statuses() -> Vec<u16> {
+    vec![429, 502, 503, 504]
+}
+
+impl Default for HttpRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_http_max_attempts(),
+            initial_backoff_ms: default_http_initial_backoff_ms(),
+            max_backoff_ms: default_http_max_backoff_ms(),
+            retry_statuses: default_http_retry_statuses(),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            proxy: None,
+            ca_cert_path: None,
+            user_agent: None,
+            retry: HttpRetryConfig::default(),
+        }
+    }
+}
+
+// ── Tools ────────────────────────────────────────────────────────────────
+
+/// Tools configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolsConfig {
+    /// Web tools (search, etc.).
+    #[serde(default)]
+    pub web: WebToolsConfig,
+
+    /// Shell exec tool settings.
+    #[serde(default, rename = "exec")]
+    pub exec_tool: ExecToolConfig,
+
+    /// `http_request` tool settings.
+    #[serde(default)]
+    pub http: HttpToolConfig,
+
+    /// `sqlite_query` / `sqlite_execute` tool settings.
+    #[serde(default)]
+    pub sqlite: SqliteToolConfig,
+
+    /// `clipboard` / `notify` desktop tool settings.
+    #[serde(default)]
+    pub desktop: DesktopToolConfig,
+
+    /// `schedule_task` tool settings.
+    #[serde(default, alias = "scheduleTask")]
+    pub schedule_task: ScheduleTaskToolConfig,
+
+    /// Named secrets the agent can pass to tools with `secret_use`
+    /// without ever seeing their values.
+    #[serde(default)]
+    pub secrets: HashMap<String, SecretRef>,
+
+    /// Post-processing of tool results too large to hand to the model.
+    #[serde(default)]
+    pub results: ToolResultsConfig,
+
+    /// Whether to restrict all tool access to the workspace directory.
+    #[serde(default, alias = "restrictToWorkspace")]
+    pub restrict_to_workspace: bool,
+
+    /// MCP server connections.
+    #[serde(default, alias = "mcpServers")]
+    pub mcp_servers: HashMap<String, MCPServerConfig>,
+
+    /// Command execution policy (allowlist/denylist).
+    #[serde(default, alias = "commandPolicy")]
+    pub command_policy: CommandPolicyConfig,
+
+    /// URL safety policy (SSRF protection).
+    #[serde(default, alias = "urlPolicy")]
+    pub url_policy: UrlPolicyConfig,
+
+    /// Audit log of tool executions.
+    #[serde(default)]
+    pub audit: ToolAuditConfig,
+}
+
+impl ToolsConfig {
+    /// `tools.secrets` with the deprecated `tools.http.secrets` merged in,
+    /// each of those as a secret granted to `http_request` only. A name
+    /// defined in both places keeps its `tools.secrets` entry.
+    pub fn all_secrets(&self) -> HashMap<String, SecretRef> {
+        let mut secrets = self.secrets.clone();
+        for (name, value) in &self.http.secrets {
+            secrets.entry(name.clone()).or_insert_with(|| SecretRef {
+                value: value.clone(),
+                tools: vec!["http_request".into()],
+                ..SecretRef::default()
+            });
+        }
+        secrets
+    }
+}
+
+/// Tool execution audit log.
+///
+/// Every tool call is recorded with its (redacted) arguments, duration and
+/// outcome: in `~/.clawft/state/audit.jsonl`, or in the session database
+/// when `agents.sessions.backend` is `"sqlite"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAuditConfig {
+    /// Record tool executions.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Rotate the JSONL log once it would grow past this many bytes.
+    #[serde(default = "default_audit_max_file_bytes", alias = "maxFileBytes")]
+    pub max_file_bytes: u64,
+
+    /// Rotated JSONL files to keep (`audit.jsonl.1` is the newest).
+    #[serde(default = "default_audit_max_files", alias = "maxFiles")]
+    pub max_files: usize,
+
+    /// Extra argument field names to redact, on top of the built-in
+    /// token/password/secret patterns. Matched case-insensitively as
+    /// substrings, ignoring `_` and `-`.
+    #[serde(default, alias = "redactFields")]
+    pub redact_fields: Vec<String>,
+}
+
+fn default_audit_max_file_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_audit_max_files() -> usize {
+    5
+}
+
+impl Default for ToolAuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_file_bytes: default_audit_max_file_bytes(),
+            max_files: default_audit_max_files(),
+            redact_fields: Vec::new(),
+        }
+    }
+}
+
+/// Post-processing of oversized tool results.
+///
+/// A result whose JSON form is larger than `max_bytes` is written whole to
+/// a spill file under `<workspace>/.clawft/tool-results/`, and the model
+/// gets a shortened version naming that file, which it can `read_file`
+/// for the details.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResultsConfig {
+    /// Size in bytes above which a result is post-processed.
+    #[serde(default = "default_tool_result_max_bytes", alias = "maxBytes")]
+    pub max_bytes: usize,
+
+    /// How oversized results are shortened, for tools without an entry
+    /// in `tools`.
+    #[serde(default)]
+    pub strategy: ResultStrategy,
+
+    /// Per-tool strategies, keyed by tool name or glob pattern
+    /// (e.g. `"mcp_github__*"`). An exact name wins over a pattern, and
+    /// a longer pattern over a shorter one.
+    #[serde(default)]
+    pub tools: HashMap<String, ResultStrategy>,
+
+    /// Model for the `summarize` strategy, in `provider/model` form.
+    /// Unset uses `agents.defaults.model`; a cheaper model is usually
+    /// enough.
+    #[serde(
+        default,
+        alias = "summaryModel",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub summary_model: Option<String>,
+
+    /// Maximum length of a summary, in tokens.
+    #[serde(
+        default = "default_tool_result_max_summary_tokens",
+        alias = "maxSummaryTokens"
+    )]
+    pub max_summary_tokens: usize,
+
+    /// Spill files to keep; the oldest are removed once there are more.
+    /// `0` keeps them all.
+    #[serde(
+        default = "default_tool_result_keep_spill_files",
+        alias = "keepSpillFiles"
+    )]
+    pub keep_spill_files: usize,
+}
+
+fn default_tool_result_max_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_tool_result_max_summary_tokens() -> usize {
+    512
+}
+
+fn default_tool_result_keep_spill_files() -> usize {
+    100
+}
+
+impl Default for ToolResultsConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_tool_result_max_bytes(),
+            strategy: ResultStrategy::default(),
+            tools: HashMap::new(),
+            summary_model: None,
+            max_summary_tokens: default_tool_result_max_summary_tokens(),
+            keep_spill_files: default_tool_result_keep_spill_files(),
+        }
+    }
+}
+
+/// How an oversized tool result is shortened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultStrategy {
+    /// Cut the result at the size limit (default).
+    #[default]
+    Truncate,
+    /// Keep the shape of JSON results -- every object key, the first
+    /// elements of each array -- and shorten the values.
+    Structured,
+    /// Have a model summarize the result. Falls back to `structured`
+    /// when the summary cannot be written.
+    Summarize,
+}
+
+/// Web tools configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WebToolsConfig {
+    /// Search engine settings.
+    #[serde(default)]
+    pub search: WebSearchConfig,
+}
+
+/// Web search tool configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSearchConfig {
+    /// Search provider queried first.
+    #[serde(default)]
+    pub provider: SearchProviderKind,
+
+    /// Providers tried after `provider`, in order, when it fails or
+    /// returns fewer results than asked for. Results are merged and
+    /// deduplicated by URL.
+    #[serde(default, alias = "fallbackChain")]
+    pub fallback_chain: Vec<SearchProviderKind>,
+
+    /// Brave Search API key.
+    #[serde(default, alias = "apiKey")]
+    pub api_key: SecretString,
+
+    /// Maximum number of search results.
+    #[serde(default = "default_max_results", alias = "maxResults")]
+    pub max_results: u32,
+
+    /// Google Custom Search settings.
+    #[serde(default)]
+    pub google: GoogleSearchConfig,
+
+    /// SearXNG settings.
+    #[serde(default)]
+    pub searxng: SearxngConfig,
+
+    /// DuckDuckGo settings.
+    #[serde(default)]
+    pub duckduckgo: DuckDuckGoConfig,
+}
+
+fn default_max_results() -> u32 {
+    5
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            provider: SearchProviderKind::default(),
+            fallback_chain: Vec::new(),
+            api_key: SecretString::default(),
+            max_results: default_max_results(),
+            google: GoogleSearchConfig::default(),
+            searxng: SearxngConfig::default(),
+            duckduckgo: DuckDuckGoConfig::default(),
+        }
+    }
+}
+
+/// Which service answers `web_search` queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProviderKind {
+    /// Brave Search API (default). Needs `api_key`.
+    #[default]
+    Brave,
+    /// Google Custom Search JSON API. Needs `google.api_key` and `google.cx`.
+    Google,
+    /// A SearXNG instance. Needs `searxng.base_url`; no key.
+    Searxng,
+    /// DuckDuckGo's HTML results page. Needs no key.
+    DuckDuckGo,
+}
+
+/// Google Custom Search settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GoogleSearchConfig {
+    /// Custom Search JSON API key.
+    #[serde(default, alias = "apiKey")]
+    pub api_key: SecretString,
+
+    /// Programmable Search Engine ID.
+    #[serde(default)]
+    pub cx: String,
+}
+
+/// SearXNG settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearxngConfig {
+    /// Base URL of the instance, e.g. `https://searx.example.org`. The
+    /// instance must have the JSON output format enabled.
+    #[serde(default, alias = "baseUrl")]
+    pub base_url: String,
+}
+
+/// DuckDuckGo settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DuckDuckGoConfig {
+    /// Override for the HTML endpoint (default
+    /// `https://html.duckduckgo.com/html/`).
+    #[serde(default, alias = "baseUrl")]
+    pub base_url: String,
+}
+
+/// `http_request` tool configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpToolConfig {
+    /// Deprecated: define secrets in `tools.secrets` and grant them to
+    /// `http_request`. Entries here are still honoured, as secrets granted
+    /// to `http_request` only (see [`ToolsConfig::all_secrets`]).
+    #[serde(default)]
+    pub secrets: HashMap<String, SecretString>,
+
+    /// Default request timeout in seconds.
+    #[serde(default = "default_http_timeout_secs", alias = "timeoutSecs")]
+    pub timeout_secs: u64,
+
+    /// Response bodies longer than this are truncated.
+    #[serde(
+        default = "default_http_max_response_bytes",
+        alias = "maxResponseBytes"
+    )]
+    pub max_response_bytes: usize,
+}
+
+fn default_http_timeout_secs() -> u64 {
+    30
+}
+
+fn default_http_max_response_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for HttpToolConfig {
+    fn default() -> Self {
+        Self {
+            secrets: HashMap::new(),
+            timeout_secs: default_http_timeout_secs(),
+            max_response_bytes: default_http_max_response_bytes(),
+        }
+    }
+}
+
+/// A named secret for `secret_use`.
+///
+/// The agent refers to the secret as `{{secret:NAME}}` in the arguments
+/// of a tool call made through `secret_use`; the value is filled in when
+/// the tool runs and never shown to the model. Only the tools listed in
+/// `tools` may receive it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretRef {
+    /// What the secret is for, shown to the agent by `secret_list`.
+    #[serde(default)]
+    pub description: String,
+
+    /// The secret value.
+    #[serde(default)]
+    pub value: SecretString,
+
+    /// Environment variable that holds the value (e.g. `"GITHUB_TOKEN"`).
+    /// When set, the env var is used if `value` is empty.
+    #[serde(default, alias = "valueEnv")]
+    pub value_env: Option<String>,
+
+    /// Tools the secret may be passed to: exact names or glob patterns.
+    /// Empty = none, so every use has to be granted explicitly.
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+/// `sqlite_query` / `sqlite_execute` tool configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SqliteToolConfig {
+    /// Register `sqlite_execute`, which may write to workspace databases.
+    /// `sqlite_query` is always read-only.
+    #[serde(default, alias = "allowWrites")]
+    pub allow_writes: bool,
+
+    /// Databases and imported CSV files larger than this are refused, and
+    /// writes that would grow a database past it fail.
+    #[serde(default = "default_sqlite_max_db_bytes", alias = "maxDbBytes")]
+    pub max_db_bytes: u64,
+
+    /// Maximum rows returned by one query.
+    #[serde(default = "default_sqlite_max_rows", alias = "maxRows")]
+    pub max_rows: usize,
+
+    /// Maximum size of the returned rows, as JSON.
+    #[serde(default = "default_sqlite_max_result_bytes", alias = "maxResultBytes")]
+    pub max_result_bytes: usize,
+}
+
+fn default_sqlite_max_db_bytes() -> u64 {
+    100 * 1024 * 1024
 }
-fn default_api_port() -> u16 {
-    18789
+
+fn default_sqlite_max_rows() -> usize {
+    500
 }
-fn default_cors_origins() -> Vec<String> {
-    vec!["http://localhost:5173".into()]
+
+fn default_sqlite_max_result_bytes() -> usize {
+    32 * 1024
 }
 
-impl Default for GatewayConfig {
+impl Default for SqliteToolConfig {
     fn default() -> Self {
         Self {
-            host: default_gateway_host(),
-            port: default_gateway_port(),
-            heartbeat_interval_minutes: 0,
-            heartbeat_prompt: default_heartbeat_prompt(),
-            api_port: default_api_port(),
-            cors_origins: default_cors_origins(),
-            api_enabled: false,
+            allow_writes: false,
+            max_db_bytes: default_sqlite_max_db_bytes(),
+            max_rows: default_sqlite_max_rows(),
+            max_result_bytes: default_sqlite_max_result_bytes(),
         }
     }
 }
 
-// ── Tools ────────────────────────────────────────────────────────────────
-
-/// Tools configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct ToolsConfig {
-    /// Web tools (search, etc.).
+/// `clipboard` / `notify` desktop tool configuration.
+///
+/// Both tools are off unless opted in here, and are only available in
+/// builds with the `desktop-tools` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopToolConfig {
+    /// Register `clipboard`, which reads and replaces the system clipboard.
     #[serde(default)]
-    pub web: WebToolsConfig,
-
-    /// Shell exec tool settings.
-    #[serde(default, rename = "exec")]
-    pub exec_tool: ExecToolConfig,
+    pub clipboard: bool,
 
-    /// Whether to restrict all tool access to the workspace directory.
-    #[serde(default, alias = "restrictToWorkspace")]
-    pub restrict_to_workspace: bool,
-
-    /// MCP server connections.
-    #[serde(default, alias = "mcpServers")]
-    pub mcp_servers: HashMap<String, MCPServerConfig>,
+    /// Register `notify`, which shows desktop notifications.
+    #[serde(default)]
+    pub notifications: bool,
 
-    /// Command execution policy (allowlist/denylist).
-    #[serde(default, alias = "commandPolicy")]
-    pub command_policy: CommandPolicyConfig,
+    /// Largest text `clipboard` will set; longer clipboard contents are
+    /// truncated to this size when read.
+    #[serde(default = "default_clipboard_max_bytes", alias = "maxClipboardBytes")]
+    pub max_clipboard_bytes: usize,
+}
 
-    /// URL safety policy (SSRF protection).
-    #[serde(default, alias = "urlPolicy")]
-    pub url_policy: UrlPolicyConfig,
+fn default_clipboard_max_bytes() -> usize {
+    64 * 1024
 }
 
-/// Web tools configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WebToolsConfig {
-    /// Search engine settings.
-    #[serde(default)]
-    pub search: WebSearchConfig,
+impl Default for DesktopToolConfig {
+    fn default() -> Self {
+        Self {
+            clipboard: false,
+            notifications: false,
+            max_clipboard_bytes: default_clipboard_max_bytes(),
+        }
+    }
 }
 
-/// Web search tool configuration.
+/// `schedule_task` tool configuration.
+///
+/// The tool lets the agent schedule one-shot jobs for itself, such as
+/// reminders; it is registered by the gateway, which runs the scheduler.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WebSearchConfig {
-    /// Search API key (e.g. Brave Search).
-    #[serde(default, alias = "apiKey")]
-    pub api_key: SecretString,
-
-    /// Maximum number of search results.
-    #[serde(default = "default_max_results", alias = "maxResults")]
-    pub max_results: u32,
+pub struct ScheduleTaskToolConfig {
+    /// Register `schedule_task`.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Most jobs an agent may have waiting to run at once.
+    #[serde(default = "default_schedule_max_pending", alias = "maxPending")]
+    pub max_pending: usize,
 }
 
-fn default_max_results() -> u32 {
-    5
+fn default_schedule_max_pending() -> usize {
+    10
 }
 
-impl Default for WebSearchConfig {
+impl Default for ScheduleTaskToolConfig {
     fn default() -> Self {
         Self {
-            api_key: SecretString::default(),
-            max_results: default_max_results(),
+            enabled: true,
+            max_pending: default_schedule_max_pending(),
         }
     }
 }
@@ -447,16 +2147,45 @@ pub struct ExecToolConfig {
     /// Command timeout in seconds.
     #[serde(default = "default_exec_timeout")]
     pub timeout: u32,
+
+    /// Run each agent session's foreground commands in one long-lived
+    /// shell, so `cd` and exported variables carry over between calls.
+    #[serde(default, alias = "persistentSession")]
+    pub persistent_session: bool,
+
+    /// Seconds a persistent session shell may sit unused before it is
+    /// closed.
+    #[serde(default = "default_exec_session_idle_secs", alias = "sessionIdleSecs")]
+    pub session_idle_secs: u64,
+
+    /// Background jobs (`exec_shell` with `background: true`) that may run
+    /// at once.
+    #[serde(
+        default = "default_exec_max_background_jobs",
+        alias = "maxBackgroundJobs"
+    )]
+    pub max_background_jobs: usize,
 }
 
 fn default_exec_timeout() -> u32 {
     60
 }
 
+fn default_exec_session_idle_secs() -> u64 {
+    600
+}
+
+fn default_exec_max_background_jobs() -> usize {
+    4
+}
+
 impl Default for ExecToolConfig {
     fn default() -> Self {
         Self {
             timeout: default_exec_timeout(),
+            persistent_session: false,
+            session_idle_secs: default_exec_session_idle_secs(),
+            max_background_jobs: default_exec_max_background_jobs(),
         }
     }
 }
@@ -484,6 +2213,58 @@ pub struct MCPServerConfig {
     /// Infrastructure servers (claude-flow, claude-code) should be internal.
     #[serde(default = "default_true", alias = "internalOnly")]
     pub internal_only: bool,
+
+    /// Which of the server's tools are registered.
+    #[serde(default)]
+    pub expose: McpExposeConfig,
+
+    /// Prefix for registered tool names. Unset uses `"{server}__"`; the
+    /// prefixed names must still contain `__`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+
+    /// Descriptions replacing the server's, keyed by the server's tool name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub descriptions: HashMap<String, String>,
+
+    /// Most tools registered from this server; the rest are dropped with a
+    /// warning. 0 means no limit.
+    #[serde(default = "default_mcp_max_tools", alias = "maxTools")]
+    pub max_tools: usize,
+
+    /// Whether and how the server may run model calls through the
+    /// providers (MCP sampling).
+    #[serde(default)]
+    pub sampling: McpSamplingConfig,
+
+    /// Seconds a tool call may take, including any wait for a free call
+    /// slot, before it fails. 0 means no limit.
+    #[serde(default = "default_mcp_call_timeout", alias = "callTimeoutSecs")]
+    pub call_timeout_secs: u64,
+
+    /// Most tool calls in flight on the server at once; further calls
+    /// wait for a slot. 0 means no limit.
+    #[serde(
+        default = "default_mcp_max_concurrent_calls",
+        alias = "maxConcurrentCalls"
+    )]
+    pub max_concurrent_calls: usize,
+
+    /// When to stop sending calls to a failing server.
+    #[serde(default)]
+    pub circuit: McpCircuitConfig,
+}
+
+fn default_mcp_max_tools() -> usize {
+    100
+}
+
+fn default_mcp_call_timeout() -> u64 {
+    30
+}
+
+fn default_mcp_max_concurrent_calls() -> usize {
+    8
 }
 
 impl Default for MCPServerConfig {
@@ -494,10 +2275,133 @@ impl Default for MCPServerConfig {
             env: HashMap::new(),
             url: String::new(),
             internal_only: true,
+            expose: McpExposeConfig::default(),
+            prefix: None,
+            descriptions: HashMap::new(),
+            max_tools: default_mcp_max_tools(),
+            sampling: McpSamplingConfig::default(),
+            call_timeout_secs: default_mcp_call_timeout(),
+            max_concurrent_calls: default_mcp_max_concurrent_calls(),
+            circuit: McpCircuitConfig::default(),
+        }
+    }
+}
+
+/// Circuit breaker for an MCP server's tool calls.
+///
+/// After `failure_threshold` consecutive failed calls (timeouts and lost
+/// connections, not tool errors) the circuit opens and the server's tools
+/// fail at once for `cooldown_secs`. The next call then probes the server,
+/// closing the circuit on success and reopening it on failure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpCircuitConfig {
+    /// Consecutive failures that open the circuit. 0 disables the breaker.
+    #[serde(default = "default_mcp_failure_threshold", alias = "failureThreshold")]
+    pub failure_threshold: u32,
+
+    /// Seconds an open circuit fails calls before letting one through.
+    #[serde(default = "default_mcp_cooldown", alias = "cooldownSecs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_mcp_failure_threshold() -> u32 {
+    5
+}
+
+fn default_mcp_cooldown() -> u64 {
+    30
+}
+
+impl Default for McpCircuitConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_mcp_failure_threshold(),
+            cooldown_secs: default_mcp_cooldown(),
+        }
+    }
+}
+
+/// Allow and deny lists over an MCP server's tool names (before prefixing).
+///
+/// Entries may be glob patterns (`*`, `?`). An empty `allow` list allows
+/// every tool; `deny` wins over `allow`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpExposeConfig {
+    /// Tools to register. Empty registers all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    /// Tools never registered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// What an MCP server may do with sampling (`sampling/createMessage`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpSamplingConfig {
+    /// Who approves each request. The capability is only advertised when
+    /// this is not `deny`.
+    #[serde(default)]
+    pub approval: SamplingApproval,
+
+    /// Tokens (prompt and completion) the server may use per process
+    /// lifetime; requests past it are refused. 0 means no limit.
+    #[serde(default = "default_sampling_token_budget", alias = "tokenBudget")]
+    pub token_budget: u64,
+
+    /// Largest `maxTokens` granted to one request; larger asks are capped.
+    #[serde(default = "default_sampling_max_tokens", alias = "maxTokens")]
+    pub max_tokens: u32,
+
+    /// Models the server's hints may select, e.g. `"anthropic/claude-sonnet-4"`.
+    /// A hint picks the first entry containing it; with no match (or no
+    /// entries) the provider router chooses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+
+    /// Seconds to wait for the user's answer under `ask`; no answer denies.
+    #[serde(default = "default_sampling_ask_timeout", alias = "askTimeoutSecs")]
+    pub ask_timeout_secs: u64,
+}
+
+fn default_sampling_token_budget() -> u64 {
+    100_000
+}
+
+fn default_sampling_max_tokens() -> u32 {
+    4096
+}
+
+fn default_sampling_ask_timeout() -> u64 {
+    120
+}
+
+impl Default for McpSamplingConfig {
+    fn default() -> Self {
+        Self {
+            approval: SamplingApproval::default(),
+            token_budget: default_sampling_token_budget(),
+            max_tokens: default_sampling_max_tokens(),
+            models: Vec::new(),
+            ask_timeout_secs: default_sampling_ask_timeout(),
         }
     }
 }
 
+/// Approval policy for MCP sampling requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplingApproval {
+    /// Refuse every request.
+    #[default]
+    Deny,
+    /// Run every request within the budget.
+    Auto,
+    /// Ask the user on the channel whose tool call led to the request;
+    /// requests with no originating conversation are refused.
+    Ask,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -524,6 +2428,8 @@ mod tests {
         assert_eq!(cfg.agents.defaults.temperature, 0.7);
         assert_eq!(cfg.agents.defaults.max_tool_iterations, 20);
         assert_eq!(cfg.agents.defaults.memory_window, 50);
+        assert_eq!(cfg.agents.defaults.max_parallel_tools, 4);
+        assert!(cfg.agents.defaults.top_p.is_none());
 
         // Channels
         assert!(cfg.channels.telegram.enabled);
@@ -594,6 +2500,9 @@ mod tests {
 
         // Tool defaults
         assert_eq!(cfg.tools.exec_tool.timeout, 60);
+        assert!(!cfg.tools.exec_tool.persistent_session);
+        assert_eq!(cfg.tools.exec_tool.session_idle_secs, 600);
+        assert_eq!(cfg.tools.exec_tool.max_background_jobs, 4);
         assert_eq!(cfg.tools.web.search.max_results, 5);
     }
 
@@ -705,6 +2614,8 @@ mod tests {
         let cfg = GatewayConfig::default();
         assert_eq!(cfg.heartbeat_interval_minutes, 0);
         assert_eq!(cfg.heartbeat_prompt, "heartbeat");
+        assert_eq!(cfg.heartbeat_backoff_after, 3);
+        assert_eq!(cfg.heartbeat_max_interval_minutes, 1440);
     }
 
     #[test]
@@ -713,11 +2624,15 @@ mod tests {
             "host": "0.0.0.0",
             "port": 8080,
             "heartbeatIntervalMinutes": 15,
-            "heartbeatPrompt": "status check"
+            "heartbeatPrompt": "status check",
+            "heartbeatBackoffAfter": 0,
+            "heartbeatMaxIntervalMinutes": 240
         }"#;
         let cfg: GatewayConfig = serde_json::from_str(json).unwrap();
         assert_eq!(cfg.heartbeat_interval_minutes, 15);
         assert_eq!(cfg.heartbeat_prompt, "status check");
+        assert_eq!(cfg.heartbeat_backoff_after, 0);
+        assert_eq!(cfg.heartbeat_max_interval_minutes, 240);
     }
 
     #[test]
@@ -726,6 +2641,20 @@ mod tests {
         let cfg: GatewayConfig = serde_json::from_str(json).unwrap();
         assert_eq!(cfg.heartbeat_interval_minutes, 0);
         assert_eq!(cfg.heartbeat_prompt, "heartbeat");
+        assert_eq!(cfg.heartbeat_delivery(), ProactiveDelivery::default());
+    }
+
+    #[test]
+    fn gateway_heartbeat_delivery_from_json() {
+        let json = r#"{
+            "heartbeatChannel": "telegram",
+            "heartbeatChatId": "12345",
+            "quietHours": {"start": "22:00", "end": "07:00", "timezone": "+02:00"}
+        }"#;
+        let cfg: GatewayConfig = serde_json::from_str(json).unwrap();
+        let delivery = cfg.heartbeat_delivery();
+        assert_eq!(delivery.target.unwrap().chat_id, "12345");
+        assert_eq!(delivery.quiet_hours.unwrap().timezone, "+02:00");
     }
 
     #[test]
@@ -740,6 +2669,7 @@ mod tests {
             },
             url: String::new(),
             internal_only: false,
+            ..Default::default()
         };
         let json = serde_json::to_string(&cfg).unwrap();
         let restored: MCPServerConfig = serde_json::from_str(&json).unwrap();
@@ -747,6 +2677,306 @@ mod tests {
         assert_eq!(restored.args.len(), 2);
         assert_eq!(restored.env["API_KEY"], "secret");
         assert!(!restored.internal_only);
+        assert_eq!(restored.max_tools, 100);
+    }
+
+    #[test]
+    fn mcp_serve_config_defaults_to_loopback() {
+        let cfg = Config::default();
+        assert_eq!(cfg.mcp_server.host, "127.0.0.1");
+        assert_eq!(cfg.mcp_server.port, 18791);
+        assert!(cfg.mcp_server.auth_token.is_empty());
+
+        let json = r#"{
+            "mcpServer": {
+                "host": "0.0.0.0",
+                "authToken": "t0ken",
+                "allowedOrigins": ["https://app.example.com"]
+            }
+        }"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.mcp_server.host, "0.0.0.0");
+        assert_eq!(cfg.mcp_server.port, 18791);
+        assert_eq!(cfg.mcp_server.auth_token.expose(), "t0ken");
+        assert_eq!(cfg.mcp_server.allowed_origins, ["https://app.example.com"]);
+    }
+
+    #[test]
+    fn backup_config_defaults_and_camel_case() {
+        let cfg = Config::default();
+        assert!(!cfg.backup.enabled);
+        assert_eq!(cfg.backup.interval_hours, 24);
+        assert_eq!(cfg.backup.keep, 7);
+        assert!(cfg.backup.s3.is_none());
+
+        let json = r#"{
+            "backup": {
+                "enabled": true,
+                "intervalHours": 6,
+                "keep": 3,
+                "exclude": ["*.log"],
+                "s3": {
+                    "endpoint": "https://minio.local:9000",
+                    "bucket": "backups",
+                    "accessKeyId": "AKID",
+                    "secretAccessKeyEnv": "BACKUP_SECRET"
+                }
+            }
+        }"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.backup.interval_hours, 6);
+        assert_eq!(cfg.backup.keep, 3);
+        assert_eq!(cfg.backup.exclude, ["*.log"]);
+        let s3 = cfg.backup.s3.unwrap();
+        assert_eq!(s3.region, "us-east-1");
+        assert_eq!(s3.access_key_id, "AKID");
+        assert!(s3.secret_access_key.is_empty());
+        assert_eq!(s3.secret_access_key_env.as_deref(), Some("BACKUP_SECRET"));
+    }
+
+    #[test]
+    fn http_tool_config_camel_case() {
+        let json = r#"{
+            "secrets": { "github": "ghp_abc", "weather": "w-123" },
+            "timeoutSecs": 10,
+            "maxResponseBytes": 2048
+        }"#;
+        let cfg: HttpToolConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.secrets["github"].expose(), "ghp_abc");
+        assert_eq!(cfg.timeout_secs, 10);
+        assert_eq!(cfg.max_response_bytes, 2048);
+
+        let default = HttpToolConfig::default();
+        assert!(default.secrets.is_empty());
+        assert_eq!(default.timeout_secs, 30);
+        assert_eq!(default.max_response_bytes, 1024 * 1024);
+    }
+
+    #[test]
+    fn sqlite_tool_config_camel_case() {
+        let json =
+            r#"{ "allowWrites": true, "maxDbBytes": 4096, "maxRows": 10, "maxResultBytes": 512 }"#;
+        let cfg: SqliteToolConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.allow_writes);
+        assert_eq!(cfg.max_db_bytes, 4096);
+        assert_eq!(cfg.max_rows, 10);
+        assert_eq!(cfg.max_result_bytes, 512);
+
+        let default = SqliteToolConfig::default();
+        assert!(!default.allow_writes);
+        assert_eq!(default.max_rows, 500);
+    }
+
+    #[test]
+    fn desktop_tool_config_camel_case() {
+        let json = r#"{ "clipboard": true, "maxClipboardBytes": 1024 }"#;
+        let cfg: DesktopToolConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.clipboard);
+        assert!(!cfg.notifications);
+        assert_eq!(cfg.max_clipboard_bytes, 1024);
+
+        let default = ToolsConfig::default().desktop;
+        assert!(!default.clipboard);
+        assert!(!default.notifications);
+        assert_eq!(default.max_clipboard_bytes, 64 * 1024);
+    }
+
+    #[test]
+    fn schedule_task_tool_config_camel_case() {
+        let json = r#"{ "scheduleTask": { "maxPending": 3 } }"#;
+        let cfg: ToolsConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.schedule_task.enabled);
+        assert_eq!(cfg.schedule_task.max_pending, 3);
+        assert_eq!(ToolsConfig::default().schedule_task.max_pending, 10);
+    }
+
+    #[test]
+    fn tool_results_config_camel_case() {
+        let json = r#"{ "results": {
+            "maxBytes": 16384,
+            "strategy": "structured",
+            "tools": { "exec": "truncate", "mcp_*": "summarize" },
+            "summaryModel": "openai/gpt-4o-mini",
+            "maxSummaryTokens": 256,
+            "keepSpillFiles": 0
+        } }"#;
+        let cfg: ToolsConfig = serde_json::from_str(json).unwrap();
+        let results = &cfg.results;
+        assert_eq!(results.max_bytes, 16384);
+        assert_eq!(results.strategy, ResultStrategy::Structured);
+        assert_eq!(results.tools["exec"], ResultStrategy::Truncate);
+        assert_eq!(results.tools["mcp_*"], ResultStrategy::Summarize);
+        assert_eq!(results.summary_model.as_deref(), Some("openai/gpt-4o-mini"));
+        assert_eq!(results.max_summary_tokens, 256);
+        assert_eq!(results.keep_spill_files, 0);
+
+        let default = ToolsConfig::default().results;
+        assert_eq!(default.max_bytes, 64 * 1024);
+        assert_eq!(default.strategy, ResultStrategy::Truncate);
+        assert!(default.tools.is_empty());
+        assert!(default.summary_model.is_none());
+        assert_eq!(default.max_summary_tokens, 512);
+        assert_eq!(default.keep_spill_files, 100);
+    }
+
+    #[test]
+    fn interactive_config_camel_case() {
+        let json = r#"{ "interactive": { "forwardUnknownCommands": true } }"#;
+        let cfg: AgentsConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.interactive.forward_unknown_commands);
+        assert!(!AgentsConfig::default().interactive.forward_unknown_commands);
+    }
+
+    #[test]
+    fn log_sink_config_camel_case() {
+        let json = r#"{ "logs": { "enabled": true, "level": "debug",
+            "maxFileBytes": 1024, "maxFiles": 2 } }"#;
+        let cfg: GatewayConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.logs.enabled);
+        assert_eq!(cfg.logs.level, "debug");
+        assert_eq!(cfg.logs.max_file_bytes, 1024);
+        assert_eq!(cfg.logs.max_files, 2);
+
+        let default = GatewayConfig::default().logs;
+        assert!(!default.enabled);
+        assert_eq!(default.level, "info");
+        assert_eq!(default.max_files, 5);
+    }
+
+    #[test]
+    fn secret_refs_camel_case() {
+        let json = r#"{ "secrets": {
+            "github_token": {
+                "description": "GitHub token for the release script",
+                "valueEnv": "GITHUB_TOKEN",
+                "tools": ["write_file", "mcp_github__*"]
+            },
+            "db": { "value": "hunter2" }
+        } }"#;
+        let cfg: ToolsConfig = serde_json::from_str(json).unwrap();
+        let github = &cfg.secrets["github_token"];
+        assert_eq!(github.description, "GitHub token for the release script");
+        assert!(github.value.is_empty());
+        assert_eq!(github.value_env.as_deref(), Some("GITHUB_TOKEN"));
+        assert_eq!(github.tools, ["write_file", "mcp_github__*"]);
+        assert_eq!(cfg.secrets["db"].value.expose(), "hunter2");
+        assert!(cfg.secrets["db"].tools.is_empty());
+        assert!(ToolsConfig::default().secrets.is_empty());
+    }
+
+    #[test]
+    fn http_secrets_merge_into_tools_secrets() {
+        let json = r#"{
+            "secrets": { "weather": { "value": "w-new", "tools": ["http_request", "exec_shell"] } },
+            "http": { "secrets": { "weather": "w-old", "github": "ghp_abc" } }
+        }"#;
+        let cfg: ToolsConfig = serde_json::from_str(json).unwrap();
+        let all = cfg.all_secrets();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["weather"].value.expose(), "w-new");
+        assert_eq!(all["weather"].tools, ["http_request", "exec_shell"]);
+        assert_eq!(all["github"].value.expose(), "ghp_abc");
+        assert_eq!(all["github"].tools, ["http_request"]);
+        assert!(ToolsConfig::default().all_secrets().is_empty());
+    }
+
+    #[test]
+    fn web_search_provider_config() {
+        let json = r#"{
+            "provider": "searxng",
+            "fallbackChain": ["duckduckgo", "google"],
+            "google": { "apiKey": "g-key", "cx": "engine-1" },
+            "searxng": { "baseUrl": "https://searx.example.org" }
+        }"#;
+        let cfg: WebSearchConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.provider, SearchProviderKind::Searxng);
+        assert_eq!(
+            cfg.fallback_chain,
+            [SearchProviderKind::DuckDuckGo, SearchProviderKind::Google]
+        );
+        assert_eq!(cfg.google.api_key.expose(), "g-key");
+        assert_eq!(cfg.google.cx, "engine-1");
+        assert_eq!(cfg.searxng.base_url, "https://searx.example.org");
+        assert!(cfg.duckduckgo.base_url.is_empty());
+        assert_eq!(cfg.max_results, 5);
+
+        let default = WebSearchConfig::default();
+        assert_eq!(default.provider, SearchProviderKind::Brave);
+        assert!(default.fallback_chain.is_empty());
+    }
+
+    #[test]
+    fn exec_tool_config_camel_case() {
+        let json = r#"{"persistentSession": true, "sessionIdleSecs": 30, "maxBackgroundJobs": 1}"#;
+        let cfg: ExecToolConfig = serde_json::from_str(json).unwrap();
+        assert!(cfg.persistent_session);
+        assert_eq!(cfg.session_idle_secs, 30);
+        assert_eq!(cfg.max_background_jobs, 1);
+        assert_eq!(cfg.timeout, 60);
+    }
+
+    #[test]
+    fn mcp_server_config_exposure() {
+        let json = r#"{
+            "command": "npx",
+            "expose": { "allow": ["issue_*"], "deny": ["issue_delete"] },
+            "prefix": "gh__",
+            "descriptions": { "issue_create": "Open an issue." },
+            "maxTools": 20
+        }"#;
+        let cfg: MCPServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.expose.allow, vec!["issue_*"]);
+        assert_eq!(cfg.expose.deny, vec!["issue_delete"]);
+        assert_eq!(cfg.prefix.as_deref(), Some("gh__"));
+        assert_eq!(cfg.descriptions["issue_create"], "Open an issue.");
+        assert_eq!(cfg.max_tools, 20);
+        assert_eq!(cfg.sampling.approval, SamplingApproval::Deny);
+    }
+
+    #[test]
+    fn mcp_server_config_sampling() {
+        let json = r#"{
+            "command": "npx",
+            "sampling": {
+                "approval": "ask",
+                "tokenBudget": 5000,
+                "maxTokens": 512,
+                "models": ["openai/gpt-4o-mini"],
+                "askTimeoutSecs": 30
+            }
+        }"#;
+        let cfg: MCPServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.sampling.approval, SamplingApproval::Ask);
+        assert_eq!(cfg.sampling.token_budget, 5000);
+        assert_eq!(cfg.sampling.max_tokens, 512);
+        assert_eq!(cfg.sampling.models, ["openai/gpt-4o-mini"]);
+        assert_eq!(cfg.sampling.ask_timeout_secs, 30);
+
+        let defaults = McpSamplingConfig::default();
+        assert_eq!(defaults.token_budget, 100_000);
+        assert_eq!(defaults.max_tokens, 4096);
+        assert_eq!(defaults.ask_timeout_secs, 120);
+    }
+
+    #[test]
+    fn mcp_server_config_call_limits() {
+        let json = r#"{
+            "command": "npx",
+            "callTimeoutSecs": 10,
+            "maxConcurrentCalls": 2,
+            "circuit": { "failureThreshold": 3, "cooldownSecs": 5 }
+        }"#;
+        let cfg: MCPServerConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.call_timeout_secs, 10);
+        assert_eq!(cfg.max_concurrent_calls, 2);
+        assert_eq!(cfg.circuit.failure_threshold, 3);
+        assert_eq!(cfg.circuit.cooldown_secs, 5);
+
+        let defaults: MCPServerConfig = serde_json::from_str(r#"{"command": "npx"}"#).unwrap();
+        assert_eq!(defaults.call_timeout_secs, 30);
+        assert_eq!(defaults.max_concurrent_calls, 8);
+        assert_eq!(defaults.circuit.failure_threshold, 5);
+        assert_eq!(defaults.circuit.cooldown_secs, 30);
     }
 
     #[test]
@@ -838,6 +3068,140 @@ mod tests {
         assert!(!cfg.api_enabled);
     }
 
+    #[test]
+    fn providers_resolve_api_keys_from_lookup() {
+        let mut providers = ProvidersConfig::default();
+        providers.openai.api_key = SecretString::new("explicit");
+        providers.resolve_api_keys(|name| match name {
+            "OPENAI_API_KEY" => Some("env-openai".into()),
+            "ANTHROPIC_API_KEY" => Some("env-anthropic".into()),
+            _ => None,
+        });
+
+        assert_eq!(providers.openai.api_key.expose(), "explicit");
+        assert_eq!(providers.anthropic.api_key.expose(), "env-anthropic");
+        assert!(providers.groq.api_key.is_empty());
+    }
+
+    #[test]
+    fn http_config_defaults_and_aliases() {
+        let cfg = Config::default();
+        assert_eq!(cfg.http.connect_timeout_secs, 10);
+        assert_eq!(cfg.http.request_timeout_secs, 60);
+        assert!(cfg.http.proxy.is_none());
+
+        let json = r#"{"http": {"requestTimeoutSecs": 5, "proxy": "http://proxy:3128",
+            "caCertPath": "/etc/ssl/corp.pem", "userAgent": "weft/1"}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.http.request_timeout_secs, 5);
+        assert_eq!(cfg.http.connect_timeout_secs, 10);
+        assert_eq!(cfg.http.proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(cfg.http.ca_cert_path.as_deref(), Some("/etc/ssl/corp.pem"));
+        assert_eq!(cfg.http.user_agent.as_deref(), Some("weft/1"));
+        assert_eq!(cfg.http.retry, HttpRetryConfig::default());
+
+        let json = r#"{"http": {"retry": {"maxAttempts": 5, "initialBackoffMs": 100,
+            "retryStatuses": [503], "retryNonIdempotent": true}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.http.retry.max_attempts, 5);
+        assert_eq!(cfg.http.retry.initial_backoff_ms, 100);
+        assert_eq!(cfg.http.retry.max_backoff_ms, 10_000);
+        assert_eq!(cfg.http.retry.retry_statuses, vec![503]);
+        assert!(cfg.http.retry.retry_non_idempotent);
+    }
+
+    #[test]
+    fn hooks_config_defaults_and_aliases() {
+        let cfg = Config::default();
+        assert_eq!(cfg.hooks.timeout_ms, 5_000);
+        assert!(!cfg.hooks.command_blocker.enabled);
+        assert_eq!(cfg.hooks.command_blocker.tools, vec!["exec_shell"]);
+        assert!(!cfg.hooks.prompt_logger.enabled);
+
+        let json = r#"{"hooks": {"timeoutMs": 250,
+            "commandBlocker": {"enabled": true, "patterns": ["/prod"]},
+            "promptLogger": {"enabled": true, "path": "/var/log/prompts.jsonl"}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.hooks.timeout_ms, 250);
+        assert_eq!(cfg.hooks.command_blocker.patterns, vec!["/prod"]);
+        assert_eq!(cfg.hooks.command_blocker.tools, vec!["exec_shell"]);
+        assert_eq!(
+            cfg.hooks.prompt_logger.path.as_deref(),
+            Some("/var/log/prompts.jsonl")
+        );
+    }
+
+    #[test]
+    fn pipeline_stage_order_parses_names_and_policies() {
+        let cfg = Config::default();
+        assert!(cfg.pipeline.stages.is_empty());
+        assert_eq!(cfg.pipeline.on_stage_error, StageErrorPolicy::Abort);
+        assert_eq!(cfg.pipeline.redactor.replacement, "[REDACTED]");
+
+        let json = r#"{"pipeline": {"onStageError": "skip",
+            "stages": ["redactor", "classifier", "router", "assembler", "transport",
+                {"name": "translator", "onError": "abort"}, "scorer", "learner"],
+            "agentStages": {"researcher": []},
+            "redactor": {"patterns": ["\\d{3}-\\d{2}-\\d{4}"]}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        let pipeline = cfg.pipeline;
+        assert_eq!(pipeline.on_stage_error, StageErrorPolicy::Skip);
+        assert_eq!(pipeline.stages.len(), 8);
+        assert_eq!(pipeline.stages[0].name(), "redactor");
+        assert_eq!(pipeline.stages[0].on_error(), None);
+        assert_eq!(pipeline.stages[5].name(), "translator");
+        assert_eq!(pipeline.stages[5].on_error(), Some(StageErrorPolicy::Abort));
+        assert!(pipeline.agent_stages["researcher"].is_empty());
+        assert_eq!(pipeline.redactor.patterns.len(), 1);
+    }
+
+    #[test]
+    fn budget_config_is_unlimited_by_default() {
+        assert_eq!(Config::default().agents.budget, BudgetConfig::default());
+
+        let json = r#"{"agents": {"budget": {"maxTokensPerTurn": 50000,
+            "maxTurnSecs": 300, "maxToolCallsPerTurn": 40, "maxCostPerDayUsd": 2.5}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        let budget = cfg.agents.budget;
+        assert_eq!(budget.max_tokens_per_turn, Some(50_000));
+        assert_eq!(budget.max_turn_secs, Some(300));
+        assert_eq!(budget.max_tool_calls_per_turn, Some(40));
+        assert_eq!(budget.max_cost_per_day_usd, Some(2.5));
+    }
+
+    #[test]
+    fn session_retention_is_off_by_default() {
+        let sessions = Config::default().agents.sessions;
+        assert!(!sessions.retention_enabled());
+        assert_eq!(sessions.retention_interval_minutes, 60);
+
+        let json = r#"{"agents": {"sessions": {"maxIdleDays": 30, "maxSessions": 500,
+            "retentionIntervalMinutes": 15}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        let sessions = cfg.agents.sessions;
+        assert!(sessions.retention_enabled());
+        assert_eq!(sessions.max_idle_days, 30);
+        assert_eq!(sessions.max_sessions, 500);
+        assert_eq!(sessions.retention_interval_minutes, 15);
+    }
+
+    #[test]
+    fn memory_backend_defaults_to_markdown() {
+        let cfg = Config::default();
+        assert_eq!(cfg.agents.memory.backend, MemoryBackendKind::Markdown);
+
+        let json = r#"{"agents": {"memory": {"backend": "sqlite"}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.agents.memory.backend, MemoryBackendKind::Sqlite);
+        assert_eq!(cfg.agents.memory.duplicate_threshold, 0.85);
+        assert_eq!(cfg.agents.memory.decay_days, 30);
+
+        let json = r#"{"agents": {"memory": {"duplicateThreshold": 0.9, "decayDays": 7}}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.agents.memory.duplicate_threshold, 0.9);
+        assert_eq!(cfg.agents.memory.decay_days, 7);
+    }
+
     #[test]
     fn provider_browser_fields_defaults() {
         let cfg: ProviderConfig = serde_json::from_str("{}").unwrap();
@@ -845,6 +3209,47 @@ mod tests {
         assert!(cfg.cors_proxy.is_none());
     }
 
+    #[test]
+    fn provider_rate_limit_parses_camel_case() {
+        let json = r#"{"rateLimit": {"requestsPerMinute": 50, "maxConcurrent": 4}}"#;
+        let cfg: ProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.rate_limit.requests_per_minute, Some(50));
+        assert_eq!(cfg.rate_limit.max_concurrent, Some(4));
+        assert_eq!(cfg.rate_limit.tokens_per_minute, None);
+        assert_eq!(cfg.rate_limit.max_queue_wait_secs, 30);
+        assert!(!cfg.rate_limit.is_unlimited());
+        assert!(ProviderConfig::default().rate_limit.is_unlimited());
+    }
+
+    #[test]
+    fn azure_provider_parses_deployments() {
+        let json = r#"{"providers": {"azure": {
+            "apiKey": "az-key",
+            "apiBase": "https://my-resource.openai.azure.com",
+            "deployments": {"gpt-4o": "prod-gpt4o"},
+            "apiVersion": "2024-10-21"
+        }}}"#;
+        let cfg: Config = serde_json::from_str(json).unwrap();
+        let azure = &cfg.providers.azure;
+        assert_eq!(azure.api_key.expose(), "az-key");
+        assert_eq!(azure.deployments["gpt-4o"], "prod-gpt4o");
+        assert_eq!(azure.api_version.as_deref(), Some("2024-10-21"));
+
+        let other = serde_json::to_value(&cfg.providers.openai).unwrap();
+        assert!(other.get("deployments").is_none());
+        assert!(other.get("api_version").is_none());
+    }
+
+    #[test]
+    fn provider_timeouts_parse_camel_case() {
+        let json = r#"{"timeoutSecs": 90, "connectTimeoutSecs": 5, "readTimeoutSecs": 20}"#;
+        let cfg: ProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.timeout_secs, Some(90));
+        assert_eq!(cfg.connect_timeout_secs, Some(5));
+        assert_eq!(cfg.read_timeout_secs, Some(20));
+        assert!(ProviderConfig::default().timeout_secs.is_none());
+    }
+
     #[test]
     fn provider_base_url_alias() {
         let json = r#"{"baseUrl": "https://example.com"}"#;
@@ -2,23 +2,31 @@
 //!
 //! Defines the data model for scheduled jobs: [`CronJob`], its
 //! [`CronSchedule`], [`CronPayload`], and runtime [`CronJobState`].
-//! The [`CronStore`] is the top-level container persisted to disk.
+//! The [`CronStore`] is the top-level container persisted to disk, and
+//! each execution is recorded as a [`CronRun`].
+//!
+//! A job fires on a cron expression, every fixed interval (optionally
+//! until a deadline), or once at a given time; see [`ScheduleKind`].
 //!
 //! All timestamps use `DateTime<Utc>` for type safety. For backward
 //! compatibility, the serde layer accepts both RFC 3339 strings and
 //! millisecond-since-epoch integers via custom deserializers.
 
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::delivery::{DeliveryTarget, ProactiveDelivery, QuietHours};
+
 /// How a cron job is scheduled.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ScheduleKind {
-    /// Fire once at a specific timestamp.
+    /// Fire once at a specific timestamp. Also accepted as `"once"`.
+    #[serde(alias = "once")]
     At,
-    /// Fire repeatedly at a fixed interval.
+    /// Fire repeatedly at a fixed interval. Also accepted as `"interval"`.
+    #[serde(alias = "interval")]
     Every,
     /// Fire according to a cron expression.
     Cron,
@@ -31,20 +39,58 @@ pub struct CronSchedule {
     pub kind: ScheduleKind,
 
     /// For [`ScheduleKind::At`]: timestamp in milliseconds since epoch.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// An RFC 3339 string is also accepted, under `at_ms` or `at`.
+    #[serde(
+        default,
+        alias = "at",
+        deserialize_with = "deserialize_optional_ms_or_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub at_ms: Option<i64>,
 
     /// For [`ScheduleKind::Every`]: interval in milliseconds.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub every_ms: Option<i64>,
 
+    /// For [`ScheduleKind::Every`]: no runs are scheduled after this time.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_datetime_or_ms",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub until: Option<DateTime<Utc>>,
+
     /// For [`ScheduleKind::Cron`]: cron expression (e.g. `"0 9 * * *"`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expr: Option<String>,
 
-    /// Timezone for cron expressions (e.g. `"UTC"`, `"Asia/Shanghai"`).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// IANA timezone the cron expression is evaluated in (e.g. `"UTC"`,
+    /// `"America/New_York"`). UTC when unset.
+    #[serde(default, alias = "timezone", skip_serializing_if = "Option::is_none")]
     pub tz: Option<String>,
+
+    /// Upper bound of a random delay, in seconds, added to each fire time
+    /// so that jobs sharing a schedule do not all fire at once.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub jitter_secs: u64,
+
+    /// What to do on startup about runs missed while the service was down.
+    #[serde(default)]
+    pub catch_up: CatchUp,
+}
+
+/// Policy for runs that fell due while the scheduler was not running.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUp {
+    /// Drop missed runs and wait for the next scheduled time.
+    #[default]
+    Skip,
+    /// Fire once if at least one run was missed.
+    RunOnce,
+    /// Fire once for every missed run.
+    RunAll,
 }
 
 impl Default for CronSchedule {
@@ -53,12 +99,66 @@ impl Default for CronSchedule {
             kind: ScheduleKind::Every,
             at_ms: None,
             every_ms: None,
+            until: None,
             expr: None,
             tz: None,
+            jitter_secs: 0,
+            catch_up: CatchUp::Skip,
+        }
+    }
+}
+
+impl CronSchedule {
+    /// Fire once at `at`.
+    ///
+    /// If the scheduler was down at that time, the run still happens when
+    /// it starts ([`CatchUp::RunOnce`]).
+    pub fn once(at: DateTime<Utc>) -> Self {
+        Self {
+            kind: ScheduleKind::At,
+            at_ms: Some(at.timestamp_millis()),
+            catch_up: CatchUp::RunOnce,
+            ..Default::default()
+        }
+    }
+
+    /// Fire every `every`, counted from when the job is scheduled, and not
+    /// after `until`.
+    pub fn interval(every: Duration, until: Option<DateTime<Utc>>) -> Self {
+        Self {
+            kind: ScheduleKind::Every,
+            every_ms: Some(every.num_milliseconds()),
+            until,
+            ..Default::default()
         }
     }
 }
 
+/// Parse an interval such as `"45m"`, `"2h"` or `"1h30m"`.
+///
+/// Units are `s`, `m`, `h` and `d`. Returns `None` for malformed or zero
+/// intervals.
+pub fn parse_interval(s: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits.parse().ok()?;
+        digits.clear();
+        total += match c {
+            's' => Duration::try_seconds(n)?,
+            'm' => Duration::try_minutes(n)?,
+            'h' => Duration::try_hours(n)?,
+            'd' => Duration::try_days(n)?,
+            _ => return None,
+        };
+    }
+    (digits.is_empty() && total > Duration::zero()).then_some(total)
+}
+
 /// What action to perform when a cron job fires.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -92,6 +192,66 @@ pub struct CronPayload {
     /// Target recipient (e.g. phone number, user ID).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub to: Option<String>,
+
+    /// Window during which the reply is held back. Falls back to
+    /// `gateway.quiet_hours` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// Where to report repeated failures of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<FailureAlert>,
+}
+
+/// Notification sent when a job keeps failing.
+///
+/// The first alert goes out after `after` consecutive failures; further
+/// alerts back off exponentially, at `2 * after`, `4 * after`, and so on,
+/// so a broken job does not flood the conversation. A successful run
+/// resets the count.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureAlert {
+    /// Channel to send the alert on.
+    pub channel: String,
+
+    /// Conversation (chat ID) on `channel`.
+    pub to: String,
+
+    /// Consecutive failures before the first alert.
+    #[serde(default = "default_failures_before_alert")]
+    pub after: u32,
+}
+
+impl FailureAlert {
+    /// Whether the `consecutive`-th failure in a row should be reported.
+    pub fn is_due(&self, consecutive: u32) -> bool {
+        let after = self.after.max(1);
+        consecutive >= after
+            && consecutive.is_multiple_of(after)
+            && (consecutive / after).is_power_of_two()
+    }
+}
+
+fn default_failures_before_alert() -> u32 {
+    3
+}
+
+impl CronPayload {
+    /// Delivery rules for the job's reply: sent to `channel`/`to` when
+    /// `deliver` is set, held back during `quiet_hours` (or `default_quiet`).
+    pub fn delivery(&self, default_quiet: Option<&QuietHours>) -> ProactiveDelivery {
+        let target = match (self.deliver, &self.channel, &self.to) {
+            (true, Some(channel), Some(to)) => Some(DeliveryTarget {
+                channel: channel.clone(),
+                chat_id: to.clone(),
+            }),
+            _ => None,
+        };
+        ProactiveDelivery {
+            target,
+            quiet_hours: self.quiet_hours.clone().or_else(|| default_quiet.cloned()),
+        }
+    }
 }
 
 fn default_payload_kind() -> PayloadKind {
@@ -106,6 +266,8 @@ impl Default for CronPayload {
             deliver: false,
             channel: None,
             to: None,
+            quiet_hours: None,
+            on_failure: None,
         }
     }
 }
@@ -149,6 +311,39 @@ pub struct CronJobState {
     /// Error message from the last failed run.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
+
+    /// Failed runs since the last successful one.
+    #[serde(default, skip_serializing_if = "is_zero_u32")]
+    pub consecutive_failures: u32,
+}
+
+/// One execution of a cron job, as kept in its run history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CronRun {
+    /// When the agent turn started (UTC).
+    pub started_at: DateTime<Utc>,
+
+    /// When it finished (UTC).
+    pub finished_at: DateTime<Utc>,
+
+    /// Whether the run succeeded.
+    pub status: JobStatus,
+
+    /// The reply, possibly truncated.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub output: String,
+
+    /// Why the run failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Prompt tokens used by the run.
+    #[serde(default)]
+    pub input_tokens: u64,
+
+    /// Completion tokens used by the run.
+    #[serde(default)]
+    pub output_tokens: u64,
 }
 
 /// A scheduled job.
@@ -187,6 +382,11 @@ pub struct CronJob {
     /// If true, delete the job after its next successful run.
     #[serde(default)]
     pub delete_after_run: bool,
+
+    /// Agent that created the job with the `schedule_task` tool; `None`
+    /// for jobs added by the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
 }
 
 /// Returns the Unix epoch as a default `DateTime<Utc>` (for `#[serde(default)]`).
@@ -247,10 +447,27 @@ where
     }
 }
 
+/// Deserialize an optional millisecond timestamp from either an integer or
+/// an RFC 3339 string.
+fn deserialize_optional_ms_or_datetime<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(deserialize_optional_datetime_or_ms(deserializer)?.map(|dt| dt.timestamp_millis()))
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+fn is_zero_u32(n: &u32) -> bool {
+    *n == 0
+}
+
 /// Persistent store for cron jobs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronStore {
@@ -314,8 +531,11 @@ mod tests {
                 kind: ScheduleKind::Cron,
                 at_ms: None,
                 every_ms: None,
+                until: None,
                 expr: Some("0 9 * * *".into()),
                 tz: Some("UTC".into()),
+                jitter_secs: 0,
+                catch_up: CatchUp::Skip,
             },
             payload: CronPayload {
                 kind: PayloadKind::AgentTurn,
@@ -323,11 +543,14 @@ mod tests {
                 deliver: true,
                 channel: Some("slack".into()),
                 to: Some("C123".into()),
+                quiet_hours: None,
+                on_failure: None,
             },
             state: CronJobState::default(),
             created_at: now,
             updated_at: now,
             delete_after_run: false,
+            created_by: None,
         };
         let json = serde_json::to_string(&job).unwrap();
         let restored: CronJob = serde_json::from_str(&json).unwrap();
@@ -337,6 +560,32 @@ mod tests {
         assert_eq!(restored.payload.channel.as_deref(), Some("slack"));
     }
 
+    #[test]
+    fn payload_delivery_targets_channel_only_when_delivering() {
+        let night = QuietHours::parse("22:00-07:00", "UTC").unwrap();
+        let mut payload = CronPayload {
+            deliver: true,
+            channel: Some("telegram".into()),
+            to: Some("42".into()),
+            ..Default::default()
+        };
+        let delivery = payload.delivery(Some(&night));
+        assert_eq!(
+            delivery.target,
+            Some(DeliveryTarget {
+                channel: "telegram".into(),
+                chat_id: "42".into(),
+            })
+        );
+        assert_eq!(delivery.quiet_hours, Some(night.clone()));
+
+        payload.deliver = false;
+        payload.quiet_hours = Some(QuietHours::parse("23:00-06:00", "UTC").unwrap());
+        let delivery = payload.delivery(Some(&night));
+        assert!(delivery.target.is_none());
+        assert_eq!(delivery.quiet_hours.unwrap().start, "23:00");
+    }
+
     #[test]
     fn cron_store_serde_roundtrip() {
         let store = CronStore {
@@ -351,6 +600,7 @@ mod tests {
                 created_at: DateTime::UNIX_EPOCH,
                 updated_at: DateTime::UNIX_EPOCH,
                 delete_after_run: true,
+                created_by: None,
             }],
         };
         let json = serde_json::to_string(&store).unwrap();
@@ -375,6 +625,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn schedule_timezone_jitter_and_catch_up_serde() {
+        let json = r#"{
+            "kind": "cron",
+            "expr": "0 0 9 * * * *",
+            "timezone": "America/New_York",
+            "jitter_secs": 30,
+            "catch_up": "run_all"
+        }"#;
+        let schedule: CronSchedule = serde_json::from_str(json).unwrap();
+        assert_eq!(schedule.tz.as_deref(), Some("America/New_York"));
+        assert_eq!(schedule.jitter_secs, 30);
+        assert_eq!(schedule.catch_up, CatchUp::RunAll);
+
+        let value = serde_json::to_value(&schedule).unwrap();
+        assert_eq!(value["tz"], "America/New_York");
+        assert_eq!(value["catch_up"], "run_all");
+
+        let legacy: CronSchedule = serde_json::from_str(r#"{"kind": "every"}"#).unwrap();
+        assert_eq!(legacy.jitter_secs, 0);
+        assert_eq!(legacy.catch_up, CatchUp::Skip);
+        let value = serde_json::to_value(&legacy).unwrap();
+        assert!(value.get("jitter_secs").is_none());
+    }
+
+    #[test]
+    fn once_schedule_accepts_rfc3339_and_aliases() {
+        let json = r#"{"kind": "once", "at": "2026-03-01T09:45:00Z"}"#;
+        let schedule: CronSchedule = serde_json::from_str(json).unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 9, 45, 0).unwrap();
+        assert_eq!(schedule.kind, ScheduleKind::At);
+        assert_eq!(schedule.at_ms, Some(at.timestamp_millis()));
+
+        // Written back in the legacy field layout.
+        let value = serde_json::to_value(CronSchedule::once(at)).unwrap();
+        assert_eq!(value["kind"], "at");
+        assert_eq!(value["at_ms"], at.timestamp_millis());
+        assert_eq!(value["catch_up"], "run_once");
+
+        let legacy: CronSchedule =
+            serde_json::from_str(r#"{"kind": "at", "at_ms": 1772358300000}"#).unwrap();
+        assert_eq!(legacy.at_ms, Some(at.timestamp_millis()));
+    }
+
+    #[test]
+    fn interval_schedule_serde() {
+        let until = Utc.with_ymd_and_hms(2026, 3, 1, 18, 0, 0).unwrap();
+        let schedule = CronSchedule::interval(Duration::minutes(10), Some(until));
+        let json = serde_json::to_string(&schedule).unwrap();
+        assert!(json.contains(r#""until":"2026-03-01T18:00:00Z""#));
+        let restored: CronSchedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.kind, ScheduleKind::Every);
+        assert_eq!(restored.every_ms, Some(600_000));
+        assert_eq!(restored.until, Some(until));
+
+        let json = r#"{"kind": "interval", "every_ms": 60000}"#;
+        let schedule: CronSchedule = serde_json::from_str(json).unwrap();
+        assert_eq!(schedule.kind, ScheduleKind::Every);
+        assert!(schedule.until.is_none());
+        assert!(!serde_json::to_string(&schedule).unwrap().contains("until"));
+    }
+
+    #[test]
+    fn parse_interval_units() {
+        assert_eq!(parse_interval("45m"), Some(Duration::minutes(45)));
+        assert_eq!(parse_interval("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_interval("90s"), Some(Duration::seconds(90)));
+        assert_eq!(parse_interval(" 2d "), Some(Duration::days(2)));
+        for bad in ["", "10", "m", "0m", "10x", "1.5h", "-5m"] {
+            assert_eq!(parse_interval(bad), None, "{bad:?}");
+        }
+    }
+
     #[test]
     fn job_status_serde() {
         let statuses = [
@@ -406,11 +729,50 @@ mod tests {
             last_run_at: Some(now),
             last_status: Some(JobStatus::Error),
             last_error: Some("connection refused".into()),
+            consecutive_failures: 2,
         };
         let json = serde_json::to_string(&state).unwrap();
         let restored: CronJobState = serde_json::from_str(&json).unwrap();
         assert_eq!(restored.last_status, Some(JobStatus::Error));
         assert_eq!(restored.last_error.as_deref(), Some("connection refused"));
+        assert_eq!(restored.consecutive_failures, 2);
+    }
+
+    #[test]
+    fn failure_alerts_back_off_exponentially() {
+        let alert: FailureAlert =
+            serde_json::from_str(r#"{"channel": "telegram", "to": "42"}"#).unwrap();
+        assert_eq!(alert.after, 3);
+        let due: Vec<u32> = (0..=50).filter(|n| alert.is_due(*n)).collect();
+        assert_eq!(due, [3, 6, 12, 24, 48]);
+
+        let every = FailureAlert {
+            after: 1,
+            ..alert.clone()
+        };
+        let due: Vec<u32> = (0..=10).filter(|n| every.is_due(*n)).collect();
+        assert_eq!(due, [1, 2, 4, 8]);
+
+        let zero = FailureAlert { after: 0, ..alert };
+        assert!(zero.is_due(1));
+    }
+
+    #[test]
+    fn cron_run_serde_roundtrip() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+        let run = CronRun {
+            started_at: start,
+            finished_at: start + chrono::Duration::seconds(4),
+            status: JobStatus::Error,
+            output: String::new(),
+            error: Some("provider unavailable".into()),
+            input_tokens: 120,
+            output_tokens: 0,
+        };
+        let json = serde_json::to_value(&run).unwrap();
+        assert!(json.get("output").is_none());
+        let restored: CronRun = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, run);
     }
 
     #[test]
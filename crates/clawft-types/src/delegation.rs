@@ -2,10 +2,22 @@
 //!
 //! Controls how tasks are dispatched between local execution, Claude AI,
 //! and Claude Flow orchestration. Rules use regex patterns to match task
-//! descriptions and route them to the appropriate target.
+//! descriptions and route them to the appropriate target. Also limits how
+//! deeply local agents may hand subtasks to each other with `spawn_agent`.
+//!
+//! Tasks can also be handed to another clawft gateway over HTTP: `remotes`
+//! names the peers this instance may delegate to, and `serve` lets this
+//! gateway run tasks for its peers. The wire types of that exchange
+//! ([`RemoteTaskRequest`], [`RemoteTaskStatus`]) live here too, so both
+//! ends agree on them.
+
+use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::secret::SecretString;
+
 // ── DelegationConfig ────────────────────────────────────────────────────
 
 /// Root configuration for task delegation routing.
@@ -42,6 +54,20 @@ pub struct DelegationConfig {
     /// Tool names that should never be delegated.
     #[serde(default, alias = "excludedTools")]
     pub excluded_tools: Vec<String>,
+
+    /// How many levels of sub-agents `spawn_agent` may nest: 1 lets the
+    /// main agent spawn sub-agents that cannot spawn their own. 0 disables
+    /// the tool.
+    #[serde(default = "default_max_spawn_depth", alias = "maxSpawnDepth")]
+    pub max_spawn_depth: u32,
+
+    /// Peer gateways that `delegate_remote` may hand tasks to, by name.
+    #[serde(default)]
+    pub remotes: HashMap<String, RemoteDelegateConfig>,
+
+    /// Running tasks handed over by peer gateways.
+    #[serde(default)]
+    pub serve: DelegationServeConfig,
 }
 
 fn default_delegation_model() -> String {
@@ -56,6 +82,10 @@ fn default_max_tokens() -> u32 {
     4096
 }
 
+fn default_max_spawn_depth() -> u32 {
+    2
+}
+
 impl Default for DelegationConfig {
     fn default() -> Self {
         Self {
@@ -66,6 +96,9 @@ impl Default for DelegationConfig {
             claude_flow_enabled: false, // Stays false until Flow fully wired
             rules: Vec::new(),
             excluded_tools: Vec::new(),
+            max_spawn_depth: default_max_spawn_depth(),
+            remotes: HashMap::new(),
+            serve: DelegationServeConfig::default(),
         }
     }
 }
@@ -108,6 +141,179 @@ pub enum DelegationTarget {
     Auto,
 }
 
+// ── Remote delegation ───────────────────────────────────────────────────
+
+/// A peer gateway this instance may delegate tasks to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteDelegateConfig {
+    /// Base URL of the peer's API (e.g. `"http://buildbox.lan:18789"`).
+    pub url: String,
+
+    /// The peer's `delegation.serve` token.
+    #[serde(default)]
+    pub token: SecretString,
+
+    /// Environment variable holding the token, used when `token` is empty.
+    #[serde(default, alias = "tokenEnv")]
+    pub token_env: Option<String>,
+
+    /// How long to wait for a delegated task to finish, in seconds.
+    #[serde(default = "default_remote_timeout_secs", alias = "timeoutSecs")]
+    pub timeout_secs: u64,
+
+    /// Seconds between status checks while a task runs.
+    #[serde(default = "default_poll_interval_secs", alias = "pollIntervalSecs")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_remote_timeout_secs() -> u64 {
+    900
+}
+
+fn default_poll_interval_secs() -> u64 {
+    2
+}
+
+/// Running tasks for peer gateways at `/api/delegation/tasks`.
+///
+/// Delegated tasks arrive on the `delegation` channel, so this gateway's
+/// own routing rules and permissions decide which agent runs them and
+/// what it may do; nothing the peer sends widens that.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationServeConfig {
+    /// Accept delegated tasks (requires `gateway.api_enabled` and a token).
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Shared token peers must send as a Bearer token.
+    #[serde(default)]
+    pub token: SecretString,
+
+    /// Environment variable holding the token, used when `token` is empty.
+    #[serde(default, alias = "tokenEnv")]
+    pub token_env: Option<String>,
+
+    /// Agents a peer may ask for by name. Tasks that name no agent go
+    /// through the routing rules; tasks naming any other are refused.
+    #[serde(default)]
+    pub agents: Vec<String>,
+
+    /// Delegated tasks that may run at once; more are refused.
+    #[serde(default = "default_serve_max_concurrent", alias = "maxConcurrent")]
+    pub max_concurrent: u32,
+
+    /// Longest a delegated task may run, in seconds. Peers may ask for
+    /// less, not more.
+    #[serde(default = "default_serve_max_task_secs", alias = "maxTaskSecs")]
+    pub max_task_secs: u64,
+}
+
+fn default_serve_max_concurrent() -> u32 {
+    2
+}
+
+fn default_serve_max_task_secs() -> u64 {
+    600
+}
+
+impl Default for DelegationServeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            token: SecretString::default(),
+            token_env: None,
+            agents: Vec::new(),
+            max_concurrent: default_serve_max_concurrent(),
+            max_task_secs: default_serve_max_task_secs(),
+        }
+    }
+}
+
+/// A task handed to a peer gateway (`POST /api/delegation/tasks`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteTaskRequest {
+    /// The task, sent to the peer's agent as its user message.
+    pub prompt: String,
+
+    /// Agent to run the task, if the peer allows asking for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    /// Tool-loop iterations the task may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<u32>,
+
+    /// Prompt plus completion tokens the task may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    /// Seconds the task may run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Where a delegated task is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteTaskState {
+    /// Queued or running on the peer.
+    Running,
+    /// Finished with an answer.
+    Completed,
+    /// Failed, hit a limit, or never finished.
+    Failed,
+    /// Cancelled on the peer.
+    Cancelled,
+}
+
+impl RemoteTaskState {
+    /// Whether the task has stopped.
+    pub fn is_finished(self) -> bool {
+        self != Self::Running
+    }
+}
+
+/// LLM usage of a delegated task on one provider and model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteUsage {
+    /// Provider the peer called.
+    pub provider: String,
+    /// Model the peer called.
+    pub model: String,
+    /// Completed LLM calls.
+    pub requests: u64,
+    /// Prompt tokens.
+    pub input_tokens: u64,
+    /// Completion tokens.
+    pub output_tokens: u64,
+    /// Cost in US dollars, priced by the peer.
+    pub cost_usd: f64,
+}
+
+/// A delegated task as the peer reports it
+/// (`GET /api/delegation/tasks/{id}`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteTaskStatus {
+    /// Task id assigned by the peer.
+    pub id: String,
+    /// Where the task is.
+    pub state: RemoteTaskState,
+    /// The agent's final answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// Why the task failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// LLM usage so far, per provider and model.
+    #[serde(default)]
+    pub usage: Vec<RemoteUsage>,
+    /// When the peer accepted the task.
+    pub submitted_at: DateTime<Utc>,
+    /// When the task stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +328,7 @@ mod tests {
         assert!(!cfg.claude_flow_enabled);
         assert!(cfg.rules.is_empty());
         assert!(cfg.excluded_tools.is_empty());
+        assert_eq!(cfg.max_spawn_depth, 2);
     }
 
     #[test]
@@ -143,6 +350,9 @@ mod tests {
                 },
             ],
             excluded_tools: vec!["shell_exec".into()],
+            max_spawn_depth: 1,
+            remotes: HashMap::new(),
+            serve: DelegationServeConfig::default(),
         };
 
         let json = serde_json::to_string(&cfg).unwrap();
@@ -158,6 +368,7 @@ mod tests {
         assert_eq!(restored.rules[0].target, DelegationTarget::Flow);
         assert_eq!(restored.rules[1].target, DelegationTarget::Local);
         assert_eq!(restored.excluded_tools, vec!["shell_exec"]);
+        assert_eq!(restored.max_spawn_depth, 1);
     }
 
     #[test]
@@ -179,7 +390,8 @@ mod tests {
             "maxTurns": 3,
             "maxTokens": 1024,
             "claudeFlowEnabled": true,
-            "excludedTools": ["dangerous_tool"]
+            "excludedTools": ["dangerous_tool"],
+            "maxSpawnDepth": 0
         }"#;
         let cfg: DelegationConfig = serde_json::from_str(json).unwrap();
         assert_eq!(cfg.claude_model, "test-model");
@@ -187,6 +399,60 @@ mod tests {
         assert_eq!(cfg.max_tokens, 1024);
         assert!(cfg.claude_flow_enabled);
         assert_eq!(cfg.excluded_tools, vec!["dangerous_tool"]);
+        assert_eq!(cfg.max_spawn_depth, 0);
+    }
+
+    #[test]
+    fn remote_delegation_config() {
+        let json = r#"{
+            "remotes": {
+                "buildbox": { "url": "http://buildbox.lan:18789", "tokenEnv": "BUILDBOX_TOKEN" }
+            },
+            "serve": { "enabled": true, "token": "s3cret", "agents": ["tester"], "maxTaskSecs": 120 }
+        }"#;
+        let cfg: DelegationConfig = serde_json::from_str(json).unwrap();
+        let peer = &cfg.remotes["buildbox"];
+        assert_eq!(peer.url, "http://buildbox.lan:18789");
+        assert_eq!(peer.token_env.as_deref(), Some("BUILDBOX_TOKEN"));
+        assert_eq!(peer.timeout_secs, 900);
+        assert_eq!(peer.poll_interval_secs, 2);
+        assert!(cfg.serve.enabled);
+        assert_eq!(cfg.serve.token.expose(), "s3cret");
+        assert_eq!(cfg.serve.agents, vec!["tester"]);
+        assert_eq!(cfg.serve.max_concurrent, 2);
+        assert_eq!(cfg.serve.max_task_secs, 120);
+
+        let cfg = DelegationConfig::default();
+        assert!(cfg.remotes.is_empty());
+        assert!(!cfg.serve.enabled);
+    }
+
+    #[test]
+    fn remote_task_status_wire_format() {
+        let status: RemoteTaskStatus = serde_json::from_str(
+            r#"{"id": "t1", "state": "completed", "answer": "3 failures",
+                "usage": [{"provider": "anthropic", "model": "claude-sonnet-4", "requests": 2,
+                           "input_tokens": 900, "output_tokens": 120, "cost_usd": 0.0045}],
+                "submitted_at": "2026-03-01T10:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(status.state, RemoteTaskState::Completed);
+        assert!(status.state.is_finished());
+        assert!(!RemoteTaskState::Running.is_finished());
+        assert_eq!(status.usage[0].input_tokens, 900);
+        assert!(status.error.is_none());
+
+        let request = RemoteTaskRequest {
+            prompt: "run the tests".into(),
+            agent: None,
+            max_iterations: Some(5),
+            max_tokens: None,
+            timeout_secs: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({"prompt": "run the tests", "max_iterations": 5})
+        );
     }
 
     #[test]
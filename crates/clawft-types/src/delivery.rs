@@ -0,0 +1,334 @@
+//! Delivery rules for proactive messages.
+//!
+//! Scheduled prompts (heartbeats and cron jobs) run with nobody waiting
+//! for the answer. A [`ProactiveDelivery`] stored in the prompt's
+//! [`InboundMessage`](crate::event::InboundMessage) metadata says what
+//! happens to the agent's reply:
+//!
+//! - [`target`](ProactiveDelivery::target) sends it to a channel and
+//!   conversation instead of back to the scheduler's pseudo-channel;
+//! - [`quiet_hours`](ProactiveDelivery::quiet_hours) holds it back while
+//!   the local time is inside the window;
+//! - a reply ending with [`NO_NOTIFY_MARKER`] is dropped entirely.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::event::OutboundMessage;
+
+/// Marker a proactive reply ends with when nothing needs the user's
+/// attention; such replies are not delivered.
+pub const NO_NOTIFY_MARKER: &str = "NO_NOTIFY";
+
+/// Whether `reply` asks not to be delivered (ends with
+/// [`NO_NOTIFY_MARKER`], ignoring trailing whitespace and punctuation).
+pub fn is_suppressed(reply: &str) -> bool {
+    reply
+        .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '.' | '`' | '*'))
+        .ends_with(NO_NOTIFY_MARKER)
+}
+
+/// A channel and conversation to deliver a reply to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryTarget {
+    /// Channel name (e.g. `"telegram"`).
+    pub channel: String,
+
+    /// Conversation within the channel (chat, user, or room ID).
+    pub chat_id: String,
+}
+
+/// A daily window during which proactive messages are held back.
+///
+/// Times are `HH:MM` in [`timezone`](Self::timezone). A window whose end
+/// is before its start wraps past midnight (`22:00`-`07:00`); equal start
+/// and end make an empty window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Start of the window, `HH:MM`.
+    pub start: String,
+
+    /// End of the window, `HH:MM`. Held messages are sent at this time.
+    pub end: String,
+
+    /// `"UTC"` or a fixed offset such as `"+02:00"` or `"-05:30"`.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+fn default_timezone() -> String {
+    "UTC".into()
+}
+
+impl QuietHours {
+    /// Metadata key of an [`OutboundMessage`] carrying the window that
+    /// applies to it.
+    pub const METADATA_KEY: &'static str = "quiet_hours";
+
+    /// Parse a `"HH:MM-HH:MM"` window in `timezone`.
+    pub fn parse(window: &str, timezone: &str) -> Result<Self, String> {
+        let (start, end) = window
+            .split_once('-')
+            .ok_or_else(|| format!("quiet hours '{window}' must look like 22:00-07:00"))?;
+        let hours = Self {
+            start: start.trim().to_string(),
+            end: end.trim().to_string(),
+            timezone: timezone.to_string(),
+        };
+        hours.validate()?;
+        Ok(hours)
+    }
+
+    /// Check the times and timezone.
+    pub fn validate(&self) -> Result<(), String> {
+        self.resolve().map(|_| ())
+    }
+
+    /// Whether `now` falls inside the window.
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        let Ok((start, end, offset)) = self.resolve() else {
+            return false;
+        };
+        let local = now.with_timezone(&offset).time();
+        if start <= end {
+            start <= local && local < end
+        } else {
+            local >= start || local < end
+        }
+    }
+
+    /// When a message held at `now` may be sent: the end of the current
+    /// window, or `now` itself outside the window.
+    pub fn release_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        if !self.contains(now) {
+            return now;
+        }
+        let Ok((_, end, offset)) = self.resolve() else {
+            return now;
+        };
+        let local = now.with_timezone(&offset);
+        let mut day = local.date_naive();
+        if local.time() >= end {
+            day += Duration::days(1);
+        }
+        offset
+            .from_local_datetime(&day.and_time(end))
+            .single()
+            .map_or(now, |t| t.with_timezone(&Utc))
+    }
+
+    /// Read the window an outbound message carries.
+    pub fn from_metadata(metadata: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        metadata
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    fn resolve(&self) -> Result<(NaiveTime, NaiveTime, FixedOffset), String> {
+        let time = |s: &str| {
+            NaiveTime::parse_from_str(s, "%H:%M")
+                .map_err(|_| format!("quiet hours time '{s}' must be HH:MM"))
+        };
+        Ok((
+            time(&self.start)?,
+            time(&self.end)?,
+            parse_offset(&self.timezone)?,
+        ))
+    }
+}
+
+/// Parse `"UTC"`, `"Z"`, or a `±HH:MM` offset.
+fn parse_offset(timezone: &str) -> Result<FixedOffset, String> {
+    let tz = timezone.trim();
+    let invalid = || format!("timezone '{timezone}' must be UTC or an offset like +02:00");
+    if tz.eq_ignore_ascii_case("utc") || tz == "Z" {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset"));
+    }
+    let tz = tz.strip_prefix("UTC").unwrap_or(tz);
+    let (sign, rest) = match tz.chars().next() {
+        Some('+') => (1, &tz[1..]),
+        Some('-') => (-1, &tz[1..]),
+        _ => return Err(invalid()),
+    };
+    let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+    let h: i32 = h.parse().map_err(|_| invalid())?;
+    let m: i32 = m.parse().map_err(|_| invalid())?;
+    if h > 14 || m > 59 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (h * 3600 + m * 60)).ok_or_else(invalid)
+}
+
+/// Where and when the reply to a scheduled prompt is delivered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProactiveDelivery {
+    /// Where the reply goes. `None` keeps the scheduler's own channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<DeliveryTarget>,
+
+    /// Window during which the reply is held back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl ProactiveDelivery {
+    /// Metadata key of an inbound message carrying its delivery rules.
+    pub const METADATA_KEY: &'static str = "proactive_delivery";
+
+    /// Mark `metadata` as a proactive prompt with these rules.
+    pub fn insert_into(&self, metadata: &mut HashMap<String, serde_json::Value>) {
+        if let Ok(value) = serde_json::to_value(self) {
+            metadata.insert(Self::METADATA_KEY.into(), value);
+        }
+    }
+
+    /// The delivery rules of a proactive prompt; `None` for messages from
+    /// people.
+    pub fn from_metadata(metadata: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        metadata
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// Apply the rules to the reply: `None` when the agent suppressed it,
+    /// otherwise the reply readdressed to the target and tagged with the
+    /// quiet hours.
+    pub fn route(&self, mut outbound: OutboundMessage) -> Option<OutboundMessage> {
+        if is_suppressed(&outbound.content) {
+            return None;
+        }
+        if let Some(target) = &self.target {
+            outbound.channel = target.channel.clone();
+            outbound.chat_id = target.chat_id.clone();
+        }
+        if let Some(quiet) = &self.quiet_hours
+            && let Ok(value) = serde_json::to_value(quiet)
+        {
+            outbound
+                .metadata
+                .insert(QuietHours::METADATA_KEY.into(), value);
+        }
+        Some(outbound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn night(tz: &str) -> QuietHours {
+        QuietHours::parse("22:00-07:00", tz).unwrap()
+    }
+
+    fn outbound(content: &str) -> OutboundMessage {
+        OutboundMessage {
+            channel: "heartbeat".into(),
+            chat_id: "heartbeat".into(),
+            content: content.into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn wrapping_window_contains_night_hours() {
+        let quiet = night("UTC");
+        assert!(quiet.contains(at("2026-05-01T23:30:00Z")));
+        assert!(quiet.contains(at("2026-05-01T06:59:00Z")));
+        assert!(!quiet.contains(at("2026-05-01T07:00:00Z")));
+        assert!(!quiet.contains(at("2026-05-01T12:00:00Z")));
+    }
+
+    #[test]
+    fn daytime_window_and_empty_window() {
+        let lunch = QuietHours::parse("12:00-13:00", "UTC").unwrap();
+        assert!(lunch.contains(at("2026-05-01T12:30:00Z")));
+        assert!(!lunch.contains(at("2026-05-01T13:00:00Z")));
+        let empty = QuietHours::parse("09:00-09:00", "UTC").unwrap();
+        assert!(!empty.contains(at("2026-05-01T09:00:00Z")));
+    }
+
+    #[test]
+    fn window_uses_timezone_offset() {
+        // 21:30 UTC is 23:30 at +02:00.
+        let quiet = night("+02:00");
+        assert!(quiet.contains(at("2026-05-01T21:30:00Z")));
+        assert!(!quiet.contains(at("2026-05-01T19:30:00Z")));
+    }
+
+    #[test]
+    fn release_at_is_the_next_window_end() {
+        let quiet = night("+02:00");
+        // 23:30 local on May 1st -> 07:00 local on May 2nd = 05:00 UTC.
+        assert_eq!(
+            quiet.release_at(at("2026-05-01T21:30:00Z")),
+            at("2026-05-02T05:00:00Z")
+        );
+        // 03:00 local on May 2nd -> 07:00 local the same day.
+        assert_eq!(
+            quiet.release_at(at("2026-05-02T01:00:00Z")),
+            at("2026-05-02T05:00:00Z")
+        );
+        let noon = at("2026-05-02T10:00:00Z");
+        assert_eq!(quiet.release_at(noon), noon);
+    }
+
+    #[test]
+    fn parse_rejects_bad_windows() {
+        assert!(QuietHours::parse("22-07", "UTC").is_err());
+        assert!(QuietHours::parse("22:00", "UTC").is_err());
+        assert!(QuietHours::parse("25:00-07:00", "UTC").is_err());
+        assert!(QuietHours::parse("22:00-07:00", "Europe/Paris").is_err());
+        assert!(QuietHours::parse("22:00-07:00", "UTC-05:30").is_ok());
+    }
+
+    #[test]
+    fn suppression_marker_ends_reply() {
+        assert!(is_suppressed("Nothing new. NO_NOTIFY"));
+        assert!(is_suppressed("NO_NOTIFY.\n"));
+        assert!(is_suppressed("`NO_NOTIFY`"));
+        assert!(!is_suppressed("NO_NOTIFY, but one email needs a reply"));
+        assert!(!is_suppressed("You have 2 meetings."));
+    }
+
+    #[test]
+    fn route_readdresses_and_tags_reply() {
+        let delivery = ProactiveDelivery {
+            target: Some(DeliveryTarget {
+                channel: "telegram".into(),
+                chat_id: "42".into(),
+            }),
+            quiet_hours: Some(night("UTC")),
+        };
+        let routed = delivery.route(outbound("Meeting moved to 10:00.")).unwrap();
+        assert_eq!(routed.channel, "telegram");
+        assert_eq!(routed.chat_id, "42");
+        assert_eq!(
+            QuietHours::from_metadata(&routed.metadata),
+            Some(night("UTC"))
+        );
+        assert!(delivery.route(outbound("All quiet. NO_NOTIFY")).is_none());
+    }
+
+    #[test]
+    fn metadata_roundtrip() {
+        let delivery = ProactiveDelivery {
+            target: None,
+            quiet_hours: Some(night("-05:00")),
+        };
+        let mut metadata = HashMap::new();
+        delivery.insert_into(&mut metadata);
+        assert_eq!(ProactiveDelivery::from_metadata(&metadata), Some(delivery));
+        assert_eq!(ProactiveDelivery::from_metadata(&HashMap::new()), None);
+    }
+}
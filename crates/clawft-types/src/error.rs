@@ -61,6 +61,15 @@ pub enum ClawftError {
         plugin: String,
     },
 
+    /// A custom pipeline stage failed and its error policy aborts the turn.
+    #[error("pipeline stage '{stage}' failed: {reason}")]
+    PipelineStage {
+        /// Name of the stage.
+        stage: String,
+        /// What went wrong.
+        reason: String,
+    },
+
     /// Underlying I/O error.
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
@@ -112,6 +121,17 @@ pub enum ChannelError {
     #[error("channel not found: {0}")]
     NotFound(String),
 
+    /// An attachment exceeds the channel's upload size limit.
+    #[error("attachment too large: {filename} is {size} bytes (limit {limit} bytes)")]
+    AttachmentTooLarge {
+        /// Name of the offending file.
+        filename: String,
+        /// Actual size in bytes.
+        size: u64,
+        /// Channel upload limit in bytes.
+        limit: u64,
+    },
+
     /// Catch-all for errors that do not fit other variants.
     #[error("{0}")]
     Other(String),
@@ -154,6 +174,16 @@ mod tests {
 
         let err = ChannelError::AuthFailed("bad token".into());
         assert_eq!(err.to_string(), "authentication failed: bad token");
+
+        let err = ChannelError::AttachmentTooLarge {
+            filename: "video.mp4".into(),
+            size: 60_000_000,
+            limit: 50_000_000,
+        };
+        assert_eq!(
+            err.to_string(),
+            "attachment too large: video.mp4 is 60000000 bytes (limit 50000000 bytes)"
+        );
     }
 
     #[test]
@@ -2,8 +2,11 @@
 //!
 //! [`InboundMessage`] represents user input arriving from a channel,
 //! while [`OutboundMessage`] represents agent responses heading back out.
+//! Files sent by the user or produced by the agent travel with the
+//! message as [`Attachment`]s.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -35,18 +38,75 @@ pub struct InboundMessage {
     #[serde(default)]
     pub media: Vec<String>,
 
+    /// Files the user sent with the message. Image attachments are passed
+    /// to multimodal models as image parts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+
     /// Arbitrary channel-specific metadata.
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
 impl InboundMessage {
-    /// Unique key for session identification: `"{channel}:{chat_id}"`.
+    /// Metadata key naming the session to use instead of the default one,
+    /// e.g. to continue a forked session from the CLI.
+    pub const SESSION_KEY: &'static str = "session_key";
+
+    /// Metadata key set to `true` to lift `agents.budget` limits for the
+    /// message's turn. Only honored for the local CLI channel.
+    pub const NO_BUDGET_KEY: &'static str = "no_budget";
+
+    /// Metadata key naming a `provider/model` to answer the message's turn
+    /// instead of the routed model. Only honored for the local CLI channel.
+    pub const MODEL_KEY: &'static str = "model";
+
+    /// Metadata key set to `true` to record a trace of the message's turn
+    /// even when `agents.sessions.trace` is off.
+    pub const TRACE_KEY: &'static str = "trace";
+
+    /// Metadata key holding [`TurnLimits`] for the message's turn.
+    pub const LIMITS_KEY: &'static str = "turn_limits";
+
+    /// The [`TurnLimits`] under [`LIMITS_KEY`](Self::LIMITS_KEY); none
+    /// when absent or unreadable.
+    pub fn turn_limits(&self) -> TurnLimits {
+        self.metadata
+            .get(Self::LIMITS_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Unique key for session identification: `"{channel}:{chat_id}"`,
+    /// unless metadata names another session under
+    /// [`SESSION_KEY`](Self::SESSION_KEY).
     pub fn session_key(&self) -> String {
+        if let Some(key) = self
+            .metadata
+            .get(Self::SESSION_KEY)
+            .and_then(|v| v.as_str())
+        {
+            return key.to_string();
+        }
         format!("{}:{}", self.channel, self.chat_id)
     }
 }
 
+/// Limits a message's sender puts on its turn. They only tighten
+/// `agents.budget` and `max_tool_iterations`; looser values are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnLimits {
+    /// Tool-loop iterations the turn may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<u32>,
+    /// Prompt plus completion tokens the turn may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    /// Wall-clock seconds the turn may take.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_secs: Option<u64>,
+}
+
 /// An outbound message to send to a chat channel.
 ///
 /// Produced by the agent pipeline and dispatched to the
@@ -70,11 +130,136 @@ pub struct OutboundMessage {
     #[serde(default)]
     pub media: Vec<String>,
 
+    /// Files to upload alongside the message text.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+
     /// Arbitrary channel-specific metadata.
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl OutboundMessage {
+    /// Metadata key set to `true` when the reply text was already
+    /// streamed to the user as it was generated.
+    pub const STREAMED_KEY: &'static str = "streamed";
+
+    /// Whether the text was streamed before this message was dispatched
+    /// (see [`STREAMED_KEY`](Self::STREAMED_KEY)).
+    pub fn is_streamed(&self) -> bool {
+        self.metadata
+            .get(Self::STREAMED_KEY)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Metadata key set to `true` on the reply to a turn that was
+    /// cancelled before it finished.
+    pub const CANCELLED_KEY: &'static str = "cancelled";
+
+    /// Whether this is the reply to a cancelled turn (see
+    /// [`CANCELLED_KEY`](Self::CANCELLED_KEY)).
+    pub fn is_cancelled(&self) -> bool {
+        self.metadata
+            .get(Self::CANCELLED_KEY)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+}
+
+/// Where the contents of an [`Attachment`] come from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AttachmentData {
+    /// A file on the local filesystem, read at send time.
+    Path {
+        /// Path to the file.
+        path: PathBuf,
+    },
+    /// Contents held in memory.
+    Bytes {
+        /// Raw file contents.
+        data: Vec<u8>,
+    },
+}
+
+/// A file attached to an [`InboundMessage`] or [`OutboundMessage`].
+///
+/// Channels upload attachments using their native file APIs. Channels
+/// without file support send a short note naming the omitted file
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File contents (path or in-memory bytes).
+    pub data: AttachmentData,
+
+    /// File name presented to the recipient.
+    pub filename: String,
+
+    /// MIME type (e.g. `"image/png"`).
+    #[serde(default = "default_mime_type")]
+    pub mime_type: String,
+
+    /// Optional caption shown with the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
+fn default_mime_type() -> String {
+    "application/octet-stream".into()
+}
+
+impl Attachment {
+    /// Attach a file from disk. The filename is taken from the last
+    /// path component.
+    pub fn from_path(path: impl Into<PathBuf>, mime_type: impl Into<String>) -> Self {
+        let path = path.into();
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".into());
+        Self {
+            data: AttachmentData::Path { path },
+            filename,
+            mime_type: mime_type.into(),
+            caption: None,
+        }
+    }
+
+    /// Attach in-memory bytes under the given filename.
+    pub fn from_bytes(
+        filename: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: Vec<u8>,
+    ) -> Self {
+        Self {
+            data: AttachmentData::Bytes { data },
+            filename: filename.into(),
+            mime_type: mime_type.into(),
+            caption: None,
+        }
+    }
+
+    /// Set the caption.
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Path of a file-backed attachment.
+    pub fn path(&self) -> Option<&Path> {
+        match self.data {
+            AttachmentData::Path { ref path } => Some(path),
+            AttachmentData::Bytes { .. } => None,
+        }
+    }
+
+    /// Whether the MIME type is an image type.
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,11 +273,58 @@ mod tests {
             content: "hello".into(),
             timestamp: Utc::now(),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         assert_eq!(msg.session_key(), "telegram:chat456");
     }
 
+    #[test]
+    fn inbound_session_key_override() {
+        let mut msg = InboundMessage {
+            channel: "cli".into(),
+            sender_id: "local".into(),
+            chat_id: "cli-session".into(),
+            content: "hello".into(),
+            timestamp: Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        msg.metadata.insert(
+            InboundMessage::SESSION_KEY.into(),
+            "cli:cli-session:fork-1a2b3c4d".into(),
+        );
+        assert_eq!(msg.session_key(), "cli:cli-session:fork-1a2b3c4d");
+    }
+
+    #[test]
+    fn inbound_turn_limits() {
+        let mut msg = InboundMessage {
+            channel: "delegation".into(),
+            sender_id: "peer".into(),
+            chat_id: "t1".into(),
+            content: "run the tests".into(),
+            timestamp: Utc::now(),
+            media: vec![],
+            attachments: vec![],
+            metadata: HashMap::new(),
+        };
+        assert_eq!(msg.turn_limits(), TurnLimits::default());
+        msg.metadata.insert(
+            InboundMessage::LIMITS_KEY.into(),
+            serde_json::json!({"max_iterations": 4, "max_secs": 60}),
+        );
+        assert_eq!(
+            msg.turn_limits(),
+            TurnLimits {
+                max_iterations: Some(4),
+                max_tokens: None,
+                max_secs: Some(60),
+            }
+        );
+    }
+
     #[test]
     fn inbound_serde_roundtrip() {
         let msg = InboundMessage {
@@ -102,6 +334,7 @@ mod tests {
             content: "test message".into(),
             timestamp: Utc::now(),
             media: vec!["https://example.com/image.png".into()],
+            attachments: vec![],
             metadata: {
                 let mut m = HashMap::new();
                 m.insert("thread_ts".into(), serde_json::json!("123.456"));
@@ -139,6 +372,7 @@ mod tests {
             content: "reply".into(),
             reply_to: Some("msg789".into()),
             media: vec![],
+            attachments: vec![],
             metadata: HashMap::new(),
         };
         let json = serde_json::to_string(&msg).unwrap();
@@ -157,5 +391,46 @@ mod tests {
         let msg: OutboundMessage = serde_json::from_str(json).unwrap();
         assert!(msg.reply_to.is_none());
         assert!(msg.media.is_empty());
+        assert!(msg.attachments.is_empty());
+    }
+
+    #[test]
+    fn attachment_from_path_uses_file_name() {
+        let att = Attachment::from_path("/tmp/out/report.pdf", "application/pdf")
+            .with_caption("Q3 report");
+        assert_eq!(att.filename, "report.pdf");
+        assert_eq!(att.path(), Some(Path::new("/tmp/out/report.pdf")));
+        assert_eq!(att.caption.as_deref(), Some("Q3 report"));
+        assert!(!att.is_image());
+    }
+
+    #[test]
+    fn attachment_serde_roundtrip() {
+        let msg = OutboundMessage {
+            channel: "slack".into(),
+            chat_id: "C1".into(),
+            content: "here you go".into(),
+            reply_to: None,
+            media: vec![],
+            attachments: vec![
+                Attachment::from_bytes("chart.png", "image/png", vec![1, 2, 3]),
+                Attachment::from_path("notes.txt", "text/plain"),
+            ],
+            metadata: HashMap::new(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["attachments"][0]["data"]["kind"], "bytes");
+        assert_eq!(json["attachments"][1]["data"]["kind"], "path");
+
+        let restored: OutboundMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.attachments, msg.attachments);
+        assert!(restored.attachments[0].is_image());
+    }
+
+    #[test]
+    fn attachment_mime_type_defaults() {
+        let json = r#"{"data": {"kind": "path", "path": "a.bin"}, "filename": "a.bin"}"#;
+        let att: Attachment = serde_json::from_str(json).unwrap();
+        assert_eq!(att.mime_type, "application/octet-stream");
     }
 }
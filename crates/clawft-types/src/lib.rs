@@ -36,6 +36,7 @@ pub mod company;
 pub mod config;
 pub mod cron;
 pub mod delegation;
+pub mod delivery;
 pub mod error;
 pub mod event;
 pub mod goal;
@@ -74,17 +74,17 @@ pub enum StopReason {
 /// but serde aliases allow deserialization from the OpenAI naming
 /// convention (`prompt_tokens`, `completion_tokens`) as well.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(from = "UsageWire")]
 pub struct Usage {
-    /// Tokens consumed by the prompt / input.
+    /// Tokens consumed by the prompt / input, including any prompt-cache
+    /// reads and writes.
     ///
     /// Deserializes from either `"input_tokens"` or `"prompt_tokens"`.
-    #[serde(alias = "prompt_tokens")]
     pub input_tokens: u32,
 
     /// Tokens generated in the response.
     ///
     /// Deserializes from either `"output_tokens"` or `"completion_tokens"`.
-    #[serde(alias = "completion_tokens")]
     pub output_tokens: u32,
 
     /// Total tokens used (input + output).
@@ -92,8 +92,94 @@ pub struct Usage {
     /// When deserializing from providers that include `total_tokens`, this
     /// field is populated directly. Otherwise it defaults to 0 and callers
     /// can use [`Usage::total`] to compute it.
-    #[serde(default)]
     pub total_tokens: u32,
+
+    /// The response was served from a response cache; no tokens were billed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+
+    /// Input tokens read from the provider's prompt cache (a subset of
+    /// `input_tokens`, billed at the discounted cache-read rate).
+    ///
+    /// Also deserializes from OpenAI's `prompt_tokens_details.cached_tokens`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub cache_read_tokens: u32,
+
+    /// Input tokens written to the provider's prompt cache (a subset of
+    /// `input_tokens`, billed at the cache-write premium).
+    #[serde(skip_serializing_if = "is_zero")]
+    pub cache_write_tokens: u32,
+
+    /// Output tokens spent on hidden reasoning (a subset of
+    /// `output_tokens`, billed at the output rate).
+    ///
+    /// Also deserializes from OpenAI's
+    /// `completion_tokens_details.reasoning_tokens`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub reasoning_tokens: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Wire form of [`Usage`], accepting both the clawft and OpenAI shapes.
+#[derive(Deserialize)]
+struct UsageWire {
+    #[serde(alias = "prompt_tokens")]
+    input_tokens: u32,
+    #[serde(alias = "completion_tokens")]
+    output_tokens: u32,
+    #[serde(default)]
+    total_tokens: u32,
+    #[serde(default)]
+    cached: bool,
+    #[serde(default)]
+    cache_read_tokens: u32,
+    #[serde(default)]
+    cache_write_tokens: u32,
+    #[serde(default)]
+    reasoning_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(default)]
+    completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+/// OpenAI's breakdown of prompt tokens.
+#[derive(Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: Option<u32>,
+}
+
+/// OpenAI's breakdown of completion tokens.
+#[derive(Deserialize)]
+struct CompletionTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: Option<u32>,
+}
+
+impl From<UsageWire> for Usage {
+    fn from(wire: UsageWire) -> Self {
+        let openai_cached = wire
+            .prompt_tokens_details
+            .and_then(|details| details.cached_tokens)
+            .unwrap_or(0);
+        let openai_reasoning = wire
+            .completion_tokens_details
+            .and_then(|details| details.reasoning_tokens)
+            .unwrap_or(0);
+        Self {
+            input_tokens: wire.input_tokens,
+            output_tokens: wire.output_tokens,
+            total_tokens: wire.total_tokens,
+            cached: wire.cached,
+            cache_read_tokens: wire.cache_read_tokens.max(openai_cached),
+            cache_write_tokens: wire.cache_write_tokens,
+            reasoning_tokens: wire.reasoning_tokens.max(openai_reasoning),
+        }
+    }
 }
 
 impl Usage {
@@ -110,6 +196,23 @@ impl Usage {
     }
 }
 
+/// How much effort a reasoning model spends thinking before it answers.
+///
+/// Sent as `reasoning_effort` to models whose [`ModelSpec::reasoning_effort`]
+/// flag is set, and dropped for all others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    /// As little reasoning as the model allows (GPT-5 only).
+    Minimal,
+    /// Favour speed and fewer reasoning tokens.
+    Low,
+    /// The provider default.
+    Medium,
+    /// Favour thorough reasoning.
+    High,
+}
+
 /// A tool-call request extracted from a model response.
 ///
 /// This is a convenience struct for pipeline stages that need to
@@ -237,6 +340,23 @@ pub static PROVIDERS: &[ProviderSpec] = &[
         strip_model_prefix: true,
     },
     // === Standard providers ===
+    // Azure serves OpenAI models under deployment names, so it must match
+    // `azure/gpt-4o` before the OpenAI keywords do.
+    ProviderSpec {
+        name: "azure",
+        keywords: &["azure"],
+        env_key: "AZURE_OPENAI_API_KEY",
+        display_name: "Azure OpenAI",
+        litellm_prefix: "azure",
+        skip_prefixes: &["azure/"],
+        is_gateway: false,
+        is_local: false,
+        is_oauth: false,
+        default_api_base: "",
+        detect_by_key_prefix: "",
+        detect_by_base_keyword: "openai.azure.com",
+        strip_model_prefix: true,
+    },
     ProviderSpec {
         name: "anthropic",
         keywords: &["anthropic", "claude"],
@@ -536,13 +656,263 @@ pub fn find_by_name(name: &str) -> Option<&'static ProviderSpec> {
     PROVIDERS.iter().find(|spec| spec.name == name)
 }
 
+// ── Model registry ───────────────────────────────────────────────────────
+
+/// Token limits and input capabilities for a model family.
+///
+/// Instances live in the static [`MODELS`] array and are looked up with
+/// [`find_model_spec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelSpec {
+    /// Bare model-name prefix (lowercase, no `provider/` routing prefix).
+    /// The longest matching prefix wins.
+    pub prefix: &'static str,
+
+    /// Context window: prompt and completion tokens together must fit.
+    pub context_window: u32,
+
+    /// Maximum completion tokens the model will generate.
+    pub max_output_tokens: u32,
+
+    /// Whether the model accepts image input.
+    pub multimodal: bool,
+
+    /// Whether the model accepts the `reasoning_effort` parameter. Other
+    /// models reject it with a 400, so it is only sent when this is set.
+    pub reasoning_effort: bool,
+}
+
+impl ModelSpec {
+    /// Largest prompt that still leaves room for `reserved_output` tokens
+    /// of completion (capped at [`max_output_tokens`](Self::max_output_tokens)).
+    pub fn max_input_tokens(&self, reserved_output: u32) -> u32 {
+        self.context_window
+            .saturating_sub(reserved_output.min(self.max_output_tokens))
+    }
+
+    /// Mark the family as accepting `reasoning_effort`.
+    const fn reasoning(self) -> Self {
+        Self {
+            reasoning_effort: true,
+            ..self
+        }
+    }
+}
+
+const fn model(prefix: &'static str, context_window: u32, max_output_tokens: u32) -> ModelSpec {
+    ModelSpec {
+        prefix,
+        context_window,
+        max_output_tokens,
+        multimodal: false,
+        reasoning_effort: false,
+    }
+}
+
+/// Like [`model`], for a family that also accepts images.
+const fn vision(prefix: &'static str, context_window: u32, max_output_tokens: u32) -> ModelSpec {
+    ModelSpec {
+        multimodal: true,
+        ..model(prefix, context_window, max_output_tokens)
+    }
+}
+
+/// Known model families and their published token limits.
+pub static MODELS: &[ModelSpec] = &[
+    // === OpenAI ===
+    vision("gpt-5-chat", 128_000, 16_384),
+    vision("gpt-5", 400_000, 128_000).reasoning(),
+    vision("gpt-4.1", 1_047_576, 32_768),
+    vision("gpt-4.5", 128_000, 16_384),
+    vision("gpt-4o", 128_000, 16_384),
+    vision("chatgpt-4o", 128_000, 16_384),
+    vision("gpt-4-turbo", 128_000, 4_096),
+    model("gpt-4-32k", 32_768, 4_096),
+    model("gpt-4", 8_192, 4_096),
+    model("gpt-3.5-turbo", 16_385, 4_096),
+    model("o1-mini", 128_000, 65_536),
+    vision("o1", 200_000, 100_000).reasoning(),
+    vision("o3", 200_000, 100_000).reasoning(),
+    vision("o4-mini", 200_000, 100_000).reasoning(),
+    // === Anthropic ===
+    vision("claude-opus-4", 200_000, 32_000),
+    vision("claude-sonnet-4", 200_000, 64_000),
+    vision("claude-haiku-4", 200_000, 64_000),
+    vision("claude-3-7-sonnet", 200_000, 64_000),
+    vision("claude-3-5", 200_000, 8_192),
+    vision("claude-3", 200_000, 4_096),
+    model("claude", 200_000, 8_192),
+    // === Google ===
+    vision("gemini-2.5", 1_048_576, 65_536),
+    vision("gemini-2.0", 1_048_576, 8_192),
+    vision("gemini-1.5-pro", 2_097_152, 8_192),
+    vision("gemini-1.5", 1_048_576, 8_192),
+    // === Others ===
+    model("deepseek-reasoner", 65_536, 32_768),
+    model("deepseek", 65_536, 8_192),
+    vision("grok-4", 256_000, 32_768),
+    model("grok", 131_072, 16_384),
+    model("llama-3.3", 131_072, 32_768),
+    model("llama-3.1", 131_072, 8_192),
+    model("mistral-large", 131_072, 8_192),
+    model("mistral", 32_768, 8_192),
+];
+
+/// Find the limits for a model name.
+///
+/// Any `provider/` routing prefixes are removed first (so
+/// `openrouter/anthropic/claude-sonnet-4` matches `claude-sonnet-4`), then
+/// the longest [`ModelSpec::prefix`] match is returned.
+pub fn find_model_spec(model: &str) -> Option<&'static ModelSpec> {
+    let bare = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    MODELS
+        .iter()
+        .filter(|spec| bare.starts_with(spec.prefix))
+        .max_by_key(|spec| spec.prefix.len())
+}
+
+// ── Pricing ──────────────────────────────────────────────────────────────
+
+/// Published list price for a model family, in US dollars per 1K tokens.
+///
+/// Instances live in the static [`MODEL_PRICES`] array and are looked up
+/// with [`find_model_price`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    /// Bare model-name prefix, matched like [`ModelSpec::prefix`].
+    pub prefix: &'static str,
+
+    /// Price of 1K prompt tokens.
+    pub input_per_1k: f64,
+
+    /// Price of 1K completion tokens.
+    pub output_per_1k: f64,
+
+    /// Price of 1K prompt tokens read from the provider's prompt cache.
+    pub cache_read_per_1k: f64,
+
+    /// Price of 1K prompt tokens written to the provider's prompt cache.
+    pub cache_write_per_1k: f64,
+}
+
+impl ModelPrice {
+    /// Cost of a call with the given token counts.
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_1k + output_tokens as f64 * self.output_per_1k)
+            / 1000.0
+    }
+
+    /// Cost of a call, pricing prompt-cache reads and writes at their own
+    /// rates and the remaining input at the regular rate.
+    pub fn usage_cost(&self, usage: &Usage) -> f64 {
+        let cache_read = f64::from(usage.cache_read_tokens);
+        let cache_write = f64::from(usage.cache_write_tokens);
+        let uncached = f64::from(
+            usage
+                .input_tokens
+                .saturating_sub(usage.cache_read_tokens)
+                .saturating_sub(usage.cache_write_tokens),
+        );
+        (uncached * self.input_per_1k
+            + cache_read * self.cache_read_per_1k
+            + cache_write * self.cache_write_per_1k
+            + f64::from(usage.output_tokens) * self.output_per_1k)
+            / 1000.0
+    }
+
+    /// Set the prompt-cache prices as multiples of the input price.
+    const fn cache(self, read: f64, write: f64) -> Self {
+        Self {
+            cache_read_per_1k: self.input_per_1k * read,
+            cache_write_per_1k: self.input_per_1k * write,
+            ..self
+        }
+    }
+}
+
+/// A price with no prompt-cache discount: cached tokens cost the same as
+/// regular input.
+const fn price(prefix: &'static str, input_per_1k: f64, output_per_1k: f64) -> ModelPrice {
+    ModelPrice {
+        prefix,
+        input_per_1k,
+        output_per_1k,
+        cache_read_per_1k: input_per_1k,
+        cache_write_per_1k: input_per_1k,
+    }
+}
+
+/// An Anthropic price: cache reads at 0.1x input, 5-minute cache writes at
+/// 1.25x.
+const fn anthropic(prefix: &'static str, input_per_1k: f64, output_per_1k: f64) -> ModelPrice {
+    price(prefix, input_per_1k, output_per_1k).cache(0.1, 1.25)
+}
+
+/// List prices for known model families. Unlisted models (local models,
+/// most gateways) are treated as free.
+pub static MODEL_PRICES: &[ModelPrice] = &[
+    // === OpenAI ===
+    price("gpt-5", 0.001_25, 0.01).cache(0.1, 1.0),
+    price("gpt-5-mini", 0.000_25, 0.002).cache(0.1, 1.0),
+    price("gpt-5-nano", 0.000_05, 0.000_4).cache(0.1, 1.0),
+    price("gpt-4.1", 0.002, 0.008).cache(0.25, 1.0),
+    price("gpt-4.1-mini", 0.000_4, 0.001_6).cache(0.25, 1.0),
+    price("gpt-4.1-nano", 0.000_1, 0.000_4).cache(0.25, 1.0),
+    price("gpt-4o", 0.002_5, 0.01).cache(0.5, 1.0),
+    price("gpt-4o-mini", 0.000_15, 0.000_6).cache(0.5, 1.0),
+    price("gpt-4-turbo", 0.01, 0.03),
+    price("gpt-4", 0.03, 0.06),
+    price("gpt-3.5-turbo", 0.000_5, 0.001_5),
+    price("o1", 0.015, 0.06).cache(0.5, 1.0),
+    price("o1-mini", 0.001_1, 0.004_4),
+    price("o3", 0.002, 0.008).cache(0.25, 1.0),
+    price("o3-mini", 0.001_1, 0.004_4).cache(0.5, 1.0),
+    price("o4-mini", 0.001_1, 0.004_4).cache(0.25, 1.0),
+    // === Anthropic ===
+    anthropic("claude-opus-4", 0.015, 0.075),
+    anthropic("claude-opus-4-5", 0.005, 0.025),
+    anthropic("claude-sonnet-4", 0.003, 0.015),
+    anthropic("claude-haiku-4", 0.001, 0.005),
+    anthropic("claude-3-7-sonnet", 0.003, 0.015),
+    anthropic("claude-3-5-sonnet", 0.003, 0.015),
+    anthropic("claude-3-5-haiku", 0.000_8, 0.004),
+    anthropic("claude-3-opus", 0.015, 0.075),
+    anthropic("claude-3-haiku", 0.000_25, 0.001_25),
+    // === Google ===
+    price("gemini-2.5-pro", 0.001_25, 0.01).cache(0.25, 1.0),
+    price("gemini-2.5-flash", 0.000_3, 0.002_5).cache(0.25, 1.0),
+    price("gemini-2.5-flash-lite", 0.000_1, 0.000_4).cache(0.25, 1.0),
+    price("gemini-2.0-flash", 0.000_1, 0.000_4),
+    price("gemini-1.5-pro", 0.001_25, 0.005),
+    price("gemini-1.5-flash", 0.000_075, 0.000_3),
+    // === Others ===
+    price("deepseek-chat", 0.000_27, 0.001_1).cache(0.25, 1.0),
+    price("deepseek-reasoner", 0.000_55, 0.002_19).cache(0.25, 1.0),
+    price("grok-4", 0.003, 0.015),
+    price("grok-3", 0.003, 0.015),
+    price("grok-3-mini", 0.000_3, 0.000_5),
+    price("mistral-large", 0.002, 0.006),
+];
+
+/// Find the list price for a model name.
+///
+/// Matching follows [`find_model_spec`]: routing prefixes are removed and
+/// the longest [`ModelPrice::prefix`] match wins.
+pub fn find_model_price(model: &str) -> Option<&'static ModelPrice> {
+    let bare = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    MODEL_PRICES
+        .iter()
+        .filter(|p| bare.starts_with(p.prefix))
+        .max_by_key(|p| p.prefix.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn provider_count() {
-        assert_eq!(PROVIDERS.len(), 19);
+        assert_eq!(PROVIDERS.len(), 20);
     }
 
     #[test]
@@ -551,6 +921,13 @@ mod tests {
         assert_eq!(spec.name, "anthropic");
     }
 
+    #[test]
+    fn find_azure_before_openai() {
+        let spec = find_by_model("azure/gpt-4o").unwrap();
+        assert_eq!(spec.name, "azure");
+        assert_eq!(find_by_model("gpt-4o").unwrap().name, "openai");
+    }
+
     #[test]
     fn find_deepseek_by_model() {
         let spec = find_by_model("deepseek-chat").unwrap();
@@ -685,6 +1062,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn model_spec_longest_prefix_wins() {
+        assert_eq!(find_model_spec("gpt-4o-mini").unwrap().prefix, "gpt-4o");
+        assert_eq!(find_model_spec("gpt-4-0613").unwrap().prefix, "gpt-4");
+        assert_eq!(
+            find_model_spec("o1-mini-2024-09-12").unwrap().prefix,
+            "o1-mini"
+        );
+        assert_eq!(
+            find_model_spec("claude-sonnet-4-5-20250929")
+                .unwrap()
+                .context_window,
+            200_000
+        );
+        assert!(find_model_spec("totally-unknown").is_none());
+    }
+
+    #[test]
+    fn model_spec_ignores_routing_prefixes() {
+        let spec = find_model_spec("openrouter/anthropic/Claude-Opus-4-1").unwrap();
+        assert_eq!(spec.prefix, "claude-opus-4");
+        assert_eq!(
+            find_model_spec("gemini/gemini-2.5-flash").unwrap().prefix,
+            "gemini-2.5"
+        );
+    }
+
+    #[test]
+    fn max_input_tokens_caps_reservation() {
+        let spec = find_model_spec("gpt-4").unwrap();
+        assert_eq!(spec.max_input_tokens(1_000), 7_192);
+        // Reserving more than the model can produce only reserves its max.
+        assert_eq!(spec.max_input_tokens(100_000), 4_096);
+    }
+
+    #[test]
+    fn model_spec_marks_multimodal_families() {
+        assert!(find_model_spec("gpt-4o-mini").unwrap().multimodal);
+        assert!(
+            find_model_spec("anthropic/claude-3-5-haiku")
+                .unwrap()
+                .multimodal
+        );
+        assert!(find_model_spec("gemini-2.0-flash").unwrap().multimodal);
+        assert!(!find_model_spec("gpt-3.5-turbo").unwrap().multimodal);
+        assert!(!find_model_spec("deepseek-chat").unwrap().multimodal);
+        assert!(!find_model_spec("claude-2.1").unwrap().multimodal);
+    }
+
+    #[test]
+    fn model_price_lookup_and_cost() {
+        let mini = find_model_price("openai/gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(mini.prefix, "gpt-4o-mini");
+        // 10K in at $0.15/M + 2K out at $0.60/M.
+        assert!((mini.cost(10_000, 2_000) - 0.0027).abs() < 1e-12);
+        assert_eq!(
+            find_model_price("claude-opus-4-5-20251101").unwrap().prefix,
+            "claude-opus-4-5"
+        );
+        assert!(find_model_price("llama3.2:latest").is_none());
+    }
+
+    #[test]
+    fn model_price_discounts_cached_input() {
+        let sonnet = find_model_price("claude-sonnet-4-5").unwrap();
+        let usage = Usage {
+            input_tokens: 11_000,
+            output_tokens: 1_000,
+            total_tokens: 12_000,
+            cached: false,
+            cache_read_tokens: 10_000,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
+        };
+        // 1K uncached at $3/M + 10K cache reads at $0.30/M + 1K out at $15/M.
+        assert!((sonnet.usage_cost(&usage) - (0.003 + 0.003 + 0.015)).abs() < 1e-12);
+
+        let write = Usage {
+            cache_read_tokens: 0,
+            cache_write_tokens: 10_000,
+            reasoning_tokens: 0,
+            ..usage
+        };
+        // Cache writes cost 1.25x input: 10K at $3.75/M.
+        assert!((sonnet.usage_cost(&write) - (0.003 + 0.0375 + 0.015)).abs() < 1e-12);
+
+        let plain = find_model_price("grok-4").unwrap();
+        assert!((plain.usage_cost(&usage) - plain.cost(11_000, 1_000)).abs() < 1e-12);
+    }
+
     #[test]
     fn llm_response_serde_roundtrip() {
         let resp = LlmResponse {
@@ -697,6 +1164,10 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 5,
                 total_tokens: 15,
+                cached: false,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                reasoning_tokens: 0,
             },
             metadata: HashMap::new(),
         };
@@ -714,6 +1185,10 @@ mod tests {
             input_tokens: 10,
             output_tokens: 5,
             total_tokens: 0,
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         };
         assert_eq!(usage.total(), 15);
     }
@@ -724,10 +1199,53 @@ mod tests {
             input_tokens: 10,
             output_tokens: 5,
             total_tokens: 20, // provider may count differently
+            cached: false,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+            reasoning_tokens: 0,
         };
         assert_eq!(usage.total(), 20);
     }
 
+    #[test]
+    fn usage_parses_openai_cached_tokens() {
+        let json = r#"{
+            "prompt_tokens": 2000,
+            "completion_tokens": 50,
+            "total_tokens": 2050,
+            "prompt_tokens_details": {"cached_tokens": 1792, "audio_tokens": 0}
+        }"#;
+        let usage: Usage = serde_json::from_str(json).unwrap();
+        assert_eq!(usage.input_tokens, 2000);
+        assert_eq!(usage.cache_read_tokens, 1792);
+        assert_eq!(usage.cache_write_tokens, 0);
+
+        let json =
+            r#"{"prompt_tokens": 10, "completion_tokens": 5, "prompt_tokens_details": null}"#;
+        let usage: Usage = serde_json::from_str(json).unwrap();
+        assert_eq!(usage.cache_read_tokens, 0);
+    }
+
+    #[test]
+    fn usage_cache_counts_roundtrip() {
+        let usage = Usage {
+            input_tokens: 1200,
+            output_tokens: 40,
+            total_tokens: 1240,
+            cached: false,
+            cache_read_tokens: 1000,
+            cache_write_tokens: 100,
+            reasoning_tokens: 0,
+        };
+        let json = serde_json::to_value(usage).unwrap();
+        assert_eq!(json["cache_read_tokens"], 1000);
+        assert_eq!(json["cache_write_tokens"], 100);
+        assert_eq!(serde_json::from_value::<Usage>(json).unwrap(), usage);
+
+        let plain = serde_json::to_value(Usage::default()).unwrap();
+        assert!(plain.get("cache_read_tokens").is_none());
+    }
+
     #[test]
     fn usage_deserializes_from_openai_field_names() {
         let json = r#"{"prompt_tokens": 100, "completion_tokens": 50, "total_tokens": 150}"#;
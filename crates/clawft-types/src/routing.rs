@@ -73,6 +73,11 @@ pub struct RoutingConfig {
     /// Rate limiting settings.
     #[serde(default, alias = "rateLimiting")]
     pub rate_limiting: RateLimitConfig,
+
+    /// Ordered message routing rules. The first rule matching an inbound
+    /// message decides which agent handles it. Applies in every mode.
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
 }
 
 fn default_routing_mode() -> String {
@@ -90,6 +95,86 @@ impl Default for RoutingConfig {
             escalation: EscalationConfig::default(),
             cost_budgets: CostBudgetConfig::default(),
             rate_limiting: RateLimitConfig::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+// ── RoutingRule ──────────────────────────────────────────────────────────
+
+/// A message routing rule: which inbound messages it matches and what
+/// happens to them.
+///
+/// ```json
+/// { "name": "oncall", "match": { "channel": "slack", "chat": "C0ONCALL*" },
+///   "action": { "route": "sre" } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoutingRule {
+    /// Name recorded in the routing trace. Defaults to `rules[<index>]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Criteria the message must meet. All criteria that are set must
+    /// match; a rule without criteria matches every message.
+    #[serde(rename = "match", default)]
+    pub matcher: RuleMatch,
+
+    /// What to do with a matching message.
+    pub action: RuleAction,
+}
+
+/// Criteria of a [`RoutingRule`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuleMatch {
+    /// Channel name, matched exactly (e.g. `"slack"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+
+    /// Conversation (chat ID) pattern: `*` matches any run of characters,
+    /// `?` a single one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat: Option<String>,
+
+    /// Sender ID pattern, with the same wildcards as `chat`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+
+    /// Regular expression searched for in the message text. Prefix it
+    /// with `(?i)` to ignore case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// Action taken on a message matched by a [`RoutingRule`].
+///
+/// Serializes as `{"route": "agent"}`, `"drop"`, or `{"confirm": "agent"}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Hand the message to the named agent.
+    Route(String),
+    /// Discard the message without a reply.
+    Drop,
+    /// Ask the sender to confirm before the named agent handles it.
+    Confirm(String),
+}
+
+impl RuleAction {
+    /// The agent this action hands the message to, if any.
+    pub fn agent(&self) -> Option<&str> {
+        match self {
+            Self::Route(agent) | Self::Confirm(agent) => Some(agent),
+            Self::Drop => None,
+        }
+    }
+
+    /// Short name of the action: `"route"`, `"drop"`, or `"confirm"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Route(_) => "route",
+            Self::Drop => "drop",
+            Self::Confirm(_) => "confirm",
         }
     }
 }
@@ -946,4 +1031,30 @@ mod tests {
         assert!(ctx.permissions.escalation_allowed);
         assert!(ctx.permissions.model_override);
     }
+
+    #[test]
+    fn routing_rules_deserialize_in_order() {
+        let json = r#"{
+            "rules": [
+                {"name": "oncall", "match": {"channel": "slack", "chat": "C0ONCALL*"},
+                 "action": {"route": "sre"}},
+                {"match": {"content": "(?i)invoice"}, "action": {"confirm": "finance"}},
+                {"match": {"sender": "spam-*"}, "action": "drop"}
+            ]
+        }"#;
+        let cfg: RoutingConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(cfg.mode, "static");
+        assert_eq!(cfg.rules.len(), 3);
+        assert_eq!(cfg.rules[0].name.as_deref(), Some("oncall"));
+        assert_eq!(cfg.rules[0].matcher.chat.as_deref(), Some("C0ONCALL*"));
+        assert_eq!(cfg.rules[0].action, RuleAction::Route("sre".into()));
+        assert_eq!(cfg.rules[1].action.agent(), Some("finance"));
+        assert_eq!(cfg.rules[1].action.kind(), "confirm");
+        assert_eq!(cfg.rules[2].action, RuleAction::Drop);
+        assert!(cfg.rules[2].name.is_none());
+
+        let restored: RoutingConfig =
+            serde_json::from_str(&serde_json::to_string(&cfg).unwrap()).unwrap();
+        assert_eq!(restored.rules, cfg.rules);
+    }
 }
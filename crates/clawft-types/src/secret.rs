@@ -33,6 +33,26 @@ impl SecretString {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Resolve the secret from its explicit value or, failing that, from
+    /// the environment variable `env_var`.
+    ///
+    /// `lookup` reads a variable by name. Callers pass their platform
+    /// environment here instead of `std::env` so scoped overrides and
+    /// `.env` values are honoured. Empty values count as unset.
+    pub fn resolve(
+        &self,
+        env_var: Option<&str>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Option<SecretString> {
+        if !self.is_empty() {
+            return Some(self.clone());
+        }
+        env_var
+            .and_then(lookup)
+            .filter(|v| !v.is_empty())
+            .map(SecretString)
+    }
 }
 
 impl fmt::Debug for SecretString {
@@ -91,6 +111,29 @@ impl PartialEq for SecretString {
 mod tests {
     use super::*;
 
+    #[test]
+    fn resolve_prefers_explicit_value() {
+        let lookup = |name: &str| (name == "KEY").then(|| "from-env".to_string());
+        let explicit = SecretString::new("inline");
+        assert_eq!(
+            explicit.resolve(Some("KEY"), lookup).unwrap().expose(),
+            "inline"
+        );
+
+        let empty = SecretString::default();
+        assert_eq!(
+            empty.resolve(Some("KEY"), lookup).unwrap().expose(),
+            "from-env"
+        );
+        assert!(empty.resolve(Some("OTHER"), lookup).is_none());
+        assert!(empty.resolve(None, lookup).is_none());
+        assert!(
+            empty
+                .resolve(Some("KEY"), |_| Some(String::new()))
+                .is_none()
+        );
+    }
+
     #[test]
     fn debug_redacts_non_empty() {
         let s = SecretString::new("my-secret-key");
@@ -4,12 +4,23 @@
 //! channel + chat_id pair. It is designed for LLM cache efficiency:
 //! consolidation writes summaries to external files but never mutates
 //! the in-memory message list.
+//!
+//! A session can be forked ([`Session::fork_at`]) to try a different turn
+//! without touching the original: the fork copies the history up to a
+//! point and records its parent in metadata.
+//!
+//! [`Session::export`] renders a session as a markdown transcript or a
+//! canonical JSON document for sharing and archiving (see [`export`]).
 
 use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub mod export;
+
+pub use export::{ExportFormat, ExportOptions, ExportedAttachment};
+
 /// A conversation session.
 ///
 /// Messages are append-only for LLM cache efficiency. The consolidation
@@ -42,6 +53,12 @@ pub struct Session {
 }
 
 impl Session {
+    /// Metadata key holding the key of the session this one was forked from.
+    pub const PARENT_KEY: &'static str = "parent";
+
+    /// Metadata key holding the number of parent messages a fork copied.
+    pub const FORK_POINT_KEY: &'static str = "forked_at";
+
     /// Create a new empty session with the given key.
     pub fn new(key: impl Into<String>) -> Self {
         let now = Utc::now();
@@ -108,6 +125,34 @@ impl Session {
             .collect()
     }
 
+    /// Fork this session, keeping its first `message_index` messages.
+    ///
+    /// The fork gets a new key (`"{key}:fork-{id}"`), a copy of the
+    /// metadata, and a pointer to this session under
+    /// [`PARENT_KEY`](Self::PARENT_KEY). `message_index` may be anything from
+    /// 0 (an empty history) to the message count (the whole history);
+    /// beyond that, `None` is returned.
+    pub fn fork_at(&self, message_index: usize) -> Option<Session> {
+        if message_index > self.messages.len() {
+            return None;
+        }
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut fork = Session::new(format!("{}:fork-{}", self.key, &id[..8]));
+        fork.messages = self.messages[..message_index].to_vec();
+        fork.last_consolidated = self.last_consolidated.min(message_index);
+        fork.metadata = self.metadata.clone();
+        fork.metadata
+            .insert(Self::PARENT_KEY.into(), self.key.clone().into());
+        fork.metadata
+            .insert(Self::FORK_POINT_KEY.into(), message_index.into());
+        Some(fork)
+    }
+
+    /// The key of the session this one was forked from, if any.
+    pub fn parent(&self) -> Option<&str> {
+        self.metadata.get(Self::PARENT_KEY).and_then(|v| v.as_str())
+    }
+
     /// Clear all messages and reset consolidation state.
     pub fn clear(&mut self) {
         self.messages.clear();
@@ -198,6 +243,53 @@ mod tests {
         assert_eq!(restored.messages.len(), 1);
     }
 
+    fn session_with(n: usize) -> Session {
+        let mut s = Session::new("telegram:1");
+        for i in 0..n {
+            s.add_message("user", &format!("message {i}"), None);
+        }
+        s.metadata.insert("agent".into(), "researcher".into());
+        s
+    }
+
+    #[test]
+    fn fork_at_zero_keeps_no_history() {
+        let s = session_with(3);
+        let fork = s.fork_at(0).unwrap();
+        assert!(fork.messages.is_empty());
+        assert!(fork.key.starts_with("telegram:1:fork-"));
+        assert_eq!(fork.parent(), Some("telegram:1"));
+        assert_eq!(fork.metadata[Session::FORK_POINT_KEY], 0);
+        assert_eq!(fork.metadata["agent"], "researcher");
+        assert_eq!(s.messages.len(), 3);
+    }
+
+    #[test]
+    fn fork_at_tail_copies_everything() {
+        let mut s = session_with(3);
+        s.last_consolidated = 2;
+        let fork = s.fork_at(3).unwrap();
+        assert_eq!(fork.messages, s.messages);
+        assert_eq!(fork.last_consolidated, 2);
+        assert_ne!(fork.key, s.fork_at(3).unwrap().key);
+    }
+
+    #[test]
+    fn fork_in_the_middle_truncates_history() {
+        let mut s = session_with(4);
+        s.last_consolidated = 3;
+        let fork = s.fork_at(1).unwrap();
+        assert_eq!(fork.messages.len(), 1);
+        assert_eq!(fork.messages[0]["content"], "message 0");
+        assert_eq!(fork.last_consolidated, 1);
+    }
+
+    #[test]
+    fn fork_past_the_end_is_rejected() {
+        assert!(session_with(2).fork_at(3).is_none());
+        assert!(Session::new("x").parent().is_none());
+    }
+
     #[test]
     fn default_session() {
         let s = Session::default();
@@ -0,0 +1,605 @@
+//! Conversation export.
+//!
+//! [`Session::export`] renders a session either as a self-contained
+//! markdown transcript or as a canonical JSON document. Tool calls are
+//! collapsed into one-line summaries with the raw arguments in an
+//! expandable `<details>` section. Attachments recorded on messages (see
+//! [`Session::ATTACHMENTS_KEY`]) are listed by [`Session::attachments`];
+//! callers that copy them next to the export set
+//! [`ExportOptions::with_attachments_dir`] so the transcript links to the
+//! copies.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+use super::Session;
+use crate::event::{Attachment, AttachmentData};
+
+/// Version of the JSON export layout.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Longest argument value shown in a tool call summary.
+const SUMMARY_VALUE_CHARS: usize = 40;
+
+/// Output format of [`Session::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A readable markdown transcript.
+    Markdown,
+    /// The session as a canonical JSON document.
+    Json,
+}
+
+impl ExportFormat {
+    /// Conventional file extension for the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown export format '{other}' (expected md or json)"
+            )),
+        }
+    }
+}
+
+/// Options for [`Session::export_with`].
+#[derive(Default)]
+pub struct ExportOptions<'a> {
+    attachments_dir: Option<String>,
+    redact: Option<&'a dyn Fn(&Value) -> Value>,
+}
+
+impl<'a> ExportOptions<'a> {
+    /// Link attachments to `dir/<bundle name>` instead of only naming them.
+    /// `dir` is relative to the exported file.
+    pub fn with_attachments_dir(mut self, dir: impl Into<String>) -> Self {
+        self.attachments_dir = Some(dir.into());
+        self
+    }
+
+    /// Pass tool call arguments through `redact` before they are written,
+    /// e.g. to apply the tool audit redaction rules.
+    pub fn with_redactor(mut self, redact: &'a dyn Fn(&Value) -> Value) -> Self {
+        self.redact = Some(redact);
+        self
+    }
+
+    fn redact(&self, args: &Value) -> Value {
+        match self.redact {
+            Some(redact) => redact(args),
+            None => args.clone(),
+        }
+    }
+
+    fn link(&self, attachment: &ExportedAttachment) -> Option<String> {
+        let dir = self.attachments_dir.as_deref()?;
+        attachment
+            .source
+            .is_some()
+            .then(|| format!("{}/{}", dir.trim_end_matches('/'), attachment.bundle_name))
+    }
+}
+
+/// An attachment referenced by a session message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedAttachment {
+    /// Index of the message the attachment belongs to.
+    pub message: usize,
+    /// Original file name.
+    pub filename: String,
+    /// MIME type.
+    pub mime_type: String,
+    /// File on disk, or `None` when the contents were only held in memory.
+    pub source: Option<PathBuf>,
+    /// File name to use when bundling; unique within the session.
+    pub bundle_name: String,
+}
+
+impl Session {
+    /// Message key holding the attachments a message was sent with, as
+    /// `[{"filename", "mime_type", "path"?}]`.
+    pub const ATTACHMENTS_KEY: &'static str = "attachments";
+
+    /// Message extras recording `attachments`, for
+    /// [`add_message`](Self::add_message). `None` when there are none.
+    pub fn attachment_extras(attachments: &[Attachment]) -> Option<HashMap<String, Value>> {
+        if attachments.is_empty() {
+            return None;
+        }
+        let entries = attachments
+            .iter()
+            .map(|a| {
+                let mut entry = json!({
+                    "filename": a.filename,
+                    "mime_type": a.mime_type,
+                });
+                if let AttachmentData::Path { path } = &a.data {
+                    entry["path"] = json!(path);
+                }
+                entry
+            })
+            .collect();
+        Some(HashMap::from([(
+            Self::ATTACHMENTS_KEY.to_string(),
+            Value::Array(entries),
+        )]))
+    }
+
+    /// Attachments referenced by the session's messages, in message order.
+    pub fn attachments(&self) -> Vec<ExportedAttachment> {
+        let mut taken = HashSet::new();
+        let mut out = Vec::new();
+        for (index, msg) in self.messages.iter().enumerate() {
+            let Some(entries) = msg.get(Self::ATTACHMENTS_KEY).and_then(Value::as_array) else {
+                continue;
+            };
+            for entry in entries {
+                let filename = entry
+                    .get("filename")
+                    .and_then(Value::as_str)
+                    .unwrap_or("attachment")
+                    .to_string();
+                let mime_type = entry
+                    .get("mime_type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let source = entry.get("path").and_then(Value::as_str).map(PathBuf::from);
+                let bundle_name = unique_name(&mut taken, &filename);
+                out.push(ExportedAttachment {
+                    message: index,
+                    filename,
+                    mime_type,
+                    source,
+                    bundle_name,
+                });
+            }
+        }
+        out
+    }
+
+    /// Export the session in `format` with default options.
+    pub fn export(&self, format: ExportFormat) -> String {
+        self.export_with(format, &ExportOptions::default())
+    }
+
+    /// Export the session in `format`.
+    pub fn export_with(&self, format: ExportFormat, options: &ExportOptions<'_>) -> String {
+        let attachments = self.attachments();
+        match format {
+            ExportFormat::Markdown => self.export_markdown(&attachments, options),
+            ExportFormat::Json => self.export_json(&attachments, options),
+        }
+    }
+
+    fn export_markdown(
+        &self,
+        attachments: &[ExportedAttachment],
+        options: &ExportOptions<'_>,
+    ) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Conversation `{}`\n", self.key);
+        let _ = writeln!(out, "- Created: {}", format_time(&self.created_at));
+        let _ = writeln!(out, "- Updated: {}", format_time(&self.updated_at));
+        let _ = writeln!(out, "- Messages: {}", self.messages.len());
+        if let Some(parent) = self.parent() {
+            let _ = writeln!(out, "- Forked from: `{parent}`");
+        }
+
+        let mut tool_names: HashMap<&str, &str> = HashMap::new();
+        for (index, msg) in self.messages.iter().enumerate() {
+            let role = msg.get("role").and_then(Value::as_str).unwrap_or("unknown");
+            let content = msg.get("content").and_then(Value::as_str).unwrap_or("");
+
+            let mut heading = role_title(role);
+            if role == "tool"
+                && let Some(name) = msg
+                    .get("tool_call_id")
+                    .and_then(Value::as_str)
+                    .and_then(|id| tool_names.get(id))
+            {
+                heading = format!("Tool result · `{name}`");
+            }
+            let _ = write!(out, "\n---\n\n### {heading}");
+            if let Some(ts) = msg.get("timestamp").and_then(Value::as_str) {
+                let _ = write!(out, " · {}", format_timestamp(ts));
+            }
+            out.push('\n');
+
+            let mut blocks = Vec::new();
+            if role == "tool" {
+                blocks.push(collapsed(
+                    &format!("Output ({} chars)", content.chars().count()),
+                    &fenced(content, ""),
+                ));
+            } else if !content.is_empty() {
+                blocks.push(content.trim_end().to_string());
+            }
+
+            let lines: Vec<String> = attachments
+                .iter()
+                .filter(|a| a.message == index)
+                .map(|a| match options.link(a) {
+                    Some(link) if a.mime_type.starts_with("image/") => {
+                        format!("- ![{}]({link}) ({})", a.filename, a.mime_type)
+                    }
+                    Some(link) => format!("- [{}]({link}) ({})", a.filename, a.mime_type),
+                    None => format!("- `{}` ({})", a.filename, a.mime_type),
+                })
+                .collect();
+            if !lines.is_empty() {
+                blocks.push(format!("Attachments:\n\n{}", lines.join("\n")));
+            }
+
+            for call in msg
+                .get("tool_calls")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let name = tool_call_name(call);
+                if let Some(id) = call.get("id").and_then(Value::as_str) {
+                    tool_names.insert(id, name);
+                }
+                let args = options.redact(&tool_call_arguments(call));
+                let raw = serde_json::to_string_pretty(&args).unwrap_or_default();
+                blocks.push(format!("**Tool call** `{name}`{}", summarize_args(&args)));
+                blocks.push(collapsed("Arguments", &fenced(&raw, "json")));
+            }
+
+            for block in blocks {
+                let _ = write!(out, "\n{block}\n");
+            }
+        }
+        out
+    }
+
+    fn export_json(
+        &self,
+        attachments: &[ExportedAttachment],
+        options: &ExportOptions<'_>,
+    ) -> String {
+        let mut bundled = attachments.iter();
+        let messages: Vec<Value> = self
+            .messages
+            .iter()
+            .map(|msg| {
+                let mut msg = msg.clone();
+                if let Some(calls) = msg.get_mut("tool_calls").and_then(Value::as_array_mut) {
+                    for call in calls {
+                        redact_tool_call(call, options);
+                    }
+                }
+                if let Some(entries) = msg
+                    .get_mut(Self::ATTACHMENTS_KEY)
+                    .and_then(Value::as_array_mut)
+                {
+                    for (entry, attachment) in entries.iter_mut().zip(bundled.by_ref()) {
+                        if let Some(link) = options.link(attachment) {
+                            entry["bundle_path"] = json!(link);
+                        }
+                    }
+                }
+                msg
+            })
+            .collect();
+
+        let doc = json!({
+            "version": EXPORT_VERSION,
+            "key": self.key,
+            "created_at": self.created_at.to_rfc3339(),
+            "updated_at": self.updated_at.to_rfc3339(),
+            "metadata": self.metadata,
+            "messages": messages,
+        });
+        let mut out = serde_json::to_string_pretty(&doc).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+}
+
+/// `name`, or `name-2`, `name-3`, ... (before the extension) if taken.
+fn unique_name(taken: &mut HashSet<String>, name: &str) -> String {
+    let name = name.replace(['/', '\\'], "_");
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{ext}")),
+        _ => (name.clone(), String::new()),
+    };
+    let mut candidate = name;
+    let mut n = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{stem}-{n}{ext}");
+        n += 1;
+    }
+    candidate
+}
+
+fn role_title(role: &str) -> String {
+    match role {
+        "user" => "User".into(),
+        "assistant" => "Assistant".into(),
+        "system" => "System".into(),
+        "tool" => "Tool result".into(),
+        other => {
+            let mut chars = other.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => "Unknown".into(),
+            }
+        }
+    }
+}
+
+fn format_time(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+fn format_timestamp(ts: &str) -> String {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| format_time(&dt.with_timezone(&Utc)))
+        .unwrap_or_else(|_| ts.to_string())
+}
+
+/// A `<details>` section showing `body` under `summary`.
+fn collapsed(summary: &str, body: &str) -> String {
+    format!("<details>\n<summary>{summary}</summary>\n\n{body}\n\n</details>")
+}
+
+/// `text` in a code fence longer than any backtick run inside it.
+fn fenced(text: &str, lang: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat((longest + 1).max(3));
+    format!("{fence}{lang}\n{}\n{fence}", text.trim_end_matches('\n'))
+}
+
+fn tool_call_name(call: &Value) -> &str {
+    call.pointer("/function/name")
+        .or_else(|| call.get("name"))
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+}
+
+/// Tool call arguments as a value; OpenAI-format calls carry them as a
+/// JSON string.
+fn tool_call_arguments(call: &Value) -> Value {
+    let args = call
+        .pointer("/function/arguments")
+        .or_else(|| call.get("arguments"))
+        .or_else(|| call.get("input"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    match args {
+        Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+        other => other,
+    }
+}
+
+/// Redact a tool call's arguments in place, keeping their encoding.
+fn redact_tool_call(call: &mut Value, options: &ExportOptions<'_>) {
+    let redacted = options.redact(&tool_call_arguments(call));
+    let slot = if call.pointer("/function/arguments").is_some() {
+        call.pointer_mut("/function/arguments")
+    } else if call.get("arguments").is_some() {
+        call.get_mut("arguments")
+    } else {
+        call.get_mut("input")
+    };
+    if let Some(slot) = slot {
+        *slot = match slot {
+            Value::String(_) => Value::String(redacted.to_string()),
+            _ => redacted,
+        };
+    }
+}
+
+/// ` — key: value, ...` for the top-level arguments, values shortened.
+fn summarize_args(args: &Value) -> String {
+    let Some(map) = args.as_object().filter(|m| !m.is_empty()) else {
+        return String::new();
+    };
+    let parts: Vec<String> = map
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let value = value.replace('\n', " ");
+            let short = if value.chars().count() > SUMMARY_VALUE_CHARS {
+                let cut: String = value.chars().take(SUMMARY_VALUE_CHARS).collect();
+                format!("{cut}...")
+            } else {
+                value
+            };
+            format!("{key}: `{}`", short.replace('`', "'"))
+        })
+        .collect();
+    format!(" — {}", parts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN_BASIC: &str = include_str!("../../tests/golden/session_export_basic.md");
+    const GOLDEN_TOOLS: &str = include_str!("../../tests/golden/session_export_tools.md");
+
+    fn at(ts: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(ts)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn session(messages: Vec<Value>) -> Session {
+        let mut session = Session::new("telegram:42");
+        session.created_at = at("2026-03-01T09:00:00Z");
+        session.updated_at = at("2026-03-01T09:05:00Z");
+        session.messages = messages;
+        session
+    }
+
+    fn basic() -> Session {
+        session(vec![
+            json!({"role": "user", "content": "What is the capital of France?", "timestamp": "2026-03-01T09:00:10Z"}),
+            json!({"role": "assistant", "content": "Paris.", "timestamp": "2026-03-01T09:00:12Z"}),
+        ])
+    }
+
+    fn with_tools() -> Session {
+        session(vec![
+            json!({
+                "role": "user",
+                "content": "Summarize this report.",
+                "timestamp": "2026-03-01T09:01:00Z",
+                "attachments": [
+                    {"filename": "report.txt", "mime_type": "text/plain", "path": "/tmp/report.txt"},
+                    {"filename": "chart.png", "mime_type": "image/png", "path": "/tmp/a/chart.png"},
+                    {"filename": "chart.png", "mime_type": "image/png", "path": "/tmp/b/chart.png"},
+                ],
+            }),
+            json!({
+                "role": "assistant",
+                "content": "",
+                "timestamp": "2026-03-01T09:01:02Z",
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {
+                        "name": "web_fetch",
+                        "arguments": "{\"url\":\"https://example.com\",\"api_key\":\"sk-secret\"}",
+                    },
+                }],
+            }),
+            json!({
+                "role": "tool",
+                "tool_call_id": "call_1",
+                "content": "```\nok\n```",
+                "timestamp": "2026-03-01T09:01:03Z",
+            }),
+            json!({"role": "assistant", "content": "It says ok.", "timestamp": "2026-03-01T09:01:05Z"}),
+        ])
+    }
+
+    fn mask_keys(args: &Value) -> Value {
+        let mut args = args.clone();
+        if let Some(key) = args.get_mut("api_key") {
+            *key = json!("[REDACTED]");
+        }
+        args
+    }
+
+    #[test]
+    fn markdown_matches_golden_basic() {
+        assert_eq!(basic().export(ExportFormat::Markdown), GOLDEN_BASIC);
+    }
+
+    #[test]
+    fn markdown_matches_golden_with_tools_and_attachments() {
+        let mask: &dyn Fn(&Value) -> Value = &mask_keys;
+        let options = ExportOptions::default()
+            .with_attachments_dir("chat_files")
+            .with_redactor(mask);
+        assert_eq!(
+            with_tools().export_with(ExportFormat::Markdown, &options),
+            GOLDEN_TOOLS
+        );
+    }
+
+    #[test]
+    fn markdown_without_attachments_dir_names_files() {
+        let md = with_tools().export(ExportFormat::Markdown);
+        assert!(md.contains("- `report.txt` (text/plain)"));
+        assert!(md.contains("sk-secret"));
+    }
+
+    #[test]
+    fn attachments_get_unique_bundle_names() {
+        let names: Vec<_> = with_tools()
+            .attachments()
+            .into_iter()
+            .map(|a| a.bundle_name)
+            .collect();
+        assert_eq!(names, ["report.txt", "chart.png", "chart-2.png"]);
+    }
+
+    #[test]
+    fn json_export_redacts_arguments_and_links_bundles() {
+        let mask: &dyn Fn(&Value) -> Value = &mask_keys;
+        let options = ExportOptions::default()
+            .with_attachments_dir("files")
+            .with_redactor(mask);
+        let out = with_tools().export_with(ExportFormat::Json, &options);
+        let doc: Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(doc["version"], EXPORT_VERSION);
+        assert_eq!(doc["key"], "telegram:42");
+        assert_eq!(doc["messages"].as_array().unwrap().len(), 4);
+        let args = doc["messages"][1]["tool_calls"][0]["function"]["arguments"]
+            .as_str()
+            .unwrap();
+        let args: Value = serde_json::from_str(args).unwrap();
+        assert_eq!(args["api_key"], "[REDACTED]");
+        assert_eq!(args["url"], "https://example.com");
+        assert_eq!(
+            doc["messages"][0]["attachments"][2]["bundle_path"],
+            "files/chart-2.png"
+        );
+    }
+
+    #[test]
+    fn json_export_round_trips_messages() {
+        let session = basic();
+        let doc: Value = serde_json::from_str(&session.export(ExportFormat::Json)).unwrap();
+        assert_eq!(doc["messages"], json!(session.messages));
+    }
+
+    #[test]
+    fn attachment_extras_record_paths() {
+        let attachments = vec![
+            Attachment::from_path("/tmp/a.pdf", "application/pdf"),
+            Attachment::from_bytes("b.bin", "application/octet-stream", vec![1, 2]),
+        ];
+        let extras = Session::attachment_extras(&attachments).unwrap();
+        let entries = extras[Session::ATTACHMENTS_KEY].as_array().unwrap();
+        assert_eq!(entries[0]["path"], "/tmp/a.pdf");
+        assert!(entries[1].get("path").is_none());
+        assert!(Session::attachment_extras(&[]).is_none());
+    }
+
+    #[test]
+    fn fenced_outgrows_inner_backticks() {
+        assert_eq!(fenced("a ``` b", ""), "````\na ``` b\n````");
+        assert_eq!(fenced("plain", "json"), "```json\nplain\n```");
+    }
+
+    #[test]
+    fn format_parses_aliases() {
+        assert_eq!("md".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("Markdown".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("json".parse(), Ok(ExportFormat::Json));
+        assert!("html".parse::<ExportFormat>().is_err());
+    }
+}
@@ -64,6 +64,10 @@ pub struct SkillDefinition {
     #[serde(default)]
     pub disable_model_invocation: bool,
 
+    /// What activates the skill automatically for a matching message.
+    #[serde(default, skip_serializing_if = "SkillTriggers::is_empty")]
+    pub triggers: SkillTriggers,
+
     /// The actual LLM instructions (markdown body).
     #[serde(skip)]
     pub instructions: String,
@@ -93,6 +97,7 @@ impl SkillDefinition {
             allowed_tools: Vec::new(),
             user_invocable: false,
             disable_model_invocation: false,
+            triggers: SkillTriggers::default(),
             instructions: String::new(),
             format: SkillFormat::default(),
             source_path: None,
@@ -101,6 +106,29 @@ impl SkillDefinition {
     }
 }
 
+/// Trigger metadata for automatic skill activation.
+///
+/// A message activates the skill when it contains one of the `keywords`
+/// or closely resembles one of the `examples` (see
+/// `agents.skills.autoActivate`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SkillTriggers {
+    /// Words or phrases that activate the skill when present in a message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+
+    /// Example messages the skill is meant for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+impl SkillTriggers {
+    /// Whether the skill declares no triggers (never auto-activated).
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty() && self.examples.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
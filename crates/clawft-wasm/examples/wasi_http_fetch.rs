@@ -0,0 +1,40 @@
+//! Minimal WASI guest that fetches one URL with [`WasiHttpClient`].
+//!
+//! Used by the `wasi_http` integration test. Build it as a component:
+//!
+//! ```sh
+//! cargo build -p clawft-wasm --example wasi_http_fetch \
+//!     --features wasi-http --target wasm32-wasip2
+//! ```
+//!
+//! (or build for `wasm32-wasip1` and wrap the module with
+//! `wasm-tools component new --adapt wasi_snapshot_preview1.command.wasm`).
+//!
+//! Usage: `wasi_http_fetch <url> [max-response-bytes]`. Prints the status
+//! code on the first line and the body after it, or `error: ...` on failure.
+
+use std::collections::HashMap;
+
+use clawft_wasm::http::{MAX_REQUEST_BODY, MAX_RESPONSE_BODY, WasiHttpClient};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(url) = args.next() else {
+        println!("error: usage: wasi_http_fetch <url> [max-response-bytes]");
+        return;
+    };
+    let max_response = args
+        .next()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(MAX_RESPONSE_BODY);
+
+    let client = WasiHttpClient::with_limits(MAX_REQUEST_BODY, max_response);
+    let headers = HashMap::from([("x-clawft-test".to_string(), "1".to_string())]);
+    match client.get(&url, &headers) {
+        Ok(resp) => {
+            println!("{}", resp.status);
+            print!("{}", String::from_utf8_lossy(&resp.body));
+        }
+        Err(e) => println!("error: {e}"),
+    }
+}
@@ -1,15 +1,26 @@
-//! WASI HTTP client stub.
+//! WASI HTTP client.
 //!
 //! Provides a [`WasiHttpClient`] with a self-contained API for HTTP operations.
-//! Currently all methods return errors, as WASI HTTP preview2 support is not yet
-//! available. Once the WASI HTTP outbound API stabilises, this will be replaced with
-//! real network calls using the `wasi:http/outgoing-handler` interface.
+//! When built for a WASI target (`wasm32-wasip1` via the component adapter, or
+//! `wasm32-wasip2`) with the `wasi-http` feature, requests go out through the
+//! `wasi:http/outgoing-handler` interface. Everywhere else every method
+//! returns an error, so native builds are unaffected.
+//!
+//! Request and response bodies are capped at [`MAX_REQUEST_BODY`] and
+//! [`MAX_RESPONSE_BODY`] by default, the same limits the plugin sandbox
+//! enforces.
 //!
 //! This module is fully decoupled from `clawft-platform` so it can compile for
 //! `wasm32-wasip2` without pulling in tokio or reqwest.
 
 use std::collections::HashMap;
 
+/// Largest request body sent by default (1 MiB).
+pub const MAX_REQUEST_BODY: usize = 1_048_576;
+
+/// Largest response body accepted by default (8 MiB).
+pub const MAX_RESPONSE_BODY: usize = 8 * 1_048_576;
+
 /// HTTP response from a request.
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
@@ -33,31 +44,115 @@ impl HttpResponse {
     }
 }
 
+/// Scheme, authority and path of a request URL, as wasi-http wants them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTarget {
+    /// `true` for `https`, `false` for `http`.
+    pub https: bool,
+    /// Host with optional port, e.g. `api.example.com:8443`.
+    pub authority: String,
+    /// Path plus query string, always starting with `/`.
+    pub path_with_query: String,
+}
+
+impl RequestTarget {
+    /// Split an absolute `http`/`https` URL. Any fragment is dropped.
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| format!("invalid URL '{url}': missing scheme"))?;
+        let https = match scheme.to_ascii_lowercase().as_str() {
+            "https" => true,
+            "http" => false,
+            other => return Err(format!("unsupported URL scheme '{other}'")),
+        };
+        let rest = rest.split('#').next().unwrap_or_default();
+        let split = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(split);
+        if authority.is_empty() || authority.contains('@') {
+            return Err(format!("invalid URL '{url}': bad host"));
+        }
+        let path_with_query = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{path}")
+        };
+        Ok(Self {
+            https,
+            authority: authority.to_string(),
+            path_with_query,
+        })
+    }
+}
+
 /// HTTP client for WASI environments.
 ///
-/// This is a stub implementation that will use WASI HTTP preview2
-/// (`wasi:http/outgoing-handler`) once it is stable. Until then, all
-/// methods return an error indicating the feature is not yet available.
-pub struct WasiHttpClient;
+/// Uses `wasi:http/outgoing-handler` when compiled for WASI with the
+/// `wasi-http` feature; otherwise all methods return an error.
+pub struct WasiHttpClient {
+    max_request_body: usize,
+    max_response_body: usize,
+}
 
 impl WasiHttpClient {
-    /// Create a new WASI HTTP client.
+    /// Create a new WASI HTTP client with the default body limits.
     pub fn new() -> Self {
-        Self
+        Self::with_limits(MAX_REQUEST_BODY, MAX_RESPONSE_BODY)
+    }
+
+    /// Create a client with explicit request and response body limits.
+    pub fn with_limits(max_request_body: usize, max_response_body: usize) -> Self {
+        Self {
+            max_request_body,
+            max_response_body,
+        }
+    }
+
+    /// Largest response body this client accepts.
+    pub fn max_response_body(&self) -> usize {
+        self.max_response_body
     }
 
     /// Send an HTTP request with the given method, URL, headers, and optional body.
     pub fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let target = RequestTarget::parse(url)?;
+        if let Some(body) = body.filter(|b| b.len() > self.max_request_body) {
+            return Err(format!(
+                "request body too large: {} bytes, max {}",
+                body.len(),
+                self.max_request_body
+            )
+            .into());
+        }
+        self.send(method, &target, headers, body)
+    }
+
+    #[cfg(all(feature = "wasi-http", target_os = "wasi"))]
+    fn send(
+        &self,
+        method: &str,
+        target: &RequestTarget,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+    ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
+        outgoing::send(method, target, headers, body, self.max_response_body)
+    }
+
+    #[cfg(not(all(feature = "wasi-http", target_os = "wasi")))]
+    fn send(
         &self,
         _method: &str,
-        _url: &str,
+        _target: &RequestTarget,
         _headers: &HashMap<String, String>,
         _body: Option<&[u8]>,
     ) -> Result<HttpResponse, Box<dyn std::error::Error + Send + Sync>> {
-        Err(
-            "WASI HTTP not yet implemented: waiting for wasi:http/outgoing-handler stabilisation"
-                .into(),
-        )
+        Err("WASI HTTP not available: build for a WASI target with the `wasi-http` feature".into())
     }
 
     /// Send an HTTP GET request.
@@ -86,6 +181,159 @@ impl Default for WasiHttpClient {
     }
 }
 
+// ---------------------------------------------------------------------------
+// wasi:http/outgoing-handler transport
+// ---------------------------------------------------------------------------
+
+#[cfg(all(feature = "wasi-http", target_os = "wasi"))]
+mod outgoing {
+    use std::collections::HashMap;
+
+    use wasi::http::outgoing_handler;
+    use wasi::http::types::{Fields, IncomingBody, Method, OutgoingBody, OutgoingRequest, Scheme};
+    use wasi::io::streams::StreamError;
+
+    use super::{HttpResponse, RequestTarget};
+
+    type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+    /// Largest chunk `blocking-write-and-flush` accepts in one call.
+    const WRITE_CHUNK: usize = 4096;
+    /// Read size for the response body stream.
+    const READ_CHUNK: u64 = 64 * 1024;
+
+    fn method(name: &str) -> Method {
+        match name.to_ascii_uppercase().as_str() {
+            "GET" => Method::Get,
+            "HEAD" => Method::Head,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            "CONNECT" => Method::Connect,
+            "OPTIONS" => Method::Options,
+            "TRACE" => Method::Trace,
+            "PATCH" => Method::Patch,
+            other => Method::Other(other.to_string()),
+        }
+    }
+
+    pub(super) fn send(
+        method_name: &str,
+        target: &RequestTarget,
+        headers: &HashMap<String, String>,
+        body: Option<&[u8]>,
+        max_response_body: usize,
+    ) -> Result<HttpResponse, BoxError> {
+        let fields = Fields::new();
+        for (name, value) in headers {
+            // The host sets Host from the authority and rejects it here.
+            if name.eq_ignore_ascii_case("host") {
+                continue;
+            }
+            fields
+                .append(&name.to_ascii_lowercase(), &value.as_bytes().to_vec())
+                .map_err(|e| format!("invalid header '{name}': {e:?}"))?;
+        }
+
+        let request = OutgoingRequest::new(fields);
+        request
+            .set_method(&method(method_name))
+            .map_err(|()| format!("invalid method '{method_name}'"))?;
+        let scheme = if target.https {
+            Scheme::Https
+        } else {
+            Scheme::Http
+        };
+        request
+            .set_scheme(Some(&scheme))
+            .map_err(|()| "invalid scheme")?;
+        request
+            .set_authority(Some(&target.authority))
+            .map_err(|()| format!("invalid authority '{}'", target.authority))?;
+        request
+            .set_path_with_query(Some(&target.path_with_query))
+            .map_err(|()| format!("invalid path '{}'", target.path_with_query))?;
+
+        let outgoing_body = request.body().map_err(|()| "request body already taken")?;
+        let future = outgoing_handler::handle(request, None)
+            .map_err(|e| format!("HTTP request failed: {e:?}"))?;
+
+        if let Some(body) = body {
+            let stream = outgoing_body
+                .write()
+                .map_err(|()| "request body stream already taken")?;
+            for chunk in body.chunks(WRITE_CHUNK) {
+                stream
+                    .blocking_write_and_flush(chunk)
+                    .map_err(|e| format!("writing request body failed: {e:?}"))?;
+            }
+            drop(stream);
+        }
+        OutgoingBody::finish(outgoing_body, None)
+            .map_err(|e| format!("finishing request body failed: {e:?}"))?;
+
+        future.subscribe().block();
+        let response = future
+            .get()
+            .ok_or("HTTP response not ready after blocking")?
+            .map_err(|()| "HTTP response already taken")?
+            .map_err(|e| format!("HTTP request failed: {e:?}"))?;
+
+        let status = response.status();
+        let mut response_headers = HashMap::new();
+        {
+            let fields = response.headers();
+            for (name, value) in fields.entries() {
+                let value = String::from_utf8_lossy(&value).into_owned();
+                response_headers
+                    .entry(name)
+                    .and_modify(|v: &mut String| {
+                        v.push_str(", ");
+                        v.push_str(&value);
+                    })
+                    .or_insert(value);
+            }
+        }
+
+        let incoming = response
+            .consume()
+            .map_err(|()| "response body already taken")?;
+        let mut body = Vec::new();
+        {
+            let stream = incoming
+                .stream()
+                .map_err(|()| "response body stream already taken")?;
+            loop {
+                match stream.blocking_read(READ_CHUNK) {
+                    Ok(chunk) => {
+                        if body.len() + chunk.len() > max_response_body {
+                            return Err(
+                                format!("response body exceeds {max_response_body} bytes").into()
+                            );
+                        }
+                        body.extend_from_slice(&chunk);
+                    }
+                    Err(StreamError::Closed) => break,
+                    Err(StreamError::LastOperationFailed(e)) => {
+                        return Err(format!(
+                            "reading response body failed: {}",
+                            e.to_debug_string()
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        drop(IncomingBody::finish(incoming));
+
+        Ok(HttpResponse {
+            status,
+            headers: response_headers,
+            body,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Sandboxed HTTP client (behind wasm-plugins feature)
 // ---------------------------------------------------------------------------
@@ -175,11 +423,55 @@ mod tests {
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(
-            err.contains("WASI HTTP not yet implemented"),
+            err.contains("WASI HTTP not available"),
             "unexpected error message: {err}"
         );
     }
 
+    #[test]
+    fn request_target_splits_url() {
+        let target = RequestTarget::parse("https://api.example.com:8443/v1/chat?x=1#frag").unwrap();
+        assert!(target.https);
+        assert_eq!(target.authority, "api.example.com:8443");
+        assert_eq!(target.path_with_query, "/v1/chat?x=1");
+
+        let target = RequestTarget::parse("HTTP://localhost?q").unwrap();
+        assert!(!target.https);
+        assert_eq!(target.authority, "localhost");
+        assert_eq!(target.path_with_query, "/?q");
+    }
+
+    #[test]
+    fn request_target_rejects_bad_urls() {
+        assert!(RequestTarget::parse("example.com/path").is_err());
+        assert!(RequestTarget::parse("ftp://example.com/").is_err());
+        assert!(RequestTarget::parse("https:///path").is_err());
+        assert!(RequestTarget::parse("https://user@example.com/").is_err());
+    }
+
+    #[test]
+    fn request_body_limit_checked_before_sending() {
+        let client = WasiHttpClient::with_limits(4, 16);
+        let err = client
+            .post("https://example.com", &HashMap::new(), b"too long")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("request body too large"),
+            "unexpected error: {err}"
+        );
+        assert_eq!(client.max_response_body(), 16);
+    }
+
+    #[test]
+    fn invalid_url_reported_before_transport() {
+        let err = WasiHttpClient::new()
+            .get("not a url", &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing scheme"), "unexpected error: {err}");
+    }
+
     #[test]
     fn get_returns_error() {
         let client = WasiHttpClient::new();
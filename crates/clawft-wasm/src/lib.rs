@@ -10,7 +10,8 @@
 //! # Platform Support
 //!
 //! The WASM build targets `wasm32-wasip2` and uses WASI preview 1 for:
-//! - HTTP outbound (LLM API calls)
+//! - HTTP outbound (LLM API calls), via `wasi:http` with the `wasi-http`
+//!   feature
 //! - Filesystem (config, sessions)
 //! - Environment variables
 //!
@@ -322,6 +323,9 @@ mod browser_entry {
             temperature: Some(rt.config.agents.defaults.temperature),
             stream: None,
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            cache: false,
         };
 
         let response = rt
@@ -333,7 +337,8 @@ mod browser_entry {
         let reply = response
             .choices
             .first()
-            .and_then(|c| c.message.content.clone())
+            .and_then(|c| c.message.text())
+            .map(String::from)
             .unwrap_or_else(|| "No response from model.".to_string());
 
         // Add assistant reply to history.
@@ -1,7 +1,7 @@
 //! WASM platform bundle.
 //!
 //! Provides [`WasmPlatform`], a self-contained struct that bundles the
-//! WASI-oriented stubs ([`WasiHttpClient`], [`WasiFileSystem`], [`WasiEnvironment`])
+//! WASI-oriented implementations ([`WasiHttpClient`], [`WasiFileSystem`], [`WasiEnvironment`])
 //! into a single object suitable for constructing a clawft agent in a WASM environment.
 //!
 //! This module is fully decoupled from `clawft-platform` so it can compile for
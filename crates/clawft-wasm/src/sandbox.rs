@@ -230,7 +230,7 @@ pub enum HttpValidationError {
 }
 
 const BLOCKED_SCHEMES: &[&str] = &["file", "data", "javascript", "ftp", "gopher"];
-const MAX_REQUEST_BODY: usize = 1_048_576; // 1 MB
+use crate::http::MAX_REQUEST_BODY;
 
 /// Validate an HTTP request against the plugin's sandbox permissions.
 ///
@@ -0,0 +1,104 @@
+//! Round-trip tests for `WasiHttpClient` running under wasmtime.
+//!
+//! The guest is the `wasi_http_fetch` example compiled to a component and
+//! run with `wasmtime run -S http`, so requests really go through
+//! `wasi:http/outgoing-handler`. A plain TCP server on localhost answers.
+//! Run with:
+//!
+//! ```sh
+//! cargo build -p clawft-wasm --example wasi_http_fetch \
+//!     --features wasi-http --target wasm32-wasip2
+//! CLAWFT_WASI_HTTP_GUEST=target/wasm32-wasip2/debug/examples/wasi_http_fetch.wasm \
+//!     cargo test -p clawft-wasm --test wasi_http
+//! ```
+//!
+//! The tests are skipped unless `CLAWFT_WASI_HTTP_GUEST` is set. The
+//! `wasmtime` binary is taken from `WASMTIME` or else `PATH`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc;
+
+fn guest_path() -> Option<PathBuf> {
+    match std::env::var_os("CLAWFT_WASI_HTTP_GUEST") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => {
+            eprintln!("skipping: CLAWFT_WASI_HTTP_GUEST is not set");
+            None
+        }
+    }
+}
+
+/// Serve one request with `body`, sending the request head back on the
+/// returned channel.
+fn serve_once(body: &'static str) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/greeting?lang=en", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: {}\r\n\
+             connection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        tx.send(head).unwrap();
+    });
+    (url, rx)
+}
+
+/// Run the guest with `args` and return what it printed.
+fn run_guest(guest: &PathBuf, args: &[&str]) -> String {
+    let wasmtime = std::env::var_os("WASMTIME").unwrap_or_else(|| "wasmtime".into());
+    let output = Command::new(wasmtime)
+        .args(["run", "-S", "http"])
+        .arg(guest)
+        .args(args)
+        .output()
+        .expect("failed to run wasmtime");
+    assert!(
+        output.status.success(),
+        "wasmtime failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn get_round_trip_through_wasi_http() {
+    let Some(guest) = guest_path() else { return };
+    let (url, head) = serve_once("hello from host");
+
+    let output = run_guest(&guest, &[&url]);
+    assert_eq!(output, "200\nhello from host");
+
+    let head = head.recv().unwrap().to_ascii_lowercase();
+    assert!(head.starts_with("get /greeting?lang=en http/1.1"), "{head}");
+    assert!(head.contains("x-clawft-test: 1"), "{head}");
+}
+
+#[test]
+fn oversized_response_is_rejected() {
+    let Some(guest) = guest_path() else { return };
+    let (url, _head) = serve_once("this body is longer than sixteen bytes");
+
+    let output = run_guest(&guest, &[&url, "16"]);
+    assert!(
+        output.starts_with("error: response body exceeds 16 bytes"),
+        "{output}"
+    );
+}
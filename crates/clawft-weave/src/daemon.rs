@@ -2868,11 +2868,19 @@ async fn dispatch(
                 .map(std::path::PathBuf::from)
                 .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| ".".into()));
 
+            let template = params["template"].as_str().unwrap_or("default");
+            let vars: std::collections::HashMap<String, String> =
+                serde_json::from_value(params["vars"].clone()).unwrap_or_default();
+
             match WorkspaceManager::new() {
-                Ok(mut mgr) => match mgr.create(name, &dir) {
-                    Ok(path) => Response::success(serde_json::json!({
+                Ok(mut mgr) => match mgr
+                    .resolve_template(template)
+                    .and_then(|t| mgr.create_from_template(name, &dir, &t, &vars))
+                {
+                    Ok(created) => Response::success(serde_json::json!({
                         "name": name,
-                        "path": path.display().to_string(),
+                        "path": created.path.display().to_string(),
+                        "notes": created.notes,
                     })),
                     Err(e) => Response::error(format!("workspace create failed: {e}")),
                 },
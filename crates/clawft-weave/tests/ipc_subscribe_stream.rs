@@ -26,9 +26,22 @@ fn base_config() -> Config {
                 model: "test/model".into(),
                 max_tokens: 1024,
                 temperature: 0.5,
+                top_p: None,
                 max_tool_iterations: 5,
                 memory_window: 10,
+                max_parallel_tools: 4,
             },
+            dispatch: Default::default(),
+            usage: Default::default(),
+            budget: Default::default(),
+            memory: Default::default(),
+            cache: Default::default(),
+            sessions: Default::default(),
+            compaction: Default::default(),
+            planning: Default::default(),
+            templates: Default::default(),
+            skills: Default::default(),
+            interactive: Default::default(),
         },
         ..Config::default()
     }